@@ -1,8 +1,90 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use std::sync::Arc;
+use std::thread;
+use tempfile::TempDir;
 
-fn benchmark_placeholder(c: &mut Criterion) {
-    c.bench_function("placeholder", |b| b.iter(|| 1 + 1));
+const SESSIONS: usize = 4;
+const SEARCHES_PER_THREAD: usize = 20;
+
+/// Services with `SESSIONS` indexed sessions of generated Rust files
+fn indexed_services(storage: &TempDir, repos: &TempDir) -> Arc<Services> {
+    let mut config = Config::default();
+    config.storage.index_dir = storage.path().to_path_buf();
+    let services = Services::new(config);
+
+    for n in 0..SESSIONS {
+        let repo = repos.path().join(format!("repo-{n}"));
+        std::fs::create_dir_all(&repo).unwrap();
+        for i in 0..200 {
+            let content = format!(
+                "// module {i} of session {n}\npub fn handler_{i}() -> u32 {{\n    \
+                 authenticate(\"user_{i}\");\n    connect_database();\n    {i}\n}}\n"
+            );
+            std::fs::write(repo.join(format!("file_{i}.rs")), content).unwrap();
+        }
+        services
+            .storage
+            .index_repository(
+                &format!("bench-{n}"),
+                &repo,
+                vec![],
+                vec![],
+                512,
+                64,
+                10,
+                true,
+            )
+            .unwrap();
+    }
+    Arc::new(services)
+}
+
+/// Run searches on every session from `threads` threads
+fn parallel_searches(services: &Arc<Services>, threads: usize, cached: bool) {
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let services = Arc::clone(services);
+            thread::spawn(move || {
+                for i in 0..SEARCHES_PER_THREAD {
+                    let session = format!("bench-{}", (t + i) % SESSIONS);
+                    if !cached {
+                        services.storage.invalidate_reader(&session);
+                    }
+                    services
+                        .search
+                        .search_session(&session, "authenticate user", Some(10))
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+fn benchmark_concurrent_search(c: &mut Criterion) {
+    let storage = TempDir::new().unwrap();
+    let repos = TempDir::new().unwrap();
+    let services = indexed_services(&storage, &repos);
+
+    let mut group = c.benchmark_group("concurrent_search");
+    for threads in [1, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("cached_readers", threads),
+            &threads,
+            |b, &t| b.iter(|| parallel_searches(&services, t, true)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("reopen_each_search", threads),
+            &threads,
+            |b, &t| b.iter(|| parallel_searches(&services, t, false)),
+        );
+    }
+    group.finish();
 }
 
-criterion_group!(benches, benchmark_placeholder);
+criterion_group!(benches, benchmark_concurrent_search);
 criterion_main!(benches);
@@ -0,0 +1,34 @@
+//! Build script
+//!
+//! With the `grpc` feature, generates the gRPC service and messages from
+//! `proto/shebe/v1/search.proto`. The proto is parsed in Rust (protox),
+//! so no `protoc` is needed. Without the feature this does nothing.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    use protox::prost::Message;
+    use std::path::PathBuf;
+
+    const PROTO: &str = "proto/shebe/v1/search.proto";
+    println!("cargo:rerun-if-changed={PROTO}");
+
+    let descriptors = protox::compile([PROTO], ["proto"]).expect("failed to parse proto files");
+
+    // Served by the reflection service, so grpcurl needs no .proto file
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR not set"));
+    std::fs::write(
+        out_dir.join("shebe_descriptor.bin"),
+        descriptors.encode_to_vec(),
+    )
+    .expect("failed to write file descriptor set");
+
+    tonic_prost_build::configure()
+        .compile_fds(descriptors)
+        .expect("failed to generate gRPC code");
+}
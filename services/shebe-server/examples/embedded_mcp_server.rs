@@ -0,0 +1,81 @@
+//! Embed a Shebe MCP server that several clients share over TCP.
+//!
+//! Indexes a directory, serves MCP on a local port and connects two
+//! clients that list the tools and search concurrently.
+//!
+//! ```sh
+//! cargo run --example embedded_mcp_server -- /path/to/repo "query"
+//! ```
+
+use serde_json::{json, Value};
+use shebe::mcp::McpServer;
+use shebe::{Config, Shebe};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Send one JSON-RPC request on a fresh connection and read the response
+async fn call(addr: SocketAddr, method: &str, params: Value) -> std::io::Result<Value> {
+    let (reader, mut writer) = TcpStream::connect(addr).await?.into_split();
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    writer.write_all(format!("{request}\n").as_bytes()).await?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .unwrap_or_default();
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let repo = args.next().unwrap_or_else(|| ".".to_string());
+    let query = args.next().unwrap_or_else(|| "fn main".to_string());
+
+    let temp = tempfile::TempDir::new()?;
+    let mut config = Config::default();
+    config.storage.index_dir = temp.path().to_path_buf();
+    let shebe = Shebe::new(config);
+
+    let stats = shebe.index(&repo).session("example").run()?.stats;
+    println!("Indexed {} files from {repo}", stats.files_indexed);
+
+    // Port 0 picks a free port; use a fixed address to share it
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = McpServer::new(Arc::new(shebe.services().clone()));
+    tokio::spawn(async move { server.serve_tcp(listener).await });
+    println!("Serving MCP on {addr}");
+
+    let (tools, search) = tokio::join!(
+        call(addr, "tools/list", json!({})),
+        call(
+            addr,
+            "tools/call",
+            json!({
+                "name": "search_code",
+                "arguments": {"query": query, "session": "example", "k": 3}
+            })
+        )
+    );
+
+    let tools = tools?;
+    let names: Vec<&str> = tools["result"]["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    println!("Client 1 sees {} tools: {}", names.len(), names.join(", "));
+
+    let search = search?;
+    let text = search["result"]["content"][0]["text"]
+        .as_str()
+        .or(search["error"]["message"].as_str())
+        .unwrap_or_default();
+    println!("Client 2 search results:\n{text}");
+    Ok(())
+}
@@ -17,17 +17,32 @@
 //!
 //! # Show configuration
 //! shebe config
+//!
+//! # Remember a default session
+//! shebe init --session myproject
 //! ```
+//!
+//! Exit codes: 0 success, 2 invalid arguments, 3 session not found,
+//! 4 index or storage error, 5 no matches (with `--fail-on-empty`),
+//! 130 interrupted with Ctrl-C, 1 anything else.
 
-use clap::Parser;
-use shebe::cli::{run, Cli};
+use shebe::cli::{error_exit_code, is_broken_pipe, run, Cli, CliDefaults};
+use shebe::core::xdg::XdgDirs;
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    // Options left out come from `shebe init` / `shebe config set`
+    let defaults = CliDefaults::load(&XdgDirs::new().cli_defaults_file()).unwrap_or_else(|e| {
+        eprintln!("Warning: {e} (ignoring CLI defaults)");
+        CliDefaults::default()
+    });
+    let cli = Cli::parse_with_defaults(std::env::args_os(), &defaults).unwrap_or_else(|e| e.exit());
 
     if let Err(e) = run(cli).await {
+        if is_broken_pipe(e.as_ref()) {
+            return;
+        }
         eprintln!("Error: {e}");
-        std::process::exit(1);
+        std::process::exit(error_exit_code(e.as_ref()));
     }
 }
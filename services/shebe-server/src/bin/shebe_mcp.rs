@@ -1,22 +1,61 @@
 //! Shebe MCP (Model Context Protocol) Server
 //!
-//! A stdio-based MCP server that exposes Shebe's search capabilities
-//! as tools for Claude Code and other MCP clients.
+//! An MCP server that exposes Shebe's search capabilities as tools for
+//! Claude Code and other MCP clients. It speaks stdio by default, or
+//! TCP with `--listen` (or `[mcp] listen`) so several clients can share
+//! one instance.
 
+use clap::Parser;
 use shebe::core::config::Config;
+use shebe::core::logging::{self, LogFormat};
+use shebe::core::maintenance::MaintenanceTask;
+use shebe::core::schedule::ReindexScheduler;
 use shebe::core::services::Services;
 use shebe::core::storage::MetadataValidator;
 use shebe::core::xdg::{migrate_legacy_paths, XdgDirs};
 use shebe::mcp::McpServer;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-fn init_logging() {
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr) // Critical: stderr not stdout
-        .with_max_level(tracing::Level::INFO)
-        .with_ansi(false) // No color codes
-        .compact() // Concise format
-        .init();
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(name = "shebe-mcp", version, about = "Shebe MCP server")]
+struct Args {
+    /// Serve MCP over TCP on this address (e.g. 0.0.0.0:7400) instead of stdio
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<SocketAddr>,
+
+    /// Storage profile from the config file ([profiles.<name>]; default: SHEBE_PROFILE)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+/// Prepare XDG directories and load the configuration
+///
+/// Exits the process when the directories cannot be created or the
+/// configuration is invalid, including an unknown `profile`.
+fn load_config(profile: Option<&str>) -> Config {
+    // Initialize XDG directories
+    let xdg = XdgDirs::new();
+    tracing::debug!("XDG directories initialized");
+
+    // Ensure XDG directories exist
+    if let Err(e) = xdg.ensure_dirs_exist() {
+        eprintln!("Failed to create XDG directories: {e}");
+        std::process::exit(1);
+    }
+
+    // Run migration from legacy paths (if needed)
+    if let Err(e) = migrate_legacy_paths(&xdg) {
+        tracing::warn!("Migration warning: {}", e);
+        tracing::info!("Continuing with current paths...");
+    }
+
+    // Load configuration
+    Config::load_with_profile(&xdg, profile).unwrap_or_else(|e| {
+        eprintln!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    })
 }
 
 /// Validate all session metadata on startup
@@ -29,8 +68,28 @@ fn validate_sessions_on_startup(services: &Services) {
         Ok(reports) => {
             let mut inconsistent_count = 0;
             let mut repaired_count = 0;
+            let corrupted_count = reports
+                .iter()
+                .filter(|report| report.index_corruption.is_some())
+                .count();
 
             for report in &reports {
+                tracing::debug!(
+                    "Session '{}' created by shebe {}, schema hash {}",
+                    report.session_id,
+                    report.created_by_version,
+                    report.tantivy_schema_hash
+                );
+                if report.schema_hash_matches == Some(false) {
+                    tracing::warn!(
+                        "Session '{}' was built with a different index schema \
+                         (hash {}, created by shebe {}); run upgrade_session",
+                        report.session_id,
+                        report.tantivy_schema_hash,
+                        report.created_by_version
+                    );
+                }
+
                 if !report.is_consistent {
                     inconsistent_count += 1;
                     tracing::warn!(
@@ -41,6 +100,10 @@ fn validate_sessions_on_startup(services: &Services) {
                         report.actual_size
                     );
 
+                    if services.storage.is_read_only() {
+                        continue;
+                    }
+
                     // Attempt auto-repair
                     match validator.auto_repair(&report.session_id) {
                         Ok(true) => {
@@ -70,6 +133,18 @@ fn validate_sessions_on_startup(services: &Services) {
             } else {
                 tracing::info!("All {} session(s) have consistent metadata", reports.len());
             }
+            // Each one was logged as it failed to open
+            if corrupted_count > 0 {
+                tracing::error!(
+                    "Found {} session(s) with a corrupted index; re-index or delete them{}",
+                    corrupted_count,
+                    if services.config.current().storage.reindex_on_corruption {
+                        " (re-indexing automatically)"
+                    } else {
+                        ""
+                    }
+                );
+            }
         }
         Err(e) => {
             tracing::error!("Failed to validate sessions: {}", e);
@@ -77,42 +152,145 @@ fn validate_sessions_on_startup(services: &Services) {
     }
 }
 
-#[tokio::main]
-async fn main() {
-    init_logging();
+/// Log the readiness state on startup
+///
+/// Failing checks are logged but do not stop the server, so sessions
+/// that still work remain available.
+fn log_readiness(services: &Services) {
+    let report = services.readiness();
 
-    // Initialize XDG directories
-    let xdg = XdgDirs::new();
-    tracing::debug!("XDG directories initialized");
+    if report.ready {
+        tracing::info!("Readiness: ready ({} checks passed)", report.checks.len());
+        return;
+    }
 
-    // Ensure XDG directories exist
-    if let Err(e) = xdg.ensure_dirs_exist() {
-        eprintln!("Failed to create XDG directories: {e}");
-        std::process::exit(1);
+    for check in report.failing() {
+        tracing::warn!("Readiness check '{}' failed: {}", check.name, check.detail);
     }
+    tracing::warn!("Readiness: not ready");
+}
 
-    // Run migration from legacy paths (if needed)
-    if let Err(e) = migrate_legacy_paths(&xdg) {
-        tracing::warn!("Migration warning: {}", e);
-        tracing::info!("Continuing with current paths...");
+/// Reload the configuration whenever the process receives SIGHUP
+///
+/// A configuration that fails to load is logged and the running one
+/// kept; the server keeps serving either way.
+#[cfg(unix)]
+fn reload_on_sighup(services: &Services) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!("SIGHUP reload unavailable: {}", e);
+            return;
+        }
+    };
+    let config = Arc::clone(&services.config);
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading configuration");
+            if let Err(e) = config.reload() {
+                tracing::error!("Configuration not reloaded, keeping the running one: {}", e);
+            }
+        }
+    });
+}
+
+/// Start the gRPC server in the background when `server.grpc_port` is set
+///
+/// A listener that fails to bind is logged; the MCP server keeps running.
+#[cfg(feature = "grpc")]
+fn start_grpc(services: &Arc<Services>, config: &Config) {
+    let addr = match config.server.grpc_addr() {
+        Ok(Some(addr)) => addr,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("Invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    let server = shebe::grpc::GrpcServer::new(Arc::clone(services));
+    tokio::spawn(async move {
+        if let Err(e) = server.serve(addr).await {
+            tracing::error!("gRPC server stopped: {}", e);
+        }
+    });
+}
+
+#[cfg(not(feature = "grpc"))]
+fn start_grpc(_services: &Arc<Services>, config: &Config) {
+    if config.server.grpc_port.is_some() {
+        tracing::warn!(
+            "server.grpc_port is set but shebe-mcp was built without the grpc feature; \
+             gRPC is not served"
+        );
     }
+}
 
-    // Load configuration
-    let config = Config::load().unwrap_or_else(|e| {
-        eprintln!("Failed to load configuration: {e}");
-        std::process::exit(1);
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    // The log format comes from the configuration, so startup logs
+    // use the default text format until it is loaded
+    let config = tracing::dispatcher::with_default(&logging::subscriber(LogFormat::Text), || {
+        load_config(args.profile.as_deref())
     });
+    logging::init(config.log.format);
+    tracing::info!(
+        "Storage profile '{}': {}",
+        config.profile_name(),
+        config.storage.index_dir.display()
+    );
 
     // Create services
     let services = Arc::new(Services::new(config));
 
     // Validate session metadata on startup
     validate_sessions_on_startup(&services);
+    services.recover_corrupted_sessions();
+    log_readiness(&services);
 
-    // Create and run MCP server
+    #[cfg(unix)]
+    reload_on_sighup(&services);
+
+    // Re-index scheduled sessions in the background
+    let config = services.config.current();
+    if config.schedule.enabled && services.storage.is_read_only() {
+        tracing::warn!("Reindex scheduler not started: server is in read-only mode");
+    } else if config.schedule.enabled {
+        ReindexScheduler::spawn(Arc::clone(&services));
+    }
+
+    // Optimize sessions while no tools are being called
+    if config.maintenance.enabled && services.storage.is_read_only() {
+        tracing::warn!("Idle maintenance not started: server is in read-only mode");
+    } else if config.maintenance.enabled {
+        MaintenanceTask::spawn(Arc::clone(&services));
+    }
+
+    // Serve gRPC next to MCP when [server] grpc_port is set
+    start_grpc(&services, &config);
+
+    // --listen takes precedence over [mcp] listen
+    let listen = args.listen.or_else(|| {
+        config.mcp.listen_addr().unwrap_or_else(|e| {
+            eprintln!("Invalid configuration: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    // Create and run MCP server; tool usage is kept across restarts
     let mut server = McpServer::new(services);
+    if config.mcp.usage_stats {
+        server = server.persist_usage(XdgDirs::new().tool_usage_file());
+    }
+    let result = match listen {
+        Some(addr) => server.listen(addr).await,
+        None => server.run().await,
+    };
 
-    if let Err(e) = server.run().await {
+    if let Err(e) = result {
         eprintln!("MCP server error: {e}");
         std::process::exit(1);
     }
@@ -0,0 +1,130 @@
+//! Collection commands - create, update and delete named groups of sessions
+//!
+//! These commands are exposed as top-level CLI commands matching MCP tool names:
+//! - `create-collection` (MCP: create_collection)
+//! - `update-collection` (MCP: update_collection)
+//! - `delete-collection` (MCP: delete_collection)
+
+use crate::cli::output::{colors, print_output};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::services::Services;
+use crate::core::storage::Collection;
+use clap::Args;
+use std::sync::Arc;
+
+/// Arguments for create-collection
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+    /// Collection name (1-64 letters, digits, '-' or '_')
+    pub name: String,
+
+    /// Sessions in the collection
+    #[arg(required = true, num_args = 1..)]
+    pub sessions: Vec<String>,
+}
+
+/// Arguments for update-collection
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    /// Collection to change
+    pub name: String,
+
+    /// Session to add (repeatable)
+    #[arg(long, value_name = "SESSION")]
+    pub add: Vec<String>,
+
+    /// Session to remove (repeatable)
+    #[arg(long, value_name = "SESSION")]
+    pub remove: Vec<String>,
+}
+
+/// Arguments for delete-collection
+#[derive(Args, Debug)]
+pub struct DeleteArgs {
+    /// Collection to delete
+    pub name: String,
+}
+
+/// Execute create-collection command
+pub async fn execute_create(
+    args: CreateArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let collection = services
+        .storage
+        .create_collection(&args.name, &args.sessions)?;
+    print_collection("Created", &collection, format);
+    Ok(())
+}
+
+/// Execute update-collection command
+pub async fn execute_update(
+    args: UpdateArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.add.is_empty() && args.remove.is_empty() {
+        return Err(CliError::InvalidArgs(
+            "Nothing to change: pass --add and/or --remove".to_string(),
+        )
+        .into());
+    }
+    let collection = services
+        .storage
+        .update_collection(&args.name, &args.add, &args.remove)?;
+    print_collection("Updated", &collection, format);
+    Ok(())
+}
+
+/// Execute delete-collection command
+pub async fn execute_delete(
+    args: DeleteArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let collection = services.storage.delete_collection(&args.name)?;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} collection '{}'",
+                colors::success("Deleted"),
+                colors::session_id(&collection.name)
+            );
+            println!(
+                "{}",
+                colors::dim(&format!(
+                    "Its {} session(s) were not changed.",
+                    collection.sessions.len()
+                ))
+            );
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&collection, format),
+    }
+    Ok(())
+}
+
+/// Print a created or updated collection
+fn print_collection(action: &str, collection: &Collection, format: OutputFormat) {
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} collection '{}'",
+                colors::success(action),
+                colors::session_id(&collection.name)
+            );
+            println!(
+                "{} {}",
+                colors::label("Sessions:"),
+                collection
+                    .sessions
+                    .iter()
+                    .map(|s| colors::session_id(s).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(collection, format),
+    }
+}
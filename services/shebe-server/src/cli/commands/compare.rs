@@ -0,0 +1,183 @@
+//! Compare-configs command - search results of two chunking configs
+
+use crate::cli::output::{colors, format_bytes, is_quiet, print_output};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::compare::{
+    CompareRequest, ConfigComparison, ConfigVariant, QueryRun, ScoreSummary, VariantReport,
+    DEFAULT_COMPARE_K,
+};
+use crate::core::services::Services;
+use clap::Args;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Arguments for the compare-configs command
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    /// Repository indexed with both configurations
+    pub path: PathBuf,
+
+    /// First configuration, e.g. "chunk_size=256,overlap=32"
+    #[arg(long = "config-a", value_name = "SETTINGS")]
+    pub a: ConfigVariant,
+
+    /// Second configuration, e.g. "chunk_size=2048,overlap=128"
+    #[arg(long = "config-b", value_name = "SETTINGS")]
+    pub b: ConfigVariant,
+
+    /// Query to run against both (repeatable)
+    #[arg(long = "query")]
+    pub queries: Vec<String>,
+
+    /// File with one query per line (blank lines and lines starting
+    /// with '#' are skipped)
+    #[arg(long)]
+    pub queries_file: Option<PathBuf>,
+
+    /// Results per query
+    #[arg(short = 'k', long, default_value_t = DEFAULT_COMPARE_K as u64,
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub k: u64,
+}
+
+/// Execute the compare-configs command
+pub async fn execute(
+    args: CompareArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut queries = args.queries;
+    if let Some(file) = &args.queries_file {
+        let content = std::fs::read_to_string(file).map_err(|e| {
+            CliError::InvalidArgs(format!("Cannot read queries file {}: {e}", file.display()))
+        })?;
+        queries.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+    if queries.is_empty() {
+        return Err(CliError::InvalidArgs(
+            "Give queries with --query or --queries-file".to_string(),
+        )
+        .into());
+    }
+
+    if !is_quiet() && format == OutputFormat::Human {
+        eprintln!(
+            "Indexing {} twice and running {} queries...",
+            colors::file_path(&args.path.display().to_string()),
+            queries.len()
+        );
+    }
+    let comparison = services.compare_configs(&CompareRequest {
+        path: args.path,
+        a: args.a,
+        b: args.b,
+        queries,
+        k: args.k as usize,
+        defaults: None,
+    })?;
+
+    match format {
+        OutputFormat::Human => print_comparison(&comparison),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&comparison, format),
+    }
+
+    Ok(())
+}
+
+/// Print the variants side by side, then one row per query
+fn print_comparison(comparison: &ConfigComparison) {
+    let (a, b) = (&comparison.a, &comparison.b);
+    println!(
+        "{} {} (top {} results per query)",
+        colors::label("Compared:"),
+        colors::file_path(&comparison.repository.display().to_string()),
+        comparison.k
+    );
+    println!();
+
+    let queries = comparison.queries.len();
+    let row = |label: &str, value: &dyn Fn(&VariantReport) -> String| {
+        println!(
+            "{}  {:<28}  {}",
+            colors::label(&format!("{label:<22}")),
+            value(a),
+            value(b)
+        );
+    };
+    println!(
+        "{:<22}  {:<28}  {}",
+        "",
+        colors::label("A"),
+        colors::label("B")
+    );
+    row("Settings", &|v| v.settings.to_string());
+    row("Chunk size / overlap", &|v| {
+        format!("{} / {}", v.chunk_size, v.overlap)
+    });
+    row("Files indexed", &|v| v.files_indexed.to_string());
+    row("Chunks", &|v| v.chunks_created.to_string());
+    row("Index size", &|v| format_bytes(v.index_size_bytes));
+    row("Indexing time", &|v| format!("{} ms", v.indexing_ms));
+    row("Queries with results", &|v| {
+        format!("{}/{queries}", v.queries_with_results)
+    });
+    row("Scores min/med/max", &|v| score_range(&v.scores));
+
+    println!();
+    println!(
+        "{}",
+        colors::label(&format!(
+            "{:<32}  {:>14}  {:>14}  {:>7}",
+            "Query", "A hits (top)", "B hits (top)", "Overlap"
+        ))
+    );
+    for query in &comparison.queries {
+        println!(
+            "{:<32}  {:>14}  {:>14}  {:>7.2}",
+            truncate(&query.query, 32),
+            hits(&query.a),
+            hits(&query.b),
+            query.file_overlap
+        );
+    }
+    println!();
+    println!(
+        "{} {}",
+        colors::label("Mean file overlap:"),
+        colors::number(&format!("{:.2}", comparison.mean_file_overlap))
+    );
+}
+
+/// Result count and top score, e.g. `10 (7.31)`
+fn hits(run: &QueryRun) -> String {
+    match run.top_score {
+        Some(top) => format!("{} ({top:.2})", run.results),
+        None => "0".to_string(),
+    }
+}
+
+/// Score range for the summary table, e.g. `1.20 / 3.05 / 7.31`
+fn score_range(scores: &ScoreSummary) -> String {
+    if scores.count == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{:.2} / {:.2} / {:.2}",
+        scores.min, scores.median, scores.max
+    )
+}
+
+/// Shorten `text` to `max` characters, ending with "..." when cut
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max - 3).collect();
+    format!("{kept}...")
+}
@@ -1,9 +1,15 @@
-//! Config command - show current configuration
+//! Config commands - show current configuration and manage CLI defaults
 
-use crate::cli::OutputFormat;
+use crate::cli::defaults::DEFAULT_KEYS;
+use crate::cli::output::{colors, print_success, print_warning};
+use crate::cli::{CliDefaults, CliError, OutputFormat};
+use crate::core::config::Config;
+use crate::core::search::{DEFAULT_BM25_B, DEFAULT_BM25_K1};
 use crate::core::services::Services;
-use clap::Args;
+use crate::core::xdg::XdgDirs;
+use clap::{Args, Subcommand};
 use serde::Serialize;
+use std::path::Path;
 use std::sync::Arc;
 
 /// Arguments for the config command
@@ -14,12 +20,47 @@ pub struct ConfigArgs {
     pub all: bool,
 }
 
+/// Subcommands of `shebe config`
+#[derive(Subcommand, Debug)]
+pub enum DefaultsCommand {
+    /// Print one CLI default, or all of them
+    Get {
+        /// session, format, k or profile
+        key: Option<String>,
+    },
+    /// Set a CLI default (checked against the known keys and values)
+    Set {
+        /// session, format, k or profile
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Remove a CLI default
+    Unset {
+        /// session, format, k or profile
+        key: String,
+    },
+}
+
+/// CLI defaults and the file they are stored in
+#[derive(Debug, Serialize)]
+pub struct CliDefaultsOutput {
+    pub file: String,
+    pub defaults: CliDefaults,
+}
+
 /// Configuration response
 #[derive(Debug, Serialize)]
 pub struct ConfigResponse {
+    /// File the server configuration was read from (none: built-in defaults)
+    pub config_file: Option<String>,
     pub data_dir: String,
+    pub profile: String,
+    pub index_dir: String,
     pub indexing: IndexingConfig,
     pub search: SearchConfig,
+    /// Per-user defaults of CLI options (`shebe init`)
+    pub cli_defaults: CliDefaultsOutput,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,12 +69,16 @@ pub struct IndexingConfig {
     pub overlap: usize,
     pub default_include: Vec<String>,
     pub default_exclude: Vec<String>,
+    pub git_metadata: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SearchConfig {
     pub default_k: usize,
     pub max_k: usize,
+    pub path_boost: f32,
+    pub bm25_k1: f32,
+    pub bm25_b: f32,
 }
 
 /// Execute the config command
@@ -42,30 +87,56 @@ pub async fn execute(
     services: &Arc<Services>,
     format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let config = &services.config;
+    let config = services.config.current();
 
     // Get data directory from XDG
     let xdg = crate::core::xdg::XdgDirs::new();
     let data_dir = xdg.state_dir.to_string_lossy().into_owned();
 
+    let defaults_file = xdg.cli_defaults_file();
+    let defaults = CliDefaults::load(&defaults_file).unwrap_or_else(|e| {
+        print_warning(&e.to_string());
+        CliDefaults::default()
+    });
+
     let response = ConfigResponse {
+        config_file: Config::source_file(&xdg).map(|path| path.display().to_string()),
         data_dir,
+        profile: config.profile_name().to_string(),
+        index_dir: config.storage.index_dir.to_string_lossy().into_owned(),
         indexing: IndexingConfig {
             chunk_size: config.indexing.chunk_size,
             overlap: config.indexing.overlap,
             default_include: config.indexing.include_patterns.clone(),
             default_exclude: config.indexing.exclude_patterns.clone(),
+            git_metadata: config.indexing.git_metadata,
         },
         search: SearchConfig {
             default_k: config.search.default_k,
             max_k: config.search.max_k,
+            path_boost: config.search.path_boost,
+            bm25_k1: DEFAULT_BM25_K1,
+            bm25_b: DEFAULT_BM25_B,
+        },
+        cli_defaults: CliDefaultsOutput {
+            file: defaults_file.display().to_string(),
+            defaults,
         },
     };
 
     match format {
         OutputFormat::Human => {
             println!("Configuration:");
+            println!(
+                "  config_file: {}",
+                response
+                    .config_file
+                    .as_deref()
+                    .unwrap_or("none (built-in defaults)")
+            );
             println!("  data_dir: {}", response.data_dir);
+            println!("  profile: {}", response.profile);
+            println!("  index_dir: {}", response.index_dir);
             println!("  indexing:");
             println!("    chunk_size: {}", response.indexing.chunk_size);
             println!("    overlap: {}", response.indexing.overlap);
@@ -77,14 +148,123 @@ pub async fn execute(
                 "    default_exclude: {:?}",
                 response.indexing.default_exclude
             );
+            println!("    git_metadata: {}", response.indexing.git_metadata);
             println!("  search:");
             println!("    default_k: {}", response.search.default_k);
             println!("    max_k: {}", response.search.max_k);
+            println!("    path_boost: {}", response.search.path_boost);
+            println!(
+                "    bm25: k1={} b={} (session default)",
+                response.search.bm25_k1, response.search.bm25_b
+            );
+            println!();
+            print_defaults_human(&response.cli_defaults.defaults, &defaults_file);
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
     }
 
     Ok(())
 }
+
+/// Execute `shebe config get|set|unset`
+pub fn execute_defaults(
+    command: DefaultsCommand,
+    xdg: &XdgDirs,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = xdg.cli_defaults_file();
+    let mut defaults = CliDefaults::load(&path)?;
+
+    match command {
+        DefaultsCommand::Get { key: None } => print_defaults(&defaults, &path, format),
+        DefaultsCommand::Get { key: Some(key) } => {
+            let value = defaults.get(&key)?;
+            match format {
+                OutputFormat::Human => println!("{}", value.as_deref().unwrap_or("(unset)")),
+                OutputFormat::Json | OutputFormat::Ndjson => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "key": key,
+                        "value": value,
+                    }))?
+                ),
+            }
+            Ok(())
+        }
+        DefaultsCommand::Set { key, value } => {
+            defaults.set(&key, &value)?;
+            if key == "profile" {
+                check_profile(xdg, &value)?;
+            }
+            defaults.save(&path)?;
+            if format == OutputFormat::Human {
+                print_success(&format!("Set {key} = {value}"));
+            }
+            print_defaults(&defaults, &path, format)
+        }
+        DefaultsCommand::Unset { key } => {
+            defaults.unset(&key)?;
+            defaults.save(&path)?;
+            if format == OutputFormat::Human {
+                print_success(&format!("Unset {key}"));
+            }
+            print_defaults(&defaults, &path, format)
+        }
+    }
+}
+
+/// Reject a profile the configuration does not define
+pub(crate) fn check_profile(
+    xdg: &XdgDirs,
+    profile: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load_with_xdg(xdg)?;
+    if config.profiles.contains_key(profile) {
+        return Ok(());
+    }
+    let available = if config.profiles.is_empty() {
+        "none configured".to_string()
+    } else {
+        config
+            .profiles
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Err(CliError::InvalidArgs(format!(
+        "Unknown profile '{profile}' (available: {available})"
+    ))
+    .into())
+}
+
+/// Print the CLI defaults stored at `path`
+pub(crate) fn print_defaults(
+    defaults: &CliDefaults,
+    path: &Path,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Human => print_defaults_human(defaults, path),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let output = CliDefaultsOutput {
+                file: path.display().to_string(),
+                defaults: defaults.clone(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_defaults_human(defaults: &CliDefaults, path: &Path) {
+    println!("CLI defaults ({}):", path.display());
+    for key in DEFAULT_KEYS {
+        match defaults.get(key).ok().flatten() {
+            Some(value) => println!("  {key}: {value}"),
+            None => println!("  {key}: {}", colors::dim("(unset)")),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Diff-sessions command - files changed between two sessions
+
+use crate::cli::commands::session_not_found;
+use crate::cli::output::{colors, print_output};
+use crate::cli::OutputFormat;
+use crate::core::services::Services;
+use crate::core::storage::DiffGroup;
+use clap::Args;
+use std::sync::Arc;
+
+/// Arguments for the diff-sessions command
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Baseline session ID
+    pub from: String,
+
+    /// Session ID compared against the baseline
+    pub to: String,
+
+    /// Compare sessions of different repositories by relative path
+    #[arg(long, short = 'f')]
+    pub force: bool,
+}
+
+/// Execute the diff-sessions command
+pub async fn execute(
+    args: DiffArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for session in [&args.from, &args.to] {
+        if !services.storage.session_exists(session) {
+            return Err(session_not_found(services, session).into());
+        }
+    }
+
+    let diff = services
+        .storage
+        .diff_sessions(&args.from, &args.to, args.force)?;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} {} -> {}",
+                colors::label("Diff:"),
+                colors::session_id(&diff.from),
+                colors::session_id(&diff.to)
+            );
+            println!("{} {}", colors::label("Compared by:"), diff.basis.label());
+            println!(
+                "{} {} added, {} removed, {} changed, {} renamed, {} unchanged",
+                colors::label("Files:"),
+                colors::number(&diff.added.count.to_string()),
+                colors::number(&diff.removed.count.to_string()),
+                colors::number(&diff.changed.count.to_string()),
+                colors::number(&diff.renamed.count.to_string()),
+                diff.unchanged
+            );
+
+            print_group("Added", &diff.added);
+            print_group("Removed", &diff.removed);
+            print_group("Changed", &diff.changed);
+            print_group("Renamed", &diff.renamed);
+
+            println!();
+            println!("{}", colors::success(&diff.verdict));
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&diff, format),
+    }
+
+    Ok(())
+}
+
+/// Print a category's listed files and how many were left out
+fn print_group(title: &str, group: &DiffGroup) {
+    if group.count == 0 {
+        return;
+    }
+    println!();
+    println!("{}", colors::label(&format!("{title} ({}):", group.count)));
+    for file in &group.files {
+        match &file.renamed_from {
+            Some(from) => println!(
+                "  {} -> {}  {}",
+                colors::file_path(from),
+                colors::file_path(&file.path),
+                colors::dim(&file.chunk_label())
+            ),
+            None => println!(
+                "  {}  {}",
+                colors::file_path(&file.path),
+                colors::dim(&file.chunk_label())
+            ),
+        }
+    }
+    if group.omitted() > 0 {
+        println!(
+            "  {}",
+            colors::dim(&format!("... and {} more", group.omitted()))
+        );
+    }
+}
@@ -1,30 +1,57 @@
 //! Index command - index a repository for search
 
-use crate::cli::output::{colors, format_duration};
-use crate::cli::OutputFormat;
+use crate::cli::commands::cancel_on_ctrl_c;
+use crate::cli::output::{
+    colors, format_bytes, format_count, format_duration, format_timing, is_quiet, print_renames,
+    print_warning, Event, NdjsonWriter, ProgressEvent, SummaryEvent,
+};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::indexer::presets::with_presets;
+use crate::core::indexer::{
+    resolve_index_root, BoundaryHint, DetectedRoot, DryRunReport, IndexOverrides, IndexProgress,
+};
+use crate::core::remote::{is_remote_url, validate_git_ref, validate_remote_url, RemoteSource};
 use crate::core::services::Services;
+use crate::core::storage::Provenance;
+use crate::core::types::{
+    FileRename, IndexProfile, PatternCounts, SkipCounts, SkippedFile, TruncatedFile,
+};
 use clap::Args;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Files between NDJSON progress events
+const PROGRESS_EVERY: usize = 100;
+
+/// How often the NDJSON progress thread checks the run
+const PROGRESS_POLL: Duration = Duration::from_millis(50);
 
 /// Arguments for the index command
 #[derive(Args, Debug)]
 pub struct IndexArgs {
-    /// Path to the repository to index
+    /// Path to the repository to index, or a git URL to shallow-clone
     pub path: PathBuf,
 
+    /// Branch or tag to clone when PATH is a git URL [default: remote's default branch]
+    #[arg(long = "ref", value_name = "REF")]
+    pub git_ref: Option<String>,
+
     /// Session ID for the index
     #[arg(long, short = 's')]
     pub session: String,
 
-    /// Characters per chunk (100-2000)
-    #[arg(long, default_value = "512")]
-    pub chunk_size: usize,
+    /// Characters per chunk (100-2000) [default: .shebe.toml, then config]
+    #[arg(long)]
+    pub chunk_size: Option<usize>,
 
-    /// Overlap between chunks (0-500)
-    #[arg(long, default_value = "64")]
-    pub overlap: usize,
+    /// Overlap between chunks (0-500) [default: .shebe.toml, then config]
+    #[arg(long)]
+    pub overlap: Option<usize>,
 
     /// Glob patterns to include (can be specified multiple times)
     #[arg(long, short = 'i')]
@@ -34,13 +61,83 @@ pub struct IndexArgs {
     #[arg(long, short = 'e')]
     pub exclude: Vec<String>,
 
+    /// Language preset added to the include patterns, e.g. rust, python, docs, web (repeatable)
+    #[arg(long = "lang", value_name = "PRESET")]
+    pub languages: Vec<String>,
+
     /// Force re-index if session exists
     #[arg(long, short = 'f')]
     pub force: bool,
 
-    /// Suppress progress output
-    #[arg(long, short = 'q')]
-    pub quiet: bool,
+    /// Allow indexing `/`, the home directory or the storage root
+    #[arg(long)]
+    pub allow_dangerous_path: bool,
+
+    /// When PATH is a file, index the repository that contains it (nearest
+    /// parent with .git, Cargo.toml, package.json or pyproject.toml)
+    #[arg(long)]
+    pub detect_root: bool,
+
+    /// Index only the first of several files with identical content
+    #[arg(long)]
+    pub dedupe: bool,
+
+    /// Align chunk ends to: none, blank_line, newline [default: none]
+    #[arg(long, value_name = "HINT")]
+    pub boundary_hint: Option<BoundaryHint>,
+
+    /// Do not store chunk text in the index; search reads it from the files
+    #[arg(long)]
+    pub no_store_chunk_text: bool,
+
+    /// Replace long base64/minified blobs with placeholders before chunking
+    #[arg(long)]
+    pub strip_high_entropy: bool,
+
+    /// Strip non-whitespace runs longer than this many characters [default: 200]
+    #[arg(long, value_name = "N", requires = "strip_high_entropy")]
+    pub strip_run_chars: Option<usize>,
+
+    /// Merge a file's final chunk into the previous one when under 15% of the chunk size
+    #[arg(long)]
+    pub merge_tail_fragments: bool,
+
+    /// Chunk CSV, TSV and JSON Lines files by character count instead of by whole records
+    #[arg(long)]
+    pub no_record_chunking: bool,
+
+    /// Index at most N chunks of one file; the rest of a longer file is left out [default: 2000]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_chunks_per_file: Option<u64>,
+
+    /// Do not read last-commit times from git; use file modification times
+    #[arg(long)]
+    pub no_git_metadata: bool,
+
+    /// Fail instead of warning when the include patterns match no file
+    #[arg(long)]
+    pub strict_patterns: bool,
+
+    /// Index only the files listed in FILE, one path per line ("-" reads stdin)
+    #[arg(long, value_name = "FILE", conflicts_with = "files_from0")]
+    pub files_from: Option<PathBuf>,
+
+    /// Like --files-from, but paths are separated by NUL bytes (as `git ls-files -z` prints)
+    #[arg(long, value_name = "FILE")]
+    pub files_from0: Option<PathBuf>,
+
+    /// Only report what would be indexed (no session is created)
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Time each indexing stage and print a performance breakdown with the slowest files
+    #[arg(long, conflicts_with = "dry_run")]
+    pub profile_indexing: bool,
+
+    /// List skipped files with the reason each was skipped (the global
+    /// `--verbose`)
+    #[arg(skip)]
+    pub verbose: bool,
 }
 
 /// Indexing result response
@@ -50,8 +147,48 @@ pub struct IndexResponse {
     pub path: String,
     pub files_indexed: usize,
     pub chunks_created: usize,
+    pub files_deduplicated: usize,
+    pub bytes_stripped: u64,
+    pub files_streamed: usize,
+    /// Files cut off at `max_chunks_per_file`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub truncated_files: Vec<TruncatedFile>,
+    /// Files that only moved since the replaced session
+    pub files_renamed: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub renamed_files: Vec<FileRename>,
     pub duration_secs: f64,
     pub throughput_files_per_sec: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    /// Repository root chosen for a file PATH with --detect-root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected_root: Option<DetectedRoot>,
+    pub skipped: SkipCounts,
+    /// Listed with --verbose (first 200)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_files: Vec<SkippedFile>,
+    /// Files matched by each include and exclude pattern
+    pub patterns: PatternCounts,
+    /// Set when the include patterns matched no file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_warning: Option<String>,
+    /// Version, config, git commit and timing of this run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Per-stage timings, with --profile-indexing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<IndexProfile>,
+}
+
+/// Dry run response
+#[derive(Debug, Serialize)]
+pub struct DryRunResponse {
+    pub path: String,
+    pub chunk_size: usize,
+    pub overlap: usize,
+    #[serde(flatten)]
+    pub report: DryRunReport,
 }
 
 /// Execute the index command
@@ -60,35 +197,78 @@ pub async fn execute(
     services: &Arc<Services>,
     format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Validate path
-    let path = args.path.canonicalize().map_err(|e| {
-        format!(
-            "Invalid path '{}': {}. Make sure the path exists and is accessible.",
-            args.path.display(),
-            e
-        )
-    })?;
-
-    if !path.is_dir() {
-        return Err(format!(
-            "Path '{}' is not a directory. Shebe can only index directories, not individual files.",
-            path.display()
+    let started = Instant::now();
+    if args.dry_run && format == OutputFormat::Ndjson {
+        return Err(CliError::InvalidArgs(
+            "--dry-run has no NDJSON events. Use --format json instead.".into(),
         )
         .into());
     }
 
+    // Expand language presets before any work is done
+    let include_patterns = with_presets(
+        (!args.include.is_empty()).then(|| args.include.clone()),
+        &args.languages,
+    )?;
+
+    // Validate path (or git URL)
+    let path_arg = args.path.to_string_lossy().into_owned();
+    let local_path = if is_remote_url(&path_arg) {
+        validate_remote_url(&path_arg)?;
+        if let Some(git_ref) = &args.git_ref {
+            validate_git_ref(git_ref)?;
+        }
+        if args.dry_run {
+            return Err(CliError::InvalidArgs(
+                "--dry-run is not supported for git URLs. \
+                 Clone the repository and dry-run the local path instead."
+                    .into(),
+            )
+            .into());
+        }
+        None
+    } else {
+        if args.git_ref.is_some() {
+            return Err(
+                CliError::InvalidArgs("--ref only applies when PATH is a git URL.".into()).into(),
+            );
+        }
+        let path = args.path.canonicalize().map_err(|e| {
+            CliError::InvalidArgs(format!(
+                "Invalid path '{}': {}. Make sure the path exists and is accessible.",
+                args.path.display(),
+                e
+            ))
+        })?;
+
+        let root = resolve_index_root(&path, args.detect_root)
+            .map_err(|e| CliError::InvalidArgs(e.to_string()))?;
+        if let Some(detected) = &root.detected {
+            if !is_quiet() && format == OutputFormat::Human {
+                eprintln!(
+                    "Repository root: {}",
+                    colors::file_path(&detected.summary())
+                );
+            }
+        }
+        Some(root)
+    };
+    let detected_root = local_path.as_ref().and_then(|root| root.detected.clone());
+    let local_path = local_path.map(|root| root.path);
+
     // Validate session ID
     if args.session.is_empty() {
-        return Err(
+        return Err(CliError::InvalidArgs(
             "Session ID cannot be empty. Provide a name like 'myproject' or 'backend'.".into(),
-        );
+        )
+        .into());
     }
     if args.session.len() > 64 {
-        return Err(format!(
+        return Err(CliError::InvalidArgs(format!(
             "Session ID '{}' is too long ({} chars). Maximum length is 64 characters.",
             args.session,
             args.session.len()
-        )
+        ))
         .into());
     }
 
@@ -98,66 +278,139 @@ pub async fn execute(
         .chars()
         .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
     {
-        return Err(format!(
+        return Err(CliError::InvalidArgs(format!(
             "Session ID '{}' contains invalid characters. \
              Use only letters, numbers, hyphens and underscores.",
             args.session
-        )
+        ))
         .into());
     }
 
     // Check if session exists (returns bool, not Result)
     let session_exists = services.storage.session_exists(&args.session);
-    if session_exists && !args.force {
-        return Err(format!(
+    if session_exists && !args.force && !args.dry_run {
+        return Err(CliError::InvalidArgs(format!(
             "Session '{}' already exists. Use --force to re-index, \
              or choose a different session name.",
             args.session
-        )
+        ))
         .into());
     }
 
     // Validate chunk size
-    if args.chunk_size < 100 || args.chunk_size > 2000 {
-        return Err(format!(
-            "Chunk size {} is out of range. Valid range is 100-2000 characters.",
-            args.chunk_size
-        )
-        .into());
+    if let Some(chunk_size) = args.chunk_size {
+        if !(100..=2000).contains(&chunk_size) {
+            return Err(CliError::InvalidArgs(format!(
+                "Chunk size {chunk_size} is out of range. Valid range is 100-2000 characters."
+            ))
+            .into());
+        }
     }
 
     // Validate overlap
-    if args.overlap > 500 {
-        return Err(format!(
-            "Overlap {} is too large. Maximum is 500 characters.",
-            args.overlap
-        )
-        .into());
+    if let Some(overlap) = args.overlap {
+        if overlap > 500 {
+            return Err(CliError::InvalidArgs(format!(
+                "Overlap {overlap} is too large. Maximum is 500 characters."
+            ))
+            .into());
+        }
     }
 
-    if args.overlap >= args.chunk_size {
-        return Err(format!(
-            "Overlap ({}) must be less than chunk size ({}).",
-            args.overlap, args.chunk_size
-        )
-        .into());
-    }
+    // Explicit file list: --files-from (lines) or --files-from0 (NUL-separated)
+    let file_list = match (&args.files_from, &args.files_from0) {
+        (Some(source), _) => Some((source, FileListFormat::Lines)),
+        (None, Some(source)) => Some((source, FileListFormat::Nul)),
+        (None, None) => None,
+    };
+    let files = match (file_list, &local_path) {
+        (None, _) => None,
+        (Some(_), None) => {
+            return Err(CliError::InvalidArgs(
+                "--files-from only applies when PATH is a local directory.".into(),
+            )
+            .into());
+        }
+        (Some(_), Some(_)) if args.dry_run => {
+            return Err(CliError::InvalidArgs(
+                "--dry-run cannot be combined with --files-from.".into(),
+            )
+            .into());
+        }
+        (Some((source, list_format)), Some(root)) => {
+            let files = read_file_list(source, list_format, root)?;
+            if files.is_empty() {
+                print_warning(&format!(
+                    "No files listed in {}; nothing was indexed.",
+                    source_label(source)
+                ));
+                return Ok(());
+            }
+            Some(files)
+        }
+    };
 
-    // Build configuration
-    let include_patterns = if args.include.is_empty() {
-        services.config.indexing.include_patterns.clone()
-    } else {
-        args.include
+    // Shallow-clone git URLs into the clone cache
+    let (path, remote) = match local_path {
+        Some(path) => (path, None),
+        None => {
+            if !is_quiet() && format == OutputFormat::Human {
+                eprintln!("Cloning {}...", colors::file_path(&path_arg));
+            }
+            let remote =
+                services
+                    .clones
+                    .clone_repo(&args.session, &path_arg, args.git_ref.as_deref())?;
+            let clone_path = services.clones.clone_path(&args.session).canonicalize()?;
+            (clone_path, Some(remote))
+        }
     };
 
-    let exclude_patterns = if args.exclude.is_empty() {
-        services.config.indexing.exclude_patterns.clone()
-    } else {
-        args.exclude
+    // Build configuration: arguments > .shebe.toml > config defaults
+    let index = services
+        .index(&path)
+        .session(&args.session)
+        .overrides(IndexOverrides {
+            chunk_size: args.chunk_size,
+            overlap: args.overlap,
+            include_patterns,
+            exclude_patterns: (!args.exclude.is_empty()).then_some(args.exclude),
+            languages: args.languages,
+            dedupe: args.dedupe,
+            boundary_hint: args.boundary_hint,
+            store_chunk_text: args.no_store_chunk_text.then_some(false),
+            strip_high_entropy: args.strip_high_entropy.then_some(true),
+            strip_run_chars: args.strip_run_chars,
+            merge_tail_fragments: args.merge_tail_fragments.then_some(true),
+            record_chunking: args.no_record_chunking.then_some(false),
+            max_chunks_per_file: args.max_chunks_per_file.map(|max| max as usize),
+            strict_patterns: args.strict_patterns.then_some(true),
+            git_metadata: args.no_git_metadata.then_some(false),
+        })
+        .force(args.force)
+        .allow_dangerous_path(args.allow_dangerous_path)
+        .profile(args.profile_indexing);
+    let index = match files {
+        Some(files) => index.files(files),
+        None => index,
     };
+    let resolved = index.resolve()?;
+    for warning in &resolved.warnings {
+        print_warning(&format!("{warning} (line skipped)"));
+    }
+
+    if args.dry_run {
+        let response = DryRunResponse {
+            path: path.to_string_lossy().into_owned(),
+            chunk_size: resolved.config.chunk_size,
+            overlap: resolved.config.overlap,
+            report: services.dry_run_index(&path, &resolved.config)?,
+        };
+        return print_dry_run(&response, format);
+    }
 
     // Index the repository
-    if !args.quiet && format == OutputFormat::Human {
+    if !is_quiet() && format == OutputFormat::Human {
         eprintln!(
             "Indexing {} as '{}'...",
             colors::file_path(&path.display().to_string()),
@@ -165,17 +418,34 @@ pub async fn execute(
         );
     }
 
-    // Call index_repository with all individual arguments
-    let stats = services.storage.index_repository(
-        &args.session,
-        &path,
-        include_patterns,
-        exclude_patterns,
-        args.chunk_size,
-        args.overlap,
-        services.config.indexing.max_file_size_mb,
-        args.force,
-    )?;
+    let max_chunks_per_file = resolved.config.max_chunks_per_file;
+
+    // Ctrl-C stops the run; no partial session is left behind
+    let progress = Arc::new(IndexProgress::new());
+    let mut events = NdjsonWriter::new();
+    let run = || {
+        cancel_on_ctrl_c(&progress, || {
+            index.progress(&progress).run_resolved(resolved)
+        })
+    };
+    let stats = if format == OutputFormat::Ndjson {
+        let done = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let reporter = scope.spawn(|| stream_progress(&progress, &done, &mut events));
+            let output = run();
+            done.store(true, Ordering::Relaxed);
+            reporter.join().expect("progress thread panicked")?;
+            Ok::<_, Box<dyn std::error::Error>>(output)
+        })??
+    } else {
+        run()?
+    }
+    .stats;
+    if remote.is_some() {
+        services
+            .storage
+            .set_remote_source(&args.session, remote.clone())?;
+    }
 
     let duration_secs = stats.duration_ms as f64 / 1000.0;
     let throughput = if duration_secs > 0.0 {
@@ -189,27 +459,382 @@ pub async fn execute(
         path: path.to_string_lossy().into_owned(),
         files_indexed: stats.files_indexed,
         chunks_created: stats.chunks_created,
+        files_deduplicated: stats.files_deduplicated,
+        bytes_stripped: stats.bytes_stripped,
+        files_streamed: stats.files_streamed,
+        truncated_files: stats.truncated_files,
+        files_renamed: stats.files_renamed,
+        renamed_files: stats.renamed_files,
         duration_secs,
         throughput_files_per_sec: throughput,
+        remote,
+        detected_root,
+        skipped: stats.skipped,
+        skipped_files: if args.verbose {
+            stats.skipped_files
+        } else {
+            Vec::new()
+        },
+        patterns: stats.patterns,
+        pattern_warning: stats.pattern_warning,
+        provenance: stats.provenance,
+        profile: stats.profile,
     };
+    if let Some(warning) = &response.pattern_warning {
+        print_warning(warning);
+    }
 
     match format {
         OutputFormat::Human => {
             println!(
                 "{} {} files ({} chunks) in {}",
                 colors::success("Indexed"),
-                colors::number(&response.files_indexed.to_string()),
-                colors::number(&response.chunks_created.to_string()),
+                colors::number(&format_count(response.files_indexed as u64)),
+                colors::number(&format_count(response.chunks_created as u64)),
                 colors::number(&format_duration(response.duration_secs))
             );
+            if response.files_deduplicated > 0 {
+                println!(
+                    "Skipped {} duplicate file(s)",
+                    colors::number(&format_count(response.files_deduplicated as u64))
+                );
+            }
+            if response.bytes_stripped > 0 {
+                println!(
+                    "Stripped {} of long blobs",
+                    colors::number(&format_bytes(response.bytes_stripped))
+                );
+            }
+            if response.files_streamed > 0 {
+                println!(
+                    "Streamed {} large file(s) in bounded pieces",
+                    colors::number(&format_count(response.files_streamed as u64))
+                );
+            }
+            if !response.truncated_files.is_empty() {
+                println!(
+                    "Indexed {} file(s) partially (max_chunks_per_file = {}):",
+                    colors::number(&format_count(response.truncated_files.len() as u64)),
+                    max_chunks_per_file
+                );
+                for file in &response.truncated_files {
+                    println!(
+                        "  {}  {}",
+                        colors::file_path(&file.path),
+                        colors::dim(&format!(
+                            "{} chunks, {} of {} ({}%)",
+                            format_count(file.chunks_indexed as u64),
+                            format_bytes(file.bytes_indexed),
+                            format_bytes(file.file_bytes),
+                            file.percent_indexed()
+                        ))
+                    );
+                }
+                println!(
+                    "  {}",
+                    colors::dim(
+                        "Re-index with a higher --max-chunks-per-file to index them in full"
+                    )
+                );
+            }
+            if response.files_renamed > 0 {
+                println!(
+                    "Renamed {} file(s) since the last run:",
+                    colors::number(&format_count(response.files_renamed as u64))
+                );
+                print_renames(&response.renamed_files, response.files_renamed, "  ");
+            }
+            if response.skipped.total() > 0 {
+                println!(
+                    "Skipped {} file(s): {}",
+                    colors::number(&format_count(response.skipped.total() as u64)),
+                    response.skipped.summary()
+                );
+            }
+            if !response.skipped_files.is_empty() {
+                for file in &response.skipped_files {
+                    println!(
+                        "  {}  {}",
+                        colors::file_path(&file.path),
+                        colors::dim(&format!("{}: {}", file.reason.label(), file.detail))
+                    );
+                }
+                let unlisted = response.skipped.total() - response.skipped_files.len();
+                if unlisted > 0 {
+                    println!("  ... and {unlisted} more");
+                }
+            }
+            if args.verbose && !response.patterns.include.is_empty() {
+                println!("Include patterns: {}", response.patterns.include_summary());
+            }
+            let exclusions = response.patterns.exclude_summary();
+            if args.verbose && !exclusions.is_empty() {
+                println!("Excluded by: {exclusions}");
+            }
             println!(
                 "Throughput: {} files/sec",
                 colors::number(&format!("{:.0}", response.throughput_files_per_sec))
             );
+            if let Some(remote) = &response.remote {
+                println!(
+                    "Cloned {} ({}) at commit {}",
+                    colors::file_path(&remote.url),
+                    remote.ref_label(),
+                    colors::number(remote.short_commit())
+                );
+            }
+            if let Some(profile) = &response.profile {
+                print_profile(profile);
+            }
         }
         OutputFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
+        OutputFormat::Ndjson => {
+            let mut summary =
+                SummaryEvent::new("index-repository", &response.session, started.elapsed());
+            summary.files_indexed = Some(response.files_indexed);
+            summary.chunks_created = Some(response.chunks_created);
+            events.finish(summary)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the performance breakdown of a profiled run
+fn print_profile(profile: &IndexProfile) {
+    println!();
+    println!(
+        "{} ({} profiled):",
+        colors::label("Performance breakdown"),
+        colors::number(&format_timing(Duration::from_micros(profile.total_us())))
+    );
+    for timing in &profile.stages {
+        println!(
+            "  {:<14}{:>10}  {:>5.1}%",
+            timing.stage.label(),
+            format_timing(Duration::from_micros(timing.duration_us)),
+            profile.percent(timing)
+        );
+    }
+    let mut memory = format!(
+        "{} chunk text held before writing",
+        format_bytes(profile.chunk_text_bytes)
+    );
+    if let Some(peak) = profile.peak_rss_bytes {
+        memory.push_str(&format!(", peak RSS {}", format_bytes(peak)));
+    }
+    println!("Memory: {memory}");
+    if !profile.slowest_files.is_empty() {
+        println!("Slowest files:");
+        for file in &profile.slowest_files {
+            println!(
+                "  {}  {}",
+                colors::file_path(&file.path),
+                colors::dim(&format_timing(Duration::from_micros(file.duration_us)))
+            );
+        }
+    }
+}
+
+/// Write a progress event every [`PROGRESS_EVERY`] files until `done`
+///
+/// A last event reports the final count when it was not already written.
+fn stream_progress(
+    progress: &IndexProgress,
+    done: &AtomicBool,
+    events: &mut NdjsonWriter,
+) -> std::io::Result<()> {
+    let mut reported = None;
+    loop {
+        let finished = done.load(Ordering::Relaxed);
+        let processed = progress.files_processed();
+        let due = match reported {
+            Some(last) => processed >= last + PROGRESS_EVERY || (finished && processed != last),
+            None => processed >= PROGRESS_EVERY || finished,
+        };
+        if due {
+            events.emit(&Event::Progress(ProgressEvent {
+                files_processed: processed,
+                files_total: progress.files_total(),
+            }))?;
+            reported = Some(processed);
+        }
+        if finished {
+            return Ok(());
+        }
+        thread::sleep(PROGRESS_POLL);
+    }
+}
+
+/// How entries are separated in a --files-from list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileListFormat {
+    /// One path per line (--files-from)
+    Lines,
+    /// NUL-separated paths, which may contain newlines (--files-from0)
+    Nul,
+}
+
+/// Name of a --files-from source for messages
+fn source_label(source: &Path) -> String {
+    if source == Path::new("-") {
+        "stdin".to_string()
+    } else {
+        format!("'{}'", source.display())
+    }
+}
+
+/// Read and check the paths listed in `source` ("-" for stdin)
+///
+/// Blank entries are skipped. Relative paths are resolved against
+/// `root`; every path must name an existing file under `root`. The
+/// first invalid entry is reported with its line (or entry) number.
+fn read_file_list(
+    source: &Path,
+    list_format: FileListFormat,
+    root: &Path,
+) -> Result<Vec<PathBuf>, CliError> {
+    let mut bytes = Vec::new();
+    let read = if source == Path::new("-") {
+        std::io::stdin().read_to_end(&mut bytes).map(|_| ())
+    } else {
+        std::fs::File::open(source).and_then(|mut file| file.read_to_end(&mut bytes).map(|_| ()))
+    };
+    read.map_err(|e| {
+        CliError::InvalidArgs(format!(
+            "Cannot read file list from {}: {}",
+            source_label(source),
+            e
+        ))
+    })?;
+
+    let (separator, unit) = match list_format {
+        FileListFormat::Lines => (b'\n', "line"),
+        FileListFormat::Nul => (b'\0', "entry"),
+    };
+
+    let mut files = Vec::new();
+    for (idx, entry) in bytes.split(|&b| b == separator).enumerate() {
+        let number = idx + 1;
+        let invalid = |problem: String| {
+            CliError::InvalidArgs(format!(
+                "{} {unit} {number}: {problem}",
+                source_label(source)
+            ))
+        };
+
+        let entry = match list_format {
+            FileListFormat::Lines => entry.strip_suffix(b"\r").unwrap_or(entry),
+            FileListFormat::Nul => entry,
+        };
+        if entry.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let entry = std::str::from_utf8(entry)
+            .map_err(|_| invalid("path is not valid UTF-8".to_string()))?;
+
+        let path = root.join(entry);
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| invalid(format!("'{entry}' cannot be read: {e}")))?;
+        if !canonical.starts_with(root) {
+            return Err(invalid(format!(
+                "'{entry}' is outside the repository {}",
+                root.display()
+            )));
+        }
+        if !canonical.is_file() {
+            return Err(invalid(format!("'{entry}' is not a file")));
+        }
+        files.push(canonical);
+    }
+
+    Ok(files)
+}
+
+/// Print a dry run report in the requested format
+fn print_dry_run(
+    response: &DryRunResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = &response.report;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} {} (no session created)",
+                colors::label("Dry run:"),
+                colors::file_path(&response.path)
+            );
+            println!(
+                "Would index {} files ({}), ~{} chunks",
+                colors::number(&format_count(report.files as u64)),
+                colors::number(&format_bytes(report.total_bytes)),
+                colors::number(&format_count(report.estimated_chunks as u64))
+            );
+            println!(
+                "Skipped: {} too large, {} not matching include patterns",
+                colors::number(&format_count(report.skipped_too_large as u64)),
+                colors::number(&format_count(report.skipped_not_included as u64))
+            );
+
+            if !report.top_extensions.is_empty() {
+                println!("\n{}", colors::label("Top extensions:"));
+                for ext in &report.top_extensions {
+                    let name = if ext.extension.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        format!(".{}", ext.extension)
+                    };
+                    println!(
+                        "  {:<12} {:>6} files  {}",
+                        name,
+                        format_count(ext.files as u64),
+                        colors::dim(&format_bytes(ext.bytes))
+                    );
+                }
+            }
+
+            let (matched, unmatched): (Vec<_>, Vec<_>) =
+                report.exclusions.iter().partition(|e| e.matched());
+            if !report.exclusions.is_empty() {
+                println!("\n{}", colors::label("Excluded:"));
+                for exclusion in &matched {
+                    println!(
+                        "  {:<24} {} files, {} directories",
+                        exclusion.pattern, exclusion.files, exclusion.directories
+                    );
+                }
+                if !unmatched.is_empty() {
+                    println!(
+                        "  {}",
+                        colors::dim(&format!(
+                            "{} other pattern(s) excluded nothing",
+                            unmatched.len()
+                        ))
+                    );
+                }
+            }
+
+            if !report.sample_paths.is_empty() {
+                println!(
+                    "\n{}",
+                    colors::label(&format!(
+                        "First {} of {} files:",
+                        report.sample_paths.len(),
+                        format_count(report.files as u64)
+                    ))
+                );
+                for path in &report.sample_paths {
+                    println!("  {}", colors::file_path(path));
+                }
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(response)?);
+        }
     }
 
     Ok(())
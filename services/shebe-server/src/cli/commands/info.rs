@@ -1,7 +1,9 @@
 //! Info command - show version and server information
 
+use crate::cli::output::colors;
 use crate::cli::OutputFormat;
 use crate::core::services::Services;
+use crate::mcp::tools::usage::UsageSnapshot;
 use clap::Args;
 use serde::Serialize;
 use std::sync::Arc;
@@ -12,6 +14,10 @@ pub struct InfoArgs {
     /// Show detailed information
     #[arg(long, short = 'd')]
     pub detailed: bool,
+
+    /// Clear the tool usage statistics shebe-mcp keeps in the state directory
+    #[arg(long)]
+    pub reset_stats: bool,
 }
 
 /// Server information response
@@ -22,8 +28,13 @@ pub struct InfoResponse {
     pub protocol: String,
     pub tools: u32,
     pub data_dir: String,
+    pub profile: String,
+    pub index_dir: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sessions: Option<u32>,
+    /// Names of the defined collections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collections: Option<Vec<String>>,
 }
 
 /// Execute the info command
@@ -36,19 +47,47 @@ pub async fn execute(
     let xdg = crate::core::xdg::XdgDirs::new();
     let data_dir = xdg.state_dir.to_string_lossy().into_owned();
 
-    let sessions = if args.detailed {
-        Some(services.storage.list_sessions()?.len() as u32)
+    if args.reset_stats {
+        let path = xdg.tool_usage_file();
+        UsageSnapshot::reset_file(&path)?;
+        match format {
+            OutputFormat::Human => println!(
+                "{} tool usage statistics ({})",
+                colors::success("Reset"),
+                colors::file_path(&path.display().to_string())
+            ),
+            OutputFormat::Json | OutputFormat::Ndjson => println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "reset_stats": true,
+                    "path": path.display().to_string()
+                }))?
+            ),
+        }
+        return Ok(());
+    }
+
+    let config = services.config.current();
+    let (sessions, collections) = if args.detailed {
+        let collections = services.storage.list_collections()?;
+        (
+            Some(services.storage.list_sessions()?.len() as u32),
+            Some(collections.into_iter().map(|c| c.name).collect()),
+        )
     } else {
-        None
+        (None, None)
     };
 
     let info = InfoResponse {
         name: "shebe".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         protocol: "MCP 2024-11-05".to_string(),
-        tools: 14,
+        tools: 16,
         data_dir,
+        profile: config.profile_name().to_string(),
+        index_dir: config.storage.index_dir.to_string_lossy().into_owned(),
         sessions,
+        collections,
     };
 
     match format {
@@ -57,11 +96,19 @@ pub async fn execute(
             println!("Protocol: {}", info.protocol);
             println!("Tools: {}", info.tools);
             println!("Data: {}", info.data_dir);
+            println!("Profile: {} ({})", info.profile, info.index_dir);
             if let Some(count) = info.sessions {
                 println!("Sessions: {count}");
             }
+            if let Some(collections) = &info.collections {
+                if collections.is_empty() {
+                    println!("Collections: none");
+                } else {
+                    println!("Collections: {}", collections.join(", "));
+                }
+            }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             println!("{}", serde_json::to_string_pretty(&info)?);
         }
     }
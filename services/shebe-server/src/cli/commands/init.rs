@@ -0,0 +1,69 @@
+//! Init command - write per-user CLI defaults
+
+use crate::cli::commands::config::{check_profile, print_defaults};
+use crate::cli::{CliDefaults, CliError, OutputFormat};
+use crate::core::xdg::XdgDirs;
+use clap::Args;
+use std::env;
+
+/// Arguments for the init command
+///
+/// The global `--format`, when given, and the active profile
+/// (`--profile` or `SHEBE_PROFILE`) are recorded too.
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Session used when a command's session is left out
+    #[arg(long, short = 's')]
+    pub session: Option<String>,
+
+    /// Results per search-code and repl query when --limit is left out (1-100)
+    #[arg(long, short = 'k')]
+    pub limit: Option<usize>,
+
+    /// Replace existing CLI defaults
+    #[arg(long, short = 'f')]
+    pub force: bool,
+}
+
+/// Execute the init command
+///
+/// `format` is the `--format` given on the command line, stored as a
+/// default; `output` is the format the new defaults are printed in.
+pub fn execute(
+    args: InitArgs,
+    xdg: &XdgDirs,
+    profile: Option<&str>,
+    format: Option<OutputFormat>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = xdg.cli_defaults_file();
+    if path.exists() && !args.force {
+        return Err(CliError::InvalidArgs(format!(
+            "CLI defaults already exist at {}: use --force to replace them, or \
+             'shebe config set <key> <value>' to change one",
+            path.display()
+        ))
+        .into());
+    }
+
+    let mut defaults = CliDefaults {
+        format,
+        ..CliDefaults::default()
+    };
+    if let Some(session) = &args.session {
+        defaults.set("session", session)?;
+    }
+    if let Some(limit) = args.limit {
+        defaults.set("k", &limit.to_string())?;
+    }
+    let profile = profile
+        .map(str::to_string)
+        .or_else(|| env::var("SHEBE_PROFILE").ok().filter(|p| !p.is_empty()));
+    if let Some(profile) = profile {
+        defaults.set("profile", &profile)?;
+        check_profile(xdg, &profile)?;
+    }
+
+    defaults.save(&path)?;
+    print_defaults(&defaults, &path, output)
+}
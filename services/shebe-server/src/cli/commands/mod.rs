@@ -3,18 +3,76 @@
 //! Each command module handles argument parsing and execution for a specific CLI command.
 //! Command names match MCP tool names (underscores become hyphens in CLI).
 
+pub mod collection;
+pub mod compare;
 pub mod completions;
 pub mod config;
+pub mod diff;
 pub mod index;
 pub mod info;
+pub mod init;
 pub mod references;
+pub mod repl;
 pub mod search;
+pub mod self_test;
 pub mod session;
+pub mod storage;
+pub mod tree;
+
+use crate::cli::output::is_quiet;
+use crate::cli::{exit_code, CliError};
+use crate::core::indexer::IndexProgress;
+use crate::core::services::Services;
+use crate::mcp::utils::session_not_found_hint;
+use std::sync::Arc;
 
 // Re-export argument types for use in mod.rs
+pub use compare::CompareArgs;
 pub use completions::CompletionsArgs;
 pub use config::ConfigArgs;
+pub use diff::DiffArgs;
 pub use index::IndexArgs;
 pub use info::InfoArgs;
+pub use init::InitArgs;
 pub use references::ReferencesArgs;
+pub use repl::ReplArgs;
 pub use search::SearchArgs;
+pub use self_test::SelfTestArgs;
+pub use storage::StorageReportArgs;
+pub use tree::TreeArgs;
+
+/// Error for a session that does not exist
+///
+/// Suggests near matches and lists the sessions when there are few,
+/// using the same hint as the MCP tools.
+pub(crate) fn session_not_found(services: &Services, session: &str) -> CliError {
+    let sessions = services.storage.list_session_ids().unwrap_or_default();
+    CliError::SessionNotFound(format!(
+        "Session '{session}' not found. Run 'shebe list-sessions' to see available sessions.{}",
+        session_not_found_hint(session, &sessions)
+    ))
+}
+
+/// Run indexing `work`, cancelling `progress` when Ctrl-C is pressed
+///
+/// The first Ctrl-C asks the run to stop; it then removes its partial
+/// session and returns `Cancelled`. A second Ctrl-C exits at once.
+pub(crate) fn cancel_on_ctrl_c<T>(progress: &Arc<IndexProgress>, work: impl FnOnce() -> T) -> T {
+    let watched = Arc::clone(progress);
+    let watcher = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        if !is_quiet() {
+            eprintln!("Cancelling... (press Ctrl-C again to exit immediately)");
+        }
+        watched.cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(exit_code::INTERRUPTED);
+        }
+    });
+
+    let output = work();
+    watcher.abort();
+    output
+}
@@ -2,22 +2,35 @@
 //!
 //! This is the CLI equivalent of the `find_references` MCP tool.
 
-use crate::cli::output::{colors, format_relative_time};
-use crate::cli::OutputFormat;
+use crate::cli::commands::session_not_found;
+use crate::cli::export::{ExportArgs, ExportRow, ExportTarget, ExportWriter};
+use crate::cli::output::{
+    colors, format_relative_time, print_warning, Event, NdjsonWriter, ReferenceEvent, SummaryEvent,
+};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::definition::DefinitionMatcher;
 use crate::core::services::Services;
+use crate::core::sources::{self, SourceFiles, UnreadableFile, DEGRADED_CONTEXT_PENALTY};
 use crate::core::storage::SessionMetadata;
-use crate::core::types::SearchRequest;
+use crate::core::types::{SearchRequest, SearchResult};
+use crate::mcp::tools::helpers::{locate_symbol, SymbolLocation};
 use clap::Args;
 use regex::Regex;
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
+
+/// Most symbols accepted in one command
+pub const MAX_SYMBOLS: usize = 20;
 
 /// Arguments for the references command
 #[derive(Args, Debug)]
 pub struct ReferencesArgs {
-    /// Symbol name to find references for (minimum 2 characters)
-    pub symbol: String,
+    /// Symbol names to find references for (minimum 2 characters each);
+    /// give several (up to 20) to find them together
+    #[arg(required = true, value_name = "SYMBOL")]
+    pub symbols: Vec<String>,
 
     /// Session ID to search
     #[arg(long, short = 's')]
@@ -31,7 +44,7 @@ pub struct ReferencesArgs {
     #[arg(long)]
     pub defined_in: Option<String>,
 
-    /// Include definition site in results
+    /// List definition sites under "Files to update" and keep --defined-in references
     #[arg(long)]
     pub include_definition: bool,
 
@@ -42,10 +55,17 @@ pub struct ReferencesArgs {
     /// Maximum number of references to return (1-500)
     #[arg(long, short = 'k', default_value = "50")]
     pub max_results: usize,
+
+    /// Exit with code 5 when no references are found
+    #[arg(long)]
+    pub fail_on_empty: bool,
+
+    #[command(flatten)]
+    pub export: ExportArgs,
 }
 
 /// Symbol type for pattern matching
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
 pub enum SymbolTypeArg {
     /// Match function/method calls (symbol(), .symbol())
     Function,
@@ -56,24 +76,31 @@ pub enum SymbolTypeArg {
     /// Same as variable
     Constant,
     /// Match all patterns (default)
+    #[default]
     Any,
 }
 
-impl Default for SymbolTypeArg {
-    fn default() -> Self {
-        Self::Any
-    }
-}
-
 /// A single reference to a symbol
 #[derive(Debug, Serialize)]
 pub struct Reference {
     pub file_path: String,
+    /// 1-based line number (0 if the file could not be read)
     pub line_number: usize,
+    /// 1-based column, counted in characters (0 if unknown)
     pub column: usize,
+    /// 1-based columns of every occurrence on the line
+    pub columns: Vec<usize>,
+    /// Byte offset of the occurrence within the file
+    pub offset: usize,
     pub context: String,
     pub pattern: String,
     pub confidence: f32,
+    /// Whether the line defines the symbol (`fn X`, `class X`, ...)
+    pub is_definition: bool,
+    /// IO error kind if the file could not be read; the context is then
+    /// the indexed chunk text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_unavailable: Option<String>,
 }
 
 /// References output response
@@ -82,13 +109,73 @@ pub struct ReferencesOutput {
     pub symbol: String,
     pub session: String,
     pub total_count: usize,
+    pub definitions: usize,
     pub high_confidence: usize,
     pub medium_confidence: usize,
     pub low_confidence: usize,
     pub unique_files: usize,
+    /// References located in indexed text because the file was unreadable
+    pub degraded_context: usize,
     pub references: Vec<Reference>,
+    /// Files that could not be read
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unreadable_files: Vec<UnreadableFile>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_indexed_at: Option<String>,
+    /// Whether definition sites count as files to update
+    #[serde(skip)]
+    pub include_definition: bool,
+}
+
+/// References to several symbols
+#[derive(Debug, Serialize)]
+pub struct MultiReferencesOutput {
+    pub session: String,
+    /// One result per symbol, in command-line order
+    pub symbols: Vec<ReferencesOutput>,
+    /// Files to update across all symbols
+    pub files_to_update: Vec<FileToUpdate>,
+}
+
+/// A file to update and the symbols referenced in it
+#[derive(Debug, Serialize)]
+pub struct FileToUpdate {
+    pub file_path: String,
+    pub symbols: Vec<String>,
+}
+
+/// Whether a reference puts its file on the "Files to update" list:
+/// high-confidence usages, plus definitions with `--include-definition`
+fn needs_update(r: &Reference, include_definition: bool) -> bool {
+    if r.is_definition {
+        include_definition
+    } else {
+        r.confidence >= 0.80
+    }
+}
+
+/// Files to update across several symbols, each with the symbols
+/// (in command-line order) that need it updated
+pub fn files_to_update(outputs: &[ReferencesOutput]) -> Vec<FileToUpdate> {
+    let mut files: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for output in outputs {
+        for r in &output.references {
+            if !needs_update(r, output.include_definition) {
+                continue;
+            }
+            let symbols = files.entry(r.file_path.as_str()).or_default();
+            if !symbols.contains(&output.symbol) {
+                symbols.push(output.symbol.clone());
+            }
+        }
+    }
+    files
+        .into_iter()
+        .map(|(file_path, symbols)| FileToUpdate {
+            file_path: file_path.to_string(),
+            symbols,
+        })
+        .collect()
 }
 
 /// Build regex patterns for matching symbol usages based on symbol type.
@@ -205,13 +292,6 @@ fn adjust_confidence(base_confidence: f32, file_path: &str, line_content: &str)
     confidence.clamp(0.0, 1.0)
 }
 
-/// Convert byte offset to line number (1-based).
-fn byte_offset_to_line_number(content: &str, byte_offset: usize) -> usize {
-    let bytes = content.as_bytes();
-    let safe_offset = byte_offset.min(bytes.len());
-    bytes[..safe_offset].iter().filter(|&&b| b == b'\n').count() + 1
-}
-
 /// Extract context lines around a line number.
 fn extract_context_lines(content: &str, line_number: usize, context_count: usize) -> String {
     let lines: Vec<&str> = content.lines().collect();
@@ -270,19 +350,67 @@ fn deduplicate_references(references: &mut Vec<Reference>) {
             .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    // Then sort by location to group duplicates
+    // Then sort by location to group duplicates (by offset where the
+    // line is unknown)
+    let location = |r: &Reference| {
+        let offset = r.context_unavailable.as_ref().map(|_| r.offset);
+        (r.file_path.clone(), r.line_number, offset)
+    };
     references.sort_by(|a, b| {
-        (&a.file_path, a.line_number)
-            .cmp(&(&b.file_path, b.line_number))
-            .then_with(|| {
-                b.confidence
-                    .partial_cmp(&a.confidence)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
+        location(a).cmp(&location(b)).then_with(|| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
     });
 
     // Deduplicate by location (keeps first = highest confidence)
-    references.dedup_by(|a, b| a.file_path == b.file_path && a.line_number == b.line_number);
+    references.dedup_by(|a, b| location(a) == location(b));
+}
+
+/// Reference for a hit in a file that could not be read, located and
+/// scored in the indexed chunk text with a confidence penalty
+fn chunk_reference(
+    result: SearchResult,
+    symbol: &str,
+    patterns: &[(Regex, &'static str, f32)],
+    definitions: &DefinitionMatcher,
+    context_lines: usize,
+    error: std::io::ErrorKind,
+) -> Option<Reference> {
+    let found = sources::locate_in_chunk(
+        &result.text,
+        result.start_offset,
+        symbol,
+        context_lines,
+        detect_language(&result.file_path),
+    )?;
+
+    let (pattern_name, base_confidence) = patterns
+        .iter()
+        .find(|(regex, _, _)| regex.is_match(&result.text))
+        .map(|(_, name, conf)| (*name, *conf))
+        .unwrap_or(("word_match", 0.60));
+    let confidence = adjust_confidence(base_confidence, &result.file_path, &found.context);
+    let is_definition = found.region.is_none() && definitions.is_definition(&found.line);
+
+    Some(Reference {
+        file_path: result.file_path,
+        line_number: 0,
+        column: 0,
+        columns: Vec::new(),
+        offset: found.offset,
+        context: found.context,
+        pattern: if is_definition {
+            "definition"
+        } else {
+            pattern_name
+        }
+        .to_string(),
+        confidence: (confidence - DEGRADED_CONTEXT_PENALTY).max(0.0),
+        is_definition,
+        context_unavailable: Some(error.to_string()),
+    })
 }
 
 /// Format results for human-readable output.
@@ -300,23 +428,26 @@ fn format_human_output(output: &ReferencesOutput, session_metadata: Option<&Sess
                 format_relative_time(&meta.last_indexed_at)
             );
         }
+        print_unreadable(&output.unreadable_files);
         return;
     }
 
-    // Group by confidence level
-    let high: Vec<&Reference> = output
-        .references
+    // Definitions apart, the rest grouped by confidence level
+    let (definitions, usages): (Vec<&Reference>, Vec<&Reference>) =
+        output.references.iter().partition(|r| r.is_definition);
+    let high: Vec<&Reference> = usages
         .iter()
+        .copied()
         .filter(|r| r.confidence >= 0.80)
         .collect();
-    let medium: Vec<&Reference> = output
-        .references
+    let medium: Vec<&Reference> = usages
         .iter()
+        .copied()
         .filter(|r| r.confidence >= 0.50 && r.confidence < 0.80)
         .collect();
-    let low: Vec<&Reference> = output
-        .references
+    let low: Vec<&Reference> = usages
         .iter()
+        .copied()
         .filter(|r| r.confidence < 0.50)
         .collect();
 
@@ -326,6 +457,17 @@ fn format_human_output(output: &ReferencesOutput, session_metadata: Option<&Sess
         colors::number(&output.total_count.to_string())
     );
 
+    // Definition sites
+    if !definitions.is_empty() {
+        println!(
+            "### Definition ({})\n",
+            colors::number(&definitions.len().to_string())
+        );
+        for r in &definitions {
+            print_single_reference(r);
+        }
+    }
+
     // High confidence
     if !high.is_empty() {
         println!(
@@ -362,6 +504,12 @@ fn format_human_output(output: &ReferencesOutput, session_metadata: Option<&Sess
     // Summary
     println!("---");
     println!("\nSummary:");
+    if !definitions.is_empty() {
+        println!(
+            "  Definitions:       {}",
+            colors::number(&definitions.len().to_string())
+        );
+    }
     println!(
         "  High confidence:   {} references",
         colors::success(&high.len().to_string())
@@ -378,6 +526,12 @@ fn format_human_output(output: &ReferencesOutput, session_metadata: Option<&Sess
         "  Total files:       {}",
         colors::number(&output.unique_files.to_string())
     );
+    if output.degraded_context > 0 {
+        println!(
+            "  Degraded context:  {} references (file unreadable)",
+            colors::warning(&output.degraded_context.to_string())
+        );
+    }
 
     // Session freshness
     if let Some(meta) = session_metadata {
@@ -388,59 +542,247 @@ fn format_human_output(output: &ReferencesOutput, session_metadata: Option<&Sess
         );
     }
 
-    // Files to update (high confidence only)
-    if !high.is_empty() {
+    // Files to update (high confidence usages, plus definitions if asked)
+    let update_files: HashSet<&str> = output
+        .references
+        .iter()
+        .filter(|r| needs_update(r, output.include_definition))
+        .map(|r| r.file_path.as_str())
+        .collect();
+    if !update_files.is_empty() {
         println!("\nFiles to update:");
-        let high_files: HashSet<&str> = high.iter().map(|r| r.file_path.as_str()).collect();
-        for file in high_files {
+        for file in update_files {
             println!("  {}", colors::file_path(file));
         }
     }
+
+    print_unreadable(&output.unreadable_files);
+}
+
+/// Print the "Warnings" section listing files that could not be read.
+fn print_unreadable(unreadable: &[UnreadableFile]) {
+    if unreadable.is_empty() {
+        return;
+    }
+    println!(
+        "\nWarnings: {} file(s) could not be read; their references use the indexed text:",
+        colors::warning(&unreadable.len().to_string())
+    );
+    for file in unreadable {
+        println!("  {}: {}", colors::file_path(&file.file_path), file.error);
+    }
 }
 
 /// Print a single reference in human-readable format.
 fn print_single_reference(r: &Reference) {
     let lang = detect_language(&r.file_path);
-    println!(
-        "#### {}:{}",
-        colors::file_path(&r.file_path),
-        colors::number(&r.line_number.to_string())
-    );
+    if r.context_unavailable.is_some() {
+        println!(
+            "#### {} (byte {})",
+            colors::file_path(&r.file_path),
+            colors::number(&r.offset.to_string())
+        );
+    } else {
+        println!(
+            "#### {}:{}:{}",
+            colors::file_path(&r.file_path),
+            colors::number(&r.line_number.to_string()),
+            colors::number(&r.column.to_string())
+        );
+    }
     println!("```{lang}");
     println!("{}", r.context.trim());
     println!("```");
     println!("  Pattern: {}", colors::dim(&r.pattern));
+    if r.columns.len() > 1 {
+        let columns: Vec<String> = r.columns.iter().map(|c| c.to_string()).collect();
+        println!("  Columns: {}", columns.join(", "));
+    }
     println!(
         "  Confidence: {}",
         colors::score(&format!("{:.2}", r.confidence))
     );
+    if let Some(error) = &r.context_unavailable {
+        println!(
+            "  Context: {} ({error}; indexed text shown)",
+            colors::warning("context_unavailable")
+        );
+    }
     println!();
 }
 
+/// Print the combined "Files to update" list of several symbols.
+fn print_files_to_update(files: &[FileToUpdate]) {
+    if files.is_empty() {
+        return;
+    }
+    println!("\nFiles to update (all symbols):");
+    for file in files {
+        println!(
+            "  {} ({})",
+            colors::file_path(&file.file_path),
+            colors::dim(&file.symbols.join(", "))
+        );
+    }
+}
+
+/// Write references (of every symbol, in order) to the --output file,
+/// confidence as the score and the context lines as the snippet
+fn export_references(outputs: &[ReferencesOutput], target: &ExportTarget) -> std::io::Result<()> {
+    let mut writer = ExportWriter::create(target)?;
+    let references = outputs.iter().flat_map(|output| &output.references);
+    for (i, r) in references.enumerate() {
+        writer.write(&ExportRow {
+            rank: i + 1,
+            file: r.file_path.clone(),
+            line: Some(r.line_number),
+            offset: r.offset,
+            score: r.confidence,
+            snippet: r.context.clone(),
+        })?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Write one NDJSON reference event per reference in `output`
+fn stream_references(output: &ReferencesOutput, events: &mut NdjsonWriter) -> std::io::Result<()> {
+    for r in &output.references {
+        events.emit(&Event::Reference(ReferenceEvent {
+            symbol: output.symbol.clone(),
+            file: r.file_path.clone(),
+            line: r.line_number,
+            column: r.column,
+            confidence: r.confidence,
+            pattern: r.pattern.clone(),
+            is_definition: r.is_definition,
+            context: r.context.clone(),
+            context_unavailable: r.context_unavailable.clone(),
+        }))?;
+    }
+    Ok(())
+}
+
 /// Execute the references command
 pub async fn execute(
     args: ReferencesArgs,
     services: &Arc<Services>,
     format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let started = Instant::now();
+
     // Validate session exists
     if !services.storage.session_exists(&args.session) {
-        return Err(format!(
-            "Session '{}' not found. Run 'shebe list-sessions' to see available sessions.",
-            args.session
-        )
+        return Err(session_not_found(services, &args.session).into());
+    }
+
+    // Validate symbols (duplicates are searched once)
+    if args.symbols.len() > MAX_SYMBOLS {
+        return Err(CliError::InvalidArgs(format!(
+            "At most {MAX_SYMBOLS} symbols can be given (got {})",
+            args.symbols.len()
+        ))
         .into());
     }
+    let mut symbols: Vec<&str> = Vec::with_capacity(args.symbols.len());
+    for symbol in &args.symbols {
+        let symbol = symbol.trim();
+        if symbol.is_empty() {
+            return Err(CliError::InvalidArgs("Symbol cannot be empty".into()).into());
+        }
+        if symbol.len() < 2 {
+            return Err(
+                CliError::InvalidArgs("Symbol must be at least 2 characters".into()).into(),
+            );
+        }
+        if !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
 
-    // Validate symbol
-    let symbol = args.symbol.trim();
-    if symbol.is_empty() {
-        return Err("Symbol cannot be empty".into());
+    // NDJSON streams each symbol's references as soon as they are found
+    let target = args.export.target()?;
+    let stream = format == OutputFormat::Ndjson && target.is_none();
+    let mut events = NdjsonWriter::new();
+    let mut outputs = Vec::with_capacity(symbols.len());
+    for symbol in &symbols {
+        let output = find_references(&args, symbol, services)?;
+        if stream {
+            stream_references(&output, &mut events)?;
+        }
+        outputs.push(output);
     }
-    if symbol.len() < 2 {
-        return Err("Symbol must be at least 2 characters".into());
+    let empty = outputs.iter().all(|o| o.total_count == 0);
+
+    match (&target, format) {
+        (Some(target), _) => {
+            export_references(&outputs, target)?;
+            let mut warned = HashSet::new();
+            let unreadable = outputs.iter().flat_map(|o| &o.unreadable_files);
+            for file in unreadable.filter(|f| warned.insert(&f.file_path)) {
+                print_warning(&format!(
+                    "Could not read {} ({}); its references use the indexed text",
+                    file.file_path, file.error
+                ));
+            }
+        }
+        (None, OutputFormat::Human) => {
+            let session_metadata = services.storage.get_session_metadata(&args.session).ok();
+            for (i, output) in outputs.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                format_human_output(output, session_metadata.as_ref());
+            }
+            if outputs.len() > 1 {
+                print_files_to_update(&files_to_update(&outputs));
+            }
+        }
+        (None, OutputFormat::Json) => {
+            if let [output] = outputs.as_slice() {
+                println!("{}", serde_json::to_string_pretty(output)?);
+            } else {
+                let output = MultiReferencesOutput {
+                    session: args.session.clone(),
+                    files_to_update: files_to_update(&outputs),
+                    symbols: outputs,
+                };
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+        }
+        (None, OutputFormat::Ndjson) => {
+            events.finish(SummaryEvent::new(
+                "find-references",
+                &args.session,
+                started.elapsed(),
+            ))?;
+        }
     }
 
+    if args.fail_on_empty && empty {
+        return Err(CliError::NoMatches(format!(
+            "No references found for '{}' in session '{}'",
+            symbols.join("', '"),
+            args.session
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Find references to `symbol` (trimmed) in the session, with the
+/// options in `args`
+///
+/// What `execute` prints or exports for each symbol. Arguments are
+/// clamped; validation of the session and symbol is left to the caller.
+pub fn find_references(
+    args: &ReferencesArgs,
+    symbol: &str,
+    services: &Services,
+) -> crate::core::error::Result<ReferencesOutput> {
+    let symbol = symbol.trim();
+
     // Clamp parameters
     let context_lines = args.context_lines.clamp(0, 10);
     let max_results = args.max_results.clamp(1, 500);
@@ -450,15 +792,30 @@ pub async fn execute(
         query: symbol.to_string(),
         session: args.session.clone(),
         k: Some(max_results * 2), // Over-fetch to allow for filtering
+        group_by_file: false,
+        dedupe_overlaps: true,
+        path_boost: Some(0.0), // Candidates are ranked by pattern confidence
+        fuzziness: Some(0),
+        expand_identifiers: false, // Only the symbol's own spelling is a reference
+        bm25_k1: None,
+        bm25_b: None,
+        within: None,
+        penalize_short_chunks: None,
+        modified_within: None,
+        section_filter: None,
+        exclude_terms: Vec::new(),
+        exclude_paths: Vec::new(),
+        explain: false,
     };
     let search_response = services.search.search(search_request)?;
 
     // Build patterns based on symbol_type
     let patterns = build_patterns(symbol, args.symbol_type);
+    let definitions = DefinitionMatcher::new(symbol);
 
     // Process search results
     let mut references: Vec<Reference> = Vec::new();
-    let mut files_cache: HashMap<String, String> = HashMap::new();
+    let mut files = SourceFiles::new();
 
     for result in search_response.results {
         // Skip definition file if requested
@@ -470,24 +827,41 @@ pub async fn execute(
             }
         }
 
-        // Read file content (cached to avoid re-reading)
-        let file_content = if let Some(content) = files_cache.get(&result.file_path) {
-            content.clone()
-        } else {
-            match std::fs::read_to_string(&result.file_path) {
-                Ok(content) => {
-                    files_cache.insert(result.file_path.clone(), content.clone());
-                    content
+        // Read file content and its comment/string regions (cached to
+        // avoid re-reading)
+        let (file_content, regions) =
+            match files.get(&result.file_path, detect_language(&result.file_path)) {
+                Ok(file) => (&file.content, &file.regions),
+                Err(error) => {
+                    // Keep the reference, located in the indexed chunk text
+                    references.extend(chunk_reference(
+                        result,
+                        symbol,
+                        &patterns,
+                        &definitions,
+                        context_lines,
+                        error,
+                    ));
+                    continue;
                 }
-                Err(_) => continue, // Skip unreadable files
-            }
+            };
+
+        // Locate the chunk in the current file (skip chunks no longer there)
+        let Some(chunk) = result.locate_in(file_content).range() else {
+            continue;
         };
 
         // Find symbol position and calculate line number
-        let chunk_start = result.start_offset;
-        if let Some(symbol_pos) = result.text.find(symbol) {
-            let absolute_offset = chunk_start + symbol_pos;
-            let line_number = byte_offset_to_line_number(&file_content, absolute_offset);
+        if let Some(symbol_pos) = file_content[chunk.clone()].find(symbol) {
+            let absolute_offset = chunk.start + symbol_pos;
+            let Some(SymbolLocation {
+                line_number,
+                column,
+                columns,
+            }) = locate_symbol(file_content, absolute_offset, symbol)
+            else {
+                continue;
+            };
 
             // Match against patterns for confidence scoring
             let (pattern_name, base_confidence) = patterns
@@ -497,67 +871,85 @@ pub async fn execute(
                 .unwrap_or(("word_match", 0.60));
 
             // Extract context lines
-            let context = extract_context_lines(&file_content, line_number, context_lines);
+            let context = extract_context_lines(file_content, line_number, context_lines);
 
             // Adjust confidence based on context
             let confidence = adjust_confidence(base_confidence, &result.file_path, &context);
 
+            // Definition sites outside comments and strings
+            let line = file_content
+                .lines()
+                .nth(line_number - 1)
+                .unwrap_or_default();
+            let is_definition =
+                regions.kind_at(absolute_offset).is_none() && definitions.is_definition(line);
+            let pattern_name = if is_definition {
+                "definition"
+            } else {
+                pattern_name
+            };
+
             references.push(Reference {
                 file_path: result.file_path,
                 line_number,
-                column: symbol_pos,
+                column,
+                columns,
+                offset: absolute_offset,
                 context,
                 pattern: pattern_name.to_string(),
                 confidence,
+                is_definition,
+                context_unavailable: None,
             });
         }
     }
+    let unreadable_files = files.unreadable();
 
     // Deduplicate (keep highest confidence per location)
     deduplicate_references(&mut references);
 
-    // Sort by confidence (descending) and truncate
+    // Sort definitions first, then by confidence (descending), and truncate
     references.sort_by(|a, b| {
-        b.confidence
-            .partial_cmp(&a.confidence)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        b.is_definition.cmp(&a.is_definition).then_with(|| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
     });
     references.truncate(max_results);
 
     // Get session metadata for timestamp
     let session_metadata = services.storage.get_session_metadata(&args.session).ok();
 
-    // Count by confidence level
-    let high_count = references.iter().filter(|r| r.confidence >= 0.80).count();
-    let medium_count = references
-        .iter()
+    // Count definitions, and the rest by confidence level
+    let definition_count = references.iter().filter(|r| r.is_definition).count();
+    let usages = || references.iter().filter(|r| !r.is_definition);
+    let high_count = usages().filter(|r| r.confidence >= 0.80).count();
+    let medium_count = usages()
         .filter(|r| r.confidence >= 0.50 && r.confidence < 0.80)
         .count();
-    let low_count = references.iter().filter(|r| r.confidence < 0.50).count();
+    let low_count = usages().filter(|r| r.confidence < 0.50).count();
     let unique_files: HashSet<_> = references.iter().map(|r| &r.file_path).collect();
+    let degraded_context = references
+        .iter()
+        .filter(|r| r.context_unavailable.is_some())
+        .count();
 
-    let output = ReferencesOutput {
+    Ok(ReferencesOutput {
         symbol: symbol.to_string(),
         session: args.session.clone(),
         total_count: references.len(),
+        definitions: definition_count,
         high_confidence: high_count,
         medium_confidence: medium_count,
         low_confidence: low_count,
         unique_files: unique_files.len(),
+        degraded_context,
         references,
+        unreadable_files,
         session_indexed_at: session_metadata
             .as_ref()
             .map(|m| m.last_indexed_at.to_rfc3339()),
-    };
-
-    match format {
-        OutputFormat::Human => {
-            format_human_output(&output, session_metadata.as_ref());
-        }
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        }
-    }
-
-    Ok(())
+        include_definition: args.include_definition,
+    })
 }
@@ -0,0 +1,294 @@
+//! REPL command - interactive search loop
+//!
+//! `shebe repl --session X` keeps services initialized between queries,
+//! which makes iterative query refinement much faster than re-running
+//! `search-code`. Lines starting with `:` are REPL commands; anything
+//! else is a search query. Results use the `search-code` formatter.
+
+use crate::cli::commands::search::{build_request, print_response, SearchArgs};
+use crate::cli::export::ExportArgs;
+use crate::cli::output::{colors, print_error};
+use crate::cli::OutputFormat;
+use crate::core::services::Services;
+use crate::core::xdg::XdgDirs;
+use clap::Args;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::sync::Arc;
+
+/// Arguments for the repl command
+#[derive(Args, Debug)]
+pub struct ReplArgs {
+    /// Session ID to search
+    #[arg(long, short = 's')]
+    pub session: String,
+
+    /// Maximum number of results per query (1-100)
+    #[arg(long, short = 'k', default_value = "10")]
+    pub limit: usize,
+
+    /// Collapse results to one entry per file
+    #[arg(long)]
+    pub group_by_file: bool,
+}
+
+/// REPL help text
+const HELP: &str = "\
+Commands:
+  :k <n>             Set result limit (1-100)
+  :session <id>      Switch to another session
+  :json              Toggle JSON output
+  :group             Toggle grouping by file
+  :files             Toggle file-only output
+  :help              Show this help
+  :quit              Exit (or Ctrl-D)
+
+Anything else is run as a search query. Ctrl-C cancels a running query.";
+
+/// A parsed REPL input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+    /// Run a search query
+    Search(String),
+    /// Set the result limit
+    SetLimit(usize),
+    /// Switch the active session
+    SwitchSession(String),
+    /// Toggle between human and JSON output
+    ToggleJson,
+    /// Toggle grouping results by file
+    ToggleGroup,
+    /// Toggle file-only output
+    ToggleFilesOnly,
+    /// Show help
+    Help,
+    /// Exit the REPL
+    Quit,
+    /// Blank line
+    Empty,
+}
+
+/// Parse a REPL input line
+///
+/// Returns an error message for unknown or malformed `:` commands.
+pub fn parse_line(line: &str) -> Result<ReplCommand, String> {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return Ok(ReplCommand::Empty);
+    }
+
+    let Some(command) = line.strip_prefix(':') else {
+        return Ok(ReplCommand::Search(line.to_string()));
+    };
+
+    let mut parts = command.split_whitespace();
+    let name = parts.next().unwrap_or("");
+    let arg = parts.next();
+
+    match (name, arg) {
+        ("k", Some(n)) => n
+            .parse::<usize>()
+            .map(ReplCommand::SetLimit)
+            .map_err(|_| format!("Invalid limit '{n}'. Usage: :k <n>")),
+        ("k", None) => Err("Usage: :k <n>".to_string()),
+        ("session", Some(id)) => Ok(ReplCommand::SwitchSession(id.to_string())),
+        ("session", None) => Err("Usage: :session <id>".to_string()),
+        ("json", None) => Ok(ReplCommand::ToggleJson),
+        ("group", None) => Ok(ReplCommand::ToggleGroup),
+        ("files", None) => Ok(ReplCommand::ToggleFilesOnly),
+        ("help" | "h" | "?", None) => Ok(ReplCommand::Help),
+        ("quit" | "q" | "exit", None) => Ok(ReplCommand::Quit),
+        _ => Err(format!(
+            "Unknown command ':{command}'. Type :help for commands."
+        )),
+    }
+}
+
+/// Mutable REPL settings
+#[derive(Debug, Clone)]
+pub struct ReplState {
+    pub session: String,
+    pub limit: usize,
+    pub format: OutputFormat,
+    pub group_by_file: bool,
+    pub files_only: bool,
+}
+
+impl ReplState {
+    /// Build the search arguments for a query with the current settings
+    pub fn search_args(&self, query: &str) -> SearchArgs {
+        SearchArgs {
+            query: query.to_string(),
+            session: self.session.clone(),
+            limit: Some(self.limit),
+            files_only: self.files_only,
+            locations: false,
+            first: false,
+            paths_only: false,
+            paths: false,
+            group_by_file: self.group_by_file,
+            keep_overlaps: false,
+            path_boost: None,
+            fuzziness: None,
+            no_expand_identifiers: false,
+            bm25_k1: None,
+            bm25_b: None,
+            keep_short_chunks: false,
+            within: None,
+            within_k: None,
+            modified_within: None,
+            not: Vec::new(),
+            not_path: Vec::new(),
+            explain: false,
+            fail_on_empty: false,
+            export: ExportArgs::default(),
+        }
+    }
+
+    /// Prompt shown before each input line
+    pub fn prompt(&self) -> String {
+        format!("shebe:{}> ", self.session)
+    }
+}
+
+/// Whether the REPL should keep reading input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineOutcome {
+    Continue,
+    Exit,
+}
+
+/// Handle a single input line
+///
+/// Errors from queries and commands are printed rather than returned,
+/// so a typo never ends the session.
+pub async fn handle_line(
+    state: &mut ReplState,
+    line: &str,
+    services: &Arc<Services>,
+) -> LineOutcome {
+    let command = match parse_line(line) {
+        Ok(command) => command,
+        Err(msg) => {
+            print_error(&msg);
+            return LineOutcome::Continue;
+        }
+    };
+
+    match command {
+        ReplCommand::Empty => {}
+        ReplCommand::Search(query) => run_query(state, &query, services).await,
+        ReplCommand::SetLimit(n) => {
+            state.limit = n.clamp(1, 100);
+            println!("limit = {}", colors::number(&state.limit.to_string()));
+        }
+        ReplCommand::SwitchSession(id) => match services.storage.open_session(&id) {
+            Ok(_) => {
+                state.session = id;
+                println!("session = {}", colors::session_id(&state.session));
+            }
+            Err(e) => print_error(&e.to_string()),
+        },
+        ReplCommand::ToggleJson => {
+            state.format = match state.format {
+                OutputFormat::Human => OutputFormat::Json,
+                OutputFormat::Json | OutputFormat::Ndjson => OutputFormat::Human,
+            };
+            println!("json = {}", state.format == OutputFormat::Json);
+        }
+        ReplCommand::ToggleGroup => {
+            state.group_by_file = !state.group_by_file;
+            println!("group_by_file = {}", state.group_by_file);
+        }
+        ReplCommand::ToggleFilesOnly => {
+            state.files_only = !state.files_only;
+            println!("files_only = {}", state.files_only);
+        }
+        ReplCommand::Help => println!("{HELP}"),
+        ReplCommand::Quit => return LineOutcome::Exit,
+    }
+
+    LineOutcome::Continue
+}
+
+/// Run a query on a blocking thread so Ctrl-C can cancel it
+async fn run_query(state: &ReplState, query: &str, services: &Arc<Services>) {
+    let args = state.search_args(query);
+    let request = build_request(&args);
+    let search_services = Arc::clone(services);
+    let task = tokio::task::spawn_blocking(move || search_services.search.search(request));
+
+    tokio::select! {
+        result = task => match result {
+            Ok(Ok(response)) => {
+                if let Err(e) = print_response(&args, &response, state.format) {
+                    print_error(&e.to_string());
+                }
+            }
+            Ok(Err(e)) => print_error(&e.to_string()),
+            Err(e) => print_error(&format!("Query failed: {e}")),
+        },
+        _ = tokio::signal::ctrl_c() => {
+            println!("{}", colors::dim("Query cancelled."));
+        }
+    }
+}
+
+/// Execute the repl command
+pub async fn execute(
+    args: ReplArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Fail early on a missing or outdated session
+    services
+        .storage
+        .open_session(&args.session)
+        .map_err(|e| format!("{e}. Run 'shebe list-sessions' to see available sessions."))?;
+
+    let mut state = ReplState {
+        session: args.session,
+        limit: args.limit.clamp(1, 100),
+        format,
+        group_by_file: args.group_by_file,
+        files_only: false,
+    };
+
+    let xdg = XdgDirs::new();
+    let history_file = xdg.repl_history_file();
+    std::fs::create_dir_all(&xdg.data_dir)?;
+
+    let mut editor = DefaultEditor::new()?;
+    // Missing history on first run is expected
+    let _ = editor.load_history(&history_file);
+
+    println!(
+        "Searching '{}'. Type {} for commands, Ctrl-D to exit.",
+        colors::session_id(&state.session),
+        colors::label(":help")
+    );
+
+    loop {
+        match editor.readline(&state.prompt()) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
+                }
+                if handle_line(&mut state, &line, services).await == LineOutcome::Exit {
+                    break;
+                }
+            }
+            // Ctrl-C at the prompt discards the current line
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if let Err(e) = editor.save_history(&history_file) {
+        tracing::warn!("Failed to save REPL history: {}", e);
+    }
+
+    Ok(())
+}
@@ -1,12 +1,28 @@
 //! Search command - search indexed code
 
-use crate::cli::output::colors;
-use crate::cli::OutputFormat;
+use crate::cli::commands::session_not_found;
+use crate::cli::export::{ExportArgs, ExportRow, ExportTarget, ExportWriter};
+use crate::cli::output::{
+    colors, format_count, format_relative_time, Event, NdjsonWriter, ResultEvent, SummaryEvent,
+};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::path_search::{search_paths, PathHit, MAX_PATH_SEARCH_K};
+use crate::core::paths;
+use crate::core::search::{locate_result, match_terms};
 use crate::core::services::Services;
-use crate::core::types::SearchRequest;
+use crate::core::types::{
+    ExclusionFilter, FuzzyExpansion, IdentifierExpansion, ModifiedTimeSource, OutputMode,
+    RecencyFilter, RecordRange, Refinement, ScoreExplanation, SearchRequest, SearchResponse,
+    SearchResult, SearchWithin, MAX_K,
+};
+use chrono::{DateTime, Utc};
 use clap::Args;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Arguments for the search command
 #[derive(Args, Debug)]
@@ -18,13 +34,98 @@ pub struct SearchArgs {
     #[arg(long, short = 's')]
     pub session: String,
 
-    /// Maximum number of results (1-100)
-    #[arg(long, short = 'k', default_value = "10")]
-    pub limit: usize,
+    /// Maximum number of results (1-100; up to search.max_k with --output;
+    /// default from the session's search defaults, then 10)
+    #[arg(long, short = 'k')]
+    pub limit: Option<usize>,
 
     /// Only show file paths (no content)
     #[arg(long)]
     pub files_only: bool,
+
+    /// Only show file, line, score and a short excerpt of each result
+    /// (default when the session's output_mode is locations)
+    #[arg(long, conflicts_with = "files_only")]
+    pub locations: bool,
+
+    /// Print only the best hit as `path:line` (exit 5 when nothing matches)
+    #[arg(long, conflicts_with_all = ["files_only", "locations", "paths_only", "output"])]
+    pub first: bool,
+
+    /// Print the distinct file paths of all results, one per line
+    #[arg(long, conflicts_with_all = ["files_only", "locations", "output"])]
+    pub paths_only: bool,
+
+    /// Match the query against file paths only, tolerating typos (no content search)
+    #[arg(long, conflicts_with_all = [
+        "files_only", "locations", "first", "paths_only", "group_by_file", "within", "output"
+    ])]
+    pub paths: bool,
+
+    /// Collapse results to one entry per file (limit counts files)
+    #[arg(long)]
+    pub group_by_file: bool,
+
+    /// Keep hits that only repeat matches from an adjacent chunk's overlap
+    #[arg(long)]
+    pub keep_overlaps: bool,
+
+    /// Boost for query matches in the file path (0 disables, default from config)
+    #[arg(long)]
+    pub path_boost: Option<f32>,
+
+    /// Also match terms within this edit distance to tolerate typos
+    /// (0-2, default from the session's search defaults, then 0)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=2))]
+    pub fuzziness: Option<u8>,
+
+    /// Match identifiers only as spelled (no getUserId <-> get_user_id variants)
+    #[arg(long)]
+    pub no_expand_identifiers: bool,
+
+    /// BM25 term frequency saturation, 0-3 [default: the session's]
+    #[arg(long, value_name = "K1")]
+    pub bm25_k1: Option<f32>,
+
+    /// BM25 length normalization, 0-1 [default: the session's]
+    #[arg(long, value_name = "B")]
+    pub bm25_b: Option<f32>,
+
+    /// Score very short chunks (e.g. a file's last few lines) like any other
+    #[arg(long)]
+    pub keep_short_chunks: bool,
+
+    /// Only search among the top hits of this query (e.g. a previous search)
+    #[arg(long, value_name = "QUERY")]
+    pub within: Option<String>,
+
+    /// Number of --within hits to search among [default: search.default_k]
+    #[arg(long, value_name = "K", requires = "within")]
+    pub within_k: Option<usize>,
+
+    /// Only files last committed (or modified) within this window ("30d",
+    /// "12h", "2w") or since this date ("2026-09-01")
+    #[arg(long, value_name = "WINDOW")]
+    pub modified_within: Option<String>,
+
+    /// Leave out chunks containing this term or phrase (repeatable)
+    #[arg(long = "not", value_name = "TERM")]
+    pub not: Vec<String>,
+
+    /// Leave out files matching this glob (repeatable, e.g. "tests/**")
+    #[arg(long = "not-path", value_name = "GLOB")]
+    pub not_path: Vec<String>,
+
+    /// Break down the scores of the top 10 results per query term
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Exit with code 5 when nothing matches
+    #[arg(long)]
+    pub fail_on_empty: bool,
+
+    #[command(flatten)]
+    pub export: ExportArgs,
 }
 
 /// Search result item
@@ -34,8 +135,32 @@ pub struct SearchResultItem {
     pub file: String,
     pub score: f32,
     pub chunk_index: usize,
+    /// Records the chunk holds (data files chunked by record)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub records: Option<RecordRange>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
+    /// Other paths with identical content (dedupe sessions only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also_present_at: Vec<String>,
+    /// The file was cut off at max_chunks_per_file
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub indexed_partially: bool,
+    /// The query also matched the file path
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub matched_in_path: bool,
+    /// The score was lowered because the chunk is very short
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub short_chunk_demoted: bool,
+    /// When the file was last committed (or modified)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Why there is no text (sessions that do not store chunk text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_unavailable: Option<String>,
+    /// Per-term score breakdown (--explain)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
 }
 
 /// Search response
@@ -44,41 +169,305 @@ pub struct SearchResponseOutput {
     pub query: String,
     pub session: String,
     pub total_results: usize,
+    /// Hits hidden as chunk overlap duplicates
+    pub overlap_duplicates_dropped: usize,
+    /// Results scored lower because their chunk is very short
+    pub short_chunks_demoted: usize,
+    /// Identifier terms also searched in their other spellings
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub identifier_expansions: Vec<IdentifierExpansion>,
+    /// Terms matched by fuzzy matching
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fuzzy_expansions: Vec<FuzzyExpansion>,
+    /// How --within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refinement: Option<Refinement>,
+    /// How --modified-within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recency: Option<RecencyFilter>,
+    /// What --not and --not-path left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<ExclusionFilter>,
+    /// Where `modified_at` comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_times: Option<ModifiedTimeSource>,
+    /// Why --explain was ignored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain_skipped: Option<String>,
     pub results: Vec<SearchResultItem>,
 }
 
+/// A matching chunk within a grouped file result
+#[derive(Debug, Serialize)]
+pub struct ChunkHitItem {
+    pub chunk_index: usize,
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Why there is no text (sessions that do not store chunk text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_unavailable: Option<String>,
+}
+
+/// File-level result item (grouped mode)
+#[derive(Debug, Serialize)]
+pub struct FileResultItem {
+    pub rank: usize,
+    pub file: String,
+    pub best_score: f32,
+    /// Matching chunks, best first
+    pub chunks: Vec<ChunkHitItem>,
+    /// Other paths with identical content (dedupe sessions only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also_present_at: Vec<String>,
+    /// The file was cut off at max_chunks_per_file
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub indexed_partially: bool,
+    /// Per-term score breakdown of the best chunk (--explain)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Compact result location (`--locations`)
+#[derive(Debug, Serialize)]
+pub struct LocationItem {
+    pub rank: usize,
+    pub file: String,
+    /// Line of the first match, when the file could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub chunk_index: usize,
+    pub score: f32,
+    /// Single line around the first match (at most 80 characters)
+    pub excerpt: String,
+}
+
+/// Locations-only search response
+#[derive(Debug, Serialize)]
+pub struct LocationsResponseOutput {
+    pub query: String,
+    pub session: String,
+    pub total_results: usize,
+    /// How --within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refinement: Option<Refinement>,
+    /// How --modified-within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recency: Option<RecencyFilter>,
+    /// What --not and --not-path left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<ExclusionFilter>,
+    pub locations: Vec<LocationItem>,
+}
+
+/// Best hit (`--first`)
+#[derive(Debug, Serialize)]
+pub struct FirstHitOutput {
+    pub file: String,
+    /// Line of the first match, when the file could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Byte offset of the chunk in the file
+    pub offset: usize,
+    pub chunk_index: usize,
+    pub score: f32,
+}
+
+/// Distinct result paths (`--paths-only`)
+#[derive(Debug, Serialize)]
+pub struct PathsResponseOutput {
+    pub query: String,
+    pub session: String,
+    pub total_files: usize,
+    /// File paths in rank order of their best hit
+    pub paths: Vec<String>,
+}
+
+/// A file matched by path (`--paths`)
+#[derive(Debug, Serialize)]
+pub struct PathMatchItem {
+    pub rank: usize,
+    #[serde(flatten)]
+    pub hit: PathHit,
+}
+
+/// Path search response (`--paths`)
+#[derive(Debug, Serialize)]
+pub struct PathMatchesOutput {
+    pub query: String,
+    pub session: String,
+    pub total_files: usize,
+    pub files: Vec<PathMatchItem>,
+}
+
+/// Grouped search response (one entry per file)
+#[derive(Debug, Serialize)]
+pub struct GroupedSearchResponseOutput {
+    pub query: String,
+    pub session: String,
+    pub total_files: usize,
+    /// Identifier terms also searched in their other spellings
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub identifier_expansions: Vec<IdentifierExpansion>,
+    /// Terms matched by fuzzy matching
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fuzzy_expansions: Vec<FuzzyExpansion>,
+    /// How --within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refinement: Option<Refinement>,
+    /// How --modified-within narrowed the search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recency: Option<RecencyFilter>,
+    /// What --not and --not-path left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<ExclusionFilter>,
+    /// Why --explain was ignored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain_skipped: Option<String>,
+    pub files: Vec<FileResultItem>,
+}
+
 /// Execute the search command
 pub async fn execute(
-    args: SearchArgs,
+    mut args: SearchArgs,
     services: &Arc<Services>,
     format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    if format == OutputFormat::Ndjson
+        && (args.first || args.paths_only || args.locations || args.paths)
+    {
+        return Err(CliError::InvalidArgs(
+            "--format ndjson cannot be combined with --first, --paths-only, --locations \
+             or --paths. Result events already carry the file and line."
+                .into(),
+        )
+        .into());
+    }
+
     // Validate session exists (returns bool, not Result)
     if !services.storage.session_exists(&args.session) {
-        return Err(format!(
-            "Session '{}' not found. Run 'shebe list-sessions' to see available sessions.",
-            args.session
-        )
+        return Err(session_not_found(services, &args.session).into());
+    }
+
+    if args.paths {
+        return print_path_matches(&args, services, format);
+    }
+
+    // Exports are not limited to a terminal's worth of results; the
+    // search service still caps k at search.max_k
+    let target = args.export.target()?;
+    if target.is_none() && format != OutputFormat::Ndjson && !has_output_mode(&args) {
+        args.locations = session_prefers_locations(services, &args.session);
+    }
+    let mut request = build_request(&args);
+    if let (Some(_), Some(limit)) = (&target, args.limit) {
+        request.k = Some(limit.max(1));
+    }
+    if args.first {
+        request.k = Some(1);
+    }
+
+    // Perform search
+    let response = services.search.search(request)?;
+
+    if args.first {
+        return print_first(&args, &response, format);
+    }
+
+    match (&target, format) {
+        (Some(target), _) => export_response(&args, &response, target)?,
+        (None, OutputFormat::Ndjson) => stream_response(&args, &response, started)?,
+        (None, _) => print_response(&args, &response, format)?,
+    }
+
+    if args.fail_on_empty && response.results.is_empty() && response.groups.is_empty() {
+        return Err(CliError::NoMatches(format!(
+            "No results found for '{}' in session '{}'",
+            args.query, args.session
+        ))
         .into());
     }
 
-    // Validate limit
-    let limit = args.limit.clamp(1, 100);
+    Ok(())
+}
+
+/// Whether a flag already picks how results are shown
+fn has_output_mode(args: &SearchArgs) -> bool {
+    args.files_only || args.locations || args.first || args.paths_only || args.group_by_file
+}
+
+/// Whether the session's search defaults ask for locations output
+fn session_prefers_locations(services: &Services, session: &str) -> bool {
+    services
+        .storage
+        .get_session_metadata(session)
+        .ok()
+        .and_then(|metadata| metadata.search_defaults)
+        .and_then(|defaults| defaults.output_mode)
+        == Some(OutputMode::Locations)
+}
 
-    // Create search request
-    let request = SearchRequest {
+/// Build the core search request for the given arguments
+///
+/// The limit is clamped to 1-100. Unset options fall back to the
+/// session's search defaults, then the server's.
+pub fn build_request(args: &SearchArgs) -> SearchRequest {
+    SearchRequest {
         query: args.query.clone(),
         session: args.session.clone(),
-        k: Some(limit),
-    };
+        k: args.limit.map(|limit| limit.clamp(1, MAX_K)),
+        group_by_file: args.group_by_file,
+        dedupe_overlaps: !args.keep_overlaps,
+        path_boost: args.path_boost,
+        fuzziness: args.fuzziness,
+        expand_identifiers: !args.no_expand_identifiers,
+        bm25_k1: args.bm25_k1,
+        bm25_b: args.bm25_b,
+        within: args.within.as_ref().map(|query| SearchWithin {
+            query: query.clone(),
+            k: args.within_k,
+        }),
+        penalize_short_chunks: args.keep_short_chunks.then_some(false),
+        modified_within: args.modified_within.clone(),
+        section_filter: None,
+        exclude_terms: args.not.clone(),
+        exclude_paths: args.not_path.clone(),
+        explain: args.explain,
+    }
+}
 
-    // Perform search
-    let response = services.search.search(request)?;
+/// Print a search response in the requested format
+///
+/// Shared by `search-code` and the interactive `repl` command.
+pub fn print_response(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.locations {
+        return print_locations(args, response, format);
+    }
+    if args.paths_only {
+        return print_paths(args, response, format);
+    }
+    if args.group_by_file {
+        return print_grouped(args, response, format);
+    }
 
     let output = SearchResponseOutput {
         query: args.query.clone(),
         session: args.session.clone(),
         total_results: response.count,
+        overlap_duplicates_dropped: response.overlap_duplicates_dropped,
+        short_chunks_demoted: response.short_chunks_demoted,
+        identifier_expansions: response.identifier_expansions.clone(),
+        fuzzy_expansions: response.fuzzy_expansions.clone(),
+        refinement: response.refinement.clone(),
+        recency: response.recency.clone(),
+        exclusions: response.exclusions.clone(),
+        modified_times: response.modified_times,
+        explain_skipped: response.explain_skipped.clone(),
         results: response
             .results
             .iter()
@@ -88,11 +477,19 @@ pub async fn execute(
                 file: r.file_path.clone(),
                 score: r.score,
                 chunk_index: r.chunk_index,
+                records: r.records,
                 text: if args.files_only {
                     None
                 } else {
                     Some(r.text.clone())
                 },
+                also_present_at: r.also_present_at.clone(),
+                indexed_partially: r.indexed_partially,
+                matched_in_path: r.matched_in_path,
+                short_chunk_demoted: r.short_chunk_demoted,
+                modified_at: r.modified_at,
+                text_unavailable: r.text_unavailable.clone(),
+                explanation: r.explanation.clone(),
             })
             .collect(),
     };
@@ -105,44 +502,601 @@ pub async fn execute(
                     colors::label(&args.query),
                     colors::session_id(&output.session)
                 );
+                print_refinement_note(response);
             } else {
                 println!(
-                    "Found {} result(s) in '{}':\n",
-                    colors::number(&output.total_results.to_string()),
+                    "Found {} result(s) in '{}':",
+                    colors::number(&format_count(output.total_results as u64)),
                     colors::session_id(&output.session)
                 );
+                print_expansion_notes(response);
+                if output.overlap_duplicates_dropped > 0 {
+                    println!(
+                        "{}",
+                        colors::dim(&format!(
+                            "({} overlapping duplicate(s) hidden, use --keep-overlaps to show)",
+                            output.overlap_duplicates_dropped
+                        ))
+                    );
+                }
+                if output.short_chunks_demoted > 0 {
+                    println!(
+                        "{}",
+                        colors::dim(&format!(
+                            "({} short chunk(s) ranked lower, use --keep-short-chunks to score \
+                             them normally)",
+                            output.short_chunks_demoted
+                        ))
+                    );
+                }
+                println!();
 
                 for result in &output.results {
                     if args.files_only {
                         println!("{}", colors::file_path(&result.file));
                     } else {
+                        let path_note = if result.matched_in_path {
+                            ", matched in path"
+                        } else {
+                            ""
+                        };
+                        let short_note = if result.short_chunk_demoted {
+                            ", short chunk"
+                        } else {
+                            ""
+                        };
+                        let records_note = match result.records {
+                            Some(records) => format!(", {}", records.describe()),
+                            None => String::new(),
+                        };
+                        let age_note = match (output.modified_times, &result.modified_at) {
+                            (Some(source), Some(at)) => {
+                                format!(", {} {}", source.verb(), format_relative_time(at))
+                            }
+                            _ => String::new(),
+                        };
                         println!(
                             "[{}] {} {}",
                             colors::rank(&result.rank.to_string()),
                             colors::file_path(&result.file),
-                            colors::dim(&format!("(score: {:.2})", result.score))
+                            colors::dim(&format!(
+                                "(score: {:.2}{records_note}{path_note}{short_note}{age_note})",
+                                result.score
+                            ))
                         );
-                        if let Some(text) = &result.text {
+                        print_explanation(result.explanation.as_ref());
+                        print_also_present(&result.also_present_at);
+                        print_partial_note(result.indexed_partially);
+                        if let Some(reason) = &result.text_unavailable {
+                            print_text_unavailable(reason);
+                        } else if let Some(text) = &result.text {
                             // Indent and truncate text for display
-                            let lines: Vec<&str> = text.lines().take(5).collect();
-                            for line in lines {
-                                let truncated = if line.len() > 100 {
-                                    format!("{}...", &line[..97])
-                                } else {
-                                    line.to_string()
-                                };
-                                println!("    {}", colors::dim(&truncated));
-                            }
+                            print_snippet(text);
                         }
                         println!();
                     }
                 }
             }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Write one NDJSON result event per hit, then the summary
+///
+/// Grouped responses get one event per file, for its best chunk.
+fn stream_response(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    started: Instant,
+) -> std::io::Result<()> {
+    let hits: Vec<&SearchResult> = if args.group_by_file {
+        response.groups.iter().map(|g| g.best()).collect()
+    } else {
+        response.results.iter().collect()
+    };
+
+    let terms = match_terms(&args.query, &response.identifier_expansions);
+    let mut files: HashMap<&str, Option<String>> = HashMap::new();
+    let mut events = NdjsonWriter::new();
+    for (i, hit) in hits.iter().enumerate() {
+        let content = files
+            .entry(&hit.file_path)
+            .or_insert_with(|| fs::read_to_string(paths::to_native(&hit.file_path)).ok());
+        let location = locate_result(hit, content.as_deref(), &terms);
+        events.emit(&Event::Result(ResultEvent {
+            rank: i + 1,
+            file: hit.file_path.clone(),
+            line: location.line,
+            chunk_index: hit.chunk_index,
+            score: hit.score,
+            text: (!args.files_only && hit.text_unavailable.is_none()).then(|| hit.text.clone()),
+            text_unavailable: hit.text_unavailable.clone(),
+        }))?;
+    }
+    events.finish(SummaryEvent::new(
+        "search-code",
+        &args.session,
+        started.elapsed(),
+    ))
+}
+
+/// Print grouped (one entry per file) search results
+fn print_grouped(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = GroupedSearchResponseOutput {
+        query: args.query.clone(),
+        session: args.session.clone(),
+        total_files: response.groups.len(),
+        identifier_expansions: response.identifier_expansions.clone(),
+        fuzzy_expansions: response.fuzzy_expansions.clone(),
+        refinement: response.refinement.clone(),
+        recency: response.recency.clone(),
+        exclusions: response.exclusions.clone(),
+        explain_skipped: response.explain_skipped.clone(),
+        files: response
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(i, g)| FileResultItem {
+                rank: i + 1,
+                file: g.file_path.clone(),
+                best_score: g.best_score,
+                chunks: g
+                    .hits
+                    .iter()
+                    .map(|h| ChunkHitItem {
+                        chunk_index: h.chunk_index,
+                        score: h.score,
+                        text: if args.files_only {
+                            None
+                        } else {
+                            Some(h.text.clone())
+                        },
+                        text_unavailable: h.text_unavailable.clone(),
+                    })
+                    .collect(),
+                also_present_at: g.best().also_present_at.clone(),
+                indexed_partially: g.best().indexed_partially,
+                explanation: g.best().explanation.clone(),
+            })
+            .collect(),
+    };
+
+    match format {
+        OutputFormat::Human => {
+            if output.files.is_empty() {
+                println!(
+                    "No results found for '{}' in session '{}'",
+                    colors::label(&args.query),
+                    colors::session_id(&output.session)
+                );
+                print_refinement_note(response);
+                return Ok(());
+            }
+
+            println!(
+                "Found {} file(s) in '{}':",
+                colors::number(&format_count(output.total_files as u64)),
+                colors::session_id(&output.session)
+            );
+            print_expansion_notes(response);
+            println!();
+
+            for file in &output.files {
+                if args.files_only {
+                    println!("{}", colors::file_path(&file.file));
+                    continue;
+                }
+
+                println!(
+                    "[{}] {} {}",
+                    colors::rank(&file.rank.to_string()),
+                    colors::file_path(&file.file),
+                    colors::dim(&format!(
+                        "(best score: {:.2}, chunk {})",
+                        file.best_score, file.chunks[0].chunk_index
+                    ))
+                );
+                print_explanation(file.explanation.as_ref());
+                print_also_present(&file.also_present_at);
+                print_partial_note(file.indexed_partially);
+                if let Some(reason) = &file.chunks[0].text_unavailable {
+                    print_text_unavailable(reason);
+                } else if let Some(text) = &file.chunks[0].text {
+                    print_snippet(text);
+                }
+                if file.chunks.len() > 1 {
+                    let more: Vec<String> = file.chunks[1..]
+                        .iter()
+                        .map(|c| c.chunk_index.to_string())
+                        .collect();
+                    println!(
+                        "    {}",
+                        colors::dim(&format!("+{} more chunks: {}", more.len(), more.join(", ")))
+                    );
+                }
+                println!();
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the best hit as `path:line` (or one JSON object), nothing else
+///
+/// The path alone is printed when the file can no longer be read.
+/// Nothing is printed when there is no hit; the error (code 5) goes to
+/// stderr.
+fn print_first(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let best = match response.groups.first() {
+        Some(group) => Some(group.best()),
+        None => response.results.first(),
+    };
+    let Some(hit) = best else {
+        return Err(CliError::NoMatches(format!(
+            "No results found for '{}' in session '{}'",
+            args.query, args.session
+        ))
+        .into());
+    };
+
+    let content = fs::read_to_string(paths::to_native(&hit.file_path)).ok();
+    let location = locate_result(
+        hit,
+        content.as_deref(),
+        &match_terms(&args.query, &response.identifier_expansions),
+    );
+    let output = FirstHitOutput {
+        file: hit.file_path.clone(),
+        line: location.line,
+        offset: hit.start_offset,
+        chunk_index: hit.chunk_index,
+        score: hit.score,
+    };
+    // Piped into `head` and the like, so a closed pipe is an error to
+    // return rather than a panic
+    let mut stdout = io::stdout().lock();
+    match (format, output.line) {
+        (OutputFormat::Json | OutputFormat::Ndjson, _) => {
+            writeln!(stdout, "{}", serde_json::to_string(&output)?)?
+        }
+        (OutputFormat::Human, Some(line)) => writeln!(stdout, "{}:{line}", output.file)?,
+        (OutputFormat::Human, None) => writeln!(stdout, "{}", output.file)?,
+    }
+    Ok(())
+}
+
+/// Match the query against indexed file paths only and print the files
+///
+/// The limit is clamped to 1-100.
+fn print_path_matches(
+    args: &SearchArgs,
+    services: &Services,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let k = args.limit.unwrap_or(10).clamp(1, MAX_PATH_SEARCH_K);
+    let hits = search_paths(&services.storage, &args.session, &args.query, k)?;
+    let empty = hits.is_empty();
+
+    match format {
+        OutputFormat::Human => {
+            if empty {
+                println!(
+                    "No file paths match '{}' in session '{}'",
+                    colors::label(&args.query),
+                    colors::session_id(&args.session)
+                );
+            } else {
+                println!(
+                    "Found {} file(s) by path in '{}':",
+                    colors::number(&format_count(hits.len() as u64)),
+                    colors::session_id(&args.session)
+                );
+                println!();
+            }
+            for (i, hit) in hits.iter().enumerate() {
+                let path: String = hit
+                    .pieces()
+                    .into_iter()
+                    .map(|(piece, matched)| {
+                        if matched {
+                            colors::label(piece).to_string()
+                        } else {
+                            colors::file_path(piece).to_string()
+                        }
+                    })
+                    .collect();
+                println!(
+                    "[{}] {path} {}",
+                    colors::rank(&(i + 1).to_string()),
+                    colors::dim(&format!("({})", hit.match_summary()))
+                );
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let output = PathMatchesOutput {
+                query: args.query.clone(),
+                session: args.session.clone(),
+                total_files: hits.len(),
+                files: hits
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, hit)| PathMatchItem { rank: i + 1, hit })
+                    .collect(),
+            };
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
     }
 
+    if args.fail_on_empty && empty {
+        return Err(CliError::NoMatches(format!(
+            "No file paths match '{}' in session '{}'",
+            args.query, args.session
+        ))
+        .into());
+    }
     Ok(())
 }
+
+/// Print each result's file path once, in rank order, undecorated
+fn print_paths(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths: Vec<String> = Vec::new();
+    let hits: Box<dyn Iterator<Item = &SearchResult>> = if args.group_by_file {
+        Box::new(response.groups.iter().map(|g| g.best()))
+    } else {
+        Box::new(response.results.iter())
+    };
+    for hit in hits {
+        if !paths.contains(&hit.file_path) {
+            paths.push(hit.file_path.clone());
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Human => {
+            for path in &paths {
+                writeln!(stdout, "{path}")?;
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let output = PathsResponseOutput {
+                query: args.query.clone(),
+                session: args.session.clone(),
+                total_files: paths.len(),
+                paths,
+            };
+            writeln!(stdout, "{}", serde_json::to_string_pretty(&output)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Print one line per result: file, line, score and excerpt
+///
+/// Grouped responses get one line per file, for its best chunk.
+fn print_locations(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hits: Vec<&SearchResult> = if args.group_by_file {
+        response.groups.iter().map(|g| g.best()).collect()
+    } else {
+        response.results.iter().collect()
+    };
+
+    let terms = match_terms(&args.query, &response.identifier_expansions);
+    let mut files: HashMap<&str, Option<String>> = HashMap::new();
+    let output = LocationsResponseOutput {
+        query: args.query.clone(),
+        session: args.session.clone(),
+        total_results: hits.len(),
+        refinement: response.refinement.clone(),
+        recency: response.recency.clone(),
+        exclusions: response.exclusions.clone(),
+        locations: hits
+            .iter()
+            .enumerate()
+            .map(|(i, hit)| {
+                let content = files
+                    .entry(&hit.file_path)
+                    .or_insert_with(|| fs::read_to_string(paths::to_native(&hit.file_path)).ok());
+                let location = locate_result(hit, content.as_deref(), &terms);
+                LocationItem {
+                    rank: i + 1,
+                    file: hit.file_path.clone(),
+                    line: location.line,
+                    chunk_index: hit.chunk_index,
+                    score: hit.score,
+                    excerpt: location.excerpt,
+                }
+            })
+            .collect(),
+    };
+
+    match format {
+        OutputFormat::Human => {
+            if output.locations.is_empty() {
+                println!(
+                    "No results found for '{}' in session '{}'",
+                    colors::label(&args.query),
+                    colors::session_id(&output.session)
+                );
+                print_refinement_note(response);
+                return Ok(());
+            }
+
+            println!(
+                "Found {} result(s) in '{}':",
+                colors::number(&format_count(output.total_results as u64)),
+                colors::session_id(&output.session)
+            );
+            print_expansion_notes(response);
+            println!();
+
+            for item in &output.locations {
+                let place = match item.line {
+                    Some(line) => format!("{}:{line}", item.file),
+                    None => format!("{} (chunk {})", item.file, item.chunk_index),
+                };
+                println!(
+                    "[{}] {} {}  {}",
+                    colors::rank(&item.rank.to_string()),
+                    colors::file_path(&place),
+                    colors::dim(&format!("({:.2})", item.score)),
+                    item.excerpt
+                );
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Write every hit of a search response to the --output file
+///
+/// Grouped responses export each matching chunk of each file. Files
+/// are read one at a time to find the line of each hit.
+fn export_response(
+    args: &SearchArgs,
+    response: &SearchResponse,
+    target: &ExportTarget,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hits: Box<dyn Iterator<Item = &SearchResult>> = if args.group_by_file {
+        Box::new(response.groups.iter().flat_map(|g| g.hits.iter()))
+    } else {
+        Box::new(response.results.iter())
+    };
+
+    let terms = match_terms(&args.query, &response.identifier_expansions);
+    let mut writer = ExportWriter::create(target)?;
+    let mut file: Option<(&str, Option<String>)> = None;
+    for (i, hit) in hits.enumerate() {
+        if file.as_ref().map(|(path, _)| *path) != Some(hit.file_path.as_str()) {
+            let content = fs::read_to_string(paths::to_native(&hit.file_path)).ok();
+            file = Some((&hit.file_path, content));
+        }
+        let content = file.as_ref().and_then(|(_, content)| content.as_deref());
+        writer.write(&ExportRow {
+            rank: i + 1,
+            file: hit.file_path.clone(),
+            line: locate_result(hit, content, &terms).line,
+            offset: hit.start_offset,
+            score: hit.score,
+            snippet: hit.text.clone(),
+        })?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Print the --modified-within and --within restrictions and which
+/// identifier variants and fuzzy matches were searched
+fn print_expansion_notes(response: &SearchResponse) {
+    for summary in [
+        response.recency_summary(),
+        response.refinement_summary(),
+        response.exclusion_summary(),
+        response.identifier_summary(),
+        response.fuzzy_summary(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        println!("{}", colors::dim(&format!("({summary})")));
+    }
+    if let Some(reason) = &response.explain_skipped {
+        println!(
+            "{}",
+            colors::warning(&format!("--explain ignored: {reason}"))
+        );
+    }
+}
+
+/// Print a result's per-term score breakdown (--explain)
+fn print_explanation(explanation: Option<&ScoreExplanation>) {
+    if let Some(explanation) = explanation {
+        println!(
+            "    {}",
+            colors::dim(&format!("score: {}", explanation.describe()))
+        );
+    }
+}
+
+/// Print how --modified-within, --within and --not narrowed the search,
+/// when used
+fn print_refinement_note(response: &SearchResponse) {
+    for summary in [
+        response.recency_summary(),
+        response.refinement_summary(),
+        response.exclusion_summary(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        println!("{}", colors::dim(&format!("({summary})")));
+    }
+}
+
+/// Print the first lines of a chunk, indented and truncated
+fn print_snippet(text: &str) {
+    for line in text.lines().take(5) {
+        let truncated = if line.len() > 100 {
+            format!("{}...", &line[..97])
+        } else {
+            line.to_string()
+        };
+        println!("    {}", colors::dim(&truncated));
+    }
+}
+
+/// Print why a result has no text (sessions that do not store it)
+fn print_text_unavailable(reason: &str) {
+    println!(
+        "    {}",
+        colors::dim(&format!("(text unavailable, {reason})"))
+    );
+}
+
+/// Print the paths of deduplicated copies of a file, if any
+fn print_also_present(paths: &[String]) {
+    if !paths.is_empty() {
+        println!(
+            "    {}",
+            colors::dim(&format!("also present at: {}", paths.join(", ")))
+        );
+    }
+}
+
+fn print_partial_note(indexed_partially: bool) {
+    if indexed_partially {
+        println!(
+            "    {}",
+            colors::warning("indexed partially (file exceeds max_chunks_per_file)")
+        );
+    }
+}
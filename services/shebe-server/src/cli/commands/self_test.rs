@@ -0,0 +1,78 @@
+//! Self-test command - index, search and read a built-in corpus
+
+use crate::cli::output::{colors, print_output};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::selftest::{SelfTestReport, StepStatus};
+use crate::core::services::Services;
+use clap::Args;
+use std::sync::Arc;
+
+/// Arguments for the self-test command
+#[derive(Args, Debug)]
+pub struct SelfTestArgs {}
+
+/// Execute the self-test command
+///
+/// Prints the report, then fails with `SelfTestFailed` if any step
+/// failed.
+pub async fn execute(
+    _args: SelfTestArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = services.self_test()?;
+
+    match format {
+        OutputFormat::Human => print_report(&report),
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&report, format),
+    }
+
+    if report.passed() {
+        return Ok(());
+    }
+    let failed: Vec<&str> = report.failed().map(|step| step.name.as_str()).collect();
+    Err(CliError::SelfTestFailed(format!(
+        "Self-test failed: {} of {} steps failed ({})",
+        failed.len(),
+        report.steps.len(),
+        failed.join(", ")
+    ))
+    .into())
+}
+
+fn print_report(report: &SelfTestReport) {
+    println!(
+        "{} {}",
+        colors::label("Scratch directory:"),
+        colors::file_path(&report.scratch_dir.display().to_string())
+    );
+    println!();
+
+    for step in &report.steps {
+        let status = match step.status {
+            StepStatus::Passed => colors::success("PASS"),
+            StepStatus::Failed => colors::error("FAIL"),
+            StepStatus::Skipped => colors::warning("SKIP"),
+        };
+        println!(
+            "  {status} {:>6}ms  {}  {}",
+            step.duration_ms,
+            step.name,
+            colors::dim(&step.detail)
+        );
+    }
+
+    println!();
+    let summary = format!(
+        "{} passed, {} failed, {} skipped in {}ms",
+        report.count(StepStatus::Passed),
+        report.count(StepStatus::Failed),
+        report.count(StepStatus::Skipped),
+        report.duration_ms
+    );
+    if report.passed() {
+        println!("{}", colors::success(&summary));
+    } else {
+        println!("{}", colors::error(&summary));
+    }
+}
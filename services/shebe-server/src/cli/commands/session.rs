@@ -1,28 +1,73 @@
-//! Session commands - list, info, delete, reindex sessions
+//! Session commands - list, info, delete, reindex, compact, optimize sessions
+//! and set their search defaults
 //!
 //! These commands are exposed as top-level CLI commands matching MCP tool names:
 //! - `list-sessions` (MCP: list_sessions)
 //! - `get-session-info` (MCP: get_session_info)
 //! - `delete-session` (MCP: delete_session)
 //! - `reindex-session` (MCP: reindex_session)
+//! - `compact-session` (MCP: compact_session)
+//! - `optimize-session` (MCP: optimize_session)
+//! - `set-session-defaults` (MCP: set_session_defaults)
 
-use crate::cli::output::{colors, format_bytes, format_relative_time};
-use crate::cli::OutputFormat;
+use crate::cli::commands::{cancel_on_ctrl_c, session_not_found};
+use crate::cli::output::{
+    colors, format_bytes, format_count, format_duration, format_relative_time, format_timestamp,
+    is_quiet, print_renames, print_warning,
+};
+use crate::cli::{CliError, OutputFormat};
+use crate::core::archive::Archive;
+use crate::core::compare::is_comparison_session;
+use crate::core::indexer::repo_config::{ConfigSource, ConfigSources, IGNORE_FILE};
+use crate::core::indexer::{BoundaryHint, IndexProgress, ReindexDryRun};
+use crate::core::remote::RemoteSource;
 use crate::core::services::Services;
+use crate::core::storage::{
+    HealthAction, IndexSettings, MetadataValidator, Provenance, SessionConfig, SessionEntry,
+    SessionHealth, CURRENT_VERSION,
+};
+use crate::core::types::{CompactionStats, OutputMode, SessionSearchDefaults, SkipCounts};
 use clap::Args;
 use serde::Serialize;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Arguments for session list
 #[derive(Args, Debug)]
-pub struct ListArgs {}
+pub struct ListArgs {
+    /// Delete corrupt and orphaned session directories
+    #[arg(long)]
+    pub prune_corrupt: bool,
+
+    /// Skip the confirmation prompt of --prune-corrupt
+    #[arg(long, short = 'f', requires = "prune_corrupt")]
+    pub force: bool,
+
+    /// Only list the sessions of this collection
+    #[arg(long, value_name = "NAME", conflicts_with = "prune_corrupt")]
+    pub collection: Option<String>,
+}
 
 /// Arguments for session info
 #[derive(Args, Debug)]
 pub struct InfoArgs {
     /// Session ID
     pub session: String,
+
+    /// Explain why a file is or isn't indexed instead of showing session info
+    #[arg(long, value_name = "PATH")]
+    pub why_not_indexed: Option<PathBuf>,
+
+    /// Check that every file's chunks are numbered 0..count (reads the
+    /// whole index; fails if any file has gaps or duplicates)
+    #[arg(long, conflicts_with = "why_not_indexed")]
+    pub check_chunks: bool,
+
+    /// Sample adjacent chunks for identifiers cut in two by a chunk
+    /// boundary and report the rate
+    #[arg(long, conflicts_with_all = ["why_not_indexed", "check_chunks"])]
+    pub check_split_tokens: bool,
 }
 
 /// Arguments for session delete
@@ -34,6 +79,10 @@ pub struct DeleteArgs {
     /// Skip confirmation prompt
     #[arg(long, short = 'f')]
     pub force: bool,
+
+    /// Also delete the cached clone of a session indexed from a git URL
+    #[arg(long)]
+    pub purge_clone: bool,
 }
 
 /// Arguments for session reindex
@@ -50,9 +99,82 @@ pub struct ReindexArgs {
     #[arg(long)]
     pub overlap: Option<usize>,
 
-    /// Force re-index even if config unchanged
+    /// Switch chunk text storage (false reads snippets from the files)
+    #[arg(long, value_name = "BOOL")]
+    pub store_chunk_text: Option<bool>,
+
+    /// Override BM25 term frequency saturation (0-3)
+    #[arg(long)]
+    pub bm25_k1: Option<f32>,
+
+    /// Override BM25 length normalization (0-1)
+    #[arg(long)]
+    pub bm25_b: Option<f32>,
+
+    /// Override the chunks indexed per file (raise it to index truncated files in full)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_chunks_per_file: Option<u64>,
+
+    /// Force re-index even if config (and remote commit) unchanged
     #[arg(long, short = 'f')]
     pub force: bool,
+
+    /// Preview files added and removed, estimated chunks and config
+    /// changes without touching the session
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for session compact
+#[derive(Args, Debug)]
+pub struct CompactArgs {
+    /// Session ID
+    pub session: String,
+}
+
+/// Arguments for session optimize
+#[derive(Args, Debug)]
+pub struct OptimizeArgs {
+    /// Session ID
+    pub session: String,
+
+    /// Segments to merge down to [default: maintenance.target_segments]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub target_segments: Option<u64>,
+}
+
+/// Arguments for set-session-defaults
+#[derive(Args, Debug)]
+pub struct SetDefaultsArgs {
+    /// Session ID
+    pub session: String,
+
+    /// Default maximum number of results (1-100)
+    #[arg(long, short = 'k')]
+    pub limit: Option<usize>,
+
+    /// Default edit distance for typo-tolerant matching (0-2)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=2))]
+    pub fuzziness: Option<u8>,
+
+    /// Default boost for query matches in the file path (0 disables)
+    #[arg(long)]
+    pub path_boost: Option<f32>,
+
+    /// Default output: full or locations
+    #[arg(long, value_name = "MODE")]
+    pub output_mode: Option<String>,
+
+    /// Remove all defaults before applying the options given
+    #[arg(long)]
+    pub clear: bool,
+}
+
+/// Search defaults after set-session-defaults
+#[derive(Debug, Serialize)]
+pub struct SessionDefaultsResponse {
+    pub session: String,
+    pub search_defaults: SessionSearchDefaults,
 }
 
 /// Session list item
@@ -63,13 +185,40 @@ pub struct SessionListItem {
     pub chunks: usize,
     pub size_bytes: u64,
     pub indexed_at: String,
+    /// Why the index cannot be opened, if its files are damaged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_corruption: Option<String>,
+}
+
+/// A session directory without readable metadata
+#[derive(Debug, Serialize)]
+pub struct BrokenSessionItem {
+    pub id: String,
+    /// "corrupt" (unreadable meta.json) or "orphaned" (no meta.json)
+    pub status: &'static str,
+    pub reason: String,
+    pub size_bytes: u64,
+}
+
+/// Broken sessions deleted by --prune-corrupt
+#[derive(Debug, Serialize)]
+pub struct PrunedSessions {
+    pub sessions: Vec<String>,
+    pub reclaimed_bytes: u64,
 }
 
 /// Session list response
 #[derive(Debug, Serialize)]
 pub struct SessionListResponse {
+    /// Collection the list was restricted to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
     pub count: usize,
     pub sessions: Vec<SessionListItem>,
+    /// Corrupt and orphaned session directories, not searchable
+    pub broken: Vec<BrokenSessionItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pruned: Option<PrunedSessions>,
 }
 
 /// Detailed session info
@@ -81,40 +230,137 @@ pub struct SessionInfoResponse {
     pub chunks: usize,
     pub size_bytes: u64,
     pub indexed_at: String,
+    pub files_deduplicated: usize,
+    pub skipped: SkipCounts,
     pub config: SessionConfigInfo,
+    pub index: SessionIndexInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    /// How the index was last built (absent for older sessions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Set with set-session-defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_defaults: Option<SessionSearchDefaults>,
+    pub health: SessionHealth,
+    /// One line per entry in `health.actions`: the problem and its fix
+    pub recommended_actions: Vec<String>,
+}
+
+/// Diagnostics about how the index was built
+#[derive(Debug, Serialize)]
+pub struct SessionIndexInfo {
+    pub created_by_version: String,
+    pub running_version: String,
+    pub schema_version: u32,
+    pub tantivy_schema_hash: String,
+    /// `None` when the session predates schema hashes
+    pub schema_hash_matches: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<IndexSettings>,
+    /// Raw chunk text size: stored in the index, or saved in no-store mode
+    /// (0 when not recorded)
+    pub chunk_text_bytes: u64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SessionConfigInfo {
     pub chunk_size: usize,
     pub overlap: usize,
+    pub dedupe: bool,
+    pub boundary_hint: BoundaryHint,
+    pub store_chunk_text: bool,
+    pub strip_high_entropy: bool,
+    pub strip_run_chars: usize,
+    pub merge_tail_fragments: bool,
+    pub record_chunking: bool,
+    pub max_chunks_per_file: usize,
+    pub bm25_k1: f32,
+    pub bm25_b: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<ConfigSources>,
 }
 
 /// Execute list-sessions command
 pub async fn execute_list(
-    _args: ListArgs,
+    args: ListArgs,
     services: &Arc<Services>,
     format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let sessions = services.storage.list_sessions()?;
+    let pruned = if args.prune_corrupt {
+        match prune_broken(services, args.force)? {
+            Some(pruned) => Some(pruned),
+            None => return Ok(()),
+        }
+    } else {
+        None
+    };
 
-    let response = SessionListResponse {
-        count: sessions.len(),
-        sessions: sessions
-            .iter()
-            .map(|s| SessionListItem {
-                id: s.id.clone(),
+    let members = match &args.collection {
+        Some(name) => Some(services.storage.get_collection(name)?.sessions),
+        None => None,
+    };
+
+    let mut sessions = Vec::new();
+    let mut broken = Vec::new();
+    // Temporary sessions of a running compare-configs are left out
+    for entry in services.storage.list_session_entries()? {
+        if is_comparison_session(entry.id()) {
+            continue;
+        }
+        if let Some(members) = &members {
+            if !members.contains(&services.storage.canonical_session_id(entry.id())) {
+                continue;
+            }
+        }
+        match entry {
+            SessionEntry::Valid(s) => sessions.push(SessionListItem {
+                index_corruption: services.storage.index_corruption(&s.id),
+                id: s.id,
                 files: s.files_indexed,
                 chunks: s.chunks_created,
                 size_bytes: s.index_size_bytes,
                 indexed_at: s.last_indexed_at.to_rfc3339(),
-            })
-            .collect(),
+            }),
+            SessionEntry::Corrupt { id, error } => broken.push(BrokenSessionItem {
+                size_bytes: services.storage.measure_session_size(&id),
+                id,
+                status: "corrupt",
+                reason: format!("unreadable meta.json: {error}"),
+            }),
+            SessionEntry::Orphaned { id, size_bytes } => broken.push(BrokenSessionItem {
+                id,
+                status: "orphaned",
+                reason: "no meta.json".to_string(),
+                size_bytes,
+            }),
+        }
+    }
+
+    let response = SessionListResponse {
+        collection: args.collection,
+        count: sessions.len(),
+        sessions,
+        broken,
+        pruned,
     };
 
     match format {
         OutputFormat::Human => {
-            if response.sessions.is_empty() {
+            if let Some(pruned) = &response.pruned {
+                println!(
+                    "{} {} broken session(s) ({} reclaimed)",
+                    colors::success("Pruned"),
+                    colors::number(&format_count(pruned.sessions.len() as u64)),
+                    colors::number(&format_bytes(pruned.reclaimed_bytes))
+                );
+            }
+            if let Some(collection) = &response.collection {
+                println!("{} {}", colors::label("Collection:"), collection);
+            }
+            if response.sessions.is_empty() && response.collection.is_some() {
+                println!("No sessions in this collection.");
+            } else if response.sessions.is_empty() {
                 println!(
                     "No sessions found. Run '{}' to index a repository.",
                     colors::label("shebe index-repository <path> -s <session>")
@@ -123,7 +369,7 @@ pub async fn execute_list(
                 println!(
                     "{} ({}):",
                     colors::label("Sessions"),
-                    colors::number(&response.count.to_string())
+                    colors::number(&format_count(response.count as u64))
                 );
                 for session in &response.sessions {
                     // Parse the timestamp for relative time
@@ -132,8 +378,8 @@ pub async fn execute_list(
                         println!(
                             "  {:<20} {:>6} files  {:>8} chunks  {:>10}  {}",
                             colors::session_id(&session.id),
-                            colors::number(&session.files.to_string()),
-                            colors::number(&session.chunks.to_string()),
+                            colors::number(&format_count(session.files as u64)),
+                            colors::number(&format_count(session.chunks as u64)),
                             colors::number(&format_bytes(session.size_bytes)),
                             colors::dim(&format_relative_time(&utc))
                         );
@@ -141,15 +387,54 @@ pub async fn execute_list(
                         println!(
                             "  {:<20} {:>6} files  {:>8} chunks  {:>10}",
                             colors::session_id(&session.id),
-                            colors::number(&session.files.to_string()),
-                            colors::number(&session.chunks.to_string()),
+                            colors::number(&format_count(session.files as u64)),
+                            colors::number(&format_count(session.chunks as u64)),
                             colors::number(&format_bytes(session.size_bytes))
                         );
                     }
+                    if let Some(cause) = &session.index_corruption {
+                        println!(
+                            "  {:<20} {}  {}",
+                            "",
+                            colors::warning("corrupted index"),
+                            colors::dim(cause)
+                        );
+                    }
+                }
+                if response
+                    .sessions
+                    .iter()
+                    .any(|s| s.index_corruption.is_some())
+                {
+                    println!(
+                        "Rebuild corrupted sessions with '{}' or remove them with '{}'.",
+                        colors::label("shebe reindex-session <session>"),
+                        colors::label("shebe delete-session <session>")
+                    );
                 }
             }
+            if !response.broken.is_empty() {
+                println!(
+                    "{} ({}):",
+                    colors::label("Broken sessions"),
+                    colors::number(&format_count(response.broken.len() as u64))
+                );
+                for session in &response.broken {
+                    println!(
+                        "  {:<20} {:<8}  {:>10}  {}",
+                        colors::session_id(&session.id),
+                        colors::warning(session.status),
+                        colors::number(&format_bytes(session.size_bytes)),
+                        colors::dim(&session.reason)
+                    );
+                }
+                println!(
+                    "Run '{}' to delete them.",
+                    colors::label("shebe list-sessions --prune-corrupt")
+                );
+            }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
     }
@@ -157,6 +442,46 @@ pub async fn execute_list(
     Ok(())
 }
 
+/// Delete corrupt and orphaned sessions, after confirmation unless `force`
+///
+/// Returns `None` if the prompt was declined.
+fn prune_broken(
+    services: &Arc<Services>,
+    force: bool,
+) -> Result<Option<PrunedSessions>, Box<dyn std::error::Error>> {
+    if !force {
+        let broken: Vec<String> = services
+            .storage
+            .list_session_entries()?
+            .iter()
+            .filter(|entry| !entry.is_valid())
+            .map(|entry| entry.id().to_string())
+            .collect();
+        if !broken.is_empty() {
+            print!(
+                "Delete {} corrupt or orphaned session(s) ({})? [y/N] ",
+                broken.len(),
+                broken.join(", ")
+            );
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+
+            if !input.trim().eq_ignore_ascii_case("y") {
+                println!("{}", colors::dim("Cancelled."));
+                return Ok(None);
+            }
+        }
+    }
+
+    let pruned = services.storage.prune_broken_sessions()?;
+    Ok(Some(PrunedSessions {
+        reclaimed_bytes: pruned.iter().map(|(_, size_bytes)| size_bytes).sum(),
+        sessions: pruned.into_iter().map(|(id, _)| id).collect(),
+    }))
+}
+
 /// Execute get-session-info command
 pub async fn execute_info(
     args: InfoArgs,
@@ -167,13 +492,28 @@ pub async fn execute_info(
     let metadata = services
         .storage
         .get_session_metadata(&args.session)
-        .map_err(|_| {
-            format!(
-                "Session '{}' not found. Run 'shebe list-sessions' to see available sessions.",
-                args.session
-            )
-        })?;
+        .map_err(|_| session_not_found(services, &args.session))?;
 
+    if let Some(path) = &args.why_not_indexed {
+        return print_why_not_indexed(services, &args.session, path, format);
+    }
+    if args.check_chunks {
+        return print_chunk_check(services, &args.session, format);
+    }
+    if args.check_split_tokens {
+        return print_split_token_check(services, &args.session, format);
+    }
+
+    let dedupe_ratio = metadata.dedupe_ratio();
+    let stale_after_days = services.config.current().health.stale_after_days;
+    let health = services
+        .storage
+        .session_health(&args.session, stale_after_days)?;
+    let recommended_actions = health
+        .actions
+        .iter()
+        .map(|action| format!("{} — {}", action.summary(), remedy(action)))
+        .collect();
     let response = SessionInfoResponse {
         id: metadata.id.clone(),
         repository_path: metadata.repository_path.to_string_lossy().into_owned(),
@@ -181,10 +521,37 @@ pub async fn execute_info(
         chunks: metadata.chunks_created,
         size_bytes: metadata.index_size_bytes,
         indexed_at: metadata.last_indexed_at.to_rfc3339(),
+        files_deduplicated: metadata.files_deduplicated,
+        skipped: metadata.skipped.clone(),
         config: SessionConfigInfo {
             chunk_size: metadata.config.chunk_size,
             overlap: metadata.config.overlap,
+            dedupe: metadata.config.dedupe,
+            boundary_hint: metadata.config.boundary_hint,
+            store_chunk_text: metadata.config.store_chunk_text,
+            strip_high_entropy: metadata.config.strip_high_entropy,
+            strip_run_chars: metadata.config.strip_run_chars,
+            merge_tail_fragments: metadata.config.merge_tail_fragments,
+            record_chunking: metadata.config.record_chunking,
+            max_chunks_per_file: metadata.config.max_chunks_per_file,
+            bm25_k1: metadata.config.bm25_k1,
+            bm25_b: metadata.config.bm25_b,
+            sources: metadata.config.sources.clone(),
+        },
+        index: SessionIndexInfo {
+            created_by_version: metadata.created_by_version.clone(),
+            running_version: CURRENT_VERSION.to_string(),
+            schema_version: metadata.schema_version,
+            tantivy_schema_hash: metadata.tantivy_schema_hash.clone(),
+            schema_hash_matches: metadata.schema_hash_matches(),
+            settings: metadata.index_settings.clone(),
+            chunk_text_bytes: metadata.chunk_text_bytes,
         },
+        remote: metadata.remote.clone(),
+        provenance: metadata.provenance.clone(),
+        search_defaults: metadata.search_defaults.filter(|d| !d.is_empty()),
+        health,
+        recommended_actions,
     };
 
     match format {
@@ -199,37 +566,182 @@ pub async fn execute_info(
                 colors::label("Repository"),
                 colors::file_path(&response.repository_path)
             );
+            if let Some(remote) = &response.remote {
+                println!(
+                    "  {}: {} ({}, commit {})",
+                    colors::label("Remote"),
+                    colors::file_path(&remote.url),
+                    remote.ref_label(),
+                    colors::number(remote.short_commit())
+                );
+            }
+            if let Some(git) = response.provenance.as_ref().and_then(|p| p.git.as_ref()) {
+                println!(
+                    "  {}: {}{}",
+                    colors::label("Git commit"),
+                    colors::number(&git.commit),
+                    if git.dirty {
+                        " (uncommitted changes)"
+                    } else {
+                        ""
+                    }
+                );
+            }
             println!(
                 "  {}: {}",
                 colors::label("Files"),
-                colors::number(&response.files.to_string())
+                colors::number(&format_count(response.files as u64))
             );
             println!(
                 "  {}: {}",
                 colors::label("Chunks"),
-                colors::number(&response.chunks.to_string())
+                colors::number(&format_count(response.chunks as u64))
             );
             println!(
                 "  {}: {}",
                 colors::label("Size"),
                 colors::number(&format_bytes(response.size_bytes))
             );
+            let text_bytes = response.index.chunk_text_bytes;
+            let text_storage = match (response.config.store_chunk_text, text_bytes) {
+                (true, 0) => "stored".to_string(),
+                (true, _) => format!("stored (up to ~{})", format_bytes(text_bytes)),
+                (false, _) => format!(
+                    "not stored, read from files (~{} saved)",
+                    format_bytes(text_bytes)
+                ),
+            };
+            println!("  {}: {}", colors::label("Chunk text"), text_storage);
+            if response.config.dedupe {
+                println!(
+                    "  {}: {} ({:.1}%)",
+                    colors::label("Duplicates"),
+                    colors::number(&format_count(response.files_deduplicated as u64)),
+                    dedupe_ratio * 100.0
+                );
+            }
+            if response.skipped.total() > 0 {
+                println!(
+                    "  {}: {} ({})",
+                    colors::label("Skipped"),
+                    colors::number(&format_count(response.skipped.total() as u64)),
+                    response.skipped.summary()
+                );
+            }
             println!(
                 "  {}: {}",
                 colors::label("Indexed"),
-                colors::dim(&response.indexed_at)
+                colors::dim(
+                    &chrono::DateTime::parse_from_rfc3339(&response.indexed_at)
+                        .map(|at| format_timestamp(at.with_timezone(&chrono::Utc)))
+                        .unwrap_or_else(|_| response.indexed_at.clone())
+                )
             );
+            let sources = response.config.sources.as_ref();
+            let source = |pick: fn(&ConfigSources) -> ConfigSource| {
+                sources.map_or(String::new(), |s| {
+                    colors::dim(&format!(" ({})", pick(s))).to_string()
+                })
+            };
             println!("  {}:", colors::label("Config"));
             println!(
-                "    chunk_size: {}",
-                colors::number(&response.config.chunk_size.to_string())
+                "    chunk_size: {}{}",
+                colors::number(&response.config.chunk_size.to_string()),
+                source(|s| s.chunk_size)
+            );
+            println!(
+                "    overlap: {}{}",
+                colors::number(&response.config.overlap.to_string()),
+                source(|s| s.overlap)
+            );
+            println!("    dedupe: {}", response.config.dedupe);
+            if response.config.boundary_hint != BoundaryHint::None {
+                println!("    boundary_hint: {}", response.config.boundary_hint);
+            }
+            if response.config.strip_high_entropy {
+                println!(
+                    "    strip_high_entropy: runs over {} chars",
+                    colors::number(&response.config.strip_run_chars.to_string())
+                );
+            }
+            if response.config.merge_tail_fragments {
+                println!("    merge_tail_fragments: true");
+            }
+            if !response.config.record_chunking {
+                println!("    record_chunking: false");
+            }
+            println!(
+                "    max_chunks_per_file: {}",
+                colors::number(&response.config.max_chunks_per_file.to_string())
+            );
+            println!(
+                "    bm25: k1={} b={}",
+                colors::number(&response.config.bm25_k1.to_string()),
+                colors::number(&response.config.bm25_b.to_string())
             );
+            if let Some(sources) = sources {
+                println!(
+                    "    include_patterns: {}",
+                    colors::dim(&format!("from {}", sources.include_patterns))
+                );
+                println!(
+                    "    exclude_patterns: {}",
+                    colors::dim(&format!("from {}", sources.exclude_patterns))
+                );
+                if !sources.ignore_patterns.is_empty() {
+                    println!("    {IGNORE_FILE}: {}", sources.ignore_patterns.join(", "));
+                }
+            }
+            if let Some(defaults) = &response.search_defaults {
+                println!(
+                    "  {}: {}",
+                    colors::label("Search defaults"),
+                    defaults.summary()
+                );
+            }
+            let index = &response.index;
+            println!("  {}:", colors::label("Index"));
+            println!(
+                "    created_by: shebe {} {}",
+                index.created_by_version,
+                colors::dim(&format!("(running {})", index.running_version))
+            );
+            let hash_status = match index.schema_hash_matches {
+                Some(true) => "matches current",
+                Some(false) => "differs from current",
+                None => "not recorded",
+            };
             println!(
-                "    overlap: {}",
-                colors::number(&response.config.overlap.to_string())
+                "    schema: v{}, hash {} {}",
+                index.schema_version,
+                index.tantivy_schema_hash,
+                colors::dim(&format!("({hash_status})"))
             );
+            match &index.settings {
+                Some(settings) => {
+                    println!("    fields:");
+                    for field in &settings.fields {
+                        println!("      {field}");
+                    }
+                }
+                None => println!("    fields: unknown"),
+            }
+            if metadata.major_version_differs() {
+                print_warning(
+                    "Session was built by a different major version; \
+                     run 'shebe reindex-session --force' if results look wrong",
+                );
+            }
+            print_health(&response.health);
+            println!("  {}:", colors::label("Recommended actions"));
+            if response.recommended_actions.is_empty() {
+                println!("    none");
+            }
+            for action in &response.recommended_actions {
+                println!("    - {action}");
+            }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
     }
@@ -237,6 +749,243 @@ pub async fn execute_info(
     Ok(())
 }
 
+/// Print the health block of get-session-info
+fn print_health(health: &SessionHealth) {
+    println!("  {}:", colors::label("Health"));
+    match health.index_docs {
+        Some(docs) if docs == health.chunks_created as u64 => {
+            println!(
+                "    index_docs: {} (matches chunks)",
+                colors::number(&format_count(docs))
+            );
+        }
+        Some(docs) => println!(
+            "    index_docs: {} {}",
+            colors::number(&format_count(docs)),
+            colors::dim(&format!("({} chunks recorded)", health.chunks_created))
+        ),
+        None => println!("    index_docs: unknown (index could not be opened)"),
+    }
+    println!(
+        "    repository: {}",
+        if health.repository_exists {
+            "present"
+        } else {
+            "missing"
+        }
+    );
+    match &health.files {
+        Some(files) => {
+            let scope = if files.sampled {
+                format!("sampled {} of {}", files.checked, files.indexed)
+            } else {
+                format!("all {} checked", files.checked)
+            };
+            println!(
+                "    files: {} missing, {} modified {}",
+                colors::number(&format_count(files.missing as u64)),
+                colors::number(&format_count(files.modified as u64)),
+                colors::dim(&format!("({scope})"))
+            );
+        }
+        None => println!("    files: not checked"),
+    }
+    println!(
+        "    freshness: {}, indexed {} {}",
+        if health.stale { "stale" } else { "fresh" },
+        format_relative_time(&health.last_indexed_at),
+        colors::dim(&format!("(stale after {} days)", health.stale_after_days))
+    );
+}
+
+/// The command that addresses a health problem
+fn remedy(action: &HealthAction) -> &'static str {
+    match action {
+        HealthAction::RepositoryMissing { .. } => {
+            "delete it with 'shebe delete-session', or index the new location with \
+             'shebe index-repository'"
+        }
+        HealthAction::SchemaOutdated { .. }
+        | HealthAction::DocCountMismatch { .. }
+        | HealthAction::FilesChanged { .. }
+        | HealthAction::Stale { .. } => "run 'shebe reindex-session --force'",
+    }
+}
+
+/// Print whether a file would be indexed and the rule that decides it
+fn print_why_not_indexed(
+    services: &Arc<Services>,
+    session: &str,
+    path: &Path,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let explanation = services.why_not_indexed(session, path)?;
+
+    match format {
+        OutputFormat::Human => {
+            let outcome = match explanation.reason {
+                Some(reason) => format!("skipped ({})", reason.label()),
+                None => "would be indexed".to_string(),
+            };
+            println!(
+                "{}: {}",
+                colors::label("File"),
+                colors::file_path(&explanation.path.display().to_string())
+            );
+            println!("  {}: {}", colors::label("Outcome"), outcome);
+            println!("  {}: {}", colors::label("Rule"), explanation.detail);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let response = serde_json::json!({
+                "session": session,
+                "path": explanation.path,
+                "indexed": explanation.reason.is_none(),
+                "reason": explanation.reason,
+                "detail": explanation.detail,
+            });
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the split token check of a session
+///
+/// Split tokens make identifiers unfindable but do not break the
+/// index, so this succeeds either way.
+fn print_split_token_check(
+    services: &Arc<Services>,
+    session: &str,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = MetadataValidator::new(&services.storage).check_split_tokens(session)?;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{}: {}",
+                colors::label("Session"),
+                colors::session_id(&report.session_id)
+            );
+            let skipped = if report.files_skipped > 0 {
+                format!(
+                    " ({} skipped, text no longer matches offsets)",
+                    report.files_skipped
+                )
+            } else {
+                String::new()
+            };
+            println!(
+                "  {}: {} files, {} chunk boundaries{}",
+                colors::label("Sampled"),
+                colors::number(&format_count(report.files_sampled as u64)),
+                colors::number(&format_count(report.pairs_checked as u64)),
+                skipped
+            );
+            println!(
+                "  {}: {} ({:.1}%)",
+                colors::label("Split tokens"),
+                colors::number(&format_count(report.split_pairs as u64)),
+                report.split_rate() * 100.0
+            );
+            for split in &report.examples {
+                println!(
+                    "  {}  chunk {}: {}|{}",
+                    colors::file_path(&split.file_path),
+                    split.chunk_index,
+                    split.before,
+                    split.after
+                );
+            }
+            if report.split_pairs == 0 {
+                println!("{}", colors::success("No identifiers split across chunks"));
+            } else {
+                println!(
+                    "{}",
+                    colors::dim(
+                        "Identifiers split across chunks cannot be found by name; \
+                         reindex-session --force re-chunks at word edges"
+                    )
+                );
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&report)?)
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the chunk index check of a session
+///
+/// Fails after printing when any file's chunk indexes are inconsistent.
+fn print_chunk_check(
+    services: &Arc<Services>,
+    session: &str,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = MetadataValidator::new(&services.storage).validate_chunk_indexes(session)?;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{}: {}",
+                colors::label("Session"),
+                colors::session_id(&report.session_id)
+            );
+            println!(
+                "  {}: {} files, {} chunks",
+                colors::label("Checked"),
+                colors::number(&format_count(report.files_checked as u64)),
+                colors::number(&format_count(report.chunks_checked as u64))
+            );
+            for problem in &report.problems {
+                let mut details = Vec::new();
+                if !problem.missing.is_empty() {
+                    details.push(format!("missing {}", join_indexes(&problem.missing)));
+                }
+                if !problem.duplicates.is_empty() {
+                    details.push(format!("duplicate {}", join_indexes(&problem.duplicates)));
+                }
+                println!(
+                    "  {}  {} chunks, {}",
+                    colors::file_path(&problem.file_path),
+                    problem.chunk_count,
+                    details.join("; ")
+                );
+            }
+            if report.is_consistent() {
+                println!("{}", colors::success("Chunk indexes are contiguous"));
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&report)?)
+        }
+    }
+
+    if report.is_consistent() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} file(s) have gaps or duplicates in their chunk indexes; \
+             re-index the session with reindex-session --force",
+            report.problems.len()
+        )
+        .into())
+    }
+}
+
+/// Chunk indexes as `1, 3, 4`
+fn join_indexes(indexes: &[usize]) -> String {
+    indexes
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Execute delete-session command
 pub async fn execute_delete(
     args: DeleteArgs,
@@ -245,11 +994,7 @@ pub async fn execute_delete(
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Check if session exists (returns bool, not Result)
     if !services.storage.session_exists(&args.session) {
-        return Err(format!(
-            "Session '{}' not found. Run 'shebe list-sessions' to see available sessions.",
-            args.session
-        )
-        .into());
+        return Err(session_not_found(services, &args.session).into());
     }
 
     // Confirmation prompt unless --force
@@ -269,20 +1014,187 @@ pub async fn execute_delete(
         }
     }
 
+    // Corrupt and orphaned sessions have no readable metadata
+    let remote = services
+        .storage
+        .get_session_metadata(&args.session)
+        .ok()
+        .and_then(|m| m.remote);
+    let freed_bytes = services.storage.measure_session_size(&args.session);
+    // Deleting drops the session from these
+    let collections = services
+        .storage
+        .collections_of(&args.session)
+        .unwrap_or_default();
     services.storage.delete_session(&args.session)?;
+    if !collections.is_empty() {
+        print_warning(&format!(
+            "session '{}' was removed from collection(s): {}",
+            args.session,
+            collections.join(", ")
+        ));
+    }
+    let clone_purged = if args.purge_clone && remote.is_some() {
+        services.clones.purge(&args.session)?
+    } else {
+        false
+    };
 
     match format {
         OutputFormat::Human => {
             println!(
-                "{} session '{}'",
+                "{} session '{}' ({} freed)",
                 colors::success("Deleted"),
-                colors::session_id(&args.session)
+                colors::session_id(&args.session),
+                colors::number(&format_bytes(freed_bytes))
             );
+            if clone_purged {
+                println!("Removed cached clone of the remote repository");
+            } else if remote.is_some() {
+                println!(
+                    "{}",
+                    colors::dim("Cached clone kept (use --purge-clone to remove it)")
+                );
+            }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             let response = serde_json::json!({
                 "deleted": true,
-                "session": args.session
+                "session": args.session,
+                "freed_bytes": freed_bytes,
+                "clone_purged": clone_purged,
+                "removed_from_collections": collections
+            });
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Config changes a re-index applies, as (label, old, new)
+fn config_changes(old: &SessionConfig, new: &SessionConfig) -> Vec<(&'static str, String, String)> {
+    let mut changes = Vec::new();
+    if old.chunk_size != new.chunk_size {
+        changes.push((
+            "Chunk size",
+            old.chunk_size.to_string(),
+            new.chunk_size.to_string(),
+        ));
+    }
+    if old.overlap != new.overlap {
+        changes.push(("Overlap", old.overlap.to_string(), new.overlap.to_string()));
+    }
+    if old.store_chunk_text != new.store_chunk_text {
+        changes.push((
+            "Chunk text",
+            text_storage_label(old.store_chunk_text).to_string(),
+            text_storage_label(new.store_chunk_text).to_string(),
+        ));
+    }
+    if old.bm25_params() != new.bm25_params() {
+        changes.push((
+            "BM25",
+            format!("k1={} b={}", old.bm25_k1, old.bm25_b),
+            format!("k1={} b={}", new.bm25_k1, new.bm25_b),
+        ));
+    }
+    if old.max_chunks_per_file != new.max_chunks_per_file {
+        changes.push((
+            "Max chunks per file",
+            old.max_chunks_per_file.to_string(),
+            new.max_chunks_per_file.to_string(),
+        ));
+    }
+    changes
+}
+
+/// Print a re-index preview in the requested format
+fn print_reindex_dry_run(
+    session: &str,
+    path: &Path,
+    preview: &ReindexDryRun,
+    old_config: &SessionConfig,
+    new_config: &SessionConfig,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let changes = config_changes(old_config, new_config);
+    let files = &preview.files;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} {} (no changes made)",
+                colors::label("Dry run:"),
+                colors::session_id(session)
+            );
+            println!(
+                "  {}: {}",
+                colors::label("Repository"),
+                colors::file_path(&path.display().to_string())
+            );
+            match (&preview.resolved_path, preview.repository_moved) {
+                (_, false) => {}
+                (Some(resolved), true) => println!(
+                    "  {}: now resolves to {}",
+                    colors::label("Path moved"),
+                    colors::file_path(&resolved.display().to_string())
+                ),
+                (None, true) => println!(
+                    "  {}: no longer exists (re-indexing would fail)",
+                    colors::label("Path moved")
+                ),
+            }
+            println!(
+                "  {}: {} added, {} removed, {} unchanged",
+                colors::label("Files"),
+                colors::number(&format_count(files.added as u64)),
+                colors::number(&format_count(files.removed as u64)),
+                colors::number(&format_count(files.unchanged as u64))
+            );
+            println!(
+                "  {}: {} indexed -> ~{} estimated",
+                colors::label("Chunks"),
+                colors::number(&format_count(preview.indexed_chunks)),
+                colors::number(&format_count(preview.report.estimated_chunks as u64))
+            );
+            for (label, old, new) in &changes {
+                println!("  {}: {old} -> {new}", colors::label(label));
+            }
+            for (label, paths, count) in [
+                ("Added", &files.sample_added, files.added),
+                ("Removed", &files.sample_removed, files.removed),
+            ] {
+                if paths.is_empty() {
+                    continue;
+                }
+                println!("\n{}", colors::label(&format!("{label} files:")));
+                for path in paths {
+                    println!("  {}", colors::file_path(path));
+                }
+                if count > paths.len() {
+                    println!(
+                        "  {}",
+                        colors::dim(&format!("... and {} more", count - paths.len()))
+                    );
+                }
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let response = serde_json::json!({
+                "session": session,
+                "repository_path": path.display().to_string(),
+                "dry_run": true,
+                "changes_made": false,
+                "repository_moved": preview.repository_moved,
+                "resolved_path": preview.resolved_path,
+                "files": files,
+                "indexed_chunks": preview.indexed_chunks,
+                "estimated_chunks": preview.report.estimated_chunks,
+                "config_changes": changes
+                    .iter()
+                    .map(|(label, old, new)| serde_json::json!({"setting": label, "old": old, "new": new}))
+                    .collect::<Vec<_>>(),
             });
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
@@ -301,16 +1213,11 @@ pub async fn execute_reindex(
     let metadata = services
         .storage
         .get_session_metadata(&args.session)
-        .map_err(|_| {
-            format!(
-                "Session '{}' not found. Run 'shebe list-sessions' to see available sessions.",
-                args.session
-            )
-        })?;
+        .map_err(|_| session_not_found(services, &args.session))?;
 
     // Get repository path (it's a PathBuf, not Option)
     let path = metadata.repository_path.clone();
-    if !path.exists() {
+    if metadata.remote.is_none() && !args.dry_run && !path.exists() {
         return Err(format!(
             "Repository path '{}' no longer exists. \
              Delete the session with 'shebe delete-session {}' and re-index from the new location.",
@@ -323,22 +1230,118 @@ pub async fn execute_reindex(
     // Build config with overrides
     let chunk_size = args.chunk_size.unwrap_or(metadata.config.chunk_size);
     let overlap = args.overlap.unwrap_or(metadata.config.overlap);
-    let include_patterns = metadata.config.include_patterns.clone();
-    let exclude_patterns = metadata.config.exclude_patterns.clone();
+    let session_config = crate::core::storage::SessionConfig {
+        chunk_size,
+        overlap,
+        include_patterns: metadata.config.include_patterns.clone(),
+        exclude_patterns: metadata.config.exclude_patterns.clone(),
+        languages: metadata.config.languages.clone(),
+        dedupe: metadata.config.dedupe,
+        boundary_hint: metadata.config.boundary_hint,
+        store_chunk_text: args
+            .store_chunk_text
+            .unwrap_or(metadata.config.store_chunk_text),
+        strip_high_entropy: metadata.config.strip_high_entropy,
+        strip_run_chars: metadata.config.strip_run_chars,
+        merge_tail_fragments: metadata.config.merge_tail_fragments,
+        record_chunking: metadata.config.record_chunking,
+        max_chunks_per_file: args
+            .max_chunks_per_file
+            .map_or(metadata.config.max_chunks_per_file, |max| max as usize),
+        strict_patterns: metadata.config.strict_patterns,
+        git_metadata: metadata.config.git_metadata
+            && services.config.current().indexing.git_metadata,
+        sources: metadata.config.sources.clone().map(|sources| {
+            sources.with_chunk_overrides(args.chunk_size.is_some(), args.overlap.is_some())
+        }),
+        bm25_k1: args.bm25_k1.unwrap_or(metadata.config.bm25_k1),
+        bm25_b: args.bm25_b.unwrap_or(metadata.config.bm25_b),
+    };
+    session_config.validate()?;
+    let storage_changed = session_config.store_chunk_text != metadata.config.store_chunk_text;
+    let max_chunks_changed =
+        session_config.max_chunks_per_file != metadata.config.max_chunks_per_file;
+
+    // Preview against the current checkout; remotes are not fetched
+    if args.dry_run {
+        let preview = services.dry_run_reindex(&args.session, &path, &session_config)?;
+        return print_reindex_dry_run(
+            &args.session,
+            &path,
+            &preview,
+            &metadata.config,
+            &session_config,
+            format,
+        );
+    }
+
+    // Fetch the recorded ref of sessions indexed from a git URL
+    let (path, remote) = match &metadata.remote {
+        Some(remote) => {
+            if !is_quiet() && format == OutputFormat::Human {
+                eprintln!(
+                    "Fetching {} ({})...",
+                    colors::file_path(&remote.url),
+                    remote.ref_label()
+                );
+            }
+            let updated = services.clones.update(&args.session, remote)?;
+            let clone_path = services.clones.clone_path(&args.session).canonicalize()?;
+            (clone_path, Some(updated))
+        }
+        None => (path, None),
+    };
+    let commit_changed = match (&metadata.remote, &remote) {
+        (Some(old), Some(new)) => old.commit != new.commit,
+        _ => false,
+    };
+    // Sessions indexed from an archive re-index when it was replaced
+    let archive = match &metadata.archive {
+        Some(_) => Archive::detect(&path)
+            .map(|archive| archive.source())
+            .transpose()?,
+        None => None,
+    };
+    let archive_changed = match (&metadata.archive, &archive) {
+        (Some(old), Some(new)) => old.content_hash != new.content_hash,
+        _ => false,
+    };
 
     // Check if config changed
-    let config_changed = args.chunk_size.is_some() || args.overlap.is_some();
-    if !args.force && !config_changed {
-        return Err("No configuration changes. Use --force to re-index anyway, \
-             or specify --chunk-size or --overlap to change settings."
+    let bm25_changed = session_config.bm25_params() != metadata.config.bm25_params();
+    let config_changed = args.chunk_size.is_some()
+        || args.overlap.is_some()
+        || storage_changed
+        || bm25_changed
+        || max_chunks_changed;
+    if !args.force && !config_changed && !commit_changed && !archive_changed {
+        if let Some(remote) = &remote {
+            return Err(CliError::InvalidArgs(format!(
+                "No configuration changes and {} is still at commit {}. \
+                 Use --force to re-index anyway.",
+                remote.ref_label(),
+                remote.short_commit()
+            ))
             .into());
+        }
+        if let Some(archive) = &archive {
+            return Err(CliError::InvalidArgs(format!(
+                "No configuration changes and the archive is unchanged (content hash {}). \
+                 Use --force to re-index anyway.",
+                archive.short_hash()
+            ))
+            .into());
+        }
+        return Err(CliError::InvalidArgs(
+            "No configuration changes. Use --force to re-index anyway, \
+             or specify --chunk-size or --overlap to change settings."
+                .into(),
+        )
+        .into());
     }
 
-    // Delete existing session
-    services.storage.delete_session(&args.session)?;
-
-    // Re-index
-    if format == OutputFormat::Human {
+    // Re-index; the existing session is replaced only on success
+    if !is_quiet() && format == OutputFormat::Human {
         eprintln!(
             "Re-indexing '{}' from {}...",
             colors::session_id(&args.session),
@@ -346,41 +1349,265 @@ pub async fn execute_reindex(
         );
     }
 
-    // Call index_repository with all individual arguments
-    let stats = services.storage.index_repository(
-        &args.session,
-        &path,
-        include_patterns,
-        exclude_patterns,
-        chunk_size,
-        overlap,
-        services.config.indexing.max_file_size_mb,
-        true, // force=true since we already deleted the session
-    )?;
+    let bm25 = session_config.bm25_params();
+    let max_chunks_per_file = session_config.max_chunks_per_file;
+    let progress = Arc::new(IndexProgress::new());
+    let stats = cancel_on_ctrl_c(&progress, || {
+        services.storage.index_repository_with_progress(
+            &args.session,
+            &path,
+            session_config,
+            services.config.current().indexing.max_file_size_mb,
+            true,
+            Some(&progress),
+        )
+    })?;
+    if remote.is_some() {
+        services
+            .storage
+            .set_remote_source(&args.session, remote.clone())?;
+    }
 
     let duration_secs = stats.duration_ms as f64 / 1000.0;
 
     match format {
         OutputFormat::Human => {
             println!(
-                "{} {} files ({} chunks) in {:.2}s",
+                "{} {} files ({} chunks) in {}",
                 colors::success("Indexed"),
-                colors::number(&stats.files_indexed.to_string()),
-                colors::number(&stats.chunks_created.to_string()),
-                duration_secs
+                colors::number(&format_count(stats.files_indexed as u64)),
+                colors::number(&format_count(stats.chunks_created as u64)),
+                colors::number(&format_duration(duration_secs))
             );
+            if storage_changed {
+                println!(
+                    "  {}: {} -> {}",
+                    colors::label("Chunk text"),
+                    text_storage_label(metadata.config.store_chunk_text),
+                    text_storage_label(!metadata.config.store_chunk_text)
+                );
+            }
+            if bm25_changed {
+                println!(
+                    "  {}: k1={} b={} -> k1={} b={}",
+                    colors::label("BM25"),
+                    metadata.config.bm25_k1,
+                    metadata.config.bm25_b,
+                    bm25.k1,
+                    bm25.b
+                );
+            }
+            if max_chunks_changed {
+                println!(
+                    "  {}: {} -> {}",
+                    colors::label("Max chunks per file"),
+                    metadata.config.max_chunks_per_file,
+                    max_chunks_per_file
+                );
+            }
+            if !stats.truncated_files.is_empty() {
+                println!(
+                    "  {}: {} file(s) at {} chunks",
+                    colors::label("Partially indexed"),
+                    colors::number(&format_count(stats.truncated_files.len() as u64)),
+                    max_chunks_per_file
+                );
+                for file in &stats.truncated_files {
+                    println!("    {}", colors::file_path(&file.path));
+                }
+            }
+            if stats.files_renamed > 0 {
+                println!(
+                    "  {}: {} file(s)",
+                    colors::label("Renamed"),
+                    colors::number(&format_count(stats.files_renamed as u64))
+                );
+                print_renames(&stats.renamed_files, stats.files_renamed, "    ");
+            }
+            if let (Some(old), Some(new)) = (&metadata.remote, &remote) {
+                println!(
+                    "  {}: {} -> {}",
+                    colors::label("Commit"),
+                    colors::number(old.short_commit()),
+                    colors::number(new.short_commit())
+                );
+            }
+            if let (Some(old), Some(new)) = (&metadata.archive, &archive) {
+                println!(
+                    "  {}: {} -> {}",
+                    colors::label("Archive hash"),
+                    colors::number(old.short_hash()),
+                    colors::number(new.short_hash())
+                );
+            }
         }
-        OutputFormat::Json => {
-            let response = serde_json::json!({
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let mut response = serde_json::json!({
                 "session": args.session,
                 "repository_path": path.display().to_string(),
                 "files_indexed": stats.files_indexed,
                 "chunks_created": stats.chunks_created,
-                "duration_secs": duration_secs
+                "duration_secs": duration_secs,
+                "bm25_k1": bm25.k1,
+                "bm25_b": bm25.b
             });
+            if let Some(remote) = &remote {
+                response["remote"] = serde_json::to_value(remote)?;
+            }
+            if let Some(archive) = &archive {
+                response["archive"] = serde_json::to_value(archive)?;
+            }
+            if !stats.truncated_files.is_empty() {
+                response["truncated_files"] = serde_json::to_value(&stats.truncated_files)?;
+            }
+            if stats.files_renamed > 0 {
+                response["files_renamed"] = stats.files_renamed.into();
+                response["renamed_files"] = serde_json::to_value(&stats.renamed_files)?;
+            }
+            if let Some(provenance) = &stats.provenance {
+                response["provenance"] = serde_json::to_value(provenance)?;
+            }
             println!("{}", serde_json::to_string_pretty(&response)?);
         }
     }
 
     Ok(())
 }
+
+/// Execute compact-session command
+pub async fn execute_compact(
+    args: CompactArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !services.storage.session_exists(&args.session) {
+        return Err(session_not_found(services, &args.session).into());
+    }
+
+    let stats = services.storage.compact_session(&args.session)?;
+    print_compaction("Compacted", &stats, format)
+}
+
+/// Execute optimize-session command
+pub async fn execute_optimize(
+    args: OptimizeArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !services.storage.session_exists(&args.session) {
+        return Err(session_not_found(services, &args.session).into());
+    }
+
+    let target_segments = args.target_segments.map_or_else(
+        || services.config.current().maintenance.target_segments,
+        |n| n as usize,
+    );
+    let stats = services
+        .storage
+        .optimize_session(&args.session, target_segments)?;
+    print_compaction("Optimized", &stats, format)
+}
+
+/// Execute set-session-defaults command
+///
+/// Options not given keep their current default. Only the session
+/// metadata is written.
+pub async fn execute_set_defaults(
+    args: SetDefaultsArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let metadata = services
+        .storage
+        .get_session_metadata(&args.session)
+        .map_err(|_| session_not_found(services, &args.session))?;
+
+    let update = SessionSearchDefaults {
+        k: args.limit,
+        fuzziness: args.fuzziness,
+        path_boost: args.path_boost,
+        output_mode: args
+            .output_mode
+            .as_deref()
+            .map(OutputMode::parse)
+            .transpose()
+            .map_err(CliError::InvalidArgs)?,
+    };
+    update.validate().map_err(CliError::InvalidArgs)?;
+
+    let current = metadata
+        .search_defaults
+        .filter(|_| !args.clear)
+        .unwrap_or_default();
+    let defaults = current.merged(update);
+    services
+        .storage
+        .set_search_defaults(&args.session, (!defaults.is_empty()).then_some(defaults))?;
+
+    let response = SessionDefaultsResponse {
+        session: args.session,
+        search_defaults: defaults,
+    };
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} search defaults of session '{}'",
+                colors::success("Updated"),
+                colors::session_id(&response.session)
+            );
+            let summary = if defaults.is_empty() {
+                "none (server defaults apply)".to_string()
+            } else {
+                defaults.summary()
+            };
+            println!("  {}: {}", colors::label("Search defaults"), summary);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print segment counts and sizes before and after compaction
+fn print_compaction(
+    verb: &str,
+    stats: &CompactionStats,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} session '{}'",
+                colors::success(verb),
+                colors::session_id(&stats.session)
+            );
+            println!(
+                "  {}: {} -> {}",
+                colors::label("Segments"),
+                colors::number(&format_count(stats.segments_before as u64)),
+                colors::number(&format_count(stats.segments_after as u64))
+            );
+            println!(
+                "  {}: {} -> {}",
+                colors::label("Size"),
+                colors::number(&format_bytes(stats.size_before_bytes)),
+                colors::number(&format_bytes(stats.size_after_bytes))
+            );
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(stats)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Chunk text storage mode for display
+fn text_storage_label(store_chunk_text: bool) -> &'static str {
+    if store_chunk_text {
+        "stored"
+    } else {
+        "not stored"
+    }
+}
@@ -0,0 +1,88 @@
+//! Get-storage-report command - disk usage across sessions
+
+use crate::cli::output::{colors, format_bytes, print_output};
+use crate::cli::OutputFormat;
+use crate::core::services::Services;
+use crate::core::storage::SIZE_DRIFT_THRESHOLD_PERCENT;
+use clap::Args;
+use std::sync::Arc;
+
+/// Arguments for the get-storage-report command
+#[derive(Args, Debug)]
+pub struct StorageReportArgs {}
+
+/// Execute the get-storage-report command
+pub async fn execute(
+    _args: StorageReportArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = services.storage.storage_report()?;
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} {}",
+                colors::label("Storage root:"),
+                colors::file_path(&report.storage_root.display().to_string())
+            );
+            println!(
+                "{} {} using {} (metadata records {})",
+                colors::label("Sessions:"),
+                colors::number(&report.sessions.len().to_string()),
+                colors::number(&format_bytes(report.total_session_bytes)),
+                format_bytes(report.total_metadata_bytes)
+            );
+            if let Some(available) = report.available_bytes {
+                println!(
+                    "{} {}",
+                    colors::label("Available:"),
+                    colors::number(&format_bytes(available))
+                );
+            }
+
+            if report.sessions.is_empty() {
+                println!();
+                println!("{}", colors::dim("No sessions found."));
+                return Ok(());
+            }
+
+            println!();
+            for session in &report.sessions {
+                let recorded = session
+                    .metadata_bytes
+                    .map(format_bytes)
+                    .unwrap_or_else(|| "unreadable".to_string());
+                let line = format!(
+                    "  {}  {} on disk, {} recorded",
+                    colors::session_id(&session.session_id),
+                    colors::number(&format_bytes(session.actual_bytes)),
+                    recorded
+                );
+                match session.drift_percent {
+                    Some(percent) if session.drifted => println!(
+                        "{line}  {}",
+                        colors::warning(&format!("{percent:.0}% drift (stale)"))
+                    ),
+                    _ => println!("{line}"),
+                }
+            }
+
+            let drifted = report.drifted_count();
+            if drifted > 0 {
+                println!();
+                println!(
+                    "{}",
+                    colors::dim(&format!(
+                        "{drifted} session(s) record a size more than \
+                         {SIZE_DRIFT_THRESHOLD_PERCENT:.0}% off from disk. \
+                         Run 'shebe compact-session' or 'shebe reindex-session' to refresh it."
+                    ))
+                );
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => print_output(&report, format),
+    }
+
+    Ok(())
+}
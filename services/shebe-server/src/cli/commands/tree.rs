@@ -0,0 +1,125 @@
+//! List-tree command - directory overview of a session
+
+use crate::cli::commands::session_not_found;
+use crate::cli::output::colors;
+use crate::cli::OutputFormat;
+use crate::core::services::Services;
+use crate::core::tree::{NodeKind, TreeNode, TreeOptions};
+use clap::Args;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Arguments for the list-tree command
+#[derive(Args, Debug)]
+pub struct TreeArgs {
+    /// Session ID
+    pub session: String,
+
+    /// Subdirectory to root the tree at (relative to the repository)
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Directory levels to show (1-10)
+    #[arg(long, short = 'd', default_value = "3")]
+    pub depth: usize,
+
+    /// Entries shown per directory before "... and N more" (1-200)
+    #[arg(long, default_value = "20")]
+    pub max_children: usize,
+}
+
+/// Tree response
+#[derive(Debug, Serialize)]
+pub struct TreeResponse {
+    pub session: String,
+    pub repository_path: String,
+    pub depth: usize,
+    pub tree: TreeNode,
+}
+
+/// Execute the list-tree command
+pub async fn execute(
+    args: TreeArgs,
+    services: &Arc<Services>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let metadata = services
+        .storage
+        .get_session_metadata(&args.session)
+        .map_err(|_| session_not_found(services, &args.session))?;
+
+    let options = TreeOptions {
+        depth: args.depth.clamp(1, 10),
+        max_children: args.max_children.clamp(1, 200),
+    };
+    let tree = services
+        .storage
+        .file_tree(&args.session, args.path.as_deref(), options)?;
+
+    let response = TreeResponse {
+        session: args.session,
+        repository_path: metadata.repository_path.to_string_lossy().into_owned(),
+        depth: options.depth,
+        tree,
+    };
+
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} {} ({} files, {} chunks)",
+                colors::label("Session:"),
+                colors::session_id(&response.session),
+                colors::number(&response.tree.file_count.to_string()),
+                colors::number(&response.tree.chunk_count.to_string())
+            );
+            let root = if response.tree.name == "." {
+                response.repository_path.clone()
+            } else {
+                format!("{}/{}", response.repository_path, response.tree.name)
+            };
+            println!("{}", colors::file_path(&root));
+            print_children(&response.tree, 1);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the children of `node` indented by `level`
+fn print_children(node: &TreeNode, level: usize) {
+    let indent = "  ".repeat(level);
+
+    for child in &node.children {
+        match child.kind {
+            NodeKind::File => println!(
+                "{indent}{} {}",
+                child.name,
+                colors::dim(&format!("({} chunks)", child.chunk_count))
+            ),
+            NodeKind::Dir => {
+                let mut counts =
+                    format!("({} files, {} chunks", child.file_count, child.chunk_count);
+                if child.is_collapsed() {
+                    counts.push_str(&format!(", {} entries collapsed", child.hidden_children));
+                }
+                counts.push(')');
+                println!(
+                    "{indent}{} {}",
+                    colors::file_path(&format!("{}/", child.name)),
+                    colors::dim(&counts)
+                );
+                print_children(child, level + 1);
+            }
+        }
+    }
+
+    if !node.children.is_empty() && node.hidden_children > 0 {
+        println!(
+            "{indent}{}",
+            colors::dim(&format!("... and {} more", node.hidden_children))
+        );
+    }
+}
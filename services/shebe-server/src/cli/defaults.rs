@@ -0,0 +1,373 @@
+//! Per-user CLI defaults
+//!
+//! `shebe init` writes `cli.toml` in the XDG config directory and
+//! `shebe config set|get|unset` manage its keys. Before the command
+//! line is parsed, each stored value becomes the default of the options
+//! it stands for, so an option given on the command line always wins:
+//!
+//! - `session`: the session of every command that reads or maintains
+//!   one session (not `index-repository` or `delete-session`, which
+//!   create and destroy sessions)
+//! - `format`: the global `--format` (`human` or `json`)
+//! - `k`: `--limit` of `search-code` and `repl`
+//! - `profile`: the global `--profile`, unless `SHEBE_PROFILE` is set
+//!
+//! The file is separate from `config.toml`, which the MCP server reads
+//! too.
+
+use crate::cli::{CliError, OutputFormat};
+use crate::core::storage::{is_valid_name, parse_session_id};
+use crate::core::types::MAX_K;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Keys of the CLI defaults file, in display order
+pub const DEFAULT_KEYS: &[&str] = &["session", "format", "k", "profile"];
+
+/// Commands whose session argument is never filled from the defaults
+const SESSION_DEFAULT_EXCLUDED: &[&str] = &["index-repository", "delete-session", "init"];
+
+/// Commands whose `--limit` defaults to `k`
+const K_COMMANDS: &[&str] = &["search-code", "repl"];
+
+/// Defaults for options left out of a CLI command
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CliDefaults {
+    /// Session of commands that take one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Output format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    /// Results per `search-code` and `repl` query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k: Option<usize>,
+    /// Storage profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+/// A stored default used for an option missing from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedDefault {
+    /// Key in the defaults file
+    pub key: &'static str,
+    /// Value used
+    pub value: String,
+}
+
+impl CliDefaults {
+    /// Read the defaults at `path` (none when the file does not exist)
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgs` when the file cannot be read, is not valid TOML,
+    /// has unknown keys or holds a value `set` would reject.
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(CliError::InvalidArgs(format!(
+                    "Cannot read CLI defaults {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        let stored: Self = toml::from_str(&text).map_err(|e| {
+            CliError::InvalidArgs(format!("Invalid CLI defaults {}: {e}", path.display()))
+        })?;
+
+        // Values edited by hand get the checks of `config set`
+        let mut defaults = Self::default();
+        for key in DEFAULT_KEYS {
+            if let Some(value) = stored.get(key)? {
+                defaults.set(key, &value).map_err(|e| {
+                    CliError::InvalidArgs(format!("Invalid CLI defaults {}: {e}", path.display()))
+                })?;
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// Write the defaults to `path`, creating its directory
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let body = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(
+            path,
+            format!("# Shebe CLI defaults (managed by `shebe init` and `shebe config`)\n\n{body}"),
+        )
+    }
+
+    /// Value of `key`, or `None` when it is unset
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgs` for a key not in [`DEFAULT_KEYS`].
+    pub fn get(&self, key: &str) -> Result<Option<String>, CliError> {
+        Ok(match key {
+            "session" => self.session.clone(),
+            "format" => self.format.map(format_name),
+            "k" => self.k.map(|k| k.to_string()),
+            "profile" => self.profile.clone(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Set `key` to `value` after checking it
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgs` for an unknown key, a malformed session ID or
+    /// profile name, a format other than `human` or `json`, and a `k`
+    /// outside 1-100.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), CliError> {
+        match key {
+            "session" => {
+                if !parse_session_id(value).is_ok_and(|id| is_valid_name(id.name)) {
+                    return Err(CliError::InvalidArgs(format!(
+                        "Invalid session ID '{value}': use 1-64 alphanumeric, hyphen or \
+                         underscore characters, optionally as 'namespace/name'"
+                    )));
+                }
+                self.session = Some(value.to_string());
+            }
+            "format" => {
+                let format = match OutputFormat::from_str(value, true) {
+                    Ok(format @ (OutputFormat::Human | OutputFormat::Json)) => format,
+                    Ok(OutputFormat::Ndjson) => {
+                        return Err(CliError::InvalidArgs(
+                            "format 'ndjson' cannot be a default: only search-code, \
+                             find-references and index-repository support it"
+                                .to_string(),
+                        ))
+                    }
+                    Err(_) => {
+                        return Err(CliError::InvalidArgs(format!(
+                            "Invalid format '{value}': use human or json"
+                        )))
+                    }
+                };
+                self.format = Some(format);
+            }
+            "k" => {
+                let k = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|k| (1..=MAX_K).contains(k))
+                    .ok_or_else(|| {
+                        CliError::InvalidArgs(format!(
+                            "Invalid k '{value}': use a number from 1 to {MAX_K}"
+                        ))
+                    })?;
+                self.k = Some(k);
+            }
+            "profile" => {
+                if !is_valid_name(value) {
+                    return Err(CliError::InvalidArgs(format!(
+                        "Invalid profile name '{value}': use 1-64 alphanumeric, hyphen or \
+                         underscore characters"
+                    )));
+                }
+                self.profile = Some(value.to_string());
+            }
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Remove `key`
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgs` for a key not in [`DEFAULT_KEYS`].
+    pub fn unset(&mut self, key: &str) -> Result<(), CliError> {
+        match key {
+            "session" => self.session = None,
+            "format" => self.format = None,
+            "k" => self.k = None,
+            "profile" => self.profile = None,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Make the stored values the defaults of `command`'s options
+    ///
+    /// Session arguments stop being required when a session is stored.
+    pub fn apply(&self, mut command: Command) -> Command {
+        if let Some(format) = self.format {
+            command = command.mut_arg("format", |arg| arg.default_value(format_name(format)));
+        }
+        if let Some(profile) = self.stored_profile() {
+            command = command.mut_arg("profile", |arg| arg.default_value(profile.to_string()));
+        }
+
+        let names: Vec<String> = command
+            .get_subcommands()
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+        for name in names {
+            command = command.mut_subcommand(&name, |mut sub| {
+                let has_arg =
+                    |sub: &Command, id: &str| sub.get_arguments().any(|a| a.get_id() == id);
+                if let Some(session) = &self.session {
+                    if has_arg(&sub, "session")
+                        && !SESSION_DEFAULT_EXCLUDED.contains(&name.as_str())
+                    {
+                        sub = sub.mut_arg("session", |arg| {
+                            arg.default_value(session.clone()).required(false)
+                        });
+                    }
+                }
+                if let Some(k) = self.k {
+                    if has_arg(&sub, "limit") && K_COMMANDS.contains(&name.as_str()) {
+                        sub = sub.mut_arg("limit", |arg| arg.default_value(k.to_string()));
+                    }
+                }
+                sub
+            });
+        }
+        command
+    }
+
+    /// Stored values that `matches` took because an option was left out
+    pub fn applied(&self, matches: &ArgMatches) -> Vec<AppliedDefault> {
+        let from_default = |matches: &ArgMatches, id: &str| {
+            matches.ids().any(|i| i.as_str() == id)
+                && matches.value_source(id) == Some(ValueSource::DefaultValue)
+        };
+
+        let mut applied = Vec::new();
+        if let Some(format) = self.format.filter(|_| from_default(matches, "format")) {
+            applied.push(AppliedDefault {
+                key: "format",
+                value: format_name(format),
+            });
+        }
+        if let Some(profile) = self
+            .stored_profile()
+            .filter(|_| from_default(matches, "profile"))
+        {
+            applied.push(AppliedDefault {
+                key: "profile",
+                value: profile.to_string(),
+            });
+        }
+        if let Some((name, sub)) = matches.subcommand() {
+            if let Some(session) = &self.session {
+                if !SESSION_DEFAULT_EXCLUDED.contains(&name) && from_default(sub, "session") {
+                    applied.push(AppliedDefault {
+                        key: "session",
+                        value: session.clone(),
+                    });
+                }
+            }
+            if let Some(k) = self.k {
+                if K_COMMANDS.contains(&name) && from_default(sub, "limit") {
+                    applied.push(AppliedDefault {
+                        key: "k",
+                        value: k.to_string(),
+                    });
+                }
+            }
+        }
+        applied
+    }
+
+    /// Stored profile, unless `SHEBE_PROFILE` selects one
+    fn stored_profile(&self) -> Option<&str> {
+        let from_env = env::var("SHEBE_PROFILE").is_ok_and(|p| !p.is_empty());
+        self.profile.as_deref().filter(|_| !from_env)
+    }
+}
+
+/// Name of `format` as given to `--format`
+fn format_name(format: OutputFormat) -> String {
+    format
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn unknown_key(key: &str) -> CliError {
+    CliError::InvalidArgs(format!(
+        "Unknown CLI default '{key}' (known: {})",
+        DEFAULT_KEYS.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_set_validates_values() {
+        let mut defaults = CliDefaults::default();
+        defaults.set("session", "team-a/backend").unwrap();
+        defaults.set("format", "JSON").unwrap();
+        defaults.set("k", "25").unwrap();
+        assert_eq!(defaults.get("format").unwrap().as_deref(), Some("json"));
+
+        for (key, value) in [
+            ("session", "a/b/c"),
+            ("format", "ndjson"),
+            ("format", "yaml"),
+            ("k", "0"),
+            ("k", "101"),
+            ("profile", "../work"),
+            ("colour", "always"),
+        ] {
+            assert!(defaults.set(key, value).is_err(), "{key}={value}");
+        }
+        assert_eq!(defaults.k, Some(25));
+    }
+
+    #[test]
+    fn test_explicit_options_win_over_defaults() {
+        let mut defaults = CliDefaults::default();
+        defaults.set("session", "myrepo").unwrap();
+        defaults.set("k", "30").unwrap();
+        let parse = |args: &[&str]| {
+            let matches = defaults
+                .apply(Cli::command())
+                .try_get_matches_from(args)
+                .unwrap();
+            let applied = defaults.applied(&matches);
+            let keys: Vec<_> = applied.iter().map(|a| a.key).collect();
+            match <Cli as clap::FromArgMatches>::from_arg_matches(&matches)
+                .unwrap()
+                .command
+            {
+                Commands::SearchCode(args) => (args.session, args.limit, keys),
+                other => panic!("unexpected command {other:?}"),
+            }
+        };
+
+        assert_eq!(
+            parse(&["shebe", "search-code", "auth"]),
+            ("myrepo".to_string(), Some(30), vec!["session", "k"])
+        );
+        assert_eq!(
+            parse(&["shebe", "search-code", "auth", "-s", "other", "-k", "5"]),
+            ("other".to_string(), Some(5), vec![])
+        );
+
+        // index-repository still requires an explicit session
+        assert!(defaults
+            .apply(Cli::command())
+            .try_get_matches_from(["shebe", "index-repository", "/tmp/repo"])
+            .is_err());
+    }
+}
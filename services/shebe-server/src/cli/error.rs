@@ -0,0 +1,142 @@
+//! Exit codes for CLI failures
+//!
+//! Scripts can tell failures apart by exit code instead of parsing
+//! stderr. Command errors are mapped by [`error_exit_code`]; argument errors
+//! caught by clap already exit with [`exit_code::INVALID_ARGS`].
+
+use crate::core::error::ShebeError;
+use std::error::Error;
+use std::io;
+use thiserror::Error;
+
+/// Process exit codes (documented in docs/guides/cli-usage.md)
+pub mod exit_code {
+    /// Command succeeded
+    pub const SUCCESS: i32 = 0;
+    /// Any failure without a more specific code
+    pub const FAILURE: i32 = 1;
+    /// Invalid arguments or options
+    pub const INVALID_ARGS: i32 = 2;
+    /// The session does not exist
+    pub const SESSION_NOT_FOUND: i32 = 3;
+    /// Indexing, search or storage failed
+    pub const INDEX_ERROR: i32 = 4;
+    /// Nothing matched and `--fail-on-empty` was given
+    pub const NO_MATCHES: i32 = 5;
+    /// Stopped by Ctrl-C
+    pub const INTERRUPTED: i32 = 130;
+}
+
+/// CLI failure with a specific exit code
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// Arguments were rejected by command validation
+    #[error("{0}")]
+    InvalidArgs(String),
+
+    /// The session does not exist (message includes suggestions)
+    #[error("{0}")]
+    SessionNotFound(String),
+
+    /// A search or find command matched nothing under `--fail-on-empty`
+    #[error("{0}")]
+    NoMatches(String),
+
+    /// `self-test` ran and at least one step failed
+    #[error("{0}")]
+    SelfTestFailed(String),
+}
+
+impl CliError {
+    /// Exit code for this failure
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::InvalidArgs(_) => exit_code::INVALID_ARGS,
+            CliError::SessionNotFound(_) => exit_code::SESSION_NOT_FOUND,
+            CliError::NoMatches(_) => exit_code::NO_MATCHES,
+            CliError::SelfTestFailed(_) => exit_code::FAILURE,
+        }
+    }
+}
+
+/// Exit code for an error returned by [`crate::cli::run`]
+pub fn error_exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if let Some(error) = error.downcast_ref::<CliError>() {
+        return error.exit_code();
+    }
+    match error.downcast_ref::<ShebeError>() {
+        Some(error) => shebe_exit_code(error),
+        None => exit_code::FAILURE,
+    }
+}
+
+/// Whether `error` is a write to a closed pipe (`shebe ... | head -1`)
+///
+/// The reader has all it wanted, so the CLI exits 0 without a message.
+pub fn is_broken_pipe(error: &(dyn Error + 'static)) -> bool {
+    let io_error = match error.downcast_ref::<ShebeError>() {
+        Some(ShebeError::IoError(e)) => Some(e),
+        _ => error.downcast_ref::<io::Error>(),
+    };
+    io_error.is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+}
+
+fn shebe_exit_code(error: &ShebeError) -> i32 {
+    match error {
+        ShebeError::SessionNotFound(_) => exit_code::SESSION_NOT_FOUND,
+        ShebeError::SessionAlreadyExists(_)
+        | ShebeError::CollectionNotFound(_)
+        | ShebeError::CollectionAlreadyExists(_)
+        | ShebeError::InvalidSession(_)
+        | ShebeError::InvalidPath(_)
+        | ShebeError::InvalidQuery(_)
+        | ShebeError::InvalidQueryField { .. }
+        | ShebeError::InvalidResultHandle(_)
+        | ShebeError::ConfigError(_) => exit_code::INVALID_ARGS,
+        ShebeError::IndexingFailed(_)
+        | ShebeError::IndexTooLarge(_)
+        | ShebeError::SearchFailed(_)
+        | ShebeError::StorageError(_)
+        | ShebeError::IndexLocked(_)
+        | ShebeError::IndexCorrupted { .. }
+        | ShebeError::SessionUnavailable { .. }
+        | ShebeError::GitError(_)
+        | ShebeError::IoError(_) => exit_code::INDEX_ERROR,
+        ShebeError::Cancelled(_) => exit_code::INTERRUPTED,
+        _ => exit_code::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(error: impl Into<Box<dyn Error>>) -> i32 {
+        error_exit_code(error.into().as_ref())
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(code(CliError::InvalidArgs("bad".into())), 2);
+        assert_eq!(code(CliError::SessionNotFound("x".into())), 3);
+        assert_eq!(code(CliError::NoMatches("none".into())), 5);
+        assert_eq!(code(CliError::SelfTestFailed("index".into())), 1);
+        assert_eq!(code(ShebeError::SessionNotFound("x".into())), 3);
+        assert_eq!(code(ShebeError::InvalidQuery("(".into())), 2);
+        assert_eq!(code(ShebeError::IndexLocked("x".into())), 4);
+        assert_eq!(code(ShebeError::Cancelled("x".into())), 130);
+        assert_eq!(code(ShebeError::JobNotFound("x".into())), 1);
+        assert_eq!(code("plain message"), 1);
+    }
+
+    #[test]
+    fn test_broken_pipe() {
+        let pipe = || io::Error::from(io::ErrorKind::BrokenPipe);
+        let boxed: Box<dyn Error> = pipe().into();
+        assert!(is_broken_pipe(boxed.as_ref()));
+        let boxed: Box<dyn Error> = ShebeError::IoError(pipe()).into();
+        assert!(is_broken_pipe(boxed.as_ref()));
+        let boxed: Box<dyn Error> = io::Error::from(io::ErrorKind::NotFound).into();
+        assert!(!is_broken_pipe(boxed.as_ref()));
+    }
+}
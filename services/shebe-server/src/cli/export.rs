@@ -0,0 +1,268 @@
+//! Export of command results to a file (`--output`)
+//!
+//! Writes every result as one row with the same columns for all
+//! commands: rank, file, line, offset, score and snippet. Rows are
+//! written as they are produced, so large result sets are never held
+//! as one formatted document. The format comes from the file extension
+//! unless `--output-format` is given.
+
+use crate::cli::output::is_quiet;
+use crate::cli::CliError;
+use clap::Args;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Column names, in order
+pub const COLUMNS: &[&str] = &["rank", "file", "line", "offset", "score", "snippet"];
+
+/// Export options shared by commands that list results
+#[derive(Args, Debug, Clone, Default)]
+pub struct ExportArgs {
+    /// Write all results to this file instead of the terminal (.csv, .json, .ndjson, .md)
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Format of the --output file [default: from its extension]
+    #[arg(long, value_name = "FORMAT", requires = "output")]
+    pub output_format: Option<ExportFormat>,
+
+    /// Write JSON as one object per line instead of an array
+    #[arg(long, requires = "output")]
+    pub ndjson: bool,
+}
+
+/// File format for `--output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// JSON array of row objects (NDJSON with --ndjson)
+    Json,
+    /// Markdown table
+    #[value(name = "md")]
+    Markdown,
+}
+
+/// Where and how to export, resolved from [`ExportArgs`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportTarget {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    /// One JSON object per line (JSON only)
+    pub ndjson: bool,
+}
+
+impl ExportArgs {
+    /// The export to perform, if `--output` was given
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgs` if the format cannot be inferred from the
+    /// extension, or `--ndjson` is combined with a non-JSON format.
+    pub fn target(&self) -> Result<Option<ExportTarget>, CliError> {
+        let Some(path) = &self.output else {
+            return Ok(None);
+        };
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let inferred = match extension.as_deref() {
+            Some("csv") => Some((ExportFormat::Csv, false)),
+            Some("json") => Some((ExportFormat::Json, false)),
+            Some("ndjson" | "jsonl") => Some((ExportFormat::Json, true)),
+            Some("md" | "markdown") => Some((ExportFormat::Markdown, false)),
+            _ => None,
+        };
+
+        let (format, ndjson) = match (self.output_format, inferred) {
+            (Some(format), Some((inferred, lines))) if format == inferred => (format, lines),
+            (Some(format), _) => (format, false),
+            (None, Some(inferred)) => inferred,
+            (None, None) => {
+                return Err(CliError::InvalidArgs(format!(
+                    "Cannot tell the format of '{}' from its extension; \
+                     use .csv, .json, .ndjson or .md, or pass --output-format",
+                    path.display()
+                )))
+            }
+        };
+        let ndjson = ndjson || self.ndjson;
+        if ndjson && format != ExportFormat::Json {
+            return Err(CliError::InvalidArgs(
+                "--ndjson needs JSON output (--output-format json)".to_string(),
+            ));
+        }
+
+        Ok(Some(ExportTarget {
+            path: path.clone(),
+            format,
+            ndjson,
+        }))
+    }
+}
+
+/// One exported result
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRow {
+    pub rank: usize,
+    pub file: String,
+    /// 1-based line, when the file could be read
+    pub line: Option<usize>,
+    /// Byte offset within the file
+    pub offset: usize,
+    pub score: f32,
+    pub snippet: String,
+}
+
+/// Streaming writer for [`ExportRow`]s
+pub struct ExportWriter {
+    out: BufWriter<File>,
+    target: ExportTarget,
+    rows: usize,
+}
+
+impl ExportWriter {
+    /// Create (or truncate) the target file and write its header
+    pub fn create(target: &ExportTarget) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(&target.path)?);
+        match target.format {
+            ExportFormat::Csv => writeln!(out, "{}", COLUMNS.join(","))?,
+            ExportFormat::Json if target.ndjson => {}
+            ExportFormat::Json => write!(out, "[")?,
+            ExportFormat::Markdown => {
+                writeln!(out, "| {} |", COLUMNS.join(" | "))?;
+                writeln!(out, "|{}", "---|".repeat(COLUMNS.len()))?;
+            }
+        }
+        Ok(Self {
+            out,
+            target: target.clone(),
+            rows: 0,
+        })
+    }
+
+    /// Append one row
+    pub fn write(&mut self, row: &ExportRow) -> io::Result<()> {
+        let line = row.line.map(|line| line.to_string()).unwrap_or_default();
+        match self.target.format {
+            ExportFormat::Csv => writeln!(
+                self.out,
+                "{},{},{},{},{},{}",
+                row.rank,
+                csv_field(&row.file),
+                line,
+                row.offset,
+                row.score,
+                csv_field(&row.snippet)
+            )?,
+            ExportFormat::Json => {
+                if self.target.ndjson {
+                    serde_json::to_writer(&mut self.out, row)?;
+                    writeln!(self.out)?;
+                } else {
+                    let separator = if self.rows == 0 { "\n  " } else { ",\n  " };
+                    write!(self.out, "{separator}")?;
+                    serde_json::to_writer(&mut self.out, row)?;
+                }
+            }
+            ExportFormat::Markdown => writeln!(
+                self.out,
+                "| {} | {} | {} | {} | {} | {} |",
+                row.rank,
+                markdown_cell(&row.file),
+                line,
+                row.offset,
+                row.score,
+                markdown_cell(&row.snippet)
+            )?,
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Write the footer, flush, and report the rows written
+    ///
+    /// Prints "Wrote N row(s) to PATH" on stderr unless `--quiet`.
+    pub fn finish(mut self) -> io::Result<usize> {
+        if self.target.format == ExportFormat::Json && !self.target.ndjson {
+            let close = if self.rows == 0 { "]\n" } else { "\n]\n" };
+            write!(self.out, "{close}")?;
+        }
+        self.out.flush()?;
+        if !is_quiet() {
+            eprintln!(
+                "Wrote {} row(s) to {}",
+                self.rows,
+                self.target.path.display()
+            );
+        }
+        Ok(self.rows)
+    }
+}
+
+/// Quote a CSV field if it contains a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Keep a value on one table row: escape pipes, break lines with `<br>`
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(output: &str) -> ExportArgs {
+        ExportArgs {
+            output: Some(PathBuf::from(output)),
+            ..ExportArgs::default()
+        }
+    }
+
+    #[test]
+    fn test_target_infers_format_from_extension() {
+        let format = |output: &str| {
+            let target = args(output).target().unwrap().unwrap();
+            (target.format, target.ndjson)
+        };
+        assert_eq!(format("report.csv"), (ExportFormat::Csv, false));
+        assert_eq!(format("report.JSON"), (ExportFormat::Json, false));
+        assert_eq!(format("report.jsonl"), (ExportFormat::Json, true));
+        assert_eq!(format("report.md"), (ExportFormat::Markdown, false));
+        assert!(args("report.txt").target().is_err());
+        assert!(ExportArgs::default().target().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_target_explicit_format_and_ndjson() {
+        let mut explicit = args("report.txt");
+        explicit.output_format = Some(ExportFormat::Json);
+        explicit.ndjson = true;
+        let target = explicit.target().unwrap().unwrap();
+        assert_eq!((target.format, target.ndjson), (ExportFormat::Json, true));
+
+        let mut mismatched = args("report.csv");
+        mismatched.ndjson = true;
+        assert!(mismatched.target().is_err());
+    }
+
+    #[test]
+    fn test_csv_and_markdown_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\"\nnext"), "\"say \"\"hi\"\"\nnext\"");
+        assert_eq!(markdown_cell("a | b\nc"), "a \\| b<br>c");
+    }
+}
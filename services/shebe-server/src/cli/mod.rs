@@ -21,9 +21,18 @@
 //! ```
 
 pub mod commands;
+pub mod defaults;
+pub mod error;
+pub mod export;
 pub mod output;
 
-use clap::{Parser, Subcommand};
+pub use defaults::{AppliedDefault, CliDefaults};
+pub use error::{error_exit_code, exit_code, is_broken_pipe, CliError};
+
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
 
 /// Shebe - BM25 Code Search Engine
 ///
@@ -40,23 +49,66 @@ pub struct Cli {
     #[arg(long, global = true, default_value = "human")]
     pub format: OutputFormat,
 
+    /// Suppress warnings and progress (results and JSON are still printed)
+    #[arg(long, short = 'q', global = true)]
+    pub quiet: bool,
+
+    /// Storage profile from the config file ([profiles.<name>]; default: SHEBE_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Print details: the CLI defaults used for options left out, and
+    /// skipped files of index-repository
+    #[arg(long, short = 'v', global = true)]
+    pub verbose: bool,
+
+    /// Stored defaults used for options left out (see [`Cli::parse_with_defaults`])
+    #[arg(skip)]
+    pub applied_defaults: Vec<AppliedDefault>,
+
+    /// Whether `--format` was given on the command line rather than
+    /// taken from a default
+    #[arg(skip)]
+    pub format_given: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Cli {
+    /// Parse `args`, taking options left out from `defaults`
+    ///
+    /// Options given in `args` always win over the stored defaults.
+    ///
+    /// # Errors
+    ///
+    /// The clap error for invalid arguments (exit it with
+    /// [`clap::Error::exit`]).
+    pub fn parse_with_defaults<I, T>(args: I, defaults: &CliDefaults) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = defaults.apply(Self::command()).try_get_matches_from(args)?;
+        let mut cli = Self::from_arg_matches(&matches)?;
+        cli.applied_defaults = defaults.applied(&matches);
+        cli.format_given = matches.value_source("format") == Some(ValueSource::CommandLine);
+        Ok(cli)
+    }
+}
+
 /// Output format for CLI commands
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Human-readable output (default)
+    #[default]
     Human,
     /// JSON output for scripting
     Json,
-}
-
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Human
-    }
+    /// One JSON event per line as results arrive (search-code,
+    /// find-references and index-repository)
+    Ndjson,
 }
 
 /// Available CLI commands
@@ -72,6 +124,9 @@ pub enum Commands {
     #[command(name = "search-code")]
     SearchCode(commands::SearchArgs),
 
+    /// Interactive search loop over a session
+    Repl(commands::ReplArgs),
+
     /// Find all references to a symbol across the indexed codebase
     #[command(name = "find-references")]
     FindReferences(commands::ReferencesArgs),
@@ -88,18 +143,69 @@ pub enum Commands {
     #[command(name = "delete-session")]
     DeleteSession(commands::session::DeleteArgs),
 
+    /// Name a group of sessions to search together
+    #[command(name = "create-collection")]
+    CreateCollection(commands::collection::CreateArgs),
+
+    /// Add sessions to or remove sessions from a collection
+    #[command(name = "update-collection")]
+    UpdateCollection(commands::collection::UpdateArgs),
+
+    /// Delete a collection, leaving its sessions alone
+    #[command(name = "delete-collection")]
+    DeleteCollection(commands::collection::DeleteArgs),
+
     /// Re-index a session using stored repository path
     #[command(name = "reindex-session")]
     ReindexSession(commands::session::ReindexArgs),
 
+    /// Merge index segments and reclaim disk space
+    #[command(name = "compact-session")]
+    CompactSession(commands::session::CompactArgs),
+
+    /// Merge index segments down to a target count and clean up (idle maintenance, now)
+    #[command(name = "optimize-session")]
+    OptimizeSession(commands::session::OptimizeArgs),
+
+    /// Set the search options a session uses when a search leaves them unset
+    #[command(name = "set-session-defaults")]
+    SetSessionDefaults(commands::session::SetDefaultsArgs),
+
+    /// Show files added, removed or changed between two sessions
+    #[command(name = "diff-sessions")]
+    DiffSessions(commands::DiffArgs),
+
+    /// Compare search results between two chunking configurations
+    #[command(name = "compare-configs")]
+    CompareConfigs(commands::CompareArgs),
+
+    /// Show disk usage of all sessions, largest first
+    #[command(name = "get-storage-report")]
+    GetStorageReport(commands::StorageReportArgs),
+
+    /// Show indexed files as a directory tree with counts
+    #[command(name = "list-tree")]
+    ListTree(commands::TreeArgs),
+
     /// Show current configuration
     #[command(name = "show-config")]
     ShowConfig(commands::ConfigArgs),
 
+    /// Write per-user CLI defaults (session, format, k, profile)
+    Init(commands::InitArgs),
+
+    /// Get or set one of the per-user CLI defaults
+    #[command(subcommand)]
+    Config(commands::config::DefaultsCommand),
+
     /// Show version and server information
     #[command(name = "get-server-info")]
     GetServerInfo(commands::InfoArgs),
 
+    /// Index, search and read a built-in corpus in a scratch directory
+    #[command(name = "self-test")]
+    SelfTest(commands::SelfTestArgs),
+
     /// Generate shell completion scripts
     ///
     /// Output completion script to stdout. To install:
@@ -111,38 +217,85 @@ pub enum Commands {
 }
 
 /// Run the CLI with the provided arguments
+///
+/// On failure, [`error_exit_code`] gives the process exit code.
 pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     use crate::core::config::Config;
     use crate::core::services::Services;
     use crate::core::xdg::{migrate_legacy_paths, XdgDirs};
     use std::sync::Arc;
 
+    output::set_quiet(cli.quiet);
+
     // Handle completions command early (doesn't need services)
     if let Commands::Completions(args) = cli.command {
         return commands::completions::execute(args);
     }
 
+    if cli.format == OutputFormat::Ndjson
+        && !matches!(
+            cli.command,
+            Commands::IndexRepository(_) | Commands::SearchCode(_) | Commands::FindReferences(_)
+        )
+    {
+        return Err(CliError::InvalidArgs(
+            "--format ndjson is only supported by search-code, find-references \
+             and index-repository"
+                .into(),
+        )
+        .into());
+    }
+
     // Initialize XDG directories
     let xdg = XdgDirs::new();
     xdg.ensure_dirs_exist()?;
 
+    if cli.verbose && !cli.quiet {
+        for applied in &cli.applied_defaults {
+            eprintln!(
+                "{}",
+                output::colors::dim(&format!(
+                    "Using default {} '{}' from {}",
+                    applied.key,
+                    applied.value,
+                    xdg.cli_defaults_file().display()
+                ))
+            );
+        }
+    }
+
+    // CLI defaults are managed before loading the configuration, which
+    // a stored profile that no longer exists would fail
+    match cli.command {
+        Commands::Init(args) => {
+            let format = cli.format_given.then_some(cli.format);
+            return commands::init::execute(args, &xdg, cli.profile.as_deref(), format, cli.format);
+        }
+        Commands::Config(command) => {
+            return commands::config::execute_defaults(command, &xdg, cli.format);
+        }
+        _ => {}
+    }
+
     // Run migration from legacy paths (if needed)
     if let Err(e) = migrate_legacy_paths(&xdg) {
         output::print_warning(&format!("Migration issue: {e}"));
     }
 
     // Load configuration
-    let config = Config::load()?;
+    let config = Config::load_with_profile(&xdg, cli.profile.as_deref())?;
 
     // Create services
     let services = Arc::new(Services::new(config));
 
     // Execute command
     match cli.command {
-        Commands::IndexRepository(args) => {
+        Commands::IndexRepository(mut args) => {
+            args.verbose = cli.verbose;
             commands::index::execute(args, &services, cli.format).await
         }
         Commands::SearchCode(args) => commands::search::execute(args, &services, cli.format).await,
+        Commands::Repl(args) => commands::repl::execute(args, &services, cli.format).await,
         Commands::FindReferences(args) => {
             commands::references::execute(args, &services, cli.format).await
         }
@@ -155,11 +308,40 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Commands::DeleteSession(args) => {
             commands::session::execute_delete(args, &services, cli.format).await
         }
+        Commands::CreateCollection(args) => {
+            commands::collection::execute_create(args, &services, cli.format).await
+        }
+        Commands::UpdateCollection(args) => {
+            commands::collection::execute_update(args, &services, cli.format).await
+        }
+        Commands::DeleteCollection(args) => {
+            commands::collection::execute_delete(args, &services, cli.format).await
+        }
         Commands::ReindexSession(args) => {
             commands::session::execute_reindex(args, &services, cli.format).await
         }
+        Commands::CompactSession(args) => {
+            commands::session::execute_compact(args, &services, cli.format).await
+        }
+        Commands::OptimizeSession(args) => {
+            commands::session::execute_optimize(args, &services, cli.format).await
+        }
+        Commands::SetSessionDefaults(args) => {
+            commands::session::execute_set_defaults(args, &services, cli.format).await
+        }
+        Commands::DiffSessions(args) => commands::diff::execute(args, &services, cli.format).await,
+        Commands::CompareConfigs(args) => {
+            commands::compare::execute(args, &services, cli.format).await
+        }
+        Commands::GetStorageReport(args) => {
+            commands::storage::execute(args, &services, cli.format).await
+        }
+        Commands::ListTree(args) => commands::tree::execute(args, &services, cli.format).await,
         Commands::ShowConfig(args) => commands::config::execute(args, &services, cli.format).await,
         Commands::GetServerInfo(args) => commands::info::execute(args, &services, cli.format).await,
-        Commands::Completions(_) => unreachable!(), // Handled above
+        Commands::SelfTest(args) => commands::self_test::execute(args, &services, cli.format).await,
+        Commands::Completions(_) | Commands::Init(_) | Commands::Config(_) => {
+            unreachable!() // Handled above
+        }
     }
 }
@@ -1,9 +1,29 @@
 //! Output formatting for CLI commands
 //!
-//! Provides utilities for formatting command output in human-readable
-//! or JSON formats. Supports colored output (respects NO_COLOR env var).
+//! Provides utilities for formatting command output in human-readable,
+//! JSON or NDJSON formats. Supports colored output (respects NO_COLOR env var).
 
 use crate::cli::OutputFormat;
+use crate::core::format::format_time_ago_short;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub use crate::core::format::{format_bytes, format_count, format_timestamp, format_timing};
+
+/// Set by the global `--quiet` flag
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress warnings and progress messages for the rest of the run
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether warnings and progress messages are suppressed
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
 
 /// Color scheme for CLI output
 pub mod colors {
@@ -60,44 +80,14 @@ pub mod colors {
     }
 }
 
-/// Format bytes into human-readable size
-pub fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    let gb_val = bytes as f64 / GB as f64;
-    let mb_val = bytes as f64 / MB as f64;
-    let kb_val = bytes as f64 / KB as f64;
-
-    if bytes >= GB {
-        format!("{gb_val:.1} GB")
-    } else if bytes >= MB {
-        format!("{mb_val:.1} MB")
-    } else if bytes >= KB {
-        format!("{kb_val:.1} KB")
-    } else {
-        format!("{bytes} B")
-    }
-}
-
 /// Format bytes with color
 pub fn format_bytes_colored(bytes: u64) -> String {
     format!("{}", colors::number(&format_bytes(bytes)))
 }
 
-/// Format duration into human-readable string
+/// Format a duration given in seconds, e.g. "850ms" or "2m 5.5s"
 pub fn format_duration(secs: f64) -> String {
-    if secs >= 60.0 {
-        let mins = (secs / 60.0).floor();
-        let remaining_secs = secs - (mins * 60.0);
-        format!("{mins:.0}m {remaining_secs:.1}s")
-    } else if secs >= 1.0 {
-        format!("{secs:.2}s")
-    } else {
-        let ms = secs * 1000.0;
-        format!("{ms:.0}ms")
-    }
+    crate::core::format::format_duration(Duration::from_secs_f64(secs.max(0.0)))
 }
 
 /// Format duration with color
@@ -107,27 +97,7 @@ pub fn format_duration_colored(secs: f64) -> String {
 
 /// Format relative time (e.g., "2h ago", "3d ago")
 pub fn format_relative_time(timestamp: &chrono::DateTime<chrono::Utc>) -> String {
-    let now = chrono::Utc::now();
-    let duration = now.signed_duration_since(*timestamp);
-
-    let secs = duration.num_seconds();
-    if secs < 0 {
-        return "in the future".to_string();
-    }
-
-    let mins = duration.num_minutes();
-    let hours = duration.num_hours();
-    let days = duration.num_days();
-
-    if days > 0 {
-        format!("{days}d ago")
-    } else if hours > 0 {
-        format!("{hours}h ago")
-    } else if mins > 0 {
-        format!("{mins}m ago")
-    } else {
-        "just now".to_string()
-    }
+    format_time_ago_short(*timestamp)
 }
 
 /// Format relative time with color (dim for older items)
@@ -145,7 +115,7 @@ pub fn print_output<T: serde::Serialize>(data: &T, format: OutputFormat) {
                 println!("{json}");
             }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Ndjson => {
             if let Ok(json) = serde_json::to_string_pretty(data) {
                 println!("{json}");
             }
@@ -153,13 +123,149 @@ pub fn print_output<T: serde::Serialize>(data: &T, format: OutputFormat) {
     }
 }
 
+/// One line of `--format ndjson` output
+///
+/// The `type` field names the event. Fields are only ever added, so a
+/// consumer can ignore the ones it does not know.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A search-code hit
+    Result(ResultEvent),
+    /// A find-references match
+    Reference(ReferenceEvent),
+    /// index-repository progress
+    Progress(ProgressEvent),
+    /// Last line of every stream
+    Summary(SummaryEvent),
+}
+
+/// A search-code hit (the best chunk of each file with --group-by-file)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultEvent {
+    pub rank: usize,
+    pub file: String,
+    /// Line of the first match, when the file could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub chunk_index: usize,
+    pub score: f32,
+    /// Chunk text (omitted with --files-only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Why there is no text (sessions that do not store chunk text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_unavailable: Option<String>,
+}
+
+/// A find-references match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceEvent {
+    pub symbol: String,
+    pub file: String,
+    /// 1-based line number (0 if the file could not be read)
+    pub line: usize,
+    /// 1-based column, counted in characters (0 if unknown)
+    pub column: usize,
+    pub confidence: f32,
+    pub pattern: String,
+    pub is_definition: bool,
+    pub context: String,
+    /// IO error kind if the file could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_unavailable: Option<String>,
+}
+
+/// index-repository progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressEvent {
+    pub files_processed: usize,
+    /// Files the run will process (0 until the walk finishes)
+    pub files_total: usize,
+}
+
+/// Last line of an NDJSON stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummaryEvent {
+    /// Command that produced the stream (e.g. "search-code")
+    pub command: String,
+    pub session: String,
+    /// Result, reference or progress lines written before this one
+    pub events: usize,
+    pub duration_ms: u64,
+    /// Files indexed (index-repository only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_indexed: Option<usize>,
+    /// Chunks created (index-repository only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks_created: Option<usize>,
+}
+
+impl SummaryEvent {
+    /// Summary of a `command` run against `session` that took `elapsed`
+    pub fn new(command: &str, session: &str, elapsed: Duration) -> Self {
+        Self {
+            command: command.to_string(),
+            session: session.to_string(),
+            events: 0,
+            duration_ms: elapsed.as_millis() as u64,
+            files_indexed: None,
+            chunks_created: None,
+        }
+    }
+}
+
+/// Writes `--format ndjson` events to stdout
+///
+/// Each event is written as one complete line with a single write and
+/// then flushed, so a consumer reading the pipe never sees a partial
+/// line. Diagnostics stay on stderr.
+#[derive(Debug, Default)]
+pub struct NdjsonWriter {
+    events: usize,
+}
+
+impl NdjsonWriter {
+    /// Writer that has not written anything yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Events written so far (the summary excluded)
+    pub fn events(&self) -> usize {
+        self.events
+    }
+
+    /// Write one event line
+    pub fn emit(&mut self, event: &Event) -> io::Result<()> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(line.as_bytes())?;
+        stdout.flush()?;
+        if !matches!(event, Event::Summary(_)) {
+            self.events += 1;
+        }
+        Ok(())
+    }
+
+    /// Write the closing summary, counting the events written before it
+    pub fn finish(mut self, mut summary: SummaryEvent) -> io::Result<()> {
+        summary.events = self.events;
+        self.emit(&Event::Summary(summary))
+    }
+}
+
 /// Print a success message
 pub fn print_success(message: &str) {
     println!("{}", colors::success(message));
 }
 
-/// Print a warning message
+/// Print a warning message (unless `--quiet`)
 pub fn print_warning(message: &str) {
+    if is_quiet() {
+        return;
+    }
     eprintln!("{}: {}", colors::warning("Warning"), message);
 }
 
@@ -173,6 +279,21 @@ pub fn print_header(title: &str) {
     println!("{}", colors::label(title));
 }
 
+/// Print renamed files as `old -> new` lines, then how many of `total`
+/// were not listed
+pub fn print_renames(renames: &[crate::core::types::FileRename], total: usize, indent: &str) {
+    for rename in renames {
+        println!(
+            "{indent}{} -> {}",
+            colors::file_path(&rename.from),
+            colors::file_path(&rename.to)
+        );
+    }
+    if total > renames.len() {
+        println!("{indent}... and {} more", total - renames.len());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +313,58 @@ mod tests {
         assert_eq!(format_duration(1.5), "1.50s");
         assert_eq!(format_duration(65.5), "1m 5.5s");
     }
+
+    #[test]
+    fn test_event_json_shape() {
+        let event = Event::Result(ResultEvent {
+            rank: 1,
+            file: "src/lib.rs".to_string(),
+            line: Some(3),
+            chunk_index: 0,
+            score: 1.5,
+            text: None,
+            text_unavailable: None,
+        });
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"result","rank":1,"file":"src/lib.rs","line":3,"chunk_index":0,"score":1.5}"#
+        );
+
+        let event = Event::Progress(ProgressEvent {
+            files_processed: 100,
+            files_total: 250,
+        });
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"progress","files_processed":100,"files_total":250}"#
+        );
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let mut summary = SummaryEvent::new("index-repository", "demo", Duration::from_millis(42));
+        summary.events = 2;
+        summary.files_indexed = Some(10);
+        summary.chunks_created = Some(31);
+        let events = [
+            Event::Reference(ReferenceEvent {
+                symbol: "authenticate".to_string(),
+                file: "src/auth.rs".to_string(),
+                line: 12,
+                column: 8,
+                confidence: 0.95,
+                pattern: "function_call".to_string(),
+                is_definition: false,
+                context: "let ok = authenticate(user);".to_string(),
+                context_unavailable: None,
+            }),
+            Event::Summary(summary),
+        ];
+        for event in events {
+            let line = serde_json::to_string(&event).unwrap();
+            assert!(!line.contains('\n'));
+            let parsed: Event = serde_json::from_str(&line).unwrap();
+            assert_eq!(parsed, event);
+        }
+    }
 }
@@ -0,0 +1,210 @@
+//! Directories that tools may read files from
+//!
+//! Tools that show file contents check that the file is indexed in the
+//! session, but a session indexed from a broad root, or a crafted
+//! `meta.json`, could still point them at any readable file. As a
+//! second line of defense every such tool authorizes the path against
+//! [`AllowedRoots`] before touching the file:
+//!
+//! - `security.allowed_roots` when configured, otherwise the repository
+//!   paths of all sessions
+//! - Paths and roots are canonicalized, so symlinks are judged by their
+//!   targets
+//! - A refused path gets the same error whether or not it exists
+//!
+//! Paths that do not exist are judged by their nearest existing
+//! ancestor, so a missing file under an allowed root still reports
+//! "not found" while one outside it is refused.
+
+use crate::core::config::SecurityConfig;
+use crate::core::error::{Result, ShebeError};
+use crate::core::storage::StorageManager;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+/// Where the allowed roots come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RootsSource {
+    /// `security.allowed_roots`
+    Configured,
+    /// Repository paths of the stored sessions
+    Sessions,
+}
+
+impl RootsSource {
+    /// Short description for tool output
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Configured => "security.allowed_roots",
+            Self::Sessions => "session repository paths (security.allowed_roots unset)",
+        }
+    }
+}
+
+/// Canonical directories (or archive files) files may be read under
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowedRoots {
+    roots: Vec<PathBuf>,
+    source: RootsSource,
+}
+
+impl AllowedRoots {
+    /// Allow reads under `roots`, canonicalized where they exist
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>, source: RootsSource) -> Self {
+        let mut roots: Vec<PathBuf> = roots.into_iter().map(|root| canonical(&root)).collect();
+        roots.sort();
+        roots.dedup();
+        Self { roots, source }
+    }
+
+    /// Effective roots: configured ones, or those of every session
+    ///
+    /// # Errors
+    ///
+    /// Storage errors from listing the sessions.
+    pub fn resolve(config: &SecurityConfig, storage: &StorageManager) -> Result<Self> {
+        if !config.allowed_roots.is_empty() {
+            return Ok(Self::new(
+                config.allowed_roots.iter().cloned(),
+                RootsSource::Configured,
+            ));
+        }
+        let roots = storage
+            .list_sessions()?
+            .into_iter()
+            .map(|metadata| metadata.repository_path)
+            // A relative path stored by an older version would resolve
+            // against the working directory (upgrade_session fixes it)
+            .filter(|path| path.is_absolute());
+        Ok(Self::new(roots, RootsSource::Sessions))
+    }
+
+    /// Canonical roots, sorted
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Where the roots come from
+    pub fn source(&self) -> RootsSource {
+        self.source
+    }
+
+    /// Canonical form of `path` if it falls under an allowed root
+    ///
+    /// # Errors
+    ///
+    /// `AccessDenied` if `path` is relative or resolves (following
+    /// symlinks) outside every root. The message does not say whether
+    /// the path exists.
+    pub fn authorize(&self, path: &Path) -> Result<PathBuf> {
+        let denied = || {
+            ShebeError::AccessDenied(format!(
+                "{} is not under {}",
+                path.display(),
+                self.source.describe()
+            ))
+        };
+        if !path.is_absolute() {
+            return Err(denied());
+        }
+        let resolved = canonical(path);
+        if self.roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(denied())
+        }
+    }
+}
+
+/// `path` with symlinks resolved, as far as it exists
+///
+/// The missing tail is joined to the canonical form of the nearest
+/// existing ancestor. Missing directories cannot be symlinks, so `..`
+/// in the tail is resolved lexically.
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    for ancestor in path.ancestors().skip(1) {
+        let Ok(mut resolved) = ancestor.canonicalize() else {
+            continue;
+        };
+        for component in path.strip_prefix(ancestor).unwrap_or(path).components() {
+            match component {
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::Normal(name) => resolved.push(name),
+                _ => {}
+            }
+        }
+        return resolved;
+    }
+    path.to_path_buf()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_authorize_under_root() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join("src/lib.rs"), "pub fn a() {}").unwrap();
+        let roots = AllowedRoots::new([repo.clone()], RootsSource::Configured);
+
+        let resolved = roots.authorize(&repo.join("src/lib.rs")).unwrap();
+        assert_eq!(resolved, repo.canonicalize().unwrap().join("src/lib.rs"));
+        // Missing files under a root are left for the caller to report
+        assert!(roots.authorize(&repo.join("src/gone.rs")).is_ok());
+    }
+
+    #[test]
+    fn test_authorize_refuses_outside_paths_alike() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(temp.path().join("secret.txt"), "token").unwrap();
+        let roots = AllowedRoots::new([repo.clone()], RootsSource::Sessions);
+
+        let existing = roots
+            .authorize(&temp.path().join("secret.txt"))
+            .unwrap_err();
+        let missing = roots
+            .authorize(&temp.path().join("nothing/here"))
+            .unwrap_err();
+        assert!(matches!(existing, ShebeError::AccessDenied(_)));
+        assert!(matches!(missing, ShebeError::AccessDenied(_)));
+        assert!(existing.to_string().ends_with(
+            "secret.txt is not under session repository paths (security.allowed_roots unset)"
+        ));
+
+        for escape in [
+            repo.join("../secret.txt"),
+            repo.join("gone/../../secret.txt"),
+        ] {
+            assert!(roots.authorize(&escape).is_err(), "{}", escape.display());
+        }
+        assert!(roots.authorize(Path::new("secret.txt")).is_err());
+    }
+
+    #[test]
+    fn test_authorize_follows_symlinks() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(temp.path().join("secret.txt"), "token").unwrap();
+        fs::write(repo.join("real.rs"), "fn a() {}").unwrap();
+        std::os::unix::fs::symlink(temp.path().join("secret.txt"), repo.join("link.rs")).unwrap();
+        std::os::unix::fs::symlink(repo.join("real.rs"), repo.join("alias.rs")).unwrap();
+        let roots = AllowedRoots::new([repo.clone()], RootsSource::Configured);
+
+        assert!(roots.authorize(&repo.join("link.rs")).is_err());
+        assert!(roots.authorize(&repo.join("alias.rs")).is_ok());
+    }
+}
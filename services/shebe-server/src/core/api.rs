@@ -0,0 +1,597 @@
+//! Library API for embedding Shebe.
+//!
+//! [`Shebe`] wraps [`Services`] with builder-style entry points, so
+//! callers set only the options they care about and keep compiling
+//! when new options are added:
+//!
+//! ```
+//! use shebe::{Config, Shebe};
+//! # let temp = tempfile::TempDir::new().unwrap();
+//! # let repo = temp.path().join("repo");
+//! # std::fs::create_dir_all(&repo).unwrap();
+//! # std::fs::write(repo.join("main.rs"), "fn main() { start_server(); }").unwrap();
+//!
+//! let mut config = Config::default();
+//! config.storage.index_dir = temp.path().join("sessions");
+//! let shebe = Shebe::new(config);
+//!
+//! let outcome = shebe.index(&repo).session("app").chunk_size(800).run()?;
+//! assert_eq!(outcome.stats.files_indexed, 1);
+//!
+//! let response = shebe.search("start_server").session("app").k(5).run()?;
+//! assert_eq!(response.results[0].file_path, repo.join("main.rs").to_string_lossy());
+//! # Ok::<(), shebe::ShebeError>(())
+//! ```
+//!
+//! The CLI and MCP adapters index through the same [`IndexBuilder`].
+
+use crate::core::config::Config;
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::repo_config::RepoFileWarning;
+use crate::core::indexer::{
+    check_index_path, resolve_session_config, BoundaryHint, DryRunReport, IndexOverrides,
+    IndexProgress, ResolvedConfig,
+};
+use crate::core::services::Services;
+use crate::core::storage::{SessionConfig, SessionMetadata};
+use crate::core::types::{IndexStats, SearchRequest, SearchResponse, SearchWithin};
+use std::path::{Path, PathBuf};
+
+/// Entry point for using Shebe as a library
+#[derive(Clone)]
+pub struct Shebe {
+    services: Services,
+}
+
+impl Shebe {
+    /// Create an instance from configuration
+    pub fn new(config: Config) -> Self {
+        Self::from(Services::new(config))
+    }
+
+    /// Underlying services, for operations without a builder
+    pub fn services(&self) -> &Services {
+        &self.services
+    }
+
+    /// Start indexing the repository at `path` (see [`IndexBuilder`])
+    pub fn index(&self, path: impl AsRef<Path>) -> IndexBuilder<'_> {
+        self.services.index(path)
+    }
+
+    /// Start a search (see [`SearchBuilder`])
+    pub fn search(&self, query: impl Into<String>) -> SearchBuilder<'_> {
+        SearchBuilder::new(&self.services, query.into())
+    }
+
+    /// Metadata of every session, sorted by ID
+    pub fn sessions(&self) -> Result<Vec<SessionMetadata>> {
+        self.services.storage.list_sessions()
+    }
+
+    /// Delete a session and its index
+    pub fn delete_session(&self, session: &str) -> Result<()> {
+        self.services.storage.delete_session(session)
+    }
+}
+
+impl From<Services> for Shebe {
+    fn from(services: Services) -> Self {
+        Self { services }
+    }
+}
+
+/// Builder for indexing a repository into a session
+///
+/// Created by [`Shebe::index`] or [`Services::index`]. Each setting is
+/// taken from the builder if set, then from the repository's
+/// `.shebe.toml`, then from the defaults (the `indexing`
+/// configuration unless [`IndexBuilder::defaults`] replaces them).
+/// The repository's `.shebeignore` always applies.
+///
+/// The filesystem root, the home directory and the storage root are
+/// refused unless [`IndexBuilder::allow_dangerous_path`] is set.
+#[must_use = "an IndexBuilder does nothing until run"]
+pub struct IndexBuilder<'a> {
+    services: &'a Services,
+    path: PathBuf,
+    session: Option<String>,
+    overrides: IndexOverrides,
+    defaults: Option<SessionConfig>,
+    force: bool,
+    allow_dangerous_path: bool,
+    files: Option<Vec<PathBuf>>,
+    progress: Option<&'a IndexProgress>,
+    profile: bool,
+}
+
+impl<'a> IndexBuilder<'a> {
+    pub(crate) fn new(services: &'a Services, path: PathBuf) -> Self {
+        Self {
+            services,
+            path,
+            session: None,
+            overrides: IndexOverrides::default(),
+            defaults: None,
+            force: false,
+            allow_dangerous_path: false,
+            files: None,
+            progress: None,
+            profile: false,
+        }
+    }
+
+    /// Session ID to create (required by [`IndexBuilder::run`])
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Characters per chunk
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.overrides.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Characters shared by consecutive chunks (less than `chunk_size`)
+    pub fn overlap(mut self, overlap: usize) -> Self {
+        self.overrides.overlap = Some(overlap);
+        self
+    }
+
+    /// Glob patterns for files to include
+    pub fn include_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overrides.include_patterns = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Glob patterns for files to exclude
+    pub fn exclude_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overrides.exclude_patterns = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Index only the first of several files with identical content
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.overrides.dedupe = dedupe;
+        self
+    }
+
+    /// Align chunk ends to blank lines or line breaks
+    pub fn boundary_hint(mut self, boundary_hint: BoundaryHint) -> Self {
+        self.overrides.boundary_hint = Some(boundary_hint);
+        self
+    }
+
+    /// Store chunk text in the index (default) or read it from the
+    /// files at query time
+    pub fn store_chunk_text(mut self, store_chunk_text: bool) -> Self {
+        self.overrides.store_chunk_text = Some(store_chunk_text);
+        self
+    }
+
+    /// Replace long base64/minified blobs with placeholders before
+    /// chunking
+    pub fn strip_high_entropy(mut self, strip_high_entropy: bool) -> Self {
+        self.overrides.strip_high_entropy = Some(strip_high_entropy);
+        self
+    }
+
+    /// Length above which non-whitespace runs are stripped (default 200)
+    pub fn strip_run_chars(mut self, strip_run_chars: usize) -> Self {
+        self.overrides.strip_run_chars = Some(strip_run_chars);
+        self
+    }
+
+    /// Merge a file's short final chunk into the one before it
+    pub fn merge_tail_fragments(mut self, merge_tail_fragments: bool) -> Self {
+        self.overrides.merge_tail_fragments = Some(merge_tail_fragments);
+        self
+    }
+
+    /// Chunk CSV, TSV and JSON Lines files from whole records (default
+    /// true)
+    pub fn record_chunking(mut self, record_chunking: bool) -> Self {
+        self.overrides.record_chunking = Some(record_chunking);
+        self
+    }
+
+    /// Chunks indexed per file before the rest is left out (default
+    /// 2000)
+    pub fn max_chunks_per_file(mut self, max_chunks_per_file: usize) -> Self {
+        self.overrides.max_chunks_per_file = Some(max_chunks_per_file);
+        self
+    }
+
+    /// Fail instead of warning when the include patterns match none of
+    /// the repository's files
+    pub fn strict_patterns(mut self, strict_patterns: bool) -> Self {
+        self.overrides.strict_patterns = Some(strict_patterns);
+        self
+    }
+
+    /// Take files' last change from git history (default: the
+    /// `[indexing] git_metadata` setting)
+    pub fn git_metadata(mut self, git_metadata: bool) -> Self {
+        self.overrides.git_metadata = Some(git_metadata);
+        self
+    }
+
+    /// Replace the session if it already exists
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Index the path even if it is the filesystem root, the home
+    /// directory or the storage root
+    pub fn allow_dangerous_path(mut self, allow: bool) -> Self {
+        self.allow_dangerous_path = allow;
+        self
+    }
+
+    /// Index exactly these files (absolute or relative to the path)
+    /// instead of walking the repository
+    ///
+    /// Include, exclude and `.shebeignore` patterns are not applied to
+    /// listed files; the size limit and `[limits]` are.
+    pub fn files<I, P>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.files = Some(files.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Report progress to (and honour cancellation from) `progress`
+    pub fn progress(mut self, progress: &'a IndexProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Time each indexing stage and report it in the stats' `profile`
+    ///
+    /// Off by default; an unprofiled run takes no timings.
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Replace all explicit settings at once
+    pub fn overrides(mut self, overrides: IndexOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Settings used where neither the builder nor `.shebe.toml` set one
+    pub fn defaults(mut self, defaults: SessionConfig) -> Self {
+        self.defaults = Some(defaults);
+        self
+    }
+
+    /// Resolve the settings this builder would index with
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if the resolved `overlap >= chunk_size`.
+    pub fn resolve(&self) -> Result<ResolvedConfig> {
+        let defaults = self
+            .defaults
+            .clone()
+            .unwrap_or_else(|| self.services.default_session_config());
+        let resolved = resolve_session_config(&self.path, self.overrides.clone(), defaults);
+        resolved.config.validate()?;
+        Ok(resolved)
+    }
+
+    /// Summarize what [`IndexBuilder::run`] would index, without indexing
+    pub fn dry_run(&self) -> Result<DryRunReport> {
+        let resolved = self.resolve()?;
+        self.services.dry_run_index(&self.path, &resolved.config)
+    }
+
+    /// Index the repository
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if no session ID was set, `InvalidPath` for a
+    /// dangerous path (see [`IndexBuilder::allow_dangerous_path`]),
+    /// `SessionAlreadyExists` without [`IndexBuilder::force`],
+    /// `IndexTooLarge` if the walk exceeds the `[limits]`, `InvalidPath`
+    /// for a bad entry in [`IndexBuilder::files`], and any
+    /// error from resolving the settings or indexing.
+    pub fn run(self) -> Result<IndexOutcome> {
+        let resolved = self.resolve()?;
+        self.run_resolved(resolved)
+    }
+
+    /// Index with settings already returned by [`IndexBuilder::resolve`]
+    ///
+    /// Lets callers report warnings or stop before indexing without
+    /// reading the repository's settings files twice.
+    pub fn run_resolved(self, resolved: ResolvedConfig) -> Result<IndexOutcome> {
+        let session = self.session.ok_or_else(|| {
+            ShebeError::InvalidSession("no session ID given for indexing".to_string())
+        })?;
+        check_index_path(
+            &self.path,
+            self.services.storage.storage_root(),
+            self.allow_dangerous_path,
+        )?;
+
+        let storage = &self.services.storage;
+        let max_file_size_mb = self.services.config.current().indexing.max_file_size_mb;
+        // A profiled run needs progress to carry its profiler
+        let own_progress;
+        let progress = match (self.progress, self.profile) {
+            (None, true) => {
+                own_progress = IndexProgress::new();
+                Some(&own_progress)
+            }
+            (progress, _) => progress,
+        };
+        if let Some(progress) = progress.filter(|_| self.profile) {
+            progress.enable_profiling();
+        }
+        let stats = match &self.files {
+            Some(files) => storage.index_files_with_progress(
+                &session,
+                &self.path,
+                files,
+                resolved.config.clone(),
+                max_file_size_mb,
+                self.force,
+                progress,
+            )?,
+            None => storage.index_repository_with_progress(
+                &session,
+                &self.path,
+                resolved.config.clone(),
+                max_file_size_mb,
+                self.force,
+                progress,
+            )?,
+        };
+
+        Ok(IndexOutcome {
+            stats,
+            config: resolved.config,
+            warnings: resolved.warnings,
+        })
+    }
+}
+
+/// Result of [`IndexBuilder::run`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct IndexOutcome {
+    /// Indexing statistics
+    pub stats: IndexStats,
+
+    /// Settings the session was indexed with
+    pub config: SessionConfig,
+
+    /// Lines skipped in `.shebe.toml` and `.shebeignore`
+    pub warnings: Vec<RepoFileWarning>,
+}
+
+/// Builder for a BM25 search, created by [`Shebe::search`]
+#[must_use = "a SearchBuilder does nothing until run"]
+pub struct SearchBuilder<'a> {
+    services: &'a Services,
+    request: SearchRequest,
+}
+
+impl<'a> SearchBuilder<'a> {
+    fn new(services: &'a Services, query: String) -> Self {
+        Self {
+            services,
+            request: SearchRequest {
+                query,
+                session: String::new(),
+                k: None,
+                group_by_file: false,
+                dedupe_overlaps: true,
+                path_boost: None,
+                fuzziness: None,
+                expand_identifiers: true,
+                bm25_k1: None,
+                bm25_b: None,
+                within: None,
+                penalize_short_chunks: None,
+                modified_within: None,
+                section_filter: None,
+                exclude_terms: Vec::new(),
+                exclude_paths: Vec::new(),
+                explain: false,
+            },
+        }
+    }
+
+    /// Session to search (required)
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.request.session = session.into();
+        self
+    }
+
+    /// Number of results (files when grouping), capped at `search.max_k`
+    pub fn k(mut self, k: usize) -> Self {
+        self.request.k = Some(k);
+        self
+    }
+
+    /// Collapse results to one entry per file
+    pub fn group_by_file(mut self, group_by_file: bool) -> Self {
+        self.request.group_by_file = group_by_file;
+        self
+    }
+
+    /// Hide hits that only repeat an adjacent chunk's overlap (default on)
+    pub fn dedupe_overlaps(mut self, dedupe_overlaps: bool) -> Self {
+        self.request.dedupe_overlaps = dedupe_overlaps;
+        self
+    }
+
+    /// Boost for query matches in the file path (0 disables)
+    pub fn path_boost(mut self, path_boost: f32) -> Self {
+        self.request.path_boost = Some(path_boost);
+        self
+    }
+
+    /// Also match terms within this edit distance (0-2)
+    pub fn fuzziness(mut self, fuzziness: u8) -> Self {
+        self.request.fuzziness = Some(fuzziness);
+        self
+    }
+
+    /// Also match other spellings of identifier terms (default on)
+    pub fn expand_identifiers(mut self, expand_identifiers: bool) -> Self {
+        self.request.expand_identifiers = expand_identifiers;
+        self
+    }
+
+    /// BM25 term frequency saturation, 0-3 (default: the session's)
+    pub fn bm25_k1(mut self, k1: f32) -> Self {
+        self.request.bm25_k1 = Some(k1);
+        self
+    }
+
+    /// BM25 length normalization, 0-1 (default: the session's)
+    pub fn bm25_b(mut self, b: f32) -> Self {
+        self.request.bm25_b = Some(b);
+        self
+    }
+
+    /// Demote chunks much shorter than the session's chunk size (default: the server's)
+    pub fn penalize_short_chunks(mut self, penalize: bool) -> Self {
+        self.request.penalize_short_chunks = Some(penalize);
+        self
+    }
+
+    /// Only search among the top `k` hits of `query` (default `k`: the search default)
+    pub fn within(mut self, query: impl Into<String>, k: Option<usize>) -> Self {
+        self.request.within = Some(SearchWithin {
+            query: query.into(),
+            k,
+        });
+        self
+    }
+
+    /// Only search files changed within `window` ("30d") or since a date ("2026-09-01")
+    pub fn modified_within(mut self, window: impl Into<String>) -> Self {
+        self.request.modified_within = Some(window.into());
+        self
+    }
+
+    /// Break down the scores of the top results per query term (default: off)
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.request.explain = explain;
+        self
+    }
+
+    /// Run the search
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if no session was set, plus any search error.
+    pub fn run(self) -> Result<SearchResponse> {
+        if self.request.session.is_empty() {
+            return Err(ShebeError::InvalidSession(
+                "no session ID given for search".to_string(),
+            ));
+        }
+        self.services.search.search(self.request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (Shebe, TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("main.rs"), "fn main() { start_server(); }").unwrap();
+        std::fs::write(repo.join("notes.md"), "# Notes\nstart here").unwrap();
+
+        let mut config = Config::default();
+        config.storage.index_dir = temp.path().join("sessions");
+        (Shebe::new(config), temp, repo)
+    }
+
+    #[test]
+    fn test_index_builder_applies_settings() {
+        let (shebe, _temp, repo) = setup();
+
+        let outcome = shebe
+            .index(&repo)
+            .session("lib")
+            .chunk_size(300)
+            .overlap(30)
+            .include_patterns(["*.rs"])
+            .run()
+            .unwrap();
+
+        assert_eq!(outcome.stats.files_indexed, 1);
+        assert_eq!(outcome.config.chunk_size, 300);
+        assert!(outcome.warnings.is_empty());
+        let metadata = shebe
+            .services()
+            .storage
+            .get_session_metadata("lib")
+            .unwrap();
+        assert_eq!(metadata.config.overlap, 30);
+        assert_eq!(shebe.sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_index_builder_requires_session_and_valid_overlap() {
+        let (shebe, _temp, repo) = setup();
+
+        let err = shebe.index(&repo).run().unwrap_err();
+        assert!(matches!(err, ShebeError::InvalidSession(_)));
+
+        let err = shebe
+            .index(&repo)
+            .session("bad")
+            .chunk_size(100)
+            .overlap(100)
+            .run()
+            .unwrap_err();
+        assert!(matches!(err, ShebeError::ConfigError(_)));
+        assert!(!shebe.services().storage.session_exists("bad"));
+    }
+
+    #[test]
+    fn test_index_builder_dry_run_creates_nothing() {
+        let (shebe, _temp, repo) = setup();
+
+        let report = shebe.index(&repo).session("dry").dry_run().unwrap();
+
+        assert_eq!(report.files, 2);
+        assert!(shebe.sessions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_builder() {
+        let (shebe, _temp, repo) = setup();
+        shebe.index(&repo).session("lib").run().unwrap();
+
+        let response = shebe.search("start").session("lib").k(1).run().unwrap();
+        assert_eq!(response.count, 1);
+
+        let err = shebe.search("start").run().unwrap_err();
+        assert!(matches!(err, ShebeError::InvalidSession(_)));
+    }
+}
@@ -0,0 +1,587 @@
+//! Indexing zip and tar archives without extracting them.
+//!
+//! `index_repository` accepts a `.zip`, `.tar`, `.tar.gz` or `.tgz`
+//! file (or a file with zip or gzip magic bytes) in place of a
+//! directory. Its entries are read one at a time in memory and go
+//! through the same patterns, size limits and UTF-8 checks as files on
+//! disk. Each entry is stored as `{archive}!{entry}`, e.g.
+//! `/drops/vendor.zip!src/lib.rs`; `read_file` and `preview_chunk`
+//! reopen the archive and extract that one entry on demand
+//! ([`read_indexed_file`]).
+//!
+//! The session records the archive's content hash ([`ArchiveSource`])
+//! so a re-index can tell whether the archive was replaced. Entries
+//! that would extract outside the archive (`../`, absolute paths),
+//! encrypted entries and unreadable archives fail the run before
+//! anything is indexed.
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::paths;
+use chrono::NaiveDate;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+use zip::ZipArchive;
+
+/// Separates the archive path from the entry path in stored paths
+pub const ARCHIVE_SEPARATOR: char = '!';
+
+/// Largest entry read back from an archive on demand (10 MB)
+pub const MAX_ENTRY_READ_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Archive formats that can be indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Format named by a file's extension
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Format of a regular file, by extension or else by magic bytes
+    ///
+    /// Returns `None` for directories and files that are not archives.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if !path.is_file() {
+            return None;
+        }
+        Self::from_extension(path).or_else(|| Self::from_magic(path))
+    }
+
+    fn from_magic(path: &Path) -> Option<Self> {
+        let mut header = [0u8; 262];
+        let mut file = File::open(path).ok()?;
+        let mut read = 0;
+        while read < header.len() {
+            match file.read(&mut header[read..]) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => read += n,
+            }
+        }
+        let header = &header[..read];
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::TarGz)
+        } else if header.get(257..262) == Some(b"ustar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Name for display ("zip", "tar", "tar.gz")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+        }
+    }
+}
+
+/// The archive a session was indexed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSource {
+    pub kind: ArchiveKind,
+    /// xxh3-128 of the archive file, as lowercase hex
+    pub content_hash: String,
+    /// Size of the archive file in bytes
+    pub size_bytes: u64,
+}
+
+impl ArchiveSource {
+    /// Abbreviated content hash for display
+    pub fn short_hash(&self) -> &str {
+        &self.content_hash[..self.content_hash.len().min(12)]
+    }
+}
+
+/// A regular file inside an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, `/`-separated, without `./`
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Modification time in seconds since the epoch, if recorded
+    pub modified: Option<i64>,
+}
+
+/// An archive file on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archive {
+    path: PathBuf,
+    kind: ArchiveKind,
+}
+
+impl Archive {
+    /// The archive at `path`, or `None` if it is not an archive file
+    ///
+    /// The path is made absolute so stored entry paths do not depend on
+    /// the working directory.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let kind = ArchiveKind::detect(path)?;
+        let path = path.canonicalize().ok()?;
+        Some(Self { path, kind })
+    }
+
+    /// Path of the archive file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> ArchiveKind {
+        self.kind
+    }
+
+    /// Stored path of an entry (`{archive}!{entry}`)
+    pub fn entry_path(&self, name: &str) -> PathBuf {
+        PathBuf::from(format!(
+            "{}{ARCHIVE_SEPARATOR}{name}",
+            paths::normalize_path(&self.path)
+        ))
+    }
+
+    /// Path an entry would have if the archive were a directory
+    ///
+    /// Patterns are matched against this path, so `**/src/**` selects
+    /// the same files in an archive as in its extracted tree.
+    pub fn extracted_path(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Hash the archive file
+    pub fn source(&self) -> Result<ArchiveSource> {
+        let mut file = File::open(&self.path)?;
+        let mut hasher = Xxh3::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut size_bytes = 0;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            size_bytes += read as u64;
+        }
+        Ok(ArchiveSource {
+            kind: self.kind,
+            content_hash: format!("{:032x}", hasher.digest128()),
+            size_bytes,
+        })
+    }
+
+    /// Regular file entries, in archive order
+    ///
+    /// Directories, links and other special entries are left out.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if the archive cannot be read, has an encrypted
+    /// entry or an entry outside the archive root.
+    pub fn entries(&self) -> Result<Vec<ArchiveEntry>> {
+        let mut entries = Vec::new();
+        self.visit(|entry, _| {
+            entries.push(entry);
+            Ok(Visit::Skip)
+        })?;
+        Ok(entries)
+    }
+
+    /// Read the entries `wanted` accepts, in archive order
+    ///
+    /// `f` gets each wanted entry with its contents, or the error that
+    /// stopped it being read; at most `max_bytes` are read from an
+    /// entry (a longer one is an error). An error returned by `f`
+    /// stops the iteration.
+    pub fn read_entries(
+        &self,
+        max_bytes: u64,
+        mut wanted: impl FnMut(&ArchiveEntry) -> bool,
+        mut f: impl FnMut(&ArchiveEntry, io::Result<Vec<u8>>) -> Result<()>,
+    ) -> Result<()> {
+        self.visit(|entry, reader| {
+            if !wanted(&entry) {
+                return Ok(Visit::Skip);
+            }
+            f(&entry, read_capped(reader, max_bytes))?;
+            Ok(Visit::Continue)
+        })
+    }
+
+    /// Extract one entry, at most `max_bytes` long
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if the archive cannot be read or has no such
+    /// entry; `IoError` if the entry cannot be read or is longer than
+    /// `max_bytes`.
+    pub fn read_entry(&self, name: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let mut contents = None;
+        self.visit(|entry, reader| {
+            if entry.name != name {
+                return Ok(Visit::Skip);
+            }
+            contents = Some(read_capped(reader, max_bytes));
+            Ok(Visit::Stop)
+        })?;
+        match contents {
+            Some(read) => Ok(read?),
+            None => Err(ShebeError::InvalidPath(format!(
+                "{} has no entry '{name}'",
+                self.path.display()
+            ))),
+        }
+    }
+
+    /// Call `f` with every regular file entry and a reader for it
+    fn visit(&self, mut f: impl FnMut(ArchiveEntry, &mut dyn Read) -> Result<Visit>) -> Result<()> {
+        let file = File::open(&self.path)?;
+        match self.kind {
+            ArchiveKind::Zip => self.visit_zip(file, &mut f),
+            ArchiveKind::Tar => self.visit_tar(BufReader::new(file), &mut f),
+            ArchiveKind::TarGz => self.visit_tar(GzDecoder::new(BufReader::new(file)), &mut f),
+        }
+    }
+
+    fn visit_zip(
+        &self,
+        file: File,
+        f: &mut dyn FnMut(ArchiveEntry, &mut dyn Read) -> Result<Visit>,
+    ) -> Result<()> {
+        let mut zip = ZipArchive::new(BufReader::new(file)).map_err(|e| self.unreadable(e))?;
+        for index in 0..zip.len() {
+            let raw = zip.by_index_raw(index).map_err(|e| self.unreadable(e))?;
+            if raw.is_dir() || raw.is_symlink() {
+                continue;
+            }
+            let name = self.entry_name(raw.name())?;
+            if raw.encrypted() {
+                return Err(ShebeError::InvalidPath(format!(
+                    "{} is encrypted (entry '{name}'); encrypted archives cannot be indexed",
+                    self.path.display()
+                )));
+            }
+            let entry = ArchiveEntry {
+                name,
+                size: raw.size(),
+                modified: raw.last_modified().and_then(zip_time),
+            };
+            drop(raw);
+
+            let mut reader = zip.by_index(index).map_err(|e| self.unreadable(e))?;
+            match f(entry, &mut reader)? {
+                Visit::Stop => break,
+                Visit::Skip | Visit::Continue => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_tar(
+        &self,
+        reader: impl Read,
+        f: &mut dyn FnMut(ArchiveEntry, &mut dyn Read) -> Result<Visit>,
+    ) -> Result<()> {
+        let mut tar = tar::Archive::new(reader);
+        for entry in tar.entries().map_err(|e| self.unreadable(e))? {
+            let mut entry = entry.map_err(|e| self.unreadable(e))?;
+            let header = entry.header();
+            if !header.entry_type().is_file() {
+                continue;
+            }
+            let raw = entry.path().map_err(|e| self.unreadable(e))?;
+            let entry_info = ArchiveEntry {
+                name: self.entry_name(&raw.to_string_lossy())?,
+                size: header.size().map_err(|e| self.unreadable(e))?,
+                modified: header.mtime().ok().and_then(|t| i64::try_from(t).ok()),
+            };
+            // A skipped entry's data is passed over by the next call to `next`
+            match f(entry_info, &mut entry)? {
+                Visit::Stop => break,
+                Visit::Skip | Visit::Continue => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Normalized entry path, refusing paths outside the archive root
+    fn entry_name(&self, raw: &str) -> Result<String> {
+        let escapes = || {
+            ShebeError::InvalidPath(format!(
+                "{} has an entry outside the archive root: '{raw}'; refusing to index it",
+                self.path.display()
+            ))
+        };
+        normalize_entry(raw).ok_or_else(escapes)
+    }
+
+    fn unreadable(&self, error: impl std::fmt::Display) -> ShebeError {
+        ShebeError::InvalidPath(format!(
+            "Cannot read {} archive {}: {error}. The archive may be corrupt or incomplete.",
+            self.kind.as_str(),
+            self.path.display()
+        ))
+    }
+}
+
+/// What [`Archive::visit`] does after an entry
+enum Visit {
+    /// The entry was not read
+    Skip,
+    /// The entry was read; go on
+    Continue,
+    /// Stop visiting entries
+    Stop,
+}
+
+/// Read all of `reader`, failing if it holds more than `max_bytes`
+fn read_capped(reader: &mut dyn Read, max_bytes: u64) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    reader
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut contents)?;
+    if contents.len() as u64 > max_bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("entry is larger than {max_bytes} bytes"),
+        ));
+    }
+    Ok(contents)
+}
+
+/// Seconds since the epoch of a zip (MS-DOS) timestamp
+fn zip_time(time: zip::DateTime) -> Option<i64> {
+    NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+        .and_hms_opt(
+            time.hour().into(),
+            time.minute().into(),
+            time.second().into(),
+        )
+        .map(|t| t.and_utc().timestamp())
+}
+
+/// Entry path with `.` and empty parts dropped
+///
+/// `None` if the entry would land outside the archive root: absolute
+/// paths, drive prefixes and `..`.
+fn normalize_entry(raw: &str) -> Option<String> {
+    if raw.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for part in raw.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            part if part.contains(':') && parts.is_empty() => return None,
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Resolve a client-supplied path against an archive session's root
+///
+/// Absolute paths (including stored `{archive}!{entry}` paths) are only
+/// normalized; relative paths name an entry of `archive` and may not
+/// leave it.
+pub fn resolve_entry(requested: &str, archive: &Path) -> Result<paths::ResolvedPath> {
+    let normalized = paths::normalize(requested);
+    if paths::to_native(&normalized).is_absolute() {
+        return paths::resolve(requested, None);
+    }
+    let entry = normalize_entry(&normalized)
+        .filter(|entry| !entry.is_empty())
+        .ok_or_else(|| {
+            ShebeError::InvalidPath(format!(
+                "'{requested}' is outside the archive '{}'",
+                archive.display()
+            ))
+        })?;
+    Ok(paths::ResolvedPath {
+        requested: requested.to_string(),
+        path: format!(
+            "{}{ARCHIVE_SEPARATOR}{entry}",
+            paths::normalize_path(archive)
+        ),
+        root: Some(archive.to_path_buf()),
+    })
+}
+
+/// Split a stored path into its archive and entry
+///
+/// Returns `None` for paths of ordinary files. The archive is the part
+/// before the first `!` that ends in an archive extension or names a
+/// file.
+pub fn split_entry_path(stored: &str) -> Option<(PathBuf, &str)> {
+    stored
+        .match_indices(ARCHIVE_SEPARATOR)
+        .map(|(idx, _)| (&stored[..idx], &stored[idx + 1..]))
+        .find(|(archive, entry)| {
+            let archive = paths::to_native(archive);
+            !entry.is_empty()
+                && (ArchiveKind::from_extension(&archive).is_some() || archive.is_file())
+        })
+        .map(|(archive, entry)| (paths::to_native(archive), entry))
+}
+
+/// Read an indexed file as UTF-8, from disk or from its archive
+///
+/// Archive entries (`{archive}!{entry}`) are extracted on demand, up
+/// to [`MAX_ENTRY_READ_BYTES`].
+pub fn read_indexed_file(stored: &str) -> io::Result<String> {
+    let Some((archive_path, entry)) = split_entry_path(stored) else {
+        return std::fs::read_to_string(paths::to_native(stored));
+    };
+    let archive = Archive::detect(&archive_path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("archive {} not found", archive_path.display()),
+        )
+    })?;
+    let bytes = archive
+        .read_entry(entry, MAX_ENTRY_READ_BYTES)
+        .map_err(|e| match e {
+            ShebeError::IoError(e) => e,
+            e => io::Error::new(io::ErrorKind::NotFound, e.to_string()),
+        })?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path, entries: &[(&str, &str)]) {
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(encoder);
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_cksum();
+            tar.append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_detect_by_extension_and_magic() {
+        let temp = TempDir::new().unwrap();
+        let zip = temp.path().join("drop.zip");
+        write_zip(&zip, &[("a.rs", "fn a() {}")]);
+        let renamed = temp.path().join("drop.bin");
+        std::fs::copy(&zip, &renamed).unwrap();
+        let tgz = temp.path().join("drop.tgz");
+        write_tar_gz(&tgz, &[("a.rs", "fn a() {}")]);
+        std::fs::write(temp.path().join("notes.txt"), "plain").unwrap();
+
+        assert_eq!(ArchiveKind::detect(&zip), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::detect(&renamed), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::detect(&tgz), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::detect(&temp.path().join("notes.txt")), None);
+        assert_eq!(ArchiveKind::detect(temp.path()), None);
+    }
+
+    #[test]
+    fn test_entries_and_read_entry() {
+        let temp = TempDir::new().unwrap();
+        for (file, write) in [
+            ("drop.zip", write_zip as fn(&Path, &[(&str, &str)])),
+            ("drop.tar.gz", write_tar_gz),
+        ] {
+            let path = temp.path().join(file);
+            write(
+                &path,
+                &[("./src/lib.rs", "pub fn lib() {}"), ("README.md", "# Drop")],
+            );
+            let archive = Archive::detect(&path).unwrap();
+
+            let names: Vec<String> = archive
+                .entries()
+                .unwrap()
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+            assert_eq!(names, ["src/lib.rs", "README.md"], "{file}");
+            assert_eq!(
+                archive.read_entry("src/lib.rs", 1024).unwrap(),
+                b"pub fn lib() {}"
+            );
+            assert!(archive.read_entry("src/lib.rs", 4).is_err());
+            assert!(archive.read_entry("missing.rs", 1024).is_err());
+        }
+    }
+
+    #[test]
+    fn test_rejects_traversal_and_corrupt_archives() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("evil.zip");
+        write_zip(
+            &path,
+            &[("ok.rs", "fn ok() {}"), ("../escape.rs", "fn x() {}")],
+        );
+        let err = Archive::detect(&path).unwrap().entries().unwrap_err();
+        assert!(
+            err.to_string().contains("outside the archive root"),
+            "{err}"
+        );
+
+        let corrupt = temp.path().join("corrupt.zip");
+        std::fs::write(&corrupt, b"PK\x03\x04 not really a zip").unwrap();
+        let err = Archive::detect(&corrupt).unwrap().entries().unwrap_err();
+        assert!(err.to_string().contains("Cannot read zip archive"), "{err}");
+    }
+
+    #[test]
+    fn test_split_and_read_indexed_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("drop.zip");
+        write_zip(&path, &[("src/lib.rs", "pub fn lib() {}")]);
+        let archive = Archive::detect(&path).unwrap();
+        let stored = paths::normalize_path(&archive.entry_path("src/lib.rs"));
+
+        let (archive_path, entry) = split_entry_path(&stored).unwrap();
+        assert_eq!(archive_path, archive.path());
+        assert_eq!(entry, "src/lib.rs");
+        assert_eq!(read_indexed_file(&stored).unwrap(), "pub fn lib() {}");
+        assert_eq!(split_entry_path("/repo/wow!.rs"), None);
+    }
+}
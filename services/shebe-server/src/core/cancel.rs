@@ -0,0 +1,100 @@
+//! Cooperative cancellation of tool calls.
+//!
+//! A tool call that runs past its time limit is abandoned by the MCP
+//! dispatcher, but blocking work it started keeps running on its own
+//! thread. The dispatcher runs each call with a [`CancelToken`], which
+//! [`spawn_blocking_in_context`](crate::core::logging::spawn_blocking_in_context)
+//! carries into that thread, and cancels it on timeout. Long loops
+//! (matching file paths, listing files, reading files for context)
+//! call [`check_cancelled`] as they go and stop instead of finishing
+//! work nobody will see.
+
+use crate::core::error::{Result, ShebeError};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Items a long loop handles between cancellation checks
+pub const CHECK_INTERVAL: usize = 1024;
+
+tokio::task_local! {
+    static TOKEN: CancelToken;
+}
+
+/// Cancel flag shared by one call and the work it started
+///
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// A token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the call's work to stop at its next check
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`CancelToken::cancel`] was called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Run `future` with this token as the current call's token
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        TOKEN.scope(self, future).await
+    }
+
+    /// Run blocking `work` with this token as the current call's token
+    pub fn sync_scope<T>(self, work: impl FnOnce() -> T) -> T {
+        TOKEN.sync_scope(self, work)
+    }
+
+    /// The current call's token, if any
+    pub fn current() -> Option<Self> {
+        TOKEN.try_with(Clone::clone).ok()
+    }
+}
+
+/// Stop if the current call has been cancelled
+///
+/// Always succeeds outside a call (CLI commands, tests).
+///
+/// # Errors
+///
+/// `Cancelled` once the call's token is cancelled.
+pub fn check_cancelled() -> Result<()> {
+    if TOKEN.try_with(CancelToken::is_cancelled).unwrap_or(false) {
+        return Err(ShebeError::Cancelled(
+            "tool call abandoned after its time limit".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_outside_a_call() {
+        assert!(check_cancelled().is_ok());
+        assert!(CancelToken::current().is_none());
+    }
+
+    #[test]
+    fn test_check_sees_cancellation() {
+        let token = CancelToken::new();
+        token.clone().sync_scope(|| {
+            assert!(check_cancelled().is_ok());
+            CancelToken::current().unwrap().cancel();
+            assert!(matches!(check_cancelled(), Err(ShebeError::Cancelled(_))));
+        });
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,530 @@
+//! Side-by-side comparison of two chunking configurations.
+//!
+//! [`Services::compare_configs`] indexes one repository twice into
+//! throwaway sessions, runs the same queries against both and reports
+//! how the top files, scores, index sizes and indexing times differ.
+//! The sessions get generated IDs under [`COMPARE_SESSION_PREFIX`],
+//! which session listings hide, and are deleted when the comparison
+//! ends, whether it succeeded or not.
+
+use crate::core::display::relative_to;
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::{BoundaryHint, IndexOverrides};
+use crate::core::services::Services;
+use crate::core::storage::{SessionConfig, SessionEntry, StorageManager};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Session ID prefix reserved for comparison sessions
+pub const COMPARE_SESSION_PREFIX: &str = "_cmp-";
+
+/// Results per query compared when none is given
+pub const DEFAULT_COMPARE_K: usize = 10;
+
+/// Most queries one comparison runs
+pub const MAX_COMPARE_QUERIES: usize = 100;
+
+/// Age after which a comparison session left by a killed run is removed
+const STALE_COMPARISON_HOURS: i64 = 1;
+
+/// Whether `session_id` belongs to a comparison run
+pub fn is_comparison_session(session_id: &str) -> bool {
+    session_id.starts_with(COMPARE_SESSION_PREFIX)
+}
+
+/// Chunking settings of one side of a comparison
+///
+/// Settings left out come from `.shebe.toml` and the configuration, as
+/// when indexing. Written as `chunk_size=256,overlap=32` on the command
+/// line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigVariant {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlap: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boundary_hint: Option<BoundaryHint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_tail_fragments: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_chunking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chunks_per_file: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_high_entropy: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_run_chars: Option<usize>,
+}
+
+impl ConfigVariant {
+    /// Setting names accepted in a variant
+    pub const KEYS: [&'static str; 8] = [
+        "chunk_size",
+        "overlap",
+        "boundary_hint",
+        "merge_tail_fragments",
+        "record_chunking",
+        "max_chunks_per_file",
+        "strip_high_entropy",
+        "strip_run_chars",
+    ];
+
+    /// Indexing overrides for this variant
+    pub fn overrides(&self) -> IndexOverrides {
+        IndexOverrides {
+            chunk_size: self.chunk_size,
+            overlap: self.overlap,
+            boundary_hint: self.boundary_hint,
+            merge_tail_fragments: self.merge_tail_fragments,
+            record_chunking: self.record_chunking,
+            max_chunks_per_file: self.max_chunks_per_file,
+            strip_high_entropy: self.strip_high_entropy,
+            strip_run_chars: self.strip_run_chars,
+            ..IndexOverrides::default()
+        }
+    }
+}
+
+impl fmt::Display for ConfigVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let settings: Vec<String> = [
+            self.chunk_size.map(|v| format!("chunk_size={v}")),
+            self.overlap.map(|v| format!("overlap={v}")),
+            self.boundary_hint.map(|v| format!("boundary_hint={v}")),
+            self.merge_tail_fragments
+                .map(|v| format!("merge_tail_fragments={v}")),
+            self.record_chunking.map(|v| format!("record_chunking={v}")),
+            self.max_chunks_per_file
+                .map(|v| format!("max_chunks_per_file={v}")),
+            self.strip_high_entropy
+                .map(|v| format!("strip_high_entropy={v}")),
+            self.strip_run_chars.map(|v| format!("strip_run_chars={v}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if settings.is_empty() {
+            f.write_str("defaults")
+        } else {
+            f.write_str(&settings.join(","))
+        }
+    }
+}
+
+impl FromStr for ConfigVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        fn parse<T: FromStr>(key: &str, value: &str) -> std::result::Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value '{value}' for {key}"))
+        }
+
+        let mut variant = ConfigVariant::default();
+        for setting in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got '{setting}'"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "chunk_size" => variant.chunk_size = Some(parse(key, value)?),
+                "overlap" => variant.overlap = Some(parse(key, value)?),
+                "boundary_hint" => variant.boundary_hint = Some(value.parse()?),
+                "merge_tail_fragments" => variant.merge_tail_fragments = Some(parse(key, value)?),
+                "record_chunking" => variant.record_chunking = Some(parse(key, value)?),
+                "max_chunks_per_file" => variant.max_chunks_per_file = Some(parse(key, value)?),
+                "strip_high_entropy" => variant.strip_high_entropy = Some(parse(key, value)?),
+                "strip_run_chars" => variant.strip_run_chars = Some(parse(key, value)?),
+                other => {
+                    return Err(format!(
+                        "unknown setting '{other}' (expected one of: {})",
+                        Self::KEYS.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(variant)
+    }
+}
+
+/// What to compare
+#[derive(Debug, Clone)]
+pub struct CompareRequest {
+    /// Repository indexed by both variants
+    pub path: PathBuf,
+
+    /// First variant
+    pub a: ConfigVariant,
+
+    /// Second variant
+    pub b: ConfigVariant,
+
+    /// Queries run against both sessions
+    pub queries: Vec<String>,
+
+    /// Results per query
+    pub k: usize,
+
+    /// Settings for anything neither the variant nor `.shebe.toml` sets
+    /// (`None` for the `indexing` configuration)
+    pub defaults: Option<SessionConfig>,
+}
+
+/// Distribution of result scores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ScoreSummary {
+    /// Number of scores
+    pub count: usize,
+    pub min: f32,
+    pub median: f32,
+    pub mean: f32,
+    pub max: f32,
+}
+
+impl ScoreSummary {
+    /// Summarize `scores` (all zero when empty)
+    pub fn from_scores(scores: &[f32]) -> Self {
+        if scores.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        Self {
+            count: sorted.len(),
+            min: sorted[0],
+            median,
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Indexing and search figures of one variant
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantReport {
+    /// Settings as given
+    pub settings: ConfigVariant,
+
+    /// Chunk size the session was indexed with
+    pub chunk_size: usize,
+
+    /// Overlap the session was indexed with
+    pub overlap: usize,
+
+    pub files_indexed: usize,
+    pub chunks_created: usize,
+    pub index_size_bytes: u64,
+    pub indexing_ms: u64,
+
+    /// Queries with at least one result
+    pub queries_with_results: usize,
+
+    /// Scores of every result of every query
+    pub scores: ScoreSummary,
+}
+
+/// Results of one query against one variant
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryRun {
+    /// Number of results
+    pub results: usize,
+
+    /// Best score (`None` without results)
+    pub top_score: Option<f32>,
+
+    /// Distinct files among the results, relative to the repository,
+    /// in rank order
+    pub files: Vec<String>,
+
+    #[serde(skip)]
+    scores: Vec<f32>,
+}
+
+/// One query run against both variants
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryComparison {
+    pub query: String,
+    pub a: QueryRun,
+    pub b: QueryRun,
+
+    /// Jaccard similarity of the two file sets (1.0 when both are empty)
+    pub file_overlap: f64,
+}
+
+/// Result of [`Services::compare_configs`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigComparison {
+    pub repository: PathBuf,
+
+    /// Results per query
+    pub k: usize,
+
+    pub a: VariantReport,
+    pub b: VariantReport,
+    pub queries: Vec<QueryComparison>,
+
+    /// Mean of the per-query file overlaps
+    pub mean_file_overlap: f64,
+}
+
+/// Jaccard similarity of two file lists (1.0 when both are empty)
+pub fn jaccard(a: &[String], b: &[String]) -> f64 {
+    let a: BTreeSet<&String> = a.iter().collect();
+    let b: BTreeSet<&String> = b.iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Comparison sessions deleted when dropped, so a failed run leaves
+/// nothing behind
+struct TempSessions<'a> {
+    storage: &'a StorageManager,
+    ids: Vec<String>,
+}
+
+impl Drop for TempSessions<'_> {
+    fn drop(&mut self) {
+        for id in &self.ids {
+            if !self.storage.session_exists(id) {
+                continue;
+            }
+            if let Err(e) = self.storage.delete_session(id) {
+                tracing::warn!("Failed to delete comparison session '{id}': {e}");
+            }
+        }
+    }
+}
+
+impl Services {
+    /// Index `request.path` with both variants, run every query against
+    /// both sessions and compare the results
+    ///
+    /// The temporary sessions are deleted before returning, also on
+    /// error. Comparison sessions over an hour old, left by a run that
+    /// was killed, are removed first.
+    ///
+    /// # Errors
+    ///
+    /// Fails without queries, on invalid settings, and when indexing or
+    /// a search fails.
+    pub fn compare_configs(&self, request: &CompareRequest) -> Result<ConfigComparison> {
+        if request.queries.is_empty() {
+            return Err(ShebeError::InvalidQuery(
+                "no queries to compare".to_string(),
+            ));
+        }
+        if request.queries.len() > MAX_COMPARE_QUERIES {
+            return Err(ShebeError::InvalidQuery(format!(
+                "{} queries given; at most {MAX_COMPARE_QUERIES} can be compared",
+                request.queries.len()
+            )));
+        }
+        if request.k == 0 {
+            return Err(ShebeError::InvalidQuery("k must be at least 1".to_string()));
+        }
+        let root = request
+            .path
+            .canonicalize()
+            .map_err(|e| ShebeError::InvalidPath(format!("{}: {e}", request.path.display())))?;
+        self.remove_stale_comparisons();
+
+        let run = uuid::Uuid::new_v4().simple().to_string();
+        let ids = [
+            format!("{COMPARE_SESSION_PREFIX}{}-a", &run[..8]),
+            format!("{COMPARE_SESSION_PREFIX}{}-b", &run[..8]),
+        ];
+        let mut temp = TempSessions {
+            storage: &self.storage,
+            ids: Vec::new(),
+        };
+
+        // Resolve both variants before indexing either, so bad settings
+        // fail fast
+        let builders = [(&ids[0], &request.a), (&ids[1], &request.b)].map(|(id, variant)| {
+            let builder = self
+                .index(&root)
+                .session(id.as_str())
+                .overrides(variant.overrides());
+            match &request.defaults {
+                Some(defaults) => builder.defaults(defaults.clone()),
+                None => builder,
+            }
+        });
+        let resolved = [builders[0].resolve()?, builders[1].resolve()?];
+
+        let mut reports = Vec::with_capacity(2);
+        let mut runs: Vec<Vec<QueryRun>> = Vec::with_capacity(2);
+        for ((builder, resolved), (id, variant)) in builders
+            .into_iter()
+            .zip(resolved)
+            .zip([(&ids[0], &request.a), (&ids[1], &request.b)])
+        {
+            temp.ids.push(id.clone());
+            let outcome = builder.run_resolved(resolved)?;
+            let index_size_bytes = self.storage.refresh_index_size(id)?;
+
+            let variant_runs = request
+                .queries
+                .iter()
+                .map(|query| self.run_query(id, query, request.k, &root))
+                .collect::<Result<Vec<_>>>()?;
+            let scores: Vec<f32> = variant_runs
+                .iter()
+                .flat_map(|run| run.scores.iter().copied())
+                .collect();
+            reports.push(VariantReport {
+                settings: variant.clone(),
+                chunk_size: outcome.config.chunk_size,
+                overlap: outcome.config.overlap,
+                files_indexed: outcome.stats.files_indexed,
+                chunks_created: outcome.stats.chunks_created,
+                index_size_bytes,
+                indexing_ms: outcome.stats.duration_ms,
+                queries_with_results: variant_runs.iter().filter(|r| r.results > 0).count(),
+                scores: ScoreSummary::from_scores(&scores),
+            });
+            runs.push(variant_runs);
+        }
+
+        let runs_b = runs.pop().unwrap_or_default();
+        let runs_a = runs.pop().unwrap_or_default();
+        let queries: Vec<QueryComparison> = request
+            .queries
+            .iter()
+            .zip(runs_a.into_iter().zip(runs_b))
+            .map(|(query, (a, b))| QueryComparison {
+                query: query.clone(),
+                file_overlap: jaccard(&a.files, &b.files),
+                a,
+                b,
+            })
+            .collect();
+        let mean_file_overlap =
+            queries.iter().map(|q| q.file_overlap).sum::<f64>() / queries.len() as f64;
+
+        let b = reports.pop().expect("both variants indexed");
+        let a = reports.pop().expect("both variants indexed");
+        Ok(ConfigComparison {
+            repository: root,
+            k: request.k,
+            a,
+            b,
+            queries,
+            mean_file_overlap,
+        })
+    }
+
+    /// Run `query` against a comparison session
+    fn run_query(&self, session: &str, query: &str, k: usize, root: &Path) -> Result<QueryRun> {
+        let response = self.search.search_session(session, query, Some(k))?;
+        let mut run = QueryRun {
+            results: response.results.len(),
+            top_score: response.results.first().map(|r| r.score),
+            ..QueryRun::default()
+        };
+        for result in &response.results {
+            run.scores.push(result.score);
+            let path = relative_to(Path::new(&result.file_path), root)
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_else(|| result.file_path.clone());
+            if !run.files.contains(&path) {
+                run.files.push(path);
+            }
+        }
+        Ok(run)
+    }
+
+    /// Delete comparison sessions left by a run that was killed
+    fn remove_stale_comparisons(&self) {
+        let Ok(entries) = self.storage.list_session_entries() else {
+            return;
+        };
+        let cutoff = Utc::now() - Duration::hours(STALE_COMPARISON_HOURS);
+        for entry in entries {
+            let SessionEntry::Valid(metadata) = entry else {
+                continue;
+            };
+            if !is_comparison_session(&metadata.id) || metadata.created_at > cutoff {
+                continue;
+            }
+            if let Err(e) = self.storage.delete_session(&metadata.id) {
+                tracing::warn!(
+                    "Failed to delete stale comparison session '{}': {e}",
+                    metadata.id
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_variant() {
+        let variant: ConfigVariant = "chunk_size=256, overlap=32,boundary_hint=newline"
+            .parse()
+            .unwrap();
+        assert_eq!(variant.chunk_size, Some(256));
+        assert_eq!(variant.overlap, Some(32));
+        assert_eq!(variant.boundary_hint, Some(BoundaryHint::Newline));
+        assert_eq!(
+            variant.to_string(),
+            "chunk_size=256,overlap=32,boundary_hint=newline"
+        );
+        assert_eq!(ConfigVariant::default().to_string(), "defaults");
+    }
+
+    #[test]
+    fn test_parse_variant_rejects_bad_settings() {
+        let err = "chunk_size=big".parse::<ConfigVariant>().unwrap_err();
+        assert!(err.contains("invalid value 'big' for chunk_size"));
+        let err = "chunk=256".parse::<ConfigVariant>().unwrap_err();
+        assert!(err.contains("unknown setting 'chunk'"));
+        let err = "chunk_size".parse::<ConfigVariant>().unwrap_err();
+        assert!(err.contains("expected key=value"));
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let files = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(jaccard(&files(&[]), &files(&[])), 1.0);
+        assert_eq!(jaccard(&files(&["a", "b"]), &files(&["b", "a"])), 1.0);
+        assert_eq!(jaccard(&files(&["a", "b"]), &files(&["b", "c"])), 1.0 / 3.0);
+        assert_eq!(jaccard(&files(&["a"]), &files(&[])), 0.0);
+    }
+
+    #[test]
+    fn test_score_summary() {
+        let summary = ScoreSummary::from_scores(&[3.0, 1.0, 2.0, 4.0]);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.max, 4.0);
+        assert_eq!(ScoreSummary::from_scores(&[]), ScoreSummary::default());
+    }
+
+    #[test]
+    fn test_comparison_session_prefix() {
+        assert!(is_comparison_session("_cmp-1a2b3c4d-a"));
+        assert!(!is_comparison_session("cmp-project"));
+    }
+}
@@ -3,11 +3,20 @@
 //! This module handles loading configuration from TOML files and
 //! environment variables, with sensible defaults for all settings.
 
+use crate::core::display::LinkTemplate;
 use crate::core::error::{Result, ShebeError};
+use crate::core::logging::LogFormat;
+use crate::core::schedule::Schedule;
+use crate::core::search::{
+    ShortChunkPenalty, DEFAULT_SHORT_CHUNK_FACTOR, DEFAULT_SHORT_CHUNK_RATIO,
+};
+use crate::core::storage::{is_valid_name, DEFAULT_NAMESPACE};
 use crate::core::xdg::XdgDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 
 /// Main configuration structure
@@ -21,6 +30,33 @@ pub struct Config {
     pub search: SearchConfig,
     #[serde(default)]
     pub limits: LimitsConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub mcp: McpConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    /// Named storage roots, selected with `--profile` or `SHEBE_PROFILE`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Further storage roots served side by side, addressed as
+    /// `<namespace>/<session>`
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, NamespaceConfig>,
+
+    /// Profile selected at startup (`None`: the default storage root)
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 /// Indexing configuration
@@ -45,6 +81,11 @@ pub struct IndexingConfig {
     /// File patterns to exclude (glob syntax)
     #[serde(default = "default_exclude_patterns")]
     pub exclude_patterns: Vec<String>,
+
+    /// Record each file's last commit time from git (false: never run
+    /// git, use filesystem modification times)
+    #[serde(default = "default_git_metadata")]
+    pub git_metadata: bool,
 }
 
 /// Storage configuration
@@ -53,6 +94,20 @@ pub struct StorageConfig {
     /// Root directory for index storage
     #[serde(default = "default_index_dir")]
     pub index_dir: PathBuf,
+
+    /// Directory for shallow clones of remote repositories
+    #[serde(default = "default_clone_dir")]
+    pub clone_dir: PathBuf,
+
+    /// Re-index sessions whose index files are found corrupted, when
+    /// their repository is still available
+    #[serde(default)]
+    pub reindex_on_corruption: bool,
+
+    /// Namespace of session IDs given without one (`default`: the
+    /// sessions under `index_dir`)
+    #[serde(default = "default_namespace")]
+    pub default_namespace: String,
 }
 
 /// Search configuration
@@ -69,6 +124,22 @@ pub struct SearchConfig {
     /// Maximum query string length
     #[serde(default = "default_max_query_length")]
     pub max_query_length: usize,
+
+    /// Boost applied to query matches in the file path (0 disables)
+    #[serde(default = "default_path_boost")]
+    pub path_boost: f32,
+
+    /// Demote chunks much shorter than the session's chunk size
+    #[serde(default = "default_penalize_short_chunks")]
+    pub penalize_short_chunks: bool,
+
+    /// Share of the chunk size below which a chunk is demoted (0-1)
+    #[serde(default = "default_short_chunk_ratio")]
+    pub short_chunk_ratio: f32,
+
+    /// Score multiplier for demoted chunks (above 0, at most 1)
+    #[serde(default = "default_short_chunk_factor")]
+    pub short_chunk_factor: f32,
 }
 
 // Default value functions
@@ -84,10 +155,22 @@ fn default_max_file_size() -> usize {
     10
 }
 
+fn default_git_metadata() -> bool {
+    true
+}
+
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
 fn default_index_dir() -> PathBuf {
     PathBuf::from("./data")
 }
 
+fn default_clone_dir() -> PathBuf {
+    PathBuf::from("./cache/clones")
+}
+
 fn default_k() -> usize {
     10
 }
@@ -100,6 +183,22 @@ fn default_max_query_length() -> usize {
     500
 }
 
+fn default_path_boost() -> f32 {
+    2.0
+}
+
+fn default_penalize_short_chunks() -> bool {
+    true
+}
+
+fn default_short_chunk_ratio() -> f32 {
+    DEFAULT_SHORT_CHUNK_RATIO
+}
+
+fn default_short_chunk_factor() -> f32 {
+    DEFAULT_SHORT_CHUNK_FACTOR
+}
+
 fn default_include_patterns() -> Vec<String> {
     vec![
         "*.rs".to_string(),
@@ -200,6 +299,70 @@ fn default_request_timeout() -> u64 {
     300
 }
 
+fn default_git_timeout() -> u64 {
+    300
+}
+
+fn default_tool_timeout() -> u64 {
+    60
+}
+
+fn default_job_retention() -> u64 {
+    3600
+}
+
+fn default_max_files_per_session() -> usize {
+    200_000
+}
+
+fn default_max_session_size_mb() -> u64 {
+    4096
+}
+
+fn default_stream_threshold_mb() -> u64 {
+    8
+}
+
+fn default_max_line_length() -> usize {
+    1_000_000
+}
+
+fn default_list_dir_max() -> usize {
+    500
+}
+
+fn default_open_failure_threshold() -> u32 {
+    3
+}
+
+fn default_open_failure_window() -> u64 {
+    60
+}
+
+fn default_open_failure_cooldown() -> u64 {
+    30
+}
+
+fn default_stale_after_days() -> u64 {
+    7
+}
+
+fn default_schedule_tick() -> u64 {
+    60
+}
+
+fn default_maintenance_idle_minutes() -> u64 {
+    30
+}
+
+fn default_target_segments() -> usize {
+    1
+}
+
+fn default_slow_query_ms() -> u64 {
+    1000
+}
+
 impl Default for IndexingConfig {
     fn default() -> Self {
         Self {
@@ -208,6 +371,7 @@ impl Default for IndexingConfig {
             max_file_size_mb: default_max_file_size(),
             include_patterns: default_include_patterns(),
             exclude_patterns: default_exclude_patterns(),
+            git_metadata: default_git_metadata(),
         }
     }
 }
@@ -216,6 +380,20 @@ impl Default for StorageConfig {
     fn default() -> Self {
         Self {
             index_dir: default_index_dir(),
+            clone_dir: default_clone_dir(),
+            reindex_on_corruption: false,
+            default_namespace: default_namespace(),
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Ratio and factor of the short-chunk penalty (applied only with
+    /// `penalize_short_chunks`)
+    pub fn short_chunk_penalty(&self) -> ShortChunkPenalty {
+        ShortChunkPenalty {
+            min_ratio: self.short_chunk_ratio,
+            factor: self.short_chunk_factor,
         }
     }
 }
@@ -226,6 +404,10 @@ impl Default for SearchConfig {
             default_k: default_k(),
             max_k: default_max_k(),
             max_query_length: default_max_query_length(),
+            path_boost: default_path_boost(),
+            penalize_short_chunks: default_penalize_short_chunks(),
+            short_chunk_ratio: default_short_chunk_ratio(),
+            short_chunk_factor: default_short_chunk_factor(),
         }
     }
 }
@@ -240,6 +422,53 @@ pub struct LimitsConfig {
     /// Request timeout in seconds
     #[serde(default = "default_request_timeout")]
     pub request_timeout_sec: u64,
+
+    /// Timeout in seconds for each git clone or fetch of a remote repository
+    #[serde(default = "default_git_timeout")]
+    pub git_timeout_sec: u64,
+
+    /// Time limit in seconds for one call of a read-only MCP tool
+    #[serde(default = "default_tool_timeout")]
+    pub tool_timeout_sec: u64,
+
+    /// How long finished background indexing jobs stay listed, in seconds
+    #[serde(default = "default_job_retention")]
+    pub job_retention_sec: u64,
+
+    /// Most files a session may index; larger walks are aborted
+    #[serde(default = "default_max_files_per_session")]
+    pub max_files_per_session: usize,
+
+    /// Most source bytes (in megabytes) a session may index
+    #[serde(default = "default_max_session_size_mb")]
+    pub max_session_size_mb: u64,
+
+    /// Files larger than this (in megabytes) are read and chunked in
+    /// bounded pieces instead of whole
+    #[serde(default = "default_stream_threshold_mb")]
+    pub stream_threshold_mb: u64,
+
+    /// Files with a longer line (in characters) are skipped; 0 disables
+    #[serde(default = "default_max_line_length")]
+    pub max_line_length: usize,
+
+    /// Most files one list_dir call may return
+    #[serde(default = "default_list_dir_max")]
+    pub list_dir_max: usize,
+
+    /// Failed opens of a session within `open_failure_window_sec` after
+    /// which its opens fail fast for a while; 0 disables
+    #[serde(default = "default_open_failure_threshold")]
+    pub open_failure_threshold: u32,
+
+    /// How far back failed opens are counted, in seconds
+    #[serde(default = "default_open_failure_window")]
+    pub open_failure_window_sec: u64,
+
+    /// How long a session's opens fail fast before one is tried again,
+    /// in seconds
+    #[serde(default = "default_open_failure_cooldown")]
+    pub open_failure_cooldown_sec: u64,
 }
 
 impl Default for LimitsConfig {
@@ -247,10 +476,342 @@ impl Default for LimitsConfig {
         Self {
             max_concurrent_indexes: default_max_concurrent_indexes(),
             request_timeout_sec: default_request_timeout(),
+            git_timeout_sec: default_git_timeout(),
+            tool_timeout_sec: default_tool_timeout(),
+            job_retention_sec: default_job_retention(),
+            max_files_per_session: default_max_files_per_session(),
+            max_session_size_mb: default_max_session_size_mb(),
+            stream_threshold_mb: default_stream_threshold_mb(),
+            max_line_length: default_max_line_length(),
+            list_dir_max: default_list_dir_max(),
+            open_failure_threshold: default_open_failure_threshold(),
+            open_failure_window_sec: default_open_failure_window(),
+            open_failure_cooldown_sec: default_open_failure_cooldown(),
+        }
+    }
+}
+
+/// Readiness and session health configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HealthConfig {
+    /// Session opened by the readiness check (skipped when unset)
+    #[serde(default)]
+    pub canary_session: Option<String>,
+
+    /// Days after which `get_session_info` reports a session as stale
+    #[serde(default = "default_stale_after_days")]
+    pub stale_after_days: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            canary_session: None,
+            stale_after_days: default_stale_after_days(),
+        }
+    }
+}
+
+/// Tool output configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OutputConfig {
+    /// Template for "open in editor" links on result paths,
+    /// e.g. `vscode://file/{path}:{line}` (unset or "none" disables links)
+    #[serde(default)]
+    pub link_format: Option<String>,
+
+    /// Show result paths relative to the session's repository path
+    #[serde(default)]
+    pub relative_paths: bool,
+
+    /// Characters of a line `preview_chunk` shows before cutting it
+    /// (minified bundles); 0 shows lines in full
+    #[serde(default = "default_max_line_chars")]
+    pub max_line_chars: usize,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            link_format: None,
+            relative_paths: false,
+            max_line_chars: default_max_line_chars(),
+        }
+    }
+}
+
+fn default_max_line_chars() -> usize {
+    300
+}
+
+/// MCP server configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpConfig {
+    /// Tool name -> enabled (`[mcp.tools]`); tools not listed are enabled
+    #[serde(default)]
+    pub tools: BTreeMap<String, bool>,
+
+    /// Serve MCP over TCP on this address (e.g. `0.0.0.0:7400`) instead
+    /// of stdio
+    #[serde(default)]
+    pub listen: Option<String>,
+
+    /// Token TCP clients must send as `Authorization: Bearer <token>`
+    /// before any request (unset accepts every connection)
+    #[serde(default)]
+    pub auth_token: Option<String>,
+
+    /// Count calls, errors and latency per tool, persisted in the XDG
+    /// state directory and shown by `get_server_info`
+    #[serde(default = "default_usage_stats")]
+    pub usage_stats: bool,
+
+    /// Capabilities advertised in the initialize handshake
+    /// (`[mcp.capabilities]`)
+    #[serde(default)]
+    pub capabilities: McpCapabilitiesConfig,
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            tools: BTreeMap::new(),
+            listen: None,
+            auth_token: None,
+            usage_stats: default_usage_stats(),
+            capabilities: McpCapabilitiesConfig::default(),
         }
     }
 }
 
+fn default_usage_stats() -> bool {
+    true
+}
+
+/// Optional capabilities of the MCP initialize handshake
+///
+/// `tools` is always advertised; these only add to it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpCapabilitiesConfig {
+    /// Advertise `experimental.shebe`: which optional features this
+    /// server was built and configured with (read-only mode, gRPC,
+    /// usage statistics, scheduler, idle maintenance)
+    #[serde(default = "default_experimental_capability")]
+    pub experimental: bool,
+}
+
+impl Default for McpCapabilitiesConfig {
+    fn default() -> Self {
+        Self {
+            experimental: default_experimental_capability(),
+        }
+    }
+}
+
+fn default_experimental_capability() -> bool {
+    true
+}
+
+impl McpConfig {
+    /// Whether the tool may be listed and called
+    pub fn tool_enabled(&self, name: &str) -> bool {
+        self.tools.get(name).copied().unwrap_or(true)
+    }
+
+    /// Tools disabled in `[mcp.tools]`, sorted by name
+    pub fn disabled_tools(&self) -> Vec<&str> {
+        self.tools
+            .iter()
+            .filter(|(_, enabled)| !**enabled)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Parsed `listen` address, if set
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if the address is not `ip:port`.
+    pub fn listen_addr(&self) -> Result<Option<SocketAddr>> {
+        self.listen
+            .as_deref()
+            .map(|listen| {
+                listen.parse().map_err(|_| {
+                    ShebeError::ConfigError(format!(
+                        "mcp.listen must be an ip:port address, got '{listen}'"
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Scheduled re-index configuration (see [`crate::core::schedule`])
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduleConfig {
+    /// Run the reindex scheduler in the MCP server
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Seconds between checks for due sessions
+    #[serde(default = "default_schedule_tick")]
+    pub tick_sec: u64,
+
+    /// Session ID -> schedule (`[schedule.sessions]`), e.g. `"6h"` or
+    /// `"daily@03:00"`; `set_reindex_schedule` overrides an entry
+    #[serde(default)]
+    pub sessions: BTreeMap<String, String>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tick_sec: default_schedule_tick(),
+            sessions: BTreeMap::new(),
+        }
+    }
+}
+
+impl ScheduleConfig {
+    /// Configured schedule of a session (`None` if unlisted or invalid)
+    pub fn schedule_for(&self, session: &str) -> Option<Schedule> {
+        Schedule::parse(self.sessions.get(session)?).ok()
+    }
+}
+
+/// Idle-time index maintenance (see [`crate::core::maintenance`])
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaintenanceConfig {
+    /// Optimize sessions in the MCP server while no tools are called
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Minutes without a tool call before sessions are optimized
+    #[serde(default = "default_maintenance_idle_minutes")]
+    pub idle_minutes: u64,
+
+    /// Segments each session is merged down to
+    #[serde(default = "default_target_segments")]
+    pub target_segments: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: default_maintenance_idle_minutes(),
+            target_segments: default_target_segments(),
+        }
+    }
+}
+
+/// Logging configuration (see [`crate::core::logging`])
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogConfig {
+    /// Log line format: `text` (default) or `json`
+    #[serde(default)]
+    pub format: LogFormat,
+
+    /// Tool calls taking at least this long are logged at WARN (0 disables)
+    #[serde(default = "default_slow_query_ms")]
+    pub slow_query_ms: u64,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            slow_query_ms: default_slow_query_ms(),
+        }
+    }
+}
+
+/// Deployment-wide server settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerConfig {
+    /// Refuse every operation that changes stored sessions (indexing,
+    /// re-indexing, deletion, upgrades, compaction, schedules)
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Serve the gRPC Search service on this port next to MCP (needs
+    /// the `grpc` feature; unset disables it)
+    #[serde(default)]
+    pub grpc_port: Option<u16>,
+
+    /// IP address the gRPC service binds to
+    #[serde(default = "default_grpc_host")]
+    pub grpc_host: String,
+
+    /// Token gRPC callers must send as `authorization: Bearer <token>`
+    /// metadata (unset accepts every call)
+    #[serde(default)]
+    pub grpc_auth_token: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            grpc_port: None,
+            grpc_host: default_grpc_host(),
+            grpc_auth_token: None,
+        }
+    }
+}
+
+fn default_grpc_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl ServerConfig {
+    /// Address to serve gRPC on, `None` when `grpc_port` is unset
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if `grpc_host` is not an IP address.
+    pub fn grpc_addr(&self) -> Result<Option<SocketAddr>> {
+        let Some(port) = self.grpc_port else {
+            return Ok(None);
+        };
+        let ip: IpAddr = self.grpc_host.parse().map_err(|_| {
+            ShebeError::ConfigError(format!(
+                "server.grpc_host must be an IP address, got '{}'",
+                self.grpc_host
+            ))
+        })?;
+        Ok(Some(SocketAddr::new(ip, port)))
+    }
+}
+
+/// Limits on what tools may read from disk (see [`crate::core::access`])
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SecurityConfig {
+    /// Directories read_file, preview_chunk and sample_files may read
+    /// files under (unset: the repository paths of all sessions)
+    #[serde(default)]
+    pub allowed_roots: Vec<PathBuf>,
+}
+
+/// A named storage root (`[profiles.<name>]`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    /// Storage root of the profile (`storage.index_dir` when selected).
+    /// Relative paths are resolved against the XDG data directory
+    /// (default: `profiles/<name>` there)
+    #[serde(default)]
+    pub storage_root: Option<PathBuf>,
+}
+
+/// A storage root served as a session namespace (`[namespaces.<name>]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NamespaceConfig {
+    /// Storage root of the namespace's sessions. Relative paths are
+    /// resolved against the XDG data directory
+    pub storage_root: PathBuf,
+}
+
 impl Config {
     /// Load configuration from TOML file
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
@@ -283,38 +844,116 @@ impl Config {
     /// 3. Legacy ./shebe.toml (for backward compatibility)
     /// 4. Defaults
     pub fn load_with_xdg(xdg: &XdgDirs) -> Result<Self> {
+        Self::load_with_profile(xdg, None)
+    }
+
+    /// File the configuration is loaded from, or `None` for defaults
+    ///
+    /// `SHEBE_CONFIG` (legacy), then the XDG config file, then
+    /// `./shebe.toml` for backward compatibility.
+    pub fn source_file(xdg: &XdgDirs) -> Option<PathBuf> {
+        if let Ok(config_path) = env::var("SHEBE_CONFIG") {
+            return Some(PathBuf::from(config_path));
+        }
+        let xdg_config = xdg.config_file();
+        if xdg_config.exists() {
+            return Some(xdg_config);
+        }
+        let legacy = PathBuf::from("shebe.toml");
+        legacy.exists().then_some(legacy)
+    }
+
+    /// Load config with explicit XDG directories and storage profile
+    ///
+    /// `profile` (`--profile`) takes precedence over `SHEBE_PROFILE`;
+    /// with neither, storage uses the default root (see
+    /// [`Config::select_profile`]).
+    pub fn load_with_profile(xdg: &XdgDirs, profile: Option<&str>) -> Result<Self> {
         // Start with defaults
-        let mut config = if let Ok(config_path) = env::var("SHEBE_CONFIG") {
-            // Load from file if SHEBE_CONFIG is set (legacy)
-            Self::from_file(config_path)?
-        } else {
-            // Try XDG config file
-            let xdg_config = xdg.config_file();
-            if xdg_config.exists() {
-                Self::from_file(xdg_config)?
-            } else if Path::new("shebe.toml").exists() {
-                // Fall back to legacy location for backward compatibility
-                Self::from_file("shebe.toml")?
-            } else {
-                // Use defaults
-                Self::default()
-            }
+        let mut config = match Self::source_file(xdg) {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
         };
 
         // Override storage path with XDG data directory if not explicitly set
         if env::var("SHEBE_DATA_DIR").is_err() && config.storage.index_dir == default_index_dir() {
             config.storage.index_dir = xdg.sessions_dir();
         }
+        if config.storage.clone_dir == default_clone_dir() {
+            config.storage.clone_dir = xdg.clones_dir();
+        }
 
         // Override with environment variables
         config.merge_env();
 
+        // Point storage at the selected profile
+        config.apply_profile(profile, xdg)?;
+
+        // Relative namespace roots live in the XDG data directory
+        for namespace in config.namespaces.values_mut() {
+            namespace.storage_root = xdg.data_dir.join(&namespace.storage_root);
+        }
+
         // Validate configuration
         config.validate()?;
 
         Ok(config)
     }
 
+    /// Select `profile`, or the one named by `SHEBE_PROFILE` if `None`
+    ///
+    /// With neither, storage keeps the default root.
+    pub fn apply_profile(&mut self, profile: Option<&str>, xdg: &XdgDirs) -> Result<()> {
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| env::var("SHEBE_PROFILE").ok().filter(|p| !p.is_empty()));
+        match profile {
+            Some(profile) => self.select_profile(&profile, xdg),
+            None => Ok(()),
+        }
+    }
+
+    /// Store sessions under the root of profile `name`
+    ///
+    /// Replaces `storage.index_dir` (including `SHEBE_DATA_DIR`) with the
+    /// profile's storage root.
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` listing the configured profiles if `name` is not
+    /// one of them.
+    pub fn select_profile(&mut self, name: &str, xdg: &XdgDirs) -> Result<()> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let available = if self.profiles.is_empty() {
+                "none configured".to_string()
+            } else {
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            };
+            ShebeError::ConfigError(format!("Unknown profile '{name}' (available: {available})"))
+        })?;
+
+        let root = profile
+            .storage_root
+            .clone()
+            .unwrap_or_else(|| Path::new("profiles").join(name));
+        self.storage.index_dir = xdg.data_dir.join(root);
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Name of the active profile (`default` when none was selected)
+    pub fn profile_name(&self) -> &str {
+        self.profile.as_deref().unwrap_or("default")
+    }
+
+    /// Storage roots of the configured namespaces, by name
+    pub fn namespace_roots(&self) -> BTreeMap<String, PathBuf> {
+        self.namespaces
+            .iter()
+            .map(|(name, namespace)| (name.clone(), namespace.storage_root.clone()))
+            .collect()
+    }
+
     /// Merge configuration with environment variables
     pub fn merge_env(&mut self) {
         // Indexing configuration
@@ -333,12 +972,22 @@ impl Config {
                 self.indexing.max_file_size_mb = size;
             }
         }
+        if let Ok(git_metadata) = env::var("SHEBE_GIT_METADATA") {
+            if let Ok(enabled) = git_metadata.parse() {
+                self.indexing.git_metadata = enabled;
+            }
+        }
 
         // Storage configuration
         if let Ok(data_dir) = env::var("SHEBE_DATA_DIR") {
             self.storage.index_dir = PathBuf::from(data_dir).join("sessions");
         }
-
+        if let Ok(reindex) = env::var("SHEBE_REINDEX_ON_CORRUPTION") {
+            if let Ok(r) = reindex.parse() {
+                self.storage.reindex_on_corruption = r;
+            }
+        }
+
         // Search configuration
         if let Ok(default_k) = env::var("SHEBE_DEFAULT_K") {
             if let Ok(k) = default_k.parse() {
@@ -355,6 +1004,16 @@ impl Config {
                 self.search.max_query_length = len;
             }
         }
+        if let Ok(path_boost) = env::var("SHEBE_PATH_BOOST") {
+            if let Ok(boost) = path_boost.parse() {
+                self.search.path_boost = boost;
+            }
+        }
+        if let Ok(penalize) = env::var("SHEBE_PENALIZE_SHORT_CHUNKS") {
+            if let Ok(p) = penalize.parse() {
+                self.search.penalize_short_chunks = p;
+            }
+        }
 
         // Limits configuration
         if let Ok(max_concurrent) = env::var("SHEBE_MAX_CONCURRENT_INDEXES") {
@@ -367,6 +1026,152 @@ impl Config {
                 self.limits.request_timeout_sec = t;
             }
         }
+        if let Ok(timeout) = env::var("SHEBE_GIT_TIMEOUT_SEC") {
+            if let Ok(t) = timeout.parse() {
+                self.limits.git_timeout_sec = t;
+            }
+        }
+        if let Ok(timeout) = env::var("SHEBE_TOOL_TIMEOUT_SEC") {
+            if let Ok(t) = timeout.parse() {
+                self.limits.tool_timeout_sec = t;
+            }
+        }
+        if let Ok(retention) = env::var("SHEBE_JOB_RETENTION_SEC") {
+            if let Ok(r) = retention.parse() {
+                self.limits.job_retention_sec = r;
+            }
+        }
+        if let Ok(max) = env::var("SHEBE_MAX_FILES_PER_SESSION") {
+            if let Ok(m) = max.parse() {
+                self.limits.max_files_per_session = m;
+            }
+        }
+        if let Ok(max) = env::var("SHEBE_MAX_SESSION_SIZE_MB") {
+            if let Ok(m) = max.parse() {
+                self.limits.max_session_size_mb = m;
+            }
+        }
+        if let Ok(threshold) = env::var("SHEBE_STREAM_THRESHOLD_MB") {
+            if let Ok(t) = threshold.parse() {
+                self.limits.stream_threshold_mb = t;
+            }
+        }
+        if let Ok(max) = env::var("SHEBE_MAX_LINE_LENGTH") {
+            if let Ok(m) = max.parse() {
+                self.limits.max_line_length = m;
+            }
+        }
+        if let Ok(max) = env::var("SHEBE_LIST_DIR_MAX") {
+            if let Ok(m) = max.parse() {
+                self.limits.list_dir_max = m;
+            }
+        }
+        if let Ok(threshold) = env::var("SHEBE_OPEN_FAILURE_THRESHOLD") {
+            if let Ok(t) = threshold.parse() {
+                self.limits.open_failure_threshold = t;
+            }
+        }
+        if let Ok(window) = env::var("SHEBE_OPEN_FAILURE_WINDOW_SEC") {
+            if let Ok(w) = window.parse() {
+                self.limits.open_failure_window_sec = w;
+            }
+        }
+        if let Ok(cooldown) = env::var("SHEBE_OPEN_FAILURE_COOLDOWN_SEC") {
+            if let Ok(c) = cooldown.parse() {
+                self.limits.open_failure_cooldown_sec = c;
+            }
+        }
+
+        // Health configuration
+        if let Ok(canary) = env::var("SHEBE_CANARY_SESSION") {
+            self.health.canary_session = Some(canary).filter(|c| !c.is_empty());
+        }
+        if let Ok(days) = env::var("SHEBE_STALE_AFTER_DAYS") {
+            if let Ok(d) = days.parse() {
+                self.health.stale_after_days = d;
+            }
+        }
+
+        // Output configuration
+        if let Ok(link_format) = env::var("SHEBE_LINK_FORMAT") {
+            self.output.link_format = Some(link_format).filter(|f| !f.is_empty());
+        }
+        if let Ok(relative) = env::var("SHEBE_RELATIVE_PATHS") {
+            if let Ok(r) = relative.parse() {
+                self.output.relative_paths = r;
+            }
+        }
+        if let Ok(max_line_chars) = env::var("SHEBE_MAX_LINE_CHARS") {
+            if let Ok(m) = max_line_chars.parse() {
+                self.output.max_line_chars = m;
+            }
+        }
+
+        // MCP configuration
+        if let Ok(listen) = env::var("SHEBE_MCP_LISTEN") {
+            self.mcp.listen = Some(listen).filter(|l| !l.is_empty());
+        }
+        if let Ok(token) = env::var("SHEBE_MCP_AUTH_TOKEN") {
+            self.mcp.auth_token = Some(token).filter(|t| !t.is_empty());
+        }
+        if let Ok(usage_stats) = env::var("SHEBE_MCP_USAGE_STATS") {
+            if let Ok(enabled) = usage_stats.parse() {
+                self.mcp.usage_stats = enabled;
+            }
+        }
+
+        // Schedule configuration
+        if let Ok(enabled) = env::var("SHEBE_SCHEDULE_ENABLED") {
+            if let Ok(e) = enabled.parse() {
+                self.schedule.enabled = e;
+            }
+        }
+
+        // Maintenance configuration
+        if let Ok(enabled) = env::var("SHEBE_MAINTENANCE_ENABLED") {
+            if let Ok(e) = enabled.parse() {
+                self.maintenance.enabled = e;
+            }
+        }
+
+        // Server configuration
+        if let Ok(read_only) = env::var("SHEBE_READ_ONLY") {
+            if let Ok(r) = read_only.parse() {
+                self.server.read_only = r;
+            }
+        }
+        if let Ok(port) = env::var("SHEBE_GRPC_PORT") {
+            if port.is_empty() {
+                self.server.grpc_port = None;
+            } else if let Ok(p) = port.parse() {
+                self.server.grpc_port = Some(p);
+            }
+        }
+        if let Ok(host) = env::var("SHEBE_GRPC_HOST") {
+            self.server.grpc_host = host;
+        }
+        if let Ok(token) = env::var("SHEBE_GRPC_AUTH_TOKEN") {
+            self.server.grpc_auth_token = Some(token).filter(|t| !t.is_empty());
+        }
+
+        // Security configuration
+        if let Ok(roots) = env::var("SHEBE_ALLOWED_ROOTS") {
+            self.security.allowed_roots = env::split_paths(&roots)
+                .filter(|root| !root.as_os_str().is_empty())
+                .collect();
+        }
+
+        // Log configuration
+        if let Ok(format) = env::var("SHEBE_LOG_FORMAT") {
+            if let Some(f) = LogFormat::parse(&format) {
+                self.log.format = f;
+            }
+        }
+        if let Ok(slow_query_ms) = env::var("SHEBE_SLOW_QUERY_MS") {
+            if let Ok(ms) = slow_query_ms.parse() {
+                self.log.slow_query_ms = ms;
+            }
+        }
     }
 
     /// Validate configuration values
@@ -403,6 +1208,14 @@ impl Config {
             ));
         }
 
+        if !self.search.path_boost.is_finite() || self.search.path_boost < 0.0 {
+            return Err(ShebeError::ConfigError(
+                "Path boost must be a non-negative number".to_string(),
+            ));
+        }
+
+        self.search.short_chunk_penalty().validate()?;
+
         // Validate limits config
         if self.limits.max_concurrent_indexes == 0 {
             return Err(ShebeError::ConfigError(
@@ -416,6 +1229,139 @@ impl Config {
             ));
         }
 
+        if self.limits.git_timeout_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Git timeout must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.tool_timeout_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Tool timeout must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.job_retention_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Job retention must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.max_files_per_session == 0 {
+            return Err(ShebeError::ConfigError(
+                "Max files per session must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.max_session_size_mb == 0 {
+            return Err(ShebeError::ConfigError(
+                "Max session size must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.stream_threshold_mb == 0 {
+            return Err(ShebeError::ConfigError(
+                "Stream threshold must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.list_dir_max == 0 {
+            return Err(ShebeError::ConfigError(
+                "List dir max must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.open_failure_window_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Open failure window must be non-zero".to_string(),
+            ));
+        }
+
+        if self.limits.open_failure_cooldown_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Open failure cooldown must be non-zero".to_string(),
+            ));
+        }
+
+        if let Some(root) = self
+            .security
+            .allowed_roots
+            .iter()
+            .find(|root| !root.is_absolute())
+        {
+            return Err(ShebeError::ConfigError(format!(
+                "security.allowed_roots must be absolute paths, got '{}'",
+                root.display()
+            )));
+        }
+
+        // Validate namespaces
+        let mut roots = vec![&self.storage.index_dir];
+        for (name, namespace) in &self.namespaces {
+            if name == DEFAULT_NAMESPACE || !is_valid_name(name) {
+                return Err(ShebeError::ConfigError(format!(
+                    "Invalid namespace name '{name}': use alphanumeric, hyphen or underscore \
+                     characters, and not '{DEFAULT_NAMESPACE}'"
+                )));
+            }
+            if roots.contains(&&namespace.storage_root) {
+                return Err(ShebeError::ConfigError(format!(
+                    "Namespace '{name}' shares its storage root {} with another namespace",
+                    namespace.storage_root.display()
+                )));
+            }
+            roots.push(&namespace.storage_root);
+        }
+        let default_ns = &self.storage.default_namespace;
+        if default_ns != DEFAULT_NAMESPACE && !self.namespaces.contains_key(default_ns) {
+            return Err(ShebeError::ConfigError(format!(
+                "Default namespace '{default_ns}' is not configured in [namespaces]"
+            )));
+        }
+
+        // Validate health config
+        if self.health.stale_after_days == 0 {
+            return Err(ShebeError::ConfigError(
+                "Stale after days must be non-zero".to_string(),
+            ));
+        }
+
+        // Validate output config
+        if let Some(link_format) = &self.output.link_format {
+            LinkTemplate::parse(link_format).map_err(ShebeError::ConfigError)?;
+        }
+
+        // Validate MCP config
+        self.mcp.listen_addr()?;
+
+        // Validate server config
+        self.server.grpc_addr()?;
+
+        // Validate schedule config
+        if self.schedule.tick_sec == 0 {
+            return Err(ShebeError::ConfigError(
+                "Schedule tick must be non-zero".to_string(),
+            ));
+        }
+
+        for (session, expression) in &self.schedule.sessions {
+            Schedule::parse(expression).map_err(|e| {
+                ShebeError::ConfigError(format!("Schedule for session '{session}': {e}"))
+            })?;
+        }
+
+        // Validate maintenance config
+        if self.maintenance.idle_minutes == 0 {
+            return Err(ShebeError::ConfigError(
+                "Maintenance idle minutes must be non-zero".to_string(),
+            ));
+        }
+        if self.maintenance.target_segments == 0 {
+            return Err(ShebeError::ConfigError(
+                "Maintenance target segments must be non-zero".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -425,6 +1371,14 @@ impl Config {
         tracing::info!("  Chunk size: {} chars", self.indexing.chunk_size);
         tracing::info!("  Overlap: {} chars", self.indexing.overlap);
         tracing::info!("  Max file size: {} MB", self.indexing.max_file_size_mb);
+        tracing::info!(
+            "  Git metadata: {}",
+            if self.indexing.git_metadata {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
         tracing::info!(
             "  Include patterns: {} patterns",
             self.indexing.include_patterns.len()
@@ -433,15 +1387,85 @@ impl Config {
             "  Exclude patterns: {} patterns",
             self.indexing.exclude_patterns.len()
         );
+        tracing::info!("  Profile: {}", self.profile_name());
         tracing::info!("  Index dir: {:?}", self.storage.index_dir);
+        tracing::info!("  Clone dir: {:?}", self.storage.clone_dir);
         tracing::info!("  Default k: {}", self.search.default_k);
         tracing::info!("  Max k: {}", self.search.max_k);
         tracing::info!("  Max query length: {}", self.search.max_query_length);
+        tracing::info!("  Path boost: {}", self.search.path_boost);
+        tracing::info!(
+            "  Short chunk penalty: {}",
+            if self.search.penalize_short_chunks {
+                format!(
+                    "x{} below {}% of chunk size",
+                    self.search.short_chunk_factor,
+                    self.search.short_chunk_ratio * 100.0
+                )
+            } else {
+                "disabled".to_string()
+            }
+        );
         tracing::info!(
             "  Max concurrent indexes: {}",
             self.limits.max_concurrent_indexes
         );
         tracing::info!("  Request timeout: {}s", self.limits.request_timeout_sec);
+        tracing::info!("  Git timeout: {}s", self.limits.git_timeout_sec);
+        tracing::info!("  Tool timeout: {}s", self.limits.tool_timeout_sec);
+        tracing::info!("  Job retention: {}s", self.limits.job_retention_sec);
+        tracing::info!(
+            "  Max files per session: {}",
+            self.limits.max_files_per_session
+        );
+        tracing::info!("  Max session size: {} MB", self.limits.max_session_size_mb);
+        tracing::info!("  Stream threshold: {} MB", self.limits.stream_threshold_mb);
+        if self.limits.max_line_length == 0 {
+            tracing::info!("  Max line length: unlimited");
+        } else {
+            tracing::info!("  Max line length: {} chars", self.limits.max_line_length);
+        }
+        tracing::info!("  List dir max: {} files", self.limits.list_dir_max);
+        tracing::info!(
+            "  Link format: {}",
+            self.output.link_format.as_deref().unwrap_or("none")
+        );
+        tracing::info!("  Relative paths: {}", self.output.relative_paths);
+        tracing::info!("  Max line chars: {}", self.output.max_line_chars);
+        let disabled = self.mcp.disabled_tools();
+        if !disabled.is_empty() {
+            tracing::info!("  Disabled MCP tools: {}", disabled.join(", "));
+        }
+        tracing::info!(
+            "  Reindex scheduler: {} ({} configured session(s))",
+            if self.schedule.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            self.schedule.sessions.len()
+        );
+        if self.maintenance.enabled {
+            tracing::info!(
+                "  Idle maintenance: after {} idle minute(s), down to {} segment(s)",
+                self.maintenance.idle_minutes,
+                self.maintenance.target_segments
+            );
+        } else {
+            tracing::info!("  Idle maintenance: disabled");
+        }
+        if self.server.read_only {
+            tracing::info!("  Read-only mode: enabled");
+        }
+        if let Some(port) = self.server.grpc_port {
+            tracing::info!("  gRPC: {}:{}", self.server.grpc_host, port);
+        }
+        tracing::info!("  Log format: {}", self.log.format);
+        if self.log.slow_query_ms == 0 {
+            tracing::info!("  Slow query log: disabled");
+        } else {
+            tracing::info!("  Slow query log: {}ms", self.log.slow_query_ms);
+        }
     }
 }
 
@@ -539,6 +1563,14 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.limits.max_concurrent_indexes, 1);
         assert_eq!(config.limits.request_timeout_sec, 300);
+        assert_eq!(config.limits.git_timeout_sec, 300);
+        assert_eq!(config.limits.tool_timeout_sec, 60);
+        assert_eq!(config.limits.job_retention_sec, 3600);
+        assert_eq!(config.limits.max_files_per_session, 200_000);
+        assert_eq!(config.limits.max_session_size_mb, 4096);
+        assert_eq!(config.limits.stream_threshold_mb, 8);
+        assert_eq!(config.limits.max_line_length, 1_000_000);
+        assert_eq!(config.limits.list_dir_max, 500);
     }
 
     #[test]
@@ -547,6 +1579,35 @@ mod tests {
         assert_eq!(config.search.max_query_length, 500);
     }
 
+    #[test]
+    fn test_path_boost_validation() {
+        let mut config = Config::default();
+        assert_eq!(config.search.path_boost, 2.0);
+
+        config.search.path_boost = 0.0;
+        assert!(config.validate().is_ok());
+
+        config.search.path_boost = -1.0;
+        assert!(config.validate().is_err());
+
+        config.search.path_boost = f32::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_short_chunk_penalty_validation() {
+        let mut config = Config::default();
+        assert!(config.search.penalize_short_chunks);
+        assert_eq!(config.search.short_chunk_ratio, 0.15);
+
+        config.search.short_chunk_ratio = 1.5;
+        assert!(config.validate().is_err());
+
+        config.search.short_chunk_ratio = 0.15;
+        config.search.short_chunk_factor = 0.0;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_limits_validation() {
         let mut config = Config::default();
@@ -556,5 +1617,341 @@ mod tests {
         config = Config::default();
         config.limits.request_timeout_sec = 0;
         assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.git_timeout_sec = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.job_retention_sec = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.max_files_per_session = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.max_session_size_mb = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.stream_threshold_mb = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.list_dir_max = 0;
+        assert!(config.validate().is_err());
+
+        config = Config::default();
+        config.limits.tool_timeout_sec = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_health_config() {
+        let config = Config::default();
+        assert!(config.health.canary_session.is_none());
+        assert_eq!(config.health.stale_after_days, 7);
+
+        let toml = r#"
+            [health]
+            canary_session = "main-repo"
+            stale_after_days = 30
+        "#;
+        let mut config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.health.canary_session.as_deref(), Some("main-repo"));
+        assert_eq!(config.health.stale_after_days, 30);
+
+        config.health.stale_after_days = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_output_config() {
+        let config = Config::default();
+        assert!(config.output.link_format.is_none());
+        assert!(!config.output.relative_paths);
+        assert_eq!(config.output.max_line_chars, 300);
+
+        let toml = r#"
+            [output]
+            link_format = "vscode://file/{path}:{line}"
+            relative_paths = true
+            max_line_chars = 120
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.output.link_format.as_deref(),
+            Some("vscode://file/{path}:{line}")
+        );
+        assert!(config.output.relative_paths);
+        assert_eq!(config.output.max_line_chars, 120);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_output_link_format_validation() {
+        let mut config = Config::default();
+        config.output.link_format = Some("none".to_string());
+        assert!(config.validate().is_ok());
+
+        config.output.link_format = Some("vscode://file/{file}".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_mcp_tools_config() {
+        let config = Config::default();
+        assert!(config.mcp.tools.is_empty());
+        assert!(config.mcp.tool_enabled("read_file"));
+
+        let toml = r#"
+            [mcp.tools]
+            read_file = false
+            list_dir = false
+            search_code = true
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(!config.mcp.tool_enabled("read_file"));
+        assert!(config.mcp.tool_enabled("search_code"));
+        assert!(config.mcp.tool_enabled("find_file"));
+        assert_eq!(config.mcp.disabled_tools(), vec!["list_dir", "read_file"]);
+    }
+
+    #[test]
+    fn test_mcp_capabilities_config() {
+        assert!(Config::default().mcp.capabilities.experimental);
+
+        let config: Config = toml::from_str("[mcp.capabilities]\nexperimental = false\n").unwrap();
+        assert!(!config.mcp.capabilities.experimental);
+        assert!(config.mcp.usage_stats);
+    }
+
+    #[test]
+    fn test_mcp_listen_config() {
+        let config = Config::default();
+        assert_eq!(config.mcp.listen_addr().unwrap(), None);
+
+        let toml = r#"
+            [mcp]
+            listen = "0.0.0.0:7400"
+            auth_token = "s3cret"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.mcp.listen_addr().unwrap(),
+            Some("0.0.0.0:7400".parse().unwrap())
+        );
+        assert_eq!(config.mcp.auth_token.as_deref(), Some("s3cret"));
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::default();
+        config.mcp.listen = Some("localhost".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("mcp.listen"));
+    }
+
+    #[test]
+    fn test_server_read_only_config() {
+        assert!(!Config::default().server.read_only);
+
+        let config: Config = toml::from_str("[server]\nread_only = true\n").unwrap();
+        assert!(config.server.read_only);
+    }
+
+    #[test]
+    fn test_security_allowed_roots_config() {
+        assert!(Config::default().security.allowed_roots.is_empty());
+
+        let toml = "[security]\nallowed_roots = [\"/home/dev/src\", \"/srv/repos\"]\n";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.security.allowed_roots,
+            vec![PathBuf::from("/home/dev/src"), PathBuf::from("/srv/repos")]
+        );
+        assert!(config.validate().is_ok());
+
+        let config: Config = toml::from_str("[security]\nallowed_roots = [\"src\"]\n").unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("must be absolute"), "{err}");
+    }
+
+    #[test]
+    fn test_server_grpc_config() {
+        let config = Config::default();
+        assert_eq!(config.server.grpc_addr().unwrap(), None);
+
+        let toml = r#"
+            [server]
+            grpc_port = 7500
+            grpc_auth_token = "s3cret"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.server.grpc_addr().unwrap(),
+            Some("127.0.0.1:7500".parse().unwrap())
+        );
+        assert_eq!(config.server.grpc_auth_token.as_deref(), Some("s3cret"));
+
+        let mut config = Config::default();
+        config.server.grpc_port = Some(7500);
+        config.server.grpc_host = "localhost".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("server.grpc_host"));
+    }
+
+    #[test]
+    fn test_schedule_config() {
+        let config = Config::default();
+        assert!(!config.schedule.enabled);
+        assert_eq!(config.schedule.tick_sec, 60);
+
+        let toml = r#"
+            [schedule]
+            enabled = true
+
+            [schedule.sessions]
+            backend = "6h"
+            docs = "daily@03:00"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.schedule.enabled);
+        assert_eq!(
+            config.schedule.schedule_for("backend"),
+            Some(Schedule::Every { minutes: 360 })
+        );
+        assert_eq!(config.schedule.schedule_for("other"), None);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_schedule_validation() {
+        let mut config = Config::default();
+        config
+            .schedule
+            .sessions
+            .insert("backend".to_string(), "every 6 hours".to_string());
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.schedule.tick_sec = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_maintenance_config() {
+        let config = Config::default();
+        assert!(!config.maintenance.enabled);
+        assert_eq!(config.maintenance.idle_minutes, 30);
+        assert_eq!(config.maintenance.target_segments, 1);
+
+        let toml = "[maintenance]\nenabled = true\nidle_minutes = 5\ntarget_segments = 3\n";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.maintenance.enabled);
+        assert_eq!(config.maintenance.idle_minutes, 5);
+        assert_eq!(config.maintenance.target_segments, 3);
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::default();
+        config.maintenance.idle_minutes = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.maintenance.target_segments = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let xdg = XdgDirs {
+            config_dir: PathBuf::from("/xdg/config"),
+            data_dir: PathBuf::from("/xdg/data"),
+            state_dir: PathBuf::from("/xdg/state"),
+            cache_dir: PathBuf::from("/xdg/cache"),
+        };
+        let toml = r#"
+            [profiles.work]
+            storage_root = "/srv/shebe-work"
+
+            [profiles.oss]
+        "#;
+        let mut config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.profile_name(), "default");
+
+        config.select_profile("work", &xdg).unwrap();
+        assert_eq!(config.profile_name(), "work");
+        assert_eq!(config.storage.index_dir, PathBuf::from("/srv/shebe-work"));
+
+        config.select_profile("oss", &xdg).unwrap();
+        assert_eq!(
+            config.storage.index_dir,
+            PathBuf::from("/xdg/data/profiles/oss")
+        );
+
+        let err = config.select_profile("home", &xdg).unwrap_err();
+        assert!(err.to_string().contains("Unknown profile 'home'"));
+        assert!(err.to_string().contains("available: oss, work"));
+        assert_eq!(config.profile_name(), "oss");
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let toml = r#"
+            [storage]
+            index_dir = "/srv/shebe"
+            default_namespace = "team-a"
+
+            [namespaces.team-a]
+            storage_root = "/srv/team-a"
+
+            [namespaces.team-b]
+            storage_root = "/srv/team-b"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.namespace_roots().get("team-b"),
+            Some(&PathBuf::from("/srv/team-b"))
+        );
+
+        let mut shared = config.clone();
+        shared.namespaces.get_mut("team-b").unwrap().storage_root = PathBuf::from("/srv/team-a");
+        let err = shared.validate().unwrap_err().to_string();
+        assert!(err.contains("shares its storage root"), "{err}");
+
+        let mut unknown = config.clone();
+        unknown.storage.default_namespace = "team-c".to_string();
+        assert!(unknown.validate().is_err());
+
+        let mut reserved = config;
+        reserved.namespaces.insert(
+            "default".to_string(),
+            NamespaceConfig {
+                storage_root: PathBuf::from("/srv/other"),
+            },
+        );
+        assert!(reserved.validate().is_err());
+    }
+
+    #[test]
+    fn test_log_config() {
+        let config = Config::default();
+        assert_eq!(config.log.format, LogFormat::Text);
+        assert_eq!(config.log.slow_query_ms, 1000);
+
+        let toml = r#"
+            [log]
+            format = "json"
+            slow_query_ms = 250
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.log.slow_query_ms, 250);
+
+        let toml = r#"
+            [log]
+            format = "xml"
+        "#;
+        assert!(toml::from_str::<Config>(toml).is_err());
     }
 }
@@ -0,0 +1,87 @@
+//! Definition sites of symbols.
+//!
+//! Tells a line that defines a symbol from one that uses it by the
+//! keyword in front of the name: `fn X`, `struct X`, `enum X`,
+//! `trait X`, `class X`, `interface X`, `def X`, `func X` (Go methods
+//! too: `func (s *Server) X`), `function X` and `type X`. Like
+//! [`crate::core::lexical`], this is a heuristic over text, not a
+//! parser; callers skip lines where the symbol sits in a comment or
+//! string.
+
+use regex::Regex;
+
+/// Keywords that introduce the name being defined
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn",
+    "struct",
+    "enum",
+    "trait",
+    "class",
+    "interface",
+    "def",
+    "func",
+    "function",
+    "type",
+];
+
+/// Recognizes lines defining one symbol
+#[derive(Debug, Clone)]
+pub struct DefinitionMatcher {
+    regex: Regex,
+}
+
+impl DefinitionMatcher {
+    /// Matcher for definitions of `symbol`
+    pub fn new(symbol: &str) -> Self {
+        let pattern = format!(
+            r"\b(?:{})\s+(?:\([^)]*\)\s*)?{}(?:\W|$)",
+            DEFINITION_KEYWORDS.join("|"),
+            regex::escape(symbol)
+        );
+        Self {
+            regex: Regex::new(&pattern).expect("escaped symbol forms a valid pattern"),
+        }
+    }
+
+    /// Whether `line` defines the symbol
+    pub fn is_definition(&self, line: &str) -> bool {
+        self.regex.is_match(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_definitions_across_languages() {
+        let matcher = DefinitionMatcher::new("handle_login");
+        for line in [
+            "pub fn handle_login(req: Request) -> Response {",
+            "    async fn handle_login<T>(&self) {}",
+            "def handle_login(request):",
+            "func handle_login(w http.ResponseWriter) {",
+            "func (s *Server) handle_login(w http.ResponseWriter) {",
+            "export function handle_login() {",
+            "pub(crate) struct handle_login;",
+            "class handle_login:",
+            "type handle_login = fn();",
+        ] {
+            assert!(matcher.is_definition(line), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_ignores_usages() {
+        let matcher = DefinitionMatcher::new("handle_login");
+        for line in [
+            "let response = handle_login(req);",
+            "router.get(\"/login\", handle_login);",
+            "use crate::auth::handle_login;",
+            "fn handle_login_v2() {}",
+            "fn other(handle_login: bool) {}",
+        ] {
+            assert!(!matcher.is_definition(line), "{line}");
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Path display for tool output.
+//!
+//! Result paths are stored absolute. [`PathDisplay`] optionally shows
+//! them relative to the session's repository path and adds "open in
+//! editor" links rendered from a [`LinkTemplate`] such as
+//! `vscode://file/{path}:{line}`.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Placeholders a link template may use
+pub const LINK_PLACEHOLDERS: &[&str] = &["{path}", "{line}", "{column}"];
+
+/// Value that disables links (also accepted as a per-request override)
+pub const LINK_FORMAT_NONE: &str = "none";
+
+/// Template for "open in editor" links
+///
+/// `{path}` is replaced with the percent-encoded absolute path,
+/// `{line}` and `{column}` with 1-based positions (1 when unknown).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkTemplate {
+    template: String,
+}
+
+impl LinkTemplate {
+    /// Parse a template, returning `None` for `"none"` or an empty string
+    ///
+    /// A template without `{path}` or with an unknown placeholder is an
+    /// error listing the valid placeholders.
+    pub fn parse(template: &str) -> Result<Option<Self>, String> {
+        let template = template.trim();
+        if template.is_empty() || template.eq_ignore_ascii_case(LINK_FORMAT_NONE) {
+            return Ok(None);
+        }
+        if !template.contains("{path}") {
+            return Err(format!("Link format '{template}' must contain {{path}}"));
+        }
+
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').map(|end| start + end + 1);
+            let placeholder = end.map(|end| &rest[start..end]);
+            match placeholder {
+                Some(p) if LINK_PLACEHOLDERS.contains(&p) => rest = &rest[end.unwrap_or(0)..],
+                _ => {
+                    return Err(format!(
+                        "Link format '{template}' has an unknown placeholder near '{}'. \
+                         Valid placeholders: {}",
+                        &rest[start..],
+                        LINK_PLACEHOLDERS.join(", ")
+                    ))
+                }
+            }
+        }
+
+        Ok(Some(Self {
+            template: template.to_string(),
+        }))
+    }
+
+    /// Whether the template needs a line number
+    pub fn uses_line(&self) -> bool {
+        self.template.contains("{line}") || self.template.contains("{column}")
+    }
+
+    /// Render a link for `path` at an optional position
+    pub fn render(&self, path: &Path, line: Option<usize>, column: Option<usize>) -> String {
+        self.template
+            .replace("{path}", &encode_path(path))
+            .replace("{line}", &line.unwrap_or(1).to_string())
+            .replace("{column}", &column.unwrap_or(1).to_string())
+    }
+}
+
+/// Percent-encode a path for use in a URL, keeping `/` and `:` readable
+fn encode_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Express `path` relative to `root`
+///
+/// Returns `None` when `path` is not inside `root` (including relative
+/// paths and paths that only reach `root` through `..`), so callers
+/// fall back to the path as stored.
+pub fn relative_to(path: &Path, root: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    if relative.as_os_str().is_empty() {
+        return Some(PathBuf::from("."));
+    }
+    Some(relative.to_path_buf())
+}
+
+/// How result paths are shown in tool output
+#[derive(Debug, Clone, Default)]
+pub struct PathDisplay {
+    /// Repository path that displayed paths are relative to (`None` shows paths as stored)
+    relative_root: Option<PathBuf>,
+    /// Repository path used to resolve stored relative paths for links
+    link_root: Option<PathBuf>,
+    link: Option<LinkTemplate>,
+}
+
+impl PathDisplay {
+    /// Display settings for a session rooted at `repository_path`
+    pub fn new(repository_path: Option<&Path>, relative: bool, link: Option<LinkTemplate>) -> Self {
+        Self {
+            relative_root: repository_path.filter(|_| relative).map(Path::to_path_buf),
+            link_root: repository_path.map(Path::to_path_buf),
+            link,
+        }
+    }
+
+    /// Whether links need line numbers (callers may skip computing them otherwise)
+    pub fn wants_line(&self) -> bool {
+        self.link.as_ref().is_some_and(LinkTemplate::uses_line)
+    }
+
+    /// Path as shown to the reader
+    pub fn path(&self, path: &str) -> String {
+        self.relative_root
+            .as_deref()
+            .and_then(|root| relative_to(Path::new(path), root))
+            .map(|relative| relative.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    /// Editor link for `path`, if links are enabled
+    pub fn link(&self, path: &str, line: Option<usize>, column: Option<usize>) -> Option<String> {
+        let link = self.link.as_ref()?;
+        let path = Path::new(path);
+        let absolute = match &self.link_root {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path.to_path_buf(),
+        };
+        Some(link.render(&absolute, line, column))
+    }
+
+    /// Markdown for `label` (already built from [`PathDisplay::path`]) with an optional link
+    ///
+    /// Renders ``[`label`](link)`` when links are enabled and `` `label` `` otherwise.
+    pub fn markdown(
+        &self,
+        path: &str,
+        label: &str,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> String {
+        match self.link(path, line, column) {
+            Some(url) => format!("[`{label}`]({url})"),
+            None => format!("`{label}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vscode() -> Option<LinkTemplate> {
+        LinkTemplate::parse("vscode://file/{path}:{line}:{column}").unwrap()
+    }
+
+    #[test]
+    fn test_link_template_parse() {
+        assert!(LinkTemplate::parse("none").unwrap().is_none());
+        assert!(LinkTemplate::parse("NONE").unwrap().is_none());
+        assert!(LinkTemplate::parse("").unwrap().is_none());
+        assert!(LinkTemplate::parse("file://{path}").unwrap().is_some());
+        assert!(LinkTemplate::parse("idea://open?file={path}&line={line}")
+            .unwrap()
+            .is_some());
+
+        let err = LinkTemplate::parse("vscode://file/{line}").unwrap_err();
+        assert!(err.contains("must contain {path}"));
+        let err = LinkTemplate::parse("vscode://file/{path}:{row}").unwrap_err();
+        assert!(err.contains("unknown placeholder near '{row}'"));
+        assert!(LinkTemplate::parse("vscode://file/{path}:{line").is_err());
+    }
+
+    #[test]
+    fn test_link_template_render() {
+        let link = vscode().unwrap();
+        assert!(link.uses_line());
+        assert_eq!(
+            link.render(Path::new("/repo/src/main.rs"), Some(42), Some(7)),
+            "vscode://file//repo/src/main.rs:42:7"
+        );
+        assert_eq!(
+            link.render(Path::new("/repo/main.rs"), None, None),
+            "vscode://file//repo/main.rs:1:1"
+        );
+
+        let file = LinkTemplate::parse("file://{path}").unwrap().unwrap();
+        assert!(!file.uses_line());
+        assert_eq!(
+            file.render(Path::new("/my repo/a (1).rs"), Some(3), None),
+            "file:///my%20repo/a%20%281%29.rs"
+        );
+    }
+
+    #[test]
+    fn test_relative_to() {
+        let root = Path::new("/home/user/repo");
+        assert_eq!(
+            relative_to(Path::new("/home/user/repo/src/lib.rs"), root),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(
+            relative_to(Path::new("/home/user/repo/"), root),
+            Some(PathBuf::from("."))
+        );
+        // Sibling directory sharing a name prefix
+        assert_eq!(relative_to(Path::new("/home/user/repo2/a.rs"), root), None);
+        // Symlink targets and other files outside the repository
+        assert_eq!(relative_to(Path::new("/opt/shared/lib.rs"), root), None);
+        // Escapes through `..`
+        assert_eq!(
+            relative_to(Path::new("/home/user/repo/../secret.rs"), root),
+            None
+        );
+        // Stored relative paths
+        assert_eq!(relative_to(Path::new("src/lib.rs"), root), None);
+    }
+
+    #[test]
+    fn test_path_display_relative() {
+        let display = PathDisplay::new(Some(Path::new("/repo")), true, None);
+        assert_eq!(display.path("/repo/src/main.rs"), "src/main.rs");
+        assert_eq!(display.path("/elsewhere/main.rs"), "/elsewhere/main.rs");
+        assert_eq!(display.path("main.rs"), "main.rs");
+        assert!(!display.wants_line());
+        assert_eq!(display.link("/repo/src/main.rs", Some(1), None), None);
+        assert_eq!(
+            display.markdown("/repo/src/main.rs", "src/main.rs", None, None),
+            "`src/main.rs`"
+        );
+    }
+
+    #[test]
+    fn test_path_display_absolute_with_links() {
+        let display = PathDisplay::new(Some(Path::new("/repo")), false, vscode());
+        assert_eq!(display.path("/repo/src/main.rs"), "/repo/src/main.rs");
+        assert!(display.wants_line());
+        assert_eq!(
+            display.markdown("/repo/src/main.rs", "/repo/src/main.rs", Some(10), Some(4)),
+            "[`/repo/src/main.rs`](vscode://file//repo/src/main.rs:10:4)"
+        );
+        // Stored relative paths are resolved against the repository for links
+        assert_eq!(
+            display.link("src/lib.rs", Some(2), None).as_deref(),
+            Some("vscode://file//repo/src/lib.rs:2:1")
+        );
+    }
+
+    #[test]
+    fn test_path_display_relative_with_links_outside_repo() {
+        let display = PathDisplay::new(Some(Path::new("/repo")), true, vscode());
+        let label = display.path("/opt/shared/util.rs");
+        assert_eq!(label, "/opt/shared/util.rs");
+        assert_eq!(
+            display.markdown("/opt/shared/util.rs", &label, Some(5), None),
+            "[`/opt/shared/util.rs`](vscode://file//opt/shared/util.rs:5:1)"
+        );
+    }
+
+    #[test]
+    fn test_path_display_default_is_plain() {
+        let display = PathDisplay::default();
+        assert_eq!(display.path("/repo/a.rs"), "/repo/a.rs");
+        assert_eq!(display.link("/repo/a.rs", None, None), None);
+    }
+}
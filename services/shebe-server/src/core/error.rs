@@ -4,6 +4,7 @@
 //! application. Protocol-specific error handling (MCP error codes)
 //! is handled in the respective adapter modules.
 
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Result type alias for Shebe operations
@@ -21,6 +22,12 @@ pub enum ShebeError {
     #[error("Invalid session: {0}")]
     InvalidSession(String),
 
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(String),
+
+    #[error("Collection already exists: {0}")]
+    CollectionAlreadyExists(String),
+
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
@@ -47,6 +54,50 @@ pub enum ShebeError {
     #[error("Storage error: {0}")]
     StorageError(String),
 
+    #[error("Index is locked: {0}")]
+    IndexLocked(String),
+
+    /// The session's Tantivy files are damaged (truncated or missing segments)
+    #[error("Index of session '{session}' is corrupted: {cause}")]
+    IndexCorrupted {
+        session: String,
+        cause: String,
+        /// Repository to re-index from, if it still exists
+        repository: Option<PathBuf>,
+    },
+
+    /// Opening the session failed repeatedly; failing fast until a retry
+    #[error(
+        "Session '{session}' is temporarily unavailable (last error: {last_error}); \
+         retrying after {retry_after_secs} seconds"
+    )]
+    SessionUnavailable {
+        session: String,
+        last_error: String,
+        retry_after_secs: u64,
+    },
+
+    #[error("Git error: {0}")]
+    GitError(String),
+
+    #[error("Job not found: {0}")]
+    JobNotFound(String),
+
+    #[error("Invalid result handle: {0}")]
+    InvalidResultHandle(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Index too large: {0}")]
+    IndexTooLarge(String),
+
+    #[error("Server is in read-only mode: {0}")]
+    ReadOnly(String),
+
+    #[error("Access outside allowed roots denied: {0}")]
+    AccessDenied(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -67,13 +118,27 @@ impl ShebeError {
     pub fn is_not_found(&self) -> bool {
         matches!(
             self,
-            ShebeError::SessionNotFound(_) | ShebeError::InvalidPath(_)
+            ShebeError::SessionNotFound(_)
+                | ShebeError::CollectionNotFound(_)
+                | ShebeError::InvalidPath(_)
+                | ShebeError::JobNotFound(_)
         )
     }
 
     /// Check if this is a conflict error (already exists)
     pub fn is_conflict(&self) -> bool {
-        matches!(self, ShebeError::SessionAlreadyExists(_))
+        matches!(
+            self,
+            ShebeError::SessionAlreadyExists(_)
+                | ShebeError::CollectionAlreadyExists(_)
+                | ShebeError::IndexLocked(_)
+        )
+    }
+
+    /// Check if this operation is refused by configuration (read-only
+    /// mode, allowed roots)
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self, ShebeError::ReadOnly(_) | ShebeError::AccessDenied(_))
     }
 
     /// Check if this is a bad request error (invalid input)
@@ -83,6 +148,7 @@ impl ShebeError {
             ShebeError::InvalidSession(_)
                 | ShebeError::InvalidQuery(_)
                 | ShebeError::InvalidQueryField { .. }
+                | ShebeError::InvalidResultHandle(_)
                 | ShebeError::ConfigError(_)
         )
     }
@@ -108,6 +174,55 @@ mod tests {
         assert!(!err.is_bad_request());
     }
 
+    #[test]
+    fn test_collection_errors() {
+        let err = ShebeError::CollectionNotFound("platform".to_string());
+        assert!(err.is_not_found());
+        assert!(!err.is_conflict());
+        let err = ShebeError::CollectionAlreadyExists("platform".to_string());
+        assert!(err.is_conflict());
+        assert!(!err.is_not_found());
+    }
+
+    #[test]
+    fn test_index_locked_is_conflict() {
+        let err = ShebeError::IndexLocked("busy".to_string());
+        assert!(err.is_conflict());
+        assert!(!err.is_not_found());
+        assert!(err.message().contains("locked"));
+    }
+
+    #[test]
+    fn test_read_only_is_forbidden() {
+        let err = ShebeError::ReadOnly("cannot delete session 'x'".to_string());
+        assert!(err.is_forbidden());
+        assert!(!err.is_conflict());
+        assert!(err.message().contains("read-only mode"));
+    }
+
+    #[test]
+    fn test_index_corrupted_message() {
+        let err = ShebeError::IndexCorrupted {
+            session: "docs".to_string(),
+            cause: "footer magic byte mismatch".to_string(),
+            repository: None,
+        };
+        assert_eq!(
+            err.message(),
+            "Index of session 'docs' is corrupted: footer magic byte mismatch"
+        );
+        assert!(!err.is_not_found());
+        assert!(!err.is_bad_request());
+    }
+
+    #[test]
+    fn test_job_not_found_is_not_found() {
+        let err = ShebeError::JobNotFound("job-7".to_string());
+        assert!(err.is_not_found());
+        assert!(!err.is_conflict());
+        assert!(err.message().contains("job-7"));
+    }
+
     #[test]
     fn test_invalid_query_is_bad_request() {
         let err = ShebeError::InvalidQuery("empty".to_string());
@@ -0,0 +1,219 @@
+//! Human-readable sizes, durations, counts and times.
+//!
+//! Every MCP tool and CLI command formats numbers with these helpers so
+//! the same value reads the same everywhere. Output never depends on
+//! the system locale: thousands are always separated by `,` and
+//! decimals by `.`. JSON output keeps the raw values.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+const SIZE_UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+/// Format a byte count with a binary unit, e.g. `512 B` or `1.5 MB`
+///
+/// Sizes of a kilobyte and more get one decimal; petabytes is the
+/// largest unit.
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < SIZE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", SIZE_UNITS[unit])
+}
+
+/// Format a duration, e.g. `850ms`, `1.50s`, `2m 5.5s` or `1h 5m`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs >= 3600.0 {
+        let total_mins = duration.as_secs() / 60;
+        format!("{}h {}m", total_mins / 60, total_mins % 60)
+    } else if secs >= 60.0 {
+        let mins = (secs / 60.0).floor();
+        format!("{mins:.0}m {:.1}s", secs - mins * 60.0)
+    } else if secs >= 1.0 {
+        format!("{secs:.2}s")
+    } else {
+        format!("{:.0}ms", secs * 1000.0)
+    }
+}
+
+/// Format a short timing, e.g. `0.4ms`, `12.5ms` or `1.50s`
+///
+/// Like [`format_duration`], with a decimal below a second so stage
+/// timings of small runs do not all read `0ms`.
+pub fn format_timing(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+    } else {
+        format_duration(duration)
+    }
+}
+
+/// Format a count with thousands separators, e.g. `1,234,567`
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format how long ago `timestamp` was, e.g. `3 hours ago`
+///
+/// Under a minute is `just now`; times ahead of the clock are
+/// `in the future`.
+pub fn format_time_ago(timestamp: DateTime<Utc>) -> String {
+    match elapsed(timestamp, Utc::now()) {
+        Elapsed::Future => "in the future".to_string(),
+        Elapsed::JustNow => "just now".to_string(),
+        Elapsed::Ago(1, unit) => format!("1 {unit} ago"),
+        Elapsed::Ago(n, unit) => format!("{n} {unit}s ago"),
+    }
+}
+
+/// Short form of [`format_time_ago`] for tables, e.g. `3h ago`
+pub fn format_time_ago_short(timestamp: DateTime<Utc>) -> String {
+    match elapsed(timestamp, Utc::now()) {
+        Elapsed::Future => "in the future".to_string(),
+        Elapsed::JustNow => "just now".to_string(),
+        Elapsed::Ago(n, unit) => format!("{n}{} ago", &unit[..1]),
+    }
+}
+
+/// Format a timestamp with how long ago it was, for info views,
+/// e.g. `2025-10-20 10:00 UTC (3 days ago)`
+pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    format!(
+        "{} ({})",
+        timestamp.format("%Y-%m-%d %H:%M UTC"),
+        format_time_ago(timestamp)
+    )
+}
+
+enum Elapsed {
+    Future,
+    JustNow,
+    /// Whole units, largest unit first: day, hour, minute
+    Ago(i64, &'static str),
+}
+
+fn elapsed(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> Elapsed {
+    let duration = now.signed_duration_since(timestamp);
+    if duration.num_seconds() < 0 {
+        Elapsed::Future
+    } else if duration.num_days() > 0 {
+        Elapsed::Ago(duration.num_days(), "day")
+    } else if duration.num_hours() > 0 {
+        Elapsed::Ago(duration.num_hours(), "hour")
+    } else if duration.num_minutes() > 0 {
+        Elapsed::Ago(duration.num_minutes(), "minute")
+    } else {
+        Elapsed::JustNow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_bytes_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KB");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(1024 * 1024 - 1), "1024.0 KB");
+        assert_eq!(format_bytes(52_428_800), "50.0 MB");
+        assert_eq!(format_bytes(1 << 30), "1.0 GB");
+        assert_eq!(format_bytes(3 << 40), "3.0 TB");
+        assert_eq!(format_bytes(1 << 50), "1.0 PB");
+        // Petabytes is the largest unit
+        assert_eq!(format_bytes(2048 << 50), "2048.0 PB");
+        assert_eq!(format_bytes(u64::MAX), "16384.0 PB");
+    }
+
+    #[test]
+    fn test_format_duration_under_a_second() {
+        assert_eq!(format_duration(Duration::ZERO), "0ms");
+        assert_eq!(format_duration(Duration::from_micros(400)), "0ms");
+        assert_eq!(format_duration(Duration::from_millis(1)), "1ms");
+        assert_eq!(format_duration(Duration::from_millis(999)), "999ms");
+    }
+
+    #[test]
+    fn test_format_timing() {
+        assert_eq!(format_timing(Duration::ZERO), "0.0ms");
+        assert_eq!(format_timing(Duration::from_micros(420)), "0.4ms");
+        assert_eq!(format_timing(Duration::from_micros(12_480)), "12.5ms");
+        assert_eq!(format_timing(Duration::from_millis(1500)), "1.50s");
+    }
+
+    #[test]
+    fn test_format_duration_seconds_and_longer() {
+        assert_eq!(format_duration(Duration::from_secs(1)), "1.00s");
+        assert_eq!(format_duration(Duration::from_millis(59_990)), "59.99s");
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m 0.0s");
+        assert_eq!(format_duration(Duration::from_millis(125_500)), "2m 5.5s");
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1h 0m");
+        assert_eq!(
+            format_duration(Duration::from_secs(26 * 3600 + 125)),
+            "26h 2m"
+        );
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+        assert_eq!(format_count(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn test_format_time_ago() {
+        let now = Utc::now();
+        let ago = |d: chrono::Duration| format_time_ago(now - d);
+        assert_eq!(ago(chrono::Duration::seconds(59)), "just now");
+        assert_eq!(ago(chrono::Duration::minutes(1)), "1 minute ago");
+        assert_eq!(ago(chrono::Duration::minutes(45)), "45 minutes ago");
+        assert_eq!(ago(chrono::Duration::hours(1)), "1 hour ago");
+        assert_eq!(ago(chrono::Duration::hours(23)), "23 hours ago");
+        assert_eq!(ago(chrono::Duration::days(1)), "1 day ago");
+        assert_eq!(ago(chrono::Duration::weeks(3)), "21 days ago");
+        assert_eq!(ago(chrono::Duration::hours(-1)), "in the future");
+    }
+
+    #[test]
+    fn test_format_time_ago_short() {
+        let now = Utc::now();
+        let ago = |d: chrono::Duration| format_time_ago_short(now - d);
+        assert_eq!(ago(chrono::Duration::seconds(10)), "just now");
+        assert_eq!(ago(chrono::Duration::minutes(5)), "5m ago");
+        assert_eq!(ago(chrono::Duration::hours(5)), "5h ago");
+        assert_eq!(ago(chrono::Duration::weeks(3)), "21d ago");
+        assert_eq!(ago(chrono::Duration::hours(-1)), "in the future");
+    }
+
+    #[test]
+    fn test_format_timestamp_shows_both() {
+        let at = Utc.with_ymd_and_hms(2025, 10, 20, 10, 0, 30).unwrap();
+        let text = format_timestamp(at);
+        assert!(text.starts_with("2025-10-20 10:00 UTC ("), "{text}");
+        assert!(text.ends_with(" days ago)"), "{text}");
+
+        let recent = format_timestamp(Utc::now() - chrono::Duration::weeks(3));
+        assert!(recent.ends_with(" UTC (21 days ago)"), "{recent}");
+    }
+}
@@ -0,0 +1,174 @@
+//! Readiness checks for the storage layer.
+//!
+//! A running process is not necessarily able to serve requests: the
+//! storage root may be missing or read-only, or an index directory may
+//! be corrupt. [`check_readiness`] runs a fixed set of checks and
+//! reports each one by name so adapters can log or return them as-is.
+
+use crate::core::storage::StorageManager;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Check name: storage root exists and is writable
+pub const CHECK_STORAGE_ROOT: &str = "storage_root";
+
+/// Check name: sessions can be listed
+pub const CHECK_LIST_SESSIONS: &str = "list_sessions";
+
+/// Check name: the configured canary session opens
+pub const CHECK_CANARY_SESSION: &str = "canary_session";
+
+/// Result of a single readiness check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HealthCheck {
+    /// Check name (one of the `CHECK_*` constants)
+    pub name: String,
+
+    /// Whether the check passed
+    pub ok: bool,
+
+    /// What was checked, or why it failed
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn pass(name: &str, detail: String) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: true,
+            detail,
+        }
+    }
+
+    fn fail(name: &str, detail: String) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: false,
+            detail,
+        }
+    }
+}
+
+/// Outcome of all readiness checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// True when every check passed
+    pub ready: bool,
+
+    /// Individual check results, in the order they ran
+    pub checks: Vec<HealthCheck>,
+}
+
+impl ReadinessReport {
+    /// Checks that did not pass
+    pub fn failing(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|c| !c.ok)
+    }
+}
+
+/// Run the readiness checks against a storage manager
+///
+/// The canary check only runs when `canary_session` is set.
+pub fn check_readiness(storage: &StorageManager, canary_session: Option<&str>) -> ReadinessReport {
+    let mut checks = vec![check_storage_root(storage), check_list_sessions(storage)];
+
+    if let Some(session_id) = canary_session {
+        checks.push(check_canary_session(storage, session_id));
+    }
+
+    ReadinessReport {
+        ready: checks.iter().all(|c| c.ok),
+        checks,
+    }
+}
+
+/// Verify the storage root is a directory we can write to
+fn check_storage_root(storage: &StorageManager) -> HealthCheck {
+    let root = storage.storage_root();
+
+    if !root.is_dir() {
+        return HealthCheck::fail(
+            CHECK_STORAGE_ROOT,
+            format!("{} does not exist or is not a directory", root.display()),
+        );
+    }
+
+    // Permission bits alone do not cover read-only mounts, so write a file
+    let probe = root.join(format!(".ready-probe-{}", std::process::id()));
+    match fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            HealthCheck::pass(
+                CHECK_STORAGE_ROOT,
+                format!("{} is writable", root.display()),
+            )
+        }
+        Err(e) => HealthCheck::fail(
+            CHECK_STORAGE_ROOT,
+            format!("{} is not writable: {e}", root.display()),
+        ),
+    }
+}
+
+/// Verify sessions can be listed
+fn check_list_sessions(storage: &StorageManager) -> HealthCheck {
+    match storage.list_sessions() {
+        Ok(sessions) => HealthCheck::pass(
+            CHECK_LIST_SESSIONS,
+            format!("{} session(s) found", sessions.len()),
+        ),
+        Err(e) => HealthCheck::fail(CHECK_LIST_SESSIONS, e.to_string()),
+    }
+}
+
+/// Verify the canary session index opens
+fn check_canary_session(storage: &StorageManager, session_id: &str) -> HealthCheck {
+    match storage.open_session(session_id) {
+        Ok(_) => HealthCheck::pass(CHECK_CANARY_SESSION, format!("'{session_id}' opened")),
+        Err(e) => HealthCheck::fail(CHECK_CANARY_SESSION, format!("'{session_id}': {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ready_with_empty_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = StorageManager::new(temp_dir.path().to_path_buf());
+
+        let report = check_readiness(&storage, None);
+
+        assert!(report.ready);
+        assert_eq!(report.checks.len(), 2);
+        assert_eq!(report.failing().count(), 0);
+    }
+
+    #[test]
+    fn test_missing_storage_root_not_ready() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = StorageManager::new(temp_dir.path().join("missing"));
+
+        let report = check_readiness(&storage, None);
+
+        assert!(!report.ready);
+        let failing: Vec<_> = report.failing().collect();
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].name, CHECK_STORAGE_ROOT);
+    }
+
+    #[test]
+    fn test_missing_canary_not_ready() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = StorageManager::new(temp_dir.path().to_path_buf());
+
+        let report = check_readiness(&storage, Some("nope"));
+
+        assert!(!report.ready);
+        let failing: Vec<_> = report.failing().collect();
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].name, CHECK_CANARY_SESSION);
+    }
+}
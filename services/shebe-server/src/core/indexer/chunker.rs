@@ -9,7 +9,7 @@
 //! # Example
 //!
 //! ```
-//! use shebe::indexer::Chunker;
+//! use shebe::core::indexer::Chunker;
 //! use std::path::Path;
 //!
 //! let chunker = Chunker::new(512, 64);
@@ -21,9 +21,119 @@
 //!     assert!(std::str::from_utf8(chunk.text.as_bytes()).is_ok());
 //! }
 //! ```
+//!
+//! Without a [`BoundaryHint`], a cut that would land inside a word
+//! (a run of alphanumeric and underscore characters) moves to the
+//! nearest word edge within [`TOKEN_EDGE_WINDOW`] characters, so an
+//! identifier is not indexed as two partial terms when the overlap is
+//! too small to hold it whole.
+//!
+//! Large files are chunked as they are read with a [`ChunkStream`],
+//! which produces the same chunks while holding only about one
+//! chunk's worth of characters. Data files such as CSV and JSON Lines
+//! are chunked from whole records with [`Chunker::chunk_records`].
+
+use super::records::RecordFormat;
+use crate::core::types::{Chunk, RecordRange};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How far back (percent of `chunk_size`) a chunk end may move to
+/// reach a boundary
+pub const BOUNDARY_TOLERANCE_PERCENT: usize = 15;
+
+/// How far (characters, at most the boundary tolerance) a cut inside a
+/// word may move to the word's edge when there is no boundary hint
+pub const TOKEN_EDGE_WINDOW: usize = 16;
+
+/// Largest final chunk (percent of `chunk_size`) merged into the one
+/// before it with [`Chunker::with_merge_tail_fragments`]
+pub const TAIL_FRAGMENT_PERCENT: usize = 15;
+
+/// Default cap on the chunks indexed from one file
+///
+/// Generous enough for any hand-written source file; it stops a single
+/// generated or data file from dominating a session.
+pub const DEFAULT_MAX_CHUNKS_PER_FILE: usize = 2000;
 
-use crate::core::types::Chunk;
-use std::path::Path;
+/// Where chunk ends are aligned, if anywhere
+///
+/// With a hint, each chunk end moves back to the nearest matching
+/// boundary within [`BOUNDARY_TOLERANCE_PERCENT`] of `chunk_size`.
+/// Without one in that window the chunk is cut at exactly
+/// `chunk_size` characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryHint {
+    /// Cut at `chunk_size` characters, moved to a word edge nearby
+    #[default]
+    None,
+
+    /// End chunks after a blank (empty or whitespace-only) line
+    BlankLine,
+
+    /// End chunks after any line break
+    Newline,
+}
+
+impl BoundaryHint {
+    /// Name used in configuration and tool parameters
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::BlankLine => "blank_line",
+            Self::Newline => "newline",
+        }
+    }
+
+    /// Whether a chunk may end before `chars[end]`
+    fn is_boundary(self, chars: &[(usize, char)], end: usize) -> bool {
+        if end == 0 || chars[end - 1].1 != '\n' {
+            return false;
+        }
+        match self {
+            Self::None => false,
+            Self::Newline => true,
+            Self::BlankLine => {
+                // The line ending at `end - 1` must hold only whitespace
+                let line = chars[..end - 1]
+                    .iter()
+                    .rev()
+                    .take_while(|(_, c)| *c != '\n');
+                let mut blank = true;
+                let mut len = 0;
+                for (_, c) in line {
+                    blank &= c.is_whitespace();
+                    len += 1;
+                }
+                blank && len < end - 1
+            }
+        }
+    }
+}
+
+impl fmt::Display for BoundaryHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BoundaryHint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "blank_line" => Ok(Self::BlankLine),
+            "newline" => Ok(Self::Newline),
+            other => Err(format!(
+                "unknown boundary hint '{other}' (expected none, blank_line or newline)"
+            )),
+        }
+    }
+}
 
 /// UTF-8 safe text chunker.
 ///
@@ -37,6 +147,15 @@ pub struct Chunker {
 
     /// Number of characters to overlap between consecutive chunks
     overlap: usize,
+
+    /// Boundary that chunk ends are moved back to
+    boundary: BoundaryHint,
+
+    /// Whether a short final chunk is merged into the previous one
+    merge_tail: bool,
+
+    /// Chunks cut from one text before the rest is dropped
+    max_chunks: Option<usize>,
 }
 
 impl Chunker {
@@ -56,7 +175,7 @@ impl Chunker {
     /// # Example
     ///
     /// ```
-    /// use shebe::indexer::Chunker;
+    /// use shebe::core::indexer::Chunker;
     ///
     /// let chunker = Chunker::new(512, 64);
     /// assert_eq!(chunker.chunk_size(), 512);
@@ -69,9 +188,85 @@ impl Chunker {
         Self {
             chunk_size,
             overlap,
+            boundary: BoundaryHint::None,
+            merge_tail: false,
+            max_chunks: None,
         }
     }
 
+    /// Align chunk ends to `boundary` (see [`BoundaryHint`])
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::{BoundaryHint, Chunker};
+    /// use std::path::Path;
+    ///
+    /// let chunker = Chunker::new(44, 3).with_boundary_hint(BoundaryHint::BlankLine);
+    /// let text = "fn alpha() {\n    run(1);\n    run(2);\n}\n\nfn beta() {\n    run(3);\n}\n";
+    /// let chunks = chunker.chunk_text(text, Path::new("lib.rs"));
+    ///
+    /// // The first chunk ends at the blank line instead of inside `fn beta`
+    /// assert!(chunks[0].text.ends_with("}\n\n"));
+    /// assert!(chunks[1].text.starts_with("}\n\nfn beta"));
+    /// ```
+    pub fn with_boundary_hint(mut self, boundary: BoundaryHint) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Merge a final chunk shorter than [`TAIL_FRAGMENT_PERCENT`] of
+    /// `chunk_size` into the chunk before it
+    ///
+    /// Such tail fragments (a file's last few characters) score
+    /// unusually high in BM25 for their length. The merged last chunk
+    /// can be up to that much longer than `chunk_size`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::Chunker;
+    /// use std::path::Path;
+    ///
+    /// let text = "x".repeat(105);
+    /// let chunks = Chunker::new(100, 0).chunk_text(&text, Path::new("a.txt"));
+    /// assert_eq!(chunks.len(), 2);
+    ///
+    /// let merged = Chunker::new(100, 0)
+    ///     .with_merge_tail_fragments(true)
+    ///     .chunk_text(&text, Path::new("a.txt"));
+    /// assert_eq!(merged.len(), 1);
+    /// assert_eq!(merged[0].end_offset, 105);
+    /// ```
+    pub fn with_merge_tail_fragments(mut self, merge: bool) -> Self {
+        self.merge_tail = merge;
+        self
+    }
+
+    /// Stop after `max_chunks` chunks, leaving the rest of the text
+    /// unchunked
+    ///
+    /// A truncated text is recognisable by its last chunk ending
+    /// before the end of the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::Chunker;
+    /// use std::path::Path;
+    ///
+    /// let text = "x".repeat(1000);
+    /// let chunks = Chunker::new(100, 0)
+    ///     .with_max_chunks(3)
+    ///     .chunk_text(&text, Path::new("a.txt"));
+    /// assert_eq!(chunks.len(), 3);
+    /// assert_eq!(chunks[2].end_offset, 300);
+    /// ```
+    pub fn with_max_chunks(mut self, max_chunks: usize) -> Self {
+        self.max_chunks = Some(max_chunks);
+        self
+    }
+
     /// Get the chunk size in characters.
     #[allow(dead_code)]
     pub fn chunk_size(&self) -> usize {
@@ -84,6 +279,44 @@ impl Chunker {
         self.overlap
     }
 
+    /// Number of chunks [`Chunker::chunk_text`] produces for text
+    /// of `chars` characters.
+    ///
+    /// Exact without a boundary hint (including merged tail
+    /// fragments) as long as no cut moves to a word edge; otherwise
+    /// chunks can be shorter or longer and this is an estimate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::Chunker;
+    ///
+    /// let chunker = Chunker::new(10, 2);
+    /// assert_eq!(chunker.chunk_count(0), 0);
+    /// assert_eq!(chunker.chunk_count(10), 2);
+    /// assert_eq!(chunker.chunk_count(17), 3);
+    /// ```
+    pub fn chunk_count(&self, chars: usize) -> usize {
+        let step = self.chunk_size.saturating_sub(self.overlap).max(1);
+        let mut count = chars.div_ceil(step);
+        if count >= 2 && self.is_tail_fragment(chars - (count - 1) * step) {
+            count -= 1;
+        }
+        self.max_chunks.map_or(count, |max| count.min(max))
+    }
+
+    /// Whether `chunks` has reached the cap set with
+    /// [`Chunker::with_max_chunks`]
+    fn is_full(&self, chunks: &[Chunk]) -> bool {
+        self.max_chunks.is_some_and(|max| chunks.len() >= max)
+    }
+
+    /// Whether a final chunk of `chars` characters is merged into the
+    /// one before it
+    fn is_tail_fragment(&self, chars: usize) -> bool {
+        self.merge_tail && chars < self.chunk_size * TAIL_FRAGMENT_PERCENT / 100
+    }
+
     /// Chunk text into overlapping segments.
     ///
     /// # Safety
@@ -106,10 +339,18 @@ impl Chunker {
     /// - Byte offsets (start_offset, end_offset)
     /// - Sequential chunk index
     ///
+    /// With a [`BoundaryHint`], each chunk end may move back to a
+    /// boundary. The next chunk still starts `overlap` characters
+    /// before the (adjusted) end, so chunks stay contiguous, and the
+    /// last chunk is the one that reaches the end of the text. Without
+    /// one, an end inside a word moves to the nearest word edge (see
+    /// [`TOKEN_EDGE_WINDOW`]) and the next chunk again starts `overlap`
+    /// characters before it.
+    ///
     /// # Example
     ///
     /// ```
-    /// use shebe::indexer::Chunker;
+    /// use shebe::core::indexer::Chunker;
     /// use std::path::Path;
     ///
     /// let chunker = Chunker::new(10, 2);
@@ -137,7 +378,7 @@ impl Chunker {
 
         while char_start_idx < char_indices.len() {
             // Calculate end index (character-based)
-            let char_end_idx = (char_start_idx + self.chunk_size).min(char_indices.len());
+            let char_end_idx = self.chunk_end(&char_indices, char_start_idx);
 
             // Get byte offsets for this chunk
             // Since we're using character indices, these byte
@@ -159,17 +400,383 @@ impl Chunker {
                 start_offset: byte_start,
                 end_offset: byte_end,
                 chunk_index: chunks.len(),
+                records: None,
+                section: None,
             });
 
-            // Move forward with overlap
-            // Step = chunk_size - overlap, but always advance at
-            // least 1 character to prevent infinite loops
-            let step = self.chunk_size.saturating_sub(self.overlap);
-            char_start_idx += step.max(1);
+            if self.is_full(&chunks) {
+                break;
+            }
+
+            if self.boundary != BoundaryHint::None {
+                if char_end_idx == char_indices.len() {
+                    break;
+                }
+                // Overlap is measured back from the adjusted end,
+                // which chunk_end keeps beyond start + overlap
+                char_start_idx = char_end_idx - self.overlap;
+                continue;
+            }
+
+            char_start_idx = self.next_start(char_start_idx, char_end_idx, char_indices.len());
+        }
+
+        self.merge_tail_fragment(&mut chunks);
+        chunks
+    }
+
+    /// Chunk a data file from whole records (see [`super::records`])
+    ///
+    /// Consecutive records are packed into a chunk while they fit in
+    /// `chunk_size` characters; chunks do not overlap, since no record
+    /// is cut. A record longer than `chunk_size` is split like any
+    /// other text, every piece reporting that one record, and a note is
+    /// logged. `max_chunks` applies as in [`Chunker::chunk_text`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::records::RecordFormat;
+    /// use shebe::core::indexer::Chunker;
+    /// use std::path::Path;
+    ///
+    /// let text = "id,note\n1,\"two\nlines\"\n2,short\n";
+    /// let chunks = Chunker::new(24, 4).chunk_records(text, Path::new("a.csv"), RecordFormat::QuotedLines);
+    ///
+    /// assert_eq!(chunks[0].text, "id,note\n1,\"two\nlines\"\n");
+    /// assert_eq!(chunks[1].records.unwrap().describe(), "record 3");
+    /// ```
+    pub fn chunk_records(&self, text: &str, file_path: &Path, format: RecordFormat) -> Vec<Chunk> {
+        let spans = format.record_spans(text);
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut next = 0;
+
+        while next < spans.len() && !self.is_full(&chunks) {
+            let first = next;
+            let mut chars = text[spans[first].clone()].chars().count();
+
+            if chars > self.chunk_size {
+                let record = &text[spans[first].clone()];
+                let splitter = Chunker {
+                    max_chunks: None,
+                    ..self.clone()
+                };
+                let pieces = splitter.chunk_text(record, file_path);
+                tracing::debug!(
+                    "Record {} of {:?} is longer than the chunk size ({} > {} chars); split into {} chunks",
+                    first + 1,
+                    file_path,
+                    chars,
+                    self.chunk_size,
+                    pieces.len()
+                );
+                for piece in pieces {
+                    if self.is_full(&chunks) {
+                        break;
+                    }
+                    chunks.push(Chunk {
+                        start_offset: spans[first].start + piece.start_offset,
+                        end_offset: spans[first].start + piece.end_offset,
+                        chunk_index: chunks.len(),
+                        records: Some(RecordRange {
+                            first: first + 1,
+                            last: first + 1,
+                        }),
+                        ..piece
+                    });
+                }
+                next += 1;
+                continue;
+            }
+
+            next += 1;
+            while let Some(span) = spans.get(next) {
+                let record_chars = text[span.clone()].chars().count();
+                if chars + record_chars > self.chunk_size {
+                    break;
+                }
+                chars += record_chars;
+                next += 1;
+            }
+
+            let range = spans[first].start..spans[next - 1].end;
+            chunks.push(Chunk {
+                text: text[range.clone()].to_string(),
+                file_path: file_path.to_path_buf(),
+                start_offset: range.start,
+                end_offset: range.end,
+                chunk_index: chunks.len(),
+                records: Some(RecordRange {
+                    first: first + 1,
+                    last: next,
+                }),
+                section: None,
+            });
         }
 
         chunks
     }
+
+    /// Chunk text that arrives in pieces (see [`ChunkStream`])
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::Chunker;
+    /// use std::path::Path;
+    ///
+    /// let chunker = Chunker::new(10, 2);
+    /// let text = "Hello 👋 World 🌍 Rust 🦀";
+    ///
+    /// let mut stream = chunker.chunk_stream(Path::new("test.txt"));
+    /// for piece in ["Hello 👋 Wo", "rld 🌍 Ru", "st 🦀"] {
+    ///     stream.push(piece);
+    /// }
+    /// assert_eq!(stream.finish(), chunker.chunk_text(text, Path::new("test.txt")));
+    /// ```
+    pub fn chunk_stream(&self, file_path: &Path) -> ChunkStream<'_> {
+        ChunkStream {
+            chunker: self,
+            file_path: file_path.to_path_buf(),
+            window: Vec::new(),
+            start: 0,
+            len: 0,
+            chunks: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Merge the last chunk into the one before it if it is a tail
+    /// fragment
+    ///
+    /// The last chunk reaches the end of the text and starts inside
+    /// the one before it, so the merged text is that chunk up to the
+    /// last one's start, followed by the last chunk.
+    fn merge_tail_fragment(&self, chunks: &mut Vec<Chunk>) {
+        let [.., _, last] = chunks.as_slice() else {
+            return;
+        };
+        if !self.is_tail_fragment(last.text.chars().count()) {
+            return;
+        }
+        if let (Some(last), Some(previous)) = (chunks.pop(), chunks.last_mut()) {
+            previous
+                .text
+                .truncate(last.start_offset - previous.start_offset);
+            previous.text.push_str(&last.text);
+            previous.end_offset = last.end_offset;
+        }
+    }
+
+    /// Character index where the chunk starting at `start` ends
+    ///
+    /// Scans back from `start + chunk_size` for a boundary, no
+    /// further than the tolerance window and never to or before
+    /// `start + overlap` (so every chunk advances). Without a boundary
+    /// hint, see [`Chunker::word_edge`].
+    fn chunk_end(&self, chars: &[(usize, char)], start: usize) -> usize {
+        let target = (start + self.chunk_size).min(chars.len());
+        if target == chars.len() {
+            return target;
+        }
+
+        let tolerance = self.chunk_size * BOUNDARY_TOLERANCE_PERCENT / 100;
+        if self.boundary == BoundaryHint::None {
+            return self.word_edge(chars, target, start + self.overlap + 1);
+        }
+        let lowest = target
+            .saturating_sub(tolerance)
+            .max(start + self.overlap + 1);
+        (lowest..=target)
+            .rev()
+            .find(|&end| self.boundary.is_boundary(chars, end))
+            .unwrap_or(target)
+    }
+
+    /// `target`, or the nearest end that does not split a word
+    ///
+    /// Looks up to [`Chunker::word_edge_window`] characters either side
+    /// of `target` (the earlier end first on a tie, never before
+    /// `lowest`). A word longer than the window is still cut at
+    /// `target`.
+    fn word_edge(&self, chars: &[(usize, char)], target: usize, lowest: usize) -> usize {
+        if !splits_word(chars, target) {
+            return target;
+        }
+        for distance in 1..=self.word_edge_window() {
+            let earlier = target.checked_sub(distance).filter(|&end| end >= lowest);
+            if let Some(end) = earlier.filter(|&end| !splits_word(chars, end)) {
+                return end;
+            }
+            let later = Some(target + distance).filter(|&end| end <= chars.len());
+            if let Some(end) = later.filter(|&end| !splits_word(chars, end)) {
+                return end;
+            }
+        }
+        target
+    }
+
+    /// How far [`Chunker::word_edge`] looks: [`TOKEN_EDGE_WINDOW`], or
+    /// less for small chunks, and nothing with a boundary hint
+    fn word_edge_window(&self) -> usize {
+        if self.boundary != BoundaryHint::None {
+            return 0;
+        }
+        TOKEN_EDGE_WINDOW.min(self.chunk_size * BOUNDARY_TOLERANCE_PERCENT / 100)
+    }
+
+    /// Character index of the chunk after the one at `start..end`,
+    /// without a boundary hint
+    ///
+    /// `overlap` characters before `end`, which is `chunk_size -
+    /// overlap` past `start` unless the end moved to a word edge. A
+    /// chunk reaching the end of the text advances by that step too,
+    /// as it always has.
+    fn next_start(&self, start: usize, end: usize, len: usize) -> usize {
+        if end < len {
+            return end - self.overlap;
+        }
+        // Always advance at least 1 character to prevent infinite loops
+        start + self.chunk_size.saturating_sub(self.overlap).max(1)
+    }
+}
+
+/// Whether `c` is part of a word (identifier) for chunk cuts
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether ending a chunk before `chars[end]` cuts a word in two
+fn splits_word(chars: &[(usize, char)], end: usize) -> bool {
+    end > 0 && end < chars.len() && is_word_char(chars[end - 1].1) && is_word_char(chars[end].1)
+}
+
+/// Incremental [`Chunker::chunk_text`] over text read in pieces
+///
+/// Characters are buffered only until the chunks starting before them
+/// have been cut, so memory stays around one chunk plus the piece
+/// being pushed, however long the text (or a single line of it).
+/// [`ChunkStream::finish`] returns the chunks `chunk_text` would for
+/// the concatenated pieces.
+#[derive(Debug)]
+pub struct ChunkStream<'a> {
+    chunker: &'a Chunker,
+    file_path: PathBuf,
+
+    /// Characters not yet chunked past, with their byte offsets
+    window: Vec<(usize, char)>,
+
+    /// Index in `window` where the next chunk starts
+    start: usize,
+
+    /// Bytes pushed so far
+    len: usize,
+
+    chunks: Vec<Chunk>,
+
+    /// Whether a boundary-aligned chunk has reached the end, or the
+    /// chunk cap has been reached
+    done: bool,
+}
+
+impl ChunkStream<'_> {
+    /// Append the next piece of text
+    pub fn push(&mut self, text: &str) {
+        if self.done {
+            self.len += text.len();
+            return;
+        }
+        let base = self.len;
+        self.window
+            .extend(text.char_indices().map(|(i, c)| (base + i, c)));
+        self.len += text.len();
+        self.cut(false);
+    }
+
+    /// Bytes of text pushed so far
+    pub fn bytes_pushed(&self) -> usize {
+        self.len
+    }
+
+    /// Cut the remaining chunks
+    pub fn finish(mut self) -> Vec<Chunk> {
+        self.cut(true);
+        self.chunker.merge_tail_fragment(&mut self.chunks);
+        self.chunks
+    }
+
+    /// Cut every chunk whose end is known, then drop the characters
+    /// no later chunk needs
+    ///
+    /// Before the end of the text a chunk is only cut once more than
+    /// `chunk_size` characters (and the word edge window) follow its
+    /// start, so the end of the buffer is never mistaken for the end
+    /// of the text.
+    fn cut(&mut self, at_end: bool) {
+        let chunker = self.chunker;
+        while !self.done && self.start < self.window.len() {
+            let lookahead = chunker.chunk_size + chunker.word_edge_window();
+            if !at_end && self.window.len() <= self.start + lookahead {
+                break;
+            }
+            let end = chunker.chunk_end(&self.window, self.start);
+            let start_offset = self.window[self.start].0;
+            let end_offset = self.window.get(end).map_or(self.len, |&(i, _)| i);
+            self.chunks.push(Chunk {
+                text: self.window[self.start..end]
+                    .iter()
+                    .map(|&(_, c)| c)
+                    .collect(),
+                file_path: self.file_path.clone(),
+                start_offset,
+                end_offset,
+                chunk_index: self.chunks.len(),
+                records: None,
+                section: None,
+            });
+
+            if chunker.is_full(&self.chunks) {
+                self.done = true;
+                self.window.clear();
+                self.start = 0;
+                break;
+            }
+
+            // Advance as chunk_text does
+            if chunker.boundary != BoundaryHint::None {
+                if end == self.window.len() {
+                    self.done = true;
+                    break;
+                }
+                self.start = end - chunker.overlap;
+            } else {
+                self.start = chunker.next_start(self.start, end, self.window.len());
+            }
+        }
+
+        let keep = self.context_start();
+        self.window.drain(..keep);
+        self.start -= keep;
+    }
+
+    /// First character the remaining chunks may look at
+    ///
+    /// That is the next chunk's start, except that a blank-line check
+    /// looks back over the whole line a chunk ends with: whitespace
+    /// before the start is kept, back to the line break (or other
+    /// character) that decides whether the line is blank.
+    fn context_start(&self) -> usize {
+        let start = self.start.min(self.window.len());
+        if self.chunker.boundary != BoundaryHint::BlankLine {
+            return start;
+        }
+        let blank_run = self.window[..start]
+            .iter()
+            .rev()
+            .take_while(|(_, c)| c.is_whitespace() && *c != '\n')
+            .count();
+        (start - blank_run).saturating_sub(1)
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +810,107 @@ mod tests {
         assert!(chunks.is_empty());
     }
 
+    #[test]
+    fn test_chunk_count_matches_chunk_text() {
+        let chunker = Chunker::new(10, 2);
+        for len in 0..40 {
+            let text = "x".repeat(len);
+            let chunks = chunker.chunk_text(&text, Path::new("test.txt"));
+            assert_eq!(chunker.chunk_count(len), chunks.len(), "len {len}");
+        }
+    }
+
+    #[test]
+    fn test_chunk_records_never_splits_quoted_newlines() {
+        let mut text = String::from("id,comment\n");
+        for i in 1..=40 {
+            text.push_str(&format!("{i},\"line one of {i}\nline two of {i}\"\n"));
+        }
+        let chunks = Chunker::new(100, 10).chunk_records(
+            &text,
+            Path::new("c.csv"),
+            RecordFormat::QuotedLines,
+        );
+        let spans = RecordFormat::QuotedLines.record_spans(&text);
+        assert_eq!(spans.len(), 41);
+
+        let mut expected_first = 1;
+        for chunk in &chunks {
+            let records = chunk.records.unwrap();
+            assert_eq!(records.first, expected_first);
+            assert_eq!(chunk.start_offset, spans[records.first - 1].start);
+            assert_eq!(chunk.end_offset, spans[records.last - 1].end);
+            assert!(chunk.text.chars().count() <= 100);
+            // Quoted fields are complete: both lines of a record together
+            assert_eq!(chunk.text.matches('"').count() % 2, 0, "{:?}", chunk.text);
+            expected_first = records.last + 1;
+        }
+        assert_eq!(expected_first, 42);
+        assert_eq!(chunks.last().unwrap().end_offset, text.len());
+    }
+
+    #[test]
+    fn test_chunk_records_splits_oversized_record() {
+        let text = format!(
+            "{{\"id\":1}}\n{{\"blob\":\"{}\"}}\n{{\"id\":3}}\n",
+            "x".repeat(250)
+        );
+        let chunks =
+            Chunker::new(100, 10).chunk_records(&text, Path::new("e.jsonl"), RecordFormat::Lines);
+
+        let describe: Vec<String> = chunks
+            .iter()
+            .map(|c| c.records.unwrap().describe())
+            .collect();
+        assert_eq!(describe.first().unwrap(), "record 1");
+        assert_eq!(describe.last().unwrap(), "record 3");
+        let pieces: Vec<&Chunk> = chunks
+            .iter()
+            .filter(|c| c.records.unwrap().first == 2)
+            .collect();
+        assert!(pieces.len() > 1);
+        assert_eq!(pieces[0].start_offset, 9);
+        assert_eq!(pieces.last().unwrap().end_offset, text.len() - 9);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.chunk_index, i);
+            assert_eq!(chunk.text, text[chunk.start_offset..chunk.end_offset]);
+        }
+    }
+
+    #[test]
+    fn test_chunk_records_respects_max_chunks() {
+        let text = "a\n".repeat(100);
+        let chunks = Chunker::new(10, 2).with_max_chunks(3).chunk_records(
+            &text,
+            Path::new("a.tsv"),
+            RecordFormat::Lines,
+        );
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].records.unwrap().describe(), "records 11–15");
+        assert_eq!(chunks[2].end_offset, 30);
+    }
+
+    #[test]
+    fn test_merge_tail_fragments() {
+        let chunker = Chunker::new(100, 10).with_merge_tail_fragments(true);
+        for len in 0..400 {
+            let text = "x".repeat(len);
+            let chunks = chunker.chunk_text(&text, Path::new("test.txt"));
+            assert_eq!(chunker.chunk_count(len), chunks.len(), "len {len}");
+            if let Some(last) = chunks.last() {
+                assert_eq!(last.end_offset, len);
+                assert!(chunks.len() == 1 || last.text.len() >= 15, "len {len}");
+            }
+        }
+
+        // 190 characters: the third chunk would hold only 10
+        let text = "y".repeat(190);
+        let chunks = chunker.chunk_text(&text, Path::new("test.txt"));
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].start_offset, 90);
+        assert_eq!(chunks[1].text.len(), 100);
+    }
+
     #[test]
     fn test_chunk_basic_text() {
         let chunker = Chunker::new(10, 2);
@@ -344,4 +1052,183 @@ mod tests {
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].text, text);
     }
+
+    /// Check chunks tile the text: each starts `overlap` characters
+    /// before the previous end, and the last one reaches the end
+    fn assert_contiguous(text: &str, chunks: &[Chunk], overlap: usize) {
+        assert_eq!(chunks[0].start_offset, 0);
+        assert_eq!(chunks.last().unwrap().end_offset, text.len());
+        for pair in chunks.windows(2) {
+            let shared = &text[pair[1].start_offset..pair[0].end_offset];
+            assert_eq!(shared.chars().count(), overlap, "{pair:?}");
+        }
+        for chunk in chunks {
+            assert_eq!(chunk.text, text[chunk.start_offset..chunk.end_offset]);
+        }
+    }
+
+    #[test]
+    fn test_boundary_hint_parses_names() {
+        for hint in [
+            BoundaryHint::None,
+            BoundaryHint::BlankLine,
+            BoundaryHint::Newline,
+        ] {
+            assert_eq!(hint.as_str().parse::<BoundaryHint>().unwrap(), hint);
+        }
+        assert!("paragraph".parse::<BoundaryHint>().is_err());
+    }
+
+    #[test]
+    fn test_blank_line_hint_cuts_between_paragraphs() {
+        let paragraph = |word: &str| format!("{}\n", [word; 8].join(" "));
+        let text = format!(
+            "{}{}\n{}{}\n{}",
+            paragraph("alpha"),
+            paragraph("beta"),
+            paragraph("gamma"),
+            paragraph("delta"),
+            paragraph("omega"),
+        );
+        let chunker = Chunker::new(100, 10).with_boundary_hint(BoundaryHint::BlankLine);
+        let chunks = chunker.chunk_text(&text, Path::new("notes.md"));
+
+        // The blank line after "beta" is 91 characters in, within 15%
+        assert!(chunks[0].text.ends_with("beta\n\n"));
+        assert_contiguous(&text, &chunks, 10);
+    }
+
+    #[test]
+    fn test_newline_hint_keeps_overlap_and_utf8() {
+        // 17-character lines, so every 18-character window has a newline
+        let text = "let café = \"☕\";\n".repeat(40);
+        let chunker = Chunker::new(120, 8).with_boundary_hint(BoundaryHint::Newline);
+        let chunks = chunker.chunk_text(&text, Path::new("main.rs"));
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.text.ends_with('\n'), "{:?}", chunk.text);
+        }
+        assert_contiguous(&text, &chunks, 8);
+    }
+
+    #[test]
+    fn test_boundary_hint_falls_back_to_exact_cuts() {
+        // No blank lines at all: cut at exactly chunk_size
+        let text = "x".repeat(95);
+        let chunker = Chunker::new(30, 5).with_boundary_hint(BoundaryHint::BlankLine);
+        let chunks = chunker.chunk_text(&text, Path::new("min.js"));
+
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|c| c.text.chars().count() == 30));
+        assert_contiguous(&text, &chunks, 5);
+    }
+
+    #[test]
+    fn test_chunk_stream_matches_chunk_text() {
+        let text = format!(
+            "{}\n  \n\t\n{}\n\n{}",
+            "fn café() { run(\"☕\"); }\n".repeat(30),
+            " ".repeat(70),
+            "x".repeat(333)
+        );
+        let chunkers = [
+            Chunker::new(40, 6),
+            Chunker::new(40, 6).with_boundary_hint(BoundaryHint::BlankLine),
+            Chunker::new(40, 6).with_boundary_hint(BoundaryHint::Newline),
+            Chunker::new(100, 10).with_merge_tail_fragments(true),
+            Chunker::new(40, 6).with_max_chunks(5),
+            Chunker::new(40, 6)
+                .with_boundary_hint(BoundaryHint::Newline)
+                .with_max_chunks(5),
+        ];
+        let path = Path::new("a.rs");
+
+        for chunker in &chunkers {
+            let expected = chunker.chunk_text(&text, path);
+            for piece_chars in [1, 7, 41, 500, 10_000] {
+                let chars: Vec<char> = text.chars().collect();
+                let mut stream = chunker.chunk_stream(path);
+                for piece in chars.chunks(piece_chars) {
+                    stream.push(&piece.iter().collect::<String>());
+                }
+                assert_eq!(
+                    stream.finish(),
+                    expected,
+                    "{chunker:?} with {piece_chars}-character pieces"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_chunks_truncates_text() {
+        let chunker = Chunker::new(10, 2).with_max_chunks(4);
+        for len in 0..60 {
+            let text = "x".repeat(len);
+            let chunks = chunker.chunk_text(&text, Path::new("test.txt"));
+            assert_eq!(chunker.chunk_count(len), chunks.len(), "len {len}");
+            assert!(chunks.len() <= 4, "len {len}");
+        }
+
+        let text = "x".repeat(100);
+        let chunks = chunker.chunk_text(&text, Path::new("test.txt"));
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[3].end_offset, 34);
+
+        // A tail that fits under the cap is still merged
+        let chunker = Chunker::new(100, 10)
+            .with_merge_tail_fragments(true)
+            .with_max_chunks(2);
+        let chunks = chunker.chunk_text(&"y".repeat(190), Path::new("test.txt"));
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].end_offset, 190);
+    }
+
+    #[test]
+    fn test_cut_inside_word_moves_to_word_edge() {
+        // The cut at 100 characters falls 10 characters into the identifier
+        let text = format!(
+            "{}extremely_long_identifier_name = 1;\n{}",
+            "ab ".repeat(30),
+            "cd ".repeat(40)
+        );
+        let chunker = Chunker::new(100, 4);
+        let chunks = chunker.chunk_text(&text, Path::new("a.rs"));
+
+        assert_eq!(chunks[0].end_offset, 90);
+        assert_eq!(chunks[1].start_offset, 86);
+        assert!(chunks[1].text.contains("extremely_long_identifier_name"));
+        for pair in chunks.windows(2) {
+            let at = pair[0].end_offset;
+            let chars: Vec<(usize, char)> = text.char_indices().collect();
+            let end = chars.iter().position(|&(i, _)| i == at).unwrap();
+            assert!(!splits_word(&chars, end), "{pair:?}");
+        }
+        assert_contiguous(&text, &chunks, 4);
+
+        // A word longer than the window is still cut at chunk_size
+        let chunks = chunker.chunk_text(&"x".repeat(250), Path::new("a.txt"));
+        assert_eq!(chunks[0].end_offset, 100);
+    }
+
+    #[test]
+    fn test_chunk_stream_of_nothing() {
+        let chunker = Chunker::new(10, 2);
+        let mut stream = chunker.chunk_stream(Path::new("a.txt"));
+        stream.push("");
+        assert!(stream.finish().is_empty());
+    }
+
+    #[test]
+    fn test_boundary_outside_window_is_ignored() {
+        // The only blank line is 40% before the target end
+        let text = format!("{}\n\n{}", "a".repeat(58), "b".repeat(100));
+        let chunker = Chunker::new(100, 0).with_boundary_hint(BoundaryHint::BlankLine);
+        let chunks = chunker.chunk_text(&text, Path::new("a.txt"));
+
+        assert_eq!(chunks[0].end_offset, 100);
+        assert_contiguous(&text, &chunks, 0);
+    }
 }
@@ -0,0 +1,337 @@
+//! Content hashing and duplicate file detection.
+//!
+//! Vendored copies and build outputs often contain byte-identical
+//! files. The pipeline hashes every file it reads and records the
+//! result in a per-session [`ContentManifest`]. When deduplication
+//! is enabled, only the first file with a given hash is chunked;
+//! later copies are recorded as duplicates of it.
+//!
+//! Comparing the hashes of two runs also finds renamed files: content
+//! that left one path and appeared under another (see [`detect_renames`]).
+
+use crate::core::types::FileRename;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use xxhash_rust::xxh3::{xxh3_128, Xxh3};
+
+/// Hash algorithm used for content hashes
+///
+/// Stored in the manifest so the algorithm can change without
+/// misinterpreting hashes written by older versions.
+pub const HASH_ALGORITHM: &str = "xxh3-128";
+
+/// Compute the content hash of a file as a lowercase hex string
+pub fn content_hash(contents: &str) -> String {
+    format!("{:032x}", xxh3_128(contents.as_bytes()))
+}
+
+/// [`content_hash`] of contents read in pieces
+#[derive(Default, Clone)]
+pub struct ContentHasher(Xxh3);
+
+impl ContentHasher {
+    /// Add the next piece of the contents
+    pub fn update(&mut self, piece: &str) {
+        self.0.update(piece.as_bytes());
+    }
+
+    /// Hash of all pieces, as [`content_hash`] gives for them joined
+    pub fn finish(&self) -> String {
+        format!("{:032x}", self.0.digest128())
+    }
+}
+
+/// Per-session record of file content hashes and duplicates
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContentManifest {
+    /// Hash algorithm used for all entries
+    pub algorithm: String,
+
+    /// Content hash per file path
+    pub files: BTreeMap<String, String>,
+
+    /// Duplicate paths per indexed (first-seen) file path
+    #[serde(default)]
+    pub duplicates: BTreeMap<String, Vec<String>>,
+
+    /// Files cut off at `max_chunks_per_file`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub truncated: BTreeSet<String>,
+}
+
+impl Default for ContentManifest {
+    fn default() -> Self {
+        Self {
+            algorithm: HASH_ALGORITHM.to_string(),
+            files: BTreeMap::new(),
+            duplicates: BTreeMap::new(),
+            truncated: BTreeSet::new(),
+        }
+    }
+}
+
+impl ContentManifest {
+    /// Record the content hash of a file
+    pub fn record(&mut self, path: String, hash: String) {
+        self.files.insert(path, hash);
+    }
+
+    /// Record that `duplicate` has the same content as `original`
+    pub fn record_duplicate(&mut self, original: &str, duplicate: String) {
+        self.duplicates
+            .entry(original.to_string())
+            .or_default()
+            .push(duplicate);
+    }
+
+    /// Paths that were skipped because they duplicate `path`
+    pub fn duplicates_of(&self, path: &str) -> &[String] {
+        self.duplicates.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Record that only the first chunks of `path` were indexed
+    pub fn record_truncated(&mut self, path: String) {
+        self.truncated.insert(path);
+    }
+
+    /// Whether `path` was cut off at the session's chunk cap
+    pub fn is_truncated(&self, path: &str) -> bool {
+        self.truncated.contains(path)
+    }
+
+    /// Total number of files skipped as duplicates
+    pub fn duplicate_count(&self) -> usize {
+        self.duplicates.values().map(Vec::len).sum()
+    }
+
+    /// Check whether any duplicates were recorded
+    pub fn has_duplicates(&self) -> bool {
+        !self.duplicates.is_empty()
+    }
+}
+
+/// Renamed files listed in [`IndexStats`](crate::core::types::IndexStats);
+/// `files_renamed` always counts all of them
+pub const MAX_RENAMED_FILES: usize = 200;
+
+/// Files whose content moved to a new path between two runs
+///
+/// Both maps go from path to content hash. A file is renamed when its
+/// old path is gone, a new path appeared, and both have the same hash.
+/// Case-only renames (`Readme.md` to `README.md`, as reported on
+/// case-insensitive filesystems) are paired first. When several gone or
+/// new paths share a hash (identical copies), each new path is paired
+/// with the gone path in the nearest directory; paths with no single
+/// nearest match stay unpaired, i.e. removed and added. Empty files all
+/// share one hash and are never paired. Sorted by new path.
+pub fn detect_renames(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<FileRename> {
+    let empty = content_hash("");
+    let by_hash = |files: &BTreeMap<String, String>, other: &BTreeMap<String, String>| {
+        let mut paths: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (path, hash) in files {
+            if *hash != empty && !other.contains_key(path) {
+                paths.entry(hash.clone()).or_default().push(path.clone());
+            }
+        }
+        paths
+    };
+    let mut gone = by_hash(previous, current);
+    let appeared = by_hash(current, previous);
+
+    let mut renames = Vec::new();
+    for (hash, mut to) in appeared {
+        let Some(mut from) = gone.remove(&hash) else {
+            continue;
+        };
+        to.retain(|new| {
+            let new_lower = new.to_lowercase();
+            match from.iter().position(|old| old.to_lowercase() == new_lower) {
+                Some(i) => {
+                    renames.push(FileRename {
+                        from: from.remove(i),
+                        to: new.clone(),
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+        pair_by_proximity(from, to, &mut renames);
+    }
+    renames.sort_by(|a, b| a.to.cmp(&b.to));
+    renames
+}
+
+/// Pair identical gone and new paths that are each other's single
+/// nearest match, until no such pair is left
+fn pair_by_proximity(mut from: Vec<String>, mut to: Vec<String>, renames: &mut Vec<FileRename>) {
+    while !from.is_empty() && !to.is_empty() {
+        let pair = to.iter().enumerate().find_map(|(t, new)| {
+            let f = nearest(new, &from)?;
+            (nearest(&from[f], &to)? == t).then_some((f, t))
+        });
+        let Some((f, t)) = pair else {
+            return;
+        };
+        renames.push(FileRename {
+            from: from.remove(f),
+            to: to.remove(t),
+        });
+    }
+}
+
+/// Index of the candidate sharing the most leading directories with
+/// `path`, if exactly one does
+fn nearest(path: &str, candidates: &[String]) -> Option<usize> {
+    let shared: Vec<usize> = candidates
+        .iter()
+        .map(|candidate| shared_directories(path, candidate))
+        .collect();
+    let best = *shared.iter().max()?;
+    let mut at_best = shared.iter().enumerate().filter(|(_, &n)| n == best);
+    let (index, _) = at_best.next()?;
+    at_best.next().is_none().then_some(index)
+}
+
+/// Leading directories two `/`-separated paths have in common
+fn shared_directories(a: &str, b: &str) -> usize {
+    directory(a)
+        .split('/')
+        .zip(directory(b).split('/'))
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// Directory part of a `/`-separated path (`""` for a bare file name)
+fn directory(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        let a = content_hash("fn main() {}");
+        let b = content_hash("fn main() {}");
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 32);
+    }
+
+    #[test]
+    fn test_content_hash_differs() {
+        assert_ne!(content_hash("fn a() {}"), content_hash("fn b() {}"));
+    }
+
+    #[test]
+    fn test_content_hasher_matches_content_hash() {
+        let text = "fn café() {}\n".repeat(10_000);
+        let mut hasher = ContentHasher::default();
+        for piece in text.split_inclusive('\n') {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finish(), content_hash(&text));
+    }
+
+    #[test]
+    fn test_manifest_records_duplicates() {
+        let mut manifest = ContentManifest::default();
+        manifest.record_duplicate("/src/a.rs", "/vendor/a.rs".to_string());
+        manifest.record_duplicate("/src/a.rs", "/build/a.rs".to_string());
+
+        assert_eq!(manifest.duplicate_count(), 2);
+        assert_eq!(
+            manifest.duplicates_of("/src/a.rs"),
+            &["/vendor/a.rs".to_string(), "/build/a.rs".to_string()]
+        );
+        assert!(manifest.duplicates_of("/src/b.rs").is_empty());
+    }
+
+    #[test]
+    fn test_manifest_stores_algorithm() {
+        let manifest = ContentManifest::default();
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert!(json.contains("\"algorithm\":\"xxh3-128\""));
+    }
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, text)| (path.to_string(), content_hash(text)))
+            .collect()
+    }
+
+    fn pairs(renames: &[FileRename]) -> Vec<(&str, &str)> {
+        renames
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_renames() {
+        let previous = files(&[
+            ("/r/src/old.rs", "fn moved() {}"),
+            ("/r/src/edit.rs", "fn before() {}"),
+            ("/r/src/keep.rs", "fn keep() {}"),
+            ("/r/gone.rs", "fn gone() {}"),
+        ]);
+        let current = files(&[
+            ("/r/src/new.rs", "fn moved() {}"),
+            ("/r/src/edit.rs", "fn after() {}"),
+            ("/r/src/keep.rs", "fn keep() {}"),
+            ("/r/added.rs", "fn added() {}"),
+        ]);
+
+        let renames = detect_renames(&previous, &current);
+        assert_eq!(pairs(&renames), vec![("/r/src/old.rs", "/r/src/new.rs")]);
+        assert!(detect_renames(&previous, &previous).is_empty());
+    }
+
+    #[test]
+    fn test_detect_renames_identical_copies_by_proximity() {
+        let previous = files(&[
+            ("/r/a/util.rs", "fn util() {}"),
+            ("/r/b/util.rs", "fn util() {}"),
+        ]);
+        let current = files(&[
+            ("/r/a/sub/util.rs", "fn util() {}"),
+            ("/r/b/sub/util.rs", "fn util() {}"),
+        ]);
+        assert_eq!(
+            pairs(&detect_renames(&previous, &current)),
+            vec![
+                ("/r/a/util.rs", "/r/a/sub/util.rs"),
+                ("/r/b/util.rs", "/r/b/sub/util.rs"),
+            ]
+        );
+
+        // No nearest candidate: removed and added
+        let previous = files(&[("/r/lib/util.rs", "fn util() {}")]);
+        let current = files(&[
+            ("/r/src/util.rs", "fn util() {}"),
+            ("/r/docs/util.rs", "fn util() {}"),
+        ]);
+        assert!(detect_renames(&previous, &current).is_empty());
+    }
+
+    #[test]
+    fn test_detect_renames_case_only_and_empty() {
+        let previous = files(&[
+            ("/r/docs/Readme.md", "# Title"),
+            ("/r/docs/readme.txt", "# Title"),
+            ("/r/empty.rs", ""),
+        ]);
+        let current = files(&[("/r/docs/README.md", "# Title"), ("/r/blank.rs", "")]);
+
+        let renames = detect_renames(&previous, &current);
+        assert_eq!(
+            pairs(&renames),
+            vec![("/r/docs/Readme.md", "/r/docs/README.md")]
+        );
+    }
+}
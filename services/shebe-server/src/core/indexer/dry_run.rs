@@ -0,0 +1,339 @@
+//! Indexing dry runs.
+//!
+//! Walking a large repository is cheap compared to reading, chunking
+//! and storing it. A dry run only walks the tree with the session's
+//! patterns and size limit and summarizes what indexing would pick
+//! up, so patterns can be checked before committing to a full index.
+//!
+//! A re-index dry run also compares the matched files with the ones a
+//! session holds, so a rebuild can be previewed before the session is
+//! replaced.
+
+use crate::core::indexer::walker::WalkReport;
+use crate::core::indexer::Chunker;
+use crate::core::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Number of matched paths listed in a dry run report
+pub const DRY_RUN_SAMPLE_PATHS: usize = 50;
+
+/// Number of extensions listed in a dry run report
+pub const DRY_RUN_TOP_EXTENSIONS: usize = 10;
+
+/// Number of added and removed paths listed in a re-index dry run
+pub const DRY_RUN_SAMPLE_CHANGES: usize = 20;
+
+/// File count and size for one file extension
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExtensionStat {
+    /// Extension without the dot (empty for files without one)
+    pub extension: String,
+
+    /// Matched files with this extension
+    pub files: usize,
+
+    /// Total size of those files in bytes
+    pub bytes: u64,
+}
+
+/// What a single exclude pattern left out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PatternExclusion {
+    /// Exclude pattern as given
+    pub pattern: String,
+
+    /// Files excluded by this pattern
+    pub files: usize,
+
+    /// Directories pruned by this pattern (their contents are not counted)
+    pub directories: usize,
+}
+
+/// Summary of what indexing a directory would do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    /// Files that would be indexed
+    pub files: usize,
+
+    /// Total size of those files in bytes
+    pub total_bytes: u64,
+
+    /// Estimated chunk count (file sizes stand in for character counts)
+    pub estimated_chunks: usize,
+
+    /// Most common extensions, by file count
+    pub top_extensions: Vec<ExtensionStat>,
+
+    /// First matched paths (sorted, relative to the root)
+    pub sample_paths: Vec<String>,
+
+    /// Exclusion counts for every exclude pattern, in the given order
+    pub exclusions: Vec<PatternExclusion>,
+
+    /// Files over the size limit
+    pub skipped_too_large: usize,
+
+    /// Files matching no include pattern
+    pub skipped_not_included: usize,
+}
+
+/// How the files a session holds compare with a new walk
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileListDelta {
+    /// Files the walk matches that the session does not hold
+    pub added: usize,
+
+    /// Files the session holds that the walk no longer matches
+    pub removed: usize,
+
+    /// Files in both (their contents are not compared)
+    pub unchanged: usize,
+
+    /// First added paths (sorted, relative to the root)
+    pub sample_added: Vec<String>,
+
+    /// First removed paths (sorted, relative to the root)
+    pub sample_removed: Vec<String>,
+}
+
+/// What re-indexing a session would change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReindexDryRun {
+    /// Walk of the repository with the effective configuration
+    pub report: DryRunReport,
+
+    /// Files added and removed compared with the session
+    pub files: FileListDelta,
+
+    /// Chunks the session holds now
+    pub indexed_chunks: u64,
+
+    /// Whether the recorded repository path no longer resolves to
+    /// itself (it was removed, or replaced by a link elsewhere)
+    pub repository_moved: bool,
+
+    /// Where the recorded path resolves to now, if anywhere
+    pub resolved_path: Option<PathBuf>,
+}
+
+impl FileListDelta {
+    /// Compare the `indexed` paths of a session with the `matched`
+    /// paths of a walk
+    ///
+    /// Paths are compared normalized, as the index stores them; sample
+    /// paths have `prefix` (the repository root) removed.
+    pub fn compare(indexed: &[String], matched: &[String], prefix: &str) -> Self {
+        let indexed: BTreeSet<String> = indexed.iter().map(|p| paths::normalize(p)).collect();
+        let matched: BTreeSet<String> = matched.iter().map(|p| paths::normalize(p)).collect();
+        let relative = |path: &String| path.strip_prefix(prefix).unwrap_or(path).to_string();
+
+        let added: Vec<&String> = matched.difference(&indexed).collect();
+        let removed: Vec<&String> = indexed.difference(&matched).collect();
+        Self {
+            added: added.len(),
+            removed: removed.len(),
+            unchanged: matched.intersection(&indexed).count(),
+            sample_added: added
+                .into_iter()
+                .take(DRY_RUN_SAMPLE_CHANGES)
+                .map(relative)
+                .collect(),
+            sample_removed: removed
+                .into_iter()
+                .take(DRY_RUN_SAMPLE_CHANGES)
+                .map(relative)
+                .collect(),
+        }
+    }
+
+    /// Whether the walk matches exactly the files the session holds
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0
+    }
+}
+
+impl PatternExclusion {
+    /// Whether the pattern left anything out
+    pub fn matched(&self) -> bool {
+        self.files > 0 || self.directories > 0
+    }
+}
+
+impl DryRunReport {
+    /// Summarize a walk of `root`
+    ///
+    /// `exclude_patterns` must be in the order the walker used.
+    pub fn from_walk(
+        report: WalkReport,
+        root: &Path,
+        exclude_patterns: Vec<String>,
+        chunker: &Chunker,
+    ) -> Self {
+        let mut extensions: HashMap<String, ExtensionStat> = HashMap::new();
+        let mut total_bytes = 0;
+        let mut estimated_chunks = 0;
+
+        for file in &report.files {
+            total_bytes += file.size;
+            estimated_chunks += chunker.chunk_count(file.size as usize);
+
+            let extension = file
+                .path
+                .extension()
+                .map(|e| e.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let stat = extensions
+                .entry(extension.clone())
+                .or_insert_with(|| ExtensionStat {
+                    extension,
+                    files: 0,
+                    bytes: 0,
+                });
+            stat.files += 1;
+            stat.bytes += file.size;
+        }
+
+        let mut top_extensions: Vec<ExtensionStat> = extensions.into_values().collect();
+        top_extensions.sort_by(|a, b| {
+            b.files
+                .cmp(&a.files)
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+        top_extensions.truncate(DRY_RUN_TOP_EXTENSIONS);
+
+        let mut sample_paths: Vec<String> = report
+            .files
+            .iter()
+            .map(|f| paths::normalize_path(f.path.strip_prefix(root).unwrap_or(&f.path)))
+            .collect();
+        sample_paths.sort();
+        sample_paths.truncate(DRY_RUN_SAMPLE_PATHS);
+
+        let exclusions = exclude_patterns
+            .into_iter()
+            .zip(report.excluded_files.iter().zip(&report.excluded_dirs))
+            .map(|(pattern, (&files, &directories))| PatternExclusion {
+                pattern,
+                files,
+                directories,
+            })
+            .collect();
+
+        Self {
+            files: report.files.len(),
+            total_bytes,
+            estimated_chunks,
+            top_extensions,
+            sample_paths,
+            exclusions,
+            skipped_too_large: report.too_large,
+            skipped_not_included: report.not_included,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::indexer::walker::WalkedFile;
+    use std::path::PathBuf;
+
+    fn walked(path: &str, size: u64) -> WalkedFile {
+        WalkedFile {
+            path: PathBuf::from(path),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_summarizes_files() {
+        let report = WalkReport {
+            files: vec![
+                walked("/repo/src/b.rs", 1000),
+                walked("/repo/src/a.rs", 100),
+                walked("/repo/README.md", 50),
+                walked("/repo/Makefile", 0),
+            ],
+            excluded_files: vec![3],
+            excluded_dirs: vec![1],
+            ignore_patterns: vec![],
+            too_large: 2,
+            not_included: 4,
+            skipped: Default::default(),
+            ..WalkReport::default()
+        };
+
+        let summary = DryRunReport::from_walk(
+            report,
+            Path::new("/repo"),
+            vec!["**/target/**".to_string()],
+            &Chunker::new(512, 64),
+        );
+
+        assert_eq!(summary.files, 4);
+        assert_eq!(summary.total_bytes, 1150);
+        // 1000 bytes -> 3 chunks, 100 -> 1, 50 -> 1, empty -> 0
+        assert_eq!(summary.estimated_chunks, 5);
+        assert_eq!(summary.top_extensions[0].extension, "rs");
+        assert_eq!(summary.top_extensions[0].files, 2);
+        assert_eq!(summary.top_extensions[0].bytes, 1100);
+        assert_eq!(
+            summary.sample_paths,
+            vec!["Makefile", "README.md", "src/a.rs", "src/b.rs"]
+        );
+        assert_eq!(
+            summary.exclusions,
+            vec![PatternExclusion {
+                pattern: "**/target/**".to_string(),
+                files: 3,
+                directories: 1,
+            }]
+        );
+        assert_eq!(summary.skipped_too_large, 2);
+        assert_eq!(summary.skipped_not_included, 4);
+    }
+
+    #[test]
+    fn test_file_list_delta() {
+        let indexed = vec![
+            "/repo/src/a.rs".to_string(),
+            "/repo/src/gone.rs".to_string(),
+        ];
+        let matched = vec![
+            "/repo/src/a.rs".to_string(),
+            "/repo/src/new.rs".to_string(),
+            "/repo/README.md".to_string(),
+        ];
+
+        let delta = FileListDelta::compare(&indexed, &matched, "/repo/");
+
+        assert_eq!((delta.added, delta.removed, delta.unchanged), (2, 1, 1));
+        assert_eq!(delta.sample_added, vec!["README.md", "src/new.rs"]);
+        assert_eq!(delta.sample_removed, vec!["src/gone.rs"]);
+        assert!(!delta.is_empty());
+        assert!(FileListDelta::compare(&indexed, &indexed, "/repo/").is_empty());
+    }
+
+    #[test]
+    fn test_sample_paths_capped() {
+        let report = WalkReport {
+            files: (0..DRY_RUN_SAMPLE_PATHS + 5)
+                .map(|i| walked(&format!("/repo/f{i:03}.rs"), 10))
+                .collect(),
+            ..WalkReport::default()
+        };
+
+        let summary = DryRunReport::from_walk(
+            report,
+            Path::new("/repo"),
+            Vec::new(),
+            &Chunker::new(512, 64),
+        );
+
+        assert_eq!(summary.files, DRY_RUN_SAMPLE_PATHS + 5);
+        assert_eq!(summary.sample_paths.len(), DRY_RUN_SAMPLE_PATHS);
+        assert_eq!(summary.sample_paths[0], "f000.rs");
+    }
+}
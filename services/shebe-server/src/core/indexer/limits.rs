@@ -0,0 +1,182 @@
+//! Guardrails against indexing far more than intended.
+//!
+//! A mistyped path (`/`, the home directory) or an overly broad
+//! include pattern can make a walk pick up millions of files and fill
+//! the disk. [`IndexLimits`] caps the files and bytes one session may
+//! index; the walker counts as it goes and stops with
+//! [`ShebeError::IndexTooLarge`] once a cap is exceeded. It also
+//! bounds the memory needed per file: files over a size threshold are
+//! read in pieces, and files with absurdly long lines are skipped.
+//! [`check_index_path`] refuses paths that are almost never meant to
+//! be indexed unless the caller explicitly allows them.
+
+use std::path::{Path, PathBuf};
+
+use crate::core::config::LimitsConfig;
+use crate::core::error::{Result, ShebeError};
+
+/// Most files and source bytes one session may index, and how large
+/// files are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexLimits {
+    /// Most files accepted by the walk
+    pub max_files: usize,
+
+    /// Most bytes of accepted files, in total
+    pub max_bytes: u64,
+
+    /// Files larger than this are read and chunked in bounded pieces
+    pub stream_threshold_bytes: u64,
+
+    /// Files with a longer line (in characters) are skipped; 0 disables
+    pub max_line_length: usize,
+}
+
+impl IndexLimits {
+    /// Limits from the `[limits]` configuration section
+    pub fn from_config(config: &LimitsConfig) -> Self {
+        Self {
+            max_files: config.max_files_per_session,
+            max_bytes: config.max_session_size_mb.saturating_mul(1024 * 1024),
+            stream_threshold_bytes: config.stream_threshold_mb.saturating_mul(1024 * 1024),
+            max_line_length: config.max_line_length,
+        }
+    }
+
+    /// Error out if `files` files totalling `bytes` exceed the limits
+    ///
+    /// The error names the limit that was hit and how to raise it.
+    pub fn check(&self, root: &Path, files: usize, bytes: u64) -> Result<()> {
+        if files > self.max_files {
+            return Err(ShebeError::IndexTooLarge(format!(
+                "{} has more than {} files to index (limits.max_files_per_session). \
+                 Narrow the include/exclude patterns or add a .shebeignore, or raise \
+                 limits.max_files_per_session (SHEBE_MAX_FILES_PER_SESSION)",
+                root.display(),
+                self.max_files
+            )));
+        }
+        if bytes > self.max_bytes {
+            return Err(ShebeError::IndexTooLarge(format!(
+                "{} has more than {} MB of files to index (limits.max_session_size_mb). \
+                 Narrow the include/exclude patterns or add a .shebeignore, or raise \
+                 limits.max_session_size_mb (SHEBE_MAX_SESSION_SIZE_MB)",
+                root.display(),
+                self.max_bytes / (1024 * 1024)
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for IndexLimits {
+    fn default() -> Self {
+        Self::from_config(&LimitsConfig::default())
+    }
+}
+
+/// Refuse to index a filesystem root, home directory or storage root
+///
+/// These are almost always a mistake: they hold far more than one
+/// project, and indexing the storage root would index Shebe's own
+/// indexes. `allow_dangerous_path` skips the check.
+///
+/// # Errors
+///
+/// `InvalidPath` naming the kind of directory and the override.
+pub fn check_index_path(
+    path: &Path,
+    storage_root: &Path,
+    allow_dangerous_path: bool,
+) -> Result<()> {
+    if allow_dangerous_path {
+        return Ok(());
+    }
+
+    let path = canonical(path);
+    let kind = if path.parent().is_none() {
+        "the filesystem root"
+    } else if dirs::home_dir().is_some_and(|home| canonical(&home) == path) {
+        "your home directory"
+    } else if canonical(storage_root) == path {
+        "the Shebe storage root"
+    } else {
+        return Ok(());
+    };
+
+    Err(ShebeError::InvalidPath(format!(
+        "Refusing to index {} because it is {kind}. Index a project directory \
+         instead, or pass allow_dangerous_path: true (--allow-dangerous-path \
+         on the command line) to index it anyway",
+        path.display()
+    )))
+}
+
+/// `path` with symlinks and `..` resolved, or as given if it cannot be
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_limits_from_config() {
+        let limits = IndexLimits::default();
+        assert_eq!(limits.max_files, 200_000);
+        assert_eq!(limits.max_bytes, 4096 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_check_names_limit_and_override() {
+        let limits = IndexLimits {
+            max_files: 10,
+            max_bytes: 1024 * 1024,
+            ..IndexLimits::default()
+        };
+        assert!(limits.check(Path::new("/repo"), 10, 1024 * 1024).is_ok());
+
+        let err = limits.check(Path::new("/repo"), 11, 0).unwrap_err();
+        assert!(matches!(err, ShebeError::IndexTooLarge(_)));
+        assert!(err.to_string().contains("limits.max_files_per_session"));
+        assert!(err.to_string().contains("SHEBE_MAX_FILES_PER_SESSION"));
+
+        let err = limits
+            .check(Path::new("/repo"), 1, 1024 * 1024 + 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("more than 1 MB"));
+        assert!(err.to_string().contains("SHEBE_MAX_SESSION_SIZE_MB"));
+    }
+
+    #[test]
+    fn test_check_index_path_refuses_dangerous_paths() {
+        let storage = TempDir::new().unwrap();
+
+        let err = check_index_path(Path::new("/"), storage.path(), false).unwrap_err();
+        assert!(err.to_string().contains("the filesystem root"));
+        assert!(err.to_string().contains("allow_dangerous_path"));
+
+        let err = check_index_path(storage.path(), storage.path(), false).unwrap_err();
+        assert!(err.to_string().contains("the Shebe storage root"));
+
+        if let Some(home) = dirs::home_dir() {
+            assert!(check_index_path(&home, storage.path(), false).is_err());
+        }
+
+        assert!(check_index_path(Path::new("/"), storage.path(), true).is_ok());
+    }
+
+    #[test]
+    fn test_check_index_path_allows_project_dirs() {
+        let storage = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+
+        assert!(check_index_path(project.path(), storage.path(), false).is_ok());
+        // Sessions live below the storage root, which is fine to index
+        let sessions = storage.path().join("sessions");
+        std::fs::create_dir(&sessions).unwrap();
+        assert!(check_index_path(&sessions, storage.path(), false).is_ok());
+    }
+}
@@ -7,6 +7,22 @@
 //! - Configurable chunk size and overlap
 //! - File system walking with pattern matching
 //! - Indexing pipeline orchestration
+//! - Content hashing and duplicate detection
+//! - Dry runs that summarize what would be indexed
+//! - Per-pattern match counts and a check for include patterns
+//!   that match nothing
+//! - Progress reporting and cancellation between files
+//! - Optional per-stage timing of a run (profiling)
+//! - Skip reasons for files left out of an index
+//! - Language presets expanded into include patterns
+//! - Repository-local settings (`.shebeignore`, `.shebe.toml`)
+//! - Size limits and dangerous path checks
+//! - Clear errors for file paths, or detection of their repository root
+//! - Optional stripping of long base64/minified blobs
+//! - Last-change times of files (git commit or filesystem mtime)
+//! - Bounded-memory reading of large files and a line length guard
+//! - Record-aware chunking of data files (CSV, TSV, JSON Lines)
+//! - Heading breadcrumbs for chunks of Markdown and reStructuredText
 //!
 //! # Safety
 //!
@@ -16,9 +32,33 @@
 //! special Unicode sequences.
 
 pub mod chunker;
+pub mod dedupe;
+pub mod dry_run;
+pub mod limits;
+pub mod modified_times;
+pub mod patterns;
 pub mod pipeline;
+pub mod presets;
+pub mod profile;
+pub mod progress;
+pub mod records;
+pub mod repo_config;
+pub mod root;
+pub mod sections;
+pub mod skipped;
+pub mod stream;
+pub mod strip;
 pub mod walker;
 
-pub use chunker::Chunker;
-pub use pipeline::IndexingPipeline;
-pub use walker::FileWalker;
+pub use chunker::{is_word_char, BoundaryHint, ChunkStream, Chunker, TOKEN_EDGE_WINDOW};
+pub use dedupe::ContentManifest;
+pub use dry_run::{DryRunReport, FileListDelta, ReindexDryRun};
+pub use limits::{check_index_path, IndexLimits};
+pub use modified_times::ModifiedTimes;
+pub use pipeline::{IndexingPipeline, IndexingPipelineBuilder};
+pub use profile::IndexProfiler;
+pub use progress::IndexProgress;
+pub use repo_config::{resolve_session_config, IndexOverrides, ResolvedConfig};
+pub use root::{resolve_index_root, DetectedRoot, IndexRoot};
+pub use skipped::{FileExplanation, SkipLog, MAX_SKIPPED_FILES};
+pub use walker::{FileWalker, WalkReport};
@@ -0,0 +1,196 @@
+//! Last-change times of indexed files.
+//!
+//! Each chunk records when its file last changed, so a search can be
+//! restricted to recently changed files (`modified_within`). In a git
+//! checkout this is the time of the last commit that touched the file,
+//! read for all files at once:
+//!
+//! 1. `git ls-files` lists the tracked files among those indexed
+//! 2. `git log --name-only` walks history from `HEAD`, newest first;
+//!    the first commit naming a file is its last change. The walk stops
+//!    as soon as every tracked file has been seen.
+//!
+//! Untracked files, repositories that are not git checkouts, a missing
+//! `git` executable and sessions with `git_metadata` off all fall back
+//! to the filesystem modification time.
+
+use crate::core::types::ModifiedTimeSource;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+/// Marks the commit time lines of the `git log` output (file names
+/// cannot contain NUL)
+const COMMIT_MARKER: char = '\0';
+
+/// When each indexed file last changed, in seconds since the epoch
+#[derive(Debug, Clone, Default)]
+pub struct ModifiedTimes {
+    source: Option<ModifiedTimeSource>,
+    times: HashMap<PathBuf, i64>,
+}
+
+impl ModifiedTimes {
+    /// Look up the last change of each of `files` (under `root`)
+    ///
+    /// With `use_git`, files tracked in a git checkout at `root` get
+    /// their last commit time. Every other file gets its modification
+    /// time; files that cannot be read get none.
+    pub fn collect<'a>(
+        root: &Path,
+        files: impl IntoIterator<Item = &'a Path>,
+        use_git: bool,
+    ) -> Self {
+        let files: HashSet<&Path> = files.into_iter().collect();
+        let commits = if use_git {
+            git_commit_times(root, &files)
+        } else {
+            None
+        };
+        let source = if commits.is_some() {
+            ModifiedTimeSource::Git
+        } else {
+            ModifiedTimeSource::Mtime
+        };
+
+        let mut times = commits.unwrap_or_default();
+        for file in files {
+            if !times.contains_key(file) {
+                if let Some(mtime) = file_mtime(file) {
+                    times.insert(file.to_path_buf(), mtime);
+                }
+            }
+        }
+
+        Self {
+            source: Some(source),
+            times,
+        }
+    }
+
+    /// Modification times recorded elsewhere, such as in an archive
+    pub fn from_mtimes(times: HashMap<PathBuf, i64>) -> Self {
+        Self {
+            source: Some(ModifiedTimeSource::Mtime),
+            times,
+        }
+    }
+
+    /// Where the times came from (`None` when none were collected)
+    pub fn source(&self) -> Option<ModifiedTimeSource> {
+        self.source
+    }
+
+    /// Last change of `file`, in seconds since the epoch
+    pub fn get(&self, file: &Path) -> Option<i64> {
+        self.times.get(file).copied()
+    }
+}
+
+/// Last commit time of each tracked file among `files`
+///
+/// `None` when `root` is not inside a git work tree or git cannot be
+/// run.
+fn git_commit_times(root: &Path, files: &HashSet<&Path>) -> Option<HashMap<PathBuf, i64>> {
+    let listed = git(root)
+        .args(["ls-files", "-z"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let mut pending: HashSet<PathBuf> = listed
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| root.join(String::from_utf8_lossy(name).as_ref()))
+        .filter(|path| files.contains(path.as_path()))
+        .collect();
+
+    let mut times = HashMap::new();
+    if pending.is_empty() {
+        return Some(times);
+    }
+
+    let mut child = git(root)
+        .args([
+            "log",
+            "--name-only",
+            "--relative",
+            "--no-renames",
+            "--format=%x00%ct",
+            "--",
+            ".",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let stdout = child.stdout.take()?;
+
+    let mut commit_time = None;
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else { break };
+        if let Some(time) = line.strip_prefix(COMMIT_MARKER) {
+            commit_time = time.trim().parse::<i64>().ok();
+        } else if let (Some(time), false) = (commit_time, line.is_empty()) {
+            let path = root.join(&line);
+            if pending.remove(&path) {
+                times.insert(path, time);
+                if pending.is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Everything needed has been read; the rest of history is not
+    let _ = child.kill();
+    let _ = child.wait();
+    Some(times)
+}
+
+/// Non-interactive git command run in `root`, with file names unquoted
+fn git(root: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.env("GIT_TERMINAL_PROMPT", "0")
+        .arg("-C")
+        .arg(root)
+        .args(["-c", "core.quotepath=off"])
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Filesystem modification time of `file`, in seconds since the epoch
+fn file_mtime(file: &Path) -> Option<i64> {
+    let modified = fs::metadata(file).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_non_git_directory_uses_mtime() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "fn a() {}").unwrap();
+
+        let times = ModifiedTimes::collect(dir.path(), [file.as_path()], true);
+        assert_eq!(times.source(), Some(ModifiedTimeSource::Mtime));
+        assert_eq!(times.get(&file), file_mtime(&file));
+        assert!(times.get(&dir.path().join("missing.rs")).is_none());
+    }
+
+    #[test]
+    fn test_default_has_no_times() {
+        let times = ModifiedTimes::default();
+        assert_eq!(times.source(), None);
+        assert!(times.get(Path::new("/a.rs")).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Include pattern diagnostics.
+//!
+//! Include patterns are matched against each file's absolute path and
+//! its file name, so `*.rs` matches everywhere but a relative pattern
+//! such as `src/**/*.rs` matches nothing. When a walk sees files but
+//! none of them match an include pattern, the session would be empty;
+//! this is reported (or refused with `strict_patterns`) together with
+//! the nearest rewrite of each pattern that would have matched.
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::types::PatternCounts;
+use glob::Pattern;
+
+/// Fewest files a walk must see before matching none of them is
+/// reported (an empty or near-empty directory is not suspicious)
+pub const ZERO_MATCH_MIN_CANDIDATES: usize = 10;
+
+/// Common corrections of an include pattern, most likely first
+///
+/// - `src/*.rs` -> `**/src/*.rs` (patterns are matched against
+///   absolute paths)
+/// - `src/` or `src` -> `**/src/**` (a directory)
+/// - `.rs` or `rs` -> `*.rs` (an extension)
+/// - `*.RS` -> `*.rs` (case)
+pub(crate) fn rewrites(pattern: &str) -> Vec<String> {
+    let has_glob = pattern.contains(['*', '?', '[']);
+    let trimmed = pattern.trim_end_matches('/');
+    let mut candidates = Vec::new();
+
+    if !trimmed.is_empty() && !has_glob && !trimmed.contains('.') {
+        candidates.push(format!("**/{}/**", trimmed.trim_start_matches('/')));
+    }
+    if trimmed.contains('/') && !pattern.starts_with("**/") && !pattern.starts_with('/') {
+        if pattern.ends_with('/') {
+            candidates.push(format!("**/{trimmed}/**"));
+        } else {
+            candidates.push(format!("**/{pattern}"));
+        }
+    }
+    if !has_glob && !pattern.contains('/') {
+        match pattern.strip_prefix('.') {
+            Some(ext) if !ext.is_empty() => candidates.push(format!("*.{ext}")),
+            None if !pattern.is_empty() => candidates.push(format!("*.{pattern}")),
+            _ => {}
+        }
+    }
+    let lower = pattern.to_lowercase();
+    if lower != pattern {
+        candidates.push(lower);
+    }
+
+    let mut unique: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate != pattern && !unique.contains(&candidate) {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+/// Rewrites of every include pattern, labelled with the pattern index
+///
+/// Rewrites that are not valid globs are dropped.
+pub(crate) fn include_rewrites(include: &[Pattern]) -> Vec<(usize, Pattern)> {
+    include
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, pattern)| {
+            rewrites(pattern.as_str())
+                .into_iter()
+                .filter_map(move |rewrite| Pattern::new(&rewrite).ok().map(|p| (idx, p)))
+        })
+        .collect()
+}
+
+/// Report include patterns that matched no file
+///
+/// `rewrite_files` counts the files each of `rewrites` would have
+/// matched. Returns the warning to show, or `None` when a file matched
+/// or too few files were seen to tell.
+///
+/// # Errors
+///
+/// `ConfigError` with the same message when `strict` is set.
+pub(crate) fn check_include_matches(
+    counts: &PatternCounts,
+    rewrites: &[(usize, Pattern)],
+    rewrite_files: &[usize],
+    strict: bool,
+) -> Result<Option<String>> {
+    if counts.include.is_empty()
+        || counts.include.iter().any(|p| p.files > 0)
+        || counts.candidates < ZERO_MATCH_MIN_CANDIDATES
+    {
+        return Ok(None);
+    }
+
+    let mut misses = Vec::new();
+    for (idx, include) in counts.include.iter().enumerate() {
+        let best = rewrites
+            .iter()
+            .zip(rewrite_files)
+            .filter(|((pattern_idx, _), &files)| *pattern_idx == idx && files > 0)
+            .max_by_key(|(_, &files)| files);
+        misses.push(match best {
+            Some(((_, rewrite), files)) => format!(
+                "pattern '{}' matched 0 files, but '{}' would match {files}",
+                include.pattern,
+                rewrite.as_str()
+            ),
+            None => format!("pattern '{}' matched 0 files", include.pattern),
+        });
+    }
+
+    let message = format!(
+        "No file matches the include patterns ({} files seen): {}. \
+         Patterns are matched against absolute paths and file names; \
+         prefix directory patterns with '**/'.",
+        counts.candidates,
+        misses.join("; ")
+    );
+    if strict {
+        return Err(ShebeError::ConfigError(message));
+    }
+    tracing::warn!("{message}");
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::PatternCount;
+
+    #[test]
+    fn test_rewrites() {
+        assert_eq!(rewrites("src/*.rs"), vec!["**/src/*.rs"]);
+        assert_eq!(rewrites("src/"), vec!["**/src/**"]);
+        assert_eq!(rewrites("src"), vec!["**/src/**", "*.src"]);
+        assert_eq!(rewrites(".rs"), vec!["*.rs"]);
+        assert_eq!(rewrites("*.RS"), vec!["*.rs"]);
+        assert!(rewrites("**/*.rs").is_empty());
+        assert!(rewrites("*.rs").is_empty());
+    }
+
+    fn counts(candidates: usize, include: &[(&str, usize)]) -> PatternCounts {
+        PatternCounts {
+            candidates,
+            include: include
+                .iter()
+                .map(|&(pattern, files)| PatternCount {
+                    pattern: pattern.to_string(),
+                    files,
+                    directories: 0,
+                })
+                .collect(),
+            exclude: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_include_matches() {
+        let rewrites = include_rewrites(&[Pattern::new("src/*.rs").unwrap()]);
+
+        // Something matched, or too few files to tell
+        let matched = counts(50, &[("src/*.rs", 3)]);
+        assert_eq!(
+            check_include_matches(&matched, &rewrites, &[40], true).unwrap(),
+            None
+        );
+        let tiny = counts(2, &[("src/*.rs", 0)]);
+        assert_eq!(
+            check_include_matches(&tiny, &rewrites, &[2], true).unwrap(),
+            None
+        );
+
+        let missed = counts(50, &[("src/*.rs", 0)]);
+        let warning = check_include_matches(&missed, &rewrites, &[40], false)
+            .unwrap()
+            .unwrap();
+        assert!(warning.contains("'src/*.rs' matched 0 files, but '**/src/*.rs' would match 40"));
+
+        let err = check_include_matches(&missed, &rewrites, &[0], true).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("pattern 'src/*.rs' matched 0 files"));
+    }
+}
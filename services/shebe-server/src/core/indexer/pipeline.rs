@@ -1,26 +1,359 @@
 //! Indexing pipeline orchestration.
 //!
 //! Coordinates the end-to-end indexing workflow:
-//! 1. Walk directory tree
-//! 2. Read file contents
-//! 3. Chunk text
-//! 4. Prepare chunks for storage
+//! 1. Walk directory tree (or check an explicit file list)
+//! 2. Read file contents (large files in bounded pieces, see
+//!    [`stream`](crate::core::indexer::stream))
+//! 3. Hash contents (and skip duplicates when enabled)
+//! 4. Strip long blobs (when enabled) and chunk text (data files by
+//!    record, see [`records`](crate::core::indexer::records))
+//! 5. Prepare chunks for storage
+//!
+//! A run whose [`IndexProgress`] has profiling enabled times each of
+//! these stages (see [`profile`](crate::core::indexer::profile)).
 
-use std::fs;
-use std::path::Path;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use crate::core::archive::{Archive, ArchiveEntry};
 use crate::core::error::{Result, ShebeError};
-use crate::core::indexer::{Chunker, FileWalker};
-use crate::core::types::{Chunk, IndexStats};
+use crate::core::indexer::dedupe::{content_hash, ContentManifest};
+use crate::core::indexer::patterns::check_include_matches;
+use crate::core::indexer::profile::{timed, IndexProfiler};
+use crate::core::indexer::records::RecordFormat;
+use crate::core::indexer::sections::{assign_sections, SectionFormat};
+use crate::core::indexer::skipped::text_from_bytes;
+use crate::core::indexer::stream::{check_line_length, read_text_streaming, SkipError};
+use crate::core::indexer::strip::strip_high_entropy;
+use crate::core::indexer::{
+    BoundaryHint, Chunker, DryRunReport, FileExplanation, FileWalker, IndexLimits, IndexProgress,
+    SkipLog, WalkReport,
+};
+use crate::core::paths;
+use crate::core::storage::SessionConfig;
+use crate::core::types::{Chunk, IndexStage, IndexStats, SkipReason, TruncatedFile};
+
+/// File size limit used when a builder does not set one
+const DEFAULT_MAX_FILE_SIZE_MB: usize = 10;
+
+/// Builder for [`IndexingPipeline`]
+///
+/// Starts from the [`SessionConfig`] defaults (512 character chunks,
+/// 64 overlap, build directories excluded), a 10 MB file size limit
+/// and the default streaming threshold and maximum line length of
+/// [`IndexLimits`].
+///
+/// # Examples
+///
+/// ```
+/// use shebe::core::indexer::IndexingPipeline;
+///
+/// let pipeline = IndexingPipeline::builder()
+///     .chunk_size(800)
+///     .overlap(80)
+///     .include_patterns(["**/*.rs", "**/*.md"])
+///     .build()
+///     .unwrap();
+/// # let _ = pipeline;
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexingPipelineBuilder {
+    chunk_size: usize,
+    overlap: usize,
+    include_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    max_file_size_mb: usize,
+    dedupe: bool,
+    boundary_hint: BoundaryHint,
+    merge_tail_fragments: bool,
+    record_chunking: bool,
+    max_chunks_per_file: usize,
+    strip_run_chars: Option<usize>,
+    strict_patterns: bool,
+    limits: Option<IndexLimits>,
+    stream_threshold: u64,
+    max_line_length: usize,
+}
+
+impl Default for IndexingPipelineBuilder {
+    fn default() -> Self {
+        let defaults = SessionConfig::default();
+        let limits = IndexLimits::default();
+        Self {
+            chunk_size: defaults.chunk_size,
+            overlap: defaults.overlap,
+            include_patterns: defaults.include_patterns,
+            exclude_patterns: defaults.exclude_patterns,
+            max_file_size_mb: DEFAULT_MAX_FILE_SIZE_MB,
+            dedupe: defaults.dedupe,
+            boundary_hint: defaults.boundary_hint,
+            merge_tail_fragments: defaults.merge_tail_fragments,
+            record_chunking: defaults.record_chunking,
+            max_chunks_per_file: defaults.max_chunks_per_file,
+            strip_run_chars: None,
+            strict_patterns: defaults.strict_patterns,
+            limits: None,
+            stream_threshold: limits.stream_threshold_bytes,
+            max_line_length: limits.max_line_length,
+        }
+    }
+}
+
+impl IndexingPipelineBuilder {
+    /// Characters per chunk
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Characters shared by consecutive chunks (less than `chunk_size`)
+    pub fn overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Glob patterns for files to include (replaces the defaults)
+    pub fn include_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Glob patterns for files to exclude (replaces the defaults)
+    pub fn exclude_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Skip files larger than this many megabytes
+    pub fn max_file_size_mb(mut self, max_file_size_mb: usize) -> Self {
+        self.max_file_size_mb = max_file_size_mb;
+        self
+    }
+
+    /// Chunk only the first of several files with identical content
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Align chunk ends to blank lines or line breaks
+    pub fn boundary_hint(mut self, boundary_hint: BoundaryHint) -> Self {
+        self.boundary_hint = boundary_hint;
+        self
+    }
+
+    /// Merge a file's short final chunk into the one before it
+    pub fn merge_tail_fragments(mut self, merge: bool) -> Self {
+        self.merge_tail_fragments = merge;
+        self
+    }
+
+    /// Chunk CSV, TSV and JSON Lines files from whole records (default
+    /// true; see [`Chunker::chunk_records`])
+    ///
+    /// Such files are always read whole.
+    pub fn record_chunking(mut self, record_chunking: bool) -> Self {
+        self.record_chunking = record_chunking;
+        self
+    }
+
+    /// Index at most this many chunks of a file (default 2000); the
+    /// rest of a longer file is left out and reported in
+    /// [`IndexStats::truncated_files`]
+    pub fn max_chunks_per_file(mut self, max_chunks: usize) -> Self {
+        self.max_chunks_per_file = max_chunks;
+        self
+    }
+
+    /// Replace non-whitespace runs longer than `run_chars` characters
+    /// (and overlong lines) with placeholders before chunking; `None`
+    /// (the default) indexes file contents as they are
+    pub fn strip_high_entropy(mut self, run_chars: Option<usize>) -> Self {
+        self.strip_run_chars = run_chars;
+        self
+    }
+
+    /// Fail instead of warning when the include patterns match none of
+    /// the files a walk sees (see [`patterns`](crate::core::indexer::patterns))
+    pub fn strict_patterns(mut self, strict: bool) -> Self {
+        self.strict_patterns = strict;
+        self
+    }
+
+    /// Abort indexing once the walk exceeds these limits (none by
+    /// default), and take their streaming threshold and maximum line
+    /// length
+    pub fn limits(mut self, limits: IndexLimits) -> Self {
+        self.limits = Some(limits);
+        self.stream_threshold = limits.stream_threshold_bytes;
+        self.max_line_length = limits.max_line_length;
+        self
+    }
+
+    /// Read and chunk files larger than this many bytes in bounded
+    /// pieces
+    ///
+    /// Files stripped with [`strip_high_entropy`](Self::strip_high_entropy)
+    /// are always read whole.
+    pub fn stream_threshold(mut self, bytes: u64) -> Self {
+        self.stream_threshold = bytes;
+        self
+    }
+
+    /// Skip files with a line longer than this many characters (0
+    /// allows any length)
+    pub fn max_line_length(mut self, chars: usize) -> Self {
+        self.max_line_length = chars;
+        self
+    }
+
+    /// Take chunking, patterns, dedupe, stripping and the pattern check
+    /// from a session configuration
+    pub fn session_config(self, config: &SessionConfig) -> Self {
+        self.chunk_size(config.chunk_size)
+            .overlap(config.overlap)
+            .include_patterns(config.include_patterns.iter().cloned())
+            .exclude_patterns(config.exclude_patterns.iter().cloned())
+            .dedupe(config.dedupe)
+            .boundary_hint(config.boundary_hint)
+            .merge_tail_fragments(config.merge_tail_fragments)
+            .record_chunking(config.record_chunking)
+            .max_chunks_per_file(config.max_chunks_per_file)
+            .strip_high_entropy(config.strip_high_entropy.then_some(config.strip_run_chars))
+            .strict_patterns(config.strict_patterns)
+    }
+
+    /// Build the pipeline
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if a pattern is invalid, `overlap >= chunk_size`
+    /// or `max_chunks_per_file` is 0.
+    pub fn build(self) -> Result<IndexingPipeline> {
+        let (chunk_size, overlap) = (self.chunk_size, self.overlap);
+        if chunk_size == 0 || overlap >= chunk_size {
+            return Err(ShebeError::ConfigError(format!(
+                "Overlap ({overlap}) must be less than chunk size ({chunk_size})"
+            )));
+        }
+        if self.max_chunks_per_file == 0 {
+            return Err(ShebeError::ConfigError(
+                "max_chunks_per_file must be at least 1".to_string(),
+            ));
+        }
+
+        let walker = FileWalker::new(
+            self.include_patterns,
+            self.exclude_patterns,
+            self.max_file_size_mb,
+        )?;
+        let chunker = Chunker::new(chunk_size, overlap)
+            .with_boundary_hint(self.boundary_hint)
+            .with_merge_tail_fragments(self.merge_tail_fragments)
+            .with_max_chunks(self.max_chunks_per_file);
+
+        Ok(IndexingPipeline {
+            walker,
+            chunker,
+            dedupe: self.dedupe,
+            record_chunking: self.record_chunking,
+            strip_run_chars: self.strip_run_chars,
+            strict_patterns: self.strict_patterns,
+            limits: self.limits,
+            stream_threshold: self.stream_threshold,
+            max_line_length: self.max_line_length,
+        })
+    }
+}
 
 /// Orchestrates the indexing pipeline
 pub struct IndexingPipeline {
     walker: FileWalker,
     chunker: Chunker,
+    dedupe: bool,
+    record_chunking: bool,
+    strip_run_chars: Option<usize>,
+    strict_patterns: bool,
+    limits: Option<IndexLimits>,
+    stream_threshold: u64,
+    max_line_length: usize,
+}
+
+/// A file's text, or its chunks (and byte length) when it was chunked
+/// while being read
+enum FileText {
+    Whole(String),
+    Chunked(Vec<Chunk>, usize),
+}
+
+/// Chunks, hashes and counts of the files indexed so far
+#[derive(Default)]
+struct IndexedFiles {
+    chunks: Vec<Chunk>,
+    manifest: ContentManifest,
+    /// First indexed path per content hash (when deduplicating)
+    first_seen: HashMap<String, String>,
+    files_indexed: usize,
+    files_deduplicated: usize,
+    bytes_stripped: u64,
+    files_streamed: usize,
+    truncated_files: Vec<TruncatedFile>,
+}
+
+impl IndexedFiles {
+    /// Chunks, statistics and manifest of a finished run
+    fn finish(self, skipped: SkipLog, start: Instant) -> (Vec<Chunk>, IndexStats, ContentManifest) {
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        tracing::info!(
+            "Indexing complete: {} files indexed ({} streamed), {} skipped, \
+             {} deduplicated, {} chunks created in {}ms",
+            self.files_indexed,
+            self.files_streamed,
+            skipped.counts.total(),
+            self.files_deduplicated,
+            self.chunks.len(),
+            duration_ms
+        );
+
+        let stats = IndexStats {
+            files_indexed: self.files_indexed,
+            chunks_created: self.chunks.len(),
+            duration_ms,
+            session: String::new(), // Filled by caller
+            files_deduplicated: self.files_deduplicated,
+            skipped: skipped.counts,
+            skipped_files: skipped.files,
+            bytes_stripped: self.bytes_stripped,
+            files_streamed: self.files_streamed,
+            truncated_files: self.truncated_files,
+            files_renamed: 0,
+            renamed_files: Vec::new(),
+            patterns: Default::default(),
+            pattern_warning: None,
+            provenance: None,
+            profile: None,
+        };
+
+        (self.chunks, stats, self.manifest)
+    }
 }
 
 impl IndexingPipeline {
+    /// Start building a pipeline (see [`IndexingPipelineBuilder`])
+    pub fn builder() -> IndexingPipelineBuilder {
+        IndexingPipelineBuilder::default()
+    }
+
     /// Create a new indexing pipeline
     ///
     /// # Arguments
@@ -33,7 +366,8 @@ impl IndexingPipeline {
     ///
     /// # Returns
     ///
-    /// A new `IndexingPipeline` instance
+    /// A new `IndexingPipeline` instance, or a config error if the
+    /// patterns are invalid or `overlap >= chunk_size`
     pub fn new(
         chunk_size: usize,
         overlap: usize,
@@ -41,10 +375,23 @@ impl IndexingPipeline {
         exclude_patterns: Vec<String>,
         max_file_size_mb: usize,
     ) -> Result<Self> {
-        let walker = FileWalker::new(include_patterns, exclude_patterns, max_file_size_mb)?;
-        let chunker = Chunker::new(chunk_size, overlap);
+        Self::builder()
+            .chunk_size(chunk_size)
+            .overlap(overlap)
+            .include_patterns(include_patterns)
+            .exclude_patterns(exclude_patterns)
+            .max_file_size_mb(max_file_size_mb)
+            .build()
+    }
 
-        Ok(Self { walker, chunker })
+    /// Enable or disable duplicate file skipping
+    ///
+    /// When enabled, only the first file (in path order) with given
+    /// contents is chunked. Later copies are recorded in the
+    /// manifest as duplicates of it.
+    pub fn with_dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
     }
 
     /// Index a directory and return chunks + stats
@@ -61,88 +408,508 @@ impl IndexingPipeline {
     ///
     /// A tuple of (chunks, statistics) or an error
     pub fn index_directory(&self, root: &Path) -> Result<(Vec<Chunk>, IndexStats)> {
+        let (chunks, stats, _manifest) = self.index_directory_with_manifest(root)?;
+        Ok((chunks, stats))
+    }
+
+    /// Index a directory and return chunks, stats and content manifest
+    ///
+    /// Same as [`IndexingPipeline::index_directory`], but also
+    /// returns the per-file content hashes and any duplicates that
+    /// were skipped.
+    pub fn index_directory_with_manifest(
+        &self,
+        root: &Path,
+    ) -> Result<(Vec<Chunk>, IndexStats, ContentManifest)> {
+        self.index_directory_with_progress(root, None)
+    }
+
+    /// Index a directory, reporting to `progress` as files are processed
+    ///
+    /// Same as [`IndexingPipeline::index_directory_with_manifest`].
+    /// Cancellation is checked between files; a cancelled run returns
+    /// [`ShebeError::Cancelled`] and nothing it produced. A walk over
+    /// more files or bytes than the pipeline's limits returns
+    /// [`ShebeError::IndexTooLarge`] before any file is read.
+    ///
+    /// Include patterns matching none of the files walked are reported
+    /// in the stats' `pattern_warning`, or with `strict_patterns` fail
+    /// with [`ShebeError::ConfigError`] before any file is read.
+    pub fn index_directory_with_progress(
+        &self,
+        root: &Path,
+        progress: Option<&IndexProgress>,
+    ) -> Result<(Vec<Chunk>, IndexStats, ContentManifest)> {
         let start = Instant::now();
+        let profiler = progress.and_then(IndexProgress::profiler);
 
         // Step 1: Collect files
         tracing::info!("Starting file collection from {:?}", root);
-        let files = self.walker.collect_files(root)?;
-        tracing::info!("Found {} files to index", files.len());
+        let walk = timed(profiler, IndexStage::Walk, || {
+            self.walker.walk_limited(root, self.limits.as_ref())
+        })?;
+        let patterns = self.walker.pattern_counts(&walk);
+        let pattern_warning = check_include_matches(
+            &patterns,
+            self.walker.include_rewrites(),
+            &walk.rewrite_files,
+            self.strict_patterns,
+        )?;
+
+        let (chunks, mut stats, manifest) = self.index_walk(root, walk, start, progress)?;
+        stats.patterns = patterns;
+        stats.pattern_warning = pattern_warning;
+        Ok((chunks, stats, manifest))
+    }
+
+    /// Index exactly `files` under `root`, reporting to `progress`
+    ///
+    /// Same as [`IndexingPipeline::index_directory_with_progress`],
+    /// but the files are taken from the list instead of a walk (see
+    /// [`FileWalker::walk_files`]): include, exclude and `.shebeignore`
+    /// patterns are not applied, while the size limit, the pipeline's
+    /// limits and the binary and UTF-8 checks are.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if a listed file is missing, not a regular file or
+    /// outside `root`, before any file is read.
+    pub fn index_files_with_progress(
+        &self,
+        root: &Path,
+        files: &[PathBuf],
+        progress: Option<&IndexProgress>,
+    ) -> Result<(Vec<Chunk>, IndexStats, ContentManifest)> {
+        let start = Instant::now();
+
+        // Step 1: Check the listed files
+        tracing::info!("Checking {} listed files under {:?}", files.len(), root);
+        let walk = timed(
+            progress.and_then(IndexProgress::profiler),
+            IndexStage::Walk,
+            || self.walker.walk_files(root, files, self.limits.as_ref()),
+        )?;
+        self.index_walk(root, walk, start, progress)
+    }
 
-        // Step 2: Read and chunk files
-        let mut all_chunks = Vec::new();
-        let mut files_indexed = 0;
-        let mut files_skipped = 0;
+    /// Read, hash and chunk the files accepted by a walk
+    fn index_walk(
+        &self,
+        root: &Path,
+        walk: WalkReport,
+        start: Instant,
+        progress: Option<&IndexProgress>,
+    ) -> Result<(Vec<Chunk>, IndexStats, ContentManifest)> {
+        // Sorted so "first occurrence" is stable
+        let mut skipped = walk.skipped;
+        let mut files = walk.files;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        tracing::info!("Found {} files to index", files.len());
+        if let Some(progress) = progress {
+            progress.check_cancelled()?;
+            progress.set_total(files.len());
+        }
 
-        for (idx, file_path) in files.iter().enumerate() {
+        // Step 2: Read, hash and chunk files
+        let profiler = progress.and_then(IndexProgress::profiler);
+        let mut indexed = IndexedFiles::default();
+        for (idx, file) in files.iter().enumerate() {
+            let file_path = &file.path;
             if idx % 100 == 0 && idx > 0 {
                 tracing::info!("Progress: {}/{} files processed", idx, files.len());
             }
+            if let Some(progress) = progress {
+                progress.check_cancelled()?;
+            }
 
-            match self.process_file(file_path) {
-                Ok(chunks) => {
-                    let chunk_count = chunks.len();
-                    all_chunks.extend(chunks);
-                    files_indexed += 1;
-
-                    tracing::debug!("Indexed {:?} ({} chunks)", file_path, chunk_count);
+            let file_start = profiler.map(|_| Instant::now());
+            let streamed = self.streams(file_path, file.size);
+            let read = if streamed {
+                self.read_chunked(file_path, profiler)
+            } else {
+                self.read_whole(file_path, profiler)
+            };
+            match read {
+                Ok((hash, text)) => {
+                    if streamed {
+                        indexed.files_streamed += 1;
+                    }
+                    self.add_file(&mut indexed, file_path, hash, text, profiler);
                 }
-                Err(e) => {
-                    tracing::warn!("Failed to process {:?}: {}", file_path, e);
-                    files_skipped += 1;
+                Err((reason, detail)) => {
+                    tracing::warn!("Skipping {:?} ({}): {}", file_path, reason.label(), detail);
+                    skipped.record(root, file_path, reason, detail);
                     // Continue processing other files
                 }
             }
+            if let (Some(profiler), Some(file_start)) = (profiler, file_start) {
+                profiler.record_file(&paths::normalize_path(file_path), file_start.elapsed());
+            }
+            if let Some(progress) = progress {
+                progress.file_processed();
+            }
         }
 
-        let duration_ms = start.elapsed().as_millis() as u64;
+        Ok(indexed.finish(skipped, start))
+    }
 
-        tracing::info!(
-            "Indexing complete: {} files indexed, {} skipped, \
-             {} chunks created in {}ms",
-            files_indexed,
-            files_skipped,
-            all_chunks.len(),
-            duration_ms
-        );
+    /// Index the entries of an archive, reporting to `progress`
+    ///
+    /// Same as [`IndexingPipeline::index_directory_with_progress`], for
+    /// the `entries` listed from `archive` (see
+    /// [`FileWalker::walk_archive`]). Accepted entries are read one at
+    /// a time, in archive order, and chunked under their stored path
+    /// (`{archive}!{entry}`).
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if the archive cannot be read, besides the errors
+    /// of a directory walk.
+    pub fn index_archive_with_progress(
+        &self,
+        archive: &Archive,
+        entries: &[ArchiveEntry],
+        progress: Option<&IndexProgress>,
+    ) -> Result<(Vec<Chunk>, IndexStats, ContentManifest)> {
+        let start = Instant::now();
+        let profiler = progress.and_then(IndexProgress::profiler);
 
-        let stats = IndexStats {
-            files_indexed,
-            chunks_created: all_chunks.len(),
-            duration_ms,
-            session: String::new(), // Filled by caller
+        // Step 1: Filter the entries
+        tracing::info!("Listing {} entries of {:?}", entries.len(), archive.path());
+        let walk = timed(profiler, IndexStage::Walk, || {
+            self.walker
+                .walk_archive(archive, entries, self.limits.as_ref())
+        })?;
+        let patterns = self.walker.pattern_counts(&walk);
+        let pattern_warning = check_include_matches(
+            &patterns,
+            self.walker.include_rewrites(),
+            &walk.rewrite_files,
+            self.strict_patterns,
+        )?;
+        let root = archive.path();
+        let mut skipped = walk.skipped;
+        let wanted: HashSet<PathBuf> = walk.files.iter().map(|f| f.path.clone()).collect();
+        tracing::info!("Found {} entries to index", wanted.len());
+        if let Some(progress) = progress {
+            progress.check_cancelled()?;
+            progress.set_total(wanted.len());
+        }
+
+        // Step 2: Read, hash and chunk the accepted entries
+        let mut indexed = IndexedFiles::default();
+        let max_bytes = self.walker.max_file_size_bytes();
+        archive.read_entries(
+            max_bytes,
+            |entry| wanted.contains(&archive.entry_path(&entry.name)),
+            |entry, read| {
+                if let Some(progress) = progress {
+                    progress.check_cancelled()?;
+                }
+                let file_start = profiler.map(|_| Instant::now());
+                let file_path = archive.entry_path(&entry.name);
+                let text = timed(profiler, IndexStage::Decode, || {
+                    let contents = read
+                        .map_err(|e| (SkipReason::ReadError, e.to_string()))
+                        .and_then(text_from_bytes)?;
+                    check_line_length(&contents, self.max_line_length)?;
+                    Ok::<_, SkipError>((content_hash(&contents), contents))
+                });
+                match text {
+                    Ok((hash, contents)) => {
+                        let text = FileText::Whole(contents);
+                        self.add_file(&mut indexed, &file_path, hash, text, profiler);
+                    }
+                    Err((reason, detail)) => {
+                        tracing::warn!("Skipping {:?} ({}): {}", file_path, reason.label(), detail);
+                        skipped.record(root, &archive.extracted_path(&entry.name), reason, detail);
+                    }
+                }
+                if let (Some(profiler), Some(file_start)) = (profiler, file_start) {
+                    profiler.record_file(&paths::normalize_path(&file_path), file_start.elapsed());
+                }
+                if let Some(progress) = progress {
+                    progress.file_processed();
+                }
+                Ok(())
+            },
+        )?;
+
+        let (chunks, mut stats, manifest) = indexed.finish(skipped, start);
+        stats.patterns = patterns;
+        stats.pattern_warning = pattern_warning;
+        Ok((chunks, stats, manifest))
+    }
+
+    /// Hash-check (when deduplicating) and chunk one file's text
+    fn add_file(
+        &self,
+        indexed: &mut IndexedFiles,
+        file_path: &Path,
+        hash: String,
+        text: FileText,
+        profiler: Option<&IndexProfiler>,
+    ) {
+        let path_str = paths::normalize_path(file_path);
+        let is_empty = match &text {
+            FileText::Whole(contents) => contents.is_empty(),
+            FileText::Chunked(chunks, _) => chunks.is_empty(),
         };
 
-        Ok((all_chunks, stats))
+        // Empty files produce no chunks, so there is nothing to dedupe
+        if self.dedupe && !is_empty {
+            if let Some(original) = indexed.first_seen.get(&hash) {
+                tracing::debug!("Skipping duplicate {:?} (same as {})", file_path, original);
+                indexed
+                    .manifest
+                    .record_duplicate(original, path_str.clone());
+                indexed.manifest.record(path_str, hash);
+                indexed.files_deduplicated += 1;
+                return;
+            }
+            indexed.first_seen.insert(hash.clone(), path_str.clone());
+        }
+        indexed.manifest.record(path_str.clone(), hash);
+
+        let (chunks, stripped, file_bytes) = match text {
+            FileText::Whole(contents) => {
+                let (chunks, stripped) = timed(profiler, IndexStage::Chunk, || {
+                    self.chunk_contents(&contents, file_path)
+                });
+                (chunks, stripped, contents.len())
+            }
+            FileText::Chunked(chunks, bytes) => (chunks, 0, bytes),
+        };
+        debug_assert!(
+            chunks.iter().enumerate().all(|(i, c)| c.chunk_index == i),
+            "chunk_index must be contiguous from 0 in {file_path:?}"
+        );
+        indexed.bytes_stripped += stripped;
+        // Only a capped file's last chunk ends before the end of the file
+        if let Some(last) = chunks.last().filter(|last| last.end_offset < file_bytes) {
+            tracing::warn!(
+                "Indexed only the first {} chunks of {:?} ({} of {} bytes); \
+                 raise max_chunks_per_file to index all of it",
+                chunks.len(),
+                file_path,
+                last.end_offset,
+                file_bytes
+            );
+            indexed.truncated_files.push(TruncatedFile {
+                path: path_str.clone(),
+                chunks_indexed: chunks.len(),
+                bytes_indexed: last.end_offset as u64,
+                file_bytes: file_bytes as u64,
+            });
+            indexed.manifest.record_truncated(path_str);
+        }
+        let chunk_count = chunks.len();
+        indexed.chunks.extend(chunks);
+        indexed.files_indexed += 1;
+
+        tracing::debug!("Indexed {:?} ({} chunks)", file_path, chunk_count);
     }
 
-    /// Process a single file: read contents and chunk
-    fn process_file(&self, path: &Path) -> Result<Vec<Chunk>> {
-        // Read file contents
-        let contents = fs::read_to_string(path).map_err(|e| {
-            // Check if it's a UTF-8 error (likely binary file)
-            if e.kind() == std::io::ErrorKind::InvalidData {
-                ShebeError::IndexingFailed(format!("Skipping non-UTF-8 file: {path:?}"))
-            } else {
-                ShebeError::IndexingFailed(format!("Failed to read {path:?}: {e}"))
+    /// Summarize what indexing a directory would do
+    ///
+    /// Only walks the directory tree; no file contents are read and
+    /// nothing is written.
+    pub fn dry_run(&self, root: &Path) -> Result<DryRunReport> {
+        Ok(self.dry_run_listing(root)?.0)
+    }
+
+    /// Like [`IndexingPipeline::dry_run`], also returning every matched
+    /// path as the index would store it
+    pub fn dry_run_listing(&self, root: &Path) -> Result<(DryRunReport, Vec<String>)> {
+        let report = self.walker.walk(root)?;
+        let matched = matched_paths(&report);
+        let mut exclude_patterns = self.walker.exclude_patterns();
+        exclude_patterns.extend(report.ignore_patterns.iter().cloned());
+        let summary = DryRunReport::from_walk(report, root, exclude_patterns, &self.chunker);
+        Ok((summary, matched))
+    }
+
+    /// Summarize what indexing an archive's `entries` would do
+    ///
+    /// Like [`IndexingPipeline::dry_run`]; sample paths are relative to
+    /// the archive root.
+    pub fn dry_run_archive(
+        &self,
+        archive: &Archive,
+        entries: &[ArchiveEntry],
+    ) -> Result<DryRunReport> {
+        Ok(self.dry_run_archive_listing(archive, entries)?.0)
+    }
+
+    /// Like [`IndexingPipeline::dry_run_archive`], also returning every
+    /// matched path as the index would store it
+    pub fn dry_run_archive_listing(
+        &self,
+        archive: &Archive,
+        entries: &[ArchiveEntry],
+    ) -> Result<(DryRunReport, Vec<String>)> {
+        let mut report = self.walker.walk_archive(archive, entries, None)?;
+        let matched = matched_paths(&report);
+        let prefix = paths::normalize_path(&archive.entry_path(""));
+        for file in &mut report.files {
+            let stored = paths::normalize_path(&file.path);
+            if let Some(name) = stored.strip_prefix(&prefix) {
+                file.path = archive.extracted_path(name);
             }
-        })?;
+        }
+        let summary = DryRunReport::from_walk(
+            report,
+            archive.path(),
+            self.walker.exclude_patterns(),
+            &self.chunker,
+        );
+        Ok((summary, matched))
+    }
+
+    /// Explain why a file under `root` would or would not be indexed
+    ///
+    /// See [`FileWalker::explain`]; a file passing its checks is also
+    /// checked against the maximum line length.
+    pub fn explain(&self, root: &Path, path: &Path) -> Result<FileExplanation> {
+        let explanation = self.walker.explain(root, path)?;
+        if explanation.reason.is_some() || self.max_line_length == 0 {
+            return Ok(explanation);
+        }
+        match read_text_streaming(&explanation.path, self.max_line_length, |_| {}) {
+            Err((reason, detail)) => Ok(FileExplanation {
+                reason: Some(reason),
+                detail,
+                ..explanation
+            }),
+            Ok(_) => Ok(explanation),
+        }
+    }
+
+    /// Whether a file of `size` bytes is chunked while it is read
+    ///
+    /// Documentation files are read whole, as their sections are found
+    /// from the full text.
+    fn streams(&self, path: &Path, size: u64) -> bool {
+        self.strip_run_chars.is_none()
+            && self.record_format(path).is_none()
+            && SectionFormat::for_path(path).is_none()
+            && size > self.stream_threshold
+    }
+
+    /// How the records of a data file are separated, when the file is
+    /// chunked by record
+    fn record_format(&self, path: &Path) -> Option<RecordFormat> {
+        self.record_chunking
+            .then(|| RecordFormat::for_path(path))
+            .flatten()
+    }
 
+    /// Read a file whole, returning its hash and text
+    ///
+    /// Checks as [`read_text`](crate::core::indexer::skipped::read_text)
+    /// does, with reading and decoding timed apart.
+    fn read_whole(
+        &self,
+        path: &Path,
+        profiler: Option<&IndexProfiler>,
+    ) -> std::result::Result<(String, FileText), SkipError> {
+        let bytes = timed(profiler, IndexStage::Read, || std::fs::read(path))
+            .map_err(|e| (SkipReason::ReadError, e.to_string()))?;
+        timed(profiler, IndexStage::Decode, || {
+            let contents = text_from_bytes(bytes)?;
+            check_line_length(&contents, self.max_line_length)?;
+            Ok((content_hash(&contents), FileText::Whole(contents)))
+        })
+    }
+
+    /// Chunk a file as it is read, returning its hash and chunks
+    ///
+    /// When profiled, time spent chunking the pieces counts as chunking
+    /// and the rest (reading and checking them) as reading.
+    fn read_chunked(
+        &self,
+        path: &Path,
+        profiler: Option<&IndexProfiler>,
+    ) -> std::result::Result<(String, FileText), SkipError> {
+        let start = profiler.map(|_| Instant::now());
+        let mut chunking = Duration::ZERO;
+        let mut stream = self.chunker.chunk_stream(path);
+        let hash = read_text_streaming(path, self.max_line_length, |piece| match profiler {
+            Some(_) => {
+                let push_start = Instant::now();
+                stream.push(piece);
+                chunking += push_start.elapsed();
+            }
+            None => stream.push(piece),
+        });
+        if let (Some(profiler), Some(start)) = (profiler, start) {
+            profiler.record(IndexStage::Chunk, chunking);
+            profiler.record(IndexStage::Read, start.elapsed().saturating_sub(chunking));
+        }
+        let hash = hash?;
+        tracing::debug!("Streamed {:?}", path);
+        let bytes = stream.bytes_pushed();
+        let chunks = timed(profiler, IndexStage::Chunk, || stream.finish());
+        Ok((hash, FileText::Chunked(chunks, bytes)))
+    }
+
+    /// Chunk file contents, returning the chunks and bytes stripped
+    ///
+    /// Chunks of documentation files get their heading breadcrumb.
+    fn chunk_contents(&self, contents: &str, path: &Path) -> (Vec<Chunk>, u64) {
+        let (mut chunks, stripped) = self.chunk_unsectioned(contents, path);
+        if let Some(format) = SectionFormat::for_path(path) {
+            assign_sections(&mut chunks, contents, format);
+        }
+        (chunks, stripped)
+    }
+
+    /// Chunk file contents without sections
+    fn chunk_unsectioned(&self, contents: &str, path: &Path) -> (Vec<Chunk>, u64) {
         // Skip empty files
         if contents.is_empty() {
             tracing::debug!("Skipping empty file: {:?}", path);
-            return Ok(Vec::new());
+            return (Vec::new(), 0);
         }
 
-        // Chunk the text
-        let chunks = self.chunker.chunk_text(&contents, path);
+        let stripped = self
+            .strip_run_chars
+            .and_then(|run_chars| strip_high_entropy(contents, run_chars));
+        match stripped {
+            Some(stripped) => {
+                tracing::debug!(
+                    "Stripped {} bytes of blobs from {:?}",
+                    stripped.bytes_stripped(),
+                    path
+                );
+                let chunks = self.chunk_text(stripped.text(), path);
+                (stripped.restore_offsets(chunks), stripped.bytes_stripped())
+            }
+            None => (self.chunk_text(contents, path), 0),
+        }
+    }
 
-        Ok(chunks)
+    /// Chunk text by record for data files, by characters otherwise
+    fn chunk_text(&self, text: &str, path: &Path) -> Vec<Chunk> {
+        match self.record_format(path) {
+            Some(format) => self.chunker.chunk_records(text, path, format),
+            None => self.chunker.chunk_text(text, path),
+        }
     }
 }
 
+/// Normalized paths of the files a walk matched
+fn matched_paths(report: &WalkReport) -> Vec<String> {
+    report
+        .files
+        .iter()
+        .map(|file| paths::normalize_path(&file.path))
+        .collect()
+}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::SkipReason;
     use std::fs;
     use tempfile::TempDir;
 
@@ -315,4 +1082,381 @@ mod tests {
         assert_eq!(stats.files_indexed, 1);
         assert!(!chunks.is_empty());
     }
+
+    #[test]
+    fn test_pipeline_dedupe_skips_identical_files() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("src/util.rs", "pub fn helper() {}"),
+            ("vendor/a/util.rs", "pub fn helper() {}"),
+            ("vendor/b/util.rs", "pub fn helper() {}"),
+            ("src/main.rs", "fn main() {}"),
+        ]);
+
+        let pipeline = IndexingPipeline::new(100, 10, vec!["*.rs".to_string()], vec![], 10)
+            .unwrap()
+            .with_dedupe(true);
+
+        let (chunks, stats, manifest) = pipeline
+            .index_directory_with_manifest(temp_dir.path())
+            .unwrap();
+
+        assert_eq!(stats.files_indexed, 2);
+        assert_eq!(stats.files_deduplicated, 2);
+        assert_eq!(manifest.duplicate_count(), 2);
+        assert_eq!(manifest.files.len(), 4);
+
+        // The first path in sorted order is kept
+        let original = temp_dir.path().join("src/util.rs");
+        let dups = manifest.duplicates_of(&original.to_string_lossy());
+        assert_eq!(dups.len(), 2);
+        assert!(dups.iter().all(|d| d.contains("vendor")));
+        assert!(chunks
+            .iter()
+            .all(|c| !c.file_path.to_string_lossy().contains("vendor")));
+    }
+
+    #[test]
+    fn test_pipeline_without_dedupe_indexes_all_copies() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("a.rs", "pub fn helper() {}"),
+            ("b.rs", "pub fn helper() {}"),
+        ]);
+
+        let pipeline =
+            IndexingPipeline::new(100, 10, vec!["*.rs".to_string()], vec![], 10).unwrap();
+
+        let (_chunks, stats, manifest) = pipeline
+            .index_directory_with_manifest(temp_dir.path())
+            .unwrap();
+
+        assert_eq!(stats.files_indexed, 2);
+        assert_eq!(stats.files_deduplicated, 0);
+        assert!(!manifest.has_duplicates());
+        // Hashes are recorded regardless of dedupe
+        assert_eq!(manifest.files.len(), 2);
+    }
+
+    #[test]
+    fn test_pipeline_caps_chunks_per_file() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("generated.rs", &"x".repeat(10_000)),
+            ("small.rs", "fn main() {}"),
+        ]);
+
+        // Whole and streamed reads stop at the same chunk
+        for threshold in [u64::MAX, 1024] {
+            let pipeline = IndexingPipeline::builder()
+                .chunk_size(100)
+                .overlap(0)
+                .include_patterns(["*.rs"])
+                .max_chunks_per_file(5)
+                .stream_threshold(threshold)
+                .build()
+                .unwrap();
+            let (chunks, stats, manifest) = pipeline
+                .index_directory_with_manifest(temp_dir.path())
+                .unwrap();
+
+            assert_eq!(chunks.len(), 6);
+            let generated = paths::normalize_path(&temp_dir.path().join("generated.rs"));
+            assert_eq!(
+                stats.truncated_files,
+                vec![TruncatedFile {
+                    path: generated.clone(),
+                    chunks_indexed: 5,
+                    bytes_indexed: 500,
+                    file_bytes: 10_000,
+                }]
+            );
+            assert!(manifest.is_truncated(&generated));
+            assert_eq!(manifest.truncated.len(), 1);
+        }
+
+        assert!(IndexingPipeline::builder()
+            .max_chunks_per_file(0)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_pipeline_chunks_data_files_by_record() {
+        let mut csv = String::from("id,address\n");
+        for i in 1..=50 {
+            csv.push_str(&format!("{i},\"{i} Main St\nSpringfield\"\n"));
+        }
+        let temp_dir = create_test_dir_with_files(&[("people.csv", &csv), ("notes.txt", &csv)]);
+
+        // Record files are read whole even above the streaming threshold
+        let pipeline = IndexingPipeline::builder()
+            .chunk_size(120)
+            .overlap(10)
+            .include_patterns(["*.csv", "*.txt"])
+            .stream_threshold(64)
+            .build()
+            .unwrap();
+        let (chunks, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+        assert_eq!(stats.files_streamed, 1);
+
+        let (csv_chunks, txt_chunks): (Vec<&Chunk>, Vec<&Chunk>) = chunks
+            .iter()
+            .partition(|c| c.file_path.extension().unwrap() == "csv");
+        assert!(txt_chunks.iter().all(|c| c.records.is_none()));
+        let mut next_record = 1;
+        for chunk in &csv_chunks {
+            let records = chunk.records.unwrap();
+            assert_eq!(records.first, next_record);
+            assert!(chunk.text.ends_with("Springfield\"\n") || records.last == 1);
+            next_record = records.last + 1;
+        }
+        assert_eq!(next_record, 52);
+
+        let pipeline = IndexingPipeline::builder()
+            .chunk_size(120)
+            .overlap(10)
+            .include_patterns(["*.csv"])
+            .record_chunking(false)
+            .build()
+            .unwrap();
+        let (chunks, _) = pipeline.index_directory(temp_dir.path()).unwrap();
+        assert!(chunks.iter().all(|c| c.records.is_none()));
+    }
+
+    #[test]
+    fn test_pipeline_rejects_overlap_not_below_chunk_size() {
+        let result = IndexingPipeline::new(100, 100, vec![], vec![], 10);
+        assert!(matches!(result, Err(ShebeError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_builder_applies_session_config() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("a.rs", "fn same() {}"),
+            ("b.rs", "fn same() {}"),
+            ("c.txt", "notes"),
+        ]);
+        let config = SessionConfig {
+            include_patterns: vec!["*.rs".to_string()],
+            dedupe: true,
+            ..SessionConfig::default()
+        };
+
+        let pipeline = IndexingPipeline::builder()
+            .session_config(&config)
+            .build()
+            .unwrap();
+        let (chunks, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+
+        assert_eq!(stats.files_indexed, 1);
+        assert_eq!(stats.files_deduplicated, 1);
+        assert_eq!(chunks.len(), 1);
+        assert!(IndexingPipeline::builder().overlap(600).build().is_err());
+    }
+
+    #[test]
+    fn test_builder_applies_boundary_hint() {
+        let paragraph = format!("{}\n", "x".repeat(90));
+        let content = format!("{paragraph}\n{paragraph}\n{paragraph}\n{paragraph}");
+        let temp_dir = create_test_dir_with_files(&[("notes.md", &content)]);
+        let config = SessionConfig::builder()
+            .chunk_size(200)
+            .overlap(10)
+            .boundary_hint(BoundaryHint::BlankLine)
+            .build()
+            .unwrap();
+
+        let pipeline = IndexingPipeline::builder()
+            .session_config(&config)
+            .build()
+            .unwrap();
+        let (chunks, _) = pipeline.index_directory(temp_dir.path()).unwrap();
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.text.ends_with("\n\n"), "{:?}", chunk.text);
+        }
+    }
+
+    #[test]
+    fn test_dry_run_reads_nothing() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("src/main.rs", "fn main() {}"),
+            ("target/debug/out.rs", "fn out() {}"),
+        ]);
+
+        let pipeline = IndexingPipeline::new(
+            512,
+            64,
+            vec!["*.rs".to_string()],
+            vec!["**/target/**".to_string()],
+            10,
+        )
+        .unwrap();
+        let report = pipeline.dry_run(temp_dir.path()).unwrap();
+
+        assert_eq!(report.files, 1);
+        assert_eq!(report.total_bytes, 12);
+        assert_eq!(report.estimated_chunks, 1);
+        assert_eq!(report.sample_paths, vec!["src/main.rs"]);
+        assert_eq!(report.exclusions[0].directories, 1);
+    }
+
+    #[test]
+    fn test_pipeline_reports_progress() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("a.rs", "fn a() {}"),
+            ("b.rs", "fn b() {}"),
+            ("bad.rs", ""),
+        ]);
+        fs::write(temp_dir.path().join("bin.rs"), [0xff, 0xfe]).unwrap();
+        let pipeline =
+            IndexingPipeline::new(100, 10, vec!["*.rs".to_string()], vec![], 10).unwrap();
+        let progress = IndexProgress::new();
+
+        let (_, stats, _) = pipeline
+            .index_directory_with_progress(temp_dir.path(), Some(&progress))
+            .unwrap();
+
+        // Skipped (non-UTF-8) files count as processed too
+        assert_eq!(stats.files_indexed, 3);
+        assert_eq!(progress.files_total(), 4);
+        assert_eq!(progress.files_processed(), 4);
+        assert_eq!(progress.percent(), 100);
+    }
+
+    #[test]
+    fn test_pipeline_stops_when_cancelled() {
+        let temp_dir = create_test_dir_with_files(&[("a.rs", "fn a() {}")]);
+        let pipeline =
+            IndexingPipeline::new(100, 10, vec!["*.rs".to_string()], vec![], 10).unwrap();
+        let progress = IndexProgress::new();
+        progress.cancel();
+
+        let result = pipeline.index_directory_with_progress(temp_dir.path(), Some(&progress));
+
+        assert!(matches!(result, Err(ShebeError::Cancelled(_))));
+        assert_eq!(progress.files_processed(), 0);
+    }
+
+    #[test]
+    fn test_pipeline_records_skip_reasons() {
+        let temp_dir = create_test_dir_with_files(&[
+            ("src/main.rs", "fn main() {}"),
+            ("debug.log", "log line"),
+        ]);
+        fs::write(temp_dir.path().join("logo.rs"), [b'P', 0, b'N', b'G']).unwrap();
+        fs::write(temp_dir.path().join("latin1.rs"), [b'c', b'a', b'f', 0xE9]).unwrap();
+
+        let pipeline =
+            IndexingPipeline::new(100, 10, vec![], vec!["*.log".to_string()], 10).unwrap();
+        let (_, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+
+        assert_eq!(stats.files_indexed, 1);
+        assert_eq!(stats.skipped.excluded_by_pattern, 1);
+        assert_eq!(stats.skipped.binary, 1);
+        assert_eq!(stats.skipped.not_utf8, 1);
+        assert_eq!(stats.skipped.total(), 3);
+
+        let mut listed: Vec<(&str, SkipReason)> = stats
+            .skipped_files
+            .iter()
+            .map(|f| (f.path.as_str(), f.reason))
+            .collect();
+        listed.sort_by_key(|&(path, _)| path);
+        assert_eq!(
+            listed,
+            vec![
+                ("debug.log", SkipReason::ExcludedByPattern),
+                ("latin1.rs", SkipReason::NotUtf8),
+                ("logo.rs", SkipReason::Binary),
+            ]
+        );
+    }
+
+    /// `n` Rust files under `src/`
+    fn create_src_files(n: usize) -> TempDir {
+        let files: Vec<(String, String)> = (0..n)
+            .map(|i| (format!("src/m{i}.rs"), format!("fn m{i}() {{}}")))
+            .collect();
+        let files: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(p, c)| (p.as_str(), c.as_str()))
+            .collect();
+        create_test_dir_with_files(&files)
+    }
+
+    #[test]
+    fn test_pipeline_counts_pattern_matches() {
+        let temp_dir = create_src_files(3);
+        fs::write(temp_dir.path().join("README.md"), "# Readme").unwrap();
+        let pipeline = IndexingPipeline::new(
+            100,
+            10,
+            vec!["**/*.rs".to_string(), "**/*.md".to_string()],
+            vec!["**/m0.rs".to_string()],
+            10,
+        )
+        .unwrap();
+
+        let (_, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+
+        assert_eq!(stats.files_indexed, 3);
+        assert_eq!(stats.patterns.candidates, 4);
+        let include: Vec<(&str, usize)> = stats
+            .patterns
+            .include
+            .iter()
+            .map(|p| (p.pattern.as_str(), p.files))
+            .collect();
+        assert_eq!(include, vec![("**/*.rs", 3), ("**/*.md", 1)]);
+        assert_eq!(stats.patterns.exclude[0].files, 1);
+        assert_eq!(stats.pattern_warning, None);
+    }
+
+    #[test]
+    fn test_pipeline_suggests_rewrite_for_unmatched_include() {
+        // Patterns match absolute paths (or file names), so a relative
+        // directory pattern matches nothing; `*.rs` alone would match
+        let temp_dir = create_src_files(12);
+        let pipeline =
+            IndexingPipeline::new(100, 10, vec!["src/*.rs".to_string()], vec![], 10).unwrap();
+
+        let (chunks, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+
+        assert!(chunks.is_empty());
+        assert_eq!(stats.patterns.include[0].files, 0);
+        let warning = stats.pattern_warning.unwrap();
+        assert!(
+            warning
+                .contains("pattern 'src/*.rs' matched 0 files, but '**/src/*.rs' would match 12"),
+            "{warning}"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_strict_patterns_fails_on_zero_matches() {
+        // No Go files at all: nothing to suggest, still an error when strict
+        let temp_dir = create_src_files(12);
+        let pipeline = IndexingPipeline::builder()
+            .include_patterns(["**/*.go"])
+            .strict_patterns(true)
+            .build()
+            .unwrap();
+
+        let err = pipeline.index_directory(temp_dir.path()).unwrap_err();
+
+        assert!(matches!(err, ShebeError::ConfigError(_)));
+        assert!(err
+            .to_string()
+            .contains("pattern '**/*.go' matched 0 files"));
+        assert!(!err.to_string().contains("would match"));
+
+        // Without strict_patterns the same walk only warns
+        let pipeline = IndexingPipeline::builder()
+            .include_patterns(["**/*.go"])
+            .build()
+            .unwrap();
+        let (_, stats) = pipeline.index_directory(temp_dir.path()).unwrap();
+        assert_eq!(stats.files_indexed, 0);
+        assert!(stats.pattern_warning.is_some());
+    }
 }
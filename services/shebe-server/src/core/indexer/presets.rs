@@ -0,0 +1,131 @@
+//! Language presets for include patterns.
+//!
+//! `languages: ["rust", "docs"]` (or `--lang rust --lang docs`) is
+//! shorthand for the include patterns of each preset's file extensions,
+//! so callers don't write `**/*.rs`, `**/*.md`, ... by hand. Presets
+//! are expanded before indexing and combined with any explicit include
+//! patterns; the session stores the resulting patterns.
+
+use crate::core::error::{Result, ShebeError};
+
+/// Preset names and the file extensions they include
+pub const PRESETS: &[(&str, &[&str])] = &[
+    ("c", &["c", "h"]),
+    ("config", &["toml", "yaml", "yml", "json", "ini"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx", "h"]),
+    ("csharp", &["cs", "csproj"]),
+    ("docs", &["md", "rst", "txt"]),
+    ("go", &["go", "mod"]),
+    ("java", &["java", "gradle", "kt", "kts"]),
+    ("python", &["py", "pyi", "toml", "cfg"]),
+    ("ruby", &["rb", "rake", "gemspec"]),
+    ("rust", &["rs", "toml"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    (
+        "web",
+        &["ts", "tsx", "js", "jsx", "mjs", "css", "scss", "html"],
+    ),
+];
+
+/// Names of all presets, in alphabetical order
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Include patterns for the named presets, without duplicates
+///
+/// # Errors
+///
+/// `ConfigError` naming the available presets if a name is unknown.
+pub fn expand(names: &[String]) -> Result<Vec<String>> {
+    let mut patterns = Vec::new();
+    for name in names {
+        let key = name.trim().to_ascii_lowercase();
+        let (_, extensions) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == key)
+            .ok_or_else(|| {
+                ShebeError::ConfigError(format!(
+                    "Unknown language preset '{name}'. Available: {}",
+                    preset_names().join(", ")
+                ))
+            })?;
+        for extension in *extensions {
+            push_unique(&mut patterns, format!("**/*.{extension}"));
+        }
+    }
+    Ok(patterns)
+}
+
+/// Explicit include patterns combined with those of the presets
+///
+/// Returns `include` unchanged when no presets are named, so the usual
+/// defaults still apply when neither is given.
+///
+/// # Errors
+///
+/// `ConfigError` if a preset name is unknown.
+pub fn with_presets(
+    include: Option<Vec<String>>,
+    languages: &[String],
+) -> Result<Option<Vec<String>>> {
+    if languages.is_empty() {
+        return Ok(include);
+    }
+    let mut patterns = include.unwrap_or_default();
+    for pattern in expand(languages)? {
+        push_unique(&mut patterns, pattern);
+    }
+    Ok(Some(patterns))
+}
+
+fn push_unique(patterns: &mut Vec<String>, pattern: String) {
+    if !patterns.contains(&pattern) {
+        patterns.push(pattern);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand_dedupes_shared_extensions() {
+        let patterns = expand(&names(&["rust", "python"])).unwrap();
+        assert_eq!(
+            patterns,
+            ["**/*.rs", "**/*.toml", "**/*.py", "**/*.pyi", "**/*.cfg"]
+        );
+    }
+
+    #[test]
+    fn test_expand_unknown_preset_lists_available() {
+        let err = expand(&names(&["rust", "cobol"])).unwrap_err().to_string();
+        assert!(err.contains("'cobol'"), "{err}");
+        assert!(err.contains("docs, go"), "{err}");
+    }
+
+    #[test]
+    fn test_with_presets_unions_explicit_patterns() {
+        let include = Some(names(&["Makefile", "**/*.md"]));
+        let patterns = with_presets(include.clone(), &names(&["Docs"])).unwrap();
+        assert_eq!(
+            patterns.unwrap(),
+            ["Makefile", "**/*.md", "**/*.rst", "**/*.txt"]
+        );
+        assert_eq!(with_presets(include.clone(), &[]).unwrap(), include);
+        assert_eq!(with_presets(None, &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_presets_are_sorted() {
+        let names = preset_names();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        assert_eq!(names, sorted);
+    }
+}
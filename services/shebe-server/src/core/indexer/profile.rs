@@ -0,0 +1,160 @@
+//! Per-stage timing of profiled indexing runs.
+//!
+//! An [`IndexProfiler`] accumulates the time a run spends walking,
+//! reading, decoding, chunking, adding documents and committing, and
+//! the slowest files. It is carried by the run's
+//! [`IndexProgress`](crate::core::indexer::IndexProgress) once
+//! profiling is enabled; stages of a run without one go through
+//! [`timed`], which then takes no timestamps at all.
+
+use crate::core::types::{FileTiming, IndexProfile, IndexStage, StageTiming};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Slowest files kept in a profile
+pub const SLOWEST_FILES: usize = 10;
+
+/// Accumulated stage times of one indexing run
+#[derive(Debug, Default)]
+pub struct IndexProfiler {
+    timings: Mutex<Timings>,
+}
+
+#[derive(Debug, Default)]
+struct Timings {
+    stages: [Duration; IndexStage::ALL.len()],
+    /// Slowest first, at most [`SLOWEST_FILES`]
+    slowest: Vec<(Duration, String)>,
+    chunk_text_bytes: u64,
+}
+
+impl IndexProfiler {
+    /// Create a profiler with nothing recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `elapsed` to `stage`
+    pub fn record(&self, stage: IndexStage, elapsed: Duration) {
+        let mut timings = self.lock();
+        timings.stages[stage_slot(stage)] += elapsed;
+    }
+
+    /// Run `f`, adding its duration to `stage`
+    pub fn time<T>(&self, stage: IndexStage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.record(stage, start.elapsed());
+        value
+    }
+
+    /// Record the time spent on one file, keeping the slowest
+    pub fn record_file(&self, path: &str, elapsed: Duration) {
+        let mut timings = self.lock();
+        let slowest = &mut timings.slowest;
+        if slowest.len() == SLOWEST_FILES && slowest.last().is_some_and(|(d, _)| *d >= elapsed) {
+            return;
+        }
+        let at = slowest.partition_point(|(d, _)| *d >= elapsed);
+        slowest.insert(at, (elapsed, path.to_string()));
+        slowest.truncate(SLOWEST_FILES);
+    }
+
+    /// Record the bytes of chunk text held before writing the index
+    pub fn record_chunk_text_bytes(&self, bytes: u64) {
+        self.lock().chunk_text_bytes = bytes;
+    }
+
+    /// Everything recorded so far, with the process's peak memory
+    pub fn profile(&self) -> IndexProfile {
+        let timings = self.lock();
+        IndexProfile {
+            stages: IndexStage::ALL
+                .iter()
+                .map(|&stage| StageTiming {
+                    stage,
+                    duration_us: micros(timings.stages[stage_slot(stage)]),
+                })
+                .collect(),
+            slowest_files: timings
+                .slowest
+                .iter()
+                .map(|(elapsed, path)| FileTiming {
+                    path: path.clone(),
+                    duration_us: micros(*elapsed),
+                })
+                .collect(),
+            chunk_text_bytes: timings.chunk_text_bytes,
+            peak_rss_bytes: peak_rss_bytes(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Timings> {
+        self.timings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run `f`, timing it as `stage` only when there is a profiler
+pub fn timed<T>(profiler: Option<&IndexProfiler>, stage: IndexStage, f: impl FnOnce() -> T) -> T {
+    match profiler {
+        Some(profiler) => profiler.time(stage, f),
+        None => f(),
+    }
+}
+
+fn stage_slot(stage: IndexStage) -> usize {
+    IndexStage::ALL
+        .iter()
+        .position(|&s| s == stage)
+        .unwrap_or_default()
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Peak resident set size (`VmHWM`), on Linux
+fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_lists_every_stage() {
+        let profiler = IndexProfiler::new();
+        profiler.record(IndexStage::Chunk, Duration::from_micros(30));
+        profiler.record(IndexStage::Chunk, Duration::from_micros(10));
+        profiler.record(IndexStage::Commit, Duration::from_micros(60));
+
+        let profile = profiler.profile();
+        let stages: Vec<IndexStage> = profile.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, IndexStage::ALL);
+        assert_eq!(profile.stages[3].duration_us, 40);
+        assert_eq!(profile.total_us(), 100);
+        assert_eq!(profile.percent(&profile.stages[5]), 60.0);
+    }
+
+    #[test]
+    fn test_profile_keeps_slowest_files() {
+        let profiler = IndexProfiler::new();
+        for i in 0..25u64 {
+            // Out of order, so insertion has to sort
+            let micros = (i * 7) % 25;
+            profiler.record_file(&format!("f{micros}.rs"), Duration::from_micros(micros));
+        }
+
+        let slowest = profiler.profile().slowest_files;
+        assert_eq!(slowest.len(), SLOWEST_FILES);
+        assert_eq!(slowest[0].path, "f24.rs");
+        assert_eq!(slowest[9].duration_us, 15);
+        assert!(slowest
+            .windows(2)
+            .all(|w| w[0].duration_us >= w[1].duration_us));
+    }
+}
@@ -0,0 +1,120 @@
+//! Progress reporting and cancellation for indexing runs.
+//!
+//! An [`IndexProgress`] is shared between the thread doing the work
+//! and whoever watches it (e.g. a background job). The pipeline
+//! updates the file counts and checks the cancel flag between files.
+//! A profiled run also carries its [`IndexProfiler`] here, so every
+//! stage that reports progress can time itself.
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::profile::IndexProfiler;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// File counts and cancel flag for one indexing run
+#[derive(Debug, Default)]
+pub struct IndexProgress {
+    files_total: AtomicUsize,
+    files_processed: AtomicUsize,
+    cancelled: AtomicBool,
+    profiler: OnceLock<IndexProfiler>,
+}
+
+impl IndexProgress {
+    /// Create progress for a run that has not started walking yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the number of files the run will process
+    pub fn set_total(&self, total: usize) {
+        self.files_total.store(total, Ordering::Relaxed);
+    }
+
+    /// Record that one more file was processed (indexed, skipped or deduplicated)
+    pub fn file_processed(&self) {
+        self.files_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Files the run will process (0 until the walk finishes)
+    pub fn files_total(&self) -> usize {
+        self.files_total.load(Ordering::Relaxed)
+    }
+
+    /// Files processed so far
+    pub fn files_processed(&self) -> usize {
+        self.files_processed.load(Ordering::Relaxed)
+    }
+
+    /// Processed share of the files, 0-100
+    pub fn percent(&self) -> u8 {
+        let total = self.files_total();
+        if total == 0 {
+            return 0;
+        }
+        (self.files_processed().min(total) * 100 / total) as u8
+    }
+
+    /// Ask the run to stop at the next file boundary
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Time the run's stages from now on (see [`IndexProfiler`])
+    pub fn enable_profiling(&self) {
+        self.profiler.get_or_init(IndexProfiler::new);
+    }
+
+    /// The run's profiler, if profiling is enabled
+    pub fn profiler(&self) -> Option<&IndexProfiler> {
+        self.profiler.get()
+    }
+
+    /// Return [`ShebeError::Cancelled`] if cancellation was requested
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(ShebeError::Cancelled(
+                "indexing stopped on request".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_percent() {
+        let progress = IndexProgress::new();
+        assert_eq!(progress.percent(), 0);
+
+        progress.set_total(4);
+        progress.file_processed();
+        assert_eq!(progress.percent(), 25);
+        for _ in 0..3 {
+            progress.file_processed();
+        }
+        assert_eq!(progress.percent(), 100);
+        assert_eq!(progress.files_processed(), 4);
+    }
+
+    #[test]
+    fn test_progress_cancel() {
+        let progress = IndexProgress::new();
+        assert!(progress.check_cancelled().is_ok());
+
+        progress.cancel();
+        assert!(progress.is_cancelled());
+        assert!(matches!(
+            progress.check_cancelled(),
+            Err(ShebeError::Cancelled(_))
+        ));
+    }
+}
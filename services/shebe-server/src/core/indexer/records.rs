@@ -0,0 +1,158 @@
+//! Record boundaries in data files.
+//!
+//! JSON Lines (`.jsonl`, `.ndjson`) and delimited files (`.csv`,
+//! `.tsv`) hold one record per line, except that a double-quoted CSV
+//! field may contain line breaks. Cutting such files every
+//! `chunk_size` characters leaves half a record at each end of most
+//! chunks, so [`Chunker::chunk_records`] builds their chunks from
+//! whole records instead and each chunk knows which records it holds
+//! (`records 1,204–1,267`).
+//!
+//! Records are numbered from 1 in file order; a CSV header row is
+//! record 1. Every line counts, blank ones included, so record numbers
+//! match line numbers unless a quoted field spans lines.
+//!
+//! [`Chunker::chunk_records`]: super::Chunker::chunk_records
+
+use crate::core::types::RecordRange;
+use std::ops::Range;
+use std::path::Path;
+use tantivy::schema::{Schema, Value};
+use tantivy::TantivyDocument;
+
+/// Extensions chunked by record (see [`RecordFormat::for_path`])
+pub const RECORD_EXTENSIONS: &[&str] = &["jsonl", "ndjson", "csv", "tsv"];
+
+/// Stored field with a chunk's first record (schema v8)
+pub(crate) const RECORD_START_FIELD: &str = "record_start";
+
+/// Stored field with a chunk's last record (schema v8)
+pub(crate) const RECORD_END_FIELD: &str = "record_end";
+
+/// How a data file separates its records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One record per line (JSON Lines, TSV)
+    Lines,
+    /// One record per line, except inside double-quoted fields (CSV)
+    QuotedLines,
+}
+
+impl RecordFormat {
+    /// Format of the file at `path`, judged by its extension
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::records::RecordFormat;
+    /// use std::path::Path;
+    ///
+    /// assert_eq!(RecordFormat::for_path(Path::new("events.ndjson")), Some(RecordFormat::Lines));
+    /// assert_eq!(RecordFormat::for_path(Path::new("users.CSV")), Some(RecordFormat::QuotedLines));
+    /// assert_eq!(RecordFormat::for_path(Path::new("main.rs")), None);
+    /// ```
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "jsonl" | "ndjson" | "tsv" => Some(Self::Lines),
+            "csv" => Some(Self::QuotedLines),
+            _ => None,
+        }
+    }
+
+    /// Byte ranges of the records in `text`, in order
+    ///
+    /// Each range includes the record's line break, so the ranges
+    /// cover `text` without gaps. A quote left open runs to the end
+    /// of the text.
+    pub fn record_spans(self, text: &str) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let mut in_quotes = false;
+        for (i, byte) in text.bytes().enumerate() {
+            match byte {
+                // An escaped quote ("") toggles twice
+                b'"' if self == Self::QuotedLines => in_quotes = !in_quotes,
+                b'\n' if !in_quotes => {
+                    spans.push(start..i + 1);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if start < text.len() {
+            spans.push(start..text.len());
+        }
+        spans
+    }
+}
+
+/// Records of a chunk's document, for chunks of data files
+///
+/// `None` for other chunks and for sessions indexed before schema v8.
+pub(crate) fn stored_records(schema: &Schema, doc: &TantivyDocument) -> Option<RecordRange> {
+    let read = |name: &str| {
+        let field = schema.get_field(name).ok()?;
+        doc.get_first(field)?.as_u64().map(|n| n as usize)
+    };
+    Some(RecordRange {
+        first: read(RECORD_START_FIELD)?,
+        last: read(RECORD_END_FIELD)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(format: RecordFormat, text: &str) -> Vec<&str> {
+        format
+            .record_spans(text)
+            .into_iter()
+            .map(|span| &text[span])
+            .collect()
+    }
+
+    #[test]
+    fn test_lines_are_records() {
+        let text = "{\"id\":1}\n{\"id\":2}\n\n{\"id\":3}";
+        assert_eq!(
+            records(RecordFormat::Lines, text),
+            vec!["{\"id\":1}\n", "{\"id\":2}\n", "\n", "{\"id\":3}"]
+        );
+        assert!(RecordFormat::Lines.record_spans("").is_empty());
+    }
+
+    #[test]
+    fn test_quoted_newlines_stay_in_their_record() {
+        let text = "id,note\n1,\"first line\nsecond line\"\n2,\"say \"\"hi\"\"\nbye\"\n3,plain\n";
+        assert_eq!(
+            records(RecordFormat::QuotedLines, text),
+            vec![
+                "id,note\n",
+                "1,\"first line\nsecond line\"\n",
+                "2,\"say \"\"hi\"\"\nbye\"\n",
+                "3,plain\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_quote_runs_to_end() {
+        let text = "a,b\n1,\"open\n2,x\n";
+        assert_eq!(
+            records(RecordFormat::QuotedLines, text),
+            vec!["a,b\n", "1,\"open\n2,x\n"]
+        );
+    }
+
+    #[test]
+    fn test_tsv_quotes_are_plain_characters() {
+        let text = "a\t\"b\nc\"\td\n";
+        assert_eq!(records(RecordFormat::Lines, text).len(), 2);
+        assert_eq!(
+            RecordFormat::for_path(Path::new("data.tsv")),
+            Some(RecordFormat::Lines)
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! Repository-local indexing settings.
+//!
+//! A repository can carry its own indexing defaults so callers do not
+//! have to pass patterns on every call:
+//!
+//! - `.shebeignore` (gitignore syntax) adds exclude patterns. The
+//!   [`FileWalker`](crate::core::indexer::FileWalker) reads it on every
+//!   walk, so re-indexing picks up edits.
+//! - `.shebe.toml` sets default `chunk_size`, `overlap` and
+//!   `include_patterns` for the repository.
+//!
+//! Explicit arguments win over `.shebe.toml`, which wins over the
+//! global defaults. Malformed lines are reported as warnings with line
+//! numbers and skipped; they never abort indexing.
+
+use crate::core::indexer::BoundaryHint;
+use crate::core::paths;
+use crate::core::storage::SessionConfig;
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Ignore file read from the repository root
+pub const IGNORE_FILE: &str = ".shebeignore";
+
+/// Settings file read from the repository root
+pub const REPO_CONFIG_FILE: &str = ".shebe.toml";
+
+/// A problem found in a repository-local file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFileWarning {
+    /// File name (`.shebeignore` or `.shebe.toml`)
+    pub file: &'static str,
+
+    /// 1-based line number
+    pub line: usize,
+
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl fmt::Display for RepoFileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+/// One `.shebeignore` pattern translated to a glob
+#[derive(Debug, Clone)]
+pub struct IgnoreRule {
+    /// 1-based line number in the ignore file
+    pub line: usize,
+
+    /// Pattern as written in the ignore file
+    pub pattern: String,
+
+    /// Equivalent glob, matched against walked paths
+    pub glob: Pattern,
+}
+
+impl IgnoreRule {
+    /// Name used when reporting what the rule excluded
+    pub fn label(&self) -> String {
+        format!("{} ({IGNORE_FILE}:{})", self.pattern, self.line)
+    }
+}
+
+/// Patterns read from a repository's `.shebeignore`
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreFile {
+    /// Usable patterns, in file order
+    pub rules: Vec<IgnoreRule>,
+
+    /// Lines that were skipped
+    pub warnings: Vec<RepoFileWarning>,
+}
+
+impl IgnoreFile {
+    /// Read `.shebeignore` from `root` (empty if there is none)
+    pub fn load(root: &Path) -> Self {
+        match fs::read_to_string(root.join(IGNORE_FILE)) {
+            Ok(content) => Self::parse(&content, root),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => Self {
+                rules: Vec::new(),
+                warnings: vec![RepoFileWarning {
+                    file: IGNORE_FILE,
+                    line: 0,
+                    message: format!("Failed to read file: {e}"),
+                }],
+            },
+        }
+    }
+
+    /// Translate gitignore-style lines into globs for paths under `root`
+    ///
+    /// Patterns containing a `/` (other than a trailing one) are
+    /// anchored at `root`; all others match at any depth. A trailing
+    /// `/` is accepted but not enforced, so `build/` also matches a
+    /// file named `build`. Negations (`!pattern`) are not supported.
+    pub fn parse(content: &str, root: &Path) -> Self {
+        let root = Pattern::escape(paths::normalize_path(root).trim_end_matches('/'));
+        let mut ignore = Self::default();
+
+        for (idx, raw) in content.lines().enumerate() {
+            let line = idx + 1;
+            let pattern = raw.trim_end();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+
+            let mut warn = |message: String| {
+                ignore.warnings.push(RepoFileWarning {
+                    file: IGNORE_FILE,
+                    line,
+                    message,
+                })
+            };
+
+            if pattern.starts_with('!') {
+                warn(format!(
+                    "Negated pattern '{pattern}' is not supported and was skipped"
+                ));
+                continue;
+            }
+
+            // "\#" and "\!" escape a leading '#' or '!'
+            let body = pattern
+                .strip_prefix('\\')
+                .filter(|rest| rest.starts_with('#') || rest.starts_with('!'))
+                .unwrap_or(pattern)
+                .trim_end_matches('/');
+            if body.is_empty() || body == "/" {
+                warn(format!(
+                    "Pattern '{pattern}' matches nothing and was skipped"
+                ));
+                continue;
+            }
+
+            let glob = match body.strip_prefix('/') {
+                Some(anchored) => format!("{root}/{anchored}"),
+                None if body.contains('/') => format!("{root}/{body}"),
+                None => format!("**/{body}"),
+            };
+
+            match Pattern::new(&glob) {
+                Ok(glob) => ignore.rules.push(IgnoreRule {
+                    line,
+                    pattern: pattern.to_string(),
+                    glob,
+                }),
+                Err(e) => warn(format!("Invalid pattern '{pattern}': {e}")),
+            }
+        }
+
+        ignore
+    }
+}
+
+/// Indexing defaults read from a repository's `.shebe.toml`
+#[derive(Debug, Clone, Default)]
+pub struct RepoConfig {
+    /// Characters per chunk
+    pub chunk_size: Option<usize>,
+
+    /// Character overlap between chunks
+    pub overlap: Option<usize>,
+
+    /// Glob patterns for files to include
+    pub include_patterns: Option<Vec<String>>,
+
+    /// Keys or values that were skipped
+    pub warnings: Vec<RepoFileWarning>,
+}
+
+impl RepoConfig {
+    /// Read `.shebe.toml` from `root` (empty if there is none)
+    pub fn load(root: &Path) -> Self {
+        match fs::read_to_string(root.join(REPO_CONFIG_FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => Self {
+                warnings: vec![RepoFileWarning {
+                    file: REPO_CONFIG_FILE,
+                    line: 0,
+                    message: format!("Failed to read file: {e}"),
+                }],
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Parse `.shebe.toml` content
+    ///
+    /// A file that is not valid TOML is ignored as a whole. Unknown keys
+    /// and out-of-range values are skipped individually.
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+
+        let table = match content.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                let line = e.span().map_or(1, |span| line_of(content, span.start));
+                config.warnings.push(RepoFileWarning {
+                    file: REPO_CONFIG_FILE,
+                    line,
+                    message: format!("Invalid TOML, file ignored: {}", e.message()),
+                });
+                return config;
+            }
+        };
+
+        for (key, value) in &table {
+            let line = key_line(content, key);
+            let mut warn = |message: String| {
+                config.warnings.push(RepoFileWarning {
+                    file: REPO_CONFIG_FILE,
+                    line,
+                    message,
+                })
+            };
+
+            match key.as_str() {
+                "chunk_size" => match value.as_integer() {
+                    Some(n @ 100..=2000) => config.chunk_size = Some(n as usize),
+                    _ => warn(format!(
+                        "chunk_size must be an integer between 100 and 2000, got {value}"
+                    )),
+                },
+                "overlap" => match value.as_integer() {
+                    Some(n @ 0..=500) => config.overlap = Some(n as usize),
+                    _ => warn(format!(
+                        "overlap must be an integer between 0 and 500, got {value}"
+                    )),
+                },
+                "include_patterns" => match include_patterns(value) {
+                    Ok(patterns) => config.include_patterns = Some(patterns),
+                    Err(message) => warn(message),
+                },
+                other => warn(format!("Unknown key '{other}' was skipped")),
+            }
+        }
+
+        config
+    }
+}
+
+/// Parse `include_patterns` as an array of valid globs
+fn include_patterns(value: &toml::Value) -> std::result::Result<Vec<String>, String> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("include_patterns must be an array of strings, got {value}"))?;
+
+    items
+        .iter()
+        .map(|item| {
+            let pattern = item
+                .as_str()
+                .ok_or_else(|| format!("include_patterns must contain strings, got {item}"))?;
+            Pattern::new(pattern)
+                .map(|_| pattern.to_string())
+                .map_err(|e| format!("Invalid include pattern '{pattern}': {e}"))
+        })
+        .collect()
+}
+
+/// 1-based line containing byte `offset`
+fn line_of(content: &str, offset: usize) -> usize {
+    content.as_bytes()[..offset.min(content.len())]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// 1-based line where top-level `key` is assigned (1 if not found)
+fn key_line(content: &str, key: &str) -> usize {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start();
+            let line = line
+                .strip_prefix('"')
+                .and_then(|l| l.strip_prefix(key))
+                .and_then(|l| l.strip_prefix('"'))
+                .or_else(|| line.strip_prefix(key));
+            line.is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .map_or(1, |idx| idx + 1)
+}
+
+/// Where an effective session setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Passed explicitly to the tool or command
+    Argument,
+    /// Read from the repository's `.shebe.toml`
+    RepoConfig,
+    /// Global defaults
+    Default,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argument => write!(f, "argument"),
+            Self::RepoConfig => write!(f, "{REPO_CONFIG_FILE}"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Where each value of a session's configuration came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSources {
+    pub chunk_size: ConfigSource,
+    pub overlap: ConfigSource,
+    pub include_patterns: ConfigSource,
+    pub exclude_patterns: ConfigSource,
+
+    /// `.shebeignore` patterns applied on top of the exclude patterns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_patterns: Vec<String>,
+}
+
+impl ConfigSources {
+    /// Mark chunk settings replaced by explicit arguments (re-indexing)
+    pub fn with_chunk_overrides(mut self, chunk_size: bool, overlap: bool) -> Self {
+        if chunk_size {
+            self.chunk_size = ConfigSource::Argument;
+        }
+        if overlap {
+            self.overlap = ConfigSource::Argument;
+        }
+        self
+    }
+}
+
+/// Values passed explicitly by the caller (`None` when not given)
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct IndexOverrides {
+    pub chunk_size: Option<usize>,
+    pub overlap: Option<usize>,
+    pub include_patterns: Option<Vec<String>>,
+    pub exclude_patterns: Option<Vec<String>>,
+    /// Presets already expanded into `include_patterns`, recorded
+    /// with the session
+    pub languages: Vec<String>,
+    pub dedupe: bool,
+    pub boundary_hint: Option<BoundaryHint>,
+    pub store_chunk_text: Option<bool>,
+    pub strip_high_entropy: Option<bool>,
+    pub strip_run_chars: Option<usize>,
+    pub merge_tail_fragments: Option<bool>,
+    pub record_chunking: Option<bool>,
+    pub max_chunks_per_file: Option<usize>,
+    pub strict_patterns: Option<bool>,
+    pub git_metadata: Option<bool>,
+}
+
+impl IndexOverrides {
+    /// Overrides that reproduce a session's stored configuration
+    ///
+    /// Pass the stored configuration as defaults too. Values that came
+    /// from `.shebe.toml` are left to it, so edits to that file apply;
+    /// the rest keep their stored value and source. Sessions indexed
+    /// before sources were tracked keep every stored value.
+    pub fn from_session(config: &SessionConfig) -> Self {
+        let sources = config.sources.as_ref();
+        let pinned = |pick: fn(&ConfigSources) -> ConfigSource| {
+            sources.is_none_or(|s| pick(s) == ConfigSource::Argument)
+        };
+        Self {
+            chunk_size: pinned(|s| s.chunk_size).then_some(config.chunk_size),
+            overlap: pinned(|s| s.overlap).then_some(config.overlap),
+            include_patterns: pinned(|s| s.include_patterns)
+                .then(|| config.include_patterns.clone()),
+            exclude_patterns: pinned(|s| s.exclude_patterns)
+                .then(|| config.exclude_patterns.clone()),
+            languages: config.languages.clone(),
+            dedupe: config.dedupe,
+            boundary_hint: None,
+            store_chunk_text: None,
+            strip_high_entropy: None,
+            strip_run_chars: None,
+            merge_tail_fragments: None,
+            record_chunking: None,
+            max_chunks_per_file: None,
+            strict_patterns: None,
+            git_metadata: None,
+        }
+    }
+}
+
+/// Effective session configuration and any repository file warnings
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    /// Configuration to index with (`sources` is always set)
+    pub config: SessionConfig,
+
+    /// Problems found in `.shebe.toml` and `.shebeignore`
+    pub warnings: Vec<RepoFileWarning>,
+}
+
+/// Resolve the configuration for indexing the repository at `root`
+///
+/// Each value is taken from `overrides` if given, then from the
+/// repository's `.shebe.toml`, then from `defaults`.
+pub fn resolve_session_config(
+    root: &Path,
+    overrides: IndexOverrides,
+    defaults: SessionConfig,
+) -> ResolvedConfig {
+    fn pick<T>(argument: Option<T>, repo: Option<T>, default: T) -> (T, ConfigSource) {
+        match (argument, repo) {
+            (Some(value), _) => (value, ConfigSource::Argument),
+            (None, Some(value)) => (value, ConfigSource::RepoConfig),
+            (None, None) => (default, ConfigSource::Default),
+        }
+    }
+
+    let repo = RepoConfig::load(root);
+    let ignore = IgnoreFile::load(root);
+
+    let (chunk_size, chunk_size_source) =
+        pick(overrides.chunk_size, repo.chunk_size, defaults.chunk_size);
+    let (overlap, overlap_source) = pick(overrides.overlap, repo.overlap, defaults.overlap);
+    let (include_patterns, include_source) = pick(
+        overrides.include_patterns,
+        repo.include_patterns,
+        defaults.include_patterns,
+    );
+    let (exclude_patterns, exclude_source) =
+        pick(overrides.exclude_patterns, None, defaults.exclude_patterns);
+
+    let mut warnings = repo.warnings;
+    warnings.extend(ignore.warnings);
+
+    ResolvedConfig {
+        config: SessionConfig {
+            chunk_size,
+            overlap,
+            include_patterns,
+            exclude_patterns,
+            languages: overrides.languages,
+            dedupe: overrides.dedupe,
+            boundary_hint: overrides.boundary_hint.unwrap_or(defaults.boundary_hint),
+            store_chunk_text: overrides
+                .store_chunk_text
+                .unwrap_or(defaults.store_chunk_text),
+            strip_high_entropy: overrides
+                .strip_high_entropy
+                .unwrap_or(defaults.strip_high_entropy),
+            strip_run_chars: overrides
+                .strip_run_chars
+                .unwrap_or(defaults.strip_run_chars),
+            merge_tail_fragments: overrides
+                .merge_tail_fragments
+                .unwrap_or(defaults.merge_tail_fragments),
+            record_chunking: overrides
+                .record_chunking
+                .unwrap_or(defaults.record_chunking),
+            max_chunks_per_file: overrides
+                .max_chunks_per_file
+                .unwrap_or(defaults.max_chunks_per_file),
+            strict_patterns: overrides
+                .strict_patterns
+                .unwrap_or(defaults.strict_patterns),
+            git_metadata: overrides.git_metadata.unwrap_or(defaults.git_metadata),
+            bm25_k1: defaults.bm25_k1,
+            bm25_b: defaults.bm25_b,
+            sources: Some(ConfigSources {
+                chunk_size: chunk_size_source,
+                overlap: overlap_source,
+                include_patterns: include_source,
+                exclude_patterns: exclude_source,
+                ignore_patterns: ignore.rules.into_iter().map(|r| r.pattern).collect(),
+            }),
+        },
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn globs(ignore: &IgnoreFile) -> Vec<&str> {
+        ignore.rules.iter().map(|r| r.glob.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ignore_translates_patterns() {
+        let content = "# generated\n\n*.log\nbuild/\n/docs/out\nsrc/gen/*.rs\n\\#notes\n";
+        let ignore = IgnoreFile::parse(content, Path::new("/repo"));
+
+        assert!(ignore.warnings.is_empty());
+        assert_eq!(
+            globs(&ignore),
+            vec![
+                "**/*.log",
+                "**/build",
+                "/repo/docs/out",
+                "/repo/src/gen/*.rs",
+                "**/#notes"
+            ]
+        );
+        assert_eq!(ignore.rules[1].line, 4);
+        assert_eq!(ignore.rules[1].label(), "build/ (.shebeignore:4)");
+    }
+
+    #[test]
+    fn test_ignore_globs_match_absolute_paths() {
+        let ignore = IgnoreFile::parse("*.log\nbuild/\n/docs/out\n", Path::new("/repo"));
+
+        assert!(ignore.rules[0]
+            .glob
+            .matches_path(Path::new("/repo/a/b/app.log")));
+        assert!(ignore.rules[1]
+            .glob
+            .matches_path(Path::new("/repo/x/build")));
+        assert!(ignore.rules[2]
+            .glob
+            .matches_path(Path::new("/repo/docs/out")));
+        assert!(!ignore.rules[2]
+            .glob
+            .matches_path(Path::new("/repo/sub/docs/out")));
+    }
+
+    #[test]
+    fn test_ignore_warns_with_line_numbers() {
+        let ignore = IgnoreFile::parse("ok.txt\n!keep.txt\nbad[\n/\n", Path::new("/repo"));
+
+        assert_eq!(ignore.rules.len(), 1);
+        let lines: Vec<usize> = ignore.warnings.iter().map(|w| w.line).collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert!(ignore.warnings[0]
+            .to_string()
+            .starts_with(".shebeignore:2: "));
+    }
+
+    #[test]
+    fn test_repo_config_parses_values() {
+        let config = RepoConfig::parse(
+            "chunk_size = 1024\noverlap = 128\ninclude_patterns = [\"*.rs\", \"*.md\"]\n",
+        );
+
+        assert!(config.warnings.is_empty());
+        assert_eq!(config.chunk_size, Some(1024));
+        assert_eq!(config.overlap, Some(128));
+        assert_eq!(
+            config.include_patterns,
+            Some(vec!["*.rs".to_string(), "*.md".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_repo_config_skips_bad_values() {
+        let config = RepoConfig::parse(
+            "# repo defaults\nchunk_size = 5\noverlap = 64\ninclude_patterns = \"*.rs\"\nfoo = 1\n",
+        );
+
+        assert_eq!(config.chunk_size, None);
+        assert_eq!(config.overlap, Some(64));
+        assert_eq!(config.include_patterns, None);
+
+        let mut lines: Vec<usize> = config.warnings.iter().map(|w| w.line).collect();
+        lines.sort_unstable();
+        assert_eq!(lines, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_repo_config_invalid_toml() {
+        let config = RepoConfig::parse("chunk_size = 1024\noverlap = = 3\n");
+
+        assert_eq!(config.chunk_size, None);
+        assert_eq!(config.warnings.len(), 1);
+        assert_eq!(config.warnings[0].line, 2);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let repo = TempDir::new().unwrap();
+        fs::write(
+            repo.path().join(REPO_CONFIG_FILE),
+            "chunk_size = 1024\noverlap = 100\ninclude_patterns = [\"*.rs\"]\n",
+        )
+        .unwrap();
+        fs::write(repo.path().join(IGNORE_FILE), "*.log\n!keep.log\n").unwrap();
+
+        let overrides = IndexOverrides {
+            overlap: Some(32),
+            ..IndexOverrides::default()
+        };
+        let resolved = resolve_session_config(repo.path(), overrides, SessionConfig::default());
+        let config = resolved.config;
+        let sources = config.sources.unwrap();
+
+        assert_eq!(config.chunk_size, 1024);
+        assert_eq!(sources.chunk_size, ConfigSource::RepoConfig);
+        assert_eq!(config.overlap, 32);
+        assert_eq!(sources.overlap, ConfigSource::Argument);
+        assert_eq!(config.include_patterns, vec!["*.rs".to_string()]);
+        assert_eq!(sources.include_patterns, ConfigSource::RepoConfig);
+        assert_eq!(
+            config.exclude_patterns,
+            SessionConfig::default().exclude_patterns
+        );
+        assert_eq!(sources.exclude_patterns, ConfigSource::Default);
+        assert_eq!(sources.ignore_patterns, vec!["*.log".to_string()]);
+        assert_eq!(resolved.warnings.len(), 1);
+        assert_eq!(resolved.warnings[0].line, 2);
+    }
+
+    #[test]
+    fn test_resolve_without_repo_files() {
+        let repo = TempDir::new().unwrap();
+
+        let resolved = resolve_session_config(
+            repo.path(),
+            IndexOverrides::default(),
+            SessionConfig::default(),
+        );
+        let sources = resolved.config.sources.unwrap();
+
+        assert!(resolved.warnings.is_empty());
+        assert_eq!(sources.chunk_size, ConfigSource::Default);
+        assert!(sources.ignore_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_overrides_from_session_reproduce_config() {
+        let repo = TempDir::new().unwrap();
+        fs::write(repo.path().join(REPO_CONFIG_FILE), "chunk_size = 1024\n").unwrap();
+        let overrides = IndexOverrides {
+            overlap: Some(32),
+            dedupe: true,
+            ..IndexOverrides::default()
+        };
+        let stored =
+            resolve_session_config(repo.path(), overrides, SessionConfig::default()).config;
+
+        // Edits to .shebe.toml apply; explicit arguments are kept
+        fs::write(repo.path().join(REPO_CONFIG_FILE), "chunk_size = 768\n").unwrap();
+        let resolved = resolve_session_config(
+            repo.path(),
+            IndexOverrides::from_session(&stored),
+            stored.clone(),
+        )
+        .config;
+
+        assert_eq!(resolved.chunk_size, 768);
+        assert_eq!(resolved.overlap, 32);
+        assert!(resolved.dedupe);
+        assert_eq!(resolved.sources, stored.sources);
+    }
+}
@@ -0,0 +1,201 @@
+//! Finding the directory to index for a path given by the user.
+//!
+//! Only directories and archives are indexed. A path to a file inside a
+//! repository (a README, a source file) is refused with a clear error,
+//! or, when the caller asks for it, replaced by the nearest parent
+//! directory holding a repository root marker such as `.git` or
+//! `Cargo.toml`.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::core::archive::ArchiveKind;
+use crate::core::error::{Result, ShebeError};
+
+/// Files and directories that mark a repository root, in the order
+/// they are checked within one directory
+pub const ROOT_MARKERS: [&str; 4] = [".git", "Cargo.toml", "package.json", "pyproject.toml"];
+
+/// Most directories checked for a marker: the file's own directory and
+/// its parents
+pub const MAX_ROOT_SEARCH_LEVELS: usize = 8;
+
+/// Repository root found for a file path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DetectedRoot {
+    /// Directory that is indexed
+    pub root: PathBuf,
+    /// File path the caller gave
+    pub file: PathBuf,
+    /// Marker found in `root`
+    pub marker: &'static str,
+}
+
+impl DetectedRoot {
+    /// Which root was chosen and why, e.g.
+    /// `/repo (contains .git; detected from /repo/src/main.rs)`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} (contains {}; detected from {})",
+            self.root.display(),
+            self.marker,
+            self.file.display()
+        )
+    }
+}
+
+/// Directory to index for `path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexRoot {
+    /// Directory or archive to index
+    pub path: PathBuf,
+    /// Set when `path` was a file and its repository root is indexed
+    pub detected: Option<DetectedRoot>,
+}
+
+/// Resolve the directory to index for an existing `path`
+///
+/// Directories and archives are indexed as given. A file is refused,
+/// unless `detect_root` is set: then the nearest directory holding one
+/// of [`ROOT_MARKERS`], starting with the file's own directory, is
+/// indexed instead.
+///
+/// # Errors
+///
+/// `InvalidPath` for a file without `detect_root`, and for a file with
+/// no marker within [`MAX_ROOT_SEARCH_LEVELS`] directories (the error
+/// lists the directories checked).
+pub fn resolve_index_root(path: &Path, detect_root: bool) -> Result<IndexRoot> {
+    if path.is_dir() || ArchiveKind::detect(path).is_some() {
+        return Ok(IndexRoot {
+            path: path.to_path_buf(),
+            detected: None,
+        });
+    }
+    if !detect_root {
+        return Err(file_path_error(path));
+    }
+    let detected = detect_repository_root(path)?;
+    Ok(IndexRoot {
+        path: detected.root.clone(),
+        detected: Some(detected),
+    })
+}
+
+/// Error for a file given where a directory is expected
+pub fn file_path_error(path: &Path) -> ShebeError {
+    ShebeError::InvalidPath(format!(
+        "Path '{}' is a file; pass its directory or use the files parameter \
+         (--files-from on the command line), or pass detect_root: true \
+         (--detect-root) to index the repository that contains it",
+        path.display()
+    ))
+}
+
+/// Walk up from `file` to the nearest directory holding a root marker
+///
+/// # Errors
+///
+/// `InvalidPath` listing the directories checked when none of the first
+/// [`MAX_ROOT_SEARCH_LEVELS`] holds a marker.
+pub fn detect_repository_root(file: &Path) -> Result<DetectedRoot> {
+    let mut checked = Vec::new();
+    for dir in file.ancestors().skip(1).take(MAX_ROOT_SEARCH_LEVELS) {
+        if let Some(marker) = ROOT_MARKERS.into_iter().find(|m| dir.join(m).exists()) {
+            return Ok(DetectedRoot {
+                root: dir.to_path_buf(),
+                file: file.to_path_buf(),
+                marker,
+            });
+        }
+        checked.push(dir.display().to_string());
+    }
+    Err(ShebeError::InvalidPath(format!(
+        "No repository root found for '{}': none of {} in the {} directories \
+         checked ({}). Pass the repository directory instead",
+        file.display(),
+        ROOT_MARKERS.join(", "),
+        checked.len(),
+        checked.join(", ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_directory_is_indexed_as_given() {
+        let temp = TempDir::new().unwrap();
+        let root = resolve_index_root(temp.path(), true).unwrap();
+        assert_eq!(root.path, temp.path());
+        assert_eq!(root.detected, None);
+    }
+
+    #[test]
+    fn test_file_is_refused_without_detect_root() {
+        let temp = TempDir::new().unwrap();
+        let readme = temp.path().join("README.md");
+        fs::write(&readme, "# Readme\n").unwrap();
+
+        let err = resolve_index_root(&readme, false).unwrap_err();
+        assert!(matches!(err, ShebeError::InvalidPath(_)));
+        assert!(err
+            .to_string()
+            .contains("is a file; pass its directory or use the files parameter"));
+        assert!(err.to_string().contains("detect_root"));
+    }
+
+    #[test]
+    fn test_nearest_marker_wins() {
+        let temp = TempDir::new().unwrap();
+        let crate_dir = temp.path().join("crates/core");
+        fs::create_dir_all(crate_dir.join("src")).unwrap();
+        fs::create_dir(temp.path().join(".git")).unwrap();
+        fs::write(crate_dir.join("Cargo.toml"), "[package]\n").unwrap();
+        let file = crate_dir.join("src/lib.rs");
+        fs::write(&file, "pub fn f() {}\n").unwrap();
+
+        let root = resolve_index_root(&file, true).unwrap();
+        assert_eq!(root.path, crate_dir);
+        let detected = root.detected.unwrap();
+        assert_eq!(detected.marker, "Cargo.toml");
+        assert_eq!(
+            detected.summary(),
+            format!(
+                "{} (contains Cargo.toml; detected from {})",
+                crate_dir.display(),
+                file.display()
+            )
+        );
+
+        let docs = temp.path().join("docs/guide.md");
+        fs::create_dir(temp.path().join("docs")).unwrap();
+        fs::write(&docs, "# Guide\n").unwrap();
+        let detected = detect_repository_root(&docs).unwrap();
+        assert_eq!(detected.root, temp.path());
+        assert_eq!(detected.marker, ".git");
+    }
+
+    #[test]
+    fn test_no_marker_lists_directories_checked() {
+        let temp = TempDir::new().unwrap();
+        let deep = temp.path().join("a/b/c/d/e/f/g/h/i");
+        fs::create_dir_all(&deep).unwrap();
+        let file = deep.join("notes.txt");
+        fs::write(&file, "notes\n").unwrap();
+        // A marker above the search limit is not found
+        fs::write(temp.path().join("package.json"), "{}\n").unwrap();
+
+        let err = detect_repository_root(&file).unwrap_err().to_string();
+        assert!(err.contains("No repository root found"), "{err}");
+        assert!(err.contains(".git, Cargo.toml, package.json, pyproject.toml"));
+        assert!(err.contains(&format!("the {MAX_ROOT_SEARCH_LEVELS} directories checked")));
+        assert!(err.contains(&deep.display().to_string()));
+        // The ninth directory up is not checked
+        assert!(err.contains(&format!("{})", temp.path().join("a/b").display())));
+        assert!(!err.contains(&format!("{})", temp.path().join("a").display())));
+    }
+}
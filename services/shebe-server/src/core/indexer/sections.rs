@@ -0,0 +1,372 @@
+//! Heading breadcrumbs of documentation chunks.
+//!
+//! A search hit in a long Markdown or reStructuredText document says
+//! little about where it sits until the file is opened. Each chunk of
+//! such a file records the headings in effect at its start offset,
+//! outermost first, as its `section` (`Storage › Session layout`).
+//! Search output shows it after the file path and `section_filter`
+//! keeps only hits whose breadcrumb contains a substring.
+//!
+//! Markdown headings are ATX (`## Title`) or setext (a line underlined
+//! with `===` or `---`); fenced code blocks and YAML front matter are
+//! skipped. reStructuredText titles are underlined (and optionally
+//! overlined) with punctuation, and their levels follow the order in
+//! which each adornment style first appears, as docutils assigns them.
+
+use crate::core::types::Chunk;
+use std::path::Path;
+use tantivy::schema::{Schema, Value};
+use tantivy::TantivyDocument;
+
+/// Extensions whose chunks get a section (see [`SectionFormat::for_path`])
+pub const SECTION_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "rst"];
+
+/// Stored and indexed field with a chunk's breadcrumb (schema v9)
+pub(crate) const SECTION_FIELD: &str = "section";
+
+/// Separator between the headings of a breadcrumb
+pub const SECTION_SEPARATOR: &str = " › ";
+
+/// Characters that can adorn a reStructuredText title
+const RST_ADORNMENTS: &str = "=-`:'\"~^_*+#<>.";
+
+/// How a documentation file marks its headings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionFormat {
+    /// Markdown and MDX
+    Markdown,
+    /// reStructuredText
+    RestructuredText,
+}
+
+/// A heading and where its line starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Byte offset of the heading's first line (an rst overline)
+    pub offset: usize,
+    /// Nesting level, 1 for the outermost
+    pub level: usize,
+    /// Heading text
+    pub title: String,
+}
+
+impl SectionFormat {
+    /// Format of the file at `path`, judged by its extension
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shebe::core::indexer::sections::SectionFormat;
+    /// use std::path::Path;
+    ///
+    /// assert_eq!(SectionFormat::for_path(Path::new("README.md")), Some(SectionFormat::Markdown));
+    /// assert_eq!(SectionFormat::for_path(Path::new("index.rst")), Some(SectionFormat::RestructuredText));
+    /// assert_eq!(SectionFormat::for_path(Path::new("main.rs")), None);
+    /// ```
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" | "mdx" => Some(Self::Markdown),
+            "rst" => Some(Self::RestructuredText),
+            _ => None,
+        }
+    }
+
+    /// Headings of `text`, in order
+    pub fn headings(self, text: &str) -> Vec<Heading> {
+        let lines = lines_with_offsets(text);
+        match self {
+            Self::Markdown => markdown_headings(&lines),
+            Self::RestructuredText => rst_headings(&lines),
+        }
+    }
+}
+
+/// Set each chunk's section to the breadcrumb at its start offset
+///
+/// Chunks before the first heading keep no section. `chunks` must be
+/// in file order, as the chunker returns them.
+pub fn assign_sections(chunks: &mut [Chunk], text: &str, format: SectionFormat) {
+    let headings = format.headings(text);
+    let mut next = headings.iter().peekable();
+    let mut trail: Vec<&Heading> = Vec::new();
+
+    for chunk in chunks {
+        while let Some(heading) = next.next_if(|h| h.offset <= chunk.start_offset) {
+            trail.retain(|outer| outer.level < heading.level);
+            trail.push(heading);
+        }
+        chunk.section = (!trail.is_empty()).then(|| {
+            trail
+                .iter()
+                .map(|heading| heading.title.as_str())
+                .collect::<Vec<_>>()
+                .join(SECTION_SEPARATOR)
+        });
+    }
+}
+
+/// Breadcrumb of a chunk's document
+///
+/// `None` for chunks before a file's first heading, for other files
+/// and for sessions indexed before schema v9.
+pub(crate) fn stored_section(schema: &Schema, doc: &TantivyDocument) -> Option<String> {
+    let field = schema.get_field(SECTION_FIELD).ok()?;
+    doc.get_first(field)?.as_str().map(str::to_string)
+}
+
+/// Lines of `text` without their line breaks, with their byte offsets
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+/// Line with at most three spaces of indentation removed, or `None`
+/// for an indented code line
+fn unindented(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+    (line.len() - trimmed.len() <= 3).then_some(trimmed)
+}
+
+fn markdown_headings(lines: &[(usize, &str)]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    // First line of the current paragraph, and its line count
+    let mut paragraph: Option<(usize, &str)> = None;
+    let mut paragraph_lines = 0;
+    let mut start = 0;
+
+    // YAML front matter
+    if lines.first().is_some_and(|(_, line)| *line == "---") {
+        if let Some(end) = lines[1..]
+            .iter()
+            .position(|(_, line)| *line == "---" || *line == "...")
+        {
+            start = end + 2;
+        }
+    }
+
+    for &(offset, line) in &lines[start..] {
+        let content = unindented(line);
+
+        // Fenced code blocks end at a fence of the same character at
+        // least as long as the opening one
+        let marker = content.and_then(fence_marker);
+        if let Some((fence_char, fence_len)) = fence {
+            if marker.is_some_and(|(c, len)| c == fence_char && len >= fence_len) {
+                fence = None;
+            }
+            continue;
+        }
+        if marker.is_some() {
+            fence = marker;
+            paragraph = None;
+            paragraph_lines = 0;
+            continue;
+        }
+
+        let Some(content) = content.filter(|c| !c.trim().is_empty()) else {
+            paragraph = None;
+            paragraph_lines = 0;
+            continue;
+        };
+
+        if let Some((level, title)) = atx_heading(content) {
+            if !title.is_empty() {
+                headings.push(Heading {
+                    offset,
+                    level,
+                    title: title.to_string(),
+                });
+            }
+            paragraph = None;
+            paragraph_lines = 0;
+            continue;
+        }
+
+        // An underline turns a one-line paragraph into a heading; after
+        // a longer paragraph or none, `---` is a thematic break
+        if let Some(level) = setext_level(content) {
+            if let (Some((text_offset, text)), 1) = (paragraph, paragraph_lines) {
+                headings.push(Heading {
+                    offset: text_offset,
+                    level,
+                    title: text.trim().to_string(),
+                });
+            }
+            paragraph = None;
+            paragraph_lines = 0;
+            continue;
+        }
+
+        paragraph.get_or_insert((offset, content));
+        paragraph_lines += 1;
+    }
+
+    headings
+}
+
+/// Opening character and length of a code fence line
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == fence_char).count();
+    (len >= 3).then_some((fence_char, len))
+}
+
+/// Level and text of an ATX heading (`## Title ##`)
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let mut title = rest.trim();
+    // An optional closing sequence of #s after a space
+    let without_closing = title.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        title = without_closing.trim_end();
+    }
+    Some((level, title))
+}
+
+/// Level of a setext underline (`===` for 1, `---` for 2)
+fn setext_level(line: &str) -> Option<usize> {
+    let line = line.trim_end();
+    if !line.is_empty() && line.chars().all(|c| c == '=') {
+        Some(1)
+    } else if !line.is_empty() && line.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn rst_headings(lines: &[(usize, &str)]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    // Adornment styles (character, overlined) in order of first use
+    let mut styles: Vec<(char, bool)> = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < lines.len() {
+        let (offset, title) = lines[i];
+        let under = rst_adornment(lines[i + 1].1);
+        let is_title = !title.trim().is_empty()
+            && !title.starts_with([' ', '\t'])
+            && rst_adornment(title).is_none();
+        match under {
+            Some((adornment, len)) if is_title && len >= title.trim_end().chars().count() => {
+                let overline = i
+                    .checked_sub(1)
+                    .and_then(|prev| rst_adornment(lines[prev].1))
+                    .is_some_and(|(c, _)| c == adornment);
+                let style = (adornment, overline);
+                let level = match styles.iter().position(|s| *s == style) {
+                    Some(position) => position + 1,
+                    None => {
+                        styles.push(style);
+                        styles.len()
+                    }
+                };
+                headings.push(Heading {
+                    offset: if overline { lines[i - 1].0 } else { offset },
+                    level,
+                    title: title.trim().to_string(),
+                });
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    headings
+}
+
+/// Character and length of a line made of one repeated adornment
+/// character (at least two)
+fn rst_adornment(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_end();
+    let first = line
+        .chars()
+        .next()
+        .filter(|c| RST_ADORNMENTS.contains(*c))?;
+    let len = line.chars().count();
+    (len >= 2 && line.chars().all(|c| c == first)).then_some((first, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::indexer::Chunker;
+
+    fn titles(format: SectionFormat, text: &str) -> Vec<(usize, String)> {
+        format
+            .headings(text)
+            .into_iter()
+            .map(|heading| (heading.level, heading.title))
+            .collect()
+    }
+
+    #[test]
+    fn test_markdown_headings() {
+        let text = "---\ntitle: Guide\n---\n\n# Guide\n\nIntro\n\n## Setup ##\n\n\
+                    ```sh\n# not a heading\n```\n\nInstall\n-------\n\n#hashtag\n\n### Notes\n";
+        assert_eq!(
+            titles(SectionFormat::Markdown, text),
+            vec![
+                (1, "Guide".to_string()),
+                (2, "Setup".to_string()),
+                (2, "Install".to_string()),
+                (3, "Notes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rst_levels_follow_first_use() {
+        let text =
+            "=====\nGuide\n=====\n\nSetup\n-----\n\nText\n\nUsage\n-----\n\nFlags\n~~~~~\n\n\
+                    Other\n=====\n";
+        assert_eq!(
+            titles(SectionFormat::RestructuredText, text),
+            vec![
+                (1, "Guide".to_string()),
+                (2, "Setup".to_string()),
+                (2, "Usage".to_string()),
+                (3, "Flags".to_string()),
+                (4, "Other".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assign_sections_follows_nesting() {
+        let text = "Preface\n\n# Storage\n\n## Session layout\n\nsessions/<id>\n\n\
+                    ## Locks\n\nOne writer\n\n# Search\n\nBM25\n";
+        let mut chunks = Chunker::new(12, 0).chunk_text(text, Path::new("arch.md"));
+        assign_sections(&mut chunks, text, SectionFormat::Markdown);
+
+        let section_at = |needle: &str| {
+            let offset = text.find(needle).unwrap();
+            chunks
+                .iter()
+                .find(|c| c.start_offset <= offset && offset < c.end_offset)
+                .and_then(|c| c.section.clone())
+        };
+        assert_eq!(chunks[0].section, None);
+        assert_eq!(
+            section_at("sessions/<id>").as_deref(),
+            Some("Storage › Session layout")
+        );
+        assert_eq!(section_at("One writer").as_deref(), Some("Storage › Locks"));
+        assert_eq!(section_at("BM25").as_deref(), Some("Search"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Skipped file tracking.
+//!
+//! Records why files were left out of an index (patterns, size,
+//! binary or non-UTF-8 content, read errors) so users can find out
+//! why a file they expect is missing. Counts cover every skipped
+//! file; the list of paths is capped at [`MAX_SKIPPED_FILES`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::paths;
+use crate::core::types::{SkipCounts, SkipReason, SkippedFile};
+
+/// Maximum number of skipped files listed in index statistics
+pub const MAX_SKIPPED_FILES: usize = 200;
+
+/// Bytes checked for NUL when detecting binary files (as git does)
+pub(crate) const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Skipped files collected during a walk and indexing run
+#[derive(Debug, Clone, Default)]
+pub struct SkipLog {
+    /// Files skipped for each reason
+    pub counts: SkipCounts,
+
+    /// First [`MAX_SKIPPED_FILES`] skipped files
+    pub files: Vec<SkippedFile>,
+}
+
+impl SkipLog {
+    /// Record a skipped file, listing it while under the cap
+    pub fn record(&mut self, root: &Path, path: &Path, reason: SkipReason, detail: String) {
+        self.push(relative_display(root, path), reason, detail);
+    }
+
+    /// Record a directory pruned from the walk (counted once)
+    pub fn record_dir(&mut self, root: &Path, path: &Path, reason: SkipReason, detail: String) {
+        self.push(format!("{}/", relative_display(root, path)), reason, detail);
+    }
+
+    fn push(&mut self, path: String, reason: SkipReason, detail: String) {
+        self.counts.add(reason);
+        if self.files.len() < MAX_SKIPPED_FILES {
+            self.files.push(SkippedFile {
+                path,
+                reason,
+                detail,
+            });
+        }
+    }
+}
+
+/// Outcome of re-checking one file against the indexing rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileExplanation {
+    /// The file that was checked
+    pub path: PathBuf,
+
+    /// Why the file is skipped, or `None` if it passes every check
+    pub reason: Option<SkipReason>,
+
+    /// The rule that decided the outcome
+    pub detail: String,
+}
+
+/// Read a file as UTF-8 text
+///
+/// Files with a NUL byte in their first 8000 bytes are binary; other
+/// files must be valid UTF-8. On failure returns the skip reason and
+/// a detail message.
+pub fn read_text(path: &Path) -> std::result::Result<String, (SkipReason, String)> {
+    let bytes = fs::read(path).map_err(|e| (SkipReason::ReadError, e.to_string()))?;
+    text_from_bytes(bytes)
+}
+
+/// Check file contents already read as UTF-8 text (see [`read_text`])
+pub fn text_from_bytes(bytes: Vec<u8>) -> std::result::Result<String, (SkipReason, String)> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if let Some(offset) = sniff.iter().position(|&b| b == 0) {
+        return Err((SkipReason::Binary, format!("NUL byte at offset {offset}")));
+    }
+
+    String::from_utf8(bytes).map_err(|e| {
+        (
+            SkipReason::NotUtf8,
+            format!("invalid UTF-8 at byte {}", e.utf8_error().valid_up_to()),
+        )
+    })
+}
+
+/// Path relative to `root` for display, or the full path outside it
+fn relative_display(root: &Path, path: &Path) -> String {
+    paths::normalize_path(path.strip_prefix(root).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_skip_log_caps_list_but_not_counts() {
+        let root = Path::new("/repo");
+        let mut log = SkipLog::default();
+        for i in 0..MAX_SKIPPED_FILES + 5 {
+            log.record(
+                root,
+                &root.join(format!("big-{i}.bin")),
+                SkipReason::TooLarge,
+                "over limit".to_string(),
+            );
+        }
+        log.record_dir(
+            root,
+            &root.join("target"),
+            SkipReason::ExcludedByPattern,
+            "pruned".to_string(),
+        );
+
+        assert_eq!(log.counts.too_large, MAX_SKIPPED_FILES + 5);
+        assert_eq!(log.counts.excluded_by_pattern, 1);
+        assert_eq!(log.files.len(), MAX_SKIPPED_FILES);
+        assert_eq!(log.files[0].path, "big-0.bin");
+    }
+
+    #[test]
+    fn test_record_dir_has_trailing_slash() {
+        let root = Path::new("/repo");
+        let mut log = SkipLog::default();
+        log.record_dir(
+            root,
+            &root.join("node_modules"),
+            SkipReason::ExcludedByPattern,
+            String::new(),
+        );
+
+        assert_eq!(log.files[0].path, "node_modules/");
+    }
+
+    #[test]
+    fn test_read_text_classifies_content() {
+        let temp = TempDir::new().unwrap();
+        let text = temp.path().join("a.rs");
+        let binary = temp.path().join("a.png");
+        let latin1 = temp.path().join("a.txt");
+        fs::write(&text, "fn main() {}").unwrap();
+        fs::write(&binary, [0x89, b'P', b'N', b'G', 0x00, 0x01]).unwrap();
+        fs::write(&latin1, [b'c', b'a', b'f', 0xE9]).unwrap();
+
+        assert_eq!(read_text(&text).unwrap(), "fn main() {}");
+        assert_eq!(
+            read_text(&binary).unwrap_err(),
+            (SkipReason::Binary, "NUL byte at offset 4".to_string())
+        );
+        assert_eq!(
+            read_text(&latin1).unwrap_err(),
+            (SkipReason::NotUtf8, "invalid UTF-8 at byte 3".to_string())
+        );
+        assert_eq!(
+            read_text(&temp.path().join("missing")).unwrap_err().0,
+            SkipReason::ReadError
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Bounded-memory reading of large files.
+//!
+//! Reading a file whole takes memory for its bytes, and chunking it
+//! several times more for its characters, so a multi-gigabyte
+//! single-line dump can exhaust memory even under the file size limit.
+//! Files over the streaming threshold are instead read in
+//! [`STREAM_BUFFER_BYTES`] pieces; each piece is checked, hashed and
+//! handed on (to a [`ChunkStream`](crate::core::indexer::chunker::ChunkStream))
+//! before the next one is read.
+//!
+//! Both ways of reading apply the same checks: a NUL byte in the first
+//! 8000 bytes makes a file binary, invalid UTF-8 is rejected, and with
+//! a maximum line length, so is any longer line.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+
+use crate::core::indexer::dedupe::ContentHasher;
+use crate::core::indexer::skipped::BINARY_SNIFF_BYTES;
+use crate::core::types::SkipReason;
+
+/// Bytes read from a streamed file at a time
+pub const STREAM_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Why a file cannot be indexed, with a detail message
+pub type SkipError = (SkipReason, String);
+
+/// Read `path` as UTF-8 text in bounded pieces, passing each to `visit`
+///
+/// Pieces end on character boundaries. Returns the
+/// [`content_hash`](crate::core::indexer::dedupe::content_hash) of the
+/// whole file, or why it is skipped (as [`read_text`] would, plus
+/// [`SkipReason::LineTooLong`]); pieces before the problem have been
+/// visited by then.
+///
+/// [`read_text`]: crate::core::indexer::skipped::read_text
+pub fn read_text_streaming(
+    path: &Path,
+    max_line_length: usize,
+    mut visit: impl FnMut(&str),
+) -> Result<String, SkipError> {
+    let read_error = |e: std::io::Error| (SkipReason::ReadError, e.to_string());
+    let mut file = File::open(path).map_err(read_error)?;
+    let mut buf = vec![0u8; STREAM_BUFFER_BYTES];
+    let mut hasher = ContentHasher::default();
+    let mut lines = LineLengths::new(max_line_length);
+    // File offset of buf[0], and bytes of a split character kept there
+    let mut offset = 0;
+    let mut pending = 0;
+
+    loop {
+        let read = fill(&mut file, &mut buf[pending..]).map_err(read_error)?;
+        if read == 0 {
+            if pending > 0 {
+                return Err(not_utf8(offset));
+            }
+            return Ok(hasher.finish());
+        }
+        let filled = &buf[..pending + read];
+
+        if offset < BINARY_SNIFF_BYTES {
+            let sniff = &filled[..filled.len().min(BINARY_SNIFF_BYTES - offset)];
+            if let Some(at) = sniff.iter().position(|&b| b == 0) {
+                return Err((
+                    SkipReason::Binary,
+                    format!("NUL byte at offset {}", offset + at),
+                ));
+            }
+        }
+
+        let text = match std::str::from_utf8(filled) {
+            Ok(text) => text,
+            // A character split by the end of the buffer: keep its bytes
+            Err(e) if e.error_len().is_none() => {
+                std::str::from_utf8(&filled[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(e) => return Err(not_utf8(offset + e.valid_up_to())),
+        };
+
+        lines.check(text)?;
+        hasher.update(text);
+        visit(text);
+
+        let consumed = text.len();
+        pending = filled.len() - consumed;
+        buf.copy_within(consumed..consumed + pending, 0);
+        offset += consumed;
+    }
+}
+
+/// Read into `buf` until it is full or the file ends
+///
+/// A full first buffer covers the binary sniff, so the checks run in
+/// the same order as for a file read whole.
+fn fill(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Reject `text` if a line is longer than `max_line_length` characters
+/// (0 allows any length)
+pub fn check_line_length(text: &str, max_line_length: usize) -> Result<(), SkipError> {
+    LineLengths::new(max_line_length).check(text)
+}
+
+/// Invalid UTF-8 at `offset`, as [`read_text`](crate::core::indexer::skipped::read_text) reports it
+fn not_utf8(offset: usize) -> SkipError {
+    (
+        SkipReason::NotUtf8,
+        format!("invalid UTF-8 at byte {offset}"),
+    )
+}
+
+/// Length of the current line across pieces of text
+struct LineLengths {
+    max: usize,
+    line: usize,
+    chars: usize,
+}
+
+impl LineLengths {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            line: 1,
+            chars: 0,
+        }
+    }
+
+    fn check(&mut self, text: &str) -> Result<(), SkipError> {
+        if self.max == 0 {
+            return Ok(());
+        }
+        for c in text.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.chars = 0;
+                continue;
+            }
+            self.chars += 1;
+            if self.chars > self.max {
+                return Err((
+                    SkipReason::LineTooLong,
+                    format!(
+                        "line {} is longer than {} characters (limits.max_line_length)",
+                        self.line, self.max
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::indexer::dedupe::content_hash;
+    use crate::core::indexer::skipped::read_text;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn streamed(path: &Path, max_line_length: usize) -> Result<(String, String), SkipError> {
+        let mut text = String::new();
+        let hash = read_text_streaming(path, max_line_length, |piece| text.push_str(piece))?;
+        Ok((text, hash))
+    }
+
+    #[test]
+    fn test_streaming_matches_reading_whole() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("big.txt");
+        // Multi-byte characters straddle the buffer boundaries
+        let text = "ab€🦀\n".repeat(STREAM_BUFFER_BYTES / 4);
+        fs::write(&path, &text).unwrap();
+
+        let (read, hash) = streamed(&path, 0).unwrap();
+        assert_eq!(read, text);
+        assert_eq!(hash, content_hash(&text));
+    }
+
+    #[test]
+    fn test_streaming_classifies_like_read_text() {
+        let temp = TempDir::new().unwrap();
+        let binary = temp.path().join("a.png");
+        let latin1 = temp.path().join("a.txt");
+        let late_latin1 = temp.path().join("b.txt");
+        let truncated = temp.path().join("c.txt");
+        fs::write(&binary, [0x89, b'P', b'N', b'G', 0x00, 0x01]).unwrap();
+        fs::write(&latin1, [b'c', b'a', b'f', 0xE9]).unwrap();
+        let mut late = vec![b'x'; STREAM_BUFFER_BYTES * 2 + 5];
+        late.extend([0xE9, b'!']);
+        fs::write(&late_latin1, late).unwrap();
+        fs::write(&truncated, [b'a', 0xF0, 0x9F]).unwrap();
+
+        for path in [&binary, &latin1, &late_latin1, &truncated] {
+            assert_eq!(
+                streamed(path, 0).unwrap_err(),
+                read_text(path).unwrap_err(),
+                "{path:?}"
+            );
+        }
+        assert_eq!(
+            streamed(&temp.path().join("missing"), 0).unwrap_err().0,
+            SkipReason::ReadError
+        );
+    }
+
+    #[test]
+    fn test_line_length_limit() {
+        let text = "short\nfive!\n€€€€€€\n";
+        assert!(check_line_length(text, 6).is_ok());
+        assert!(check_line_length(text, 0).is_ok());
+        assert_eq!(
+            check_line_length(text, 5).unwrap_err(),
+            (
+                SkipReason::LineTooLong,
+                "line 3 is longer than 5 characters (limits.max_line_length)".to_string()
+            )
+        );
+
+        // Lines continue across pieces
+        let mut lines = LineLengths::new(5);
+        assert!(lines.check("abc").is_ok());
+        assert!(lines.check("de\nabc").is_ok());
+        assert!(lines.check("def").is_err());
+    }
+}
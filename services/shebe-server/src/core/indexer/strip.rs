@@ -0,0 +1,313 @@
+//! Stripping of high-entropy blobs before chunking.
+//!
+//! Lock files, sourcemaps and embedded base64 images contain long runs
+//! of random-looking characters. Chunked as they are, they bloat the
+//! index with unique tokens and can dominate BM25 scoring. With
+//! `strip_high_entropy`, [`strip_high_entropy`] replaces each run of
+//! non-whitespace longer than the configured length, and each line
+//! longer than [`MAX_LINE_CHARS`], with a short placeholder recording
+//! how many bytes it stood for:
+//!
+//! ```text
+//! const logo = [stripped 51203 bytes]
+//! ```
+//!
+//! Chunks are cut from the stripped text and [`StrippedText::restore_offsets`]
+//! maps their offsets back to the original file, so previews and
+//! `read_file` show the real content. [`matches_stripped`] lets drift
+//! checks recognize stripped chunk text as current.
+
+use crate::core::types::Chunk;
+use std::ops::Range;
+
+/// Default length (in characters) above which a non-whitespace run is
+/// stripped
+pub const DEFAULT_STRIP_RUN_CHARS: usize = 200;
+
+/// Smallest accepted run length, so long identifiers are never stripped
+pub const MIN_STRIP_RUN_CHARS: usize = 32;
+
+/// Lines longer than this many characters are stripped entirely
+/// (minified code, where runs are broken up by the odd space)
+pub const MAX_LINE_CHARS: usize = 5_000;
+
+const PLACEHOLDER_PREFIX: &str = "[stripped ";
+const PLACEHOLDER_SUFFIX: &str = " bytes]";
+
+/// Placeholder standing for `bytes` stripped bytes
+pub fn placeholder(bytes: usize) -> String {
+    format!("{PLACEHOLDER_PREFIX}{bytes}{PLACEHOLDER_SUFFIX}")
+}
+
+/// One stripped blob: where its placeholder is and what it replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    /// Byte range of the placeholder in the stripped text
+    stripped: Range<usize>,
+    /// Byte range of the blob in the original text
+    original: Range<usize>,
+}
+
+/// Text with its blobs replaced by placeholders
+#[derive(Debug, Clone)]
+pub struct StrippedText {
+    text: String,
+    spans: Vec<Span>,
+}
+
+impl StrippedText {
+    /// The text to chunk and index
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Bytes of the original text replaced by placeholders
+    pub fn bytes_stripped(&self) -> u64 {
+        self.spans
+            .iter()
+            .map(|span| span.original.len() as u64)
+            .sum()
+    }
+
+    /// Map chunks cut from [`StrippedText::text`] back to the original
+    ///
+    /// Chunk ends falling inside a placeholder are moved out to include
+    /// all of it, so each chunk's text covers whole blobs and its
+    /// offsets are the original byte range. Chunks that then repeat
+    /// the previous chunk's range are dropped and the rest renumbered.
+    pub fn restore_offsets(&self, chunks: Vec<Chunk>) -> Vec<Chunk> {
+        let mut restored: Vec<Chunk> = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let range = self.widen(chunk.start_offset..chunk.end_offset);
+            let start_offset = self.original_offset(range.start);
+            let end_offset = self.original_offset(range.end);
+            if restored.last().is_some_and(|last| {
+                last.start_offset == start_offset && last.end_offset == end_offset
+            }) {
+                continue;
+            }
+            restored.push(Chunk {
+                text: self.text[range].to_string(),
+                start_offset,
+                end_offset,
+                chunk_index: restored.len(),
+                ..chunk
+            });
+        }
+        restored
+    }
+
+    /// Extend `range` so it does not end inside a placeholder
+    fn widen(&self, range: Range<usize>) -> Range<usize> {
+        let start = self
+            .placeholder_at(range.start)
+            .map_or(range.start, |s| s.start);
+        let end = self.placeholder_at(range.end).map_or(range.end, |s| s.end);
+        start..end
+    }
+
+    /// Placeholder strictly containing `offset` of the stripped text
+    fn placeholder_at(&self, offset: usize) -> Option<Range<usize>> {
+        let index = self
+            .spans
+            .partition_point(|span| span.stripped.end <= offset);
+        self.spans
+            .get(index)
+            .filter(|span| span.stripped.start < offset)
+            .map(|span| span.stripped.clone())
+    }
+
+    /// Original byte offset of `offset` in the stripped text (which
+    /// must not be inside a placeholder)
+    fn original_offset(&self, offset: usize) -> usize {
+        let before = self
+            .spans
+            .partition_point(|span| span.stripped.end <= offset);
+        match before.checked_sub(1).map(|i| &self.spans[i]) {
+            Some(span) => span.original.end + (offset - span.stripped.end),
+            None => offset,
+        }
+    }
+}
+
+/// Replace long non-whitespace runs and overlong lines of `text`
+///
+/// Runs longer than `max_run_chars` characters, and lines longer than
+/// [`MAX_LINE_CHARS`], become placeholders. Returns `None` when nothing
+/// was stripped.
+///
+/// # Example
+///
+/// ```
+/// use shebe::core::indexer::strip::strip_high_entropy;
+///
+/// let text = format!("let logo = \"{}\";\nfn main() {{}}\n", "QUJD".repeat(100));
+/// let stripped = strip_high_entropy(&text, 200).unwrap();
+///
+/// assert_eq!(stripped.text(), "let logo = [stripped 403 bytes]\nfn main() {}\n");
+/// assert_eq!(stripped.bytes_stripped(), 403);
+/// ```
+pub fn strip_high_entropy(text: &str, max_run_chars: usize) -> Option<StrippedText> {
+    let blobs = find_blobs(text, max_run_chars);
+    if blobs.is_empty() {
+        return None;
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut spans = Vec::with_capacity(blobs.len());
+    let mut copied = 0;
+    for blob in blobs {
+        stripped.push_str(&text[copied..blob.start]);
+        let start = stripped.len();
+        stripped.push_str(&placeholder(blob.len()));
+        spans.push(Span {
+            stripped: start..stripped.len(),
+            original: blob.clone(),
+        });
+        copied = blob.end;
+    }
+    stripped.push_str(&text[copied..]);
+
+    Some(StrippedText {
+        text: stripped,
+        spans,
+    })
+}
+
+/// Byte ranges of the blobs in `text`, in order
+fn find_blobs(text: &str, max_run_chars: usize) -> Vec<Range<usize>> {
+    let mut blobs = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content.chars().count() > MAX_LINE_CHARS {
+            blobs.push(line_start..line_start + content.len());
+        } else {
+            let mut run_start = None;
+            let mut run_chars = 0;
+            for (i, c) in content
+                .char_indices()
+                .chain(std::iter::once((content.len(), ' ')))
+            {
+                if c.is_whitespace() {
+                    if let Some(start) = run_start.take() {
+                        if run_chars > max_run_chars {
+                            blobs.push(line_start + start..line_start + i);
+                        }
+                    }
+                    run_chars = 0;
+                } else {
+                    run_start.get_or_insert(i);
+                    run_chars += 1;
+                }
+            }
+        }
+        line_start += line.len();
+    }
+    blobs
+}
+
+/// Whether chunk `text` is `original` with blobs replaced by placeholders
+///
+/// Each placeholder must stand for exactly as many bytes of
+/// non-newline content as it records; the text around placeholders
+/// must match exactly.
+pub fn matches_stripped(original: &str, text: &str) -> bool {
+    let mut original = original;
+    let mut rest = text;
+    while let Some(at) = rest.find(PLACEHOLDER_PREFIX) {
+        let (literal, tail) = rest.split_at(at);
+        let Some(remaining) = original.strip_prefix(literal) else {
+            return false;
+        };
+        let tail = &tail[PLACEHOLDER_PREFIX.len()..];
+        let Some((bytes, after)) = tail.split_once(PLACEHOLDER_SUFFIX) else {
+            return false;
+        };
+        let Ok(bytes) = bytes.parse::<usize>() else {
+            return false;
+        };
+        match remaining.get(..bytes) {
+            Some(blob) if !blob.contains('\n') => original = &remaining[bytes..],
+            _ => return false,
+        }
+        rest = after;
+    }
+    original == rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::indexer::Chunker;
+    use std::path::Path;
+
+    fn blob(len: usize) -> String {
+        "aGVsbG8gd29ybGQ".chars().cycle().take(len).collect()
+    }
+
+    #[test]
+    fn test_short_runs_are_kept() {
+        let text = format!("fn main() {{ let id = \"{}\"; }}\n", blob(100));
+        assert!(strip_high_entropy(&text, 200).is_none());
+    }
+
+    #[test]
+    fn test_long_run_replaced_in_place() {
+        let text = format!("before\nimg = {}\nafter\n", blob(300));
+        let stripped = strip_high_entropy(&text, 200).unwrap();
+
+        assert_eq!(
+            stripped.text(),
+            "before\nimg = [stripped 300 bytes]\nafter\n"
+        );
+        assert_eq!(stripped.bytes_stripped(), 300);
+    }
+
+    #[test]
+    fn test_long_line_replaced_without_newline() {
+        let line = "a = 1; ".repeat(1000);
+        let text = format!("{line}\r\nfn keep() {{}}\n");
+        let stripped = strip_high_entropy(&text, 200).unwrap();
+
+        assert_eq!(stripped.text(), "[stripped 7000 bytes]\r\nfn keep() {}\n");
+    }
+
+    #[test]
+    fn test_restored_offsets_cover_original_content() {
+        let text = format!(
+            "fn alpha() {{}}\nconst DATA: &str = \"{}\";\nfn beta() {{}}\n",
+            blob(4000)
+        );
+        let stripped = strip_high_entropy(&text, 200).unwrap();
+
+        let chunker = Chunker::new(20, 4);
+        let chunks = chunker.chunk_text(stripped.text(), Path::new("lib.rs"));
+        let restored = stripped.restore_offsets(chunks);
+
+        assert_eq!(restored.first().unwrap().start_offset, 0);
+        assert_eq!(restored.last().unwrap().end_offset, text.len());
+        for (i, chunk) in restored.iter().enumerate() {
+            assert_eq!(chunk.chunk_index, i);
+            let original = &text[chunk.start_offset..chunk.end_offset];
+            assert!(matches_stripped(original, &chunk.text), "{chunk:?}");
+        }
+        // One chunk holds the whole placeholder and the blob's offsets
+        let holder = restored
+            .iter()
+            .find(|c| c.text.contains("[stripped 4003 bytes]"))
+            .unwrap();
+        assert!(holder.end_offset - holder.start_offset > 4000);
+        assert!(restored.iter().any(|c| c.text.contains("fn beta")));
+    }
+
+    #[test]
+    fn test_matches_stripped_checks_lengths() {
+        let original = format!("x = {};", blob(250));
+        assert!(matches_stripped(&original, "x = [stripped 250 bytes];"));
+        assert!(!matches_stripped(&original, "x = [stripped 249 bytes];"));
+        assert!(!matches_stripped(&original, "y = [stripped 250 bytes];"));
+        assert!(matches_stripped("plain", "plain"));
+        assert!(!matches_stripped("a\nb", "[stripped 3 bytes]"));
+    }
+}
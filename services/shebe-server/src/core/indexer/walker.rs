@@ -1,14 +1,83 @@
 //! File system walker with pattern-based filtering.
 //!
 //! Traverses directory trees and filters files using glob patterns.
-//! Handles errors gracefully (permission denied, etc.) without
-//! crashing.
+//! Patterns from the repository's `.shebeignore` are added to the
+//! exclude patterns on every walk. Handles errors gracefully
+//! (permission denied, etc.) without crashing. The same rules can be
+//! re-applied to a single file to explain why it is not indexed.
 
 use glob::Pattern;
-use std::path::{Path, PathBuf};
-use walkdir::{DirEntry, WalkDir};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
 
+use crate::core::archive::{Archive, ArchiveEntry};
 use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::limits::IndexLimits;
+use crate::core::indexer::patterns::include_rewrites;
+use crate::core::indexer::repo_config::IgnoreFile;
+use crate::core::indexer::skipped::{FileExplanation, SkipLog};
+use crate::core::indexer::stream::read_text_streaming;
+use crate::core::paths;
+use crate::core::types::{PatternCount, PatternCounts, SkipReason};
+
+/// A file accepted by the walker
+#[derive(Debug, Clone)]
+pub struct WalkedFile {
+    /// Path of the file
+    pub path: PathBuf,
+
+    /// File size in bytes
+    pub size: u64,
+}
+
+/// Files accepted by a walk and counts of everything left out
+#[derive(Debug, Clone, Default)]
+pub struct WalkReport {
+    /// Accepted files, in walk order
+    pub files: Vec<WalkedFile>,
+
+    /// Files excluded by each exclude pattern, followed by each
+    /// `.shebeignore` pattern (same order as the patterns)
+    pub excluded_files: Vec<usize>,
+
+    /// Directories pruned by each exclude pattern, followed by each
+    /// `.shebeignore` pattern (same order as the patterns)
+    pub excluded_dirs: Vec<usize>,
+
+    /// `.shebeignore` patterns applied, labelled with their line
+    pub ignore_patterns: Vec<String>,
+
+    /// Files over the size limit
+    pub too_large: usize,
+
+    /// Files matching no include pattern
+    pub not_included: usize,
+
+    /// Files checked against the patterns
+    pub candidates: usize,
+
+    /// Files matching each include pattern (same order as the patterns)
+    pub included_files: Vec<usize>,
+
+    /// Files matching no include pattern that each rewrite of one
+    /// would match (same order as [`FileWalker::include_rewrites`])
+    pub rewrite_files: Vec<usize>,
+
+    /// Skipped files with reasons (hidden directories are not listed)
+    pub skipped: SkipLog,
+}
+
+/// Detail for files matching no include pattern
+const NOT_INCLUDED: &str = "matches no include pattern";
+
+/// Why a file path was accepted or rejected by the patterns
+enum PatternMatch {
+    Included,
+    NotIncluded,
+    Excluded(usize),
+}
 
 /// File system walker with pattern-based filtering
 pub struct FileWalker {
@@ -18,6 +87,10 @@ pub struct FileWalker {
     /// Patterns to exclude (e.g., "**/target/**", "**/.git/**")
     exclude_patterns: Vec<Pattern>,
 
+    /// Common corrections of the include patterns, with the index of
+    /// the pattern each rewrites
+    include_rewrites: Vec<(usize, Pattern)>,
+
     /// Maximum file size in bytes (skip larger files)
     max_file_size_bytes: u64,
 }
@@ -61,6 +134,7 @@ impl FileWalker {
             .collect::<Result<Vec<_>>>()?;
 
         Ok(Self {
+            include_rewrites: include_rewrites(&include),
             include_patterns: include,
             exclude_patterns: exclude,
             max_file_size_bytes: (max_file_size_mb as u64) * 1024 * 1024,
@@ -80,15 +154,69 @@ impl FileWalker {
     ///
     /// A vector of file paths that match the criteria
     pub fn collect_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
+        let report = self.walk(root)?;
+        Ok(report.files.into_iter().map(|f| f.path).collect())
+    }
 
-        for entry in WalkDir::new(root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| self.should_process_entry(e, root))
-        {
+    /// Walk a directory and report accepted files with skip counts
+    ///
+    /// Applies the same filtering as [`FileWalker::collect_files`],
+    /// but also records file sizes and why other entries were left
+    /// out. Nothing is read besides file metadata and `.shebeignore`.
+    pub fn walk(&self, root: &Path) -> Result<WalkReport> {
+        self.walk_limited(root, None)
+    }
+
+    /// Walk a directory, stopping once accepted files exceed `limits`
+    ///
+    /// Same as [`FileWalker::walk`], but the accepted files and their
+    /// sizes are counted as the walk goes, so a walk over far too many
+    /// files stops early instead of listing them all.
+    ///
+    /// # Errors
+    ///
+    /// `IndexTooLarge` naming the limit that was exceeded.
+    pub fn walk_limited(&self, root: &Path, limits: Option<&IndexLimits>) -> Result<WalkReport> {
+        let ignore = IgnoreFile::load(root);
+        for warning in &ignore.warnings {
+            tracing::warn!("Ignoring line in {}", warning);
+        }
+
+        let mut exclude = self.exclude_patterns.clone();
+        exclude.extend(ignore.rules.iter().map(|r| r.glob.clone()));
+
+        let mut report = WalkReport {
+            excluded_files: vec![0; exclude.len()],
+            excluded_dirs: vec![0; exclude.len()],
+            included_files: vec![0; self.include_patterns.len()],
+            rewrite_files: vec![0; self.include_rewrites.len()],
+            ignore_patterns: ignore.rules.iter().map(|r| r.label()).collect(),
+            ..WalkReport::default()
+        };
+
+        let exclude_labels = self.exclude_labels(&ignore);
+
+        let mut total_bytes = 0u64;
+        let mut entries = WalkDir::new(root).follow_links(false).into_iter();
+        while let Some(entry) = entries.next() {
             match entry {
                 Ok(entry) => {
+                    if entry.file_type().is_dir() {
+                        if let Some(skip) = directory_skip(entry.path(), root, &exclude) {
+                            if let Some(idx) = skip {
+                                report.excluded_dirs[idx] += 1;
+                                report.skipped.record_dir(
+                                    root,
+                                    entry.path(),
+                                    SkipReason::ExcludedByPattern,
+                                    format!("directory matches {}", exclude_labels[idx]),
+                                );
+                            }
+                            entries.skip_current_dir();
+                        }
+                        continue;
+                    }
+
                     if !entry.file_type().is_file() {
                         continue;
                     }
@@ -96,21 +224,56 @@ impl FileWalker {
                     let path = entry.path();
 
                     // Check file size
-                    if let Ok(metadata) = entry.metadata() {
-                        if metadata.len() > self.max_file_size_bytes {
-                            tracing::debug!(
-                                "Skipping large file: {:?} \
-                                 ({} bytes)",
-                                path,
-                                metadata.len()
-                            );
-                            continue;
-                        }
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    if size > self.max_file_size_bytes {
+                        tracing::debug!(
+                            "Skipping large file: {:?} \
+                             ({} bytes)",
+                            path,
+                            size
+                        );
+                        report.too_large += 1;
+                        report.skipped.record(
+                            root,
+                            path,
+                            SkipReason::TooLarge,
+                            self.size_detail(size),
+                        );
+                        continue;
                     }
 
                     // Check patterns
-                    if self.matches_patterns(path) {
-                        files.push(path.to_path_buf());
+                    report.candidates += 1;
+                    match self.match_patterns(path, &exclude, Some(&mut report.included_files)) {
+                        PatternMatch::Included => {
+                            report.files.push(WalkedFile {
+                                path: path.to_path_buf(),
+                                size,
+                            });
+                            total_bytes += size;
+                            if let Some(limits) = limits {
+                                limits.check(root, report.files.len(), total_bytes)?;
+                            }
+                        }
+                        PatternMatch::NotIncluded => {
+                            report.not_included += 1;
+                            self.count_rewrites(path, &mut report.rewrite_files);
+                            report.skipped.record(
+                                root,
+                                path,
+                                SkipReason::ExcludedByPattern,
+                                NOT_INCLUDED.to_string(),
+                            );
+                        }
+                        PatternMatch::Excluded(idx) => {
+                            report.excluded_files[idx] += 1;
+                            report.skipped.record(
+                                root,
+                                path,
+                                SkipReason::ExcludedByPattern,
+                                format!("matches {}", exclude_labels[idx]),
+                            );
+                        }
                     }
                 }
                 Err(e) => {
@@ -120,75 +283,444 @@ impl FileWalker {
             }
         }
 
-        Ok(files)
+        Ok(report)
     }
 
-    /// Determine if a directory entry should be processed
+    /// Check an explicit list of files instead of walking `root`
+    ///
+    /// The caller chose these files, so include, exclude and
+    /// `.shebeignore` patterns are not applied; the size limit and
+    /// `limits` are. Paths may be absolute or relative to `root`, and
+    /// a file listed twice is accepted once.
     ///
-    /// Filters out hidden directories and excluded patterns.
-    /// Never filters the root directory itself.
-    fn should_process_entry(&self, entry: &DirEntry, root: &Path) -> bool {
-        let path = entry.path();
-
-        // Never filter the root directory
-        if path == root {
-            return true;
+    /// # Errors
+    ///
+    /// `InvalidPath` if a file does not exist, is not a regular file
+    /// or is outside `root`; `IndexTooLarge` naming the limit that
+    /// was exceeded.
+    pub fn walk_files(
+        &self,
+        root: &Path,
+        files: &[PathBuf],
+        limits: Option<&IndexLimits>,
+    ) -> Result<WalkReport> {
+        let mut report = WalkReport::default();
+        let mut seen = HashSet::new();
+        let mut total_bytes = 0u64;
+
+        for file in files {
+            let path = root.join(file);
+            relative_to_root(root, &path)?;
+            let metadata = fs::metadata(&path).map_err(|_| {
+                ShebeError::InvalidPath(format!("File not found: {}", path.display()))
+            })?;
+            if !metadata.is_file() {
+                return Err(ShebeError::InvalidPath(format!(
+                    "{} is not a file",
+                    path.display()
+                )));
+            }
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+
+            let size = metadata.len();
+            if size > self.max_file_size_bytes {
+                report.too_large += 1;
+                report
+                    .skipped
+                    .record(root, &path, SkipReason::TooLarge, self.size_detail(size));
+                continue;
+            }
+
+            report.files.push(WalkedFile { path, size });
+            total_bytes += size;
+            if let Some(limits) = limits {
+                limits.check(root, report.files.len(), total_bytes)?;
+            }
         }
 
-        // Skip hidden directories (starting with '.')
-        // but only if they're not the root
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if name.starts_with('.') && entry.file_type().is_dir() {
-                return false;
+        Ok(report)
+    }
+
+    /// Filter the entries of an archive as a walk filters files
+    ///
+    /// Each entry is checked as if the archive were extracted in place
+    /// (see [`Archive::extracted_path`]): entries in hidden
+    /// directories are left out, then the size limit, include and
+    /// exclude patterns and `limits` apply. Accepted files carry the
+    /// entry's stored path (`{archive}!{entry}`). There is no
+    /// `.shebeignore` for archives.
+    ///
+    /// # Errors
+    ///
+    /// `IndexTooLarge` naming the limit that was exceeded.
+    pub fn walk_archive(
+        &self,
+        archive: &Archive,
+        entries: &[ArchiveEntry],
+        limits: Option<&IndexLimits>,
+    ) -> Result<WalkReport> {
+        let root = archive.path();
+        let mut report = WalkReport {
+            excluded_files: vec![0; self.exclude_patterns.len()],
+            excluded_dirs: vec![0; self.exclude_patterns.len()],
+            included_files: vec![0; self.include_patterns.len()],
+            rewrite_files: vec![0; self.include_rewrites.len()],
+            ..WalkReport::default()
+        };
+        let exclude_labels = self.exclude_labels(&IgnoreFile::default());
+        let mut total_bytes = 0u64;
+
+        for entry in entries {
+            let hidden = entry
+                .name
+                .rsplit_once('/')
+                .is_some_and(|(dirs, _)| dirs.split('/').any(|d| d.starts_with('.')));
+            if hidden {
+                continue;
+            }
+            let path = archive.extracted_path(&entry.name);
+            if entry.size > self.max_file_size_bytes {
+                report.too_large += 1;
+                report.skipped.record(
+                    root,
+                    &path,
+                    SkipReason::TooLarge,
+                    self.size_detail(entry.size),
+                );
+                continue;
+            }
+
+            report.candidates += 1;
+            match self.match_patterns(
+                &path,
+                &self.exclude_patterns,
+                Some(&mut report.included_files),
+            ) {
+                PatternMatch::Included => {
+                    report.files.push(WalkedFile {
+                        path: archive.entry_path(&entry.name),
+                        size: entry.size,
+                    });
+                    total_bytes += entry.size;
+                    if let Some(limits) = limits {
+                        limits.check(root, report.files.len(), total_bytes)?;
+                    }
+                }
+                PatternMatch::NotIncluded => {
+                    report.not_included += 1;
+                    self.count_rewrites(&path, &mut report.rewrite_files);
+                    report.skipped.record(
+                        root,
+                        &path,
+                        SkipReason::ExcludedByPattern,
+                        NOT_INCLUDED.to_string(),
+                    );
+                }
+                PatternMatch::Excluded(idx) => {
+                    report.excluded_files[idx] += 1;
+                    report.skipped.record(
+                        root,
+                        &path,
+                        SkipReason::ExcludedByPattern,
+                        format!("matches {}", exclude_labels[idx]),
+                    );
+                }
             }
         }
 
-        // Check exclude patterns for directories
-        // (skip entire directory trees early)
-        if entry.file_type().is_dir() {
-            for pattern in &self.exclude_patterns {
-                if pattern.matches_path(path) {
-                    tracing::debug!("Skipping excluded directory: {:?}", path);
-                    return false;
+        Ok(report)
+    }
+
+    /// Re-check one file against the walk rules and its content
+    ///
+    /// Applies the hidden-directory, exclude, size and include checks
+    /// of [`FileWalker::walk`] (with the repository's `.shebeignore`),
+    /// then reads the file to check for binary or non-UTF-8 content.
+    /// `path` may be absolute or relative to `root`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if the file does not exist, is outside `root`, is
+    /// a symbolic link or is not a regular file.
+    pub fn explain(&self, root: &Path, path: &Path) -> Result<FileExplanation> {
+        let path = root.join(path);
+        let relative = relative_to_root(root, &path)?;
+        let metadata = fs::symlink_metadata(&path)
+            .map_err(|_| ShebeError::InvalidPath(format!("File not found: {}", path.display())))?;
+        if metadata.file_type().is_symlink() {
+            return Err(ShebeError::InvalidPath(format!(
+                "{} is a symbolic link; links are not followed while indexing",
+                path.display()
+            )));
+        }
+        if !metadata.is_file() {
+            return Err(ShebeError::InvalidPath(format!(
+                "{} is not a file",
+                path.display()
+            )));
+        }
+
+        let ignore = IgnoreFile::load(root);
+        let mut exclude = self.exclude_patterns.clone();
+        exclude.extend(ignore.rules.iter().map(|r| r.glob.clone()));
+        let exclude_labels = self.exclude_labels(&ignore);
+
+        let skipped = |reason, detail: String| FileExplanation {
+            path: path.clone(),
+            reason: Some(reason),
+            detail,
+        };
+
+        // Directories are pruned top-down, so the outermost match wins
+        let mut dir = root.to_path_buf();
+        for component in relative.parent().into_iter().flat_map(Path::components) {
+            dir.push(component);
+            match directory_skip(&dir, root, &exclude) {
+                Some(None) => {
+                    return Ok(skipped(
+                        SkipReason::ExcludedByPattern,
+                        format!(
+                            "inside hidden directory `{}`; hidden directories are never indexed",
+                            dir.strip_prefix(root).unwrap_or(&dir).display()
+                        ),
+                    ))
+                }
+                Some(Some(idx)) => {
+                    return Ok(skipped(
+                        SkipReason::ExcludedByPattern,
+                        format!(
+                            "directory `{}` matches {}",
+                            dir.strip_prefix(root).unwrap_or(&dir).display(),
+                            exclude_labels[idx]
+                        ),
+                    ))
                 }
+                None => {}
             }
         }
 
-        true
+        if metadata.len() > self.max_file_size_bytes {
+            return Ok(skipped(
+                SkipReason::TooLarge,
+                self.size_detail(metadata.len()),
+            ));
+        }
+
+        match self.match_patterns(&path, &exclude, None) {
+            PatternMatch::NotIncluded => {
+                return Ok(skipped(
+                    SkipReason::ExcludedByPattern,
+                    NOT_INCLUDED.to_string(),
+                ))
+            }
+            PatternMatch::Excluded(idx) => {
+                return Ok(skipped(
+                    SkipReason::ExcludedByPattern,
+                    format!("matches {}", exclude_labels[idx]),
+                ))
+            }
+            PatternMatch::Included => {}
+        }
+
+        if let Err((reason, detail)) = read_text_streaming(&path, 0, |_| {}) {
+            return Ok(skipped(reason, detail));
+        }
+
+        Ok(FileExplanation {
+            path,
+            reason: None,
+            detail: "passes the pattern, size and content checks".to_string(),
+        })
+    }
+
+    /// Labels for the exclude patterns followed by the `.shebeignore` rules
+    fn exclude_labels(&self, ignore: &IgnoreFile) -> Vec<String> {
+        self.exclude_patterns
+            .iter()
+            .map(|p| format!("exclude pattern `{}`", p.as_str()))
+            .chain(ignore.rules.iter().map(|r| format!("`{}`", r.label())))
+            .collect()
+    }
+
+    /// Detail for a file over the size limit
+    fn size_detail(&self, size: u64) -> String {
+        format!(
+            "{size} bytes exceeds the {} MB limit",
+            self.max_file_size_bytes / (1024 * 1024)
+        )
+    }
+
+    /// Files larger than this many bytes are skipped
+    pub fn max_file_size_bytes(&self) -> u64 {
+        self.max_file_size_bytes
+    }
+
+    /// Rewrites of the include patterns, each with the index of the
+    /// pattern it rewrites
+    pub fn include_rewrites(&self) -> &[(usize, Pattern)] {
+        &self.include_rewrites
     }
 
-    /// Check if a file path matches the include/exclude patterns
-    fn matches_patterns(&self, path: &Path) -> bool {
+    /// Files matched by each include and exclude pattern in a walk
+    pub fn pattern_counts(&self, report: &WalkReport) -> PatternCounts {
+        let include = self
+            .include_patterns
+            .iter()
+            .zip(&report.included_files)
+            .map(|(pattern, &files)| PatternCount {
+                pattern: pattern.as_str().to_string(),
+                files,
+                directories: 0,
+            })
+            .collect();
+        let exclude = self
+            .exclude_patterns()
+            .into_iter()
+            .chain(report.ignore_patterns.iter().cloned())
+            .zip(report.excluded_files.iter().zip(&report.excluded_dirs))
+            .map(|(pattern, (&files, &directories))| PatternCount {
+                pattern,
+                files,
+                directories,
+            })
+            .collect();
+        PatternCounts {
+            candidates: report.candidates,
+            include,
+            exclude,
+        }
+    }
+
+    /// Count a file matching no include pattern against the rewrites
+    fn count_rewrites(&self, path: &Path, counts: &mut [usize]) {
+        let Some(path_str) = path.to_str().map(paths::normalize) else {
+            return;
+        };
+        for ((_, rewrite), count) in self.include_rewrites.iter().zip(counts) {
+            if include_matches(rewrite, &path_str, path) {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Exclude patterns as given, in order
+    pub fn exclude_patterns(&self) -> Vec<String> {
+        self.exclude_patterns
+            .iter()
+            .map(|p| p.as_str().to_string())
+            .collect()
+    }
+
+    /// Check a file path against the include patterns and `exclude`
+    ///
+    /// Patterns are matched against the normalized path, so `/` in a
+    /// pattern matches either separator on Windows. With
+    /// `include_counts`, every include pattern is tried and the ones
+    /// that match are counted.
+    fn match_patterns(
+        &self,
+        path: &Path,
+        exclude: &[Pattern],
+        include_counts: Option<&mut [usize]>,
+    ) -> PatternMatch {
         // Convert path to string for matching
         let path_str = match path.to_str() {
-            Some(s) => s,
-            None => return false,
+            Some(s) => paths::normalize(s),
+            None => return PatternMatch::NotIncluded,
         };
 
         // If no include patterns, include all
-        let matches_include = self.include_patterns.is_empty()
-            || self.include_patterns.iter().any(|p| {
-                // Match against both full path and filename
-                p.matches(path_str)
-                    || path
-                        .file_name()
-                        .and_then(|f| f.to_str())
-                        .map(|f| p.matches(f))
-                        .unwrap_or(false)
-            });
+        let matches_include = match include_counts {
+            Some(counts) => {
+                let mut matched = self.include_patterns.is_empty();
+                for (pattern, count) in self.include_patterns.iter().zip(counts) {
+                    if include_matches(pattern, &path_str, path) {
+                        *count += 1;
+                        matched = true;
+                    }
+                }
+                matched
+            }
+            None => {
+                self.include_patterns.is_empty()
+                    || self
+                        .include_patterns
+                        .iter()
+                        .any(|p| include_matches(p, &path_str, path))
+            }
+        };
 
         if !matches_include {
-            return false;
+            return PatternMatch::NotIncluded;
         }
 
         // Must not match any exclude pattern
-        !self
-            .exclude_patterns
-            .iter()
-            .any(|p| p.matches(path_str) || p.matches_path(path))
+        match exclude.iter().position(|p| p.matches(&path_str)) {
+            Some(idx) => PatternMatch::Excluded(idx),
+            None => PatternMatch::Included,
+        }
     }
 }
 
+/// Whether an include pattern matches a file's normalized path or
+/// its file name
+fn include_matches(pattern: &Pattern, path_str: &str, path: &Path) -> bool {
+    pattern.matches(path_str)
+        || path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .is_some_and(|f| pattern.matches(f))
+}
+
+/// Determine whether a directory should be skipped
+///
+/// Returns `Some(None)` for hidden directories, `Some(Some(idx))`
+/// when exclude pattern `idx` matches and `None` to descend.
+/// Never skips the root directory itself.
+fn directory_skip(path: &Path, root: &Path, exclude: &[Pattern]) -> Option<Option<usize>> {
+    // Never filter the root directory
+    if path == root {
+        return None;
+    }
+
+    // Skip hidden directories (starting with '.')
+    // but only if they're not the root
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        if name.starts_with('.') {
+            return Some(None);
+        }
+    }
+
+    // Check exclude patterns (skip entire directory trees early)
+    let path_str = paths::normalize_path(path);
+    let idx = exclude
+        .iter()
+        .position(|pattern| pattern.matches(&path_str))?;
+    tracing::debug!("Skipping excluded directory: {:?}", path);
+    Some(Some(idx))
+}
+
+/// `path` relative to `root`
+///
+/// # Errors
+///
+/// `InvalidPath` if `path` is not under `root` or climbs out of it
+/// with `..`.
+fn relative_to_root<'a>(root: &Path, path: &'a Path) -> Result<&'a Path> {
+    path.strip_prefix(root)
+        .ok()
+        .filter(|rel| !rel.components().any(|c| c == Component::ParentDir))
+        .ok_or_else(|| {
+            ShebeError::InvalidPath(format!(
+                "{} is outside the repository {}",
+                path.display(),
+                root.display()
+            ))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +749,37 @@ mod tests {
         assert_eq!(files.len(), 3);
     }
 
+    #[test]
+    fn test_walk_limited_counts_accepted_files_only() {
+        let temp_dir = create_test_files(&["a.rs", "b.rs", "c.md", "d.md"]);
+        let walker = FileWalker::new(vec!["*.rs".to_string()], vec![], 10).unwrap();
+
+        // "test content" is 12 bytes per file
+        let fits = IndexLimits {
+            max_files: 2,
+            max_bytes: 24,
+            ..IndexLimits::default()
+        };
+        let report = walker.walk_limited(temp_dir.path(), Some(&fits)).unwrap();
+        assert_eq!(report.files.len(), 2);
+
+        for limits in [
+            IndexLimits {
+                max_files: 1,
+                max_bytes: 24,
+                ..IndexLimits::default()
+            },
+            IndexLimits {
+                max_files: 2,
+                max_bytes: 23,
+                ..IndexLimits::default()
+            },
+        ] {
+            let result = walker.walk_limited(temp_dir.path(), Some(&limits));
+            assert!(matches!(result, Err(ShebeError::IndexTooLarge(_))));
+        }
+    }
+
     #[test]
     fn test_walker_include_patterns() {
         let temp_dir = create_test_files(&["file1.rs", "file2.md", "file3.txt"]);
@@ -294,4 +857,237 @@ mod tests {
 
         assert_eq!(files.len(), 3);
     }
+
+    #[test]
+    fn test_walk_reports_skip_counts() {
+        let temp_dir = create_test_files(&[
+            "src/main.rs",
+            "src/lib.rs",
+            "README.md",
+            "target/debug/a.rs",
+            "target/release/b.rs",
+            "gen.lock",
+        ]);
+
+        let walker = FileWalker::new(
+            vec!["*.rs".to_string(), "*.lock".to_string()],
+            vec!["**/target/**".to_string(), "*.lock".to_string()],
+            10,
+        )
+        .unwrap();
+        let report = walker.walk(temp_dir.path()).unwrap();
+
+        assert_eq!(report.files.len(), 2);
+        assert!(report.files.iter().all(|f| f.size == 12));
+        // "**/target/**" matches target/debug and target/release, not target itself
+        assert_eq!(report.excluded_dirs, vec![2, 0]);
+        assert_eq!(report.excluded_files, vec![0, 1]);
+        assert_eq!(report.not_included, 1);
+        assert_eq!(report.too_large, 0);
+
+        // Per pattern: README.md is the only file matching no include pattern
+        let counts = walker.pattern_counts(&report);
+        assert_eq!(counts.candidates, 4);
+        assert_eq!(counts.include_summary(), "*.rs (2 files), *.lock (1 file)");
+        assert_eq!(
+            counts.exclude_summary(),
+            "**/target/** (0 files, 2 directories), *.lock (1 file)"
+        );
+    }
+
+    #[test]
+    fn test_walk_applies_shebeignore() {
+        let temp_dir = create_test_files(&[
+            "src/main.rs",
+            "src/gen/out.rs",
+            "docs/guide.md",
+            "app.log",
+            "logs/app.log",
+        ]);
+        fs::write(
+            temp_dir.path().join(".shebeignore"),
+            "# local excludes
+*.log
+/src/gen/
+!keep.log
+",
+        )
+        .unwrap();
+
+        let walker = FileWalker::new(vec![], vec!["**/docs/**".to_string()], 10).unwrap();
+        let report = walker.walk(temp_dir.path()).unwrap();
+
+        let mut names: Vec<String> = report
+            .files
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![".shebeignore", "main.rs"]);
+        assert_eq!(
+            report.ignore_patterns,
+            vec!["*.log (.shebeignore:2)", "/src/gen/ (.shebeignore:3)"]
+        );
+        // Session patterns first, then .shebeignore patterns
+        assert_eq!(report.excluded_files, vec![1, 2, 0]);
+        assert_eq!(report.excluded_dirs, vec![0, 0, 1]);
+    }
+
+    fn skipped_detail(report: &WalkReport, path: &str) -> (SkipReason, String) {
+        let file = report
+            .skipped
+            .files
+            .iter()
+            .find(|f| f.path == path)
+            .unwrap_or_else(|| panic!("{path} not in {:?}", report.skipped.files));
+        (file.reason, file.detail.clone())
+    }
+
+    #[test]
+    fn test_walk_records_skipped_files() {
+        let temp_dir = create_test_files(&[
+            "src/main.rs",
+            "README.md",
+            "target/debug/a.rs",
+            "gen.lock",
+            ".git/config",
+        ]);
+        fs::write(temp_dir.path().join("big.rs"), vec![b'x'; 1024 * 1024 + 1]).unwrap();
+
+        let walker = FileWalker::new(
+            vec!["*.rs".to_string(), "*.lock".to_string()],
+            vec!["**/target/**".to_string(), "*.lock".to_string()],
+            1,
+        )
+        .unwrap();
+        let report = walker.walk(temp_dir.path()).unwrap();
+
+        assert_eq!(
+            skipped_detail(&report, "target/debug/"),
+            (
+                SkipReason::ExcludedByPattern,
+                "directory matches exclude pattern `**/target/**`".to_string()
+            )
+        );
+        assert_eq!(
+            skipped_detail(&report, "gen.lock"),
+            (
+                SkipReason::ExcludedByPattern,
+                "matches exclude pattern `*.lock`".to_string()
+            )
+        );
+        assert_eq!(
+            skipped_detail(&report, "README.md").1,
+            "matches no include pattern"
+        );
+        assert_eq!(
+            skipped_detail(&report, "big.rs"),
+            (
+                SkipReason::TooLarge,
+                "1048577 bytes exceeds the 1 MB limit".to_string()
+            )
+        );
+        // Hidden directories are never indexed and not listed
+        assert_eq!(report.skipped.counts.excluded_by_pattern, 3);
+        assert_eq!(report.skipped.counts.too_large, 1);
+        assert_eq!(report.skipped.files.len(), 4);
+    }
+
+    #[test]
+    fn test_explain_reports_deciding_rule() {
+        let temp_dir = create_test_files(&[
+            "src/main.rs",
+            "src/gen/out.rs",
+            "target/debug/a.rs",
+            ".github/ci.rs",
+            "notes.md",
+        ]);
+        let root = temp_dir.path();
+        fs::write(root.join(".shebeignore"), "/src/gen/\n").unwrap();
+        fs::write(root.join("blob.rs"), [b'a', 0, b'b']).unwrap();
+        fs::write(root.join("big.rs"), vec![b'x'; 1024 * 1024 + 1]).unwrap();
+
+        let walker = FileWalker::new(
+            vec!["*.rs".to_string()],
+            vec!["**/target/**".to_string()],
+            1,
+        )
+        .unwrap();
+        let explain = |path: &str| {
+            let explanation = walker.explain(root, Path::new(path)).unwrap();
+            (explanation.reason, explanation.detail)
+        };
+
+        assert_eq!(explain("src/main.rs").0, None);
+        assert_eq!(
+            explain(&root.join("src/main.rs").to_string_lossy()).0,
+            None,
+            "absolute paths are accepted"
+        );
+        assert_eq!(
+            explain("src/gen/out.rs"),
+            (
+                Some(SkipReason::ExcludedByPattern),
+                "directory `src/gen` matches `/src/gen/ (.shebeignore:1)`".to_string()
+            )
+        );
+        assert_eq!(
+            explain("target/debug/a.rs").1,
+            "directory `target/debug` matches exclude pattern `**/target/**`"
+        );
+        assert!(explain(".github/ci.rs")
+            .1
+            .contains("hidden directory `.github`"));
+        assert_eq!(explain("notes.md").1, "matches no include pattern");
+        assert_eq!(explain("big.rs").0, Some(SkipReason::TooLarge));
+        assert_eq!(explain("blob.rs").0, Some(SkipReason::Binary));
+    }
+
+    #[test]
+    fn test_explain_rejects_missing_and_outside_paths() {
+        let temp_dir = create_test_files(&["src/main.rs"]);
+        let walker = FileWalker::new(vec![], vec![], 10).unwrap();
+
+        for path in ["src/missing.rs", "../outside.rs", "/etc/hostname", "src"] {
+            let result = walker.explain(temp_dir.path(), Path::new(path));
+            assert!(
+                matches!(result, Err(ShebeError::InvalidPath(_))),
+                "{path}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_walk_files_ignores_patterns_and_checks_paths() {
+        let temp_dir = create_test_files(&["src/main.rs", "target/gen.rs", "notes.md"]);
+        let walker = FileWalker::new(
+            vec!["*.rs".to_string()],
+            vec!["**/target/**".to_string()],
+            10,
+        )
+        .unwrap();
+        let files = [
+            PathBuf::from("target/gen.rs"),
+            PathBuf::from("notes.md"),
+            temp_dir.path().join("notes.md"),
+        ];
+
+        let report = walker.walk_files(temp_dir.path(), &files, None).unwrap();
+        let accepted: Vec<_> = report.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            accepted,
+            [
+                temp_dir.path().join("target/gen.rs"),
+                temp_dir.path().join("notes.md")
+            ]
+        );
+
+        for path in ["src/missing.rs", "../outside.rs", "src"] {
+            let result = walker.walk_files(temp_dir.path(), &[PathBuf::from(path)], None);
+            assert!(
+                matches!(result, Err(ShebeError::InvalidPath(_))),
+                "{path}: {result:?}"
+            );
+        }
+    }
 }
@@ -0,0 +1,669 @@
+//! Background indexing jobs.
+//!
+//! Indexing a large repository takes minutes, longer than clients
+//! are willing to wait on a single request. [`JobManager`] runs the
+//! work on its own thread and tracks it by [`JobId`] so callers can
+//! poll progress and cancel it.
+//!
+//! - At most `max_running` jobs run at once; the rest are queued
+//! - Only one job per session may be queued or running
+//! - Finished jobs stay listed for the retention window
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::{IndexOverrides, IndexProgress};
+use crate::core::remote::{strip_credentials, RemoteSource};
+use crate::core::storage::{SessionConfig, SessionMetadata};
+use crate::core::types::IndexStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often a queued job re-checks for a free slot or cancellation
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Identifier of a background job (e.g. `job-3`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct JobId(String);
+
+impl JobId {
+    /// Wrap a job ID received from a client
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// The ID as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Lifecycle state of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for a free indexing slot
+    Queued,
+    /// Indexing
+    Running,
+    /// Finished successfully
+    Completed,
+    /// Finished with an error
+    Failed,
+    /// Stopped on request
+    Cancelled,
+}
+
+impl JobState {
+    /// Whether the job has stopped for good
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+
+    /// Lowercase name, as serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Completed => "completed",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Where an indexing job reads the repository from
+#[derive(Debug, Clone)]
+pub enum IndexSource {
+    /// Local directory (already validated and canonicalized)
+    Local(PathBuf),
+    /// Git URL to shallow-clone into the clone cache
+    Remote {
+        url: String,
+        git_ref: Option<String>,
+    },
+}
+
+impl IndexSource {
+    /// Path or URL, for status reports
+    pub fn label(&self) -> String {
+        match self {
+            IndexSource::Local(path) => path.display().to_string(),
+            IndexSource::Remote { url, .. } => strip_credentials(url),
+        }
+    }
+}
+
+/// Everything a background indexing job needs
+///
+/// Settings are resolved when the job runs, so `.shebe.toml` in a
+/// freshly cloned repository is honoured as in a synchronous index.
+#[derive(Debug, Clone)]
+pub struct IndexJobRequest {
+    pub session: String,
+    pub source: IndexSource,
+    /// Explicit request settings (highest priority)
+    pub overrides: IndexOverrides,
+    /// Settings used where neither the request nor `.shebe.toml` set one
+    pub defaults: SessionConfig,
+    /// Replace an existing session
+    pub force: bool,
+    /// Index the filesystem root, home directory or storage root
+    pub allow_dangerous_path: bool,
+}
+
+impl IndexJobRequest {
+    /// Re-index an existing session from its stored source and settings
+    ///
+    /// Fails with `InvalidPath` when a local repository no longer
+    /// exists: indexing it would leave the session empty.
+    pub fn reindex(metadata: &SessionMetadata) -> Result<Self> {
+        let source = match &metadata.remote {
+            Some(remote) => IndexSource::Remote {
+                url: remote.url.clone(),
+                git_ref: remote.git_ref.clone(),
+            },
+            None if !metadata.repository_path.exists() => {
+                return Err(ShebeError::InvalidPath(format!(
+                    "Repository path no longer exists: {}",
+                    metadata.repository_path.display()
+                )))
+            }
+            None => IndexSource::Local(metadata.repository_path.clone()),
+        };
+
+        Ok(Self {
+            session: metadata.id.clone(),
+            source,
+            overrides: IndexOverrides::from_session(&metadata.config),
+            defaults: metadata.config.clone(),
+            force: true,
+            // The path was accepted when the session was first indexed
+            allow_dangerous_path: true,
+        })
+    }
+}
+
+/// What a successful job produced
+#[derive(Debug, Clone)]
+pub struct JobOutput {
+    /// Indexing statistics
+    pub stats: IndexStats,
+    /// Remote repository the session was cloned from, if any
+    pub remote: Option<RemoteSource>,
+}
+
+/// Snapshot of a job, as reported to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub session: String,
+    /// Path or URL being indexed
+    pub source: String,
+    pub state: JobState,
+    /// Files the run will process (0 until the directory walk finishes)
+    pub files_total: usize,
+    pub files_processed: usize,
+    /// 0-100; 100 once completed
+    pub progress_percent: u8,
+    /// Cancellation was requested but the job has not stopped yet
+    pub cancel_requested: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<IndexStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Book-keeping for one job
+struct Job {
+    id: JobId,
+    session: String,
+    source: String,
+    state: JobState,
+    progress: Arc<IndexProgress>,
+    output: Option<JobOutput>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    started_at: Option<DateTime<Utc>>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+impl Job {
+    fn status(&self) -> JobStatus {
+        let progress_percent = match self.state {
+            JobState::Completed => 100,
+            _ => self.progress.percent(),
+        };
+        JobStatus {
+            id: self.id.clone(),
+            session: self.session.clone(),
+            source: self.source.clone(),
+            state: self.state,
+            files_total: self.progress.files_total(),
+            files_processed: self.progress.files_processed(),
+            progress_percent,
+            cancel_requested: self.progress.is_cancelled() && !self.state.is_finished(),
+            stats: self.output.as_ref().map(|o| o.stats.clone()),
+            remote: self.output.as_ref().and_then(|o| o.remote.clone()),
+            error: self.error.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+        }
+    }
+
+    fn finish(&mut self, state: JobState) {
+        self.state = state;
+        self.finished_at = Some(Utc::now());
+    }
+}
+
+/// Runs indexing work in the background and tracks it by [`JobId`]
+pub struct JobManager {
+    /// Jobs in creation order
+    jobs: Mutex<Vec<Job>>,
+    /// Number of running jobs, signalled when one finishes
+    running: Mutex<usize>,
+    slot_freed: Condvar,
+    max_running: usize,
+    retention: Duration,
+    next_id: AtomicU64,
+}
+
+impl JobManager {
+    /// Create a manager running at most `max_running` jobs at once and
+    /// listing finished jobs for `retention`
+    pub fn new(max_running: usize, retention: Duration) -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            running: Mutex::new(0),
+            slot_freed: Condvar::new(),
+            max_running: max_running.max(1),
+            retention,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Queue `work` for `session` and return its job ID
+    ///
+    /// `source` describes what is indexed (shown in status reports).
+    /// `work` runs on its own thread once a slot is free; it should
+    /// report through and honour the [`IndexProgress`] it is given.
+    ///
+    /// # Errors
+    ///
+    /// `IndexLocked` if another job for `session` is queued or running.
+    pub fn start<F>(self: &Arc<Self>, session: &str, source: &str, work: F) -> Result<JobId>
+    where
+        F: FnOnce(&IndexProgress) -> Result<JobOutput> + Send + 'static,
+    {
+        let id = JobId(format!(
+            "job-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        ));
+        let progress = Arc::new(IndexProgress::new());
+
+        {
+            let mut jobs = self.lock_jobs();
+            self.prune(&mut jobs);
+            if let Some(active) = jobs
+                .iter()
+                .find(|job| job.session == session && !job.state.is_finished())
+            {
+                return Err(ShebeError::IndexLocked(format!(
+                    "session '{session}' is already being indexed by {}",
+                    active.id
+                )));
+            }
+            jobs.push(Job {
+                id: id.clone(),
+                session: session.to_string(),
+                source: source.to_string(),
+                state: JobState::Queued,
+                progress: Arc::clone(&progress),
+                output: None,
+                error: None,
+                created_at: Utc::now(),
+                started_at: None,
+                finished_at: None,
+            });
+        }
+
+        let manager = Arc::clone(self);
+        let job_id = id.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("shebe-{id}"))
+            .spawn(move || manager.run(&job_id, &progress, work));
+        if let Err(e) = spawned {
+            self.update(&id, |job| {
+                job.error = Some(format!("Failed to start job thread: {e}"));
+                job.finish(JobState::Failed);
+            });
+            return Err(ShebeError::IoError(e));
+        }
+
+        tracing::info!("Started {} for session '{}' ({})", id, session, source);
+        Ok(id)
+    }
+
+    /// Current status of a job
+    ///
+    /// # Errors
+    ///
+    /// `JobNotFound` if the ID is unknown or the job has expired.
+    pub fn status(&self, id: &JobId) -> Result<JobStatus> {
+        let mut jobs = self.lock_jobs();
+        self.prune(&mut jobs);
+        jobs.iter()
+            .find(|job| &job.id == id)
+            .map(Job::status)
+            .ok_or_else(|| ShebeError::JobNotFound(id.to_string()))
+    }
+
+    /// All active jobs and finished jobs within the retention window,
+    /// oldest first
+    pub fn list(&self) -> Vec<JobStatus> {
+        let mut jobs = self.lock_jobs();
+        self.prune(&mut jobs);
+        jobs.iter().map(Job::status).collect()
+    }
+
+    /// The queued or running job for `session`, if any
+    pub fn active_job(&self, session: &str) -> Option<JobId> {
+        self.lock_jobs()
+            .iter()
+            .find(|job| job.session == session && !job.state.is_finished())
+            .map(|job| job.id.clone())
+    }
+
+    /// Request cancellation of a job
+    ///
+    /// A queued job is cancelled immediately. A running job stops at
+    /// the next file boundary and removes its partial session; its
+    /// status shows `cancel_requested` until then. Finished jobs are
+    /// returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// `JobNotFound` if the ID is unknown or the job has expired.
+    pub fn cancel(&self, id: &JobId) -> Result<JobStatus> {
+        let mut jobs = self.lock_jobs();
+        self.prune(&mut jobs);
+        let job = jobs
+            .iter_mut()
+            .find(|job| &job.id == id)
+            .ok_or_else(|| ShebeError::JobNotFound(id.to_string()))?;
+
+        match job.state {
+            JobState::Queued => {
+                job.progress.cancel();
+                job.finish(JobState::Cancelled);
+                tracing::info!("Cancelled queued {}", id);
+            }
+            JobState::Running => {
+                job.progress.cancel();
+                tracing::info!("Cancellation requested for {}", id);
+            }
+            _ => {}
+        }
+        Ok(job.status())
+    }
+
+    /// Job thread: wait for a slot, run the work, record the outcome
+    fn run<F>(&self, id: &JobId, progress: &IndexProgress, work: F)
+    where
+        F: FnOnce(&IndexProgress) -> Result<JobOutput>,
+    {
+        if !self.acquire_slot(progress) {
+            // Cancelled while queued; `cancel` already recorded it
+            return;
+        }
+        // `cancel` may have finished the job between taking the slot and here
+        let mut started = false;
+        self.update(id, |job| {
+            if job.state == JobState::Queued {
+                job.state = JobState::Running;
+                job.started_at = Some(Utc::now());
+                started = true;
+            }
+        });
+        if !started {
+            self.release_slot();
+            return;
+        }
+
+        let result = work(progress);
+        self.release_slot();
+
+        self.update(id, |job| match result {
+            Ok(output) => {
+                job.output = Some(output);
+                job.finish(JobState::Completed);
+            }
+            Err(ShebeError::Cancelled(_)) => job.finish(JobState::Cancelled),
+            Err(e) => {
+                job.error = Some(e.to_string());
+                job.finish(JobState::Failed);
+            }
+        });
+        tracing::info!("{} finished", id);
+    }
+
+    /// Block until fewer than `max_running` jobs run
+    ///
+    /// Returns false if the job was cancelled while waiting.
+    fn acquire_slot(&self, progress: &IndexProgress) -> bool {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        while *running >= self.max_running {
+            if progress.is_cancelled() {
+                return false;
+            }
+            running = self
+                .slot_freed
+                .wait_timeout(running, QUEUE_POLL_INTERVAL)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        if progress.is_cancelled() {
+            return false;
+        }
+        *running += 1;
+        true
+    }
+
+    fn release_slot(&self) {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        *running = running.saturating_sub(1);
+        self.slot_freed.notify_one();
+    }
+
+    fn update(&self, id: &JobId, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.lock_jobs().iter_mut().find(|job| &job.id == id) {
+            f(job);
+        }
+    }
+
+    /// Drop finished jobs older than the retention window
+    fn prune(&self, jobs: &mut Vec<Job>) {
+        let now = Utc::now();
+        jobs.retain(|job| match job.finished_at {
+            Some(finished) => (now - finished).to_std().unwrap_or_default() < self.retention,
+            None => true,
+        });
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, Vec<Job>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    fn output() -> JobOutput {
+        JobOutput {
+            stats: IndexStats {
+                files_indexed: 3,
+                chunks_created: 7,
+                duration_ms: 12,
+                session: "s".to_string(),
+                files_deduplicated: 0,
+                skipped: Default::default(),
+                skipped_files: Vec::new(),
+                bytes_stripped: 0,
+                files_streamed: 0,
+                truncated_files: Vec::new(),
+                files_renamed: 0,
+                renamed_files: Vec::new(),
+                patterns: Default::default(),
+                pattern_warning: None,
+                provenance: None,
+                profile: None,
+            },
+            remote: None,
+        }
+    }
+
+    fn manager(max_running: usize) -> Arc<JobManager> {
+        Arc::new(JobManager::new(max_running, Duration::from_secs(60)))
+    }
+
+    /// Poll until the job reaches `state` (panics after 5s)
+    fn wait_for(manager: &JobManager, id: &JobId, state: JobState) -> JobStatus {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let status = manager.status(id).unwrap();
+            if status.state == state {
+                return status;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "{id} stuck in {:?}",
+                status.state
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Work that runs until cancelled, reporting progress
+    fn until_cancelled(progress: &IndexProgress) -> Result<JobOutput> {
+        progress.set_total(10);
+        progress.file_processed();
+        loop {
+            progress.check_cancelled()?;
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_job_completes() {
+        let manager = manager(1);
+        let id = manager
+            .start("s", "/repo", |progress| {
+                progress.set_total(3);
+                Ok(output())
+            })
+            .unwrap();
+        assert_eq!(id.as_str(), "job-1");
+
+        let status = wait_for(&manager, &id, JobState::Completed);
+        assert_eq!(status.progress_percent, 100);
+        assert_eq!(status.stats.unwrap().chunks_created, 7);
+        assert!(status.started_at.is_some());
+        assert!(status.finished_at.is_some());
+        assert_eq!(manager.list().len(), 1);
+        assert!(manager.active_job("s").is_none());
+    }
+
+    #[test]
+    fn test_job_failure_is_recorded() {
+        let manager = manager(1);
+        let id = manager
+            .start("s", "/repo", |_| {
+                Err(ShebeError::IndexingFailed("disk full".to_string()))
+            })
+            .unwrap();
+
+        let status = wait_for(&manager, &id, JobState::Failed);
+        assert!(status.error.unwrap().contains("disk full"));
+        assert!(status.stats.is_none());
+    }
+
+    #[test]
+    fn test_one_job_per_session() {
+        let manager = manager(2);
+        let id = manager.start("s", "/repo", until_cancelled).unwrap();
+
+        let err = manager.start("s", "/repo", |_| Ok(output())).unwrap_err();
+        assert!(matches!(err, ShebeError::IndexLocked(_)));
+        assert!(err.to_string().contains(id.as_str()));
+        assert_eq!(manager.active_job("s"), Some(id.clone()));
+
+        // Other sessions are unaffected, and the session frees up once the job ends
+        let other = manager.start("t", "/other", |_| Ok(output())).unwrap();
+        wait_for(&manager, &other, JobState::Completed);
+        manager.cancel(&id).unwrap();
+        wait_for(&manager, &id, JobState::Cancelled);
+        assert!(manager.start("s", "/repo", |_| Ok(output())).is_ok());
+    }
+
+    #[test]
+    fn test_cancel_running_job() {
+        let manager = manager(1);
+        let id = manager.start("s", "/repo", until_cancelled).unwrap();
+        let status = wait_for(&manager, &id, JobState::Running);
+        assert!(!status.cancel_requested);
+
+        let status = manager.cancel(&id).unwrap();
+        assert!(status.cancel_requested || status.state == JobState::Cancelled);
+
+        let status = wait_for(&manager, &id, JobState::Cancelled);
+        assert!(!status.cancel_requested);
+        assert_eq!(status.files_total, 10);
+        assert!(status.error.is_none());
+    }
+
+    #[test]
+    fn test_queued_job_waits_for_slot_and_can_be_cancelled() {
+        let manager = manager(1);
+        let (release, gate) = mpsc::channel::<()>();
+        let first = manager
+            .start("a", "/a", move |_| {
+                gate.recv().ok();
+                Ok(output())
+            })
+            .unwrap();
+        wait_for(&manager, &first, JobState::Running);
+
+        let second = manager.start("b", "/b", |_| Ok(output())).unwrap();
+        let third = manager.start("c", "/c", |_| Ok(output())).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(manager.status(&second).unwrap().state, JobState::Queued);
+
+        let status = manager.cancel(&third).unwrap();
+        assert_eq!(status.state, JobState::Cancelled);
+
+        release.send(()).unwrap();
+        wait_for(&manager, &first, JobState::Completed);
+        wait_for(&manager, &second, JobState::Completed);
+        assert_eq!(manager.status(&third).unwrap().state, JobState::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_finished_job_is_noop() {
+        let manager = manager(1);
+        let id = manager.start("s", "/repo", |_| Ok(output())).unwrap();
+        wait_for(&manager, &id, JobState::Completed);
+
+        let status = manager.cancel(&id).unwrap();
+        assert_eq!(status.state, JobState::Completed);
+        assert!(!status.cancel_requested);
+    }
+
+    #[test]
+    fn test_unknown_job() {
+        let manager = manager(1);
+        let err = manager.status(&JobId::new("job-99")).unwrap_err();
+        assert!(matches!(err, ShebeError::JobNotFound(_)));
+        assert!(matches!(
+            manager.cancel(&JobId::new("job-99")),
+            Err(ShebeError::JobNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_finished_jobs_expire_after_retention() {
+        let manager = Arc::new(JobManager::new(1, Duration::from_millis(50)));
+        let id = manager.start("s", "/repo", |_| Ok(output())).unwrap();
+        wait_for(&manager, &id, JobState::Completed);
+
+        thread::sleep(Duration::from_millis(80));
+        assert!(manager.list().is_empty());
+        assert!(matches!(
+            manager.status(&id),
+            Err(ShebeError::JobNotFound(_))
+        ));
+    }
+}
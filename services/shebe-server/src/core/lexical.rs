@@ -0,0 +1,459 @@
+//! Comment and string regions of source files.
+//!
+//! A lightweight scanner, not a parser: it tracks the comment and string
+//! delimiters of a language so that a byte offset can be classified as
+//! code, comment or string. It knows enough to follow block comments and
+//! strings across lines (`/* ... */`, Python docstrings, template
+//! literals, `<!-- ... -->`), which line-by-line checks cannot.
+//!
+//! Unknown languages have no regions; every offset counts as code.
+
+use std::ops::Range;
+
+/// What a region of a file contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    Comment,
+    String,
+}
+
+/// Comment and string regions of one file, in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Regions {
+    spans: Vec<(Range<usize>, RegionKind)>,
+}
+
+impl Regions {
+    /// Kind of region containing byte `offset`, or `None` for code
+    pub fn kind_at(&self, offset: usize) -> Option<RegionKind> {
+        let idx = self.spans.partition_point(|(range, _)| range.end <= offset);
+        self.spans
+            .get(idx)
+            .filter(|(range, _)| range.contains(&offset))
+            .map(|(_, kind)| *kind)
+    }
+
+    /// The regions as byte ranges
+    pub fn spans(&self) -> &[(Range<usize>, RegionKind)] {
+        &self.spans
+    }
+
+    fn push(&mut self, range: Range<usize>, kind: RegionKind) {
+        if !range.is_empty() {
+            self.spans.push((range, kind));
+        }
+    }
+}
+
+/// A string literal delimiter
+struct StringDelim {
+    open: &'static str,
+    close: &'static str,
+    /// Backslash escapes the next character
+    escapes: bool,
+    /// May span lines; otherwise an unterminated string ends at the newline
+    multiline: bool,
+    /// `${ ... }` switches back to code (template literals)
+    interpolation: bool,
+}
+
+const fn string(open: &'static str, close: &'static str, multiline: bool) -> StringDelim {
+    StringDelim {
+        open,
+        close,
+        escapes: true,
+        multiline,
+        interpolation: false,
+    }
+}
+
+const DOUBLE: StringDelim = string("\"", "\"", false);
+const DOUBLE_MULTILINE: StringDelim = string("\"", "\"", true);
+const SINGLE: StringDelim = string("'", "'", false);
+const SINGLE_MULTILINE: StringDelim = string("'", "'", true);
+const TEMPLATE: StringDelim = StringDelim {
+    open: "`",
+    close: "`",
+    escapes: true,
+    multiline: true,
+    interpolation: true,
+};
+const GO_RAW: StringDelim = StringDelim {
+    open: "`",
+    close: "`",
+    escapes: false,
+    multiline: true,
+    interpolation: false,
+};
+const TRIPLE_DOUBLE: StringDelim = string("\"\"\"", "\"\"\"", true);
+const TRIPLE_SINGLE: StringDelim = string("'''", "'''", true);
+
+/// Comment and string syntax of a language
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    /// Tried in order, so longer delimiters come first
+    strings: &'static [StringDelim],
+    /// `'x'` is a character literal (and `'a` a Rust lifetime)
+    char_literals: bool,
+    /// Rust raw strings (`r"..."`, `r#"..."#`)
+    raw_strings: bool,
+    /// Line comments only start at the beginning of a word (shell `#`)
+    comment_after_space: bool,
+}
+
+const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+const HTML_BLOCK: &[(&str, &str)] = &[("<!--", "-->")];
+
+const fn syntax(
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    strings: &'static [StringDelim],
+) -> Syntax {
+    Syntax {
+        line_comments,
+        block_comments,
+        strings,
+        char_literals: false,
+        raw_strings: false,
+        comment_after_space: false,
+    }
+}
+
+impl Syntax {
+    /// Syntax for a language name as returned by `detect_language`
+    fn for_language(language: &str) -> Option<Self> {
+        let syntax = match language {
+            "rust" => Syntax {
+                char_literals: true,
+                raw_strings: true,
+                ..syntax(&["//"], C_BLOCK, &[DOUBLE_MULTILINE])
+            },
+            "c" | "cpp" | "java" | "csharp" | "kotlin" | "scala" | "swift" => Syntax {
+                char_literals: true,
+                ..syntax(&["//"], C_BLOCK, &[TRIPLE_DOUBLE, DOUBLE])
+            },
+            "go" => Syntax {
+                char_literals: true,
+                ..syntax(&["//"], C_BLOCK, &[DOUBLE, GO_RAW])
+            },
+            "javascript" | "typescript" => syntax(&["//"], C_BLOCK, &[DOUBLE, SINGLE, TEMPLATE]),
+            "php" => syntax(&["//", "#"], C_BLOCK, &[DOUBLE_MULTILINE, SINGLE_MULTILINE]),
+            "css" | "scss" => syntax(&[], C_BLOCK, &[DOUBLE, SINGLE]),
+            "python" => syntax(&["#"], &[], &[TRIPLE_DOUBLE, TRIPLE_SINGLE, DOUBLE, SINGLE]),
+            "ruby" | "perl" | "r" | "elixir" | "julia" | "yaml" | "toml" => {
+                syntax(&["#"], &[], &[DOUBLE, SINGLE])
+            }
+            "bash" => Syntax {
+                comment_after_space: true,
+                ..syntax(&["#"], &[], &[DOUBLE_MULTILINE, SINGLE_MULTILINE])
+            },
+            "sql" => syntax(&["--"], C_BLOCK, &[SINGLE_MULTILINE, DOUBLE]),
+            "lua" | "haskell" => syntax(&["--"], &[], &[DOUBLE, SINGLE]),
+            "html" | "xml" | "markdown" => syntax(&[], HTML_BLOCK, &[]),
+            _ => return None,
+        };
+        Some(syntax)
+    }
+}
+
+/// Find the comment and string regions of `content`
+///
+/// `language` is a name as returned by `detect_language` (`"rust"`,
+/// `"python"`, ...).
+pub fn scan(content: &str, language: &str) -> Regions {
+    let mut regions = Regions::default();
+    let Some(syntax) = Syntax::for_language(language) else {
+        return regions;
+    };
+
+    let mut i = 0;
+    while i < content.len() {
+        let rest = &content[i..];
+
+        if let Some(end) = line_comment_end(content, i, &syntax) {
+            regions.push(i..end, RegionKind::Comment);
+            i = end;
+            continue;
+        }
+        if let Some((open, close)) = syntax
+            .block_comments
+            .iter()
+            .find(|(open, _)| rest.starts_with(open))
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(content.len(), |n| i + open.len() + n + close.len());
+            regions.push(i..end, RegionKind::Comment);
+            i = end;
+            continue;
+        }
+        if syntax.raw_strings {
+            if let Some(end) = raw_string_end(content, i) {
+                regions.push(i..end, RegionKind::String);
+                i = end;
+                continue;
+            }
+        }
+        if let Some(delim) = syntax.strings.iter().find(|d| rest.starts_with(d.open)) {
+            i = scan_string(content, i, delim, &mut regions);
+            continue;
+        }
+        if syntax.char_literals && rest.starts_with('\'') {
+            if let Some(end) = char_literal_end(content, i) {
+                regions.push(i..end, RegionKind::String);
+                i = end;
+                continue;
+            }
+        }
+
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    regions
+}
+
+/// End of a line comment starting at `i`, if one does
+fn line_comment_end(content: &str, i: usize, syntax: &Syntax) -> Option<usize> {
+    let rest = &content[i..];
+    syntax
+        .line_comments
+        .iter()
+        .find(|prefix| rest.starts_with(*prefix))?;
+    if syntax.comment_after_space
+        && content[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace())
+    {
+        return None;
+    }
+    Some(rest.find('\n').map_or(content.len(), |n| i + n))
+}
+
+/// Record the string starting at `start` and return the offset after it
+///
+/// Interpolated expressions of template literals are left out of the
+/// string regions.
+fn scan_string(content: &str, start: usize, delim: &StringDelim, regions: &mut Regions) -> usize {
+    let bytes = content.as_bytes();
+    let mut span_start = start;
+    let mut i = start + delim.open.len();
+    while i < content.len() {
+        let rest = &content[i..];
+        if delim.escapes && bytes[i] == b'\\' {
+            i += 1 + rest[1..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        if rest.starts_with(delim.close) {
+            let end = i + delim.close.len();
+            regions.push(span_start..end, RegionKind::String);
+            return end;
+        }
+        if !delim.multiline && bytes[i] == b'\n' {
+            break;
+        }
+        if delim.interpolation && rest.starts_with("${") {
+            regions.push(span_start..i, RegionKind::String);
+            i = interpolation_end(content, i + 2);
+            span_start = i;
+            continue;
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    regions.push(span_start..i, RegionKind::String);
+    i
+}
+
+/// Offset after the `}` closing an interpolation whose body starts at `i`
+fn interpolation_end(content: &str, i: usize) -> usize {
+    let mut depth = 1;
+    for (offset, c) in content[i..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + offset + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    content.len()
+}
+
+/// End of a Rust raw string (`r"..."`, `br#"..."#`) starting at `i`
+fn raw_string_end(content: &str, i: usize) -> Option<usize> {
+    let rest = &content[i..];
+    let after_prefix = rest.strip_prefix("br").or_else(|| rest.strip_prefix('r'))?;
+    // `r` must start a token, not end an identifier like `bar"`
+    if content[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    let hashes = after_prefix.len() - after_prefix.trim_start_matches('#').len();
+    let body = after_prefix[hashes..].strip_prefix('"')?;
+    let close = format!("\"{}", "#".repeat(hashes));
+    let body_start = content.len() - body.len();
+    Some(
+        body.find(&close)
+            .map_or(content.len(), |n| body_start + n + close.len()),
+    )
+}
+
+/// End of a character literal (`'x'`, `'\n'`) starting at `i`
+///
+/// Returns `None` for a quote that does not start one, such as a Rust
+/// lifetime (`'a`).
+fn char_literal_end(content: &str, i: usize) -> Option<usize> {
+    let mut chars = content[i + 1..].char_indices();
+    let (_, first) = chars.next()?;
+    let body_len = if first == '\\' {
+        // Escapes are short: '\n', '\x7f', '\u{1F600}'
+        content[i + 2..]
+            .char_indices()
+            .take(11)
+            .find(|(_, c)| *c == '\'')
+            .map(|(n, _)| n + 1)?
+    } else {
+        let (n, c) = chars.next()?;
+        if c != '\'' {
+            return None;
+        }
+        n
+    };
+    Some(i + 1 + body_len + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Region kind at the first occurrence of `needle`
+    fn kind_of(content: &str, language: &str, needle: &str) -> Option<RegionKind> {
+        let offset = content.find(needle).expect("needle in content");
+        scan(content, language).kind_at(offset)
+    }
+
+    #[test]
+    fn test_multiline_block_comment() {
+        let content = "fn main() {}\n/*\n * Notes\n *\n   old_name was\n   renamed\n */\nfn old_name_user() {}\n";
+        assert_eq!(
+            kind_of(content, "rust", "old_name was"),
+            Some(RegionKind::Comment)
+        );
+        assert_eq!(kind_of(content, "rust", "old_name_user"), None);
+        assert_eq!(kind_of(content, "rust", "fn main"), None);
+    }
+
+    #[test]
+    fn test_line_comments() {
+        let content = "let x = 1; // target here\ntarget();\n";
+        assert_eq!(
+            kind_of(content, "rust", "target here"),
+            Some(RegionKind::Comment)
+        );
+        assert_eq!(kind_of(content, "rust", "target()"), None);
+        let content = "x = 1  # target\ntarget()\n";
+        assert_eq!(
+            kind_of(content, "python", "target\n"),
+            Some(RegionKind::Comment)
+        );
+        assert_eq!(kind_of(content, "python", "target()"), None);
+    }
+
+    #[test]
+    fn test_comment_markers_inside_strings() {
+        let content = "let url = \"http://example.com\"; target();\n";
+        assert_eq!(kind_of(content, "rust", "target"), None);
+        assert_eq!(
+            kind_of(content, "rust", "example"),
+            Some(RegionKind::String)
+        );
+        let content = "s = \"# not a comment\"\ntarget()\n";
+        assert_eq!(kind_of(content, "python", "target"), None);
+    }
+
+    #[test]
+    fn test_python_docstring() {
+        let content = "def f():\n    \"\"\"Summary.\n\n    Calls target to do it.\n    \"\"\"\n    target()\n";
+        assert_eq!(
+            kind_of(content, "python", "target to"),
+            Some(RegionKind::String)
+        );
+        assert_eq!(kind_of(content, "python", "target()"), None);
+    }
+
+    #[test]
+    fn test_template_literal() {
+        let content = "const msg = `\n  Please call target\n  ${target(x)} done\n`;\ntarget();\n";
+        assert_eq!(
+            kind_of(content, "javascript", "target\n"),
+            Some(RegionKind::String)
+        );
+        // Interpolated expressions are code
+        assert_eq!(kind_of(content, "javascript", "target(x)"), None);
+        assert_eq!(
+            kind_of(content, "javascript", " done"),
+            Some(RegionKind::String)
+        );
+        assert_eq!(kind_of(content, "javascript", "target();"), None);
+    }
+
+    #[test]
+    fn test_html_comment() {
+        let content = "<div>\n<!--\n  <span>target</span>\n-->\n<p>target</p>\n";
+        assert_eq!(
+            kind_of(content, "html", "target</span>"),
+            Some(RegionKind::Comment)
+        );
+        assert_eq!(kind_of(content, "html", "target</p>"), None);
+    }
+
+    #[test]
+    fn test_rust_char_literals_and_lifetimes() {
+        let content = "fn f<'a>(s: &'a str) -> bool { s == \"x\" || c == '\"' } target();\n";
+        assert_eq!(kind_of(content, "rust", "target"), None);
+        let content = "let s = r#\"raw \"quoted\" target\"#; target();\n";
+        assert_eq!(
+            kind_of(content, "rust", "target\""),
+            Some(RegionKind::String)
+        );
+        assert_eq!(kind_of(content, "rust", "target()"), None);
+    }
+
+    #[test]
+    fn test_single_line_string_ends_at_newline() {
+        // An unterminated string does not swallow the rest of the file
+        let content = "printf(\"oops\n);\ntarget();\n";
+        assert_eq!(kind_of(content, "c", "target"), None);
+    }
+
+    #[test]
+    fn test_shell_hash_inside_word() {
+        let content = "echo ${#items} target\n# target\n";
+        assert_eq!(kind_of(content, "bash", "target\n#"), None);
+        assert_eq!(kind_of(content, "bash", "target\n"), None);
+        let offset = content.rfind("target").unwrap();
+        assert_eq!(
+            scan(content, "bash").kind_at(offset),
+            Some(RegionKind::Comment)
+        );
+    }
+
+    #[test]
+    fn test_unknown_language_is_code() {
+        let content = "/* target */";
+        assert_eq!(kind_of(content, "", "target"), None);
+        assert!(scan(content, "").spans().is_empty());
+    }
+
+    #[test]
+    fn test_unterminated_block_comment_runs_to_end() {
+        let content = "code();\n/* target\n";
+        assert_eq!(kind_of(content, "go", "target"), Some(RegionKind::Comment));
+    }
+}
@@ -0,0 +1,345 @@
+//! Locating files from a vague description.
+//!
+//! A needle such as "the retry config" may name a file (`retry_config.rs`)
+//! or something inside one. [`locate`] tries both: path matching scores
+//! each indexed path by the share of the needle's words found among the
+//! words of its path relative to the repository root, and a BM25 content
+//! search (one hit per file) finds the rest. The two lists are merged per
+//! file; the combined score adds the path share (0-1) to the content score
+//! relative to the best content hit (0-1), so a file found both ways ranks
+//! above one found either way.
+//!
+//! Words are split at path separators, punctuation and identifier case
+//! changes ([`split_identifier`]), so `RetryConfig.java` and
+//! `retry-config.yaml` both match "retry config". A needle word matches a
+//! path word it equals or begins (`conf` matches `config`).
+
+use crate::core::display::relative_to;
+use crate::core::error::{Result, ShebeError};
+use crate::core::search::identifiers::split_identifier;
+use crate::core::search::{preprocess_query, SearchService};
+use crate::core::storage::StorageManager;
+use crate::core::types::{SearchRequest, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Default number of files returned
+pub const DEFAULT_LOCATE_K: usize = 10;
+
+/// Most files returned
+pub const MAX_LOCATE_K: usize = 100;
+
+/// Which matching a locate runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocateStrategy {
+    /// Path and content matching, merged
+    #[default]
+    Auto,
+    /// Path matching only
+    Path,
+    /// Content search only
+    Content,
+}
+
+impl LocateStrategy {
+    /// Name as used in tool parameters
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LocateStrategy::Auto => "auto",
+            LocateStrategy::Path => "path",
+            LocateStrategy::Content => "content",
+        }
+    }
+
+    fn uses_paths(self) -> bool {
+        self != LocateStrategy::Content
+    }
+
+    fn uses_content(self) -> bool {
+        self != LocateStrategy::Path
+    }
+}
+
+/// A file found by [`locate`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocateHit {
+    /// Indexed file path
+    pub file_path: String,
+
+    /// Share of the needle's words found in the path, if any were
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_score: Option<f32>,
+
+    /// BM25 score of the file's best chunk, if the content matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_score: Option<f32>,
+
+    /// Combined ranking score (0-2)
+    pub score: f32,
+}
+
+impl LocateHit {
+    /// How the file was found: "path match", "content match" or both
+    pub fn found_by(&self) -> &'static str {
+        match (self.path_score.is_some(), self.content_score.is_some()) {
+            (true, true) => "path + content match",
+            (true, false) => "path match",
+            _ => "content match",
+        }
+    }
+}
+
+/// Lowercase words of a needle or path
+///
+/// Splits at anything but letters and digits, then splits identifiers
+/// at case changes (`RetryConfig` -> `retry`, `config`).
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|piece| !piece.is_empty())
+        .flat_map(|piece| split_identifier(piece).unwrap_or_else(|| vec![piece.to_lowercase()]))
+        .collect()
+}
+
+/// Score `paths` by the share of the needle's words found in each
+///
+/// Paths are matched relative to `root` when under it. Paths matching
+/// no word are left out; the rest are sorted best first, then shortest.
+pub fn path_matches<'a>(
+    needle: &str,
+    paths: impl IntoIterator<Item = &'a str>,
+    root: Option<&Path>,
+) -> Vec<(String, f32)> {
+    let needle_words = words(needle);
+    if needle_words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<(String, f32)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let relative = root.and_then(|root| relative_to(Path::new(path), root));
+            let path_words = words(&relative.map_or_else(
+                || path.to_string(),
+                |relative| relative.to_string_lossy().into_owned(),
+            ));
+            let found = needle_words
+                .iter()
+                .filter(|word| path_words.iter().any(|p| p.starts_with(word.as_str())))
+                .count();
+            (found > 0).then(|| (path.to_string(), found as f32 / needle_words.len() as f32))
+        })
+        .collect();
+
+    matches.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .total_cmp(a_score)
+            .then(a.len().cmp(&b.len()))
+            .then(a.cmp(b))
+    });
+    matches
+}
+
+/// Merge path matches and per-file content hits into the top `k` files
+///
+/// Content scores are taken relative to the best one, so both kinds of
+/// score count equally. Ties keep path matches first.
+pub fn merge_hits(
+    path_matches: &[(String, f32)],
+    content: &[SearchResult],
+    k: usize,
+) -> Vec<LocateHit> {
+    let best_content = content
+        .iter()
+        .map(|result| result.score)
+        .fold(0.0f32, f32::max);
+
+    let mut hits: Vec<LocateHit> = Vec::new();
+    let mut by_path: HashMap<String, usize> = HashMap::new();
+    for (path, score) in path_matches {
+        by_path.insert(path.clone(), hits.len());
+        hits.push(LocateHit {
+            file_path: path.clone(),
+            path_score: Some(*score),
+            content_score: None,
+            score: *score,
+        });
+    }
+    for result in content {
+        let relative = if best_content > 0.0 {
+            result.score / best_content
+        } else {
+            0.0
+        };
+        match by_path.get(&result.file_path) {
+            Some(&i) if hits[i].content_score.is_none() => {
+                hits[i].content_score = Some(result.score);
+                hits[i].score += relative;
+            }
+            Some(_) => {}
+            None => {
+                by_path.insert(result.file_path.clone(), hits.len());
+                hits.push(LocateHit {
+                    file_path: result.file_path.clone(),
+                    path_score: None,
+                    content_score: Some(result.score),
+                    score: relative,
+                });
+            }
+        }
+    }
+
+    // Stable, so equal scores keep path matches ahead of content hits
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(k);
+    hits
+}
+
+/// Find the files of a session that `needle` most likely refers to
+///
+/// Returns up to `k` files, best first. Content search uses the needle
+/// as literal text, without path boost (paths are matched separately).
+pub fn locate(
+    storage: &StorageManager,
+    search: &SearchService,
+    session: &str,
+    needle: &str,
+    strategy: LocateStrategy,
+    k: usize,
+) -> Result<Vec<LocateHit>> {
+    if needle.trim().is_empty() {
+        return Err(ShebeError::InvalidQuery(
+            "needle cannot be empty".to_string(),
+        ));
+    }
+
+    let paths = if strategy.uses_paths() {
+        let root = storage
+            .get_session_metadata(session)
+            .ok()
+            .map(|metadata| metadata.repository_path);
+        let files = storage.searchable_file_paths(session)?;
+        path_matches(needle, files.iter().map(String::as_str), root.as_deref())
+    } else {
+        Vec::new()
+    };
+
+    let content = if strategy.uses_content() {
+        search
+            .search(SearchRequest {
+                query: preprocess_query(needle, true),
+                session: session.to_string(),
+                k: Some(k),
+                group_by_file: true,
+                dedupe_overlaps: true,
+                path_boost: Some(0.0),
+                fuzziness: Some(0),
+                expand_identifiers: true,
+                bm25_k1: None,
+                bm25_b: None,
+                within: None,
+                penalize_short_chunks: None,
+                modified_within: None,
+                section_filter: None,
+                exclude_terms: Vec::new(),
+                exclude_paths: Vec::new(),
+                explain: false,
+            })?
+            .results
+    } else {
+        Vec::new()
+    };
+
+    Ok(merge_hits(&paths, &content, k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_hit(file_path: &str, score: f32) -> SearchResult {
+        SearchResult {
+            score,
+            text: String::new(),
+            file_path: file_path.to_string(),
+            chunk_index: 0,
+            session: None,
+            start_offset: 0,
+            end_offset: 0,
+            also_present_at: Vec::new(),
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        }
+    }
+
+    #[test]
+    fn test_words_split_paths_and_identifiers() {
+        assert_eq!(
+            words("src/net/RetryConfig.java"),
+            ["src", "net", "retry", "config", "java"]
+        );
+        assert_eq!(words("retry-config.yaml"), ["retry", "config", "yaml"]);
+        assert_eq!(words("the retry_config"), ["the", "retry", "config"]);
+    }
+
+    #[test]
+    fn test_path_matches_score_by_overlap() {
+        let paths = [
+            "/repo/src/retry_config.rs",
+            "/repo/src/retry.rs",
+            "/repo/docs/configuration.md",
+            "/repo/src/main.rs",
+        ];
+        let matches = path_matches("retry config", paths, Some(Path::new("/repo")));
+        assert_eq!(
+            matches,
+            [
+                ("/repo/src/retry_config.rs".to_string(), 1.0),
+                ("/repo/src/retry.rs".to_string(), 0.5),
+                ("/repo/docs/configuration.md".to_string(), 0.5),
+            ]
+        );
+
+        // The repository root's own name does not match
+        let matches = path_matches("repo", paths, Some(Path::new("/repo")));
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn test_merge_ranks_both_first() {
+        let paths = vec![
+            ("a/retry_config.rs".to_string(), 1.0),
+            ("a/retry.rs".to_string(), 0.5),
+        ];
+        let content = vec![
+            content_hit("a/client.rs", 8.0),
+            content_hit("a/retry.rs", 6.0),
+        ];
+
+        let hits = merge_hits(&paths, &content, 10);
+        let order: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|hit| (hit.file_path.as_str(), hit.found_by()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("a/retry.rs", "path + content match"),
+                ("a/retry_config.rs", "path match"),
+                ("a/client.rs", "content match"),
+            ]
+        );
+        assert_eq!(hits[0].score, 1.25);
+        // Tied at 1.0: the path match stays ahead of the content hit
+        assert_eq!(hits[1].score, hits[2].score);
+
+        assert_eq!(merge_hits(&paths, &content, 1).len(), 1);
+    }
+}
@@ -0,0 +1,270 @@
+//! Logging setup, request IDs and per-call phase timings.
+//!
+//! The MCP server gives every request an ID ([`new_request_id`]) and
+//! runs it inside a tracing span carrying that ID, so all log lines of
+//! one tool call share it. [`spawn_blocking_in_context`] carries the
+//! span over to blocking threads, where storage and search work runs.
+//!
+//! While a tool call runs, its [`PhaseTimings`] collects how long each
+//! phase took (opening the index, searching, formatting the output).
+//! Calls taking at least `log.slow_query_ms` are logged at WARN with
+//! that breakdown.
+
+use crate::core::cancel::CancelToken;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{Dispatch, Span};
+
+/// Characters of a query kept in log lines
+pub const LOG_QUERY_MAX_CHARS: usize = 80;
+
+/// Log line format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Compact human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with span fields (e.g. for Loki)
+    Json,
+}
+
+impl LogFormat {
+    /// Parse `text` or `json` (case-insensitive)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Build the stderr subscriber for a format (INFO and above, no colors)
+///
+/// Logs go to stderr because stdout carries the MCP protocol.
+pub fn subscriber(format: LogFormat) -> Dispatch {
+    let builder = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .with_ansi(false);
+    match format {
+        LogFormat::Text => Dispatch::new(builder.compact().finish()),
+        LogFormat::Json => Dispatch::new(builder.json().finish()),
+    }
+}
+
+/// Install the stderr subscriber as the global default
+pub fn init(format: LogFormat) {
+    if tracing::dispatcher::set_global_default(subscriber(format)).is_err() {
+        tracing::warn!("A global tracing subscriber is already set; keeping it");
+    }
+}
+
+/// A new random request ID (UUID v4)
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+tokio::task_local! {
+    static PHASES: PhaseTimings;
+}
+
+/// Time spent in each phase of one call, in first-recorded order
+///
+/// Clones share the same record. Phases recorded more than once
+/// (e.g. one search per query of a batch) are summed.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimings {
+    phases: Arc<Mutex<Vec<(&'static str, Duration)>>>,
+}
+
+impl PhaseTimings {
+    /// Add `elapsed` to `phase`
+    pub fn record(&self, phase: &'static str, elapsed: Duration) {
+        let mut phases = self.phases.lock().unwrap_or_else(|e| e.into_inner());
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+    }
+
+    /// Recorded phases and their total durations
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        self.phases
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Run `future` with these timings as the current call's timings
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        PHASES.scope(self, future).await
+    }
+
+    /// The current call's timings, if any
+    pub fn current() -> Option<Self> {
+        PHASES.try_with(Clone::clone).ok()
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    /// e.g. `index_open=2.1ms search=140.0ms format=0.3ms`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phases = self.phases();
+        if phases.is_empty() {
+            return write!(f, "none");
+        }
+        let parts: Vec<String> = phases
+            .iter()
+            .map(|(name, elapsed)| format!("{name}={:.1}ms", elapsed.as_secs_f64() * 1000.0))
+            .collect();
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Add `elapsed` to a phase of the current call (no-op outside a call)
+pub fn record_phase(phase: &'static str, elapsed: Duration) {
+    let _ = PHASES.try_with(|phases| phases.record(phase, elapsed));
+}
+
+/// Run `work` and record its duration as a phase of the current call
+pub fn time_phase<T>(phase: &'static str, work: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let output = work();
+    record_phase(phase, start.elapsed());
+    output
+}
+
+/// Run `work` on a blocking thread in the caller's logging context
+///
+/// The current span (and with it the request ID), subscriber, phase
+/// timings and cancel token carry over, so log lines and timings from
+/// the blocking work belong to the calling request and the work can
+/// see when the call is abandoned.
+pub fn spawn_blocking_in_context<T, F>(work: F) -> tokio::task::JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let span = Span::current();
+    let dispatch = tracing::dispatcher::get_default(Dispatch::clone);
+    let phases = PhaseTimings::current();
+    let cancel = CancelToken::current();
+
+    tokio::task::spawn_blocking(move || {
+        tracing::dispatcher::with_default(&dispatch, || {
+            let _entered = span.enter();
+            let work = move || match phases {
+                Some(phases) => PHASES.sync_scope(phases, work),
+                None => work(),
+            };
+            match cancel {
+                Some(cancel) => cancel.sync_scope(work),
+                None => work(),
+            }
+        })
+    })
+}
+
+/// Whether a call took long enough for the slow query log
+///
+/// A threshold of 0 disables the log.
+pub fn is_slow(elapsed: Duration, slow_query_ms: u64) -> bool {
+    slow_query_ms > 0 && elapsed >= Duration::from_millis(slow_query_ms)
+}
+
+/// Shorten a query for log lines to [`LOG_QUERY_MAX_CHARS`] characters
+pub fn truncate_for_log(query: &str) -> String {
+    let query = query.trim();
+    if query.chars().count() <= LOG_QUERY_MAX_CHARS {
+        return query.to_string();
+    }
+    let kept: String = query.chars().take(LOG_QUERY_MAX_CHARS - 3).collect();
+    format!("{kept}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parse() {
+        assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse(" Text "), Some(LogFormat::Text));
+        assert_eq!(LogFormat::parse("xml"), None);
+        assert_eq!(LogFormat::Json.to_string(), "json");
+    }
+
+    #[test]
+    fn test_phase_timings_sum_repeated_phases() {
+        let timings = PhaseTimings::default();
+        timings.record("index_open", Duration::from_millis(2));
+        timings.record("search", Duration::from_millis(10));
+        timings.record("search", Duration::from_millis(5));
+
+        assert_eq!(
+            timings.phases(),
+            vec![
+                ("index_open", Duration::from_millis(2)),
+                ("search", Duration::from_millis(15))
+            ]
+        );
+        assert_eq!(timings.to_string(), "index_open=2.0ms search=15.0ms");
+        assert_eq!(PhaseTimings::default().to_string(), "none");
+    }
+
+    #[test]
+    fn test_record_phase_outside_call_is_noop() {
+        record_phase("search", Duration::from_millis(1));
+        assert!(PhaseTimings::current().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_work_records_into_calling_scope() {
+        let timings = PhaseTimings::default();
+
+        timings
+            .clone()
+            .scope(async {
+                record_phase("format", Duration::from_millis(1));
+                spawn_blocking_in_context(|| {
+                    record_phase("search", Duration::from_millis(3));
+                })
+                .await
+                .unwrap();
+            })
+            .await;
+
+        assert_eq!(timings.to_string(), "format=1.0ms search=3.0ms");
+    }
+
+    #[test]
+    fn test_is_slow() {
+        assert!(is_slow(Duration::from_millis(1000), 1000));
+        assert!(!is_slow(Duration::from_millis(999), 1000));
+        assert!(!is_slow(Duration::from_secs(60), 0));
+    }
+
+    #[test]
+    fn test_truncate_for_log() {
+        assert_eq!(truncate_for_log("  getUserId "), "getUserId");
+
+        let long = "é".repeat(100);
+        let truncated = truncate_for_log(&long);
+        assert_eq!(truncated.chars().count(), LOG_QUERY_MAX_CHARS);
+        assert!(truncated.ends_with("..."));
+    }
+}
@@ -0,0 +1,227 @@
+//! Idle-time index maintenance.
+//!
+//! After many re-index cycles a session's index is spread over many
+//! small Tantivy segments, with deleted documents and stale files left
+//! behind. With `maintenance.enabled`, [`MaintenanceTask`] waits until
+//! no tool has been called for `maintenance.idle_minutes`, then
+//! optimizes every session with [`StorageManager::optimize_session`]:
+//! segments are merged down to `maintenance.target_segments`, deleted
+//! documents purged, stale files removed and `index_size_bytes`
+//! refreshed. `optimize_session` runs the same code path on demand.
+//!
+//! - A session being indexed by a job, or whose index writer is held by
+//!   another operation, is skipped until the next idle period
+//! - A tool call during a pass stops it after the current session
+//! - Each idle period gets at most one pass
+//!
+//! [`StorageManager::optimize_session`]: crate::core::storage::StorageManager::optimize_session
+
+use crate::core::error::ShebeError;
+use crate::core::services::Services;
+use crate::core::types::CompactionStats;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Seconds between idle checks (shorter idle periods check more often)
+const TICK_SEC: u64 = 60;
+
+/// Tool calls seen by the server, for idle detection
+#[derive(Debug)]
+pub struct Activity {
+    /// Calls currently executing
+    in_flight: AtomicUsize,
+    /// Incremented by every call
+    generation: AtomicU64,
+    /// When the last call finished (or the server started)
+    last_finished: Mutex<Instant>,
+}
+
+impl Default for Activity {
+    fn default() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            generation: AtomicU64::new(0),
+            last_finished: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl Activity {
+    /// Record the start of a call; it ends when the guard is dropped
+    pub fn begin(&self) -> ActivityGuard<'_> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        ActivityGuard { activity: self }
+    }
+
+    /// How long no call has been running at `now` (`None` while one is)
+    pub fn idle_for(&self, now: Instant) -> Option<Duration> {
+        if self.in_flight.load(Ordering::SeqCst) > 0 {
+            return None;
+        }
+        let last = *self.last_finished.lock().unwrap_or_else(|e| e.into_inner());
+        Some(now.saturating_duration_since(last))
+    }
+
+    /// Changes whenever a call starts
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+}
+
+/// A call in progress (see [`Activity::begin`])
+pub struct ActivityGuard<'a> {
+    activity: &'a Activity,
+}
+
+impl Drop for ActivityGuard<'_> {
+    fn drop(&mut self) {
+        *self
+            .activity
+            .last_finished
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        self.activity.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Optimizes sessions once the server has been idle long enough
+///
+/// Errors are logged; they never stop the task.
+pub struct MaintenanceTask {
+    services: Arc<Services>,
+    /// Activity generation the last complete pass ran in
+    optimized_generation: Option<u64>,
+}
+
+impl MaintenanceTask {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self {
+            services,
+            optimized_generation: None,
+        }
+    }
+
+    /// Run the task on the current tokio runtime
+    pub fn spawn(services: Arc<Services>) -> tokio::task::JoinHandle<()> {
+        let config = services.config.current();
+        let tick = Duration::from_secs(TICK_SEC.min(config.maintenance.idle_minutes * 60));
+        tracing::info!(
+            "Idle maintenance started (after {} idle minute(s))",
+            config.maintenance.idle_minutes
+        );
+        tokio::spawn(Self::new(services).run(tick))
+    }
+
+    async fn run(mut self, tick: Duration) {
+        let mut interval = tokio::time::interval(tick);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            // Merging blocks on disk IO
+            self = match tokio::task::spawn_blocking(move || {
+                self.tick(Instant::now());
+                self
+            })
+            .await
+            {
+                Ok(task) => task,
+                Err(e) => {
+                    tracing::error!("Idle maintenance stopped: {}", e);
+                    return;
+                }
+            };
+        }
+    }
+
+    /// Optimize every session if the server is idle at `now`
+    ///
+    /// Returns the sessions optimized; empty when the server is busy,
+    /// not yet idle long enough, or already optimized in this idle
+    /// period.
+    pub fn tick(&mut self, now: Instant) -> Vec<CompactionStats> {
+        let config = self.services.config.current();
+        let idle_after = Duration::from_secs(config.maintenance.idle_minutes * 60);
+        let activity = &self.services.activity;
+        let generation = activity.generation();
+        let idle = |now| {
+            activity
+                .idle_for(now)
+                .is_some_and(|idle| idle >= idle_after)
+        };
+        if self.optimized_generation == Some(generation) || !idle(now) {
+            return Vec::new();
+        }
+
+        let sessions = match self.services.storage.list_sessions() {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                tracing::warn!("Idle maintenance could not list sessions: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut optimized = Vec::new();
+        for metadata in sessions {
+            if activity.generation() != generation || !idle(now) {
+                tracing::debug!("Idle maintenance interrupted by a tool call");
+                return optimized;
+            }
+            if let Some(job) = self.services.jobs.active_job(&metadata.id) {
+                tracing::debug!(
+                    "Idle maintenance skipped '{}': {} is indexing it",
+                    metadata.id,
+                    job
+                );
+                continue;
+            }
+            match self
+                .services
+                .storage
+                .optimize_session(&metadata.id, config.maintenance.target_segments)
+            {
+                Ok(stats) => {
+                    tracing::debug!(
+                        "Idle maintenance optimized '{}': {} -> {} segment(s)",
+                        stats.session,
+                        stats.segments_before,
+                        stats.segments_after
+                    );
+                    optimized.push(stats);
+                }
+                Err(ShebeError::IndexLocked(_)) => {
+                    tracing::debug!("Idle maintenance skipped '{}': index locked", metadata.id)
+                }
+                Err(e) => {
+                    tracing::warn!("Idle maintenance of '{}' failed: {}", metadata.id, e)
+                }
+            }
+        }
+
+        if !optimized.is_empty() {
+            tracing::info!("Idle maintenance optimized {} session(s)", optimized.len());
+        }
+        self.optimized_generation = Some(generation);
+        optimized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_idle_and_generation() {
+        let activity = Activity::default();
+        let start = activity.generation();
+
+        let guard = activity.begin();
+        assert_eq!(activity.idle_for(Instant::now()), None);
+        assert_eq!(activity.generation(), start + 1);
+        drop(guard);
+
+        let later = Instant::now() + Duration::from_secs(90);
+        assert!(activity.idle_for(later).unwrap() >= Duration::from_secs(90));
+    }
+}
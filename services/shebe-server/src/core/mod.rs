@@ -5,25 +5,72 @@
 //!
 //! # Architecture
 //!
+//! - **access**: Directories tools may read files from
+//! - **api**: Builder-style library API ([`Shebe`])
+//! - **archive**: Zip and tar archives indexed without extraction
+//! - **cancel**: Cooperative cancellation of timed-out tool calls
+//! - **compare**: Search results of two chunking configurations side by side
 //! - **config**: Configuration loading (TOML + environment)
+//! - **definition**: Lines that define (rather than use) a symbol
+//! - **display**: Relative paths and editor links in tool output
 //! - **error**: Error types and Result alias
+//! - **format**: Human-readable sizes, durations, counts and times
+//! - **health**: Storage readiness checks
 //! - **types**: Domain data structures
 //! - **xdg**: XDG directory handling
 //! - **storage**: Session and Tantivy index management
 //! - **search**: BM25 search implementation
+//! - **selftest**: End-to-end check against a built-in corpus
 //! - **indexer**: File walking and chunking pipeline
+//! - **jobs**: Background indexing jobs with progress and cancellation
+//! - **lexical**: Comment and string regions of source files
+//! - **locate**: Files matching a vague description by path or content
+//! - **logging**: Log setup, request IDs and slow call timings
+//! - **maintenance**: Idle-time segment merging and cleanup
+//! - **path_search**: Files ranked by how well a query matches their paths
+//! - **paths**: Normalized file paths (Windows separators, drives, UNC)
+//! - **reload**: Configuration reload while running
+//! - **remote**: Shallow clones of remote git repositories
+//! - **results**: Short-lived handles for recent search results
+//! - **schedule**: Scheduled background re-indexing
 //! - **services**: Unified service container
+//! - **sources**: Source files read back for reference context
+//! - **tree**: Directory tree aggregation over indexed files
 
+pub mod access;
+pub mod api;
+pub mod archive;
+pub mod cancel;
+pub mod compare;
 pub mod config;
+pub mod definition;
+pub mod display;
 pub mod error;
+pub mod format;
+pub mod health;
 pub mod indexer;
+pub mod jobs;
+pub mod lexical;
+pub mod locate;
+pub mod logging;
+pub mod maintenance;
+pub mod path_search;
+pub mod paths;
+pub mod reload;
+pub mod remote;
+pub mod results;
+pub mod schedule;
 pub mod search;
+pub mod selftest;
 pub mod services;
+pub mod sources;
 pub mod storage;
+pub mod tree;
 pub mod types;
 pub mod xdg;
 
 // Re-export key types for convenience
+pub use api::Shebe;
 pub use config::Config;
 pub use error::{Result, ShebeError};
 pub use services::Services;
@@ -0,0 +1,473 @@
+//! Path-only file search.
+//!
+//! [`search_paths`] ranks the indexed files of a session by how well the
+//! words of a query match the words of each path relative to the
+//! repository root. File contents are never read or scored, so it stays
+//! fast on large sessions.
+//!
+//! Paths split into words as in [`locate`](crate::core::locate) (at
+//! separators, punctuation and identifier case changes), keeping each
+//! word's position so matches can be highlighted. A query word matches a
+//! path word that
+//!
+//! - equals it, or begins with it (`conf` matches `config`),
+//! - is within a small edit distance of it (`sevice` matches `service`;
+//!   one edit from 4 characters, two from 8), or
+//! - contains its letters in order, starting with the same letter
+//!   (`hndlr` matches `handler`; 3 characters or more).
+//!
+//! Files rank by the number of query words matched, then by a score in
+//! which words found in the file name count twice as much as words found
+//! in directory names, then by depth (shallower first).
+
+use crate::core::display::relative_to;
+use crate::core::error::{Result, ShebeError};
+use crate::core::locate::words;
+use crate::core::paths;
+use crate::core::search::fuzzy::bounded_distance;
+use crate::core::storage::StorageManager;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::path::Path;
+
+/// Default number of files returned
+pub const DEFAULT_PATH_SEARCH_K: usize = 20;
+
+/// Most files returned
+pub const MAX_PATH_SEARCH_K: usize = 100;
+
+/// How a query word matched a path word, weakest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WordMatch {
+    /// The path word contains the query word's letters in order
+    Subsequence,
+    /// The path word is within a small edit distance
+    Typo,
+    /// The path word begins with the query word
+    Prefix,
+    /// Same word
+    Exact,
+}
+
+impl WordMatch {
+    /// Name as shown in tool output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WordMatch::Subsequence => "subsequence",
+            WordMatch::Typo => "typo",
+            WordMatch::Prefix => "prefix",
+            WordMatch::Exact => "exact",
+        }
+    }
+
+    /// Contribution to a file's score (before the file name weight)
+    fn weight(self) -> f32 {
+        match self {
+            WordMatch::Exact => 1.0,
+            WordMatch::Prefix => 0.9,
+            WordMatch::Typo => 0.7,
+            WordMatch::Subsequence => 0.5,
+        }
+    }
+}
+
+/// A query word and the path word it matched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchedWord {
+    /// Word of the query (lowercase)
+    pub query_word: String,
+    /// Word of the path it matched (lowercase)
+    pub path_word: String,
+    pub kind: WordMatch,
+    /// Whether the path word is part of the file name
+    pub in_file_name: bool,
+    /// Byte range of the path word in [`PathHit::relative_path`]
+    #[serde(skip)]
+    pub range: Range<usize>,
+}
+
+/// A file found by [`search_paths`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathHit {
+    /// Indexed file path
+    pub file_path: String,
+    /// Path relative to the repository root (as matched)
+    pub relative_path: String,
+    /// Matched query words, in query order
+    pub matches: Vec<MatchedWord>,
+    /// Number of distinct words in the query
+    pub query_words: usize,
+    /// Ranking score (higher is better)
+    pub score: f32,
+}
+
+impl PathHit {
+    /// Query words matched in the file name
+    pub fn in_file_name(&self) -> usize {
+        self.matches.iter().filter(|m| m.in_file_name).count()
+    }
+
+    /// Directories above the file
+    pub fn depth(&self) -> usize {
+        self.relative_path.matches('/').count()
+    }
+
+    /// The relative path in pieces, each marked whether it is a matched word
+    pub fn pieces(&self) -> Vec<(&str, bool)> {
+        let mut ranges: Vec<&Range<usize>> = self.matches.iter().map(|m| &m.range).collect();
+        ranges.sort_by_key(|range| range.start);
+        ranges.dedup();
+
+        let mut pieces = Vec::with_capacity(ranges.len() * 2 + 1);
+        let mut at = 0;
+        for range in ranges {
+            if at < range.start {
+                pieces.push((&self.relative_path[at..range.start], false));
+            }
+            pieces.push((&self.relative_path[range.clone()], true));
+            at = range.end;
+        }
+        if at < self.relative_path.len() {
+            pieces.push((&self.relative_path[at..], false));
+        }
+        pieces
+    }
+
+    /// The relative path with each matched word wrapped in `open` and `close`
+    pub fn highlighted(&self, open: &str, close: &str) -> String {
+        self.pieces()
+            .into_iter()
+            .map(|(piece, matched)| {
+                if matched {
+                    format!("{open}{piece}{close}")
+                } else {
+                    piece.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// How the query matched, e.g. "3/3 words, 2 in file name; sevice ~ service"
+    ///
+    /// Typo and subsequence matches are listed with the path word they matched.
+    pub fn match_summary(&self) -> String {
+        let mut summary = format!(
+            "{}/{} words, {} in file name",
+            self.matches.len(),
+            self.query_words,
+            self.in_file_name()
+        );
+        let inexact: Vec<String> = self
+            .matches
+            .iter()
+            .filter(|m| matches!(m.kind, WordMatch::Typo | WordMatch::Subsequence))
+            .map(|m| format!("{} ~ {}", m.query_word, m.path_word))
+            .collect();
+        if !inexact.is_empty() {
+            summary.push_str("; ");
+            summary.push_str(&inexact.join(", "));
+        }
+        summary
+    }
+}
+
+/// A word of a path and where it is
+#[derive(Debug, Clone, PartialEq)]
+struct PathWord {
+    /// Lowercase text
+    text: String,
+    range: Range<usize>,
+    in_file_name: bool,
+}
+
+/// Split a relative path into words with their byte ranges
+///
+/// Words break at anything but letters and digits, and at identifier
+/// case changes as in [`split_identifier`](crate::core::search::identifiers::split_identifier).
+fn path_words(path: &str) -> Vec<PathWord> {
+    let file_name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let chars: Vec<(usize, char)> = path.char_indices().collect();
+    let mut words = Vec::new();
+    let mut push = |start: usize, end: usize| {
+        words.push(PathWord {
+            text: path[start..end].to_lowercase(),
+            range: start..end,
+            in_file_name: start >= file_name_start,
+        });
+    };
+
+    let mut start: Option<usize> = None;
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if let Some(begin) = start.take() {
+                push(begin, offset);
+            }
+            continue;
+        }
+        let Some(begin) = start else {
+            start = Some(offset);
+            continue;
+        };
+        let prev = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|&(_, n)| n);
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next.is_some_and(char::is_lowercase)));
+        if boundary {
+            push(begin, offset);
+            start = Some(offset);
+        }
+    }
+    if let Some(begin) = start {
+        push(begin, path.len());
+    }
+    words
+}
+
+/// How `query_word` matches `path_word`, if it does
+fn match_word(query_word: &str, path_word: &str) -> Option<WordMatch> {
+    if query_word == path_word {
+        return Some(WordMatch::Exact);
+    }
+    let query: Vec<char> = query_word.chars().collect();
+    if query.len() >= 2 && path_word.starts_with(query_word) {
+        return Some(WordMatch::Prefix);
+    }
+    let max_edits = match query.len() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if max_edits > 0 && bounded_distance(&query, path_word, max_edits).is_some() {
+        return Some(WordMatch::Typo);
+    }
+    if query.len() >= 3 && path_word.starts_with(query[0]) && is_subsequence(&query, path_word) {
+        return Some(WordMatch::Subsequence);
+    }
+    None
+}
+
+/// Whether `word` contains every character of `query` in order
+fn is_subsequence(query: &[char], word: &str) -> bool {
+    let mut wanted = query.iter().peekable();
+    for c in word.chars() {
+        if wanted.peek() == Some(&&c) {
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none()
+}
+
+/// Match every query word against `relative` (a relative path)
+///
+/// `None` when no query word matches. Each query word takes its best
+/// path word: strongest match, then one in the file name, then the last.
+fn match_path(query_words: &[String], file_path: &str, relative: String) -> Option<PathHit> {
+    let words = path_words(&relative);
+    let mut matches = Vec::new();
+    for query_word in query_words {
+        let best = words
+            .iter()
+            .filter_map(|word| match_word(query_word, &word.text).map(|kind| (kind, word)))
+            .max_by_key(|(kind, word)| (*kind, word.in_file_name, word.range.start));
+        if let Some((kind, word)) = best {
+            matches.push(MatchedWord {
+                query_word: query_word.clone(),
+                path_word: word.text.clone(),
+                kind,
+                in_file_name: word.in_file_name,
+                range: word.range.clone(),
+            });
+        }
+    }
+    if matches.is_empty() {
+        return None;
+    }
+
+    let score = matches
+        .iter()
+        .map(|m| m.kind.weight() * if m.in_file_name { 1.0 } else { 0.5 })
+        .sum();
+    Some(PathHit {
+        file_path: file_path.to_string(),
+        relative_path: relative,
+        matches,
+        query_words: query_words.len(),
+        score,
+    })
+}
+
+/// Best first: more query words matched, higher score, shallower, shorter
+fn compare_hits(a: &PathHit, b: &PathHit) -> Ordering {
+    b.matches
+        .len()
+        .cmp(&a.matches.len())
+        .then(b.score.total_cmp(&a.score))
+        .then(a.depth().cmp(&b.depth()))
+        .then(a.relative_path.len().cmp(&b.relative_path.len()))
+        .then(a.relative_path.cmp(&b.relative_path))
+}
+
+/// Rank `paths` by how well `query` matches them, keeping the top `k`
+///
+/// Paths are matched relative to `root` when under it. Paths matching
+/// no query word are left out.
+pub fn rank_paths<'a>(
+    query: &str,
+    paths: impl IntoIterator<Item = &'a str>,
+    root: Option<&Path>,
+    k: usize,
+) -> Vec<PathHit> {
+    let mut query_words = words(query);
+    let mut seen = std::collections::HashSet::new();
+    query_words.retain(|word| seen.insert(word.clone()));
+    if query_words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<PathHit> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let relative = root
+                .and_then(|root| relative_to(Path::new(path), root))
+                .map_or_else(|| path.to_string(), |r| paths::normalize_path(&r));
+            match_path(&query_words, path, relative)
+        })
+        .collect();
+    hits.sort_by(compare_hits);
+    hits.truncate(k);
+    hits
+}
+
+/// Find the files of a session whose path best matches `query`
+///
+/// Returns up to `k` files, best first. Only the indexed file list is
+/// read.
+///
+/// # Errors
+///
+/// Returns `InvalidQuery` for a query without words and
+/// `SessionNotFound` if the session does not exist.
+pub fn search_paths(
+    storage: &StorageManager,
+    session: &str,
+    query: &str,
+    k: usize,
+) -> Result<Vec<PathHit>> {
+    if words(query).is_empty() {
+        return Err(ShebeError::InvalidQuery(
+            "query must contain at least one letter or digit".to_string(),
+        ));
+    }
+
+    let root = storage
+        .get_session_metadata(session)
+        .ok()
+        .map(|metadata| metadata.repository_path);
+    let files = storage.searchable_file_paths(session)?;
+    Ok(rank_paths(
+        query,
+        files.iter().map(String::as_str),
+        root.as_deref(),
+        k,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(query: &str, paths: &[&str]) -> Vec<String> {
+        rank_paths(query, paths.iter().copied(), Some(Path::new("/repo")), 10)
+            .into_iter()
+            .map(|hit| hit.relative_path)
+            .collect()
+    }
+
+    #[test]
+    fn test_path_words_keep_positions() {
+        let words = path_words("src/userService/HTTPHandler.rs");
+        let found: Vec<(&str, &str, bool)> = words
+            .iter()
+            .map(|w| {
+                (
+                    w.text.as_str(),
+                    &"src/userService/HTTPHandler.rs"[w.range.clone()],
+                    w.in_file_name,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("src", "src", false),
+                ("user", "user", false),
+                ("service", "Service", false),
+                ("http", "HTTP", true),
+                ("handler", "Handler", true),
+                ("rs", "rs", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_word_kinds() {
+        assert_eq!(match_word("user", "user"), Some(WordMatch::Exact));
+        assert_eq!(match_word("conf", "config"), Some(WordMatch::Prefix));
+        assert_eq!(match_word("sevice", "service"), Some(WordMatch::Typo));
+        assert_eq!(match_word("hndlr", "handler"), Some(WordMatch::Subsequence));
+        // Short words need an exact or prefix match
+        assert_eq!(match_word("usr", "use"), None);
+        assert_eq!(match_word("x", "xml"), None);
+        assert_eq!(match_word("handler", "router"), None);
+    }
+
+    #[test]
+    fn test_typo_query_finds_file() {
+        let paths = [
+            "/repo/src/user/service_handler.rs",
+            "/repo/src/user/model.rs",
+            "/repo/src/billing/service.rs",
+            "/repo/README.md",
+        ];
+        let hits = rank_paths("user sevice handler", paths, Some(Path::new("/repo")), 10);
+        assert_eq!(hits[0].relative_path, "src/user/service_handler.rs");
+        assert_eq!(hits[0].matches.len(), 3);
+        assert_eq!(hits[0].matches[1].kind, WordMatch::Typo);
+        assert_eq!(
+            hits[0].highlighted("[", "]"),
+            "src/[user]/[service]_[handler].rs"
+        );
+        assert!(hits.iter().all(|hit| hit.relative_path != "README.md"));
+    }
+
+    #[test]
+    fn test_file_name_ranks_above_directory() {
+        assert_eq!(
+            ranked(
+                "config",
+                &[
+                    "/repo/config/loader.rs",
+                    "/repo/src/deep/nested/config.rs",
+                    "/repo/src/config.rs",
+                ]
+            ),
+            [
+                "src/config.rs",
+                "src/deep/nested/config.rs",
+                "config/loader.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_match_is_empty() {
+        assert!(ranked("zebra", &["/repo/src/main.rs"]).is_empty());
+        assert!(ranked("--", &["/repo/src/main.rs"]).is_empty());
+        // The repository root's own name does not match
+        assert!(ranked("repo", &["/repo/src/main.rs"]).is_empty());
+    }
+}
@@ -0,0 +1,430 @@
+//! Normalized file paths for the index
+//!
+//! The index stores one spelling of each file path so that lookups
+//! match however a client writes the path. On Windows:
+//!
+//! - Separators are `/`: `C:\repo\main.rs` is stored as `C:/repo/main.rs`
+//! - Drive letters are upper-case
+//! - Verbatim prefixes (`\\?\C:\...`, `\\?\UNC\...`) added by
+//!   `canonicalize` are dropped
+//! - UNC paths keep their leading `//` (`//server/share/main.rs`)
+//! - Repeated separators, `.` components and trailing separators are
+//!   removed
+//!
+//! On Unix a backslash is an ordinary file name character, so paths are
+//! stored as given. [`to_native`] turns a stored path back into one for
+//! filesystem access.
+//!
+//! Clients may also name files relative to a session's repository root;
+//! [`resolve`] turns those into stored paths without letting them escape
+//! the root.
+
+use crate::core::error::{Result, ShebeError};
+use std::path::{Component, Path, PathBuf};
+
+/// Path conventions to normalize for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// `/` separators only; paths are kept as given
+    Unix,
+    /// `\` or `/` separators, drive letters and UNC shares
+    Windows,
+}
+
+impl PathStyle {
+    /// Style of the platform shebe was built for
+    pub const fn native() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+}
+
+/// Normalize `path` for storage in (or lookup against) the index
+pub fn normalize(path: &str) -> String {
+    normalize_with(path, PathStyle::native())
+}
+
+/// Normalize a filesystem path for the index
+pub fn normalize_path(path: &Path) -> String {
+    normalize(&path.to_string_lossy())
+}
+
+/// Filesystem path for a stored (normalized) path
+pub fn to_native(stored: &str) -> PathBuf {
+    PathBuf::from(to_native_with(stored, PathStyle::native()))
+}
+
+/// Normalize `path` following the conventions of `style`
+pub fn normalize_with(path: &str, style: PathStyle) -> String {
+    match style {
+        PathStyle::Unix => path.to_string(),
+        PathStyle::Windows => normalize_windows(path),
+    }
+}
+
+/// Convert a stored path to the separators of `style`
+pub fn to_native_with(stored: &str, style: PathStyle) -> String {
+    match style {
+        PathStyle::Unix => stored.to_string(),
+        PathStyle::Windows => stored.replace('/', "\\"),
+    }
+}
+
+/// A client-supplied file path resolved for lookup in the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    /// Path as the client wrote it
+    pub requested: String,
+    /// Normalized absolute path, as stored in the index
+    pub path: String,
+    /// Repository root a relative request was resolved against
+    pub root: Option<PathBuf>,
+}
+
+impl ResolvedPath {
+    /// Whether the request was relative to the repository root
+    pub fn is_relative(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// Markdown label: `` `path` ``, or `` `requested` (`path`) `` for
+    /// relative requests
+    pub fn label(&self) -> String {
+        if self.is_relative() {
+            format!("`{}` (`{}`)", self.requested, self.path)
+        } else {
+            format!("`{}`", self.path)
+        }
+    }
+
+    /// Quoted path for error messages, naming the repository root for
+    /// relative requests
+    pub fn describe(&self) -> String {
+        match &self.root {
+            Some(root) => format!(
+                "'{}' (resolved to '{}' under repository root '{}')",
+                self.requested,
+                self.path,
+                root.display()
+            ),
+            None => format!("'{}'", self.path),
+        }
+    }
+}
+
+/// Resolve a client-supplied path against a session's repository root
+///
+/// Absolute paths are only normalized. Relative paths are joined to
+/// `root` and must stay under it, both lexically (`..`) and after
+/// following symlinks.
+pub fn resolve(requested: &str, root: Option<&Path>) -> Result<ResolvedPath> {
+    let normalized = normalize(requested);
+    if to_native(&normalized).is_absolute() {
+        return Ok(ResolvedPath {
+            requested: requested.to_string(),
+            path: normalized,
+            root: None,
+        });
+    }
+
+    let Some(root) = root else {
+        return Err(ShebeError::InvalidPath(format!(
+            "'{requested}' is relative, but the session has no repository path \
+             to resolve it against. Pass an absolute path."
+        )));
+    };
+    if !root.is_dir() {
+        return Err(ShebeError::InvalidPath(format!(
+            "cannot resolve '{requested}': the session's repository root '{}' \
+             no longer exists. Pass an absolute path or re-index the session \
+             from the repository's current location.",
+            root.display()
+        )));
+    }
+
+    let joined = join_lexically(root, &to_native(&normalized));
+    let escapes = || {
+        ShebeError::InvalidPath(format!(
+            "'{requested}' resolves to '{}', outside the repository root '{}'",
+            joined.display(),
+            root.display()
+        ))
+    };
+    if !joined.starts_with(root) {
+        return Err(escapes());
+    }
+    // Symlinks inside the repository may still point outside it
+    if let (Ok(real), Ok(real_root)) = (joined.canonicalize(), root.canonicalize()) {
+        if !real.starts_with(real_root) {
+            return Err(escapes());
+        }
+    }
+
+    Ok(ResolvedPath {
+        requested: requested.to_string(),
+        path: normalize_path(&joined),
+        root: Some(root.to_path_buf()),
+    })
+}
+
+/// `root` joined with `relative`, with `.` and `..` resolved lexically
+fn join_lexically(root: &Path, relative: &Path) -> PathBuf {
+    let mut joined = root.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::ParentDir => {
+                joined.pop();
+            }
+            Component::CurDir => {}
+            other => joined.push(other),
+        }
+    }
+    joined
+}
+
+fn normalize_windows(path: &str) -> String {
+    let slashed = path.replace('\\', "/");
+
+    // Verbatim paths name the same file without the prefix
+    let slashed = if let Some(share) = slashed.strip_prefix("//?/UNC/") {
+        format!("//{share}")
+    } else if let Some(rest) = slashed.strip_prefix("//?/") {
+        rest.to_string()
+    } else {
+        slashed
+    };
+
+    let (prefix, rest) = split_prefix(&slashed);
+    let absolute = rest.starts_with('/');
+    let parts: Vec<&str> = rest
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+
+    let mut normalized = prefix;
+    if absolute && !normalized.starts_with("//") {
+        normalized.push('/');
+    }
+    normalized.push_str(&parts.join("/"));
+    if normalized.is_empty() && (absolute || !rest.is_empty()) {
+        // "/" or "." on their own
+        normalized.push(if absolute { '/' } else { '.' });
+    }
+    normalized
+}
+
+/// Split a `/`-separated Windows path into its prefix and the rest
+///
+/// The prefix is an upper-cased drive (`C:`), a UNC share root (`//`)
+/// or empty. For UNC paths the rest is `server/share/...`.
+fn split_prefix(path: &str) -> (String, &str) {
+    if let Some(rest) = path.strip_prefix("//") {
+        return ("//".to_string(), rest);
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = format!("{}:", (bytes[0] as char).to_ascii_uppercase());
+        return (drive, &path[2..]);
+    }
+    (String::new(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows(path: &str) -> String {
+        normalize_with(path, PathStyle::Windows)
+    }
+
+    #[test]
+    fn test_drive_letters() {
+        assert_eq!(windows(r"C:\repo\src\main.rs"), "C:/repo/src/main.rs");
+        assert_eq!(windows("C:/repo/src/main.rs"), "C:/repo/src/main.rs");
+        assert_eq!(windows(r"c:\repo\main.rs"), "C:/repo/main.rs");
+        assert_eq!(windows(r"C:\"), "C:/");
+        assert_eq!(windows("C:"), "C:");
+        // Drive-relative paths stay relative
+        assert_eq!(windows(r"C:repo\main.rs"), "C:repo/main.rs");
+    }
+
+    #[test]
+    fn test_unc_paths() {
+        assert_eq!(
+            windows(r"\\server\share\repo\main.rs"),
+            "//server/share/repo/main.rs"
+        );
+        assert_eq!(
+            windows("//server/share/repo/main.rs"),
+            "//server/share/repo/main.rs"
+        );
+        assert_eq!(windows(r"\\server\share\"), "//server/share");
+    }
+
+    #[test]
+    fn test_verbatim_prefixes_are_dropped() {
+        assert_eq!(windows(r"\\?\C:\repo\main.rs"), "C:/repo/main.rs");
+        assert_eq!(windows(r"\\?\c:\repo\main.rs"), "C:/repo/main.rs");
+        assert_eq!(
+            windows(r"\\?\UNC\server\share\main.rs"),
+            "//server/share/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_mixed_and_repeated_separators() {
+        assert_eq!(windows(r"C:\repo/src\\main.rs"), "C:/repo/src/main.rs");
+        assert_eq!(windows(r"C:\repo\.\src\main.rs"), "C:/repo/src/main.rs");
+        assert_eq!(windows(r"C:\repo\src\"), "C:/repo/src");
+        assert_eq!(windows(r"src\lib.rs"), "src/lib.rs");
+        assert_eq!(windows(r".\src\lib.rs"), "src/lib.rs");
+        assert_eq!(windows(r"\repo\main.rs"), "/repo/main.rs");
+        assert_eq!(windows("/"), "/");
+        assert_eq!(windows("."), ".");
+        assert_eq!(windows(""), "");
+    }
+
+    #[test]
+    fn test_parent_components_are_kept() {
+        // Resolving `..` needs the filesystem (symlinks), so it is left alone
+        assert_eq!(windows(r"C:\repo\..\other\a.rs"), "C:/repo/../other/a.rs");
+    }
+
+    #[test]
+    fn test_windows_spellings_agree() {
+        let spellings = [
+            r"C:\repo\src\main.rs",
+            "C:/repo/src/main.rs",
+            r"c:\repo\src\main.rs",
+            r"C:\repo/src\main.rs",
+            r"\\?\C:\repo\src\main.rs",
+            r"C:\\repo\\src\\main.rs",
+        ];
+        for spelling in spellings {
+            assert_eq!(windows(spelling), "C:/repo/src/main.rs", "{spelling}");
+        }
+    }
+
+    #[test]
+    fn test_to_native_windows() {
+        assert_eq!(
+            to_native_with("C:/repo/src/main.rs", PathStyle::Windows),
+            r"C:\repo\src\main.rs"
+        );
+        assert_eq!(
+            to_native_with("//server/share/main.rs", PathStyle::Windows),
+            r"\\server\share\main.rs"
+        );
+        // Round trip
+        let stored = windows(r"\\server\share\repo\main.rs");
+        assert_eq!(
+            to_native_with(&stored, PathStyle::Windows),
+            r"\\server\share\repo\main.rs"
+        );
+    }
+
+    #[test]
+    fn test_unix_paths_are_unchanged() {
+        for path in ["/repo/src/main.rs", r"/repo/odd\name.rs", "/repo//a.rs"] {
+            assert_eq!(normalize_with(path, PathStyle::Unix), path);
+            assert_eq!(to_native_with(path, PathStyle::Unix), path);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_relative() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+
+        let resolved = resolve("src/lib.rs", Some(root)).unwrap();
+        assert_eq!(resolved.path, format!("{}/src/lib.rs", root.display()));
+        assert!(resolved.is_relative());
+        assert_eq!(
+            resolved.label(),
+            format!("`src/lib.rs` (`{}/src/lib.rs`)", root.display())
+        );
+        assert_eq!(
+            resolved.describe(),
+            format!(
+                "'src/lib.rs' (resolved to '{0}/src/lib.rs' under repository root '{0}')",
+                root.display()
+            )
+        );
+        // `..` that stays inside the root is fine
+        let resolved = resolve("./src/../src/lib.rs", Some(root)).unwrap();
+        assert_eq!(resolved.path, format!("{}/src/lib.rs", root.display()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_absolute_unchanged() {
+        let resolved = resolve("/repo/src/lib.rs", Some(Path::new("/elsewhere"))).unwrap();
+        assert_eq!(resolved.path, "/repo/src/lib.rs");
+        assert!(!resolved.is_relative());
+        assert_eq!(resolved.label(), "`/repo/src/lib.rs`");
+        assert_eq!(resolved.describe(), "'/repo/src/lib.rs'");
+        // No root needed
+        assert!(resolve("/repo/src/lib.rs", None).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_escapes() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+
+        let err = resolve("../../etc/passwd", Some(root))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("outside the repository root"), "{err}");
+        assert!(err.contains(&root.display().to_string()), "{err}");
+
+        // A symlink pointing out of the repository
+        std::os::unix::fs::symlink("/etc", root.join("etc")).unwrap();
+        let err = resolve("etc/passwd", Some(root)).unwrap_err().to_string();
+        assert!(err.contains("outside the repository root"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_relative_needs_existing_root() {
+        let err = resolve("src/lib.rs", Some(Path::new("/nonexistent/repo")))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("'/nonexistent/repo' no longer exists"),
+            "{err}"
+        );
+        assert!(resolve("src/lib.rs", None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_style_unix() {
+        assert_eq!(PathStyle::native(), PathStyle::Unix);
+        assert_eq!(
+            normalize_path(Path::new("/repo/src/main.rs")),
+            "/repo/src/main.rs"
+        );
+        assert_eq!(to_native("/repo/main.rs"), PathBuf::from("/repo/main.rs"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_native_style_windows() {
+        assert_eq!(PathStyle::native(), PathStyle::Windows);
+        assert_eq!(
+            normalize_path(Path::new(r"C:\repo\src\main.rs")),
+            "C:/repo/src/main.rs"
+        );
+        assert_eq!(
+            to_native("C:/repo/main.rs"),
+            PathBuf::from(r"C:\repo\main.rs")
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Reloading the configuration of a running server.
+//!
+//! [`LiveConfig`] holds the configuration that services and tool
+//! handlers read on every call. [`LiveConfig::reload`] re-reads it from
+//! its [`ConfigSource`], validates it and swaps it in atomically, then
+//! hands the new values to subscribers (search defaults, index limits,
+//! enabled tools). A configuration that fails to load or validate
+//! leaves the running one untouched.
+//!
+//! Some settings are only read at startup ([`RESTART_KEYS`]: storage
+//! directories, the listen address, the job and clone limits, ...).
+//! Changes to them are reported as requiring a restart and their
+//! running values are kept, so the live configuration keeps describing
+//! what the server actually does.
+
+use crate::core::config::Config;
+use crate::core::error::Result;
+use crate::core::xdg::XdgDirs;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Settings that only take effect after a restart, by dotted key
+pub const RESTART_KEYS: &[&str] = &[
+    "storage.index_dir",
+    "storage.default_namespace",
+    "storage.clone_dir",
+    "limits.max_concurrent_indexes",
+    "limits.git_timeout_sec",
+    "limits.job_retention_sec",
+    "mcp.listen",
+    "mcp.auth_token",
+    "schedule.enabled",
+    "schedule.tick_sec",
+    "maintenance.enabled",
+    "log.format",
+    "server.read_only",
+    "server.grpc_port",
+    "server.grpc_host",
+    "server.grpc_auth_token",
+];
+
+/// Settings whose values are never shown
+const SECRET_KEYS: &[&str] = &["mcp.auth_token", "server.grpc_auth_token"];
+
+/// Where a reload reads the configuration from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// `SHEBE_CONFIG`, the XDG config file or `./shebe.toml`, then
+    /// environment variables (see [`Config::load`])
+    #[default]
+    Default,
+
+    /// A TOML file, then environment variables
+    File(PathBuf),
+}
+
+impl ConfigSource {
+    /// Read and validate the configuration
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if the file cannot be read or a value is invalid,
+    /// and TOML errors for a malformed file.
+    pub fn load(&self) -> Result<Config> {
+        self.load_profile(None)
+    }
+
+    /// Read and validate the configuration for storage profile `profile`
+    ///
+    /// `None` falls back to `SHEBE_PROFILE` (see [`Config::apply_profile`]).
+    ///
+    /// # Errors
+    ///
+    /// As [`ConfigSource::load`], and `ConfigError` for an unknown
+    /// profile.
+    pub fn load_profile(&self, profile: Option<&str>) -> Result<Config> {
+        let xdg = XdgDirs::new();
+        match self {
+            Self::Default => Config::load_with_profile(&xdg, profile),
+            Self::File(path) => {
+                let mut config = Config::from_file(path)?;
+                config.merge_env();
+                config.apply_profile(profile, &xdg)?;
+                config.validate()?;
+                Ok(config)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(
+                f,
+                "SHEBE_CONFIG, XDG config file or ./shebe.toml, and environment"
+            ),
+            Self::File(path) => write!(f, "{} and environment", path.display()),
+        }
+    }
+}
+
+/// One changed setting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigChange {
+    /// Dotted key, e.g. `limits.list_dir_max`
+    pub key: String,
+
+    /// Value before the reload (`(unset)` if absent)
+    pub old: String,
+
+    /// Value read by the reload (`(unset)` if absent)
+    pub new: String,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.key, self.old, self.new)
+    }
+}
+
+/// What a reload changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReloadReport {
+    /// Where the configuration was read from
+    pub source: String,
+
+    /// Changed settings now in effect
+    pub applied: Vec<ConfigChange>,
+
+    /// Changed settings that keep their running value until restart
+    pub requires_restart: Vec<ConfigChange>,
+}
+
+impl ReloadReport {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.requires_restart.is_empty()
+    }
+}
+
+type Subscriber = Box<dyn Fn(&Config) + Send + Sync>;
+
+/// The running configuration, replaceable while the server runs
+///
+/// Readers take a snapshot with [`LiveConfig::current`]; a snapshot
+/// never changes, so one call sees consistent values even if a reload
+/// happens meanwhile.
+pub struct LiveConfig {
+    current: RwLock<Arc<Config>>,
+    source: RwLock<ConfigSource>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    /// Held while reloading, so reloads do not interleave
+    reloading: Mutex<()>,
+}
+
+impl LiveConfig {
+    /// Live configuration starting at `config`, reloaded from the
+    /// default locations
+    pub fn new(config: Config) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(config)),
+            source: RwLock::new(ConfigSource::Default),
+            subscribers: Mutex::new(Vec::new()),
+            reloading: Mutex::new(()),
+        }
+    }
+
+    /// Snapshot of the configuration in effect
+    pub fn current(&self) -> Arc<Config> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Where [`LiveConfig::reload`] reads from
+    pub fn source(&self) -> ConfigSource {
+        self.source
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Read later reloads from `source`
+    pub fn set_source(&self, source: ConfigSource) {
+        *self.source.write().unwrap_or_else(|e| e.into_inner()) = source;
+    }
+
+    /// Call `subscriber` with the new configuration after every reload
+    pub fn on_reload(&self, subscriber: impl Fn(&Config) + Send + Sync + 'static) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Box::new(subscriber));
+    }
+
+    /// Re-read the configuration from its source and apply it
+    ///
+    /// The storage profile selected at startup stays selected.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [`ConfigSource::load`]; the running
+    /// configuration is kept.
+    pub fn reload(&self) -> Result<ReloadReport> {
+        let source = self.source();
+        let loaded = source.load_profile(self.current().profile.as_deref())?;
+        let mut report = self.replace(loaded)?;
+        report.source = source.to_string();
+        Ok(report)
+    }
+
+    /// Apply `loaded` in place of the running configuration
+    ///
+    /// Settings in [`RESTART_KEYS`] keep their running values. Changes
+    /// are logged, with secrets redacted.
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if `loaded` is invalid; the running configuration
+    /// is kept.
+    pub fn replace(&self, mut loaded: Config) -> Result<ReloadReport> {
+        loaded.validate()?;
+        let _reloading = self.reloading.lock().unwrap_or_else(|e| e.into_inner());
+
+        let running = self.current();
+        let (requires_restart, applied): (Vec<_>, Vec<_>) = diff(&running, &loaded)
+            .into_iter()
+            .partition(|change| is_restart_key(&change.key));
+        keep_restart_settings(&running, &mut loaded);
+
+        let config = Arc::new(loaded);
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::clone(&config);
+        for subscriber in self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            subscriber(&config);
+        }
+
+        if applied.is_empty() && requires_restart.is_empty() {
+            tracing::info!("Configuration reloaded: no changes");
+        }
+        for change in &applied {
+            tracing::info!("Configuration reloaded: {}", change);
+        }
+        for change in &requires_restart {
+            tracing::warn!("Configuration change requires restart: {}", change);
+        }
+
+        Ok(ReloadReport {
+            source: String::new(),
+            applied,
+            requires_restart,
+        })
+    }
+}
+
+impl fmt::Debug for LiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiveConfig")
+            .field("current", &self.current())
+            .field("source", &self.source())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether a change to `key` only takes effect after a restart
+///
+/// Profiles and namespaces are only resolved at startup, like the
+/// storage they select.
+fn is_restart_key(key: &str) -> bool {
+    RESTART_KEYS.contains(&key) || key.starts_with("profiles.") || key.starts_with("namespaces.")
+}
+
+/// Copy the settings in [`RESTART_KEYS`] from `running` to `loaded`
+fn keep_restart_settings(running: &Config, loaded: &mut Config) {
+    loaded.storage = running.storage.clone();
+    loaded.profiles = running.profiles.clone();
+    loaded.namespaces = running.namespaces.clone();
+    loaded.profile = running.profile.clone();
+    loaded.limits.max_concurrent_indexes = running.limits.max_concurrent_indexes;
+    loaded.limits.git_timeout_sec = running.limits.git_timeout_sec;
+    loaded.limits.job_retention_sec = running.limits.job_retention_sec;
+    loaded.mcp.listen = running.mcp.listen.clone();
+    loaded.mcp.auth_token = running.mcp.auth_token.clone();
+    loaded.schedule.enabled = running.schedule.enabled;
+    loaded.schedule.tick_sec = running.schedule.tick_sec;
+    loaded.log.format = running.log.format;
+    loaded.server = running.server.clone();
+}
+
+/// Settings that differ between two configurations, sorted by key
+pub fn diff(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    let (old, new) = (flatten(old), flatten(new));
+    let unset = || "(unset)".to_string();
+
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| {
+            let shown = |value: Option<&String>| match value {
+                None => unset(),
+                Some(_) if SECRET_KEYS.contains(&key.as_str()) => "(set)".to_string(),
+                Some(value) => value.clone(),
+            };
+            ConfigChange {
+                key: key.clone(),
+                old: shown(old.get(key)),
+                new: shown(new.get(key)),
+            }
+        })
+        .collect()
+}
+
+/// Every setting of `config` by dotted key (unset options left out)
+fn flatten(config: &Config) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            Value::Null => {}
+            Value::String(s) => {
+                out.insert(prefix.to_string(), s.clone());
+            }
+            other => {
+                out.insert(prefix.to_string(), other.to_string());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(config) {
+        walk("", &value, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_diff_lists_changed_keys_and_redacts_secrets() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.search.default_k = 20;
+        new.mcp.tools.insert("delete_session".to_string(), false);
+        new.mcp.auth_token = Some("hunter2".to_string());
+
+        let changes = diff(&old, &new);
+        let shown: Vec<String> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            shown,
+            [
+                "mcp.auth_token: (unset) -> (set)",
+                "mcp.tools.delete_session: (unset) -> false",
+                "search.default_k: 10 -> 20",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_keeps_restart_settings() {
+        let live = LiveConfig::new(Config::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&calls);
+        live.on_reload(move |config| {
+            assert_eq!(config.search.max_k, 50);
+            seen.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut loaded = Config::default();
+        loaded.search.max_k = 50;
+        loaded.storage.index_dir = PathBuf::from("/elsewhere");
+        loaded.limits.max_concurrent_indexes = 9;
+        loaded.server.read_only = true;
+        let report = live.replace(loaded).unwrap();
+
+        let keys = |changes: &[ConfigChange]| -> Vec<String> {
+            changes.iter().map(|c| c.key.clone()).collect()
+        };
+        assert_eq!(keys(&report.applied), ["search.max_k"]);
+        assert_eq!(
+            keys(&report.requires_restart),
+            [
+                "limits.max_concurrent_indexes",
+                "server.read_only",
+                "storage.index_dir"
+            ]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let current = live.current();
+        assert_eq!(current.search.max_k, 50);
+        // Nothing restart-only differs from the running configuration
+        assert!(diff(&Config::default(), &current)
+            .iter()
+            .all(|change| !is_restart_key(&change.key)));
+    }
+
+    #[test]
+    fn test_replace_rejects_invalid_config() {
+        let live = LiveConfig::new(Config::default());
+        let mut loaded = Config::default();
+        loaded.search.default_k = 0;
+
+        assert!(live.replace(loaded).is_err());
+        assert_eq!(live.current().search.default_k, 10);
+    }
+}
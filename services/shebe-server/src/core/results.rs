@@ -0,0 +1,381 @@
+//! Short-lived handles for recent search results.
+//!
+//! Follow-up tools (preview a chunk, read a file) need the file path and
+//! chunk index of a search hit. Copying them out of the search output is
+//! error prone, so searches record their hits here and label each with a
+//! handle (`r1`, `r2`, ...) that later requests can pass instead.
+//!
+//! - Handles are numbered across all sessions and never reused, so a
+//!   handle names one hit of one search, and a handle from an earlier
+//!   search stays valid while that search is cached
+//! - Only the last [`DEFAULT_MAX_QUERIES`] searches per session are kept,
+//!   each for [`DEFAULT_TTL`]
+//! - Each search records when the session was last indexed; handles from
+//!   before a re-index are rejected
+
+use crate::core::error::{Result, ShebeError};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a search's handles stay valid
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Searches kept per session
+pub const DEFAULT_MAX_QUERIES: usize = 20;
+
+/// Where a result handle points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultTarget {
+    /// Indexed (absolute) file path
+    pub file_path: String,
+    /// Chunk index within the file
+    pub chunk_index: usize,
+}
+
+/// Hits of one search
+#[derive(Debug)]
+struct CachedSearch {
+    /// Query as given by the client, for error messages
+    query: String,
+    /// Session's `last_indexed_at` when the search ran
+    indexed_at: Option<DateTime<Utc>>,
+    stored_at: Instant,
+    /// Number of the first hit's handle
+    first_id: usize,
+    /// Hits by handle; `None` for hits of other sessions in a merged search
+    hits: Vec<Option<ResultTarget>>,
+}
+
+impl CachedSearch {
+    fn get(&self, id: usize) -> Option<&ResultTarget> {
+        id.checked_sub(self.first_id)
+            .and_then(|offset| self.hits.get(offset))
+            .and_then(Option::as_ref)
+    }
+}
+
+/// Cached searches per session (oldest first) and the last handle issued
+#[derive(Debug, Default)]
+struct CacheState {
+    last_id: usize,
+    sessions: HashMap<String, VecDeque<CachedSearch>>,
+}
+
+/// Recent search hits per session, addressable by handle
+///
+/// Thread-safe; shared by all requests through [`crate::core::Services`].
+#[derive(Debug)]
+pub struct ResultCache {
+    ttl: Duration,
+    max_queries: usize,
+    state: Mutex<CacheState>,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL, DEFAULT_MAX_QUERIES)
+    }
+}
+
+impl ResultCache {
+    /// Cache keeping `max_queries` searches per session for `ttl`
+    pub fn new(ttl: Duration, max_queries: usize) -> Self {
+        Self {
+            ttl,
+            max_queries: max_queries.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Record the hits of a search, returning the first hit's handle number
+    ///
+    /// Hit `i` gets handle `r{first + i}`. `indexed_at` is the session's
+    /// last indexing time; handles are rejected once it changes.
+    pub fn store(
+        &self,
+        session: &str,
+        query: &str,
+        indexed_at: Option<DateTime<Utc>>,
+        hits: Vec<ResultTarget>,
+    ) -> usize {
+        let mut state = self.lock();
+        let first_id = state.last_id + 1;
+        state.last_id += hits.len();
+        let hits = hits.into_iter().map(Some).collect();
+        self.push(&mut state, session, query, indexed_at, first_id, hits);
+        first_id
+    }
+
+    /// Record the hits of a search over several sessions (a collection)
+    ///
+    /// Like [`Self::store`], but hit `i` belongs to the session it is
+    /// paired with and resolves only for that session. `indexed_at`
+    /// gives each session's last indexing time.
+    pub fn store_merged(
+        &self,
+        query: &str,
+        indexed_at: impl Fn(&str) -> Option<DateTime<Utc>>,
+        hits: Vec<(String, ResultTarget)>,
+    ) -> usize {
+        let mut state = self.lock();
+        let first_id = state.last_id + 1;
+        state.last_id += hits.len();
+
+        let count = hits.len();
+        let mut by_session: BTreeMap<String, Vec<Option<ResultTarget>>> = BTreeMap::new();
+        for (i, (session, target)) in hits.into_iter().enumerate() {
+            by_session
+                .entry(session)
+                .or_insert_with(|| vec![None; count])[i] = Some(target);
+        }
+        for (session, hits) in by_session {
+            let indexed_at = indexed_at(&session);
+            self.push(&mut state, &session, query, indexed_at, first_id, hits);
+        }
+        first_id
+    }
+
+    /// Add a search to `session`'s cache, dropping expired and evicted ones
+    fn push(
+        &self,
+        state: &mut CacheState,
+        session: &str,
+        query: &str,
+        indexed_at: Option<DateTime<Utc>>,
+        first_id: usize,
+        hits: Vec<Option<ResultTarget>>,
+    ) {
+        let searches = state.sessions.entry(session.to_string()).or_default();
+        searches.retain(|s| s.stored_at.elapsed() < self.ttl);
+        searches.push_back(CachedSearch {
+            query: query.to_string(),
+            indexed_at,
+            stored_at: Instant::now(),
+            first_id,
+            hits,
+        });
+        while searches.len() > self.max_queries {
+            searches.pop_front();
+        }
+    }
+
+    /// Resolve `handle` (e.g. `r3`) from a search of `session`
+    ///
+    /// `indexed_at` is the session's current last indexing time.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidResultHandle` when the handle is malformed, belongs to
+    /// another session, has expired or was evicted, or predates a
+    /// re-index of the session.
+    pub fn resolve(
+        &self,
+        session: &str,
+        handle: &str,
+        indexed_at: Option<DateTime<Utc>>,
+    ) -> Result<ResultTarget> {
+        let id = parse_handle(handle)?;
+        let state = self.lock();
+
+        let found = state
+            .sessions
+            .get(session)
+            .and_then(|searches| find_hit(searches, id));
+        let Some((search, target)) = found else {
+            let owner = state.sessions.iter().find(|(name, searches)| {
+                name.as_str() != session && find_hit(searches, id).is_some()
+            });
+            if let Some((other, _)) = owner {
+                return Err(ShebeError::InvalidResultHandle(format!(
+                    "{handle} is a result from session '{other}', not '{session}'"
+                )));
+            }
+            return Err(ShebeError::InvalidResultHandle(format!(
+                "{handle} is unknown or has expired (results are kept for {} minutes, \
+                 last {} searches per session); rerun the search",
+                self.ttl.as_secs() / 60,
+                self.max_queries
+            )));
+        };
+
+        if search.stored_at.elapsed() >= self.ttl {
+            return Err(ShebeError::InvalidResultHandle(format!(
+                "{handle} from search '{}' has expired; rerun the search",
+                search.query
+            )));
+        }
+        if search.indexed_at != indexed_at {
+            return Err(ShebeError::InvalidResultHandle(format!(
+                "{handle} from search '{}' predates the last re-index of session \
+                 '{session}'; rerun the search",
+                search.query
+            )));
+        }
+
+        Ok(target.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // A panic while holding the lock leaves the cache usable
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The cached search holding handle number `id`, and its hit
+fn find_hit(
+    searches: &VecDeque<CachedSearch>,
+    id: usize,
+) -> Option<(&CachedSearch, &ResultTarget)> {
+    searches.iter().find_map(|s| Some((s, s.get(id)?)))
+}
+
+/// Label of handle number `id` (`r{id}`)
+pub fn handle_label(id: usize) -> String {
+    format!("r{id}")
+}
+
+/// Parse a handle label (`r3`, case-insensitive) into its number
+fn parse_handle(handle: &str) -> Result<usize> {
+    handle
+        .trim()
+        .strip_prefix(['r', 'R'])
+        .and_then(|digits| digits.parse::<usize>().ok())
+        .filter(|&id| id > 0)
+        .ok_or_else(|| {
+            ShebeError::InvalidResultHandle(format!(
+                "'{handle}' is not a result handle (expected e.g. r3 from search_code)"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(paths: &[&str]) -> Vec<ResultTarget> {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| ResultTarget {
+                file_path: path.to_string(),
+                chunk_index: i,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_handles_continue_across_searches() {
+        let cache = ResultCache::default();
+        let indexed_at = Some(Utc::now());
+
+        assert_eq!(cache.store("s", "a", indexed_at, hits(&["/a", "/b"])), 1);
+        assert_eq!(cache.store("s", "b", indexed_at, hits(&["/c"])), 3);
+
+        assert_eq!(
+            cache.resolve("s", "r2", indexed_at).unwrap().file_path,
+            "/b"
+        );
+        assert_eq!(
+            cache.resolve("s", "R3", indexed_at).unwrap().file_path,
+            "/c"
+        );
+        assert_eq!(handle_label(3), "r3");
+    }
+
+    #[test]
+    fn test_merged_handles_resolve_per_session() {
+        let cache = ResultCache::default();
+        let indexed_at = Some(Utc::now());
+        let hit = |session: &str, path: &str| {
+            (
+                session.to_string(),
+                ResultTarget {
+                    file_path: path.to_string(),
+                    chunk_index: 0,
+                },
+            )
+        };
+
+        let first = cache.store_merged(
+            "q",
+            |_| indexed_at,
+            vec![hit("api", "/a"), hit("web", "/w"), hit("api", "/b")],
+        );
+
+        assert_eq!(first, 1);
+        assert_eq!(
+            cache.resolve("api", "r3", indexed_at).unwrap().file_path,
+            "/b"
+        );
+        assert_eq!(
+            cache.resolve("web", "r2", indexed_at).unwrap().file_path,
+            "/w"
+        );
+        let err = cache.resolve("api", "r2", indexed_at).unwrap_err();
+        assert!(err.to_string().contains("from session 'web'"));
+        assert_eq!(cache.store("api", "next", indexed_at, hits(&["/c"])), 4);
+    }
+
+    #[test]
+    fn test_oldest_searches_are_evicted() {
+        let cache = ResultCache::new(DEFAULT_TTL, 2);
+        for query in ["a", "b", "c"] {
+            cache.store("s", query, None, hits(&["/x"]));
+        }
+
+        let err = cache.resolve("s", "r1", None).unwrap_err();
+        assert!(err.to_string().contains("rerun the search"));
+        assert!(cache.resolve("s", "r2", None).is_ok());
+        assert!(cache.resolve("s", "r3", None).is_ok());
+    }
+
+    #[test]
+    fn test_expired_handle_is_rejected() {
+        let cache = ResultCache::new(Duration::ZERO, DEFAULT_MAX_QUERIES);
+        cache.store("s", "auth", None, hits(&["/x"]));
+
+        let err = cache.resolve("s", "r1", None).unwrap_err();
+        assert!(matches!(err, ShebeError::InvalidResultHandle(_)));
+        assert!(err.to_string().contains("expired; rerun the search"));
+    }
+
+    #[test]
+    fn test_reindex_invalidates_handles() {
+        let cache = ResultCache::default();
+        let before = Utc::now();
+        cache.store("s", "auth", Some(before), hits(&["/x"]));
+
+        let after = before + chrono::Duration::seconds(5);
+        let err = cache.resolve("s", "r1", Some(after)).unwrap_err();
+        assert!(err.to_string().contains("predates the last re-index"));
+    }
+
+    #[test]
+    fn test_handle_from_other_session_is_rejected() {
+        let cache = ResultCache::default();
+        cache.store("other", "auth", None, hits(&["/x", "/y"]));
+        assert_eq!(cache.store("mine", "auth", None, hits(&["/z"])), 3);
+
+        let err = cache.resolve("mine", "r1", None).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("r1 is a result from session 'other', not 'mine'"));
+        assert_eq!(cache.resolve("mine", "r3", None).unwrap().file_path, "/z");
+    }
+
+    #[test]
+    fn test_malformed_handles() {
+        let cache = ResultCache::default();
+        for handle in ["", "3", "r", "r0", "rx", "chunk-1"] {
+            let err = cache.resolve("s", handle, None).unwrap_err();
+            assert!(
+                err.to_string().contains("is not a result handle"),
+                "{handle}"
+            );
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Scheduled background re-indexing.
+//!
+//! Sessions can be kept fresh without explicit `reindex_session` calls.
+//! A session's schedule is the one set with `set_reindex_schedule`
+//! (stored in its metadata), or else its entry in the
+//! `[schedule.sessions]` configuration table. [`ReindexScheduler`]
+//! checks for due sessions every `schedule.tick_sec` and re-indexes
+//! each in a background job ([`crate::core::jobs`]) with the session's
+//! stored configuration.
+//!
+//! - Nothing runs unless `schedule.enabled` is set
+//! - A session that is already being indexed is skipped until a later tick
+//! - The next run counts from the later of the last indexing and the
+//!   last scheduled run, so a failed run waits for its next slot
+//!   instead of being retried every tick
+
+use crate::core::config::ScheduleConfig;
+use crate::core::jobs::{IndexJobRequest, JobId, JobState};
+use crate::core::services::Services;
+use crate::core::storage::SessionMetadata;
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Schedule value that disables re-indexing
+pub const SCHEDULE_OFF: &str = "off";
+
+/// Prefix of daily schedules (`daily@03:00`)
+const DAILY_PREFIX: &str = "daily@";
+
+const MINUTES_PER_HOUR: u64 = 60;
+const MINUTES_PER_DAY: u64 = 24 * MINUTES_PER_HOUR;
+
+/// When a session is re-indexed
+///
+/// Written as `off`, an interval (`30m`, `6h`, `2d`) or a daily time
+/// in UTC (`daily@03:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Schedule {
+    /// Never (overrides a configured schedule)
+    Off,
+    /// A fixed number of minutes after the last run
+    Every { minutes: u64 },
+    /// Once a day at a UTC time
+    DailyAt { hour: u32, minute: u32 },
+}
+
+impl Schedule {
+    /// Parse a schedule expression
+    ///
+    /// Accepts `off`, an interval such as `30m`, `6h` or `2d`, or a daily
+    /// UTC time `daily@HH:MM`; anything else is an error naming the forms.
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim().to_ascii_lowercase();
+        if expression == SCHEDULE_OFF {
+            return Ok(Schedule::Off);
+        }
+
+        if let Some(time) = expression.strip_prefix(DAILY_PREFIX) {
+            let parsed = NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+                format!("Invalid daily time '{time}': expected HH:MM (24-hour, UTC)")
+            })?;
+            return Ok(Schedule::DailyAt {
+                hour: chrono::Timelike::hour(&parsed),
+                minute: chrono::Timelike::minute(&parsed),
+            });
+        }
+
+        let invalid = || {
+            format!(
+                "Invalid schedule '{expression}': expected '{SCHEDULE_OFF}', an interval \
+                 such as '30m', '6h' or '2d', or '{DAILY_PREFIX}HH:MM'"
+            )
+        };
+        let split = expression.len().checked_sub(1).ok_or_else(invalid)?;
+        let (count, unit) = expression.split_at(split);
+        let count: u64 = count.parse().map_err(|_| invalid())?;
+        let unit_minutes = match unit {
+            "m" => 1,
+            "h" => MINUTES_PER_HOUR,
+            "d" => MINUTES_PER_DAY,
+            _ => return Err(invalid()),
+        };
+        if count == 0 {
+            return Err(format!(
+                "Invalid schedule '{expression}': interval must be non-zero"
+            ));
+        }
+        let minutes = count
+            .checked_mul(unit_minutes)
+            .filter(|minutes| TimeDelta::try_minutes(*minutes as i64).is_some())
+            .ok_or_else(|| format!("Invalid schedule '{expression}': interval is too long"))?;
+        Ok(Schedule::Every { minutes })
+    }
+
+    /// Whether the schedule ever runs
+    pub fn is_off(&self) -> bool {
+        matches!(self, Schedule::Off)
+    }
+
+    /// First run strictly after `base` (`None` when off)
+    pub fn next_after(&self, base: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match *self {
+            Schedule::Off => None,
+            Schedule::Every { minutes } => {
+                base.checked_add_signed(TimeDelta::try_minutes(minutes as i64)?)
+            }
+            Schedule::DailyAt { hour, minute } => {
+                let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+                let today = base.date_naive().and_time(time).and_utc();
+                if today > base {
+                    Some(today)
+                } else {
+                    today.checked_add_signed(TimeDelta::days(1))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Schedule::Off => f.write_str(SCHEDULE_OFF),
+            Schedule::Every { minutes } if minutes % MINUTES_PER_DAY == 0 => {
+                write!(f, "{}d", minutes / MINUTES_PER_DAY)
+            }
+            Schedule::Every { minutes } if minutes % MINUTES_PER_HOUR == 0 => {
+                write!(f, "{}h", minutes / MINUTES_PER_HOUR)
+            }
+            Schedule::Every { minutes } => write!(f, "{minutes}m"),
+            Schedule::DailyAt { hour, minute } => {
+                write!(f, "{DAILY_PREFIX}{hour:02}:{minute:02}")
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        Schedule::parse(&expression)
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(schedule: Schedule) -> Self {
+        schedule.to_string()
+    }
+}
+
+/// Record of a session's most recent scheduled re-index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledRun {
+    /// Background job that ran it (`None` if it could not be started)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub state: JobState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ScheduledRun {
+    /// When the run ended (or started, if it never finished)
+    pub fn reference_time(&self) -> DateTime<Utc> {
+        self.finished_at.unwrap_or(self.started_at)
+    }
+}
+
+/// Where a session's effective schedule comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleSource {
+    /// Set with `set_reindex_schedule`
+    Session,
+    /// The `[schedule.sessions]` configuration table
+    Config,
+}
+
+impl fmt::Display for ScheduleSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleSource::Session => f.write_str("set_reindex_schedule"),
+            ScheduleSource::Config => f.write_str("config"),
+        }
+    }
+}
+
+/// A session's schedule, next run and last scheduled run
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub schedule: Schedule,
+    pub source: ScheduleSource,
+    /// When the next run is due (`None` when off); may be in the past
+    /// if the run is waiting for the next tick or a free session
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<ScheduledRun>,
+    /// Whether the server runs schedules at all (`schedule.enabled`)
+    pub scheduler_enabled: bool,
+}
+
+/// The schedule that applies to a session and where it comes from
+pub fn effective_schedule(
+    metadata: &SessionMetadata,
+    config: &ScheduleConfig,
+) -> Option<(Schedule, ScheduleSource)> {
+    metadata
+        .reindex_schedule
+        .map(|schedule| (schedule, ScheduleSource::Session))
+        .or_else(|| {
+            config
+                .schedule_for(&metadata.id)
+                .map(|schedule| (schedule, ScheduleSource::Config))
+        })
+}
+
+/// When a session is next due under `schedule`
+pub fn next_run(metadata: &SessionMetadata, schedule: Schedule) -> Option<DateTime<Utc>> {
+    let base = metadata
+        .last_scheduled_run
+        .as_ref()
+        .map_or(metadata.last_indexed_at, |run| {
+            run.reference_time().max(metadata.last_indexed_at)
+        });
+    schedule.next_after(base)
+}
+
+/// Schedule status of a session (`None` if it was never scheduled)
+pub fn schedule_status(
+    metadata: &SessionMetadata,
+    config: &ScheduleConfig,
+) -> Option<ScheduleStatus> {
+    let (schedule, source) = effective_schedule(metadata, config)?;
+    Some(ScheduleStatus {
+        schedule,
+        source,
+        next_run: next_run(metadata, schedule),
+        last_run: metadata.last_scheduled_run.clone(),
+        scheduler_enabled: config.enabled,
+    })
+}
+
+/// Re-indexes sessions whose schedule is due
+///
+/// Errors are logged and recorded in the session's last scheduled run;
+/// they never stop the scheduler.
+pub struct ReindexScheduler {
+    services: Arc<Services>,
+    /// Jobs started by the scheduler and not yet recorded as finished
+    pending: HashMap<String, JobId>,
+}
+
+impl ReindexScheduler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self {
+            services,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Run the scheduler on the current tokio runtime
+    pub fn spawn(services: Arc<Services>) -> tokio::task::JoinHandle<()> {
+        let tick = Duration::from_secs(services.config.current().schedule.tick_sec);
+        tracing::info!(
+            "Reindex scheduler started (checking every {}s)",
+            tick.as_secs()
+        );
+        tokio::spawn(Self::new(services).run(tick))
+    }
+
+    async fn run(mut self, tick: Duration) {
+        let mut interval = tokio::time::interval(tick);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.tick(Utc::now());
+        }
+    }
+
+    /// Record finished runs, then start runs due at `now`
+    ///
+    /// Returns the sessions a job was started for.
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        self.collect_finished();
+
+        let sessions = match self.services.storage.list_sessions() {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                tracing::warn!("Reindex scheduler could not list sessions: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut started = Vec::new();
+        for metadata in sessions {
+            if self.pending.contains_key(&metadata.id) {
+                continue;
+            }
+            let Some((schedule, _)) =
+                effective_schedule(&metadata, &self.services.config.current().schedule)
+            else {
+                continue;
+            };
+            if next_run(&metadata, schedule).is_none_or(|due| due > now) {
+                continue;
+            }
+            if let Some(active) = self.services.jobs.active_job(&metadata.id) {
+                tracing::debug!(
+                    "Scheduled reindex of '{}' deferred: {} is indexing it",
+                    metadata.id,
+                    active
+                );
+                continue;
+            }
+            if self.start(&metadata, now) {
+                started.push(metadata.id);
+            }
+        }
+        started
+    }
+
+    /// Start a re-index job for a due session, returning whether it started
+    fn start(&mut self, metadata: &SessionMetadata, now: DateTime<Utc>) -> bool {
+        let started = IndexJobRequest::reindex(metadata)
+            .and_then(|request| self.services.start_index_job(request));
+
+        match started {
+            Ok(job_id) => {
+                tracing::info!(
+                    "Scheduled reindex of '{}' started as {}",
+                    metadata.id,
+                    job_id
+                );
+                // Recorded once finished: the job rewrites the metadata meanwhile
+                self.pending.insert(metadata.id.clone(), job_id);
+                true
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Scheduled reindex of '{}' failed to start: {}",
+                    metadata.id,
+                    e
+                );
+                let run = ScheduledRun {
+                    job_id: None,
+                    started_at: now,
+                    finished_at: Some(now),
+                    state: JobState::Failed,
+                    error: Some(e.to_string()),
+                };
+                self.record(&metadata.id, run);
+                false
+            }
+        }
+    }
+
+    /// Record the outcome of scheduled jobs that have finished
+    fn collect_finished(&mut self) {
+        let mut finished = Vec::new();
+        for (session, job_id) in &self.pending {
+            let run = match self.services.jobs.status(job_id) {
+                Ok(status) if !status.state.is_finished() => continue,
+                Ok(status) => ScheduledRun {
+                    job_id: Some(job_id.to_string()),
+                    started_at: status.started_at.unwrap_or(status.created_at),
+                    finished_at: Some(status.finished_at.unwrap_or_else(Utc::now)),
+                    state: status.state,
+                    error: status.error,
+                },
+                // Expired before this tick saw it finish
+                Err(e) => ScheduledRun {
+                    job_id: Some(job_id.to_string()),
+                    started_at: Utc::now(),
+                    finished_at: Some(Utc::now()),
+                    state: JobState::Failed,
+                    error: Some(e.to_string()),
+                },
+            };
+            finished.push((session.clone(), run));
+        }
+
+        for (session, run) in finished {
+            self.pending.remove(&session);
+            match run.state {
+                JobState::Completed => {
+                    tracing::info!("Scheduled reindex of '{}' completed", session)
+                }
+                state => tracing::warn!(
+                    "Scheduled reindex of '{}' {}: {}",
+                    session,
+                    state.as_str(),
+                    run.error.as_deref().unwrap_or("no error reported")
+                ),
+            }
+            self.record(&session, run);
+        }
+    }
+
+    fn record(&self, session: &str, run: ScheduledRun) {
+        if let Err(e) = self.services.storage.record_scheduled_run(session, run) {
+            tracing::warn!("Could not record scheduled run of '{}': {}", session, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_intervals() {
+        assert_eq!(Schedule::parse("30m"), Ok(Schedule::Every { minutes: 30 }));
+        assert_eq!(
+            Schedule::parse(" 6H "),
+            Ok(Schedule::Every { minutes: 360 })
+        );
+        assert_eq!(
+            Schedule::parse("2d"),
+            Ok(Schedule::Every { minutes: 2 * 1440 })
+        );
+        assert_eq!(Schedule::parse("off"), Ok(Schedule::Off));
+    }
+
+    #[test]
+    fn test_parse_daily() {
+        assert_eq!(
+            Schedule::parse("daily@03:00"),
+            Ok(Schedule::DailyAt { hour: 3, minute: 0 })
+        );
+        assert!(Schedule::parse("daily@25:00").is_err());
+        assert!(Schedule::parse("daily@3am").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for expression in [
+            "",
+            "h",
+            "0h",
+            "6",
+            "6w",
+            "-1h",
+            "six hours",
+            "99999999999999d",
+        ] {
+            assert!(
+                Schedule::parse(expression).is_err(),
+                "'{expression}' should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for expression in ["off", "45m", "90m", "6h", "1d", "daily@03:05"] {
+            let schedule = Schedule::parse(expression).unwrap();
+            assert_eq!(Schedule::parse(&schedule.to_string()), Ok(schedule));
+        }
+        assert_eq!(Schedule::parse("120m").unwrap().to_string(), "2h");
+        assert_eq!(Schedule::parse("48h").unwrap().to_string(), "2d");
+    }
+
+    #[test]
+    fn test_next_after() {
+        let every = Schedule::parse("6h").unwrap();
+        assert_eq!(every.next_after(at(10, 0)), Some(at(16, 0)));
+
+        let daily = Schedule::parse("daily@03:00").unwrap();
+        assert_eq!(daily.next_after(at(1, 0)), Some(at(3, 0)));
+        assert_eq!(
+            daily.next_after(at(3, 0)),
+            Some(at(3, 0) + TimeDelta::days(1))
+        );
+        assert_eq!(
+            daily.next_after(at(10, 0)),
+            Some(at(3, 0) + TimeDelta::days(1))
+        );
+
+        assert_eq!(Schedule::Off.next_after(at(10, 0)), None);
+    }
+}
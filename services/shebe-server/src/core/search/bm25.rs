@@ -2,71 +2,1110 @@
 //!
 //! This module provides the SearchService for executing BM25-ranked
 //! queries over indexed content.
+//!
+//! # Concurrency
+//!
+//! `SearchService` is `Send + Sync` and holds no locks of its own.
+//! Each query gets the session's shared reader from
+//! [`StorageManager::session_reader`] and searches a snapshot of it, so
+//! concurrent searches on different sessions do not block each other
+//! and throughput grows with the number of cores. Searches are
+//! CPU-bound and synchronous; async callers should run them on
+//! `tokio::task::spawn_blocking`.
 
+use super::exclusions::Exclusions;
+use super::explain::{self, MAX_EXPLAINED_RESULTS};
+use super::fuzzy::{self, MAX_FUZZINESS};
+use super::identifiers::expand_identifiers;
+use super::overlap::{drop_overlap_duplicates, MatchLocator};
+use super::recency::{modified_since_query, parse_modified_within, MODIFIED_AT_FIELD};
+use super::refine::DocSetQuery;
+use super::scoring::{self, Bm25Params};
+use super::sections::section_filter;
+use super::short_chunks::{demoting_top_docs, ShortChunkPenalty, CHAR_COUNT_FIELD};
+use super::similar::{
+    self, DEFAULT_MIN_TERM_LEN, DEFAULT_SEED_TERMS, MAX_SEED_TERMS, MIN_SHARED_TERMS,
+};
 use crate::core::error::{Result, ShebeError};
-use crate::core::storage::StorageManager;
-use crate::core::types::{SearchRequest, SearchResponse, SearchResult};
-use std::sync::Arc;
+use crate::core::indexer::records::stored_records;
+use crate::core::indexer::sections::stored_section;
+use crate::core::logging::time_phase;
+use crate::core::paths;
+use crate::core::storage::{SessionReader, StorageManager, TantivyIndex};
+use crate::core::types::{
+    ExclusionFilter, FileGroup, FuzzyExpansion, RecencyFilter, Refinement, ScoreExplanation,
+    SearchRequest, SearchResponse, SearchResult, SearchWithin, SimilarRequest, SimilarResponse,
+    SimilarResult,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::ErrorKind;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 use std::time::Instant;
 use tantivy::{
-    collector::TopDocs,
-    query::QueryParser,
-    schema::{Field, Value},
-    TantivyDocument,
+    collector::{Count, TopDocs},
+    query::{BooleanQuery, BoostQuery, EnableScoring, Occur, Query, QueryParser, TermQuery},
+    schema::{Field, IndexRecordOption, Value},
+    DocAddress, DocId, DocSet, Score, Searcher, TantivyDocument, Term,
 };
 
+/// Candidate chunks fetched per requested file in grouped mode
+const GROUP_CANDIDATES_PER_FILE: usize = 10;
+
+/// Upper bound on candidate chunks fetched in grouped mode
+const MAX_GROUP_CANDIDATES: usize = 2000;
+
+/// Upper bound on candidate chunks fetched to backfill overlap duplicates
+const MAX_OVERLAP_CANDIDATES: usize = 2000;
+
+/// Candidate chunks fetched per requested result of a similar-chunks search
+const SIMILAR_CANDIDATES_PER_RESULT: usize = 5;
+
+/// Upper bound on candidate chunks fetched for a similar-chunks search
+const MAX_SIMILAR_CANDIDATES: usize = 500;
+
+/// A session index opened for searching
+///
+/// The searcher is a snapshot of the committed segments, shared by
+/// every query run against it.
+struct OpenSession {
+    session_id: String,
+    reader: Arc<SessionReader>,
+    searcher: Searcher,
+}
+
+impl OpenSession {
+    fn index(&self) -> &TantivyIndex {
+        self.reader.index()
+    }
+}
+
+/// How one query's hits are scored
+#[derive(Debug, Clone, Copy)]
+struct Scoring {
+    /// Weight of query matches in the file path (0 disables)
+    path_boost: f32,
+    /// Edit distance for fuzzy term matching (0 disables)
+    fuzziness: u8,
+    /// BM25 parameters for the chunk text
+    bm25: Bm25Params,
+    /// Chunks shorter than this many characters score times the
+    /// penalty factor (`None` disables)
+    short_chunks: Option<(u64, f32)>,
+    /// Only chunks of files changed at or after this time (`None`: all)
+    modified_since: Option<DateTime<Utc>>,
+}
+
+/// Chunks a query may return besides matching it
+#[derive(Clone, Copy, Default)]
+struct SearchFilter<'a> {
+    /// Only these chunks (`within`, `section_filter`)
+    keep: Option<&'a DocSetQuery>,
+    /// None of these chunks (`exclude_terms`, `exclude_paths`)
+    exclude: Option<&'a dyn Query>,
+}
+
+/// Reject empty and whitespace-only queries
+fn validate_query(query_str: &str) -> Result<()> {
+    if query_str.trim().is_empty() {
+        return Err(ShebeError::InvalidQuery(
+            "Query cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Result counts, path boost and short-chunk penalty applied when a
+/// request leaves them out
+#[derive(Debug, Clone, Copy)]
+struct SearchDefaults {
+    default_k: usize,
+    max_k: usize,
+    path_boost: f32,
+    /// Penalty for short chunks (`None` disables)
+    short_chunks: Option<ShortChunkPenalty>,
+}
+
 /// BM25 search service
 pub struct SearchService {
     storage: Arc<StorageManager>,
-    default_k: usize,
-    max_k: usize,
+    defaults: RwLock<SearchDefaults>,
 }
 
 impl SearchService {
     /// Create a new search service
+    ///
+    /// Path boosting and the short-chunk penalty are disabled until set
+    /// with [`Self::with_path_boost`] and [`Self::with_short_chunk_penalty`].
     pub fn new(storage: Arc<StorageManager>, default_k: usize, max_k: usize) -> Self {
         Self {
             storage,
+            defaults: RwLock::new(SearchDefaults {
+                default_k,
+                max_k,
+                path_boost: 0.0,
+                short_chunks: None,
+            }),
+        }
+    }
+
+    /// Set the default boost for query matches in the file path
+    ///
+    /// Chunks whose file path also matches the query get the path
+    /// match score, multiplied by `path_boost`, added to their content
+    /// score. Files matched only by path are never returned. A boost
+    /// of 0 keeps plain content scoring.
+    pub fn with_path_boost(self, path_boost: f32) -> Self {
+        self.write_defaults().path_boost = path_boost;
+        self
+    }
+
+    /// Set the default penalty for very short chunks
+    ///
+    /// Chunks shorter than the penalty's share of the session's chunk
+    /// size have their score multiplied by its factor before ranking
+    /// (see [`super::short_chunks`]). `None` keeps plain scores.
+    pub fn with_short_chunk_penalty(self, penalty: Option<ShortChunkPenalty>) -> Self {
+        self.write_defaults().short_chunks = penalty;
+        self
+    }
+
+    /// Replace the default and maximum result counts, the path boost
+    /// and the short-chunk penalty
+    ///
+    /// Used when the configuration is reloaded; searches already
+    /// running keep the values they started with.
+    pub fn set_defaults(
+        &self,
+        default_k: usize,
+        max_k: usize,
+        path_boost: f32,
+        short_chunks: Option<ShortChunkPenalty>,
+    ) {
+        *self.write_defaults() = SearchDefaults {
             default_k,
             max_k,
-        }
+            path_boost,
+            short_chunks,
+        };
+    }
+
+    /// Results kept for a request asking for `k` (default and limit applied)
+    pub(super) fn result_limit(&self, k: Option<usize>) -> usize {
+        let defaults = self.defaults();
+        k.unwrap_or(defaults.default_k).min(defaults.max_k)
+    }
+
+    fn defaults(&self) -> SearchDefaults {
+        *self.defaults.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_defaults(&self) -> RwLockWriteGuard<'_, SearchDefaults> {
+        self.defaults.write().unwrap_or_else(|e| e.into_inner())
     }
 
     /// Execute a search query
-    pub fn search(&self, request: SearchRequest) -> Result<SearchResponse> {
-        self.search_session(&request.session, &request.query, request.k)
+    ///
+    /// `request.path_boost` overrides the service default. A non-zero
+    /// `request.fuzziness` also matches indexed terms within that edit
+    /// distance (see [`super::fuzzy`]). With `request.expand_identifiers`,
+    /// identifier terms also match their other spellings (see
+    /// [`super::identifiers`]). `request.bm25_k1` and `request.bm25_b`
+    /// override the session's BM25 parameters (see [`super::scoring`]).
+    /// With `request.within`, only chunks in the inner query's top hits
+    /// are searched (see [`super::refine`]). `request.penalize_short_chunks`
+    /// turns the short-chunk penalty on or off (see [`super::short_chunks`]).
+    /// `request.modified_within` keeps only files changed in that window
+    /// (see [`super::recency`]). `request.section_filter` keeps only
+    /// chunks under matching document headings (see [`super::sections`]).
+    /// `request.explain` breaks down the scores of the top results per
+    /// term (see [`super::explain`]).
+    pub fn search(&self, mut request: SearchRequest) -> Result<SearchResponse> {
+        self.validate_request(&request)?;
+        let open = time_phase("index_open", || self.open_session(&request.session))?;
+        open.reader.search_defaults().apply(&mut request);
+        time_phase("search", || self.run_request(&open, &request))
+    }
+
+    /// Execute several queries against one session
+    ///
+    /// The session index and searcher are opened once and shared by
+    /// all queries, which run in order. Each query gets its own
+    /// result: an invalid query fails only its slot. A request for a
+    /// session other than `session_id` fails with `InvalidSession`.
+    /// The whole batch fails only when the session cannot be opened.
+    pub fn search_batch(
+        &self,
+        session_id: &str,
+        requests: &[SearchRequest],
+    ) -> Result<Vec<Result<SearchResponse>>> {
+        let open = time_phase("index_open", || self.open_session(session_id))?;
+
+        Ok(requests
+            .iter()
+            .map(|request| {
+                if request.session != session_id {
+                    return Err(ShebeError::InvalidSession(format!(
+                        "Batch query for session '{}' in a batch for '{session_id}'",
+                        request.session
+                    )));
+                }
+                self.validate_request(request)?;
+                let mut request = request.clone();
+                open.reader.search_defaults().apply(&mut request);
+                time_phase("search", || self.run_request(&open, &request))
+            })
+            .collect())
+    }
+
+    /// Check request options
+    fn validate_request(&self, request: &SearchRequest) -> Result<()> {
+        let path_boost = request.path_boost.unwrap_or(self.defaults().path_boost);
+        if !path_boost.is_finite() || path_boost < 0.0 {
+            return Err(ShebeError::InvalidQuery(format!(
+                "path_boost must be a non-negative number, got {path_boost}"
+            )));
+        }
+        if let Some(fuzziness) = request.fuzziness.filter(|&f| f > MAX_FUZZINESS) {
+            return Err(ShebeError::InvalidQuery(format!(
+                "fuzziness must be between 0 and {MAX_FUZZINESS}, got {fuzziness}"
+            )));
+        }
+        Bm25Params::default()
+            .with_overrides(request.bm25_k1, request.bm25_b)
+            .validate()
+            .map_err(|e| match e {
+                ShebeError::ConfigError(message) => ShebeError::InvalidQuery(message),
+                other => other,
+            })?;
+        validate_query(&request.query)?;
+        if let Some(within) = &request.within {
+            validate_query(&within.query)?;
+            if within.k == Some(0) {
+                return Err(ShebeError::InvalidQuery(
+                    "within.k must be at least 1".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a validated request against an opened session
+    ///
+    /// The session's search defaults must already be applied.
+    fn run_request(&self, open: &OpenSession, request: &SearchRequest) -> Result<SearchResponse> {
+        let path_boost = request.path_boost.unwrap_or(self.defaults().path_boost);
+        let (query_str, identifier_expansions) = if request.expand_identifiers {
+            expand_identifiers(&request.query)
+        } else {
+            (request.query.clone(), Vec::new())
+        };
+
+        let penalty = match request.penalize_short_chunks {
+            Some(false) => None,
+            Some(true) => Some(self.defaults().short_chunks.unwrap_or_default()),
+            None => self.defaults().short_chunks,
+        };
+        let scoring = Scoring {
+            path_boost,
+            fuzziness: request.fuzziness.unwrap_or(0),
+            bm25: open
+                .reader
+                .bm25_params()
+                .with_overrides(request.bm25_k1, request.bm25_b),
+            short_chunks: Self::short_chunk_threshold(open, penalty),
+            modified_since: match &request.modified_within {
+                Some(window) => Some(Self::modified_since(open, window)?),
+                None => None,
+            },
+        };
+        let filter = match &request.within {
+            Some(within) => Some(self.within_filter(open, within, request, scoring)?),
+            None => None,
+        };
+        let sections = match &request.section_filter {
+            Some(text) => Some(section_filter(
+                &open.searcher,
+                open.index().schema(),
+                &open.session_id,
+                text,
+            )?),
+            None => None,
+        };
+        let keep = match (filter.as_ref().map(|(query, _)| query), &sections) {
+            (Some(within), Some(sections)) => Some(within.intersect(sections)),
+            (within, sections) => within.or(sections.as_ref()).cloned(),
+        };
+        let exclusions = if request.exclude_terms.is_empty() && request.exclude_paths.is_empty() {
+            None
+        } else {
+            let exclusions = Exclusions::new(
+                open.index(),
+                &open.searcher,
+                open.reader.repository_path(),
+                &request.exclude_terms,
+                &request.exclude_paths,
+            )?;
+            // Before identifier expansion, whose extra spellings would
+            // otherwise let an excluded term through
+            exclusions.check_query_terms(&Self::query_terms(open, &request.query)?)?;
+            Some(exclusions)
+        };
+        let filter_query = SearchFilter {
+            keep: keep.as_ref(),
+            exclude: exclusions.as_ref().and_then(|e| e.query.as_deref()),
+        };
+
+        let mut response = if request.group_by_file {
+            self.run_grouped(open, &query_str, request.k, scoring, filter_query)?
+        } else {
+            self.run_ungrouped(
+                open,
+                &query_str,
+                request.k,
+                request.dedupe_overlaps,
+                scoring,
+                filter_query,
+            )?
+        };
+        if let (Some(within), Some((filter_query, inner_matches))) = (&request.within, &filter) {
+            let content_query =
+                Self::content_query(open, &query_str, scoring, &response.fuzzy_expansions)?;
+            let mut clauses = vec![
+                (Occur::Must, content_query),
+                (
+                    Occur::Must,
+                    Box::new(filter_query.clone()) as Box<dyn Query>,
+                ),
+            ];
+            if let Some(since) = scoring.modified_since {
+                clauses.push((Occur::Must, modified_since_query(since)));
+            }
+            let refined = BooleanQuery::new(clauses);
+            let survived = open
+                .searcher
+                .search(&refined, &Count)
+                .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+            response.refinement = Some(Refinement {
+                within_query: within.query.clone(),
+                inner_matches: *inner_matches,
+                survived,
+            });
+        }
+        if let (Some(window), Some(since)) = (&request.modified_within, scoring.modified_since) {
+            response.recency = Some(RecencyFilter {
+                window: window.trim().to_string(),
+                since,
+            });
+        }
+        if exclusions.is_some() {
+            response.exclusions = Some(ExclusionFilter {
+                terms: request.exclude_terms.clone(),
+                paths: request.exclude_paths.clone(),
+                removed: match filter_query.exclude {
+                    Some(exclude) => Self::count_excluded(
+                        open,
+                        &query_str,
+                        scoring,
+                        &response.fuzzy_expansions,
+                        keep.as_ref(),
+                        exclude,
+                    )?,
+                    None => 0,
+                },
+            });
+        }
+        if sections.is_some() {
+            response.section_filter = request
+                .section_filter
+                .as_ref()
+                .map(|s| s.trim().to_string());
+        }
+        if request.explain {
+            Self::explain_results(open, &query_str, scoring, &mut response)?;
+        }
+        response.query = request.query.clone();
+        response.identifier_expansions = identifier_expansions;
+        Ok(response)
+    }
+
+    /// Attach a per-term score breakdown to the top results
+    ///
+    /// The content and path queries are explained separately, as
+    /// [`Self::collect_hits`] adds their scores; filters score nothing
+    /// and are left out. A session whose index cannot be explained
+    /// keeps plain results, with the reason in `explain_skipped`.
+    fn explain_results(
+        open: &OpenSession,
+        query_str: &str,
+        scoring: Scoring,
+        response: &mut SearchResponse,
+    ) -> Result<()> {
+        let (index, searcher) = (open.index(), &open.searcher);
+        if let Some(reason) = explain::missing_requirement(index.schema()) {
+            tracing::warn!(
+                "Ignoring explain for session '{}': {}",
+                open.session_id,
+                reason
+            );
+            response.explain_skipped = Some(reason);
+            return Ok(());
+        }
+
+        let weight = |query: &dyn Query| {
+            query
+                .weight(EnableScoring::enabled_from_searcher(searcher))
+                .map_err(|e| ShebeError::SearchFailed(format!("Failed to build query: {e}")))
+        };
+        let content = Self::content_query(open, query_str, scoring, &response.fuzzy_expansions)?;
+        let content = weight(content.as_ref())?;
+        let path = match Self::parse_path_query(index, query_str)? {
+            Some(path_query) if scoring.path_boost > 0.0 => Some(weight(path_query.as_ref())?),
+            _ => None,
+        };
+
+        for result in response.results.iter_mut().take(MAX_EXPLAINED_RESULTS) {
+            let chunk = index.chunk_query(&result.file_path, result.chunk_index)?;
+            let found = searcher
+                .search(&chunk, &TopDocs::with_limit(1))
+                .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+            let Some((_, address)) = found.first() else {
+                continue;
+            };
+            let segment = searcher.segment_reader(address.segment_ord);
+            let Ok(explanation) = content.explain(segment, address.doc_id) else {
+                continue;
+            };
+
+            let mut terms = explain::term_contributions(&explanation, false, 1.0);
+            if let Some(path) = path.as_ref().filter(|_| result.matched_in_path) {
+                if let Ok(explanation) = path.explain(segment, address.doc_id) {
+                    terms.extend(explain::term_contributions(
+                        &explanation,
+                        true,
+                        scoring.path_boost,
+                    ));
+                }
+            }
+            result.explanation = Some(ScoreExplanation {
+                terms,
+                short_chunk_factor: scoring
+                    .short_chunks
+                    .filter(|_| result.short_chunk_demoted)
+                    .map(|(_, factor)| factor),
+            });
+        }
+
+        // Grouped results are copies of each group's best hit
+        for (group, result) in response.groups.iter_mut().zip(&response.results) {
+            group.hits[0].explanation = result.explanation.clone();
+        }
+        Ok(())
+    }
+
+    /// Start of a request's `modified_within` window
+    ///
+    /// # Errors
+    ///
+    /// `InvalidQuery` for a malformed window or a session indexed
+    /// before change times were recorded.
+    fn modified_since(open: &OpenSession, window: &str) -> Result<DateTime<Utc>> {
+        let since = parse_modified_within(window, Utc::now())?;
+        if open.reader.modified_times().is_none() {
+            return Err(ShebeError::InvalidQuery(format!(
+                "Session '{}' was indexed without file change times; \
+                 re-index it to filter by modified_within",
+                open.session_id
+            )));
+        }
+        Ok(since)
+    }
+
+    /// Run a request's `within` query and build the filter for its top hits
+    ///
+    /// The inner query gets the request's identifier expansion and BM25
+    /// parameters, but no fuzzy matching or path boost. It keeps its
+    /// top `within.k` chunks (default `k`, clamped to `max_k`). Returns
+    /// the filter and the number of chunks it admits.
+    fn within_filter(
+        &self,
+        open: &OpenSession,
+        within: &SearchWithin,
+        request: &SearchRequest,
+        scoring: Scoring,
+    ) -> Result<(DocSetQuery, usize)> {
+        let inner_str = if request.expand_identifiers {
+            expand_identifiers(&within.query).0
+        } else {
+            within.query.clone()
+        };
+        let inner_scoring = Scoring {
+            path_boost: 0.0,
+            fuzziness: 0,
+            short_chunks: None,
+            modified_since: None,
+            ..scoring
+        };
+        let inner_query = Self::content_query(open, &inner_str, inner_scoring, &[])?;
+        let defaults = self.defaults();
+        let limit = within.k.unwrap_or(defaults.default_k).min(defaults.max_k);
+        let addresses: Vec<DocAddress> = open
+            .searcher
+            .search(&inner_query, &TopDocs::with_limit(limit))
+            .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?
+            .into_iter()
+            .map(|(_, address)| address)
+            .collect();
+        Ok((
+            DocSetQuery::new(&open.searcher, &addresses),
+            addresses.len(),
+        ))
     }
 
     /// Execute search with explicit parameters
+    ///
+    /// Chunk overlap duplicates are dropped (see [`Self::search_session_with`]).
     pub fn search_session(
         &self,
         session_id: &str,
         query_str: &str,
         k: Option<usize>,
+    ) -> Result<SearchResponse> {
+        self.search_session_with(session_id, query_str, k, true)
+    }
+
+    /// Execute search, optionally dropping chunk overlap duplicates
+    ///
+    /// With `dedupe_overlaps`, a hit whose matches all lie in the
+    /// region it shares with a better-scoring adjacent chunk of the
+    /// same file is dropped. The window of candidate chunks is doubled
+    /// until `k` hits survive, the index runs out of hits, or
+    /// `MAX_OVERLAP_CANDIDATES` is reached.
+    pub fn search_session_with(
+        &self,
+        session_id: &str,
+        query_str: &str,
+        k: Option<usize>,
+        dedupe_overlaps: bool,
+    ) -> Result<SearchResponse> {
+        validate_query(query_str)?;
+        let open = self.open_session(session_id)?;
+        let scoring = self.session_scoring(&open);
+        self.run_ungrouped(
+            &open,
+            query_str,
+            k,
+            dedupe_overlaps,
+            scoring,
+            SearchFilter::default(),
+        )
+    }
+
+    fn run_ungrouped(
+        &self,
+        open: &OpenSession,
+        query_str: &str,
+        k: Option<usize>,
+        dedupe_overlaps: bool,
+        scoring: Scoring,
+        filter: SearchFilter<'_>,
+    ) -> Result<SearchResponse> {
+        let start = Instant::now();
+
+        // Determine k (result limit)
+        let defaults = self.defaults();
+        let k_limit = k.unwrap_or(defaults.default_k).min(defaults.max_k);
+
+        let expansions = Self::expand_query(open, query_str, scoring.fuzziness)?;
+        let (mut results, overlap_duplicates_dropped) = if dedupe_overlaps {
+            let mut locator = MatchLocator::new(open.index(), query_str)?;
+            locator.add_terms(expansions.iter().flat_map(|e| e.matches.iter().cloned()));
+            let mut chunk_limit = k_limit;
+            loop {
+                let hits =
+                    Self::collect_hits(open, query_str, chunk_limit, scoring, &expansions, filter)?;
+                let exhausted = hits.len() < chunk_limit;
+                let (kept, dropped) =
+                    drop_overlap_duplicates(hits, k_limit, |hit| locator.spans(hit));
+
+                if kept.len() >= k_limit || exhausted || chunk_limit >= MAX_OVERLAP_CANDIDATES {
+                    break (kept, dropped);
+                }
+                chunk_limit = (chunk_limit * 2).min(MAX_OVERLAP_CANDIDATES);
+            }
+        } else {
+            (
+                Self::collect_hits(open, query_str, k_limit, scoring, &expansions, filter)?,
+                0,
+            )
+        };
+        self.attach_manifest_details(&open.session_id, results.iter_mut())?;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let count = results.len();
+        let short_chunks_demoted = results.iter().filter(|r| r.short_chunk_demoted).count();
+
+        Ok(SearchResponse {
+            query: query_str.to_string(),
+            results,
+            count,
+            duration_ms,
+            groups: Vec::new(),
+            overlap_duplicates_dropped,
+            short_chunks_demoted,
+            fuzziness: scoring.fuzziness,
+            fuzzy_expansions: expansions,
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: open.reader.modified_times(),
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        })
+    }
+
+    /// Execute search and collapse hits to one entry per file
+    ///
+    /// `k` is the number of files to return (clamped to `max_k`).
+    /// Files are ranked by their best chunk score. Candidate chunks
+    /// are fetched in a window of `k * GROUP_CANDIDATES_PER_FILE`,
+    /// doubled until `k` distinct files are found, the index runs
+    /// out of hits, or `MAX_GROUP_CANDIDATES` is reached. Per-file
+    /// chunk counts only cover hits inside that window.
+    pub fn search_session_grouped(
+        &self,
+        session_id: &str,
+        query_str: &str,
+        k: Option<usize>,
+    ) -> Result<SearchResponse> {
+        validate_query(query_str)?;
+        let open = self.open_session(session_id)?;
+        let scoring = self.session_scoring(&open);
+        self.run_grouped(&open, query_str, k, scoring, SearchFilter::default())
+    }
+
+    fn run_grouped(
+        &self,
+        open: &OpenSession,
+        query_str: &str,
+        k: Option<usize>,
+        scoring: Scoring,
+        filter: SearchFilter<'_>,
     ) -> Result<SearchResponse> {
         let start = Instant::now();
 
-        // Validate query
-        if query_str.trim().is_empty() {
+        let defaults = self.defaults();
+        let file_limit = k.unwrap_or(defaults.default_k).min(defaults.max_k);
+
+        let expansions = Self::expand_query(open, query_str, scoring.fuzziness)?;
+
+        let mut chunk_limit = (file_limit * GROUP_CANDIDATES_PER_FILE).min(MAX_GROUP_CANDIDATES);
+        let mut groups = loop {
+            let hits =
+                Self::collect_hits(open, query_str, chunk_limit, scoring, &expansions, filter)?;
+            let exhausted = hits.len() < chunk_limit;
+            let groups = group_hits_by_file(hits);
+
+            if groups.len() >= file_limit || exhausted || chunk_limit >= MAX_GROUP_CANDIDATES {
+                break groups;
+            }
+            chunk_limit = (chunk_limit * 2).min(MAX_GROUP_CANDIDATES);
+        };
+        groups.truncate(file_limit);
+        self.attach_manifest_details(
+            &open.session_id,
+            groups.iter_mut().flat_map(|g| &mut g.hits),
+        )?;
+
+        let results: Vec<SearchResult> = groups.iter().map(|g| g.best().clone()).collect();
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let count = results.len();
+        let short_chunks_demoted = results.iter().filter(|r| r.short_chunk_demoted).count();
+
+        Ok(SearchResponse {
+            query: query_str.to_string(),
+            results,
+            count,
+            duration_ms,
+            groups,
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted,
+            fuzziness: scoring.fuzziness,
+            fuzzy_expansions: expansions,
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: open.reader.modified_times(),
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        })
+    }
+
+    /// Find chunks similar to one indexed chunk ("more like this")
+    ///
+    /// The source chunk's most distinctive terms (see [`super::similar`])
+    /// are OR-combined and scored with the session's BM25 parameters.
+    /// Hits must share at least [`MIN_SHARED_TERMS`] seed terms; each
+    /// lists the ones it matched. The source chunk is never returned,
+    /// nor any chunk of its file with `exclude_source_file`. A chunk
+    /// with fewer seed terms than that gets no results.
+    pub fn find_similar(&self, request: SimilarRequest) -> Result<SimilarResponse> {
+        let start = Instant::now();
+        let defaults = self.defaults();
+        let k = request.k.unwrap_or(defaults.default_k).min(defaults.max_k);
+        let min_term_len = request.min_term_len.unwrap_or(DEFAULT_MIN_TERM_LEN);
+        let max_terms = request.max_terms.unwrap_or(DEFAULT_SEED_TERMS);
+        if k == 0 {
+            return Err(ShebeError::InvalidQuery("k must be at least 1".to_string()));
+        }
+        if min_term_len == 0 {
             return Err(ShebeError::InvalidQuery(
-                "Query cannot be empty".to_string(),
+                "min_term_len must be at least 1".to_string(),
             ));
         }
+        if !(MIN_SHARED_TERMS..=MAX_SEED_TERMS).contains(&max_terms) {
+            return Err(ShebeError::InvalidQuery(format!(
+                "max_terms must be between {MIN_SHARED_TERMS} and {MAX_SEED_TERMS}, got {max_terms}"
+            )));
+        }
+
+        let open = time_phase("index_open", || self.open_session(&request.session))?;
+        let (index, searcher) = (open.index(), &open.searcher);
+        let schema = index.schema();
+        let text_field = schema
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+        let file_path_field = schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing file_path field: {e}")))?;
+
+        // The source chunk and its text
+        let source_query = index.chunk_query(&request.file_path, request.chunk_index)?;
+        let found = searcher
+            .search(&source_query, &TopDocs::with_limit(1))
+            .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+        let Some(source) = Self::hits_to_results(&open, found, &HashSet::new(), None)?.pop() else {
+            return Err(ShebeError::InvalidQuery(format!(
+                "Chunk not found: file {}, chunk index {}",
+                request.file_path, request.chunk_index
+            )));
+        };
+        if let Some(reason) = source.text_unavailable {
+            return Err(ShebeError::SearchFailed(format!(
+                "Cannot read chunk {} of {}: {reason}",
+                request.chunk_index, request.file_path
+            )));
+        }
+
+        let mut analyzer = index
+            .index()
+            .tokenizer_for_field(text_field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text tokenizer: {e}")))?;
+        let frequencies = similar::term_frequencies(&mut analyzer, &source.text, min_term_len);
+        let doc_freq = |term: &str| {
+            searcher
+                .doc_freq(&Term::from_field_text(text_field, term))
+                .unwrap_or(0)
+        };
+        let seed_terms =
+            similar::select_seed_terms(frequencies, doc_freq, searcher.num_docs(), max_terms);
+
+        let mut results = Vec::new();
+        if seed_terms.len() >= MIN_SHARED_TERMS {
+            let bm25 = open.reader.bm25_params();
+            let seed_queries: Vec<Box<dyn Query>> = seed_terms
+                .iter()
+                .map(|term| scoring::term_query(Term::from_field_text(text_field, term), bm25))
+                .collect();
+            let excluded: Box<dyn Query> = if request.exclude_source_file {
+                Box::new(TermQuery::new(
+                    Term::from_field_text(file_path_field, &request.file_path),
+                    IndexRecordOption::Basic,
+                ))
+            } else {
+                Box::new(source_query)
+            };
+            let mut clauses: Vec<(Occur, Box<dyn Query>)> = seed_queries
+                .iter()
+                .map(|query| (Occur::Should, query.box_clone()))
+                .collect();
+            clauses.push((Occur::MustNot, excluded));
+
+            // Hits matching a single seed term are fetched but dropped
+            let limit = (k * SIMILAR_CANDIDATES_PER_RESULT).min(MAX_SIMILAR_CANDIDATES);
+            let top_docs = searcher
+                .search(&BooleanQuery::new(clauses), &TopDocs::with_limit(limit))
+                .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+
+            let mut matched: HashMap<DocAddress, Vec<String>> = HashMap::new();
+            for (term, query) in seed_terms.iter().zip(&seed_queries) {
+                for address in Self::matching_hits(searcher, query.as_ref(), &top_docs)? {
+                    matched.entry(address).or_default().push(term.clone());
+                }
+            }
+            let mut kept: Vec<(Score, DocAddress)> = top_docs
+                .into_iter()
+                .filter(|(_, address)| matched.get(address).map_or(0, Vec::len) >= MIN_SHARED_TERMS)
+                .collect();
+            kept.truncate(k);
+
+            let addresses: Vec<DocAddress> = kept.iter().map(|(_, address)| *address).collect();
+            let mut hits = Self::hits_to_results(&open, kept, &HashSet::new(), None)?;
+            self.attach_manifest_details(&open.session_id, hits.iter_mut())?;
+            results = hits
+                .into_iter()
+                .zip(addresses)
+                .map(|(result, address)| SimilarResult {
+                    result,
+                    matched_terms: matched.remove(&address).unwrap_or_default(),
+                })
+                .collect();
+        }
+
+        Ok(SimilarResponse {
+            file_path: request.file_path,
+            chunk_index: request.chunk_index,
+            seed_terms,
+            results,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Fill `also_present_at` and `indexed_partially` from the
+    /// session's content manifest
+    ///
+    /// Only sessions indexed with dedupe have duplicate paths, and only
+    /// files over `max_chunks_per_file` are truncated; for all others
+    /// this is a no-op.
+    fn attach_manifest_details<'a>(
+        &self,
+        session_id: &str,
+        results: impl Iterator<Item = &'a mut SearchResult>,
+    ) -> Result<()> {
+        let Some(manifest) = self.storage.get_content_manifest(session_id)? else {
+            return Ok(());
+        };
+        if !manifest.has_duplicates() && manifest.truncated.is_empty() {
+            return Ok(());
+        }
+
+        for result in results {
+            result.also_present_at = manifest.duplicates_of(&result.file_path).to_vec();
+            result.indexed_partially = manifest.is_truncated(&result.file_path);
+        }
+
+        Ok(())
+    }
+
+    /// Scoring for the explicit-parameter searches: the service's path
+    /// boost and short-chunk penalty and the session's BM25 parameters,
+    /// no fuzzy matching
+    fn session_scoring(&self, open: &OpenSession) -> Scoring {
+        let defaults = self.defaults();
+        Scoring {
+            path_boost: defaults.path_boost,
+            fuzziness: 0,
+            bm25: open.reader.bm25_params(),
+            short_chunks: Self::short_chunk_threshold(open, defaults.short_chunks),
+            modified_since: None,
+        }
+    }
+
+    /// Minimum chunk length and factor of `penalty` for a session
+    ///
+    /// `None` when there is no penalty or the session's chunk size is
+    /// unknown.
+    fn short_chunk_threshold(
+        open: &OpenSession,
+        penalty: Option<ShortChunkPenalty>,
+    ) -> Option<(u64, f32)> {
+        let penalty = penalty?;
+        let chunk_size = open.reader.chunk_size()?;
+        Some((penalty.min_chars(chunk_size), penalty.factor))
+    }
 
+    /// Get the session's shared reader and a searcher for its queries
+    fn open_session(&self, session_id: &str) -> Result<OpenSession> {
         // Check session exists
         if !self.storage.session_exists(session_id) {
             return Err(ShebeError::SessionNotFound(session_id.to_string()));
         }
 
-        // Determine k (result limit)
-        let k_limit = k.unwrap_or(self.default_k).min(self.max_k);
-
-        // Open session index
-        let index = self.storage.open_session(session_id)?;
-        let reader = index
-            .reader()
-            .map_err(|e| ShebeError::SearchFailed(format!("Failed to create reader: {e}")))?;
+        let reader = self.storage.session_reader(session_id)?;
         let searcher = reader.searcher();
+        Ok(OpenSession {
+            session_id: session_id.to_string(),
+            reader,
+            searcher,
+        })
+    }
+
+    /// Expand the query's `text` terms to similar indexed terms
+    ///
+    /// Returns nothing when `fuzziness` is 0.
+    fn expand_query(
+        open: &OpenSession,
+        query_str: &str,
+        fuzziness: u8,
+    ) -> Result<Vec<FuzzyExpansion>> {
+        if fuzziness == 0 {
+            return Ok(Vec::new());
+        }
+
+        let text_field = open
+            .index()
+            .schema()
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+        let terms = Self::query_terms(open, query_str)?;
+        fuzzy::expand_terms(&open.searcher, text_field, &terms, fuzziness)
+    }
+
+    /// Distinct chunk text terms of a query, tokenized as indexed
+    fn query_terms(open: &OpenSession, query_str: &str) -> Result<Vec<String>> {
+        let text_field = open
+            .index()
+            .schema()
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+        let query = QueryParser::for_index(open.index().index(), vec![text_field])
+            .parse_query(query_str)
+            .map_err(|e| ShebeError::InvalidQuery(format!("Failed to parse query: {e}")))?;
+
+        let mut terms: Vec<String> = Vec::new();
+        query.query_terms(&mut |term, _| {
+            if term.field() == text_field {
+                if let Some(text) = term.value().as_str() {
+                    if !terms.iter().any(|t| t == text) {
+                        terms.push(text.to_string());
+                    }
+                }
+            }
+        });
+        Ok(terms)
+    }
+
+    /// Chunks a search matched that `exclude` left out
+    ///
+    /// Counts the content query (with its fuzzy `expansions`) under the
+    /// same filters, with `exclude` as a required clause.
+    fn count_excluded(
+        open: &OpenSession,
+        query_str: &str,
+        scoring: Scoring,
+        expansions: &[FuzzyExpansion],
+        keep: Option<&DocSetQuery>,
+        exclude: &dyn Query,
+    ) -> Result<u64> {
+        let mut clauses = vec![
+            (
+                Occur::Must,
+                Self::content_query(open, query_str, scoring, expansions)?,
+            ),
+            (Occur::Must, exclude.box_clone()),
+        ];
+        if let Some(keep) = keep {
+            clauses.push((Occur::Must, Box::new(keep.clone()) as Box<dyn Query>));
+        }
+        if let Some(since) = scoring.modified_since {
+            clauses.push((Occur::Must, modified_since_query(since)));
+        }
+        let removed = open
+            .searcher
+            .search(&BooleanQuery::new(clauses), &Count)
+            .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+        Ok(removed as u64)
+    }
+
+    /// Run a BM25 query and return the top `limit` chunks
+    ///
+    /// With a positive `path_boost`, the query is also run against the
+    /// tokenized file path as an optional boosted clause. Hits that
+    /// matched it are flagged with `matched_in_path`. Fuzzy
+    /// `expansions` are OR-combined with the content query. Content
+    /// terms score with `scoring.bm25`, and chunks shorter than
+    /// `scoring.short_chunks` are demoted (flagged with
+    /// `short_chunk_demoted`). A `filter` and `scoring.modified_since`
+    /// restrict hits to their chunks without changing their scores.
+    fn collect_hits(
+        open: &OpenSession,
+        query_str: &str,
+        limit: usize,
+        scoring: Scoring,
+        expansions: &[FuzzyExpansion],
+        filter: SearchFilter<'_>,
+    ) -> Result<Vec<SearchResult>> {
+        let (index, searcher) = (open.index(), &open.searcher);
+
+        let content_query = Self::content_query(open, query_str, scoring, expansions)?;
+        let mut filters: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        if let Some(keep) = filter.keep {
+            filters.push((Occur::Must, Box::new(keep.clone())));
+        }
+        if let Some(exclude) = filter.exclude {
+            filters.push((Occur::MustNot, exclude.box_clone()));
+        }
+        if let Some(since) = scoring.modified_since {
+            filters.push((Occur::Must, modified_since_query(since)));
+        }
+        let content_query: Box<dyn Query> = if filters.is_empty() {
+            content_query
+        } else {
+            filters.insert(0, (Occur::Must, content_query));
+            Box::new(BooleanQuery::new(filters))
+        };
+
+        let path_boost = scoring.path_boost;
+        let path_query = if path_boost > 0.0 {
+            Self::parse_path_query(index, query_str)?
+        } else {
+            None
+        };
+
+        let query: Box<dyn Query> = match &path_query {
+            Some(path_query) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, content_query),
+                (
+                    Occur::Should,
+                    Box::new(BoostQuery::new(path_query.box_clone(), path_boost)),
+                ),
+            ])),
+            None => content_query,
+        };
+
+        // Execute search with BM25 ranking
+        let top_docs = match scoring.short_chunks {
+            Some((min_chars, factor)) => {
+                searcher.search(&query, &demoting_top_docs(limit, min_chars, factor))
+            }
+            None => searcher.search(&query, &TopDocs::with_limit(limit)),
+        }
+        .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+
+        let path_matches = match &path_query {
+            Some(path_query) => Self::matching_hits(searcher, path_query.as_ref(), &top_docs)?,
+            None => HashSet::new(),
+        };
+
+        let min_chars = scoring.short_chunks.map(|(min_chars, _)| min_chars);
+        Self::hits_to_results(open, top_docs, &path_matches, min_chars)
+    }
+
+    /// Load the documents of `top_docs` as search results
+    ///
+    /// Hits in `path_matches` are flagged with `matched_in_path`, and
+    /// chunks shorter than `short_chunk_chars` characters with
+    /// `short_chunk_demoted`. Chunk text comes from the files for
+    /// sessions that do not store it.
+    fn hits_to_results(
+        open: &OpenSession,
+        top_docs: Vec<(Score, DocAddress)>,
+        path_matches: &HashSet<DocAddress>,
+        short_chunk_chars: Option<u64>,
+    ) -> Result<Vec<SearchResult>> {
+        let (index, searcher) = (open.index(), &open.searcher);
         let schema = index.schema();
 
         // Get schema fields
@@ -85,18 +1124,9 @@ impl SearchService {
         let chunk_index_field = schema
             .get_field("chunk_index")
             .map_err(|e| ShebeError::SearchFailed(format!("Missing chunk_index field: {e}")))?;
-
-        // Parse query
-        let query_parser = QueryParser::for_index(index.index(), vec![text_field]);
-
-        let query = query_parser
-            .parse_query(query_str)
-            .map_err(|e| ShebeError::InvalidQuery(format!("Failed to parse query: {e}")))?;
-
-        // Execute search with BM25 ranking
-        let top_docs = searcher
-            .search(&query, &TopDocs::with_limit(k_limit))
-            .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?;
+        // Sessions indexed before chunk lengths were recorded have none
+        let char_count_field = schema.get_field(CHAR_COUNT_FIELD).ok();
+        let modified_at_field = schema.get_field(MODIFIED_AT_FIELD).ok();
 
         // Extract results
         let mut results = Vec::new();
@@ -110,20 +1140,116 @@ impl SearchService {
                 text: Self::extract_text(&doc, text_field),
                 file_path: Self::extract_text(&doc, file_path_field),
                 chunk_index: Self::extract_i64(&doc, chunk_index_field) as usize,
+                session: None,
                 start_offset: Self::extract_i64(&doc, offset_start_field) as usize,
                 end_offset: Self::extract_i64(&doc, offset_end_field) as usize,
+                also_present_at: Vec::new(),
+                matched_in_path: path_matches.contains(&doc_address),
+                short_chunk_demoted: match (short_chunk_chars, char_count_field) {
+                    (Some(min_chars), Some(field)) => doc
+                        .get_first(field)
+                        .and_then(|v| v.as_u64())
+                        .is_some_and(|chars| chars < min_chars),
+                    _ => false,
+                },
+                modified_at: modified_at_field
+                    .and_then(|field| doc.get_first(field))
+                    .and_then(|v| v.as_i64())
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+                text_unavailable: None,
+                indexed_partially: false,
+                explanation: None,
+                records: stored_records(schema, &doc),
+                section: stored_section(schema, &doc),
             });
         }
 
-        let duration_ms = start.elapsed().as_millis() as u64;
-        let count = results.len();
+        if !index.stores_chunk_text() {
+            read_chunk_texts(&mut results);
+        }
 
-        Ok(SearchResponse {
-            query: query_str.to_string(),
-            results,
-            count,
-            duration_ms,
-        })
+        Ok(results)
+    }
+
+    /// Parse the query against the chunk text with the scoring's BM25
+    /// parameters and fuzzy `expansions`
+    fn content_query(
+        open: &OpenSession,
+        query_str: &str,
+        scoring: Scoring,
+        expansions: &[FuzzyExpansion],
+    ) -> Result<Box<dyn Query>> {
+        let index = open.index();
+        let text_field = index
+            .schema()
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+        let content_query = QueryParser::for_index(index.index(), vec![text_field])
+            .parse_query(query_str)
+            .map_err(|e| ShebeError::InvalidQuery(format!("Failed to parse query: {e}")))?;
+        let content_query = scoring::with_params(content_query, text_field, scoring.bm25);
+        Ok(fuzzy::with_expansions(
+            content_query,
+            text_field,
+            expansions,
+            scoring.bm25,
+        ))
+    }
+
+    /// Parse the query against the tokenized file path
+    ///
+    /// Returns `None` for indexes built before the `path_tokens` field
+    /// existed and for queries that do not parse as a path query.
+    fn parse_path_query(index: &TantivyIndex, query_str: &str) -> Result<Option<Box<dyn Query>>> {
+        let Ok(path_field) = index.schema().get_field("path_tokens") else {
+            return Ok(None);
+        };
+
+        Ok(QueryParser::for_index(index.index(), vec![path_field])
+            .parse_query(query_str)
+            .ok())
+    }
+
+    /// Hits (from `top_docs`) that `query` matches
+    ///
+    /// Used for the path query and for the seed terms of a similar-chunks
+    /// search.
+    fn matching_hits(
+        searcher: &Searcher,
+        query: &dyn Query,
+        top_docs: &[(Score, DocAddress)],
+    ) -> Result<HashSet<DocAddress>> {
+        let weight = query
+            .weight(EnableScoring::disabled_from_searcher(searcher))
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to build query: {e}")))?;
+
+        let mut by_segment: BTreeMap<u32, Vec<DocId>> = BTreeMap::new();
+        for (_, address) in top_docs {
+            by_segment
+                .entry(address.segment_ord)
+                .or_default()
+                .push(address.doc_id);
+        }
+
+        let mut matches = HashSet::new();
+        for (segment_ord, mut doc_ids) in by_segment {
+            let mut scorer = weight
+                .scorer(searcher.segment_reader(segment_ord), 1.0)
+                .map_err(|e| ShebeError::SearchFailed(format!("Query failed: {e}")))?;
+
+            // Doc sets only move forward, so visit hits in doc id order
+            doc_ids.sort_unstable();
+            for doc_id in doc_ids {
+                if scorer.doc() < doc_id {
+                    scorer.seek(doc_id);
+                }
+                if scorer.doc() == doc_id {
+                    matches.insert(DocAddress::new(segment_ord, doc_id));
+                }
+            }
+        }
+
+        Ok(matches)
     }
 
     /// Extract text field from document
@@ -140,23 +1266,788 @@ impl SearchService {
     }
 }
 
+/// Fill chunk text from the files on disk (sessions that do not store it)
+///
+/// Each file is read once. Results whose file is missing, unreadable or
+/// no longer covers the indexed offsets keep empty text and say why in
+/// `text_unavailable`.
+fn read_chunk_texts(results: &mut [SearchResult]) {
+    let mut files: HashMap<String, std::io::Result<String>> = HashMap::new();
+
+    for result in results {
+        let content = files
+            .entry(result.file_path.clone())
+            .or_insert_with(|| std::fs::read_to_string(paths::to_native(&result.file_path)));
+        let unavailable = match content {
+            Ok(content) => match content.get(result.start_offset..result.end_offset) {
+                Some(text) => {
+                    result.text = text.to_string();
+                    continue;
+                }
+                None => "file changed since indexing",
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => "file missing",
+            Err(_) => "file unreadable",
+        };
+        result.text_unavailable = Some(unavailable.to_string());
+    }
+}
+
+/// Group score-ordered hits by file, preserving best-first order
+///
+/// Because hits arrive sorted by score, the first hit seen for a file
+/// is its best chunk and the order of first appearance is the ranking
+/// by best chunk score.
+fn group_hits_by_file(hits: Vec<SearchResult>) -> Vec<FileGroup> {
+    let mut groups: Vec<FileGroup> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for hit in hits {
+        match positions.get(&hit.file_path) {
+            Some(&pos) => groups[pos].hits.push(hit),
+            None => {
+                positions.insert(hit.file_path.clone(), groups.len());
+                groups.push(FileGroup {
+                    file_path: hit.file_path.clone(),
+                    best_score: hit.score,
+                    hits: vec![hit],
+                });
+            }
+        }
+    }
+
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::Config;
     use crate::core::storage::SessionConfig;
     use crate::core::types::Chunk;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
-    async fn setup_test_service() -> (SearchService, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+    async fn setup_test_service() -> (SearchService, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+
+        let service = SearchService::new(storage, 10, 100);
+        (service, temp_dir)
+    }
+
+    async fn create_test_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunks = vec![
+            Chunk {
+                text: "async function main() {}".to_string(),
+                file_path: PathBuf::from("test.rs"),
+                start_offset: 0,
+                end_offset: 24,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            },
+            Chunk {
+                text: "sync function helper() {}".to_string(),
+                file_path: PathBuf::from("test.rs"),
+                start_offset: 25,
+                end_offset: 50,
+                chunk_index: 1,
+                records: None,
+                section: None,
+            },
+            Chunk {
+                text: "async fn process_data(x: i32) -> i32 { x * 2 }".to_string(),
+                file_path: PathBuf::from("lib.rs"),
+                start_offset: 0,
+                end_offset: 47,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            },
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_basic_query() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session("test-session", "async function", Some(10))
+            .unwrap();
+
+        assert!(!response.results.is_empty());
+        assert!(response.results[0].text.contains("async"));
+        assert!(response.results[0].score > 0.0);
+        assert_eq!(response.count, response.results.len());
+    }
+
+    #[tokio::test]
+    async fn test_search_phrase_query() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session("test-session", "\"async function\"", Some(10))
+            .unwrap();
+
+        assert!(!response.results.is_empty());
+        assert!(response.results[0].text.contains("async function"));
+    }
+
+    #[tokio::test]
+    async fn test_search_empty_query_error() {
+        let (service, _temp) = setup_test_service().await;
+
+        let result = service.search_session("test-session", "", Some(10));
+
+        assert!(result.is_err());
+        match result {
+            Err(ShebeError::InvalidQuery(_)) => {}
+            _ => panic!("Expected InvalidQuery error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_whitespace_query_error() {
+        let (service, _temp) = setup_test_service().await;
+
+        let result = service.search_session("test-session", "   ", Some(10));
+
+        assert!(result.is_err());
+        match result {
+            Err(ShebeError::InvalidQuery(_)) => {}
+            _ => panic!("Expected InvalidQuery error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_session_not_found() {
+        let (service, _temp) = setup_test_service().await;
+
+        let result = service.search_session("nonexistent-session", "test query", Some(10));
+
+        assert!(result.is_err());
+        match result {
+            Err(ShebeError::SessionNotFound(_)) => {}
+            _ => panic!("Expected SessionNotFound error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_k_parameter() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        // Request only 1 result
+        let response = service
+            .search_session("test-session", "function", Some(1))
+            .unwrap();
+
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_max_k_enforcement() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+
+        // Create service with max_k = 2
+        let service = SearchService::new(storage.clone(), 10, 2);
+        create_test_session(&storage, "test-session").await;
+
+        // Request 100 results (exceeds max_k)
+        let response = service
+            .search_session("test-session", "function", Some(100))
+            .unwrap();
+
+        // Should only return max_k results
+        assert!(response.results.len() <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_default_k() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+
+        // Create service with default_k = 5
+        let service = SearchService::new(storage.clone(), 5, 100);
+        create_test_session(&storage, "test-session").await;
+
+        // Don't specify k (should use default)
+        let response = service
+            .search_session("test-session", "function", None)
+            .unwrap();
+
+        // Should return up to default_k results
+        assert!(response.results.len() <= 5);
+    }
+
+    #[tokio::test]
+    async fn test_search_result_metadata() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session("test-session", "async", Some(10))
+            .unwrap();
+
+        assert!(!response.results.is_empty());
+        let result = &response.results[0];
+
+        // Check metadata is populated
+        assert!(!result.file_path.is_empty());
+        assert!(result.end_offset > result.start_offset);
+    }
+
+    #[tokio::test]
+    async fn test_search_request_wrapper() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let request = SearchRequest {
+            query: "async".to_string(),
+            session: "test-session".to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: None,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        };
+
+        let response = service.search(request).unwrap();
+
+        assert!(!response.results.is_empty());
+        assert_eq!(response.query, "async");
+    }
+
+    fn batch_request(query: &str, session: &str) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            session: session.to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: None,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_matches_single_searches() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let mut grouped = batch_request("async", "test-session");
+        grouped.group_by_file = true;
+        let requests = vec![
+            batch_request("async", "test-session"),
+            batch_request("helper", "test-session"),
+            grouped,
+        ];
+
+        let batch = service.search_batch("test-session", &requests).unwrap();
+        assert_eq!(batch.len(), 3);
+        for (request, response) in requests.into_iter().zip(batch) {
+            let single = service.search(request).unwrap();
+            let response = response.unwrap();
+            let key = |r: &SearchResult| (r.file_path.clone(), r.chunk_index, r.score);
+            assert_eq!(
+                response.results.iter().map(key).collect::<Vec<_>>(),
+                single.results.iter().map(key).collect::<Vec<_>>()
+            );
+            assert_eq!(response.groups.len(), single.groups.len());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_reports_failures_per_query() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let mut fuzzy = batch_request("helper", "test-session");
+        fuzzy.fuzziness = Some(9);
+        let requests = vec![
+            batch_request("async AND (", "test-session"),
+            batch_request("   ", "test-session"),
+            fuzzy,
+            batch_request("async", "other-session"),
+            batch_request("helper", "test-session"),
+        ];
+
+        let batch = service.search_batch("test-session", &requests).unwrap();
+        assert!(matches!(batch[0], Err(ShebeError::InvalidQuery(_))));
+        assert!(matches!(batch[1], Err(ShebeError::InvalidQuery(_))));
+        assert!(matches!(batch[2], Err(ShebeError::InvalidQuery(_))));
+        assert!(matches!(batch[3], Err(ShebeError::InvalidSession(_))));
+        assert!(!batch[4].as_ref().unwrap().results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_session_not_found() {
+        let (service, _temp) = setup_test_service().await;
+
+        let result = service.search_batch("missing", &[batch_request("async", "missing")]);
+        assert!(matches!(result, Err(ShebeError::SessionNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_duration_tracking() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session("test-session", "async", Some(10))
+            .unwrap();
+
+        // Duration should be tracked (non-negative by definition of u64)
+        // Just verify it's returned
+        let _ = response.duration_ms;
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_collapses_chunks_per_file() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session_grouped("test-session", "function", Some(10))
+            .unwrap();
+
+        // Both "function" chunks live in test.rs
+        assert_eq!(response.count, 1);
+        assert_eq!(response.groups.len(), 1);
+        let group = &response.groups[0];
+        assert_eq!(group.file_path, "test.rs");
+        assert_eq!(group.hits.len(), 2);
+        assert_eq!(group.best_score, group.hits[0].score);
+        assert!(group.hits[0].score >= group.hits[1].score);
+        assert_eq!(response.results[0].chunk_index, group.best().chunk_index);
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_ranks_by_best_chunk() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session_grouped("test-session", "async OR function", Some(10))
+            .unwrap();
+
+        assert_eq!(response.groups.len(), 2);
+        assert!(response.groups[0].best_score >= response.groups[1].best_score);
+        let files: Vec<&str> = response
+            .groups
+            .iter()
+            .map(|g| g.file_path.as_str())
+            .collect();
+        assert!(files.contains(&"test.rs"));
+        assert!(files.contains(&"lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_k_counts_files() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        // k=1 returns one file, but that file keeps all of its chunk hits
+        let response = service
+            .search_session_grouped("test-session", "async OR function", Some(1))
+            .unwrap();
+
+        assert_eq!(response.count, 1);
+        assert_eq!(response.groups.len(), 1);
+        assert_eq!(response.groups[0].file_path, "test.rs");
+        assert_eq!(response.groups[0].hits.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_respects_max_k() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+
+        // max_k caps the number of files, not chunks
+        let service = SearchService::new(storage.clone(), 10, 1);
+        create_test_session(&storage, "test-session").await;
+
+        let response = service
+            .search_session_grouped("test-session", "async OR function", Some(100))
+            .unwrap();
+
+        assert_eq!(response.groups.len(), 1);
+        assert_eq!(response.results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_request_group_by_file_dispatch() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "test-session").await;
+
+        let request = SearchRequest {
+            query: "function".to_string(),
+            session: "test-session".to_string(),
+            k: Some(10),
+            group_by_file: true,
+            dedupe_overlaps: true,
+            path_boost: None,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        };
+
+        let response = service.search(request).unwrap();
+        assert_eq!(response.groups.len(), 1);
+
+        // Ungrouped search never populates groups
+        let flat = service
+            .search_session("test-session", "function", Some(10))
+            .unwrap();
+        assert_eq!(flat.results.len(), 2);
+        assert!(flat.groups.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_lists_duplicate_paths() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "dedupe-session").await;
+
+        let mut manifest = crate::core::indexer::ContentManifest::default();
+        manifest.record_duplicate("lib.rs", "vendor/lib.rs".to_string());
+        storage
+            .update_content_manifest("dedupe-session", &manifest)
+            .unwrap();
+
+        let response = service
+            .search_session("dedupe-session", "async", Some(10))
+            .unwrap();
+
+        for result in &response.results {
+            if result.file_path == "lib.rs" {
+                assert_eq!(result.also_present_at, vec!["vendor/lib.rs".to_string()]);
+            } else {
+                assert!(result.also_present_at.is_empty());
+            }
+        }
+
+        let grouped = service
+            .search_session_grouped("dedupe-session", "async", Some(10))
+            .unwrap();
+        let lib = grouped
+            .groups
+            .iter()
+            .find(|g| g.file_path == "lib.rs")
+            .unwrap();
+        assert_eq!(lib.best().also_present_at.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_marks_truncated_files() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_test_session(&storage, "capped-session").await;
+
+        let mut manifest = crate::core::indexer::ContentManifest::default();
+        manifest.record_truncated("lib.rs".to_string());
+        storage
+            .update_content_manifest("capped-session", &manifest)
+            .unwrap();
+
+        let response = service
+            .search_session("capped-session", "async", Some(10))
+            .unwrap();
+        assert!(!response.results.is_empty());
+        for result in &response.results {
+            assert_eq!(result.indexed_partially, result.file_path == "lib.rs");
+        }
+    }
+
+    /// Index one file split into two chunks that share bytes 30..40,
+    /// with "needle" at bytes 32..38, plus a weaker match in other.rs
+    fn create_overlap_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let text = format!("{}needle{}", "x ".repeat(16), " y".repeat(16));
+        assert_eq!(&text[32..38], "needle");
+
+        let chunks = vec![
+            Chunk {
+                text: text[0..40].to_string(),
+                file_path: PathBuf::from("overlap.rs"),
+                start_offset: 0,
+                end_offset: 40,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            },
+            Chunk {
+                text: text[30..70].to_string(),
+                file_path: PathBuf::from("overlap.rs"),
+                start_offset: 30,
+                end_offset: 70,
+                chunk_index: 1,
+                records: None,
+                section: None,
+            },
+            Chunk {
+                text: format!("needle {}", "z ".repeat(40)),
+                file_path: PathBuf::from("other.rs"),
+                start_offset: 0,
+                end_offset: 87,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            },
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_drops_overlap_duplicate_and_backfills() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_overlap_session(&storage, "overlap-session");
+
+        let response = service
+            .search_session("overlap-session", "needle", Some(2))
+            .unwrap();
+
+        assert_eq!(response.count, 2);
+        assert_eq!(response.overlap_duplicates_dropped, 1);
+        let overlap_hits = response
+            .results
+            .iter()
+            .filter(|r| r.file_path == "overlap.rs")
+            .count();
+        assert_eq!(overlap_hits, 1);
+        assert!(response.results.iter().any(|r| r.file_path == "other.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_search_keeps_overlaps_when_disabled() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_overlap_session(&storage, "overlap-session");
+
+        let request = SearchRequest {
+            query: "needle".to_string(),
+            session: "overlap-session".to_string(),
+            k: Some(2),
+            group_by_file: false,
+            dedupe_overlaps: false,
+            path_boost: None,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        };
+        let response = service.search(request).unwrap();
+
+        assert_eq!(response.count, 2);
+        assert_eq!(response.overlap_duplicates_dropped, 0);
+        assert!(response.results.iter().all(|r| r.file_path == "overlap.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_search_keeps_adjacent_hits_matching_outside_overlap() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_overlap_session(&storage, "overlap-session");
+
+        // Each chunk has matches outside the shared bytes
+        let response = service
+            .search_session("overlap-session", "x OR y", Some(10))
+            .unwrap();
+
+        assert_eq!(response.count, 2);
+        assert_eq!(response.overlap_duplicates_dropped, 0);
+    }
+
+    /// Session where auth_handler.rs matches "auth" slightly worse by content than other.rs
+    fn create_path_boost_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |path: &str, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        let chunks = vec![
+            chunk(
+                "src/auth_handler.rs",
+                "fn handle(request: Request) -> Response { check auth token then respond }",
+            ),
+            chunk("src/other.rs", "fn run() { log auth state }"),
+            chunk("src/unrelated.rs", "fn main() { start server }"),
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_path_boost_ranks_path_match_first() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_path_boost_session(&storage, "path-session");
+
+        // Without the boost, other.rs wins on content alone
+        let plain = service
+            .search_session("path-session", "auth", Some(10))
+            .unwrap();
+        assert_eq!(plain.results[0].file_path, "src/other.rs");
+        assert!(plain.results.iter().all(|r| !r.matched_in_path));
+
+        let service = service.with_path_boost(Config::default().search.path_boost);
+        let boosted = service
+            .search_session("path-session", "auth", Some(10))
+            .unwrap();
+
+        assert_eq!(boosted.count, 2);
+        assert_eq!(boosted.results[0].file_path, "src/auth_handler.rs");
+        assert!(boosted.results[0].matched_in_path);
+        assert!(!boosted.results[1].matched_in_path);
+        // Content scoring is unchanged for files without a path match
+        assert_eq!(boosted.results[1].score, plain.results[0].score);
+    }
+
+    #[tokio::test]
+    async fn test_path_boost_request_override() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_path_boost_session(&storage, "path-session");
+        let service = service.with_path_boost(2.0);
+
+        let request = |path_boost: Option<f32>| SearchRequest {
+            query: "auth".to_string(),
+            session: "path-session".to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        };
+
+        let disabled = service.search(request(Some(0.0))).unwrap();
+        assert_eq!(disabled.results[0].file_path, "src/other.rs");
+
+        let grouped = service
+            .search(SearchRequest {
+                group_by_file: true,
+                ..request(None)
+            })
+            .unwrap();
+        assert_eq!(grouped.groups[0].file_path, "src/auth_handler.rs");
+        assert!(grouped.groups[0].best().matched_in_path);
+
+        let invalid = service.search(request(Some(-1.0)));
+        assert!(matches!(invalid, Err(ShebeError::InvalidQuery(_))));
+    }
+
+    #[tokio::test]
+    async fn test_path_only_match_not_returned() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_path_boost_session(&storage, "path-session");
+        let service = service.with_path_boost(2.0);
+
+        // "handler" only appears in a path
+        let response = service
+            .search_session("path-session", "handler", Some(10))
+            .unwrap();
 
-        let service = SearchService::new(storage, 10, 100);
-        (service, temp_dir)
+        assert_eq!(response.count, 0);
     }
 
-    async fn create_test_session(storage: &Arc<StorageManager>, session_id: &str) {
+    fn create_fuzzy_session(storage: &Arc<StorageManager>, session_id: &str) {
         let mut index = storage
             .create_session(
                 session_id,
@@ -165,202 +2056,633 @@ mod tests {
             )
             .unwrap();
 
+        let chunk = |path: &str, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
         let chunks = vec![
-            Chunk {
-                text: "async function main() {}".to_string(),
-                file_path: PathBuf::from("test.rs"),
-                start_offset: 0,
-                end_offset: 24,
-                chunk_index: 0,
-            },
-            Chunk {
-                text: "sync function helper() {}".to_string(),
-                file_path: PathBuf::from("test.rs"),
-                start_offset: 25,
-                end_offset: 50,
-                chunk_index: 1,
-            },
-            Chunk {
-                text: "async fn process_data(x: i32) -> i32 { x * 2 }".to_string(),
-                file_path: PathBuf::from("lib.rs"),
-                start_offset: 0,
-                end_offset: 47,
-                chunk_index: 0,
-            },
+            chunk("src/config.rs", "fn load_config() { read config file }"),
+            chunk("src/typo.rs", "// confg is misspelled here"),
+            chunk("src/auth.rs", "fn check_authentication(user: &User) {}"),
         ];
 
         index.add_chunks(&chunks, session_id).unwrap();
         index.commit().unwrap();
     }
 
+    fn fuzzy_request(query: &str, fuzziness: u8) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            session: "fuzzy-session".to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: Some(0.0),
+            fuzziness: Some(fuzziness),
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        }
+    }
+
     #[tokio::test]
-    async fn test_search_basic_query() {
+    async fn test_fuzzy_search_finds_one_character_typo() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        let response = service
-            .search_session("test-session", "async function", Some(10))
-            .unwrap();
+        let exact = service.search(fuzzy_request("authetication", 0)).unwrap();
+        assert_eq!(exact.count, 0);
+        assert!(exact.fuzzy_summary().is_none());
 
-        assert!(!response.results.is_empty());
-        assert!(response.results[0].text.contains("async"));
-        assert!(response.results[0].score > 0.0);
-        assert_eq!(response.count, response.results.len());
+        let fuzzy = service.search(fuzzy_request("authetication", 1)).unwrap();
+        assert_eq!(fuzzy.count, 1);
+        assert_eq!(fuzzy.results[0].file_path, "src/auth.rs");
+        assert_eq!(
+            fuzzy.fuzzy_expansions,
+            vec![FuzzyExpansion {
+                term: "authetication".to_string(),
+                matches: vec!["authentication".to_string()],
+            }]
+        );
+        assert_eq!(
+            fuzzy.fuzzy_summary().unwrap(),
+            "Fuzzy matching (distance 1): authetication -> authentication"
+        );
     }
 
     #[tokio::test]
-    async fn test_search_phrase_query() {
+    async fn test_fuzzy_search_ranks_exact_match_first() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        let response = service
-            .search_session("test-session", "\"async function\"", Some(10))
-            .unwrap();
+        let response = service.search(fuzzy_request("confg", 1)).unwrap();
 
-        assert!(!response.results.is_empty());
-        assert!(response.results[0].text.contains("async function"));
+        assert_eq!(response.count, 2);
+        assert_eq!(response.results[0].file_path, "src/typo.rs");
+        assert_eq!(response.results[1].file_path, "src/config.rs");
+        assert_eq!(response.fuzzy_expansions[0].matches, vec!["config"]);
     }
 
     #[tokio::test]
-    async fn test_search_empty_query_error() {
+    async fn test_fuzzy_search_skips_short_terms() {
         let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        let result = service.search_session("test-session", "", Some(10));
+        // "usr" is under the minimum length, so "user" is not matched
+        let response = service.search(fuzzy_request("usr", 2)).unwrap();
+        assert_eq!(response.count, 0);
+        assert!(response.fuzzy_expansions.is_empty());
+        assert_eq!(
+            response.fuzzy_summary().unwrap(),
+            "Fuzzy matching (distance 2) found no similar terms"
+        );
+    }
 
-        assert!(result.is_err());
-        match result {
-            Err(ShebeError::InvalidQuery(_)) => {}
-            _ => panic!("Expected InvalidQuery error"),
-        }
+    #[tokio::test]
+    async fn test_fuzzy_search_rejects_large_fuzziness() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_fuzzy_session(&storage, "fuzzy-session");
+
+        let err = service.search(fuzzy_request("config", 3)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("fuzziness must be between 0 and 2"));
     }
 
     #[tokio::test]
-    async fn test_search_whitespace_query_error() {
+    async fn test_identifier_expansion_matches_other_spelling() {
         let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        let result = service.search_session("test-session", "   ", Some(10));
+        let response = service.search(fuzzy_request("loadConfig", 0)).unwrap();
 
-        assert!(result.is_err());
-        match result {
-            Err(ShebeError::InvalidQuery(_)) => {}
-            _ => panic!("Expected InvalidQuery error"),
-        }
+        assert_eq!(response.count, 1);
+        assert_eq!(response.results[0].file_path, "src/config.rs");
+        assert_eq!(response.query, "loadConfig");
+        assert_eq!(
+            response.identifier_summary().unwrap(),
+            "Identifier variants: loadConfig -> load_config, LoadConfig, load-config, LOAD_CONFIG"
+        );
     }
 
     #[tokio::test]
-    async fn test_search_session_not_found() {
+    async fn test_identifier_expansion_disabled() {
         let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        let result = service.search_session("nonexistent-session", "test query", Some(10));
+        let mut request = fuzzy_request("loadConfig", 0);
+        request.expand_identifiers = false;
+        let response = service.search(request).unwrap();
 
-        assert!(result.is_err());
-        match result {
-            Err(ShebeError::SessionNotFound(_)) => {}
-            _ => panic!("Expected SessionNotFound error"),
-        }
+        assert_eq!(response.count, 0);
+        assert!(response.identifier_summary().is_none());
     }
 
     #[tokio::test]
-    async fn test_search_k_parameter() {
+    async fn test_identifier_expansion_skips_quoted_phrase() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_fuzzy_session(&storage, "fuzzy-session");
 
-        // Request only 1 result
-        let response = service
-            .search_session("test-session", "function", Some(1))
-            .unwrap();
+        let response = service.search(fuzzy_request("\"loadConfig\"", 0)).unwrap();
+
+        assert_eq!(response.count, 0);
+        assert!(response.identifier_expansions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identifier_expansion_with_path_boost_and_exclusion() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_fuzzy_session(&storage, "fuzzy-session");
+
+        let mut request = fuzzy_request("fn -checkAuthentication", 0);
+        request.path_boost = Some(2.0);
+        let response = service.search(request).unwrap();
 
-        assert_eq!(response.results.len(), 1);
         assert_eq!(response.count, 1);
+        assert_eq!(response.results[0].file_path, "src/config.rs");
+    }
+
+    fn create_length_session(
+        storage: &Arc<StorageManager>,
+        session_id: &str,
+        config: SessionConfig,
+    ) {
+        let mut index = storage
+            .create_session(session_id, PathBuf::from("/test/repo"), config)
+            .unwrap();
+
+        let chunk = |path: &str, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        let chunks = vec![
+            chunk("src/short.rs", "parse parse config"),
+            chunk(
+                "src/long.rs",
+                "parse parse parse parse config loader reads every file and builds \
+                 the settings tree for the server at startup",
+            ),
+            chunk("src/other.rs", "unrelated words"),
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn length_request(session: &str, b: Option<f32>) -> SearchRequest {
+        SearchRequest {
+            query: "parse".to_string(),
+            session: session.to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: Some(0.0),
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: b,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        }
     }
 
     #[tokio::test]
-    async fn test_search_max_k_enforcement() {
-        let temp_dir = TempDir::new().unwrap();
-        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+    async fn test_bm25_b_override_changes_ordering() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_length_session(&storage, "length-session", SessionConfig::default());
 
-        // Create service with max_k = 2
-        let service = SearchService::new(storage.clone(), 10, 2);
-        create_test_session(&storage, "test-session").await;
+        let flat = service
+            .search(length_request("length-session", Some(0.0)))
+            .unwrap();
+        assert_eq!(flat.results[0].file_path, "src/long.rs");
 
-        // Request 100 results (exceeds max_k)
-        let response = service
-            .search_session("test-session", "function", Some(100))
+        let normalized = service
+            .search(length_request("length-session", Some(1.0)))
             .unwrap();
+        assert_eq!(normalized.results[0].file_path, "src/short.rs");
 
-        // Should only return max_k results
-        assert!(response.results.len() <= 2);
+        let invalid = service.search(length_request("length-session", Some(1.5)));
+        assert!(
+            matches!(invalid, Err(ShebeError::InvalidQuery(ref m)) if m.contains("bm25_b")),
+            "{invalid:?}"
+        );
     }
 
     #[tokio::test]
-    async fn test_search_default_k() {
-        let temp_dir = TempDir::new().unwrap();
-        let storage = Arc::new(StorageManager::new(temp_dir.path().to_path_buf()));
+    async fn test_bm25_session_params_apply_by_default() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_length_session(
+            &storage,
+            "flat-session",
+            SessionConfig::builder().bm25_b(0.0).build().unwrap(),
+        );
 
-        // Create service with default_k = 5
-        let service = SearchService::new(storage.clone(), 5, 100);
-        create_test_session(&storage, "test-session").await;
+        let response = service
+            .search(length_request("flat-session", None))
+            .unwrap();
+        assert_eq!(response.results[0].file_path, "src/long.rs");
 
-        // Don't specify k (should use default)
+        // A request override takes precedence over the session's value
         let response = service
-            .search_session("test-session", "function", None)
+            .search(length_request("flat-session", Some(1.0)))
             .unwrap();
+        assert_eq!(response.results[0].file_path, "src/short.rs");
+    }
 
-        // Should return up to default_k results
-        assert!(response.results.len() <= 5);
+    fn create_refine_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |path: &str, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        let chunks = vec![
+            chunk(
+                "src/retry.rs",
+                "the retry policy caps backoff and sets a timeout",
+            ),
+            chunk(
+                "src/client.rs",
+                "retry the request when the policy allows, with a timeout",
+            ),
+            chunk("src/jobs.rs", "retry policy for queued jobs"),
+            chunk("src/net.rs", "timeout handling only"),
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn refine_request(query: &str, within: Option<SearchWithin>) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            session: "refine-session".to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: Some(0.0),
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        }
+    }
+
+    fn files(response: &SearchResponse) -> Vec<&str> {
+        let mut files: Vec<&str> = response
+            .results
+            .iter()
+            .map(|r| r.file_path.as_str())
+            .collect();
+        files.sort_unstable();
+        files
     }
 
     #[tokio::test]
-    async fn test_search_result_metadata() {
+    async fn test_within_keeps_inner_phrase_unlike_naive_and() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_refine_session(&storage, "refine-session");
 
-        let response = service
-            .search_session("test-session", "async", Some(10))
+        // AND-ing the query strings loses the phrase: client.rs matches too
+        let naive = service
+            .search(refine_request("retry AND policy AND timeout", None))
             .unwrap();
+        assert_eq!(files(&naive), ["src/client.rs", "src/retry.rs"]);
+        assert!(naive.refinement.is_none());
 
-        assert!(!response.results.is_empty());
-        let result = &response.results[0];
+        let within = SearchWithin {
+            query: "\"retry policy\"".to_string(),
+            k: None,
+        };
+        let refined = service
+            .search(refine_request("timeout", Some(within)))
+            .unwrap();
+        assert_eq!(files(&refined), ["src/retry.rs"]);
+        assert_eq!(
+            refined.refinement,
+            Some(Refinement {
+                within_query: "\"retry policy\"".to_string(),
+                inner_matches: 2,
+                survived: 1,
+            })
+        );
+        assert_eq!(
+            refined.refinement_summary().unwrap(),
+            "Refined within '\"retry policy\"': 1 of 2 inner matches also match 'timeout'"
+        );
 
-        // Check metadata is populated
-        assert!(!result.file_path.is_empty());
-        assert!(result.end_offset > result.start_offset);
+        // The filter does not change the primary query's scores
+        let plain = service.search(refine_request("timeout", None)).unwrap();
+        let plain_score = plain
+            .results
+            .iter()
+            .find(|r| r.file_path == "src/retry.rs")
+            .unwrap()
+            .score;
+        assert_eq!(refined.results[0].score, plain_score);
     }
 
     #[tokio::test]
-    async fn test_search_request_wrapper() {
+    async fn test_within_k_limits_inner_hits() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_refine_session(&storage, "refine-session");
 
-        let request = SearchRequest {
-            query: "async".to_string(),
-            session: "test-session".to_string(),
-            k: Some(10),
+        let within = |k| {
+            Some(SearchWithin {
+                query: "policy".to_string(),
+                k: Some(k),
+            })
         };
+        let all = service.search(refine_request("retry", within(10))).unwrap();
+        assert_eq!(all.refinement.as_ref().unwrap().inner_matches, 3);
+        assert_eq!(all.count, 3);
 
-        let response = service.search(request).unwrap();
+        let top = service.search(refine_request("retry", within(1))).unwrap();
+        assert_eq!(top.refinement.as_ref().unwrap().inner_matches, 1);
+        assert_eq!(top.count, 1);
 
-        assert!(!response.results.is_empty());
-        assert_eq!(response.query, "async");
+        let invalid = service.search(refine_request("retry", within(0)));
+        assert!(
+            matches!(invalid, Err(ShebeError::InvalidQuery(ref m)) if m.contains("within.k")),
+            "{invalid:?}"
+        );
+    }
+
+    /// Session where two-term queries match a path and chunks of
+    /// different lengths, plus filler chunks matching one term
+    fn create_explain_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |path: String, text: String| Chunk {
+            end_offset: text.len(),
+            text,
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        let mut chunks = vec![
+            chunk(
+                "src/auth.rs".to_string(),
+                "fn check() { auth token auth verify }".to_string(),
+            ),
+            chunk(
+                "src/session.rs".to_string(),
+                "fn refresh() { token expires then token renews for the user session }".to_string(),
+            ),
+        ];
+        for i in 0..11 {
+            chunks.push(chunk(
+                format!("src/filler_{i}.rs"),
+                format!("fn note_{i}() {{ auth log }}"),
+            ));
+        }
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn explain_request(bm25_k1: Option<f32>) -> SearchRequest {
+        SearchRequest {
+            query: "auth token".to_string(),
+            session: "explain-session".to_string(),
+            k: Some(20),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: Some(2.0),
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: true,
+        }
+    }
+
+    fn assert_explained(result: &SearchResult) {
+        let explanation = result.explanation.as_ref().unwrap();
+        let total = explanation.total();
+        assert!(
+            (total - result.score).abs() <= 1e-4 * result.score.max(1.0),
+            "{} sums to {total}, scored {}",
+            explanation.describe(),
+            result.score
+        );
     }
 
     #[tokio::test]
-    async fn test_search_duration_tracking() {
+    async fn test_explain_contributions_sum_to_score() {
         let (service, _temp) = setup_test_service().await;
         let storage = Arc::clone(&service.storage);
-        create_test_session(&storage, "test-session").await;
+        create_explain_session(&storage, "explain-session");
 
-        let response = service
-            .search_session("test-session", "async", Some(10))
+        let response = service.search(explain_request(None)).unwrap();
+
+        assert_eq!(response.count, 13);
+        let best = &response.results[0];
+        assert_eq!(best.file_path, "src/auth.rs");
+        let terms = &best.explanation.as_ref().unwrap().terms;
+        let content: Vec<(&str, f32)> = terms
+            .iter()
+            .filter(|t| !t.in_path)
+            .map(|t| (t.term.as_str(), t.tf))
+            .collect();
+        assert_eq!(content, [("auth", 2.0), ("token", 1.0)]);
+        assert!(terms
+            .iter()
+            .all(|t| t.idf > 0.0 && t.field_length.is_some()));
+        // "auth" also matched the path, at the path boost
+        assert!(terms.iter().any(|t| t.in_path && t.term == "auth"));
+
+        for result in response.results.iter().take(MAX_EXPLAINED_RESULTS) {
+            assert_explained(result);
+        }
+        // Only the top results are explained
+        let explained = response
+            .results
+            .iter()
+            .filter(|r| r.explanation.is_some())
+            .count();
+        assert_eq!(explained, MAX_EXPLAINED_RESULTS);
+    }
+
+    #[tokio::test]
+    async fn test_explain_with_tuned_bm25_params() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_explain_session(&storage, "explain-session");
+
+        let response = service.search(explain_request(Some(2.0))).unwrap();
+
+        let session = response
+            .results
+            .iter()
+            .find(|r| r.file_path == "src/session.rs")
             .unwrap();
+        let token = &session.explanation.as_ref().unwrap().terms[0];
+        assert_eq!((token.term.as_str(), token.tf), ("token", 2.0));
+        for result in response.results.iter().take(MAX_EXPLAINED_RESULTS) {
+            assert_explained(result);
+        }
 
-        // Duration should be tracked (non-negative by definition of u64)
-        // Just verify it's returned
-        let _ = response.duration_ms;
+        let plain = service
+            .search(SearchRequest {
+                explain: false,
+                ..explain_request(None)
+            })
+            .unwrap();
+        assert!(plain.results.iter().all(|r| r.explanation.is_none()));
+        assert!(plain.explain_skipped.is_none());
+    }
+
+    /// Session where src/limit.rs has two chunks about token buckets
+    fn create_similar_session(storage: &Arc<StorageManager>, session_id: &str) {
+        let mut index = storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |path: &str, chunk_index: usize, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index,
+            records: None,
+            section: None,
+        };
+        let chunks = vec![
+            chunk("src/limit.rs", 0, "refill token bucket capacity"),
+            chunk("src/limit.rs", 1, "drain token bucket capacity"),
+            chunk("src/throttle.rs", 0, "check token bucket before sending"),
+            chunk("src/page.rs", 0, "render page capacity"),
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn similar_request(exclude_source_file: bool) -> SimilarRequest {
+        SimilarRequest {
+            session: "similar-session".to_string(),
+            file_path: "src/limit.rs".to_string(),
+            chunk_index: 0,
+            k: None,
+            min_term_len: None,
+            max_terms: None,
+            exclude_source_file,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_excludes_source() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_similar_session(&storage, "similar-session");
+
+        let response = service.find_similar(similar_request(false)).unwrap();
+        let hits: Vec<(&str, usize)> = response
+            .results
+            .iter()
+            .map(|s| (s.result.file_path.as_str(), s.result.chunk_index))
+            .collect();
+        // page.rs shares only "capacity", and "refill" is in no other chunk
+        assert_eq!(hits, [("src/limit.rs", 1), ("src/throttle.rs", 0)]);
+        assert!(!response.seed_terms.contains(&"refill".to_string()));
+        assert_eq!(
+            response.results[0].matched_terms.len(),
+            3,
+            "{:?}",
+            response.results[0]
+        );
+
+        let response = service.find_similar(similar_request(true)).unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].result.file_path, "src/throttle.rs");
+        let mut matched = response.results[0].matched_terms.clone();
+        matched.sort();
+        assert_eq!(matched, ["bucket", "token"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_missing_chunk() {
+        let (service, _temp) = setup_test_service().await;
+        let storage = Arc::clone(&service.storage);
+        create_similar_session(&storage, "similar-session");
+
+        let mut request = similar_request(false);
+        request.chunk_index = 7;
+        let result = service.find_similar(request);
+        assert!(
+            matches!(result, Err(ShebeError::InvalidQuery(ref m)) if m.contains("Chunk not found")),
+            "{result:?}"
+        );
     }
 }
@@ -0,0 +1,229 @@
+//! Searches over several sessions at once.
+//!
+//! A collection search runs the request against each member session
+//! and merges the responses: results (or file groups) are ranked by
+//! score across sessions, labelled with the session they came from, and
+//! cut to `k`. BM25 scores of different sessions are compared as they
+//! are; each session weighs terms by its own statistics.
+
+use super::SearchService;
+use crate::core::error::{Result, ShebeError};
+use crate::core::types::{SearchRequest, SearchResponse};
+
+impl SearchService {
+    /// Execute `request` against each of `sessions` and merge the results
+    ///
+    /// `request.session` is ignored. At most `request.k` results (files
+    /// when grouping) are kept, the default result count when it is
+    /// not set.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if `sessions` is empty; otherwise the first
+    /// error of a member search.
+    pub fn search_sessions(
+        &self,
+        sessions: &[String],
+        request: SearchRequest,
+    ) -> Result<SearchResponse> {
+        if sessions.is_empty() {
+            return Err(ShebeError::InvalidSession(
+                "No sessions to search".to_string(),
+            ));
+        }
+        let k = self.result_limit(request.k);
+        let responses = sessions
+            .iter()
+            .map(|session| {
+                let request = SearchRequest {
+                    session: session.clone(),
+                    ..request.clone()
+                };
+                Ok((session.clone(), self.search(request)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(merge_responses(responses, k))
+    }
+}
+
+/// Merge the responses of one request to several sessions
+///
+/// Keeps the `k` best results (or file groups, for grouped responses)
+/// across all sessions, best first, each labelled with its session. Ties
+/// keep the order of `responses`. Counters are added up; query notes
+/// (expansions, filters) are taken from the first response that has
+/// them.
+pub fn merge_responses(responses: Vec<(String, SearchResponse)>, k: usize) -> SearchResponse {
+    let mut responses = responses.into_iter();
+    let Some((session, mut merged)) = responses.next() else {
+        return SearchResponse {
+            query: String::new(),
+            results: Vec::new(),
+            count: 0,
+            duration_ms: 0,
+            groups: Vec::new(),
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        };
+    };
+    label(&mut merged, &session);
+
+    for (session, mut response) in responses {
+        label(&mut response, &session);
+        merged.results.append(&mut response.results);
+        merged.groups.append(&mut response.groups);
+        merged.duration_ms += response.duration_ms;
+        merged.overlap_duplicates_dropped += response.overlap_duplicates_dropped;
+        for expansion in response.fuzzy_expansions {
+            if !merged.fuzzy_expansions.contains(&expansion) {
+                merged.fuzzy_expansions.push(expansion);
+            }
+        }
+        for expansion in response.identifier_expansions {
+            if !merged.identifier_expansions.contains(&expansion) {
+                merged.identifier_expansions.push(expansion);
+            }
+        }
+        if let (Some(merged), Some(refinement)) = (&mut merged.refinement, response.refinement) {
+            merged.inner_matches += refinement.inner_matches;
+            merged.survived += refinement.survived;
+        }
+        if let (Some(merged), Some(exclusions)) = (&mut merged.exclusions, response.exclusions) {
+            merged.removed += exclusions.removed;
+        }
+        merged.modified_times = merged.modified_times.or(response.modified_times);
+        merged.explain_skipped = merged.explain_skipped.or(response.explain_skipped);
+    }
+
+    if merged.groups.is_empty() {
+        merged.results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        merged.results.truncate(k);
+    } else {
+        merged
+            .groups
+            .sort_by(|a, b| b.best_score.total_cmp(&a.best_score));
+        merged.groups.truncate(k);
+        merged.results = merged.groups.iter().map(|g| g.best().clone()).collect();
+    }
+    merged.count = merged.results.len();
+    merged.short_chunks_demoted = merged
+        .results
+        .iter()
+        .filter(|r| r.short_chunk_demoted)
+        .count();
+    merged
+}
+
+/// Mark every result of `response` as coming from `session`
+fn label(response: &mut SearchResponse, session: &str) {
+    let hits = response
+        .results
+        .iter_mut()
+        .chain(response.groups.iter_mut().flat_map(|g| g.hits.iter_mut()));
+    for hit in hits {
+        hit.session = Some(session.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{FileGroup, SearchResult};
+
+    fn hit(file: &str, score: f32) -> SearchResult {
+        SearchResult {
+            score,
+            text: String::new(),
+            file_path: file.to_string(),
+            chunk_index: 0,
+            session: None,
+            start_offset: 0,
+            end_offset: 0,
+            records: None,
+            section: None,
+            also_present_at: Vec::new(),
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+        }
+    }
+
+    fn response(results: Vec<SearchResult>, groups: Vec<FileGroup>) -> SearchResponse {
+        let mut response = merge_responses(Vec::new(), 0);
+        response.query = "needle".to_string();
+        response.count = results.len();
+        response.results = results;
+        response.groups = groups;
+        response.duration_ms = 2;
+        response.overlap_duplicates_dropped = 1;
+        response
+    }
+
+    #[test]
+    fn test_merge_ranks_results_across_sessions() {
+        let api = response(
+            vec![hit("/api/a.rs", 3.0), hit("/api/b.rs", 1.0)],
+            Vec::new(),
+        );
+        let web = response(
+            vec![hit("/web/c.rs", 2.0), hit("/web/d.rs", 0.5)],
+            Vec::new(),
+        );
+
+        let merged = merge_responses(vec![("api".into(), api), ("web".into(), web)], 3);
+
+        let ranked: Vec<(&str, Option<&str>)> = merged
+            .results
+            .iter()
+            .map(|r| (r.file_path.as_str(), r.session.as_deref()))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("/api/a.rs", Some("api")),
+                ("/web/c.rs", Some("web")),
+                ("/api/b.rs", Some("api")),
+            ]
+        );
+        assert_eq!(merged.count, 3);
+        assert_eq!(merged.duration_ms, 4);
+        assert_eq!(merged.overlap_duplicates_dropped, 2);
+    }
+
+    #[test]
+    fn test_merge_ranks_groups_by_best_score() {
+        let group = |file: &str, score: f32| FileGroup {
+            file_path: file.to_string(),
+            best_score: score,
+            hits: vec![hit(file, score), hit(file, score / 2.0)],
+        };
+        let api = response(Vec::new(), vec![group("/api/a.rs", 1.0)]);
+        let web = response(Vec::new(), vec![group("/web/b.rs", 2.0)]);
+
+        let merged = merge_responses(vec![("api".into(), api), ("web".into(), web)], 10);
+
+        assert_eq!(merged.groups[0].file_path, "/web/b.rs");
+        assert!(merged.groups[0]
+            .hits
+            .iter()
+            .all(|h| h.session.as_deref() == Some("web")));
+        let best: Vec<&str> = merged
+            .results
+            .iter()
+            .map(|r| r.file_path.as_str())
+            .collect();
+        assert_eq!(best, vec!["/web/b.rs", "/api/a.rs"]);
+    }
+}
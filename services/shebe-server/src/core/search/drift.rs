@@ -0,0 +1,301 @@
+//! Checking indexed chunk offsets against the file on disk.
+//!
+//! Offsets are captured at index time. When a file changes afterwards,
+//! slicing it at those offsets shows the wrong lines. [`locate_chunk`]
+//! finds where the chunk text is now: at its indexed offsets, moved
+//! elsewhere (exact match), edited in place (first and last bytes found
+//! near the indexed offset), or gone.
+
+use crate::core::indexer::strip::matches_stripped;
+use crate::core::types::SearchResult;
+use std::ops::Range;
+
+/// Bytes from each end of the chunk used to find an edited chunk
+const ANCHOR_BYTES: usize = 40;
+
+/// How far (in bytes, beyond the chunk length) from the indexed offsets
+/// an edited chunk is searched for
+const EDIT_WINDOW_BYTES: usize = 4096;
+
+/// Where a chunk's text is in the current file contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkLocation {
+    /// The text is still at its indexed offsets
+    Current(Range<usize>),
+
+    /// The text is unchanged but now starts `delta` bytes later
+    Moved { range: Range<usize>, delta: isize },
+
+    /// The text was edited; its first and last bytes were found near the
+    /// indexed offsets, `delta` bytes from the indexed start
+    Edited { range: Range<usize>, delta: isize },
+
+    /// The text is no longer in the file (deleted, rewritten or truncated)
+    Missing,
+}
+
+impl ChunkLocation {
+    /// Byte range of the chunk in the current file, if it was found
+    pub fn range(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Current(range) | Self::Moved { range, .. } | Self::Edited { range, .. } => {
+                Some(range.clone())
+            }
+            Self::Missing => None,
+        }
+    }
+
+    /// Whether the file changed around the chunk since indexing
+    pub fn is_drifted(&self) -> bool {
+        !matches!(self, Self::Current(_))
+    }
+
+    /// One-line explanation for drifted chunks, `None` when current
+    pub fn note(&self) -> Option<String> {
+        match self {
+            Self::Current(_) => None,
+            Self::Moved { range, delta } => Some(format!(
+                "File changed since indexing: chunk moved {} (now bytes {}-{})",
+                describe_delta(*delta),
+                range.start,
+                range.end
+            )),
+            Self::Edited { range, delta } => Some(format!(
+                "File changed since indexing: chunk was edited; showing bytes {}-{} \
+                 (start moved {})",
+                range.start,
+                range.end,
+                describe_delta(*delta)
+            )),
+            Self::Missing => Some(
+                "File changed since indexing: chunk no longer found in the file; \
+                 showing the indexed text. Re-index the session to refresh it."
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl SearchResult {
+    /// Locate this result's chunk in the current file contents
+    pub fn locate_in(&self, content: &str) -> ChunkLocation {
+        locate_chunk(content, &self.text, self.start_offset..self.end_offset)
+    }
+}
+
+/// Find chunk `text`, indexed at `indexed`, in the current `content`
+///
+/// Tries, in order: the indexed offsets, the exact text nearest to the
+/// indexed start, and the chunk's first and last [`ANCHOR_BYTES`] bytes
+/// within a window around the indexed offsets. Text indexed with
+/// blobs stripped is current when the file still has them.
+pub fn locate_chunk(content: &str, text: &str, indexed: Range<usize>) -> ChunkLocation {
+    if let Some(slice) = content.get(indexed.clone()) {
+        if slice == text || matches_stripped(slice, text) {
+            return ChunkLocation::Current(indexed);
+        }
+    }
+    if text.is_empty() {
+        return ChunkLocation::Missing;
+    }
+
+    let delta = |start: usize| start as isize - indexed.start as isize;
+
+    if let Some(start) = nearest(content.match_indices(text).map(|(i, _)| i), indexed.start) {
+        return ChunkLocation::Moved {
+            range: start..start + text.len(),
+            delta: delta(start),
+        };
+    }
+
+    match find_edited(content, text, indexed.start) {
+        Some(range) => ChunkLocation::Edited {
+            delta: delta(range.start),
+            range,
+        },
+        None => ChunkLocation::Missing,
+    }
+}
+
+/// Locate an edited chunk by its first and last bytes
+fn find_edited(content: &str, text: &str, indexed_start: usize) -> Option<Range<usize>> {
+    if text.len() < 2 * ANCHOR_BYTES {
+        return None;
+    }
+    let head = &text[..floor_char_boundary(text, ANCHOR_BYTES)];
+    let tail = &text[ceil_char_boundary(text, text.len() - ANCHOR_BYTES)..];
+
+    let reach = text.len() + EDIT_WINDOW_BYTES;
+    let lo = floor_char_boundary(content, indexed_start.saturating_sub(reach));
+    let hi = ceil_char_boundary(content, indexed_start.saturating_add(2 * reach));
+    let window = &content[lo..hi];
+
+    let start = lo
+        + nearest(
+            window.match_indices(head).map(|(i, _)| i),
+            indexed_start.saturating_sub(lo),
+        )?;
+
+    // The edited chunk keeps roughly its length: between half and double
+    let min_end = start + text.len() / 2;
+    let max_end = start + text.len() * 2;
+    let expected_end = start + text.len();
+    let end = nearest(
+        window
+            .match_indices(tail)
+            .map(|(i, _)| lo + i + tail.len())
+            .filter(|&end| end >= start + head.len() && (min_end..=max_end).contains(&end)),
+        expected_end,
+    )?;
+
+    Some(start..end)
+}
+
+/// Candidate closest to `target` (earliest on ties)
+fn nearest(candidates: impl Iterator<Item = usize>, target: usize) -> Option<usize> {
+    candidates.min_by_key(|&i| i.abs_diff(target))
+}
+
+fn describe_delta(delta: isize) -> String {
+    match delta {
+        0 => "0 bytes".to_string(),
+        d if d > 0 => format!("+{d} bytes"),
+        d => format!("{d} bytes"),
+    }
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file of numbered functions and the chunk covering `fn f3`
+    fn original() -> (String, String, Range<usize>) {
+        let function = |i: usize| {
+            format!(
+                "fn f{i}() {{\n    let value = compute({i});\n    let scaled = scale(value);\n    \
+                 validate(scaled);\n    store(scaled);\n}}\n"
+            )
+        };
+        let content: String = (0..8).map(|i| format!("{}\n", function(i))).collect();
+        let chunk = function(3);
+        let start = content.find(&chunk).unwrap();
+        let range = start..start + chunk.len();
+        (content, chunk, range)
+    }
+
+    #[test]
+    fn test_unchanged_file_is_current() {
+        let (content, chunk, range) = original();
+        let location = locate_chunk(&content, &chunk, range.clone());
+
+        assert_eq!(location, ChunkLocation::Current(range));
+        assert!(!location.is_drifted());
+        assert!(location.note().is_none());
+    }
+
+    #[test]
+    fn test_insertion_before_chunk_corrects_offsets() {
+        let (content, chunk, range) = original();
+        let inserted = "// new header line\nuse std::fmt;\n";
+        let changed = format!("{inserted}{content}");
+
+        let location = locate_chunk(&changed, &chunk, range.clone());
+        let moved = range.start + inserted.len()..range.end + inserted.len();
+        assert_eq!(
+            location,
+            ChunkLocation::Moved {
+                range: moved.clone(),
+                delta: inserted.len() as isize,
+            }
+        );
+        assert_eq!(&changed[moved], chunk);
+        assert!(location.note().unwrap().contains("moved +33 bytes"));
+    }
+
+    #[test]
+    fn test_edited_chunk_found_by_anchors() {
+        let (content, chunk, range) = original();
+        let edited = chunk.replace("scale(value);", "scale(value) * 2;\n    audit(scaled);");
+        let changed = content.replace(&chunk, &edited);
+
+        let location = locate_chunk(&changed, &chunk, range.clone());
+        let ChunkLocation::Edited {
+            range: found,
+            delta,
+        } = &location
+        else {
+            panic!("expected an edited chunk, got {location:?}");
+        };
+        assert_eq!(*delta, 0);
+        assert_eq!(&changed[found.clone()], edited);
+        assert!(location.is_drifted());
+    }
+
+    #[test]
+    fn test_deleted_chunk_is_missing() {
+        let (content, chunk, range) = original();
+        let changed = content.replace(&chunk, "");
+
+        let location = locate_chunk(&changed, &chunk, range);
+        assert_eq!(location, ChunkLocation::Missing);
+        assert!(location.range().is_none());
+        assert!(location
+            .note()
+            .unwrap()
+            .contains("showing the indexed text"));
+    }
+
+    #[test]
+    fn test_truncated_file_is_missing() {
+        let (content, chunk, range) = original();
+        let changed = &content[..range.start + 10];
+
+        assert_eq!(
+            locate_chunk(changed, &chunk, range.clone()),
+            ChunkLocation::Missing
+        );
+        assert_eq!(locate_chunk("", &chunk, range), ChunkLocation::Missing);
+    }
+
+    #[test]
+    fn test_nearest_copy_wins_and_utf8_is_safe() {
+        let chunk = "héllo wörld";
+        let content = format!("{chunk}\n{}\n{chunk}\n", "é".repeat(20));
+        let second = content.rfind(chunk).unwrap();
+
+        // Indexed offsets point inside a multi-byte character
+        let location = locate_chunk(&content, chunk, second - 3..second - 3 + chunk.len());
+        assert_eq!(location.range(), Some(second..second + chunk.len()));
+    }
+
+    #[test]
+    fn test_stripped_chunk_is_current() {
+        let content = format!(
+            "fn a() {{}}
+let img = {};
+fn b() {{}}
+",
+            "QUJD".repeat(100)
+        );
+        let chunk = "fn a() {}\nlet img = [stripped 401 bytes]\nfn b() {}\n";
+
+        let location = locate_chunk(&content, chunk, 0..content.len());
+        assert_eq!(location, ChunkLocation::Current(0..content.len()));
+    }
+}
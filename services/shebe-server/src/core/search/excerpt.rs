@@ -0,0 +1,348 @@
+//! Compact result locations for token-efficient output.
+//!
+//! "Where is X mentioned" questions need the file and line of each hit,
+//! not the chunk text. [`locate_result`] reduces a result to the line of
+//! its first query term match and a short single-line excerpt centred
+//! on that match.
+//!
+//! Matching mirrors the index tokenizer: text is split into runs of
+//! alphanumeric characters and compared lowercased, so `getUser` in a
+//! query matches `getuser` and `GetUser`, but not `getUserName`. A term
+//! of several words (the `read only` of a `read_only` spelling) matches
+//! those words in a row.
+
+use crate::core::types::{IdentifierExpansion, SearchResult};
+
+/// Maximum excerpt length in characters (including ellipses)
+pub const EXCERPT_MAX_CHARS: usize = 80;
+
+/// Marks text cut from either end of an excerpt
+const ELLIPSIS: char = '…';
+
+/// Boolean operators, which are not search terms
+const OPERATORS: &[&str] = &["AND", "OR", "NOT"];
+
+/// Field prefixes accepted in queries (see `validate_query_fields`)
+const FIELD_PREFIXES: &[&str] = &["content:", "file_path:"];
+
+/// Where a result's first match is, in compact form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultLocation {
+    /// 1-based line of the match in the current file, `None` when the
+    /// file could not be read or the chunk is no longer in it
+    pub line: Option<usize>,
+
+    /// Single line around the match, at most [`EXCERPT_MAX_CHARS`]
+    pub excerpt: String,
+}
+
+/// Lowercased search terms of a query
+///
+/// Drops boolean operators, field prefixes and the word after `NOT`
+/// (excluded terms never match); terms keep query order without
+/// duplicates.
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut query = query.to_string();
+    for prefix in FIELD_PREFIXES {
+        query = query.replace(prefix, " ");
+    }
+
+    let mut terms: Vec<String> = Vec::new();
+    let mut excluded = false;
+    for (_, word) in words(&query) {
+        if OPERATORS.contains(&word) {
+            excluded = word == "NOT";
+            continue;
+        }
+        if std::mem::take(&mut excluded) {
+            continue;
+        }
+        let term = word.to_lowercase();
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Lowercased terms a result of `query` can match
+///
+/// The [`query_terms`] followed by the identifier spellings the search
+/// added (`expansions`), each as the words the index sees, so a hit
+/// that matched only `read_only` for `ReadOnly` is located at it.
+pub fn match_terms(query: &str, expansions: &[IdentifierExpansion]) -> Vec<String> {
+    let mut terms = query_terms(query);
+    for variant in expansions.iter().flat_map(|e| &e.variants) {
+        let term = words(variant)
+            .map(|(_, word)| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !term.is_empty() && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Locate a result's first match for compact output
+///
+/// `content` is the current file, when it could be read. Without a
+/// match (phrase-only or fuzzy hits, path matches) the excerpt is the
+/// chunk's first non-blank line.
+pub fn locate_result(
+    result: &SearchResult,
+    content: Option<&str>,
+    terms: &[String],
+) -> ResultLocation {
+    let text = &result.text;
+    let offset = first_match(text, terms);
+
+    let excerpt = match offset {
+        Some(offset) => excerpt_around(text, offset, EXCERPT_MAX_CHARS),
+        None => text
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| excerpt_around(line, 0, EXCERPT_MAX_CHARS))
+            .unwrap_or_default(),
+    };
+
+    let line = content
+        .and_then(|content| {
+            let start = result.locate_in(content).range()?.start;
+            Some(line_at(content, start))
+        })
+        .map(|first| first + offset.map_or(0, |offset| newlines(&text[..offset])));
+
+    ResultLocation { line, excerpt }
+}
+
+/// Byte offset of the earliest term match in `text`
+///
+/// Terms of several space-separated words match those words in a row.
+pub fn first_match(text: &str, terms: &[String]) -> Option<usize> {
+    if terms.is_empty() {
+        return None;
+    }
+    let terms: Vec<Vec<&str>> = terms.iter().map(|t| t.split(' ').collect()).collect();
+    let words: Vec<(usize, String)> = words(text)
+        .map(|(offset, word)| (offset, word.to_lowercase()))
+        .collect();
+    (0..words.len())
+        .find(|&i| {
+            terms.iter().any(|term| {
+                words[i..].len() >= term.len()
+                    && term.iter().zip(&words[i..]).all(|(t, (_, w))| t == w)
+            })
+        })
+        .map(|i| words[i].0)
+}
+
+/// Single-line excerpt of at most `max_chars` characters around `offset`
+///
+/// `offset` must be a char boundary of `text`. The excerpt is taken from
+/// the line containing it, with whitespace collapsed; cut ends are marked
+/// with an ellipsis.
+pub fn excerpt_around(text: &str, offset: usize, max_chars: usize) -> String {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    let line = &text[line_start..line_end];
+
+    // Collapse whitespace, remembering where the match ends up
+    let mut chars: Vec<char> = Vec::new();
+    let mut match_at = 0;
+    let mut pending_space = false;
+    for (i, c) in line.char_indices() {
+        if line_start + i == offset {
+            if pending_space && !chars.is_empty() {
+                chars.push(' ');
+            }
+            pending_space = false;
+            match_at = chars.len();
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && !chars.is_empty() {
+            chars.push(' ');
+        }
+        pending_space = false;
+        chars.push(c);
+    }
+
+    if chars.len() <= max_chars {
+        return chars.into_iter().collect();
+    }
+
+    // Centre a window on the match, leaving room for the ellipses
+    let width = max_chars.saturating_sub(2).max(1);
+    let start = match_at.saturating_sub(width / 2).min(chars.len() - width);
+    let end = start + width;
+
+    let mut excerpt = String::new();
+    if start > 0 {
+        excerpt.push(ELLIPSIS);
+    }
+    excerpt.extend(&chars[start..end]);
+    if end < chars.len() {
+        excerpt.push(ELLIPSIS);
+    }
+    excerpt
+}
+
+/// Runs of alphanumeric characters with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while let Some(&(_, c)) = rest.peek() {
+            if c.is_alphanumeric() {
+                break;
+            }
+            rest.next();
+        }
+        let (start, _) = *rest.peek()?;
+        let mut end = start;
+        while let Some(&(i, c)) = rest.peek() {
+            if !c.is_alphanumeric() {
+                break;
+            }
+            end = i + c.len_utf8();
+            rest.next();
+        }
+        Some((start, &text[start..end]))
+    })
+}
+
+/// 1-based line of a byte offset
+fn line_at(content: &str, offset: usize) -> usize {
+    newlines(&content[..offset.min(content.len())]) + 1
+}
+
+fn newlines(text: &str) -> usize {
+    text.bytes().filter(|&b| b == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(text: &str, start_offset: usize) -> SearchResult {
+        SearchResult {
+            score: 1.0,
+            text: text.to_string(),
+            file_path: "/repo/src/lib.rs".to_string(),
+            chunk_index: 0,
+            session: None,
+            start_offset,
+            end_offset: start_offset + text.len(),
+            also_present_at: Vec::new(),
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        }
+    }
+
+    #[test]
+    fn test_query_terms_skips_operators_and_fields() {
+        assert_eq!(
+            query_terms("Patient AND (login OR content:auth) NOT legacy patient"),
+            ["patient", "login", "auth"]
+        );
+        assert_eq!(query_terms("\"exact phrase\""), ["exact", "phrase"]);
+        assert_eq!(query_terms("file_path:session"), ["session"]);
+    }
+
+    #[test]
+    fn test_first_match_whole_words_case_insensitive() {
+        let terms = vec!["getuser".to_string()];
+        assert_eq!(
+            first_match("let x = getUserName(); getUser()", &terms),
+            Some(23)
+        );
+        assert_eq!(first_match("nothing here", &terms), None);
+        assert_eq!(first_match("anything", &[]), None);
+    }
+
+    #[test]
+    fn test_match_terms_adds_identifier_variants() {
+        let expansions = vec![IdentifierExpansion {
+            term: "ReadOnly".to_string(),
+            variants: vec!["read_only".to_string(), "readOnly".to_string()],
+        }];
+        let terms = match_terms("ReadOnly config", &expansions);
+        assert_eq!(terms, ["readonly", "config", "read only"]);
+
+        let text = "{\n    let mode = Mode::default();\n    self.read_only = true;\n}";
+        assert_eq!(first_match(text, &terms), text.find("read_only"));
+        assert_eq!(first_match("read the docs only", &terms), None);
+    }
+
+    #[test]
+    fn test_excerpt_short_line_kept_whole() {
+        let text = "fn a() {}\n    let   needle = 1;\n";
+        let offset = text.find("needle").unwrap();
+        assert_eq!(excerpt_around(text, offset, 80), "let needle = 1;");
+    }
+
+    #[test]
+    fn test_excerpt_centred_on_match() {
+        let text = format!("{}needle{}", "a ".repeat(100), " b".repeat(100));
+        let offset = text.find("needle").unwrap();
+
+        let excerpt = excerpt_around(&text, offset, 80);
+
+        assert_eq!(excerpt.chars().count(), 80);
+        assert!(excerpt.starts_with(ELLIPSIS) && excerpt.ends_with(ELLIPSIS));
+        let at = excerpt.find("needle").unwrap();
+        let before = excerpt[..at].chars().count();
+        assert!((35..=45).contains(&before), "match not centred: {excerpt}");
+    }
+
+    #[test]
+    fn test_excerpt_utf8_safe() {
+        let text = format!("{}needle{}", "ü→".repeat(60), "日本".repeat(60));
+        let offset = text.find("needle").unwrap();
+
+        let excerpt = excerpt_around(&text, offset, 80);
+
+        assert!(excerpt.chars().count() <= 80);
+        assert!(excerpt.contains("needle"));
+    }
+
+    #[test]
+    fn test_excerpt_at_line_start_and_end() {
+        let long = "x".repeat(200);
+        let text = format!("needle {long}");
+        let excerpt = excerpt_around(&text, 0, 80);
+        assert!(excerpt.starts_with("needle"));
+        assert!(excerpt.ends_with(ELLIPSIS));
+
+        let text = format!("{long} needle");
+        let excerpt = excerpt_around(&text, text.find("needle").unwrap(), 80);
+        assert!(excerpt.starts_with(ELLIPSIS));
+        assert!(excerpt.ends_with("needle"));
+    }
+
+    #[test]
+    fn test_locate_result_line_of_match() {
+        let content = "// header\n\nfn other() {}\nfn needle() {}\n";
+        let chunk = &content[11..];
+        let located = locate_result(&result(chunk, 11), Some(content), &["needle".to_string()]);
+
+        assert_eq!(located.line, Some(4));
+        assert_eq!(located.excerpt, "fn needle() {}");
+    }
+
+    #[test]
+    fn test_locate_result_without_file_or_match() {
+        let located = locate_result(&result("\n\n  fn first() {}\nfn b() {}", 0), None, &[]);
+
+        assert_eq!(located.line, None);
+        assert_eq!(located.excerpt, "fn first() {}");
+    }
+}
@@ -0,0 +1,188 @@
+//! Negative terms and paths of a search.
+//!
+//! `exclude_terms` leaves out chunks containing any of the terms: each
+//! is tokenized like chunk text and becomes a `MustNot` term clause
+//! (a phrase clause when it has several words). `exclude_paths` leaves
+//! out files matching any of the globs, matched against the stored
+//! path and, for relative globs, the path inside the repository. The
+//! matching paths are picked from the `file_path` term dictionary, so
+//! they exclude as one more `MustNot` clause and no result is dropped
+//! after ranking.
+//!
+//! The number of chunks removed is one count query: the search with
+//! the exclusions as a `Must` clause instead. Counting visits the
+//! matching postings but neither scores nor loads any document.
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::paths;
+use crate::core::storage::TantivyIndex;
+use glob::Pattern;
+use std::collections::HashSet;
+use std::path::Path;
+use tantivy::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery, TermSetQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Searcher, Term};
+
+/// Most entries in `exclude_terms` or `exclude_paths`
+pub const MAX_EXCLUSIONS: usize = 20;
+
+/// What a search leaves out
+pub(super) struct Exclusions {
+    /// Matches every chunk to leave out (`None`: nothing to leave out)
+    pub(super) query: Option<Box<dyn Query>>,
+    /// Single-word excluded terms, tokenized
+    single_terms: HashSet<String>,
+}
+
+impl Exclusions {
+    /// Build the exclusions of one search
+    ///
+    /// Relative `exclude_paths` globs also match paths relative to
+    /// `root`.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidQuery` for too many entries, a term without any word
+    /// and an invalid or empty glob.
+    pub(super) fn new(
+        index: &TantivyIndex,
+        searcher: &Searcher,
+        root: Option<&Path>,
+        terms: &[String],
+        path_globs: &[String],
+    ) -> Result<Self> {
+        for (name, entries) in [("exclude_terms", terms), ("exclude_paths", path_globs)] {
+            if entries.len() > MAX_EXCLUSIONS {
+                return Err(ShebeError::InvalidQuery(format!(
+                    "{name} takes at most {MAX_EXCLUSIONS} entries, got {}",
+                    entries.len()
+                )));
+            }
+        }
+
+        let schema = index.schema();
+        let text_field = schema
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+        let mut analyzer = index
+            .index()
+            .tokenizer_for_field(text_field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text tokenizer: {e}")))?;
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        let mut single_terms = HashSet::new();
+        for term in terms {
+            let mut tokens: Vec<String> = Vec::new();
+            analyzer
+                .token_stream(term)
+                .process(&mut |token| tokens.push(token.text.clone()));
+            let query: Box<dyn Query> = match tokens.as_slice() {
+                [] => {
+                    return Err(ShebeError::InvalidQuery(format!(
+                        "exclude_terms entry '{term}' has no searchable words"
+                    )))
+                }
+                [token] => {
+                    single_terms.insert(token.clone());
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(text_field, token),
+                        IndexRecordOption::Basic,
+                    ))
+                }
+                words => Box::new(PhraseQuery::new(
+                    words
+                        .iter()
+                        .map(|word| Term::from_field_text(text_field, word))
+                        .collect(),
+                )),
+            };
+            clauses.push((Occur::Should, query));
+        }
+
+        if !path_globs.is_empty() {
+            let file_path_field = schema
+                .get_field("file_path")
+                .map_err(|e| ShebeError::SearchFailed(format!("Missing file_path field: {e}")))?;
+            let paths = matching_paths(searcher, file_path_field, root, path_globs)?;
+            if !paths.is_empty() {
+                clauses.push((Occur::Should, Box::new(TermSetQuery::new(paths))));
+            }
+        }
+
+        Ok(Self {
+            query: (!clauses.is_empty())
+                .then(|| Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>),
+            single_terms,
+        })
+    }
+
+    /// Reject a query all of whose `query_terms` are excluded
+    ///
+    /// # Errors
+    ///
+    /// `InvalidQuery` naming the terms, since no chunk could match.
+    pub(super) fn check_query_terms(&self, query_terms: &[String]) -> Result<()> {
+        if query_terms.is_empty() || !query_terms.iter().all(|t| self.single_terms.contains(t)) {
+            return Ok(());
+        }
+        Err(ShebeError::InvalidQuery(format!(
+            "exclude_terms excludes every query term ({}), so nothing could match. \
+             Remove them from exclude_terms or search for something else",
+            query_terms.join(", ")
+        )))
+    }
+}
+
+/// Stored file paths matching any of `globs`, as terms of `field`
+fn matching_paths(
+    searcher: &Searcher,
+    field: Field,
+    root: Option<&Path>,
+    globs: &[String],
+) -> Result<Vec<Term>> {
+    let patterns = globs
+        .iter()
+        .map(|glob| {
+            if glob.trim().is_empty() {
+                return Err(ShebeError::InvalidQuery(
+                    "exclude_paths entries cannot be empty".to_string(),
+                ));
+            }
+            Pattern::new(&paths::normalize(glob)).map_err(|e| {
+                ShebeError::InvalidQuery(format!("Invalid exclude_paths glob '{glob}': {e}"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let prefix = root.map(|root| format!("{}/", paths::normalize_path(root)));
+
+    let mut seen = HashSet::new();
+    let mut terms = Vec::new();
+    for segment in searcher.segment_readers() {
+        let inverted = segment
+            .inverted_index(field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read file paths: {e}")))?;
+        let mut stream = inverted
+            .terms()
+            .stream()
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read file paths: {e}")))?;
+        while stream.advance() {
+            let Ok(path) = std::str::from_utf8(stream.key()) else {
+                continue;
+            };
+            if seen.contains(path) {
+                continue;
+            }
+            let relative = prefix
+                .as_deref()
+                .and_then(|prefix| path.strip_prefix(prefix));
+            let matched = patterns.iter().any(|pattern| {
+                pattern.matches(path) || relative.is_some_and(|r| pattern.matches(r))
+            });
+            if matched {
+                seen.insert(path.to_string());
+                terms.push(Term::from_field_text(field, path));
+            }
+        }
+    }
+    Ok(terms)
+}
@@ -0,0 +1,177 @@
+//! Per-term score breakdowns ("explain") for search results.
+//!
+//! Tantivy explains a score as a tree of formula nodes, which is too
+//! verbose to show per result. [`term_contributions`] walks that tree
+//! and keeps, for each matched term, its frequency, IDF, field length
+//! and the points it added to the score, with enclosing boosts (fuzzy
+//! expansions, path boost) applied. Explanations cost one extra query
+//! evaluation per result, so only the top [`MAX_EXPLAINED_RESULTS`]
+//! results get one.
+
+use crate::core::types::TermContribution;
+use serde_json::Value;
+use tantivy::query::Explanation;
+use tantivy::schema::{FieldType, Schema};
+
+/// Results explained per search
+pub const MAX_EXPLAINED_RESULTS: usize = 10;
+
+/// Why a session's index cannot be explained, or `None` if it can
+///
+/// Term frequencies and field lengths are read from the `text` field's
+/// postings and field norms; an index built without them would report
+/// made-up numbers.
+pub(super) fn missing_requirement(schema: &Schema) -> Option<String> {
+    let indexing = schema
+        .get_field("text")
+        .ok()
+        .map(|field| schema.get_field_entry(field).field_type())
+        .and_then(|field_type| match field_type {
+            FieldType::Str(options) => options.get_indexing_options(),
+            _ => None,
+        });
+    match indexing {
+        Some(indexing) if indexing.index_option().has_freq() && indexing.fieldnorms() => None,
+        _ => Some(
+            "the session's text field has no term frequencies or field norms; \
+             re-index it to explain scores"
+                .to_string(),
+        ),
+    }
+}
+
+/// Matched terms of an explanation and their share of its score
+///
+/// `boost` multiplies every contribution (the path boost for the path
+/// query). Terms are returned in the order the query lists them;
+/// scoring nodes that are not term or phrase matches are kept under
+/// their description so the contributions still add up.
+pub(super) fn term_contributions(
+    explanation: &Explanation,
+    in_path: bool,
+    boost: f32,
+) -> Vec<TermContribution> {
+    let tree = serde_json::to_value(explanation).unwrap_or(Value::Null);
+    let mut terms = Vec::new();
+    collect(&tree, in_path, boost, &mut terms);
+    terms
+}
+
+fn collect(node: &Value, in_path: bool, boost: f32, terms: &mut Vec<TermContribution>) {
+    let value = node["value"].as_f64().unwrap_or(0.0) as f32;
+    let description = node["description"].as_str().unwrap_or_default();
+    let details = node["details"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+
+    let term = match term_text(node) {
+        Some(term) => Some(term),
+        None if description == "Phrase Scorer" => Some("phrase".to_string()),
+        None if details.is_empty() && value != 0.0 => Some(description.to_string()),
+        None => None,
+    };
+    if let Some(term) = term {
+        terms.push(TermContribution {
+            term,
+            in_path,
+            tf: find_const(node, "freq,").unwrap_or(0.0),
+            idf: find_const(node, "idf").unwrap_or(0.0),
+            field_length: find_const(node, "dl,"),
+            contribution: value * boost,
+        });
+        return;
+    }
+
+    // "Boost x0.5 of ..." scales everything below it
+    let boost = description
+        .strip_prefix("Boost x")
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|factor| factor.parse::<f32>().ok())
+        .map_or(boost, |factor| boost * factor);
+    for detail in details {
+        collect(detail, in_path, boost, terms);
+    }
+}
+
+/// Term text from a `Term=Term(field=0, type=Str, "auth")` context line
+fn term_text(node: &Value) -> Option<String> {
+    node["context"]
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .find_map(|context| {
+            let term = context.strip_prefix("Term=")?;
+            let start = term.find('"')?;
+            let end = term.rfind('"')?;
+            Some(if end > start {
+                term[start + 1..end].to_string()
+            } else {
+                term.to_string()
+            })
+        })
+}
+
+/// Value of the first node below `node` whose description starts with `prefix`
+fn find_const(node: &Value, prefix: &str) -> Option<f32> {
+    node["details"].as_array()?.iter().find_map(|detail| {
+        let description = detail["description"].as_str().unwrap_or_default();
+        if description.starts_with(prefix) {
+            detail["value"].as_f64().map(|v| v as f32)
+        } else {
+            find_const(detail, prefix)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{STRING, TEXT};
+
+    fn term_node(term: &str, score: f32, freq: f32, idf: f32) -> Explanation {
+        let mut idf_node = Explanation::new("idf, computed as log(...)", idf);
+        idf_node.add_const("n, number of docs containing this term", 1.0);
+        let mut tf_node = Explanation::new("freq / (freq + k1 * ...)", 0.5);
+        tf_node.add_const("freq, occurrences of term within document", freq);
+        tf_node.add_const("dl, length of field", 12.0);
+        let mut node = Explanation::new("TermQuery, product of...", score);
+        node.add_detail(idf_node);
+        node.add_detail(tf_node);
+        node.add_context(format!("Term=Term(field=0, type=Str, {term:?})"));
+        node
+    }
+
+    #[test]
+    fn test_term_contributions_apply_boosts() {
+        let mut fuzzy = Explanation::new("Boost x0.5 of ...", 1.0);
+        fuzzy.add_detail(term_node("tokne", 2.0, 1.0, 3.0));
+        let mut root = Explanation::new("BooleanClause. sum of ...", 5.0);
+        root.add_detail(term_node("auth", 4.0, 3.0, 2.41));
+        root.add_detail(fuzzy);
+
+        let terms = term_contributions(&root, false, 1.0);
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].term, "auth");
+        assert_eq!(terms[0].tf, 3.0);
+        assert_eq!(terms[0].idf, 2.41);
+        assert_eq!(terms[0].field_length, Some(12.0));
+        assert_eq!(terms[0].contribution, 4.0);
+        assert_eq!(terms[1].term, "tokne");
+        assert_eq!(terms[1].contribution, 1.0);
+
+        let path = term_contributions(&term_node("auth", 2.0, 1.0, 1.0), true, 0.5);
+        assert!(path[0].in_path);
+        assert_eq!(path[0].contribution, 1.0);
+    }
+
+    #[test]
+    fn test_missing_requirement() {
+        let mut builder = Schema::builder();
+        builder.add_text_field("text", TEXT);
+        assert!(missing_requirement(&builder.build()).is_none());
+
+        // Untokenized, without frequencies or field norms
+        let mut builder = Schema::builder();
+        builder.add_text_field("text", STRING);
+        assert!(missing_requirement(&builder.build()).is_some());
+    }
+}
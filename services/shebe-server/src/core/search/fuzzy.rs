@@ -0,0 +1,207 @@
+//! Typo-tolerant term expansion.
+//!
+//! With fuzzy matching, each query term of at least
+//! [`MIN_FUZZY_TERM_LEN`] characters is expanded to the indexed terms
+//! within the requested edit distance that share its first character
+//! (`confg` -> `config`). Expansions are OR-combined with the original
+//! query at a lower boost, so exact matches still rank first.
+
+use super::scoring::{self, Bm25Params};
+use crate::core::error::{Result, ShebeError};
+use crate::core::types::FuzzyExpansion;
+use std::collections::BTreeMap;
+use tantivy::{
+    query::{BooleanQuery, BoostQuery, Occur, Query},
+    schema::Field,
+    Searcher, Term,
+};
+
+/// Largest supported edit distance
+pub const MAX_FUZZINESS: u8 = 2;
+
+/// Terms shorter than this (in characters) are never expanded
+pub const MIN_FUZZY_TERM_LEN: usize = 4;
+
+/// Expanded terms kept per query term (closest, then most frequent)
+const MAX_EXPANSIONS_PER_TERM: usize = 20;
+
+/// Boost applied to the expanded terms relative to the exact query
+const FUZZY_BOOST: f32 = 0.5;
+
+/// Expand query terms to similar indexed terms of `field`
+///
+/// Returns one entry per term with at least one match, in query
+/// order. Terms that are too short are left out.
+pub(crate) fn expand_terms(
+    searcher: &Searcher,
+    field: Field,
+    terms: &[String],
+    distance: u8,
+) -> Result<Vec<FuzzyExpansion>> {
+    let mut expansions = Vec::new();
+    if distance == 0 {
+        return Ok(expansions);
+    }
+
+    for term in terms {
+        if term.chars().count() < MIN_FUZZY_TERM_LEN {
+            continue;
+        }
+        let matches = similar_terms(searcher, field, term, distance as usize)?;
+        if !matches.is_empty() {
+            expansions.push(FuzzyExpansion {
+                term: term.clone(),
+                matches,
+            });
+        }
+    }
+
+    Ok(expansions)
+}
+
+/// OR-combine `exact` with the expanded terms at a lower boost
+///
+/// The expanded terms score with `bm25`.
+pub(crate) fn with_expansions(
+    exact: Box<dyn Query>,
+    field: Field,
+    expansions: &[FuzzyExpansion],
+    bm25: Bm25Params,
+) -> Box<dyn Query> {
+    let alternatives: Vec<(Occur, Box<dyn Query>)> = expansions
+        .iter()
+        .flat_map(|expansion| &expansion.matches)
+        .map(|text| {
+            let query = scoring::term_query(Term::from_field_text(field, text), bm25);
+            (Occur::Should, query)
+        })
+        .collect();
+
+    if alternatives.is_empty() {
+        return exact;
+    }
+
+    Box::new(BooleanQuery::new(vec![
+        (Occur::Should, exact),
+        (
+            Occur::Should,
+            Box::new(BoostQuery::new(
+                Box::new(BooleanQuery::new(alternatives)),
+                FUZZY_BOOST,
+            )),
+        ),
+    ]))
+}
+
+/// Indexed terms within `distance` of `term` sharing its first character
+fn similar_terms(
+    searcher: &Searcher,
+    field: Field,
+    term: &str,
+    distance: usize,
+) -> Result<Vec<String>> {
+    let Some(first) = term.chars().next() else {
+        return Ok(Vec::new());
+    };
+    let prefix = first.to_string();
+    let target: Vec<char> = term.chars().collect();
+
+    // Candidate term -> document frequency summed over segments
+    let mut candidates: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for segment in searcher.segment_readers() {
+        let inverted = segment
+            .inverted_index(field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read terms: {e}")))?;
+        let mut stream = inverted
+            .terms()
+            .range()
+            .ge(prefix.as_bytes())
+            .into_stream()
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read terms: {e}")))?;
+
+        while stream.advance() {
+            if !stream.key().starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let Ok(text) = std::str::from_utf8(stream.key()) else {
+                continue;
+            };
+            if text == term {
+                continue;
+            }
+            if let Some(found) = bounded_distance(&target, text, distance) {
+                let entry = candidates.entry(text.to_string()).or_insert((found, 0));
+                entry.1 += u64::from(stream.value().doc_freq);
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, (usize, u64))> = candidates.into_iter().collect();
+    ranked.sort_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.1 .1.cmp(&a.1 .1)));
+    ranked.truncate(MAX_EXPANSIONS_PER_TERM);
+
+    Ok(ranked.into_iter().map(|(text, _)| text).collect())
+}
+
+/// Edit distance between `target` and `candidate`, if at most `max`
+///
+/// Counts insertions, deletions, substitutions and transpositions of
+/// adjacent characters as one edit each.
+pub(crate) fn bounded_distance(target: &[char], candidate: &str, max: usize) -> Option<usize> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    if target.len().abs_diff(candidate.len()) > max {
+        return None;
+    }
+
+    let width = candidate.len() + 1;
+    let mut previous: Vec<usize> = Vec::new();
+    let mut row: Vec<usize> = (0..width).collect();
+    for i in 1..=target.len() {
+        let before = std::mem::replace(&mut row, vec![i; width]);
+        for j in 1..width {
+            let cost = usize::from(target[i - 1] != candidate[j - 1]);
+            row[j] = (before[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(before[j - 1] + cost);
+            if i > 1
+                && j > 1
+                && target[i - 1] == candidate[j - 2]
+                && target[i - 2] == candidate[j - 1]
+            {
+                row[j] = row[j].min(previous[j - 2] + 1);
+            }
+        }
+        if row.iter().all(|&d| d > max) {
+            return None;
+        }
+        previous = before;
+    }
+
+    let found = row[width - 1];
+    (found <= max).then_some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str, max: usize) -> Option<usize> {
+        bounded_distance(&a.chars().collect::<Vec<_>>(), b, max)
+    }
+
+    #[test]
+    fn test_bounded_distance_counts_edits() {
+        assert_eq!(distance("confg", "config", 2), Some(1));
+        assert_eq!(distance("authetication", "authentication", 1), Some(1));
+        assert_eq!(distance("recieve", "receive", 1), Some(1));
+        assert_eq!(distance("handler", "handler", 1), Some(0));
+        assert_eq!(distance("cnofig", "config", 2), Some(1));
+    }
+
+    #[test]
+    fn test_bounded_distance_rejects_far_terms() {
+        assert_eq!(distance("config", "configuration", 2), None);
+        assert_eq!(distance("parser", "printer", 1), None);
+        assert_eq!(distance("parser", "passer", 1), Some(1));
+    }
+}
@@ -0,0 +1,341 @@
+//! Identifier spelling expansion.
+//!
+//! The same name is spelled differently across languages and files
+//! (`getUserId`, `get_user_id`, `GetUserId`, `get-user-id`). The
+//! default tokenizer indexes camel and Pascal case as one term
+//! (`getuserid`) but snake and kebab case as a run of terms
+//! (`get user id`), so a query in one spelling misses the others.
+//! [`expand_identifiers`] rewrites each identifier-like query term to
+//! an OR of its spellings. Terms inside quoted phrases, with a field
+//! prefix, or carrying query syntax (`^`, `~`, `*`, escapes) are left
+//! as written.
+
+use crate::core::types::IdentifierExpansion;
+
+/// Variants generated per identifier term
+pub const MAX_VARIANTS_PER_TERM: usize = 6;
+
+/// Split an identifier into lowercase words
+///
+/// Words break at `_` and `-`, where lowercase or a digit is followed
+/// by uppercase, and before the last capital of an acronym
+/// (`parseURLToID` -> `parse`, `url`, `to`, `id`). Digits stay with
+/// the preceding word (`sha256Hash` -> `sha256`, `hash`).
+///
+/// Returns `None` unless the term is made only of ASCII letters,
+/// digits, `_` and `-`, and splits into at least two words that each
+/// start with a letter.
+pub fn split_identifier(term: &str) -> Option<Vec<String>> {
+    if !term
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+
+    let mut words = Vec::new();
+    for segment in term.split(['_', '-']) {
+        if segment.is_empty() {
+            return None;
+        }
+        let chars: Vec<char> = segment.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, c) = (chars[i - 1], chars[i]);
+            let boundary = c.is_ascii_uppercase()
+                && (prev.is_ascii_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_ascii_uppercase()
+                        && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase())));
+            if boundary {
+                words.push(
+                    chars[start..i]
+                        .iter()
+                        .collect::<String>()
+                        .to_ascii_lowercase(),
+                );
+                start = i;
+            }
+        }
+        words.push(
+            chars[start..]
+                .iter()
+                .collect::<String>()
+                .to_ascii_lowercase(),
+        );
+    }
+
+    let starts_with_letter = |w: &String| w.starts_with(|c: char| c.is_ascii_alphabetic());
+    if words.len() < 2 || !words.iter().all(starts_with_letter) {
+        return None;
+    }
+    Some(words)
+}
+
+/// Case-style spellings of an identifier, other than `term` itself
+///
+/// In order: snake, camel, Pascal, kebab and screaming snake case,
+/// at most [`MAX_VARIANTS_PER_TERM`]. Returns `None` if `term` is not
+/// an identifier (see [`split_identifier`]).
+pub fn identifier_variants(term: &str) -> Option<Vec<String>> {
+    let words = split_identifier(term)?;
+    let capitalized: Vec<String> = words.iter().map(|w| capitalize(w)).collect();
+
+    let spellings = [
+        words.join("_"),
+        format!("{}{}", words[0], capitalized[1..].concat()),
+        capitalized.concat(),
+        words.join("-"),
+        words.join("_").to_ascii_uppercase(),
+    ];
+
+    let mut variants: Vec<String> = Vec::new();
+    for spelling in spellings {
+        if spelling != term && !variants.contains(&spelling) {
+            variants.push(spelling);
+        }
+    }
+    variants.truncate(MAX_VARIANTS_PER_TERM);
+    Some(variants)
+}
+
+/// Rewrite identifier terms of a query to an OR of their spellings
+///
+/// `getUserId` becomes `(getUserId OR "get_user_id")`. Variants that
+/// the tokenizer indexes the same way as the term or an earlier
+/// variant (camel and Pascal case; snake, kebab and screaming snake
+/// case) add no clause, so each term gains at most one. A leading
+/// `+` or `-` applies to the whole group.
+///
+/// Returns the rewritten query and the expanded terms in query order.
+pub fn expand_identifiers(query: &str) -> (String, Vec<IdentifierExpansion>) {
+    let mut rewritten = String::with_capacity(query.len());
+    let mut expansions: Vec<IdentifierExpansion> = Vec::new();
+    let mut chunk = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in query.chars() {
+        if in_quotes {
+            rewritten.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_quotes = false;
+            }
+            continue;
+        }
+        if escaped {
+            chunk.push(c);
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => {
+                chunk.push(c);
+                escaped = true;
+            }
+            '"' | '(' | ')' => {
+                rewritten.push_str(&expand_chunk(&chunk, &mut expansions));
+                chunk.clear();
+                rewritten.push(c);
+                in_quotes = c == '"';
+            }
+            c if c.is_whitespace() => {
+                rewritten.push_str(&expand_chunk(&chunk, &mut expansions));
+                chunk.clear();
+                rewritten.push(c);
+            }
+            _ => chunk.push(c),
+        }
+    }
+    rewritten.push_str(&expand_chunk(&chunk, &mut expansions));
+
+    (rewritten, expansions)
+}
+
+/// Expand one whitespace-delimited query chunk, or return it as is
+fn expand_chunk(chunk: &str, expansions: &mut Vec<IdentifierExpansion>) -> String {
+    let (prefix, term) = match chunk.strip_prefix(['+', '-']) {
+        Some(term) => (&chunk[..1], term),
+        None => ("", chunk),
+    };
+    let Some(variants) = identifier_variants(term) else {
+        return chunk.to_string();
+    };
+
+    let mut forms = vec![index_form(term)];
+    let mut clauses = vec![term.to_string()];
+    for variant in &variants {
+        let form = index_form(variant);
+        if !forms.contains(&form) {
+            forms.push(form);
+            if variant.contains(['_', '-']) {
+                clauses.push(format!("\"{variant}\""));
+            } else {
+                clauses.push(variant.clone());
+            }
+        }
+    }
+
+    if !expansions.iter().any(|e| e.term == term) {
+        expansions.push(IdentifierExpansion {
+            term: term.to_string(),
+            variants,
+        });
+    }
+    format!("{prefix}({})", clauses.join(" OR "))
+}
+
+/// The terms the default tokenizer produces for a spelling
+fn index_form(spelling: &str) -> String {
+    spelling
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier_case_styles() {
+        let expected = ["get", "user", "id"];
+        assert_eq!(split_identifier("getUserId").unwrap(), expected);
+        assert_eq!(split_identifier("GetUserId").unwrap(), expected);
+        assert_eq!(split_identifier("get_user_id").unwrap(), expected);
+        assert_eq!(split_identifier("get-user-id").unwrap(), expected);
+        assert_eq!(split_identifier("GET_USER_ID").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_split_identifier_acronyms() {
+        assert_eq!(
+            split_identifier("parseURLToID").unwrap(),
+            vec!["parse", "url", "to", "id"]
+        );
+        assert_eq!(
+            split_identifier("HTTPServer").unwrap(),
+            vec!["http", "server"]
+        );
+        assert_eq!(
+            split_identifier("XMLHttpRequest").unwrap(),
+            vec!["xml", "http", "request"]
+        );
+    }
+
+    #[test]
+    fn test_split_identifier_digits() {
+        assert_eq!(
+            split_identifier("sha256Hash").unwrap(),
+            vec!["sha256", "hash"]
+        );
+        assert_eq!(
+            split_identifier("getHTTP2Client").unwrap(),
+            vec!["get", "http2", "client"]
+        );
+        assert_eq!(
+            split_identifier("utf8_decode").unwrap(),
+            vec!["utf8", "decode"]
+        );
+        assert_eq!(split_identifier("ID2Name").unwrap(), vec!["id2", "name"]);
+        // A word may not start with a digit
+        assert_eq!(split_identifier("x86_64"), None);
+        assert_eq!(split_identifier("2024-01-01"), None);
+    }
+
+    #[test]
+    fn test_split_identifier_rejects_non_identifiers() {
+        assert_eq!(split_identifier("config"), None);
+        assert_eq!(split_identifier("AND"), None);
+        assert_eq!(split_identifier("_private"), None);
+        assert_eq!(split_identifier("a__b"), None);
+        assert_eq!(split_identifier("user.id"), None);
+        assert_eq!(split_identifier("getUser^2"), None);
+    }
+
+    #[test]
+    fn test_identifier_variants() {
+        assert_eq!(
+            identifier_variants("getUserId").unwrap(),
+            vec!["get_user_id", "GetUserId", "get-user-id", "GET_USER_ID"]
+        );
+        assert_eq!(
+            identifier_variants("parseURLToID").unwrap(),
+            vec![
+                "parse_url_to_id",
+                "parseUrlToId",
+                "ParseUrlToId",
+                "parse-url-to-id",
+                "PARSE_URL_TO_ID"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identifier_variants_capped_for_long_terms() {
+        let variants = identifier_variants("aVeryLongIdentifierNameWithManyWordsInIt").unwrap();
+        assert!(variants.len() <= MAX_VARIANTS_PER_TERM);
+        assert_eq!(
+            variants[0],
+            "a_very_long_identifier_name_with_many_words_in_it"
+        );
+    }
+
+    #[test]
+    fn test_expand_identifiers_adds_one_clause_per_index_form() {
+        let (query, expansions) = expand_identifiers("getUserId");
+        assert_eq!(query, "(getUserId OR \"get_user_id\")");
+        assert_eq!(expansions.len(), 1);
+        assert_eq!(expansions[0].term, "getUserId");
+
+        let (query, _) = expand_identifiers("get_user_id");
+        assert_eq!(query, "(get_user_id OR getUserId)");
+    }
+
+    #[test]
+    fn test_expand_identifiers_keeps_query_syntax() {
+        let (query, expansions) = expand_identifiers("fetch AND -userId (loadConfig OR config)");
+        assert_eq!(
+            query,
+            "fetch AND -(userId OR \"user_id\") ((loadConfig OR \"load_config\") OR config)"
+        );
+        assert_eq!(expansions.len(), 2);
+    }
+
+    #[test]
+    fn test_expand_identifiers_skips_phrases_and_syntax() {
+        for query in [
+            "\"getUserId from cache\"",
+            "content:getUserId",
+            "getUserId^2",
+            "getUserId~1",
+            "getUser*",
+            "get\\-user",
+        ] {
+            let (rewritten, expansions) = expand_identifiers(query);
+            assert_eq!(rewritten, query);
+            assert!(expansions.is_empty(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_expand_identifiers_records_repeated_term_once() {
+        let (query, expansions) = expand_identifiers("userId OR userId");
+        assert_eq!(query, "(userId OR \"user_id\") OR (userId OR \"user_id\")");
+        assert_eq!(expansions.len(), 1);
+    }
+}
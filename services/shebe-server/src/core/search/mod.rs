@@ -1,10 +1,45 @@
 //! Search module for BM25 full-text search.
 //!
 //! This module provides search functionality over indexed content
-//! using Tantivy's BM25 ranking algorithm.
+//! using Tantivy's BM25 ranking algorithm, with optional
+//! typo-tolerant (fuzzy) term matching and identifier spelling
+//! expansion ([`identifiers`]). [`drift`] checks result
+//! offsets against files changed since indexing; [`excerpt`] reduces
+//! results to a line and a short excerpt for compact output.
+//! [`scoring`] applies per-session BM25 parameters (k1, b); [`refine`]
+//! restricts a search to another query's top hits; [`similar`] picks
+//! the seed terms of a "more like this" search. [`short_chunks`]
+//! demotes chunks much shorter than the session's chunk size;
+//! [`recency`] keeps only files changed within a time window;
+//! [`sections`] keeps only chunks under matching document headings;
+//! [`exclusions`] leaves out chunks with negative terms or paths;
+//! [`explain`] breaks result scores down per query term;
+//! [`collection`] searches several sessions and merges the results.
 
 mod bm25;
+pub mod collection;
+pub mod drift;
+pub mod excerpt;
+mod exclusions;
+pub mod explain;
+pub mod fuzzy;
+pub mod identifiers;
+mod overlap;
 mod query;
+pub mod recency;
+mod refine;
+pub mod scoring;
+mod sections;
+pub mod short_chunks;
+pub mod similar;
 
 pub use bm25::SearchService;
+pub use collection::merge_responses;
+pub use drift::{locate_chunk, ChunkLocation};
+pub use excerpt::{locate_result, match_terms, query_terms, ResultLocation, EXCERPT_MAX_CHARS};
+pub use exclusions::MAX_EXCLUSIONS;
+pub use explain::MAX_EXPLAINED_RESULTS;
 pub use query::{preprocess_query, validate_query_fields};
+pub use recency::parse_modified_within;
+pub use scoring::{Bm25Params, DEFAULT_BM25_B, DEFAULT_BM25_K1, MAX_BM25_K1};
+pub use short_chunks::{ShortChunkPenalty, DEFAULT_SHORT_CHUNK_FACTOR, DEFAULT_SHORT_CHUNK_RATIO};
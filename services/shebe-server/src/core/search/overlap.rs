@@ -0,0 +1,206 @@
+//! Chunk overlap deduplication for search results.
+//!
+//! Consecutive chunks of a file share `overlap` characters, so a term
+//! that lands in the shared region matches both chunks and the same
+//! code shows up twice in the results. This module locates the query
+//! matches inside each hit and drops the lower-scoring hit of an
+//! adjacent pair when all of its matches fall inside the overlap.
+
+use crate::core::error::{Result, ShebeError};
+use crate::core::storage::TantivyIndex;
+use crate::core::types::SearchResult;
+use std::collections::HashSet;
+use std::ops::Range;
+use tantivy::{query::QueryParser, tokenizer::TextAnalyzer};
+
+/// Finds the byte spans of query terms inside search hits
+pub(crate) struct MatchLocator {
+    terms: HashSet<String>,
+    tokenizer: TextAnalyzer,
+}
+
+impl MatchLocator {
+    /// Build a locator for the `text` field terms of a query
+    pub(crate) fn new(index: &TantivyIndex, query_str: &str) -> Result<Self> {
+        let text_field = index
+            .schema()
+            .get_field("text")
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing text field: {e}")))?;
+
+        let query = QueryParser::for_index(index.index(), vec![text_field])
+            .parse_query(query_str)
+            .map_err(|e| ShebeError::InvalidQuery(format!("Failed to parse query: {e}")))?;
+
+        let mut terms = HashSet::new();
+        query.query_terms(&mut |term, _| {
+            if term.field() == text_field {
+                if let Some(text) = term.value().as_str() {
+                    terms.insert(text.to_string());
+                }
+            }
+        });
+
+        let tokenizer = index
+            .index()
+            .tokenizer_for_field(text_field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Missing tokenizer: {e}")))?;
+
+        Ok(Self { terms, tokenizer })
+    }
+
+    /// Also locate these terms (e.g. fuzzy expansions of query terms)
+    pub(crate) fn add_terms(&mut self, terms: impl IntoIterator<Item = String>) {
+        self.terms.extend(terms);
+    }
+
+    /// File byte ranges of every query term occurrence in a hit
+    pub(crate) fn spans(&mut self, hit: &SearchResult) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        if self.terms.is_empty() {
+            return spans;
+        }
+
+        let terms = &self.terms;
+        let mut stream = self.tokenizer.token_stream(&hit.text);
+        stream.process(&mut |token| {
+            if terms.contains(&token.text) {
+                spans
+                    .push(hit.start_offset + token.offset_from..hit.start_offset + token.offset_to);
+            }
+        });
+
+        spans
+    }
+}
+
+/// Drop hits that only repeat matches of an adjacent, better hit
+///
+/// `hits` must be in score order. A hit is dropped when a kept hit
+/// from the same file has a neighbouring `chunk_index` and every
+/// match span of the hit lies inside the bytes both chunks share.
+/// Hits without located matches are always kept. Stops once `limit`
+/// hits are kept and returns them with the number of hits dropped.
+pub(crate) fn drop_overlap_duplicates(
+    hits: Vec<SearchResult>,
+    limit: usize,
+    mut spans_of: impl FnMut(&SearchResult) -> Vec<Range<usize>>,
+) -> (Vec<SearchResult>, usize) {
+    let mut kept: Vec<SearchResult> = Vec::new();
+    let mut dropped = 0;
+
+    for hit in hits {
+        if kept.len() >= limit {
+            break;
+        }
+
+        let overlaps: Vec<Range<usize>> = kept
+            .iter()
+            .filter(|k| {
+                k.file_path == hit.file_path && k.chunk_index.abs_diff(hit.chunk_index) == 1
+            })
+            .map(|k| k.start_offset.max(hit.start_offset)..k.end_offset.min(hit.end_offset))
+            .filter(|overlap| !overlap.is_empty())
+            .collect();
+
+        if !overlaps.is_empty() {
+            let spans = spans_of(&hit);
+            let duplicate = !spans.is_empty()
+                && overlaps.iter().any(|overlap| {
+                    spans
+                        .iter()
+                        .all(|s| overlap.start <= s.start && s.end <= overlap.end)
+                });
+            if duplicate {
+                dropped += 1;
+                continue;
+            }
+        }
+
+        kept.push(hit);
+    }
+
+    (kept, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Match span inside the bytes shared by chunks 0 (0..100) and 1 (80..180)
+    const IN_OVERLAP: Range<usize> = 85..90;
+
+    fn hit(file: &str, chunk_index: usize, start: usize, end: usize) -> SearchResult {
+        SearchResult {
+            score: 1.0,
+            text: String::new(),
+            file_path: file.to_string(),
+            chunk_index,
+            session: None,
+            start_offset: start,
+            end_offset: end,
+            also_present_at: Vec::new(),
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        }
+    }
+
+    #[test]
+    fn test_drops_match_inside_overlap() {
+        let hits = vec![hit("a.rs", 0, 0, 100), hit("a.rs", 1, 80, 180)];
+        let (kept, dropped) = drop_overlap_duplicates(hits, 10, |_| vec![IN_OVERLAP]);
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].chunk_index, 0);
+        assert_eq!(dropped, 1);
+    }
+
+    #[test]
+    fn test_keeps_match_outside_overlap() {
+        let hits = vec![hit("a.rs", 0, 0, 100), hit("a.rs", 1, 80, 180)];
+        let (kept, dropped) = drop_overlap_duplicates(hits, 10, |_| vec![IN_OVERLAP, 150..155]);
+
+        assert_eq!(kept.len(), 2);
+        assert_eq!(dropped, 0);
+    }
+
+    #[test]
+    fn test_keeps_non_adjacent_and_other_files() {
+        let hits = vec![
+            hit("a.rs", 0, 0, 100),
+            hit("a.rs", 2, 160, 260),
+            hit("b.rs", 1, 80, 180),
+        ];
+        let (kept, dropped) = drop_overlap_duplicates(hits, 10, |_| vec![IN_OVERLAP]);
+
+        assert_eq!(kept.len(), 3);
+        assert_eq!(dropped, 0);
+    }
+
+    #[test]
+    fn test_keeps_hits_without_spans() {
+        let hits = vec![hit("a.rs", 0, 0, 100), hit("a.rs", 1, 80, 180)];
+        let (kept, _) = drop_overlap_duplicates(hits, 10, |_| Vec::new());
+
+        assert_eq!(kept.len(), 2);
+    }
+
+    #[test]
+    fn test_stops_at_limit() {
+        let hits = vec![
+            hit("a.rs", 0, 0, 100),
+            hit("b.rs", 0, 0, 100),
+            hit("a.rs", 1, 80, 180),
+        ];
+        let (kept, dropped) = drop_overlap_duplicates(hits, 2, |_| vec![IN_OVERLAP]);
+
+        // The duplicate lies past the limit, so it is not counted
+        assert_eq!(kept.len(), 2);
+        assert_eq!(dropped, 0);
+    }
+}
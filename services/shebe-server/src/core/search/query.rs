@@ -38,7 +38,7 @@ static MULTI_COLON_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\w+:\w+:\w+
 /// # Examples
 ///
 /// ```
-/// use shebe_server::core::search::preprocess_query;
+/// use shebe::core::search::preprocess_query;
 ///
 /// // Normal mode: Curly braces are escaped
 /// assert_eq!(preprocess_query("{id}", false), "\\{id\\}");
@@ -134,7 +134,7 @@ static FIELD_PREFIX_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w+):([^\s
 /// # Examples
 ///
 /// ```
-/// use shebe_server::core::search::validate_query_fields;
+/// use shebe::core::search::validate_query_fields;
 ///
 /// // Valid fields pass
 /// assert!(validate_query_fields("content:test").is_ok());
@@ -0,0 +1,130 @@
+//! Restriction of a search to recently changed files.
+//!
+//! Each chunk records when its file last changed in the `modified_at`
+//! fast field (schema v7): the last commit touching it in a git
+//! checkout, its modification time otherwise (see
+//! [`crate::core::indexer::modified_times`]). `modified_within` is
+//! either a window back from now (`"12h"`, `"30d"`, `"2w"`) or a start
+//! date (`"2026-09-01"`, or RFC 3339 with a time). Chunks of files
+//! changed before that are filtered out without changing the scores
+//! of the rest.
+
+use crate::core::error::{Result, ShebeError};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::ops::Bound;
+use tantivy::query::{ConstScoreQuery, Query, RangeQuery};
+
+/// Fast field holding each chunk's file change time (seconds since the epoch)
+pub(crate) const MODIFIED_AT_FIELD: &str = "modified_at";
+
+/// Start of a `modified_within` window, relative to `now`
+///
+/// # Errors
+///
+/// `InvalidQuery` for anything but a positive count of hours, days or
+/// weeks, a `YYYY-MM-DD` date or an RFC 3339 time not in the future.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use shebe::core::search::parse_modified_within;
+///
+/// let now = Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+/// let since = parse_modified_within("30d", now).unwrap();
+/// assert_eq!(since, Utc.with_ymd_and_hms(2026, 9, 17, 12, 0, 0).unwrap());
+///
+/// let since = parse_modified_within("2026-09-01", now).unwrap();
+/// assert_eq!(since, Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap());
+/// ```
+pub fn parse_modified_within(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    let invalid = || {
+        ShebeError::InvalidQuery(format!(
+            "modified_within must be a window like '12h', '30d' or '2w', \
+             or a date like '2026-09-01', got '{value}'"
+        ))
+    };
+
+    let since = if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc()
+    } else if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        time.with_timezone(&Utc)
+    } else {
+        let split = value.char_indices().last().map_or(0, |(i, _)| i);
+        let (count, unit) = value.split_at(split);
+        let count: i64 = count.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?;
+        let window = match unit {
+            "h" => Duration::try_hours(count),
+            "d" => Duration::try_days(count),
+            "w" => Duration::try_weeks(count),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        now.checked_sub_signed(window).ok_or_else(invalid)?
+    };
+
+    if since > now {
+        return Err(ShebeError::InvalidQuery(format!(
+            "modified_within date '{value}' is in the future"
+        )));
+    }
+    Ok(since)
+}
+
+/// Filter matching chunks of files changed at or after `since`, scoring 0
+pub(super) fn modified_since_query(since: DateTime<Utc>) -> Box<dyn Query> {
+    let range = RangeQuery::new_i64_bounds(
+        MODIFIED_AT_FIELD.to_string(),
+        Bound::Included(since.timestamp()),
+        Bound::Unbounded,
+    );
+    Box::new(ConstScoreQuery::new(Box::new(range), 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        assert_eq!(
+            parse_modified_within("12h", now()).unwrap(),
+            now() - Duration::hours(12)
+        );
+        assert_eq!(
+            parse_modified_within("2w", now()).unwrap(),
+            now() - Duration::days(14)
+        );
+        assert_eq!(
+            parse_modified_within("2026-10-01T08:30:00+02:00", now()).unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 1, 6, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_windows() {
+        for value in [
+            "",
+            "d",
+            "0d",
+            "-3d",
+            "30",
+            "30m",
+            "3.5d",
+            "3é",
+            "2026-13-01",
+            "2027-01-01",
+        ] {
+            assert!(
+                parse_modified_within(value, now()).is_err(),
+                "'{value}' should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Search within the results of another query.
+//!
+//! A refined search first runs the inner (`within`) query and keeps
+//! its top hits, then evaluates the primary query only over those
+//! chunks. [`DocSetQuery`] is the filter: it matches a fixed set of
+//! documents and scores nothing, so combined as a `Must` clause it
+//! restricts the primary query without changing its ranking.
+//!
+//! Unlike AND-ing the two query strings, the inner query keeps its own
+//! syntax (a phrase stays a phrase) and its own `k` cutoff.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tantivy::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{
+    DocAddress, DocId, DocSet, Score, Searcher, SegmentId, SegmentReader, TantivyError, TERMINATED,
+};
+
+/// Matches a fixed set of documents with a score of 0
+#[derive(Debug, Clone)]
+pub(crate) struct DocSetQuery {
+    /// Sorted doc ids per segment
+    docs: Arc<HashMap<SegmentId, Vec<DocId>>>,
+}
+
+impl DocSetQuery {
+    /// Build the filter for documents found by `searcher`
+    pub(crate) fn new(searcher: &Searcher, addresses: &[DocAddress]) -> Self {
+        let mut docs: HashMap<SegmentId, Vec<DocId>> = HashMap::new();
+        for address in addresses {
+            let segment_id = searcher.segment_reader(address.segment_ord).segment_id();
+            docs.entry(segment_id).or_default().push(address.doc_id);
+        }
+        for ids in docs.values_mut() {
+            ids.sort_unstable();
+            ids.dedup();
+        }
+        Self {
+            docs: Arc::new(docs),
+        }
+    }
+
+    /// Documents matched by both `self` and `other`
+    pub(crate) fn intersect(&self, other: &DocSetQuery) -> Self {
+        let docs = self
+            .docs
+            .iter()
+            .filter_map(|(segment_id, ids)| {
+                let others = other.docs.get(segment_id)?;
+                let both: Vec<DocId> = ids
+                    .iter()
+                    .filter(|id| others.binary_search(id).is_ok())
+                    .copied()
+                    .collect();
+                Some((*segment_id, both))
+            })
+            .collect();
+        Self {
+            docs: Arc::new(docs),
+        }
+    }
+}
+
+impl Query for DocSetQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(DocSetWeight {
+            docs: Arc::clone(&self.docs),
+        }))
+    }
+}
+
+struct DocSetWeight {
+    docs: Arc<HashMap<SegmentId, Vec<DocId>>>,
+}
+
+impl Weight for DocSetWeight {
+    fn scorer(&self, reader: &SegmentReader, _boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        match self.docs.get(&reader.segment_id()) {
+            Some(ids) => Ok(Box::new(DocSetScorer {
+                ids: ids.clone(),
+                cursor: 0,
+            })),
+            None => Ok(Box::new(EmptyScorer)),
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("Within refinement filter", 0.0))
+    }
+}
+
+struct DocSetScorer {
+    ids: Vec<DocId>,
+    cursor: usize,
+}
+
+impl DocSet for DocSetScorer {
+    fn advance(&mut self) -> DocId {
+        self.cursor = (self.cursor + 1).min(self.ids.len());
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.ids.get(self.cursor).copied().unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        (self.ids.len() - self.cursor) as u32
+    }
+}
+
+impl Scorer for DocSetScorer {
+    fn score(&mut self) -> Score {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::{Count, DocSetCollector};
+    use tantivy::query::{AllQuery, BooleanQuery, Occur, QueryParser};
+    use tantivy::schema::{Schema, TEXT};
+    use tantivy::{doc, Index};
+
+    #[test]
+    fn test_doc_set_query_matches_only_given_docs() {
+        let mut builder = Schema::builder();
+        let text = builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = index.writer(15_000_000).unwrap();
+        for body in ["alpha beta", "alpha", "beta", "alpha gamma"] {
+            writer.add_document(doc!(text => body)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let all: Vec<DocAddress> = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .unwrap()
+            .into_iter()
+            .collect();
+        let mut picked: Vec<DocAddress> = all.into_iter().filter(|a| a.doc_id % 2 == 0).collect();
+        picked.reverse();
+        let filter = DocSetQuery::new(&searcher, &picked);
+        assert_eq!(searcher.search(&filter, &Count).unwrap(), 2);
+
+        // docs 0 and 2 are picked; only doc 0 also contains "alpha"
+        let alpha = QueryParser::for_index(&index, vec![text])
+            .parse_query("alpha")
+            .unwrap();
+        let refined =
+            BooleanQuery::new(vec![(Occur::Must, alpha), (Occur::Must, Box::new(filter))]);
+        let hits = searcher.search(&refined, &DocSetCollector).unwrap();
+        assert_eq!(hits.into_iter().map(|a| a.doc_id).collect::<Vec<_>>(), [0]);
+    }
+}
@@ -0,0 +1,362 @@
+//! BM25 parameters (k1, b) per session and per request.
+//!
+//! Tantivy scores with fixed constants (`k1 = 1.2`, `b = 0.75`), which
+//! suit short documents. For sessions with large chunks a lower `b`
+//! (less length normalization) or different `k1` (term frequency
+//! saturation) can rank better. [`with_params`] rewrites the term
+//! clauses of a parsed query into [`TunedTermQuery`]s, which compute
+//! the same BM25 formula with the given parameters:
+//!
+//! ```text
+//! idf(t) * tf * (k1 + 1) / (tf + k1 * (1 - b + b * len / avg_len))
+//! ```
+//!
+//! With the default parameters queries are left untouched, so scores
+//! are exactly Tantivy's. Phrase and `^`-boosted clauses always keep
+//! Tantivy's scoring.
+
+use crate::core::error::{Result, ShebeError};
+use serde::{Deserialize, Serialize};
+use tantivy::fieldnorm::FieldNormReader;
+use tantivy::postings::SegmentPostings;
+use tantivy::query::{
+    BooleanQuery, EmptyScorer, EnableScoring, Explanation, Query, Scorer, TermQuery, Weight,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Postings, Score, SegmentReader, TantivyError, Term};
+
+/// Tantivy's term frequency saturation
+pub const DEFAULT_BM25_K1: f32 = 1.2;
+
+/// Tantivy's length normalization
+pub const DEFAULT_BM25_B: f32 = 0.75;
+
+/// Largest accepted `k1`
+pub const MAX_BM25_K1: f32 = 3.0;
+
+/// BM25 term frequency saturation (`k1`) and length normalization (`b`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bm25Params {
+    /// Term frequency saturation, 0-3 (0 ignores term frequency)
+    pub k1: f32,
+    /// Length normalization, 0-1 (0 ignores chunk length)
+    pub b: f32,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self {
+            k1: DEFAULT_BM25_K1,
+            b: DEFAULT_BM25_B,
+        }
+    }
+}
+
+impl Bm25Params {
+    /// Check that `k1` is within 0-3 and `b` within 0-1
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` naming the parameter and its range.
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=MAX_BM25_K1).contains(&self.k1) {
+            return Err(ShebeError::ConfigError(format!(
+                "bm25_k1 must be between 0 and {MAX_BM25_K1}, got {}",
+                self.k1
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.b) {
+            return Err(ShebeError::ConfigError(format!(
+                "bm25_b must be between 0 and 1, got {}",
+                self.b
+            )));
+        }
+        Ok(())
+    }
+
+    /// These parameters with `k1` and/or `b` replaced where given
+    pub fn with_overrides(self, k1: Option<f32>, b: Option<f32>) -> Self {
+        Self {
+            k1: k1.unwrap_or(self.k1),
+            b: b.unwrap_or(self.b),
+        }
+    }
+
+    /// Whether these are Tantivy's built-in parameters
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A term query scored with `params`
+pub(crate) fn term_query(term: Term, params: Bm25Params) -> Box<dyn Query> {
+    if params.is_default() {
+        Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+    } else {
+        Box::new(TunedTermQuery { term, params })
+    }
+}
+
+/// Rewrite the term clauses on `field` in `query` to score with `params`
+///
+/// Boolean queries are rewritten clause by clause; all other queries
+/// are kept as they are. Returns `query` unchanged for the default
+/// parameters.
+pub(crate) fn with_params(
+    query: Box<dyn Query>,
+    field: Field,
+    params: Bm25Params,
+) -> Box<dyn Query> {
+    if params.is_default() {
+        return query;
+    }
+    if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+        if term_query.term().field() == field {
+            return Box::new(TunedTermQuery {
+                term: term_query.term().clone(),
+                params,
+            });
+        }
+        return query;
+    }
+    if let Some(boolean) = query.downcast_ref::<BooleanQuery>() {
+        let clauses = boolean
+            .clauses()
+            .iter()
+            .map(|(occur, clause)| (*occur, with_params(clause.box_clone(), field, params)))
+            .collect();
+        return Box::new(BooleanQuery::new(clauses));
+    }
+    query
+}
+
+/// Term query scored with BM25 using custom `k1` and `b`
+#[derive(Debug, Clone)]
+pub(crate) struct TunedTermQuery {
+    term: Term,
+    params: Bm25Params,
+}
+
+impl Query for TunedTermQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let (idf, average_length) = match enable_scoring {
+            EnableScoring::Enabled {
+                statistics_provider,
+                ..
+            } => {
+                let total_docs = statistics_provider.total_num_docs()?.max(1) as Score;
+                let total_tokens = statistics_provider.total_num_tokens(self.term.field())?;
+                let doc_freq = statistics_provider.doc_freq(&self.term)? as Score;
+                let idf = (1.0 + (total_docs - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+                (idf, total_tokens as Score / total_docs)
+            }
+            EnableScoring::Disabled { .. } => (0.0, 1.0),
+        };
+        Ok(Box::new(TunedTermWeight {
+            term: self.term.clone(),
+            params: self.params,
+            idf,
+            average_length,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        visitor(&self.term, false);
+    }
+}
+
+struct TunedTermWeight {
+    term: Term,
+    params: Bm25Params,
+    idf: Score,
+    average_length: Score,
+}
+
+impl TunedTermWeight {
+    fn tuned_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> tantivy::Result<Option<TunedTermScorer>> {
+        let field = self.term.field();
+        let postings = reader
+            .inverted_index(field)?
+            .read_postings(&self.term, IndexRecordOption::WithFreqs)?;
+        let Some(postings) = postings else {
+            return Ok(None);
+        };
+        Ok(Some(TunedTermScorer {
+            postings,
+            fieldnorms: reader.get_fieldnorms_reader(field)?,
+            params: self.params,
+            weight: boost * self.idf * (self.params.k1 + 1.0),
+            average_length: self.average_length.max(Score::MIN_POSITIVE),
+        }))
+    }
+}
+
+impl Weight for TunedTermWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(match self.tuned_scorer(reader, boost)? {
+            Some(scorer) => Box::new(scorer),
+            None => Box::new(EmptyScorer),
+        })
+    }
+
+    /// Uses Tantivy's descriptions for the term frequency and field
+    /// length, so both explanations are summarized the same way
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let does_not_match =
+            || TantivyError::InvalidArgument(format!("Document #({doc}) does not match"));
+        let Some(mut scorer) = self.tuned_scorer(reader, 1.0)? else {
+            return Err(does_not_match());
+        };
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(does_not_match());
+        }
+        let mut explanation = Explanation::new("BM25 with session k1 and b", scorer.score());
+        explanation.add_const("k1", self.params.k1);
+        explanation.add_const("b", self.params.b);
+        explanation.add_const("idf", self.idf);
+        explanation.add_const(
+            "freq, occurrences of term within document",
+            scorer.postings.term_freq() as Score,
+        );
+        explanation.add_const(
+            "dl, length of field",
+            scorer.fieldnorms.fieldnorm(doc) as Score,
+        );
+        explanation.add_context(format!("Term={:?}", self.term));
+        Ok(explanation)
+    }
+}
+
+struct TunedTermScorer {
+    postings: SegmentPostings,
+    fieldnorms: FieldNormReader,
+    params: Bm25Params,
+    /// `boost * idf * (k1 + 1)`
+    weight: Score,
+    average_length: Score,
+}
+
+impl DocSet for TunedTermScorer {
+    fn advance(&mut self) -> DocId {
+        self.postings.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.postings.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.postings.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings.size_hint()
+    }
+}
+
+impl Scorer for TunedTermScorer {
+    fn score(&mut self) -> Score {
+        let Bm25Params { k1, b } = self.params;
+        let term_freq = self.postings.term_freq() as Score;
+        let length = self.fieldnorms.fieldnorm(self.doc()) as Score;
+        let norm = k1 * (1.0 - b + b * length / self.average_length);
+        self.weight * term_freq / (term_freq + norm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::TopDocs;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{Schema, TEXT};
+    use tantivy::{doc, Index};
+
+    fn test_index() -> (Index, Field) {
+        let mut builder = Schema::builder();
+        let text = builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        writer
+            .add_document(doc!(text => "parse parse config"))
+            .unwrap();
+        writer
+            .add_document(doc!(
+                text => "parse parse parse parse config loader reads every file \
+                         and builds the settings tree for the server at startup"
+            ))
+            .unwrap();
+        writer
+            .add_document(doc!(text => "unrelated words"))
+            .unwrap();
+        writer.commit().unwrap();
+        (index, text)
+    }
+
+    fn scores(index: &Index, field: Field, query: &str, params: Bm25Params) -> Vec<Score> {
+        let query = QueryParser::for_index(index, vec![field])
+            .parse_query(query)
+            .unwrap();
+        let query = with_params(query, field, params);
+        let searcher = index.reader().unwrap().searcher();
+        let mut hits = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        hits.sort_by_key(|(_, address)| address.doc_id);
+        hits.into_iter().map(|(score, _)| score).collect()
+    }
+
+    #[test]
+    fn test_validate_bounds() {
+        assert!(Bm25Params::default().validate().is_ok());
+        assert!(Bm25Params { k1: 0.0, b: 1.0 }.validate().is_ok());
+
+        let err = Bm25Params { k1: 3.5, b: 0.5 }.validate().unwrap_err();
+        assert!(err.to_string().contains("bm25_k1 must be between 0 and 3"));
+        let err = Bm25Params { k1: 1.0, b: -0.1 }.validate().unwrap_err();
+        assert!(err.to_string().contains("bm25_b must be between 0 and 1"));
+        assert!(Bm25Params {
+            k1: f32::NAN,
+            b: 0.5
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_tuned_scores_match_tantivy_defaults() {
+        let (index, field) = test_index();
+        let tantivy = scores(&index, field, "parse config", Bm25Params::default());
+
+        // Tiny offsets bypass the default shortcut but score the same
+        let tuned = scores(
+            &index,
+            field,
+            "parse config",
+            Bm25Params {
+                k1: DEFAULT_BM25_K1 + 1e-6,
+                b: DEFAULT_BM25_B,
+            },
+        );
+
+        assert_eq!(tantivy.len(), 2);
+        for (a, b) in tantivy.iter().zip(&tuned) {
+            assert!((a - b).abs() < 1e-3, "{tantivy:?} vs {tuned:?}");
+        }
+    }
+
+    #[test]
+    fn test_b_controls_length_normalization() {
+        let (index, field) = test_index();
+
+        // Without length normalization the longer chunk's extra matches win
+        let flat = scores(&index, field, "parse", Bm25Params { k1: 1.2, b: 0.0 });
+        assert!(flat[1] > flat[0], "{flat:?}");
+
+        // Full normalization penalizes the long chunk
+        let normalized = scores(&index, field, "parse", Bm25Params { k1: 1.2, b: 1.0 });
+        assert!(normalized[0] > normalized[1], "{normalized:?}");
+    }
+}
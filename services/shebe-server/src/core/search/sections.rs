@@ -0,0 +1,69 @@
+//! Restriction of a search to documentation sections.
+//!
+//! Chunks of Markdown and reStructuredText files record the headings
+//! in effect at their start in the `section` field (schema v9, see
+//! [`crate::core::indexer::sections`]). `section_filter` keeps only
+//! chunks whose breadcrumb contains the filter text, ignoring case.
+//! The field holds each breadcrumb as one term, so the matching ones
+//! are picked from the term dictionary and their chunks become a
+//! [`DocSetQuery`] filter that leaves scores unchanged.
+
+use super::refine::DocSetQuery;
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::sections::SECTION_FIELD;
+use tantivy::collector::DocSetCollector;
+use tantivy::query::TermSetQuery;
+use tantivy::schema::Schema;
+use tantivy::{DocAddress, Searcher, Term};
+
+/// Filter matching chunks whose breadcrumb contains `text`
+///
+/// # Errors
+///
+/// `InvalidQuery` for a blank `text` or a session indexed before
+/// sections were recorded.
+pub(super) fn section_filter(
+    searcher: &Searcher,
+    schema: &Schema,
+    session_id: &str,
+    text: &str,
+) -> Result<DocSetQuery> {
+    let needle = text.trim().to_lowercase();
+    if needle.is_empty() {
+        return Err(ShebeError::InvalidQuery(
+            "section_filter cannot be empty".to_string(),
+        ));
+    }
+    let field = schema.get_field(SECTION_FIELD).map_err(|_| {
+        ShebeError::InvalidQuery(format!(
+            "Session '{session_id}' was indexed without document sections; \
+             re-index it to filter by section_filter"
+        ))
+    })?;
+
+    let mut terms = Vec::new();
+    for segment in searcher.segment_readers() {
+        let inverted = segment
+            .inverted_index(field)
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read sections: {e}")))?;
+        let mut stream = inverted
+            .terms()
+            .stream()
+            .map_err(|e| ShebeError::SearchFailed(format!("Failed to read sections: {e}")))?;
+        while stream.advance() {
+            let Ok(section) = std::str::from_utf8(stream.key()) else {
+                continue;
+            };
+            if section.to_lowercase().contains(&needle) {
+                terms.push(Term::from_field_text(field, section));
+            }
+        }
+    }
+
+    let addresses: Vec<DocAddress> = searcher
+        .search(&TermSetQuery::new(terms), &DocSetCollector)
+        .map_err(|e| ShebeError::SearchFailed(format!("Search failed: {e}")))?
+        .into_iter()
+        .collect();
+    Ok(DocSetQuery::new(searcher, &addresses))
+}
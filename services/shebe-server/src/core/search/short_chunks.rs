@@ -0,0 +1,119 @@
+//! Demotion of very short chunks.
+//!
+//! BM25 normalizes term frequency by chunk length, so a file's short
+//! trailing chunk (its last few dozen characters) that mentions a query
+//! term can outscore a full chunk that actually implements it. With a
+//! [`ShortChunkPenalty`], chunks shorter than `min_ratio` of the
+//! session's chunk size have their score multiplied by `factor` before
+//! the top hits are selected.
+//!
+//! Chunk lengths come from the `char_count` fast field (schema v6).
+//! Sessions indexed without it are never demoted.
+
+use crate::core::error::{Result, ShebeError};
+use tantivy::collector::{Collector, TopDocs};
+use tantivy::{DocAddress, DocId, Score, SegmentReader};
+
+/// Default share of the chunk size below which a chunk is demoted
+pub const DEFAULT_SHORT_CHUNK_RATIO: f32 = 0.15;
+
+/// Default score multiplier for demoted chunks
+pub const DEFAULT_SHORT_CHUNK_FACTOR: f32 = 0.5;
+
+/// Fast field holding each chunk's length in characters
+pub(crate) const CHAR_COUNT_FIELD: &str = "char_count";
+
+/// How short chunks are demoted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShortChunkPenalty {
+    /// Chunks shorter than this share of the chunk size are demoted (0-1)
+    pub min_ratio: f32,
+    /// Multiplier applied to a demoted chunk's score (0-1)
+    pub factor: f32,
+}
+
+impl Default for ShortChunkPenalty {
+    fn default() -> Self {
+        Self {
+            min_ratio: DEFAULT_SHORT_CHUNK_RATIO,
+            factor: DEFAULT_SHORT_CHUNK_FACTOR,
+        }
+    }
+}
+
+impl ShortChunkPenalty {
+    /// Check that `min_ratio` is within 0-1 and `factor` above 0 and at most 1
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` naming the setting and its range.
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.min_ratio) {
+            return Err(ShebeError::ConfigError(format!(
+                "short_chunk_ratio must be between 0 and 1, got {}",
+                self.min_ratio
+            )));
+        }
+        if !(self.factor > 0.0 && self.factor <= 1.0) {
+            return Err(ShebeError::ConfigError(format!(
+                "short_chunk_factor must be above 0 and at most 1, got {}",
+                self.factor
+            )));
+        }
+        Ok(())
+    }
+
+    /// Chunks with fewer characters than this are demoted
+    ///
+    /// `min_ratio` of `chunk_size`, rounded to the nearest character.
+    pub fn min_chars(&self, chunk_size: usize) -> u64 {
+        (chunk_size as f64 * f64::from(self.min_ratio)).round() as u64
+    }
+}
+
+/// Top `limit` hits, with chunks under `min_chars` characters scored
+/// times `factor`
+///
+/// Segments without the `char_count` fast field keep their scores.
+pub(super) fn demoting_top_docs(
+    limit: usize,
+    min_chars: u64,
+    factor: f32,
+) -> impl Collector<Fruit = Vec<(Score, DocAddress)>> {
+    TopDocs::with_limit(limit).tweak_score(move |segment: &SegmentReader| {
+        let char_counts = segment.fast_fields().u64(CHAR_COUNT_FIELD).ok();
+        move |doc: DocId, score: Score| match char_counts.as_ref().and_then(|c| c.first(doc)) {
+            Some(chars) if chars < min_chars => score * factor,
+            _ => score,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_chars() {
+        let penalty = ShortChunkPenalty::default();
+        assert_eq!(penalty.min_chars(512), 77);
+        assert_eq!(penalty.min_chars(100), 15);
+        assert_eq!(
+            ShortChunkPenalty {
+                min_ratio: 0.0,
+                factor: 0.5
+            }
+            .min_chars(512),
+            0
+        );
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        assert!(ShortChunkPenalty::default().validate().is_ok());
+        for (min_ratio, factor) in [(-0.1, 0.5), (1.5, 0.5), (0.15, 0.0), (0.15, 1.5)] {
+            let penalty = ShortChunkPenalty { min_ratio, factor };
+            assert!(penalty.validate().is_err(), "{penalty:?}");
+        }
+    }
+}
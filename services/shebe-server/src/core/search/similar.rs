@@ -0,0 +1,124 @@
+//! "More like this" seed terms for a chunk.
+//!
+//! To find chunks similar to a source chunk, its text is tokenized the
+//! way the index tokenizes it and each term is weighted by TF-IDF:
+//! frequency in the chunk times the BM25 inverse document frequency
+//! across the session. The top terms become a disjunctive query (see
+//! [`super::SearchService::find_similar`]).
+//!
+//! Terms no other chunk contains cannot match anything and are skipped,
+//! as are terms shorter than `min_term_len` and plain numbers. A hit
+//! must share at least [`MIN_SHARED_TERMS`] seed terms, so one common
+//! word in otherwise unrelated code is not reported as similar.
+
+use std::collections::HashMap;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+
+/// Seed terms taken from the source chunk by default
+pub const DEFAULT_SEED_TERMS: usize = 10;
+
+/// Most seed terms a request may ask for
+pub const MAX_SEED_TERMS: usize = 25;
+
+/// Shortest seed term (in characters) by default
+pub const DEFAULT_MIN_TERM_LEN: usize = 3;
+
+/// Seed terms a hit must share with the source chunk
+pub const MIN_SHARED_TERMS: usize = 2;
+
+/// Indexed terms of `text` with at least `min_term_len` characters,
+/// and how often each occurs
+pub(crate) fn term_frequencies(
+    analyzer: &mut TextAnalyzer,
+    text: &str,
+    min_term_len: usize,
+) -> HashMap<String, u32> {
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    let mut stream = analyzer.token_stream(text);
+    stream.process(&mut |token| {
+        let term = token.text.as_str();
+        if term.chars().count() >= min_term_len && !term.chars().all(|c| c.is_ascii_digit()) {
+            *frequencies.entry(term.to_string()).or_default() += 1;
+        }
+    });
+    frequencies
+}
+
+/// The `limit` most distinctive terms, best first
+///
+/// `doc_freq` gives the number of chunks containing a term (the source
+/// chunk included) out of `num_docs`. Terms found in no other chunk are
+/// dropped. Ties are broken alphabetically so the result is stable.
+pub(crate) fn select_seed_terms(
+    frequencies: HashMap<String, u32>,
+    doc_freq: impl Fn(&str) -> u64,
+    num_docs: u64,
+    limit: usize,
+) -> Vec<String> {
+    let mut weighted: Vec<(String, f32)> = frequencies
+        .into_iter()
+        .filter_map(|(term, tf)| {
+            let df = doc_freq(&term);
+            (df >= 2).then(|| {
+                let weight = tf as f32 * idf(df, num_docs);
+                (term, weight)
+            })
+        })
+        .collect();
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    weighted.truncate(limit);
+    weighted.into_iter().map(|(term, _)| term).collect()
+}
+
+/// BM25 inverse document frequency (as Tantivy computes it)
+fn idf(doc_freq: u64, num_docs: u64) -> f32 {
+    let (df, n) = (doc_freq as f32, num_docs.max(doc_freq) as f32);
+    (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer};
+
+    fn analyzer() -> TextAnalyzer {
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .build()
+    }
+
+    #[test]
+    fn test_term_frequencies_skips_short_terms_and_numbers() {
+        let frequencies = term_frequencies(
+            &mut analyzer(),
+            "let Token = token_bucket(42); if ok { token }",
+            3,
+        );
+        assert_eq!(frequencies.get("token"), Some(&3));
+        assert_eq!(frequencies.get("bucket"), Some(&1));
+        assert_eq!(frequencies.get("let"), Some(&1));
+        assert!(!frequencies.contains_key("42"));
+        assert!(!frequencies.contains_key("if"));
+        assert!(!frequencies.contains_key("ok"));
+    }
+
+    #[test]
+    fn test_select_seed_terms_prefers_rare_shared_terms() {
+        let frequencies = HashMap::from([
+            ("common".to_string(), 3),
+            ("rare".to_string(), 1),
+            ("unique".to_string(), 5),
+            ("other".to_string(), 1),
+        ]);
+        let doc_freq = |term: &str| match term {
+            "common" => 90,
+            "rare" => 2,
+            "unique" => 1,
+            _ => 10,
+        };
+        let seeds = select_seed_terms(frequencies, doc_freq, 100, 2);
+
+        // "unique" occurs only in the source chunk, so it cannot match
+        assert_eq!(seeds, ["rare", "other"]);
+    }
+}
@@ -0,0 +1,621 @@
+//! End-to-end check of indexing, search and file access.
+//!
+//! [`Services::self_test`] writes a small corpus built into the binary
+//! to a scratch directory under the storage root, indexes it, runs
+//! searches with known answers, reads the files back as `read_file`
+//! and `preview_chunk` do, then deletes everything again. Each step is
+//! timed and reported as passed, failed or skipped, so a broken install
+//! shows which layer fails instead of a first error.
+//!
+//! The scratch directory holds its own storage root, so the run never
+//! lists, opens or changes the sessions already stored. Directories left
+//! by a run that was killed are removed by the next run after an hour.
+
+use crate::core::archive;
+use crate::core::error::{Result, ShebeError};
+use crate::core::paths;
+use crate::core::search::{locate_chunk, ChunkLocation};
+use crate::core::services::Services;
+use crate::core::storage::{SessionConfig, TantivyIndex};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tantivy::collector::TopDocs;
+use tantivy::schema::Value;
+use tantivy::TantivyDocument;
+
+/// Name prefix of scratch directories under the storage root
+pub const SELFTEST_DIR_PREFIX: &str = "selftest-";
+
+/// Session the corpus is indexed into, inside the scratch storage
+pub const SELFTEST_SESSION: &str = "selftest";
+
+/// Age after which a scratch directory left by a killed run is removed
+const STALE_SELFTEST_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A file of the built-in corpus
+#[derive(Debug, Clone, Copy)]
+pub struct CorpusFile {
+    /// Path relative to the corpus root, `/`-separated
+    pub path: &'static str,
+
+    /// File contents
+    pub contents: &'static str,
+}
+
+/// Files indexed by the self-test
+///
+/// Besides plain source and prose, they hold text that has broken
+/// tokenizers and byte offsets before: emoji, CJK without spaces
+/// inside words, right-to-left scripts and a CSV field with a line
+/// break (chunked by record).
+pub const CORPUS: &[CorpusFile] = &[
+    CorpusFile {
+        path: "src/auth.rs",
+        contents: "/// Check a user's credentials against the session store\n\
+                   pub fn authenticate_user(name: &str, token: &str) -> bool {\n    \
+                   !name.is_empty() && verify_token(token)\n}\n\n\
+                   fn verify_token(token: &str) -> bool {\n    \
+                   token.len() == 32\n}\n",
+    },
+    CorpusFile {
+        path: "docs/emoji.md",
+        contents: "# Launch checklist 🚀\n\n\
+                   - 🔥 warm the cache before the rocket leaves the pad\n\
+                   - ✅ confirm telemetry 👩‍🚀 with mission control\n\
+                   - 🧪 run the smoke tests one more time\n",
+    },
+    CorpusFile {
+        path: "docs/cjk.txt",
+        contents: "全文検索 エンジン は 索引 を 使います。\n\
+                   代码 搜索 工具 支持 中文。\n\
+                   한국어 검색 도 됩니다.\n",
+    },
+    CorpusFile {
+        path: "docs/rtl.txt",
+        contents: "مرحبا بالعالم، هذا نص عربي للاختبار.\n\
+                   שלום עולם, זה טקסט בעברית.\n\
+                   Mixed: العربية and עברית in one line.\n",
+    },
+    CorpusFile {
+        path: "data/users.csv",
+        contents: "id,name,note\n\
+                   1,Ada Lovelace,\"analytical engine\nfirst program\"\n\
+                   2,Grace Hopper,compiler pioneer\n\
+                   3,Zoë Ørsted,\"naïve café, déjà vu\"\n",
+    },
+];
+
+/// Searches run against the corpus and the file each must find
+pub const EXPECTED_HITS: &[(&str, &str)] = &[
+    ("authenticate_user", "src/auth.rs"),
+    ("rocket", "docs/emoji.md"),
+    ("全文検索", "docs/cjk.txt"),
+    ("검색", "docs/cjk.txt"),
+    ("مرحبا", "docs/rtl.txt"),
+    ("שלום", "docs/rtl.txt"),
+    ("hopper", "data/users.csv"),
+];
+
+/// Results fetched per search
+const SEARCH_K: usize = 10;
+
+/// Outcome of one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    /// Not run because a step it depends on failed
+    Skipped,
+}
+
+impl StepStatus {
+    /// Lowercase label for output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StepStatus::Passed => "passed",
+            StepStatus::Failed => "failed",
+            StepStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// One timed step of a self-test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestStep {
+    /// Step name (`index`, `search "rocket"`, ...)
+    pub name: String,
+
+    pub status: StepStatus,
+
+    /// Time the step took (0 when skipped)
+    pub duration_ms: u64,
+
+    /// What was checked, or why it failed or was skipped
+    pub detail: String,
+}
+
+/// Result of [`Services::self_test`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// Scratch directory the run used (removed by the `cleanup` step)
+    pub scratch_dir: PathBuf,
+
+    /// Steps in the order they ran
+    pub steps: Vec<SelfTestStep>,
+
+    /// Time the whole run took
+    pub duration_ms: u64,
+}
+
+impl SelfTestReport {
+    /// True when no step failed
+    pub fn passed(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    /// Steps that failed
+    pub fn failed(&self) -> impl Iterator<Item = &SelfTestStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Failed)
+    }
+
+    /// Number of steps with `status`
+    pub fn count(&self, status: StepStatus) -> usize {
+        self.steps.iter().filter(|s| s.status == status).count()
+    }
+}
+
+/// Steps recorded so far, and the first failed step later ones need
+struct Steps {
+    steps: Vec<SelfTestStep>,
+    blocked_by: Option<String>,
+}
+
+impl Steps {
+    /// Run `check` and record it; a failure skips every later step
+    fn required(
+        &mut self,
+        name: &str,
+        check: impl FnOnce() -> std::result::Result<String, String>,
+    ) -> bool {
+        let passed = self.run(name, check);
+        if !passed && self.blocked_by.is_none() {
+            self.blocked_by = Some(name.to_string());
+        }
+        passed
+    }
+
+    /// Run `check` and record it unless an earlier required step failed
+    fn run(
+        &mut self,
+        name: &str,
+        check: impl FnOnce() -> std::result::Result<String, String>,
+    ) -> bool {
+        if let Some(blocker) = &self.blocked_by {
+            self.steps.push(SelfTestStep {
+                name: name.to_string(),
+                status: StepStatus::Skipped,
+                duration_ms: 0,
+                detail: format!("needs step '{blocker}', which failed"),
+            });
+            return false;
+        }
+        self.always(name, check)
+    }
+
+    /// Run `check` and record it, even after failures
+    fn always(
+        &mut self,
+        name: &str,
+        check: impl FnOnce() -> std::result::Result<String, String>,
+    ) -> bool {
+        let start = Instant::now();
+        let outcome = check();
+        let (status, detail) = match outcome {
+            Ok(detail) => (StepStatus::Passed, detail),
+            Err(detail) => (StepStatus::Failed, detail),
+        };
+        self.steps.push(SelfTestStep {
+            name: name.to_string(),
+            status,
+            duration_ms: start.elapsed().as_millis() as u64,
+            detail,
+        });
+        status == StepStatus::Passed
+    }
+}
+
+/// Removes the scratch directory if a run ends without its cleanup step
+struct ScratchDir(PathBuf);
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.0) {
+                tracing::warn!(
+                    "Failed to remove self-test directory {}: {e}",
+                    self.0.display()
+                );
+            }
+        }
+    }
+}
+
+impl Services {
+    /// Index the built-in corpus in a scratch directory, check search
+    /// and file access against it, then remove it
+    ///
+    /// Step failures are reported in the [`SelfTestReport`], not as
+    /// errors; check [`SelfTestReport::passed`].
+    ///
+    /// # Errors
+    ///
+    /// `ReadOnly` in read-only mode, since the run writes under the
+    /// storage root.
+    pub fn self_test(&self) -> Result<SelfTestReport> {
+        if self.storage.is_read_only() {
+            return Err(ShebeError::ReadOnly(
+                "cannot run the self-test, which writes a scratch session".to_string(),
+            ));
+        }
+        let start = Instant::now();
+        let root = self.storage.storage_root();
+        remove_stale_scratch_dirs(root);
+
+        let run = uuid::Uuid::new_v4().simple().to_string();
+        let scratch = ScratchDir(root.join(format!("{SELFTEST_DIR_PREFIX}{}", &run[..8])));
+        let corpus_dir = scratch.0.join("corpus");
+        let mut config = (*self.config.current()).clone();
+        config.storage.index_dir = scratch.0.join("storage");
+        config.server.read_only = false;
+
+        let mut steps = Steps {
+            steps: Vec::new(),
+            blocked_by: None,
+        };
+        {
+            let services = Services::new(config);
+            steps.required("write corpus", || write_corpus(&corpus_dir));
+            steps.required("index", || index_corpus(&services, &corpus_dir));
+            for (query, expected) in EXPECTED_HITS {
+                steps.run(&format!("search \"{query}\""), || {
+                    check_search(&services, query, expected)
+                });
+            }
+            steps.run("read_file", || check_read_file(&services));
+            steps.run("preview_chunk", || check_preview_chunk(&services));
+            steps.run("delete session", || {
+                services
+                    .storage
+                    .delete_session(SELFTEST_SESSION)
+                    .map_err(|e| e.to_string())?;
+                if services.storage.session_exists(SELFTEST_SESSION) {
+                    return Err("session directory still exists after deletion".to_string());
+                }
+                Ok(format!("deleted session '{SELFTEST_SESSION}'"))
+            });
+        }
+        steps.always("cleanup", || {
+            if scratch.0.exists() {
+                fs::remove_dir_all(&scratch.0)
+                    .map_err(|e| format!("cannot remove {}: {e}", scratch.0.display()))?;
+            }
+            if scratch.0.exists() {
+                return Err(format!("{} still exists", scratch.0.display()));
+            }
+            Ok(format!("removed {}", scratch.0.display()))
+        });
+
+        Ok(SelfTestReport {
+            scratch_dir: scratch.0.clone(),
+            steps: steps.steps,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+/// Write [`CORPUS`] under `dir`
+fn write_corpus(dir: &Path) -> std::result::Result<String, String> {
+    let mut bytes = 0;
+    for file in CORPUS {
+        let path = dir.join(paths::to_native(file.path));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {e}", parent.display()))?;
+        }
+        fs::write(&path, file.contents)
+            .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+        bytes += file.contents.len();
+    }
+    Ok(format!(
+        "wrote {} files ({bytes} bytes) to {}",
+        CORPUS.len(),
+        dir.display()
+    ))
+}
+
+/// Index the corpus with default settings and check every file made it
+fn index_corpus(services: &Services, dir: &Path) -> std::result::Result<String, String> {
+    let outcome = services
+        .index(dir)
+        .session(SELFTEST_SESSION)
+        .defaults(SessionConfig::default())
+        .run()
+        .map_err(|e| e.to_string())?;
+    let stats = outcome.stats;
+    if stats.files_indexed != CORPUS.len() {
+        return Err(format!(
+            "indexed {} of {} files",
+            stats.files_indexed,
+            CORPUS.len()
+        ));
+    }
+    Ok(format!(
+        "{} files, {} chunks",
+        stats.files_indexed, stats.chunks_created
+    ))
+}
+
+/// Check that `query` finds `expected` among its results
+fn check_search(
+    services: &Services,
+    query: &str,
+    expected: &str,
+) -> std::result::Result<String, String> {
+    let response = services
+        .search
+        .search_session(SELFTEST_SESSION, query, Some(SEARCH_K))
+        .map_err(|e| e.to_string())?;
+    let rank = response
+        .results
+        .iter()
+        .position(|r| paths::to_native(&r.file_path).ends_with(paths::to_native(expected)));
+    match rank {
+        Some(rank) => Ok(format!(
+            "{expected} at rank {} of {}",
+            rank + 1,
+            response.results.len()
+        )),
+        None if response.results.is_empty() => Err(format!("no results; expected {expected}")),
+        None => Err(format!(
+            "{expected} not in {} results (top: {})",
+            response.results.len(),
+            response.results[0].file_path
+        )),
+    }
+}
+
+/// Absolute indexed path of a corpus file, resolved the way `read_file`
+/// resolves a relative path
+fn indexed_path(repository: &Path, file: &CorpusFile) -> std::result::Result<String, String> {
+    paths::resolve(file.path, Some(repository))
+        .map(|resolved| resolved.path)
+        .map_err(|e| e.to_string())
+}
+
+/// Check every corpus file is in the manifest and reads back unchanged
+fn check_read_file(services: &Services) -> std::result::Result<String, String> {
+    let metadata = services
+        .storage
+        .get_session_metadata(SELFTEST_SESSION)
+        .map_err(|e| e.to_string())?;
+    let manifest = services
+        .storage
+        .session_files(SELFTEST_SESSION)
+        .map_err(|e| e.to_string())?;
+    for file in CORPUS {
+        let path = indexed_path(&metadata.repository_path, file)?;
+        if !manifest.files.iter().any(|f| f.path == path) {
+            return Err(format!("{} is not in the session's file list", file.path));
+        }
+        let contents = archive::read_indexed_file(&path)
+            .map_err(|e| format!("cannot read {}: {e}", file.path))?;
+        if contents != file.contents {
+            return Err(format!("{} reads back different content", file.path));
+        }
+    }
+    Ok(format!("{} files read back unchanged", CORPUS.len()))
+}
+
+/// Check the first chunk of every corpus file is at its indexed offsets
+fn check_preview_chunk(services: &Services) -> std::result::Result<String, String> {
+    let metadata = services
+        .storage
+        .get_session_metadata(SELFTEST_SESSION)
+        .map_err(|e| e.to_string())?;
+    let index = services
+        .storage
+        .open_session(SELFTEST_SESSION)
+        .map_err(|e| e.to_string())?;
+    for file in CORPUS {
+        let path = indexed_path(&metadata.repository_path, file)?;
+        let (range, text) = first_chunk(&index, &path).map_err(|e| e.to_string())?;
+        let Some(text) = text else {
+            return Err(format!("chunk 0 of {} has no stored text", file.path));
+        };
+        match locate_chunk(file.contents, &text, range.clone()) {
+            ChunkLocation::Current(_) => {}
+            _ => {
+                return Err(format!(
+                    "chunk 0 of {} does not match bytes {}..{} of the file",
+                    file.path, range.start, range.end
+                ))
+            }
+        }
+    }
+    Ok(format!(
+        "chunk 0 of {} files found at its indexed offsets",
+        CORPUS.len()
+    ))
+}
+
+/// Offsets and stored text of chunk 0 of `file_path`
+fn first_chunk(index: &TantivyIndex, file_path: &str) -> Result<(Range<usize>, Option<String>)> {
+    let searcher = index
+        .index()
+        .reader()
+        .map_err(|e| ShebeError::StorageError(format!("Failed to open reader: {e}")))?
+        .searcher();
+    let top_docs = searcher
+        .search(&index.chunk_query(file_path, 0)?, &TopDocs::with_limit(1))
+        .map_err(|e| ShebeError::SearchFailed(e.to_string()))?;
+    let Some((_, address)) = top_docs.first() else {
+        return Err(ShebeError::SearchFailed(format!(
+            "chunk 0 of {file_path} not found"
+        )));
+    };
+    let doc: TantivyDocument = searcher
+        .doc(*address)
+        .map_err(|e| ShebeError::SearchFailed(e.to_string()))?;
+    let schema = index.schema();
+    let offset = |name: &str| {
+        schema
+            .get_field(name)
+            .ok()
+            .and_then(|field| doc.get_first(field)?.as_i64())
+            .map(|n| n as usize)
+            .ok_or_else(|| ShebeError::StorageError(format!("chunk has no {name}")))
+    };
+    let range = offset("offset_start")?..offset("offset_end")?;
+    let text = schema
+        .get_field("text")
+        .ok()
+        .and_then(|field| doc.get_first(field)?.as_str().map(str::to_string));
+    Ok((range, text))
+}
+
+/// Remove scratch directories left by runs that were killed
+fn remove_stale_scratch_dirs(root: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().starts_with(SELFTEST_DIR_PREFIX) {
+            continue;
+        }
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+        if age.is_some_and(|age| age > STALE_SELFTEST_AGE) {
+            if let Err(e) = fs::remove_dir_all(entry.path()) {
+                tracing::warn!(
+                    "Failed to remove stale self-test directory {}: {e}",
+                    entry.path().display()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use tempfile::TempDir;
+
+    fn services(read_only: bool) -> (Services, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp.path().to_path_buf();
+        config.server.read_only = read_only;
+        (Services::new(config), temp)
+    }
+
+    #[test]
+    fn test_self_test_passes_and_cleans_up() {
+        let (services, temp) = services(false);
+        let report = services.self_test().unwrap();
+
+        let failures: Vec<_> = report.failed().collect();
+        assert!(report.passed(), "failed steps: {failures:#?}");
+        let names: Vec<&str> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names[..2], ["write corpus", "index"]);
+        assert_eq!(
+            names.iter().filter(|n| n.starts_with("search ")).count(),
+            EXPECTED_HITS.len()
+        );
+        assert_eq!(
+            names[names.len() - 4..],
+            ["read_file", "preview_chunk", "delete session", "cleanup"]
+        );
+        assert_eq!(report.count(StepStatus::Passed), report.steps.len());
+
+        assert!(report.scratch_dir.starts_with(temp.path()));
+        assert!(!report.scratch_dir.exists());
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_self_test_leaves_existing_sessions_alone() {
+        let (services, temp) = services(false);
+        let repo = TempDir::new().unwrap();
+        fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+        services.index(repo.path()).session("mine").run().unwrap();
+        let before = services.storage.get_session_metadata("mine").unwrap();
+
+        assert!(services.self_test().unwrap().passed());
+
+        assert_eq!(services.storage.list_session_ids().unwrap(), ["mine"]);
+        let after = services.storage.get_session_metadata("mine").unwrap();
+        assert_eq!(after.last_indexed_at, before.last_indexed_at);
+        assert!(!fs::read_dir(temp.path()).unwrap().flatten().any(|e| e
+            .file_name()
+            .to_string_lossy()
+            .starts_with(SELFTEST_DIR_PREFIX)));
+    }
+
+    #[test]
+    fn test_failed_step_skips_dependent_steps() {
+        // A file where the storage root directory should be
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("root");
+        fs::write(&root, "not a directory").unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = root;
+        let services = Services::new(config);
+
+        let report = services.self_test().unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.steps[0].name, "write corpus");
+        assert_eq!(report.steps[0].status, StepStatus::Failed);
+        let last = report.steps.last().unwrap();
+        assert_eq!(last.name, "cleanup");
+        assert_eq!(last.status, StepStatus::Passed);
+        assert_eq!(
+            report.count(StepStatus::Skipped),
+            report.steps.len() - 2,
+            "{:#?}",
+            report.steps
+        );
+        assert!(report.steps[1].detail.contains("write corpus"));
+    }
+
+    #[test]
+    fn test_self_test_refused_in_read_only_mode() {
+        let (services, _temp) = services(true);
+        assert!(matches!(services.self_test(), Err(ShebeError::ReadOnly(_))));
+    }
+
+    #[test]
+    fn test_stale_scratch_dirs_are_removed() {
+        let temp = TempDir::new().unwrap();
+        let stale = temp.path().join(format!("{SELFTEST_DIR_PREFIX}old"));
+        let fresh = temp.path().join(format!("{SELFTEST_DIR_PREFIX}new"));
+        fs::create_dir_all(&stale).unwrap();
+        fs::create_dir_all(&fresh).unwrap();
+        let old = SystemTime::now() - STALE_SELFTEST_AGE * 2;
+        fs::File::open(&stale).unwrap().set_modified(old).unwrap();
+
+        remove_stale_scratch_dirs(temp.path());
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+    }
+}
@@ -2,12 +2,30 @@
 //!
 //! Provides shared access to all core services.
 
+use crate::core::access::AllowedRoots;
+use crate::core::api::IndexBuilder;
+use crate::core::archive::{self, Archive};
 use crate::core::config::Config;
-use crate::core::error::Result;
-use crate::core::indexer::IndexingPipeline;
+use crate::core::error::{Result, ShebeError};
+use crate::core::health::{check_readiness, ReadinessReport};
+use crate::core::indexer::{
+    Chunker, DryRunReport, FileExplanation, FileListDelta, IndexLimits, IndexProgress,
+    IndexingPipeline, ReindexDryRun, WalkReport,
+};
+use crate::core::jobs::{IndexJobRequest, IndexSource, JobId, JobManager, JobOutput, JobStatus};
+use crate::core::maintenance::Activity;
+use crate::core::paths;
+use crate::core::reload::LiveConfig;
+use crate::core::remote::CloneCache;
+use crate::core::results::{ResultCache, ResultTarget};
 use crate::core::search::SearchService;
-use crate::core::storage::StorageManager;
-use std::sync::Arc;
+use crate::core::storage::{BreakerSettings, SessionConfig, StorageManager};
+use crate::core::types::{SearchRequest, SearchResponse, SearchResult};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 
 /// Unified services container
 ///
@@ -20,25 +38,93 @@ pub struct Services {
     /// Search service for BM25 queries
     pub search: Arc<SearchService>,
 
-    /// Application configuration
-    pub config: Arc<Config>,
+    /// Shallow clones of remote repositories
+    pub clones: Arc<CloneCache>,
+
+    /// Background indexing jobs
+    pub jobs: Arc<JobManager>,
+
+    /// Handles for recent search results
+    pub results: Arc<ResultCache>,
+
+    /// Application configuration, replaced on reload
+    pub config: Arc<LiveConfig>,
+
+    /// Tool calls in progress and the last one, for idle maintenance
+    pub activity: Arc<Activity>,
+
+    /// `last_indexed_at` of each session when it was last re-indexed
+    /// because of a corrupted index
+    recoveries: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
 }
 
 impl Services {
     /// Create services from configuration
     pub fn new(config: Config) -> Self {
-        let storage = Arc::new(StorageManager::new(config.storage.index_dir.clone()));
+        let storage = Arc::new(
+            StorageManager::new(config.storage.index_dir.clone())
+                .with_namespaces(config.namespace_roots(), &config.storage.default_namespace)
+                .with_index_limits(IndexLimits::from_config(&config.limits))
+                .with_open_breaker(BreakerSettings::from_config(&config.limits))
+                .with_read_only(config.server.read_only),
+        );
+
+        let search = Arc::new(
+            SearchService::new(
+                Arc::clone(&storage),
+                config.search.default_k,
+                config.search.max_k,
+            )
+            .with_path_boost(config.search.path_boost)
+            .with_short_chunk_penalty(
+                config
+                    .search
+                    .penalize_short_chunks
+                    .then(|| config.search.short_chunk_penalty()),
+            ),
+        );
+
+        let clones = Arc::new(CloneCache::new(
+            config.storage.clone_dir.clone(),
+            Duration::from_secs(config.limits.git_timeout_sec),
+        ));
 
-        let search = Arc::new(SearchService::new(
-            Arc::clone(&storage),
-            config.search.default_k,
-            config.search.max_k,
+        let jobs = Arc::new(JobManager::new(
+            config.limits.max_concurrent_indexes,
+            Duration::from_secs(config.limits.job_retention_sec),
         ));
 
+        let config = Arc::new(LiveConfig::new(config));
+        let (weak_storage, weak_search) = (Arc::downgrade(&storage), Arc::downgrade(&search));
+        config.on_reload(move |config| {
+            if let Some(storage) = Weak::upgrade(&weak_storage) {
+                storage.set_index_limits(IndexLimits::from_config(&config.limits));
+                storage
+                    .open_breaker()
+                    .set_settings(BreakerSettings::from_config(&config.limits));
+            }
+            if let Some(search) = Weak::upgrade(&weak_search) {
+                search.set_defaults(
+                    config.search.default_k,
+                    config.search.max_k,
+                    config.search.path_boost,
+                    config
+                        .search
+                        .penalize_short_chunks
+                        .then(|| config.search.short_chunk_penalty()),
+                );
+            }
+        });
+
         Self {
             storage,
             search,
-            config: Arc::new(config),
+            clones,
+            jobs,
+            results: Arc::new(ResultCache::default()),
+            config,
+            activity: Arc::new(Activity::default()),
+            recoveries: Arc::default(),
         }
     }
 
@@ -50,13 +136,369 @@ impl Services {
         include_patterns: Vec<String>,
         exclude_patterns: Vec<String>,
     ) -> Result<IndexingPipeline> {
-        IndexingPipeline::new(
-            self.config.indexing.chunk_size,
-            self.config.indexing.overlap,
-            include_patterns,
-            exclude_patterns,
-            self.config.indexing.max_file_size_mb,
-        )
+        let config = self.config.current();
+        IndexingPipeline::builder()
+            .chunk_size(config.indexing.chunk_size)
+            .overlap(config.indexing.overlap)
+            .include_patterns(include_patterns)
+            .exclude_patterns(exclude_patterns)
+            .max_file_size_mb(config.indexing.max_file_size_mb)
+            .build()
+    }
+
+    /// Start indexing the repository at `path` (see [`IndexBuilder`])
+    pub fn index(&self, path: impl AsRef<Path>) -> IndexBuilder<'_> {
+        IndexBuilder::new(self, path.as_ref().to_path_buf())
+    }
+
+    /// Session settings from the `indexing` configuration
+    pub fn default_session_config(&self) -> SessionConfig {
+        let config = self.config.current();
+        SessionConfig {
+            chunk_size: config.indexing.chunk_size,
+            overlap: config.indexing.overlap,
+            include_patterns: config.indexing.include_patterns.clone(),
+            exclude_patterns: config.indexing.exclude_patterns.clone(),
+            git_metadata: config.indexing.git_metadata,
+            ..SessionConfig::default()
+        }
+    }
+
+    /// Summarize what indexing `path` with `session_config` would do
+    ///
+    /// Walks the directory with the session's patterns and the
+    /// configured file size limit. No session is created and storage
+    /// is not touched, so the session ID does not matter.
+    pub fn dry_run_index(
+        &self,
+        path: &Path,
+        session_config: &SessionConfig,
+    ) -> Result<DryRunReport> {
+        let pipeline = self.session_pipeline(session_config)?;
+        match Archive::detect(path) {
+            Some(archive) => pipeline.dry_run_archive(&archive, &archive.entries()?),
+            None => pipeline.dry_run(path),
+        }
+    }
+
+    /// Preview re-indexing `session_id` from `path` with `session_config`
+    ///
+    /// Walks `path` as [`Services::dry_run_index`] does and compares the
+    /// matched files with the ones the session holds (from its file
+    /// manifest, or its index). Only reads: the session is neither
+    /// locked nor replaced, so a preview can be interrupted at any time.
+    /// A `path` that no longer exists matches no files.
+    ///
+    /// # Errors
+    ///
+    /// `SessionNotFound` if the session does not exist.
+    pub fn dry_run_reindex(
+        &self,
+        session_id: &str,
+        path: &Path,
+        session_config: &SessionConfig,
+    ) -> Result<ReindexDryRun> {
+        let indexed = self.storage.session_files(session_id)?;
+        let indexed_paths: Vec<String> = indexed.files.iter().map(|f| f.path.clone()).collect();
+        let resolved_path = path.canonicalize().ok();
+        let repository_moved = resolved_path.as_deref() != Some(path);
+
+        let pipeline = self.session_pipeline(session_config)?;
+        let (report, matched, prefix) = match Archive::detect(path) {
+            Some(archive) => {
+                let (report, matched) =
+                    pipeline.dry_run_archive_listing(&archive, &archive.entries()?)?;
+                (
+                    report,
+                    matched,
+                    paths::normalize_path(&archive.entry_path("")),
+                )
+            }
+            None if path.is_dir() => {
+                let (report, matched) = pipeline.dry_run_listing(path)?;
+                (report, matched, format!("{}/", paths::normalize_path(path)))
+            }
+            None => (
+                DryRunReport::from_walk(
+                    WalkReport::default(),
+                    path,
+                    Vec::new(),
+                    &Chunker::new(session_config.chunk_size, session_config.overlap),
+                ),
+                Vec::new(),
+                String::new(),
+            ),
+        };
+
+        Ok(ReindexDryRun {
+            files: FileListDelta::compare(&indexed_paths, &matched, &prefix),
+            report,
+            indexed_chunks: indexed.total_chunks,
+            repository_moved,
+            resolved_path,
+        })
+    }
+
+    /// Pipeline with a session's settings and the configured size limit
+    fn session_pipeline(&self, session_config: &SessionConfig) -> Result<IndexingPipeline> {
+        IndexingPipeline::builder()
+            .session_config(session_config)
+            .max_file_size_mb(self.config.current().indexing.max_file_size_mb)
+            .build()
+    }
+
+    /// Explain why a file is or is not part of a session's index
+    ///
+    /// Re-applies the session's patterns, its repository's
+    /// `.shebeignore` and the configured file size limit to `path`
+    /// (absolute, or relative to the repository), then checks the
+    /// file's content. Reflects the file as it is now, which may
+    /// differ from when the session was indexed.
+    pub fn why_not_indexed(&self, session: &str, path: &Path) -> Result<FileExplanation> {
+        let metadata = self.storage.get_session_metadata(session)?;
+        self.session_pipeline(&metadata.config)?
+            .explain(&metadata.repository_path, path)
+    }
+
+    /// Index a repository in the background
+    ///
+    /// Returns as soon as the job is queued; poll it with
+    /// [`Services::get_job_status`]. Remote sources are cloned by the
+    /// job itself.
+    ///
+    /// # Errors
+    ///
+    /// `IndexLocked` if a job for the same session is queued or running,
+    /// and `ReadOnly` in read-only mode.
+    pub fn start_index_job(&self, request: IndexJobRequest) -> Result<JobId> {
+        if self.storage.is_read_only() {
+            return Err(ShebeError::ReadOnly(format!(
+                "cannot index session '{}'",
+                request.session
+            )));
+        }
+        let services = self.clone();
+        let session = request.session.clone();
+        let source = request.source.label();
+        self.jobs.start(&session, &source, move |progress| {
+            services.run_index_job(request, progress)
+        })
+    }
+
+    /// Re-index sessions found with a corrupted index
+    ///
+    /// Does nothing unless `storage.reindex_on_corruption` is set.
+    /// Sessions reported by [`StorageManager::take_corrupted`] are
+    /// re-indexed in background jobs from their stored repository and
+    /// settings. A session is tried once per indexed state, so a failed
+    /// re-index is not repeated on every access. Returns the sessions
+    /// and the jobs started for them.
+    pub fn recover_corrupted_sessions(&self) -> Vec<(String, JobId)> {
+        let corrupted = self.storage.take_corrupted();
+        if corrupted.is_empty()
+            || !self.config.current().storage.reindex_on_corruption
+            || self.storage.is_read_only()
+        {
+            return Vec::new();
+        }
+
+        let mut recoveries = self.recoveries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut started = Vec::new();
+        for session in corrupted {
+            let Ok(metadata) = self.storage.get_session_metadata(&session) else {
+                continue;
+            };
+            if recoveries.get(&session) == Some(&metadata.last_indexed_at) {
+                tracing::error!(
+                    "Session '{}' is still corrupted after an automatic re-index; \
+                     re-index or delete it",
+                    session
+                );
+                continue;
+            }
+            if self.jobs.active_job(&session).is_some() {
+                continue;
+            }
+            match IndexJobRequest::reindex(&metadata).and_then(|r| self.start_index_job(r)) {
+                Ok(job_id) => {
+                    tracing::error!(
+                        "Session '{}' has a corrupted index; re-indexing it from {} as {} \
+                         (storage.reindex_on_corruption)",
+                        session,
+                        metadata.repository_path.display(),
+                        job_id
+                    );
+                    recoveries.insert(session.clone(), metadata.last_indexed_at);
+                    started.push((session, job_id));
+                }
+                Err(e) => tracing::error!(
+                    "Session '{}' has a corrupted index and cannot be re-indexed: {}",
+                    session,
+                    e
+                ),
+            }
+        }
+        started
+    }
+
+    /// Status of a background job
+    pub fn get_job_status(&self, id: &JobId) -> Result<JobStatus> {
+        self.jobs.status(id)
+    }
+
+    /// Active and recently finished background jobs, oldest first
+    pub fn list_jobs(&self) -> Vec<JobStatus> {
+        self.jobs.list()
+    }
+
+    /// Cancel a background job (see [`JobManager::cancel`])
+    pub fn cancel_job(&self, id: &JobId) -> Result<JobStatus> {
+        self.jobs.cancel(id)
+    }
+
+    /// Body of an indexing job: clone if remote, resolve settings, index
+    fn run_index_job(
+        &self,
+        request: IndexJobRequest,
+        progress: &IndexProgress,
+    ) -> Result<JobOutput> {
+        let session = request.session.as_str();
+        let (path, remote) = match &request.source {
+            IndexSource::Local(path) => (path.clone(), None),
+            IndexSource::Remote { url, git_ref } => {
+                let source = self.clones.clone_repo(session, url, git_ref.as_deref())?;
+                progress.check_cancelled()?;
+                (
+                    self.clones.clone_path(session).canonicalize()?,
+                    Some(source),
+                )
+            }
+        };
+
+        let stats = self
+            .index(&path)
+            .session(session)
+            .overrides(request.overrides)
+            .defaults(request.defaults)
+            .force(request.force)
+            .allow_dangerous_path(request.allow_dangerous_path)
+            .progress(progress)
+            .run()?
+            .stats;
+        if let Some(source) = &remote {
+            self.storage
+                .set_remote_source(session, Some(source.clone()))?;
+        }
+
+        Ok(JobOutput { stats, remote })
+    }
+
+    /// Record search hits, returning the first hit's handle number
+    ///
+    /// Hit `i` of `results` gets handle `r{first + i}` (see
+    /// [`crate::core::results`]).
+    pub fn remember_results(&self, session: &str, query: &str, results: &[SearchResult]) -> usize {
+        let hits = results
+            .iter()
+            .map(|result| ResultTarget {
+                file_path: result.file_path.clone(),
+                chunk_index: result.chunk_index,
+            })
+            .collect();
+        self.results
+            .store(session, query, self.last_indexed_at(session), hits)
+    }
+
+    /// Record the hits of a collection search, returning the first
+    /// hit's handle number
+    ///
+    /// Each hit's handle resolves for the session it came from.
+    pub fn remember_merged_results(&self, query: &str, results: &[SearchResult]) -> usize {
+        let hits = results
+            .iter()
+            .map(|result| {
+                (
+                    result.session.clone().unwrap_or_default(),
+                    ResultTarget {
+                        file_path: result.file_path.clone(),
+                        chunk_index: result.chunk_index,
+                    },
+                )
+            })
+            .collect();
+        self.results
+            .store_merged(query, |session| self.last_indexed_at(session), hits)
+    }
+
+    /// Execute `request` against every session of collection `name`
+    ///
+    /// Results are merged across the sessions and labelled with the one
+    /// they came from (see [`crate::core::search::collection`]).
+    ///
+    /// # Errors
+    ///
+    /// `CollectionNotFound` if there is no such collection,
+    /// `InvalidSession` if it has no sessions left, and errors of the
+    /// member searches.
+    pub fn search_collection(&self, name: &str, request: SearchRequest) -> Result<SearchResponse> {
+        let collection = self.storage.get_collection(name)?;
+        if collection.sessions.is_empty() {
+            return Err(ShebeError::InvalidSession(format!(
+                "Collection '{name}' has no sessions left; add some with update_collection"
+            )));
+        }
+        self.search.search_sessions(&collection.sessions, request)
+    }
+
+    /// Resolve a result handle from a recent search of `session`
+    ///
+    /// # Errors
+    ///
+    /// `SessionNotFound` if the session does not exist;
+    /// `InvalidResultHandle` if the handle is malformed, expired, from
+    /// another session or from before the session was re-indexed.
+    pub fn resolve_result(&self, session: &str, handle: &str) -> Result<ResultTarget> {
+        let indexed_at = self.storage.get_session_metadata(session)?.last_indexed_at;
+        self.results.resolve(session, handle, Some(indexed_at))
+    }
+
+    /// Roots files may be read under (see [`crate::core::access`])
+    ///
+    /// # Errors
+    ///
+    /// Storage errors from listing the sessions.
+    pub fn allowed_roots(&self) -> Result<AllowedRoots> {
+        AllowedRoots::resolve(&self.config.current().security, &self.storage)
+    }
+
+    /// Check that an indexed file (or the archive holding it) may be
+    /// read from disk, returning its canonical path
+    ///
+    /// # Errors
+    ///
+    /// `AccessDenied` if the file is outside the allowed roots.
+    pub fn authorize_read(&self, stored: &str) -> Result<PathBuf> {
+        let path = match archive::split_entry_path(stored) {
+            Some((archive_path, _)) => archive_path,
+            None => paths::to_native(stored),
+        };
+        self.allowed_roots()?.authorize(&path)
+    }
+
+    fn last_indexed_at(&self, session: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.storage
+            .get_session_metadata(session)
+            .ok()
+            .map(|metadata| metadata.last_indexed_at)
+    }
+
+    /// Run the storage readiness checks
+    ///
+    /// Includes the canary session check when `health.canary_session`
+    /// is configured.
+    pub fn readiness(&self) -> ReadinessReport {
+        let config = self.config.current();
+        check_readiness(&self.storage, config.health.canary_session.as_deref())
     }
 }
 
@@ -73,8 +515,8 @@ mod tests {
 
         let services = Services::new(config);
 
-        assert_eq!(services.config.search.default_k, 10);
-        assert_eq!(services.config.search.max_k, 100);
+        assert_eq!(services.config.current().search.default_k, 10);
+        assert_eq!(services.config.current().search.max_k, 100);
     }
 
     #[test]
@@ -89,6 +531,9 @@ mod tests {
         // Both should point to same Arc instances
         assert!(Arc::ptr_eq(&services.storage, &cloned.storage));
         assert!(Arc::ptr_eq(&services.search, &cloned.search));
+        assert!(Arc::ptr_eq(&services.clones, &cloned.clones));
+        assert!(Arc::ptr_eq(&services.jobs, &cloned.jobs));
+        assert!(Arc::ptr_eq(&services.results, &cloned.results));
         assert!(Arc::ptr_eq(&services.config, &cloned.config));
     }
 
@@ -108,4 +553,48 @@ mod tests {
             )
             .expect("Pipeline creation should succeed");
     }
+
+    #[test]
+    fn test_index_job_runs_in_background() {
+        use crate::core::indexer::IndexOverrides;
+        use crate::core::jobs::JobState;
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.join("lib.rs"), "pub fn lib() {}").unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("sessions");
+        let services = Services::new(config);
+
+        let request = IndexJobRequest {
+            session: "bg".to_string(),
+            source: IndexSource::Local(repo.clone()),
+            overrides: IndexOverrides::default(),
+            defaults: SessionConfig {
+                include_patterns: vec!["*.rs".to_string()],
+                ..SessionConfig::default()
+            },
+            force: false,
+            allow_dangerous_path: false,
+        };
+        let id = services.start_index_job(request).unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        let status = loop {
+            let status = services.get_job_status(&id).unwrap();
+            if status.state.is_finished() {
+                break status;
+            }
+            assert!(std::time::Instant::now() < deadline, "job did not finish");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        assert_eq!(status.state, JobState::Completed, "{:?}", status.error);
+        assert_eq!(status.files_total, 2);
+        assert_eq!(status.stats.unwrap().files_indexed, 2);
+        assert!(services.storage.session_exists("bg"));
+        assert_eq!(services.list_jobs().len(), 1);
+    }
 }
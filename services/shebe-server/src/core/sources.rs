@@ -0,0 +1,165 @@
+//! Source files read back while turning search hits into references.
+//!
+//! `find_references` (MCP tool and CLI command) reads the file of each
+//! hit for exact line numbers and context. [`SourceFiles`] caches each
+//! file with its comment/string regions and remembers the files that
+//! could not be read, so a reference in such a file is still reported
+//! from the indexed chunk text (see [`locate_in_chunk`]) instead of
+//! silently dropped, and the file is listed in a warning.
+
+use crate::core::archive;
+use crate::core::lexical::{self, RegionKind, Regions};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+
+/// Confidence taken off a reference whose file could not be read
+pub const DEGRADED_CONTEXT_PENALTY: f32 = 0.10;
+
+/// A readable source file
+#[derive(Debug)]
+pub struct SourceFile {
+    pub content: String,
+    /// Comment and string regions of `content`
+    pub regions: Regions,
+}
+
+/// A file whose references fell back to the indexed chunk text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnreadableFile {
+    pub file_path: String,
+    /// IO error kind ("permission denied", "entity not found", ...)
+    pub error: String,
+}
+
+/// Files read so far, by indexed path
+#[derive(Debug, Default)]
+pub struct SourceFiles {
+    files: HashMap<String, Result<SourceFile, io::ErrorKind>>,
+}
+
+impl SourceFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read `file_path` (once) and scan it as `language`
+    ///
+    /// Returns the IO error kind if the file cannot be read; the failure
+    /// is cached and listed by [`SourceFiles::unreadable`].
+    pub fn get(&mut self, file_path: &str, language: &str) -> Result<&SourceFile, io::ErrorKind> {
+        self.files
+            .entry(file_path.to_string())
+            .or_insert_with(|| {
+                archive::read_indexed_file(file_path)
+                    .map(|content| SourceFile {
+                        regions: lexical::scan(&content, language),
+                        content,
+                    })
+                    .map_err(|e| e.kind())
+            })
+            .as_ref()
+            .map_err(|kind| *kind)
+    }
+
+    /// Files that could not be read, sorted by path
+    pub fn unreadable(&self) -> Vec<UnreadableFile> {
+        let mut unreadable: Vec<UnreadableFile> = self
+            .files
+            .iter()
+            .filter_map(|(path, file)| {
+                file.as_ref().err().map(|kind| UnreadableFile {
+                    file_path: path.clone(),
+                    error: kind.to_string(),
+                })
+            })
+            .collect();
+        unreadable.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        unreadable
+    }
+}
+
+/// A symbol occurrence found in a chunk's indexed text
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkMatch {
+    /// Byte offset in the file when it was indexed
+    pub offset: usize,
+    /// Lines of the chunk around the occurrence
+    pub context: String,
+    /// The chunk line containing the occurrence (may start mid-line)
+    pub line: String,
+    /// Comment or string region as far as the chunk shows it
+    pub region: Option<RegionKind>,
+}
+
+/// Find `symbol` in the text of a chunk indexed at `start_offset`
+///
+/// For files that can no longer be read. Line numbers are unknown, so
+/// the occurrence is identified by its byte offset at index time.
+pub fn locate_in_chunk(
+    text: &str,
+    start_offset: usize,
+    symbol: &str,
+    context_lines: usize,
+    language: &str,
+) -> Option<ChunkMatch> {
+    let position = text.find(symbol)?;
+    let line_index = text[..position].matches('\n').count();
+    let lines: Vec<&str> = text.lines().collect();
+    let start = line_index.saturating_sub(context_lines);
+    let end = (line_index + context_lines + 1).min(lines.len());
+
+    Some(ChunkMatch {
+        offset: start_offset + position,
+        context: lines[start..end].join("\n"),
+        line: lines
+            .get(line_index)
+            .copied()
+            .unwrap_or_default()
+            .to_string(),
+        region: lexical::scan(text, language).kind_at(position),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_files_records_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().join("present.rs");
+        std::fs::write(&present, "fn present() {}\n").unwrap();
+        let missing = dir.path().join("missing.rs");
+
+        let mut files = SourceFiles::new();
+        let source = files.get(&present.to_string_lossy(), "rust").unwrap();
+        assert_eq!(source.content, "fn present() {}\n");
+        assert_eq!(
+            files.get(&missing.to_string_lossy(), "rust").unwrap_err(),
+            io::ErrorKind::NotFound
+        );
+
+        assert_eq!(
+            files.unreadable(),
+            [UnreadableFile {
+                file_path: missing.to_string_lossy().into_owned(),
+                error: "entity not found".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_locate_in_chunk() {
+        let text = "fn a() {}\n// call b\nfn c() { target(); }\nfn d() {}\n";
+        let found = locate_in_chunk(text, 100, "target", 1, "rust").unwrap();
+        assert_eq!(found.offset, 100 + text.find("target").unwrap());
+        assert_eq!(found.context, "// call b\nfn c() { target(); }\nfn d() {}");
+        assert_eq!(found.line, "fn c() { target(); }");
+        assert_eq!(found.region, None);
+
+        let comment = locate_in_chunk(text, 0, "call", 0, "rust").unwrap();
+        assert_eq!(comment.region, Some(RegionKind::Comment));
+        assert_eq!(locate_in_chunk(text, 0, "absent", 2, "rust"), None);
+    }
+}
@@ -0,0 +1,348 @@
+//! Fast failure for sessions whose index keeps failing to open.
+//!
+//! An index on a flaky network filesystem can take seconds to fail to
+//! open, and every tool call on the session would wait for that again.
+//! [`OpenBreaker`] counts failed opens per session. After
+//! `failure_threshold` failures within `window` the session is
+//! *tripped*: opens fail at once with [`ShebeError::SessionUnavailable`]
+//! until `cooldown` has passed. The next open is then let through as a
+//! probe; if it succeeds the session is closed again, if it fails the
+//! cooldown starts over.
+//!
+//! Only I/O and storage errors count. Any other answer (the session is
+//! missing, its schema is outdated, its files are corrupted) shows the
+//! storage itself responded and clears the failures. State is kept in
+//! memory only and can be cleared with [`OpenBreaker::reset`].
+
+use crate::core::config::LimitsConfig;
+use crate::core::error::{Result, ShebeError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// When a session trips and for how long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerSettings {
+    /// Failed opens within `window` that trip a session (0 disables)
+    pub failure_threshold: u32,
+
+    /// How far back failures are counted
+    pub window: Duration,
+
+    /// How long a tripped session fails fast before the next probe
+    pub cooldown: Duration,
+}
+
+impl BreakerSettings {
+    /// Settings from the `[limits]` section
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        Self {
+            failure_threshold: limits.open_failure_threshold,
+            window: Duration::from_secs(limits.open_failure_window_sec),
+            cooldown: Duration::from_secs(limits.open_failure_cooldown_sec),
+        }
+    }
+}
+
+impl Default for BreakerSettings {
+    fn default() -> Self {
+        Self::from_config(&LimitsConfig::default())
+    }
+}
+
+/// Breaker state of one session, for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionBreaker {
+    pub session: String,
+
+    /// Failed opens within the window
+    pub failures: u32,
+
+    /// Message of the last failed open
+    pub last_error: String,
+
+    /// Whether opens currently fail fast
+    pub tripped: bool,
+
+    /// Seconds until the next probe (0 when not tripped or due now)
+    pub retry_after_secs: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    /// Times of failed opens within the window, oldest first
+    failures: Vec<Instant>,
+    last_error: String,
+    /// Opens fail fast until then
+    open_until: Option<Instant>,
+    /// A probe is running; its result decides the state
+    probing: bool,
+}
+
+/// Failed opens per session (see the [module docs](self))
+#[derive(Debug)]
+pub struct OpenBreaker {
+    settings: RwLock<BreakerSettings>,
+    sessions: Mutex<HashMap<String, State>>,
+}
+
+impl Default for OpenBreaker {
+    fn default() -> Self {
+        Self::new(BreakerSettings::default())
+    }
+}
+
+impl OpenBreaker {
+    pub fn new(settings: BreakerSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            sessions: Mutex::default(),
+        }
+    }
+
+    /// Replace the settings (on configuration reload)
+    ///
+    /// Sessions already tripped keep their current cooldown.
+    pub fn set_settings(&self, settings: BreakerSettings) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    fn settings(&self) -> BreakerSettings {
+        *self.settings.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `open` for `session_id` unless the session is tripped
+    ///
+    /// # Errors
+    ///
+    /// `SessionUnavailable` while tripped, otherwise whatever `open`
+    /// returns.
+    pub fn call<T>(&self, session_id: &str, open: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.check(session_id, Instant::now())?;
+        let result = open();
+        match &result {
+            Err(e) if counts_as_failure(e) => self.record_failure(session_id, e, Instant::now()),
+            _ => self.record_answer(session_id),
+        }
+        result
+    }
+
+    /// Fail if `session_id` is tripped; after the cooldown, let one
+    /// caller through as the probe
+    fn check(&self, session_id: &str, now: Instant) -> Result<()> {
+        let cooldown = self.settings().cooldown;
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = sessions.get_mut(session_id) else {
+            return Ok(());
+        };
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        if now < open_until {
+            return Err(ShebeError::SessionUnavailable {
+                session: session_id.to_string(),
+                last_error: state.last_error.clone(),
+                retry_after_secs: ceil_secs(open_until - now),
+            });
+        }
+        // Half-open: this caller probes, everyone else keeps failing
+        // fast until it reports back
+        state.probing = true;
+        state.open_until = Some(now + cooldown);
+        Ok(())
+    }
+
+    fn record_failure(&self, session_id: &str, error: &ShebeError, now: Instant) {
+        let settings = self.settings();
+        if settings.failure_threshold == 0 {
+            return;
+        }
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let state = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| State {
+                failures: Vec::new(),
+                last_error: String::new(),
+                open_until: None,
+                probing: false,
+            });
+        state
+            .failures
+            .retain(|&at| now.saturating_duration_since(at) < settings.window);
+        state.failures.push(now);
+        state.last_error = error.to_string();
+
+        let was_probing = std::mem::take(&mut state.probing);
+        if was_probing || state.failures.len() >= settings.failure_threshold as usize {
+            if state.open_until.is_none() || was_probing {
+                tracing::warn!(
+                    "Session '{}' failed to open {} time(s) (last error: {}); \
+                     failing fast for {}s",
+                    session_id,
+                    state.failures.len(),
+                    state.last_error,
+                    settings.cooldown.as_secs()
+                );
+            }
+            state.open_until = Some(now + settings.cooldown);
+        }
+    }
+
+    /// The storage answered for `session_id`; forget its failures
+    fn record_answer(&self, session_id: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(state) = sessions.remove(session_id) {
+            if state.open_until.is_some() {
+                tracing::info!("Session '{}' opens again", session_id);
+            }
+        }
+    }
+
+    /// Forget the failures of `session_id`; returns whether it had any
+    pub fn reset(&self, session_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id)
+            .is_some()
+    }
+
+    /// Breaker state of `session_id`, if it has failed recently
+    pub fn session(&self, session_id: &str) -> Option<SessionBreaker> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session_id)
+            .map(|state| describe(session_id, state, Instant::now()))
+    }
+
+    /// Tripped sessions, sorted by ID
+    pub fn tripped(&self) -> Vec<SessionBreaker> {
+        let now = Instant::now();
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tripped: Vec<SessionBreaker> = sessions
+            .iter()
+            .filter(|(_, state)| state.open_until.is_some())
+            .map(|(id, state)| describe(id, state, now))
+            .collect();
+        tripped.sort_by(|a, b| a.session.cmp(&b.session));
+        tripped
+    }
+}
+
+/// Whether `error` suggests the storage itself is failing
+fn counts_as_failure(error: &ShebeError) -> bool {
+    matches!(error, ShebeError::IoError(_) | ShebeError::StorageError(_))
+}
+
+fn describe(session_id: &str, state: &State, now: Instant) -> SessionBreaker {
+    SessionBreaker {
+        session: session_id.to_string(),
+        failures: state.failures.len() as u32,
+        last_error: state.last_error.clone(),
+        tripped: state.open_until.is_some(),
+        retry_after_secs: state
+            .open_until
+            .map_or(0, |until| ceil_secs(until.saturating_duration_since(now))),
+    }
+}
+
+/// Whole seconds in `duration`, rounded up
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(threshold: u32) -> OpenBreaker {
+        OpenBreaker::new(BreakerSettings {
+            failure_threshold: threshold,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    fn flaky() -> ShebeError {
+        ShebeError::StorageError("stale NFS file handle".to_string())
+    }
+
+    #[test]
+    fn test_trips_after_threshold_within_window() {
+        let breaker = breaker(3);
+        let start = Instant::now();
+        for i in 0..2 {
+            breaker.record_failure("s", &flaky(), start + Duration::from_secs(i));
+            assert!(breaker.check("s", start).is_ok());
+        }
+        breaker.record_failure("s", &flaky(), start + Duration::from_secs(2));
+
+        let err = breaker
+            .check("s", start + Duration::from_secs(12))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Session 's' is temporarily unavailable (last error: Storage error: \
+             stale NFS file handle); retrying after 20 seconds"
+        );
+        assert_eq!(breaker.tripped().len(), 1);
+    }
+
+    #[test]
+    fn test_old_failures_fall_out_of_window() {
+        let breaker = breaker(2);
+        let start = Instant::now();
+        breaker.record_failure("s", &flaky(), start);
+        breaker.record_failure("s", &flaky(), start + Duration::from_secs(61));
+        assert!(breaker.check("s", start + Duration::from_secs(62)).is_ok());
+        assert_eq!(breaker.session("s").unwrap().failures, 1);
+    }
+
+    #[test]
+    fn test_probe_after_cooldown() {
+        let breaker = breaker(1);
+        let start = Instant::now();
+        breaker.record_failure("s", &flaky(), start);
+        let after = start + Duration::from_secs(31);
+
+        // One probe goes through; others fail fast while it runs
+        assert!(breaker.check("s", after).is_ok());
+        assert!(breaker.check("s", after).is_err());
+
+        // A failed probe starts the cooldown over
+        breaker.record_failure("s", &flaky(), after);
+        assert!(breaker.check("s", after + Duration::from_secs(29)).is_err());
+
+        // A successful probe closes the breaker
+        assert!(breaker.check("s", after + Duration::from_secs(31)).is_ok());
+        breaker.record_answer("s");
+        assert!(breaker.session("s").is_none());
+        assert!(breaker.tripped().is_empty());
+    }
+
+    #[test]
+    fn test_only_storage_failures_count() {
+        let breaker = breaker(1);
+        let result: Result<()> =
+            breaker.call("s", || Err(ShebeError::SessionNotFound("s".to_string())));
+        assert!(matches!(result, Err(ShebeError::SessionNotFound(_))));
+        assert!(breaker.session("s").is_none());
+
+        let _ = breaker.call::<()>("s", || Err(flaky()));
+        assert!(breaker.session("s").unwrap().tripped);
+    }
+
+    #[test]
+    fn test_reset_and_disabled() {
+        let breaker = breaker(1);
+        breaker.record_failure("s", &flaky(), Instant::now());
+        assert!(breaker.reset("s"));
+        assert!(!breaker.reset("s"));
+        assert!(breaker.check("s", Instant::now()).is_ok());
+
+        let disabled = self::breaker(0);
+        disabled.record_failure("s", &flaky(), Instant::now());
+        assert!(disabled.session("s").is_none());
+    }
+}
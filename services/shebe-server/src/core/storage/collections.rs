@@ -0,0 +1,388 @@
+//! Named groups of sessions searched together.
+//!
+//! A [`Collection`] names the sessions of, say, every service of one
+//! platform, so a search can cover all of them by that name. The
+//! registry is `collections.json` in the storage root; members are
+//! stored as canonical session IDs and must exist when added. Deleting
+//! a session drops it from every collection it belonged to.
+
+use super::session::write_json;
+use super::{is_valid_name, StorageManager};
+use crate::core::error::{Result, ShebeError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// File name of the registry under the storage root
+pub const COLLECTIONS_FILE: &str = "collections.json";
+
+/// Named group of sessions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Collection {
+    /// Collection name (same rules as a session name)
+    pub name: String,
+
+    /// Member session IDs, in the order they were added
+    pub sessions: Vec<String>,
+
+    pub created_at: DateTime<Utc>,
+
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Contents of `collections.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Registry {
+    #[serde(default)]
+    collections: BTreeMap<String, Collection>,
+}
+
+impl StorageManager {
+    /// Every collection, by name
+    pub fn list_collections(&self) -> Result<Vec<Collection>> {
+        Ok(self.read_registry()?.collections.into_values().collect())
+    }
+
+    /// The collection called `name`
+    ///
+    /// # Errors
+    ///
+    /// `CollectionNotFound` if there is none.
+    pub fn get_collection(&self, name: &str) -> Result<Collection> {
+        self.read_registry()?
+            .collections
+            .remove(name)
+            .ok_or_else(|| ShebeError::CollectionNotFound(name.to_string()))
+    }
+
+    /// Names of the collections `session_id` belongs to
+    pub fn collections_of(&self, session_id: &str) -> Result<Vec<String>> {
+        let session_id = self.canonical_session_id(session_id);
+        Ok(self
+            .read_registry()?
+            .collections
+            .into_values()
+            .filter(|c| c.sessions.contains(&session_id))
+            .map(|c| c.name)
+            .collect())
+    }
+
+    /// Create collection `name` of `sessions`
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` for an invalid name or no sessions,
+    /// `SessionNotFound` for a member that does not exist, and
+    /// `CollectionAlreadyExists` if the name is taken.
+    pub fn create_collection(&self, name: &str, sessions: &[String]) -> Result<Collection> {
+        self.ensure_collections_writable("create", name)?;
+        if !is_valid_name(name) {
+            return Err(ShebeError::InvalidSession(format!(
+                "Invalid collection name '{name}': use 1-64 letters, digits, '-' or '_'"
+            )));
+        }
+        if sessions.is_empty() {
+            return Err(ShebeError::InvalidSession(format!(
+                "Collection '{name}' needs at least one session"
+            )));
+        }
+        let members = self.existing_members(sessions)?;
+
+        let _guard = self
+            .collections_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut registry = self.read_registry()?;
+        if registry.collections.contains_key(name) {
+            return Err(ShebeError::CollectionAlreadyExists(name.to_string()));
+        }
+        let now = Utc::now();
+        let collection = Collection {
+            name: name.to_string(),
+            sessions: members,
+            created_at: now,
+            updated_at: now,
+        };
+        registry
+            .collections
+            .insert(name.to_string(), collection.clone());
+        self.write_registry(&registry)?;
+        Ok(collection)
+    }
+
+    /// Add and remove members of collection `name`
+    ///
+    /// Sessions already in the collection are not added twice.
+    ///
+    /// # Errors
+    ///
+    /// `CollectionNotFound` if there is no such collection,
+    /// `SessionNotFound` for an added session that does not exist, and
+    /// `InvalidSession` for a removed session that is not a member or
+    /// if no member would be left.
+    pub fn update_collection(
+        &self,
+        name: &str,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Collection> {
+        self.ensure_collections_writable("update", name)?;
+        let added = self.existing_members(add)?;
+
+        let _guard = self
+            .collections_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut registry = self.read_registry()?;
+        let collection = registry
+            .collections
+            .get_mut(name)
+            .ok_or_else(|| ShebeError::CollectionNotFound(name.to_string()))?;
+        for session in remove {
+            let session = self.canonical_session_id(session);
+            let Some(at) = collection.sessions.iter().position(|s| *s == session) else {
+                return Err(ShebeError::InvalidSession(format!(
+                    "Session '{session}' is not in collection '{name}'"
+                )));
+            };
+            collection.sessions.remove(at);
+        }
+        for session in added {
+            if !collection.sessions.contains(&session) {
+                collection.sessions.push(session);
+            }
+        }
+        if collection.sessions.is_empty() {
+            return Err(ShebeError::InvalidSession(format!(
+                "Collection '{name}' would have no sessions left; delete it instead"
+            )));
+        }
+        collection.updated_at = Utc::now();
+        let collection = collection.clone();
+        self.write_registry(&registry)?;
+        Ok(collection)
+    }
+
+    /// Delete collection `name`, leaving its sessions alone
+    ///
+    /// # Errors
+    ///
+    /// `CollectionNotFound` if there is no such collection.
+    pub fn delete_collection(&self, name: &str) -> Result<Collection> {
+        self.ensure_collections_writable("delete", name)?;
+        let _guard = self
+            .collections_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut registry = self.read_registry()?;
+        let collection = registry
+            .collections
+            .remove(name)
+            .ok_or_else(|| ShebeError::CollectionNotFound(name.to_string()))?;
+        self.write_registry(&registry)?;
+        Ok(collection)
+    }
+
+    /// Drop a deleted session from every collection
+    ///
+    /// Returns the names of the collections it was removed from.
+    /// Collections left without members are kept, so searching them
+    /// names the problem instead of the collection silently vanishing.
+    pub(super) fn remove_from_collections(&self, session_id: &str) -> Result<Vec<String>> {
+        let session_id = self.canonical_session_id(session_id);
+        let _guard = self
+            .collections_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut registry = self.read_registry()?;
+        let now = Utc::now();
+        let mut removed = Vec::new();
+        for collection in registry.collections.values_mut() {
+            let before = collection.sessions.len();
+            collection.sessions.retain(|s| *s != session_id);
+            if collection.sessions.len() != before {
+                collection.updated_at = now;
+                removed.push(collection.name.clone());
+            }
+        }
+        if !removed.is_empty() {
+            self.write_registry(&registry)?;
+        }
+        Ok(removed)
+    }
+
+    /// Canonical IDs of `sessions`, without duplicates
+    fn existing_members(&self, sessions: &[String]) -> Result<Vec<String>> {
+        let mut members: Vec<String> = Vec::with_capacity(sessions.len());
+        for session in sessions {
+            if !self.session_exists(session) {
+                return Err(ShebeError::SessionNotFound(session.clone()));
+            }
+            let session = self.canonical_session_id(session);
+            if !members.contains(&session) {
+                members.push(session);
+            }
+        }
+        Ok(members)
+    }
+
+    fn ensure_collections_writable(&self, action: &str, name: &str) -> Result<()> {
+        if self.is_read_only() {
+            return Err(ShebeError::ReadOnly(format!(
+                "cannot {action} collection '{name}'"
+            )));
+        }
+        Ok(())
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.storage_root().join(COLLECTIONS_FILE)
+    }
+
+    fn read_registry(&self) -> Result<Registry> {
+        let path = self.registry_path();
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn write_registry(&self, registry: &Registry) -> Result<()> {
+        fs::create_dir_all(self.storage_root())?;
+        write_json(&self.registry_path(), registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::SessionConfig;
+    use tempfile::{tempdir, TempDir};
+
+    fn setup(sessions: &[&str]) -> (TempDir, StorageManager) {
+        let temp = tempdir().unwrap();
+        let manager = StorageManager::new(temp.path().to_path_buf());
+        for session in sessions {
+            manager
+                .create_session(
+                    session,
+                    PathBuf::from("/test/repo"),
+                    SessionConfig::default(),
+                )
+                .unwrap();
+        }
+        (temp, manager)
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_collection_crud() {
+        let (_temp, manager) = setup(&["api", "web", "worker"]);
+
+        let created = manager
+            .create_collection("platform", &ids(&["api", "web", "api"]))
+            .unwrap();
+        assert_eq!(created.sessions, ids(&["api", "web"]));
+        assert!(manager.storage_root().join(COLLECTIONS_FILE).exists());
+        assert!(matches!(
+            manager.create_collection("platform", &ids(&["api"])),
+            Err(ShebeError::CollectionAlreadyExists(_))
+        ));
+
+        let updated = manager
+            .update_collection("platform", &ids(&["worker"]), &ids(&["api"]))
+            .unwrap();
+        assert_eq!(updated.sessions, ids(&["web", "worker"]));
+        assert_eq!(manager.get_collection("platform").unwrap(), updated);
+        assert_eq!(manager.collections_of("web").unwrap(), ids(&["platform"]));
+        assert!(manager.collections_of("api").unwrap().is_empty());
+
+        manager.delete_collection("platform").unwrap();
+        assert!(manager.list_collections().unwrap().is_empty());
+        assert!(matches!(
+            manager.get_collection("platform"),
+            Err(ShebeError::CollectionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_collection_members_must_exist() {
+        let (_temp, manager) = setup(&["api"]);
+
+        assert!(matches!(
+            manager.create_collection("platform", &ids(&["api", "missing"])),
+            Err(ShebeError::SessionNotFound(s)) if s == "missing"
+        ));
+        assert!(matches!(
+            manager.create_collection("bad name", &ids(&["api"])),
+            Err(ShebeError::InvalidSession(_))
+        ));
+        assert!(matches!(
+            manager.create_collection("platform", &[]),
+            Err(ShebeError::InvalidSession(_))
+        ));
+
+        manager
+            .create_collection("platform", &ids(&["api"]))
+            .unwrap();
+        assert!(matches!(
+            manager.update_collection("platform", &ids(&["missing"]), &[]),
+            Err(ShebeError::SessionNotFound(_))
+        ));
+        assert!(matches!(
+            manager.update_collection("platform", &[], &ids(&["web"])),
+            Err(ShebeError::InvalidSession(_))
+        ));
+        assert!(matches!(
+            manager.update_collection("platform", &[], &ids(&["api"])),
+            Err(ShebeError::InvalidSession(_))
+        ));
+    }
+
+    #[test]
+    fn test_deleting_session_leaves_collections() {
+        let (_temp, manager) = setup(&["api", "web"]);
+        manager
+            .create_collection("platform", &ids(&["api", "web"]))
+            .unwrap();
+        manager
+            .create_collection("backend", &ids(&["api"]))
+            .unwrap();
+
+        manager.delete_session("api").unwrap();
+
+        assert_eq!(
+            manager.get_collection("platform").unwrap().sessions,
+            ids(&["web"])
+        );
+        assert!(manager
+            .get_collection("backend")
+            .unwrap()
+            .sessions
+            .is_empty());
+    }
+
+    #[test]
+    fn test_read_only_refuses_collection_changes() {
+        let (temp, manager) = setup(&["api"]);
+        manager
+            .create_collection("platform", &ids(&["api"]))
+            .unwrap();
+
+        let manager = StorageManager::new(temp.path().to_path_buf()).with_read_only(true);
+        assert!(matches!(
+            manager.create_collection("other", &ids(&["api"])),
+            Err(ShebeError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            manager.delete_collection("platform"),
+            Err(ShebeError::ReadOnly(_))
+        ));
+        assert_eq!(manager.list_collections().unwrap().len(), 1);
+    }
+}
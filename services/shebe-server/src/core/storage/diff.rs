@@ -0,0 +1,466 @@
+//! Differences between two sessions of the same repository.
+//!
+//! [`StorageManager::diff_sessions`] lists the files each session
+//! indexed, keyed by their path relative to the repository, and sorts
+//! them into added, removed and changed files. Files are compared by
+//! content hash when both sessions have a content manifest, and by
+//! chunk count otherwise (sessions indexed before manifests existed).
+//! With content hashes, a file whose content moved to a new path is
+//! listed once as renamed instead of as removed and added.
+
+use super::{SessionMetadata, StorageManager};
+use crate::core::display::relative_to;
+use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::dedupe::detect_renames;
+use crate::core::paths;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Files listed per category; the counts always cover every file
+pub const MAX_DIFF_FILES: usize = 100;
+
+/// How files were compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffBasis {
+    /// Content hash and chunk count
+    ContentHash,
+    /// Chunk count only (a session has no content manifest)
+    ChunkCount,
+}
+
+impl DiffBasis {
+    /// Short description for output
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffBasis::ContentHash => "content hash and chunk count",
+            DiffBasis::ChunkCount => "chunk count (no content hashes recorded)",
+        }
+    }
+}
+
+/// One added, removed, changed or renamed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    /// Path relative to the repository (the new path if renamed)
+    pub path: String,
+
+    /// Path in the `from` session of a renamed file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+
+    /// Chunks in the `from` session (`None` if added)
+    pub from_chunks: Option<usize>,
+
+    /// Chunks in the `to` session (`None` if removed)
+    pub to_chunks: Option<usize>,
+}
+
+impl FileDiff {
+    /// Chunk counts for output, e.g. `4 chunks` or `10 -> 12 chunks`
+    pub fn chunk_label(&self) -> String {
+        match (self.from_chunks, self.to_chunks) {
+            (Some(from), Some(to)) if from != to => format!("{from} -> {to} chunks"),
+            (_, Some(chunks)) | (Some(chunks), None) => format!("{chunks} chunks"),
+            (None, None) => "0 chunks".to_string(),
+        }
+    }
+}
+
+/// Files in one category, listed up to [`MAX_DIFF_FILES`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiffGroup {
+    /// Number of files in the category
+    pub count: usize,
+
+    /// First files in path order
+    pub files: Vec<FileDiff>,
+}
+
+impl DiffGroup {
+    fn push(&mut self, file: FileDiff) {
+        self.count += 1;
+        if self.files.len() < MAX_DIFF_FILES {
+            self.files.push(file);
+        }
+    }
+
+    /// Files in the category that are not listed
+    pub fn omitted(&self) -> usize {
+        self.count - self.files.len()
+    }
+}
+
+/// What changed in the index between two sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDiff {
+    /// Baseline session
+    pub from: String,
+
+    /// Session compared against the baseline
+    pub to: String,
+
+    pub from_repository: PathBuf,
+    pub to_repository: PathBuf,
+
+    /// How files were compared
+    pub basis: DiffBasis,
+
+    /// Files only in `to`
+    pub added: DiffGroup,
+
+    /// Files only in `from`
+    pub removed: DiffGroup,
+
+    /// Files in both whose content or chunk count differs
+    pub changed: DiffGroup,
+
+    /// Files whose unchanged content moved to a new path (content
+    /// hash basis only)
+    pub renamed: DiffGroup,
+
+    /// Files identical in both
+    pub unchanged: usize,
+
+    /// One-line summary, e.g. "231 files changed since backend-v1"
+    pub verdict: String,
+}
+
+impl SessionDiff {
+    /// Added, removed, changed and renamed files
+    pub fn total_changes(&self) -> usize {
+        self.added.count + self.removed.count + self.changed.count + self.renamed.count
+    }
+}
+
+/// A file as indexed by one session
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexedFile {
+    chunks: usize,
+    hash: Option<String>,
+}
+
+impl StorageManager {
+    /// Compare the files indexed by two sessions
+    ///
+    /// `from` is the baseline. Sessions are expected to index the same
+    /// repository (same path, or the same remote URL); pass `force` to
+    /// compare sessions of different repositories by relative path.
+    ///
+    /// # Errors
+    ///
+    /// `SessionNotFound` if a session does not exist and
+    /// `InvalidSession` if the repositories differ without `force`.
+    pub fn diff_sessions(&self, from: &str, to: &str, force: bool) -> Result<SessionDiff> {
+        let from_meta = self.get_session_metadata(from)?;
+        let to_meta = self.get_session_metadata(to)?;
+
+        if !force && !same_repository(&from_meta, &to_meta) {
+            return Err(ShebeError::InvalidSession(format!(
+                "Sessions '{from}' and '{to}' index different repositories ({} and {}). \
+                 Pass force to compare them by relative path anyway",
+                repository_label(&from_meta),
+                repository_label(&to_meta)
+            )));
+        }
+
+        let (from_files, from_hashed) = self.indexed_files(&from_meta)?;
+        let (to_files, to_hashed) = self.indexed_files(&to_meta)?;
+        let basis = if from_hashed && to_hashed {
+            DiffBasis::ContentHash
+        } else {
+            DiffBasis::ChunkCount
+        };
+
+        let mut diff = SessionDiff {
+            from: from.to_string(),
+            to: to.to_string(),
+            from_repository: from_meta.repository_path.clone(),
+            to_repository: to_meta.repository_path.clone(),
+            basis,
+            added: DiffGroup::default(),
+            removed: DiffGroup::default(),
+            changed: DiffGroup::default(),
+            renamed: DiffGroup::default(),
+            unchanged: 0,
+            verdict: String::new(),
+        };
+
+        let renames = match basis {
+            DiffBasis::ContentHash => detect_renames(&hashes(&from_files), &hashes(&to_files)),
+            DiffBasis::ChunkCount => Vec::new(),
+        };
+        let renamed_from: BTreeMap<&str, &str> = renames
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        let renamed_to: BTreeMap<&str, &str> = renames
+            .iter()
+            .map(|r| (r.to.as_str(), r.from.as_str()))
+            .collect();
+
+        for (path, old) in &from_files {
+            let file = |new: Option<&IndexedFile>| FileDiff {
+                path: path.clone(),
+                renamed_from: None,
+                from_chunks: Some(old.chunks),
+                to_chunks: new.map(|f| f.chunks),
+            };
+            match to_files.get(path) {
+                None if renamed_from.contains_key(path.as_str()) => {}
+                None => diff.removed.push(file(None)),
+                Some(new) if files_differ(old, new, basis) => diff.changed.push(file(Some(new))),
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+        for (path, new) in &to_files {
+            if from_files.contains_key(path) {
+                continue;
+            }
+            match renamed_to.get(path.as_str()) {
+                Some(old_path) => diff.renamed.push(FileDiff {
+                    path: path.clone(),
+                    renamed_from: Some(old_path.to_string()),
+                    from_chunks: from_files.get(*old_path).map(|f| f.chunks),
+                    to_chunks: Some(new.chunks),
+                }),
+                None => diff.added.push(FileDiff {
+                    path: path.clone(),
+                    renamed_from: None,
+                    from_chunks: None,
+                    to_chunks: Some(new.chunks),
+                }),
+            }
+        }
+
+        diff.verdict = verdict(diff.total_changes(), from);
+        Ok(diff)
+    }
+
+    /// Indexed files of a session by relative path, and whether content
+    /// hashes were available
+    ///
+    /// Files recorded only in the manifest (duplicates skipped by
+    /// dedupe, empty files) are included with 0 chunks.
+    fn indexed_files(
+        &self,
+        metadata: &SessionMetadata,
+    ) -> Result<(BTreeMap<String, IndexedFile>, bool)> {
+        let counts = self.session_files(&metadata.id)?.chunk_counts();
+        let manifest = self.get_content_manifest(&metadata.id)?;
+        let root = &metadata.repository_path;
+        let relative = |stored: &str| {
+            relative_to(&paths::to_native(stored), root)
+                .map(|p| paths::normalize_path(&p))
+                .unwrap_or_else(|| stored.to_string())
+        };
+
+        let mut files: BTreeMap<String, IndexedFile> = counts
+            .into_iter()
+            .map(|(path, chunks)| (relative(&path), IndexedFile { chunks, hash: None }))
+            .collect();
+        let hashed = manifest.is_some();
+        for (path, hash) in manifest.into_iter().flat_map(|m| m.files) {
+            files
+                .entry(relative(&path))
+                .or_insert(IndexedFile {
+                    chunks: 0,
+                    hash: None,
+                })
+                .hash = Some(hash);
+        }
+
+        Ok((files, hashed))
+    }
+}
+
+/// Whether two sessions index the same repository
+fn same_repository(a: &SessionMetadata, b: &SessionMetadata) -> bool {
+    match (&a.remote, &b.remote) {
+        // Each session has its own clone, so compare the URLs
+        (Some(a), Some(b)) => a.url == b.url,
+        _ => a.repository_path == b.repository_path,
+    }
+}
+
+fn repository_label(metadata: &SessionMetadata) -> String {
+    match &metadata.remote {
+        Some(remote) => remote.url.clone(),
+        None => metadata.repository_path.display().to_string(),
+    }
+}
+
+/// Content hash per relative path of the files that have one
+fn hashes(files: &BTreeMap<String, IndexedFile>) -> BTreeMap<String, String> {
+    files
+        .iter()
+        .filter_map(|(path, file)| Some((path.clone(), file.hash.clone()?)))
+        .collect()
+}
+
+fn files_differ(old: &IndexedFile, new: &IndexedFile, basis: DiffBasis) -> bool {
+    old.chunks != new.chunks || (basis == DiffBasis::ContentHash && old.hash != new.hash)
+}
+
+/// e.g. "231 files changed since backend-v1"
+fn verdict(changes: usize, from: &str) -> String {
+    match changes {
+        0 => format!("No files changed since {from}"),
+        1 => format!("1 file changed since {from}"),
+        n => format!("{n} files changed since {from}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::SessionConfig;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::{tempdir, TempDir};
+
+    fn index(manager: &StorageManager, session: &str, repo: &Path) {
+        let config = SessionConfig {
+            include_patterns: vec!["*.rs".to_string()],
+            ..SessionConfig::default()
+        };
+        manager
+            .index_repository_with_config(session, repo, config, 10, true)
+            .unwrap();
+    }
+
+    fn setup() -> (TempDir, StorageManager, PathBuf) {
+        let temp = tempdir().unwrap();
+        let manager = StorageManager::new(temp.path().join("index"));
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("keep.rs"), "fn keep() {}").unwrap();
+        fs::write(repo.join("edit.rs"), "fn edit() {}").unwrap();
+        fs::write(repo.join("gone.rs"), "fn gone() {}").unwrap();
+        (temp, manager, repo)
+    }
+
+    #[test]
+    fn test_diff_sessions_categories() {
+        let (_temp, manager, repo) = setup();
+        index(&manager, "v1", &repo);
+
+        fs::write(repo.join("edit.rs"), "fn edited() {}").unwrap();
+        fs::remove_file(repo.join("gone.rs")).unwrap();
+        fs::write(repo.join("new.rs"), "fn new() {}").unwrap();
+        index(&manager, "v2", &repo);
+
+        let diff = manager.diff_sessions("v1", "v2", false).unwrap();
+
+        assert_eq!(diff.basis, DiffBasis::ContentHash);
+        assert_eq!(diff.added.files[0].path, "new.rs");
+        assert_eq!(diff.removed.files[0].path, "gone.rs");
+        assert_eq!(
+            diff.changed.files,
+            vec![FileDiff {
+                path: "edit.rs".to_string(),
+                renamed_from: None,
+                from_chunks: Some(1),
+                to_chunks: Some(1),
+            }]
+        );
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.verdict, "3 files changed since v1");
+    }
+
+    #[test]
+    fn test_diff_sessions_renamed() {
+        let (_temp, manager, repo) = setup();
+        index(&manager, "v1", &repo);
+
+        fs::create_dir_all(repo.join("old")).unwrap();
+        fs::rename(repo.join("keep.rs"), repo.join("old/kept.rs")).unwrap();
+        index(&manager, "v2", &repo);
+
+        let diff = manager.diff_sessions("v1", "v2", false).unwrap();
+
+        assert_eq!(diff.added.count, 0);
+        assert_eq!(diff.removed.count, 0);
+        assert_eq!(
+            diff.renamed.files,
+            vec![FileDiff {
+                path: "old/kept.rs".to_string(),
+                renamed_from: Some("keep.rs".to_string()),
+                from_chunks: Some(1),
+                to_chunks: Some(1),
+            }]
+        );
+        assert_eq!(diff.unchanged, 2);
+        assert_eq!(diff.verdict, "1 file changed since v1");
+    }
+
+    #[test]
+    fn test_diff_sessions_without_manifest_uses_chunk_counts() {
+        let (_temp, manager, repo) = setup();
+        index(&manager, "v1", &repo);
+        fs::write(repo.join("edit.rs"), "fn edited() {}").unwrap();
+        index(&manager, "v2", &repo);
+        fs::remove_file(manager.get_session_path("v1").join("manifest.json")).unwrap();
+
+        let diff = manager.diff_sessions("v1", "v2", false).unwrap();
+
+        assert_eq!(diff.basis, DiffBasis::ChunkCount);
+        assert_eq!(diff.changed.count, 0);
+        assert_eq!(diff.verdict, "No files changed since v1");
+    }
+
+    #[test]
+    fn test_diff_sessions_refuses_other_repository() {
+        let (temp, manager, repo) = setup();
+        index(&manager, "v1", &repo);
+        let other = temp.path().join("other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("keep.rs"), "fn keep() {}").unwrap();
+        index(&manager, "other", &other);
+
+        let err = manager.diff_sessions("v1", "other", false).unwrap_err();
+        assert!(matches!(err, ShebeError::InvalidSession(_)));
+        assert!(err.to_string().contains("Pass force"));
+
+        // Forced, files are matched by relative path
+        let diff = manager.diff_sessions("v1", "other", true).unwrap();
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.removed.count, 2);
+        assert!(matches!(
+            manager.diff_sessions("v1", "missing", true),
+            Err(ShebeError::SessionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_diff_chunk_label() {
+        let file = |from_chunks, to_chunks| FileDiff {
+            path: "lib.rs".to_string(),
+            renamed_from: None,
+            from_chunks,
+            to_chunks,
+        };
+        assert_eq!(file(Some(10), Some(12)).chunk_label(), "10 -> 12 chunks");
+        assert_eq!(file(Some(3), Some(3)).chunk_label(), "3 chunks");
+        assert_eq!(file(None, Some(4)).chunk_label(), "4 chunks");
+        assert_eq!(file(Some(2), None).chunk_label(), "2 chunks");
+    }
+
+    #[test]
+    fn test_diff_group_caps_listed_files() {
+        let mut group = DiffGroup::default();
+        for i in 0..MAX_DIFF_FILES + 5 {
+            group.push(FileDiff {
+                path: format!("{i}.rs"),
+                renamed_from: None,
+                from_chunks: None,
+                to_chunks: Some(1),
+            });
+        }
+
+        assert_eq!(group.count, MAX_DIFF_FILES + 5);
+        assert_eq!(group.files.len(), MAX_DIFF_FILES);
+        assert_eq!(group.omitted(), 5);
+    }
+}
@@ -0,0 +1,233 @@
+//! File manifest: the files of a session without reading its index.
+//!
+//! Every index run writes `files.json` next to the index, listing each
+//! indexed file with its chunk count and size in the order it was
+//! indexed. Listing a session's files (list_dir, find_file, the file
+//! tree, path search, diffs) then reads one small file instead of every
+//! stored chunk. Sessions indexed before the manifest existed, and
+//! manifests that no longer match the index, fall back to a scan of the
+//! index, with a warning once per session.
+
+use super::{StorageManager, TantivyIndex};
+use crate::core::error::Result;
+use crate::core::paths;
+use crate::core::types::Chunk;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+/// File name of the manifest in a session directory
+pub(super) const FILE_MANIFEST_NAME: &str = "files.json";
+
+/// One indexed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// File path as stored in the index
+    pub path: String,
+    /// Chunks indexed for the file
+    pub chunk_count: usize,
+    /// Bytes covered by the file's chunks (its size when indexed)
+    pub size_bytes: u64,
+}
+
+/// Indexed files of a session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileManifest {
+    /// Chunks in the index when the manifest was written
+    pub total_chunks: u64,
+    /// Files in indexed order
+    pub files: Vec<ManifestFile>,
+}
+
+impl FileManifest {
+    /// Manifest of the chunks written to a new index
+    pub fn from_chunks(chunks: &[Chunk]) -> Self {
+        let mut files: Vec<ManifestFile> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for chunk in chunks {
+            let path = paths::normalize_path(&chunk.file_path);
+            let position = *positions.entry(path.clone()).or_insert_with(|| {
+                files.push(ManifestFile {
+                    path,
+                    chunk_count: 0,
+                    size_bytes: 0,
+                });
+                files.len() - 1
+            });
+            let file = &mut files[position];
+            file.chunk_count += 1;
+            file.size_bytes = file.size_bytes.max(chunk.end_offset as u64);
+        }
+        Self {
+            total_chunks: chunks.len() as u64,
+            files,
+        }
+    }
+
+    /// Manifest read back from every stored chunk of an index
+    ///
+    /// Files are sorted by path, since the index does not keep the
+    /// order they were indexed in.
+    pub fn scan(index: &TantivyIndex) -> Result<Self> {
+        let files: Vec<ManifestFile> = index
+            .file_sizes()?
+            .into_iter()
+            .map(|(path, (chunk_count, size_bytes))| ManifestFile {
+                path,
+                chunk_count,
+                size_bytes,
+            })
+            .collect();
+        Ok(Self {
+            total_chunks: files.iter().map(|f| f.chunk_count as u64).sum(),
+            files,
+        })
+    }
+
+    /// Number of chunks per file path
+    pub fn chunk_counts(&self) -> BTreeMap<String, usize> {
+        self.files
+            .iter()
+            .map(|f| (f.path.clone(), f.chunk_count))
+            .collect()
+    }
+
+    /// Every file path, sorted
+    pub fn sorted_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.files.iter().map(|f| f.path.clone()).collect();
+        paths.sort();
+        paths
+    }
+}
+
+impl StorageManager {
+    /// Path of a session's file manifest
+    fn file_manifest_path(&self, session_id: &str) -> PathBuf {
+        self.get_session_path(session_id).join(FILE_MANIFEST_NAME)
+    }
+
+    /// Get the file manifest written when a session was indexed
+    ///
+    /// Returns `None` for sessions indexed before manifests existed.
+    pub fn get_file_manifest(&self, session_id: &str) -> Result<Option<FileManifest>> {
+        let path = self.file_manifest_path(session_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Every indexed file of a session
+    ///
+    /// Read from the file manifest when it matches the index, otherwise
+    /// from a scan of every stored chunk (warning once per session).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionNotFound` if the session does not exist.
+    pub fn session_files(&self, session_id: &str) -> Result<FileManifest> {
+        let reader = self.session_reader(session_id)?;
+        let num_docs = reader.searcher().num_docs();
+
+        let problem = match self.get_file_manifest(session_id) {
+            Ok(Some(manifest)) if manifest.total_chunks == num_docs => return Ok(manifest),
+            Ok(Some(manifest)) => format!(
+                "{FILE_MANIFEST_NAME} lists {} chunks but the index holds {num_docs}",
+                manifest.total_chunks
+            ),
+            Ok(None) => format!("no {FILE_MANIFEST_NAME} (indexed by an older version)"),
+            Err(e) => format!("{FILE_MANIFEST_NAME} is unreadable ({e})"),
+        };
+        self.warn_manifest_fallback(session_id, &problem);
+        FileManifest::scan(reader.index())
+    }
+
+    /// Warn that a session's files are read by a scan, once per session
+    fn warn_manifest_fallback(&self, session_id: &str, problem: &str) {
+        let mut warned = self
+            .manifest_warnings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if warned.insert(session_id.to_string()) {
+            tracing::warn!(
+                "Session '{session_id}': {problem}; listing files by scanning the index. \
+                 Re-index the session to speed this up."
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::SessionConfig;
+    use tempfile::TempDir;
+
+    fn chunk(path: &str, chunk_index: usize, start: usize, end: usize) -> Chunk {
+        Chunk {
+            text: "x".repeat(end - start),
+            file_path: PathBuf::from(path),
+            start_offset: start,
+            end_offset: end,
+            chunk_index,
+            records: None,
+            section: None,
+        }
+    }
+
+    #[test]
+    fn test_from_chunks_keeps_indexed_order() {
+        let manifest = FileManifest::from_chunks(&[
+            chunk("/repo/z.rs", 0, 0, 10),
+            chunk("/repo/z.rs", 1, 8, 25),
+            chunk("/repo/a.rs", 0, 0, 4),
+        ]);
+
+        assert_eq!(manifest.total_chunks, 3);
+        let files: Vec<(&str, usize, u64)> = manifest
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.chunk_count, f.size_bytes))
+            .collect();
+        assert_eq!(files, vec![("/repo/z.rs", 2, 25), ("/repo/a.rs", 1, 4)]);
+        assert_eq!(manifest.sorted_paths(), vec!["/repo/a.rs", "/repo/z.rs"]);
+    }
+
+    #[test]
+    fn test_session_files_falls_back_to_scan() {
+        let temp = TempDir::new().unwrap();
+        let storage = StorageManager::new(temp.path().to_path_buf());
+        let mut index = storage
+            .create_session("s", PathBuf::from("/repo"), SessionConfig::default())
+            .unwrap();
+        let chunks = [
+            chunk("/repo/b.rs", 0, 0, 10),
+            chunk("/repo/b.rs", 1, 8, 30),
+            chunk("/repo/a.rs", 0, 0, 5),
+        ];
+        index.add_chunks(&chunks, "s").unwrap();
+        index.commit().unwrap();
+
+        // No manifest: scanned, sorted by path
+        let scanned = storage.session_files("s").unwrap();
+        assert_eq!(scanned.total_chunks, 3);
+        assert_eq!(
+            scanned.chunk_counts(),
+            FileManifest::from_chunks(&chunks).chunk_counts()
+        );
+        assert_eq!(scanned.files[0].path, "/repo/a.rs");
+        assert_eq!(scanned.files[1].size_bytes, 30);
+
+        // A manifest that matches the index is used as written
+        let written = FileManifest::from_chunks(&chunks);
+        let path = storage.file_manifest_path("s");
+        fs::write(&path, serde_json::to_string(&written).unwrap()).unwrap();
+        assert_eq!(storage.session_files("s").unwrap(), written);
+
+        // A stale one is ignored
+        let stale = FileManifest::from_chunks(&chunks[..1]);
+        fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+        assert_eq!(storage.session_files("s").unwrap(), scanned);
+    }
+}
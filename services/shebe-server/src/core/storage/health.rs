@@ -0,0 +1,321 @@
+//! Health and freshness of a single session.
+//!
+//! [`StorageManager::session_health`] checks what the metadata alone
+//! cannot tell: whether the index still holds the chunks it recorded,
+//! whether the repository is still where it was indexed from, how many
+//! indexed files were deleted or modified since, and whether the
+//! session is old enough to be considered stale. Each problem found
+//! becomes a [`HealthAction`], in the order it should be addressed.
+
+use super::{SessionMetadata, StorageManager, TantivyIndex, SCHEMA_VERSION};
+use crate::core::error::Result;
+use crate::core::paths;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Most indexed files checked on disk; larger sessions are sampled
+pub const MAX_FILE_CHECKS: usize = 500;
+
+/// Indexed files compared with the files on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileFreshness {
+    /// Files recorded at the last indexing
+    pub indexed: usize,
+
+    /// Files checked on disk
+    pub checked: usize,
+
+    /// Whether only an evenly spread sample of the files was checked
+    pub sampled: bool,
+
+    /// Checked files that no longer exist
+    pub missing: usize,
+
+    /// Checked files modified after the last indexing
+    pub modified: usize,
+}
+
+impl FileFreshness {
+    /// Missing and modified files among those checked
+    pub fn changed(&self) -> usize {
+        self.missing + self.modified
+    }
+}
+
+/// A problem found by [`StorageManager::session_health`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum HealthAction {
+    /// The repository path no longer exists
+    RepositoryMissing { path: PathBuf },
+
+    /// The session was indexed with an older schema
+    SchemaOutdated { version: u32, current: u32 },
+
+    /// The index holds a different number of chunks than recorded
+    /// (`None`: the index could not be opened)
+    DocCountMismatch {
+        index_docs: Option<u64>,
+        chunks_created: usize,
+    },
+
+    /// Indexed files were deleted or modified since indexing
+    FilesChanged {
+        changed: usize,
+        checked: usize,
+        sampled: bool,
+    },
+
+    /// The last indexing is older than the staleness threshold
+    Stale { age_days: u64 },
+}
+
+impl HealthAction {
+    /// What is wrong, without the remedy, e.g. "37 files changed since
+    /// indexing"
+    pub fn summary(&self) -> String {
+        match self {
+            Self::RepositoryMissing { path } => {
+                format!("repository path {} is missing", path.display())
+            }
+            Self::SchemaOutdated { version, current } => {
+                format!("index uses schema v{version} (current v{current})")
+            }
+            Self::DocCountMismatch {
+                index_docs: Some(docs),
+                chunks_created,
+            } => format!("index holds {docs} chunks but {chunks_created} were recorded"),
+            Self::DocCountMismatch {
+                index_docs: None, ..
+            } => "index could not be opened".to_string(),
+            Self::FilesChanged {
+                changed,
+                checked,
+                sampled,
+            } => {
+                if *sampled {
+                    format!("{changed} of {checked} sampled files changed since indexing")
+                } else if *changed == 1 {
+                    "1 file changed since indexing".to_string()
+                } else {
+                    format!("{changed} files changed since indexing")
+                }
+            }
+            Self::Stale { age_days } => format!("last indexed {age_days} days ago"),
+        }
+    }
+}
+
+/// Health of a session's index against its metadata and the repository
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionHealth {
+    pub session_id: String,
+
+    pub repository_path: PathBuf,
+
+    /// Whether `repository_path` still exists
+    pub repository_exists: bool,
+
+    /// Documents in the Tantivy index (`None` if it could not be opened)
+    pub index_docs: Option<u64>,
+
+    /// Chunks recorded in the metadata
+    pub chunks_created: usize,
+
+    /// Indexed files compared with the disk (`None` when the repository
+    /// is missing or the indexed files are unknown)
+    pub files: Option<FileFreshness>,
+
+    pub last_indexed_at: DateTime<Utc>,
+
+    /// Seconds since `last_indexed_at`
+    pub age_seconds: i64,
+
+    /// Threshold the age is checked against
+    pub stale_after_days: u64,
+
+    /// Whether the session is older than `stale_after_days`
+    pub stale: bool,
+
+    pub schema_version: u32,
+
+    pub current_schema_version: u32,
+
+    /// Problems found, most urgent first (empty when healthy)
+    pub actions: Vec<HealthAction>,
+}
+
+impl SessionHealth {
+    /// Whether no problems were found
+    pub fn is_healthy(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Whole days since the last indexing
+    pub fn age_days(&self) -> u64 {
+        (self.age_seconds.max(0) / 86_400) as u64
+    }
+}
+
+impl StorageManager {
+    /// Check a session's index, repository and freshness
+    ///
+    /// At most [`MAX_FILE_CHECKS`] indexed files are checked on disk.
+    /// Sessions with an older schema are still checked; the index is
+    /// opened without the schema version check.
+    ///
+    /// # Errors
+    ///
+    /// `SessionNotFound` if the session does not exist.
+    pub fn session_health(&self, session_id: &str, stale_after_days: u64) -> Result<SessionHealth> {
+        let metadata = self.get_session_metadata(session_id)?;
+        let index = TantivyIndex::open(&self.tantivy_dir(session_id)).ok();
+        let index_docs = index
+            .as_ref()
+            .and_then(|index| index.reader().ok())
+            .map(|reader| reader.searcher().num_docs());
+
+        // Archive entries are not files on disk; reindex_session compares
+        // the archive's content hash instead
+        let is_archive = metadata.archive.is_some();
+        let repository_exists = if is_archive {
+            metadata.repository_path.is_file()
+        } else {
+            metadata.repository_path.is_dir()
+        };
+        let files = if repository_exists && !is_archive {
+            self.indexed_file_paths(session_id, index.as_ref())?
+                .map(|files| check_files(&files, &metadata))
+        } else {
+            None
+        };
+
+        let age_seconds = (Utc::now() - metadata.last_indexed_at).num_seconds();
+        let stale = age_seconds > stale_after_days.saturating_mul(86_400) as i64;
+
+        let mut health = SessionHealth {
+            session_id: metadata.id.clone(),
+            repository_path: metadata.repository_path.clone(),
+            repository_exists,
+            index_docs,
+            chunks_created: metadata.chunks_created,
+            files,
+            last_indexed_at: metadata.last_indexed_at,
+            age_seconds,
+            stale_after_days,
+            stale,
+            schema_version: metadata.schema_version,
+            current_schema_version: SCHEMA_VERSION,
+            actions: Vec::new(),
+        };
+        health.actions = recommend(&health);
+        Ok(health)
+    }
+
+    /// Paths of the files read at the last indexing, in path order
+    ///
+    /// From the content manifest, or from the index for sessions
+    /// indexed before manifests existed. `None` if neither is readable.
+    fn indexed_file_paths(
+        &self,
+        session_id: &str,
+        index: Option<&TantivyIndex>,
+    ) -> Result<Option<Vec<String>>> {
+        if let Some(manifest) = self.get_content_manifest(session_id)? {
+            return Ok(Some(manifest.files.into_keys().collect()));
+        }
+        Ok(index
+            .and_then(|index| index.file_chunk_counts().ok())
+            .map(|counts| counts.into_keys().collect()))
+    }
+}
+
+/// Check up to [`MAX_FILE_CHECKS`] files, spread evenly over `files`
+fn check_files(files: &[String], metadata: &SessionMetadata) -> FileFreshness {
+    let checked = files.len().min(MAX_FILE_CHECKS);
+    let mut freshness = FileFreshness {
+        indexed: files.len(),
+        checked,
+        sampled: checked < files.len(),
+        missing: 0,
+        modified: 0,
+    };
+    for i in 0..checked {
+        let file = &files[i * files.len() / checked];
+        match std::fs::metadata(paths::to_native(file)).and_then(|m| m.modified()) {
+            Ok(modified) if DateTime::<Utc>::from(modified) > metadata.last_indexed_at => {
+                freshness.modified += 1;
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => freshness.missing += 1,
+            // Unreadable modification times say nothing about freshness
+            Err(_) => {}
+        }
+    }
+    freshness
+}
+
+/// Problems in `health`, most urgent first
+fn recommend(health: &SessionHealth) -> Vec<HealthAction> {
+    let mut actions = Vec::new();
+    if !health.repository_exists {
+        actions.push(HealthAction::RepositoryMissing {
+            path: health.repository_path.clone(),
+        });
+    }
+    if health.schema_version < health.current_schema_version {
+        actions.push(HealthAction::SchemaOutdated {
+            version: health.schema_version,
+            current: health.current_schema_version,
+        });
+    }
+    if health.index_docs != Some(health.chunks_created as u64) {
+        actions.push(HealthAction::DocCountMismatch {
+            index_docs: health.index_docs,
+            chunks_created: health.chunks_created,
+        });
+    }
+    if let Some(files) = health.files.as_ref().filter(|f| f.changed() > 0) {
+        actions.push(HealthAction::FilesChanged {
+            changed: files.changed(),
+            checked: files.checked,
+            sampled: files.sampled,
+        });
+    }
+    if health.stale {
+        actions.push(HealthAction::Stale {
+            age_days: health.age_days(),
+        });
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_summaries() {
+        let changed = HealthAction::FilesChanged {
+            changed: 37,
+            checked: 120,
+            sampled: false,
+        };
+        assert_eq!(changed.summary(), "37 files changed since indexing");
+
+        let sampled = HealthAction::FilesChanged {
+            changed: 4,
+            checked: 500,
+            sampled: true,
+        };
+        assert_eq!(
+            sampled.summary(),
+            "4 of 500 sampled files changed since indexing"
+        );
+        assert_eq!(
+            HealthAction::Stale { age_days: 12 }.summary(),
+            "last indexed 12 days ago"
+        );
+    }
+}
@@ -8,29 +8,82 @@
 //!
 //! - **TantivyIndex**: Wraps Tantivy index operations
 //! - **StorageManager**: Manages session-based storage
+//! - **namespace**: Session IDs of the form `namespace/name`, one
+//!   storage root per namespace
+//! - **staging**: Builds sessions in a temporary directory, renamed
+//!   into place on success
+//! - **readers**: Index readers shared by concurrent searches
+//! - **breaker**: Fast failure for sessions that keep failing to open
+//! - **report**: Disk usage across all sessions
+//! - **collections**: Named groups of sessions searched together
+//! - **files**: Indexed files of a session, from `files.json` or a
+//!   scan of the index
+//! - **diff**: Files added, removed or changed between two sessions
+//! - **health**: Index health and freshness of a single session
 //! - **SessionMetadata**: Tracks session statistics
+//! - **provenance**: Version, config, git commit and timing of an index run
+//! - **upgrade**: Migrates sessions from older schema versions
 //!
 //! # Session Storage Structure
 //!
 //! ```text
+//! {storage_root}/collections.json  # Collections of sessions
 //! {storage_root}/sessions/
 //! ├── {session-id-1}/
 //! │   ├── meta.json           # Session metadata
+//! │   ├── manifest.json       # Content hashes and duplicates
+//! │   ├── files.json          # Indexed files with chunk counts
 //! │   └── tantivy/            # Tantivy index
 //! │       ├── .managed.json
 //! │       ├── meta.json
 //! │       └── [segment files]
+//! └── .tmp-{session-id}-{nonce}/   # Session being indexed
 //! ```
 
+mod breaker;
+mod collections;
+mod diff;
+mod files;
+mod health;
+mod namespace;
+mod provenance;
+mod readers;
+mod report;
 mod session;
+mod staging;
 mod tantivy;
+mod upgrade;
 mod validator;
 
 // Note: SessionConfig and SessionMetadata used in shebe-mcp binary and integration tests
+pub use breaker::{BreakerSettings, OpenBreaker, SessionBreaker};
+pub use collections::{Collection, COLLECTIONS_FILE};
+pub use diff::{DiffBasis, DiffGroup, FileDiff, SessionDiff, MAX_DIFF_FILES};
+pub use files::{FileManifest, ManifestFile};
+pub use health::{FileFreshness, HealthAction, SessionHealth, MAX_FILE_CHECKS};
+pub use namespace::{
+    is_valid_name, parse_session_id, SessionId, DEFAULT_NAMESPACE, SESSION_ID_PATTERN,
+};
+pub use provenance::{GitState, Provenance};
+pub use readers::SessionReader;
+pub use report::{
+    drift_percent, size_drifted, SessionUsage, StorageReport, SIZE_DRIFT_MIN_BYTES,
+    SIZE_DRIFT_THRESHOLD_PERCENT,
+};
 #[allow(unused_imports)]
-pub use session::{SessionConfig, SessionMetadata, StorageManager};
+pub use session::{
+    SessionConfig, SessionConfigBuilder, SessionEntry, SessionMetadata, StorageManager,
+    CURRENT_VERSION, UNKNOWN,
+};
+pub use upgrade::{UpgradeReport, UpgradeStrategy};
 // Note: Used in shebe-mcp binary, not in lib tests
 #[allow(unused_imports)]
-pub use validator::{MetadataValidator, ValidationReport};
-// Re-export schema version for use in MCP tools
-pub use tantivy::SCHEMA_VERSION;
+pub use validator::{
+    ChunkIndexProblem, ChunkIndexReport, MetadataValidator, SplitToken, SplitTokenReport,
+    ValidationReport, SPLIT_TOKEN_SAMPLE_FILES,
+};
+// Re-export index wrapper and schema version for search and MCP tools
+pub use tantivy::{
+    current_schema_hash, current_schema_hash_for, ChunkSpan, FieldSettings, IndexSettings,
+    TantivyIndex, SCHEMA_VERSION,
+};
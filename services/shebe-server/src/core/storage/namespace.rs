@@ -0,0 +1,101 @@
+//! Session namespaces: several storage roots served side by side.
+//!
+//! A session ID is either a bare name (`backend`), which lives in the
+//! default namespace, or a namespace and a name joined by one slash
+//! (`team-a/backend`). Each namespace has its own storage root
+//! (`[namespaces.<name>]`), so sessions and metadata in one are never
+//! seen through another. The `default` namespace is `storage.index_dir`.
+
+use crate::core::error::{Result, ShebeError};
+
+/// Namespace whose storage root is `storage.index_dir`
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// JSON schema pattern for session IDs, with an optional namespace
+pub const SESSION_ID_PATTERN: &str = "^([a-zA-Z0-9_-]{1,64}/)?[a-zA-Z0-9_-]{1,64}$";
+
+/// A session ID split into its namespace and name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionId<'a> {
+    /// Namespace given before the slash (`None` for a bare ID)
+    pub namespace: Option<&'a str>,
+    /// Session name within the namespace
+    pub name: &'a str,
+}
+
+/// Split `id` into namespace and name
+///
+/// A bare ID is returned as the name, unchecked, as it always was.
+///
+/// # Errors
+///
+/// `InvalidSession` when either side of the slash is empty or holds
+/// anything but alphanumeric, hyphen or underscore characters (so at
+/// most one slash).
+pub fn parse_session_id(id: &str) -> Result<SessionId<'_>> {
+    let Some((namespace, name)) = id.split_once('/') else {
+        return Ok(SessionId {
+            namespace: None,
+            name: id,
+        });
+    };
+    if !is_valid_name(namespace) || !is_valid_name(name) {
+        return Err(ShebeError::InvalidSession(format!(
+            "Invalid session ID '{id}': use 'name' or 'namespace/name', each part made of \
+             alphanumeric, hyphen or underscore characters"
+        )));
+    }
+    Ok(SessionId {
+        namespace: Some(namespace),
+        name,
+    })
+}
+
+/// Whether `name` can be a namespace or a session name: 1-64
+/// alphanumeric, hyphen or underscore characters
+pub fn is_valid_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_and_namespaced_ids() {
+        assert_eq!(
+            parse_session_id("backend").unwrap(),
+            SessionId {
+                namespace: None,
+                name: "backend"
+            }
+        );
+        assert_eq!(
+            parse_session_id("team-a/backend").unwrap(),
+            SessionId {
+                namespace: Some("team-a"),
+                name: "backend"
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_ids() {
+        for id in ["/backend", "team-a/", "a/b/c", "team a/backend", "../etc"] {
+            let err = parse_session_id(id).unwrap_err();
+            assert!(matches!(err, ShebeError::InvalidSession(_)), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_schema_pattern_allows_one_slash() {
+        let pattern = regex::Regex::new(SESSION_ID_PATTERN).unwrap();
+        assert!(pattern.is_match("backend"));
+        assert!(pattern.is_match("team-a/backend"));
+        assert!(!pattern.is_match("a/b/c"));
+        assert!(!pattern.is_match("team-a/"));
+    }
+}
@@ -0,0 +1,168 @@
+//! How an index was produced.
+//!
+//! Each index run records the shebe version, the effective session
+//! configuration, the text tokenizer, the resolved repository path,
+//! the git commit (and whether the checkout had uncommitted changes),
+//! the host platform and when the run started and finished. The record
+//! is returned with the run's statistics and persisted in the session
+//! metadata, so an index can be traced back to its inputs later.
+
+use crate::core::storage::session::{SessionConfig, CURRENT_VERSION};
+use crate::core::storage::tantivy::IndexSettings;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Schema field whose tokenizer is recorded
+const TEXT_FIELD: &str = "text";
+
+/// Inputs and environment of one index run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Shebe version that built the index
+    pub shebe_version: String,
+
+    /// Effective session configuration (patterns, chunking, flags)
+    pub config: SessionConfig,
+
+    /// Tokenizer of the chunk text field
+    pub tokenizer: String,
+
+    /// Canonical repository path that was indexed
+    pub repository_path: PathBuf,
+
+    /// Checkout state (`null` when the path is not a git repository or
+    /// the session has `git_metadata` off)
+    pub git: Option<GitState>,
+
+    /// Host operating system and architecture ("linux-x86_64")
+    pub platform: String,
+
+    /// When indexing started
+    pub started_at: DateTime<Utc>,
+
+    /// When the finished session was written
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Commit a git checkout was at when it was indexed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitState {
+    /// Full SHA of `HEAD`
+    pub commit: String,
+
+    /// Whether tracked files had uncommitted changes
+    pub dirty: bool,
+}
+
+impl Provenance {
+    /// Record a run over `repository_path` that began at `started_at`
+    ///
+    /// `finished_at` is set to `started_at`; call [`Provenance::finish`]
+    /// once the session is written. Git state is read only when the
+    /// config has `git_metadata` on.
+    pub fn start(
+        repository_path: &Path,
+        config: &SessionConfig,
+        started_at: DateTime<Utc>,
+    ) -> Self {
+        let tokenizer = IndexSettings::current_for(config.store_chunk_text)
+            .fields
+            .into_iter()
+            .find(|field| field.name == TEXT_FIELD)
+            .and_then(|field| field.tokenizer)
+            .unwrap_or_default();
+        let git = if config.git_metadata {
+            GitState::read(repository_path)
+        } else {
+            None
+        };
+
+        Self {
+            shebe_version: CURRENT_VERSION.to_string(),
+            config: config.clone(),
+            tokenizer,
+            repository_path: repository_path.to_path_buf(),
+            git,
+            platform: host_platform(),
+            started_at,
+            finished_at: started_at,
+        }
+    }
+
+    /// Mark the run finished now
+    pub fn finish(&mut self) {
+        self.finished_at = Utc::now();
+    }
+}
+
+impl GitState {
+    /// `HEAD` and dirty state of the checkout at `root`
+    ///
+    /// `None` if `root` is not inside a git work tree, has no commits
+    /// or `git` is not installed.
+    pub fn read(root: &Path) -> Option<Self> {
+        let commit = git_output(root, &["rev-parse", "--verify", "HEAD"])?;
+        let status = git_output(root, &["status", "--porcelain", "--untracked-files=no"])?;
+        Some(Self {
+            commit,
+            dirty: !status.is_empty(),
+        })
+    }
+}
+
+/// Trimmed stdout of a successful git command run in `root`
+fn git_output(root: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Operating system and CPU architecture of this build
+fn host_platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_non_git_directory_has_no_git_state() {
+        let dir = TempDir::new().unwrap();
+        let provenance = Provenance::start(dir.path(), &SessionConfig::default(), Utc::now());
+
+        assert!(provenance.git.is_none());
+        assert_eq!(provenance.shebe_version, CURRENT_VERSION);
+        assert!(!provenance.tokenizer.is_empty());
+        assert_eq!(provenance.finished_at, provenance.started_at);
+
+        let json = serde_json::to_value(&provenance).unwrap();
+        assert!(json["git"].is_null());
+    }
+
+    #[test]
+    fn test_git_metadata_off_skips_git() {
+        // The crate itself lives in a git checkout
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = SessionConfig {
+            git_metadata: false,
+            ..Default::default()
+        };
+
+        let provenance = Provenance::start(root, &config, Utc::now());
+        assert!(provenance.git.is_none());
+    }
+}
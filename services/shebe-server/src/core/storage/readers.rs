@@ -0,0 +1,322 @@
+//! Index readers shared by concurrent searches.
+//!
+//! Opening a session's Tantivy index and creating a reader is the most
+//! expensive part of a small query. [`ReaderCache`] keeps one open
+//! reader per session so concurrent searches share it.
+//!
+//! # Concurrency
+//!
+//! - Sessions are spread over [`SHARDS`] independently locked maps, so
+//!   lookups for different sessions rarely touch the same lock
+//! - Locks are only held to look up or insert an `Arc`; opening an index
+//!   and searching it happen outside any lock
+//! - A cached reader is reused while the session's [`IndexFingerprint`]
+//!   is unchanged. Re-indexing, incremental updates and compaction all
+//!   rewrite the index's `meta.json`, so the next search opens a fresh
+//!   reader. So does a change to the session's own `meta.json` (e.g.
+//!   search defaults set by another process). Searches already running
+//!   keep their snapshot.
+//!
+//! Searches on different sessions therefore scale with the number of
+//! cores; searches on the same session share one reader and only
+//! contend inside Tantivy's own (read-only) segment access.
+
+use super::tantivy::TantivyIndex;
+use crate::core::error::{Result, ShebeError};
+use crate::core::search::scoring::Bm25Params;
+use crate::core::types::{ModifiedTimeSource, SessionSearchDefaults};
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tantivy::{IndexReader, ReloadPolicy, Searcher};
+
+/// Number of independently locked maps
+pub const SHARDS: usize = 16;
+
+/// Identifies one on-disk state of a session index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexFingerprint {
+    /// Session's `last_indexed_at`
+    indexed_at: DateTime<Utc>,
+    /// Modification time and size of the Tantivy `meta.json`
+    meta_modified: Option<SystemTime>,
+    meta_len: u64,
+    /// Modification time and size of the session metadata
+    session_meta_modified: Option<SystemTime>,
+    session_meta_len: u64,
+}
+
+impl IndexFingerprint {
+    /// Fingerprint of the index in `tantivy_dir`, last indexed at `indexed_at`
+    pub fn read(tantivy_dir: &Path, indexed_at: DateTime<Utc>) -> Self {
+        let (meta_modified, meta_len) = file_stamp(&tantivy_dir.join("meta.json"));
+        Self {
+            indexed_at,
+            meta_modified,
+            meta_len,
+            session_meta_modified: None,
+            session_meta_len: 0,
+        }
+    }
+
+    /// Also cover the session metadata at `path`
+    pub fn with_session_metadata(mut self, path: &Path) -> Self {
+        (self.session_meta_modified, self.session_meta_len) = file_stamp(path);
+        self
+    }
+}
+
+/// Modification time and size of a file (`None`, 0 if unreadable)
+fn file_stamp(path: &Path) -> (Option<SystemTime>, u64) {
+    let meta = std::fs::metadata(path).ok();
+    (
+        meta.as_ref().and_then(|m| m.modified().ok()),
+        meta.map_or(0, |m| m.len()),
+    )
+}
+
+/// An open session index with a reader for searching
+pub struct SessionReader {
+    index: TantivyIndex,
+    reader: IndexReader,
+    fingerprint: IndexFingerprint,
+    bm25: Bm25Params,
+    chunk_size: Option<usize>,
+    modified_times: Option<ModifiedTimeSource>,
+    search_defaults: SessionSearchDefaults,
+    repository_path: Option<PathBuf>,
+}
+
+impl SessionReader {
+    /// Create a reader for `index`
+    ///
+    /// The reader is never reloaded; a changed index gets a new
+    /// `SessionReader` instead.
+    pub fn new(index: TantivyIndex, fingerprint: IndexFingerprint) -> Result<Self> {
+        let reader = index
+            .index()
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e| ShebeError::StorageError(format!("Failed to create reader: {e}")))?;
+        Ok(Self {
+            index,
+            reader,
+            fingerprint,
+            bm25: Bm25Params::default(),
+            chunk_size: None,
+            modified_times: None,
+            search_defaults: SessionSearchDefaults::default(),
+            repository_path: None,
+        })
+    }
+
+    /// Score searches with the session's BM25 parameters
+    pub fn with_bm25_params(mut self, bm25: Bm25Params) -> Self {
+        self.bm25 = bm25;
+        self
+    }
+
+    /// Record the chunk size the session was indexed with
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Record where the session's chunk change times came from
+    pub fn with_modified_times(mut self, source: Option<ModifiedTimeSource>) -> Self {
+        self.modified_times = source;
+        self
+    }
+
+    /// Apply the session's search defaults to requests
+    pub fn with_search_defaults(mut self, defaults: SessionSearchDefaults) -> Self {
+        self.search_defaults = defaults;
+        self
+    }
+
+    /// Record the repository the session was indexed from
+    pub fn with_repository_path(mut self, path: PathBuf) -> Self {
+        self.repository_path = Some(path);
+        self
+    }
+
+    /// BM25 parameters of the session
+    pub fn bm25_params(&self) -> Bm25Params {
+        self.bm25
+    }
+
+    /// Characters per chunk the session was indexed with, if known
+    pub fn chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    /// Where chunks' `modified_at` came from (`None` if not recorded)
+    pub fn modified_times(&self) -> Option<ModifiedTimeSource> {
+        self.modified_times
+    }
+
+    /// Search options the session uses when a request leaves them unset
+    pub fn search_defaults(&self) -> SessionSearchDefaults {
+        self.search_defaults
+    }
+
+    /// Repository the session was indexed from, if known
+    pub fn repository_path(&self) -> Option<&Path> {
+        self.repository_path.as_deref()
+    }
+
+    /// The session index (schema, tokenizers)
+    pub fn index(&self) -> &TantivyIndex {
+        &self.index
+    }
+
+    /// Snapshot of the index for one or more queries
+    pub fn searcher(&self) -> Searcher {
+        self.reader.searcher()
+    }
+}
+
+type Shard = RwLock<HashMap<String, Arc<SessionReader>>>;
+
+/// Open readers per session, sharded by session ID
+pub struct ReaderCache {
+    shards: Vec<Shard>,
+}
+
+impl Default for ReaderCache {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| RwLock::default()).collect(),
+        }
+    }
+}
+
+impl ReaderCache {
+    /// Cached reader for `session_id` if it matches `fingerprint`,
+    /// otherwise a reader from `open`
+    ///
+    /// `open` runs without any lock held. When two threads open the
+    /// same session at once, both readers are valid and the last one
+    /// is kept.
+    pub fn get_or_open(
+        &self,
+        session_id: &str,
+        fingerprint: &IndexFingerprint,
+        open: impl FnOnce() -> Result<SessionReader>,
+    ) -> Result<Arc<SessionReader>> {
+        let shard = self.shard(session_id);
+        {
+            let readers = shard.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(reader) = readers.get(session_id) {
+                if reader.fingerprint == *fingerprint {
+                    return Ok(Arc::clone(reader));
+                }
+            }
+        }
+
+        let reader = Arc::new(open()?);
+        shard
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_id.to_string(), Arc::clone(&reader));
+        Ok(reader)
+    }
+
+    /// Drop the cached reader of `session_id`
+    ///
+    /// Searches holding it keep working; file handles are released once
+    /// they finish.
+    pub fn invalidate(&self, session_id: &str) {
+        self.shard(session_id)
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+    }
+
+    /// Number of cached readers
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap_or_else(|e| e.into_inner()).len())
+            .sum()
+    }
+
+    /// Whether no readers are cached
+    #[cfg(test)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn shard(&self, session_id: &str) -> &Shard {
+        let mut hasher = DefaultHasher::new();
+        session_id.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open_reader(dir: &Path, fingerprint: &IndexFingerprint) -> Result<SessionReader> {
+        SessionReader::new(TantivyIndex::open(dir)?, fingerprint.clone())
+    }
+
+    #[test]
+    fn test_reader_reused_until_fingerprint_changes() {
+        let temp = TempDir::new().unwrap();
+        TantivyIndex::create(temp.path()).unwrap();
+        let cache = ReaderCache::default();
+        let indexed_at = Utc::now();
+        let fingerprint = IndexFingerprint::read(temp.path(), indexed_at);
+
+        let first = cache
+            .get_or_open("s", &fingerprint, || open_reader(temp.path(), &fingerprint))
+            .unwrap();
+        let again = cache
+            .get_or_open("s", &fingerprint, || panic!("should be cached"))
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let reindexed =
+            IndexFingerprint::read(temp.path(), indexed_at + chrono::Duration::seconds(1));
+        let fresh = cache
+            .get_or_open("s", &reindexed, || open_reader(temp.path(), &reindexed))
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &fresh));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_invalidate_drops_reader() {
+        let temp = TempDir::new().unwrap();
+        TantivyIndex::create(temp.path()).unwrap();
+        let cache = ReaderCache::default();
+        let fingerprint = IndexFingerprint::read(temp.path(), Utc::now());
+
+        cache
+            .get_or_open("s", &fingerprint, || open_reader(temp.path(), &fingerprint))
+            .unwrap();
+        cache.invalidate("s");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_failed_open_is_not_cached() {
+        let cache = ReaderCache::default();
+        let fingerprint = IndexFingerprint::read(Path::new("/nonexistent"), Utc::now());
+
+        let result = cache.get_or_open("s", &fingerprint, || {
+            Err(ShebeError::StorageError("boom".to_string()))
+        });
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+    }
+}
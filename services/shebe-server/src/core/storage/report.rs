@@ -0,0 +1,227 @@
+//! Disk usage across all sessions.
+//!
+//! [`StorageManager::storage_report`] measures every session directory
+//! and compares it with the `index_size_bytes` recorded in its metadata.
+//! Sessions whose recorded size has drifted from what is on disk are
+//! flagged, so stale sizes (e.g. after chunks were deleted or segments
+//! merged) are easy to spot before deciding what to delete.
+
+use super::StorageManager;
+use crate::core::error::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// Drift (in percent) above which a recorded size is considered stale
+pub const SIZE_DRIFT_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// Differences smaller than this are never reported as drift
+///
+/// Keeps tiny sessions, where a few KB of metadata is a large
+/// percentage, from being flagged.
+pub const SIZE_DRIFT_MIN_BYTES: u64 = 64 * 1024;
+
+/// Difference between an actual and a recorded size, in percent of the
+/// recorded size
+///
+/// A recorded size of 0 counts as 100% drift if anything is on disk.
+pub fn drift_percent(actual: u64, recorded: u64) -> f64 {
+    if recorded == 0 {
+        return if actual == 0 { 0.0 } else { 100.0 };
+    }
+    actual.abs_diff(recorded) as f64 / recorded as f64 * 100.0
+}
+
+/// Whether a recorded size is far enough off to be reported
+pub fn size_drifted(actual: u64, recorded: u64) -> bool {
+    actual.abs_diff(recorded) >= SIZE_DRIFT_MIN_BYTES
+        && drift_percent(actual, recorded) > SIZE_DRIFT_THRESHOLD_PERCENT
+}
+
+/// Disk usage of a single session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUsage {
+    pub session_id: String,
+
+    /// Size of the session directory on disk
+    pub actual_bytes: u64,
+
+    /// `index_size_bytes` from the session metadata (`None` if unreadable)
+    pub metadata_bytes: Option<u64>,
+
+    /// Drift between actual and recorded size, in percent
+    pub drift_percent: Option<f64>,
+
+    /// Whether the recorded size is stale
+    pub drifted: bool,
+}
+
+/// Disk usage of all sessions under a storage root
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageReport {
+    pub storage_root: PathBuf,
+
+    /// Sessions, largest first
+    pub sessions: Vec<SessionUsage>,
+
+    /// Sum of all session directories on disk
+    pub total_session_bytes: u64,
+
+    /// Sum of all recorded session sizes
+    pub total_metadata_bytes: u64,
+
+    /// Space left on the storage root's filesystem (`None` if unknown)
+    pub available_bytes: Option<u64>,
+}
+
+impl StorageReport {
+    /// Number of sessions whose recorded size is stale
+    pub fn drifted_count(&self) -> usize {
+        self.sessions.iter().filter(|s| s.drifted).count()
+    }
+}
+
+impl StorageManager {
+    /// Measure every session on disk and compare with its metadata
+    ///
+    /// Sessions with unreadable metadata are still measured; they are
+    /// listed without a recorded size.
+    pub fn storage_report(&self) -> Result<StorageReport> {
+        let mut sessions = Vec::new();
+
+        for session_id in self.list_session_ids()? {
+            let actual_bytes = self.measure_session_size(&session_id);
+            let metadata_bytes = self.recorded_size(&session_id);
+            let (drift, drifted) = match metadata_bytes {
+                Some(recorded) => (
+                    Some(drift_percent(actual_bytes, recorded)),
+                    size_drifted(actual_bytes, recorded),
+                ),
+                None => (None, false),
+            };
+
+            sessions.push(SessionUsage {
+                session_id,
+                actual_bytes,
+                metadata_bytes,
+                drift_percent: drift,
+                drifted,
+            });
+        }
+
+        sessions.sort_by(|a, b| {
+            b.actual_bytes
+                .cmp(&a.actual_bytes)
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+
+        let total_session_bytes = sessions.iter().map(|s| s.actual_bytes).sum();
+        let total_metadata_bytes = sessions.iter().filter_map(|s| s.metadata_bytes).sum();
+        let available_bytes = fs4::available_space(self.storage_root()).ok();
+
+        Ok(StorageReport {
+            storage_root: self.storage_root().to_path_buf(),
+            sessions,
+            total_session_bytes,
+            total_metadata_bytes,
+            available_bytes,
+        })
+    }
+
+    /// `index_size_bytes` straight from `meta.json`
+    ///
+    /// Read without deserializing the full metadata, so sessions from
+    /// older schema versions are still reported.
+    fn recorded_size(&self, session_id: &str) -> Option<u64> {
+        let content = fs::read_to_string(self.metadata_path(session_id)).ok()?;
+        let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+        value.get("index_size_bytes")?.as_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::storage::SessionConfig;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_drift_percent() {
+        assert_eq!(drift_percent(0, 0), 0.0);
+        assert_eq!(drift_percent(10, 0), 100.0);
+        assert_eq!(drift_percent(150, 100), 50.0);
+        assert_eq!(drift_percent(50, 100), 50.0);
+    }
+
+    #[test]
+    fn test_size_drifted_needs_percent_and_bytes() {
+        // Large percentage, tiny absolute difference
+        assert!(!size_drifted(4 * 1024, 1024));
+        // Large absolute difference, small percentage
+        assert!(!size_drifted(100 * 1024 * 1024, 95 * 1024 * 1024));
+        // Both
+        assert!(size_drifted(1024 * 1024, 4 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_storage_report_empty_root() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+
+        let report = manager.storage_report().unwrap();
+
+        assert!(report.sessions.is_empty());
+        assert_eq!(report.total_session_bytes, 0);
+        assert_eq!(report.drifted_count(), 0);
+    }
+
+    #[test]
+    fn test_storage_report_unreadable_metadata() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "broken",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        fs::write(manager.metadata_path("broken"), "not json").unwrap();
+
+        let report = manager.storage_report().unwrap();
+
+        assert_eq!(report.sessions.len(), 1);
+        assert_eq!(report.sessions[0].metadata_bytes, None);
+        assert!(!report.sessions[0].drifted);
+        assert!(report.sessions[0].actual_bytes > 0);
+    }
+
+    #[test]
+    fn test_storage_report_sorted_largest_first() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        for id in ["small", "large"] {
+            manager
+                .create_session(id, PathBuf::from("/test/repo"), SessionConfig::default())
+                .unwrap();
+        }
+        fs::write(
+            manager.get_session_path("large").join("padding.bin"),
+            vec![0u8; 4096],
+        )
+        .unwrap();
+
+        let report = manager.storage_report().unwrap();
+
+        let ids: Vec<_> = report
+            .sessions
+            .iter()
+            .map(|s| s.session_id.as_str())
+            .collect();
+        assert_eq!(ids, ["large", "small"]);
+        assert_eq!(
+            report.total_session_bytes,
+            report.sessions.iter().map(|s| s.actual_bytes).sum::<u64>()
+        );
+    }
+}
@@ -3,20 +3,100 @@
 //! This module manages session-based indexes, including
 //! creation, deletion and metadata tracking.
 
+use crate::core::archive::{Archive, ArchiveSource};
 use crate::core::error::{Result, ShebeError};
-use crate::core::storage::tantivy::{TantivyIndex, SCHEMA_VERSION};
+use crate::core::indexer::chunker::DEFAULT_MAX_CHUNKS_PER_FILE;
+use crate::core::indexer::dedupe::{detect_renames, MAX_RENAMED_FILES};
+use crate::core::indexer::profile::timed;
+use crate::core::indexer::repo_config::ConfigSources;
+use crate::core::indexer::root::file_path_error;
+use crate::core::indexer::strip::{DEFAULT_STRIP_RUN_CHARS, MIN_STRIP_RUN_CHARS};
+use crate::core::indexer::{
+    BoundaryHint, ContentManifest, IndexLimits, IndexProgress, ModifiedTimes,
+};
+use crate::core::remote::RemoteSource;
+use crate::core::schedule::{Schedule, ScheduledRun};
+use crate::core::search::scoring::{Bm25Params, DEFAULT_BM25_B, DEFAULT_BM25_K1};
+use crate::core::storage::breaker::{BreakerSettings, OpenBreaker};
+use crate::core::storage::files::{FileManifest, FILE_MANIFEST_NAME};
+use crate::core::storage::namespace::{parse_session_id, DEFAULT_NAMESPACE};
+use crate::core::storage::provenance::Provenance;
+use crate::core::storage::readers::{IndexFingerprint, ReaderCache, SessionReader};
+use crate::core::storage::staging::{is_staging_name, StagingDir};
+use crate::core::storage::tantivy::{
+    current_schema_hash_for, is_corruption, IndexSettings, TantivyIndex, SCHEMA_VERSION,
+};
+use crate::core::tree::{build_tree, TreeNode, TreeOptions};
+use crate::core::types::{
+    IndexStage, IndexStats, ModifiedTimeSource, SessionSearchDefaults, SkipCounts,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 
 /// Session configuration
+///
+/// Marked non-exhaustive so new options do not break callers; build
+/// one outside this crate with [`SessionConfig::builder`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SessionConfig {
     pub chunk_size: usize,
     pub overlap: usize,
     pub include_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
+    /// Language presets expanded into `include_patterns`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Index only the first of several files with identical content
+    #[serde(default)]
+    pub dedupe: bool,
+    /// Boundary that chunk ends are aligned to
+    #[serde(default)]
+    pub boundary_hint: BoundaryHint,
+    /// Store chunk text in the index; when false, search reads it from
+    /// the files at query time (smaller index, more query-time IO)
+    #[serde(default = "default_store_chunk_text")]
+    pub store_chunk_text: bool,
+    /// Replace long base64/minified blobs with placeholders before
+    /// chunking (offsets still point into the original files)
+    #[serde(default)]
+    pub strip_high_entropy: bool,
+    /// Non-whitespace runs longer than this many characters are
+    /// stripped when `strip_high_entropy` is set
+    #[serde(default = "default_strip_run_chars")]
+    pub strip_run_chars: usize,
+    /// Merge a file's short final chunk into the one before it
+    #[serde(default)]
+    pub merge_tail_fragments: bool,
+    /// Chunk data files (CSV, TSV, JSON Lines) from whole records
+    #[serde(default = "default_record_chunking")]
+    pub record_chunking: bool,
+    /// Chunks kept per file; the rest of a longer file is not indexed
+    /// and its search results are marked as partially indexed
+    #[serde(default = "default_max_chunks_per_file")]
+    pub max_chunks_per_file: usize,
+    /// Fail instead of warning when the include patterns match none of
+    /// the repository's files
+    #[serde(default)]
+    pub strict_patterns: bool,
+    /// Record each file's last commit time from git (filesystem mtime
+    /// when off or when the repository is not a git checkout)
+    #[serde(default = "default_git_metadata")]
+    pub git_metadata: bool,
+    /// Where each value came from (`None` for sessions indexed before
+    /// repository-local settings were tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<ConfigSources>,
+    /// BM25 term frequency saturation used when searching (0-3)
+    #[serde(default = "default_bm25_k1")]
+    pub bm25_k1: f32,
+    /// BM25 length normalization used when searching (0-1)
+    #[serde(default = "default_bm25_b")]
+    pub bm25_b: f32,
 }
 
 impl Default for SessionConfig {
@@ -32,10 +112,240 @@ impl Default for SessionConfig {
                 "**/dist/**".to_string(),
                 "**/build/**".to_string(),
             ],
+            languages: Vec::new(),
+            dedupe: false,
+            boundary_hint: BoundaryHint::None,
+            store_chunk_text: true,
+            strip_high_entropy: false,
+            strip_run_chars: DEFAULT_STRIP_RUN_CHARS,
+            merge_tail_fragments: false,
+            record_chunking: true,
+            max_chunks_per_file: DEFAULT_MAX_CHUNKS_PER_FILE,
+            strict_patterns: false,
+            git_metadata: true,
+            sources: None,
+            bm25_k1: DEFAULT_BM25_K1,
+            bm25_b: DEFAULT_BM25_B,
         }
     }
 }
 
+fn default_store_chunk_text() -> bool {
+    true
+}
+
+fn default_git_metadata() -> bool {
+    true
+}
+
+fn default_record_chunking() -> bool {
+    true
+}
+
+fn default_strip_run_chars() -> usize {
+    DEFAULT_STRIP_RUN_CHARS
+}
+
+fn default_max_chunks_per_file() -> usize {
+    DEFAULT_MAX_CHUNKS_PER_FILE
+}
+
+fn default_bm25_k1() -> f32 {
+    DEFAULT_BM25_K1
+}
+
+fn default_bm25_b() -> f32 {
+    DEFAULT_BM25_B
+}
+
+impl SessionConfig {
+    /// Start building a configuration from the defaults
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shebe::SessionConfig;
+    ///
+    /// let config = SessionConfig::builder()
+    ///     .chunk_size(800)
+    ///     .include_patterns(["**/*.py"])
+    ///     .dedupe(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(config.overlap, 64);
+    /// ```
+    pub fn builder() -> SessionConfigBuilder {
+        SessionConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    /// Check that the chunking and BM25 settings are usable
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if `overlap >= chunk_size`, `strip_run_chars` is
+    /// below 32, `max_chunks_per_file` is 0, `bm25_k1` is outside 0-3
+    /// or `bm25_b` outside 0-1.
+    pub fn validate(&self) -> Result<()> {
+        if self.chunk_size == 0 || self.overlap >= self.chunk_size {
+            return Err(ShebeError::ConfigError(format!(
+                "Overlap ({}) must be less than chunk size ({})",
+                self.overlap, self.chunk_size
+            )));
+        }
+        if self.strip_run_chars < MIN_STRIP_RUN_CHARS {
+            return Err(ShebeError::ConfigError(format!(
+                "strip_run_chars must be at least {MIN_STRIP_RUN_CHARS}, got {}",
+                self.strip_run_chars
+            )));
+        }
+        if self.max_chunks_per_file == 0 {
+            return Err(ShebeError::ConfigError(
+                "max_chunks_per_file must be at least 1".to_string(),
+            ));
+        }
+        self.bm25_params().validate()
+    }
+
+    /// BM25 parameters searches of the session score with
+    pub fn bm25_params(&self) -> Bm25Params {
+        Bm25Params {
+            k1: self.bm25_k1,
+            b: self.bm25_b,
+        }
+    }
+}
+
+/// Builder for [`SessionConfig`]
+#[derive(Debug, Clone)]
+pub struct SessionConfigBuilder {
+    config: SessionConfig,
+}
+
+impl SessionConfigBuilder {
+    /// Characters per chunk
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.config.chunk_size = chunk_size;
+        self
+    }
+
+    /// Characters shared by consecutive chunks (less than `chunk_size`)
+    pub fn overlap(mut self, overlap: usize) -> Self {
+        self.config.overlap = overlap;
+        self
+    }
+
+    /// Glob patterns for files to include (replaces the defaults)
+    pub fn include_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.include_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Glob patterns for files to exclude (replaces the defaults)
+    pub fn exclude_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.exclude_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Index only the first of several files with identical content
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.config.dedupe = dedupe;
+        self
+    }
+
+    /// Align chunk ends to blank lines or line breaks
+    pub fn boundary_hint(mut self, boundary_hint: BoundaryHint) -> Self {
+        self.config.boundary_hint = boundary_hint;
+        self
+    }
+
+    /// Store chunk text in the index (default) or read it from the
+    /// files at query time
+    pub fn store_chunk_text(mut self, store_chunk_text: bool) -> Self {
+        self.config.store_chunk_text = store_chunk_text;
+        self
+    }
+
+    /// Replace long base64/minified blobs with placeholders before
+    /// chunking
+    pub fn strip_high_entropy(mut self, strip_high_entropy: bool) -> Self {
+        self.config.strip_high_entropy = strip_high_entropy;
+        self
+    }
+
+    /// Length above which non-whitespace runs are stripped (default 200)
+    pub fn strip_run_chars(mut self, strip_run_chars: usize) -> Self {
+        self.config.strip_run_chars = strip_run_chars;
+        self
+    }
+
+    /// Merge a file's short final chunk into the one before it
+    pub fn merge_tail_fragments(mut self, merge_tail_fragments: bool) -> Self {
+        self.config.merge_tail_fragments = merge_tail_fragments;
+        self
+    }
+
+    /// Chunk data files from whole records (default true)
+    pub fn record_chunking(mut self, record_chunking: bool) -> Self {
+        self.config.record_chunking = record_chunking;
+        self
+    }
+
+    /// Chunks kept per file before the rest is left unindexed
+    /// (default 2000)
+    pub fn max_chunks_per_file(mut self, max_chunks_per_file: usize) -> Self {
+        self.config.max_chunks_per_file = max_chunks_per_file;
+        self
+    }
+
+    /// Fail instead of warning when the include patterns match none of
+    /// the repository's files
+    pub fn strict_patterns(mut self, strict_patterns: bool) -> Self {
+        self.config.strict_patterns = strict_patterns;
+        self
+    }
+
+    /// Take files' last change from git history (default) or only
+    /// from the filesystem
+    pub fn git_metadata(mut self, git_metadata: bool) -> Self {
+        self.config.git_metadata = git_metadata;
+        self
+    }
+
+    /// BM25 term frequency saturation, 0-3 (default 1.2)
+    pub fn bm25_k1(mut self, k1: f32) -> Self {
+        self.config.bm25_k1 = k1;
+        self
+    }
+
+    /// BM25 length normalization, 0-1 (default 0.75)
+    pub fn bm25_b(mut self, b: f32) -> Self {
+        self.config.bm25_b = b;
+        self
+    }
+
+    /// Build the configuration
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError` if `overlap >= chunk_size`, `strip_run_chars` is
+    /// below 32, `max_chunks_per_file` is 0 or a BM25 parameter is out
+    /// of range.
+    pub fn build(self) -> Result<SessionConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
 /// Session metadata (Schema v3)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMetadata {
@@ -48,35 +358,408 @@ pub struct SessionMetadata {
     pub index_size_bytes: u64,
     pub config: SessionConfig,
     pub schema_version: u32,
+    /// Files skipped as duplicates (see `manifest.json` for paths)
+    #[serde(default)]
+    pub files_deduplicated: usize,
+    /// Shebe version that created the session ("unknown" for older sessions)
+    #[serde(default = "unknown")]
+    pub created_by_version: String,
+    /// Fingerprint of the Tantivy schema ("unknown" for older sessions)
+    #[serde(default = "unknown")]
+    pub tantivy_schema_hash: String,
+    /// Tokenizer and field settings the index was built with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_settings: Option<IndexSettings>,
+    /// Remote repository the session was cloned from (`None` for local paths)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    /// Archive the session was indexed from, with its content hash
+    /// (`None` for directories)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveSource>,
+    /// Files left out of the index by reason (zero for older sessions)
+    #[serde(default)]
+    pub skipped: SkipCounts,
+    /// Bytes of chunk text indexed (zero for older sessions)
+    #[serde(default)]
+    pub chunk_text_bytes: u64,
+    /// Background re-index schedule set with `set_reindex_schedule`
+    /// (`None` falls back to `[schedule.sessions]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reindex_schedule: Option<Schedule>,
+    /// Most recent scheduled re-index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_scheduled_run: Option<ScheduledRun>,
+    /// Search options used when a request leaves them unset, set with
+    /// `set_session_defaults`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_defaults: Option<SessionSearchDefaults>,
+    /// Where chunks' `modified_at` came from (`None` for sessions
+    /// indexed before change times were recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_times: Option<ModifiedTimeSource>,
+    /// Version, config, git commit and timing of the last full index
+    /// (`None` for sessions indexed before provenance was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// Placeholder for diagnostics missing from older metadata files
+pub const UNKNOWN: &str = "unknown";
+
+/// Chunks added to a new index between cancellation checks
+const WRITE_BATCH_CHUNKS: usize = 10_000;
+
+fn unknown() -> String {
+    UNKNOWN.to_string()
+}
+
+/// Version of the running binary, recorded in new sessions
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Leading numeric component of a version string ("0.5.1" -> 0)
+fn major_version(version: &str) -> Option<u64> {
+    version.split('.').next()?.parse().ok()
+}
+
+impl SessionMetadata {
+    /// Fraction of scanned files skipped as duplicates (0.0-1.0)
+    pub fn dedupe_ratio(&self) -> f64 {
+        let scanned = self.files_indexed + self.files_deduplicated;
+        if scanned == 0 {
+            return 0.0;
+        }
+        self.files_deduplicated as f64 / scanned as f64
+    }
+
+    /// Whether the index was built with the current Tantivy schema
+    ///
+    /// Returns `None` when the schema hash was not recorded.
+    pub fn schema_hash_matches(&self) -> Option<bool> {
+        if self.tantivy_schema_hash == UNKNOWN {
+            return None;
+        }
+        Some(self.tantivy_schema_hash == current_schema_hash_for(self.config.store_chunk_text))
+    }
+
+    /// Whether the session was created by a different major version
+    ///
+    /// Returns false when the creating version is unknown.
+    pub fn major_version_differs(&self) -> bool {
+        match (
+            major_version(&self.created_by_version),
+            major_version(CURRENT_VERSION),
+        ) {
+            (Some(created), Some(running)) => created != running,
+            _ => false,
+        }
+    }
+}
+
+/// A session directory as found by [`StorageManager::list_session_entries`]
+#[derive(Debug, Clone)]
+pub enum SessionEntry {
+    /// A session with readable metadata
+    Valid(Box<SessionMetadata>),
+    /// `meta.json` exists but cannot be read or parsed
+    Corrupt { id: String, error: String },
+    /// A directory without `meta.json`, such as one left by a crash
+    Orphaned { id: String, size_bytes: u64 },
+}
+
+impl SessionEntry {
+    /// Session ID (the directory name)
+    pub fn id(&self) -> &str {
+        match self {
+            SessionEntry::Valid(metadata) => &metadata.id,
+            SessionEntry::Corrupt { id, .. } | SessionEntry::Orphaned { id, .. } => id,
+        }
+    }
+
+    /// Whether the session can be opened and searched
+    pub fn is_valid(&self) -> bool {
+        matches!(self, SessionEntry::Valid(_))
+    }
 }
 
 /// Session-based storage manager
+///
+/// Thread-safe: searches share one cached reader per session (see
+/// [`StorageManager::session_reader`]).
 pub struct StorageManager {
-    /// Root directory for all sessions
+    /// Root directory for all sessions of the `default` namespace
     storage_root: PathBuf,
+    /// Storage roots of the other namespaces, by name
+    namespaces: BTreeMap<String, PathBuf>,
+    /// Namespace of session IDs given without one
+    default_namespace: String,
+    /// Open readers for searching, per session
+    readers: ReaderCache,
+    /// Most files and bytes one session may index
+    index_limits: RwLock<IndexLimits>,
+    /// Refuse every change to stored sessions
+    read_only: bool,
+    /// Sessions already warned about listing files without a manifest
+    pub(super) manifest_warnings: Mutex<HashSet<String>>,
+    /// Sessions found with a corrupted index since the last [`StorageManager::take_corrupted`]
+    corrupted: Mutex<HashSet<String>>,
+    /// Sessions whose opens fail fast after repeated failures
+    open_breaker: Arc<OpenBreaker>,
+    /// Serializes changes to the collection registry
+    pub(super) collections_lock: Mutex<()>,
+    /// Fail new sessions after adding their chunks, to test cleanup
+    #[cfg(test)]
+    fail_before_commit: bool,
 }
 
 impl StorageManager {
     /// Create a new storage manager
     pub fn new(storage_root: PathBuf) -> Self {
-        Self { storage_root }
+        Self {
+            storage_root,
+            namespaces: BTreeMap::new(),
+            default_namespace: DEFAULT_NAMESPACE.to_string(),
+            readers: ReaderCache::default(),
+            index_limits: RwLock::new(IndexLimits::default()),
+            read_only: false,
+            manifest_warnings: Mutex::default(),
+            corrupted: Mutex::default(),
+            open_breaker: Arc::default(),
+            collections_lock: Mutex::default(),
+            #[cfg(test)]
+            fail_before_commit: false,
+        }
+    }
+
+    /// Serve the sessions of further storage roots as namespaces
+    ///
+    /// `team-a/backend` is then session `backend` under the root of
+    /// namespace `team-a`; the `default` namespace is the storage root.
+    /// Bare session IDs resolve to `default_namespace`.
+    pub fn with_namespaces(
+        mut self,
+        namespaces: BTreeMap<String, PathBuf>,
+        default_namespace: &str,
+    ) -> Self {
+        self.namespaces = namespaces;
+        self.default_namespace = default_namespace.to_string();
+        self
+    }
+
+    /// Names of all namespaces, `default` included, sorted
+    pub fn namespaces(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.namespaces.keys().cloned().collect();
+        names.push(DEFAULT_NAMESPACE.to_string());
+        names.sort();
+        names
+    }
+
+    /// Namespace of session IDs given without one
+    pub fn default_namespace(&self) -> &str {
+        &self.default_namespace
+    }
+
+    /// Storage root of `namespace`, if it is configured
+    fn namespace_root(&self, namespace: &str) -> Option<&Path> {
+        if namespace == DEFAULT_NAMESPACE {
+            Some(&self.storage_root)
+        } else {
+            self.namespaces.get(namespace).map(PathBuf::as_path)
+        }
+    }
+
+    /// Directory holding the sessions of a namespace, and the session's
+    /// name in it
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` for a malformed ID or an unknown namespace.
+    fn locate<'a>(&self, session_id: &'a str) -> Result<(PathBuf, &'a str)> {
+        let id = parse_session_id(session_id)?;
+        let namespace = id.namespace.unwrap_or(&self.default_namespace);
+        let root = self.namespace_root(namespace).ok_or_else(|| {
+            ShebeError::InvalidSession(format!(
+                "Unknown namespace '{namespace}' in session '{session_id}' (available: {})",
+                self.namespaces().join(", ")
+            ))
+        })?;
+        Ok((root.join("sessions"), id.name))
+    }
+
+    /// The one spelling of a session ID: bare for the default
+    /// namespace, `namespace/name` for the others
+    ///
+    /// IDs that cannot be resolved are returned unchanged.
+    pub fn canonical_session_id(&self, session_id: &str) -> String {
+        match parse_session_id(session_id) {
+            Ok(id) => qualified_id(
+                id.namespace.unwrap_or(&self.default_namespace),
+                id.name,
+                &self.default_namespace,
+            ),
+            Err(_) => session_id.to_string(),
+        }
+    }
+
+    /// Abort indexing runs that exceed `limits` (default: the
+    /// `[limits]` configuration defaults)
+    pub fn with_index_limits(self, limits: IndexLimits) -> Self {
+        self.set_index_limits(limits);
+        self
+    }
+
+    /// Replace the index limits for runs started from now on
+    pub fn set_index_limits(&self, limits: IndexLimits) {
+        *self.index_limits.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    /// Trip sessions that fail to open repeatedly (default: the
+    /// `[limits]` configuration defaults)
+    pub fn with_open_breaker(self, settings: BreakerSettings) -> Self {
+        self.open_breaker.set_settings(settings);
+        self
+    }
+
+    /// Sessions whose opens fail fast after repeated failures
+    pub fn open_breaker(&self) -> &Arc<OpenBreaker> {
+        &self.open_breaker
+    }
+
+    /// Refuse to create, change or delete sessions (`server.read_only`)
+    ///
+    /// Every mutating method then fails with [`ShebeError::ReadOnly`]
+    /// before touching the disk, whichever adapter called it.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Whether sessions may not be changed
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`ShebeError::ReadOnly`] in read-only mode
+    pub(super) fn ensure_writable(&self, action: &str, session_id: &str) -> Result<()> {
+        if self.read_only {
+            return Err(ShebeError::ReadOnly(format!(
+                "cannot {action} session '{session_id}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Canonical absolute form of a repository path to index
+    ///
+    /// Sessions store this path, so a later re-index resolves to the
+    /// same directory whatever the working directory is then.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if the path does not exist, is not a directory, or
+    /// lies inside the storage root's session directory (which would
+    /// index the index). Clones of remote repositories may live under
+    /// the storage root, so the rest of it is allowed.
+    pub fn resolve_repository_path(&self, path: &Path) -> Result<PathBuf> {
+        let canonical = path.canonicalize().map_err(|e| {
+            ShebeError::InvalidPath(format!(
+                "Cannot resolve repository path '{}': {e}. \
+                 Make sure it exists and is accessible.",
+                path.display()
+            ))
+        })?;
+        if canonical.is_file() {
+            return Err(file_path_error(&canonical));
+        }
+        if !canonical.is_dir() {
+            return Err(ShebeError::InvalidPath(format!(
+                "Repository path '{}' is not a directory",
+                canonical.display()
+            )));
+        }
+        for root in std::iter::once(&self.storage_root).chain(self.namespaces.values()) {
+            let sessions_dir = root
+                .canonicalize()
+                .unwrap_or_else(|_| root.clone())
+                .join("sessions");
+            if canonical.starts_with(&sessions_dir) {
+                return Err(ShebeError::InvalidPath(format!(
+                    "Repository path '{}' is inside the session storage '{}'",
+                    canonical.display(),
+                    sessions_dir.display()
+                )));
+            }
+        }
+        Ok(canonical)
+    }
+
+    /// Check that an archive can be indexed as requested
+    ///
+    /// Returns the archive's absolute path, which the session records
+    /// as its repository.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` for a file list (archives are always indexed
+    /// whole); `ConfigError` with `store_chunk_text` off, since chunk
+    /// text could only be read back by extracting entries per hit.
+    fn check_archive(
+        &self,
+        archive: &Archive,
+        files: Option<&[PathBuf]>,
+        session_config: &SessionConfig,
+    ) -> Result<PathBuf> {
+        if files.is_some() {
+            return Err(ShebeError::InvalidPath(format!(
+                "{} is an archive; a list of files can only be indexed from a directory",
+                archive.path().display()
+            )));
+        }
+        if !session_config.store_chunk_text {
+            return Err(ShebeError::ConfigError(format!(
+                "{} is an archive; archives are indexed with store_chunk_text on",
+                archive.path().display()
+            )));
+        }
+        Ok(archive.path().to_path_buf())
+    }
+
+    /// Root directory for all sessions of the `default` namespace
+    pub fn storage_root(&self) -> &Path {
+        &self.storage_root
+    }
+
+    /// Directory holding all session directories of the `default` namespace
+    fn sessions_dir(&self) -> PathBuf {
+        self.storage_root.join("sessions")
     }
 
     /// Get session directory path
+    ///
+    /// An ID naming no configured namespace maps to a directory that is
+    /// never created, so such sessions are simply not found.
     fn session_dir(&self, session_id: &str) -> PathBuf {
-        self.storage_root.join("sessions").join(session_id)
+        match self.locate(session_id) {
+            Ok((sessions_dir, name)) => sessions_dir.join(name),
+            Err(_) => self.sessions_dir().join(".unresolved").join(session_id),
+        }
     }
 
     /// Get Tantivy index directory path
-    fn tantivy_dir(&self, session_id: &str) -> PathBuf {
+    pub(super) fn tantivy_dir(&self, session_id: &str) -> PathBuf {
         self.session_dir(session_id).join("tantivy")
     }
 
     /// Get metadata file path
-    fn metadata_path(&self, session_id: &str) -> PathBuf {
+    pub(super) fn metadata_path(&self, session_id: &str) -> PathBuf {
         self.session_dir(session_id).join("meta.json")
     }
 
+    /// Get content manifest file path
+    fn manifest_path(&self, session_id: &str) -> PathBuf {
+        self.session_dir(session_id).join("manifest.json")
+    }
+
     /// Create a new session
     pub fn create_session(
         &self,
@@ -84,61 +767,145 @@ impl StorageManager {
         repository_path: PathBuf,
         config: SessionConfig,
     ) -> Result<TantivyIndex> {
+        self.ensure_writable("create", session_id)?;
+        let session_id = &self.canonical_session_id(session_id);
+        self.locate(session_id)?;
         let session_dir = self.session_dir(session_id);
 
         // Check if session already exists
         if session_dir.exists() {
             return Err(ShebeError::SessionAlreadyExists(session_id.to_string()));
         }
+        self.readers.invalidate(session_id);
 
         // Create session directory
         fs::create_dir_all(&session_dir)?;
 
         // Create Tantivy index
         let tantivy_dir = self.tantivy_dir(session_id);
-        let index = TantivyIndex::create(&tantivy_dir)?;
+        let index = TantivyIndex::create_with(&tantivy_dir, config.store_chunk_text)?;
 
         // Write initial metadata
-        let now = Utc::now();
-        let metadata = SessionMetadata {
-            id: session_id.to_string(),
-            repository_path,
-            created_at: now,
-            last_indexed_at: now,
-            files_indexed: 0,
-            chunks_created: 0,
-            index_size_bytes: 0,
-            config,
-            schema_version: SCHEMA_VERSION,
-        };
+        let metadata = new_metadata(session_id, repository_path, config);
         self.update_session_metadata(session_id, &metadata)?;
 
         Ok(index)
     }
 
     /// Open an existing session
+    ///
+    /// Fails with [`ShebeError::SessionUnavailable`] while the session
+    /// is tripped after repeated failures (see [`super::breaker`]).
     pub fn open_session(&self, session_id: &str) -> Result<TantivyIndex> {
-        let tantivy_dir = self.tantivy_dir(session_id);
+        self.open_breaker.call(session_id, || {
+            let tantivy_dir = self.tantivy_dir(session_id);
 
-        if !tantivy_dir.exists() {
-            return Err(ShebeError::SessionNotFound(session_id.to_string()));
-        }
+            if !tantivy_dir.exists() {
+                return Err(ShebeError::SessionNotFound(session_id.to_string()));
+            }
 
-        // Check schema version compatibility
-        let metadata = self.get_session_metadata(session_id)?;
-        if metadata.schema_version < SCHEMA_VERSION {
-            return Err(ShebeError::InvalidSession(format!(
-                "Session '{}' uses schema v{} but current version is v{}.\n\
-                 Repository path: {}\n\
-                 Re-index to upgrade: use upgrade_session tool or index_repository with force=true.",
-                session_id,
-                metadata.schema_version,
-                SCHEMA_VERSION,
-                metadata.repository_path.display()
-            )));
+            let metadata = self.get_session_metadata(session_id)?;
+            check_schema_version(session_id, &metadata)?;
+            warn_on_major_version(session_id, &metadata);
+            self.open_index(session_id, &metadata)
+        })
+    }
+
+    /// Open a session's index, checking that its segments can be read
+    ///
+    /// Damaged files fail with [`ShebeError::IndexCorrupted`] and the
+    /// session is remembered for [`Self::take_corrupted`].
+    fn open_index(&self, session_id: &str, metadata: &SessionMetadata) -> Result<TantivyIndex> {
+        let error = match TantivyIndex::open_verified(&self.tantivy_dir(session_id)) {
+            Ok(index) => return Ok(index),
+            Err(e) if is_corruption(&e) => e,
+            Err(e) => {
+                return Err(ShebeError::StorageError(format!(
+                    "Failed to open index: {e}"
+                )))
+            }
+        };
+
+        tracing::error!(
+            "Index of session '{}' is corrupted: {}. Re-index or delete the session",
+            session_id,
+            error
+        );
+        self.corrupted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_id.to_string());
+        // Remote sessions are re-cloned, so only local paths must still exist
+        let repository = (metadata.remote.is_some() || metadata.repository_path.exists())
+            .then(|| metadata.repository_path.clone());
+        Err(ShebeError::IndexCorrupted {
+            session: session_id.to_string(),
+            cause: error.to_string(),
+            repository,
+        })
+    }
+
+    /// Why a session's index cannot be opened, if it is corrupted
+    ///
+    /// Uses the cached reader when there is one, so listing sessions
+    /// only opens each index once. Other failures (missing session,
+    /// outdated schema) return `None`.
+    pub fn index_corruption(&self, session_id: &str) -> Option<String> {
+        match self.session_reader(session_id) {
+            Err(ShebeError::IndexCorrupted { cause, .. }) => Some(cause),
+            _ => None,
         }
+    }
+
+    /// Sessions found with a corrupted index since the last call
+    pub fn take_corrupted(&self) -> Vec<String> {
+        let mut corrupted = self.corrupted.lock().unwrap_or_else(|e| e.into_inner());
+        let mut sessions: Vec<String> = corrupted.drain().collect();
+        sessions.sort();
+        sessions
+    }
 
-        TantivyIndex::open(&tantivy_dir)
+    /// Shared reader for searching a session
+    ///
+    /// Concurrent callers get the same reader until the index or the
+    /// session metadata changes on disk (re-index, incremental update,
+    /// compaction, new search defaults); see
+    /// [`super::readers`]. Performs the same checks as
+    /// [`StorageManager::open_session`], including failing fast while
+    /// the session is tripped.
+    pub fn session_reader(&self, session_id: &str) -> Result<Arc<SessionReader>> {
+        self.open_breaker.call(session_id, || {
+            let tantivy_dir = self.tantivy_dir(session_id);
+
+            if !tantivy_dir.exists() {
+                return Err(ShebeError::SessionNotFound(session_id.to_string()));
+            }
+
+            let metadata = self.get_session_metadata(session_id)?;
+            check_schema_version(session_id, &metadata)?;
+            let fingerprint = IndexFingerprint::read(&tantivy_dir, metadata.last_indexed_at)
+                .with_session_metadata(&self.metadata_path(session_id));
+            self.readers.get_or_open(session_id, &fingerprint, || {
+                warn_on_major_version(session_id, &metadata);
+                Ok(SessionReader::new(
+                    self.open_index(session_id, &metadata)?,
+                    fingerprint.clone(),
+                )?
+                .with_bm25_params(metadata.config.bm25_params())
+                .with_search_defaults(metadata.search_defaults.unwrap_or_default())
+                .with_chunk_size(metadata.config.chunk_size)
+                .with_modified_times(metadata.modified_times)
+                .with_repository_path(metadata.repository_path.clone()))
+            })
+        })
+    }
+
+    /// Drop the cached reader of a session
+    ///
+    /// Only needed after changing a session's files outside
+    /// `StorageManager`; changes made through it are detected.
+    pub fn invalidate_reader(&self, session_id: &str) {
+        self.readers.invalidate(session_id);
     }
 
     /// Check if a session exists
@@ -146,15 +913,28 @@ impl StorageManager {
         self.session_dir(session_id).exists()
     }
 
-    /// Delete a session
+    /// Delete a session, dropping it from the collections it belonged to
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.ensure_writable("delete", session_id)?;
         let session_dir = self.session_dir(session_id);
 
         if !session_dir.exists() {
             return Err(ShebeError::SessionNotFound(session_id.to_string()));
         }
 
+        // Release the cached reader's file handles first
+        self.readers.invalidate(session_id);
         fs::remove_dir_all(session_dir)?;
+        match self.remove_from_collections(session_id) {
+            Ok(collections) if !collections.is_empty() => tracing::warn!(
+                "Deleted session '{session_id}' removed from collection(s): {}",
+                collections.join(", ")
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                "Could not remove deleted session '{session_id}' from collections: {e}"
+            ),
+        }
         Ok(())
     }
 
@@ -178,36 +958,239 @@ impl StorageManager {
         session_id: &str,
         metadata: &SessionMetadata,
     ) -> Result<()> {
-        let meta_path = self.metadata_path(session_id);
+        self.ensure_writable("update", session_id)?;
+        write_json(&self.metadata_path(session_id), metadata)
+    }
 
-        let json = serde_json::to_string_pretty(metadata)?;
-        fs::write(meta_path, json)?;
+    /// Record the remote repository a session was indexed from
+    ///
+    /// Called right after indexing, so `index_size_bytes` is refreshed
+    /// along with it.
+    pub fn set_remote_source(&self, session_id: &str, remote: Option<RemoteSource>) -> Result<()> {
+        let mut metadata = self.get_session_metadata(session_id)?;
+        metadata.remote = remote;
+        self.update_session_metadata(session_id, &metadata)?;
+        self.refresh_index_size(session_id).map(|_| ())
+    }
 
+    /// Set or clear (`None`) a session's re-index schedule
+    pub fn set_reindex_schedule(&self, session_id: &str, schedule: Option<Schedule>) -> Result<()> {
+        let mut metadata = self.get_session_metadata(session_id)?;
+        metadata.reindex_schedule = schedule;
+        self.update_session_metadata(session_id, &metadata)
+    }
+
+    /// Set or clear (`None`) a session's search defaults
+    ///
+    /// Only the metadata is written; the cached reader is dropped so
+    /// the next search uses the new defaults.
+    pub fn set_search_defaults(
+        &self,
+        session_id: &str,
+        defaults: Option<SessionSearchDefaults>,
+    ) -> Result<()> {
+        let mut metadata = self.get_session_metadata(session_id)?;
+        metadata.search_defaults = defaults;
+        self.update_session_metadata(session_id, &metadata)?;
+        self.readers.invalidate(session_id);
         Ok(())
     }
 
-    /// List all sessions
+    /// Record the latest scheduled re-index of a session
+    pub fn record_scheduled_run(&self, session_id: &str, run: ScheduledRun) -> Result<()> {
+        let mut metadata = self.get_session_metadata(session_id)?;
+        metadata.last_scheduled_run = Some(run);
+        self.update_session_metadata(session_id, &metadata)
+    }
+
+    /// Size of a session's directory on disk (index, metadata, manifest)
+    ///
+    /// This is what `index_size_bytes` records. Returns 0 for a missing
+    /// session.
+    pub fn measure_session_size(&self, session_id: &str) -> u64 {
+        calculate_directory_size(&self.get_session_path(session_id))
+    }
+
+    /// Re-measure a session on disk and store the size as `index_size_bytes`
+    ///
+    /// Returns the measured size. Metadata is only rewritten when the
+    /// recorded size is off. The size includes `meta.json` itself, so
+    /// it is re-measured until writing it no longer changes it.
+    pub fn refresh_index_size(&self, session_id: &str) -> Result<u64> {
+        let mut metadata = self.get_session_metadata(session_id)?;
+        let mut size = self.measure_session_size(session_id);
+        // A rewrite only changes the number's digits, so this settles
+        // after one or two rounds
+        for _ in 0..3 {
+            if metadata.index_size_bytes == size {
+                break;
+            }
+            metadata.index_size_bytes = size;
+            self.update_session_metadata(session_id, &metadata)?;
+            size = self.measure_session_size(session_id);
+        }
+        Ok(size)
+    }
+
+    /// Get the content manifest of a session
+    ///
+    /// Returns `None` for sessions indexed before manifests existed.
+    pub fn get_content_manifest(&self, session_id: &str) -> Result<Option<ContentManifest>> {
+        let manifest_path = self.manifest_path(session_id);
+
+        if !manifest_path.exists() {
+            if !self.session_exists(session_id) {
+                return Err(ShebeError::SessionNotFound(session_id.to_string()));
+            }
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&manifest_path)?;
+        let manifest: ContentManifest = serde_json::from_str(&contents)?;
+
+        Ok(Some(manifest))
+    }
+
+    /// Write the content manifest of a session
+    pub fn update_content_manifest(
+        &self,
+        session_id: &str,
+        manifest: &ContentManifest,
+    ) -> Result<()> {
+        self.ensure_writable("update", session_id)?;
+        write_json(&self.manifest_path(session_id), manifest)
+    }
+
+    /// List all sessions with readable metadata
+    ///
+    /// Corrupt and orphaned session directories are left out; see
+    /// [`list_session_entries`](Self::list_session_entries).
     pub fn list_sessions(&self) -> Result<Vec<SessionMetadata>> {
-        let sessions_dir = self.storage_root.join("sessions");
+        Ok(self
+            .list_session_entries()?
+            .into_iter()
+            .filter_map(|entry| match entry {
+                SessionEntry::Valid(metadata) => Some(*metadata),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// List every session directory, including ones whose metadata is
+    /// missing or unreadable, in all namespaces
+    ///
+    /// Broken directories still take disk space and block their session
+    /// ID, so they are listed for [`prune_broken_sessions`](Self::prune_broken_sessions).
+    pub fn list_session_entries(&self) -> Result<Vec<SessionEntry>> {
+        Ok(self.entries_for(self.list_session_ids()?))
+    }
+
+    /// List every session directory of one namespace, as
+    /// [`list_session_entries`](Self::list_session_entries) does
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if `namespace` is not configured.
+    pub fn list_session_entries_in(&self, namespace: &str) -> Result<Vec<SessionEntry>> {
+        Ok(self.entries_for(self.list_session_ids_in(namespace)?))
+    }
+
+    fn entries_for(&self, ids: Vec<String>) -> Vec<SessionEntry> {
+        ids.into_iter()
+            .map(|id| {
+                if !self.metadata_path(&id).exists() {
+                    let size_bytes = self.measure_session_size(&id);
+                    return SessionEntry::Orphaned { id, size_bytes };
+                }
+                match self.get_session_metadata(&id) {
+                    Ok(metadata) => SessionEntry::Valid(Box::new(metadata)),
+                    Err(e) => SessionEntry::Corrupt {
+                        id,
+                        error: e.to_string(),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Delete every corrupt and orphaned session directory
+    ///
+    /// Returns the deleted session IDs with the bytes each freed.
+    pub fn prune_broken_sessions(&self) -> Result<Vec<(String, u64)>> {
+        self.prune_entries(self.list_session_entries()?)
+    }
+
+    /// Delete the corrupt and orphaned session directories of one namespace
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if `namespace` is not configured.
+    pub fn prune_broken_sessions_in(&self, namespace: &str) -> Result<Vec<(String, u64)>> {
+        self.prune_entries(self.list_session_entries_in(namespace)?)
+    }
+
+    fn prune_entries(&self, entries: Vec<SessionEntry>) -> Result<Vec<(String, u64)>> {
+        let mut pruned = Vec::new();
+        for entry in entries {
+            if entry.is_valid() {
+                continue;
+            }
+            let id = entry.id().to_string();
+            let size_bytes = self.measure_session_size(&id);
+            self.delete_session(&id)?;
+            pruned.push((id, size_bytes));
+        }
+        Ok(pruned)
+    }
+
+    /// List the IDs of all session directories in all namespaces, sorted
+    ///
+    /// Cheaper than `list_sessions` as no metadata is read. Sessions
+    /// still being indexed are not listed. IDs are canonical (see
+    /// [`canonical_session_id`](Self::canonical_session_id)).
+    pub fn list_session_ids(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for namespace in self.namespaces() {
+            ids.extend(self.list_session_ids_in(&namespace)?);
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// List the IDs of the session directories of one namespace, sorted
+    ///
+    /// # Errors
+    ///
+    /// `InvalidSession` if `namespace` is not configured.
+    pub fn list_session_ids_in(&self, namespace: &str) -> Result<Vec<String>> {
+        let root = self.namespace_root(namespace).ok_or_else(|| {
+            ShebeError::InvalidSession(format!(
+                "Unknown namespace '{namespace}' (available: {})",
+                self.namespaces().join(", ")
+            ))
+        })?;
+        let sessions_dir = root.join("sessions");
 
         if !sessions_dir.exists() {
             return Ok(Vec::new());
         }
 
-        let mut sessions = Vec::new();
+        let mut ids = Vec::new();
 
         for entry in fs::read_dir(sessions_dir)? {
             let entry = entry?;
             if entry.file_type()?.is_dir() {
-                if let Some(session_id) = entry.file_name().to_str() {
-                    if let Ok(metadata) = self.get_session_metadata(session_id) {
-                        sessions.push(metadata);
+                match entry.file_name().to_str() {
+                    Some(name) if !is_staging_name(name) => {
+                        ids.push(qualified_id(namespace, name, &self.default_namespace))
                     }
+                    _ => {}
                 }
             }
         }
 
-        Ok(sessions)
+        ids.sort();
+        Ok(ids)
     }
 
     /// Get the full path to a session directory
@@ -249,76 +1232,476 @@ impl StorageManager {
         overlap: usize,
         max_file_size_mb: usize,
         force: bool,
+    ) -> Result<crate::core::types::IndexStats> {
+        let config = SessionConfig::builder()
+            .chunk_size(chunk_size)
+            .overlap(overlap)
+            .include_patterns(include_patterns)
+            .exclude_patterns(exclude_patterns)
+            .build()?;
+        self.index_repository_with_config(session_id, path, config, max_file_size_mb, force)
+    }
+
+    /// Index a repository using a full session configuration
+    ///
+    /// Same as [`StorageManager::index_repository`], but takes a
+    /// [`SessionConfig`] so options such as `dedupe` are honoured
+    /// and persisted with the session.
+    pub fn index_repository_with_config(
+        &self,
+        session_id: &str,
+        path: &std::path::Path,
+        session_config: SessionConfig,
+        max_file_size_mb: usize,
+        force: bool,
+    ) -> Result<crate::core::types::IndexStats> {
+        self.index_repository_with_progress(
+            session_id,
+            path,
+            session_config,
+            max_file_size_mb,
+            force,
+            None,
+        )
+    }
+
+    /// Index a repository, reporting to `progress` as files are processed
+    ///
+    /// Same as [`StorageManager::index_repository_with_config`]. If
+    /// cancellation is requested the run stops between files (or
+    /// batches of chunks while writing) and returns
+    /// [`ShebeError::Cancelled`].
+    ///
+    /// The session is built in a staging directory and renamed into
+    /// place only once its index and metadata are written, so a failed
+    /// or cancelled run leaves nothing behind: a new session ID stays
+    /// free and, with `force`, the existing session is kept as it was.
+    /// A walk over more files or bytes than the storage manager's
+    /// [`IndexLimits`] stops with [`ShebeError::IndexTooLarge`] before
+    /// anything is written.
+    pub fn index_repository_with_progress(
+        &self,
+        session_id: &str,
+        path: &std::path::Path,
+        session_config: SessionConfig,
+        max_file_size_mb: usize,
+        force: bool,
+        progress: Option<&IndexProgress>,
+    ) -> Result<crate::core::types::IndexStats> {
+        self.index_session(
+            session_id,
+            path,
+            None,
+            session_config,
+            max_file_size_mb,
+            force,
+            progress,
+        )
+    }
+
+    /// Index exactly `files` of a repository into a session
+    ///
+    /// Same as [`StorageManager::index_repository_with_progress`], but
+    /// only the listed files (absolute or relative to `path`) are
+    /// indexed and the session's include, exclude and `.shebeignore`
+    /// patterns are not applied to them. The session records `path` as
+    /// its repository, so a later re-index walks the whole tree.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPath` if a listed file is missing, not a regular file or
+    /// outside `path`, before anything is written.
+    #[allow(clippy::too_many_arguments)] // All parameters are necessary
+    pub fn index_files_with_progress(
+        &self,
+        session_id: &str,
+        path: &std::path::Path,
+        files: &[PathBuf],
+        session_config: SessionConfig,
+        max_file_size_mb: usize,
+        force: bool,
+        progress: Option<&IndexProgress>,
+    ) -> Result<crate::core::types::IndexStats> {
+        self.index_session(
+            session_id,
+            path,
+            Some(files),
+            session_config,
+            max_file_size_mb,
+            force,
+            progress,
+        )
+    }
+
+    /// Index a walk of `path` (or only `files`) into a new session
+    #[allow(clippy::too_many_arguments)] // All parameters are necessary
+    fn index_session(
+        &self,
+        session_id: &str,
+        path: &std::path::Path,
+        files: Option<&[PathBuf]>,
+        session_config: SessionConfig,
+        max_file_size_mb: usize,
+        force: bool,
+        progress: Option<&IndexProgress>,
     ) -> Result<crate::core::types::IndexStats> {
         use std::time::Instant;
 
         let start = Instant::now();
+        let started_at = Utc::now();
+        self.ensure_writable("index", session_id)?;
+        let session_id = &self.canonical_session_id(session_id);
+        self.locate(session_id)?;
 
-        // Handle force re-indexing
-        if self.session_exists(session_id) {
-            if force {
-                self.delete_session(session_id)?;
-            } else {
-                return Err(ShebeError::SessionAlreadyExists(session_id.to_string()));
-            }
+        let replace_existing = self.session_exists(session_id);
+        if replace_existing && !force {
+            return Err(ShebeError::SessionAlreadyExists(session_id.to_string()));
         }
-
-        // Create session config with patterns first (before moving into pipeline)
-        let session_config = SessionConfig {
-            chunk_size,
-            overlap,
-            include_patterns: include_patterns.clone(),
-            exclude_patterns: exclude_patterns.clone(),
+        let archive = Archive::detect(path);
+        let path = &match &archive {
+            Some(archive) => self.check_archive(archive, files, &session_config)?,
+            None => self.resolve_repository_path(path)?,
         };
+        let mut provenance = Provenance::start(path, &session_config, started_at);
 
         // Create indexing pipeline
-        let pipeline = crate::core::indexer::IndexingPipeline::new(
-            chunk_size,
-            overlap,
-            include_patterns,
-            exclude_patterns,
-            max_file_size_mb,
+        let pipeline = crate::core::indexer::IndexingPipeline::builder()
+            .session_config(&session_config)
+            .max_file_size_mb(max_file_size_mb)
+            .limits(*self.index_limits.read().unwrap_or_else(|e| e.into_inner()))
+            .build()?;
+
+        // Index directory (or the listed files, or the archive's entries)
+        let (chunks, mut stats, manifest, times) = match (&archive, files) {
+            (Some(archive), _) => {
+                let entries = archive.entries()?;
+                let (chunks, stats, manifest) =
+                    pipeline.index_archive_with_progress(archive, &entries, progress)?;
+                let times = entries
+                    .iter()
+                    .filter_map(|e| Some((archive.entry_path(&e.name), e.modified?)))
+                    .collect();
+                (chunks, stats, manifest, ModifiedTimes::from_mtimes(times))
+            }
+            (None, files) => {
+                let (chunks, stats, manifest) = match files {
+                    Some(files) => pipeline.index_files_with_progress(path, files, progress)?,
+                    None => pipeline.index_directory_with_progress(path, progress)?,
+                };
+                // Last change of each indexed file (one git log for all of them)
+                let times = ModifiedTimes::collect(
+                    path,
+                    chunks.iter().map(|c| c.file_path.as_path()),
+                    session_config.git_metadata,
+                );
+                (chunks, stats, manifest, times)
+            }
+        };
+
+        // Files that only moved since the run this one replaces
+        if replace_existing {
+            self.record_renames(session_id, &manifest, &mut stats);
+        }
+
+        // Build the session in a staging directory, removed on any error
+        let (sessions_dir, name) = self.locate(session_id)?;
+        let staging = StagingDir::create(&sessions_dir, name)?;
+        let index = TantivyIndex::create_with(
+            &staging.path().join("tantivy"),
+            session_config.store_chunk_text,
+        )?;
+        self.write_new_session(session_id, index, &chunks, &times, progress)?;
+        write_json(&staging.path().join("manifest.json"), &manifest)?;
+        write_json(
+            &staging.path().join(FILE_MANIFEST_NAME),
+            &FileManifest::from_chunks(&chunks),
         )?;
 
-        // Index directory
-        let (chunks, mut stats) = pipeline.index_directory(path)?;
+        let mut metadata = new_metadata(session_id, path.to_path_buf(), session_config);
+        metadata.files_indexed = stats.files_indexed;
+        metadata.chunks_created = stats.chunks_created;
+        metadata.files_deduplicated = stats.files_deduplicated;
+        metadata.skipped = stats.skipped.clone();
+        metadata.chunk_text_bytes = chunks.iter().map(|c| c.text.len() as u64).sum();
+        let profiler = progress.and_then(IndexProgress::profiler);
+        if let Some(profiler) = profiler {
+            profiler.record_chunk_text_bytes(metadata.chunk_text_bytes);
+        }
+        metadata.modified_times = times.source();
+        metadata.archive = archive.as_ref().map(Archive::source).transpose()?;
+        // Keep the schedule of a session being replaced
+        if replace_existing {
+            if let Ok(previous) = self.get_session_metadata(session_id) {
+                metadata.reindex_schedule = previous.reindex_schedule;
+                metadata.last_scheduled_run = previous.last_scheduled_run;
+                metadata.search_defaults = previous.search_defaults;
+            }
+        } else if self.session_exists(session_id) {
+            // Created by someone else while this run was indexing
+            return Err(ShebeError::SessionAlreadyExists(session_id.to_string()));
+        }
+        provenance.finish();
+        metadata.provenance = Some(provenance.clone());
+        write_json(&staging.path().join("meta.json"), &metadata)?;
 
-        // Create session and get index
-        let mut index =
-            self.create_session(session_id, path.to_path_buf(), session_config.clone())?;
+        // Swap the finished session into place
+        self.readers.invalidate(session_id);
+        staging.persist(&self.session_dir(session_id))?;
 
-        // Add chunks to index
-        index.add_chunks(&chunks, session_id)?;
+        // Measured last, so the size covers the metadata just written
+        self.refresh_index_size(session_id)?;
 
-        // Commit index
-        index.commit()?;
+        // Calculate duration in seconds
+        let duration_secs = start.elapsed().as_secs_f64();
+
+        // Return stats
+        stats.session = session_id.to_string();
+        stats.duration_ms = (duration_secs * 1000.0) as u64;
+        stats.provenance = Some(provenance);
+        stats.profile = profiler.map(|profiler| profiler.profile());
+
+        Ok(stats)
+    }
 
-        // Calculate index size
+    /// Set `stats.files_renamed` from the content manifest of the
+    /// session about to be replaced
+    ///
+    /// A missing or unreadable previous manifest, or one hashed with
+    /// another algorithm, reports no renames.
+    fn record_renames(&self, session_id: &str, manifest: &ContentManifest, stats: &mut IndexStats) {
+        let previous = match self.get_content_manifest(session_id) {
+            Ok(Some(previous)) if previous.algorithm == manifest.algorithm => previous,
+            _ => return,
+        };
+        let mut renames = detect_renames(&previous.files, &manifest.files);
+        if !renames.is_empty() {
+            tracing::info!(
+                "Session '{}': {} file(s) renamed since the last run",
+                session_id,
+                renames.len()
+            );
+        }
+        stats.files_renamed = renames.len();
+        renames.truncate(MAX_RENAMED_FILES);
+        stats.renamed_files = renames;
+    }
+
+    /// Add and commit a new session's chunks with their files' change `times`
+    ///
+    /// Cancellation is checked between batches of chunks and once more
+    /// before the commit.
+    fn write_new_session(
+        &self,
+        session_id: &str,
+        mut index: TantivyIndex,
+        chunks: &[crate::core::types::Chunk],
+        times: &ModifiedTimes,
+        progress: Option<&IndexProgress>,
+    ) -> Result<()> {
+        let profiler = progress.and_then(IndexProgress::profiler);
+        for batch in chunks.chunks(WRITE_BATCH_CHUNKS) {
+            if let Some(progress) = progress {
+                progress.check_cancelled()?;
+            }
+            timed(profiler, IndexStage::AddDocument, || {
+                index.add_chunks_with_times(batch, session_id, times)
+            })?;
+        }
+
+        if let Some(progress) = progress {
+            progress.check_cancelled()?;
+        }
+        #[cfg(test)]
+        if self.fail_before_commit {
+            return Err(ShebeError::StorageError(
+                "injected write failure".to_string(),
+            ));
+        }
+
+        // Commit index and release the writer lock
+        timed(profiler, IndexStage::Commit, || index.commit())?;
+        index.release_writer()
+    }
+
+    /// Merge a session's index segments and reclaim disk space
+    ///
+    /// Incremental operations leave many small segments behind, which
+    /// slows down search. This merges them into a single segment,
+    /// removes obsolete files and updates `index_size_bytes` in the
+    /// session metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionNotFound` if the session does not exist and
+    /// `IndexLocked` if another operation is writing to the index.
+    pub fn compact_session(&self, session_id: &str) -> Result<crate::core::types::CompactionStats> {
+        self.ensure_writable("compact", session_id)?;
+        self.optimize_session(session_id, 1)
+    }
+
+    /// Merge a session's index down to `target_segments` segments,
+    /// purge deleted documents and reclaim disk space
+    ///
+    /// Same as [`StorageManager::compact_session`] with a segment
+    /// target; used by idle-time maintenance. The index writer lock is
+    /// held throughout, so this never runs alongside another write.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionNotFound` if the session does not exist and
+    /// `IndexLocked` if another operation is writing to the index.
+    pub fn optimize_session(
+        &self,
+        session_id: &str,
+        target_segments: usize,
+    ) -> Result<crate::core::types::CompactionStats> {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        self.ensure_writable("optimize", session_id)?;
+
+        let mut index = self.open_session(session_id)?;
         let session_path = self.get_session_path(session_id);
-        let index_size_bytes = calculate_directory_size(&session_path);
 
-        // Update metadata with correct counts and last_indexed_at
-        let mut metadata = self.get_session_metadata(session_id)?;
-        metadata.last_indexed_at = Utc::now();
-        metadata.files_indexed = stats.files_indexed;
-        metadata.chunks_created = stats.chunks_created;
-        metadata.index_size_bytes = index_size_bytes;
+        let segments_before = index.segment_count()?;
+        let size_before_bytes = calculate_directory_size(&session_path);
+
+        let segments_after = index.merge_segments_to(target_segments)?;
+        index.release_writer()?;
+
+        let size_after_bytes = self.refresh_index_size(session_id)?;
+
+        Ok(crate::core::types::CompactionStats {
+            session: session_id.to_string(),
+            segments_before,
+            segments_after,
+            size_before_bytes,
+            size_after_bytes,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Every distinct file path with chunks in a session's index, sorted
+    pub fn searchable_file_paths(&self, session_id: &str) -> Result<Vec<String>> {
+        Ok(self.session_files(session_id)?.sorted_paths())
+    }
+
+    /// Build a directory tree of the files indexed in a session
+    ///
+    /// Paths are shown relative to the session's repository path.
+    /// `subtree` (relative to the repository) roots the tree at a
+    /// subdirectory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionNotFound` if the session does not exist and
+    /// `InvalidPath` if no indexed file lies under `subtree`.
+    pub fn file_tree(
+        &self,
+        session_id: &str,
+        subtree: Option<&str>,
+        options: TreeOptions,
+    ) -> Result<TreeNode> {
+        let metadata = self.get_session_metadata(session_id)?;
+        let files = self.session_files(session_id)?.chunk_counts();
+
+        build_tree(&files, &metadata.repository_path, subtree, options).ok_or_else(|| {
+            ShebeError::InvalidPath(format!(
+                "No indexed files under '{}'",
+                subtree.unwrap_or_default()
+            ))
+        })
+    }
+}
 
-        self.update_session_metadata(session_id, &metadata)?;
+/// Session ID of `name` in `namespace`, bare for the default namespace
+fn qualified_id(namespace: &str, name: &str, default_namespace: &str) -> String {
+    if namespace == default_namespace {
+        name.to_string()
+    } else {
+        format!("{namespace}/{name}")
+    }
+}
 
-        // Calculate duration in seconds
-        let duration_secs = start.elapsed().as_secs_f64();
+/// Metadata of a session that has no files indexed yet
+fn new_metadata(
+    session_id: &str,
+    repository_path: PathBuf,
+    config: SessionConfig,
+) -> SessionMetadata {
+    let now = Utc::now();
+    let store_chunk_text = config.store_chunk_text;
+    SessionMetadata {
+        id: session_id.to_string(),
+        repository_path,
+        created_at: now,
+        last_indexed_at: now,
+        files_indexed: 0,
+        chunks_created: 0,
+        index_size_bytes: 0,
+        config,
+        schema_version: SCHEMA_VERSION,
+        files_deduplicated: 0,
+        created_by_version: CURRENT_VERSION.to_string(),
+        tantivy_schema_hash: current_schema_hash_for(store_chunk_text),
+        index_settings: Some(IndexSettings::current_for(store_chunk_text)),
+        remote: None,
+        archive: None,
+        skipped: Default::default(),
+        chunk_text_bytes: 0,
+        reindex_schedule: None,
+        last_scheduled_run: None,
+        search_defaults: None,
+        modified_times: None,
+        provenance: None,
+    }
+}
 
-        // Return stats
-        stats.session = session_id.to_string();
-        stats.duration_ms = (duration_secs * 1000.0) as u64;
+/// Write `value` to `path` as pretty-printed JSON
+///
+/// The JSON goes to a sibling temporary file that is then renamed over
+/// `path`, so concurrent readers (e.g. while a scheduled re-index
+/// records its run) never see a truncated file.
+pub(super) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
 
-        Ok(stats)
+/// Reject sessions indexed with an older schema version
+fn check_schema_version(session_id: &str, metadata: &SessionMetadata) -> Result<()> {
+    if metadata.schema_version < SCHEMA_VERSION {
+        return Err(ShebeError::InvalidSession(format!(
+            "Session '{}' uses schema v{} but current version is v{}.\n\
+             Repository path: {}\n\
+             Re-index to upgrade: use upgrade_session tool or index_repository with force=true.",
+            session_id,
+            metadata.schema_version,
+            SCHEMA_VERSION,
+            metadata.repository_path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Warn when a session was created by another major version
+fn warn_on_major_version(session_id: &str, metadata: &SessionMetadata) {
+    if metadata.major_version_differs() {
+        tracing::warn!(
+            "Session '{}' was created by shebe {} but this is shebe {}; \
+             re-index if search results look wrong",
+            session_id,
+            metadata.created_by_version,
+            CURRENT_VERSION
+        );
     }
 }
 
 /// Calculate directory size recursively
-#[allow(dead_code)] // Used by index_repository method
 fn calculate_directory_size(path: &std::path::Path) -> u64 {
     let mut total = 0;
 
@@ -487,6 +1870,89 @@ mod tests {
         assert_eq!(metadata.config.overlap, 64);
     }
 
+    #[test]
+    fn test_session_records_diagnostics() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+
+        manager
+            .create_session(
+                "test-session",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let metadata = manager.get_session_metadata("test-session").unwrap();
+        assert_eq!(metadata.created_by_version, CURRENT_VERSION);
+        assert_eq!(metadata.tantivy_schema_hash, current_schema_hash_for(true));
+        assert_eq!(metadata.index_settings, Some(IndexSettings::current()));
+        assert!(!metadata.major_version_differs());
+    }
+
+    #[test]
+    fn test_old_metadata_loads_as_unknown() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "old-session",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        // Rewrite meta.json without the diagnostic fields
+        let meta_path = manager.metadata_path("old-session");
+        let mut json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("created_by_version");
+        fields.remove("tantivy_schema_hash");
+        fields.remove("index_settings");
+        fs::write(&meta_path, json.to_string()).unwrap();
+
+        let mut metadata = manager.get_session_metadata("old-session").unwrap();
+        assert_eq!(metadata.created_by_version, UNKNOWN);
+        assert_eq!(metadata.tantivy_schema_hash, UNKNOWN);
+        assert!(metadata.index_settings.is_none());
+        assert!(!metadata.major_version_differs());
+        assert!(manager.open_session("old-session").is_ok());
+
+        metadata.created_by_version = "999.0.0".to_string();
+        assert!(metadata.major_version_differs());
+    }
+
+    #[test]
+    fn test_set_remote_source() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "remote",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        assert!(manager
+            .get_session_metadata("remote")
+            .unwrap()
+            .remote
+            .is_none());
+
+        let source = RemoteSource {
+            url: "https://github.com/org/repo.git".to_string(),
+            git_ref: Some("main".to_string()),
+            commit: "abc123".to_string(),
+        };
+        manager
+            .set_remote_source("remote", Some(source.clone()))
+            .unwrap();
+
+        let metadata = manager.get_session_metadata("remote").unwrap();
+        assert_eq!(metadata.remote, Some(source));
+    }
+
     #[test]
     fn test_update_metadata() {
         let temp_dir = tempdir().unwrap();
@@ -553,6 +2019,37 @@ mod tests {
         assert_eq!(sessions.len(), 0);
     }
 
+    #[test]
+    fn test_list_session_ids_sorted() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        assert!(manager.list_session_ids().unwrap().is_empty());
+
+        for id in ["beta", "alpha"] {
+            manager
+                .create_session(id, PathBuf::from("/test/repo"), SessionConfig::default())
+                .unwrap();
+        }
+
+        assert_eq!(manager.list_session_ids().unwrap(), vec!["alpha", "beta"]);
+    }
+
+    #[test]
+    fn test_list_session_ids_skips_staging_dirs() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "alpha",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        fs::create_dir_all(temp_dir.path().join("sessions/.tmp-beta-0123456789ab")).unwrap();
+
+        assert_eq!(manager.list_session_ids().unwrap(), vec!["alpha"]);
+    }
+
     // Helper to create test fixture with files
     fn create_test_fixture(base_dir: &std::path::Path) -> std::path::PathBuf {
         let fixture_dir = base_dir.join("test-repo");
@@ -603,6 +2100,35 @@ mod tests {
         assert_eq!(stats.session, "test-session");
     }
 
+    #[test]
+    fn test_index_repository_persists_skip_counts() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        fs::write(repo_path.join("image.txt"), [b'G', b'I', b'F', 0]).unwrap();
+
+        let stats = manager
+            .index_repository(
+                "test-session",
+                &repo_path,
+                vec!["**/*.txt".to_string()],
+                vec!["**/subdir/**".to_string()],
+                512,
+                64,
+                10,
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(stats.files_indexed, 3);
+        assert_eq!(stats.skipped.excluded_by_pattern, 1);
+        assert_eq!(stats.skipped.binary, 1);
+        assert_eq!(stats.skipped_files.len(), 2);
+
+        let metadata = manager.get_session_metadata("test-session").unwrap();
+        assert_eq!(metadata.skipped, stats.skipped);
+    }
+
     #[test]
     fn test_metadata_correct_after_indexing() {
         let temp_dir = tempdir().unwrap();
@@ -745,6 +2271,217 @@ mod tests {
         assert_eq!(stats2.session, "test-session");
     }
 
+    #[test]
+    fn test_index_repository_cancelled_keeps_existing_session() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        let config = SessionConfig {
+            include_patterns: vec!["**/*.txt".to_string()],
+            ..SessionConfig::default()
+        };
+
+        manager
+            .index_repository_with_config("test-session", &repo_path, config.clone(), 10, false)
+            .unwrap();
+
+        let progress = IndexProgress::new();
+        progress.cancel();
+        let result = manager.index_repository_with_progress(
+            "test-session",
+            &repo_path,
+            config.clone(),
+            10,
+            true,
+            Some(&progress),
+        );
+        assert!(matches!(result, Err(ShebeError::Cancelled(_))));
+        assert_eq!(
+            manager
+                .get_session_metadata("test-session")
+                .unwrap()
+                .files_indexed,
+            4
+        );
+
+        // A cancelled first run leaves no session behind
+        let result = manager.index_repository_with_progress(
+            "other-session",
+            &repo_path,
+            config,
+            10,
+            false,
+            Some(&progress),
+        );
+        assert!(matches!(result, Err(ShebeError::Cancelled(_))));
+        assert!(!manager.session_exists("other-session"));
+    }
+
+    #[test]
+    fn test_index_repository_over_limits_leaves_no_session() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = create_test_fixture(temp_dir.path());
+        let config = SessionConfig {
+            include_patterns: vec!["**/*.txt".to_string()],
+            ..SessionConfig::default()
+        };
+
+        for limits in [
+            IndexLimits {
+                max_files: 3,
+                max_bytes: u64::MAX,
+                ..IndexLimits::default()
+            },
+            IndexLimits {
+                max_files: 100,
+                max_bytes: 10,
+                ..IndexLimits::default()
+            },
+        ] {
+            let manager =
+                StorageManager::new(temp_dir.path().to_path_buf()).with_index_limits(limits);
+            let result = manager.index_repository_with_config(
+                "too-big",
+                &repo_path,
+                config.clone(),
+                10,
+                false,
+            );
+
+            assert!(matches!(result, Err(ShebeError::IndexTooLarge(_))));
+            assert!(!manager.session_exists("too-big"));
+            assert!(!manager.get_session_path("too-big").exists());
+        }
+    }
+
+    /// Entries of the sessions directory, including staging directories
+    fn session_dir_entries(manager: &StorageManager) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(manager.sessions_dir())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_index_repository_write_failure_leaves_no_residue() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        manager
+            .create_session("other", repo_path.clone(), SessionConfig::default())
+            .unwrap();
+
+        manager.fail_before_commit = true;
+        let result = manager.index_repository_with_config(
+            "test-session",
+            &repo_path,
+            SessionConfig::default(),
+            10,
+            false,
+        );
+        assert!(matches!(result, Err(ShebeError::StorageError(_))));
+        assert!(!manager.session_exists("test-session"));
+        assert_eq!(session_dir_entries(&manager), vec!["other"]);
+
+        // The session ID is still free
+        manager.fail_before_commit = false;
+        let stats = manager
+            .index_repository_with_config(
+                "test-session",
+                &repo_path,
+                SessionConfig::default(),
+                10,
+                false,
+            )
+            .unwrap();
+        assert_eq!(stats.files_indexed, 4);
+        assert_eq!(session_dir_entries(&manager), vec!["other", "test-session"]);
+    }
+
+    #[test]
+    fn test_index_repository_write_failure_keeps_existing_session() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        let config = SessionConfig {
+            include_patterns: vec!["**/*.txt".to_string()],
+            ..SessionConfig::default()
+        };
+        manager
+            .index_repository_with_config("test-session", &repo_path, config.clone(), 10, false)
+            .unwrap();
+        fs::write(repo_path.join("file5.txt"), "Hello from file 5").unwrap();
+
+        manager.fail_before_commit = true;
+        let result = manager.index_repository_with_config(
+            "test-session",
+            &repo_path,
+            config.clone(),
+            10,
+            true,
+        );
+        assert!(result.is_err());
+        assert_eq!(session_dir_entries(&manager), vec!["test-session"]);
+        assert_eq!(
+            manager
+                .get_session_metadata("test-session")
+                .unwrap()
+                .files_indexed,
+            4
+        );
+        assert_eq!(
+            manager
+                .open_session("test-session")
+                .unwrap()
+                .file_chunk_counts()
+                .unwrap()
+                .len(),
+            4
+        );
+
+        // A successful run replaces it without leaving the old one behind
+        manager.fail_before_commit = false;
+        let stats = manager
+            .index_repository_with_config("test-session", &repo_path, config, 10, true)
+            .unwrap();
+        assert_eq!(stats.files_indexed, 5);
+        assert_eq!(session_dir_entries(&manager), vec!["test-session"]);
+    }
+
+    #[test]
+    fn test_index_repository_over_limits_keeps_existing_session() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = create_test_fixture(temp_dir.path());
+        let config = SessionConfig {
+            include_patterns: vec!["**/*.txt".to_string()],
+            ..SessionConfig::default()
+        };
+        StorageManager::new(temp_dir.path().to_path_buf())
+            .index_repository_with_config("test-session", &repo_path, config.clone(), 10, false)
+            .unwrap();
+
+        let manager =
+            StorageManager::new(temp_dir.path().to_path_buf()).with_index_limits(IndexLimits {
+                max_files: 3,
+                max_bytes: u64::MAX,
+                ..IndexLimits::default()
+            });
+        let err = manager
+            .index_repository_with_config("test-session", &repo_path, config, 10, true)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("limits.max_files_per_session"));
+        assert_eq!(
+            manager
+                .get_session_metadata("test-session")
+                .unwrap()
+                .files_indexed,
+            4
+        );
+    }
+
     #[test]
     fn test_index_repository_with_filters() {
         let temp_dir = tempdir().unwrap();
@@ -808,6 +2545,87 @@ mod tests {
         assert_eq!(metadata.chunks_created, 0);
     }
 
+    /// `path` relative to the current directory, without changing it
+    fn relative_to_cwd(path: &Path) -> PathBuf {
+        let cwd = std::env::current_dir().unwrap();
+        let mut relative = PathBuf::new();
+        for _ in cwd.components().skip(1) {
+            relative.push("..");
+        }
+        relative.join(path.strip_prefix("/").unwrap())
+    }
+
+    fn index_defaults(manager: &StorageManager, session_id: &str, path: &Path) -> Result<()> {
+        manager
+            .index_repository(session_id, path, vec![], vec![], 512, 64, 10, false)
+            .map(|_| ())
+    }
+
+    #[test]
+    fn test_index_repository_stores_canonical_path() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        let canonical = repo_path.canonicalize().unwrap();
+
+        let relative = relative_to_cwd(&repo_path.join("subdir").join(".."));
+        assert!(relative.is_relative());
+        index_defaults(&manager, "relative", &relative).unwrap();
+        let metadata = manager.get_session_metadata("relative").unwrap();
+        assert_eq!(metadata.repository_path, canonical);
+
+        #[cfg(unix)]
+        {
+            let link = temp_dir.path().join("repo-link");
+            std::os::unix::fs::symlink(&repo_path, &link).unwrap();
+            index_defaults(&manager, "symlinked", &link).unwrap();
+            let metadata = manager.get_session_metadata("symlinked").unwrap();
+            assert_eq!(metadata.repository_path, canonical);
+        }
+    }
+
+    #[test]
+    fn test_index_repository_rejects_invalid_paths() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        index_defaults(&manager, "indexed", &repo_path).unwrap();
+
+        let missing = temp_dir.path().join("missing");
+        let file = repo_path.join("file1.txt");
+        let nested = temp_dir.path().join("sessions").join("indexed");
+        for (session_id, path) in [("missing", missing), ("file", file), ("nested", nested)] {
+            let result = index_defaults(&manager, session_id, &path);
+            assert!(
+                matches!(result, Err(ShebeError::InvalidPath(_))),
+                "{session_id}: {result:?}"
+            );
+            assert!(!manager.session_exists(session_id));
+        }
+    }
+
+    #[test]
+    fn test_upgrade_session_fixes_stored_relative_path() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let repo_path = create_test_fixture(temp_dir.path());
+        let relative = relative_to_cwd(&repo_path);
+        manager
+            .create_session("legacy", relative.clone(), SessionConfig::default())
+            .unwrap();
+
+        // Reading does not depend on the working directory
+        let metadata = manager.get_session_metadata("legacy").unwrap();
+        assert_eq!(metadata.repository_path, relative);
+
+        manager.upgrade_session("legacy").unwrap();
+        let metadata = manager.get_session_metadata("legacy").unwrap();
+        assert_eq!(metadata.repository_path, repo_path.canonicalize().unwrap());
+        let stored = fs::read_to_string(manager.metadata_path("legacy")).unwrap();
+        let stored: SessionMetadata = serde_json::from_str(&stored).unwrap();
+        assert!(stored.repository_path.is_absolute());
+    }
+
     #[test]
     fn test_new_session_has_current_schema_version() {
         let temp_dir = tempdir().unwrap();
@@ -870,6 +2688,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compact_session_merges_segments() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "compact",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        // Simulate incremental updates: one small commit per file
+        // (kept below the merge policy's threshold for background merges)
+        let mut index = manager.open_session("compact").unwrap();
+        for i in 0..4 {
+            let chunk = crate::core::types::Chunk {
+                text: format!("fn handler{i}() {{}}"),
+                file_path: PathBuf::from(format!("/test/repo/f{i}.rs")),
+                start_offset: 0,
+                end_offset: 16,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            };
+            index.add_chunks(&[chunk], "compact").unwrap();
+            index.commit().unwrap();
+        }
+        index.release_writer().unwrap();
+        drop(index);
+
+        let stats = manager.compact_session("compact").unwrap();
+        assert_eq!(stats.session, "compact");
+        assert!(stats.segments_before > 1);
+        assert_eq!(stats.segments_after, 1);
+
+        let metadata = manager.get_session_metadata("compact").unwrap();
+        assert_eq!(metadata.index_size_bytes, stats.size_after_bytes);
+    }
+
+    #[test]
+    fn test_index_repository_with_dedupe() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().join("storage"));
+
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(repo.join("vendor")).unwrap();
+        fs::write(repo.join("lib.rs"), "pub fn shared() {}").unwrap();
+        fs::write(repo.join("vendor/lib.rs"), "pub fn shared() {}").unwrap();
+        fs::write(repo.join("main.rs"), "fn main() {}").unwrap();
+
+        let config = SessionConfig {
+            include_patterns: vec!["**/*.rs".to_string()],
+            exclude_patterns: vec![],
+            dedupe: true,
+            ..SessionConfig::default()
+        };
+        let stats = manager
+            .index_repository_with_config("dedupe", &repo, config, 10, false)
+            .unwrap();
+
+        assert_eq!(stats.files_indexed, 2);
+        assert_eq!(stats.files_deduplicated, 1);
+
+        let metadata = manager.get_session_metadata("dedupe").unwrap();
+        assert!(metadata.config.dedupe);
+        assert_eq!(metadata.files_deduplicated, 1);
+        assert!((metadata.dedupe_ratio() - 1.0 / 3.0).abs() < 1e-9);
+
+        let manifest = manager.get_content_manifest("dedupe").unwrap().unwrap();
+        assert_eq!(
+            manifest.algorithm,
+            crate::core::indexer::dedupe::HASH_ALGORITHM
+        );
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(manifest.duplicate_count(), 1);
+    }
+
+    #[test]
+    fn test_content_manifest_missing() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        manager
+            .create_session(
+                "bare",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        assert!(manager.get_content_manifest("bare").unwrap().is_none());
+        assert!(matches!(
+            manager.get_content_manifest("missing"),
+            Err(ShebeError::SessionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_compact_nonexistent_session() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+
+        let result = manager.compact_session("missing");
+        assert!(matches!(result, Err(ShebeError::SessionNotFound(_))));
+    }
+
+    #[test]
+    fn test_compact_session_while_locked() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let mut holder = manager
+            .create_session(
+                "busy",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        holder.commit().unwrap();
+
+        let result = manager.compact_session("busy");
+        assert!(matches!(result, Err(ShebeError::IndexLocked(_))));
+    }
+
     // NOTE: Backward compatibility test removed - project policy is NO backward compatibility
     // Old sessions (v1, v2) must be re-indexed to v3
 }
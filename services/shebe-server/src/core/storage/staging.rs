@@ -0,0 +1,147 @@
+//! Staging directories for building sessions.
+//!
+//! A session is indexed into a hidden sibling of its final directory
+//! (`sessions/.tmp-<id>-<nonce>`) and only renamed into place once the
+//! index is committed and its metadata written. Until then the
+//! [`StagingDir`] guard removes the directory when dropped, so a
+//! failed, cancelled or panicking run leaves no half-built session
+//! behind and the session ID stays free (or keeps its previous index).
+
+use crate::core::error::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of staging directory names
+///
+/// Session IDs cannot start with a dot, so staging directories never
+/// collide with sessions and are skipped when listing them.
+pub(super) const STAGING_PREFIX: &str = ".tmp-";
+
+/// Whether a directory name under `sessions/` is not a session
+pub(super) fn is_staging_name(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// A session directory under construction, removed unless persisted
+#[derive(Debug)]
+pub(super) struct StagingDir {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl StagingDir {
+    /// Create an empty staging directory for `session_id` in `sessions_dir`
+    pub(super) fn create(sessions_dir: &Path, session_id: &str) -> Result<Self> {
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let path = sessions_dir.join(format!("{STAGING_PREFIX}{session_id}-{}", &nonce[..12]));
+        fs::create_dir_all(&path)?;
+        Ok(Self {
+            path,
+            persisted: false,
+        })
+    }
+
+    /// Directory the session is built in
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rename the staged session to `target`, replacing any session there
+    ///
+    /// An existing session is moved aside first and put back if the
+    /// rename fails, so `target` always holds a complete session.
+    pub(super) fn persist(mut self, target: &Path) -> Result<()> {
+        if !target.exists() {
+            fs::rename(&self.path, target)?;
+            self.persisted = true;
+            return Ok(());
+        }
+
+        let mut previous = self.path.clone().into_os_string();
+        previous.push("-old");
+        let previous = PathBuf::from(previous);
+
+        fs::rename(target, &previous)?;
+        if let Err(e) = fs::rename(&self.path, target) {
+            if let Err(restore) = fs::rename(&previous, target) {
+                tracing::warn!(
+                    "Failed to restore previous session at {}: {}",
+                    target.display(),
+                    restore
+                );
+            }
+            return Err(e.into());
+        }
+        self.persisted = true;
+
+        if let Err(e) = fs::remove_dir_all(&previous) {
+            tracing::warn!(
+                "Failed to remove replaced session at {}: {}",
+                previous.display(),
+                e
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if self.persisted {
+            return;
+        }
+        match fs::remove_dir_all(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!(
+                "Failed to remove staging directory {}: {}",
+                self.path.display(),
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_dropped_staging_dir_is_removed() {
+        let temp = TempDir::new().unwrap();
+        let staging = StagingDir::create(temp.path(), "api").unwrap();
+        fs::write(staging.path().join("meta.json"), "{}").unwrap();
+
+        let name = staging.path().file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with(".tmp-api-"));
+        assert!(is_staging_name(&name));
+
+        drop(staging);
+        assert!(entries(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_persist_replaces_existing_session() {
+        let temp = TempDir::new().unwrap();
+        let target = temp.path().join("api");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("meta.json"), "old").unwrap();
+
+        let staging = StagingDir::create(temp.path(), "api").unwrap();
+        fs::write(staging.path().join("meta.json"), "new").unwrap();
+        staging.persist(&target).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("meta.json")).unwrap(), "new");
+        assert_eq!(entries(temp.path()), vec!["api"]);
+    }
+}
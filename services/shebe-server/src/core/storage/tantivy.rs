@@ -4,50 +4,251 @@
 //! managing and searching indexes.
 
 use crate::core::error::{Result, ShebeError};
+use crate::core::indexer::ModifiedTimes;
+use crate::core::paths;
 use crate::core::types::Chunk;
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
+use tantivy::collector::DocSetCollector;
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, TermQuery};
 use tantivy::schema::*;
-use tantivy::{doc, Index, IndexReader, IndexWriter};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyError};
+use xxhash_rust::xxh3::xxh3_64;
 
 /// Current schema version
 /// Version 1: Initial schema (chunk_index STORED only)
 /// Version 2: Added INDEXED flag to chunk_index for preview_chunk queries
 /// Version 3: Added repository_path, last_indexed_at and patterns to SessionMetadata
-pub const SCHEMA_VERSION: u32 = 3;
+/// Version 4: Added tokenized path_tokens field for query-time path boosting
+/// Version 5: Chunk text storage is optional (SessionConfig::store_chunk_text)
+/// Version 6: Added char_count and line_count fast fields for short-chunk demotion
+/// Version 7: Added modified_at fast field (last commit or mtime) for modified_within
+/// Version 8: Added record_start and record_end for data files chunked by record
+/// Version 9: Added section (heading breadcrumb) for Markdown and reStructuredText
+pub const SCHEMA_VERSION: u32 = 9;
+
+/// Whether a Tantivy error means the index files on disk are damaged
+///
+/// Truncated files fail as `InvalidData` or `UnexpectedEof` I/O errors
+/// and deleted segments as read errors. Other I/O errors, such as
+/// missing permissions, are not corruption.
+pub fn is_corruption(err: &TantivyError) -> bool {
+    match err {
+        TantivyError::DataCorruption(_)
+        | TantivyError::OpenReadError(_)
+        | TantivyError::IncompatibleIndex(_) => true,
+        TantivyError::IoError(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof
+        ),
+        _ => false,
+    }
+}
 
 /// Create the Tantivy schema for chunk indexing
 ///
+/// Without `store_chunk_text`, the text field is indexed but not
+/// stored; search reads chunk text from the files at query time.
+///
 /// Fields:
-/// - text: Full-text searchable content (TEXT | STORED)
+/// - text: Full-text searchable content (TEXT, STORED if `store_chunk_text`)
 /// - file_path: Source file path (STRING | STORED)
+/// - path_tokens: Tokenized file path for path boosting (TEXT)
 /// - session: Session identifier (STRING | STORED)
 /// - offset_start: Byte offset start (i64 | STORED)
 /// - offset_end: Byte offset end (i64 | STORED)
 /// - chunk_index: Sequential chunk number (i64 | STORED)
+/// - char_count: Characters in the chunk (u64 | FAST | STORED)
+/// - line_count: Lines in the chunk (u64 | FAST | STORED)
+/// - modified_at: File's last change, seconds since the epoch (i64 | FAST | STORED)
+/// - record_start: First record in a data file chunk, 1-based (u64 | STORED)
+/// - record_end: Last record in a data file chunk (u64 | STORED)
+/// - section: Heading breadcrumb of a documentation chunk (STRING | STORED)
 /// - indexed_at: Timestamp (Date | STORED)
-pub fn create_schema() -> Schema {
+pub fn create_schema_for(store_chunk_text: bool) -> Schema {
     let mut builder = Schema::builder();
 
     // Searchable text content
-    builder.add_text_field("text", TEXT | STORED);
+    if store_chunk_text {
+        builder.add_text_field("text", TEXT | STORED);
+    } else {
+        builder.add_text_field("text", TEXT);
+    }
 
     // Metadata (stored for retrieval)
     builder.add_text_field("file_path", STRING | STORED);
     builder.add_text_field("session", STRING | STORED);
 
+    // Tokenized file path for path boosting (searchable, not stored)
+    builder.add_text_field("path_tokens", TEXT);
+
     // Offset fields for highlighting
     builder.add_i64_field("offset_start", STORED);
     builder.add_i64_field("offset_end", STORED);
     builder.add_i64_field("chunk_index", INDEXED | STORED);
 
+    // Chunk length, read per hit at query time
+    builder.add_u64_field("char_count", FAST | STORED);
+    builder.add_u64_field("line_count", FAST | STORED);
+
+    // File's last change, filtered on at query time
+    builder.add_i64_field("modified_at", FAST | STORED);
+
+    // Records held by chunks of data files (absent for other files)
+    builder.add_u64_field("record_start", STORED);
+    builder.add_u64_field("record_end", STORED);
+
+    // Heading breadcrumb of documentation chunks (absent for other files)
+    builder.add_text_field("section", STRING | STORED);
+
     // Timestamp
     builder.add_date_field("indexed_at", STORED);
 
     builder.build()
 }
 
+/// How one schema field is indexed, recorded for diagnostics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSettings {
+    /// Field name
+    pub name: String,
+
+    /// Value type ("Str", "I64", "Date", ...)
+    pub field_type: String,
+
+    /// Tokenizer for indexed text fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+
+    /// Whether the field is searchable
+    pub indexed: bool,
+
+    /// Whether the field value is stored
+    pub stored: bool,
+}
+
+impl std::fmt::Display for FieldSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut flags = vec![self.field_type.clone()];
+        if self.indexed {
+            flags.push("indexed".to_string());
+        }
+        if self.stored {
+            flags.push("stored".to_string());
+        }
+        if let Some(tokenizer) = &self.tokenizer {
+            flags.push(format!("tokenizer={tokenizer}"));
+        }
+        write!(f, "{} ({})", self.name, flags.join(", "))
+    }
+}
+
+/// Analyzer and field settings an index was built with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSettings {
+    /// Fields in schema order
+    pub fields: Vec<FieldSettings>,
+}
+
+impl IndexSettings {
+    /// Describe the fields of a schema
+    pub fn from_schema(schema: &Schema) -> Self {
+        let fields = schema
+            .fields()
+            .map(|(_, entry)| {
+                let tokenizer = match entry.field_type() {
+                    FieldType::Str(options) => options
+                        .get_indexing_options()
+                        .map(|indexing| indexing.tokenizer().to_string()),
+                    _ => None,
+                };
+                FieldSettings {
+                    name: entry.name().to_string(),
+                    field_type: entry.field_type().value_type().name().to_string(),
+                    tokenizer,
+                    indexed: entry.is_indexed(),
+                    stored: entry.is_stored(),
+                }
+            })
+            .collect();
+
+        Self { fields }
+    }
+
+    /// Settings of the schema built by this version
+    pub fn current() -> Self {
+        Self::current_for(true)
+    }
+
+    /// Settings of the schema built by this version for a text storage mode
+    pub fn current_for(store_chunk_text: bool) -> Self {
+        Self::from_schema(&create_schema_for(store_chunk_text))
+    }
+
+    /// Human-readable differences from `self` to `newer`
+    ///
+    /// Returns one line per added, removed or changed field.
+    pub fn diff(&self, newer: &IndexSettings) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        for old in &self.fields {
+            match newer.fields.iter().find(|f| f.name == old.name) {
+                None => changes.push(format!("field removed: {old}")),
+                Some(new) if new != old => changes.push(format!("field changed: {old} -> {new}")),
+                Some(_) => {}
+            }
+        }
+        for new in &newer.fields {
+            if !self.fields.iter().any(|f| f.name == new.name) {
+                changes.push(format!("field added: {new}"));
+            }
+        }
+
+        changes
+    }
+}
+
+/// Stable fingerprint of a schema (xxh3-64 of its JSON form)
+pub fn schema_hash(schema: &Schema) -> String {
+    let json = serde_json::to_string(schema).unwrap_or_default();
+    format!("{:016x}", xxh3_64(json.as_bytes()))
+}
+
+/// Fingerprint of the schema built by this version
+pub fn current_schema_hash() -> String {
+    current_schema_hash_for(true)
+}
+
+/// Fingerprint of the schema built by this version for a text storage mode
+pub fn current_schema_hash_for(store_chunk_text: bool) -> String {
+    schema_hash(&create_schema_for(store_chunk_text))
+}
+
+/// Position of one chunk within its file, as stored in the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSpan {
+    pub chunk_index: usize,
+    /// Byte offset where the chunk starts
+    pub start_offset: usize,
+    /// Byte offset where the chunk ends (exclusive)
+    pub end_offset: usize,
+    /// Chunk text, `None` when the session does not store it
+    pub text: Option<String>,
+}
+
+/// Memory budget for the index writer (50MB)
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
 /// Tantivy index wrapper
+///
+/// The writer is acquired lazily on the first write operation and
+/// held open until [`TantivyIndex::release_writer`] is called or the
+/// wrapper is dropped. This lets callers batch many add/delete
+/// operations and decide when to commit, instead of paying for a new
+/// writer (and producing a new segment) per operation. Read-only
+/// users (search, preview) never take the writer lock.
 pub struct TantivyIndex {
     /// Tantivy index instance
     index: Index,
@@ -55,14 +256,15 @@ pub struct TantivyIndex {
     /// Schema definition
     schema: Schema,
 
-    /// Index writer (for adding documents)
-    writer: IndexWriter,
+    /// Index writer (acquired on first write)
+    writer: Option<IndexWriter>,
 }
 
 impl std::fmt::Debug for TantivyIndex {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TantivyIndex")
             .field("schema", &"<schema>")
+            .field("writer_open", &self.writer.is_some())
             .finish()
     }
 }
@@ -70,8 +272,13 @@ impl std::fmt::Debug for TantivyIndex {
 impl TantivyIndex {
     /// Create a new Tantivy index at the given path
     pub fn create(index_dir: &Path) -> Result<Self> {
+        Self::create_with(index_dir, true)
+    }
+
+    /// Create a new Tantivy index, storing chunk text only if `store_chunk_text`
+    pub fn create_with(index_dir: &Path, store_chunk_text: bool) -> Result<Self> {
         // Create schema
-        let schema = create_schema();
+        let schema = create_schema_for(store_chunk_text);
 
         // Create index directory
         std::fs::create_dir_all(index_dir)?;
@@ -80,15 +287,31 @@ impl TantivyIndex {
         let index = Index::create_in_dir(index_dir, schema.clone())
             .map_err(|e| ShebeError::StorageError(format!("Failed to create index: {e}")))?;
 
-        // Create index writer (50MB heap)
-        let writer = index
-            .writer(50_000_000)
-            .map_err(|e| ShebeError::StorageError(format!("Failed to create writer: {e}")))?;
+        Ok(Self {
+            index,
+            schema,
+            writer: None,
+        })
+    }
+
+    /// Open an existing index and check that its segments can be read
+    ///
+    /// Unlike [`Self::open`], this creates a reader, which opens every
+    /// segment file, so a truncated or missing file fails here instead
+    /// of in the first search. The Tantivy error is returned as is for
+    /// [`is_corruption`].
+    pub fn open_verified(index_dir: &Path) -> tantivy::Result<Self> {
+        let index = Index::open_in_dir(index_dir)?;
+        index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let schema = index.schema();
 
         Ok(Self {
             index,
             schema,
-            writer,
+            writer: None,
         })
     }
 
@@ -99,19 +322,72 @@ impl TantivyIndex {
 
         let schema = index.schema();
 
-        let writer = index
-            .writer(50_000_000)
-            .map_err(|e| ShebeError::StorageError(format!("Failed to create writer: {e}")))?;
-
         Ok(Self {
             index,
             schema,
-            writer,
+            writer: None,
         })
     }
 
+    /// Get the index writer, acquiring it on first use
+    ///
+    /// Only one writer may exist per index directory. If another
+    /// process or operation holds it, this returns
+    /// [`ShebeError::IndexLocked`].
+    fn writer_mut(&mut self) -> Result<&mut IndexWriter> {
+        if self.writer.is_none() {
+            let writer = self.index.writer(WRITER_HEAP_BYTES).map_err(|e| match e {
+                tantivy::TantivyError::LockFailure(..) => ShebeError::IndexLocked(
+                    "another indexing or compaction operation is writing to this index. \
+                     Wait for it to finish and retry."
+                        .to_string(),
+                ),
+                other => ShebeError::StorageError(format!("Failed to create writer: {other}")),
+            })?;
+            self.writer = Some(writer);
+        }
+
+        Ok(self.writer.as_mut().expect("writer acquired above"))
+    }
+
+    /// Check whether a writer is currently held
+    pub fn has_writer(&self) -> bool {
+        self.writer.is_some()
+    }
+
     /// Add chunks to the index (batch operation)
+    ///
+    /// Changes are not visible to readers until [`TantivyIndex::commit`].
+    ///
+    /// Each file's chunks must be in order with contiguous `chunk_index`
+    /// values from 0 (a batch may start partway through a file);
+    /// lookups by `(file_path, chunk_index)` rely on it. Checked with a
+    /// debug assertion.
+    ///
+    /// Chunks get no `modified_at`; use [`TantivyIndex::add_chunks_with_times`]
+    /// to record when their files last changed.
     pub fn add_chunks(&mut self, chunks: &[Chunk], session_id: &str) -> Result<()> {
+        self.add_chunks_with_times(chunks, session_id, &ModifiedTimes::default())
+    }
+
+    /// Add chunks, recording each file's last change from `times`
+    ///
+    /// Same as [`TantivyIndex::add_chunks`]; chunks of files missing
+    /// from `times` get no `modified_at`.
+    pub fn add_chunks_with_times(
+        &mut self,
+        chunks: &[Chunk],
+        session_id: &str,
+        times: &ModifiedTimes,
+    ) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        debug_assert!(
+            contiguous_chunk_indexes(chunks),
+            "chunk_index must be contiguous from 0 per file"
+        );
+
         // Get schema fields
         let text_field = self
             .schema
@@ -121,6 +397,10 @@ impl TantivyIndex {
             .schema
             .get_field("file_path")
             .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+        let path_tokens_field = self
+            .schema
+            .get_field("path_tokens")
+            .map_err(|e| ShebeError::StorageError(format!("Missing path_tokens field: {e}")))?;
         let session_field = self
             .schema
             .get_field("session")
@@ -137,29 +417,67 @@ impl TantivyIndex {
             .schema
             .get_field("chunk_index")
             .map_err(|e| ShebeError::StorageError(format!("Missing chunk_index field: {e}")))?;
+        let char_count_field = self
+            .schema
+            .get_field("char_count")
+            .map_err(|e| ShebeError::StorageError(format!("Missing char_count field: {e}")))?;
+        let line_count_field = self
+            .schema
+            .get_field("line_count")
+            .map_err(|e| ShebeError::StorageError(format!("Missing line_count field: {e}")))?;
+        let modified_at_field = self
+            .schema
+            .get_field("modified_at")
+            .map_err(|e| ShebeError::StorageError(format!("Missing modified_at field: {e}")))?;
+        let record_start_field = self
+            .schema
+            .get_field("record_start")
+            .map_err(|e| ShebeError::StorageError(format!("Missing record_start field: {e}")))?;
+        let record_end_field = self
+            .schema
+            .get_field("record_end")
+            .map_err(|e| ShebeError::StorageError(format!("Missing record_end field: {e}")))?;
+        let section_field = self
+            .schema
+            .get_field("section")
+            .map_err(|e| ShebeError::StorageError(format!("Missing section field: {e}")))?;
         let indexed_at_field = self
             .schema
             .get_field("indexed_at")
             .map_err(|e| ShebeError::StorageError(format!("Missing indexed_at field: {e}")))?;
 
         let now = Utc::now();
+        let writer = self.writer_mut()?;
 
         // Add each chunk as a document
         for chunk in chunks {
-            let doc = doc!(
+            let file_path = paths::normalize_path(&chunk.file_path);
+            let mut doc = doc!(
                 text_field => chunk.text.as_str(),
-                file_path_field =>
-                    chunk.file_path.to_str().unwrap_or(""),
+                file_path_field => file_path.as_str(),
+                path_tokens_field => file_path.as_str(),
                 session_field => session_id,
                 offset_start_field => chunk.start_offset as i64,
                 offset_end_field => chunk.end_offset as i64,
                 chunk_index_field => chunk.chunk_index as i64,
+                char_count_field => chunk.text.chars().count() as u64,
+                line_count_field => chunk.text.lines().count() as u64,
                 indexed_at_field => tantivy::DateTime::from_timestamp_secs(
                     now.timestamp()
                 ),
             );
-
-            self.writer
+            if let Some(modified_at) = times.get(&chunk.file_path) {
+                doc.add_i64(modified_at_field, modified_at);
+            }
+            if let Some(records) = chunk.records {
+                doc.add_u64(record_start_field, records.first as u64);
+                doc.add_u64(record_end_field, records.last as u64);
+            }
+            if let Some(section) = &chunk.section {
+                doc.add_text(section_field, section);
+            }
+
+            writer
                 .add_document(doc)
                 .map_err(|e| ShebeError::StorageError(format!("Failed to add document: {e}")))?;
         }
@@ -167,14 +485,342 @@ impl TantivyIndex {
         Ok(())
     }
 
+    /// Delete all chunks belonging to a file
+    ///
+    /// Changes are not visible to readers until [`TantivyIndex::commit`].
+    pub fn delete_file(&mut self, file_path: &str) -> Result<()> {
+        let file_path_field = self
+            .schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+
+        let term = Term::from_field_text(file_path_field, &paths::normalize(file_path));
+        self.writer_mut()?.delete_term(term);
+
+        Ok(())
+    }
+
     /// Commit changes to disk
+    ///
+    /// The writer stays open so further operations can be batched
+    /// into the next commit.
     pub fn commit(&mut self) -> Result<()> {
-        self.writer
+        self.writer_mut()?
             .commit()
             .map_err(|e| ShebeError::StorageError(format!("Failed to commit: {e}")))?;
         Ok(())
     }
 
+    /// Release the writer, waiting for background merges to finish
+    ///
+    /// Uncommitted changes are discarded.
+    pub fn release_writer(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer
+                .wait_merging_threads()
+                .map_err(|e| ShebeError::StorageError(format!("Failed to release writer: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// Number of searchable segments in the index
+    pub fn segment_count(&self) -> Result<usize> {
+        self.index
+            .searchable_segment_ids()
+            .map(|ids| ids.len())
+            .map_err(|e| ShebeError::StorageError(format!("Failed to list segments: {e}")))
+    }
+
+    /// Number of indexed chunks per file path
+    pub fn file_chunk_counts(&self) -> Result<BTreeMap<String, usize>> {
+        Ok(self
+            .file_chunk_indexes()?
+            .into_iter()
+            .map(|(path, indexes)| (path, indexes.len()))
+            .collect())
+    }
+
+    /// Indexed `chunk_index` values per file path, sorted
+    ///
+    /// Reads every stored document; used for consistency checks.
+    pub fn file_chunk_indexes(&self) -> Result<BTreeMap<String, Vec<usize>>> {
+        let file_path_field = self
+            .schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+        let chunk_index_field = self
+            .schema
+            .get_field("chunk_index")
+            .map_err(|e| ShebeError::StorageError(format!("Missing chunk_index field: {e}")))?;
+
+        let searcher = self.reader()?.searcher();
+        let doc_addresses = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .map_err(|e| ShebeError::StorageError(format!("Failed to list documents: {e}")))?;
+
+        let mut indexes: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for address in doc_addresses {
+            let doc: TantivyDocument = searcher
+                .doc(address)
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read document: {e}")))?;
+            if let Some(path) = doc.get_first(file_path_field).and_then(|v| v.as_str()) {
+                let chunk_index = doc
+                    .get_first(chunk_index_field)
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(-1);
+                indexes
+                    .entry(path.to_string())
+                    .or_default()
+                    .push(usize::try_from(chunk_index).unwrap_or(usize::MAX));
+            }
+        }
+        for chunk_indexes in indexes.values_mut() {
+            chunk_indexes.sort_unstable();
+        }
+
+        Ok(indexes)
+    }
+
+    /// Chunk count and bytes covered (highest `offset_end`) per file path
+    ///
+    /// Reads every stored document; sessions with a file manifest list
+    /// their files without it.
+    pub fn file_sizes(&self) -> Result<BTreeMap<String, (usize, u64)>> {
+        let file_path_field = self
+            .schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+        let offset_end_field = self
+            .schema
+            .get_field("offset_end")
+            .map_err(|e| ShebeError::StorageError(format!("Missing offset_end field: {e}")))?;
+
+        let searcher = self.reader()?.searcher();
+        let doc_addresses = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .map_err(|e| ShebeError::StorageError(format!("Failed to list documents: {e}")))?;
+
+        let mut sizes: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+        for address in doc_addresses {
+            let doc: TantivyDocument = searcher
+                .doc(address)
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read document: {e}")))?;
+            if let Some(path) = doc.get_first(file_path_field).and_then(|v| v.as_str()) {
+                let end = doc
+                    .get_first(offset_end_field)
+                    .and_then(|v| v.as_i64())
+                    .and_then(|end| u64::try_from(end).ok())
+                    .unwrap_or(0);
+                let (chunks, size) = sizes.entry(path.to_string()).or_default();
+                *chunks += 1;
+                *size = (*size).max(end);
+            }
+        }
+
+        Ok(sizes)
+    }
+
+    /// Text of the lowest-numbered chunk of `file_path`, read with
+    /// `searcher` (from this index)
+    ///
+    /// `None` if the file has no chunks or chunk text is not stored.
+    pub fn first_chunk_text(&self, searcher: &Searcher, file_path: &str) -> Result<Option<String>> {
+        let (Ok(text_field), Ok(file_path_field), Ok(chunk_index_field)) = (
+            self.schema.get_field("text"),
+            self.schema.get_field("file_path"),
+            self.schema.get_field("chunk_index"),
+        ) else {
+            return Ok(None);
+        };
+        if !self.stores_chunk_text() {
+            return Ok(None);
+        }
+
+        let query = TermQuery::new(
+            Term::from_field_text(file_path_field, file_path),
+            Default::default(),
+        );
+        let doc_addresses = searcher
+            .search(&query, &DocSetCollector)
+            .map_err(|e| ShebeError::StorageError(format!("Failed to list chunks: {e}")))?;
+
+        let mut first: Option<(i64, String)> = None;
+        for address in doc_addresses {
+            let doc: TantivyDocument = searcher
+                .doc(address)
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read document: {e}")))?;
+            let chunk_index = doc
+                .get_first(chunk_index_field)
+                .and_then(|v| v.as_i64())
+                .unwrap_or(i64::MAX);
+            if first
+                .as_ref()
+                .is_some_and(|(index, _)| *index <= chunk_index)
+            {
+                continue;
+            }
+            if let Some(text) = doc.get_first(text_field).and_then(|v| v.as_str()) {
+                first = Some((chunk_index, text.to_string()));
+            }
+        }
+
+        Ok(first.map(|(_, text)| text))
+    }
+
+    /// Paths of the indexed files, sorted, read from the `file_path`
+    /// term dictionary (no document is loaded)
+    ///
+    /// A file deleted since the last merge can still be listed.
+    pub fn indexed_file_paths(&self, searcher: &Searcher) -> Result<Vec<String>> {
+        let file_path_field = self
+            .schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+
+        let mut paths = std::collections::BTreeSet::new();
+        for segment in searcher.segment_readers() {
+            let inverted = segment
+                .inverted_index(file_path_field)
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read file paths: {e}")))?;
+            let mut stream = inverted
+                .terms()
+                .stream()
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read file paths: {e}")))?;
+            while stream.advance() {
+                if let Ok(path) = std::str::from_utf8(stream.key()) {
+                    paths.insert(path.to_string());
+                }
+            }
+        }
+        Ok(paths.into_iter().collect())
+    }
+
+    /// Offsets (and stored text) of the chunks of `file_path`, in
+    /// chunk order, read with `searcher` (from this index)
+    pub fn file_chunk_spans(&self, searcher: &Searcher, file_path: &str) -> Result<Vec<ChunkSpan>> {
+        let field = |name: &str| {
+            self.schema
+                .get_field(name)
+                .map_err(|e| ShebeError::StorageError(format!("Missing {name} field: {e}")))
+        };
+        let file_path_field = field("file_path")?;
+        let chunk_index_field = field("chunk_index")?;
+        let offset_start_field = field("offset_start")?;
+        let offset_end_field = field("offset_end")?;
+        let text_field = field("text")?;
+
+        let query = TermQuery::new(
+            Term::from_field_text(file_path_field, file_path),
+            Default::default(),
+        );
+        let doc_addresses = searcher
+            .search(&query, &DocSetCollector)
+            .map_err(|e| ShebeError::StorageError(format!("Failed to list chunks: {e}")))?;
+
+        let offset = |doc: &TantivyDocument, field: Field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_i64())
+                .and_then(|v| usize::try_from(v).ok())
+                .unwrap_or(0)
+        };
+        let mut spans = Vec::with_capacity(doc_addresses.len());
+        for address in doc_addresses {
+            let doc: TantivyDocument = searcher
+                .doc(address)
+                .map_err(|e| ShebeError::StorageError(format!("Failed to read document: {e}")))?;
+            spans.push(ChunkSpan {
+                chunk_index: offset(&doc, chunk_index_field),
+                start_offset: offset(&doc, offset_start_field),
+                end_offset: offset(&doc, offset_end_field),
+                text: doc
+                    .get_first(text_field)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            });
+        }
+        spans.sort_by_key(|span| span.chunk_index);
+        Ok(spans)
+    }
+
+    /// Query matching the chunk at `chunk_index` of `file_path`
+    ///
+    /// `file_path` must be normalized as stored. This is how
+    /// `preview_chunk` finds a chunk.
+    pub fn chunk_query(&self, file_path: &str, chunk_index: usize) -> Result<BooleanQuery> {
+        let file_path_field = self
+            .schema
+            .get_field("file_path")
+            .map_err(|e| ShebeError::StorageError(format!("Missing file_path field: {e}")))?;
+        let chunk_index_field = self
+            .schema
+            .get_field("chunk_index")
+            .map_err(|e| ShebeError::StorageError(format!("Missing chunk_index field: {e}")))?;
+
+        let file_term = Term::from_field_text(file_path_field, file_path);
+        let chunk_term = Term::from_field_i64(chunk_index_field, chunk_index as i64);
+        let file_query: Box<dyn Query> = Box::new(TermQuery::new(file_term, Default::default()));
+        let chunk_query: Box<dyn Query> = Box::new(TermQuery::new(chunk_term, Default::default()));
+
+        Ok(BooleanQuery::new(vec![
+            (Occur::Must, file_query),
+            (Occur::Must, chunk_query),
+        ]))
+    }
+
+    /// Merge all searchable segments into one and remove obsolete files
+    ///
+    /// Pending changes are committed first. Returns the segment count
+    /// after merging.
+    pub fn merge_segments(&mut self) -> Result<usize> {
+        self.merge_segments_to(1)
+    }
+
+    /// Merge searchable segments down to at most `target` and remove
+    /// obsolete files
+    ///
+    /// The smallest segments are merged into one, together with every
+    /// segment holding deleted documents so those are purged. Pending
+    /// changes are committed first. Returns the segment count after
+    /// merging.
+    pub fn merge_segments_to(&mut self, target: usize) -> Result<usize> {
+        self.commit()?;
+
+        // Wait for background merges so none of the segments below
+        // are already claimed by another merge operation
+        self.release_writer()?;
+
+        // Only the ids are kept: live `SegmentMeta`s pin their files,
+        // which would stop the garbage collection below
+        let segment_ids: Vec<_> = {
+            let mut metas = self
+                .index
+                .searchable_segment_metas()
+                .map_err(|e| ShebeError::StorageError(format!("Failed to list segments: {e}")))?;
+            metas.sort_by_key(|meta| (!meta.has_deletes(), meta.num_docs()));
+
+            let with_deletes = metas.iter().filter(|meta| meta.has_deletes()).count();
+            let excess = (metas.len() + 1).saturating_sub(target.max(1));
+            let merged = if excess > 1 { excess } else { 0 }.max(with_deletes);
+            metas[..merged].iter().map(|meta| meta.id()).collect()
+        };
+
+        if !segment_ids.is_empty() {
+            self.writer_mut()?
+                .merge(&segment_ids)
+                .wait()
+                .map_err(|e| ShebeError::StorageError(format!("Failed to merge segments: {e}")))?;
+        }
+
+        // Let any follow-up merges settle before removing stale files
+        self.release_writer()?;
+        self.writer_mut()?
+            .garbage_collect_files()
+            .wait()
+            .map_err(|e| ShebeError::StorageError(format!("Failed to collect garbage: {e}")))?;
+
+        self.segment_count()
+    }
+
     /// Get an index reader for searching
     pub fn reader(&self) -> Result<IndexReader> {
         self.index
@@ -187,12 +833,32 @@ impl TantivyIndex {
         &self.schema
     }
 
+    /// Whether chunk text is stored (false for no-store sessions)
+    pub fn stores_chunk_text(&self) -> bool {
+        self.schema
+            .get_field("text")
+            .map(|field| self.schema.get_field_entry(field).is_stored())
+            .unwrap_or(false)
+    }
+
     /// Get a reference to the underlying Tantivy index
     pub fn index(&self) -> &Index {
         &self.index
     }
 }
 
+/// Whether each file's chunks follow each other with `chunk_index`
+/// counting up by one, starting from 0 for every file after the first
+fn contiguous_chunk_indexes(chunks: &[Chunk]) -> bool {
+    chunks.windows(2).all(|pair| {
+        if pair[1].file_path == pair[0].file_path {
+            pair[1].chunk_index == pair[0].chunk_index + 1
+        } else {
+            pair[1].chunk_index == 0
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,15 +867,22 @@ mod tests {
 
     #[test]
     fn test_schema_has_all_fields() {
-        let schema = create_schema();
+        let schema = create_schema_for(true);
 
-        // Verify all 7 fields exist
+        // Verify all 13 fields exist
         assert!(schema.get_field("text").is_ok());
         assert!(schema.get_field("file_path").is_ok());
         assert!(schema.get_field("session").is_ok());
+        assert!(schema.get_field("path_tokens").is_ok());
         assert!(schema.get_field("offset_start").is_ok());
         assert!(schema.get_field("offset_end").is_ok());
         assert!(schema.get_field("chunk_index").is_ok());
+        assert!(schema.get_field("char_count").is_ok());
+        assert!(schema.get_field("line_count").is_ok());
+        assert!(schema.get_field("modified_at").is_ok());
+        assert!(schema.get_field("record_start").is_ok());
+        assert!(schema.get_field("record_end").is_ok());
+        assert!(schema.get_field("section").is_ok());
         assert!(schema.get_field("indexed_at").is_ok());
     }
 
@@ -240,6 +913,8 @@ mod tests {
             start_offset: 0,
             end_offset: 12,
             chunk_index: 0,
+            records: None,
+            section: None,
         };
 
         index.add_chunks(&[chunk], "test-session").unwrap();
@@ -266,6 +941,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 7,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
             Chunk {
                 text: "chunk 2".to_string(),
@@ -273,6 +950,8 @@ mod tests {
                 start_offset: 7,
                 end_offset: 14,
                 chunk_index: 1,
+                records: None,
+                section: None,
             },
             Chunk {
                 text: "chunk 3".to_string(),
@@ -280,6 +959,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 7,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
         ];
 
@@ -312,7 +993,7 @@ mod tests {
 
     #[test]
     fn test_chunk_index_is_indexed() {
-        let schema = create_schema();
+        let schema = create_schema_for(true);
         let chunk_index_field = schema.get_field("chunk_index").unwrap();
         let field_entry = schema.get_field_entry(chunk_index_field);
 
@@ -325,13 +1006,76 @@ mod tests {
 
     #[test]
     fn test_schema_version_constant() {
-        // Verify schema version is set to 2 after adding INDEXED flag to chunk_index
         assert_eq!(
-            SCHEMA_VERSION, 3,
-            "SCHEMA_VERSION should be 3 after adding repository_path and patterns"
+            SCHEMA_VERSION, 9,
+            "SCHEMA_VERSION should be 9 after adding the section field"
         );
     }
 
+    #[test]
+    fn test_no_store_schema_indexes_text_only() {
+        let schema = create_schema_for(false);
+        let text = schema.get_field_entry(schema.get_field("text").unwrap());
+        assert!(text.is_indexed());
+        assert!(!text.is_stored());
+        assert_ne!(current_schema_hash_for(false), current_schema_hash());
+
+        let temp_dir = tempdir().unwrap();
+        let mut index = TantivyIndex::create_with(&temp_dir.path().join("idx"), false).unwrap();
+        assert!(!index.stores_chunk_text());
+        index
+            .add_chunks(&[small_chunk("/a.rs", "needle")], "s")
+            .unwrap();
+        index.commit().unwrap();
+
+        // Searchable, but the text is not returned
+        assert_eq!(search_files(&index, "needle"), vec!["/a.rs".to_string()]);
+        let searcher = index.reader().unwrap().searcher();
+        let doc: TantivyDocument = searcher.doc(tantivy::DocAddress::new(0, 0)).unwrap();
+        assert!(doc.get_first(schema.get_field("text").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_schema_hash_is_stable() {
+        assert_eq!(schema_hash(&create_schema_for(true)), current_schema_hash());
+        assert_eq!(current_schema_hash().len(), 16);
+
+        let mut builder = Schema::builder();
+        builder.add_text_field("text", TEXT);
+        assert_ne!(schema_hash(&builder.build()), current_schema_hash());
+    }
+
+    #[test]
+    fn test_index_settings_describe_fields() {
+        let settings = IndexSettings::current();
+        let text = settings.fields.iter().find(|f| f.name == "text").unwrap();
+        let file_path = settings
+            .fields
+            .iter()
+            .find(|f| f.name == "file_path")
+            .unwrap();
+
+        assert_eq!(text.field_type, "Str");
+        assert_eq!(text.tokenizer.as_deref(), Some("default"));
+        assert!(text.indexed && text.stored);
+        assert_eq!(file_path.tokenizer.as_deref(), Some("raw"));
+    }
+
+    #[test]
+    fn test_index_settings_diff() {
+        let current = IndexSettings::current();
+        assert!(current.diff(&current).is_empty());
+
+        let mut older = current.clone();
+        older.fields.retain(|f| f.name != "path_tokens");
+        older.fields[0].stored = false;
+
+        let changes = older.diff(&current);
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].starts_with("field changed: text (Str, indexed, tokenizer=default)"));
+        assert!(changes[1].starts_with("field added: path_tokens"));
+    }
+
     // --- Phase 1C: Boundary tests ---
 
     #[test]
@@ -371,6 +1115,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 40,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
             Chunk {
                 text: "fn goodbye() { println!(\"bye\"); }".to_string(),
@@ -378,6 +1124,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 34,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
         ];
 
@@ -406,9 +1154,195 @@ mod tests {
         let index = TantivyIndex::create(&index_dir).unwrap();
 
         let debug_str = format!("{:?}", index);
+        assert!(debug_str.contains("writer_open"));
         assert!(
             debug_str.contains("TantivyIndex"),
             "Debug output should contain struct name"
         );
     }
+
+    fn small_chunk(file: &str, text: &str) -> Chunk {
+        Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(file),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        }
+    }
+
+    fn search_files(index: &TantivyIndex, query: &str) -> Vec<String> {
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let text_field = index.schema().get_field("text").unwrap();
+        let file_field = index.schema().get_field("file_path").unwrap();
+        let parser = tantivy::query::QueryParser::for_index(index.index(), vec![text_field]);
+        let query = parser.parse_query(query).unwrap();
+        let mut files: Vec<String> = searcher
+            .search(&query, &tantivy::collector::TopDocs::with_limit(100))
+            .unwrap()
+            .into_iter()
+            .map(|(_, addr)| {
+                let doc: tantivy::TantivyDocument = searcher.doc(addr).unwrap();
+                doc.get_first(file_field)
+                    .and_then(|v| v.as_str())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_open_does_not_acquire_writer() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("lazy_index");
+        let mut index = TantivyIndex::create(&index_dir).unwrap();
+        index.commit().unwrap();
+        assert!(index.has_writer());
+
+        // A reader-only open must not contend for the writer lock
+        let reopened = TantivyIndex::open(&index_dir).unwrap();
+        assert!(!reopened.has_writer());
+    }
+
+    #[test]
+    fn test_concurrent_writer_is_rejected() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("locked_index");
+        let mut first = TantivyIndex::create(&index_dir).unwrap();
+        first.commit().unwrap();
+
+        let mut second = TantivyIndex::open(&index_dir).unwrap();
+        let err = second
+            .add_chunks(&[small_chunk("/a.rs", "alpha")], "s")
+            .unwrap_err();
+        assert!(matches!(err, ShebeError::IndexLocked(_)), "got: {err:?}");
+
+        // Releasing the first writer frees the lock
+        first.release_writer().unwrap();
+        assert!(second
+            .add_chunks(&[small_chunk("/a.rs", "alpha")], "s")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_writer_reused_across_commits() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("reuse_index");
+        let mut index = TantivyIndex::create(&index_dir).unwrap();
+
+        for i in 0..5 {
+            let file = format!("/src/file{i}.rs");
+            index
+                .add_chunks(&[small_chunk(&file, "shared_token")], "s")
+                .unwrap();
+            index.commit().unwrap();
+        }
+
+        assert_eq!(search_files(&index, "shared_token").len(), 5);
+    }
+
+    #[test]
+    fn test_delete_file_removes_chunks() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("delete_index");
+        let mut index = TantivyIndex::create(&index_dir).unwrap();
+
+        index
+            .add_chunks(
+                &[
+                    small_chunk("/keep.rs", "needle"),
+                    small_chunk("/drop.rs", "needle"),
+                ],
+                "s",
+            )
+            .unwrap();
+        index.commit().unwrap();
+
+        index.delete_file("/drop.rs").unwrap();
+        index.commit().unwrap();
+
+        assert_eq!(search_files(&index, "needle"), vec!["/keep.rs".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_segments_bounds_segment_count() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("merge_index");
+        let mut index = TantivyIndex::create(&index_dir).unwrap();
+
+        // Many small commits produce many small segments
+        for i in 0..12 {
+            let file = format!("/src/file{i}.rs");
+            let text = format!("common_term unique{i}");
+            index.add_chunks(&[small_chunk(&file, &text)], "s").unwrap();
+            index.commit().unwrap();
+        }
+
+        let before = search_files(&index, "common_term");
+        let after_count = index.merge_segments().unwrap();
+
+        assert_eq!(after_count, 1);
+        assert_eq!(index.segment_count().unwrap(), 1);
+        assert_eq!(search_files(&index, "common_term"), before);
+        assert_eq!(before.len(), 12);
+    }
+
+    #[test]
+    fn test_file_chunk_counts() {
+        let temp_dir = tempdir().unwrap();
+        let index_dir = temp_dir.path().join("counts_index");
+        let mut index = TantivyIndex::create(&index_dir).unwrap();
+
+        index
+            .add_chunks(
+                &[
+                    small_chunk("/a.rs", "one"),
+                    Chunk {
+                        chunk_index: 1,
+                        ..small_chunk("/a.rs", "two")
+                    },
+                    small_chunk("/b.rs", "three"),
+                ],
+                "s",
+            )
+            .unwrap();
+        index.commit().unwrap();
+
+        let counts = index.file_chunk_counts().unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["/a.rs"], 2);
+        assert_eq!(counts["/b.rs"], 1);
+
+        let indexes = index.file_chunk_indexes().unwrap();
+        assert_eq!(indexes["/a.rs"], vec![0, 1]);
+        assert_eq!(indexes["/b.rs"], vec![0]);
+    }
+
+    #[test]
+    fn test_contiguous_chunk_indexes() {
+        let chunk = |file: &str, chunk_index: usize| Chunk {
+            chunk_index,
+            ..small_chunk(file, "x")
+        };
+        assert!(contiguous_chunk_indexes(&[]));
+        // A batch may start partway through a file
+        assert!(contiguous_chunk_indexes(&[
+            chunk("/a.rs", 4),
+            chunk("/a.rs", 5),
+            chunk("/b.rs", 0),
+        ]));
+        assert!(!contiguous_chunk_indexes(&[
+            chunk("/a.rs", 0),
+            chunk("/a.rs", 2)
+        ]));
+        assert!(!contiguous_chunk_indexes(&[
+            chunk("/a.rs", 0),
+            chunk("/b.rs", 1)
+        ]));
+    }
 }
@@ -0,0 +1,608 @@
+//! Migration of sessions indexed with an older schema version.
+//!
+//! [`StorageManager::upgrade_session`] picks the cheapest way to bring a
+//! session to [`SCHEMA_VERSION`]:
+//!
+//! - **Metadata rewrite**: the index on disk already has the current
+//!   schema, so `meta.json` is rewritten in place, with fields missing
+//!   from older metadata filled in
+//! - **Re-index**: the index schema changed, so the repository is
+//!   indexed again with the session's stored configuration
+//!
+//! When a re-index is needed but the repository cannot be found, the
+//! upgrade fails and lists what is missing.
+//!
+//! Older `meta.json` files are read as plain JSON, since they may lack
+//! fields that [`SessionMetadata`] requires (`repository_path`,
+//! `last_indexed_at` and the config patterns were added in v3).
+
+use super::session::{SessionConfig, SessionMetadata, StorageManager, CURRENT_VERSION, UNKNOWN};
+use super::tantivy::{current_schema_hash_for, schema_hash, IndexSettings, TantivyIndex};
+use super::SCHEMA_VERSION;
+use crate::core::archive::ArchiveKind;
+use crate::core::error::{Result, ShebeError};
+use crate::core::types::IndexStats;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Max file size used when re-indexing (MB)
+const REINDEX_MAX_FILE_SIZE_MB: usize = 100;
+
+/// How a session was brought to the current schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeStrategy {
+    /// Already current (or newer); nothing was changed
+    UpToDate,
+    /// Only `meta.json` was rewritten; the index was kept
+    MetadataRewrite,
+    /// The repository was indexed again
+    Reindex,
+}
+
+impl UpgradeStrategy {
+    /// Short name for tool output
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::UpToDate => "none",
+            Self::MetadataRewrite => "metadata rewrite",
+            Self::Reindex => "re-index",
+        }
+    }
+}
+
+/// Outcome of [`StorageManager::upgrade_session`]
+#[derive(Debug, Clone)]
+pub struct UpgradeReport {
+    /// Strategy applied
+    pub strategy: UpgradeStrategy,
+    /// Schema version before the upgrade
+    pub from_version: u32,
+    /// Schema version after the upgrade
+    pub to_version: u32,
+    /// What changed; for `UpToDate`, differences that need no upgrade
+    pub changes: Vec<String>,
+    /// Repository the session was re-indexed from (`Reindex` only)
+    pub repository_path: Option<PathBuf>,
+    /// Statistics of the re-index (`Reindex` only)
+    pub stats: Option<IndexStats>,
+}
+
+/// What the index on disk looks like
+struct IndexFacts {
+    schema_hash: String,
+    settings: IndexSettings,
+    /// Chunks per indexed file path
+    files: BTreeMap<String, usize>,
+}
+
+impl IndexFacts {
+    fn read(index: &TantivyIndex) -> Result<Self> {
+        Ok(Self {
+            schema_hash: schema_hash(index.schema()),
+            settings: IndexSettings::from_schema(index.schema()),
+            files: index.file_chunk_counts()?,
+        })
+    }
+}
+
+/// Metadata read from a `meta.json` of any schema version
+struct MigratedMetadata {
+    metadata: SessionMetadata,
+    /// Fields that were missing and have been filled in
+    filled: Vec<String>,
+}
+
+impl StorageManager {
+    /// Bring a session to the current schema version
+    ///
+    /// Rewrites `meta.json` when the index already has the current
+    /// schema, and otherwise re-indexes the stored repository path with
+    /// the stored configuration (and remote source). Sessions from a
+    /// newer schema version are left alone. A relative or `..` repository
+    /// path stored by an older version is resolved against the current
+    /// directory and stored that way.
+    ///
+    /// # Errors
+    ///
+    /// `SessionNotFound` if the session does not exist, and
+    /// `InvalidSession` listing what is missing when the session cannot
+    /// be upgraded automatically.
+    pub fn upgrade_session(&self, session_id: &str) -> Result<UpgradeReport> {
+        self.ensure_writable("upgrade", session_id)?;
+        let meta_path = self.metadata_path(session_id);
+        if !meta_path.exists() {
+            return Err(ShebeError::SessionNotFound(session_id.to_string()));
+        }
+
+        let raw: Value = serde_json::from_str(&fs::read_to_string(&meta_path)?).map_err(|e| {
+            ShebeError::InvalidSession(format!(
+                "Session '{session_id}' cannot be upgraded: meta.json is not valid JSON ({e}). \
+                 Delete the session and index the repository again."
+            ))
+        })?;
+        let from_version = raw
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .map_or(1, |v| v as u32);
+
+        if from_version > SCHEMA_VERSION {
+            return Ok(UpgradeReport {
+                strategy: UpgradeStrategy::UpToDate,
+                from_version,
+                to_version: from_version,
+                changes: Vec::new(),
+                repository_path: None,
+                stats: None,
+            });
+        }
+
+        let store_chunk_text = raw
+            .pointer("/config/store_chunk_text")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let index = TantivyIndex::open(&self.tantivy_dir(session_id))
+            .and_then(|index| IndexFacts::read(&index));
+        let index_current = matches!(
+            &index,
+            Ok(facts) if facts.schema_hash == current_schema_hash_for(store_chunk_text)
+        );
+        let impossible = |missing: &[String]| {
+            ShebeError::InvalidSession(impossible_message(
+                session_id,
+                from_version,
+                index_current,
+                index.as_ref().err(),
+                missing,
+            ))
+        };
+
+        // Every strategy needs complete metadata; a re-index also needs
+        // the repository
+        let MigratedMetadata {
+            mut metadata,
+            mut filled,
+        } = migrate_metadata(session_id, raw, index.as_ref().ok())
+            .map_err(|missing| impossible(&missing))?;
+        if let Some(canonical) = canonical_stored_path(&metadata.repository_path) {
+            filled.push(format!(
+                "repository_path: {} -> {}",
+                metadata.repository_path.display(),
+                canonical.display()
+            ));
+            metadata.repository_path = canonical;
+        }
+        if !index_current {
+            if let Some(problem) = repository_problem(&metadata.repository_path) {
+                return Err(impossible(&[problem]));
+            }
+        }
+
+        if index_current {
+            let changes = metadata_changes(&metadata, from_version, &filled);
+            if filled.is_empty() && changes.is_empty() {
+                return Ok(UpgradeReport {
+                    strategy: UpgradeStrategy::UpToDate,
+                    from_version,
+                    to_version: SCHEMA_VERSION,
+                    changes: created_by_change(&metadata).into_iter().collect(),
+                    repository_path: None,
+                    stats: None,
+                });
+            }
+
+            metadata.schema_version = SCHEMA_VERSION;
+            metadata.tantivy_schema_hash = current_schema_hash_for(store_chunk_text);
+            metadata.index_settings = Some(IndexSettings::current_for(store_chunk_text));
+            self.update_session_metadata(session_id, &metadata)?;
+            self.refresh_index_size(session_id)?;
+
+            return Ok(UpgradeReport {
+                strategy: UpgradeStrategy::MetadataRewrite,
+                from_version,
+                to_version: SCHEMA_VERSION,
+                changes: filled.into_iter().chain(changes).collect(),
+                repository_path: None,
+                stats: None,
+            });
+        }
+
+        let facts = index.as_ref().ok();
+        let mut changes = filled;
+        changes.extend(index_changes(&metadata, from_version, facts));
+
+        // `force` replaces the old session only once the repository has
+        // been read, so a failed re-index leaves it in place
+        let repository_path = metadata.repository_path.clone();
+        let stats = self.index_repository_with_config(
+            session_id,
+            &repository_path,
+            metadata.config.clone(),
+            REINDEX_MAX_FILE_SIZE_MB,
+            true,
+        )?;
+        // Keep the clone's origin (the checkout itself is re-indexed as is)
+        if metadata.remote.is_some() {
+            self.set_remote_source(session_id, metadata.remote.take())?;
+        }
+
+        Ok(UpgradeReport {
+            strategy: UpgradeStrategy::Reindex,
+            from_version,
+            to_version: SCHEMA_VERSION,
+            changes,
+            repository_path: Some(repository_path),
+            stats: Some(stats),
+        })
+    }
+}
+
+/// Canonical form of a repository path stored by an older version
+///
+/// Sessions used to store the path as given, so relative paths and
+/// paths with `.` or `..` may be on disk. `None` if the stored path is
+/// already clean or no longer resolves to a directory.
+fn canonical_stored_path(path: &Path) -> Option<PathBuf> {
+    use std::path::Component;
+
+    let unclean = !path.is_absolute()
+        || path
+            .components()
+            .any(|c| matches!(c, Component::CurDir | Component::ParentDir));
+    if !unclean {
+        return None;
+    }
+    path.canonicalize()
+        .ok()
+        .filter(|canonical| canonical.is_dir())
+}
+
+/// Read `raw` into [`SessionMetadata`], filling fields older versions lack
+///
+/// Returns the fields that could not be recovered on failure.
+fn migrate_metadata(
+    session_id: &str,
+    mut raw: Value,
+    index: Option<&IndexFacts>,
+) -> std::result::Result<MigratedMetadata, Vec<String>> {
+    let Some(fields) = raw.as_object_mut() else {
+        return Err(vec!["meta.json: not a JSON object".to_string()]);
+    };
+    let mut filled = Vec::new();
+    let mut missing = Vec::new();
+
+    if !fields.contains_key("id") {
+        fields.insert("id".into(), Value::from(session_id));
+        filled.push(format!("id: not recorded -> '{session_id}'"));
+    }
+    if !fields.contains_key("schema_version") {
+        fields.insert("schema_version".into(), Value::from(1));
+        filled.push("schema_version: not recorded -> v1 (assumed)".to_string());
+    }
+    if !fields.contains_key("created_at") {
+        fields.insert("created_at".into(), Value::from(Utc::now().to_rfc3339()));
+        filled.push("created_at: not recorded -> now".to_string());
+    }
+    if !fields.contains_key("last_indexed_at") {
+        let created_at = fields["created_at"].clone();
+        fields.insert("last_indexed_at".into(), created_at);
+        filled.push("last_indexed_at: not recorded -> created_at".to_string());
+    }
+
+    if let Some(index) = index {
+        let counts = [
+            ("files_indexed", index.files.len()),
+            ("chunks_created", index.files.values().sum()),
+        ];
+        for (key, count) in counts {
+            if !fields.contains_key(key) {
+                fields.insert(key.into(), Value::from(count));
+                filled.push(format!("{key}: not recorded -> {count} (counted in index)"));
+            }
+        }
+    }
+    for key in ["files_indexed", "chunks_created", "index_size_bytes"] {
+        if !fields.contains_key(key) {
+            fields.insert(key.into(), Value::from(0));
+            filled.push(format!("{key}: not recorded -> 0"));
+        }
+    }
+
+    fill_config(fields, &mut filled);
+
+    if !fields.contains_key("repository_path") {
+        let inferred = index.and_then(|index| common_directory(index.files.keys()));
+        match inferred {
+            Some(path) => {
+                filled.push(format!(
+                    "repository_path: not recorded -> {} (common directory of indexed files)",
+                    path.display()
+                ));
+                fields.insert(
+                    "repository_path".into(),
+                    Value::from(path.to_string_lossy()),
+                );
+            }
+            None => missing.push(
+                "repository_path: not recorded (added in schema v3) and the index has no \
+                 files to infer it from"
+                    .to_string(),
+            ),
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+    serde_json::from_value(raw)
+        .map(|metadata| MigratedMetadata { metadata, filled })
+        .map_err(|e| vec![format!("meta.json: {e}")])
+}
+
+/// Fill the session config fields older versions lack with defaults
+fn fill_config(fields: &mut Map<String, Value>, filled: &mut Vec<String>) {
+    let defaults = serde_json::to_value(SessionConfig::default()).unwrap_or_default();
+    let Value::Object(defaults) = defaults else {
+        return;
+    };
+
+    let config = fields
+        .entry("config")
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Some(config) = config.as_object_mut() else {
+        return;
+    };
+    for key in [
+        "chunk_size",
+        "overlap",
+        "include_patterns",
+        "exclude_patterns",
+    ] {
+        if let (false, Some(default)) = (config.contains_key(key), defaults.get(key)) {
+            filled.push(format!("config.{key}: not recorded -> {default}"));
+            config.insert(key.into(), default.clone());
+        }
+    }
+}
+
+/// Deepest directory containing every path
+fn common_directory<'a>(paths: impl IntoIterator<Item = &'a String>) -> Option<PathBuf> {
+    let mut paths = paths.into_iter().map(Path::new);
+    let mut common = paths.next()?.parent()?.to_path_buf();
+    for path in paths {
+        while !path.starts_with(&common) {
+            if !common.pop() {
+                return None;
+            }
+        }
+    }
+    // A filesystem root is no repository
+    common.parent().is_some().then_some(common)
+}
+
+/// Why `path` cannot be re-indexed, if it cannot
+fn repository_problem(path: &Path) -> Option<String> {
+    if !path.exists() {
+        Some(format!(
+            "repository_path: {} no longer exists",
+            path.display()
+        ))
+    } else if !path.is_dir() && ArchiveKind::detect(path).is_none() {
+        Some(format!(
+            "repository_path: {} is not a directory",
+            path.display()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Recorded metadata that a rewrite brings up to date
+fn metadata_changes(
+    metadata: &SessionMetadata,
+    from_version: u32,
+    filled: &[String],
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    if from_version != SCHEMA_VERSION {
+        changes.push(format!(
+            "schema version: v{from_version} -> v{SCHEMA_VERSION}"
+        ));
+    }
+
+    let store_chunk_text = metadata.config.store_chunk_text;
+    let current_hash = current_schema_hash_for(store_chunk_text);
+    if metadata.tantivy_schema_hash != current_hash {
+        changes.push(format!(
+            "schema hash: `{}` -> `{}`",
+            metadata.tantivy_schema_hash, current_hash
+        ));
+    }
+    match &metadata.index_settings {
+        None => changes.push("index settings: not recorded -> recorded".to_string()),
+        Some(settings) if *settings != IndexSettings::current_for(store_chunk_text) => {
+            changes.push("index settings: updated".to_string())
+        }
+        Some(_) => {}
+    }
+
+    // Filled fields alone still need the file rewritten
+    if changes.is_empty() && !filled.is_empty() {
+        changes.push("meta.json: rewritten with missing fields".to_string());
+    }
+    changes
+}
+
+/// Differences between the index on disk and a fresh one
+fn index_changes(
+    metadata: &SessionMetadata,
+    from_version: u32,
+    index: Option<&IndexFacts>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    if from_version != SCHEMA_VERSION {
+        changes.push(format!(
+            "schema version: v{from_version} -> v{SCHEMA_VERSION}"
+        ));
+    }
+
+    let store_chunk_text = metadata.config.store_chunk_text;
+    let current = IndexSettings::current_for(store_chunk_text);
+    match index {
+        Some(index) => {
+            changes.push(format!(
+                "schema hash: `{}` -> `{}`",
+                index.schema_hash,
+                current_schema_hash_for(store_chunk_text)
+            ));
+            changes.extend(index.settings.diff(&current));
+        }
+        None => changes.push("index: unreadable -> rebuilt".to_string()),
+    }
+    changes.extend(created_by_change(metadata));
+    changes
+}
+
+fn created_by_change(metadata: &SessionMetadata) -> Option<String> {
+    (metadata.created_by_version != CURRENT_VERSION && metadata.created_by_version != UNKNOWN).then(
+        || {
+            format!(
+                "created by: shebe {} -> shebe {}",
+                metadata.created_by_version, CURRENT_VERSION
+            )
+        },
+    )
+}
+
+/// Error message for a session that cannot be upgraded automatically
+fn impossible_message(
+    session_id: &str,
+    from_version: u32,
+    index_current: bool,
+    index_error: Option<&ShebeError>,
+    missing: &[String],
+) -> String {
+    let reason = match index_error {
+        Some(e) => format!("the index cannot be read ({e}), so it must be rebuilt"),
+        None if index_current => "its metadata cannot be completed".to_string(),
+        None => "the index schema changed, so the repository must be re-indexed".to_string(),
+    };
+    let missing: String = missing.iter().map(|m| format!("- {m}\n")).collect();
+    format!(
+        "Session '{session_id}' (schema v{from_version}) cannot be upgraded automatically: \
+         {reason}.\n\
+         Missing:\n{missing}\
+         Possible solutions:\n\
+         - Move repository back to original location\n\
+         - Delete session and create new one at current location"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use serde_json::json;
+
+    fn facts(paths: &[&str]) -> IndexFacts {
+        let settings = IndexSettings::current();
+        IndexFacts {
+            schema_hash: current_schema_hash_for(true),
+            settings,
+            files: paths.iter().map(|p| (p.to_string(), 2)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v2_metadata_fills_fields() {
+        let raw = json!({
+            "id": "legacy",
+            "created_at": "2024-01-02T03:04:05Z",
+            "files_indexed": 2,
+            "chunks_created": 4,
+            "index_size_bytes": 1024,
+            "config": {"chunk_size": 512, "overlap": 64},
+            "schema_version": 2
+        });
+        let index = facts(&["/repo/src/a.rs", "/repo/lib/b.rs"]);
+
+        let migrated =
+            migrate_metadata("legacy", raw, Some(&index)).unwrap_or_else(|m| panic!("{m:?}"));
+        let metadata = migrated.metadata;
+
+        assert_eq!(metadata.repository_path, PathBuf::from("/repo"));
+        assert_eq!(
+            metadata.last_indexed_at,
+            "2024-01-02T03:04:05Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(metadata.config.chunk_size, 512);
+        assert_eq!(
+            metadata.config.include_patterns,
+            SessionConfig::default().include_patterns
+        );
+        assert_eq!(metadata.files_indexed, 2);
+        assert_eq!(metadata.tantivy_schema_hash, UNKNOWN);
+        assert!(migrated
+            .filled
+            .iter()
+            .any(|f| f.starts_with("repository_path: not recorded -> /repo")));
+    }
+
+    #[test]
+    fn test_migrate_without_index_reports_missing_path() {
+        let raw = json!({"id": "legacy", "schema_version": 1, "config": {}});
+
+        let missing = migrate_metadata("legacy", raw, None).err().unwrap();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].starts_with("repository_path: not recorded"));
+    }
+
+    #[test]
+    fn test_common_directory() {
+        let paths = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            common_directory(&paths(&["/repo/src/a.rs", "/repo/src/deep/b.rs"])),
+            Some(PathBuf::from("/repo/src"))
+        );
+        assert_eq!(
+            common_directory(&paths(&["/repo/a.rs", "/other/b.rs"])),
+            None
+        );
+        assert_eq!(common_directory(&paths(&[])), None);
+        assert_eq!(common_directory(&paths(&["a.rs"])), None);
+    }
+
+    #[test]
+    fn test_index_changes_lists_field_diff() {
+        let mut metadata: SessionMetadata = serde_json::from_value(json!({
+            "id": "s",
+            "repository_path": "/repo",
+            "created_at": "2024-01-02T03:04:05Z",
+            "last_indexed_at": "2024-01-02T03:04:05Z",
+            "files_indexed": 0,
+            "chunks_created": 0,
+            "index_size_bytes": 0,
+            "config": SessionConfig::default(),
+            "schema_version": 3
+        }))
+        .unwrap();
+        metadata.created_by_version = "0.4.0".to_string();
+        let mut index = facts(&[]);
+        index.schema_hash = "0123456789abcdef".to_string();
+        index.settings.fields.retain(|f| f.name != "path_tokens");
+
+        let changes = index_changes(&metadata, 3, Some(&index));
+        assert_eq!(
+            changes[0],
+            format!("schema version: v3 -> v{SCHEMA_VERSION}")
+        );
+        assert!(changes[1].starts_with("schema hash: `0123456789abcdef` -> `"));
+        assert!(changes[2].starts_with("field added: path_tokens (Str, indexed"));
+        assert_eq!(
+            changes[3],
+            format!("created by: shebe 0.4.0 -> shebe {CURRENT_VERSION}")
+        );
+    }
+}
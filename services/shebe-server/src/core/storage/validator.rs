@@ -1,10 +1,15 @@
 //! Metadata validation and consistency checking.
 //!
 //! This module provides tools to validate that session metadata
-//! matches the actual state of the Tantivy index on disk.
+//! matches the actual state of the Tantivy index on disk, and that
+//! every file's chunks are numbered `0..count` so lookups by
+//! `(file_path, chunk_index)` find them. It also samples adjacent
+//! chunks for identifiers cut in two by a chunk boundary.
 
 use crate::core::error::Result;
-use crate::core::storage::StorageManager;
+use crate::core::indexer::is_word_char;
+use crate::core::storage::tantivy::ChunkSpan;
+use crate::core::storage::{drift_percent, size_drifted, StorageManager};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use walkdir::WalkDir;
@@ -23,14 +28,191 @@ pub struct ValidationReport {
     /// Actual measured values
     pub actual_size: u64,
 
+    /// Drift between actual and recorded size, in percent
+    pub drift_percent: f64,
+
+    /// Index diagnostics ("unknown" for sessions that predate them)
+    pub created_by_version: String,
+    pub tantivy_schema_hash: String,
+
     /// Validation results
     pub size_matches: bool,
     pub is_consistent: bool,
 
+    /// Whether the schema hash matches this version (`None` if unknown)
+    pub schema_hash_matches: Option<bool>,
+
+    /// Why the Tantivy index cannot be opened, if its files are damaged
+    ///
+    /// Independent of `is_consistent`, which only compares metadata:
+    /// a corrupted index needs a re-index, not a metadata repair.
+    pub index_corruption: Option<String>,
+
     /// Validation timestamp
     pub validated_at: String,
 }
 
+/// Most missing or duplicate chunk indexes listed per file
+const MAX_LISTED_INDEXES: usize = 20;
+
+/// A file whose chunk indexes are not exactly `0..chunk_count`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkIndexProblem {
+    /// Indexed file path
+    pub file_path: String,
+
+    /// Number of chunks indexed for the file
+    pub chunk_count: usize,
+
+    /// Indexes below the highest one that have no chunk (first 20)
+    pub missing: Vec<usize>,
+
+    /// Indexes held by more than one chunk (first 20)
+    pub duplicates: Vec<usize>,
+}
+
+impl ChunkIndexProblem {
+    /// Check a file's sorted chunk indexes, `None` if they are `0..len`
+    pub fn check(file_path: &str, sorted_indexes: &[usize]) -> Option<Self> {
+        let contiguous = sorted_indexes
+            .iter()
+            .enumerate()
+            .all(|(i, &index)| i == index);
+        if contiguous {
+            return None;
+        }
+
+        let duplicates: Vec<usize> = sorted_indexes
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .fold(Vec::new(), |mut acc, index| {
+                if acc.last() != Some(&index) {
+                    acc.push(index);
+                }
+                acc
+            })
+            .into_iter()
+            .take(MAX_LISTED_INDEXES)
+            .collect();
+        let highest = sorted_indexes.last().copied().unwrap_or(0);
+        let missing = (0..highest)
+            .filter(|index| sorted_indexes.binary_search(index).is_err())
+            .take(MAX_LISTED_INDEXES)
+            .collect();
+
+        Some(Self {
+            file_path: file_path.to_string(),
+            chunk_count: sorted_indexes.len(),
+            missing,
+            duplicates,
+        })
+    }
+}
+
+/// Result of checking every file's chunk indexes in a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkIndexReport {
+    /// Session ID checked
+    pub session_id: String,
+
+    /// Files and chunks found in the index
+    pub files_checked: usize,
+    pub chunks_checked: usize,
+
+    /// Files with gaps or duplicate indexes, by path
+    pub problems: Vec<ChunkIndexProblem>,
+}
+
+impl ChunkIndexReport {
+    /// Whether every file's chunks are numbered `0..count`
+    pub fn is_consistent(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Most files whose adjacent chunks are checked for split tokens
+pub const SPLIT_TOKEN_SAMPLE_FILES: usize = 200;
+
+/// An identifier cut by a chunk boundary and whole in neither chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitToken {
+    /// Indexed file path
+    pub file_path: String,
+
+    /// Chunk ending inside the identifier
+    pub chunk_index: usize,
+
+    /// Part of the identifier before the cut
+    pub before: String,
+
+    /// Part of the identifier after the cut
+    pub after: String,
+}
+
+/// Result of sampling a session's chunk boundaries for split tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitTokenReport {
+    /// Session ID checked
+    pub session_id: String,
+
+    /// Files sampled, and those skipped because their chunk text no
+    /// longer matches the offsets (text not stored and the file changed
+    /// or is gone)
+    pub files_sampled: usize,
+    pub files_skipped: usize,
+
+    /// Adjacent chunk pairs checked, and those splitting an identifier
+    pub pairs_checked: usize,
+    pub split_pairs: usize,
+
+    /// Split identifiers, in file order (first 20)
+    pub examples: Vec<SplitToken>,
+}
+
+impl SplitTokenReport {
+    /// Fraction of checked chunk pairs that split an identifier
+    pub fn split_rate(&self) -> f64 {
+        if self.pairs_checked == 0 {
+            0.0
+        } else {
+            self.split_pairs as f64 / self.pairs_checked as f64
+        }
+    }
+}
+
+/// Split identifier between two consecutive chunks of a file, if any
+///
+/// `before` and `after` are the chunks' text. The identifier counts as
+/// split only if `after` starts past its first character, so chunk
+/// overlap that repeats it whole is not reported.
+fn split_token(
+    cur: &ChunkSpan,
+    before: &str,
+    next: &ChunkSpan,
+    after: &str,
+) -> Option<(String, String)> {
+    if next.start_offset > cur.end_offset || cur.end_offset >= next.end_offset {
+        return None;
+    }
+    let head_len: usize = before
+        .chars()
+        .rev()
+        .take_while(|&c| is_word_char(c))
+        .map(char::len_utf8)
+        .sum();
+    let tail: String = after
+        .get(cur.end_offset - next.start_offset..)?
+        .chars()
+        .take_while(|&c| is_word_char(c))
+        .collect();
+    let word_start = cur.end_offset - head_len;
+    if head_len == 0 || tail.is_empty() || next.start_offset <= word_start {
+        return None;
+    }
+    Some((before[before.len() - head_len..].to_string(), tail))
+}
+
 /// Metadata validator for session consistency checks
 pub struct MetadataValidator<'a> {
     storage_manager: &'a StorageManager,
@@ -45,9 +227,10 @@ impl<'a> MetadataValidator<'a> {
     /// Validate a session's metadata against actual index state
     ///
     /// Checks:
-    /// - Index size on disk matches metadata
+    /// - Index size on disk matches metadata (see [`size_drifted`])
     /// - Files indexed count is non-zero (if index exists)
     /// - Chunks created count is non-zero (if index exists)
+    /// - The index and its segments open (reported as `index_corruption`)
     pub fn validate_session(&self, session_id: &str) -> Result<ValidationReport> {
         // Read metadata file
         let metadata = self.storage_manager.get_session_metadata(session_id)?;
@@ -55,10 +238,8 @@ impl<'a> MetadataValidator<'a> {
         // Measure actual index size on disk
         let actual_size = self.measure_index_size(session_id)?;
 
-        // Check if size matches (within 1MB tolerance for small variations)
-        let size_tolerance = 1024 * 1024; // 1MB
-        let size_diff = actual_size.abs_diff(metadata.index_size_bytes);
-        let size_matches = size_diff < size_tolerance;
+        // Sizes match unless they drifted past the report threshold
+        let size_matches = !size_drifted(actual_size, metadata.index_size_bytes);
 
         // Tantivy creates small metadata files (~10KB) even for empty indexes
         // Only require non-zero file/chunk counts if index has substantial data
@@ -76,8 +257,13 @@ impl<'a> MetadataValidator<'a> {
             metadata_chunks: metadata.chunks_created,
             metadata_size: metadata.index_size_bytes,
             actual_size,
+            drift_percent: drift_percent(actual_size, metadata.index_size_bytes),
+            created_by_version: metadata.created_by_version.clone(),
+            tantivy_schema_hash: metadata.tantivy_schema_hash.clone(),
             size_matches,
             is_consistent,
+            schema_hash_matches: metadata.schema_hash_matches(),
+            index_corruption: self.storage_manager.index_corruption(session_id),
             validated_at: chrono::Utc::now().to_rfc3339(),
         })
     }
@@ -129,6 +315,91 @@ impl<'a> MetadataValidator<'a> {
         }
     }
 
+    /// Check that each file's chunk indexes are contiguous from 0
+    ///
+    /// Reads every stored document, so unlike [`Self::validate_session`]
+    /// it is not run at startup.
+    pub fn validate_chunk_indexes(&self, session_id: &str) -> Result<ChunkIndexReport> {
+        let indexes = self
+            .storage_manager
+            .open_session(session_id)?
+            .file_chunk_indexes()?;
+
+        let problems = indexes
+            .iter()
+            .filter_map(|(path, chunk_indexes)| ChunkIndexProblem::check(path, chunk_indexes))
+            .collect();
+
+        Ok(ChunkIndexReport {
+            session_id: session_id.to_string(),
+            files_checked: indexes.len(),
+            chunks_checked: indexes.values().map(Vec::len).sum(),
+            problems,
+        })
+    }
+
+    /// Sample adjacent chunk pairs for identifiers cut in two
+    ///
+    /// Checks up to [`SPLIT_TOKEN_SAMPLE_FILES`] files, spread evenly
+    /// over the indexed paths. Sessions that do not store chunk text
+    /// are checked against the files on disk; files that no longer
+    /// match their offsets are skipped.
+    pub fn check_split_tokens(&self, session_id: &str) -> Result<SplitTokenReport> {
+        let index = self.storage_manager.open_session(session_id)?;
+        let stores_text = index.stores_chunk_text();
+        let searcher = index.reader()?.searcher();
+        let paths = index.indexed_file_paths(&searcher)?;
+        let sample: Vec<&String> = if paths.len() <= SPLIT_TOKEN_SAMPLE_FILES {
+            paths.iter().collect()
+        } else {
+            (0..SPLIT_TOKEN_SAMPLE_FILES)
+                .map(|i| &paths[i * paths.len() / SPLIT_TOKEN_SAMPLE_FILES])
+                .collect()
+        };
+
+        let mut report = SplitTokenReport {
+            session_id: session_id.to_string(),
+            files_sampled: 0,
+            files_skipped: 0,
+            pairs_checked: 0,
+            split_pairs: 0,
+            examples: Vec::new(),
+        };
+        for path in sample {
+            let spans = index.file_chunk_spans(&searcher, path)?;
+            if spans.is_empty() {
+                continue;
+            }
+            let Some(texts) = chunk_texts(path, &spans, stores_text) else {
+                report.files_skipped += 1;
+                continue;
+            };
+            report.files_sampled += 1;
+
+            for (i, pair) in spans.windows(2).enumerate() {
+                if pair[1].chunk_index != pair[0].chunk_index + 1 {
+                    continue;
+                }
+                report.pairs_checked += 1;
+                if let Some((before, after)) =
+                    split_token(&pair[0], &texts[i], &pair[1], &texts[i + 1])
+                {
+                    report.split_pairs += 1;
+                    if report.examples.len() < MAX_LISTED_INDEXES {
+                        report.examples.push(SplitToken {
+                            file_path: path.clone(),
+                            chunk_index: pair[0].chunk_index,
+                            before,
+                            after,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
     /// Validate all sessions
     pub fn validate_all_sessions(&self) -> Result<Vec<ValidationReport>> {
         let sessions = self.storage_manager.list_sessions()?;
@@ -147,6 +418,27 @@ impl<'a> MetadataValidator<'a> {
     }
 }
 
+/// Text of each chunk, from the stored text or the file on disk
+///
+/// `None` if a chunk's length does not match its offsets.
+fn chunk_texts(path: &str, spans: &[ChunkSpan], stores_text: bool) -> Option<Vec<String>> {
+    let file = if stores_text {
+        String::new()
+    } else {
+        std::fs::read_to_string(path).ok()?
+    };
+    spans
+        .iter()
+        .map(|span| {
+            let text = match &span.text {
+                Some(text) => text.clone(),
+                None => file.get(span.start_offset..span.end_offset)?.to_string(),
+            };
+            (text.len() == span.end_offset.saturating_sub(span.start_offset)).then_some(text)
+        })
+        .collect()
+}
+
 /// Calculate total size of a directory recursively
 fn calculate_directory_size(dir_path: &Path) -> u64 {
     let mut total_size = 0u64;
@@ -191,6 +483,8 @@ mod tests {
 
         // Empty session should be consistent
         assert!(report.is_consistent);
+        assert_eq!(report.created_by_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(report.schema_hash_matches, Some(true));
         assert_eq!(report.metadata_files, 0);
         assert_eq!(report.metadata_chunks, 0);
         // Tantivy creates small metadata files (~1-10KB) even for empty indexes
@@ -260,6 +554,96 @@ mod tests {
         assert!(!repaired);
     }
 
+    #[test]
+    fn test_chunk_index_problem_check() {
+        assert_eq!(ChunkIndexProblem::check("/a.rs", &[0, 1, 2]), None);
+        assert_eq!(ChunkIndexProblem::check("/a.rs", &[]), None);
+
+        let problem = ChunkIndexProblem::check("/a.rs", &[0, 2, 2, 2, 5]).unwrap();
+        assert_eq!(problem.chunk_count, 5);
+        assert_eq!(problem.missing, vec![1, 3, 4]);
+        assert_eq!(problem.duplicates, vec![2]);
+
+        let problem = ChunkIndexProblem::check("/b.rs", &[1]).unwrap();
+        assert_eq!(problem.missing, vec![0]);
+        assert!(problem.duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_split_token() {
+        let span = |chunk_index, start_offset, end_offset| ChunkSpan {
+            chunk_index,
+            start_offset,
+            end_offset,
+            text: None,
+        };
+        // "let long_name = 1;" cut after "long_"
+        let cur = span(0, 0, 9);
+        assert_eq!(
+            split_token(&cur, "let long_", &span(1, 7, 18), "g_name = 1;"),
+            Some(("long_".to_string(), "name".to_string()))
+        );
+        // Overlap repeats the whole identifier
+        assert_eq!(
+            split_token(&cur, "let long_", &span(1, 4, 18), "long_name = 1;"),
+            None
+        );
+        // Cut at a word edge
+        let cur = span(0, 0, 4);
+        assert_eq!(
+            split_token(&cur, "let ", &span(1, 4, 18), "long_name = 1;"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_chunk_indexes_reports_gaps() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let mut index = manager
+            .create_session(
+                "gaps",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |file: &str, chunk_index: usize| crate::core::types::Chunk {
+            text: format!("chunk {chunk_index}"),
+            file_path: PathBuf::from(file),
+            start_offset: 0,
+            end_offset: 7,
+            chunk_index,
+            records: None,
+            section: None,
+        };
+        index
+            .add_chunks(
+                &[chunk("/test/repo/ok.rs", 0), chunk("/test/repo/ok.rs", 1)],
+                "gaps",
+            )
+            .unwrap();
+        // Separate batches, as a buggy writer could produce
+        index
+            .add_chunks(&[chunk("/test/repo/bad.rs", 0)], "gaps")
+            .unwrap();
+        index
+            .add_chunks(&[chunk("/test/repo/bad.rs", 2)], "gaps")
+            .unwrap();
+        index.commit().unwrap();
+        drop(index);
+
+        let validator = MetadataValidator::new(&manager);
+        let report = validator.validate_chunk_indexes("gaps").unwrap();
+
+        assert!(!report.is_consistent());
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.chunks_checked, 4);
+        assert_eq!(report.problems.len(), 1);
+        assert_eq!(report.problems[0].file_path, "/test/repo/bad.rs");
+        assert_eq!(report.problems[0].missing, vec![1]);
+    }
+
     #[test]
     fn test_calculate_directory_size() {
         let temp_dir = tempdir().unwrap();
@@ -297,6 +681,8 @@ mod tests {
             start_offset: 0,
             end_offset: 33,
             chunk_index: 0,
+            records: None,
+            section: None,
         };
         tantivy_index
             .add_chunks(&[chunk], "indexed-session")
@@ -321,6 +707,56 @@ mod tests {
         assert!(report.size_matches);
         assert_eq!(report.metadata_files, 1);
         assert_eq!(report.metadata_chunks, 1);
+        assert_eq!(report.index_corruption, None);
+    }
+
+    #[test]
+    fn test_validate_session_with_truncated_segment() {
+        let temp_dir = tempdir().unwrap();
+        let manager = StorageManager::new(temp_dir.path().to_path_buf());
+        let mut index = manager
+            .create_session(
+                "truncated",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        let chunk = crate::core::types::Chunk {
+            text: "fn main() {}".to_string(),
+            file_path: PathBuf::from("/test/repo/main.rs"),
+            start_offset: 0,
+            end_offset: 12,
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        index.add_chunks(&[chunk], "truncated").unwrap();
+        index.commit().unwrap();
+        drop(index);
+
+        // Cut a segment's doc store in half, as a power loss might
+        let tantivy_dir = manager.get_session_path("truncated").join("tantivy");
+        let store = std::fs::read_dir(&tantivy_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "store"))
+            .unwrap();
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&store)
+            .unwrap();
+        file.set_len(file.metadata().unwrap().len() / 2).unwrap();
+
+        let report = MetadataValidator::new(&manager)
+            .validate_session("truncated")
+            .unwrap();
+        let cause = report.index_corruption.expect("corruption reported");
+        assert!(cause.contains("corrupted"), "{cause}");
+        assert!(matches!(
+            manager.open_session("truncated"),
+            Err(ShebeError::IndexCorrupted { ref session, .. }) if session == "truncated"
+        ));
+        assert_eq!(manager.take_corrupted(), vec!["truncated".to_string()]);
     }
 
     // --- Phase 1B: Boundary tests ---
@@ -343,6 +779,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 30,
                 chunk_index: 0,
+                records: None,
+                section: None,
             })
             .collect();
         tantivy_index
@@ -0,0 +1,335 @@
+//! Directory tree aggregation over indexed files.
+//!
+//! Flat file listings are hard to scan for large sessions. This module
+//! folds indexed file paths into a directory tree with recursive file
+//! and chunk counts. Paths are made relative to the repository root,
+//! children are ranked by size, and the tree is cut off at a maximum
+//! depth and a maximum number of children per directory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+/// Default number of directory levels shown below the root
+pub const DEFAULT_TREE_DEPTH: usize = 3;
+
+/// Default number of children shown per directory
+pub const DEFAULT_TREE_MAX_CHILDREN: usize = 20;
+
+/// Kind of tree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    Dir,
+    File,
+}
+
+/// A directory or file in the tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeNode {
+    /// Last path component (the subtree path for the root)
+    pub name: String,
+
+    /// Directory or file
+    pub kind: NodeKind,
+
+    /// Files at or below this node
+    pub file_count: usize,
+
+    /// Chunks at or below this node
+    pub chunk_count: usize,
+
+    /// Visible children, largest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+
+    /// Children left out by the depth limit or the child threshold
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub hidden_children: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl TreeNode {
+    /// Whether the children were cut off by the depth limit
+    pub fn is_collapsed(&self) -> bool {
+        self.children.is_empty() && self.hidden_children > 0
+    }
+}
+
+/// Limits applied when building a tree
+#[derive(Debug, Clone, Copy)]
+pub struct TreeOptions {
+    /// Directory levels shown below the root (at least 1)
+    pub depth: usize,
+
+    /// Children shown per directory before "and N more" (at least 1)
+    pub max_children: usize,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_TREE_DEPTH,
+            max_children: DEFAULT_TREE_MAX_CHILDREN,
+        }
+    }
+}
+
+/// Intermediate directory used while folding paths
+#[derive(Default)]
+struct DirBuilder {
+    dirs: BTreeMap<String, DirBuilder>,
+    files: BTreeMap<String, usize>,
+}
+
+impl DirBuilder {
+    fn insert(&mut self, components: &[String], chunks: usize) {
+        match components {
+            [] => {}
+            [file] => {
+                *self.files.entry(file.clone()).or_insert(0) += chunks;
+            }
+            [dir, rest @ ..] => self
+                .dirs
+                .entry(dir.clone())
+                .or_default()
+                .insert(rest, chunks),
+        }
+    }
+
+    fn into_node(self, name: String, depth: usize, max_children: usize) -> TreeNode {
+        let mut children: Vec<TreeNode> = self
+            .dirs
+            .into_iter()
+            .map(|(name, dir)| dir.into_node(name, depth.saturating_sub(1), max_children))
+            .chain(self.files.into_iter().map(|(name, chunks)| TreeNode {
+                name,
+                kind: NodeKind::File,
+                file_count: 1,
+                chunk_count: chunks,
+                children: Vec::new(),
+                hidden_children: 0,
+            }))
+            .collect();
+
+        let file_count = children.iter().map(|c| c.file_count).sum();
+        let chunk_count = children.iter().map(|c| c.chunk_count).sum();
+
+        // Largest first; directories before files of the same size
+        children.sort_by(|a, b| {
+            b.file_count
+                .cmp(&a.file_count)
+                .then_with(|| (a.kind == NodeKind::File).cmp(&(b.kind == NodeKind::File)))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let hidden_children = if depth == 0 {
+            std::mem::take(&mut children).len()
+        } else if children.len() > max_children {
+            let hidden = children.len() - max_children;
+            children.truncate(max_children);
+            hidden
+        } else {
+            0
+        };
+
+        TreeNode {
+            name,
+            kind: NodeKind::Dir,
+            file_count,
+            chunk_count,
+            children,
+            hidden_children,
+        }
+    }
+}
+
+/// Split a file path into components relative to `root`
+///
+/// Paths outside `root` keep all their components.
+fn relative_components(path: &str, root: &Path) -> Vec<String> {
+    let path = Path::new(path);
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build a directory tree from per-file chunk counts
+///
+/// File paths are made relative to `root`. When `subtree` is given
+/// (relative to `root`), only files below it are included and the
+/// tree is rooted there, which lets callers expand a collapsed
+/// directory. Returns `None` when no file falls under `subtree`.
+pub fn build_tree(
+    files: &BTreeMap<String, usize>,
+    root: &Path,
+    subtree: Option<&str>,
+    options: TreeOptions,
+) -> Option<TreeNode> {
+    let prefix: Vec<String> = subtree
+        .map(|s| relative_components(s, Path::new("")))
+        .unwrap_or_default();
+
+    let mut builder = DirBuilder::default();
+    let mut matched = false;
+    for (path, &chunks) in files {
+        let components = relative_components(path, root);
+        if let Some(rest) = components.strip_prefix(prefix.as_slice()) {
+            if !rest.is_empty() {
+                builder.insert(rest, chunks);
+                matched = true;
+            }
+        }
+    }
+
+    if !matched && !prefix.is_empty() {
+        return None;
+    }
+
+    let name = if prefix.is_empty() {
+        ".".to_string()
+    } else {
+        prefix.join("/")
+    };
+
+    Some(builder.into_node(name, options.depth.max(1), options.max_children.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, usize)]) -> BTreeMap<String, usize> {
+        entries.iter().map(|(p, c)| (p.to_string(), *c)).collect()
+    }
+
+    fn child<'a>(node: &'a TreeNode, name: &str) -> &'a TreeNode {
+        node.children.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_counts_are_recursive() {
+        let files = files(&[
+            ("/repo/src/main.rs", 2),
+            ("/repo/src/core/a.rs", 3),
+            ("/repo/src/core/b.rs", 1),
+            ("/repo/README.md", 1),
+        ]);
+
+        let tree = build_tree(&files, Path::new("/repo"), None, TreeOptions::default()).unwrap();
+
+        assert_eq!(tree.name, ".");
+        assert_eq!(tree.file_count, 4);
+        assert_eq!(tree.chunk_count, 7);
+
+        let src = child(&tree, "src");
+        assert_eq!(src.kind, NodeKind::Dir);
+        assert_eq!(src.file_count, 3);
+        assert_eq!(src.chunk_count, 6);
+        assert_eq!(child(src, "core").file_count, 2);
+    }
+
+    #[test]
+    fn test_children_ranked_by_file_count() {
+        let files = files(&[
+            ("/repo/a/x.rs", 1),
+            ("/repo/b/x.rs", 1),
+            ("/repo/b/y.rs", 1),
+            ("/repo/top.rs", 1),
+        ]);
+
+        let tree = build_tree(&files, Path::new("/repo"), None, TreeOptions::default()).unwrap();
+        let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+
+        assert_eq!(names, vec!["b", "a", "top.rs"]);
+    }
+
+    #[test]
+    fn test_depth_collapses_directories() {
+        let files = files(&[("/repo/a/b/c/d.rs", 1), ("/repo/a/e.rs", 1)]);
+        let options = TreeOptions {
+            depth: 2,
+            max_children: 20,
+        };
+
+        let tree = build_tree(&files, Path::new("/repo"), None, options).unwrap();
+        let a = child(&tree, "a");
+        let b = child(a, "b");
+
+        assert!(!a.is_collapsed());
+        assert!(b.is_collapsed());
+        assert_eq!(b.hidden_children, 1);
+        assert_eq!(b.file_count, 1);
+    }
+
+    #[test]
+    fn test_max_children_hides_rest() {
+        let entries: Vec<(String, usize)> = (0..5).map(|i| (format!("/repo/f{i}.rs"), 1)).collect();
+        let files: BTreeMap<String, usize> = entries.into_iter().collect();
+        let options = TreeOptions {
+            depth: 3,
+            max_children: 2,
+        };
+
+        let tree = build_tree(&files, Path::new("/repo"), None, options).unwrap();
+
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.hidden_children, 3);
+        assert_eq!(tree.file_count, 5);
+    }
+
+    #[test]
+    fn test_subtree_roots_tree() {
+        let files = files(&[("/repo/src/core/a.rs", 1), ("/repo/docs/b.md", 1)]);
+
+        let tree = build_tree(
+            &files,
+            Path::new("/repo"),
+            Some("src/core"),
+            TreeOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(tree.name, "src/core");
+        assert_eq!(tree.file_count, 1);
+        assert_eq!(tree.children[0].name, "a.rs");
+
+        assert!(build_tree(
+            &files,
+            Path::new("/repo"),
+            Some("nope"),
+            TreeOptions::default()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_paths_outside_root_kept() {
+        let files = files(&[("/other/x.rs", 1)]);
+
+        let tree = build_tree(&files, Path::new("/repo"), None, TreeOptions::default()).unwrap();
+
+        assert_eq!(child(&tree, "other").file_count, 1);
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let files = files(&[("/repo/a.rs", 2)]);
+        let tree = build_tree(&files, Path::new("/repo"), None, TreeOptions::default()).unwrap();
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["kind"], "dir");
+        assert_eq!(json["children"][0]["kind"], "file");
+        assert_eq!(json["children"][0]["chunk_count"], 2);
+        assert!(json.get("hidden_children").is_none());
+    }
+}
@@ -4,11 +4,15 @@
 //! application, including chunks, search results, requests, and
 //! responses.
 
+use crate::core::format::format_count;
+use crate::core::search::fuzzy::MAX_FUZZINESS;
+use crate::core::storage::Provenance;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// A single text chunk from a document
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Chunk {
     /// The actual text content
     pub text: String,
@@ -24,6 +28,52 @@ pub struct Chunk {
 
     /// Sequential chunk number within the file
     pub chunk_index: usize,
+
+    /// Records the chunk holds, for data files chunked by record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<RecordRange>,
+
+    /// Headings in effect at the chunk's start, for documentation files
+    /// (`Storage › Session layout`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// 1-based record numbers covered by a chunk of a data file
+///
+/// A chunk holds whole records; a record longer than the chunk size is
+/// split and each piece reports that one record (`first == last`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl RecordRange {
+    /// Render as `record 7` or `records 1,204–1,267`
+    pub fn describe(&self) -> String {
+        if self.first == self.last {
+            format!("record {self}")
+        } else {
+            format!("records {self}")
+        }
+    }
+}
+
+/// `7` or `1,204–1,267`
+impl std::fmt::Display for RecordRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first == self.last {
+            f.write_str(&format_count(self.first as u64))
+        } else {
+            write!(
+                f,
+                "{}–{}",
+                format_count(self.first as u64),
+                format_count(self.last as u64)
+            )
+        }
+    }
 }
 
 /// Search result returned by query
@@ -41,9 +91,122 @@ pub struct SearchResult {
     /// Chunk index within file
     pub chunk_index: usize,
 
+    /// Session the chunk belongs to, set on results of a collection search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+
     /// Byte offsets for highlighting
     pub start_offset: usize,
     pub end_offset: usize,
+
+    /// Records the chunk holds, for data files chunked by record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<RecordRange>,
+
+    /// Headings in effect at the chunk's start, for documentation files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+
+    /// Other paths with identical content (skipped by dedupe)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_present_at: Vec<String>,
+
+    /// Whether the query also matched the file path (path boost applied)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub matched_in_path: bool,
+
+    /// Whether the score was lowered because the chunk is very short
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub short_chunk_demoted: bool,
+
+    /// When the file last changed (see [`SearchResponse::modified_times`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+
+    /// Why `text` is empty, for sessions that do not store chunk text
+    /// and could not read it from the file ("file missing", ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_unavailable: Option<String>,
+
+    /// Whether the file was cut off at `max_chunks_per_file`, so
+    /// matches past its last indexed chunk are missing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub indexed_partially: bool,
+
+    /// How the score was computed (top results of an `explain` search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Per-term breakdown of a result's score
+///
+/// The contributions add up to the score before the short-chunk
+/// penalty; `short_chunk_factor` is applied on top.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Matched query terms, content terms first
+    pub terms: Vec<TermContribution>,
+
+    /// Factor the score was multiplied by because the chunk is very short
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_chunk_factor: Option<f32>,
+}
+
+impl ScoreExplanation {
+    /// Score these contributions add up to, after the short-chunk penalty
+    pub fn total(&self) -> f32 {
+        let sum: f32 = self.terms.iter().map(|t| t.contribution).sum();
+        sum * self.short_chunk_factor.unwrap_or(1.0)
+    }
+
+    /// Render as `term 'auth': tf=3, idf=2.41, contribution 4.20; ...`
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self.terms.iter().map(TermContribution::describe).collect();
+        if let Some(factor) = self.short_chunk_factor {
+            parts.push(format!("short chunk x{factor}"));
+        }
+        parts.join("; ")
+    }
+}
+
+/// One matched term's share of a result's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermContribution {
+    /// Indexed term ("phrase" for a phrase query)
+    pub term: String,
+
+    /// Whether the term matched the file path (path boost) rather than
+    /// the chunk text
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub in_path: bool,
+
+    /// Occurrences of the term in the field
+    pub tf: f32,
+
+    /// Inverse document frequency of the term in the session
+    pub idf: f32,
+
+    /// Field length in tokens, as stored in the field norm (approximate
+    /// for long chunks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_length: Option<f32>,
+
+    /// Points added to the score, after path and fuzzy boosts
+    pub contribution: f32,
+}
+
+impl TermContribution {
+    /// Render as `term 'auth': tf=3, idf=2.41, contribution 4.20`
+    pub fn describe(&self) -> String {
+        format!(
+            "{} '{}': tf={}, idf={:.2}, contribution {:.2}",
+            if self.in_path { "path" } else { "term" },
+            self.term,
+            self.tf,
+            self.idf,
+            self.contribution
+        )
+    }
 }
 
 /// Statistics from an indexing operation
@@ -60,6 +223,421 @@ pub struct IndexStats {
 
     /// Session identifier
     pub session: String,
+
+    /// Files skipped because their content duplicates an indexed file
+    #[serde(default)]
+    pub files_deduplicated: usize,
+
+    /// Files left out of the index, by reason
+    #[serde(default)]
+    pub skipped: SkipCounts,
+
+    /// First files left out of the index (capped), in walk order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_files: Vec<SkippedFile>,
+
+    /// Bytes of long blobs replaced by placeholders before chunking
+    #[serde(default)]
+    pub bytes_stripped: u64,
+
+    /// Files over the streaming threshold, read and chunked in bounded pieces
+    #[serde(default)]
+    pub files_streamed: usize,
+
+    /// Files cut off at `max_chunks_per_file`, in indexing order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated_files: Vec<TruncatedFile>,
+
+    /// Files found under a new path with unchanged content, compared
+    /// with the session this run replaced
+    #[serde(default)]
+    pub files_renamed: usize,
+
+    /// First renamed files (capped), by new path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renamed_files: Vec<FileRename>,
+
+    /// Files matched by each include and exclude pattern
+    #[serde(default)]
+    pub patterns: PatternCounts,
+
+    /// Why the include patterns look wrong (they matched no file)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_warning: Option<String>,
+
+    /// How the index was produced (set once the session is written)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+
+    /// Time spent in each indexing stage, when the run was profiled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<IndexProfile>,
+}
+
+/// Why a file was left out of an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Matched an exclude or `.shebeignore` pattern, or no include pattern
+    ExcludedByPattern,
+
+    /// Larger than the configured maximum file size
+    TooLarge,
+
+    /// Contains NUL bytes
+    Binary,
+
+    /// Could not be read
+    ReadError,
+
+    /// Text that is not valid UTF-8
+    NotUtf8,
+
+    /// Has a line longer than the configured maximum line length
+    LineTooLong,
+}
+
+impl SkipReason {
+    /// All reasons, in display order
+    pub const ALL: [SkipReason; 6] = [
+        SkipReason::ExcludedByPattern,
+        SkipReason::TooLarge,
+        SkipReason::Binary,
+        SkipReason::ReadError,
+        SkipReason::NotUtf8,
+        SkipReason::LineTooLong,
+    ];
+
+    /// Human-readable name
+    pub fn label(&self) -> &'static str {
+        match self {
+            SkipReason::ExcludedByPattern => "excluded by pattern",
+            SkipReason::TooLarge => "too large",
+            SkipReason::Binary => "binary",
+            SkipReason::ReadError => "read error",
+            SkipReason::NotUtf8 => "not UTF-8",
+            SkipReason::LineTooLong => "line too long",
+        }
+    }
+}
+
+/// A file left out of an index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    /// Path relative to the indexed directory (directories end in `/`)
+    pub path: String,
+
+    /// Why it was skipped
+    pub reason: SkipReason,
+
+    /// The pattern, size or error behind the reason
+    pub detail: String,
+}
+
+/// A file indexed only up to `max_chunks_per_file` chunks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TruncatedFile {
+    /// Path as stored in the index (what search results show)
+    pub path: String,
+
+    /// Chunks indexed (the cap)
+    pub chunks_indexed: usize,
+
+    /// Bytes covered by the indexed chunks
+    pub bytes_indexed: u64,
+
+    /// Size of the whole file
+    pub file_bytes: u64,
+}
+
+impl TruncatedFile {
+    /// Share of the file that was indexed, in percent
+    pub fn percent_indexed(&self) -> u64 {
+        (self.bytes_indexed * 100)
+            .checked_div(self.file_bytes)
+            .unwrap_or(100)
+    }
+}
+
+/// A stage of an indexing run, as timed by a profiled run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexStage {
+    /// Walking the tree and matching patterns
+    Walk,
+
+    /// Reading file bytes (streamed files are also checked here)
+    Read,
+
+    /// Binary, UTF-8 and line length checks, and hashing
+    Decode,
+
+    /// Splitting text into chunks
+    Chunk,
+
+    /// Adding chunks to the Tantivy index
+    AddDocument,
+
+    /// Committing the index
+    Commit,
+}
+
+impl IndexStage {
+    /// All stages, in pipeline order
+    pub const ALL: [IndexStage; 6] = [
+        IndexStage::Walk,
+        IndexStage::Read,
+        IndexStage::Decode,
+        IndexStage::Chunk,
+        IndexStage::AddDocument,
+        IndexStage::Commit,
+    ];
+
+    /// Name as shown in the breakdown
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndexStage::Walk => "walk",
+            IndexStage::Read => "read",
+            IndexStage::Decode => "decode",
+            IndexStage::Chunk => "chunk",
+            IndexStage::AddDocument => "add_document",
+            IndexStage::Commit => "commit",
+        }
+    }
+}
+
+/// Time spent in one indexing stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageTiming {
+    /// Stage timed
+    pub stage: IndexStage,
+
+    /// Total time in the stage, in microseconds
+    pub duration_us: u64,
+}
+
+/// Time spent reading, decoding and chunking one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileTiming {
+    /// Path as stored in the index
+    pub path: String,
+
+    /// Time spent on the file, in microseconds
+    pub duration_us: u64,
+}
+
+/// Where a profiled indexing run spent its time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexProfile {
+    /// Every stage, in pipeline order
+    pub stages: Vec<StageTiming>,
+
+    /// Slowest files (at most ten), slowest first
+    pub slowest_files: Vec<FileTiming>,
+
+    /// Bytes of chunk text held in memory before writing the index
+    #[serde(default)]
+    pub chunk_text_bytes: u64,
+
+    /// Peak resident memory of the process, where the platform reports
+    /// it (Linux)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl IndexProfile {
+    /// Time spent in all stages, in microseconds
+    pub fn total_us(&self) -> u64 {
+        self.stages.iter().map(|s| s.duration_us).sum()
+    }
+
+    /// Share of the profiled time spent in `timing`'s stage, in percent
+    pub fn percent(&self, timing: &StageTiming) -> f64 {
+        match self.total_us() {
+            0 => 0.0,
+            total => timing.duration_us as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// A file whose content moved to another path between two index runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRename {
+    /// Previous path
+    pub from: String,
+
+    /// New path
+    pub to: String,
+}
+
+/// Files matched by one include or exclude pattern during a walk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternCount {
+    /// Pattern as given (`.shebeignore` rules with their line)
+    pub pattern: String,
+
+    /// Files the pattern matched (for an exclude pattern: excluded)
+    pub files: usize,
+
+    /// Directories pruned by an exclude pattern (their contents are
+    /// not counted)
+    #[serde(default)]
+    pub directories: usize,
+}
+
+impl PatternCount {
+    /// "pattern (3 files)", with pruned directories if any
+    pub fn summary(&self) -> String {
+        let files = match self.files {
+            1 => "1 file".to_string(),
+            n => format!("{n} files"),
+        };
+        match self.directories {
+            0 => format!("{} ({files})", self.pattern),
+            1 => format!("{} ({files}, 1 directory)", self.pattern),
+            n => format!("{} ({files}, {n} directories)", self.pattern),
+        }
+    }
+}
+
+/// Files each include and exclude pattern matched during a walk
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternCounts {
+    /// Files checked against the patterns (outside hidden and pruned
+    /// directories, within the size limit)
+    pub candidates: usize,
+
+    /// Files matching each include pattern, in the given order
+    pub include: Vec<PatternCount>,
+
+    /// Each exclude pattern, then each `.shebeignore` rule, in order
+    pub exclude: Vec<PatternCount>,
+}
+
+impl PatternCounts {
+    /// Include pattern counts joined for display
+    pub fn include_summary(&self) -> String {
+        Self::join(self.include.iter())
+    }
+
+    /// Counts of the exclude patterns that left something out, joined
+    /// for display (empty if none did)
+    pub fn exclude_summary(&self) -> String {
+        Self::join(
+            self.exclude
+                .iter()
+                .filter(|p| p.files > 0 || p.directories > 0),
+        )
+    }
+
+    fn join<'a>(counts: impl Iterator<Item = &'a PatternCount>) -> String {
+        counts
+            .map(PatternCount::summary)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Number of files left out of an index for each reason
+///
+/// A directory pruned by an exclude pattern counts once, as its
+/// contents are never walked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkipCounts {
+    /// Files (and pruned directories) excluded by patterns
+    pub excluded_by_pattern: usize,
+
+    /// Files over the size limit
+    pub too_large: usize,
+
+    /// Binary files
+    pub binary: usize,
+
+    /// Files that could not be read
+    pub read_error: usize,
+
+    /// Text files that are not valid UTF-8
+    pub not_utf8: usize,
+
+    /// Files with a line over the maximum line length
+    pub line_too_long: usize,
+}
+
+impl SkipCounts {
+    fn slot(&mut self, reason: SkipReason) -> &mut usize {
+        match reason {
+            SkipReason::ExcludedByPattern => &mut self.excluded_by_pattern,
+            SkipReason::TooLarge => &mut self.too_large,
+            SkipReason::Binary => &mut self.binary,
+            SkipReason::ReadError => &mut self.read_error,
+            SkipReason::NotUtf8 => &mut self.not_utf8,
+            SkipReason::LineTooLong => &mut self.line_too_long,
+        }
+    }
+
+    /// Count one skipped file
+    pub fn add(&mut self, reason: SkipReason) {
+        *self.slot(reason) += 1;
+    }
+
+    /// Files skipped for `reason`
+    pub fn get(&self, reason: SkipReason) -> usize {
+        match reason {
+            SkipReason::ExcludedByPattern => self.excluded_by_pattern,
+            SkipReason::TooLarge => self.too_large,
+            SkipReason::Binary => self.binary,
+            SkipReason::ReadError => self.read_error,
+            SkipReason::NotUtf8 => self.not_utf8,
+            SkipReason::LineTooLong => self.line_too_long,
+        }
+    }
+
+    /// Files skipped for any reason
+    pub fn total(&self) -> usize {
+        SkipReason::ALL.iter().map(|&reason| self.get(reason)).sum()
+    }
+
+    /// Non-zero counts joined for display ("3 excluded by pattern, 1 binary")
+    pub fn summary(&self) -> String {
+        self.breakdown()
+            .iter()
+            .map(|(reason, count)| format!("{count} {}", reason.label()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Non-zero counts as "reason: count" pairs, in display order
+    pub fn breakdown(&self) -> Vec<(SkipReason, usize)> {
+        SkipReason::ALL
+            .iter()
+            .map(|&reason| (reason, self.get(reason)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+}
+
+/// Result of compacting a session index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionStats {
+    /// Session identifier
+    pub session: String,
+
+    /// Searchable segments before compaction
+    pub segments_before: usize,
+
+    /// Searchable segments after compaction
+    pub segments_after: usize,
+
+    /// Session directory size before compaction (bytes)
+    pub size_before_bytes: u64,
+
+    /// Session directory size after compaction (bytes)
+    pub size_after_bytes: u64,
+
+    /// Compaction duration in milliseconds
+    pub duration_ms: u64,
 }
 
 /// Session metadata
@@ -135,8 +713,239 @@ pub struct SearchRequest {
     /// Session identifier
     pub session: String,
 
-    /// Number of results to return (optional)
+    /// Number of results to return (session default, else server
+    /// default, if unset)
+    ///
+    /// When `group_by_file` is set, this is the number of files.
+    pub k: Option<usize>,
+
+    /// Collapse results to one entry per file (best-scoring chunk)
+    #[serde(default)]
+    pub group_by_file: bool,
+
+    /// Hide adjacent-chunk hits whose matches only fall in the shared overlap
+    #[serde(default = "default_dedupe_overlaps")]
+    pub dedupe_overlaps: bool,
+
+    /// Boost for query matches in the file path (session default, else
+    /// server default, if unset)
+    #[serde(default)]
+    pub path_boost: Option<f32>,
+
+    /// Edit distance for typo-tolerant term matching (0 disables, max 2;
+    /// session default, else 0, if unset)
+    #[serde(default)]
+    pub fuzziness: Option<u8>,
+
+    /// Also match other spellings of identifier terms (`getUserId` <-> `get_user_id`)
+    #[serde(default = "default_expand_identifiers")]
+    pub expand_identifiers: bool,
+
+    /// BM25 term frequency saturation for this search (session's if unset)
+    #[serde(default)]
+    pub bm25_k1: Option<f32>,
+
+    /// BM25 length normalization for this search (session's if unset)
+    #[serde(default)]
+    pub bm25_b: Option<f32>,
+
+    /// Only search among the top hits of another query
+    #[serde(default)]
+    pub within: Option<SearchWithin>,
+
+    /// Demote chunks much shorter than the chunk size (server default if unset)
+    #[serde(default)]
+    pub penalize_short_chunks: Option<bool>,
+
+    /// Only files last changed within this window ("30d", "12h", "2w")
+    /// or since this ISO date ("2026-09-01")
+    #[serde(default)]
+    pub modified_within: Option<String>,
+
+    /// Only chunks whose heading breadcrumb contains this text
+    /// (case-insensitive; documentation files only)
+    #[serde(default)]
+    pub section_filter: Option<String>,
+
+    /// Leave out chunks containing any of these terms (a term of
+    /// several words leaves out chunks containing them as a phrase)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_terms: Vec<String>,
+
+    /// Leave out files matching any of these globs (relative globs
+    /// match paths inside the repository)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_paths: Vec<String>,
+
+    /// Break down the scores of the top results (at most 10) per term
+    #[serde(default)]
+    pub explain: bool,
+}
+
+/// Inner query of a refined search
+///
+/// The primary query is evaluated only over the chunks this query
+/// ranks in its top `k`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchWithin {
+    /// Inner query string (its own syntax, e.g. a phrase stays a phrase)
+    pub query: String,
+
+    /// Number of inner hits to refine (search default if unset)
+    #[serde(default)]
+    pub k: Option<usize>,
+}
+
+fn default_dedupe_overlaps() -> bool {
+    true
+}
+
+fn default_expand_identifiers() -> bool {
+    true
+}
+
+/// Most results one search may ask for
+pub const MAX_K: usize = 100;
+
+/// How search results are shown by `search_code` and `shebe search`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Each result with its code snippet
+    #[default]
+    Full,
+    /// A table of locations with a short excerpt
+    Locations,
+}
+
+impl OutputMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "full" => Ok(Self::Full),
+            "locations" => Ok(Self::Locations),
+            other => Err(format!(
+                "Invalid output_mode: '{other}'. Must be 'full' or 'locations'."
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Locations => "locations",
+        }
+    }
+}
+
+/// Search options a session uses when a request leaves them unset
+///
+/// Set with `set_session_defaults` and stored in the session metadata.
+/// `SearchService` applies `k`, `fuzziness` and `path_boost`; the
+/// search tools apply `output_mode`. Values given with a request
+/// always win.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionSearchDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub k: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzziness: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_boost: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<OutputMode>,
+}
+
+impl SessionSearchDefaults {
+    /// Whether no option is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the options against the bounds of the per-request parameters
+    ///
+    /// The first option out of range is reported, with the value given.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(k) = self.k {
+            if !(1..=MAX_K).contains(&k) {
+                return Err(format!("k must be between 1 and {MAX_K}, got {k}"));
+            }
+        }
+        if let Some(fuzziness) = self.fuzziness {
+            if fuzziness > MAX_FUZZINESS {
+                return Err(format!(
+                    "fuzziness must be between 0 and {MAX_FUZZINESS}, got {fuzziness}"
+                ));
+            }
+        }
+        if let Some(path_boost) = self.path_boost {
+            if !path_boost.is_finite() || path_boost < 0.0 {
+                return Err(format!(
+                    "path_boost must be a non-negative number, got {path_boost}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// These defaults with the options set in `update` replacing them
+    pub fn merged(self, update: SessionSearchDefaults) -> Self {
+        Self {
+            k: update.k.or(self.k),
+            fuzziness: update.fuzziness.or(self.fuzziness),
+            path_boost: update.path_boost.or(self.path_boost),
+            output_mode: update.output_mode.or(self.output_mode),
+        }
+    }
+
+    /// Fill the options `request` leaves unset
+    pub fn apply(&self, request: &mut SearchRequest) {
+        request.k = request.k.or(self.k);
+        request.fuzziness = request.fuzziness.or(self.fuzziness);
+        request.path_boost = request.path_boost.or(self.path_boost);
+    }
+
+    /// Options set, e.g. `k=20, fuzziness=1`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(k) = self.k {
+            parts.push(format!("k={k}"));
+        }
+        if let Some(fuzziness) = self.fuzziness {
+            parts.push(format!("fuzziness={fuzziness}"));
+        }
+        if let Some(path_boost) = self.path_boost {
+            parts.push(format!("path_boost={path_boost}"));
+        }
+        if let Some(mode) = self.output_mode {
+            parts.push(format!("output_mode={}", mode.as_str()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Search hits for a single file, used by grouped search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGroup {
+    /// Source file path
+    pub file_path: String,
+
+    /// Score of the best-matching chunk (used for ranking)
+    pub best_score: f32,
+
+    /// Matching chunks, best first
+    pub hits: Vec<SearchResult>,
+}
+
+impl FileGroup {
+    /// Best-scoring chunk for this file
+    pub fn best(&self) -> &SearchResult {
+        &self.hits[0]
+    }
+
+    /// Chunk indexes of the additional (non-best) hits
+    pub fn more_chunk_indexes(&self) -> Vec<usize> {
+        self.hits.iter().skip(1).map(|h| h.chunk_index).collect()
+    }
 }
 
 /// Response from search operation
@@ -146,6 +955,8 @@ pub struct SearchResponse {
     pub query: String,
 
     /// Search results
+    ///
+    /// In grouped mode this holds the best chunk of each file.
     pub results: Vec<SearchResult>,
 
     /// Number of results returned
@@ -153,6 +964,322 @@ pub struct SearchResponse {
 
     /// Query duration in milliseconds
     pub duration_ms: u64,
+
+    /// Per-file hit groups (only populated when `group_by_file` was requested)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<FileGroup>,
+
+    /// Hits dropped as chunk overlap duplicates
+    #[serde(default)]
+    pub overlap_duplicates_dropped: usize,
+
+    /// Returned hits whose score was lowered because the chunk is very short
+    #[serde(default)]
+    pub short_chunks_demoted: usize,
+
+    /// Edit distance used for fuzzy matching (0 if disabled)
+    #[serde(default)]
+    pub fuzziness: u8,
+
+    /// Indexed terms that query terms were expanded to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fuzzy_expansions: Vec<FuzzyExpansion>,
+
+    /// Identifier terms that were also searched in their other spellings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifier_expansions: Vec<IdentifierExpansion>,
+
+    /// How a `within` refinement narrowed the search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refinement: Option<Refinement>,
+
+    /// Where the results' `modified_at` comes from (`None` for sessions
+    /// indexed before change times were recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_times: Option<ModifiedTimeSource>,
+
+    /// How `modified_within` narrowed the search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recency: Option<RecencyFilter>,
+
+    /// `section_filter` the results were restricted by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_filter: Option<String>,
+
+    /// What `exclude_terms` and `exclude_paths` left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<ExclusionFilter>,
+
+    /// Why `explain` was ignored (session index lacks what it needs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain_skipped: Option<String>,
+}
+
+impl SearchResponse {
+    /// One-line note on fuzzy matching, or `None` if it was disabled
+    ///
+    /// e.g. "Fuzzy matching (distance 1): confg -> config"
+    pub fn fuzzy_summary(&self) -> Option<String> {
+        if self.fuzziness == 0 {
+            return None;
+        }
+        if self.fuzzy_expansions.is_empty() {
+            return Some(format!(
+                "Fuzzy matching (distance {}) found no similar terms",
+                self.fuzziness
+            ));
+        }
+        let expanded: Vec<String> = self
+            .fuzzy_expansions
+            .iter()
+            .map(FuzzyExpansion::describe)
+            .collect();
+        Some(format!(
+            "Fuzzy matching (distance {}): {}",
+            self.fuzziness,
+            expanded.join("; ")
+        ))
+    }
+
+    /// One-line note on identifier spellings searched, or `None` if there were none
+    ///
+    /// e.g. "Identifier variants: getUserId -> get_user_id, GetUserId"
+    pub fn identifier_summary(&self) -> Option<String> {
+        if self.identifier_expansions.is_empty() {
+            return None;
+        }
+        let expanded: Vec<String> = self
+            .identifier_expansions
+            .iter()
+            .map(IdentifierExpansion::describe)
+            .collect();
+        Some(format!("Identifier variants: {}", expanded.join("; ")))
+    }
+
+    /// One-line note on a `modified_within` filter, or `None` if there was none
+    ///
+    /// e.g. "Only files committed since 2026-09-17 (modified_within 30d)".
+    /// Sessions that fell back to file modification times say so.
+    pub fn recency_summary(&self) -> Option<String> {
+        let recency = self.recency.as_ref()?;
+        let source = self.modified_times.unwrap_or(ModifiedTimeSource::Mtime);
+        let mut summary = format!(
+            "Only files {} since {} (modified_within {})",
+            source.verb(),
+            recency.since.format("%Y-%m-%d %H:%M UTC"),
+            recency.window
+        );
+        if source == ModifiedTimeSource::Mtime {
+            summary.push_str(
+                ". Note: no git history for this session, so file modification times were used",
+            );
+        }
+        Some(summary)
+    }
+
+    /// One-line note on a `section_filter`, or `None` if there was none
+    ///
+    /// e.g. "Only document sections matching 'storage'"
+    pub fn section_summary(&self) -> Option<String> {
+        self.section_filter
+            .as_ref()
+            .map(|filter| format!("Only document sections matching '{filter}'"))
+    }
+
+    /// One-line note on `exclude_terms` and `exclude_paths`, or `None`
+    /// if there were none
+    ///
+    /// e.g. "Excluded terms 'test', 'mock' and paths 'tests/**': 4 otherwise matching chunks removed"
+    pub fn exclusion_summary(&self) -> Option<String> {
+        let exclusions = self.exclusions.as_ref()?;
+        let quoted = |items: &[String]| {
+            items
+                .iter()
+                .map(|item| format!("'{item}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let excluded = match (exclusions.terms.is_empty(), exclusions.paths.is_empty()) {
+            (false, false) => format!(
+                "terms {} and paths {}",
+                quoted(&exclusions.terms),
+                quoted(&exclusions.paths)
+            ),
+            (false, true) => format!("terms {}", quoted(&exclusions.terms)),
+            _ => format!("paths {}", quoted(&exclusions.paths)),
+        };
+        let chunks = if exclusions.removed == 1 {
+            "chunk"
+        } else {
+            "chunks"
+        };
+        Some(format!(
+            "Excluded {excluded}: {} otherwise matching {chunks} removed",
+            exclusions.removed
+        ))
+    }
+
+    /// One-line note on a `within` refinement, or `None` if there was none
+    ///
+    /// e.g. "Refined within 'retry policy': 3 of 12 inner matches also match 'timeout'"
+    pub fn refinement_summary(&self) -> Option<String> {
+        let refinement = self.refinement.as_ref()?;
+        Some(format!(
+            "Refined within '{}': {} of {} inner matches also match '{}'",
+            refinement.within_query, refinement.survived, refinement.inner_matches, self.query
+        ))
+    }
+}
+
+/// Where the last-change time of indexed files comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModifiedTimeSource {
+    /// Last commit touching the file (untracked files: their mtime)
+    Git,
+
+    /// Filesystem modification time (not a git checkout, or git
+    /// integration turned off)
+    Mtime,
+}
+
+impl ModifiedTimeSource {
+    /// Verb for a file's last change ("committed", "modified")
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Git => "committed",
+            Self::Mtime => "modified",
+        }
+    }
+}
+
+/// Restriction of a search to recently changed files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecencyFilter {
+    /// Window as requested ("30d", "2026-09-01")
+    pub window: String,
+
+    /// Files last changed before this time were left out
+    pub since: DateTime<Utc>,
+}
+
+/// Terms and paths left out of a search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExclusionFilter {
+    /// `exclude_terms` as requested
+    pub terms: Vec<String>,
+
+    /// `exclude_paths` as requested
+    pub paths: Vec<String>,
+
+    /// Chunks the query matched that the exclusions left out
+    pub removed: u64,
+}
+
+/// Result of evaluating a search within another query's hits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Refinement {
+    /// Inner query string
+    pub within_query: String,
+
+    /// Inner query hits the search was restricted to (at most its `k`)
+    pub inner_matches: usize,
+
+    /// Inner hits that the primary query also matched
+    pub survived: usize,
+}
+
+/// Similar indexed terms found for one query term by fuzzy matching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzyExpansion {
+    /// Query term as tokenized (e.g. lowercased)
+    pub term: String,
+
+    /// Indexed terms within the edit distance, closest first
+    pub matches: Vec<String>,
+}
+
+impl FuzzyExpansion {
+    /// Render as `term -> match1, match2`
+    pub fn describe(&self) -> String {
+        format!("{} -> {}", self.term, self.matches.join(", "))
+    }
+}
+
+/// Other spellings searched for one identifier query term
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentifierExpansion {
+    /// Query term as written
+    pub term: String,
+
+    /// Case-style variants (snake, camel, Pascal, kebab, screaming snake)
+    pub variants: Vec<String>,
+}
+
+impl IdentifierExpansion {
+    /// Render as `term -> variant1, variant2`
+    pub fn describe(&self) -> String {
+        format!("{} -> {}", self.term, self.variants.join(", "))
+    }
+}
+
+/// Request for chunks similar to one indexed chunk ("more like this")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarRequest {
+    /// Session identifier
+    pub session: String,
+
+    /// File of the source chunk, as stored in the index
+    pub file_path: String,
+
+    /// Index of the source chunk within its file
+    pub chunk_index: usize,
+
+    /// Number of similar chunks to return (search default if unset)
+    #[serde(default)]
+    pub k: Option<usize>,
+
+    /// Shortest seed term in characters (default 3)
+    #[serde(default)]
+    pub min_term_len: Option<usize>,
+
+    /// Number of seed terms taken from the source chunk (default 10)
+    #[serde(default)]
+    pub max_terms: Option<usize>,
+
+    /// Leave out every chunk of the source file, not just the source chunk
+    #[serde(default)]
+    pub exclude_source_file: bool,
+}
+
+/// A chunk similar to the source chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarResult {
+    /// The matching chunk, scored by its seed term matches
+    #[serde(flatten)]
+    pub result: SearchResult,
+
+    /// Seed terms the chunk contains, most distinctive first
+    pub matched_terms: Vec<String>,
+}
+
+/// Response from a similar-chunks search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarResponse {
+    /// File of the source chunk
+    pub file_path: String,
+
+    /// Index of the source chunk within its file
+    pub chunk_index: usize,
+
+    /// Terms searched for, most distinctive first
+    pub seed_terms: Vec<String>,
+
+    /// Similar chunks, best first
+    pub results: Vec<SimilarResult>,
+
+    /// Query duration in milliseconds
+    pub duration_ms: u64,
 }
 
 /// Response from listing sessions
@@ -194,6 +1321,8 @@ mod tests {
             start_offset: 0,
             end_offset: 13,
             chunk_index: 0,
+            records: None,
+            section: None,
         };
 
         assert_eq!(chunk.text, "Hello, world!");
@@ -207,6 +1336,18 @@ mod tests {
             chunks_created: 500,
             duration_ms: 1000,
             session: "test-session".to_string(),
+            files_deduplicated: 0,
+            skipped: Default::default(),
+            skipped_files: Vec::new(),
+            bytes_stripped: 0,
+            files_streamed: 0,
+            truncated_files: Vec::new(),
+            files_renamed: 0,
+            renamed_files: Vec::new(),
+            patterns: Default::default(),
+            pattern_warning: None,
+            provenance: None,
+            profile: None,
         };
 
         let response: IndexResponse = stats.into();
@@ -227,6 +1368,37 @@ mod tests {
         assert_eq!(req.query, "test query");
         assert_eq!(req.session, "test-session");
         assert_eq!(req.k, Some(10));
+        assert!(!req.group_by_file);
+    }
+
+    #[test]
+    fn test_file_group_more_chunk_indexes() {
+        let hit = |chunk_index: usize, score: f32| SearchResult {
+            score,
+            text: String::new(),
+            file_path: "src/lib.rs".to_string(),
+            chunk_index,
+            session: None,
+            start_offset: 0,
+            end_offset: 0,
+            also_present_at: vec![],
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        };
+        let group = FileGroup {
+            file_path: "src/lib.rs".to_string(),
+            best_score: 3.0,
+            hits: vec![hit(4, 3.0), hit(1, 2.0), hit(7, 1.0)],
+        };
+
+        assert_eq!(group.best().chunk_index, 4);
+        assert_eq!(group.more_chunk_indexes(), vec![1, 7]);
     }
 
     #[test]
@@ -240,4 +1412,105 @@ mod tests {
         assert!(req.include_patterns.is_empty());
         assert!(req.exclude_patterns.is_empty());
     }
+
+    #[test]
+    fn test_skip_counts() {
+        let mut counts = SkipCounts::default();
+        assert_eq!(counts.total(), 0);
+        assert!(counts.breakdown().is_empty());
+
+        counts.add(SkipReason::TooLarge);
+        counts.add(SkipReason::NotUtf8);
+        counts.add(SkipReason::TooLarge);
+
+        assert_eq!(counts.get(SkipReason::TooLarge), 2);
+        assert_eq!(counts.total(), 3);
+        assert_eq!(
+            counts.breakdown(),
+            vec![(SkipReason::TooLarge, 2), (SkipReason::NotUtf8, 1)]
+        );
+        assert_eq!(counts.summary(), "2 too large, 1 not UTF-8");
+    }
+
+    #[test]
+    fn test_skip_reason_serialization() {
+        let json = serde_json::to_string(&SkipReason::ExcludedByPattern).unwrap();
+        assert_eq!(json, "\"excluded_by_pattern\"");
+
+        // Stats from before skip tracking still deserialize
+        let stats: IndexStats = serde_json::from_str(
+            r#"{"files_indexed": 1, "chunks_created": 2, "duration_ms": 3, "session": "s"}"#,
+        )
+        .unwrap();
+        assert_eq!(stats.skipped, SkipCounts::default());
+        assert!(stats.skipped_files.is_empty());
+    }
+
+    #[test]
+    fn test_session_search_defaults_fill_unset_options() {
+        let defaults = SessionSearchDefaults {
+            k: Some(20),
+            fuzziness: Some(1),
+            path_boost: Some(0.5),
+            output_mode: Some(OutputMode::Locations),
+        };
+        let mut request: SearchRequest =
+            serde_json::from_str(r#"{"query": "q", "session": "s"}"#).unwrap();
+        defaults.apply(&mut request);
+        assert_eq!(request.k, Some(20));
+        assert_eq!(request.fuzziness, Some(1));
+        assert_eq!(request.path_boost, Some(0.5));
+
+        // Request values win, including an explicit 0
+        let mut request: SearchRequest = serde_json::from_str(
+            r#"{"query": "q", "session": "s", "k": 8, "fuzziness": 0, "path_boost": 0.0}"#,
+        )
+        .unwrap();
+        defaults.apply(&mut request);
+        assert_eq!(request.k, Some(8));
+        assert_eq!(request.fuzziness, Some(0));
+        assert_eq!(request.path_boost, Some(0.0));
+
+        assert_eq!(
+            defaults.summary(),
+            "k=20, fuzziness=1, path_boost=0.5, output_mode=locations"
+        );
+    }
+
+    #[test]
+    fn test_session_search_defaults_merge_and_validate() {
+        let current = SessionSearchDefaults {
+            k: Some(20),
+            fuzziness: Some(1),
+            ..Default::default()
+        };
+        let merged = current.merged(SessionSearchDefaults {
+            k: Some(8),
+            output_mode: Some(OutputMode::Full),
+            ..Default::default()
+        });
+        assert_eq!(merged.k, Some(8));
+        assert_eq!(merged.fuzziness, Some(1));
+        assert_eq!(merged.output_mode, Some(OutputMode::Full));
+        assert!(merged.validate().is_ok());
+        assert!(SessionSearchDefaults::default().is_empty());
+
+        let invalid = |defaults: SessionSearchDefaults| defaults.validate().unwrap_err();
+        assert!(invalid(SessionSearchDefaults {
+            k: Some(0),
+            ..Default::default()
+        })
+        .contains("k must be between 1 and 100"));
+        assert!(invalid(SessionSearchDefaults {
+            fuzziness: Some(3),
+            ..Default::default()
+        })
+        .contains("fuzziness"));
+        assert!(invalid(SessionSearchDefaults {
+            path_boost: Some(f32::NAN),
+            ..Default::default()
+        })
+        .contains("path_boost"));
+        assert!(OutputMode::parse("compact").is_err());
+    }
 }
@@ -123,22 +123,42 @@ impl XdgDirs {
         self.config_dir.join("config.toml")
     }
 
+    /// Get the CLI defaults file written by `shebe init`
+    pub fn cli_defaults_file(&self) -> PathBuf {
+        self.config_dir.join("cli.toml")
+    }
+
     /// Get sessions directory path
     pub fn sessions_dir(&self) -> PathBuf {
         self.data_dir.join("sessions")
     }
 
+    /// Get REPL history file path
+    pub fn repl_history_file(&self) -> PathBuf {
+        self.data_dir.join("repl_history")
+    }
+
     /// Get logs directory path
     pub fn logs_dir(&self) -> PathBuf {
         self.state_dir.join("logs")
     }
 
+    /// Get the file tool usage statistics are persisted to
+    pub fn tool_usage_file(&self) -> PathBuf {
+        self.state_dir.join("tool-usage.json")
+    }
+
     /// Get progress directory path (for future use)
     #[allow(dead_code)]
     pub fn progress_dir(&self) -> PathBuf {
         self.state_dir.join("progress")
     }
 
+    /// Get directory for shallow clones of remote repositories
+    pub fn clones_dir(&self) -> PathBuf {
+        self.cache_dir.join("clones")
+    }
+
     /// Get query cache directory path (for future use)
     #[allow(dead_code)]
     pub fn query_cache_dir(&self) -> PathBuf {
@@ -234,7 +254,7 @@ mod tests {
         let xdg = XdgDirs::new();
         // Should use XDG_CONFIG_HOME if SHEBE_* vars not set
         assert!(
-            xdg.config_dir == PathBuf::from("/custom/config/shebe")
+            xdg.config_dir == std::path::Path::new("/custom/config/shebe")
                 || xdg.config_dir.ends_with(".config/shebe"),
             "Expected /custom/config/shebe or default, got {:?}",
             xdg.config_dir
@@ -252,7 +272,7 @@ mod tests {
         let xdg = XdgDirs::new();
         // Should use XDG_DATA_HOME if SHEBE_* vars not set
         assert!(
-            xdg.data_dir == PathBuf::from("/custom/data/shebe")
+            xdg.data_dir == std::path::Path::new("/custom/data/shebe")
                 || xdg.data_dir.ends_with(".local/share/shebe"),
             "Expected /custom/data/shebe or default, got {:?}",
             xdg.data_dir
@@ -271,7 +291,7 @@ mod tests {
         let xdg = XdgDirs::new();
         // SHEBE_CONFIG_DIR should win
         assert!(
-            xdg.config_dir == PathBuf::from("/shebe/config"),
+            xdg.config_dir == std::path::Path::new("/shebe/config"),
             "Expected /shebe/config, got {:?}",
             xdg.config_dir
         );
@@ -289,7 +309,7 @@ mod tests {
         let xdg = XdgDirs::new();
         // SHEBE_DATA_DIR should win over XDG_DATA_HOME
         assert!(
-            xdg.data_dir == PathBuf::from("/shebe/data"),
+            xdg.data_dir == std::path::Path::new("/shebe/data"),
             "Expected /shebe/data, got {:?}",
             xdg.data_dir
         );
@@ -329,6 +349,17 @@ mod tests {
         assert!(sessions.ends_with("shebe/sessions"));
     }
 
+    #[test]
+    #[serial]
+    fn test_repl_history_in_data_dir() {
+        clear_env_vars();
+
+        let xdg = XdgDirs::new();
+        let history = xdg.repl_history_file();
+        assert!(history.starts_with(&xdg.data_dir));
+        assert!(history.ends_with("shebe/repl_history"));
+    }
+
     #[test]
     #[serial]
     fn test_logs_dir_resolution() {
@@ -434,6 +465,18 @@ mod tests {
         clear_env_vars();
     }
 
+    #[test]
+    #[serial]
+    fn test_xdg_clones_dir() {
+        clear_env_vars();
+        env::set_var("SHEBE_CACHE_DIR", "/test/cache");
+
+        let xdg = XdgDirs::new();
+        assert_eq!(xdg.clones_dir(), PathBuf::from("/test/cache/clones"));
+
+        clear_env_vars();
+    }
+
     #[test]
     #[serial]
     fn test_shebe_state_dir_priority() {
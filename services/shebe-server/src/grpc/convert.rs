@@ -0,0 +1,188 @@
+//! Conversions between core types and the generated messages
+//!
+//! Counts and offsets are `uint64` on the wire; timestamps are RFC 3339
+//! strings, as in the JSON output. Request fields left unset take the
+//! same defaults as the library's [`SearchBuilder`](crate::core::api::SearchBuilder).
+
+use super::proto;
+use crate::core::storage::SessionMetadata;
+use crate::core::types::{
+    FileGroup, FileRename, IndexStats, RecordRange, ScoreExplanation, SearchRequest,
+    SearchResponse, SearchResult, SearchWithin, SkipCounts, TermContribution, TruncatedFile,
+};
+
+impl From<proto::SearchRequest> for SearchRequest {
+    fn from(request: proto::SearchRequest) -> Self {
+        Self {
+            query: request.query,
+            session: request.session,
+            k: request.k.map(|k| k as usize),
+            group_by_file: request.group_by_file,
+            dedupe_overlaps: request.dedupe_overlaps.unwrap_or(true),
+            path_boost: request.path_boost,
+            fuzziness: request
+                .fuzziness
+                .map(|fuzziness| fuzziness.min(u8::MAX as u32) as u8),
+            expand_identifiers: request.expand_identifiers.unwrap_or(true),
+            bm25_k1: request.bm25_k1,
+            bm25_b: request.bm25_b,
+            within: request.within.map(|within| SearchWithin {
+                query: within.query,
+                k: within.k.map(|k| k as usize),
+            }),
+            penalize_short_chunks: request.penalize_short_chunks,
+            modified_within: request.modified_within,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: request.explain,
+        }
+    }
+}
+
+impl From<SearchResult> for proto::SearchResult {
+    fn from(result: SearchResult) -> Self {
+        Self {
+            score: result.score,
+            text: result.text,
+            file_path: result.file_path,
+            chunk_index: result.chunk_index as u64,
+            start_offset: result.start_offset as u64,
+            end_offset: result.end_offset as u64,
+            also_present_at: result.also_present_at,
+            matched_in_path: result.matched_in_path,
+            short_chunk_demoted: result.short_chunk_demoted,
+            modified_at: result.modified_at.map(|at| at.to_rfc3339()),
+            text_unavailable: result.text_unavailable,
+            indexed_partially: result.indexed_partially,
+            explanation: result.explanation.map(Into::into),
+            records: result.records.map(Into::into),
+        }
+    }
+}
+
+impl From<RecordRange> for proto::RecordRange {
+    fn from(records: RecordRange) -> Self {
+        Self {
+            first: records.first as u64,
+            last: records.last as u64,
+        }
+    }
+}
+
+impl From<ScoreExplanation> for proto::ScoreExplanation {
+    fn from(explanation: ScoreExplanation) -> Self {
+        Self {
+            terms: explanation.terms.into_iter().map(Into::into).collect(),
+            short_chunk_factor: explanation.short_chunk_factor,
+        }
+    }
+}
+
+impl From<TermContribution> for proto::TermContribution {
+    fn from(term: TermContribution) -> Self {
+        Self {
+            term: term.term,
+            in_path: term.in_path,
+            tf: term.tf,
+            idf: term.idf,
+            field_length: term.field_length,
+            contribution: term.contribution,
+        }
+    }
+}
+
+impl From<FileGroup> for proto::FileGroup {
+    fn from(group: FileGroup) -> Self {
+        Self {
+            file_path: group.file_path,
+            best_score: group.best_score,
+            hits: group.hits.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<SearchResponse> for proto::SearchResponse {
+    fn from(response: SearchResponse) -> Self {
+        Self {
+            query: response.query,
+            results: response.results.into_iter().map(Into::into).collect(),
+            count: response.count as u64,
+            duration_ms: response.duration_ms,
+            groups: response.groups.into_iter().map(Into::into).collect(),
+            overlap_duplicates_dropped: response.overlap_duplicates_dropped as u64,
+            short_chunks_demoted: response.short_chunks_demoted as u64,
+            fuzziness: response.fuzziness as u32,
+            explain_skipped: response.explain_skipped,
+        }
+    }
+}
+
+impl From<SkipCounts> for proto::SkipCounts {
+    fn from(skipped: SkipCounts) -> Self {
+        Self {
+            excluded_by_pattern: skipped.excluded_by_pattern as u64,
+            too_large: skipped.too_large as u64,
+            binary: skipped.binary as u64,
+            read_error: skipped.read_error as u64,
+            not_utf8: skipped.not_utf8 as u64,
+            line_too_long: skipped.line_too_long as u64,
+        }
+    }
+}
+
+impl From<TruncatedFile> for proto::TruncatedFile {
+    fn from(file: TruncatedFile) -> Self {
+        Self {
+            path: file.path,
+            chunks_indexed: file.chunks_indexed as u64,
+            bytes_indexed: file.bytes_indexed,
+            file_bytes: file.file_bytes,
+        }
+    }
+}
+
+impl From<FileRename> for proto::FileRename {
+    fn from(rename: FileRename) -> Self {
+        Self {
+            from: rename.from,
+            to: rename.to,
+        }
+    }
+}
+
+impl From<IndexStats> for proto::IndexStats {
+    fn from(stats: IndexStats) -> Self {
+        Self {
+            session: stats.session,
+            files_indexed: stats.files_indexed as u64,
+            chunks_created: stats.chunks_created as u64,
+            duration_ms: stats.duration_ms,
+            files_deduplicated: stats.files_deduplicated as u64,
+            skipped: Some(stats.skipped.into()),
+            bytes_stripped: stats.bytes_stripped,
+            files_streamed: stats.files_streamed as u64,
+            truncated_files: stats.truncated_files.into_iter().map(Into::into).collect(),
+            pattern_warning: stats.pattern_warning,
+            files_renamed: stats.files_renamed as u64,
+            renamed_files: stats.renamed_files.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<SessionMetadata> for proto::SessionMetadata {
+    fn from(metadata: SessionMetadata) -> Self {
+        Self {
+            id: metadata.id,
+            repository_path: metadata.repository_path.to_string_lossy().into_owned(),
+            created_at: metadata.created_at.to_rfc3339(),
+            last_indexed_at: metadata.last_indexed_at.to_rfc3339(),
+            files_indexed: metadata.files_indexed as u64,
+            chunks_created: metadata.chunks_created as u64,
+            index_size_bytes: metadata.index_size_bytes,
+            chunk_size: metadata.config.chunk_size as u32,
+            overlap: metadata.config.overlap as u32,
+            schema_version: metadata.schema_version,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! gRPC status codes for core errors
+
+use crate::core::error::ShebeError;
+use tonic::{Code, Status};
+
+impl From<ShebeError> for Status {
+    fn from(err: ShebeError) -> Self {
+        let code = match &err {
+            ShebeError::SessionNotFound(_)
+            | ShebeError::CollectionNotFound(_)
+            | ShebeError::JobNotFound(_) => Code::NotFound,
+            ShebeError::SessionAlreadyExists(_) | ShebeError::CollectionAlreadyExists(_) => {
+                Code::AlreadyExists
+            }
+            ShebeError::InvalidSession(_)
+            | ShebeError::InvalidPath(_)
+            | ShebeError::InvalidQuery(_)
+            | ShebeError::InvalidQueryField { .. }
+            | ShebeError::InvalidResultHandle(_)
+            | ShebeError::ConfigError(_)
+            | ShebeError::TomlError(_) => Code::InvalidArgument,
+            ShebeError::IndexLocked(_) => Code::Aborted,
+            ShebeError::IndexCorrupted { .. } => Code::DataLoss,
+            ShebeError::Cancelled(_) => Code::Cancelled,
+            ShebeError::IndexTooLarge(_) => Code::ResourceExhausted,
+            ShebeError::ReadOnly(_) => Code::FailedPrecondition,
+            ShebeError::AccessDenied(_) => Code::PermissionDenied,
+            ShebeError::SessionUnavailable { .. } => Code::Unavailable,
+            ShebeError::IndexingFailed(_)
+            | ShebeError::SearchFailed(_)
+            | ShebeError::StorageError(_)
+            | ShebeError::GitError(_)
+            | ShebeError::IoError(_)
+            | ShebeError::SerdeError(_) => Code::Internal,
+        };
+        Status::new(code, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes() {
+        let status = Status::from(ShebeError::SessionNotFound("missing".to_string()));
+        assert_eq!(status.code(), Code::NotFound);
+        assert!(status.message().contains("missing"));
+
+        let status = Status::from(ShebeError::InvalidQuery("empty".to_string()));
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let status = Status::from(ShebeError::ReadOnly("delete".to_string()));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+}
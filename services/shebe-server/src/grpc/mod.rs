@@ -0,0 +1,29 @@
+//! gRPC adapter (`grpc` feature)
+//!
+//! Serves the `shebe.v1.Search` service from `proto/shebe/v1/search.proto`
+//! over the same [`Services`](crate::core::services::Services) container
+//! as the MCP server, next to the standard `grpc.health.v1` and reflection
+//! services so grpcurl works without the proto file. `shebe-mcp` starts
+//! it when `[server] grpc_port` is set.
+//!
+//! - **convert**: Core types to and from the generated messages
+//! - **error**: `ShebeError` to gRPC status codes
+//! - **server**: Listener setup, health, reflection and authentication
+//! - **service**: The `Search` service implementation
+
+pub mod convert;
+pub mod error;
+pub mod server;
+pub mod service;
+
+/// Messages, client and server generated from the proto file
+#[allow(clippy::all, clippy::pedantic, missing_docs)]
+pub mod proto {
+    tonic::include_proto!("shebe.v1");
+
+    /// Encoded file descriptor set, served by the reflection service
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("shebe_descriptor");
+}
+
+pub use server::GrpcServer;
+pub use service::SearchService;
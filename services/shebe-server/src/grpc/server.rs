@@ -0,0 +1,154 @@
+//! gRPC listener
+//!
+//! Serves the `Search` service with the standard health and reflection
+//! services. When `server.grpc_auth_token` is set, `Search` calls need
+//! an `authorization: Bearer <token>` header; health checks and
+//! reflection stay open so probes and grpcurl work without it.
+
+use super::proto;
+use super::proto::search_server::SearchServer;
+use super::service::SearchService;
+use crate::core::error::{Result, ShebeError};
+use crate::core::services::Services;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::service::Interceptor;
+use tonic::transport::Server;
+use tonic::{Request, Status};
+
+/// gRPC server over a shared [`Services`] container
+pub struct GrpcServer {
+    services: Arc<Services>,
+}
+
+impl GrpcServer {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Bind `addr` and serve until the task is dropped
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+            ShebeError::ConfigError(format!("Cannot bind gRPC listener on {addr}: {e}"))
+        })?;
+        tracing::info!("gRPC server listening on {addr}");
+        self.serve_with_listener(listener).await
+    }
+
+    /// Serve on a listener that is already bound (tests bind port 0)
+    pub async fn serve_with_listener(self, listener: tokio::net::TcpListener) -> Result<()> {
+        let token = self
+            .services
+            .config
+            .current()
+            .server
+            .grpc_auth_token
+            .clone();
+        if token.is_none() {
+            if let Ok(addr) = listener.local_addr() {
+                if !addr.ip().is_loopback() {
+                    tracing::warn!(
+                        "gRPC server on {addr} accepts unauthenticated calls; \
+                         set server.grpc_auth_token"
+                    );
+                }
+            }
+        }
+
+        let (health_reporter, health_service) = tonic_health::server::health_reporter();
+        health_reporter
+            .set_serving::<SearchServer<SearchService>>()
+            .await;
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+            .build_v1()
+            .map_err(|e| ShebeError::ConfigError(format!("Cannot build gRPC reflection: {e}")))?;
+        let search = SearchServer::with_interceptor(
+            SearchService::new(Arc::clone(&self.services)),
+            BearerAuth { token },
+        );
+
+        Server::builder()
+            .add_service(health_service)
+            .add_service(reflection)
+            .add_service(search)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .map_err(|e| {
+                ShebeError::IoError(std::io::Error::other(format!("gRPC server failed: {e}")))
+            })
+    }
+}
+
+/// Checks the bearer token of each `Search` call, when one is configured
+#[derive(Clone)]
+struct BearerAuth {
+    token: Option<String>,
+}
+
+impl Interceptor for BearerAuth {
+    fn call(&mut self, request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        let Some(token) = &self.token else {
+            return Ok(request);
+        };
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if header
+            .strip_prefix("Bearer ")
+            .is_some_and(|given| token_matches(given.trim(), token))
+        {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("missing or invalid bearer token"))
+        }
+    }
+}
+
+/// Compare tokens in constant time, as the MCP TCP listener does
+fn token_matches(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(auth: &mut BearerAuth, header: Option<&str>) -> tonic::Code {
+        let mut request = Request::new(());
+        if let Some(header) = header {
+            request
+                .metadata_mut()
+                .insert("authorization", header.parse().unwrap());
+        }
+        match auth.call(request) {
+            Ok(_) => tonic::Code::Ok,
+            Err(status) => status.code(),
+        }
+    }
+
+    #[test]
+    fn test_bearer_auth() {
+        let mut open = BearerAuth { token: None };
+        assert_eq!(call(&mut open, None), tonic::Code::Ok);
+
+        let mut auth = BearerAuth {
+            token: Some("s3cret".to_string()),
+        };
+        assert_eq!(call(&mut auth, Some("Bearer s3cret")), tonic::Code::Ok);
+        assert_eq!(
+            call(&mut auth, Some("Bearer wrong")),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(call(&mut auth, None), tonic::Code::Unauthenticated);
+    }
+}
@@ -0,0 +1,215 @@
+//! The `shebe.v1.Search` service
+//!
+//! Each call runs its storage or search work on a blocking thread, as
+//! the MCP tools do. Sessions listed by `ListSessions` leave out the
+//! temporary sessions of a running config comparison.
+
+use super::proto;
+use super::proto::search_server::Search;
+use crate::core::compare::is_comparison_session;
+use crate::core::indexer::repo_config::IndexOverrides;
+use crate::core::logging::spawn_blocking_in_context;
+use crate::core::services::Services;
+use crate::core::types::SearchRequest;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+/// Results buffered ahead of a slow `SearchStream` reader
+const STREAM_BUFFER: usize = 32;
+
+/// `Search` service over a shared [`Services`] container
+pub struct SearchService {
+    services: Arc<Services>,
+}
+
+impl SearchService {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Run storage or search work off the async runtime
+    async fn blocking<T, F>(&self, work: F) -> Result<T, Status>
+    where
+        F: FnOnce(&Services) -> crate::core::error::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let services = Arc::clone(&self.services);
+        spawn_blocking_in_context(move || work(&services))
+            .await
+            .map_err(|e| Status::internal(format!("Task failed: {e}")))?
+            .map_err(Status::from)
+    }
+}
+
+/// Check a session ID: 1-64 alphanumeric, hyphen or underscore
+/// characters, starting with an alphanumeric one, optionally prefixed
+/// by a namespace of the same form (`team-a/backend`)
+fn validate_session(session: &str) -> Result<(), Status> {
+    let valid = session.splitn(2, '/').all(|part| {
+        (1..=64).contains(&part.len())
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            && part.starts_with(|c: char| c.is_alphanumeric())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(Status::invalid_argument(format!(
+            "Invalid session '{session}': use 1-64 alphanumeric, hyphen or underscore \
+             characters, starting with an alphanumeric one, optionally as namespace/name"
+        )))
+    }
+}
+
+/// Check that `path` is an existing absolute path and canonicalize it
+fn validate_path(path: &str) -> Result<PathBuf, Status> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(Status::invalid_argument("Path must be absolute"));
+    }
+    path.canonicalize().map_err(|e| {
+        Status::invalid_argument(format!("Cannot resolve path {}: {e}", path.display()))
+    })
+}
+
+/// Search request with a checked session ID
+fn search_request(request: proto::SearchRequest) -> Result<SearchRequest, Status> {
+    validate_session(&request.session)?;
+    Ok(request.into())
+}
+
+#[tonic::async_trait]
+impl Search for SearchService {
+    async fn index(
+        &self,
+        request: Request<proto::IndexRequest>,
+    ) -> Result<Response<proto::IndexStats>, Status> {
+        let request = request.into_inner();
+        validate_session(&request.session)?;
+        let path = validate_path(&request.path)?;
+        let overrides = IndexOverrides {
+            chunk_size: request.chunk_size.map(|v| v as usize),
+            overlap: request.overlap.map(|v| v as usize),
+            include_patterns: (!request.include_patterns.is_empty())
+                .then_some(request.include_patterns),
+            exclude_patterns: (!request.exclude_patterns.is_empty())
+                .then_some(request.exclude_patterns),
+            max_chunks_per_file: request.max_chunks_per_file.map(|v| v as usize),
+            ..IndexOverrides::default()
+        };
+
+        let outcome = self
+            .blocking(move |services| {
+                services
+                    .index(&path)
+                    .session(request.session)
+                    .overrides(overrides)
+                    .force(request.force)
+                    .run()
+            })
+            .await?;
+        Ok(Response::new(outcome.stats.into()))
+    }
+
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<proto::SearchResponse>, Status> {
+        let request = search_request(request.into_inner())?;
+        let response = self
+            .blocking(move |services| services.search.search(request))
+            .await?;
+        Ok(Response::new(response.into()))
+    }
+
+    type SearchStreamStream = ReceiverStream<Result<proto::SearchResult, Status>>;
+
+    /// Ranking needs the whole top k (path boost, short-chunk penalty,
+    /// overlap dedupe), so results are sent once it is known: one message
+    /// each, converted as they are sent, instead of one response that can
+    /// exceed the client's message size limit
+    async fn search_stream(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<Self::SearchStreamStream>, Status> {
+        let request = search_request(request.into_inner())?;
+        let response = self
+            .blocking(move |services| services.search.search(request))
+            .await?;
+
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            for result in response.results {
+                // The client went away; stop converting
+                if sender.send(Ok(result.into())).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn list_sessions(
+        &self,
+        _request: Request<proto::ListSessionsRequest>,
+    ) -> Result<Response<proto::ListSessionsResponse>, Status> {
+        let sessions = self
+            .blocking(|services| services.storage.list_sessions())
+            .await?;
+        Ok(Response::new(proto::ListSessionsResponse {
+            sessions: sessions
+                .into_iter()
+                .filter(|session| !is_comparison_session(&session.id))
+                .map(Into::into)
+                .collect(),
+        }))
+    }
+
+    async fn delete_session(
+        &self,
+        request: Request<proto::DeleteSessionRequest>,
+    ) -> Result<Response<proto::DeleteSessionResponse>, Status> {
+        let session = request.into_inner().session;
+        validate_session(&session)?;
+        let freed_bytes = self
+            .blocking(move |services| {
+                let storage = &services.storage;
+                if !storage.session_exists(&session) {
+                    return Err(crate::core::error::ShebeError::SessionNotFound(session));
+                }
+                let freed_bytes = storage.measure_session_size(&session);
+                storage.delete_session(&session)?;
+                Ok(freed_bytes)
+            })
+            .await?;
+        Ok(Response::new(proto::DeleteSessionResponse { freed_bytes }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_session() {
+        assert!(validate_session("backend-v2").is_ok());
+        assert!(validate_session("a").is_ok());
+        assert!(validate_session("").is_err());
+        assert!(validate_session("../etc").is_err());
+        assert!(validate_session("_cmp-1a2b3c4d-a").is_err());
+        assert!(validate_session(&"x".repeat(65)).is_err());
+        assert!(validate_session("team-a/backend").is_ok());
+        assert!(validate_session("team-a/").is_err());
+        assert!(validate_session("a/b/c").is_err());
+    }
+
+    #[test]
+    fn test_validate_path_must_be_absolute() {
+        let err = validate_path("relative/repo").unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+}
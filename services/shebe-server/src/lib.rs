@@ -14,6 +14,7 @@
 //!   - search (BM25 queries)
 //!   - indexer (file walking, chunking)
 //!   - services (unified service container)
+//!   - api (builder-style library API, [`Shebe`])
 //!
 //! - **mcp**: MCP adapter (depends on core)
 //!   - server, tools, protocol
@@ -21,12 +22,15 @@
 //! - **cli**: CLI adapter (depends on core)
 //!   - commands, output formatting
 //!
+//! - **grpc**: gRPC adapter (depends on core, `grpc` feature)
+//!   - Search service, health, reflection
+//!
 //! # Key Features
 //!
 //! - UTF-8 safe chunking (character-based, never panics)
 //! - BM25 search via Tantivy (no vector embeddings)
 //! - Session-based indexing (isolated indexes)
-//! - MCP server (14 tools)
+//! - MCP server (16 tools)
 //! - CLI for scripting and manual operations
 //! - Production ready (Docker, logging)
 
@@ -39,13 +43,23 @@ pub mod cli;
 // MCP (Model Context Protocol) adapter
 pub mod mcp;
 
+// gRPC adapter, built with the `grpc` feature
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 // Re-export commonly used types for convenience
+pub use core::api::{IndexBuilder, IndexOutcome, SearchBuilder, Shebe};
 pub use core::config::Config;
 pub use core::error::{Result, ShebeError};
 pub use core::services::Services;
-pub use core::storage::{SessionConfig, SessionMetadata, StorageManager};
+pub use core::storage::{SessionConfig, SessionConfigBuilder, SessionMetadata, StorageManager};
 pub use core::types::*;
 
+// Compile the README's library example as a doctest
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
+
 #[cfg(test)]
 mod tests {
     // Module-level integration tests are in tests/ directory
@@ -41,6 +41,12 @@ impl From<crate::core::error::ShebeError> for McpError {
             ShebeError::InvalidSession(s) => {
                 McpError::InvalidParams(format!("Invalid session: {s}"))
             }
+            ShebeError::CollectionNotFound(s) => {
+                McpError::InvalidParams(format!("Collection not found: {s}"))
+            }
+            ShebeError::CollectionAlreadyExists(s) => {
+                McpError::InvalidParams(format!("Collection already exists: {s}"))
+            }
             ShebeError::InvalidPath(p) => McpError::InvalidParams(format!("Invalid path: {p}")),
             ShebeError::InvalidQuery(s) => McpError::InvalidParams(format!("Invalid query: {s}")),
             ShebeError::InvalidQueryField {
@@ -72,6 +78,56 @@ impl From<crate::core::error::ShebeError> for McpError {
                 format!("Search failed: {s}"),
             ),
             ShebeError::StorageError(s) => McpError::InternalError(format!("Storage error: {s}")),
+            ShebeError::IndexLocked(s) => McpError::ToolError(
+                crate::mcp::protocol::INDEX_LOCKED,
+                format!("Index is locked: {s}"),
+            ),
+            ShebeError::IndexCorrupted {
+                session,
+                cause,
+                repository,
+            } => {
+                let remedy = match repository {
+                    Some(path) => format!(
+                        "Rebuild it from {} with reindex_session, or remove it with delete_session.",
+                        path.display()
+                    ),
+                    None => "Its repository is no longer available; \
+                             remove it with delete_session."
+                        .to_string(),
+                };
+                McpError::ToolError(
+                    crate::mcp::protocol::INDEX_CORRUPTED,
+                    format!("Index of session '{session}' is corrupted: {cause}\n{remedy}"),
+                )
+            }
+            e @ ShebeError::SessionUnavailable { .. } => McpError::ToolError(
+                crate::mcp::protocol::SESSION_UNAVAILABLE,
+                format!("{e}. Pass reset: true to get_session_info to retry at once."),
+            ),
+            ShebeError::GitError(s) => McpError::ToolError(
+                crate::mcp::protocol::INDEXING_FAILED,
+                format!("Git error: {s}"),
+            ),
+            ShebeError::JobNotFound(s) => McpError::InvalidParams(format!("Job not found: {s}")),
+            ShebeError::InvalidResultHandle(s) => {
+                McpError::InvalidParams(format!("Invalid result handle: {s}"))
+            }
+            ShebeError::Cancelled(s) => McpError::ToolError(
+                crate::mcp::protocol::INDEXING_FAILED,
+                format!("Cancelled: {s}"),
+            ),
+            ShebeError::IndexTooLarge(s) => McpError::ToolError(
+                crate::mcp::protocol::INDEXING_FAILED,
+                format!("Index too large: {s}"),
+            ),
+            ShebeError::ReadOnly(s) => McpError::ToolError(
+                crate::mcp::protocol::READ_ONLY,
+                format!("Server is in read-only mode: {s}"),
+            ),
+            e @ ShebeError::AccessDenied(_) => {
+                McpError::ToolError(crate::mcp::protocol::ACCESS_DENIED, e.to_string())
+            }
             ShebeError::IoError(e) => McpError::InternalError(format!("I/O error: {e}")),
             ShebeError::SerdeError(e) => {
                 McpError::InternalError(format!("Serialization error: {e}"))
@@ -193,6 +249,69 @@ mod tests {
         assert!(matches!(mcp, McpError::InternalError(_)));
     }
 
+    #[test]
+    fn test_index_locked_to_mcp_error() {
+        let err = ShebeError::IndexLocked("busy".to_string());
+        let mcp: McpError = err.into();
+        match mcp {
+            McpError::ToolError(code, msg) => {
+                assert_eq!(code, protocol::INDEX_LOCKED);
+                assert!(msg.contains("busy"));
+            }
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_index_corrupted_to_mcp_error() {
+        let corrupted = |repository: Option<&str>| ShebeError::IndexCorrupted {
+            session: "docs".to_string(),
+            cause: "truncated segment".to_string(),
+            repository: repository.map(std::path::PathBuf::from),
+        };
+
+        match McpError::from(corrupted(Some("/repo"))) {
+            McpError::ToolError(code, msg) => {
+                assert_eq!(code, protocol::INDEX_CORRUPTED);
+                assert!(msg.contains("truncated segment"));
+                assert!(msg.contains("from /repo with reindex_session"), "{msg}");
+            }
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+        match McpError::from(corrupted(None)) {
+            McpError::ToolError(_, msg) => {
+                assert!(!msg.contains("reindex_session"), "{msg}");
+                assert!(msg.contains("delete_session"), "{msg}");
+            }
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_git_error_to_mcp_error() {
+        let err = ShebeError::GitError("authentication failed".to_string());
+        let mcp: McpError = err.into();
+        match mcp {
+            McpError::ToolError(code, msg) => {
+                assert_eq!(code, protocol::INDEXING_FAILED);
+                assert!(msg.contains("authentication failed"));
+            }
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_job_errors_to_mcp_error() {
+        let mcp: McpError = ShebeError::JobNotFound("job-1".to_string()).into();
+        assert!(matches!(mcp, McpError::InvalidParams(ref msg) if msg.contains("job-1")));
+
+        let mcp: McpError = ShebeError::Cancelled("stopped".to_string()).into();
+        assert!(matches!(mcp, McpError::ToolError(code, _) if code == protocol::INDEXING_FAILED));
+
+        let mcp: McpError = ShebeError::IndexTooLarge("too many files".to_string()).into();
+        assert!(matches!(mcp, McpError::ToolError(code, _) if code == protocol::INDEXING_FAILED));
+    }
+
     #[test]
     fn test_io_error_to_mcp_error() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file missing");
@@ -1,75 +1,240 @@
 //! MCP protocol method handlers
 
+use crate::core::logging::{is_slow, truncate_for_log, PhaseTimings};
 use crate::core::services::Services;
 use crate::mcp::error::McpError;
+use crate::mcp::handshake::{self, Handshake};
 use crate::mcp::protocol::*;
 use crate::mcp::tools::{
-    DeleteSessionHandler, FindFileHandler, FindReferencesHandler, GetServerInfoHandler,
-    GetSessionInfoHandler, IndexRepositoryHandler, ListDirHandler, ListSessionsHandler,
-    PreviewChunkHandler, ReadFileHandler, ReindexSessionHandler, SearchCodeHandler,
-    ShowShebeConfigHandler, ToolRegistry, UpgradeSessionHandler,
+    CancelJobHandler, CompactSessionHandler, CompareConfigsHandler, CreateCollectionHandler,
+    DeleteCollectionHandler, DeleteSessionHandler, DiffSessionsHandler, FindFileHandler,
+    FindReferencesHandler, FindSimilarHandler, GetJobStatusHandler, GetServerInfoHandler,
+    GetSessionInfoHandler, GetStorageReportHandler, IndexRepositoryAsyncHandler,
+    IndexRepositoryHandler, ListDirHandler, ListSessionsHandler, ListTreeHandler, LocateHandler,
+    OptimizeSessionHandler, PreviewChunkHandler, ReadFileHandler, ReindexSessionHandler,
+    ReloadConfigHandler, RunSelfTestHandler, SampleFilesHandler, SearchBatchHandler,
+    SearchCodeHandler, SearchPathsHandler, SetReindexScheduleHandler, SetSessionDefaultsHandler,
+    ShowShebeConfigHandler, ToolRegistry, ToolUsage, UpdateCollectionHandler,
+    UpgradeSessionHandler,
+};
+use crate::mcp::utils::{
+    detect_repository, no_sessions_guidance, session_not_found_hint, NO_SESSIONS_GUIDANCE_TOOLS,
 };
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use tracing::{debug, info};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, info_span, warn, Instrument};
 
 pub struct ProtocolHandlers {
     initialized: AtomicBool,
-    tool_registry: ToolRegistry,
+    /// What this connection's client agreed on in `initialize`
+    handshake: Mutex<Option<Handshake>>,
+    tool_registry: Arc<ToolRegistry>,
+    services: Arc<Services>,
+    /// Git checkout the server was started in, suggested for indexing
+    repository_hint: Option<PathBuf>,
 }
 
 impl ProtocolHandlers {
     pub fn new(services: Arc<Services>) -> Self {
-        let mut registry = ToolRegistry::new();
+        let config = services.config.current();
+        let usage = Arc::new(ToolUsage::new(config.mcp.usage_stats));
+        let mut registry = ToolRegistry::with_usage(Arc::clone(&usage));
 
         // Register all available tools
         registry.register(Arc::new(SearchCodeHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(SearchBatchHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(ListSessionsHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(GetSessionInfoHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(IndexRepositoryHandler::new(Arc::clone(&services))));
-        registry.register(Arc::new(GetServerInfoHandler::new()));
-        registry.register(Arc::new(ShowShebeConfigHandler::new(Arc::clone(
-            &services.config,
-        ))));
+        registry.register(Arc::new(
+            GetServerInfoHandler::new()
+                .with_read_only(services.storage.is_read_only())
+                .with_profile(config.profile_name(), &config.storage.index_dir)
+                .with_usage(usage)
+                .with_open_breaker(Arc::clone(services.storage.open_breaker()))
+                .with_storage(Arc::clone(&services.storage)),
+        ));
+        registry.register(Arc::new(
+            ShowShebeConfigHandler::new(Arc::clone(&services.config))
+                .with_storage(Arc::clone(&services.storage)),
+        ));
         registry.register(Arc::new(ReadFileHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(DeleteSessionHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(CreateCollectionHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(UpdateCollectionHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(DeleteCollectionHandler::new(Arc::clone(
+            &services,
+        ))));
         registry.register(Arc::new(ListDirHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(FindFileHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(SampleFilesHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(LocateHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(SearchPathsHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(FindReferencesHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(PreviewChunkHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(FindSimilarHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(ReindexSessionHandler::new(Arc::clone(&services))));
         registry.register(Arc::new(UpgradeSessionHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(CompactSessionHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(OptimizeSessionHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(ListTreeHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(IndexRepositoryAsyncHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(GetJobStatusHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(CancelJobHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(GetStorageReportHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(SetReindexScheduleHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(SetSessionDefaultsHandler::new(Arc::clone(
+            &services,
+        ))));
+        registry.register(Arc::new(DiffSessionsHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(CompareConfigsHandler::new(Arc::clone(&services))));
+        registry.register(Arc::new(ReloadConfigHandler::new(Arc::clone(
+            &services.config,
+        ))));
+        registry.register(Arc::new(RunSelfTestHandler::new(Arc::clone(&services))));
+
+        // Apply [mcp.tools] enable/disable settings
+        let unknown = registry.configure(&config.mcp.tools);
+        if !unknown.is_empty() {
+            warn!(
+                "Unknown tool names in [mcp.tools] ignored: {}. Valid names: {}",
+                unknown.join(", "),
+                registry.names().join(", ")
+            );
+        }
+        let disabled = config.mcp.disabled_tools();
+        if !disabled.is_empty() {
+            info!("Disabled tools: {}", disabled.join(", "));
+        }
+        if services.storage.is_read_only() {
+            registry.set_read_only(true);
+            info!("Read-only mode: tools that change sessions are unavailable");
+        }
 
+        // Re-apply [mcp.tools] whenever the configuration is reloaded
+        let tool_registry = Arc::new(registry);
+        let weak_registry = Arc::downgrade(&tool_registry);
+        services.config.on_reload(move |config| {
+            if let Some(registry) = Weak::upgrade(&weak_registry) {
+                registry.usage().set_enabled(config.mcp.usage_stats);
+                let unknown = registry.configure(&config.mcp.tools);
+                if !unknown.is_empty() {
+                    warn!(
+                        "Unknown tool names in [mcp.tools] ignored: {}",
+                        unknown.join(", ")
+                    );
+                }
+            }
+        });
+
+        Self {
+            initialized: AtomicBool::new(false),
+            handshake: Mutex::new(None),
+            tool_registry,
+            services,
+            repository_hint: std::env::current_dir()
+                .ok()
+                .and_then(|dir| detect_repository(&dir)),
+        }
+    }
+
+    /// Replace the repository suggested when no sessions exist
+    ///
+    /// Defaults to the working directory if it is a git checkout.
+    pub fn with_repository_hint(mut self, repository: Option<PathBuf>) -> Self {
+        self.repository_hint = repository;
+        self
+    }
+
+    /// Usage statistics of the tools (see [`ToolUsage`])
+    pub fn usage(&self) -> &Arc<ToolUsage> {
+        self.tool_registry.usage()
+    }
+
+    /// Handlers for another client connection
+    ///
+    /// Shares the tool registry, services and repository hint; only the
+    /// connection's own state (its handshake and whether it sent
+    /// `initialized`) is fresh.
+    pub fn for_connection(&self) -> Self {
         Self {
             initialized: AtomicBool::new(false),
-            tool_registry: registry,
+            handshake: Mutex::new(None),
+            tool_registry: Arc::clone(&self.tool_registry),
+            services: Arc::clone(&self.services),
+            repository_hint: self.repository_hint.clone(),
         }
     }
 
     /// Handle initialize request
+    ///
+    /// Accepts the client's protocol version only if it is supported
+    /// (see [`handshake`]); otherwise answers with an `INVALID_PARAMS`
+    /// error listing the supported versions.
     pub async fn handle_initialize(
         &self,
         request: JsonRpcRequest,
     ) -> Result<JsonRpcResponse, McpError> {
-        let _params: InitializeParams =
-            serde_json::from_value(request.params.unwrap_or(Value::Null))?;
+        let params: InitializeParams =
+            match serde_json::from_value(request.params.unwrap_or(Value::Null)) {
+                Ok(params) => params,
+                Err(e) => {
+                    return Ok(self.create_error_response(
+                        request.id,
+                        INVALID_PARAMS,
+                        format!("Invalid initialize params: {e}"),
+                    ));
+                }
+            };
+        let client = handshake::client_label(&params.client_info);
 
-        info!("Client initialized");
+        let protocol_version = match handshake::negotiate_version(&params.protocol_version) {
+            Ok(version) => version,
+            Err(message) => {
+                warn!(client = %client, "Rejected initialize: {}", message);
+                let mut response = self.create_error_response(request.id, INVALID_PARAMS, message);
+                if let Some(error) = response.error.as_mut() {
+                    error.data = Some(handshake::unsupported_version_data(
+                        &params.protocol_version,
+                    ));
+                }
+                return Ok(response);
+            }
+        };
 
+        let config = self.services.config.current();
+        let capabilities =
+            handshake::server_capabilities(&config, self.services.storage.is_read_only());
         let result = InitializeResult {
-            protocol_version: "2024-11-05".to_string(),
-            capabilities: ServerCapabilities {
-                tools: ToolsCapability {
-                    list_changed: false,
-                },
-            },
+            protocol_version: protocol_version.to_string(),
+            capabilities: capabilities.clone(),
             server_info: ServerInfo {
                 name: "shebe-mcp".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
+                profile: Some(config.profile_name().to_string()),
             },
         };
 
+        info!(client = %client, protocol_version, "Client initialized");
+        *self.handshake.lock().unwrap_or_else(|e| e.into_inner()) = Some(Handshake {
+            protocol_version: protocol_version.to_string(),
+            client,
+            capabilities,
+        });
+
         Ok(JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id: request.id,
@@ -78,6 +243,14 @@ impl ProtocolHandlers {
         })
     }
 
+    /// What this connection's client agreed on, once it initialized
+    pub fn handshake(&self) -> Option<Handshake> {
+        self.handshake
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
     /// Handle initialized notification
     pub async fn handle_initialized(
         &self,
@@ -144,7 +317,7 @@ impl ProtocolHandlers {
             }
         };
 
-        let params: ToolCallParams = match serde_json::from_value(params_value) {
+        let mut params: ToolCallParams = match serde_json::from_value(params_value) {
             Ok(p) => p,
             Err(e) => {
                 return Ok(self.create_error_response(
@@ -155,20 +328,67 @@ impl ProtocolHandlers {
             }
         };
 
-        // Get tool handler from registry
-        let handler = match self.tool_registry.get(&params.name) {
-            Some(h) => h,
-            None => {
-                return Ok(self.create_error_response(
-                    request.id,
-                    INVALID_REQUEST,
-                    format!("Tool not found: {}", params.name),
-                ));
+        // Get tool handler from registry (unknown and disabled tools fail)
+        let handler = match self.tool_registry.resolve(&params.name) {
+            Ok(h) => h,
+            Err(e) => {
+                let (code, message) = Self::error_parts(&e);
+                return Ok(self.create_error_response(request.id, code, message));
             }
         };
 
-        // Execute tool and handle errors
-        match handler.execute(params.arguments).await {
+        // `team-a/backend` and `backend` may name the same session; tools
+        // and caches see one spelling
+        if let Some(Value::String(session)) = params.arguments.get_mut("session") {
+            *session = self.services.storage.canonical_session_id(session);
+        }
+
+        // Execute tool and handle errors; the call holds off idle maintenance
+        let _active = self.services.activity.begin();
+        let session = params
+            .arguments
+            .get("session")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let path = params
+            .arguments
+            .get("path")
+            .and_then(Value::as_str)
+            .map(PathBuf::from);
+        let query = call_query(&params.arguments);
+
+        // Every log line of the call carries the tool and session;
+        // phase timings are filled in by the tool as it runs
+        let span = info_span!("tool_call", tool = %params.name, session = tracing::field::Empty);
+        if let Some(session) = &session {
+            span.record("session", session.as_str());
+        }
+        let phases = PhaseTimings::default();
+        let timeout = Duration::from_secs(self.services.config.current().limits.tool_timeout_sec);
+        let start = Instant::now();
+        let outcome = phases
+            .clone()
+            .scope(handshake::scope(
+                self.handshake(),
+                self.tool_registry
+                    .execute(handler.as_ref(), params.arguments, timeout),
+            ))
+            .instrument(span.clone())
+            .await;
+        span.in_scope(|| {
+            self.log_call(
+                &params.name,
+                session.as_deref(),
+                query.as_deref(),
+                start.elapsed(),
+                &phases,
+            )
+        });
+
+        // The call may have found a corrupted index to re-index
+        self.services.recover_corrupted_sessions();
+
+        match outcome {
             Ok(result) => Ok(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id: request.id,
@@ -176,15 +396,15 @@ impl ProtocolHandlers {
                 error: None,
             }),
             Err(e) => {
-                // Map McpError to proper JSON-RPC error code
-                let (code, message) = match &e {
-                    McpError::ParseError(msg) => (PARSE_ERROR, msg.clone()),
-                    McpError::InvalidRequest(msg) => (INVALID_REQUEST, msg.clone()),
-                    McpError::InvalidParams(msg) => (INVALID_PARAMS, msg.clone()),
-                    McpError::InternalError(msg) => (INTERNAL_ERROR, msg.clone()),
-                    McpError::ToolError(code, msg) => (*code, msg.clone()),
-                    McpError::Io(e) => (INTERNAL_ERROR, format!("I/O error: {e}")),
-                    McpError::Json(e) => (INTERNAL_ERROR, format!("JSON error: {e}")),
+                let (code, message) = Self::error_parts(&e);
+                let message = match session {
+                    Some(session) if code == INDEX_CORRUPTED => {
+                        self.with_recovery_note(message, &session)
+                    }
+                    Some(session) => {
+                        self.with_session_hint(message, &session, &params.name, path.as_deref())
+                    }
+                    None => message,
                 };
 
                 Ok(self.create_error_response(request.id, code, message))
@@ -192,6 +412,87 @@ impl ProtocolHandlers {
         }
     }
 
+    /// Log a finished tool call, and warn when it was slow
+    ///
+    /// Calls taking at least `log.slow_query_ms` are logged at WARN
+    /// with the tool, session, truncated query and phase timings.
+    fn log_call(
+        &self,
+        tool: &str,
+        session: Option<&str>,
+        query: Option<&str>,
+        elapsed: Duration,
+        phases: &PhaseTimings,
+    ) {
+        let duration_ms = elapsed.as_millis() as u64;
+        debug!(duration_ms, phases = %phases, "Tool call completed");
+
+        if is_slow(elapsed, self.services.config.current().log.slow_query_ms) {
+            warn!(
+                tool,
+                session = session.unwrap_or("-"),
+                query = %truncate_for_log(query.unwrap_or("-")),
+                duration_ms,
+                phases = %phases,
+                "Slow tool call"
+            );
+        }
+    }
+
+    /// Map an McpError to its JSON-RPC error code and message
+    fn error_parts(error: &McpError) -> (i32, String) {
+        match error {
+            McpError::ParseError(msg) => (PARSE_ERROR, msg.clone()),
+            McpError::InvalidRequest(msg) => (INVALID_REQUEST, msg.clone()),
+            McpError::InvalidParams(msg) => (INVALID_PARAMS, msg.clone()),
+            McpError::InternalError(msg) => (INTERNAL_ERROR, msg.clone()),
+            McpError::ToolError(code, msg) => (*code, msg.clone()),
+            McpError::Io(e) => (INTERNAL_ERROR, format!("I/O error: {e}")),
+            McpError::Json(e) => (INTERNAL_ERROR, format!("JSON error: {e}")),
+        }
+    }
+
+    /// Append near-match suggestions to a "session not found" error
+    ///
+    /// Tools word the error differently, so any "not found" error for a
+    /// session argument that does not exist gets the hint. With no
+    /// sessions at all, search and browse tools instead explain how to
+    /// index one, suggesting the call's `path` argument or the
+    /// repository the server was started in.
+    fn with_session_hint(
+        &self,
+        message: String,
+        session: &str,
+        tool: &str,
+        path: Option<&Path>,
+    ) -> String {
+        if !message.contains("not found") || self.services.storage.session_exists(session) {
+            return message;
+        }
+        let sessions = match self.services.storage.list_session_ids() {
+            Ok(sessions) => sessions,
+            Err(_) => return message,
+        };
+        if sessions.is_empty() && NO_SESSIONS_GUIDANCE_TOOLS.contains(&tool) {
+            let repository = path.or(self.repository_hint.as_deref());
+            return format!("{message}{}", no_sessions_guidance(session, repository));
+        }
+        format!("{message}{}", session_not_found_hint(session, &sessions))
+    }
+
+    /// Add the automatic re-index of a corrupted session to its error
+    fn with_recovery_note(&self, message: String, session: &str) -> String {
+        match self.services.jobs.active_job(session) {
+            Some(job_id) if self.services.config.current().storage.reindex_on_corruption => {
+                format!(
+                    "{message}\nRe-indexing automatically as {job_id} \
+                     (storage.reindex_on_corruption); retry once get_job_status reports it completed."
+                )
+            }
+            _ => message,
+        }
+    }
+
     /// Create an error response with proper structure
     fn create_error_response(
         &self,
@@ -223,3 +524,24 @@ impl ProtocolHandlers {
 }
 
 // ProtocolHandlers now requires Services, so Default is not implemented
+/// Query text of a tool call for the slow query log
+///
+/// Uses the `query` or `symbol` argument, or the queries of a batch
+/// joined with ` | `.
+fn call_query(arguments: &Value) -> Option<String> {
+    if let Some(query) = arguments
+        .get("query")
+        .or_else(|| arguments.get("symbol"))
+        .and_then(Value::as_str)
+    {
+        return Some(query.to_string());
+    }
+    let queries = arguments.get("queries")?.as_array()?;
+    Some(
+        queries
+            .iter()
+            .filter_map(|q| q.get("query").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    )
+}
@@ -0,0 +1,122 @@
+//! MCP initialize handshake
+//!
+//! The client proposes a protocol version; the server accepts it if it
+//! is one of [`SUPPORTED_PROTOCOL_VERSIONS`] and otherwise answers with
+//! an error listing the supported ones, so an incompatible client stops
+//! at `initialize` instead of failing on a later call. Capabilities come
+//! from `[mcp.capabilities]` and the features this server was built and
+//! configured with. The accepted handshake is kept per connection and
+//! made visible to tool calls (see [`Handshake::current`]), which is how
+//! `get_server_info` echoes it.
+
+use crate::core::config::Config;
+use crate::mcp::protocol::{ClientInfo, ServerCapabilities, ToolsCapability};
+use serde_json::{json, Value};
+use std::future::Future;
+
+/// Protocol versions this server speaks, oldest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-06-18"];
+
+tokio::task_local! {
+    static HANDSHAKE: Option<Handshake>;
+}
+
+/// What a connection agreed on in `initialize`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: String,
+    pub client: String,
+    pub capabilities: ServerCapabilities,
+}
+
+impl Handshake {
+    /// Handshake of the connection the current tool call came from
+    ///
+    /// `None` outside a tool call or before the client initialized.
+    pub fn current() -> Option<Self> {
+        HANDSHAKE.try_with(Clone::clone).ok().flatten()
+    }
+}
+
+/// Run `future` (a tool call) with `handshake` as its connection's
+pub async fn scope<F: Future>(handshake: Option<Handshake>, future: F) -> F::Output {
+    HANDSHAKE.scope(handshake, future).await
+}
+
+/// `name version` of the client, as logged and shown
+pub fn client_label(client: &ClientInfo) -> String {
+    format!("{} {}", client.name, client.version)
+}
+
+/// The client's protocol version if supported, or the error message
+pub fn negotiate_version(requested: &str) -> Result<&'static str, String> {
+    SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|version| **version == requested)
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "Unsupported protocol version '{requested}'; this server supports {}",
+                SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+            )
+        })
+}
+
+/// Capabilities advertised to clients under `config`
+///
+/// `read_only` is the storage's actual mode, which `--read-only` can
+/// set without the configuration saying so.
+pub fn server_capabilities(config: &Config, read_only: bool) -> ServerCapabilities {
+    let experimental = config.mcp.capabilities.experimental.then(|| {
+        json!({
+            "shebe": {
+                "readOnly": read_only,
+                "grpc": cfg!(feature = "grpc"),
+                "usageStats": config.mcp.usage_stats,
+                "scheduledReindex": config.schedule.enabled,
+                "idleMaintenance": config.maintenance.enabled,
+            }
+        })
+    });
+    ServerCapabilities {
+        tools: ToolsCapability {
+            list_changed: false,
+        },
+        experimental,
+    }
+}
+
+/// Error `data` for an unsupported version, as the MCP spec suggests
+pub fn unsupported_version_data(requested: &str) -> Value {
+    json!({
+        "supported": SUPPORTED_PROTOCOL_VERSIONS,
+        "requested": requested,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_version() {
+        assert_eq!(negotiate_version("2024-11-05"), Ok("2024-11-05"));
+        assert_eq!(negotiate_version("2025-06-18"), Ok("2025-06-18"));
+        let err = negotiate_version("1.0").unwrap_err();
+        assert!(err.contains("'1.0'"), "{err}");
+        assert!(err.contains("2024-11-05, 2025-06-18"), "{err}");
+    }
+
+    #[test]
+    fn test_server_capabilities() {
+        let mut config = Config::default();
+        let capabilities = server_capabilities(&config, true);
+        let shebe = &capabilities.experimental.unwrap()["shebe"];
+        assert_eq!(shebe["readOnly"], true);
+        assert_eq!(shebe["grpc"], cfg!(feature = "grpc"));
+        assert_eq!(shebe["usageStats"], true);
+
+        config.mcp.capabilities.experimental = false;
+        assert!(server_capabilities(&config, false).experimental.is_none());
+    }
+}
@@ -1,14 +1,18 @@
 //! Cursor-based pagination for MCP tools
 //!
-//! Provides opaque cursors for list_dir pagination. Cursors are
-//! base64-encoded JSON containing offset, sort mode and a session
+//! Provides opaque cursors for list_dir and search_code pagination.
+//! Cursors are base64-encoded JSON containing an offset, what the
+//! listing was built from (sort mode or query) and a session
 //! fingerprint so stale cursors are rejected after reindexing.
 
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64;
 
 use crate::core::storage::SessionMetadata;
+use crate::core::types::SearchRequest;
 
 /// Cursor for paginating list_dir results.
 ///
@@ -27,20 +31,12 @@ pub struct ListDirCursor {
 impl ListDirCursor {
     /// Encode cursor as URL-safe base64
     pub fn encode(&self) -> String {
-        let json =
-            serde_json::to_string(self).expect("ListDirCursor serialization should not fail");
-        URL_SAFE_NO_PAD.encode(json.as_bytes())
+        encode_cursor(self)
     }
 
     /// Decode cursor from URL-safe base64
     pub fn decode(encoded: &str) -> Result<Self, String> {
-        let bytes = URL_SAFE_NO_PAD
-            .decode(encoded)
-            .map_err(|e| format!("Invalid cursor encoding: {e}"))?;
-
-        let json = String::from_utf8(bytes).map_err(|e| format!("Invalid cursor UTF-8: {e}"))?;
-
-        serde_json::from_str(&json).map_err(|e| format!("Invalid cursor format: {e}"))
+        decode_cursor(encoded)
     }
 
     /// Verify this cursor matches the current session state.
@@ -48,13 +44,52 @@ impl ListDirCursor {
     /// Returns an error message if the fingerprint does not match
     /// (session was reindexed since cursor was created).
     pub fn verify(&self, metadata: &SessionMetadata) -> Result<(), String> {
-        let current = session_fingerprint(metadata);
-        if self.fingerprint != current {
-            return Err("Cursor is stale (session was reindexed). \
-                 Start from the first page by omitting the cursor."
+        verify_fingerprint(&self.fingerprint, metadata)
+    }
+}
+
+/// Cursor for paginating search_code results.
+///
+/// The next page is the results ranked after `last_rank` for the same
+/// query and options, so the query fingerprint must match as well as
+/// the session fingerprint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchCursor {
+    /// Rank of the last result returned so far (1-based)
+    pub last_rank: usize,
+    /// Query fingerprint (see [`query_fingerprint`])
+    pub query: String,
+    /// Session fingerprint for staleness detection
+    pub fingerprint: String,
+}
+
+impl SearchCursor {
+    /// Encode cursor as URL-safe base64
+    pub fn encode(&self) -> String {
+        encode_cursor(self)
+    }
+
+    /// Decode cursor from URL-safe base64
+    pub fn decode(encoded: &str) -> Result<Self, String> {
+        decode_cursor(encoded)
+    }
+
+    /// Verify this cursor was created for `request` and the current
+    /// session state.
+    ///
+    /// Returns an error message if the query or options differ, or the
+    /// session was reindexed since the cursor was created.
+    pub fn verify(
+        &self,
+        request: &SearchRequest,
+        metadata: &SessionMetadata,
+    ) -> Result<(), String> {
+        if self.query != query_fingerprint(request) {
+            return Err("Cursor does not match this query. Use the same query \
+                 and search options as the previous page, or omit the cursor."
                 .to_string());
         }
-        Ok(())
+        verify_fingerprint(&self.fingerprint, metadata)
     }
 }
 
@@ -71,6 +106,46 @@ pub fn session_fingerprint(meta: &SessionMetadata) -> String {
     )
 }
 
+/// Fingerprint of what ranks search results: the session, query and
+/// options of `request`.
+///
+/// `k` and `explain` are left out: they change how many results are
+/// shown and how, not their order.
+pub fn query_fingerprint(request: &SearchRequest) -> String {
+    let ranking = SearchRequest {
+        k: None,
+        explain: false,
+        ..request.clone()
+    };
+    let json =
+        serde_json::to_string(&ranking).expect("SearchRequest serialization should not fail");
+    format!("{:016x}", xxh3_64(json.as_bytes()))
+}
+
+fn encode_cursor(cursor: &impl Serialize) -> String {
+    let json = serde_json::to_string(cursor).expect("cursor serialization should not fail");
+    URL_SAFE_NO_PAD.encode(json.as_bytes())
+}
+
+fn decode_cursor<T: DeserializeOwned>(encoded: &str) -> Result<T, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid cursor encoding: {e}"))?;
+
+    let json = String::from_utf8(bytes).map_err(|e| format!("Invalid cursor UTF-8: {e}"))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Invalid cursor format: {e}"))
+}
+
+fn verify_fingerprint(fingerprint: &str, metadata: &SessionMetadata) -> Result<(), String> {
+    if fingerprint != session_fingerprint(metadata) {
+        return Err("Cursor is stale (session was reindexed). \
+             Start from the first page by omitting the cursor."
+            .to_string());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,7 +162,20 @@ mod tests {
             chunks_created: 8741,
             index_size_bytes: 0,
             config: crate::core::storage::SessionConfig::default(),
-            schema_version: 3,
+            schema_version: 4,
+            files_deduplicated: 0,
+            created_by_version: "unknown".to_string(),
+            tantivy_schema_hash: "unknown".to_string(),
+            index_settings: None,
+            remote: None,
+            archive: None,
+            skipped: Default::default(),
+            chunk_text_bytes: 0,
+            reindex_schedule: None,
+            last_scheduled_run: None,
+            search_defaults: None,
+            modified_times: None,
+            provenance: None,
         }
     }
 
@@ -222,6 +310,84 @@ mod tests {
         );
     }
 
+    fn sample_request(query: &str) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            session: "test".to_string(),
+            k: Some(10),
+            group_by_file: false,
+            dedupe_overlaps: true,
+            path_boost: None,
+            fuzziness: None,
+            expand_identifiers: true,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        }
+    }
+
+    #[test]
+    fn test_search_cursor_roundtrip_and_verify() {
+        let meta = sample_metadata();
+        let request = sample_request("auth");
+        let cursor = SearchCursor {
+            last_rank: 10,
+            query: query_fingerprint(&request),
+            fingerprint: session_fingerprint(&meta),
+        };
+
+        let decoded = SearchCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(cursor, decoded);
+        assert!(decoded.verify(&request, &meta).is_ok());
+    }
+
+    #[test]
+    fn test_query_fingerprint_ignores_page_size_and_explain() {
+        let request = sample_request("auth");
+        let other_page = SearchRequest {
+            k: Some(25),
+            explain: true,
+            ..request.clone()
+        };
+        assert_eq!(query_fingerprint(&request), query_fingerprint(&other_page));
+
+        let fuzzy = SearchRequest {
+            fuzziness: Some(1),
+            ..request.clone()
+        };
+        assert_ne!(query_fingerprint(&request), query_fingerprint(&fuzzy));
+        assert_ne!(
+            query_fingerprint(&request),
+            query_fingerprint(&sample_request("session"))
+        );
+    }
+
+    #[test]
+    fn test_search_cursor_verify_rejects_other_query_and_stale_session() {
+        let meta = sample_metadata();
+        let cursor = SearchCursor {
+            last_rank: 10,
+            query: query_fingerprint(&sample_request("auth")),
+            fingerprint: session_fingerprint(&meta),
+        };
+
+        let err = cursor.verify(&sample_request("login"), &meta).unwrap_err();
+        assert!(err.contains("does not match this query"), "{err}");
+
+        let stale = SearchCursor {
+            fingerprint: "0-0-0".to_string(),
+            ..cursor
+        };
+        let err = stale.verify(&sample_request("auth"), &meta).unwrap_err();
+        assert!(err.contains("stale"), "{err}");
+    }
+
     #[test]
     fn test_cursor_decode_valid_base64_wrong_schema() {
         // Valid JSON with missing required fields
@@ -47,6 +47,13 @@ pub const SESSION_NOT_FOUND: i32 = -32001;
 pub const SESSION_ALREADY_EXISTS: i32 = -32002;
 pub const INDEXING_FAILED: i32 = -32003;
 pub const SEARCH_FAILED: i32 = -32004;
+pub const INDEX_LOCKED: i32 = -32005;
+pub const UNAUTHORIZED: i32 = -32006;
+pub const READ_ONLY: i32 = -32007;
+pub const TIMED_OUT: i32 = -32008;
+pub const INDEX_CORRUPTED: i32 = -32009;
+pub const SESSION_UNAVAILABLE: i32 = -32010;
+pub const ACCESS_DENIED: i32 = -32011;
 
 /// MCP initialize request parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,12 +85,15 @@ pub struct InitializeResult {
     pub server_info: ServerInfo,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerCapabilities {
     pub tools: ToolsCapability,
+    /// Non-standard capabilities, keyed by vendor (`shebe`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ToolsCapability {
     pub list_changed: bool,
@@ -93,6 +103,9 @@ pub struct ToolsCapability {
 pub struct ServerInfo {
     pub name: String,
     pub version: String,
+    /// Active storage profile (`default` when none was selected)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 /// MCP tool schema
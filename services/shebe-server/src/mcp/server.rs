@@ -1,67 +1,189 @@
 //! MCP server implementation
 
+use crate::core::logging::new_request_id;
 use crate::core::services::Services;
 use crate::mcp::error::McpError;
 use crate::mcp::handlers::ProtocolHandlers;
 use crate::mcp::protocol::*;
-use crate::mcp::transport::StdioTransport;
+use crate::mcp::transport::{is_authorized, LineTransport, AUTH_PREFIX, MAX_AUTH_LINE};
 use serde_json::Value;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tracing::{debug, error, info};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, BufReader, Lines};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+/// How long a TCP client has to send its `Authorization` line
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct McpServer {
-    transport: StdioTransport,
     handlers: Arc<ProtocolHandlers>,
+    /// Token TCP clients must present (`[mcp] auth_token`)
+    auth_token: Option<String>,
 }
 
 impl McpServer {
     pub fn new(services: Arc<Services>) -> Self {
+        let auth_token = services.config.current().mcp.auth_token.clone();
         Self {
-            transport: StdioTransport::new(),
             handlers: Arc::new(ProtocolHandlers::new(services)),
+            auth_token,
+        }
+    }
+
+    /// Persist tool usage statistics to `path` (see
+    /// [`ToolUsage::persist_to`](crate::mcp::tools::ToolUsage::persist_to))
+    ///
+    /// Loads the counts saved there and writes them back every minute and
+    /// when [`run`](Self::run) or [`listen`](Self::listen) returns.
+    pub fn persist_usage(self, path: PathBuf) -> Self {
+        self.handlers.usage().persist_to(path);
+        self
+    }
+
+    /// Server for another client, sharing tools and services
+    fn for_connection(&self) -> Self {
+        Self {
+            handlers: Arc::new(self.handlers.for_connection()),
+            auth_token: self.auth_token.clone(),
         }
     }
 
-    /// Run the MCP server (blocking)
-    pub async fn run(&mut self) -> Result<(), McpError> {
+    /// Run the MCP server on stdin/stdout until EOF or Ctrl+C (blocking)
+    pub async fn run(&self) -> Result<(), McpError> {
         info!("Starting Shebe MCP server");
 
-        let stdin = tokio::io::stdin();
-        let mut reader = BufReader::new(stdin).lines();
+        let outcome = tokio::select! {
+            result = self.serve_connection(tokio::io::stdin(), tokio::io::stdout()) => result,
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received shutdown signal");
+                Ok(())
+            }
+        };
+
+        info!("MCP server shutting down");
+        self.handlers.usage().flush();
+        outcome
+    }
 
-        // Spawn signal handler
-        let mut shutdown = tokio::spawn(async {
-            tokio::signal::ctrl_c().await.ok();
-        });
+    /// Listen for TCP clients on `addr` until Ctrl+C (blocking)
+    pub async fn listen(&self, addr: SocketAddr) -> Result<(), McpError> {
+        let listener = TcpListener::bind(addr).await?;
+
+        let outcome = tokio::select! {
+            result = self.serve_tcp(listener) => result,
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received shutdown signal");
+                Ok(())
+            }
+        };
+
+        info!("MCP server shutting down");
+        self.handlers.usage().flush();
+        outcome
+    }
+
+    /// Accept TCP clients on `listener`, one JSON-RPC session each
+    ///
+    /// Connections are served concurrently with the same tools and
+    /// services. With `[mcp] auth_token` set, a client's first line
+    /// must be `Authorization: Bearer <token>`; otherwise it gets an
+    /// error response and is disconnected.
+    pub async fn serve_tcp(&self, listener: TcpListener) -> Result<(), McpError> {
+        let local = listener.local_addr()?;
+        info!("Serving MCP over TCP on {}", local);
+        if self.auth_token.is_none() {
+            warn!(
+                "No mcp.auth_token configured: every client that can reach {} is accepted",
+                local
+            );
+        }
 
-        // Main loop
         loop {
-            tokio::select! {
-                // Process stdin messages
-                line = reader.next_line() => {
-                    match line? {
-                        Some(line) if !line.trim().is_empty() => {
-                            self.process_and_respond(&line).await?;
-                        }
-                        None => break, // EOF
-                        _ => continue,
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Out of file descriptors and the like; keep serving
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let connection = self.for_connection();
+            tokio::spawn(
+                async move {
+                    info!("Client connected");
+                    match connection.serve_tcp_connection(stream).await {
+                        Ok(()) => info!("Client disconnected"),
+                        Err(e) => warn!("Connection closed with error: {}", e),
                     }
                 }
+                .instrument(info_span!("mcp_connection", peer = %peer)),
+            );
+        }
+    }
 
-                // Handle Ctrl+C
-                _ = &mut shutdown => {
-                    info!("Received shutdown signal");
-                    break;
-                }
+    /// Serve one client over `reader` and `writer` until it disconnects
+    ///
+    /// Requests are answered in order. No authentication is done.
+    pub async fn serve_connection<R, W>(&self, reader: R, writer: W) -> Result<(), McpError>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = BufReader::new(reader).lines();
+        let mut transport = LineTransport::from_writer(writer);
+        self.serve_lines(&mut lines, &mut transport).await
+    }
+
+    /// Authenticate a TCP client if required, then serve it
+    async fn serve_tcp_connection(&self, stream: TcpStream) -> Result<(), McpError> {
+        let (reader, writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut transport = LineTransport::from_writer(writer);
+
+        if let Some(token) = &self.auth_token {
+            let line = tokio::time::timeout(AUTH_TIMEOUT, read_auth_line(&mut reader)).await;
+            let authorized = matches!(&line, Ok(Ok(Some(line))) if is_authorized(line, token));
+            if !authorized {
+                warn!("Rejected unauthenticated client");
+                let response = self.create_error_response(
+                    None,
+                    UNAUTHORIZED,
+                    format!("Unauthorized: send '{AUTH_PREFIX}<token>' as the first line"),
+                );
+                transport.send_response(response).await?;
+                return Ok(());
             }
         }
 
-        info!("MCP server shutting down");
+        self.serve_lines(&mut reader.lines(), &mut transport).await
+    }
+
+    /// Answer each non-empty line until EOF
+    async fn serve_lines<R, W>(
+        &self,
+        lines: &mut Lines<BufReader<R>>,
+        transport: &mut LineTransport<W>,
+    ) -> Result<(), McpError>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        while let Some(line) = lines.next_line().await? {
+            if !line.trim().is_empty() {
+                self.process_and_respond(&line, transport).await?;
+            }
+        }
         Ok(())
     }
 
-    async fn process_and_respond(&mut self, line: &str) -> Result<(), McpError> {
+    async fn process_and_respond<W: AsyncWrite + Unpin>(
+        &self,
+        line: &str,
+        transport: &mut LineTransport<W>,
+    ) -> Result<(), McpError> {
         debug!("Received: {}", line);
 
         // Check if this is a JSON-RPC notification (no id field).
@@ -73,7 +195,7 @@ impl McpServer {
         match self.process_message(line).await {
             Ok(response) => {
                 if !is_notification {
-                    self.transport.send_response(response).await?;
+                    transport.send_response(response).await?;
                 }
             }
             Err(e) => {
@@ -81,7 +203,7 @@ impl McpServer {
                 if !is_notification {
                     let error_response =
                         self.create_error_response(None, INTERNAL_ERROR, e.to_string());
-                    self.transport.send_response(error_response).await?;
+                    transport.send_response(error_response).await?;
                 }
             }
         }
@@ -94,22 +216,33 @@ impl McpServer {
         let request: JsonRpcRequest =
             serde_json::from_str(line).map_err(|e| McpError::ParseError(e.to_string()))?;
 
+        // Every log line of this request carries its ID
+        let span = info_span!(
+            "mcp_request",
+            request_id = %new_request_id(),
+            method = %request.method
+        );
+
         // Route to handler
-        match request.method.as_str() {
-            "initialize" => self.handlers.handle_initialize(request).await,
-            "initialized" | "notifications/initialized" => {
-                self.handlers.handle_initialized(request).await
+        async {
+            match request.method.as_str() {
+                "initialize" => self.handlers.handle_initialize(request).await,
+                "initialized" | "notifications/initialized" => {
+                    self.handlers.handle_initialized(request).await
+                }
+                "notifications/cancelled" => self.handlers.handle_cancelled(request).await,
+                "tools/list" => self.handlers.handle_tools_list(request).await,
+                "tools/call" => self.handlers.handle_tools_call(request).await,
+                "ping" => self.handlers.handle_ping(request).await,
+                _ => Ok(self.create_error_response(
+                    request.id,
+                    METHOD_NOT_FOUND,
+                    format!("Unknown method: {}", request.method),
+                )),
             }
-            "notifications/cancelled" => self.handlers.handle_cancelled(request).await,
-            "tools/list" => self.handlers.handle_tools_list(request).await,
-            "tools/call" => self.handlers.handle_tools_call(request).await,
-            "ping" => self.handlers.handle_ping(request).await,
-            _ => Ok(self.create_error_response(
-                request.id,
-                METHOD_NOT_FOUND,
-                format!("Unknown method: {}", request.method),
-            )),
         }
+        .instrument(span)
+        .await
     }
 
     fn create_error_response(
@@ -131,6 +264,21 @@ impl McpServer {
     }
 }
 
+/// Read a client's first line, `None` at EOF or past [`MAX_AUTH_LINE`] bytes
+///
+/// Bounded so an unauthenticated client cannot make the server buffer
+/// an endless line while the token is still unchecked.
+async fn read_auth_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    (&mut *reader)
+        .take(MAX_AUTH_LINE)
+        .read_line(&mut line)
+        .await?;
+    Ok(line.ends_with('\n').then_some(line))
+}
+
 // McpServer now requires Services, so Default is not implemented
 
 #[cfg(test)]
@@ -140,33 +288,125 @@ mod tests {
     use tempfile::TempDir;
 
     fn create_test_server() -> (McpServer, TempDir) {
+        create_test_server_with(Config::default())
+    }
+
+    fn create_test_server_with(mut config: Config) -> (McpServer, TempDir) {
         let temp_dir = TempDir::new().unwrap();
-        let mut config = Config::default();
         config.storage.index_dir = temp_dir.path().to_path_buf();
         let services = Arc::new(Services::new(config));
         (McpServer::new(services), temp_dir)
     }
 
-    #[tokio::test]
-    async fn test_process_message_initialize() {
-        let (server, _temp) = create_test_server();
-
-        let msg = serde_json::json!({
+    fn initialize_message(protocol_version: &str) -> String {
+        serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
             "method": "initialize",
             "params": {
-                "protocolVersion": "2024-11-05",
+                "protocolVersion": protocol_version,
                 "capabilities": {"tools": {}},
                 "clientInfo": {"name": "test", "version": "1.0"}
             }
         })
-        .to_string();
+        .to_string()
+    }
 
-        let response = server.process_message(&msg).await.unwrap();
+    #[tokio::test]
+    async fn test_process_message_initialize() {
+        let (server, _temp) = create_test_server();
+
+        let response = server
+            .process_message(&initialize_message("2024-11-05"))
+            .await
+            .unwrap();
         assert!(response.error.is_none());
         let result = response.result.unwrap();
         assert_eq!(result["protocolVersion"], "2024-11-05");
+        assert_eq!(result["serverInfo"]["name"], "shebe-mcp");
+        assert_eq!(result["serverInfo"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(result["serverInfo"]["profile"], "default");
+        assert_eq!(result["capabilities"]["tools"]["listChanged"], false);
+        let shebe = &result["capabilities"]["experimental"]["shebe"];
+        assert_eq!(shebe["readOnly"], false);
+        assert_eq!(shebe["grpc"], cfg!(feature = "grpc"));
+
+        // get_server_info echoes what this connection negotiated
+        let call = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "get_server_info", "arguments": {}}
+        })
+        .to_string();
+        let response = server.process_message(&call).await.unwrap();
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("- **Client:** test 1.0"), "{text}");
+        assert!(text.contains("- **Negotiated Protocol:** MCP 2024-11-05"));
+        assert!(text.contains("\"experimental\":{\"shebe\""), "{text}");
+
+        // A newer supported version is echoed back
+        let response = server
+            .process_message(&initialize_message("2025-06-18"))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["protocolVersion"], "2025-06-18");
+    }
+
+    #[tokio::test]
+    async fn test_process_message_initialize_unsupported_version() {
+        let (server, _temp) = create_test_server();
+
+        let response = server
+            .process_message(&initialize_message("2023-01-01"))
+            .await
+            .unwrap();
+        assert_eq!(response.id, Some(serde_json::json!(1)));
+        assert!(response.result.is_none());
+        let err = response.error.unwrap();
+        assert_eq!(err.code, INVALID_PARAMS);
+        assert!(
+            err.message
+                .contains("Unsupported protocol version '2023-01-01'"),
+            "{}",
+            err.message
+        );
+        let data = err.data.unwrap();
+        assert_eq!(data["requested"], "2023-01-01");
+        assert_eq!(
+            data["supported"],
+            serde_json::json!(crate::mcp::handshake::SUPPORTED_PROTOCOL_VERSIONS)
+        );
+
+        // Missing fields are reported against the request, too
+        let msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "initialize",
+            "params": {"capabilities": {}}
+        })
+        .to_string();
+        let response = server.process_message(&msg).await.unwrap();
+        assert_eq!(response.id, Some(serde_json::json!(5)));
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_process_message_initialize_capability_disabled() {
+        let mut config = Config::default();
+        config.mcp.capabilities.experimental = false;
+        let (server, _temp) = create_test_server_with(config);
+
+        let response = server
+            .process_message(&initialize_message("2024-11-05"))
+            .await
+            .unwrap();
+        let capabilities = &response.result.unwrap()["capabilities"];
+        assert!(capabilities.get("experimental").is_none(), "{capabilities}");
+        assert_eq!(capabilities["tools"]["listChanged"], false);
     }
 
     #[tokio::test]
@@ -200,7 +440,7 @@ mod tests {
         assert!(response.error.is_none());
         let result = response.result.unwrap();
         let tools = result["tools"].as_array().unwrap();
-        assert_eq!(tools.len(), 14);
+        assert_eq!(tools.len(), 35);
     }
 
     #[tokio::test]
@@ -277,4 +517,172 @@ mod tests {
         assert_eq!(err.code, INTERNAL_ERROR);
         assert_eq!(err.message, "test error");
     }
+
+    /// Events captured with the fields of their enclosing spans
+    mod capture {
+        use std::collections::BTreeMap;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id};
+        use tracing::{Event, Level, Subscriber};
+        use tracing_subscriber::layer::{Context, Layer};
+        use tracing_subscriber::registry::LookupSpan;
+
+        #[derive(Debug, Clone)]
+        pub struct Captured {
+            pub level: Level,
+            /// Event fields (including `message`), then span fields
+            pub fields: BTreeMap<String, String>,
+        }
+
+        #[derive(Clone, Default)]
+        pub struct Capture(pub Arc<Mutex<Vec<Captured>>>);
+
+        struct SpanFields(BTreeMap<String, String>);
+
+        #[derive(Default)]
+        struct Fields(BTreeMap<String, String>);
+
+        impl Visit for Fields {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{value:?}"));
+            }
+        }
+
+        impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+            fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+                let mut fields = Fields::default();
+                attrs.record(&mut fields);
+                let span = ctx.span(id).unwrap();
+                span.extensions_mut().insert(SpanFields(fields.0));
+            }
+
+            fn on_record(&self, id: &Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+                let mut fields = Fields::default();
+                values.record(&mut fields);
+                let span = ctx.span(id).unwrap();
+                let mut extensions = span.extensions_mut();
+                if let Some(existing) = extensions.get_mut::<SpanFields>() {
+                    existing.0.extend(fields.0);
+                }
+            }
+
+            fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+                let mut fields = Fields::default();
+                event.record(&mut fields);
+                if let Some(scope) = ctx.event_scope(event) {
+                    for span in scope {
+                        if let Some(span_fields) = span.extensions().get::<SpanFields>() {
+                            for (name, value) in &span_fields.0 {
+                                fields.0.entry(name.clone()).or_insert(value.clone());
+                            }
+                        }
+                    }
+                }
+                self.0.lock().unwrap().push(Captured {
+                    level: *event.metadata().level(),
+                    fields: fields.0,
+                });
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_logs_share_request_id() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        std::fs::write(repo.join("lib.rs"), "fn load_config() {}\n").unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("index");
+        config.log.slow_query_ms = 1;
+        let server = McpServer::new(Arc::new(Services::new(config)));
+
+        let capture = capture::Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let call = |id: u64, name: &str, arguments: Value| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments}
+            })
+            .to_string()
+        };
+        let index = call(
+            1,
+            "index_repository",
+            serde_json::json!({"path": repo, "session": "logged"}),
+        );
+        let search = call(
+            2,
+            "search_code",
+            serde_json::json!({"query": "load_config", "session": "logged"}),
+        );
+        assert!(server
+            .process_message(&index)
+            .await
+            .unwrap()
+            .error
+            .is_none());
+        assert!(server
+            .process_message(&search)
+            .await
+            .unwrap()
+            .error
+            .is_none());
+
+        let events = capture.0.lock().unwrap().clone();
+        let for_tool = |tool: &str| -> Vec<capture::Captured> {
+            events
+                .iter()
+                .filter(|e| e.fields.get("tool").map(String::as_str) == Some(tool))
+                .cloned()
+                .collect()
+        };
+
+        // Indexing logs (including the pipeline's) share one request ID
+        let index_events = for_tool("index_repository");
+        assert!(index_events.len() > 2);
+        let request_id = index_events[0].fields["request_id"].clone();
+        assert_eq!(request_id.len(), 36);
+        assert!(index_events
+            .iter()
+            .all(|e| e.fields["request_id"] == request_id
+                && e.fields["session"] == "logged"
+                && e.fields["method"] == "tools/call"));
+        let slow = index_events
+            .iter()
+            .find(|e| e.fields["message"] == "Slow tool call")
+            .expect("indexing takes over 1ms");
+        assert_eq!(slow.level, tracing::Level::WARN);
+        assert!(slow.fields.contains_key("duration_ms"));
+
+        // Searches get a new ID and a phase breakdown
+        let search_events = for_tool("search_code");
+        let completed = search_events
+            .iter()
+            .find(|e| e.fields["message"] == "Tool call completed")
+            .unwrap();
+        assert_ne!(completed.fields["request_id"], request_id);
+        let phases = &completed.fields["phases"];
+        for phase in ["index_open=", "search=", "format="] {
+            assert!(phases.contains(phase), "{phases}");
+        }
+        if let Some(slow) = search_events
+            .iter()
+            .find(|e| e.fields["message"] == "Slow tool call")
+        {
+            assert_eq!(slow.fields["query"], "load_config");
+        }
+    }
 }
@@ -0,0 +1,162 @@
+//! Cancel job tool handler
+
+use super::get_job_status::format_job_status;
+use super::handler::{text_content, McpToolHandler};
+use crate::core::jobs::{JobId, JobState};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct CancelJobHandler {
+    services: Arc<Services>,
+}
+
+impl CancelJobHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for CancelJobHandler {
+    fn name(&self) -> &str {
+        "cancel_job"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "cancel_job".to_string(),
+            description: "Cancel a background indexing job started with index_repository_async. \
+                         A queued job is cancelled immediately; a running job stops after the \
+                         file it is processing and its partial session is deleted (an existing \
+                         session being re-indexed is kept if the job had not finished reading \
+                         files). Finished jobs are left unchanged."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "job_id": {
+                        "type": "string",
+                        "description": "Job ID returned by index_repository_async"
+                    }
+                },
+                "required": ["job_id"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct CancelJobArgs {
+            job_id: String,
+        }
+
+        let args: CancelJobArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let id = JobId::new(args.job_id);
+        let already_finished = self.services.get_job_status(&id)?.state.is_finished();
+        let status = self.services.cancel_job(&id)?;
+
+        let headline = if already_finished {
+            format!(
+                "Job `{id}` already {}; nothing to cancel.",
+                status.state.as_str()
+            )
+        } else if status.cancel_requested {
+            format!("Cancellation requested for job `{id}`; it stops after the current file.")
+        } else if status.state == JobState::Cancelled {
+            format!("Job `{id}` cancelled.")
+        } else {
+            // Finished on its own between the two lookups
+            format!(
+                "Job `{id}` {} before it could be cancelled.",
+                status.state.as_str()
+            )
+        };
+
+        Ok(text_content(format!(
+            "{headline}\n\n{}",
+            format_job_status(&status)
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::indexer::IndexProgress;
+    use crate::core::jobs::JobOutput;
+    use crate::core::Result;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (CancelJobHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let services = Arc::new(Services::new(config));
+        (CancelJobHandler::new(services), temp_dir)
+    }
+
+    fn text_of(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        }
+    }
+
+    fn until_cancelled(progress: &IndexProgress) -> Result<JobOutput> {
+        loop {
+            progress.check_cancelled()?;
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn wait_for(handler: &CancelJobHandler, id: &JobId, state: JobState) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while handler.services.get_job_status(id).unwrap().state != state {
+            assert!(Instant::now() < deadline, "{id} never reached {state:?}");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_job() {
+        let (handler, _temp) = setup_test_handler();
+        let id = handler
+            .services
+            .jobs
+            .start("s", "/repo", until_cancelled)
+            .unwrap();
+        wait_for(&handler, &id, JobState::Running);
+
+        let result = handler
+            .execute(json!({"job_id": id.as_str()}))
+            .await
+            .unwrap();
+        assert!(text_of(&result).contains(&format!("job `{id}`")));
+
+        wait_for(&handler, &id, JobState::Cancelled);
+        let result = handler
+            .execute(json!({"job_id": id.as_str()}))
+            .await
+            .unwrap();
+        assert!(text_of(&result).contains("already cancelled; nothing to cancel"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_job() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"job_id": "job-404"})).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+
+        let result = handler.execute(json!({})).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+}
@@ -0,0 +1,208 @@
+//! Compact session tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_bytes;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::CompactionStats;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct CompactSessionHandler {
+    services: Arc<Services>,
+}
+
+impl CompactSessionHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format compaction summary
+    fn format_summary(&self, stats: &CompactionStats) -> String {
+        let saved = stats
+            .size_before_bytes
+            .saturating_sub(stats.size_after_bytes);
+        format!(
+            "**Session Compacted:** `{}`\n\n\
+             **Segments:** {} -> {}\n\
+             **Index size:** {} -> {} ({} reclaimed)\n\
+             **Duration:** {}ms",
+            stats.session,
+            stats.segments_before,
+            stats.segments_after,
+            format_bytes(stats.size_before_bytes),
+            format_bytes(stats.size_after_bytes),
+            format_bytes(saved),
+            stats.duration_ms
+        )
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for CompactSessionHandler {
+    fn name(&self) -> &str {
+        "compact_session"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "compact_session".to_string(),
+            description: "Merge a session's index segments into one and reclaim disk space. \
+                         Repeated incremental updates leave many small segments that slow \
+                         down search; compaction fixes this without re-indexing. \
+                         Reports segment count and index size before and after. \
+                         Fails if another operation is currently writing to the session."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to compact",
+                        "pattern": SESSION_ID_PATTERN
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct CompactArgs {
+            session: String,
+        }
+
+        let args: CompactArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let stats = self
+            .services
+            .storage
+            .compact_session(&args.session)
+            .map_err(|e| {
+                use crate::core::error::ShebeError;
+                match e {
+                    ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
+                        "Session '{}' not found. Use list_sessions to see available sessions.",
+                        args.session
+                    )),
+                    _ => McpError::from(e),
+                }
+            })?;
+
+        Ok(text_content(self.format_summary(&stats)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::core::types::Chunk;
+    use crate::mcp::protocol::INDEX_LOCKED;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (CompactSessionHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        (CompactSessionHandler::new(services), temp_dir)
+    }
+
+    fn create_fragmented_session(services: &Arc<Services>, session_id: &str) {
+        let mut index = services
+            .storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        for i in 0..4 {
+            let chunk = Chunk {
+                text: format!("content {i}"),
+                file_path: PathBuf::from(format!("f{i}.rs")),
+                start_offset: 0,
+                end_offset: 9,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            };
+            index.add_chunks(&[chunk], session_id).unwrap();
+            index.commit().unwrap();
+        }
+    }
+
+    fn text_of(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[test]
+    fn test_compact_session_schema() {
+        let (handler, _temp) = setup_test_handler();
+        let schema = handler.schema();
+        assert_eq!(schema.name, "compact_session");
+        assert_eq!(schema.input_schema["required"], json!(["session"]));
+    }
+
+    #[tokio::test]
+    async fn test_compact_session_reports_stats() {
+        let (handler, _temp) = setup_test_handler();
+        create_fragmented_session(&handler.services, "frag");
+
+        let result = handler.execute(json!({"session": "frag"})).await.unwrap();
+        let text = text_of(&result);
+
+        assert!(text.contains("**Session Compacted:** `frag`"));
+        assert!(text.contains("-> 1\n"));
+        assert!(text.contains("reclaimed"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_session_not_found() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"session": "missing"})).await;
+        match result {
+            Err(McpError::InvalidRequest(msg)) => assert!(msg.contains("not found")),
+            other => panic!("Expected InvalidRequest, got: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compact_session_locked() {
+        let (handler, _temp) = setup_test_handler();
+        let mut holder = handler
+            .services
+            .storage
+            .create_session(
+                "busy",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        holder.commit().unwrap();
+
+        let result = handler.execute(json!({"session": "busy"})).await;
+        match result {
+            Err(McpError::ToolError(code, _)) => assert_eq!(code, INDEX_LOCKED),
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Compare configs tool handler
+//!
+//! Indexes a repository with two chunking configurations into temporary
+//! sessions, runs the same queries against both and reports how the
+//! results differ. The temporary sessions are deleted afterwards.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{format_bytes, run_blocking};
+use super::index_repository::IndexRepositoryHandler;
+use crate::core::compare::{
+    CompareRequest, ConfigComparison, ConfigVariant, QueryRun, ScoreSummary, DEFAULT_COMPARE_K,
+    MAX_COMPARE_QUERIES,
+};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct CompareConfigsHandler {
+    services: Arc<Services>,
+}
+
+impl CompareConfigsHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Schema of one configuration
+    fn variant_schema(description: &str) -> Value {
+        json!({
+            "type": "object",
+            "description": description,
+            "properties": {
+                "chunk_size": {"type": "integer", "minimum": 100, "maximum": 2000},
+                "overlap": {"type": "integer", "minimum": 0},
+                "boundary_hint": {"type": "string", "enum": ["none", "blank_line", "newline"]},
+                "merge_tail_fragments": {"type": "boolean"},
+                "record_chunking": {"type": "boolean"},
+                "max_chunks_per_file": {"type": "integer", "minimum": 1},
+                "strip_high_entropy": {"type": "boolean"},
+                "strip_run_chars": {"type": "integer", "minimum": 32}
+            },
+            "additionalProperties": false
+        })
+    }
+
+    /// Format the comparison as Markdown tables
+    fn format_comparison(comparison: &ConfigComparison) -> String {
+        let (a, b) = (&comparison.a, &comparison.b);
+        let mut output = format!(
+            "# Config Comparison: `{}`\n\n**Results per query:** {}\n\n",
+            comparison.repository.display(),
+            comparison.k
+        );
+
+        output.push_str("| | A | B |\n");
+        output.push_str("|---|---|---|\n");
+        output.push_str(&format!("| Settings | {} | {} |\n", a.settings, b.settings));
+        output.push_str(&format!(
+            "| Chunk size / overlap | {} / {} | {} / {} |\n",
+            a.chunk_size, a.overlap, b.chunk_size, b.overlap
+        ));
+        output.push_str(&format!(
+            "| Files indexed | {} | {} |\n",
+            a.files_indexed, b.files_indexed
+        ));
+        output.push_str(&format!(
+            "| Chunks | {} | {} |\n",
+            a.chunks_created, b.chunks_created
+        ));
+        output.push_str(&format!(
+            "| Index size | {} | {} |\n",
+            format_bytes(a.index_size_bytes),
+            format_bytes(b.index_size_bytes)
+        ));
+        output.push_str(&format!(
+            "| Indexing time | {} ms | {} ms |\n",
+            a.indexing_ms, b.indexing_ms
+        ));
+        let queries = comparison.queries.len();
+        output.push_str(&format!(
+            "| Queries with results | {}/{queries} | {}/{queries} |\n",
+            a.queries_with_results, b.queries_with_results
+        ));
+        output.push_str(&format!(
+            "| Scores min / median / max | {} | {} |\n",
+            Self::format_scores(&a.scores),
+            Self::format_scores(&b.scores)
+        ));
+
+        output.push_str("\n## Queries\n\n");
+        output.push_str("| Query | A results (top) | B results (top) | File overlap |\n");
+        output.push_str("|-------|-----------------|-----------------|--------------|\n");
+        for query in &comparison.queries {
+            output.push_str(&format!(
+                "| `{}` | {} | {} | {:.2} |\n",
+                query.query.replace('|', "\\|"),
+                Self::format_hits(&query.a),
+                Self::format_hits(&query.b),
+                query.file_overlap
+            ));
+        }
+        output.push_str(&format!(
+            "\n**Mean file overlap:** {:.2} (Jaccard similarity of the files in each \
+             variant's top results; 1.00 means the same files)\n",
+            comparison.mean_file_overlap
+        ));
+        output
+    }
+
+    fn format_scores(scores: &ScoreSummary) -> String {
+        if scores.count == 0 {
+            return "-".to_string();
+        }
+        format!(
+            "{:.2} / {:.2} / {:.2}",
+            scores.min, scores.median, scores.max
+        )
+    }
+
+    fn format_hits(run: &QueryRun) -> String {
+        match run.top_score {
+            Some(top) => format!("{} ({top:.2})", run.results),
+            None => "0".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for CompareConfigsHandler {
+    fn name(&self) -> &str {
+        "compare_configs"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "compare_configs".to_string(),
+            description: "Compare search behavior of two chunking configurations on one \
+                         repository. Indexes the repository twice into temporary sessions \
+                         (deleted afterwards), runs every query against both and reports per \
+                         variant the index size, indexing time, chunk count and score \
+                         distribution, and per query the result counts, top scores and overlap \
+                         of the top-k files (Jaccard). Settings left out of a configuration come \
+                         from .shebe.toml and the server configuration. Use format='json' for \
+                         structured output."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Absolute path to the repository"
+                    },
+                    "config_a": Self::variant_schema("First configuration"),
+                    "config_b": Self::variant_schema("Second configuration"),
+                    "queries": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "minItems": 1,
+                        "maxItems": MAX_COMPARE_QUERIES,
+                        "description": "Queries run against both configurations"
+                    },
+                    "k": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 100,
+                        "default": DEFAULT_COMPARE_K,
+                        "description": "Results per query"
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Output format: 'markdown' (default) or 'json'",
+                        "default": "markdown",
+                        "enum": ["markdown", "json"]
+                    }
+                },
+                "required": ["path", "config_a", "config_b", "queries"]
+            }),
+        }
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct CompareConfigsArgs {
+            path: String,
+            config_a: ConfigVariant,
+            config_b: ConfigVariant,
+            queries: Vec<String>,
+            #[serde(default = "default_k")]
+            k: usize,
+            #[serde(default = "default_format")]
+            format: String,
+        }
+        fn default_k() -> usize {
+            DEFAULT_COMPARE_K
+        }
+        fn default_format() -> String {
+            "markdown".to_string()
+        }
+
+        let args: CompareConfigsArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let json_output = match args.format.as_str() {
+            "markdown" => false,
+            "json" => true,
+            other => {
+                return Err(McpError::InvalidParams(format!(
+                    "Invalid format: '{other}'. Must be 'markdown' or 'json'."
+                )))
+            }
+        };
+        let queries: Vec<String> = args
+            .queries
+            .into_iter()
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty())
+            .collect();
+        if queries.is_empty() {
+            return Err(McpError::InvalidParams(
+                "queries must contain at least one non-empty query".to_string(),
+            ));
+        }
+        if args.k == 0 || args.k > 100 {
+            return Err(McpError::InvalidParams(
+                "k must be between 1 and 100".to_string(),
+            ));
+        }
+        let path = IndexRepositoryHandler::validate_path(&args.path)?;
+
+        let request = CompareRequest {
+            path,
+            a: args.config_a,
+            b: args.config_b,
+            queries,
+            k: args.k,
+            defaults: Some(IndexRepositoryHandler::default_session_config(
+                &self.services,
+            )),
+        };
+        let services = Arc::clone(&self.services);
+        let comparison = run_blocking(move || services.compare_configs(&request)).await?;
+
+        let text = if json_output {
+            serde_json::to_string_pretty(&comparison).map_err(|e| {
+                McpError::InternalError(format!("Failed to serialize comparison: {e}"))
+            })?
+        } else {
+            Self::format_comparison(&comparison)
+        };
+
+        Ok(text_content(text))
+    }
+}
@@ -0,0 +1,85 @@
+//! Create collection tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_collection;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct CreateCollectionHandler {
+    services: Arc<Services>,
+}
+
+impl CreateCollectionHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for CreateCollectionHandler {
+    fn name(&self) -> &str {
+        "create_collection"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "create_collection".to_string(),
+            description: "Name a group of sessions (e.g. every service of one platform) so \
+                         search_code and find_references can search them together with \
+                         collection=\"<name>\" instead of session. Every session must exist. \
+                         Deleting a session removes it from its collections."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Collection name (1-64 letters, digits, '-' or '_')",
+                        "pattern": "^[a-zA-Z0-9_-]{1,64}$"
+                    },
+                    "sessions": {
+                        "type": "array",
+                        "items": {"type": "string", "pattern": SESSION_ID_PATTERN},
+                        "minItems": 1,
+                        "description": "Session IDs in the collection"
+                    }
+                },
+                "required": ["name", "sessions"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct CreateArgs {
+            name: String,
+            sessions: Vec<String>,
+        }
+
+        let args: CreateArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let collection = self
+            .services
+            .storage
+            .create_collection(&args.name, &args.sessions)?;
+
+        Ok(text_content(format!(
+            "**Collection Created:** `{}`\n\n{}\n\
+             Search it with search_code or find_references and collection=\"{}\".",
+            collection.name,
+            format_collection(&collection),
+            collection.name
+        )))
+    }
+}
@@ -0,0 +1,69 @@
+//! Delete collection tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct DeleteCollectionHandler {
+    services: Arc<Services>,
+}
+
+impl DeleteCollectionHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for DeleteCollectionHandler {
+    fn name(&self) -> &str {
+        "delete_collection"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "delete_collection".to_string(),
+            description: "Delete a collection (see create_collection). Only the name goes; \
+                         its sessions and their indexes are not changed."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Collection to delete"
+                    }
+                },
+                "required": ["name"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct DeleteArgs {
+            name: String,
+        }
+
+        let args: DeleteArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let collection = self.services.storage.delete_collection(&args.name)?;
+
+        Ok(text_content(format!(
+            "**Collection Deleted:** `{}`\n\n\
+             Its {} session(s) were not changed.",
+            collection.name,
+            collection.sessions.len()
+        )))
+    }
+}
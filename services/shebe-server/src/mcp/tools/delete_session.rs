@@ -3,6 +3,7 @@
 use super::handler::{text_content, McpToolHandler};
 use super::helpers::format_bytes;
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
@@ -26,8 +27,10 @@ impl DeleteSessionHandler {
         files_indexed: usize,
         chunks_created: usize,
         index_size_bytes: u64,
+        clone_purged: bool,
+        collections: &[String],
     ) -> String {
-        format!(
+        let mut summary = format!(
             "**Session Deleted:** `{}`\n\n\
              **Freed Resources:**\n\
              - Files indexed: {}\n\
@@ -38,7 +41,18 @@ impl DeleteSessionHandler {
             files_indexed,
             chunks_created,
             format_bytes(index_size_bytes)
-        )
+        );
+        if clone_purged {
+            summary.push_str("\nCached clone of the remote repository removed.");
+        }
+        if !collections.is_empty() {
+            let names: Vec<String> = collections.iter().map(|c| format!("`{c}`")).collect();
+            summary.push_str(&format!(
+                "\n\n**Warning:** the session was removed from collection(s) {}.",
+                names.join(", ")
+            ));
+        }
+        summary
     }
 }
 
@@ -48,6 +62,10 @@ impl McpToolHandler for DeleteSessionHandler {
         "delete_session"
     }
 
+    fn mutates(&self) -> bool {
+        true
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "delete_session".to_string(),
@@ -55,6 +73,9 @@ impl McpToolHandler for DeleteSessionHandler {
                          This is a DESTRUCTIVE operation that cannot be undone. \
                          Requires confirm=true parameter to prevent accidental deletion. \
                          Frees disk space and removes session from list_sessions. \
+                         Sessions indexed from a git URL keep their cached clone unless \
+                         purge_clone=true. The session is removed from the collections \
+                         it belonged to. \
                          To recreate session, re-run index_repository."
                 .to_string(),
             input_schema: json!({
@@ -63,11 +84,17 @@ impl McpToolHandler for DeleteSessionHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID to delete",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "confirm": {
                         "type": "boolean",
                         "description": "Must be true to confirm deletion (safety check)",
+                    },
+                    "purge_clone": {
+                        "type": "boolean",
+                        "description": "Also delete the cached clone of a session indexed from a \
+                                       git URL (default: false)",
+                        "default": false
                     }
                 },
                 "required": ["session", "confirm"]
@@ -80,6 +107,8 @@ impl McpToolHandler for DeleteSessionHandler {
         struct DeleteArgs {
             session: String,
             confirm: bool,
+            #[serde(default)]
+            purge_clone: bool,
         }
 
         // Parse arguments
@@ -95,26 +124,33 @@ impl McpToolHandler for DeleteSessionHandler {
             ));
         }
 
-        // Get session metadata before deletion (for summary)
+        if !self.services.storage.session_exists(&args.session) {
+            return Err(McpError::InvalidRequest(format!(
+                "Session '{}' not found. Use list_sessions to see available sessions.",
+                args.session
+            )));
+        }
+
+        // Get session metadata before deletion (for summary). Corrupt and
+        // orphaned sessions have none but can still be deleted.
         let metadata = self
             .services
             .storage
             .get_session_metadata(&args.session)
-            .map_err(|e| {
-                use crate::core::error::ShebeError;
-                match e {
-                    ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
-                        "Session '{}' not found. Use list_sessions to see available sessions.",
-                        args.session
-                    )),
-                    _ => McpError::from(e),
-                }
-            })?;
+            .ok();
 
         // Extract stats for summary
-        let files_indexed = metadata.files_indexed;
-        let chunks_created = metadata.chunks_created;
-        let index_size_bytes = metadata.index_size_bytes;
+        let files_indexed = metadata.as_ref().map_or(0, |m| m.files_indexed);
+        let chunks_created = metadata.as_ref().map_or(0, |m| m.chunks_created);
+        let remote = metadata.and_then(|m| m.remote);
+        // Measured rather than recorded, the metadata can be stale
+        let index_size_bytes = self.services.storage.measure_session_size(&args.session);
+        // Deleting drops the session from these
+        let collections = self
+            .services
+            .storage
+            .collections_of(&args.session)
+            .unwrap_or_default();
 
         // Delete session (atomic operation)
         self.services
@@ -122,12 +158,24 @@ impl McpToolHandler for DeleteSessionHandler {
             .delete_session(&args.session)
             .map_err(|e| McpError::InternalError(format!("Failed to delete session: {e}")))?;
 
+        // Remove the cached clone of remote sessions on request
+        let clone_purged = if args.purge_clone && remote.is_some() {
+            self.services
+                .clones
+                .purge(&args.session)
+                .map_err(|e| McpError::InternalError(format!("Failed to purge clone: {e}")))?
+        } else {
+            false
+        };
+
         // Format summary
         let summary = self.format_summary(
             &args.session,
             files_indexed,
             chunks_created,
             index_size_bytes,
+            clone_purged,
+            &collections,
         );
 
         Ok(text_content(summary))
@@ -170,6 +218,8 @@ mod tests {
             start_offset: 0,
             end_offset: 12,
             chunk_index: 0,
+            records: None,
+            section: None,
         }];
 
         index.add_chunks(&chunks, session_id).unwrap();
@@ -250,6 +300,26 @@ mod tests {
         assert!(handler.services.storage.session_exists("test-no-confirm"));
     }
 
+    #[tokio::test]
+    async fn test_delete_corrupt_session() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "corrupt").await;
+        let session_dir = handler.services.storage.get_session_path("corrupt");
+        std::fs::write(session_dir.join("meta.json"), "{not json").unwrap();
+
+        let result = handler
+            .execute(json!({"session": "corrupt", "confirm": true}))
+            .await
+            .unwrap();
+
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => {
+                assert!(text.contains("**Session Deleted:** `corrupt`"));
+            }
+        }
+        assert!(!handler.services.storage.session_exists("corrupt"));
+    }
+
     #[tokio::test]
     async fn test_delete_metadata_summary() {
         let (handler, _temp) = setup_test_handler().await;
@@ -279,4 +349,50 @@ mod tests {
         assert!(text.contains(&metadata.files_indexed.to_string()));
         assert!(text.contains(&metadata.chunks_created.to_string()));
     }
+
+    #[tokio::test]
+    async fn test_delete_session_purge_clone() {
+        use crate::core::remote::RemoteSource;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("sessions");
+        config.storage.clone_dir = temp_dir.path().join("clones");
+        let handler = DeleteSessionHandler::new(Arc::new(Services::new(config)));
+
+        let remote = RemoteSource {
+            url: "https://github.com/org/repo.git".to_string(),
+            git_ref: None,
+            commit: "abc123".to_string(),
+        };
+        for session in ["kept", "purged"] {
+            create_test_session(&handler.services, session).await;
+            std::fs::create_dir_all(handler.services.clones.clone_path(session)).unwrap();
+            handler
+                .services
+                .storage
+                .set_remote_source(session, Some(remote.clone()))
+                .unwrap();
+        }
+
+        let result = handler
+            .execute(json!({"session": "kept", "confirm": true}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(!text.contains("Cached clone"));
+        assert!(handler.services.clones.clone_path("kept").exists());
+
+        let result = handler
+            .execute(json!({"session": "purged", "confirm": true, "purge_clone": true}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(text.contains("Cached clone of the remote repository removed."));
+        assert!(!handler.services.clones.clone_path("purged").exists());
+    }
 }
@@ -0,0 +1,271 @@
+//! Diff sessions tool handler
+//!
+//! Compares the files indexed by two sessions of the same repository,
+//! e.g. after re-indexing under a new session ID.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::run_blocking;
+use crate::core::services::Services;
+use crate::core::storage::{DiffGroup, SessionDiff, SESSION_ID_PATTERN};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct DiffSessionsHandler {
+    services: Arc<Services>,
+}
+
+impl DiffSessionsHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the diff as Markdown, ending with the verdict line
+    fn format_diff(diff: &SessionDiff) -> String {
+        let mut output = format!("# Session Diff: `{}` -> `{}`\n\n", diff.from, diff.to);
+        if diff.from_repository == diff.to_repository {
+            output.push_str(&format!(
+                "**Repository:** `{}`\n",
+                diff.from_repository.display()
+            ));
+        } else {
+            output.push_str(&format!(
+                "**Repositories:** `{}` -> `{}`\n",
+                diff.from_repository.display(),
+                diff.to_repository.display()
+            ));
+        }
+        output.push_str(&format!("**Compared by:** {}\n\n", diff.basis.label()));
+
+        output.push_str("| Change | Files |\n");
+        output.push_str("|--------|-------|\n");
+        output.push_str(&format!("| Added | {} |\n", diff.added.count));
+        output.push_str(&format!("| Removed | {} |\n", diff.removed.count));
+        output.push_str(&format!("| Changed | {} |\n", diff.changed.count));
+        output.push_str(&format!("| Renamed | {} |\n", diff.renamed.count));
+        output.push_str(&format!("| Unchanged | {} |\n", diff.unchanged));
+
+        Self::format_group(&mut output, "Added", &diff.added);
+        Self::format_group(&mut output, "Removed", &diff.removed);
+        Self::format_group(&mut output, "Changed", &diff.changed);
+        Self::format_group(&mut output, "Renamed", &diff.renamed);
+
+        output.push_str(&format!("\n**{}**\n", diff.verdict));
+        output
+    }
+
+    /// List a category's files, noting how many were left out
+    fn format_group(output: &mut String, title: &str, group: &DiffGroup) {
+        if group.count == 0 {
+            return;
+        }
+        output.push_str(&format!("\n## {title} ({})\n\n", group.count));
+        for file in &group.files {
+            match &file.renamed_from {
+                Some(from) => output.push_str(&format!(
+                    "- `{from}` -> `{}` ({})\n",
+                    file.path,
+                    file.chunk_label()
+                )),
+                None => output.push_str(&format!("- `{}` ({})\n", file.path, file.chunk_label())),
+            }
+        }
+        if group.omitted() > 0 {
+            output.push_str(&format!("- ... and {} more\n", group.omitted()));
+        }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for DiffSessionsHandler {
+    fn name(&self) -> &str {
+        "diff_sessions"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "diff_sessions".to_string(),
+            description: "Compare two sessions of the same repository, e.g. after re-indexing \
+                         under a new session ID: which files were added, removed, changed \
+                         (different content hash or chunk count) or renamed (same content \
+                         under a new path). Shows counts per category and \
+                         lists up to 100 files each, ending with a one-line verdict such as \
+                         '231 files changed since backend-v1'. Sessions of different repositories \
+                         are refused unless force=true. Use format='json' for structured output."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "from": {
+                        "type": "string",
+                        "description": "Baseline session ID",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "to": {
+                        "type": "string",
+                        "description": "Session ID compared against the baseline",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "force": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Compare sessions of different repositories by relative path"
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Output format: 'markdown' (default) or 'json'",
+                        "default": "markdown",
+                        "enum": ["markdown", "json"]
+                    }
+                },
+                "required": ["from", "to"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct DiffSessionsArgs {
+            from: String,
+            to: String,
+            #[serde(default)]
+            force: bool,
+            #[serde(default = "default_format")]
+            format: String,
+        }
+        fn default_format() -> String {
+            "markdown".to_string()
+        }
+
+        let args: DiffSessionsArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let json_output = match args.format.as_str() {
+            "markdown" => false,
+            "json" => true,
+            other => {
+                return Err(McpError::InvalidParams(format!(
+                    "Invalid format: '{other}'. Must be 'markdown' or 'json'."
+                )))
+            }
+        };
+
+        let services = Arc::clone(&self.services);
+        let diff = run_blocking(move || {
+            services
+                .storage
+                .diff_sessions(&args.from, &args.to, args.force)
+        })
+        .await?;
+
+        let text = if json_output {
+            serde_json::to_string_pretty(&diff)
+                .map_err(|e| McpError::InternalError(format!("Failed to serialize diff: {e}")))?
+        } else {
+            Self::format_diff(&diff)
+        };
+
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::mcp::protocol::{ContentBlock, SESSION_NOT_FOUND};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (DiffSessionsHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("index");
+
+        let services = Arc::new(Services::new(config));
+        (DiffSessionsHandler::new(services), temp_dir)
+    }
+
+    fn index(handler: &DiffSessionsHandler, session: &str, repo: &Path) {
+        handler
+            .services
+            .storage
+            .index_repository_with_config(session, repo, SessionConfig::default(), 10, true)
+            .unwrap();
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    /// Index `repo` as `v1`, change it and index it again as `v2`
+    fn two_versions(handler: &DiffSessionsHandler, temp: &TempDir) {
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(repo.join("old.rs"), "fn old() {}").unwrap();
+        index(handler, "v1", &repo);
+
+        fs::write(repo.join("main.rs"), "fn main() { run(); }").unwrap();
+        fs::remove_file(repo.join("old.rs")).unwrap();
+        fs::write(repo.join("new.rs"), "fn new() {}").unwrap();
+        index(handler, "v2", &repo);
+    }
+
+    #[tokio::test]
+    async fn test_diff_sessions_markdown() {
+        let (handler, temp) = setup_test_handler();
+        two_versions(&handler, &temp);
+
+        let result = handler
+            .execute(json!({"from": "v1", "to": "v2"}))
+            .await
+            .unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("# Session Diff: `v1` -> `v2`"));
+        assert!(text.contains("| Added | 1 |"));
+        assert!(text.contains("## Removed (1)\n\n- `old.rs` (1 chunks)"));
+        assert!(text.contains("- `main.rs` (1 chunks)"));
+        assert!(text.trim_end().ends_with("**3 files changed since v1**"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_sessions_json() {
+        let (handler, temp) = setup_test_handler();
+        two_versions(&handler, &temp);
+
+        let result = handler
+            .execute(json!({"from": "v1", "to": "v2", "format": "json"}))
+            .await
+            .unwrap();
+        let diff: Value = serde_json::from_str(extract_text(&result)).unwrap();
+
+        assert_eq!(diff["basis"], "content_hash");
+        assert_eq!(diff["added"]["count"], 1);
+        assert_eq!(diff["added"]["files"][0]["path"], "new.rs");
+        assert_eq!(diff["changed"]["files"][0]["from_chunks"], 1);
+        assert_eq!(diff["verdict"], "3 files changed since v1");
+    }
+
+    #[tokio::test]
+    async fn test_diff_sessions_errors() {
+        let (handler, temp) = setup_test_handler();
+        two_versions(&handler, &temp);
+
+        let result = handler.execute(json!({"from": "v1", "to": "nope"})).await;
+        assert!(matches!(result, Err(McpError::ToolError(code, _)) if code == SESSION_NOT_FOUND));
+
+        let result = handler
+            .execute(json!({"from": "v1", "to": "v2", "format": "xml"}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+}
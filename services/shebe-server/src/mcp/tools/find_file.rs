@@ -1,7 +1,12 @@
 //! Find file by pattern tool handler
 
-use super::handler::{text_content, McpToolHandler};
+use super::handler::{requested_timeout, text_content, McpToolHandler};
+use super::helpers::run_blocking;
+use crate::core::cancel::{check_cancelled, CHECK_INTERVAL};
+use crate::core::display::relative_to;
+use crate::core::paths;
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
@@ -9,12 +14,9 @@ use glob::Pattern as GlobPattern;
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
-use tantivy::collector::TopDocs;
-use tantivy::query::AllQuery;
-use tantivy::schema::Value as TantivyValue;
-use tantivy::TantivyDocument;
+use std::time::Duration;
 
 const DEFAULT_LIMIT: usize = 100;
 const MAX_LIMIT: usize = 10000;
@@ -37,6 +39,62 @@ impl PatternType {
     }
 }
 
+/// Every distinct file path indexed in a session
+pub(super) fn session_file_paths(
+    services: &Services,
+    session: &str,
+) -> Result<Vec<String>, McpError> {
+    services
+        .storage
+        .searchable_file_paths(session)
+        .map_err(McpError::from)
+}
+
+/// Repository root a relative glob pattern is matched against
+///
+/// `None` for absolute patterns. Relative patterns may not climb out
+/// of the repository with `..`.
+pub(super) fn relative_root(
+    services: &Services,
+    session: &str,
+    pattern: &str,
+) -> Result<Option<PathBuf>, McpError> {
+    let native = paths::to_native(&paths::normalize(pattern));
+    if native.is_absolute() {
+        return Ok(None);
+    }
+    if native.components().any(|c| c == Component::ParentDir) {
+        return Err(McpError::InvalidParams(format!(
+            "Invalid glob pattern '{pattern}': relative patterns are matched inside \
+             the repository root and cannot contain '..'"
+        )));
+    }
+    Ok(services
+        .storage
+        .get_session_metadata(session)
+        .ok()
+        .map(|metadata| metadata.repository_path))
+}
+
+/// Whether an indexed path matches glob `pattern`
+///
+/// Relative patterns (`src/**/*.rs`) also match paths relative to
+/// `root` (see [`relative_root`]).
+pub(super) fn glob_matcher<'a>(
+    pattern: &str,
+    root: Option<&'a Path>,
+) -> Result<impl Fn(&str) -> bool + 'a, McpError> {
+    let glob = GlobPattern::new(pattern)
+        .map_err(|e| McpError::InvalidParams(format!("Invalid glob pattern '{pattern}': {e}")))?;
+
+    Ok(move |path: &str| {
+        glob.matches(path)
+            || root
+                .and_then(|root| relative_to(Path::new(path), root))
+                .is_some_and(|relative| glob.matches(&paths::normalize_path(&relative)))
+    })
+}
+
 pub struct FindFileHandler {
     services: Arc<Services>,
 }
@@ -46,88 +104,48 @@ impl FindFileHandler {
         Self { services }
     }
 
-    /// Get all file paths from session (helper)
-    async fn get_all_file_paths(&self, session: &str) -> Result<Vec<String>, McpError> {
-        // Open session index
-        let index = self
-            .services
-            .storage
-            .open_session(session)
-            .map_err(McpError::from)?;
-
-        let reader = index
-            .index()
-            .reader()
-            .map_err(|e| McpError::InternalError(format!("Failed to open reader: {e}")))?;
-
-        let searcher = reader.searcher();
-        let query = AllQuery;
-
-        let file_path_field = index
-            .schema()
-            .get_field("file_path")
-            .map_err(|e| McpError::InternalError(format!("file_path field missing: {e}")))?;
-
-        let mut files = HashSet::new();
-
-        let top_docs = searcher
-            .search(&query, &TopDocs::with_limit(100000))
-            .map_err(|e| McpError::InternalError(format!("Search failed: {e}")))?;
-
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc: TantivyDocument = searcher
-                .doc(doc_address)
-                .map_err(|e| McpError::InternalError(format!("Doc retrieval failed: {e}")))?;
-
-            if let Some(path) = retrieved_doc
-                .get_first(file_path_field)
-                .and_then(|v| v.as_str())
-            {
-                files.insert(path.to_string());
-            }
-        }
-
-        Ok(files.into_iter().collect())
-    }
-
-    /// Match files using pattern
-    async fn find_matching_files(
-        &self,
+    /// Match files using pattern, returning the session's file count
+    /// and the first `limit` matches
+    ///
+    /// Relative glob patterns (`src/**/*.rs`) also match paths relative
+    /// to `root`. Blocking; stops early when the call is cancelled.
+    fn find_matching_files(
+        services: &Services,
         session: &str,
         pattern: &str,
         pattern_type: PatternType,
+        root: Option<&Path>,
         limit: usize,
-    ) -> Result<Vec<String>, McpError> {
+    ) -> Result<(usize, Vec<String>), McpError> {
         // Get all files from session
-        let all_files = self.get_all_file_paths(session).await?;
+        let all_files = session_file_paths(services, session)?;
 
-        // Compile pattern and filter
-        let matches: Vec<String> = match pattern_type {
-            PatternType::Glob => {
-                let glob = GlobPattern::new(pattern).map_err(|e| {
-                    McpError::InvalidParams(format!("Invalid glob pattern '{pattern}': {e}"))
-                })?;
-
-                all_files
-                    .into_iter()
-                    .filter(|path| glob.matches(path))
-                    .take(limit)
-                    .collect()
-            }
+        // Compile pattern
+        let matches: Box<dyn Fn(&str) -> bool + '_> = match pattern_type {
+            PatternType::Glob => Box::new(glob_matcher(pattern, root)?),
             PatternType::Regex => {
                 let re = Regex::new(pattern).map_err(|e| {
                     McpError::InvalidParams(format!("Invalid regex pattern '{pattern}': {e}"))
                 })?;
-
-                all_files
-                    .into_iter()
-                    .filter(|path| re.is_match(path))
-                    .take(limit)
-                    .collect()
+                Box::new(move |path: &str| re.is_match(path))
             }
         };
 
-        Ok(matches)
+        // Filter, checking for cancellation on large sessions
+        let mut found = Vec::new();
+        for (i, path) in all_files.iter().enumerate() {
+            if i % CHECK_INTERVAL == 0 {
+                check_cancelled()?;
+            }
+            if matches(path) {
+                found.push(path.clone());
+                if found.len() == limit {
+                    break;
+                }
+            }
+        }
+
+        Ok((all_files.len(), found))
     }
 
     /// Format results
@@ -135,12 +153,17 @@ impl FindFileHandler {
         &self,
         session: &str,
         pattern: &str,
+        root: Option<&Path>,
         matches: &[String],
         total_files: usize,
     ) -> String {
+        let pattern = match root {
+            Some(root) => format!("`{pattern}` (relative to `{}`)", root.display()),
+            None => format!("`{pattern}`"),
+        };
         let mut output = format!(
             "**Session:** `{}`\n\
-             **Pattern:** `{}`\n\
+             **Pattern:** {}\n\
              **Matches:** {} of {} total files\n\n",
             session,
             pattern,
@@ -169,6 +192,10 @@ impl McpToolHandler for FindFileHandler {
         "find_file"
     }
 
+    fn timeout(&self, args: &Value, default: Duration) -> Option<Duration> {
+        requested_timeout(args, default)
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "find_file".to_string(),
@@ -185,12 +212,13 @@ impl McpToolHandler for FindFileHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID to search",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "pattern": {
                         "type": "string",
                         "description": "Glob or regex pattern. Examples: '*.rs', '**/src/**/*.py', \
-                                       '.*test.*' (regex)",
+                                       '.*test.*' (regex). Relative globs such as 'src/*.rs' also \
+                                       match paths relative to the session's repository root.",
                         "minLength": 1
                     },
                     "pattern_type": {
@@ -205,6 +233,12 @@ impl McpToolHandler for FindFileHandler {
                         "default": 100,
                         "minimum": 1,
                         "maximum": 10000
+                    },
+                    "timeout_sec": {
+                        "type": "integer",
+                        "description": "Time limit for this call in seconds (default: \
+                                       limits.tool_timeout_sec, 60)",
+                        "minimum": 1
                     }
                 },
                 "required": ["session", "pattern"]
@@ -251,14 +285,35 @@ impl McpToolHandler for FindFileHandler {
         let pattern_type =
             PatternType::from_str(&args.pattern_type).map_err(McpError::InvalidParams)?;
 
-        // Find matching files
-        let total_files = self.get_all_file_paths(&args.session).await?.len();
-        let matches = self
-            .find_matching_files(&args.session, &args.pattern, pattern_type, args.limit)
-            .await?;
+        let root = match pattern_type {
+            PatternType::Glob => relative_root(&self.services, &args.session, &args.pattern)?,
+            PatternType::Regex => None,
+        };
+
+        // Find matching files (off the async runtime)
+        let services = Arc::clone(&self.services);
+        let (session, pattern, match_root) =
+            (args.session.clone(), args.pattern.clone(), root.clone());
+        let (total_files, matches) = run_blocking(move || {
+            Self::find_matching_files(
+                &services,
+                &session,
+                &pattern,
+                pattern_type,
+                match_root.as_deref(),
+                args.limit,
+            )
+        })
+        .await?;
 
         // Format response
-        let formatted = self.format_results(&args.session, &args.pattern, &matches, total_files);
+        let formatted = self.format_results(
+            &args.session,
+            &args.pattern,
+            root.as_deref(),
+            &matches,
+            total_files,
+        );
 
         Ok(text_content(formatted))
     }
@@ -286,6 +341,7 @@ mod tests {
         (handler, temp_dir)
     }
 
+    /// Index `files`, relative to a repository in the test's temp dir
     async fn create_test_session_with_files(
         services: &Arc<Services>,
         session_id: &str,
@@ -301,7 +357,7 @@ mod tests {
             .unwrap();
 
         for (file_path, content) in files {
-            let full_path = PathBuf::from(file_path);
+            let full_path = services.storage.storage_root().join("repo").join(file_path);
             if let Some(parent) = full_path.parent() {
                 let _ = fs::create_dir_all(parent);
             }
@@ -314,6 +370,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: content.len(),
                 chunk_index: 0,
+                records: None,
+                section: None,
             }];
 
             index.add_chunks(&chunks, session_id).unwrap();
@@ -329,9 +387,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test.rs", "fn main() {}"),
-                ("/tmp/shebe-main.rs", "fn test() {}"),
-                ("/tmp/shebe-lib.py", "def test(): pass"),
+                ("shebe-test.rs", "fn main() {}"),
+                ("shebe-main.rs", "fn test() {}"),
+                ("shebe-lib.py", "def test(): pass"),
             ],
         )
         .await;
@@ -353,11 +411,6 @@ mod tests {
         assert!(text.contains("shebe-test.rs"));
         assert!(text.contains("shebe-main.rs"));
         assert!(!text.contains("lib.py"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test.rs");
-        let _ = fs::remove_file("/tmp/shebe-main.rs");
-        let _ = fs::remove_file("/tmp/shebe-lib.py");
     }
 
     #[tokio::test]
@@ -367,9 +420,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe/src/test.rs", "fn test() {}"),
-                ("/tmp/shebe/tests/test.rs", "fn integration() {}"),
-                ("/tmp/shebe/main.rs", "fn main() {}"),
+                ("shebe/src/test.rs", "fn test() {}"),
+                ("shebe/tests/test.rs", "fn integration() {}"),
+                ("shebe/main.rs", "fn main() {}"),
             ],
         )
         .await;
@@ -391,14 +444,6 @@ mod tests {
         assert!(text.contains("src/test.rs"));
         assert!(text.contains("tests/test.rs"));
         assert!(!text.contains("main.rs"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe/src/test.rs");
-        let _ = fs::remove_file("/tmp/shebe/tests/test.rs");
-        let _ = fs::remove_file("/tmp/shebe/main.rs");
-        let _ = fs::remove_dir("/tmp/shebe/src");
-        let _ = fs::remove_dir("/tmp/shebe/tests");
-        let _ = fs::remove_dir("/tmp/shebe");
     }
 
     #[tokio::test]
@@ -408,9 +453,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe/TestController.php", "class TestController {}"),
-                ("/tmp/shebe/UserController.php", "class UserController {}"),
-                ("/tmp/shebe/config.php", "return [];"),
+                ("shebe/TestController.php", "class TestController {}"),
+                ("shebe/UserController.php", "class UserController {}"),
+                ("shebe/config.php", "return [];"),
             ],
         )
         .await;
@@ -433,12 +478,6 @@ mod tests {
         assert!(text.contains("TestController.php"));
         assert!(text.contains("UserController.php"));
         assert!(!text.contains("config.php"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe/TestController.php");
-        let _ = fs::remove_file("/tmp/shebe/UserController.php");
-        let _ = fs::remove_file("/tmp/shebe/config.php");
-        let _ = fs::remove_dir("/tmp/shebe");
     }
 
     #[tokio::test]
@@ -448,8 +487,8 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test.rs", "fn main() {}"),
-                ("/tmp/shebe-lib.rs", "fn test() {}"),
+                ("shebe-test.rs", "fn main() {}"),
+                ("shebe-lib.rs", "fn test() {}"),
             ],
         )
         .await;
@@ -469,10 +508,6 @@ mod tests {
 
         assert!(text.contains("**Matches:** 0 of 2"));
         assert!(text.contains("No files match the pattern"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test.rs");
-        let _ = fs::remove_file("/tmp/shebe-lib.rs");
     }
 
     #[tokio::test]
@@ -481,7 +516,7 @@ mod tests {
         create_test_session_with_files(
             &handler.services,
             "test-session",
-            vec![("/tmp/shebe-test.rs", "fn main() {}")],
+            vec![("shebe-test.rs", "fn main() {}")],
         )
         .await;
 
@@ -498,9 +533,6 @@ mod tests {
         } else {
             panic!("Expected InvalidParams error for invalid glob");
         }
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test.rs");
     }
 
     #[tokio::test]
@@ -509,7 +541,7 @@ mod tests {
         create_test_session_with_files(
             &handler.services,
             "test-session",
-            vec![("/tmp/shebe-test.rs", "fn main() {}")],
+            vec![("shebe-test.rs", "fn main() {}")],
         )
         .await;
 
@@ -527,9 +559,6 @@ mod tests {
         } else {
             panic!("Expected InvalidParams error for invalid regex");
         }
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test.rs");
     }
 
     #[tokio::test]
@@ -537,7 +566,7 @@ mod tests {
         let (handler, _temp) = setup_test_handler().await;
 
         let files: Vec<_> = (0..20)
-            .map(|i| (format!("/tmp/shebe-test-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-test-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -560,10 +589,5 @@ mod tests {
         // Count matched files - should be 10 (limited)
         let match_count = text.matches("- `").count();
         assert_eq!(match_count, 10);
-
-        // Cleanup
-        for i in 0..20 {
-            let _ = fs::remove_file(format!("/tmp/shebe-test-{:02}.rs", i));
-        }
     }
 }
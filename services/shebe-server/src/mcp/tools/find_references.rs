@@ -31,9 +31,13 @@
 //!
 //! The tool returns:
 //! - **Locations** (file:line), not full code bodies
+//! - **Definition sites** (`fn X`, `class X`, ...) in their own section, first
 //! - **Confidence scores** (high/medium/low) to prioritize work
-//! - **"Files to update"** list for systematic refactoring
+//! - **"Files to update"** list for systematic refactoring (usages only,
+//!   unless `include_definition` is set)
 //! - **Session freshness** to warn about stale indexes
+//! - **Warnings** for files that could not be read; their references are
+//!   still listed, from the indexed chunk text
 //!
 //! # When NOT to Use
 //!
@@ -41,21 +45,60 @@
 //! - For understanding code structure (use `search_code` or `get_symbols_overview`)
 //! - For single-file searches (use grep or read the file directly)
 
-use super::handler::{text_content, McpToolHandler};
+use super::handler::{requested_timeout, text_content, McpToolHandler};
 use super::helpers::{
-    byte_offset_to_line_number, detect_language, extract_context_lines, format_time_ago,
+    collection_display, detect_language, extract_context_lines, format_section, format_time_ago,
+    locate_symbol, path_display, run_blocking, SearchTarget,
 };
+use crate::core::cancel::check_cancelled;
+use crate::core::definition::DefinitionMatcher;
+use crate::core::display::PathDisplay;
+use crate::core::error::ShebeError;
+use crate::core::lexical::RegionKind;
 use crate::core::services::Services;
-use crate::core::storage::SessionMetadata;
-use crate::core::types::SearchRequest;
+use crate::core::sources::{self, SourceFiles, UnreadableFile, DEGRADED_CONTEXT_PENALTY};
+use crate::core::storage::{SessionMetadata, SESSION_ID_PATTERN};
+use crate::core::types::{SearchRequest, SearchResult};
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{build_truncation_footer, estimate_tokens, EntrySize, ResponseBudget};
 use async_trait::async_trait;
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How to get references trimmed by the response budget.
+const TRUNCATION_HINT: &str = "lower `context_lines` (0 shows locations only) or `max_results`, \
+     or narrow with `symbol_type` and `defined_in`";
+
+/// Highest confidence of a reference inside a comment or string (Low band).
+const IN_REGION_CONFIDENCE: f32 = 0.40;
+
+/// Most symbols accepted in one call.
+pub const MAX_SYMBOLS: usize = 20;
+
+/// Most references shown across all symbols of one call.
+pub const MAX_TOTAL_REFERENCES: usize = 500;
+
+/// The `symbol` argument: one symbol, or several found together.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SymbolArg {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// References to one symbol of a multi-symbol call.
+struct SymbolReferences {
+    symbol: String,
+    /// Ranked references, without locations claimed by an earlier symbol
+    references: Vec<Reference>,
+    /// Later symbols also found at each reference's location
+    also_matches: Vec<Vec<String>>,
+}
 
 /// Handler for the find_references MCP tool.
 pub struct FindReferencesHandler {
@@ -75,7 +118,7 @@ pub enum SymbolType {
 /// A single reference to a symbol found in the codebase.
 ///
 /// Designed for minimal token usage while providing actionable information:
-/// - Location (file:line) for navigation
+/// - Location (file:line:column) for navigation
 /// - Minimal context (configurable lines) for verification
 /// - Confidence score for prioritization
 /// - Pattern name for understanding match type
@@ -87,13 +130,20 @@ pub struct Reference {
     /// Used in "Files to update" list for systematic refactoring.
     pub file_path: String,
     /// 1-based line number for IDE navigation (file:line format).
+    /// 0 when the file could not be read (see `context_unavailable`).
     pub line_number: usize,
-    /// Column offset within the line (0-based). For precise cursor positioning.
+    /// 1-based column within the line, counted in characters. For precise
+    /// cursor positioning (file:line:column format). 0 when unknown.
     pub column: usize,
+    /// 1-based columns of every occurrence of the symbol on this line.
+    pub columns: Vec<usize>,
+    /// Byte offset of the occurrence within the file.
+    pub offset: usize,
     /// Context lines around the reference (configurable via `context_lines` param).
     /// Kept minimal to reduce token usage while allowing verification.
     pub context: String,
-    /// Pattern that matched (e.g., "function_call", "type_annotation").
+    /// Pattern that matched (e.g., "function_call", "type_annotation"), or
+    /// "in_comment"/"in_string" for references inside comments and strings.
     /// Helps understand why this location was flagged.
     pub pattern: String,
     /// Confidence score (0.0 to 1.0). Used for grouping:
@@ -101,6 +151,16 @@ pub struct Reference {
     /// - Medium (0.50-0.79): Review before updating
     /// - Low (<0.50): Possible false positive
     pub confidence: f32,
+    /// Whether the line defines the symbol (`fn X`, `class X`, ...).
+    /// Definitions are listed apart from the confidence groups.
+    pub is_definition: bool,
+    /// IO error kind if the file could not be read. The context is then
+    /// the indexed chunk text and the location a byte offset.
+    pub context_unavailable: Option<String>,
+    /// Heading breadcrumb of the chunk, for documentation files.
+    pub section: Option<String>,
+    /// Session of the reference, for a search of a collection.
+    pub session: Option<String>,
 }
 
 impl FindReferencesHandler {
@@ -235,6 +295,31 @@ impl FindReferencesHandler {
         confidence.clamp(0.0, 1.0)
     }
 
+    /// Demote a reference inside a comment or string literal.
+    ///
+    /// Catches references in block comments, docstrings and template
+    /// literals spanning lines, which the line-based checks of
+    /// `adjust_confidence` miss. Such references drop into the Low band
+    /// and are labeled `in_comment` or `in_string`.
+    fn apply_region(
+        region: Option<RegionKind>,
+        pattern: &'static str,
+        confidence: f32,
+    ) -> (&'static str, f32) {
+        match region {
+            Some(RegionKind::Comment) => ("in_comment", confidence.min(IN_REGION_CONFIDENCE)),
+            Some(RegionKind::String) => ("in_string", confidence.min(IN_REGION_CONFIDENCE)),
+            None => (pattern, confidence),
+        }
+    }
+
+    /// Where a reference is: file and line, or file and byte offset where
+    /// the line is unknown.
+    fn location(r: &Reference) -> (String, usize, Option<usize>) {
+        let offset = r.context_unavailable.as_ref().map(|_| r.offset);
+        (r.file_path.clone(), r.line_number, offset)
+    }
+
     /// Deduplicate references, keeping highest confidence per location.
     fn deduplicate_references(references: &mut Vec<Reference>) {
         // Sort by confidence descending first
@@ -245,26 +330,281 @@ impl FindReferencesHandler {
         });
 
         // Then sort by location to group duplicates
+        let location = Self::location;
         references.sort_by(|a, b| {
-            (&a.file_path, a.line_number)
-                .cmp(&(&b.file_path, b.line_number))
-                .then_with(|| {
-                    b.confidence
-                        .partial_cmp(&a.confidence)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                })
+            location(a).cmp(&location(b)).then_with(|| {
+                b.confidence
+                    .partial_cmp(&a.confidence)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
         });
 
         // Deduplicate by location (keeps first = highest confidence)
-        references.dedup_by(|a, b| a.file_path == b.file_path && a.line_number == b.line_number);
+        references.dedup_by(|a, b| location(a) == location(b));
+    }
+
+    /// Deduplicate, then keep the first `max_results` references:
+    /// definitions, then the rest by confidence (descending).
+    fn rank_references(references: &mut Vec<Reference>, max_results: usize) {
+        Self::deduplicate_references(references);
+        references.sort_by(|a, b| {
+            b.is_definition.cmp(&a.is_definition).then_with(|| {
+                b.confidence
+                    .partial_cmp(&a.confidence)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
+        references.truncate(max_results);
+    }
+
+    /// Combine the ranked references of a multi-symbol call.
+    ///
+    /// Past `MAX_TOTAL_REFERENCES` in all, the lowest-ranked references
+    /// of every symbol go first. A location found for several symbols is
+    /// kept under the first of them, noting the others. Also returns the
+    /// files to update (as in `format_results`), each with the symbols it
+    /// touches in call order.
+    fn merge_symbols(
+        mut ranked: Vec<(String, Vec<Reference>)>,
+        include_definition: bool,
+    ) -> (Vec<SymbolReferences>, Vec<(String, Vec<String>)>) {
+        // Overall cap: all first references, then all second, ...
+        let total: usize = ranked.iter().map(|(_, refs)| refs.len()).sum();
+        if total > MAX_TOTAL_REFERENCES {
+            let mut keep = vec![0; ranked.len()];
+            let mut left = MAX_TOTAL_REFERENCES;
+            let mut rank = 0;
+            while left > 0 {
+                for ((_, refs), keep) in ranked.iter().zip(keep.iter_mut()) {
+                    if left > 0 && rank < refs.len() {
+                        *keep += 1;
+                        left -= 1;
+                    }
+                }
+                rank += 1;
+            }
+            for ((_, refs), keep) in ranked.iter_mut().zip(keep) {
+                refs.truncate(keep);
+            }
+        }
+
+        // Files to update, with every symbol that touches them
+        let mut files_to_update: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (symbol, refs) in &ranked {
+            for r in refs {
+                let update = if r.is_definition {
+                    include_definition
+                } else {
+                    r.confidence >= 0.80
+                };
+                let symbols = files_to_update.entry(r.file_path.clone()).or_default();
+                if update && !symbols.contains(symbol) {
+                    symbols.push(symbol.clone());
+                }
+            }
+        }
+        files_to_update.retain(|_, symbols| !symbols.is_empty());
+
+        // Each location once, under the first symbol found there
+        let mut claimed: HashMap<(String, usize, Option<usize>), (usize, usize)> = HashMap::new();
+        let mut merged: Vec<SymbolReferences> = Vec::with_capacity(ranked.len());
+        for (owner, (symbol, refs)) in ranked.into_iter().enumerate() {
+            let mut references = Vec::with_capacity(refs.len());
+            for r in refs {
+                let location = Self::location(&r);
+                if let Some(&(first, i)) = claimed.get(&location) {
+                    merged[first].also_matches[i].push(symbol.clone());
+                    continue;
+                }
+                claimed.insert(location, (owner, references.len()));
+                references.push(r);
+            }
+            merged.push(SymbolReferences {
+                symbol,
+                also_matches: vec![Vec::new(); references.len()],
+                references,
+            });
+        }
+
+        (merged, files_to_update.into_iter().collect())
+    }
+
+    /// Reference for a hit in a file that could not be read.
+    ///
+    /// Located and scored in the indexed chunk text, with a confidence
+    /// penalty since the file may have changed since indexing.
+    fn chunk_reference(
+        result: SearchResult,
+        symbol: &str,
+        patterns: &[(Regex, &'static str, f32)],
+        definitions: &DefinitionMatcher,
+        context_lines: usize,
+        error: std::io::ErrorKind,
+    ) -> Option<Reference> {
+        let language = detect_language(&result.file_path);
+        let found = sources::locate_in_chunk(
+            &result.text,
+            result.start_offset,
+            symbol,
+            context_lines,
+            language,
+        )?;
+
+        let (pattern_name, base_confidence) = patterns
+            .iter()
+            .find(|(regex, _, _)| regex.is_match(&result.text))
+            .map(|(_, name, conf)| (*name, *conf))
+            .unwrap_or(("word_match", 0.60));
+        let confidence =
+            Self::adjust_confidence(base_confidence, &result.file_path, &found.context);
+        let (pattern_name, confidence) = Self::apply_region(found.region, pattern_name, confidence);
+        let is_definition = found.region.is_none() && definitions.is_definition(&found.line);
+
+        Some(Reference {
+            section: result.section,
+            session: result.session,
+            file_path: result.file_path,
+            line_number: 0,
+            column: 0,
+            columns: Vec::new(),
+            offset: found.offset,
+            context: found.context,
+            pattern: if is_definition {
+                "definition"
+            } else {
+                pattern_name
+            }
+            .to_string(),
+            confidence: (confidence - DEGRADED_CONTEXT_PENALTY).max(0.0),
+            is_definition,
+            context_unavailable: Some(error.to_string()),
+        })
+    }
+
+    /// Locate the symbol in each search hit and score it as a reference
+    ///
+    /// Reads every hit's file (once, cached in `files`) for the exact
+    /// line and context. Hits in `skip_file` are left out. Blocking;
+    /// stops early when the call is cancelled.
+    fn collect_references(
+        results: Vec<SearchResult>,
+        symbol: &str,
+        symbol_type: SymbolType,
+        skip_file: Option<&str>,
+        context_lines: usize,
+        files: &mut SourceFiles,
+    ) -> Result<Vec<Reference>, ShebeError> {
+        let patterns = Self::build_patterns(symbol, symbol_type);
+        let definitions = DefinitionMatcher::new(symbol);
+
+        let mut references: Vec<Reference> = Vec::new();
+
+        for result in results {
+            check_cancelled()?;
+
+            // Skip definition file if requested
+            if skip_file.is_some_and(|defined_in| result.file_path.ends_with(defined_in)) {
+                continue;
+            }
+
+            // Read file content and its comment/string regions (cached to
+            // avoid re-reading)
+            let language = detect_language(&result.file_path);
+            let (file_content, regions) = match files.get(&result.file_path, language) {
+                Ok(file) => (&file.content, &file.regions),
+                Err(error) => {
+                    // Keep the reference, located in the indexed chunk text
+                    references.extend(Self::chunk_reference(
+                        result,
+                        symbol,
+                        &patterns,
+                        &definitions,
+                        context_lines,
+                        error,
+                    ));
+                    continue;
+                }
+            };
+
+            // Locate the chunk in the current file (skip chunks no longer there)
+            let Some(chunk) = result.locate_in(file_content).range() else {
+                continue;
+            };
+
+            // Find symbol position and calculate line number
+            if let Some(symbol_pos) = file_content[chunk.clone()].find(symbol) {
+                let absolute_offset = chunk.start + symbol_pos;
+                let Some(location) = locate_symbol(file_content, absolute_offset, symbol) else {
+                    continue;
+                };
+                let line_number = location.line_number;
+
+                // Match against patterns for confidence scoring
+                let (pattern_name, base_confidence) = patterns
+                    .iter()
+                    .find(|(regex, _, _)| regex.is_match(&result.text))
+                    .map(|(_, name, conf)| (*name, *conf))
+                    .unwrap_or(("word_match", 0.60));
+
+                // Extract context lines
+                let context = extract_context_lines(file_content, line_number, context_lines);
+
+                // Adjust confidence based on context
+                let confidence =
+                    Self::adjust_confidence(base_confidence, &result.file_path, &context);
+                let region = regions.kind_at(absolute_offset);
+                let (pattern_name, confidence) =
+                    Self::apply_region(region, pattern_name, confidence);
+
+                // Definition sites outside comments and strings
+                let line = file_content
+                    .lines()
+                    .nth(line_number - 1)
+                    .unwrap_or_default();
+                let is_definition = region.is_none() && definitions.is_definition(line);
+                let pattern_name = if is_definition {
+                    "definition"
+                } else {
+                    pattern_name
+                };
+
+                references.push(Reference {
+                    section: result.section,
+                    session: result.session,
+                    file_path: result.file_path,
+                    line_number,
+                    column: location.column,
+                    columns: location.columns,
+                    offset: absolute_offset,
+                    context,
+                    pattern: pattern_name.to_string(),
+                    confidence,
+                    is_definition,
+                    context_unavailable: None,
+                });
+            }
+        }
+        Ok(references)
     }
 
     /// Format results as markdown output.
+    ///
+    /// Definition sites come first, then the other references by
+    /// confidence. Definitions are left out of "Files to update" unless
+    /// `include_definition` is set. When the output exceeds `budget`,
+    /// context is dropped from the lowest-confidence references first,
+    /// then those references are left out. Files that could not be read
+    /// are listed under "Warnings".
+    #[allow(clippy::too_many_arguments)]
     fn format_results(
         &self,
         symbol: &str,
         references: &[Reference],
+        unreadable: &[UnreadableFile],
+        include_definition: bool,
         session_metadata: Option<&SessionMetadata>,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
     ) -> String {
         if references.is_empty() {
             let mut output = format!("No references found for `{symbol}`\n");
@@ -275,98 +615,386 @@ impl FindReferencesHandler {
                     format_time_ago(meta.last_indexed_at)
                 ));
             }
+            output.push_str(&Self::format_warnings(unreadable, display));
             return output;
         }
 
-        // Group by confidence level
-        let mut high: Vec<&Reference> = Vec::new();
-        let mut medium: Vec<&Reference> = Vec::new();
-        let mut low: Vec<&Reference> = Vec::new();
-
-        for r in references {
-            if r.confidence >= 0.80 {
-                high.push(r);
-            } else if r.confidence >= 0.50 {
-                medium.push(r);
-            } else {
-                low.push(r);
-            }
-        }
+        // Definitions apart, the rest grouped by confidence level
+        let groups = Self::group_references(references);
+        let [definitions, high, medium, low] = groups.each_ref().map(|(_, group)| {
+            group
+                .iter()
+                .map(|&i| &references[i])
+                .collect::<Vec<&Reference>>()
+        });
 
         let mut output = format!(
             "## References to `{symbol}` ({} found)\n\n",
             references.len()
         );
 
-        // High confidence
-        if !high.is_empty() {
-            output.push_str(&format!("### High Confidence ({})\n\n", high.len()));
-            for r in &high {
-                output.push_str(&self.format_single_reference(r));
-            }
+        let entries = self.reference_entries(references, &groups, &[], display);
+
+        // Summary
+        let unique_files: HashSet<_> = references.iter().map(|r| &r.file_path).collect();
+
+        let mut summary = String::from("---\n\n**Summary:**\n");
+        if !definitions.is_empty() {
+            summary.push_str(&format!("- Definitions: {}\n", definitions.len()));
+        }
+        summary.push_str(&format!("- High confidence: {} references\n", high.len()));
+        summary.push_str(&format!(
+            "- Medium confidence: {} references\n",
+            medium.len()
+        ));
+        summary.push_str(&format!("- Low confidence: {} references\n", low.len()));
+        summary.push_str(&format!("- Total files: {}\n", unique_files.len()));
+        let degraded = references
+            .iter()
+            .filter(|r| r.context_unavailable.is_some())
+            .count();
+        if degraded > 0 {
+            summary.push_str(&format!(
+                "- Degraded context: {degraded} references (file unreadable)\n"
+            ));
+        }
+
+        // Session freshness
+        if let Some(meta) = session_metadata {
+            summary.push_str(&format!(
+                "- Session indexed: {} ({})\n",
+                meta.last_indexed_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                format_time_ago(meta.last_indexed_at)
+            ));
         }
 
-        // Medium confidence
-        if !medium.is_empty() {
-            output.push_str(&format!("### Medium Confidence ({})\n\n", medium.len()));
-            for r in &medium {
-                output.push_str(&self.format_single_reference(r));
+        // Files to update (high confidence usages, plus definitions if asked)
+        let to_update = high
+            .iter()
+            .chain(definitions.iter().filter(|_| include_definition));
+        let update_files: HashSet<_> = to_update.map(|r| r.file_path.as_str()).collect();
+        if !update_files.is_empty() {
+            summary.push_str("\n**Files to update:**\n");
+            for file in update_files {
+                summary.push_str(&format!(
+                    "- {}\n",
+                    display.markdown(file, &display.path(file), None, None)
+                ));
             }
         }
+        summary.push_str(&Self::format_warnings(unreadable, display));
+
+        let fixed = estimate_tokens(&output) + estimate_tokens(&summary);
+        let (body, plan) = budget.fit(fixed, &entries);
+        output.push_str(&body);
+        output.push_str(&summary);
+
+        if plan.is_truncated() {
+            let mut omitted = Vec::new();
+            if plan.compacted() > 0 {
+                omitted.push(format!(
+                    "Context omitted for {} of {} references (lowest confidence first)",
+                    plan.compacted(),
+                    references.len()
+                ));
+            }
+            if plan.dropped() > 0 {
+                omitted.push(format!(
+                    "{} lowest-confidence references not shown (counted in the summary)",
+                    plan.dropped()
+                ));
+            }
+            output.push_str(&build_truncation_footer(budget, &omitted, TRUNCATION_HINT));
+        }
+
+        output
+    }
 
-        // Low confidence
-        if !low.is_empty() {
-            output.push_str(&format!("### Low Confidence ({})\n\n", low.len()));
-            for r in &low {
-                output.push_str(&self.format_single_reference(r));
+    /// Indices of the definitions, then of the other references by
+    /// confidence level, each with its group label.
+    fn group_references(references: &[Reference]) -> [(&'static str, Vec<usize>); 4] {
+        let mut groups = [
+            ("Definition", Vec::new()),
+            ("High Confidence", Vec::new()),
+            ("Medium Confidence", Vec::new()),
+            ("Low Confidence", Vec::new()),
+        ];
+        for (i, r) in references.iter().enumerate() {
+            let group = if r.is_definition {
+                0
+            } else if r.confidence >= 0.80 {
+                1
+            } else if r.confidence >= 0.50 {
+                2
+            } else {
+                3
+            };
+            groups[group].1.push(i);
+        }
+        groups
+    }
+
+    /// One entry (with and without context) per reference, in group order.
+    ///
+    /// Each group heading travels with the group's first reference.
+    /// `also_matches` holds, per reference, other symbols found at the
+    /// same location (empty for a single-symbol call).
+    fn reference_entries(
+        &self,
+        references: &[Reference],
+        groups: &[(&'static str, Vec<usize>)],
+        also_matches: &[Vec<String>],
+        display: &PathDisplay,
+    ) -> Vec<(String, Option<String>)> {
+        let mut entries = Vec::with_capacity(references.len());
+        for (label, group) in groups {
+            for (n, &i) in group.iter().enumerate() {
+                let heading = if n == 0 {
+                    format!("### {label} ({})\n\n", group.len())
+                } else {
+                    String::new()
+                };
+                let r = &references[i];
+                let also = also_matches.get(i).map_or(&[][..], Vec::as_slice);
+                entries.push((
+                    format!(
+                        "{heading}{}",
+                        self.format_single_reference(r, true, also, display)
+                    ),
+                    Some(format!(
+                        "{heading}{}",
+                        self.format_single_reference(r, false, also, display)
+                    )),
+                ));
             }
         }
+        entries
+    }
 
-        // Summary
-        let unique_files: HashSet<_> = references.iter().map(|r| &r.file_path).collect();
+    /// Format the references of a multi-symbol call as markdown output.
+    ///
+    /// One section per symbol, in call order, grouped as in
+    /// `format_results`. References compete for the budget by rank
+    /// across symbols (all first references before any second one), so
+    /// trimming takes the lowest-ranked references of every symbol
+    /// rather than whole symbols at the end. "Files to update" lists
+    /// each file once with the symbols it touches.
+    fn format_multi_results(
+        &self,
+        symbols: &[SymbolReferences],
+        files_to_update: &[(String, Vec<String>)],
+        unreadable: &[UnreadableFile],
+        session_metadata: Option<&SessionMetadata>,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
+    ) -> String {
+        let total: usize = symbols.iter().map(|s| s.references.len()).sum();
+        let names: Vec<String> = symbols.iter().map(|s| format!("`{}`", s.symbol)).collect();
+        let header = format!(
+            "# References to {} symbols: {} ({total} found)\n\n",
+            symbols.len(),
+            names.join(", ")
+        );
 
-        output.push_str("---\n\n**Summary:**\n");
-        output.push_str(&format!("- High confidence: {} references\n", high.len()));
-        output.push_str(&format!(
-            "- Medium confidence: {} references\n",
-            medium.len()
-        ));
-        output.push_str(&format!("- Low confidence: {} references\n", low.len()));
-        output.push_str(&format!("- Total files: {}\n", unique_files.len()));
+        // Per-symbol headings, summary lines and rendered references
+        let mut summary = String::from("---\n\n**Summary:**\n");
+        let mut blocks = Vec::with_capacity(symbols.len());
+        for s in symbols {
+            let groups = Self::group_references(&s.references);
+            let head = if s.references.is_empty() {
+                format!("## `{}`\n\nNo references found\n\n", s.symbol)
+            } else {
+                format!("## `{}` ({} found)\n\n", s.symbol, s.references.len())
+            };
+            let [definitions, high, medium, low] = groups.each_ref().map(|(_, g)| g.len());
+            summary.push_str(&format!(
+                "- `{}`: {definitions} definitions, {high} high, {medium} medium, \
+                 {low} low confidence\n",
+                s.symbol
+            ));
+            let entries = self.reference_entries(&s.references, &groups, &s.also_matches, display);
+            blocks.push((head, entries));
+        }
 
-        // Session freshness
+        let unique_files: HashSet<_> = symbols
+            .iter()
+            .flat_map(|s| &s.references)
+            .map(|r| &r.file_path)
+            .collect();
+        summary.push_str(&format!("- Total files: {}\n", unique_files.len()));
+        let degraded = symbols
+            .iter()
+            .flat_map(|s| &s.references)
+            .filter(|r| r.context_unavailable.is_some())
+            .count();
+        if degraded > 0 {
+            summary.push_str(&format!(
+                "- Degraded context: {degraded} references (file unreadable)\n"
+            ));
+        }
         if let Some(meta) = session_metadata {
-            output.push_str(&format!(
+            summary.push_str(&format!(
                 "- Session indexed: {} ({})\n",
                 meta.last_indexed_at.format("%Y-%m-%d %H:%M:%S UTC"),
                 format_time_ago(meta.last_indexed_at)
             ));
         }
+        if !files_to_update.is_empty() {
+            summary.push_str("\n**Files to update:**\n");
+            for (file, touched) in files_to_update {
+                let touched: Vec<String> = touched.iter().map(|s| format!("`{s}`")).collect();
+                summary.push_str(&format!(
+                    "- {} ({})\n",
+                    display.markdown(file, &display.path(file), None, None),
+                    touched.join(", ")
+                ));
+            }
+        }
+        summary.push_str(&Self::format_warnings(unreadable, display));
+
+        // References in priority order: by rank, then by symbol
+        let mut order: Vec<(usize, usize)> = blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(s, (_, entries))| (0..entries.len()).map(move |rank| (rank, s)))
+            .collect();
+        order.sort_unstable();
+        let sizes: Vec<EntrySize> = order
+            .iter()
+            .map(|&(rank, s)| {
+                let (full, compact) = &blocks[s].1[rank];
+                EntrySize::measure(full, compact.as_deref())
+            })
+            .collect();
+        let mut priority = vec![Vec::new(); blocks.len()];
+        for (p, &(_, s)) in order.iter().enumerate() {
+            priority[s].push(p);
+        }
 
-        // Files to update (high confidence only)
-        if !high.is_empty() {
-            output.push_str("\n**Files to update:**\n");
-            let high_files: HashSet<_> = high.iter().map(|r| r.file_path.as_str()).collect();
-            for file in high_files {
-                output.push_str(&format!("- `{file}`\n"));
+        let fixed = estimate_tokens(&header)
+            + estimate_tokens(&summary)
+            + blocks
+                .iter()
+                .map(|(head, _)| estimate_tokens(head))
+                .sum::<usize>();
+        let plan = budget.plan(fixed, &sizes);
+
+        let mut output = header;
+        for ((head, entries), priority) in blocks.iter().zip(&priority) {
+            output.push_str(head);
+            for ((full, compact), &p) in entries.iter().zip(priority) {
+                if !plan.is_kept(p) {
+                    continue;
+                }
+                match compact {
+                    Some(compact) if plan.is_compacted(p) => output.push_str(compact),
+                    _ => output.push_str(full),
+                }
             }
         }
+        output.push_str(&summary);
+
+        if plan.is_truncated() {
+            let mut omitted = Vec::new();
+            if plan.compacted() > 0 {
+                omitted.push(format!(
+                    "Context omitted for {} of {total} references (lowest-ranked first)",
+                    plan.compacted()
+                ));
+            }
+            if plan.dropped() > 0 {
+                omitted.push(format!(
+                    "{} lowest-ranked references not shown across all symbols \
+                     (counted in the summary)",
+                    plan.dropped()
+                ));
+            }
+            output.push_str(&build_truncation_footer(budget, &omitted, TRUNCATION_HINT));
+        }
 
         output
     }
 
+    /// List files whose references fell back to the indexed chunk text.
+    fn format_warnings(unreadable: &[UnreadableFile], display: &PathDisplay) -> String {
+        if unreadable.is_empty() {
+            return String::new();
+        }
+        let mut output = format!(
+            "\n### Warnings\n\n\
+             {} file(s) could not be read; their references use the indexed \
+             text and may be out of date:\n",
+            unreadable.len()
+        );
+        for file in unreadable {
+            output.push_str(&format!(
+                "- {}: {}\n",
+                display.markdown(&file.file_path, &display.path(&file.file_path), None, None),
+                file.error
+            ));
+        }
+        output
+    }
+
     /// Format a single reference for output.
-    fn format_single_reference(&self, r: &Reference) -> String {
-        let lang = detect_language(&r.file_path);
-        format!(
-            "#### {}:{}\n```{}\n{}\n```\n- **Pattern:** {}\n- **Confidence:** {:.2}\n\n",
-            r.file_path,
-            r.line_number,
-            lang,
-            r.context.trim(),
-            r.pattern,
-            r.confidence
-        )
+    ///
+    /// Without context only the location, pattern and confidence are shown.
+    /// `also_matches` are other symbols found at the same location.
+    fn format_single_reference(
+        &self,
+        r: &Reference,
+        with_context: bool,
+        also_matches: &[String],
+        display: &PathDisplay,
+    ) -> String {
+        let mut output = if r.context_unavailable.is_some() {
+            // Line unknown: no editor link
+            format!("#### {} (byte {})\n", display.path(&r.file_path), r.offset)
+        } else {
+            let location = format!(
+                "{}:{}:{}",
+                display.path(&r.file_path),
+                r.line_number,
+                r.column
+            );
+            match display.link(&r.file_path, Some(r.line_number), Some(r.column)) {
+                Some(url) => format!("#### [{location}]({url})\n"),
+                None => format!("#### {location}\n"),
+            }
+        };
+        output.push_str(&format_section(
+            &display.path(&r.file_path),
+            r.section.as_deref(),
+        ));
+        if with_context {
+            output.push_str(&format!(
+                "```{}\n{}\n```\n",
+                detect_language(&r.file_path),
+                r.context.trim()
+            ));
+        }
+        if let Some(session) = &r.session {
+            output.push_str(&format!("- **Session:** `{session}`\n"));
+        }
+        output.push_str(&format!(
+            "- **Pattern:** {}\n- **Confidence:** {:.2}\n",
+            r.pattern, r.confidence
+        ));
+        if r.columns.len() > 1 {
+            let columns: Vec<String> = r.columns.iter().map(|c| c.to_string()).collect();
+            output.push_str(&format!("- **Columns:** {}\n", columns.join(", ")));
+        }
+        if !also_matches.is_empty() {
+            let also: Vec<String> = also_matches.iter().map(|s| format!("`{s}`")).collect();
+            output.push_str(&format!("- **Also matches:** {}\n", also.join(", ")));
+        }
+        if let Some(error) = &r.context_unavailable {
+            output.push_str(&format!(
+                "- **Context:** context_unavailable ({error}; indexed text shown)\n"
+            ));
+        }
+        output.push('\n');
+        output
     }
 }
 
@@ -376,6 +1004,10 @@ impl McpToolHandler for FindReferencesHandler {
         "find_references"
     }
 
+    fn timeout(&self, args: &Value, default: Duration) -> Option<Duration> {
+        requested_timeout(args, default)
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "find_references".to_string(),
@@ -437,22 +1069,46 @@ Use find_references first to get the list, then use appropriate tools to make ch
 
 ## Output
 
-Returns a "Files to update" list with high-confidence references grouped first.
-Use this list to systematically update each file."#
+Lines defining the symbol (`fn X`, `class X`, `def X`, `struct X`, `func X`, ...)
+are listed first under "Definition". The rest are grouped by confidence. The
+"Files to update" list covers high-confidence usages; definition sites are only
+added with include_definition=true. Use this list to systematically update each file.
+
+## Several Symbols
+
+Pass an array as `symbol` (2-20 names, e.g. a struct and its methods) to find them
+together with one session and option set. The output has one section per symbol;
+max_results applies to each symbol, up to 500 references in all. A location found
+for several symbols is listed once, under the first, with "Also matches". The
+combined "Files to update" list notes which symbols touch each file."#
                 .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "symbol": {
-                        "type": "string",
-                        "description": "Symbol name to find references for",
-                        "minLength": 2,
-                        "maxLength": 200
+                        "description": "Symbol name to find references for, or an array of 2-20 \
+                                       symbols (e.g. a type and its methods) to find in one call",
+                        "oneOf": [
+                            {"type": "string", "minLength": 2, "maxLength": 200},
+                            {
+                                "type": "array",
+                                "items": {"type": "string", "minLength": 2, "maxLength": 200},
+                                "minItems": 2,
+                                "maxItems": MAX_SYMBOLS
+                            }
+                        ]
                     },
                     "session": {
                         "type": "string",
-                        "description": "Session ID to search",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "description": "Session ID to search. Required unless collection is given.",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "collection": {
+                        "type": "string",
+                        "description": "Search every session of this collection instead of one session \
+                                       (see create_collection). References are labelled with their \
+                                       session and paths are shown in full.",
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "symbol_type": {
                         "type": "string",
@@ -466,7 +1122,8 @@ Use this list to systematically update each file."#
                     },
                     "include_definition": {
                         "type": "boolean",
-                        "description": "Include definition site in results",
+                        "description": "List definition sites under Files to update, and keep \
+                                       references in the defined_in file",
                         "default": false
                     },
                     "context_lines": {
@@ -482,9 +1139,33 @@ Use this list to systematically update each file."#
                         "default": 50,
                         "minimum": 1,
                         "maximum": 500
+                    },
+                    "link_format": {
+                        "type": "string",
+                        "description": "Template for an \"open in editor\" link on each reference, using \
+                                       {path}, {line} and {column} (e.g. 'vscode://file/{path}:{line}:{column}'). \
+                                       'none' disables links. Default: server output.link_format (none)."
+                    },
+                    "relative_paths": {
+                        "type": "boolean",
+                        "description": "If true, show paths relative to the session's repository path \
+                                       (paths outside it stay absolute). \
+                                       Default: server output.relative_paths (false)."
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Absolute path of the repository the session covers (optional). \
+                                       Only used when no sessions exist yet: the error then suggests an \
+                                       index_repository call for this path."
+                    },
+                    "timeout_sec": {
+                        "type": "integer",
+                        "description": "Time limit for this call in seconds (default: \
+                                       limits.tool_timeout_sec, 60)",
+                        "minimum": 1
                     }
                 },
-                "required": ["symbol", "session"]
+                "required": ["symbol"]
             }),
         }
     }
@@ -492,8 +1173,9 @@ Use this list to systematically update each file."#
     async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
         #[derive(Deserialize)]
         struct FindReferencesArgs {
-            symbol: String,
-            session: String,
+            symbol: SymbolArg,
+            session: Option<String>,
+            collection: Option<String>,
             #[serde(default)]
             symbol_type: Option<String>,
             #[serde(default)]
@@ -504,6 +1186,8 @@ Use this list to systematically update each file."#
             context_lines: usize,
             #[serde(default = "default_max_results")]
             max_results: usize,
+            link_format: Option<String>,
+            relative_paths: Option<bool>,
         }
         fn default_context_lines() -> usize {
             2
@@ -516,112 +1200,146 @@ Use this list to systematically update each file."#
         let args: FindReferencesArgs =
             serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
 
-        // Validate symbol
-        if args.symbol.trim().is_empty() {
-            return Err(McpError::InvalidParams(
-                "Symbol cannot be empty".to_string(),
-            ));
-        }
-        if args.symbol.len() < 2 {
-            return Err(McpError::InvalidParams(
-                "Symbol must be at least 2 characters".to_string(),
-            ));
-        }
-
-        // Search using SearchService
-        let search_request = SearchRequest {
-            query: args.symbol.clone(),
-            session: args.session.clone(),
-            k: Some(args.max_results * 2), // Over-fetch to allow for filtering
-        };
-        let search_response = self
-            .services
-            .search
-            .search(search_request)
-            .map_err(McpError::from)?;
-
-        // Build patterns based on symbol_type
-        let symbol_type = Self::parse_symbol_type(&args.symbol_type);
-        let patterns = Self::build_patterns(&args.symbol, symbol_type);
-
-        // Process search results
-        let mut references: Vec<Reference> = Vec::new();
-        let mut files_cache: HashMap<String, String> = HashMap::new();
-
-        for result in search_response.results {
-            // Skip definition file if requested
-            if !args.include_definition {
-                if let Some(ref defined_in) = args.defined_in {
-                    if result.file_path.ends_with(defined_in) {
-                        continue;
-                    }
+        // Validate symbols (duplicates are found once)
+        let symbols = match args.symbol {
+            SymbolArg::One(symbol) => vec![symbol],
+            SymbolArg::Many(symbols) => {
+                if !(2..=MAX_SYMBOLS).contains(&symbols.len()) {
+                    return Err(McpError::InvalidParams(format!(
+                        "symbol array must have 2 to {MAX_SYMBOLS} symbols (got {})",
+                        symbols.len()
+                    )));
                 }
-            }
-
-            // Read file content (cached to avoid re-reading)
-            let file_content = if let Some(content) = files_cache.get(&result.file_path) {
-                content.clone()
-            } else {
-                match std::fs::read_to_string(&result.file_path) {
-                    Ok(content) => {
-                        files_cache.insert(result.file_path.clone(), content.clone());
-                        content
+                let mut unique: Vec<String> = Vec::with_capacity(symbols.len());
+                for symbol in symbols {
+                    if !unique.contains(&symbol) {
+                        unique.push(symbol);
                     }
-                    Err(_) => continue, // Skip unreadable files
                 }
-            };
-
-            // Find symbol position and calculate line number
-            let chunk_start = result.start_offset;
-            if let Some(symbol_pos) = result.text.find(&args.symbol) {
-                let absolute_offset = chunk_start + symbol_pos;
-                let line_number = byte_offset_to_line_number(&file_content, absolute_offset);
-
-                // Match against patterns for confidence scoring
-                let (pattern_name, base_confidence) = patterns
-                    .iter()
-                    .find(|(regex, _, _)| regex.is_match(&result.text))
-                    .map(|(_, name, conf)| (*name, *conf))
-                    .unwrap_or(("word_match", 0.60));
-
-                // Extract context lines
-                let context = extract_context_lines(&file_content, line_number, args.context_lines);
-
-                // Adjust confidence based on context
-                let confidence =
-                    Self::adjust_confidence(base_confidence, &result.file_path, &context);
-
-                references.push(Reference {
-                    file_path: result.file_path,
-                    line_number,
-                    column: symbol_pos,
-                    context,
-                    pattern: pattern_name.to_string(),
-                    confidence,
-                });
+                unique
+            }
+        };
+        for symbol in &symbols {
+            if symbol.trim().is_empty() {
+                return Err(McpError::InvalidParams(
+                    "Symbol cannot be empty".to_string(),
+                ));
+            }
+            if symbol.len() < 2 {
+                return Err(McpError::InvalidParams(
+                    "Symbol must be at least 2 characters".to_string(),
+                ));
             }
         }
 
-        // Deduplicate (keep highest confidence per location)
-        Self::deduplicate_references(&mut references);
+        let target = SearchTarget::from_args(args.session.clone(), args.collection.clone())?;
+        let display = match &target {
+            SearchTarget::Session(session) => path_display(
+                &self.services,
+                session,
+                args.link_format.as_deref(),
+                args.relative_paths,
+            )?,
+            SearchTarget::Collection(_) => {
+                collection_display(&self.services, args.link_format.as_deref())?
+            }
+        };
 
-        // Sort by confidence (descending) and truncate
-        references.sort_by(|a, b| {
-            b.confidence
-                .partial_cmp(&a.confidence)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        references.truncate(args.max_results);
+        // Search every symbol over one searcher using SearchService
+        let requests: Vec<SearchRequest> = symbols
+            .iter()
+            .map(|symbol| SearchRequest {
+                query: symbol.clone(),
+                session: target.session().unwrap_or_default().to_string(),
+                k: Some(args.max_results * 2), // Over-fetch to allow for filtering
+                group_by_file: false,
+                dedupe_overlaps: true,
+                path_boost: Some(0.0), // Candidates are ranked by pattern confidence
+                fuzziness: Some(0),
+                expand_identifiers: false, // Only the symbol's own spelling is a reference
+                bm25_k1: None,
+                bm25_b: None,
+                within: None,
+                penalize_short_chunks: None,
+                modified_within: None,
+                section_filter: None,
+                exclude_terms: Vec::new(),
+                exclude_paths: Vec::new(),
+                explain: false,
+            })
+            .collect();
+        let services = Arc::clone(&self.services);
+        let responses = match target.clone() {
+            SearchTarget::Session(session) => {
+                run_blocking(move || services.search.search_batch(&session, &requests)).await?
+            }
+            // Each symbol over every member session, merged
+            SearchTarget::Collection(collection) => {
+                services.storage.get_collection(&collection)?;
+                run_blocking(move || {
+                    Ok::<_, ShebeError>(
+                        requests
+                            .into_iter()
+                            .map(|request| services.search_collection(&collection, request))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .await?
+            }
+        };
+
+        // Locate and score each symbol's hits, reading each file once
+        // (off the async runtime), and rank them
+        let symbol_type = Self::parse_symbol_type(&args.symbol_type);
+        let skip_file = args.defined_in.clone().filter(|_| !args.include_definition);
+        let (context_lines, max_results) = (args.context_lines, args.max_results);
+        let (ranked, unreadable) = run_blocking(move || {
+            let mut files = SourceFiles::new();
+            let mut ranked = Vec::with_capacity(symbols.len());
+            for (symbol, response) in symbols.into_iter().zip(responses) {
+                let mut references = Self::collect_references(
+                    response?.results,
+                    &symbol,
+                    symbol_type,
+                    skip_file.as_deref(),
+                    context_lines,
+                    &mut files,
+                )?;
+                Self::rank_references(&mut references, max_results);
+                ranked.push((symbol, references));
+            }
+            Ok::<_, ShebeError>((ranked, files.unreadable()))
+        })
+        .await?;
 
         // Get session metadata for timestamp
-        let session_metadata = self
-            .services
-            .storage
-            .get_session_metadata(&args.session)
-            .ok();
+        let session_metadata = target
+            .session()
+            .and_then(|session| self.services.storage.get_session_metadata(session).ok());
 
         // Format and return results
-        let output = self.format_results(&args.symbol, &references, session_metadata.as_ref());
+        let output = if ranked.len() == 1 {
+            let (symbol, references) = &ranked[0];
+            self.format_results(
+                symbol,
+                references,
+                &unreadable,
+                args.include_definition,
+                session_metadata.as_ref(),
+                &ResponseBudget::default(),
+                &display,
+            )
+        } else {
+            let (symbols, files_to_update) = Self::merge_symbols(ranked, args.include_definition);
+            self.format_multi_results(
+                &symbols,
+                &files_to_update,
+                &unreadable,
+                session_metadata.as_ref(),
+                &ResponseBudget::default(),
+                &display,
+            )
+        };
         Ok(text_content(output))
     }
 }
@@ -629,6 +1347,7 @@ Use this list to systematically update each file."#
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::display::LinkTemplate;
 
     #[test]
     fn test_parse_symbol_type() {
@@ -758,24 +1477,52 @@ mod tests {
         assert!(adjusted <= 1.0);
     }
 
+    #[test]
+    fn test_apply_region() {
+        assert_eq!(
+            FindReferencesHandler::apply_region(Some(RegionKind::Comment), "function_call", 0.95),
+            ("in_comment", IN_REGION_CONFIDENCE)
+        );
+        assert_eq!(
+            FindReferencesHandler::apply_region(Some(RegionKind::String), "word_match", 0.30),
+            ("in_string", 0.30)
+        );
+        assert_eq!(
+            FindReferencesHandler::apply_region(None, "function_call", 0.95),
+            ("function_call", 0.95)
+        );
+    }
+
     #[test]
     fn test_deduplicate_keeps_highest_confidence() {
         let mut refs = vec![
             Reference {
                 file_path: "a.rs".to_string(),
                 line_number: 10,
-                column: 0,
+                column: 1,
+                columns: vec![1],
+                offset: 0,
                 context: "".to_string(),
                 pattern: "word_match".to_string(),
                 confidence: 0.60,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
             },
             Reference {
                 file_path: "a.rs".to_string(),
                 line_number: 10,
-                column: 0,
+                column: 1,
+                columns: vec![1],
+                offset: 0,
                 context: "".to_string(),
                 pattern: "function_call".to_string(),
                 confidence: 0.95,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
             },
         ];
 
@@ -792,26 +1539,44 @@ mod tests {
             Reference {
                 file_path: "a.rs".to_string(),
                 line_number: 10,
-                column: 0,
+                column: 1,
+                columns: vec![1],
+                offset: 0,
                 context: "".to_string(),
                 pattern: "test".to_string(),
                 confidence: 0.80,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
             },
             Reference {
                 file_path: "a.rs".to_string(),
                 line_number: 20,
-                column: 0,
+                column: 1,
+                columns: vec![1],
+                offset: 0,
                 context: "".to_string(),
                 pattern: "test".to_string(),
                 confidence: 0.80,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
             },
             Reference {
                 file_path: "b.rs".to_string(),
                 line_number: 10,
-                column: 0,
+                column: 1,
+                columns: vec![1],
+                offset: 0,
                 context: "".to_string(),
                 pattern: "test".to_string(),
                 confidence: 0.80,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
             },
         ];
 
@@ -830,4 +1595,197 @@ mod tests {
         assert!(word_pattern.0.is_match("foo.bar"));
         assert!(!word_pattern.0.is_match("fooXbar")); // . should not match any char
     }
+
+    /// 200 references with 10 lines of context, confidence descending
+    fn oversized_references() -> Vec<Reference> {
+        let context = "    let value = handle_login(request, &session);\n".repeat(10);
+        (0..200)
+            .map(|i| Reference {
+                file_path: format!("src/module_{i}.rs"),
+                line_number: i + 1,
+                column: 17,
+                columns: vec![17],
+                offset: 0,
+                context: context.clone(),
+                pattern: "function_call".to_string(),
+                confidence: 0.95 - i as f32 * 0.004,
+                is_definition: false,
+                context_unavailable: None,
+                section: None,
+                session: None,
+            })
+            .collect()
+    }
+
+    fn test_handler() -> (FindReferencesHandler, tempfile::TempDir) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = crate::core::config::Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let handler = FindReferencesHandler::new(Arc::new(Services::new(config)));
+        (handler, temp_dir)
+    }
+
+    #[test]
+    fn test_format_results_drops_context_to_fit_budget() {
+        let (handler, _temp) = test_handler();
+        let references = oversized_references();
+        let budget = ResponseBudget::new(10_000);
+
+        let output = handler.format_results(
+            "handle_login",
+            &references,
+            &[],
+            false,
+            None,
+            &budget,
+            &PathDisplay::default(),
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        let shown = output.matches("#### ").count();
+        let with_context = output.matches("```rust").count();
+        assert_eq!(shown, 200);
+        assert!(with_context > 0 && with_context < shown);
+        assert!(output.contains(&format!(
+            "Context omitted for {} of 200 references (lowest confidence first)",
+            shown - with_context
+        )));
+        // Highest-confidence references keep their context
+        assert!(output.contains("#### src/module_0.rs:1:17\n```rust"));
+        assert!(output.contains("- High confidence: "));
+        assert!(output.contains(TRUNCATION_HINT));
+    }
+
+    #[test]
+    fn test_format_results_drops_low_confidence_references() {
+        let (handler, _temp) = test_handler();
+        let references = oversized_references();
+        let budget = ResponseBudget::new(2_000);
+
+        let output = handler.format_results(
+            "handle_login",
+            &references,
+            &[],
+            false,
+            None,
+            &budget,
+            &PathDisplay::default(),
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        let shown = output.matches("#### ").count();
+        assert!(shown < 200);
+        assert_eq!(output.matches("```rust").count(), 0);
+        assert!(output.contains(&format!(
+            "{} lowest-confidence references not shown",
+            200 - shown
+        )));
+        // The summary still counts every reference
+        assert!(output.contains("## References to `handle_login` (200 found)"));
+    }
+
+    #[test]
+    fn test_format_results_within_budget_has_no_footer() {
+        let (handler, _temp) = test_handler();
+        let references: Vec<Reference> = oversized_references().into_iter().take(3).collect();
+
+        let output = handler.format_results(
+            "handle_login",
+            &references,
+            &[],
+            false,
+            None,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
+
+        assert_eq!(output.matches("```rust").count(), 3);
+        assert!(!output.contains("RESPONSE TRUNCATED"));
+    }
+
+    #[test]
+    fn test_format_results_relative_paths_with_links() {
+        let (handler, _temp) = test_handler();
+        let mut references: Vec<Reference> = oversized_references().into_iter().take(2).collect();
+        references[0].file_path = "/repo/src/auth.rs".to_string();
+        references[1].file_path = "/opt/vendor/auth.rs".to_string();
+        let link = LinkTemplate::parse("vscode://file/{path}:{line}:{column}").unwrap();
+        let display = PathDisplay::new(Some(std::path::Path::new("/repo")), true, link);
+
+        let output = handler.format_results(
+            "handle_login",
+            &references,
+            &[],
+            false,
+            None,
+            &ResponseBudget::default(),
+            &display,
+        );
+
+        assert!(output.contains("#### [src/auth.rs:1:17](vscode://file//repo/src/auth.rs:1:17)\n"));
+        // Files outside the repository fall back to absolute paths
+        assert!(output
+            .contains("#### [/opt/vendor/auth.rs:2:17](vscode://file//opt/vendor/auth.rs:2:17)\n"));
+        assert!(output.contains("- [`src/auth.rs`](vscode://file//repo/src/auth.rs:1:1)\n"));
+    }
+
+    #[test]
+    fn test_merge_symbols_caps_total_by_rank() {
+        let ranked: Vec<(String, Vec<Reference>)> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|symbol| {
+                let mut refs = oversized_references();
+                for r in &mut refs {
+                    r.file_path = format!("src/{symbol}/{}", r.file_path);
+                }
+                (symbol.to_string(), refs)
+            })
+            .collect();
+
+        let (merged, files) = FindReferencesHandler::merge_symbols(ranked, false);
+
+        let kept: Vec<usize> = merged.iter().map(|s| s.references.len()).collect();
+        assert_eq!(kept, vec![167, 167, 166]);
+        // The highest-ranked references of every symbol are kept
+        assert!(merged
+            .iter()
+            .all(|s| s.references[0].file_path.ends_with("module_0.rs")));
+        // Only high-confidence usages (the first 38 of each) need updating
+        assert_eq!(files.len(), 3 * 38);
+    }
+
+    #[test]
+    fn test_merge_symbols_lists_shared_location_once() {
+        let refs = || oversized_references().into_iter().take(2).collect();
+        let ranked = vec![
+            ("first".to_string(), refs()),
+            ("second".to_string(), refs()),
+        ];
+
+        let (merged, files) = FindReferencesHandler::merge_symbols(ranked, false);
+
+        assert_eq!(merged[0].references.len(), 2);
+        assert!(merged[1].references.is_empty());
+        assert_eq!(merged[0].also_matches[0], vec!["second".to_string()]);
+        assert_eq!(
+            files[0],
+            (
+                "src/module_0.rs".to_string(),
+                vec!["first".to_string(), "second".to_string()]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_invalid_link_format() {
+        let (handler, _temp) = test_handler();
+
+        let args = json!({
+            "symbol": "handle_login",
+            "session": "missing",
+            "link_format": "vscode://{line}"
+        });
+        let result = handler.execute(args).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
 }
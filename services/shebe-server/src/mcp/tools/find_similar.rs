@@ -0,0 +1,340 @@
+//! Find similar chunks tool handler
+//!
+//! "More like this" for a search hit: the chunk's most distinctive
+//! terms are searched for across the session, and every hit lists the
+//! terms it shares with the source chunk.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{label_hits, path_display, resolve_session_path, run_blocking};
+use super::search_code::{format_also_present, format_partial_note, format_snippet, ChunkLookup};
+use crate::core::display::PathDisplay;
+use crate::core::results::handle_label;
+use crate::core::search::similar::{
+    DEFAULT_MIN_TERM_LEN, DEFAULT_SEED_TERMS, MAX_SEED_TERMS, MIN_SHARED_TERMS,
+};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::{SearchResult, SimilarRequest, SimilarResponse};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{build_truncation_footer, estimate_tokens, ResponseBudget};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const DEFAULT_K: usize = 10;
+const MAX_K: usize = 100;
+
+/// Longest `min_term_len` accepted
+const MAX_MIN_TERM_LEN: usize = 40;
+
+/// Shown instead of a snippet dropped to fit the response budget
+const SNIPPET_OMITTED: &str = "_Snippet omitted (response budget); view with preview_chunk._";
+
+/// How to get results trimmed by the response budget
+const TRUNCATION_HINT: &str = "lower `k`; view omitted snippets with preview_chunk";
+
+pub struct FindSimilarHandler {
+    services: Arc<Services>,
+}
+
+impl FindSimilarHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the similar chunks as Markdown
+    fn format_similar(
+        response: &SimilarResponse,
+        first_handle: usize,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
+    ) -> String {
+        let mut output = format!(
+            "# Similar to {} (chunk {})\n\n",
+            display.markdown(
+                &response.file_path,
+                &display.path(&response.file_path),
+                None,
+                None
+            ),
+            response.chunk_index
+        );
+
+        if response.seed_terms.len() < MIN_SHARED_TERMS {
+            output.push_str(
+                "Nothing similar found: this chunk has no distinctive terms that other \
+                 chunks of the session also contain (terms shorter than `min_term_len` \
+                 and numbers are ignored).",
+            );
+            return output;
+        }
+
+        let seeds: Vec<String> = response
+            .seed_terms
+            .iter()
+            .map(|term| format!("`{term}`"))
+            .collect();
+        output.push_str(&format!(
+            "**Seed terms:** {} (hits share at least {MIN_SHARED_TERMS})\n\n",
+            seeds.join(", ")
+        ));
+
+        if response.results.is_empty() {
+            output.push_str(&format!(
+                "Nothing similar found: no other chunk shares {MIN_SHARED_TERMS} or more of \
+                 these terms ({}ms).",
+                response.duration_ms
+            ));
+            return output;
+        }
+
+        output.push_str(&format!(
+            "Found {} similar chunks ({}ms):\n\n",
+            response.results.len(),
+            response.duration_ms
+        ));
+
+        let mut chunks = ChunkLookup::new(display);
+        let entries: Vec<(String, Option<String>)> = response
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, similar)| {
+                let result = &similar.result;
+                let chunk = chunks.locate(result);
+                let mut head = format!(
+                    "## Result {} (score: {:.2}, handle {})\n",
+                    i + 1,
+                    result.score,
+                    handle_label(first_handle + i)
+                );
+                head.push_str(&format!(
+                    "**File:** {} (chunk {}, bytes {}-{})\n",
+                    display.markdown(
+                        &result.file_path,
+                        &display.path(&result.file_path),
+                        chunk.line,
+                        None
+                    ),
+                    result.chunk_index,
+                    chunk.range.start,
+                    chunk.range.end
+                ));
+                head.push_str(&format!(
+                    "**Matched terms:** {} ({} of {})\n\n",
+                    similar.matched_terms.join(", "),
+                    similar.matched_terms.len(),
+                    response.seed_terms.len()
+                ));
+                head.push_str(&chunk.format_note());
+                head.push_str(&format_also_present(
+                    &result.also_present_at,
+                    display,
+                    chunk.line,
+                ));
+                head.push_str(format_partial_note(result));
+                (
+                    format!("{head}{}\n", format_snippet(result)),
+                    Some(format!("{head}{SNIPPET_OMITTED}\n\n")),
+                )
+            })
+            .collect();
+
+        let (body, plan) = budget.fit(estimate_tokens(&output), &entries);
+        output.push_str(&body);
+        if plan.is_truncated() {
+            let mut omitted = Vec::new();
+            if plan.compacted() > 0 {
+                omitted.push(format!(
+                    "Snippets omitted for {} of {} results (lowest-scoring first)",
+                    plan.compacted(),
+                    entries.len()
+                ));
+            }
+            if plan.dropped() > 0 {
+                omitted.push(format!(
+                    "{} lowest-scoring results not shown",
+                    plan.dropped()
+                ));
+            }
+            output.push_str(&build_truncation_footer(budget, &omitted, TRUNCATION_HINT));
+        }
+
+        output
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for FindSimilarHandler {
+    fn name(&self) -> &str {
+        "find_similar"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "find_similar".to_string(),
+            description: "Find chunks similar to a search hit ('more like this'). Takes the \
+                         chunk's most distinctive terms (TF-IDF against the session), searches \
+                         for them across the session and returns the best k chunks sharing at \
+                         least 2 of them, each labelled with the terms it matched. The source \
+                         chunk itself is never returned; exclude_source_file=true also skips \
+                         the rest of its file. Pass a result handle from search_code \
+                         (result='r3') or file_path and chunk_index. Says so plainly when \
+                         nothing similar exists."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID containing the chunk",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "result": {
+                        "type": "string",
+                        "description": "Result handle from a recent search_code or search_batch \
+                                       call (e.g. 'r3'). Use instead of file_path and chunk_index.",
+                        "pattern": "^[rR][0-9]+$"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "File path: absolute (from search result) or relative \
+                                       to the session's repository root (e.g. 'src/lib.rs'). \
+                                       Required with chunk_index unless result is given.",
+                        "minLength": 1
+                    },
+                    "chunk_index": {
+                        "type": "integer",
+                        "description": "Chunk index (from search result). \
+                                       Required with file_path unless result is given.",
+                        "minimum": 0
+                    },
+                    "k": {
+                        "type": "integer",
+                        "description": "Number of similar chunks to return (default: 10, max: 100)",
+                        "default": DEFAULT_K,
+                        "minimum": 1,
+                        "maximum": MAX_K
+                    },
+                    "min_term_len": {
+                        "type": "integer",
+                        "description": "Ignore seed terms shorter than this many characters \
+                                       (default: 3)",
+                        "default": DEFAULT_MIN_TERM_LEN,
+                        "minimum": 1,
+                        "maximum": MAX_MIN_TERM_LEN
+                    },
+                    "max_terms": {
+                        "type": "integer",
+                        "description": "Number of seed terms taken from the chunk (default: 10)",
+                        "default": DEFAULT_SEED_TERMS,
+                        "minimum": MIN_SHARED_TERMS,
+                        "maximum": MAX_SEED_TERMS
+                    },
+                    "exclude_source_file": {
+                        "type": "boolean",
+                        "description": "Skip every chunk of the source file, not just the source \
+                                       chunk (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct FindSimilarArgs {
+            session: String,
+            result: Option<String>,
+            file_path: Option<String>,
+            chunk_index: Option<usize>,
+            #[serde(default = "default_k")]
+            k: usize,
+            #[serde(default = "default_min_term_len")]
+            min_term_len: usize,
+            #[serde(default = "default_max_terms")]
+            max_terms: usize,
+            #[serde(default)]
+            exclude_source_file: bool,
+        }
+        fn default_k() -> usize {
+            DEFAULT_K
+        }
+        fn default_min_term_len() -> usize {
+            DEFAULT_MIN_TERM_LEN
+        }
+        fn default_max_terms() -> usize {
+            DEFAULT_SEED_TERMS
+        }
+
+        let args: FindSimilarArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        if !(1..=MAX_K).contains(&args.k) {
+            return Err(McpError::InvalidParams(format!(
+                "k must be between 1 and {MAX_K}"
+            )));
+        }
+        if !(1..=MAX_MIN_TERM_LEN).contains(&args.min_term_len) {
+            return Err(McpError::InvalidParams(format!(
+                "min_term_len must be between 1 and {MAX_MIN_TERM_LEN}"
+            )));
+        }
+
+        let (file_path, chunk_index) = match (args.result, args.file_path, args.chunk_index) {
+            (Some(handle), None, None) => {
+                let target = self
+                    .services
+                    .resolve_result(&args.session, &handle)
+                    .map_err(McpError::from)?;
+                (target.file_path, target.chunk_index)
+            }
+            (Some(_), _, _) => {
+                return Err(McpError::InvalidParams(
+                    "Pass either result or file_path and chunk_index, not both".to_string(),
+                ))
+            }
+            (None, Some(file_path), Some(chunk_index)) => (file_path, chunk_index),
+            (None, _, _) => {
+                return Err(McpError::InvalidParams(
+                    "file_path and chunk_index are required unless result is given".to_string(),
+                ))
+            }
+        };
+
+        // Relative paths resolve against the repository root
+        let file = resolve_session_path(&self.services, &args.session, &file_path)?;
+        let display = path_display(&self.services, &args.session, None, None)?;
+
+        let request = SimilarRequest {
+            session: args.session.clone(),
+            file_path: file.path,
+            chunk_index,
+            k: Some(args.k),
+            min_term_len: Some(args.min_term_len),
+            max_terms: Some(args.max_terms),
+            exclude_source_file: args.exclude_source_file,
+        };
+        let search = Arc::clone(&self.services.search);
+        let response = run_blocking(move || search.find_similar(request)).await?;
+
+        let hits: Vec<SearchResult> = response
+            .results
+            .iter()
+            .map(|similar| similar.result.clone())
+            .collect();
+        let label = format!("similar to {}#{}", response.file_path, response.chunk_index);
+        let first_handle = label_hits(&self.services, Some(&args.session), &label, &hits);
+
+        Ok(text_content(Self::format_similar(
+            &response,
+            first_handle,
+            &ResponseBudget::default(),
+            &display,
+        )))
+    }
+}
@@ -0,0 +1,300 @@
+//! Get job status tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_time_ago;
+use crate::core::jobs::{JobId, JobState, JobStatus};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct GetJobStatusHandler {
+    services: Arc<Services>,
+}
+
+impl GetJobStatusHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format a list of jobs as a Markdown table
+    fn format_job_list(&self, jobs: &[JobStatus]) -> String {
+        if jobs.is_empty() {
+            return "No background jobs. Start one with index_repository_async.".to_string();
+        }
+
+        let mut output = format!("**Jobs:** {}\n\n", jobs.len());
+        output.push_str("| Job | Session | State | Progress | Created |\n");
+        output.push_str("|-----|---------|-------|----------|---------|\n");
+        for job in jobs {
+            output.push_str(&format!(
+                "| `{}` | `{}` | {} | {} | {} |\n",
+                job.id,
+                job.session,
+                job.state.as_str(),
+                format_progress(job),
+                format_time_ago(job.created_at)
+            ));
+        }
+        output
+    }
+}
+
+/// Format one job's status as Markdown
+///
+/// Shared with cancel_job, which reports the status after cancelling.
+pub(super) fn format_job_status(job: &JobStatus) -> String {
+    let mut output = format!("## Job `{}`: {}\n\n", job.id, job.state.as_str());
+    output.push_str(&format!("- **Session:** `{}`\n", job.session));
+    output.push_str(&format!("- **Source:** {}\n", job.source));
+    output.push_str(&format!("- **Progress:** {}\n", format_progress(job)));
+    if job.cancel_requested {
+        output.push_str("- **Cancellation requested:** stops after the current file\n");
+    }
+    output.push_str(&format!(
+        "- **Created:** {}\n",
+        format_time_ago(job.created_at)
+    ));
+    if let Some(started) = job.started_at {
+        output.push_str(&format!("- **Started:** {}\n", format_time_ago(started)));
+    }
+    if let Some(finished) = job.finished_at {
+        output.push_str(&format!("- **Finished:** {}\n", format_time_ago(finished)));
+    }
+
+    if let Some(stats) = &job.stats {
+        output.push_str(&format!(
+            "\n**Result:** {} files indexed, {} chunks created in {:.1}s\n",
+            stats.files_indexed,
+            stats.chunks_created,
+            stats.duration_ms as f64 / 1000.0
+        ));
+        if stats.skipped.total() > 0 {
+            output.push_str(&format!(
+                "**Skipped:** {} files ({})\n",
+                stats.skipped.total(),
+                stats.skipped.summary()
+            ));
+        }
+        if !stats.truncated_files.is_empty() {
+            let paths: Vec<&str> = stats
+                .truncated_files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect();
+            output.push_str(&format!(
+                "**Partially indexed:** {} files cut off at max_chunks_per_file ({})\n",
+                paths.len(),
+                paths.join(", ")
+            ));
+        }
+        if stats.files_renamed > 0 {
+            output.push_str(&format!(
+                "**Renamed:** {} files with unchanged content under a new path\n",
+                stats.files_renamed
+            ));
+        }
+        if let Some(warning) = &stats.pattern_warning {
+            output.push_str(&format!("**Warning:** {warning}\n"));
+        }
+    }
+    if let Some(remote) = &job.remote {
+        output.push_str(&format!(
+            "**Cloned:** {} ({}, commit {})\n",
+            remote.url,
+            remote.ref_label(),
+            remote.short_commit()
+        ));
+    }
+    if let Some(error) = &job.error {
+        output.push_str(&format!("\n**Error:** {error}\n"));
+    }
+    if job.state == JobState::Cancelled {
+        output.push_str("\nNo partial session was kept.\n");
+    }
+    output
+}
+
+/// "42% (420/1000 files)", or the phase before files are counted
+fn format_progress(job: &JobStatus) -> String {
+    match job.state {
+        JobState::Queued => "waiting for a free indexing slot".to_string(),
+        JobState::Running if job.files_total == 0 => "scanning repository".to_string(),
+        _ => format!(
+            "{}% ({}/{} files)",
+            job.progress_percent, job.files_processed, job.files_total
+        ),
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for GetJobStatusHandler {
+    fn name(&self) -> &str {
+        "get_job_status"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "get_job_status".to_string(),
+            description: "Get the status of a background indexing job started with \
+                         index_repository_async: queued, running (with % of files processed), \
+                         completed (with statistics), failed (with the error) or cancelled. \
+                         Omit job_id to list all active jobs and jobs finished within the \
+                         server's retention window."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "job_id": {
+                        "type": "string",
+                        "description": "Job ID returned by index_repository_async (omit to list jobs)"
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct GetJobStatusArgs {
+            job_id: Option<String>,
+        }
+
+        let args: GetJobStatusArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let text = match args.job_id {
+            Some(id) => format_job_status(&self.services.get_job_status(&JobId::new(id))?),
+            None => self.format_job_list(&self.services.list_jobs()),
+        };
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::jobs::JobOutput;
+    use crate::core::types::IndexStats;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (GetJobStatusHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let services = Arc::new(Services::new(config));
+        (GetJobStatusHandler::new(services), temp_dir)
+    }
+
+    fn text_of(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        }
+    }
+
+    fn finished_job(handler: &GetJobStatusHandler) -> JobId {
+        let id = handler
+            .services
+            .jobs
+            .start("done", "/repo", |progress| {
+                progress.set_total(2);
+                progress.file_processed();
+                progress.file_processed();
+                Ok(JobOutput {
+                    stats: IndexStats {
+                        files_indexed: 2,
+                        chunks_created: 5,
+                        duration_ms: 1500,
+                        session: "done".to_string(),
+                        files_deduplicated: 0,
+                        skipped: Default::default(),
+                        skipped_files: Vec::new(),
+                        bytes_stripped: 0,
+                        files_streamed: 0,
+                        truncated_files: Vec::new(),
+                        files_renamed: 0,
+                        renamed_files: Vec::new(),
+                        patterns: Default::default(),
+                        pattern_warning: None,
+                        provenance: None,
+                        profile: None,
+                    },
+                    remote: None,
+                })
+            })
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !handler
+            .services
+            .get_job_status(&id)
+            .unwrap()
+            .state
+            .is_finished()
+        {
+            assert!(Instant::now() < deadline, "job did not finish");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        id
+    }
+
+    #[tokio::test]
+    async fn test_get_job_status_completed() {
+        let (handler, _temp) = setup_test_handler();
+        let id = finished_job(&handler);
+
+        let result = handler
+            .execute(json!({"job_id": id.as_str()}))
+            .await
+            .unwrap();
+        let text = text_of(&result);
+
+        assert!(text.contains(&format!("## Job `{id}`: completed")));
+        assert!(text.contains("- **Progress:** 100% (2/2 files)"));
+        assert!(text.contains("**Result:** 2 files indexed, 5 chunks created in 1.5s"));
+    }
+
+    #[tokio::test]
+    async fn test_get_job_status_lists_jobs() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({})).await.unwrap();
+        assert!(text_of(&result).contains("No background jobs"));
+
+        let id = finished_job(&handler);
+        let result = handler.execute(json!({})).await.unwrap();
+        let text = text_of(&result);
+        assert!(text.contains("**Jobs:** 1"));
+        assert!(text.contains(&format!(
+            "| `{id}` | `done` | completed | 100% (2/2 files) |"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_get_job_status_unknown_job() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"job_id": "job-404"})).await;
+        match result {
+            Err(McpError::InvalidParams(msg)) => assert!(msg.contains("job-404")),
+            other => panic!("Expected InvalidParams, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_format_progress_phases() {
+        let (handler, _temp) = setup_test_handler();
+        let id = finished_job(&handler);
+        let mut status = handler.services.get_job_status(&id).unwrap();
+
+        status.state = JobState::Queued;
+        assert_eq!(format_progress(&status), "waiting for a free indexing slot");
+        status.state = JobState::Running;
+        status.files_total = 0;
+        assert_eq!(format_progress(&status), "scanning repository");
+    }
+}
@@ -1,14 +1,33 @@
 //! Get server info tool handler
 //!
-//! Returns version and build information about the running shebe-mcp server.
+//! Returns version and build information about the running shebe-mcp server,
+//! what the calling connection negotiated in `initialize`, and how often
+//! each tool was called.
 
 use super::handler::{text_content, McpToolHandler};
+use super::usage::{ToolUsage, UsageSnapshot};
+use crate::core::storage::{Collection, OpenBreaker, SessionBreaker, StorageManager};
 use crate::mcp::error::McpError;
+use crate::mcp::handshake::{Handshake, SUPPORTED_PROTOCOL_VERSIONS};
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-pub struct GetServerInfoHandler;
+pub struct GetServerInfoHandler {
+    read_only: bool,
+    /// Active storage profile and its index directory
+    profile: Option<(String, PathBuf)>,
+    /// Tool usage statistics of the registry serving this tool
+    usage: Option<Arc<ToolUsage>>,
+    /// Sessions failing fast after repeated open failures
+    open_breaker: Option<Arc<OpenBreaker>>,
+    /// Storage whose collections are listed
+    storage: Option<Arc<StorageManager>>,
+}
 
 impl Default for GetServerInfoHandler {
     fn default() -> Self {
@@ -18,7 +37,43 @@ impl Default for GetServerInfoHandler {
 
 impl GetServerInfoHandler {
     pub fn new() -> Self {
-        Self
+        Self {
+            read_only: false,
+            profile: None,
+            usage: None,
+            open_breaker: None,
+            storage: None,
+        }
+    }
+
+    /// Report the server as read-only (`server.read_only`)
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Report the storage profile in use and where its sessions live
+    pub fn with_profile(mut self, name: &str, index_dir: &Path) -> Self {
+        self.profile = Some((name.to_string(), index_dir.to_path_buf()));
+        self
+    }
+
+    /// Report the tool usage counted in `usage`
+    pub fn with_usage(mut self, usage: Arc<ToolUsage>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Report the sessions tripped in `breaker`
+    pub fn with_open_breaker(mut self, breaker: Arc<OpenBreaker>) -> Self {
+        self.open_breaker = Some(breaker);
+        self
+    }
+
+    /// List the collections defined in `storage`
+    pub fn with_storage(mut self, storage: Arc<StorageManager>) -> Self {
+        self.storage = Some(storage);
+        self
     }
 
     fn format_info(&self) -> String {
@@ -34,28 +89,189 @@ impl GetServerInfoHandler {
         output.push_str("## Server Details\n");
         output.push_str("- **Name:** shebe-mcp\n");
         output.push_str("- **Description:** BM25 full-text search MCP server\n");
-        output.push_str("- **Protocol:** MCP 2024-11-05\n\n");
+        output.push_str(&format!(
+            "- **Protocol:** MCP {}\n",
+            SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+        ));
+        if let Some((name, index_dir)) = &self.profile {
+            output.push_str(&format!(
+                "- **Profile:** {name} (`{}`)\n",
+                index_dir.display()
+            ));
+        }
+        if self.read_only {
+            output
+                .push_str("- **Mode:** read-only (tools that change sessions are unavailable)\n\n");
+        } else {
+            output.push_str("- **Mode:** read-write\n\n");
+        }
+
+        if let Some(handshake) = Handshake::current() {
+            output.push_str(&format_handshake(&handshake));
+        }
+
+        let tripped = self
+            .open_breaker
+            .as_ref()
+            .map(|breaker| breaker.tripped())
+            .unwrap_or_default();
+        if !tripped.is_empty() {
+            output.push_str(&format_unavailable(&tripped));
+        }
+
+        if let Some(storage) = &self.storage {
+            match storage.list_collections() {
+                Ok(collections) => output.push_str(&format_collections(&collections)),
+                Err(e) => output.push_str(&format!(
+                    "## Collections
+- Unavailable: {e}
+
+"
+                )),
+            }
+        }
 
         output.push_str("## Available Tools\n");
         output.push_str("- search_code: Search indexed code\n");
+        output.push_str("- search_batch: Run up to 20 searches on one session in one call\n");
         output.push_str("- list_sessions: List all sessions\n");
         output.push_str("- get_session_info: Get session details\n");
         output.push_str("- index_repository: Index a repository (synchronous)\n");
         output.push_str("- get_server_info: Show server version (this tool)\n");
         output.push_str("- show_shebe_config: Show current configuration\n");
+        output.push_str("- reload_config: Re-read the configuration without restarting\n");
         output.push_str("- read_file: Read file contents (supports offset pagination)\n");
         output.push_str("- delete_session: Delete session and all data\n");
+        output.push_str("- create_collection: Name a group of sessions to search together\n");
+        output.push_str("- update_collection: Add or remove sessions of a collection\n");
+        output.push_str("- delete_collection: Delete a collection (its sessions are kept)\n");
         output.push_str("- list_dir: List files in session (supports cursor pagination)\n");
         output.push_str("- find_file: Find files by pattern (glob/regex)\n");
+        output.push_str("- sample_files: First lines of files matching glob patterns\n");
+        output.push_str("- locate: Files matching a vague description by path or content\n");
+        output.push_str("- search_paths: Files whose path roughly matches a few words\n");
         output.push_str("- find_references: Find all references to a symbol\n");
         output.push_str("- preview_chunk: Show N lines before/after search result chunk\n");
+        output.push_str("- find_similar: Chunks similar to a search hit (more like this)\n");
         output.push_str("- reindex_session: Re-index session using stored repository path\n");
         output.push_str("- upgrade_session: Upgrade session metadata to latest format\n");
+        output.push_str("- compact_session: Merge index segments and reclaim disk space\n");
+        output.push_str("- optimize_session: Merge segments to a target count and clean up\n");
+        output.push_str("- list_tree: Directory tree of indexed files with counts\n");
+        output.push_str("- index_repository_async: Index a repository as a background job\n");
+        output.push_str("- get_job_status: Progress and result of background indexing jobs\n");
+        output.push_str("- cancel_job: Cancel a queued or running indexing job\n");
+        output.push_str("- get_storage_report: Disk usage of all sessions\n");
+        output.push_str(
+            "- set_reindex_schedule: Re-index a session in the background on a schedule\n",
+        );
+        output.push_str("- set_session_defaults: Search options a session uses by default\n");
+        output.push_str("- diff_sessions: Files added, removed or changed between two sessions\n");
+        output.push_str("- compare_configs: Search results of two chunking configurations\n");
+        output
+            .push_str("- run_self_test: Index, search and read a built-in corpus, then clean up\n");
+
+        if let Some(usage) = &self.usage {
+            output.push('\n');
+            output.push_str(&format_usage(&usage.snapshot(), usage.is_enabled()));
+        }
 
         output
     }
 }
 
+/// "Unavailable Sessions" section: sessions failing fast after
+/// repeated open failures
+fn format_unavailable(tripped: &[SessionBreaker]) -> String {
+    let mut output = String::from("## Unavailable Sessions\n");
+    for session in tripped {
+        output.push_str(&format!(
+            "- **{}:** failed to open {} time(s), retrying after {}s (last error: {})\n",
+            session.session, session.failures, session.retry_after_secs, session.last_error
+        ));
+    }
+    output.push_str("\nPass reset: true to get_session_info to retry a session at once.\n\n");
+    output
+}
+
+/// "Collections" section: named groups of sessions and their members
+fn format_collections(collections: &[Collection]) -> String {
+    let mut output = String::from("## Collections\n");
+    if collections.is_empty() {
+        output.push_str("- None defined (create one with create_collection)\n");
+    }
+    for collection in collections {
+        let sessions: Vec<String> = collection
+            .sessions
+            .iter()
+            .map(|session| format!("`{session}`"))
+            .collect();
+        output.push_str(&format!(
+            "- **{}:** {} session(s): {}\n",
+            collection.name,
+            collection.sessions.len(),
+            sessions.join(", ")
+        ));
+    }
+    output.push('\n');
+    output
+}
+
+/// "Connection" section: what the calling client negotiated
+fn format_handshake(handshake: &Handshake) -> String {
+    let capabilities = serde_json::to_string(&handshake.capabilities)
+        .unwrap_or_else(|_| "(unavailable)".to_string());
+    format!(
+        "## Connection\n\
+         - **Client:** {}\n\
+         - **Negotiated Protocol:** MCP {}\n\
+         - **Capabilities:** `{capabilities}`\n\n",
+        handshake.client, handshake.protocol_version
+    )
+}
+
+/// "Tool Usage" section: calls, error rate and latency per tool
+fn format_usage(snapshot: &UsageSnapshot, enabled: bool) -> String {
+    let mut output = format!(
+        "## Tool Usage (since {})\n",
+        snapshot.since.format("%Y-%m-%d %H:%M UTC")
+    );
+    if !enabled {
+        output.push_str("- Not counting calls (`[mcp] usage_stats = false`)\n");
+    }
+    let called = snapshot.called();
+    if called.is_empty() {
+        output.push_str("- No tool calls recorded\n");
+    }
+    for (name, stats) in called {
+        let latency = |quantile| {
+            stats
+                .percentile(quantile)
+                .map(format_latency)
+                .unwrap_or_else(|| "-".to_string())
+        };
+        output.push_str(&format!(
+            "- **{name}:** {} calls, {} errors ({:.1}%), p50 {}, p95 {}\n",
+            stats.calls,
+            stats.errors,
+            stats.error_rate() * 100.0,
+            latency(0.5),
+            latency(0.95)
+        ));
+    }
+    output
+}
+
+/// "850ms", "1.5s"
+fn format_latency(latency: Duration) -> String {
+    let ms = latency.as_millis();
+    if ms < 1_000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", latency.as_secs_f64())
+    }
+}
+
 #[async_trait]
 impl McpToolHandler for GetServerInfoHandler {
     fn name(&self) -> &str {
@@ -66,19 +282,43 @@ impl McpToolHandler for GetServerInfoHandler {
         ToolSchema {
             name: "get_server_info".to_string(),
             description: "Get version and build information about the running shebe-mcp server. \
-                         Returns server version, protocol version and available tools. \
+                         Returns server version, supported protocol versions, the protocol \
+                         version and capabilities negotiated by this connection, available \
+                         tools, defined collections and per-tool usage (calls, error rate, \
+                         p50/p95 latency). \
                          Use this to check which version of shebe-mcp is running. \
                          Fast operation (<1ms)."
                 .to_string(),
             input_schema: json!({
                 "type": "object",
-                "properties": {},
+                "properties": {
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Clear the tool usage statistics \
+                                        (in memory and on disk) and start counting again",
+                        "default": false
+                    }
+                },
                 "required": []
             }),
         }
     }
 
-    async fn execute(&self, _args: Value) -> Result<ToolResult, McpError> {
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct ServerInfoArgs {
+            /// Clear the tool usage statistics
+            #[serde(default)]
+            reset: bool,
+        }
+
+        let args: ServerInfoArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+        if args.reset {
+            if let Some(usage) = &self.usage {
+                usage.reset();
+            }
+        }
         let text = self.format_info();
         Ok(text_content(text))
     }
@@ -144,8 +384,137 @@ mod tests {
         assert!(output.contains("index_repository"));
         assert!(output.contains("get_server_info"));
         assert!(output.contains("show_shebe_config"));
+        assert!(output.contains("reload_config"));
         assert!(output.contains("preview_chunk"));
         assert!(output.contains("find_references"));
+        assert!(output.contains("compact_session"));
+        assert!(output.contains("optimize_session"));
+        assert!(output.contains("list_tree"));
         assert!(output.contains("upgrade_session"));
+        assert!(output.contains("index_repository_async"));
+        assert!(output.contains("get_job_status"));
+        assert!(output.contains("cancel_job"));
+        assert!(output.contains("get_storage_report"));
+        assert!(output.contains("set_reindex_schedule"));
+        assert!(output.contains("diff_sessions"));
+        assert!(output.contains("locate"));
+        assert!(output.contains("find_similar"));
+        assert!(output.contains("search_batch"));
+        assert!(output.contains("sample_files"));
+        assert!(output.contains("create_collection"));
+        assert!(output.contains("update_collection"));
+        assert!(output.contains("delete_collection"));
+    }
+
+    #[tokio::test]
+    async fn test_usage_section_and_reset() {
+        let usage = Arc::new(ToolUsage::default());
+        let handler = GetServerInfoHandler::new().with_usage(Arc::clone(&usage));
+        let search = usage.track("search_code");
+        search.record(Duration::from_millis(12), false);
+        search.record(Duration::from_millis(40), true);
+        usage
+            .track("read_file")
+            .record(Duration::from_millis(2), false);
+        usage.track("list_sessions");
+
+        let output = handler.format_info();
+        assert!(output.contains("## Tool Usage (since "), "{output}");
+        assert!(
+            output.contains("- **search_code:** 2 calls, 1 errors (50.0%), p50 15ms, p95 40ms\n"),
+            "{output}"
+        );
+        assert!(output.contains("- **read_file:** 1 calls, 0 errors (0.0%), p50 2ms"));
+        assert!(
+            !output.contains("**list_sessions:**"),
+            "uncalled tools are omitted"
+        );
+        assert!(
+            output.find("**search_code:**").unwrap() < output.find("**read_file:**").unwrap(),
+            "most called first"
+        );
+
+        let result = handler.execute(json!({"reset": true})).await.unwrap();
+        let crate::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+        assert!(text.contains("- No tool calls recorded"), "{text}");
+        assert_eq!(usage.snapshot().tools["search_code"].calls, 0);
+
+        usage.set_enabled(false);
+        assert!(handler
+            .format_info()
+            .contains("Not counting calls (`[mcp] usage_stats = false`)"));
+        assert!(!GetServerInfoHandler::new()
+            .format_info()
+            .contains("Tool Usage"));
+    }
+
+    #[tokio::test]
+    async fn test_format_info_echoes_handshake() {
+        use crate::mcp::protocol::{ServerCapabilities, ToolsCapability};
+
+        let handler = GetServerInfoHandler::new();
+        assert!(!handler.format_info().contains("## Connection"));
+
+        let handshake = Handshake {
+            protocol_version: "2025-06-18".to_string(),
+            client: "claude-code 2.0.1".to_string(),
+            capabilities: ServerCapabilities {
+                tools: ToolsCapability {
+                    list_changed: false,
+                },
+                experimental: None,
+            },
+        };
+        let output =
+            crate::mcp::handshake::scope(Some(handshake), async { handler.format_info() }).await;
+        assert!(
+            output.contains("- **Client:** claude-code 2.0.1\n"),
+            "{output}"
+        );
+        assert!(output.contains("- **Negotiated Protocol:** MCP 2025-06-18\n"));
+        assert!(output.contains("- **Capabilities:** `{\"tools\":{\"listChanged\":false}}`"));
+    }
+
+    #[test]
+    fn test_format_info_lists_unavailable_sessions() {
+        use crate::core::error::ShebeError;
+        use crate::core::storage::BreakerSettings;
+
+        let breaker = Arc::new(OpenBreaker::new(BreakerSettings {
+            failure_threshold: 1,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }));
+        let handler = GetServerInfoHandler::new().with_open_breaker(Arc::clone(&breaker));
+        assert!(!handler.format_info().contains("Unavailable Sessions"));
+
+        let _ = breaker.call::<()>("nfs-repo", || {
+            Err(ShebeError::StorageError("timed out".to_string()))
+        });
+        let output = handler.format_info();
+        assert!(output.contains("## Unavailable Sessions\n"), "{output}");
+        assert!(output.contains(
+            "- **nfs-repo:** failed to open 1 time(s), retrying after 30s \
+             (last error: Storage error: timed out)"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_format_info_shows_mode() {
+        assert!(GetServerInfoHandler::new()
+            .format_info()
+            .contains("- **Mode:** read-write"));
+        assert!(GetServerInfoHandler::new()
+            .with_read_only(true)
+            .format_info()
+            .contains("- **Mode:** read-only"));
+
+        assert!(!GetServerInfoHandler::new()
+            .format_info()
+            .contains("**Profile:**"));
+        assert!(GetServerInfoHandler::new()
+            .with_profile("work", Path::new("/srv/work/sessions"))
+            .format_info()
+            .contains("- **Profile:** work (`/srv/work/sessions`)"));
     }
 }
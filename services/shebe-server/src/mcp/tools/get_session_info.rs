@@ -1,14 +1,25 @@
 //! Get session info tool handler
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::format_bytes;
+use super::helpers::{
+    format_bytes, format_count, format_provenance, format_schedule, format_scheduled_run,
+    format_time_ago, format_timestamp,
+};
+use crate::core::indexer::repo_config::{ConfigSource, ConfigSources, IGNORE_FILE};
+use crate::core::indexer::strip::MAX_LINE_CHARS;
+use crate::core::indexer::BoundaryHint;
+use crate::core::schedule::schedule_status;
 use crate::core::services::Services;
-use crate::core::storage::SessionMetadata;
+use crate::core::storage::{
+    current_schema_hash_for, HealthAction, SessionBreaker, SessionHealth, SessionMetadata,
+    CURRENT_VERSION, SCHEMA_VERSION, SESSION_ID_PATTERN,
+};
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::path::Path;
 use std::sync::Arc;
 
 pub struct GetSessionInfoHandler {
@@ -20,52 +31,152 @@ impl GetSessionInfoHandler {
         Self { services }
     }
 
-    fn format_info(&self, metadata: &SessionMetadata) -> String {
+    fn format_info(&self, metadata: &SessionMetadata, health: &SessionHealth) -> String {
         let mut output = format!("# Session: {}\n\n", metadata.id);
 
         output.push_str("## Overview\n");
-        output.push_str("- **Status:** Ready\n");
+        output.push_str(&format_status(
+            self.services.storage.open_breaker().session(&metadata.id),
+        ));
         output.push_str(&format!(
             "- **Repository Path:** {}\n",
             metadata.repository_path.display()
         ));
-        output.push_str(&format!("- **Files:** {}\n", metadata.files_indexed));
-        output.push_str(&format!("- **Chunks:** {}\n", metadata.chunks_created));
+        if let Some(remote) = &metadata.remote {
+            output.push_str(&format!(
+                "- **Remote:** {} ({}, commit `{}`)\n",
+                remote.url,
+                remote.ref_label(),
+                remote.short_commit()
+            ));
+        }
+        if let Some(archive) = &metadata.archive {
+            output.push_str(&format!(
+                "- **Archive:** {}, {} (content hash `{}`)\n",
+                archive.kind.as_str(),
+                format_bytes(archive.size_bytes),
+                archive.short_hash()
+            ));
+        }
+        output.push_str(&format!(
+            "- **Files:** {}\n",
+            format_count(metadata.files_indexed as u64)
+        ));
+        output.push_str(&format!(
+            "- **Chunks:** {}\n",
+            format_count(metadata.chunks_created as u64)
+        ));
         output.push_str(&format!(
             "- **Size:** {}\n",
             format_bytes(metadata.index_size_bytes)
         ));
+        output.push_str(&format_text_storage(metadata));
         output.push_str(&format!(
             "- **Created:** {}\n",
-            metadata.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+            format_timestamp(metadata.created_at)
         ));
         output.push_str(&format!(
             "- **Last Indexed:** {}\n\n",
-            metadata.last_indexed_at.format("%Y-%m-%d %H:%M:%S UTC")
+            format_timestamp(metadata.last_indexed_at)
         ));
 
+        let sources = metadata.config.sources.as_ref();
+        let source = |pick: fn(&ConfigSources) -> ConfigSource| {
+            sources.map_or(String::new(), |s| format!(" (from {})", pick(s)))
+        };
+
         output.push_str("## Configuration\n");
         output.push_str(&format!(
-            "- **Chunk size:** {} chars\n",
-            metadata.config.chunk_size
+            "- **Chunk size:** {} chars{}\n",
+            metadata.config.chunk_size,
+            source(|s| s.chunk_size)
         ));
         output.push_str(&format!(
-            "- **Overlap:** {} chars\n",
-            metadata.config.overlap
+            "- **Overlap:** {} chars{}\n",
+            metadata.config.overlap,
+            source(|s| s.overlap)
         ));
         output.push_str(&format!(
-            "- **Include patterns:** {}\n",
-            metadata.config.include_patterns.join(", ")
+            "- **Include patterns:** {}{}\n",
+            metadata.config.include_patterns.join(", "),
+            source(|s| s.include_patterns)
         ));
+        if !metadata.config.languages.is_empty() {
+            output.push_str(&format!(
+                "- **Language presets:** {}\n",
+                metadata.config.languages.join(", ")
+            ));
+        }
         output.push_str(&format!(
-            "- **Exclude patterns:** {}\n\n",
-            metadata.config.exclude_patterns.join(", ")
+            "- **Exclude patterns:** {}{}\n",
+            metadata.config.exclude_patterns.join(", "),
+            source(|s| s.exclude_patterns)
         ));
+        if let Some(sources) = sources.filter(|s| !s.ignore_patterns.is_empty()) {
+            output.push_str(&format!(
+                "- **{IGNORE_FILE} patterns:** {}\n",
+                sources.ignore_patterns.join(", ")
+            ));
+        }
+        output.push_str(&format!(
+            "- **Dedupe:** {}\n",
+            if metadata.config.dedupe {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ));
+        if metadata.config.boundary_hint != BoundaryHint::None {
+            output.push_str(&format!(
+                "- **Chunk boundaries:** {}\n",
+                metadata.config.boundary_hint
+            ));
+        }
+        if metadata.config.strip_high_entropy {
+            output.push_str(&format!(
+                "- **Blob stripping:** runs over {} chars, lines over {MAX_LINE_CHARS} chars\n",
+                metadata.config.strip_run_chars
+            ));
+        }
+        if metadata.config.merge_tail_fragments {
+            output.push_str("- **Tail fragments:** merged into the previous chunk\n");
+        }
+        if !metadata.config.record_chunking {
+            output.push_str("- **Data files:** chunked by characters, not by record\n");
+        }
+        output.push_str(&format!(
+            "- **Max chunks per file:** {}\n",
+            metadata.config.max_chunks_per_file
+        ));
+        output.push_str(&format!(
+            "- **BM25:** k1={}, b={}\n",
+            metadata.config.bm25_k1, metadata.config.bm25_b
+        ));
+        if let Some(defaults) = metadata.search_defaults.filter(|d| !d.is_empty()) {
+            output.push_str(&format!("- **Search defaults:** {}\n", defaults.summary()));
+        }
+        output.push('\n');
 
         output.push_str("## Statistics\n");
         let avg_chunks = metadata.chunks_created as f64 / metadata.files_indexed.max(1) as f64;
         output.push_str(&format!("- **Avg chunks/file:** {avg_chunks:.2}\n"));
 
+        if metadata.config.dedupe {
+            output.push_str(&format!(
+                "- **Duplicates skipped:** {} ({:.1}% dedupe ratio)\n",
+                format_count(metadata.files_deduplicated as u64),
+                metadata.dedupe_ratio() * 100.0
+            ));
+        }
+
+        if metadata.skipped.total() > 0 {
+            output.push_str(&format!(
+                "- **Files skipped:** {} ({})\n",
+                format_count(metadata.skipped.total() as u64),
+                metadata.skipped.summary()
+            ));
+        }
+
         if metadata.chunks_created > 0 {
             let avg_chunk_size =
                 (metadata.index_size_bytes as f64 / metadata.chunks_created.max(1) as f64) as u64;
@@ -75,10 +186,236 @@ impl GetSessionInfoHandler {
             ));
         }
 
+        if let Some(status) = schedule_status(metadata, &self.services.config.current().schedule) {
+            output.push_str("\n## Schedule\n");
+            output.push_str(&format!(
+                "- **Reindex schedule:** {}\n",
+                format_schedule(&status)
+            ));
+            if let Some(run) = &status.last_run {
+                output.push_str(&format!(
+                    "- **Last scheduled run:** {}\n",
+                    format_scheduled_run(run)
+                ));
+            }
+        }
+
+        output.push_str("\n## Index\n");
+        output.push_str(&format!(
+            "- **Created by:** shebe {} (running {})\n",
+            metadata.created_by_version, CURRENT_VERSION
+        ));
+        let hash_status = match metadata.schema_hash_matches() {
+            Some(true) => "matches current".to_string(),
+            Some(false) => format!(
+                "current is `{}`",
+                current_schema_hash_for(metadata.config.store_chunk_text)
+            ),
+            None => "not recorded".to_string(),
+        };
+        output.push_str(&format!(
+            "- **Schema:** v{} (current v{}), hash `{}` ({})\n",
+            metadata.schema_version, SCHEMA_VERSION, metadata.tantivy_schema_hash, hash_status
+        ));
+        match &metadata.index_settings {
+            Some(settings) => {
+                output.push_str("- **Fields:**\n");
+                for field in &settings.fields {
+                    output.push_str(&format!("  - {field}\n"));
+                }
+            }
+            None => output.push_str("- **Fields:** unknown\n"),
+        }
+        if metadata.major_version_differs() {
+            output.push_str(
+                "\nThis session was built by a different major version. \
+                 Run upgrade_session if search results look wrong.\n",
+            );
+        }
+
+        if let Some(provenance) = &metadata.provenance {
+            output.push_str("\n## Provenance\n");
+            output.push_str(&format_provenance(provenance));
+        }
+
+        output.push_str(&format_health(health));
+        output.push_str("\n## Recommended actions\n");
+        if health.is_healthy() {
+            output.push_str("- None: the index matches its metadata and the repository\n");
+        }
+        for action in &health.actions {
+            output.push_str(&format!("- {} — {}\n", action.summary(), remedy(action)));
+        }
+
         output
     }
 }
 
+/// Format the health section: index documents, repository, files on
+/// disk and age
+fn format_health(health: &SessionHealth) -> String {
+    let mut output = String::from("\n## Health\n");
+    let docs = match health.index_docs {
+        Some(docs) if docs == health.chunks_created as u64 => {
+            format!("{} (matches recorded chunks)", format_count(docs))
+        }
+        Some(docs) => format!(
+            "{} ({} chunks recorded)",
+            format_count(docs),
+            format_count(health.chunks_created as u64)
+        ),
+        None => "unknown (index could not be opened)".to_string(),
+    };
+    output.push_str(&format!("- **Index documents:** {docs}\n"));
+    output.push_str(&format!(
+        "- **Repository:** {}\n",
+        if health.repository_exists {
+            "present"
+        } else {
+            "missing"
+        }
+    ));
+    let files = match &health.files {
+        Some(files) => {
+            let scope = if files.sampled {
+                format!(
+                    "sampled {} of {} indexed files",
+                    files.checked, files.indexed
+                )
+            } else {
+                format!("all {} indexed files checked", files.checked)
+            };
+            format!(
+                "{} missing, {} modified since indexing ({scope})",
+                files.missing, files.modified
+            )
+        }
+        None if !health.repository_exists => "not checked (repository missing)".to_string(),
+        None => "not checked (indexed files unknown)".to_string(),
+    };
+    output.push_str(&format!("- **Files on disk:** {files}\n"));
+    output.push_str(&format!(
+        "- **Freshness:** indexed {}, {} (stale after {} days)\n",
+        format_time_ago(health.last_indexed_at),
+        if health.stale { "stale" } else { "fresh" },
+        health.stale_after_days
+    ));
+    output
+}
+
+/// The tool call that addresses a health problem
+fn remedy(action: &HealthAction) -> &'static str {
+    match action {
+        HealthAction::RepositoryMissing { .. } => {
+            "delete the session with delete_session, or index the repository's new location \
+             with index_repository"
+        }
+        HealthAction::SchemaOutdated { .. } => "run upgrade_session",
+        HealthAction::DocCountMismatch { .. } | HealthAction::FilesChanged { .. } => {
+            "run reindex_session with force=true"
+        }
+        HealthAction::Stale { .. } => {
+            "run reindex_session with force=true, or keep it fresh with set_reindex_schedule"
+        }
+    }
+}
+
+/// Format the chunk text storage mode with its estimated size
+///
+/// The estimate is the raw chunk text; the index compresses stored
+/// text, so the actual share is smaller.
+fn format_text_storage(metadata: &SessionMetadata) -> String {
+    let bytes = metadata.chunk_text_bytes;
+    match (metadata.config.store_chunk_text, bytes) {
+        (true, 0) => "- **Chunk text:** stored in the index\n".to_string(),
+        (true, _) => format!(
+            "- **Chunk text:** stored in the index (up to ~{}; re-index with \
+             store_chunk_text=false to save it)\n",
+            format_bytes(bytes)
+        ),
+        (false, _) => format!(
+            "- **Chunk text:** not stored, read from the files at query time \
+             (~{} saved)\n",
+            format_bytes(bytes)
+        ),
+    }
+}
+
+impl GetSessionInfoHandler {
+    /// Explain whether a file would be indexed, and whether it was
+    fn format_why_not_indexed(
+        &self,
+        metadata: &SessionMetadata,
+        path: &str,
+    ) -> Result<String, McpError> {
+        let explanation = self
+            .services
+            .why_not_indexed(&metadata.id, Path::new(path))?;
+        let display = explanation
+            .path
+            .strip_prefix(&metadata.repository_path)
+            .unwrap_or(&explanation.path)
+            .display()
+            .to_string();
+
+        let mut output = format!("# Session: {}\n\n", metadata.id);
+        output.push_str(&format!("## Why not indexed: `{display}`\n"));
+        match explanation.reason {
+            Some(reason) => {
+                output.push_str(&format!("- **Outcome:** skipped ({})\n", reason.label()))
+            }
+            None => output.push_str("- **Outcome:** would be indexed\n"),
+        }
+        output.push_str(&format!("- **Rule:** {}\n", explanation.detail));
+
+        // The manifest lists every file read at the last indexing
+        if let Some(manifest) = self.services.storage.get_content_manifest(&metadata.id)? {
+            let key = explanation.path.to_string_lossy();
+            let original = manifest
+                .duplicates
+                .iter()
+                .find(|(_, copies)| copies.iter().any(|copy| *copy == key))
+                .map(|(original, _)| original);
+            let last_run = match original {
+                Some(original) => format!("skipped as a duplicate of `{original}`"),
+                None if manifest.is_truncated(&key) => format!(
+                    "indexed partially (cut off at max_chunks_per_file = {})",
+                    metadata.config.max_chunks_per_file
+                ),
+                None if manifest.files.contains_key(key.as_ref()) => "indexed".to_string(),
+                None => "not indexed".to_string(),
+            };
+            output.push_str(&format!(
+                "- **Last indexing ({}):** {last_run}\n",
+                metadata.last_indexed_at.format("%Y-%m-%d %H:%M:%S UTC")
+            ));
+        }
+
+        output.push_str(&format!(
+            "\nChecked against the session's patterns, {IGNORE_FILE} and the current \
+             {} MB file size limit. Re-index to pick up changes.\n",
+            self.services.config.current().indexing.max_file_size_mb
+        ));
+        Ok(output)
+    }
+}
+
+/// "Status" line, naming recent open failures (see `OpenBreaker`)
+fn format_status(breaker: Option<SessionBreaker>) -> String {
+    match breaker {
+        None => "- **Status:** Ready\n".to_string(),
+        Some(b) if b.tripped => format!(
+            "- **Status:** Unavailable: failed to open {} time(s) recently, failing fast \
+             for {}s more (last error: {}). Pass reset: true to retry now.\n",
+            b.failures, b.retry_after_secs, b.last_error
+        ),
+        Some(b) => format!(
+            "- **Status:** Ready ({} recent open failure(s); last error: {})\n",
+            b.failures, b.last_error
+        ),
+    }
+}
+
 #[async_trait]
 impl McpToolHandler for GetSessionInfoHandler {
     fn name(&self) -> &str {
@@ -90,14 +427,24 @@ impl McpToolHandler for GetSessionInfoHandler {
             name: "get_session_info".to_string(),
             description: "Get detailed metadata and statistics for a specific indexed session. \
                          Shows: status, file count, chunk count, index size, creation date, \
-                         chunk configuration (size/overlap), computed statistics (avg chunks/file, avg chunk size). \
+                         chunk configuration (size/overlap), computed statistics (avg chunks/file, avg chunk size), \
+                         reindex schedule (next and last scheduled run, if any), \
+                         index diagnostics (creating shebe version, schema hash, field tokenizers) \
+                         and index health: index document count vs recorded chunks, whether the \
+                         repository still exists, indexed files missing or modified on disk \
+                         (sampled for large sessions), staleness, and recommended actions. \
                          \
                          USE THIS TO: \
                          (1) Verify indexing results after index_repository completes, \
                          (2) Understand session scope and size before large search operations, \
-                         (3) Debug search issues (check if session has expected file count). \
+                         (3) Debug search issues (check if session has expected file count), \
+                         (4) Find out why a file is missing: pass why_not_indexed with its path to \
+                         re-check it against the session's patterns, .shebeignore, size limit and \
+                         binary/UTF-8 checks, \
+                         (5) Retry a session reported as temporarily unavailable after repeated \
+                         open failures: pass reset: true to clear its failures. \
                          \
-                         PERFORMANCE: <5ms (very fast, single metadata file read). \
+                         PERFORMANCE: <50ms (metadata read plus up to 500 file stats). \
                          \
                          OPTIONAL: Not required for search_code, but helpful for context."
                 .to_string(),
@@ -107,7 +454,18 @@ impl McpToolHandler for GetSessionInfoHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID to inspect",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "why_not_indexed": {
+                        "type": "string",
+                        "description": "File path (absolute or relative to the repository) to \
+                                       explain instead of showing session info"
+                    },
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Clear the session's recent open failures so the next \
+                                       call opens it again instead of failing fast",
+                        "default": false
                     }
                 },
                 "required": ["session"]
@@ -119,11 +477,19 @@ impl McpToolHandler for GetSessionInfoHandler {
         #[derive(Deserialize)]
         struct InfoArgs {
             session: String,
+            #[serde(default)]
+            why_not_indexed: Option<String>,
+            #[serde(default)]
+            reset: bool,
         }
 
         let args: InfoArgs =
             serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
 
+        // Before reading anything, so a reset works even while the
+        // storage is still failing
+        let was_reset = args.reset && self.services.storage.open_breaker().reset(&args.session);
+
         // Get metadata from storage
         let metadata = self
             .services
@@ -132,7 +498,24 @@ impl McpToolHandler for GetSessionInfoHandler {
             .map_err(McpError::from)?;
 
         // Format output
-        let text = self.format_info(&metadata);
+        let text = match &args.why_not_indexed {
+            Some(path) => self.format_why_not_indexed(&metadata, path)?,
+            None => {
+                let stale_after_days = self.services.config.current().health.stale_after_days;
+                let health = self
+                    .services
+                    .storage
+                    .session_health(&args.session, stale_after_days)?;
+                self.format_info(&metadata, &health)
+            }
+        };
+        let text = if was_reset {
+            format!(
+                "Recent open failures cleared; the next call opens the session again.\n\n{text}"
+            )
+        } else {
+            text
+        };
 
         Ok(text_content(text))
     }
@@ -158,6 +541,25 @@ mod tests {
         (handler, temp_dir)
     }
 
+    /// Health of a session with no problems
+    fn healthy(metadata: &SessionMetadata) -> SessionHealth {
+        SessionHealth {
+            session_id: metadata.id.clone(),
+            repository_path: metadata.repository_path.clone(),
+            repository_exists: true,
+            index_docs: Some(metadata.chunks_created as u64),
+            chunks_created: metadata.chunks_created,
+            files: None,
+            last_indexed_at: metadata.last_indexed_at,
+            age_seconds: 0,
+            stale_after_days: 7,
+            stale: false,
+            schema_version: metadata.schema_version,
+            current_schema_version: SCHEMA_VERSION,
+            actions: Vec::new(),
+        }
+    }
+
     #[tokio::test]
     async fn test_get_session_info_handler_name() {
         let (handler, _temp) = setup_test_handler().await;
@@ -232,10 +634,23 @@ mod tests {
             chunks_created: 500,
             index_size_bytes: 52428800, // 50 MB
             config: SessionConfig::default(),
-            schema_version: 3,
+            schema_version: 4,
+            files_deduplicated: 0,
+            created_by_version: "unknown".to_string(),
+            tantivy_schema_hash: "unknown".to_string(),
+            index_settings: None,
+            remote: None,
+            archive: None,
+            skipped: Default::default(),
+            chunk_text_bytes: 0,
+            reindex_schedule: None,
+            last_scheduled_run: None,
+            search_defaults: None,
+            modified_times: None,
+            provenance: None,
         };
 
-        let output = handler.format_info(&metadata);
+        let output = handler.format_info(&metadata, &healthy(&metadata));
 
         assert!(output.contains("# Session: test-session"));
         assert!(output.contains("## Overview"));
@@ -243,9 +658,9 @@ mod tests {
         assert!(output.contains("**Repository Path:**"));
         assert!(output.contains("**Files:** 100"));
         assert!(output.contains("**Chunks:** 500"));
-        assert!(output.contains("**Size:** 50.00 MB"));
-        assert!(output.contains("**Created:** 2025-10-21"));
-        assert!(output.contains("**Last Indexed:** 2025-10-21"));
+        assert!(output.contains("**Size:** 50.0 MB"));
+        assert!(output.contains("**Created:** 2025-10-21 10:00 UTC ("));
+        assert!(output.contains("**Last Indexed:** 2025-10-21 10:00 UTC ("));
         assert!(output.contains("## Configuration"));
         assert!(output.contains("**Chunk size:** 512 chars"));
         assert!(output.contains("**Overlap:** 64 chars"));
@@ -253,6 +668,134 @@ mod tests {
         assert!(output.contains("**Exclude patterns:**"));
         assert!(output.contains("## Statistics"));
         assert!(output.contains("**Avg chunks/file:** 5.00"));
+        assert!(output.contains("**Dedupe:** disabled"));
+        assert!(!output.contains("Duplicates skipped"));
+        assert!(output.contains("**Created by:** shebe unknown"));
+        assert!(output.contains("hash `unknown` (not recorded)"));
+        assert!(output.contains("**Fields:** unknown"));
+        assert!(!output.contains("**Remote:**"));
+    }
+
+    #[tokio::test]
+    async fn test_format_info_dedupe_ratio() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        use chrono::Utc;
+        let metadata = SessionMetadata {
+            id: "dedupe-session".to_string(),
+            repository_path: PathBuf::from("/test/repo"),
+            created_at: Utc::now(),
+            last_indexed_at: Utc::now(),
+            files_indexed: 75,
+            chunks_created: 300,
+            index_size_bytes: 1024,
+            config: SessionConfig {
+                dedupe: true,
+                ..SessionConfig::default()
+            },
+            schema_version: 4,
+            files_deduplicated: 25,
+            created_by_version: "unknown".to_string(),
+            tantivy_schema_hash: "unknown".to_string(),
+            index_settings: None,
+            remote: None,
+            archive: None,
+            skipped: Default::default(),
+            chunk_text_bytes: 0,
+            reindex_schedule: None,
+            last_scheduled_run: None,
+            search_defaults: None,
+            modified_times: None,
+            provenance: None,
+        };
+
+        let output = handler.format_info(&metadata, &healthy(&metadata));
+
+        assert!(output.contains("**Dedupe:** enabled"));
+        assert!(output.contains("**Duplicates skipped:** 25 (25.0% dedupe ratio)"));
+    }
+
+    #[tokio::test]
+    async fn test_format_info_remote() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        use crate::core::remote::RemoteSource;
+        use chrono::Utc;
+        let metadata = SessionMetadata {
+            id: "remote-session".to_string(),
+            repository_path: PathBuf::from("/cache/clones/remote-session"),
+            created_at: Utc::now(),
+            last_indexed_at: Utc::now(),
+            files_indexed: 10,
+            chunks_created: 20,
+            index_size_bytes: 1024,
+            config: SessionConfig::default(),
+            schema_version: 4,
+            files_deduplicated: 0,
+            created_by_version: "unknown".to_string(),
+            tantivy_schema_hash: "unknown".to_string(),
+            index_settings: None,
+            remote: Some(RemoteSource {
+                url: "https://github.com/org/repo.git".to_string(),
+                git_ref: Some("v1.2.0".to_string()),
+                commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            }),
+            archive: None,
+            skipped: Default::default(),
+            chunk_text_bytes: 0,
+            reindex_schedule: None,
+            last_scheduled_run: None,
+            search_defaults: None,
+            modified_times: None,
+            provenance: None,
+        };
+
+        let output = handler.format_info(&metadata, &healthy(&metadata));
+
+        assert!(output.contains(
+            "- **Remote:** https://github.com/org/repo.git (v1.2.0, commit `0123456789ab`)"
+        ));
+    }
+
+    #[test]
+    fn test_format_health_sections() {
+        use crate::core::storage::FileFreshness;
+        use chrono::Utc;
+        let health = SessionHealth {
+            session_id: "drifted".to_string(),
+            repository_path: PathBuf::from("/test/repo"),
+            repository_exists: true,
+            index_docs: Some(480),
+            chunks_created: 500,
+            files: Some(FileFreshness {
+                indexed: 2000,
+                checked: 500,
+                sampled: true,
+                missing: 3,
+                modified: 34,
+            }),
+            last_indexed_at: Utc::now() - chrono::Duration::days(10),
+            age_seconds: 10 * 86_400,
+            stale_after_days: 7,
+            stale: true,
+            schema_version: SCHEMA_VERSION,
+            current_schema_version: SCHEMA_VERSION,
+            actions: vec![HealthAction::Stale { age_days: 10 }],
+        };
+
+        let output = format_health(&health);
+        assert!(output.contains("- **Index documents:** 480 (500 chunks recorded)"));
+        assert!(output.contains("- **Repository:** present"));
+        assert!(output
+            .contains("3 missing, 34 modified since indexing (sampled 500 of 2000 indexed files)"));
+        assert!(output.contains("- **Freshness:** indexed 10 days ago, stale (stale after 7 days)"));
+        assert_eq!(
+            remedy(&HealthAction::RepositoryMissing {
+                path: PathBuf::from("/test/repo")
+            }),
+            "delete the session with delete_session, or index the repository's new location \
+             with index_repository"
+        );
     }
 
     #[tokio::test]
@@ -273,6 +816,8 @@ mod tests {
             start_offset: 0,
             end_offset: 12,
             chunk_index: 0,
+            records: None,
+            section: None,
         }];
         index.add_chunks(&chunks, "test-session").unwrap();
         index.commit().unwrap();
@@ -289,7 +834,68 @@ mod tests {
                 assert!(text.contains("## Overview"));
                 assert!(text.contains("## Configuration"));
                 assert!(text.contains("## Statistics"));
+                assert!(text.contains(&format!("**Created by:** shebe {CURRENT_VERSION}")));
+                assert!(text.contains("(matches current)"));
+                assert!(text.contains("  - text (Str, indexed, stored, tokenizer=default)"));
             }
         }
     }
+
+    fn text_of(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_session_info_why_not_indexed() {
+        let (handler, temp) = setup_test_handler().await;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.join("debug.log"), "started").unwrap();
+        let repo = repo.canonicalize().unwrap();
+        handler
+            .services
+            .storage
+            .index_repository(
+                "why",
+                &repo,
+                vec![],
+                vec!["*.log".to_string()],
+                512,
+                64,
+                10,
+                false,
+            )
+            .unwrap();
+
+        let info = handler.execute(json!({"session": "why"})).await.unwrap();
+        assert!(text_of(&info).contains("- **Files skipped:** 1 (1 excluded by pattern)"));
+
+        let result = handler
+            .execute(json!({"session": "why", "why_not_indexed": "debug.log"}))
+            .await
+            .unwrap();
+        let text = text_of(&result);
+        assert!(text.contains("## Why not indexed: `debug.log`"), "{text}");
+        assert!(text.contains("- **Outcome:** skipped (excluded by pattern)"));
+        assert!(text.contains("- **Rule:** matches exclude pattern `*.log`"));
+        assert!(text.contains("UTC):** not indexed"), "{text}");
+
+        let main = repo.join("src/main.rs");
+        let result = handler
+            .execute(json!({"session": "why", "why_not_indexed": main.to_string_lossy()}))
+            .await
+            .unwrap();
+        let text = text_of(&result);
+        assert!(text.contains("## Why not indexed: `src/main.rs`"), "{text}");
+        assert!(text.contains("- **Outcome:** would be indexed"));
+        assert!(text.contains("UTC):** indexed"), "{text}");
+
+        let result = handler
+            .execute(json!({"session": "why", "why_not_indexed": "missing.rs"}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
 }
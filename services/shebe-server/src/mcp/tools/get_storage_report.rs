@@ -0,0 +1,209 @@
+//! Storage report (disk usage across sessions) tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_bytes;
+use crate::core::services::Services;
+use crate::core::storage::{StorageReport, SIZE_DRIFT_THRESHOLD_PERCENT};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct GetStorageReportHandler {
+    services: Arc<Services>,
+}
+
+impl GetStorageReportHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the report as a Markdown table, largest session first
+    fn format_report(&self, report: &StorageReport) -> String {
+        let mut output = format!(
+            "**Storage root:** `{}`\n\
+             **Sessions:** {} using {} (metadata records {})\n",
+            report.storage_root.display(),
+            report.sessions.len(),
+            format_bytes(report.total_session_bytes),
+            format_bytes(report.total_metadata_bytes)
+        );
+        if let Some(available) = report.available_bytes {
+            output.push_str(&format!("**Available:** {}\n", format_bytes(available)));
+        }
+        output.push('\n');
+
+        if report.sessions.is_empty() {
+            output.push_str("No sessions found.");
+            return output;
+        }
+
+        output.push_str("| Session | On disk | Recorded | Drift |\n");
+        output.push_str("|---------|---------|----------|-------|\n");
+        for session in &report.sessions {
+            let recorded = session
+                .metadata_bytes
+                .map(format_bytes)
+                .unwrap_or_else(|| "unreadable".to_string());
+            let drift = match session.drift_percent {
+                Some(percent) if session.drifted => format!("{percent:.0}% (stale)"),
+                Some(percent) => format!("{percent:.0}%"),
+                None => "-".to_string(),
+            };
+            output.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                session.session_id,
+                format_bytes(session.actual_bytes),
+                recorded,
+                drift
+            ));
+        }
+
+        let drifted = report.drifted_count();
+        if drifted > 0 {
+            output.push_str(&format!(
+                "\n{drifted} session(s) record a size more than {SIZE_DRIFT_THRESHOLD_PERCENT:.0}% \
+                 off from disk. Run compact_session or reindex_session to refresh it.\n"
+            ));
+        }
+
+        output
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for GetStorageReportHandler {
+    fn name(&self) -> &str {
+        "get_storage_report"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "get_storage_report".to_string(),
+            description: "Show disk usage of all sessions, largest first, with the total and \
+                         the space left on the storage filesystem. Each session's size on disk \
+                         is compared with the size recorded in its metadata; sessions more \
+                         than 10% off are marked stale. Use it to decide which sessions to \
+                         delete or compact. Use format='json' for structured output."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "description": "Output format: 'markdown' (default) or 'json'",
+                        "default": "markdown",
+                        "enum": ["markdown", "json"]
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct GetStorageReportArgs {
+            #[serde(default = "default_format")]
+            format: String,
+        }
+        fn default_format() -> String {
+            "markdown".to_string()
+        }
+
+        let args: GetStorageReportArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let json_output = match args.format.as_str() {
+            "markdown" => false,
+            "json" => true,
+            other => {
+                return Err(McpError::InvalidParams(format!(
+                    "Invalid format: '{other}'. Must be 'markdown' or 'json'."
+                )))
+            }
+        };
+
+        let report = self
+            .services
+            .storage
+            .storage_report()
+            .map_err(McpError::from)?;
+
+        let text = if json_output {
+            serde_json::to_string_pretty(&report)
+                .map_err(|e| McpError::InternalError(format!("Failed to serialize report: {e}")))?
+        } else {
+            self.format_report(&report)
+        };
+
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::mcp::protocol::ContentBlock;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (GetStorageReportHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        (GetStorageReportHandler::new(services), temp_dir)
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_markdown() {
+        let (handler, _temp) = setup_test_handler();
+        handler
+            .services
+            .storage
+            .create_session(
+                "alpha",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let result = handler.execute(json!({})).await.unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("**Sessions:** 1 using"));
+        assert!(text.contains("| `alpha` |"));
+        assert!(!text.contains("stale"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_json() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"format": "json"})).await.unwrap();
+        let report: Value = serde_json::from_str(extract_text(&result)).unwrap();
+
+        assert_eq!(report["sessions"].as_array().unwrap().len(), 0);
+        assert_eq!(report["total_session_bytes"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_invalid_format() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"format": "xml"})).await;
+
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+}
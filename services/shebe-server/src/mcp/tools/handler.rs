@@ -4,6 +4,7 @@ use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ContentBlock, ToolResult, ToolSchema};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::time::Duration;
 
 /// Trait for MCP tool implementations
 ///
@@ -17,10 +18,35 @@ pub trait McpToolHandler: Send + Sync {
     /// Tool schema for tools/list
     fn schema(&self) -> ToolSchema;
 
+    /// Whether the tool creates, changes or deletes sessions
+    ///
+    /// Mutating tools are hidden and refused in read-only mode.
+    fn mutates(&self) -> bool {
+        false
+    }
+
+    /// Time limit for a call with `args`, `None` for no limit
+    ///
+    /// Read-only tools get `default` (`limits.tool_timeout_sec`); tools
+    /// that change sessions are never cut off halfway. Tools taking a
+    /// `timeout_sec` argument return [`requested_timeout`].
+    fn timeout(&self, _args: &Value, default: Duration) -> Option<Duration> {
+        (!self.mutates()).then_some(default)
+    }
+
     /// Execute tool with arguments
     async fn execute(&self, args: Value) -> Result<ToolResult, McpError>;
 }
 
+/// Time limit set by a call's `timeout_sec` argument, else `default`
+pub fn requested_timeout(args: &Value, default: Duration) -> Option<Duration> {
+    let requested = args
+        .get("timeout_sec")
+        .and_then(Value::as_u64)
+        .filter(|secs| *secs > 0);
+    Some(requested.map_or(default, Duration::from_secs))
+}
+
 /// Helper function to create a text content block
 pub fn text_content(text: String) -> ToolResult {
     ToolResult {
@@ -31,6 +57,21 @@ pub fn text_content(text: String) -> ToolResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_requested_timeout() {
+        let default = Duration::from_secs(60);
+        let timeout = |args: Value| requested_timeout(&args, default);
+
+        assert_eq!(timeout(json!({})), Some(default));
+        assert_eq!(timeout(json!({"timeout_sec": 0})), Some(default));
+        assert_eq!(timeout(json!({"timeout_sec": "5"})), Some(default));
+        assert_eq!(
+            timeout(json!({"timeout_sec": 300})),
+            Some(Duration::from_secs(300))
+        );
+    }
 
     #[test]
     fn test_text_content() {
@@ -1,64 +1,39 @@
 //! Helper functions for MCP tools
 
-use chrono::{DateTime, Utc};
-
-/// Format a timestamp as human-readable relative time.
-///
-/// # Examples
+use crate::core::archive;
+use crate::core::display::{LinkTemplate, PathDisplay};
+use crate::core::indexer::sections::SECTION_SEPARATOR;
+use crate::core::jobs::JobState;
+use crate::core::logging::spawn_blocking_in_context;
+use crate::core::paths::{self, ResolvedPath};
+use crate::core::schedule::{ScheduleStatus, ScheduledRun};
+use crate::core::services::Services;
+use crate::core::storage::{Collection, Provenance};
+use crate::core::types::{FileRename, IndexProfile, SearchResult, TruncatedFile};
+use crate::mcp::error::McpError;
+use std::time::Duration;
+
+pub use crate::core::format::{
+    format_bytes, format_count, format_duration, format_time_ago, format_timestamp, format_timing,
+};
+
+/// Run synchronous core work (searches) on tokio's blocking pool.
 ///
-/// - "1 day ago"
-/// - "3 hours ago"
-/// - "45 minutes ago"
-/// - "just now"
-pub fn format_time_ago(timestamp: DateTime<Utc>) -> String {
-    let now = Utc::now();
-    let duration = now.signed_duration_since(timestamp);
-
-    let days = duration.num_days();
-    if days > 0 {
-        return if days == 1 {
-            "1 day ago".to_string()
-        } else {
-            format!("{days} days ago")
-        };
-    }
-
-    let hours = duration.num_hours();
-    if hours > 0 {
-        return if hours == 1 {
-            "1 hour ago".to_string()
-        } else {
-            format!("{hours} hours ago")
-        };
-    }
-
-    let minutes = duration.num_minutes();
-    if minutes > 0 {
-        return if minutes == 1 {
-            "1 minute ago".to_string()
-        } else {
-            format!("{minutes} minutes ago")
-        };
-    }
-
-    "just now".to_string()
-}
-
-/// Format bytes as human-readable size
-pub fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{bytes} B")
-    }
+/// Searches are CPU-bound; running them inline would stall the async
+/// worker thread and every request queued behind it, and a call's time
+/// limit could not fire. The work runs in the request's logging
+/// context (span, phase timings and cancel token) and may fail with a
+/// `ShebeError` or an `McpError`.
+pub async fn run_blocking<T, E, F>(work: F) -> Result<T, McpError>
+where
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+    E: Into<McpError> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking_in_context(work)
+        .await
+        .map_err(|e| McpError::InternalError(format!("Search task failed: {e}")))?
+        .map_err(Into::into)
 }
 
 /// Detect programming language from file extension
@@ -103,6 +78,17 @@ pub fn detect_language(file_path: &str) -> &str {
     }
 }
 
+/// Format the heading breadcrumb of a documentation chunk, e.g.
+/// "**Section:** docs/architecture.md › Storage › Session layout\n"
+///
+/// Returns an empty string for chunks without a section.
+pub fn format_section(shown_path: &str, section: Option<&str>) -> String {
+    match section {
+        Some(section) => format!("**Section:** {shown_path}{SECTION_SEPARATOR}{section}\n"),
+        None => String::new(),
+    }
+}
+
 /// Truncate text if it exceeds max length
 pub fn truncate_text(text: &str, max_chars: usize) -> String {
     if text.len() <= max_chars {
@@ -141,6 +127,58 @@ pub fn byte_offset_to_line_number(file_content: &str, byte_offset: usize) -> usi
         + 1
 }
 
+/// Where a symbol occurrence sits within its line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolLocation {
+    /// 1-based line number
+    pub line_number: usize,
+    /// 1-based column of the occurrence, counted in characters
+    pub column: usize,
+    /// 1-based columns of every occurrence of the symbol on the line
+    pub columns: Vec<usize>,
+}
+
+/// Locate the symbol occurrence starting at `byte_offset`.
+///
+/// Columns are counted in characters from the start of the line, so
+/// multi-byte characters before the symbol count once. Returns `None`
+/// if the symbol does not start at `byte_offset`.
+///
+/// # Arguments
+///
+/// * `file_content` - The full file content as a string
+/// * `byte_offset` - Byte offset of the occurrence in the file
+/// * `symbol` - The symbol text
+pub fn locate_symbol(
+    file_content: &str,
+    byte_offset: usize,
+    symbol: &str,
+) -> Option<SymbolLocation> {
+    if symbol.is_empty()
+        || !file_content.is_char_boundary(byte_offset)
+        || !file_content[byte_offset..].starts_with(symbol)
+    {
+        return None;
+    }
+
+    let line_start = file_content[..byte_offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = file_content[byte_offset..]
+        .find('\n')
+        .map_or(file_content.len(), |i| byte_offset + i);
+    let line = &file_content[line_start..line_end];
+
+    let char_column = |byte: usize| line[..byte].chars().count() + 1;
+
+    Some(SymbolLocation {
+        line_number: byte_offset_to_line_number(file_content, byte_offset),
+        column: char_column(byte_offset - line_start),
+        columns: line
+            .match_indices(symbol)
+            .map(|(i, _)| char_column(i))
+            .collect(),
+    })
+}
+
 /// Extract context lines around a given line number.
 ///
 /// Returns formatted lines with line numbers, suitable for displaying
@@ -198,9 +236,301 @@ fn truncate_line(line: &str, max_len: usize) -> String {
     }
 }
 
+/// Resolve how result paths are shown for a session.
+///
+/// Per-request `link_format` and `relative_paths` override the server's
+/// `[output]` settings; `link_format: "none"` disables links. An invalid
+/// template is reported as invalid params.
+pub fn path_display(
+    services: &Services,
+    session: &str,
+    link_format: Option<&str>,
+    relative_paths: Option<bool>,
+) -> Result<PathDisplay, McpError> {
+    let link = link_template(services, link_format)?;
+    let relative = relative_paths.unwrap_or(services.config.current().output.relative_paths);
+
+    let repository_path = services
+        .storage
+        .get_session_metadata(session)
+        .ok()
+        .map(|metadata| metadata.repository_path);
+
+    Ok(PathDisplay::new(repository_path.as_deref(), relative, link))
+}
+
+/// Resolve how result paths of a collection search are shown
+///
+/// A collection spans several repositories, so paths are shown as
+/// stored; only `link_format` (or `output.link_format`) applies.
+pub fn collection_display(
+    services: &Services,
+    link_format: Option<&str>,
+) -> Result<PathDisplay, McpError> {
+    Ok(PathDisplay::new(
+        None,
+        false,
+        link_template(services, link_format)?,
+    ))
+}
+
+/// Link template from `link_format`, falling back to `output.link_format`
+fn link_template(
+    services: &Services,
+    link_format: Option<&str>,
+) -> Result<Option<LinkTemplate>, McpError> {
+    let config = services.config.current();
+    match link_format.or(config.output.link_format.as_deref()) {
+        Some(format) => LinkTemplate::parse(format).map_err(McpError::InvalidParams),
+        None => Ok(None),
+    }
+}
+
+/// Label search hits with handles for preview_chunk and read_file.
+///
+/// Returns the first hit's handle number. Without a session the hits
+/// come from a collection search, and each handle resolves for the
+/// session its hit came from.
+pub fn label_hits(
+    services: &Services,
+    session: Option<&str>,
+    query: &str,
+    results: &[SearchResult],
+) -> usize {
+    match session {
+        Some(session) => services.remember_results(session, query, results),
+        None => services.remember_merged_results(query, results),
+    }
+}
+
+/// Member list of a collection: "**Sessions (2):** `api`, `web`"
+pub fn format_collection(collection: &Collection) -> String {
+    let sessions: Vec<String> = collection
+        .sessions
+        .iter()
+        .map(|session| format!("`{session}`"))
+        .collect();
+    format!(
+        "**Sessions ({}):** {}\n",
+        collection.sessions.len(),
+        sessions.join(", ")
+    )
+}
+
+/// What a search runs against: one session or every session of a
+/// collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTarget {
+    Session(String),
+    Collection(String),
+}
+
+impl SearchTarget {
+    /// Target from a tool's `session` and `collection` arguments
+    ///
+    /// Exactly one of them must be given.
+    pub fn from_args(
+        session: Option<String>,
+        collection: Option<String>,
+    ) -> Result<Self, McpError> {
+        match (session, collection) {
+            (Some(session), None) => Ok(Self::Session(session)),
+            (None, Some(collection)) => Ok(Self::Collection(collection)),
+            (Some(_), Some(_)) => Err(McpError::InvalidParams(
+                "Pass either session or collection, not both".to_string(),
+            )),
+            (None, None) => Err(McpError::InvalidParams(
+                "session or collection is required".to_string(),
+            )),
+        }
+    }
+
+    /// The session, unless this is a collection
+    pub fn session(&self) -> Option<&str> {
+        match self {
+            Self::Session(session) => Some(session),
+            Self::Collection(_) => None,
+        }
+    }
+
+    /// The collection, unless this is a single session
+    pub fn collection(&self) -> Option<&str> {
+        match self {
+            Self::Session(_) => None,
+            Self::Collection(collection) => Some(collection),
+        }
+    }
+}
+
+/// Resolve a client-supplied file path for `session`
+///
+/// Relative paths are resolved against the session's repository root
+/// (see [`paths::resolve`]), or name an entry when the session indexed
+/// an archive (see [`archive::resolve_entry`]); absolute paths are only
+/// normalized.
+pub fn resolve_session_path(
+    services: &Services,
+    session: &str,
+    requested: &str,
+) -> Result<ResolvedPath, McpError> {
+    let root = if paths::to_native(&paths::normalize(requested)).is_absolute() {
+        None
+    } else {
+        let metadata = services
+            .storage
+            .get_session_metadata(session)
+            .map_err(McpError::from)?;
+        if metadata.archive.is_some() {
+            return archive::resolve_entry(requested, &metadata.repository_path)
+                .map_err(McpError::from);
+        }
+        Some(metadata.repository_path)
+    };
+    paths::resolve(requested, root.as_deref()).map_err(McpError::from)
+}
+
+/// Format a session's schedule and next run on one line
+///
+/// e.g. "`6h` (from config), next run 2025-03-10 16:00 UTC"
+pub fn format_schedule(status: &ScheduleStatus) -> String {
+    let mut line = format!("`{}` (from {})", status.schedule, status.source);
+    if let Some(next_run) = status.next_run {
+        line.push_str(&format!(
+            ", next run {}",
+            next_run.format("%Y-%m-%d %H:%M UTC")
+        ));
+    }
+    if !status.scheduler_enabled && !status.schedule.is_off() {
+        line.push_str(" (scheduler disabled)");
+    }
+    line
+}
+
+/// Format the outcome of a scheduled re-index
+pub fn format_scheduled_run(run: &ScheduledRun) -> String {
+    let finished = run.finished_at.unwrap_or(run.started_at);
+    let outcome = match run.state {
+        JobState::Queued | JobState::Running => format!(
+            "running since {}",
+            run.started_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+        state => format!(
+            "{} {}",
+            state.as_str(),
+            finished.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
+    match (&run.error, &run.job_id) {
+        (Some(error), _) => format!("{outcome}: {error}"),
+        (None, Some(job_id)) => format!("{outcome} ({job_id})"),
+        (None, None) => outcome,
+    }
+}
+
+/// Format how an index was produced, one bullet per item
+///
+/// The session config is shown in full as JSON so the run can be
+/// repeated exactly.
+pub fn format_provenance(provenance: &Provenance) -> String {
+    let git = match &provenance.git {
+        Some(git) if git.dirty => format!("commit `{}` (uncommitted changes)", git.commit),
+        Some(git) => format!("commit `{}`", git.commit),
+        None => "none (not a git checkout, or git_metadata off)".to_string(),
+    };
+    let config = serde_json::to_string(&provenance.config).unwrap_or_default();
+    format!(
+        "- **Shebe version:** {}\n\
+         - **Repository:** {}\n\
+         - **Git:** {git}\n\
+         - **Platform:** {}\n\
+         - **Tokenizer:** {}\n\
+         - **Started:** {}\n\
+         - **Finished:** {}\n\
+         - **Config:** `{config}`\n",
+        provenance.shebe_version,
+        provenance.repository_path.display(),
+        provenance.platform,
+        provenance.tokenizer,
+        provenance.started_at.format("%Y-%m-%d %H:%M:%S%.3f UTC"),
+        provenance.finished_at.format("%Y-%m-%d %H:%M:%S%.3f UTC"),
+    )
+}
+
+/// List files cut off at `max_chunks_per_file`, one bullet per file
+pub fn format_truncated_files(files: &[TruncatedFile], max_chunks_per_file: usize) -> String {
+    let mut output = format!(
+        "Partially indexed files: {} (max_chunks_per_file = {max_chunks_per_file}; \
+         re-index with a higher value to index them in full)\n",
+        files.len()
+    );
+    for file in files {
+        output.push_str(&format!(
+            "- {}: {} chunks, {} of {} ({}%)\n",
+            file.path,
+            file.chunks_indexed,
+            format_bytes(file.bytes_indexed),
+            format_bytes(file.file_bytes),
+            file.percent_indexed()
+        ));
+    }
+    output
+}
+
+/// Performance breakdown of a profiled run: a table of stages, then
+/// memory and the slowest files
+pub fn format_index_profile(profile: &IndexProfile) -> String {
+    let mut output = format!(
+        "Performance breakdown ({} profiled):\n\n\
+         | Stage | Time | Share |\n\
+         |-------|------|-------|\n",
+        format_timing(Duration::from_micros(profile.total_us()))
+    );
+    for timing in &profile.stages {
+        output.push_str(&format!(
+            "| {} | {} | {:.1}% |\n",
+            timing.stage.label(),
+            format_timing(Duration::from_micros(timing.duration_us)),
+            profile.percent(timing)
+        ));
+    }
+    output.push_str(&format!(
+        "\nChunk text held before writing: {}\n",
+        format_bytes(profile.chunk_text_bytes)
+    ));
+    if let Some(peak) = profile.peak_rss_bytes {
+        output.push_str(&format!("Peak memory (RSS): {}\n", format_bytes(peak)));
+    }
+    if !profile.slowest_files.is_empty() {
+        output.push_str("\nSlowest files:\n");
+        for (rank, file) in profile.slowest_files.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. {} ({})\n",
+                rank + 1,
+                file.path,
+                format_timing(Duration::from_micros(file.duration_us))
+            ));
+        }
+    }
+    output
+}
+
+/// List files that only moved since the previous run, one bullet per
+/// file, noting how many of `total` were not listed
+pub fn format_renamed_files(files: &[FileRename], total: usize) -> String {
+    let mut output = format!("Renamed files: {total} (same content under a new path)\n");
+    for file in files {
+        output.push_str(&format!("- {} -> {}\n", file.from, file.to));
+    }
+    if total > files.len() {
+        output.push_str(&format!("- ... and {} more\n", total - files.len()));
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
 
     #[test]
     fn test_format_bytes_b() {
@@ -209,20 +539,20 @@ mod tests {
 
     #[test]
     fn test_format_bytes_kb() {
-        assert_eq!(format_bytes(2048), "2.00 KB");
-        assert_eq!(format_bytes(1536), "1.50 KB");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(1536), "1.5 KB");
     }
 
     #[test]
     fn test_format_bytes_mb() {
-        assert_eq!(format_bytes(1048576), "1.00 MB");
-        assert_eq!(format_bytes(5242880), "5.00 MB");
+        assert_eq!(format_bytes(1048576), "1.0 MB");
+        assert_eq!(format_bytes(5242880), "5.0 MB");
     }
 
     #[test]
     fn test_format_bytes_gb() {
-        assert_eq!(format_bytes(1073741824), "1.00 GB");
-        assert_eq!(format_bytes(2147483648), "2.00 GB");
+        assert_eq!(format_bytes(1073741824), "1.0 GB");
+        assert_eq!(format_bytes(2147483648), "2.0 GB");
     }
 
     #[test]
@@ -396,6 +726,52 @@ mod tests {
         assert_eq!(byte_offset_to_line_number(content, 13), 3); // After "世界\n"
     }
 
+    // Tests for locate_symbol
+
+    #[test]
+    fn test_locate_symbol_twice_on_line() {
+        let content = "fn main() {\n    let x = foo(foo(1));\n}\n";
+        let offset = content.find("foo").unwrap();
+
+        let loc = locate_symbol(content, offset, "foo").unwrap();
+        assert_eq!(loc.line_number, 2);
+        assert_eq!(loc.column, 13);
+        assert_eq!(loc.columns, vec![13, 17]);
+
+        let second = locate_symbol(content, offset + 4, "foo").unwrap();
+        assert_eq!(second.column, 17);
+        assert_eq!(second.columns, vec![13, 17]);
+    }
+
+    #[test]
+    fn test_locate_symbol_multibyte_before_symbol() {
+        // "é" is 2 bytes and "世界" is 6 bytes, but each character is one column
+        let content = "x\nlet é = \"世界\"; bar();\n";
+        let offset = content.find("bar").unwrap();
+
+        let loc = locate_symbol(content, offset, "bar").unwrap();
+        assert_eq!(loc.line_number, 2);
+        assert_eq!(loc.column, 15);
+        assert_eq!(loc.columns, vec![15]);
+    }
+
+    #[test]
+    fn test_locate_symbol_first_line_without_newline() {
+        let loc = locate_symbol("foo", 0, "foo").unwrap();
+        assert_eq!(loc.line_number, 1);
+        assert_eq!(loc.column, 1);
+        assert_eq!(loc.columns, vec![1]);
+    }
+
+    #[test]
+    fn test_locate_symbol_mismatch() {
+        let content = "let 世 = foo;";
+        assert!(locate_symbol(content, 0, "foo").is_none());
+        assert!(locate_symbol(content, 5, "foo").is_none()); // inside "世"
+        assert!(locate_symbol(content, 100, "foo").is_none());
+        assert!(locate_symbol(content, 0, "").is_none());
+    }
+
     // Tests for extract_context_lines
 
     #[test]
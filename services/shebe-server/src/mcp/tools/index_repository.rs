@@ -4,17 +4,33 @@
 //! directly from Claude Code.
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::format_time_ago;
+use super::helpers::{
+    format_bytes, format_count, format_duration, format_index_profile, format_provenance,
+    format_renamed_files, format_time_ago, format_truncated_files,
+};
+use crate::core::archive::ArchiveKind;
+use crate::core::error::ShebeError;
+use crate::core::indexer::presets::{preset_names, with_presets};
+use crate::core::indexer::repo_config::{
+    ConfigSource, RepoFileWarning, IGNORE_FILE, REPO_CONFIG_FILE,
+};
+use crate::core::indexer::{
+    check_index_path, resolve_index_root, BoundaryHint, DetectedRoot, DryRunReport, IndexOverrides,
+    IndexRoot,
+};
+use crate::core::remote::{is_remote_url, validate_git_ref, validate_remote_url};
 use crate::core::services::Services;
-use crate::core::storage::SCHEMA_VERSION;
+use crate::core::storage::{SessionConfig, SCHEMA_VERSION};
+use crate::core::types::IndexStats;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::ToolResult;
 use crate::mcp::protocol::ToolSchema;
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Default include pattern (all files)
 const DEFAULT_INCLUDE: &[&str] = &["**/*"];
@@ -32,42 +48,109 @@ const DEFAULT_EXCLUDE: &[&str] = &[
     "**/venv/**",
 ];
 
-/// Request parameters for index_repository tool
+/// Request parameters for index_repository (and index_repository_async)
 #[derive(Debug, Deserialize)]
-struct IndexRequest {
-    /// Absolute path to repository
-    path: String,
+pub(super) struct IndexRequest {
+    /// Absolute path to repository, or a git URL to clone
+    pub(super) path: String,
+    /// Branch or tag to clone when `path` is a URL (optional, default: remote's default branch)
+    #[serde(default, rename = "ref")]
+    pub(super) git_ref: Option<String>,
     /// Session identifier
-    session: String,
+    pub(super) session: String,
     /// Glob patterns to include (optional)
     #[serde(default)]
-    include_patterns: Option<Vec<String>>,
+    pub(super) include_patterns: Option<Vec<String>>,
     /// Glob patterns to exclude (optional)
     #[serde(default)]
-    exclude_patterns: Option<Vec<String>>,
-    /// Characters per chunk (optional, default: 512)
-    #[serde(default = "default_chunk_size")]
-    chunk_size: usize,
-    /// Overlap between chunks (optional, default: 64)
-    #[serde(default = "default_overlap")]
-    overlap: usize,
+    pub(super) exclude_patterns: Option<Vec<String>>,
+    /// Language presets added to the include patterns (optional)
+    #[serde(default)]
+    pub(super) languages: Vec<String>,
+    /// Characters per chunk (optional, default: .shebe.toml, then config)
+    #[serde(default)]
+    pub(super) chunk_size: Option<usize>,
+    /// Overlap between chunks (optional, default: .shebe.toml, then config)
+    #[serde(default)]
+    pub(super) overlap: Option<usize>,
     /// Force re-indexing if session exists (optional, default: true)
     #[serde(default = "default_force")]
-    force: bool,
-}
-
-fn default_chunk_size() -> usize {
-    512
-}
-
-fn default_overlap() -> usize {
-    64
+    pub(super) force: bool,
+    /// Skip files whose content duplicates an indexed file (optional, default: false)
+    #[serde(default)]
+    pub(super) dedupe: bool,
+    /// Boundary that chunk ends are aligned to (optional, default: none)
+    #[serde(default)]
+    pub(super) boundary_hint: Option<BoundaryHint>,
+    /// Store chunk text in the index (optional, default: true)
+    #[serde(default)]
+    pub(super) store_chunk_text: Option<bool>,
+    /// Replace long base64/minified blobs with placeholders (optional, default: false)
+    #[serde(default)]
+    pub(super) strip_high_entropy: Option<bool>,
+    /// Run length above which blobs are stripped (optional, default: 200)
+    #[serde(default)]
+    pub(super) strip_run_chars: Option<usize>,
+    /// Merge a file's short final chunk into the previous one (optional, default: false)
+    #[serde(default)]
+    pub(super) merge_tail_fragments: Option<bool>,
+    /// Chunk data files from whole records (optional, default: true)
+    #[serde(default)]
+    pub(super) record_chunking: Option<bool>,
+    /// Chunks indexed per file before the rest is left out (optional, default: 2000)
+    #[serde(default)]
+    pub(super) max_chunks_per_file: Option<usize>,
+    /// Fail when the include patterns match no file (optional, default: false)
+    #[serde(default)]
+    pub(super) strict_patterns: Option<bool>,
+    /// Only report what would be indexed (optional, default: false)
+    #[serde(default)]
+    pub(super) dry_run: bool,
+    /// List skipped files with reasons (optional, default: false)
+    #[serde(default)]
+    pub(super) verbose: bool,
+    /// Time each indexing stage and report a breakdown (optional, default: false)
+    #[serde(default)]
+    pub(super) profile: bool,
+    /// Index `/`, the home directory or the storage root (optional, default: false)
+    #[serde(default)]
+    pub(super) allow_dangerous_path: bool,
+    /// Index the repository root of a file path (optional, default: false)
+    #[serde(default)]
+    pub(super) detect_root: bool,
 }
 
 fn default_force() -> bool {
     true
 }
 
+impl IndexRequest {
+    /// Settings passed explicitly in the request
+    ///
+    /// Language presets are expanded and added to `include_patterns`.
+    pub(super) fn overrides(&self) -> Result<IndexOverrides, McpError> {
+        let include_patterns = with_presets(self.include_patterns.clone(), &self.languages)
+            .map_err(|e| McpError::InvalidParams(e.to_string()))?;
+        Ok(IndexOverrides {
+            chunk_size: self.chunk_size,
+            overlap: self.overlap,
+            include_patterns,
+            exclude_patterns: self.exclude_patterns.clone(),
+            languages: self.languages.clone(),
+            dedupe: self.dedupe,
+            boundary_hint: self.boundary_hint,
+            store_chunk_text: self.store_chunk_text,
+            strip_high_entropy: self.strip_high_entropy,
+            strip_run_chars: self.strip_run_chars,
+            merge_tail_fragments: self.merge_tail_fragments,
+            record_chunking: self.record_chunking,
+            max_chunks_per_file: self.max_chunks_per_file,
+            strict_patterns: self.strict_patterns,
+            git_metadata: None,
+        })
+    }
+}
+
 /// Handler for index_repository MCP tool
 pub struct IndexRepositoryHandler {
     services: Arc<Services>,
@@ -80,7 +163,30 @@ impl IndexRepositoryHandler {
     }
 
     /// Validate and canonicalize repository path
-    fn validate_path(path: &str) -> Result<PathBuf, McpError> {
+    pub(super) fn validate_path(path: &str) -> Result<PathBuf, McpError> {
+        let canonical = Self::canonical_path(path)?;
+
+        // Must be a directory or an archive
+        if !canonical.is_dir() && ArchiveKind::detect(&canonical).is_none() {
+            return Err(McpError::InvalidParams(
+                "Path must be a directory or a zip/tar archive".to_string(),
+            ));
+        }
+
+        Ok(canonical)
+    }
+
+    /// Validate the path to index
+    ///
+    /// A file path is refused, or with `detect_root` replaced by the
+    /// repository root that contains it.
+    fn validate_index_path(path: &str, detect_root: bool) -> Result<IndexRoot, McpError> {
+        let canonical = Self::canonical_path(path)?;
+        Ok(resolve_index_root(&canonical, detect_root)?)
+    }
+
+    /// Canonical form of an absolute path that exists
+    fn canonical_path(path: &str) -> Result<PathBuf, McpError> {
         let path = PathBuf::from(path);
 
         // Must be absolute
@@ -96,45 +202,56 @@ impl IndexRepositoryHandler {
             )));
         }
 
-        // Must be a directory
-        if !path.is_dir() {
-            return Err(McpError::InvalidParams(
-                "Path must be a directory".to_string(),
-            ));
-        }
-
         // Canonicalize to prevent path traversal attacks
-        let canonical = path
-            .canonicalize()
-            .map_err(|e| McpError::InvalidParams(format!("Cannot resolve path: {e}")))?;
-
-        Ok(canonical)
+        path.canonicalize()
+            .map_err(|e| McpError::InvalidParams(format!("Cannot resolve path: {e}")))
     }
 
-    /// Validate session identifier
-    fn validate_session(session: &str) -> Result<(), McpError> {
-        // Length check
-        if session.is_empty() || session.len() > 64 {
-            return Err(McpError::InvalidParams(
-                "Session must be 1-64 characters".to_string(),
-            ));
+    /// Validate a git URL and ref before anything is cloned
+    fn validate_remote(req: &IndexRequest) -> Result<(), McpError> {
+        validate_remote_url(&req.path)?;
+        if let Some(git_ref) = &req.git_ref {
+            validate_git_ref(git_ref)?;
         }
-
-        // Character check (alphanumeric, hyphen, underscore)
-        if !session
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-        {
+        if req.dry_run {
             return Err(McpError::InvalidParams(
-                "Session must contain only alphanumeric, hyphen, underscore".to_string(),
+                "dry_run is not supported for git URLs. Clone the repository locally \
+                 and dry-run the local path instead"
+                    .to_string(),
             ));
         }
+        Ok(())
+    }
 
-        // Must start with alphanumeric
-        if !session.chars().next().unwrap().is_alphanumeric() {
-            return Err(McpError::InvalidParams(
-                "Session must start with alphanumeric character".to_string(),
-            ));
+    /// Validate session identifier
+    ///
+    /// A namespace prefix (`team-a/backend`) follows the same rules as
+    /// the name.
+    fn validate_session(session: &str) -> Result<(), McpError> {
+        for part in session.splitn(2, '/') {
+            // Length check
+            if part.is_empty() || part.len() > 64 {
+                return Err(McpError::InvalidParams(
+                    "Session must be 1-64 characters".to_string(),
+                ));
+            }
+
+            // Character check (alphanumeric, hyphen, underscore)
+            if !part
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(McpError::InvalidParams(
+                    "Session must contain only alphanumeric, hyphen, underscore".to_string(),
+                ));
+            }
+
+            // Must start with alphanumeric
+            if !part.chars().next().unwrap().is_alphanumeric() {
+                return Err(McpError::InvalidParams(
+                    "Session must start with alphanumeric character".to_string(),
+                ));
+            }
         }
 
         Ok(())
@@ -159,6 +276,420 @@ impl IndexRepositoryHandler {
         }
         Ok(())
     }
+
+    /// Validate a request and check the session can be (re)indexed
+    ///
+    /// Returns the canonical local directory (or archive) to index, or
+    /// `None` when `path` is a git URL.
+    pub(super) fn validate_request(
+        services: &Services,
+        req: &IndexRequest,
+    ) -> Result<Option<IndexRoot>, McpError> {
+        let remote = is_remote_url(&req.path);
+        let local_path = if remote {
+            Self::validate_remote(req)?;
+            None
+        } else if req.git_ref.is_some() {
+            return Err(McpError::InvalidParams(
+                "ref only applies when path is a git URL".to_string(),
+            ));
+        } else {
+            Some(Self::validate_index_path(&req.path, req.detect_root)?)
+        };
+        Self::validate_session(&req.session)?;
+        if let Some(chunk_size) = req.chunk_size {
+            Self::validate_chunk_size(chunk_size)?;
+        }
+        if let Some(overlap) = req.overlap {
+            Self::validate_overlap(overlap)?;
+        }
+
+        if !req.dry_run {
+            // Refuse paths holding far more than one project
+            if let Some(root) = &local_path {
+                check_index_path(
+                    &root.path,
+                    services.storage.storage_root(),
+                    req.allow_dangerous_path,
+                )?;
+            }
+
+            // Refuse while a background job is indexing the session
+            if let Some(job) = services.jobs.active_job(&req.session) {
+                return Err(ShebeError::IndexLocked(format!(
+                    "session '{}' is being indexed by background job {job}",
+                    req.session
+                ))
+                .into());
+            }
+        }
+
+        // Check if session already exists (unless force or dry run)
+        let session_exists = services.storage.session_exists(&req.session);
+
+        if session_exists && !req.force && !req.dry_run {
+            // Get metadata for enhanced error message
+            let metadata = services
+                .storage
+                .get_session_metadata(&req.session)
+                .map_err(McpError::from)?;
+
+            let schema_status = if metadata.schema_version == SCHEMA_VERSION {
+                "current"
+            } else {
+                "outdated"
+            };
+
+            return Err(McpError::InvalidParams(format!(
+                "Session '{}' already exists.\n\
+                 - Last indexed: {} ({})\n\
+                 - Files indexed: {}\n\
+                 - Schema version: v{} ({})\n\
+                 Use force=true to re-index, or use existing session for search.",
+                req.session,
+                metadata.last_indexed_at.format("%Y-%m-%d %H:%M UTC"),
+                format_time_ago(metadata.last_indexed_at),
+                metadata.files_indexed,
+                metadata.schema_version,
+                schema_status
+            )));
+        }
+
+        Ok(local_path)
+    }
+
+    /// Settings used where neither the request nor `.shebe.toml` set one
+    pub(super) fn default_session_config(services: &Services) -> SessionConfig {
+        SessionConfig {
+            chunk_size: services.config.current().indexing.chunk_size,
+            overlap: services.config.current().indexing.overlap,
+            include_patterns: DEFAULT_INCLUDE.iter().map(|s| s.to_string()).collect(),
+            exclude_patterns: DEFAULT_EXCLUDE.iter().map(|s| s.to_string()).collect(),
+            git_metadata: services.config.current().indexing.git_metadata,
+            ..SessionConfig::default()
+        }
+    }
+
+    /// Input schema properties shared with index_repository_async
+    pub(super) fn index_properties() -> Value {
+        json!({
+            "path": {
+                "type": "string",
+                "description": "Absolute path to the repository to index, or a git URL \
+                               (https://, ssh://, git:// or user@host:path) to shallow-clone \
+                               into the server's clone cache and index. file:// URLs are refused."
+            },
+            "ref": {
+                "type": "string",
+                "description": "Branch or tag to clone when path is a git URL \
+                               (default: the remote's default branch)"
+            },
+            "session": {
+                "type": "string",
+                "pattern": "^([a-zA-Z0-9][a-zA-Z0-9-_]{0,63}/)?[a-zA-Z0-9][a-zA-Z0-9-_]{0,63}$",
+                "description": "Unique session identifier (alphanumeric, hyphens, underscores, max 64 chars), \
+                               optionally prefixed by a configured namespace (team-a/backend)"
+            },
+            "include_patterns": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns for files to include (default: all)",
+                "default": DEFAULT_INCLUDE
+            },
+            "exclude_patterns": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns for files to exclude",
+                "default": DEFAULT_EXCLUDE
+            },
+            "languages": {
+                "type": "array",
+                "items": {"type": "string", "enum": preset_names()},
+                "description": "Language presets whose file extensions are added to \
+                               include_patterns, e.g. [\"rust\", \"docs\"] for **/*.rs, \
+                               **/*.toml, **/*.md, **/*.rst and **/*.txt. \"docs\" is \
+                               md/rst/txt, \"web\" is ts/tsx/js/css/html. The session \
+                               records the resulting patterns."
+            },
+            "chunk_size": {
+                "type": "integer",
+                "minimum": 100,
+                "maximum": 2000,
+                "default": 512,
+                "description": "Number of characters per chunk (default: .shebe.toml \
+                               in the repository, then server config)"
+            },
+            "overlap": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 500,
+                "default": 64,
+                "description": "Number of overlapping characters between chunks \
+                               (default: .shebe.toml in the repository, then server config)"
+            },
+            "force": {
+                "type": "boolean",
+                "default": true,
+                "description": "Re-index even if session exists. Default is true (always re-indexes). \
+                               Set to false to skip if session exists."
+            },
+            "dedupe": {
+                "type": "boolean",
+                "default": false,
+                "description": "Index only the first of several files with identical content \
+                               (vendored copies, build outputs). Duplicate paths are shown as \
+                               'Also present at' in search_code results."
+            },
+            "boundary_hint": {
+                "type": "string",
+                "enum": ["none", "blank_line", "newline"],
+                "default": "none",
+                "description": "Move each chunk end back (up to 15% of chunk_size) to the \
+                               nearest blank line or line break, so chunks do not split \
+                               functions or statements mid-line"
+            },
+            "store_chunk_text": {
+                "type": "boolean",
+                "default": true,
+                "description": "Store chunk text in the index. Set to false for a much smaller \
+                               index; snippets are then read from the files at query time \
+                               (results for files deleted since indexing show no text)."
+            },
+            "strip_high_entropy": {
+                "type": "boolean",
+                "default": false,
+                "description": "Replace long base64/minified blobs (lock files, sourcemaps, embedded \
+                               images) with a short placeholder before chunking, for a smaller index \
+                               and better ranking. Offsets still point into the real files."
+            },
+            "strip_run_chars": {
+                "type": "integer",
+                "minimum": 32,
+                "default": 200,
+                "description": "With strip_high_entropy, strip runs of non-whitespace longer than \
+                               this many characters (lines over 5000 characters are always stripped)"
+            },
+            "merge_tail_fragments": {
+                "type": "boolean",
+                "default": false,
+                "description": "Merge a file's final chunk into the one before it when it is under \
+                               15% of chunk_size, so a few trailing lines do not form a tiny chunk \
+                               that ranks above substantive code"
+            },
+            "record_chunking": {
+                "type": "boolean",
+                "default": true,
+                "description": "Chunk CSV, TSV and JSON Lines files (.csv, .tsv, .jsonl, .ndjson) \
+                               from whole records, never splitting one (CSV quoted fields may span \
+                               lines); results then show record numbers. A record longer than \
+                               chunk_size is still split."
+            },
+            "max_chunks_per_file": {
+                "type": "integer",
+                "minimum": 1,
+                "default": 2000,
+                "description": "Index at most this many chunks of one file, so a huge generated \
+                               or data file cannot dominate the session. Capped files are listed \
+                               in the result and their search results are marked as partially \
+                               indexed."
+            },
+            "strict_patterns": {
+                "type": "boolean",
+                "default": false,
+                "description": "Fail instead of warning when the include patterns match none of \
+                               the repository's files (checked once at least 10 files are seen). \
+                               The message suggests a rewrite that would match, e.g. \
+                               'src/*.rs' -> '**/src/*.rs'."
+            },
+            "allow_dangerous_path": {
+                "type": "boolean",
+                "default": false,
+                "description": "Allow indexing the filesystem root, the home directory or the \
+                               server's storage root, which are refused by default. Sessions \
+                               are also capped at the server's limits.max_files_per_session \
+                               and limits.max_session_size_mb."
+            },
+            "detect_root": {
+                "type": "boolean",
+                "default": false,
+                "description": "When path is a file (e.g. a README), index the repository that \
+                               contains it: the nearest parent directory holding .git, \
+                               Cargo.toml, package.json or pyproject.toml (up to 8 levels). \
+                               The chosen root is reported. Without it a file path is refused."
+            }
+        })
+    }
+
+    /// Line naming the repository root chosen for a file path
+    pub(super) fn format_detected_root(detected: &DetectedRoot) -> String {
+        format!("Repository root: {}", detected.summary())
+    }
+
+    /// Skip counts, and with `verbose` the skipped files and reasons
+    fn format_skipped(stats: &IndexStats, verbose: bool) -> String {
+        let total = stats.skipped.total();
+        if total == 0 {
+            return String::new();
+        }
+
+        let mut output = format!(
+            "\nFiles skipped: {} ({})",
+            format_count(total as u64),
+            stats.skipped.summary()
+        );
+        if !verbose {
+            return output;
+        }
+
+        let listed = stats.skipped_files.len();
+        if listed < total {
+            output.push_str(&format!("\n\nSkipped files (first {listed} of {total}):"));
+        } else {
+            output.push_str("\n\nSkipped files:");
+        }
+        for file in &stats.skipped_files {
+            output.push_str(&format!(
+                "\n- {} ({}: {})",
+                file.path,
+                file.reason.label(),
+                file.detail
+            ));
+        }
+        output
+    }
+
+    /// Files matched per pattern, and the warning when none matched
+    fn format_patterns(stats: &IndexStats) -> String {
+        let mut output = String::new();
+        if !stats.patterns.include.is_empty() {
+            output.push_str(&format!(
+                "\nInclude patterns: {}",
+                stats.patterns.include_summary()
+            ));
+        }
+        let exclusions = stats.patterns.exclude_summary();
+        if !exclusions.is_empty() {
+            output.push_str(&format!("\nExcluded by: {exclusions}"));
+        }
+        if let Some(warning) = &stats.pattern_warning {
+            output.push_str(&format!("\n\nWARNING: {warning}"));
+        }
+        output
+    }
+
+    /// Format repository-local settings and warnings as Markdown
+    ///
+    /// Returns an empty string when the repository has neither file.
+    fn format_repo_settings(config: &SessionConfig, warnings: &[RepoFileWarning]) -> String {
+        let mut output = String::new();
+
+        if let Some(sources) = &config.sources {
+            let from_repo: Vec<&str> = [
+                ("chunk_size", sources.chunk_size),
+                ("overlap", sources.overlap),
+                ("include_patterns", sources.include_patterns),
+            ]
+            .into_iter()
+            .filter(|(_, source)| *source == ConfigSource::RepoConfig)
+            .map(|(name, _)| name)
+            .collect();
+            if !from_repo.is_empty() {
+                output.push_str(&format!(
+                    "\nFrom `{REPO_CONFIG_FILE}`: {}",
+                    from_repo.join(", ")
+                ));
+            }
+            if !sources.ignore_patterns.is_empty() {
+                output.push_str(&format!(
+                    "\nFrom `{IGNORE_FILE}`: {} exclude pattern(s)",
+                    sources.ignore_patterns.len()
+                ));
+            }
+        }
+
+        if !warnings.is_empty() {
+            output.push_str("\n\nWarnings (lines skipped):");
+            for warning in warnings {
+                output.push_str(&format!("\n- {warning}"));
+            }
+        }
+
+        output
+    }
+
+    /// Format a dry run report as Markdown
+    fn format_dry_run(path: &Path, config: &SessionConfig, report: &DryRunReport) -> String {
+        let mut output = format!(
+            "# Dry Run: `{}`\n\n\
+             No session was created. Indexing with these settings would process:\n\n\
+             - **Files:** {}\n\
+             - **Total size:** {}\n\
+             - **Estimated chunks:** ~{} ({} chars/chunk, {} overlap)\n\
+             - **Skipped (too large):** {}\n\
+             - **Skipped (no include match):** {}\n",
+            path.display(),
+            format_count(report.files as u64),
+            format_bytes(report.total_bytes),
+            format_count(report.estimated_chunks as u64),
+            config.chunk_size,
+            config.overlap,
+            format_count(report.skipped_too_large as u64),
+            format_count(report.skipped_not_included as u64)
+        );
+
+        if !report.top_extensions.is_empty() {
+            output.push_str("\n## Top Extensions\n\n");
+            output.push_str("| Extension | Files | Size |\n|-----------|-------|------|\n");
+            for ext in &report.top_extensions {
+                let name = if ext.extension.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    format!(".{}", ext.extension)
+                };
+                output.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    name,
+                    format_count(ext.files as u64),
+                    format_bytes(ext.bytes)
+                ));
+            }
+        }
+
+        let (matched, unmatched): (Vec<_>, Vec<_>) =
+            report.exclusions.iter().partition(|e| e.matched());
+        if !matched.is_empty() {
+            output.push_str("\n## Exclusions\n\n");
+            output
+                .push_str("| Pattern | Files | Directories |\n|---------|-------|-------------|\n");
+            for exclusion in &matched {
+                output.push_str(&format!(
+                    "| `{}` | {} | {} |\n",
+                    exclusion.pattern, exclusion.files, exclusion.directories
+                ));
+            }
+            output.push_str("\nFiles inside pruned directories are not counted.\n");
+        }
+        if !unmatched.is_empty() {
+            let patterns: Vec<String> = unmatched
+                .iter()
+                .map(|e| format!("`{}`", e.pattern))
+                .collect();
+            output.push_str(&format!("\nExcluded nothing: {}\n", patterns.join(", ")));
+        }
+
+        if !report.sample_paths.is_empty() {
+            output.push_str(&format!(
+                "\n## Matched Files (first {} of {})\n\n",
+                report.sample_paths.len(),
+                report.files
+            ));
+            for path in &report.sample_paths {
+                output.push_str(&format!("- `{path}`\n"));
+            }
+        }
+
+        output
+    }
 }
 
 #[async_trait]
@@ -167,6 +698,10 @@ impl McpToolHandler for IndexRepositoryHandler {
         "index_repository"
     }
 
+    fn mutates(&self) -> bool {
+        true
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "index_repository".to_string(),
@@ -185,58 +720,52 @@ impl McpToolHandler for IndexRepositoryHandler {
                          \
                          FILE FILTERING: Use glob patterns. Defaults exclude build artifacts (target/, node_modules/, \
                          .git/, dist/, __pycache__/). Customize with include_patterns and exclude_patterns. \
+                         A .shebeignore file (gitignore syntax) at the repository root adds excludes, and a \
+                         .shebe.toml can set chunk_size, overlap and include_patterns for the repository; \
+                         explicit parameters take precedence. \
+                         \
+                         REMOTE REPOSITORIES: path may be a git URL (https://, ssh://, git://, user@host:path). \
+                         The repository is shallow-cloned (depth 1, optional ref) with the git CLI into the \
+                         server's clone cache and the URL and commit are recorded, so reindex_session fetches \
+                         the same ref again. git runs non-interactively: private repositories need a credential \
+                         helper or ssh-agent key. \
                          \
                          CHUNKING: Default 512 chars/chunk with 64 char overlap. Increase chunk_size (max 2000) \
                          for verbose languages (Java, C++), decrease (min 100) for dense code (Python, Ruby)."
                 .to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "path": {
-                        "type": "string",
-                        "description": "Absolute path to the repository to index"
-                    },
-                    "session": {
-                        "type": "string",
-                        "pattern": "^[a-zA-Z0-9][a-zA-Z0-9-_]{0,63}$",
-                        "description": "Unique session identifier (alphanumeric, hyphens, underscores, max 64 chars)"
-                    },
-                    "include_patterns": {
-                        "type": "array",
-                        "items": {"type": "string"},
-                        "description": "Glob patterns for files to include (default: all)",
-                        "default": DEFAULT_INCLUDE
-                    },
-                    "exclude_patterns": {
-                        "type": "array",
-                        "items": {"type": "string"},
-                        "description": "Glob patterns for files to exclude",
-                        "default": DEFAULT_EXCLUDE
-                    },
-                    "chunk_size": {
-                        "type": "integer",
-                        "minimum": 100,
-                        "maximum": 2000,
-                        "default": 512,
-                        "description": "Number of characters per chunk"
-                    },
-                    "overlap": {
-                        "type": "integer",
-                        "minimum": 0,
-                        "maximum": 500,
-                        "default": 64,
-                        "description": "Number of overlapping characters between chunks"
-                    },
-                    "force": {
-                        "type": "boolean",
-                        "default": true,
-                        "description": "Re-index even if session exists. Default is true (always re-indexes). \
-                                       Set to false to skip if session exists."
-                    }
-                },
-                "required": ["path", "session"],
-                "additionalProperties": false
-            }),
+            input_schema: {
+                let mut properties = Self::index_properties();
+                properties["dry_run"] = json!({
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Only walk the repository and report what would be indexed \
+                                   (file count, size, estimated chunks, top extensions, first 50 \
+                                   paths, exclusions per pattern). No session is created, so the \
+                                   session ID may already exist. Use to check patterns first."
+                });
+                properties["verbose"] = json!({
+                    "type": "boolean",
+                    "default": false,
+                    "description": "List the skipped files (first 200) with the reason each was \
+                                   skipped: excluded by pattern, too large, binary, read error or \
+                                   not UTF-8. Counts per reason are always shown."
+                });
+                properties["profile"] = json!({
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Time each indexing stage (walk, read, decode, chunk, \
+                                   add_document, commit) and append a performance breakdown: \
+                                   per-stage totals and shares, peak memory and the ten \
+                                   slowest files. Use when indexing is slow to see where the \
+                                   time goes; unprofiled runs take no timings."
+                });
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": ["path", "session"],
+                    "additionalProperties": false
+                })
+            },
         }
     }
 
@@ -245,77 +774,127 @@ impl McpToolHandler for IndexRepositoryHandler {
         let req: IndexRequest = serde_json::from_value(args)
             .map_err(|e| McpError::InvalidParams(format!("Invalid parameters: {e}")))?;
 
-        // Validate parameters
-        let path = Self::validate_path(&req.path)?;
-        Self::validate_session(&req.session)?;
-        Self::validate_chunk_size(req.chunk_size)?;
-        Self::validate_overlap(req.overlap)?;
-
-        // Check if session already exists (unless force)
-        let session_exists = self.services.storage.session_exists(&req.session);
-
-        if session_exists && !req.force {
-            // Get metadata for enhanced error message
-            let metadata = self
+        let local_path = Self::validate_request(&self.services, &req)?;
+
+        // Shallow-clone remote repositories into the clone cache
+        let (path, source, detected) = match local_path {
+            Some(root) => (root.path, None, root.detected),
+            None => {
+                let source = self.services.clones.clone_repo(
+                    &req.session,
+                    &req.path,
+                    req.git_ref.as_deref(),
+                )?;
+                let clone_path = self
+                    .services
+                    .clones
+                    .clone_path(&req.session)
+                    .canonicalize()
+                    .map_err(|e| McpError::InternalError(format!("Cannot resolve clone: {e}")))?;
+                (clone_path, Some(source), None)
+            }
+        };
+
+        // Resolve configuration: parameters > .shebe.toml > defaults
+        let index = self
+            .services
+            .index(&path)
+            .session(&req.session)
+            .overrides(req.overrides()?)
+            .defaults(Self::default_session_config(&self.services))
+            .force(req.force)
+            .allow_dangerous_path(req.allow_dangerous_path)
+            .profile(req.profile);
+        let resolved = index.resolve()?;
+        let repo_settings = Self::format_repo_settings(&resolved.config, &resolved.warnings);
+
+        if req.dry_run {
+            let report = self
                 .services
-                .storage
-                .get_session_metadata(&req.session)
+                .dry_run_index(&path, &resolved.config)
                 .map_err(McpError::from)?;
-
-            let schema_status = if metadata.schema_version == SCHEMA_VERSION {
-                "current"
-            } else {
-                "outdated"
-            };
-
-            return Err(McpError::InvalidParams(format!(
-                "Session '{}' already exists.\n\
-                 - Last indexed: {} ({})\n\
-                 - Files indexed: {}\n\
-                 - Schema version: v{} ({})\n\
-                 Use force=true to re-index, or use existing session for search.",
-                req.session,
-                metadata.last_indexed_at.format("%Y-%m-%d %H:%M UTC"),
-                format_time_ago(metadata.last_indexed_at),
-                metadata.files_indexed,
-                metadata.schema_version,
-                schema_status
-            )));
+            let mut output = Self::format_dry_run(&path, &resolved.config, &report);
+            if let Some(detected) = &detected {
+                output.push_str(&format!("\n{}\n", Self::format_detected_root(detected)));
+            }
+            output.push_str(&repo_settings);
+            return Ok(text_content(output));
         }
 
-        // Prepare indexing configuration
-        let include_patterns = req
-            .include_patterns
-            .unwrap_or_else(|| DEFAULT_INCLUDE.iter().map(|s| s.to_string()).collect());
-        let exclude_patterns = req
-            .exclude_patterns
-            .unwrap_or_else(|| DEFAULT_EXCLUDE.iter().map(|s| s.to_string()).collect());
-
-        // Get max file size from config
-        let max_file_size_mb = self.services.config.indexing.max_file_size_mb;
-
         // Index repository synchronously
-        let stats = self.services.storage.index_repository(
-            &req.session,
-            &path,
-            include_patterns,
-            exclude_patterns,
-            req.chunk_size,
-            req.overlap,
-            max_file_size_mb,
-            req.force,
-        )?;
+        let max_chunks_per_file = resolved.config.max_chunks_per_file;
+        let stats = index.run_resolved(resolved)?.stats;
+        if let Some(source) = &source {
+            self.services
+                .storage
+                .set_remote_source(&req.session, Some(source.clone()))?;
+        }
 
         // Format completion message
-        let message = format!(
+        let mut message = format!(
             "Indexing complete!\n\
              Files indexed: {}\n\
              Chunks created: {}\n\
-             Duration: {:.1}s",
-            stats.files_indexed,
-            stats.chunks_created,
-            stats.duration_ms as f64 / 1000.0
+             Duration: {}",
+            format_count(stats.files_indexed as u64),
+            format_count(stats.chunks_created as u64),
+            format_duration(Duration::from_millis(stats.duration_ms))
         );
+        if req.dedupe {
+            message.push_str(&format!(
+                "\nDuplicate files skipped: {}",
+                format_count(stats.files_deduplicated as u64)
+            ));
+        }
+        if stats.bytes_stripped > 0 {
+            message.push_str(&format!(
+                "\nLong blobs stripped: {}",
+                format_bytes(stats.bytes_stripped)
+            ));
+        }
+        if stats.files_streamed > 0 {
+            message.push_str(&format!(
+                "\nLarge files streamed: {}",
+                format_count(stats.files_streamed as u64)
+            ));
+        }
+        if let Some(detected) = &detected {
+            message.push('\n');
+            message.push_str(&Self::format_detected_root(detected));
+        }
+        if let Some(source) = &source {
+            message.push_str(&format!(
+                "\nCloned: {} ({}, commit {})",
+                source.url,
+                source.ref_label(),
+                source.short_commit()
+            ));
+        }
+        if !stats.truncated_files.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&format_truncated_files(
+                &stats.truncated_files,
+                max_chunks_per_file,
+            ));
+        }
+        if stats.files_renamed > 0 {
+            message.push_str("\n\n");
+            message.push_str(&format_renamed_files(
+                &stats.renamed_files,
+                stats.files_renamed,
+            ));
+        }
+        message.push_str(&Self::format_skipped(&stats, req.verbose));
+        message.push_str(&Self::format_patterns(&stats));
+        message.push_str(&repo_settings);
+        if let Some(provenance) = &stats.provenance {
+            message.push_str("\n\nProvenance:\n");
+            message.push_str(&format_provenance(provenance));
+        }
+        if let Some(profile) = &stats.profile {
+            message.push_str("\n\n");
+            message.push_str(&format_index_profile(profile));
+        }
 
         Ok(text_content(message))
     }
@@ -0,0 +1,244 @@
+//! Background repository indexing tool handler
+//!
+//! Implements the index_repository_async MCP tool: same parameters as
+//! index_repository, but the work runs as a background job and the
+//! tool returns a job ID to poll with get_job_status.
+
+use super::handler::{text_content, McpToolHandler};
+use super::index_repository::{IndexRepositoryHandler, IndexRequest};
+use crate::core::jobs::{IndexJobRequest, IndexSource};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct IndexRepositoryAsyncHandler {
+    services: Arc<Services>,
+}
+
+impl IndexRepositoryAsyncHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for IndexRepositoryAsyncHandler {
+    fn name(&self) -> &str {
+        "index_repository_async"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "index_repository_async".to_string(),
+            description:
+                "Start indexing a repository in the BACKGROUND and return a job ID at once. \
+                         Takes the same parameters as index_repository (local path or git URL). \
+                         Use for large repositories (10,000+ files) or remote clones that would \
+                         outlast a request timeout; index_repository is simpler for small ones. \
+                         \
+                         Poll get_job_status with the job ID for progress (files processed, %) and \
+                         the final statistics; cancel_job stops the job between files and removes \
+                         the partial session. Only one job per session may run at a time, and jobs \
+                         beyond the server's max_concurrent_indexes wait in a queue."
+                    .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": IndexRepositoryHandler::index_properties(),
+                "required": ["path", "session"],
+                "additionalProperties": false
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        let req: IndexRequest = serde_json::from_value(args)
+            .map_err(|e| McpError::InvalidParams(format!("Invalid parameters: {e}")))?;
+        if req.dry_run {
+            return Err(McpError::InvalidParams(
+                "dry_run is not supported by index_repository_async; use index_repository"
+                    .to_string(),
+            ));
+        }
+        if req.profile {
+            return Err(McpError::InvalidParams(
+                "profile is not supported by index_repository_async; use index_repository"
+                    .to_string(),
+            ));
+        }
+
+        let local_path = IndexRepositoryHandler::validate_request(&self.services, &req)?;
+        let detected = local_path.as_ref().and_then(|root| root.detected.clone());
+        let source = match local_path {
+            Some(root) => IndexSource::Local(root.path),
+            None => IndexSource::Remote {
+                url: req.path.clone(),
+                git_ref: req.git_ref.clone(),
+            },
+        };
+        let request = IndexJobRequest {
+            session: req.session.clone(),
+            source,
+            overrides: req.overrides()?,
+            defaults: IndexRepositoryHandler::default_session_config(&self.services),
+            force: req.force,
+            allow_dangerous_path: req.allow_dangerous_path,
+        };
+        let label = request.source.label();
+        let id = self.services.start_index_job(request)?;
+
+        let root = detected
+            .map(|detected| {
+                format!(
+                    "{}\n",
+                    IndexRepositoryHandler::format_detected_root(&detected)
+                )
+            })
+            .unwrap_or_default();
+        Ok(text_content(format!(
+            "Indexing job started.\n\
+             Job ID: {id}\n\
+             Session: {}\n\
+             Source: {label}\n\
+             {root}\n\
+             Poll progress with get_job_status (job_id=\"{id}\"); stop it with cancel_job.",
+            req.session
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::jobs::{JobId, JobState};
+    use crate::mcp::protocol::INDEX_LOCKED;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (IndexRepositoryAsyncHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("sessions");
+        config.storage.clone_dir = temp_dir.path().join("clones");
+        let services = Arc::new(Services::new(config));
+        (IndexRepositoryAsyncHandler::new(services), temp_dir)
+    }
+
+    fn create_repo(temp: &TempDir) -> String {
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("main.rs"), "fn main() { start(); }").unwrap();
+        repo.to_string_lossy().into_owned()
+    }
+
+    fn job_id(result: &ToolResult) -> JobId {
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        let id = text
+            .lines()
+            .find_map(|line| line.strip_prefix("Job ID: "))
+            .expect("job ID in output");
+        JobId::new(id)
+    }
+
+    #[test]
+    fn test_schema_matches_index_repository() {
+        let (handler, _temp) = setup_test_handler();
+        let schema = handler.schema();
+        assert_eq!(schema.name, "index_repository_async");
+        let properties = &schema.input_schema["properties"];
+        assert!(properties["path"].is_object());
+        assert!(properties["ref"].is_object());
+        assert!(properties.get("dry_run").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_async_completes() {
+        let (handler, temp) = setup_test_handler();
+        let path = create_repo(&temp);
+
+        let result = handler
+            .execute(json!({"path": path, "session": "bg"}))
+            .await
+            .unwrap();
+        let id = job_id(&result);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let status = loop {
+            let status = handler.services.get_job_status(&id).unwrap();
+            if status.state.is_finished() {
+                break status;
+            }
+            assert!(Instant::now() < deadline, "job did not finish");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(status.state, JobState::Completed, "{:?}", status.error);
+        assert_eq!(status.stats.unwrap().files_indexed, 1);
+        assert!(handler.services.storage.session_exists("bg"));
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_async_validates_before_queueing() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler
+            .execute(json!({"path": "relative/path", "session": "bg"}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+
+        let result = handler
+            .execute(json!({"path": "/tmp", "session": "bg", "dry_run": true}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+
+        let result = handler
+            .execute(json!({"path": "/tmp", "session": "bg", "profile": true}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+        assert!(handler.services.list_jobs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_async_one_job_per_session() {
+        let (handler, temp) = setup_test_handler();
+        let path = create_repo(&temp);
+
+        // Hold the only indexing slot so the first job stays queued
+        let (release, gate) = std::sync::mpsc::channel::<()>();
+        handler
+            .services
+            .jobs
+            .start("other", "/elsewhere", move |_| {
+                gate.recv().ok();
+                Err(crate::core::error::ShebeError::IndexingFailed(
+                    "stopped".to_string(),
+                ))
+            })
+            .unwrap();
+
+        handler
+            .execute(json!({"path": path, "session": "bg"}))
+            .await
+            .unwrap();
+        let result = handler
+            .execute(json!({"path": path, "session": "bg"}))
+            .await;
+        match result {
+            Err(McpError::ToolError(code, msg)) => {
+                assert_eq!(code, INDEX_LOCKED);
+                assert!(msg.contains("background job"), "{msg}");
+            }
+            other => panic!("Expected ToolError, got: {other:?}"),
+        }
+
+        release.send(()).unwrap();
+    }
+}
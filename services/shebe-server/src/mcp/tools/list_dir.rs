@@ -1,26 +1,33 @@
 //! List directory (all files) tool handler
 
 use super::handler::{text_content, McpToolHandler};
+use super::helpers::{detect_language, format_bytes, path_display, run_blocking};
+use crate::core::cancel::{check_cancelled, CHECK_INTERVAL};
+use crate::core::display::PathDisplay;
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
 use crate::mcp::error::McpError;
 use crate::mcp::pagination::{session_fingerprint, ListDirCursor};
 use crate::mcp::protocol::{ToolResult, ToolSchema};
-use crate::mcp::utils::{build_list_dir_warning, LIST_DIR_DEFAULT_LIMIT, LIST_DIR_MAX_LIMIT};
+use crate::mcp::utils::{
+    build_list_dir_warning, LIST_DIR_COLUMNS_DEFAULT_LIMIT, LIST_DIR_DEFAULT_LIMIT,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
 use std::sync::Arc;
-use tantivy::collector::TopDocs;
-use tantivy::query::AllQuery;
-use tantivy::schema::Value as TantivyValue;
-use tantivy::TantivyDocument;
+use std::time::SystemTime;
+
+/// Characters of a file's first line shown in the `first_line` column
+const FIRST_LINE_MAX_CHARS: usize = 60;
 
 #[derive(Debug, Clone)]
 pub enum SortOrder {
-    Alpha,   // Alphabetical by path
-    Size,    // By file size (largest first)
-    Indexed, // By indexed order (insertion order)
+    Alpha,    // Alphabetical by path
+    Size,     // By file size (largest first)
+    Indexed,  // By indexed order (insertion order)
+    Modified, // By modification time (newest first)
 }
 
 impl SortOrder {
@@ -29,19 +36,98 @@ impl SortOrder {
             "alpha" => Ok(Self::Alpha),
             "size" => Ok(Self::Size),
             "indexed" => Ok(Self::Indexed),
+            "modified" => Ok(Self::Modified),
             _ => Err(format!(
                 "Invalid sort order: '{s}'. \
-                 Must be 'alpha', 'size' or 'indexed'."
+                 Must be 'alpha', 'size', 'indexed' or 'modified'."
             )),
         }
     }
 }
 
+/// Optional table columns after the file path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Chunks,
+    Size,
+    Language,
+    LastModified,
+    FirstLine,
+}
+
+impl Column {
+    const NAMES: [&'static str; 5] = ["chunks", "size", "language", "last_modified", "first_line"];
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "chunks" => Ok(Self::Chunks),
+            "size" => Ok(Self::Size),
+            "language" => Ok(Self::Language),
+            "last_modified" => Ok(Self::LastModified),
+            "first_line" => Ok(Self::FirstLine),
+            _ => Err(format!(
+                "Invalid column: '{s}'. Must be one of: {}.",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Chunks => "Chunks",
+            Self::Size => "Size",
+            Self::Language => "Language",
+            Self::LastModified => "Last Modified",
+            Self::FirstLine => "First Line",
+        }
+    }
+
+    /// Whether the column reads the file from disk
+    fn needs_stat(self) -> bool {
+        matches!(self, Self::Size | Self::LastModified)
+    }
+}
+
+/// Parse requested columns, dropping duplicates (default: chunks only)
+fn parse_columns(names: Option<&[String]>) -> Result<Vec<Column>, String> {
+    let Some(names) = names else {
+        return Ok(vec![Column::Chunks]);
+    };
+    let mut columns = Vec::new();
+    for name in names {
+        let column = Column::from_str(name)?;
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    Ok(columns)
+}
+
+/// First line of a chunk, cut to [`FIRST_LINE_MAX_CHARS`] characters
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim_end();
+    if line.chars().count() <= FIRST_LINE_MAX_CHARS {
+        return line.to_string();
+    }
+    let kept: String = line.chars().take(FIRST_LINE_MAX_CHARS - 3).collect();
+    format!("{kept}...")
+}
+
+/// Make text safe for a Markdown table cell
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
 #[derive(Debug, Clone)]
 struct FileEntry {
     path: String,
     chunk_count: usize,
-    size_bytes: u64,
+    /// Size on disk (`None` if not read or the file is gone)
+    size_bytes: Option<u64>,
+    /// Modification time (`None` if not read or the file is gone)
+    modified: Option<SystemTime>,
+    /// Text of the lowest-numbered indexed chunk (read for the shown page)
+    first_chunk: Option<String>,
 }
 
 pub struct ListDirHandler {
@@ -53,89 +139,91 @@ impl ListDirHandler {
         Self { services }
     }
 
-    /// Get unique file paths from Tantivy index
-    async fn get_file_list(
-        &self,
+    /// Every indexed file with its chunk count, sorted
+    ///
+    /// Blocking; stops early when the call is cancelled.
+    fn get_file_list(
+        services: &Services,
         session: &str,
         sort: SortOrder,
+        columns: &[Column],
     ) -> Result<Vec<FileEntry>, McpError> {
-        // Open session index
-        let index = self
-            .services
+        // From the session's file manifest (in indexed order)
+        let mut files: Vec<FileEntry> = services
             .storage
-            .open_session(session)
-            .map_err(McpError::from)?;
-
-        let reader = index
-            .index()
-            .reader()
-            .map_err(|e| McpError::InternalError(format!("Failed to open reader: {e}")))?;
-
-        let searcher = reader.searcher();
+            .session_files(session)
+            .map_err(McpError::from)?
+            .files
+            .into_iter()
+            .map(|file| FileEntry {
+                path: file.path,
+                chunk_count: file.chunk_count,
+                size_bytes: None,
+                modified: None,
+                first_chunk: None,
+            })
+            .collect();
 
-        // Query all documents
-        let query = AllQuery;
-
-        // Collect unique file_path values
-        let file_path_field = index
-            .schema()
-            .get_field("file_path")
-            .map_err(|e| McpError::InternalError(format!("file_path field missing: {e}")))?;
-
-        let mut file_map: HashMap<String, FileEntry> = HashMap::new();
-
-        // Collect documents (we need to aggregate by file_path)
-        let top_docs = searcher
-            .search(&query, &TopDocs::with_limit(100000))
-            .map_err(|e| McpError::InternalError(format!("Search failed: {e}")))?;
-
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc: TantivyDocument = searcher
-                .doc(doc_address)
-                .map_err(|e| McpError::InternalError(format!("Doc retrieval failed: {e}")))?;
-
-            // Extract file_path
-            let file_path = retrieved_doc
-                .get_first(file_path_field)
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| McpError::InternalError("Missing file_path".to_string()))?
-                .to_string();
-
-            // Track unique files
-            file_map
-                .entry(file_path.clone())
-                .or_insert_with(|| FileEntry {
-                    path: file_path,
-                    chunk_count: 0,
-                    size_bytes: 0, // Will populate if sort=size
-                })
-                .chunk_count += 1;
+        // Stat files for size/time sorts and columns (missing files keep None)
+        let needs_stat = matches!(sort, SortOrder::Size | SortOrder::Modified)
+            || columns.iter().any(|c| c.needs_stat());
+        if needs_stat {
+            for (i, entry) in files.iter_mut().enumerate() {
+                if i % CHECK_INTERVAL == 0 {
+                    check_cancelled()?;
+                }
+                if let Ok(metadata) = std::fs::metadata(&entry.path) {
+                    entry.size_bytes = Some(metadata.len());
+                    entry.modified = metadata.modified().ok();
+                }
+            }
         }
 
-        // Convert to vec
-        let mut files: Vec<FileEntry> = file_map.into_values().collect();
-
         // Sort by requested order
         match sort {
             SortOrder::Alpha => files.sort_by(|a, b| a.path.cmp(&b.path)),
             SortOrder::Size => {
-                // Need to stat files for size
-                for entry in &mut files {
-                    if let Ok(metadata) = std::fs::metadata(&entry.path) {
-                        entry.size_bytes = metadata.len();
-                    }
-                }
-                files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+                files.sort_by_key(|f| std::cmp::Reverse(f.size_bytes.unwrap_or(0)));
+            }
+            SortOrder::Modified => {
+                // Newest first; files that are gone sort last
+                files.sort_by(|a, b| b.modified.cmp(&a.modified).then(a.path.cmp(&b.path)));
             }
             SortOrder::Indexed => {
-                // Keep insertion order (effectively indexed order)
+                // Keep the manifest's order
             }
         }
 
         Ok(files)
     }
 
+    /// Fill in the first chunk's text of each file
+    ///
+    /// Blocking; files keep `None` in no-store sessions.
+    fn read_first_chunks(
+        services: &Services,
+        session: &str,
+        mut files: Vec<FileEntry>,
+    ) -> Result<Vec<FileEntry>, McpError> {
+        let reader = services
+            .storage
+            .session_reader(session)
+            .map_err(McpError::from)?;
+        let searcher = reader.searcher();
+        for (i, entry) in files.iter_mut().enumerate() {
+            if i % CHECK_INTERVAL == 0 {
+                check_cancelled()?;
+            }
+            entry.first_chunk = reader
+                .index()
+                .first_chunk_text(&searcher, &entry.path)
+                .map_err(McpError::from)?;
+        }
+        Ok(files)
+    }
+
     /// Format file list as Markdown with pagination info
+    #[allow(clippy::too_many_arguments)]
     fn format_file_list(
         &self,
         session: &str,
@@ -143,30 +231,77 @@ impl ListDirHandler {
         total: usize,
         range_start: usize,
         range_end: usize,
+        display: &PathDisplay,
+        columns: &[Column],
+        reduced_page: Option<usize>,
     ) -> String {
         let mut output = format!(
             "**Session:** `{}`\n\
-             **Files:** {} (showing {}-{})\n\n",
+             **Files:** {} (showing {}-{})\n",
             session,
             total,
             range_start + 1,
             range_end,
         );
+        if let Some(page) = reduced_page {
+            output.push_str(&format!(
+                "**Page size:** {page} (default lowered for extra columns; \
+                 set limit for more)\n"
+            ));
+        }
+        output.push('\n');
 
         if files.is_empty() {
             output.push_str("No files found in this session.");
             return output;
         }
 
-        output.push_str("| File Path | Chunks |\n");
-        output.push_str("|-----------|--------|\n");
+        output.push_str("| File Path |");
+        for column in columns {
+            output.push_str(&format!(" {} |", column.header()));
+        }
+        output.push_str("\n|-----------|");
+        for column in columns {
+            output.push_str(&format!("{}|", "-".repeat(column.header().len() + 2)));
+        }
+        output.push('\n');
 
         for entry in files {
-            output.push_str(&format!("| `{}` | {} |\n", entry.path, entry.chunk_count));
+            output.push_str(&format!(
+                "| {} |",
+                display.markdown(&entry.path, &display.path(&entry.path), None, None)
+            ));
+            for column in columns {
+                output.push_str(&format!(" {} |", Self::cell(entry, *column)));
+            }
+            output.push('\n');
         }
 
         output
     }
+
+    /// Value of one optional column ("-" when unavailable)
+    fn cell(entry: &FileEntry, column: Column) -> String {
+        let value = match column {
+            Column::Chunks => Some(entry.chunk_count.to_string()),
+            Column::Size => entry.size_bytes.map(format_bytes),
+            Column::Language => Some(detect_language(&entry.path))
+                .filter(|language| !language.is_empty())
+                .map(str::to_string),
+            Column::LastModified => entry.modified.map(|modified| {
+                DateTime::<Utc>::from(modified)
+                    .format("%Y-%m-%d %H:%M UTC")
+                    .to_string()
+            }),
+            Column::FirstLine => entry
+                .first_chunk
+                .as_ref()
+                .map(|text| first_line(text))
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| table_cell(&line)),
+        };
+        value.unwrap_or_else(|| "-".to_string())
+    }
 }
 
 #[async_trait]
@@ -183,10 +318,11 @@ impl McpToolHandler for ListDirHandler {
                 no filtering. Use when you want to see all files in \
                 a session. For pattern-based search, use find_file \
                 instead. Returns list sorted alphabetically by \
-                default. Auto-truncates to 500 files max to stay \
-                under MCP 25k token limit (shows warning if \
-                truncated). Supports cursor-based pagination for \
-                navigating large file lists."
+                default. Auto-truncates to 500 files max \
+                (limits.list_dir_max) to stay under MCP 25k token \
+                limit (shows warning if truncated). Supports \
+                cursor-based pagination for navigating large file \
+                lists."
                 .to_string(),
             input_schema: json!({
                 "type": "object",
@@ -195,30 +331,74 @@ impl McpToolHandler for ListDirHandler {
                         "type": "string",
                         "description":
                             "Session ID to list files from",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "limit": {
                         "type": "integer",
                         "description":
                             "Max files to return \
-                             (default: 100, max: 500)",
+                             (default: 100, max: limits.list_dir_max, \
+                             500 unless configured)",
                         "default": 100,
-                        "minimum": 1,
-                        "maximum": 500
+                        "minimum": 1
                     },
                     "sort": {
                         "type": "string",
                         "description":
                             "Sort order: 'alpha' (default), \
-                             'size', 'indexed'",
+                             'size', 'indexed', or 'modified' \
+                             (newest first; requires the \
+                             last_modified column)",
                         "default": "alpha",
-                        "enum": ["alpha", "size", "indexed"]
+                        "enum": ["alpha", "size", "indexed", "modified"]
+                    },
+                    "columns": {
+                        "type": "array",
+                        "items": {
+                            "type": "string",
+                            "enum": Column::NAMES
+                        },
+                        "description":
+                            "Columns to show after the path, in \
+                             order (default: ['chunks']). size and \
+                             last_modified come from disk ('-' if \
+                             the file is gone); first_line is the \
+                             start of the first indexed chunk. \
+                             Extra columns lower the default page \
+                             size to 40."
                     },
                     "cursor": {
                         "type": "string",
                         "description":
                             "Pagination cursor from previous \
                              response. Omit for first page."
+                    },
+                    "link_format": {
+                        "type": "string",
+                        "description":
+                            "Template for an \"open in editor\" \
+                             link on each file, using {path} \
+                             (e.g. 'file://{path}'). 'none' \
+                             disables links. Default: server \
+                             output.link_format (none)."
+                    },
+                    "relative_paths": {
+                        "type": "boolean",
+                        "description":
+                            "If true, show paths relative to \
+                             the session's repository path \
+                             (paths outside it stay absolute). \
+                             Default: server \
+                             output.relative_paths (false)."
+                    },
+                    "path": {
+                        "type": "string",
+                        "description":
+                            "Absolute path of the repository \
+                             the session covers (optional). \
+                             Only used when no sessions exist \
+                             yet: the error then suggests an \
+                             index_repository call for it."
                     }
                 },
                 "required": ["session"]
@@ -235,6 +415,9 @@ impl McpToolHandler for ListDirHandler {
             #[serde(default = "default_sort")]
             sort: String,
             cursor: Option<String>,
+            link_format: Option<String>,
+            relative_paths: Option<bool>,
+            columns: Option<Vec<String>>,
         }
         fn default_limit() -> Option<usize> {
             None
@@ -249,18 +432,32 @@ impl McpToolHandler for ListDirHandler {
 
         // Parse sort order
         let sort = SortOrder::from_str(&args.sort).map_err(McpError::InvalidParams)?;
+        let columns = parse_columns(args.columns.as_deref()).map_err(McpError::InvalidParams)?;
+        if matches!(sort, SortOrder::Modified) && !columns.contains(&Column::LastModified) {
+            return Err(McpError::InvalidParams(
+                "Sort order 'modified' requires the last_modified column \
+                 (columns: [\"last_modified\"])."
+                    .to_string(),
+            ));
+        }
 
-        // Determine effective limit
-        let effective_limit = match args.limit {
-            Some(requested) => {
-                if requested > LIST_DIR_MAX_LIMIT {
-                    LIST_DIR_MAX_LIMIT
-                } else {
-                    requested
-                }
-            }
-            None => LIST_DIR_DEFAULT_LIMIT,
+        let display = path_display(
+            &self.services,
+            &args.session,
+            args.link_format.as_deref(),
+            args.relative_paths,
+        )?;
+
+        // Determine effective limit (the maximum can change on reload)
+        let max_limit = self.services.config.current().limits.list_dir_max;
+        let extra_columns = columns.iter().any(|c| *c != Column::Chunks);
+        let default_limit = if extra_columns {
+            LIST_DIR_COLUMNS_DEFAULT_LIMIT
+        } else {
+            LIST_DIR_DEFAULT_LIMIT
         };
+        let effective_limit = args.limit.unwrap_or(default_limit).min(max_limit);
+        let reduced_page = (extra_columns && args.limit.is_none()).then_some(effective_limit);
 
         // Determine start index from cursor
         let start_index = if let Some(ref cursor_str) = args.cursor {
@@ -291,18 +488,30 @@ impl McpToolHandler for ListDirHandler {
         };
 
         // Get all files from index
-        let all_files = self.get_file_list(&args.session, sort).await?;
+        let services = Arc::clone(&self.services);
+        let (session, list_columns) = (args.session.clone(), columns.clone());
+        let all_files =
+            run_blocking(move || Self::get_file_list(&services, &session, sort, &list_columns))
+                .await?;
         let total_count = all_files.len();
 
         // Compute page slice
         let page_end = (start_index + effective_limit).min(total_count);
-        let page_files = if start_index < total_count {
-            &all_files[start_index..page_end]
-        } else {
-            &[]
-        };
+        let mut page_files = all_files
+            .get(start_index..page_end)
+            .unwrap_or_default()
+            .to_vec();
         let shown_count = page_files.len();
 
+        // Chunk text is only read for the files shown
+        if columns.contains(&Column::FirstLine) {
+            let services = Arc::clone(&self.services);
+            let session = args.session.clone();
+            page_files =
+                run_blocking(move || Self::read_first_chunks(&services, &session, page_files))
+                    .await?;
+        }
+
         // Check if there are more results after this page
         let has_more = page_end < total_count;
 
@@ -314,6 +523,7 @@ impl McpToolHandler for ListDirHandler {
             let warning = build_list_dir_warning(
                 effective_limit.min(total_count),
                 total_count,
+                max_limit,
                 &args.session,
             );
             output.push_str(&warning);
@@ -322,10 +532,13 @@ impl McpToolHandler for ListDirHandler {
         // Add file list with range info
         let formatted = self.format_file_list(
             &args.session,
-            page_files,
+            &page_files,
             total_count,
             start_index,
             start_index + shown_count,
+            &display,
+            &columns,
+            reduced_page,
         );
         output.push_str(&formatted);
 
@@ -376,6 +589,7 @@ mod tests {
         (handler, temp_dir)
     }
 
+    /// Index `files`, relative to a repository in the test's temp dir
     async fn create_test_session_with_files(
         services: &Arc<Services>,
         session_id: &str,
@@ -391,7 +605,7 @@ mod tests {
             .unwrap();
 
         for (file_path, content) in files {
-            let full_path = PathBuf::from(file_path);
+            let full_path = repo_file(services, file_path);
             if let Some(parent) = full_path.parent() {
                 let _ = fs::create_dir_all(parent);
             }
@@ -404,6 +618,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: content.len(),
                 chunk_index: 0,
+                records: None,
+                section: None,
             }];
 
             index.add_chunks(&chunks, session_id).unwrap();
@@ -412,6 +628,11 @@ mod tests {
         index.commit().unwrap();
     }
 
+    /// Path of `file` in the test repository, beside the session storage
+    fn repo_file(services: &Services, file: &str) -> PathBuf {
+        services.storage.storage_root().join("repo").join(file)
+    }
+
     /// Helper to extract text from ToolResult
     fn extract_text(result: &ToolResult) -> &str {
         match &result.content[0] {
@@ -426,9 +647,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test-a.rs", "fn main() {}"),
-                ("/tmp/shebe-test-b.rs", "fn test() {}"),
-                ("/tmp/shebe-test-c.rs", "fn run() {}"),
+                ("shebe-test-a.rs", "fn main() {}"),
+                ("shebe-test-b.rs", "fn test() {}"),
+                ("shebe-test-c.rs", "fn run() {}"),
             ],
         )
         .await;
@@ -445,14 +666,9 @@ mod tests {
 
         assert!(text.contains("**Session:** `test-session`"));
         assert!(text.contains("**Files:** 3 (showing 1-3)"));
-        assert!(text.contains("/tmp/shebe-test-a.rs"));
-        assert!(text.contains("/tmp/shebe-test-b.rs"));
-        assert!(text.contains("/tmp/shebe-test-c.rs"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test-a.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-b.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-c.rs");
+        assert!(text.contains("shebe-test-a.rs"));
+        assert!(text.contains("shebe-test-b.rs"));
+        assert!(text.contains("shebe-test-c.rs"));
     }
 
     #[tokio::test]
@@ -462,9 +678,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test-1.rs", "fn test1() {}"),
-                ("/tmp/shebe-test-2.rs", "fn test2() {}"),
-                ("/tmp/shebe-test-3.rs", "fn test3() {}"),
+                ("shebe-test-1.rs", "fn test1() {}"),
+                ("shebe-test-2.rs", "fn test2() {}"),
+                ("shebe-test-3.rs", "fn test3() {}"),
             ],
         )
         .await;
@@ -481,11 +697,6 @@ mod tests {
         let text = extract_text(&tool_result);
 
         assert!(text.contains("(showing 1-2)"));
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test-1.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-2.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-3.rs");
     }
 
     #[tokio::test]
@@ -495,9 +706,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test-z.rs", "fn z() {}"),
-                ("/tmp/shebe-test-a.rs", "fn a() {}"),
-                ("/tmp/shebe-test-m.rs", "fn m() {}"),
+                ("shebe-test-z.rs", "fn z() {}"),
+                ("shebe-test-a.rs", "fn a() {}"),
+                ("shebe-test-m.rs", "fn m() {}"),
             ],
         )
         .await;
@@ -518,11 +729,6 @@ mod tests {
         let m_pos = text.find("shebe-test-m.rs").unwrap();
         let z_pos = text.find("shebe-test-z.rs").unwrap();
         assert!(a_pos < m_pos && m_pos < z_pos);
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test-z.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-a.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-m.rs");
     }
 
     #[tokio::test]
@@ -532,15 +738,9 @@ mod tests {
             &handler.services,
             "test-session",
             vec![
-                ("/tmp/shebe-test-small.rs", "fn test() {}"),
-                (
-                    "/tmp/shebe-test-large.rs",
-                    "fn test() {}\n".repeat(100).as_str(),
-                ),
-                (
-                    "/tmp/shebe-test-medium.rs",
-                    "fn test() {}\n".repeat(10).as_str(),
-                ),
+                ("shebe-test-small.rs", "fn test() {}"),
+                ("shebe-test-large.rs", "fn test() {}\n".repeat(100).as_str()),
+                ("shebe-test-medium.rs", "fn test() {}\n".repeat(10).as_str()),
             ],
         )
         .await;
@@ -561,11 +761,6 @@ mod tests {
         let medium_pos = text.find("shebe-test-medium.rs").unwrap();
         let small_pos = text.find("shebe-test-small.rs").unwrap();
         assert!(large_pos < medium_pos && medium_pos < small_pos);
-
-        // Cleanup
-        let _ = fs::remove_file("/tmp/shebe-test-small.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-large.rs");
-        let _ = fs::remove_file("/tmp/shebe-test-medium.rs");
     }
 
     #[tokio::test]
@@ -606,7 +801,7 @@ mod tests {
 
         // Create 150 files
         let files: Vec<_> = (0..150)
-            .map(|i| (format!("/tmp/shebe-test-{:03}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-test-{:03}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -624,11 +819,6 @@ mod tests {
         let text = extract_text(&tool_result);
 
         assert!(text.contains("(showing 1-100)"));
-
-        // Cleanup
-        for i in 0..150 {
-            let _ = fs::remove_file(format!("/tmp/shebe-test-{:03}.rs", i));
-        }
     }
 
     // Truncation tests
@@ -641,7 +831,7 @@ mod tests {
 
         // Create 200 files (more than default limit of 100)
         let files: Vec<_> = (0..200)
-            .map(|i| (format!("/tmp/shebe-truncate-{:03}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-truncate-{:03}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -664,16 +854,11 @@ mod tests {
         assert!(text.contains("200")); // total files
 
         // Count actual file entries (should be exactly 100)
-        let file_count = text.matches("| `/tmp/shebe-truncate-").count();
+        let file_count = text.matches("shebe-truncate-").count();
         assert_eq!(file_count, LIST_DIR_DEFAULT_LIMIT);
 
         // Should have a next-page cursor
         assert!(text.contains("cursor="));
-
-        // Cleanup
-        for i in 0..200 {
-            let _ = fs::remove_file(format!("/tmp/shebe-truncate-{:03}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -684,7 +869,7 @@ mod tests {
 
         // Create 600 files (more than max limit of 500)
         let files: Vec<_> = (0..600)
-            .map(|i| (format!("/tmp/shebe-maxlimit-{:03}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-maxlimit-{:03}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -704,17 +889,12 @@ mod tests {
         let text = extract_text(&tool_result);
 
         // Should enforce max limit of 500
-        let file_count = text.matches("| `/tmp/shebe-maxlimit-").count();
+        let file_count = text.matches("shebe-maxlimit-").count();
         assert_eq!(file_count, LIST_DIR_MAX_LIMIT);
 
         // Should show warning
         assert!(text.contains("OUTPUT TRUNCATED"));
         assert!(text.contains("MAXIMUM 500 FILES"));
-
-        // Cleanup
-        for i in 0..600 {
-            let _ = fs::remove_file(format!("/tmp/shebe-maxlimit-{:03}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -723,7 +903,7 @@ mod tests {
 
         // Repository with fewer files than default limit
         let files: Vec<_> = (0..50)
-            .map(|i| (format!("/tmp/shebe-small-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-small-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -740,16 +920,11 @@ mod tests {
         assert!(!text.contains("OUTPUT TRUNCATED"));
 
         // Should show all 50 files
-        let file_count = text.matches("| `/tmp/shebe-small-").count();
+        let file_count = text.matches("shebe-small-").count();
         assert_eq!(file_count, 50);
 
         // Should NOT have a cursor (all files shown)
         assert!(!text.contains("cursor="));
-
-        // Cleanup
-        for i in 0..50 {
-            let _ = fs::remove_file(format!("/tmp/shebe-small-{:02}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -758,7 +933,7 @@ mod tests {
 
         // Create 400 files
         let files: Vec<_> = (0..400)
-            .map(|i| (format!("/tmp/shebe-userlimit-{:03}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-userlimit-{:03}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -777,17 +952,12 @@ mod tests {
         let text = extract_text(&tool_result);
 
         // Should show exactly 250 files
-        let file_count = text.matches("| `/tmp/shebe-userlimit-").count();
+        let file_count = text.matches("shebe-userlimit-").count();
         assert_eq!(file_count, 250);
 
         // Should show warning (400 total > 250 shown)
         assert!(text.contains("OUTPUT TRUNCATED"));
         assert!(text.contains("250 of 400"));
-
-        // Cleanup
-        for i in 0..400 {
-            let _ = fs::remove_file(format!("/tmp/shebe-userlimit-{:03}.rs", i));
-        }
     }
 
     // Pagination cursor tests
@@ -798,7 +968,7 @@ mod tests {
 
         // Create 5 files
         let files: Vec<_> = (0..5)
-            .map(|i| (format!("/tmp/shebe-page-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-page-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -824,11 +994,6 @@ mod tests {
         // Should have next cursor
         assert!(text.contains("cursor="));
         assert!(text.contains("More results available"));
-
-        // Cleanup
-        for i in 0..5 {
-            let _ = fs::remove_file(format!("/tmp/shebe-page-{:02}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -837,7 +1002,7 @@ mod tests {
 
         // Create 5 files
         let files: Vec<_> = (0..5)
-            .map(|i| (format!("/tmp/shebe-page2-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-page2-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -876,11 +1041,6 @@ mod tests {
 
         // Should NOT have truncation warning (cursor page)
         assert!(!text.contains("OUTPUT TRUNCATED"));
-
-        // Cleanup
-        for i in 0..5 {
-            let _ = fs::remove_file(format!("/tmp/shebe-page2-{:02}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -889,7 +1049,7 @@ mod tests {
 
         // Create 5 files
         let files: Vec<_> = (0..5)
-            .map(|i| (format!("/tmp/shebe-lastpg-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-lastpg-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -925,11 +1085,6 @@ mod tests {
 
         // Should NOT have cursor (last page)
         assert!(!text.contains("cursor="));
-
-        // Cleanup
-        for i in 0..5 {
-            let _ = fs::remove_file(format!("/tmp/shebe-lastpg-{:02}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -975,7 +1130,7 @@ mod tests {
 
         // Create session with files
         let files: Vec<_> = (0..3)
-            .map(|i| (format!("/tmp/shebe-stale-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-stale-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1008,11 +1163,6 @@ mod tests {
                 panic!("Expected InvalidParams, got: {:?}", other);
             }
         }
-
-        // Cleanup
-        for i in 0..3 {
-            let _ = fs::remove_file(format!("/tmp/shebe-stale-{:02}.rs", i));
-        }
     }
 
     #[tokio::test]
@@ -1021,7 +1171,7 @@ mod tests {
 
         // Create session
         let files: Vec<_> = (0..3)
-            .map(|i| (format!("/tmp/shebe-sortmm-{:02}.rs", i), "fn test() {}"))
+            .map(|i| (format!("shebe-sortmm-{:02}.rs", i), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1060,11 +1210,6 @@ mod tests {
                 panic!("Expected InvalidParams, got: {:?}", other);
             }
         }
-
-        // Cleanup
-        for i in 0..3 {
-            let _ = fs::remove_file(format!("/tmp/shebe-sortmm-{:02}.rs", i));
-        }
     }
 
     // -- Phase 2 helpers --------------------------------------------------
@@ -1101,9 +1246,9 @@ mod tests {
             &handler.services,
             "compat-session",
             vec![
-                ("/tmp/shebe-compat-a.rs", "fn a() {}"),
-                ("/tmp/shebe-compat-b.rs", "fn b() {}"),
-                ("/tmp/shebe-compat-c.rs", "fn c() {}"),
+                ("shebe-compat-a.rs", "fn a() {}"),
+                ("shebe-compat-b.rs", "fn b() {}"),
+                ("shebe-compat-c.rs", "fn c() {}"),
             ],
         )
         .await;
@@ -1125,11 +1270,6 @@ mod tests {
         assert!(!text.contains("cursor="));
         assert!(!text.contains("More results available"));
         assert!(!text.contains("nextCursor"));
-
-        // Cleanup
-        for s in &["a", "b", "c"] {
-            let _ = fs::remove_file(format!("/tmp/shebe-compat-{s}.rs"));
-        }
     }
 
     /// P1 Boundary: Single-file session needs no pagination.
@@ -1139,7 +1279,7 @@ mod tests {
         create_test_session_with_files(
             &handler.services,
             "single-session",
-            vec![("/tmp/shebe-single-a.rs", "fn main() {}")],
+            vec![("shebe-single-a.rs", "fn main() {}")],
         )
         .await;
 
@@ -1156,8 +1296,6 @@ mod tests {
         assert!(text.contains("**Files:** 1"));
         assert!(!text.contains("cursor="));
         assert!(!text.contains("More results available"));
-
-        let _ = fs::remove_file("/tmp/shebe-single-a.rs");
     }
 
     /// P1 Boundary: limit=1 pages through all files one at a time.
@@ -1167,7 +1305,7 @@ mod tests {
         let (handler, _temp) = setup_test_handler().await;
 
         let files: Vec<_> = (0..5)
-            .map(|i| (format!("/tmp/shebe-lim1-{i:02}.rs"), "fn test() {}"))
+            .map(|i| (format!("shebe-lim1-{i:02}.rs"), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1203,10 +1341,6 @@ mod tests {
 
         assert_eq!(pages, 5, "5 files at limit=1 needs 5 pages");
         assert_eq!(collected.len(), 5);
-
-        for i in 0..5 {
-            let _ = fs::remove_file(format!("/tmp/shebe-lim1-{i:02}.rs"));
-        }
     }
 
     /// P1 Boundary: Exact divisibility -- last page is full, no
@@ -1218,7 +1352,7 @@ mod tests {
         let total = 500_usize;
         let limit = 100_usize;
         let files: Vec<_> = (0..total)
-            .map(|i| (format!("/tmp/shebe-exact-{i:03}.rs"), "fn test() {}"))
+            .map(|i| (format!("shebe-exact-{i:03}.rs"), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1258,10 +1392,6 @@ mod tests {
             !text.contains("cursor="),
             "Last page of exact-divisible set must not have cursor"
         );
-
-        for i in 0..total {
-            let _ = fs::remove_file(format!("/tmp/shebe-exact-{i:03}.rs"));
-        }
     }
 
     /// P1 Boundary: Limit larger than total files returns all
@@ -1272,7 +1402,7 @@ mod tests {
 
         let total = 50_usize;
         let files: Vec<_> = (0..total)
-            .map(|i| (format!("/tmp/shebe-exceed-{i:02}.rs"), "fn test() {}"))
+            .map(|i| (format!("shebe-exceed-{i:02}.rs"), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1292,10 +1422,6 @@ mod tests {
         assert!(text.contains("**Files:** 50"));
         assert_eq!(extract_file_paths(text).len(), total);
         assert!(!text.contains("cursor="));
-
-        for i in 0..total {
-            let _ = fs::remove_file(format!("/tmp/shebe-exceed-{i:02}.rs"));
-        }
     }
 
     /// P2 Beyond: Empty session (0 files) with no cursor returns
@@ -1337,7 +1463,7 @@ mod tests {
 
         let total = 250_usize;
         let files: Vec<_> = (0..total)
-            .map(|i| (format!("/tmp/shebe-noovlp-{i:03}.rs"), "fn test() {}"))
+            .map(|i| (format!("shebe-noovlp-{i:03}.rs"), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1376,10 +1502,6 @@ mod tests {
 
         // No gaps: total matches session file count
         assert_eq!(all_files.len(), total);
-
-        for i in 0..total {
-            let _ = fs::remove_file(format!("/tmp/shebe-noovlp-{i:03}.rs"));
-        }
     }
 
     /// P1 Center: Sort order maintained at page boundaries.
@@ -1389,7 +1511,7 @@ mod tests {
         let (handler, _temp) = setup_test_handler().await;
 
         let files: Vec<_> = (0..10)
-            .map(|i| (format!("/tmp/shebe-sortpg-{i:02}.rs"), "fn test() {}"))
+            .map(|i| (format!("shebe-sortpg-{i:02}.rs"), "fn test() {}"))
             .collect();
         let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
 
@@ -1430,9 +1552,234 @@ mod tests {
                 i + 1
             );
         }
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_relative_paths_with_links() {
+        let (handler, _temp) = setup_test_handler().await;
+        let mut index = handler
+            .services
+            .storage
+            .create_session(
+                "display-session",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        let chunks: Vec<Chunk> = ["/test/repo/src/lib.rs", "/test/repo2/other.rs"]
+            .iter()
+            .map(|path| Chunk {
+                text: "fn f() {}".to_string(),
+                file_path: PathBuf::from(path),
+                start_offset: 0,
+                end_offset: 9,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            })
+            .collect();
+        index.add_chunks(&chunks, "display-session").unwrap();
+        index.commit().unwrap();
+
+        let args = json!({
+            "session": "display-session",
+            "link_format": "file://{path}",
+            "relative_paths": true
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("| [`src/lib.rs`](file:///test/repo/src/lib.rs) | 1 |"));
+        // A sibling directory sharing the repository's name prefix stays absolute
+        assert!(text.contains("| [`/test/repo2/other.rs`](file:///test/repo2/other.rs) | 1 |"));
+
+        let args = json!({
+            "session": "display-session",
+            "link_format": "none"
+        });
+        let result = handler.execute(args).await.unwrap();
+        assert!(extract_text(&result).contains("| `/test/repo/src/lib.rs` | 1 |"));
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_invalid_link_format() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let args = json!({
+            "session": "any-session",
+            "link_format": "file://{path}{nope}"
+        });
+        let result = handler.execute(args).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+
+    // -- Optional columns ----------------------------------------------------
+
+    #[tokio::test]
+    async fn test_list_dir_default_columns_unchanged() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session_with_files(
+            &handler.services,
+            "cols-default",
+            vec![("shebe-cols-default.rs", "fn main() {}")],
+        )
+        .await;
+
+        let result = handler
+            .execute(json!({"session": "cols-default"}))
+            .await
+            .unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("| File Path | Chunks |\n|-----------|--------|\n"));
+        let path = repo_file(&handler.services, "shebe-cols-default.rs");
+        assert!(text.contains(&format!("| `{}` | 1 |", path.display())));
+        assert!(!text.contains("Page size"));
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_columns_from_disk_with_deleted_file() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session_with_files(
+            &handler.services,
+            "cols-disk",
+            vec![
+                ("shebe-cols-kept.py", "print('hello')\n"),
+                ("shebe-cols-gone.rs", "fn gone() {}"),
+            ],
+        )
+        .await;
+        fs::remove_file(repo_file(&handler.services, "shebe-cols-gone.rs")).unwrap();
+
+        let args = json!({
+            "session": "cols-disk",
+            "columns": ["size", "language", "last_modified", "chunks"]
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("| File Path | Size | Language | Last Modified | Chunks |"));
+        let kept = text
+            .lines()
+            .find(|line| line.contains("shebe-cols-kept.py"))
+            .unwrap();
+        assert!(kept.contains("| 15 B | python | "), "{kept}");
+        assert!(kept.ends_with(" UTC | 1 |"), "{kept}");
+        // Size and date fall back to "-"; the language comes from the path
+        let gone = repo_file(&handler.services, "shebe-cols-gone.rs");
+        assert!(text.contains(&format!("| `{}` | - | rust | - | 1 |", gone.display())));
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_first_line_column_is_utf8_safe() {
+        let (handler, _temp) = setup_test_handler().await;
+        let emoji_line = format!("// {} done\nfn second() {{}}", "🦀".repeat(70));
+        create_test_session_with_files(
+            &handler.services,
+            "cols-first-line",
+            vec![
+                ("shebe-cols-emoji.rs", emoji_line.as_str()),
+                ("shebe-cols-pipe.rs", "let x = a | b;\nfn rest() {}"),
+                ("shebe-cols-unknown.xyz", "\n"),
+            ],
+        )
+        .await;
+
+        let args = json!({
+            "session": "cols-first-line",
+            "columns": ["language", "first_line"]
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = extract_text(&result);
+
+        let path = |file| repo_file(&handler.services, file).display().to_string();
+        let expected = format!("// {}...", "🦀".repeat(54));
+        assert_eq!(expected.chars().count(), FIRST_LINE_MAX_CHARS);
+        assert!(
+            text.contains(&format!(
+                "| `{}` | rust | {expected} |",
+                path("shebe-cols-emoji.rs")
+            )),
+            "{text}"
+        );
+        assert!(text.contains(&format!(
+            "| `{}` | rust | let x = a \\| b; |",
+            path("shebe-cols-pipe.rs")
+        )));
+        assert!(text.contains(&format!("| `{}` | - | - |", path("shebe-cols-unknown.xyz"))));
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_columns_lower_default_page_size() {
+        let (handler, _temp) = setup_test_handler().await;
+        let files: Vec<_> = (0..45)
+            .map(|i| (format!("shebe-cols-page-{i:02}.rs"), "fn test() {}"))
+            .collect();
+        let file_refs: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        create_test_session_with_files(&handler.services, "cols-page", file_refs).await;
 
-        for i in 0..10 {
-            let _ = fs::remove_file(format!("/tmp/shebe-sortpg-{i:02}.rs"));
+        let args = json!({"session": "cols-page", "columns": ["language"]});
+        let result = handler.execute(args).await.unwrap();
+        let text = extract_text(&result);
+        assert!(text.contains(&format!("(showing 1-{LIST_DIR_COLUMNS_DEFAULT_LIMIT})")));
+        assert!(text.contains("**Page size:** 40 (default lowered for extra columns"));
+
+        // An explicit limit is used as given
+        let args = json!({"session": "cols-page", "columns": ["language"], "limit": 45});
+        let result = handler.execute(args).await.unwrap();
+        let text = extract_text(&result);
+        assert!(text.contains("(showing 1-45)"));
+        assert!(!text.contains("Page size"));
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_sort_modified() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session_with_files(
+            &handler.services,
+            "cols-modified",
+            vec![
+                ("shebe-cols-old.rs", "fn old() {}"),
+                ("shebe-cols-new.rs", "fn new() {}"),
+            ],
+        )
+        .await;
+        let hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(repo_file(&handler.services, "shebe-cols-old.rs"))
+            .unwrap()
+            .set_modified(hour_ago)
+            .unwrap();
+
+        // The sort needs the column
+        let args = json!({"session": "cols-modified", "sort": "modified"});
+        let result = handler.execute(args).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+
+        let args = json!({
+            "session": "cols-modified",
+            "sort": "modified",
+            "columns": ["last_modified"]
+        });
+        let result = handler.execute(args).await.unwrap();
+        let paths = extract_file_paths(extract_text(&result));
+        let expected: Vec<String> = ["shebe-cols-new.rs", "shebe-cols-old.rs"]
+            .iter()
+            .map(|file| repo_file(&handler.services, file).display().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[tokio::test]
+    async fn test_list_dir_invalid_column() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let args = json!({"session": "any-session", "columns": ["owner"]});
+        let result = handler.execute(args).await;
+        match result {
+            Err(McpError::InvalidParams(msg)) => assert!(msg.contains("'owner'"), "{msg}"),
+            other => panic!("expected InvalidParams, got {other:?}"),
         }
     }
 }
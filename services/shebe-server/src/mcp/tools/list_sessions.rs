@@ -1,12 +1,17 @@
 //! List sessions tool handler
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::{format_bytes, format_time_ago};
+use super::helpers::{
+    format_bytes, format_count, format_schedule, format_scheduled_run, format_timestamp,
+};
+use crate::core::compare::is_comparison_session;
+use crate::core::schedule::schedule_status;
 use crate::core::services::Services;
-use crate::core::storage::{SessionMetadata, SCHEMA_VERSION};
+use crate::core::storage::{SessionEntry, SessionMetadata, SCHEMA_VERSION};
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::sync::Arc;
 
@@ -29,8 +34,14 @@ impl ListSessionsHandler {
 
         for session in sessions {
             output.push_str(&format!("## {}\n", session.id));
-            output.push_str(&format!("- **Files:** {}\n", session.files_indexed));
-            output.push_str(&format!("- **Chunks:** {}\n", session.chunks_created));
+            output.push_str(&format!(
+                "- **Files:** {}\n",
+                format_count(session.files_indexed as u64)
+            ));
+            output.push_str(&format!(
+                "- **Chunks:** {}\n",
+                format_count(session.chunks_created as u64)
+            ));
             output.push_str(&format!(
                 "- **Size:** {}\n",
                 format_bytes(session.index_size_bytes)
@@ -47,18 +58,92 @@ impl ListSessionsHandler {
                 session.schema_version, schema_status
             ));
 
-            // Last indexed with relative time
+            if let Some(cause) = self.services.storage.index_corruption(&session.id) {
+                let remedy = if session.remote.is_some() || session.repository_path.exists() {
+                    "rebuild it with reindex_session or remove it with delete_session"
+                } else {
+                    "its repository is gone, remove it with delete_session"
+                };
+                output.push_str(&format!(
+                    "- **Status:** corrupted index, not searchable; {remedy} ({cause})\n"
+                ));
+            }
+
+            output.push_str(&format!(
+                "- **Last indexed:** {}\n",
+                format_timestamp(session.last_indexed_at)
+            ));
+
+            if let Some(status) = schedule_status(session, &self.services.config.current().schedule)
+            {
+                output.push_str(&format!(
+                    "- **Reindex schedule:** {}\n",
+                    format_schedule(&status)
+                ));
+                if let Some(run) = &status.last_run {
+                    output.push_str(&format!(
+                        "- **Last scheduled run:** {}\n",
+                        format_scheduled_run(run)
+                    ));
+                }
+            }
+
             output.push_str(&format!(
-                "- **Last indexed:** {} ({})\n",
-                session.last_indexed_at.format("%Y-%m-%d %H:%M UTC"),
-                format_time_ago(session.last_indexed_at)
+                "- **Created:** {}\n\n",
+                format_timestamp(session.created_at)
             ));
+        }
+
+        output
+    }
+
+    /// Format corrupt and orphaned session directories
+    fn format_broken(&self, broken: &[SessionEntry]) -> String {
+        if broken.is_empty() {
+            return String::new();
+        }
 
-            output.push_str(&format!("- **Created:** {}\n\n", session.created_at));
+        let mut output = format!(
+            "Broken sessions ({}), not searchable. \
+             Delete them with list_sessions prune=true:\n\n",
+            broken.len()
+        );
+
+        for entry in broken {
+            let size_bytes = match entry {
+                SessionEntry::Corrupt { id, error } => {
+                    output.push_str(&format!("## {id} (corrupt)\n"));
+                    output.push_str(&format!("- **Problem:** unreadable meta.json: {error}\n"));
+                    self.services.storage.measure_session_size(id)
+                }
+                SessionEntry::Orphaned { id, size_bytes } => {
+                    output.push_str(&format!("## {id} (orphaned)\n"));
+                    output.push_str("- **Problem:** no meta.json\n");
+                    *size_bytes
+                }
+                SessionEntry::Valid(_) => continue,
+            };
+            output.push_str(&format!("- **Size:** {}\n\n", format_bytes(size_bytes)));
         }
 
         output
     }
+
+    /// Format the sessions deleted by `prune`
+    fn format_pruned(pruned: &[(String, u64)]) -> String {
+        if pruned.is_empty() {
+            return "No broken sessions to prune.\n\n".to_string();
+        }
+
+        let reclaimed: u64 = pruned.iter().map(|(_, size_bytes)| size_bytes).sum();
+        let ids: Vec<&str> = pruned.iter().map(|(id, _)| id.as_str()).collect();
+        format!(
+            "Pruned {} broken session(s) ({}), {} reclaimed.\n\n",
+            pruned.len(),
+            ids.join(", "),
+            format_bytes(reclaimed)
+        )
+    }
 }
 
 #[async_trait]
@@ -71,7 +156,14 @@ impl McpToolHandler for ListSessionsHandler {
         ToolSchema {
             name: "list_sessions".to_string(),
             description: "List all indexed code sessions available for search_code queries. \
-                         Shows: session ID, file count, chunk count, index size, creation timestamp. \
+                         Shows: session ID, file count, chunk count, index size, creation timestamp, \
+                         and reindex schedule if one is set. \
+                         Sessions whose index files are damaged show a corrupted index status. \
+                         Corrupt or orphaned session directories are listed separately; \
+                         prune=true deletes them and reports the disk space reclaimed. \
+                         With [namespaces] configured, lists the default namespace; \
+                         namespace picks another, all_namespaces=true lists all of them. \
+                         collection lists only the sessions of that collection. \
                          \
                          USE THIS FIRST: Run before search_code to discover which sessions exist. \
                          Each session represents a specific indexed repository/codebase. \
@@ -82,22 +174,126 @@ impl McpToolHandler for ListSessionsHandler {
                 .to_string(),
             input_schema: json!({
                 "type": "object",
-                "properties": {},
+                "properties": {
+                    "prune": {
+                        "type": "boolean",
+                        "description": "Delete corrupt and orphaned session directories (default: false)",
+                        "default": false
+                    },
+                    "namespace": {
+                        "type": "string",
+                        "pattern": "^[a-zA-Z0-9_-]{1,64}$",
+                        "description": "Namespace to list (default: the configured default namespace)"
+                    },
+                    "all_namespaces": {
+                        "type": "boolean",
+                        "description": "List the sessions of every namespace (default: false)",
+                        "default": false
+                    },
+                    "collection": {
+                        "type": "string",
+                        "pattern": "^[a-zA-Z0-9_-]{1,64}$",
+                        "description": "Only list the sessions of this collection (in any namespace)"
+                    }
+                },
                 "additionalProperties": false
             }),
         }
     }
 
-    async fn execute(&self, _args: Value) -> Result<ToolResult, McpError> {
-        // Get sessions from storage
-        let sessions = self
-            .services
-            .storage
-            .list_sessions()
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct ListArgs {
+            #[serde(default)]
+            prune: bool,
+            #[serde(default)]
+            namespace: Option<String>,
+            #[serde(default)]
+            all_namespaces: bool,
+            #[serde(default)]
+            collection: Option<String>,
+        }
+
+        let args: ListArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+        let storage = &self.services.storage;
+        if args.all_namespaces && args.namespace.is_some() {
+            return Err(McpError::InvalidParams(
+                "Use namespace or all_namespaces, not both".to_string(),
+            ));
+        }
+        let members = match &args.collection {
+            Some(_) if args.prune || args.namespace.is_some() || args.all_namespaces => {
+                return Err(McpError::InvalidParams(
+                    "collection cannot be combined with prune, namespace or all_namespaces"
+                        .to_string(),
+                ));
+            }
+            Some(name) => Some(storage.get_collection(name)?.sessions),
+            None => None,
+        };
+        let namespace = match &args.namespace {
+            // Members of a collection may be in any namespace
+            _ if args.all_namespaces || members.is_some() => None,
+            Some(namespace) => Some(namespace.as_str()),
+            None => Some(storage.default_namespace()),
+        };
+
+        // prune=true is the confirmation, as confirm=true is for delete_session
+        let mut text = String::new();
+        if args.prune {
+            let pruned = match namespace {
+                Some(namespace) => storage.prune_broken_sessions_in(namespace),
+                None => storage.prune_broken_sessions(),
+            }
             .map_err(McpError::from)?;
+            text.push_str(&Self::format_pruned(&pruned));
+        }
 
-        // Format output
-        let text = self.format_sessions(&sessions);
+        // Get sessions from storage, leaving out temporary comparison sessions
+        let mut sessions = Vec::new();
+        let mut broken = Vec::new();
+        let entries = match namespace {
+            Some(namespace) => storage.list_session_entries_in(namespace),
+            None => storage.list_session_entries(),
+        }
+        .map_err(McpError::from)?;
+        for entry in entries {
+            if is_comparison_session(entry.id()) {
+                continue;
+            }
+            if let Some(members) = &members {
+                if !members.contains(&storage.canonical_session_id(entry.id())) {
+                    continue;
+                }
+            }
+            match entry {
+                SessionEntry::Valid(metadata) => sessions.push(*metadata),
+                entry => broken.push(entry),
+            }
+        }
+
+        // Format output, naming the collection or, when there is more
+        // than one, the namespace
+        let namespaces = storage.namespaces();
+        if let Some(collection) = &args.collection {
+            text.push_str(&format!("Collection: {collection}\n\n"));
+        } else if namespaces.len() > 1 {
+            text.push_str(&match namespace {
+                Some(namespace) => format!(
+                    "Namespace: {namespace} (all: {}; all_namespaces=true lists every one)\n\n",
+                    namespaces.join(", ")
+                ),
+                None => format!("Namespaces: {}\n\n", namespaces.join(", ")),
+            });
+        }
+        text.push_str(&self.format_sessions(&sessions));
+        if !broken.is_empty() {
+            if sessions.is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str(&self.format_broken(&broken));
+        }
 
         Ok(text_content(text))
     }
@@ -184,6 +380,58 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_list_sessions_broken_and_prune() {
+        let (handler, temp) = setup_test_handler().await;
+        let storage = &handler.services.storage;
+        storage
+            .create_session(
+                "good",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        storage
+            .create_session(
+                "garbled",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        std::fs::write(
+            storage.get_session_path("garbled").join("meta.json"),
+            "{oops",
+        )
+        .unwrap();
+        let orphan = temp.path().join("sessions").join("orphan");
+        std::fs::create_dir_all(&orphan).unwrap();
+        std::fs::write(orphan.join("leftover.bin"), vec![0u8; 2048]).unwrap();
+
+        let result = handler.execute(json!({})).await.unwrap();
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => {
+                assert!(text.contains("Available sessions (1)"));
+                assert!(text.contains("Broken sessions (2)"));
+                assert!(text.contains("## garbled (corrupt)"));
+                assert!(text.contains("unreadable meta.json"));
+                assert!(text.contains("## orphan (orphaned)"));
+                assert!(text.contains("no meta.json"));
+            }
+        }
+
+        let result = handler.execute(json!({"prune": true})).await.unwrap();
+        match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => {
+                assert!(text.contains("Pruned 2 broken session(s) (garbled, orphan)"));
+                assert!(text.contains("reclaimed"));
+                assert!(!text.contains("Broken sessions"));
+            }
+        }
+        assert!(storage.session_exists("good"));
+        assert!(!storage.session_exists("garbled"));
+        assert!(!orphan.exists());
+    }
+
     #[tokio::test]
     async fn test_format_sessions_empty() {
         let (handler, _temp) = setup_test_handler().await;
@@ -203,24 +451,76 @@ mod tests {
             repository_path: PathBuf::from("/test/repo"),
             created_at: Utc.with_ymd_and_hms(2025, 10, 21, 10, 0, 0).unwrap(),
             last_indexed_at: Utc.with_ymd_and_hms(2025, 10, 21, 10, 0, 0).unwrap(),
-            files_indexed: 100,
-            chunks_created: 500,
+            files_indexed: 1200,
+            chunks_created: 54000,
             index_size_bytes: 1048576, // 1 MB
             config: SessionConfig::default(),
-            schema_version: 3,
+            schema_version: SCHEMA_VERSION,
+            files_deduplicated: 0,
+            created_by_version: "unknown".to_string(),
+            tantivy_schema_hash: "unknown".to_string(),
+            index_settings: None,
+            remote: None,
+            archive: None,
+            skipped: Default::default(),
+            chunk_text_bytes: 0,
+            reindex_schedule: None,
+            last_scheduled_run: None,
+            search_defaults: None,
+            modified_times: None,
+            provenance: None,
         }];
 
         let output = handler.format_sessions(&sessions);
 
         assert!(output.contains("Available sessions (1)"));
         assert!(output.contains("## test-session"));
-        assert!(output.contains("**Files:** 100"));
-        assert!(output.contains("**Chunks:** 500"));
-        assert!(output.contains("**Size:** 1.00 MB"));
-        assert!(output.contains("**Schema:** v3 (current)"));
-        assert!(output.contains("**Last indexed:**"));
-        assert!(output.contains("2025-10-21"));
-        assert!(output.contains("**Created:** 2025-10-21")); // Check for date only, not full timestamp
+        assert!(output.contains("**Files:** 1,200"));
+        assert!(output.contains("**Chunks:** 54,000"));
+        assert!(output.contains("**Size:** 1.0 MB"));
+        assert!(output.contains(&format!("**Schema:** v{SCHEMA_VERSION} (current)")));
+        assert!(output.contains("**Last indexed:** 2025-10-21 10:00 UTC ("));
+        assert!(output.contains("**Created:** 2025-10-21 10:00 UTC ("));
+        assert!(output.contains(" days ago)\n"));
+    }
+
+    #[tokio::test]
+    async fn test_format_sessions_schedule() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        use crate::core::jobs::JobState;
+        use crate::core::schedule::{Schedule, ScheduledRun};
+        use chrono::{TimeZone, Utc};
+        let indexed_at = Utc.with_ymd_and_hms(2025, 10, 21, 10, 0, 0).unwrap();
+        let storage = &handler.services.storage;
+        storage
+            .create_session(
+                "scheduled",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        let mut metadata = storage.get_session_metadata("scheduled").unwrap();
+        metadata.last_indexed_at = indexed_at;
+        metadata.reindex_schedule = Some(Schedule::Every { minutes: 360 });
+        metadata.last_scheduled_run = Some(ScheduledRun {
+            job_id: Some("job-4".to_string()),
+            started_at: indexed_at,
+            finished_at: Some(Utc.with_ymd_and_hms(2025, 10, 21, 11, 0, 0).unwrap()),
+            state: JobState::Failed,
+            error: Some("repository path does not exist".to_string()),
+        });
+
+        let output = handler.format_sessions(&[metadata]);
+
+        assert!(output.contains(
+            "**Reindex schedule:** `6h` (from set_reindex_schedule), \
+             next run 2025-10-21 17:00 UTC (scheduler disabled)"
+        ));
+        assert!(output.contains(
+            "**Last scheduled run:** failed 2025-10-21 11:00 UTC: \
+             repository path does not exist"
+        ));
     }
 
     #[tokio::test]
@@ -0,0 +1,406 @@
+//! List tree (directory overview) tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::tree::{
+    NodeKind, TreeNode, TreeOptions, DEFAULT_TREE_DEPTH, DEFAULT_TREE_MAX_CHILDREN,
+};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Maximum depth accepted by the tool
+const MAX_TREE_DEPTH: usize = 10;
+
+/// Maximum children per directory accepted by the tool
+const MAX_TREE_CHILDREN: usize = 200;
+
+pub struct ListTreeHandler {
+    services: Arc<Services>,
+}
+
+impl ListTreeHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the tree as an indented Markdown list
+    fn format_tree(
+        &self,
+        session: &str,
+        repository_path: &Path,
+        tree: &TreeNode,
+        depth: usize,
+    ) -> String {
+        let root = if tree.name == "." {
+            repository_path.to_path_buf()
+        } else {
+            repository_path.join(&tree.name)
+        };
+
+        let mut output = format!(
+            "**Session:** `{}`\n\
+             **Root:** `{}` ({} files, {} chunks)\n\
+             **Depth:** {}\n\n",
+            session,
+            root.display(),
+            tree.file_count,
+            tree.chunk_count,
+            depth
+        );
+
+        if tree.children.is_empty() {
+            output.push_str("No files found in this session.");
+            return output;
+        }
+
+        let prefix = if tree.name == "." {
+            String::new()
+        } else {
+            format!("{}/", tree.name)
+        };
+        let mut collapsed = false;
+        format_children(tree, &prefix, 0, &mut output, &mut collapsed);
+
+        if collapsed {
+            output.push_str(
+                "\nCollapsed directories can be expanded with `path` \
+                 (e.g. path=\"src/core\").\n",
+            );
+        }
+
+        output
+    }
+}
+
+/// Append the children of `node` at the given indent level
+fn format_children(
+    node: &TreeNode,
+    prefix: &str,
+    level: usize,
+    output: &mut String,
+    collapsed: &mut bool,
+) {
+    let indent = "  ".repeat(level);
+
+    for child in &node.children {
+        match child.kind {
+            NodeKind::File => {
+                output.push_str(&format!(
+                    "{indent}- `{}` ({} chunks)\n",
+                    child.name, child.chunk_count
+                ));
+            }
+            NodeKind::Dir => {
+                let path = format!("{prefix}{}/", child.name);
+                if child.is_collapsed() {
+                    *collapsed = true;
+                    output.push_str(&format!(
+                        "{indent}- `{}/` ({} files, {} chunks, {} entries collapsed: `{}`)\n",
+                        child.name,
+                        child.file_count,
+                        child.chunk_count,
+                        child.hidden_children,
+                        path.trim_end_matches('/')
+                    ));
+                } else {
+                    output.push_str(&format!(
+                        "{indent}- `{}/` ({} files, {} chunks)\n",
+                        child.name, child.file_count, child.chunk_count
+                    ));
+                    format_children(child, &path, level + 1, output, collapsed);
+                }
+            }
+        }
+    }
+
+    if !node.children.is_empty() && node.hidden_children > 0 {
+        output.push_str(&format!("{indent}- … and {} more\n", node.hidden_children));
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for ListTreeHandler {
+    fn name(&self) -> &str {
+        "list_tree"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "list_tree".to_string(),
+            description: "Show the files indexed in a session as a directory tree with \
+                         per-directory file and chunk counts. Easier to scan than list_dir \
+                         for large sessions. Paths are relative to the repository root, \
+                         directories are ranked by file count, and the tree is cut off at \
+                         `depth` levels (default 3) and `max_children` entries per directory \
+                         (default 20, the rest shown as '… and N more'). Expand a collapsed \
+                         directory by calling again with `path`. Use format='json' for the \
+                         nested structure."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to show",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Subdirectory to root the tree at, relative to the \
+                                       repository (e.g. 'src/core'). Omit for the whole session."
+                    },
+                    "depth": {
+                        "type": "integer",
+                        "description": "Directory levels to show (default: 3, max: 10)",
+                        "default": DEFAULT_TREE_DEPTH,
+                        "minimum": 1,
+                        "maximum": MAX_TREE_DEPTH
+                    },
+                    "max_children": {
+                        "type": "integer",
+                        "description": "Entries shown per directory before '… and N more' \
+                                       (default: 20, max: 200)",
+                        "default": DEFAULT_TREE_MAX_CHILDREN,
+                        "minimum": 1,
+                        "maximum": MAX_TREE_CHILDREN
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Output format: 'markdown' (default) or 'json' \
+                                       (nested tree)",
+                        "default": "markdown",
+                        "enum": ["markdown", "json"]
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct ListTreeArgs {
+            session: String,
+            path: Option<String>,
+            #[serde(default = "default_depth")]
+            depth: usize,
+            #[serde(default = "default_max_children")]
+            max_children: usize,
+            #[serde(default = "default_format")]
+            format: String,
+        }
+        fn default_depth() -> usize {
+            DEFAULT_TREE_DEPTH
+        }
+        fn default_max_children() -> usize {
+            DEFAULT_TREE_MAX_CHILDREN
+        }
+        fn default_format() -> String {
+            "markdown".to_string()
+        }
+
+        let args: ListTreeArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let json_output = match args.format.as_str() {
+            "markdown" => false,
+            "json" => true,
+            other => {
+                return Err(McpError::InvalidParams(format!(
+                    "Invalid format: '{other}'. Must be 'markdown' or 'json'."
+                )))
+            }
+        };
+
+        let options = TreeOptions {
+            depth: args.depth.clamp(1, MAX_TREE_DEPTH),
+            max_children: args.max_children.clamp(1, MAX_TREE_CHILDREN),
+        };
+
+        let metadata = self
+            .services
+            .storage
+            .get_session_metadata(&args.session)
+            .map_err(McpError::from)?;
+
+        let tree = self
+            .services
+            .storage
+            .file_tree(&args.session, args.path.as_deref(), options)
+            .map_err(McpError::from)?;
+
+        let text = if json_output {
+            serde_json::to_string_pretty(&tree)
+                .map_err(|e| McpError::InternalError(format!("Failed to serialize tree: {e}")))?
+        } else {
+            self.format_tree(
+                &args.session,
+                &metadata.repository_path,
+                &tree,
+                options.depth,
+            )
+        };
+
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::core::types::Chunk;
+    use crate::mcp::protocol::ContentBlock;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (ListTreeHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        (ListTreeHandler::new(services), temp_dir)
+    }
+
+    fn create_test_session(services: &Arc<Services>, session_id: &str, files: &[&str]) {
+        let mut index = services
+            .storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunks: Vec<Chunk> = files
+            .iter()
+            .map(|path| Chunk {
+                text: "fn main() {}".to_string(),
+                file_path: PathBuf::from(path),
+                start_offset: 0,
+                end_offset: 12,
+                chunk_index: 0,
+                records: None,
+                section: None,
+            })
+            .collect();
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_markdown() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(
+            &handler.services,
+            "tree",
+            &[
+                "/test/repo/src/main.rs",
+                "/test/repo/src/core/a.rs",
+                "/test/repo/README.md",
+            ],
+        );
+
+        let result = handler.execute(json!({"session": "tree"})).await.unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("**Root:** `/test/repo` (3 files, 3 chunks)"));
+        assert!(text.contains("- `src/` (2 files, 2 chunks)"));
+        assert!(text.contains("  - `core/` (1 files, 1 chunks)"));
+        assert!(text.contains("- `README.md` (1 chunks)"));
+        assert!(!text.contains("/test/repo/src"));
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_collapses_by_depth_and_children() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(
+            &handler.services,
+            "tree",
+            &[
+                "/test/repo/src/core/a.rs",
+                "/test/repo/a.rs",
+                "/test/repo/b.rs",
+                "/test/repo/c.rs",
+            ],
+        );
+
+        let result = handler
+            .execute(json!({"session": "tree", "depth": 1, "max_children": 2}))
+            .await
+            .unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("1 entries collapsed: `src`"));
+        assert!(text.contains("- … and 2 more"));
+        assert!(text.contains("expanded with `path`"));
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_subtree_json() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(
+            &handler.services,
+            "tree",
+            &["/test/repo/src/core/a.rs", "/test/repo/docs/b.md"],
+        );
+
+        let result = handler
+            .execute(json!({"session": "tree", "path": "src", "format": "json"}))
+            .await
+            .unwrap();
+        let tree: Value = serde_json::from_str(extract_text(&result)).unwrap();
+
+        assert_eq!(tree["name"], "src");
+        assert_eq!(tree["file_count"], 1);
+        assert_eq!(tree["children"][0]["name"], "core");
+        assert_eq!(tree["children"][0]["children"][0]["kind"], "file");
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_unknown_path() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(&handler.services, "tree", &["/test/repo/a.rs"]);
+
+        let result = handler
+            .execute(json!({"session": "tree", "path": "nope"}))
+            .await;
+
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_invalid_format() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler
+            .execute(json!({"session": "tree", "format": "xml"}))
+            .await;
+
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_session_not_found() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"session": "missing"})).await;
+
+        assert!(result.is_err());
+    }
+}
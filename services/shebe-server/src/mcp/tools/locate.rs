@@ -0,0 +1,209 @@
+//! Locate tool handler
+//!
+//! Finds the files a vague description most likely refers to ("the
+//! retry config"), by file path and by content in one call, so an
+//! agent need not choose between find_file and search_code first.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{path_display, run_blocking};
+use crate::core::locate::{locate, LocateHit, LocateStrategy, DEFAULT_LOCATE_K, MAX_LOCATE_K};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct LocateHandler {
+    services: Arc<Services>,
+}
+
+impl LocateHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format located files as a table
+    fn format_hits(
+        &self,
+        session: &str,
+        needle: &str,
+        strategy: LocateStrategy,
+        hits: &[LocateHit],
+    ) -> Result<String, McpError> {
+        let mut output = format!(
+            "**Session:** `{session}`\n\
+             **Needle:** `{needle}` (strategy: {})\n",
+            strategy.as_str()
+        );
+
+        if hits.is_empty() {
+            output.push_str(
+                "\nNo file path or content matches. Try other words, or search_code with \
+                 fuzziness for misspellings.",
+            );
+            return Ok(output);
+        }
+
+        let by_path = hits.iter().filter(|hit| hit.path_score.is_some()).count();
+        let by_content = hits
+            .iter()
+            .filter(|hit| hit.content_score.is_some())
+            .count();
+        output.push_str(&format!(
+            "**Found:** {} files ({by_path} by path, {by_content} by content)\n\n",
+            hits.len()
+        ));
+
+        let display = path_display(&self.services, session, None, None)?;
+        output.push_str("| # | File | Found by | Score |\n");
+        output.push_str("|---|------|----------|-------|\n");
+        for (i, hit) in hits.iter().enumerate() {
+            output.push_str(&format!(
+                "| {} | `{}` | {} | {:.2} |\n",
+                i + 1,
+                display.path(&hit.file_path),
+                hit.found_by(),
+                hit.score
+            ));
+        }
+        output.push_str(
+            "\nPath matches share words with the file path; content matches are the \
+             best search_code hit per file. Use read_file or search_code to look inside.",
+        );
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for LocateHandler {
+    fn name(&self) -> &str {
+        "locate"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "locate".to_string(),
+            description: "Find the files a vague description refers to (e.g. 'the retry \
+                         config', 'user login handler'). Matches the words against file paths \
+                         (retry_config.rs, RetryConfig.java) and searches file contents, then \
+                         merges both into one ranked table labelled 'path match', 'content \
+                         match' or both. Use when unsure whether a name is a file or \
+                         something inside one; use find_file for glob patterns and \
+                         search_code for ranked code snippets."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to search",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "needle": {
+                        "type": "string",
+                        "description": "Free text naming what to find",
+                        "minLength": 1
+                    },
+                    "k": {
+                        "type": "integer",
+                        "description": "Files returned (default: 10, max: 100)",
+                        "default": DEFAULT_LOCATE_K,
+                        "minimum": 1,
+                        "maximum": MAX_LOCATE_K
+                    },
+                    "strategy": {
+                        "type": "string",
+                        "description": "'auto' (default) matches paths and content, 'path' \
+                                       only file paths, 'content' only file contents",
+                        "default": "auto",
+                        "enum": ["auto", "path", "content"]
+                    }
+                },
+                "required": ["session", "needle"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct LocateArgs {
+            session: String,
+            needle: String,
+            #[serde(default = "default_k")]
+            k: usize,
+            #[serde(default)]
+            strategy: LocateStrategy,
+        }
+        fn default_k() -> usize {
+            DEFAULT_LOCATE_K
+        }
+
+        let args: LocateArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        if args.needle.trim().is_empty() {
+            return Err(McpError::InvalidParams(
+                "needle cannot be empty".to_string(),
+            ));
+        }
+        if !(1..=MAX_LOCATE_K).contains(&args.k) {
+            return Err(McpError::InvalidParams(format!(
+                "k must be between 1 and {MAX_LOCATE_K}"
+            )));
+        }
+
+        // Path listing and search both read the index (off the async runtime)
+        let services = Arc::clone(&self.services);
+        let (session, needle) = (args.session.clone(), args.needle.clone());
+        let hits = run_blocking(move || {
+            locate(
+                &services.storage,
+                &services.search,
+                &session,
+                &needle,
+                args.strategy,
+                args.k,
+            )
+        })
+        .await?;
+
+        let text = self.format_hits(&args.session, &args.needle, args.strategy, &hits)?;
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (LocateHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let handler = LocateHandler::new(Arc::new(Services::new(config)));
+        (handler, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_locate_rejects_bad_arguments() {
+        let (handler, _temp) = setup_test_handler();
+
+        for args in [
+            json!({"session": "s", "needle": "  "}),
+            json!({"session": "s", "needle": "retry", "k": 0}),
+            json!({"session": "s", "needle": "retry", "strategy": "fuzzy"}),
+        ] {
+            let result = handler.execute(args.clone()).await;
+            assert!(
+                matches!(result, Err(McpError::InvalidParams(_))),
+                "{args}: {result:?}"
+            );
+        }
+    }
+}
@@ -3,38 +3,82 @@
 //! This module contains all MCP tool handlers that expose Shebe's
 //! functionality to Claude Code.
 
+pub mod cancel_job;
+pub mod compact_session;
+pub mod compare_configs;
+pub mod create_collection;
+pub mod delete_collection;
 pub mod delete_session;
+pub mod diff_sessions;
 pub mod find_file;
 pub mod find_references;
+pub mod find_similar;
+pub mod get_job_status;
 pub mod get_server_info;
 pub mod get_session_info;
+pub mod get_storage_report;
 pub mod handler;
 pub mod helpers;
 pub mod index_repository;
+pub mod index_repository_async;
 pub mod list_dir;
 pub mod list_sessions;
+pub mod list_tree;
+pub mod locate;
+pub mod optimize_session;
 pub mod preview_chunk;
 pub mod read_file;
 pub mod registry;
 pub mod reindex_session;
+pub mod reload_config;
+pub mod run_self_test;
+pub mod sample_files;
+pub mod search_batch;
 pub mod search_code;
+pub mod search_paths;
+pub mod set_reindex_schedule;
+pub mod set_session_defaults;
 pub mod show_shebe_config;
+pub mod update_collection;
 pub mod upgrade_session;
+pub mod usage;
 
+pub use cancel_job::CancelJobHandler;
+pub use compact_session::CompactSessionHandler;
+pub use compare_configs::CompareConfigsHandler;
+pub use create_collection::CreateCollectionHandler;
+pub use delete_collection::DeleteCollectionHandler;
 pub use delete_session::DeleteSessionHandler;
+pub use diff_sessions::DiffSessionsHandler;
 pub use find_file::FindFileHandler;
 pub use find_references::FindReferencesHandler;
+pub use find_similar::FindSimilarHandler;
+pub use get_job_status::GetJobStatusHandler;
 pub use get_server_info::GetServerInfoHandler;
 pub use get_session_info::GetSessionInfoHandler;
+pub use get_storage_report::GetStorageReportHandler;
 pub use handler::{text_content, McpToolHandler};
 pub use helpers::{detect_language, format_bytes, truncate_text};
 pub use index_repository::IndexRepositoryHandler;
+pub use index_repository_async::IndexRepositoryAsyncHandler;
 pub use list_dir::ListDirHandler;
 pub use list_sessions::ListSessionsHandler;
+pub use list_tree::ListTreeHandler;
+pub use locate::LocateHandler;
+pub use optimize_session::OptimizeSessionHandler;
 pub use preview_chunk::PreviewChunkHandler;
 pub use read_file::ReadFileHandler;
-pub use registry::ToolRegistry;
+pub use registry::{execute_with_timeout, ToolRegistry};
 pub use reindex_session::ReindexSessionHandler;
+pub use reload_config::ReloadConfigHandler;
+pub use run_self_test::RunSelfTestHandler;
+pub use sample_files::SampleFilesHandler;
+pub use search_batch::SearchBatchHandler;
 pub use search_code::SearchCodeHandler;
+pub use search_paths::SearchPathsHandler;
+pub use set_reindex_schedule::SetReindexScheduleHandler;
+pub use set_session_defaults::SetSessionDefaultsHandler;
 pub use show_shebe_config::ShowShebeConfigHandler;
+pub use update_collection::UpdateCollectionHandler;
 pub use upgrade_session::UpgradeSessionHandler;
+pub use usage::ToolUsage;
@@ -0,0 +1,142 @@
+//! Optimize session tool handler
+//!
+//! Runs the idle-time maintenance pass (see [`crate::core::maintenance`])
+//! on one session immediately.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_bytes;
+use crate::core::error::ShebeError;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::CompactionStats;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct OptimizeSessionHandler {
+    services: Arc<Services>,
+}
+
+impl OptimizeSessionHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format optimization summary
+    fn format_summary(&self, stats: &CompactionStats, target_segments: usize) -> String {
+        let saved = stats
+            .size_before_bytes
+            .saturating_sub(stats.size_after_bytes);
+        format!(
+            "**Session Optimized:** `{}`\n\n\
+             **Segments:** {} -> {} (target {})\n\
+             **Index size:** {} -> {} ({} reclaimed)\n\
+             **Duration:** {}ms",
+            stats.session,
+            stats.segments_before,
+            stats.segments_after,
+            target_segments,
+            format_bytes(stats.size_before_bytes),
+            format_bytes(stats.size_after_bytes),
+            format_bytes(saved),
+            stats.duration_ms
+        )
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for OptimizeSessionHandler {
+    fn name(&self) -> &str {
+        "optimize_session"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "optimize_session".to_string(),
+            description: "Run index maintenance on a session now: merge its segments down to \
+                         a target count, purge deleted documents, remove stale files and \
+                         refresh the recorded index size. The same pass the server runs on \
+                         idle sessions when maintenance is enabled. Reports segment count and \
+                         index size before and after. Fails if another operation is currently \
+                         writing to the session."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to optimize",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "target_segments": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Segments to merge down to \
+                                       (default: maintenance.target_segments, 1)"
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct OptimizeArgs {
+            session: String,
+            target_segments: Option<usize>,
+        }
+
+        let args: OptimizeArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+        let target_segments = args
+            .target_segments
+            .unwrap_or(self.services.config.current().maintenance.target_segments);
+        if target_segments == 0 {
+            return Err(McpError::InvalidParams(
+                "target_segments must be at least 1".to_string(),
+            ));
+        }
+
+        let stats = self
+            .services
+            .storage
+            .optimize_session(&args.session, target_segments)
+            .map_err(|e| match e {
+                ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
+                    "Session '{}' not found. Use list_sessions to see available sessions.",
+                    args.session
+                )),
+                _ => McpError::from(e),
+            })?;
+
+        Ok(text_content(self.format_summary(&stats, target_segments)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_optimize_session_rejects_zero_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let handler = OptimizeSessionHandler::new(Arc::new(Services::new(config)));
+
+        let result = handler
+            .execute(json!({"session": "any", "target_segments": 0}))
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+}
@@ -3,22 +3,37 @@
 //! Provides context expansion for search results by showing N lines before and after a chunk.
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::detect_language;
+use super::helpers::{detect_language, resolve_session_path};
+use crate::core::archive;
+use crate::core::format::format_count;
+use crate::core::indexer::records::{stored_records, RecordFormat};
+use crate::core::paths::ResolvedPath;
+use crate::core::search::{locate_chunk, ChunkLocation};
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::RecordRange;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{build_truncation_footer, estimate_tokens, EntrySize, ResponseBudget};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
-use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
-use tantivy::schema::{Term, Value as TantivyValue};
+use tantivy::schema::Value as TantivyValue;
 use tantivy::{IndexReader, TantivyDocument};
 
 const DEFAULT_CONTEXT_LINES: usize = 10;
 const MAX_CONTEXT_LINES: usize = 100;
 
+/// Tokens for the code fence and chunk boundary markers
+const FRAME_TOKENS: usize = 30;
+
+/// How to get lines trimmed by the response budget
+const TRUNCATION_HINT: &str =
+    "lower `context_lines`, or use read_file with `offset` to page through the file";
+
 pub struct PreviewChunkHandler {
     services: Arc<Services>,
 }
@@ -32,9 +47,10 @@ impl PreviewChunkHandler {
     async fn get_chunk_metadata(
         &self,
         session: &str,
-        file_path: &str,
+        file: &ResolvedPath,
         chunk_index: usize,
     ) -> Result<ChunkMetadata, McpError> {
+        let file_path = file.path.as_str();
         // Open session index
         let index = self
             .services
@@ -51,27 +67,20 @@ impl PreviewChunkHandler {
         let schema = index.schema();
 
         // Get required fields
-        let file_path_field = schema
-            .get_field("file_path")
-            .map_err(|e| McpError::InternalError(format!("file_path field missing: {e}")))?;
-        let chunk_index_field = schema
-            .get_field("chunk_index")
-            .map_err(|e| McpError::InternalError(format!("chunk_index field missing: {e}")))?;
         let offset_start_field = schema
             .get_field("offset_start")
             .map_err(|e| McpError::InternalError(format!("offset_start field missing: {e}")))?;
         let offset_end_field = schema
             .get_field("offset_end")
             .map_err(|e| McpError::InternalError(format!("offset_end field missing: {e}")))?;
+        let text_field = schema
+            .get_field("text")
+            .map_err(|e| McpError::InternalError(format!("text field missing: {e}")))?;
 
         // Query for specific chunk
-        let file_term = Term::from_field_text(file_path_field, file_path);
-        let chunk_term = Term::from_field_i64(chunk_index_field, chunk_index as i64);
-
-        let file_query: Box<dyn Query> = Box::new(TermQuery::new(file_term, Default::default()));
-        let chunk_query: Box<dyn Query> = Box::new(TermQuery::new(chunk_term, Default::default()));
-
-        let query = BooleanQuery::new(vec![(Occur::Must, file_query), (Occur::Must, chunk_query)]);
+        let query = index
+            .chunk_query(file_path, chunk_index)
+            .map_err(McpError::from)?;
 
         let top_docs = searcher
             .search(&query, &tantivy::collector::TopDocs::with_limit(1))
@@ -79,8 +88,9 @@ impl PreviewChunkHandler {
 
         if top_docs.is_empty() {
             return Err(McpError::InvalidRequest(format!(
-                "Chunk not found: file '{file_path}', chunk index {chunk_index}. \
-                 File may not be indexed or chunk index invalid."
+                "Chunk not found: file {}, chunk index {chunk_index}. \
+                 File may not be indexed or chunk index invalid.",
+                file.describe()
             )));
         }
 
@@ -102,55 +112,72 @@ impl PreviewChunkHandler {
             .ok_or_else(|| McpError::InternalError("Missing offset_end".to_string()))?
             as usize;
 
+        let text = retrieved_doc
+            .get_first(text_field)
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
         Ok(ChunkMetadata {
             file_path: file_path.to_string(),
             chunk_index,
             offset_start,
             offset_end,
+            text,
+            records: stored_records(schema, &retrieved_doc),
         })
     }
 
-    /// Read file and extract lines with context
-    fn extract_context_lines(
-        &self,
-        file_path: &Path,
-        chunk_metadata: &ChunkMetadata,
-        context_lines: usize,
-    ) -> Result<ContextExtraction, McpError> {
-        // Read file
-        let contents = std::fs::read_to_string(file_path).map_err(|e| {
+    /// Read the chunk's file (or archive entry) as it is now
+    fn read_file(&self, file_path: &str) -> Result<String, McpError> {
+        archive::read_indexed_file(file_path).map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 McpError::InvalidRequest(format!(
-                    "File not found: {}. May have been deleted or moved since indexing. \
-                     Try re-indexing the session.",
-                    file_path.display()
+                    "File not found: {file_path}. May have been deleted or moved since indexing. \
+                     Try re-indexing the session."
                 ))
             } else if e.kind() == std::io::ErrorKind::InvalidData {
                 McpError::InvalidRequest("File contains non-UTF-8 data (binary file).".to_string())
             } else {
                 McpError::InternalError(format!("Failed to read file: {e}"))
             }
-        })?;
+        })
+    }
 
+    /// Extract the lines of the chunk at `chunk` (byte range) with context
+    ///
+    /// Lines longer than `max_line_chars` characters are cut (0 keeps
+    /// them whole). The line-number gutter is as wide as the largest
+    /// line number shown, so cut lines stay aligned with the rest.
+    fn extract_context_lines(
+        &self,
+        contents: &str,
+        chunk: Range<usize>,
+        context_lines: usize,
+        max_line_chars: usize,
+    ) -> Result<ContextExtraction, McpError> {
         // Convert byte offsets to line numbers
-        let line_info = self.offset_to_lines(
-            &contents,
-            chunk_metadata.offset_start,
-            chunk_metadata.offset_end,
-        )?;
+        let line_info = self.offset_to_lines(contents, chunk.start, chunk.end)?;
 
         // Calculate context boundaries
         let start_line = line_info.start_line.saturating_sub(context_lines);
         let end_line = (line_info.end_line + context_lines).min(line_info.total_lines - 1);
+        let gutter = (end_line + 1).to_string().len().max(4);
 
         // Extract lines
         let all_lines: Vec<&str> = contents.lines().collect();
+        let mut cut_lines = Vec::new();
         let context_lines_vec: Vec<String> = all_lines[start_line..=end_line]
             .iter()
             .enumerate()
             .map(|(i, line)| {
                 let line_num = start_line + i + 1; // 1-indexed
-                format!("{line_num:4} | {line}")
+                match cut_line(line, max_line_chars) {
+                    Some(cut) => {
+                        cut_lines.push(line_num);
+                        format!("{line_num:>gutter$} | {cut}")
+                    }
+                    None => format!("{line_num:>gutter$} | {line}"),
+                }
             })
             .collect();
 
@@ -161,6 +188,11 @@ impl PreviewChunkHandler {
             context_start_line: start_line + 1,         // 1-indexed
             context_end_line: end_line + 1,             // 1-indexed
             total_lines: line_info.total_lines,
+            requested_context: context_lines,
+            cut_lines,
+            max_line_chars,
+            records: None,
+            note: None,
         })
     }
 
@@ -210,53 +242,240 @@ impl PreviewChunkHandler {
         })
     }
 
+    /// Format the indexed text of a chunk no longer found in its file
+    fn format_indexed_chunk(
+        &self,
+        chunk: &ChunkMetadata,
+        text: &str,
+        session: &str,
+        note: &str,
+    ) -> String {
+        let lang = detect_language(&chunk.file_path);
+        format!(
+            "**File:** `{}`\n\
+             **Session:** `{}`\n\
+             **Chunk:** {} (bytes {}-{} when indexed)\n\
+             **Note:** {}\n\n\
+             ```{}\n{}\n```\n",
+            chunk.file_path,
+            session,
+            chunk.chunk_index,
+            chunk.offset_start,
+            chunk.offset_end,
+            note,
+            lang,
+            text.trim_end_matches('\n')
+        )
+    }
+
     /// Format preview with chunk boundaries
+    ///
+    /// When the preview exceeds `budget`, the context window shrinks
+    /// evenly on both sides (farthest lines first) and the header states
+    /// the window shown; if the chunk alone is too large, its trailing
+    /// lines are dropped too.
     fn format_preview(
         &self,
         extraction: &ContextExtraction,
-        file_path: &str,
+        file: &ResolvedPath,
         session: &str,
+        budget: &ResponseBudget,
     ) -> String {
-        let lang = detect_language(file_path);
+        let lang = detect_language(&file.path);
+        let first = extraction.context_start_line;
+        let chunk = extraction.chunk_start_line..=extraction.chunk_end_line;
+        let before = extraction.chunk_start_line - first;
+        let after = extraction.context_end_line - extraction.chunk_end_line;
+
+        // Line numbers by priority: the chunk, then context nearest first
+        let mut order: Vec<usize> = chunk.clone().collect();
+        for distance in 1..=before.max(after) {
+            if distance <= before {
+                order.push(extraction.chunk_start_line - distance);
+            }
+            if distance <= after {
+                order.push(extraction.chunk_end_line + distance);
+            }
+        }
+        let sizes: Vec<EntrySize> = order
+            .iter()
+            .map(|&n| EntrySize {
+                full: estimate_tokens(&extraction.lines[n - first]) + 1,
+                compact: (!chunk.contains(&n)).then_some(0),
+            })
+            .collect();
 
-        let mut output = format!(
-            "**File:** `{}`\n\
-             **Session:** `{}`\n\
-             **Chunk Lines:** {}-{} (of {} total)\n\
-             **Context:** {} lines before + {} lines after\n\n",
-            file_path,
-            session,
-            extraction.chunk_start_line,
-            extraction.chunk_end_line,
-            extraction.total_lines,
-            extraction.chunk_start_line - extraction.context_start_line,
-            extraction.context_end_line - extraction.chunk_end_line
-        );
+        let note = match &extraction.note {
+            Some(note) => format!("**Note:** {note}\n"),
+            None => String::new(),
+        };
+        let header = |before: usize, after: usize, window: Option<usize>, cut: usize| {
+            let mut header = format!(
+                "**File:** {}\n\
+                 **Session:** `{}`\n\
+                 **Chunk Lines:** {}-{} (of {} total)\n\
+                 **Context:** {} lines before + {} lines after\n",
+                file.label(),
+                session,
+                extraction.chunk_start_line,
+                extraction.chunk_end_line,
+                extraction.total_lines,
+                before,
+                after,
+            );
+            if let Some(records) = &extraction.records {
+                header.push_str(&format!("**Chunk Records:** {records}\n"));
+            }
+            if let Some(window) = window {
+                header.push_str(&format!(
+                    "**Context Reduced:** requested ±{} lines, showing ±{window} due to size limits\n",
+                    extraction.requested_context
+                ));
+            }
+            if cut > 0 {
+                header.push_str(&format!(
+                    "**Long Lines:** {cut} lines cut at {} characters (`output.max_line_chars`)\n",
+                    extraction.max_line_chars
+                ));
+            }
+            format!("{header}{note}\n")
+        };
+        let fixed = estimate_tokens(&header(
+            before,
+            after,
+            Some(before.max(after)),
+            extraction.cut_lines.len(),
+        )) + FRAME_TOKENS;
+        let plan = budget.plan(fixed, &sizes);
+
+        let mut shown = vec![false; extraction.lines.len()];
+        for (rank, &n) in order.iter().enumerate() {
+            shown[n - first] = plan.is_kept(rank) && !plan.is_compacted(rank);
+        }
+
+        // Keep the window even: up to the first distance missing a line
+        let distance = |n: usize| {
+            (extraction.chunk_start_line.saturating_sub(n))
+                .max(n.saturating_sub(extraction.chunk_end_line))
+        };
+        let window = (1..=before.max(after))
+            .find(|&d| {
+                (d <= before && !shown[extraction.chunk_start_line - d - first])
+                    || (d <= after && !shown[extraction.chunk_end_line + d - first])
+            })
+            .map_or(before.max(after), |d| d - 1);
+        for n in first..=extraction.context_end_line {
+            if !chunk.contains(&n) && distance(n) > window {
+                shown[n - first] = false;
+            }
+        }
+        let reduced = (window < before.max(after)).then_some(window);
+        let cut = extraction
+            .cut_lines
+            .iter()
+            .filter(|&&n| shown[n - first])
+            .count();
+        let shown_before = (first..extraction.chunk_start_line)
+            .filter(|&n| shown[n - first])
+            .count();
+        let shown_after = (extraction.chunk_end_line + 1..=extraction.context_end_line)
+            .filter(|&n| shown[n - first])
+            .count();
+        let chunk_hidden = chunk.clone().filter(|&n| !shown[n - first]).count();
+
+        let mut output = header(shown_before, shown_after, reduced, cut);
 
         // Add visual chunk boundaries
         output.push_str(&format!("```{lang}\n"));
 
         for (i, line) in extraction.lines.iter().enumerate() {
-            let line_num = extraction.context_start_line + i;
+            let line_num = first + i;
 
             // Mark chunk boundaries
             if line_num == extraction.chunk_start_line {
                 output.push_str("┌─ CHUNK START ─────────────────────\n");
             }
 
-            output.push_str(line);
-            output.push('\n');
+            if shown[i] {
+                output.push_str(line);
+                output.push('\n');
+            }
 
             if line_num == extraction.chunk_end_line {
-                output.push_str("└─ CHUNK END ───────────────────────\n");
+                if chunk_hidden > 0 {
+                    output.push_str("└─ CHUNK TRUNCATED ─────────────────\n");
+                } else {
+                    output.push_str("└─ CHUNK END ───────────────────────\n");
+                }
             }
         }
 
         output.push_str("```\n");
+
+        if plan.is_truncated() {
+            let mut omitted = Vec::new();
+            let context_hidden = before + after - shown_before - shown_after;
+            if context_hidden > 0 {
+                omitted.push(format!(
+                    "{context_hidden} of {} context lines omitted (farthest from the chunk first)",
+                    before + after
+                ));
+            }
+            if chunk_hidden > 0 {
+                omitted.push(format!(
+                    "{} chunk lines not shown (lines {}-{})",
+                    chunk_hidden,
+                    extraction.chunk_end_line - chunk_hidden + 1,
+                    extraction.chunk_end_line
+                ));
+            }
+            output.push_str(&build_truncation_footer(budget, &omitted, TRUNCATION_HINT));
+        }
+
         output
     }
 }
 
+/// `line` cut after `max_chars` characters, or `None` if it fits
+///
+/// Cuts on a character boundary and says how much was left out.
+fn cut_line(line: &str, max_chars: usize) -> Option<String> {
+    if max_chars == 0 {
+        return None;
+    }
+    let (end, _) = line.char_indices().nth(max_chars)?;
+    let rest = line[end..].chars().count();
+    Some(format!("{}… [+{rest} chars]", &line[..end]))
+}
+
+/// Records line of a data file chunk's preview
+///
+/// A chunk cut from a record longer than the chunk size holds only
+/// part of it, which the line says.
+fn record_header(
+    contents: &str,
+    file_path: &str,
+    records: RecordRange,
+    chunk: Range<usize>,
+) -> String {
+    let total = RecordFormat::for_path(Path::new(file_path))
+        .map(|format| format.record_spans(contents))
+        .unwrap_or_default();
+    let partial = records.first == records.last
+        && total
+            .get(records.first - 1)
+            .is_some_and(|record| *record != chunk);
+    let total = format_count(total.len() as u64);
+    if partial {
+        format!(
+            "part of record {records} (of {total} total); the record is longer than the \
+             chunk size and was split"
+        )
+    } else {
+        format!("{records} (of {total} total)")
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 struct ChunkMetadata {
@@ -264,6 +483,10 @@ struct ChunkMetadata {
     chunk_index: usize,
     offset_start: usize,
     offset_end: usize,
+    /// Chunk text as indexed (`None` when the session does not store it)
+    text: Option<String>,
+    /// Records held, for chunks of data files
+    records: Option<RecordRange>,
 }
 
 #[derive(Debug)]
@@ -281,6 +504,15 @@ struct ContextExtraction {
     context_start_line: usize,
     context_end_line: usize,
     total_lines: usize,
+    /// Context lines asked for on each side
+    requested_context: usize,
+    /// Line numbers cut at `max_line_chars`
+    cut_lines: Vec<usize>,
+    max_line_chars: usize,
+    /// Records held, for chunks of data files (see [`record_header`])
+    records: Option<String>,
+    /// Set when the file changed around the chunk since indexing
+    note: Option<String>,
 }
 
 #[async_trait]
@@ -296,6 +528,8 @@ impl McpToolHandler for PreviewChunkHandler {
                          Provides context expansion without retrieving the entire file. \
                          Use when search results need more surrounding code for understanding. \
                          Shows chunk boundaries with visual markers and line numbers. \
+                         Pass a result handle from search_code (result='r3') instead of \
+                         file_path and chunk_index. \
                          Default: 10 lines context (configurable, max 100)."
                 .to_string(),
             input_schema: json!({
@@ -304,16 +538,25 @@ impl McpToolHandler for PreviewChunkHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID containing the chunk",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "result": {
+                        "type": "string",
+                        "description": "Result handle from a recent search_code or search_batch \
+                                       call (e.g. 'r3'). Use instead of file_path and chunk_index.",
+                        "pattern": "^[rR][0-9]+$"
                     },
                     "file_path": {
                         "type": "string",
-                        "description": "Absolute file path (from search result)",
+                        "description": "File path: absolute (from search result) or relative \
+                                       to the session's repository root (e.g. 'src/lib.rs'). \
+                                       Required with chunk_index unless result is given.",
                         "minLength": 1
                     },
                     "chunk_index": {
                         "type": "integer",
-                        "description": "Chunk index (from search result)",
+                        "description": "Chunk index (from search result). \
+                                       Required with file_path unless result is given.",
                         "minimum": 0
                     },
                     "context_lines": {
@@ -324,7 +567,7 @@ impl McpToolHandler for PreviewChunkHandler {
                         "maximum": 100
                     }
                 },
-                "required": ["session", "file_path", "chunk_index"]
+                "required": ["session"]
             }),
         }
     }
@@ -333,8 +576,9 @@ impl McpToolHandler for PreviewChunkHandler {
         #[derive(Deserialize)]
         struct PreviewArgs {
             session: String,
-            file_path: String,
-            chunk_index: usize,
+            result: Option<String>,
+            file_path: Option<String>,
+            chunk_index: Option<usize>,
             #[serde(default = "default_context_lines")]
             context_lines: usize,
         }
@@ -353,17 +597,81 @@ impl McpToolHandler for PreviewChunkHandler {
             )));
         }
 
+        let (file_path, chunk_index) = match (args.result, args.file_path, args.chunk_index) {
+            (Some(handle), None, None) => {
+                let target = self
+                    .services
+                    .resolve_result(&args.session, &handle)
+                    .map_err(McpError::from)?;
+                (target.file_path, target.chunk_index)
+            }
+            (Some(_), _, _) => {
+                return Err(McpError::InvalidParams(
+                    "Pass either result or file_path and chunk_index, not both".to_string(),
+                ))
+            }
+            (None, Some(file_path), Some(chunk_index)) => (file_path, chunk_index),
+            (None, _, _) => {
+                return Err(McpError::InvalidParams(
+                    "file_path and chunk_index are required unless result is given".to_string(),
+                ))
+            }
+        };
+
+        // Relative paths resolve against the repository root
+        let file = resolve_session_path(&self.services, &args.session, &file_path)?;
+        let file_path = file.path.clone();
+
         // Get chunk metadata from Tantivy
         let chunk_metadata = self
-            .get_chunk_metadata(&args.session, &args.file_path, args.chunk_index)
+            .get_chunk_metadata(&args.session, &file, chunk_index)
             .await?;
 
-        // Extract context from file
-        let path = Path::new(&args.file_path);
-        let extraction = self.extract_context_lines(path, &chunk_metadata, args.context_lines)?;
+        // Locate the chunk in the file as it is now
+        self.services.authorize_read(&file_path)?;
+        let contents = self.read_file(&file_path)?;
+        let indexed = chunk_metadata.offset_start..chunk_metadata.offset_end;
+        let location = match &chunk_metadata.text {
+            Some(text) => locate_chunk(&contents, text, indexed),
+            // Without stored text, edits inside the chunk go unnoticed
+            None if contents.get(indexed.clone()).is_some() => ChunkLocation::Current(indexed),
+            None => {
+                return Err(McpError::InvalidRequest(format!(
+                    "File changed since indexing: bytes {}-{} are no longer in {}, and \
+                     session '{}' does not store chunk text. Re-index the session to refresh it.",
+                    indexed.start, indexed.end, file_path, args.session
+                )))
+            }
+        };
 
-        // Format response
-        let formatted = self.format_preview(&extraction, &args.file_path, &args.session);
+        // Format response (the indexed text when the chunk is gone)
+        let formatted = match location.range() {
+            Some(range) => {
+                let max_line_chars = self.services.config.current().output.max_line_chars;
+                let mut extraction = self.extract_context_lines(
+                    &contents,
+                    range.clone(),
+                    args.context_lines,
+                    max_line_chars,
+                )?;
+                extraction.note = location.note();
+                extraction.records = chunk_metadata
+                    .records
+                    .map(|records| record_header(&contents, &file_path, records, range));
+                self.format_preview(
+                    &extraction,
+                    &file,
+                    &args.session,
+                    &ResponseBudget::default(),
+                )
+            }
+            None => self.format_indexed_chunk(
+                &chunk_metadata,
+                chunk_metadata.text.as_deref().unwrap_or_default(),
+                &args.session,
+                &location.note().unwrap_or_default(),
+            ),
+        };
 
         Ok(text_content(formatted))
     }
@@ -453,6 +761,14 @@ mod tests {
     }
 
     // Helper function to create test handler
+    fn absolute(path: &str) -> ResolvedPath {
+        ResolvedPath {
+            requested: path.to_string(),
+            path: path.to_string(),
+            root: None,
+        }
+    }
+
     fn create_test_handler() -> PreviewChunkHandler {
         let config = crate::core::config::Config::default();
         let services = Arc::new(crate::core::services::Services::new(config));
@@ -475,19 +791,12 @@ mod tests {
     fn test_extract_context_lines_basic() {
         let (handler, _temp) = create_test_handler_with_storage();
 
-        let dir = tempfile::TempDir::new().unwrap();
-        let file = dir.path().join("test.rs");
-        std::fs::write(&file, "line 0\nline 1\nline 2\nline 3\nline 4\nline 5\n").unwrap();
+        let contents = "line 0\nline 1\nline 2\nline 3\nline 4\nline 5\n";
 
         // Chunk covers "line 2\n" which starts at offset 14 and ends at 20
-        let metadata = ChunkMetadata {
-            file_path: file.to_str().unwrap().to_string(),
-            chunk_index: 0,
-            offset_start: 14,
-            offset_end: 20,
-        };
-
-        let result = handler.extract_context_lines(&file, &metadata, 1).unwrap();
+        let result = handler
+            .extract_context_lines(contents, 14..20, 1, 0)
+            .unwrap();
 
         assert_eq!(result.chunk_start_line, 3); // 1-indexed line 3
         assert_eq!(result.chunk_end_line, 3);
@@ -501,19 +810,10 @@ mod tests {
     fn test_extract_context_lines_at_file_start() {
         let (handler, _temp) = create_test_handler_with_storage();
 
-        let dir = tempfile::TempDir::new().unwrap();
-        let file = dir.path().join("start.rs");
-        std::fs::write(&file, "first\nsecond\nthird\nfourth\n").unwrap();
+        let contents = "first\nsecond\nthird\nfourth\n";
 
         // Chunk covers "first\n" (offset 0-5)
-        let metadata = ChunkMetadata {
-            file_path: file.to_str().unwrap().to_string(),
-            chunk_index: 0,
-            offset_start: 0,
-            offset_end: 5,
-        };
-
-        let result = handler.extract_context_lines(&file, &metadata, 5).unwrap();
+        let result = handler.extract_context_lines(contents, 0..5, 5, 0).unwrap();
 
         // Before-context should be truncated to 0 (can't go before start)
         assert_eq!(result.context_start_line, 1);
@@ -524,19 +824,12 @@ mod tests {
     fn test_extract_context_lines_at_file_end() {
         let (handler, _temp) = create_test_handler_with_storage();
 
-        let dir = tempfile::TempDir::new().unwrap();
-        let file = dir.path().join("end.rs");
-        std::fs::write(&file, "aaa\nbbb\nccc\n").unwrap();
+        let contents = "aaa\nbbb\nccc\n";
 
         // Chunk covers "ccc\n" (line 2, offset 8-11)
-        let metadata = ChunkMetadata {
-            file_path: file.to_str().unwrap().to_string(),
-            chunk_index: 0,
-            offset_start: 8,
-            offset_end: 11,
-        };
-
-        let result = handler.extract_context_lines(&file, &metadata, 10).unwrap();
+        let result = handler
+            .extract_context_lines(contents, 8..11, 10, 0)
+            .unwrap();
 
         // After-context should be truncated to end of file
         assert_eq!(result.chunk_end_line, 3); // last line
@@ -548,18 +841,9 @@ mod tests {
     fn test_extract_context_lines_zero_context() {
         let (handler, _temp) = create_test_handler_with_storage();
 
-        let dir = tempfile::TempDir::new().unwrap();
-        let file = dir.path().join("zero.rs");
-        std::fs::write(&file, "aa\nbb\ncc\ndd\nee\n").unwrap();
-
-        let metadata = ChunkMetadata {
-            file_path: file.to_str().unwrap().to_string(),
-            chunk_index: 0,
-            offset_start: 3, // "bb" line
-            offset_end: 5,
-        };
+        let contents = "aa\nbb\ncc\ndd\nee\n";
 
-        let result = handler.extract_context_lines(&file, &metadata, 0).unwrap();
+        let result = handler.extract_context_lines(contents, 3..5, 0, 0).unwrap();
 
         // Zero context = just the chunk line
         assert_eq!(result.context_start_line, result.chunk_start_line);
@@ -583,15 +867,195 @@ mod tests {
             context_start_line: 1,
             context_end_line: 3,
             total_lines: 3,
+            requested_context: 1,
+            cut_lines: Vec::new(),
+            max_line_chars: 300,
+            records: None,
+            note: None,
         };
 
-        let output = handler.format_preview(&extraction, "/src/main.rs", "test-session");
+        let output = handler.format_preview(
+            &extraction,
+            &absolute("/src/main.rs"),
+            "test-session",
+            &ResponseBudget::default(),
+        );
 
         assert!(output.contains("/src/main.rs"));
         assert!(output.contains("test-session"));
         assert!(output.contains("CHUNK START"));
         assert!(output.contains("CHUNK END"));
         assert!(output.contains("```rust"));
+        assert!(!output.contains("RESPONSE TRUNCATED"));
+    }
+
+    /// Lines 1-100 of 400 chars each, chunk at lines 41-60
+    fn oversized_extraction() -> ContextExtraction {
+        ContextExtraction {
+            lines: (1..=100)
+                .map(|n| format!("{n:4} | {}", "x".repeat(400)))
+                .collect(),
+            chunk_start_line: 41,
+            chunk_end_line: 60,
+            context_start_line: 1,
+            context_end_line: 100,
+            total_lines: 100,
+            requested_context: 40,
+            cut_lines: Vec::new(),
+            max_line_chars: 300,
+            records: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_format_preview_drops_far_context_to_fit_budget() {
+        let handler = create_test_handler();
+        let budget = ResponseBudget::new(5_000);
+
+        let output = handler.format_preview(
+            &oversized_extraction(),
+            &absolute("/src/main.rs"),
+            "test-session",
+            &budget,
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        // The whole chunk and the nearest context survive
+        for n in 40..=61 {
+            assert!(output.contains(&format!("{n:4} | ")), "line {n} missing");
+        }
+        assert!(!output.contains("   1 | "));
+        assert!(!output.contains(" 100 | "));
+        assert!(output.contains("CHUNK END"));
+
+        let shown_context = output.matches(" | ").count() - 20;
+        assert!(output.contains(&format!(
+            "{} of 80 context lines omitted (farthest from the chunk first)",
+            80 - shown_context
+        )));
+        assert!(!output.contains("chunk lines not shown"));
+    }
+
+    #[test]
+    fn test_format_preview_drops_chunk_tail_last() {
+        let handler = create_test_handler();
+        let budget = ResponseBudget::new(1_500);
+
+        let output = handler.format_preview(
+            &oversized_extraction(),
+            &absolute("/src/main.rs"),
+            "test-session",
+            &budget,
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        assert!(output.contains("**Context:** 0 lines before + 0 lines after"));
+        assert!(output.contains("  41 | "));
+        assert!(output.contains("CHUNK TRUNCATED"));
+
+        let hidden = 20 - output.matches(" | ").count();
+        assert!(output.contains("80 of 80 context lines omitted"));
+        assert!(output.contains(&format!(
+            "{hidden} chunk lines not shown (lines {}-60)",
+            61 - hidden
+        )));
+        assert!(output.contains(TRUNCATION_HINT));
+    }
+
+    #[test]
+    fn test_cut_line() {
+        assert_eq!(cut_line("short", 10), None);
+        assert_eq!(cut_line("exactly10!", 10), None);
+        assert_eq!(cut_line("long line here", 4).unwrap(), "long… [+10 chars]");
+        // Multi-byte characters are never split
+        assert_eq!(cut_line("ééééé", 2).unwrap(), "éé… [+3 chars]");
+        assert_eq!(cut_line(&"x".repeat(5_000), 0), None);
+    }
+
+    /// 250 lines of 5,000 characters (a minified bundle), chunk at 120-130
+    fn minified_contents() -> (String, Range<usize>) {
+        let lines: Vec<String> = (1..=250)
+            .map(|n| format!("{n:05}{}", "a".repeat(4_995)))
+            .collect();
+        let contents = lines.join("\n") + "\n";
+        let start: usize = lines[..119].iter().map(|l| l.len() + 1).sum();
+        let end = start + lines[119..130].iter().map(|l| l.len() + 1).sum::<usize>() - 1;
+        (contents, start..end)
+    }
+
+    #[test]
+    fn test_format_preview_cuts_long_lines_and_reduces_window() {
+        let handler = create_test_handler();
+        let (contents, chunk) = minified_contents();
+        let extraction = handler
+            .extract_context_lines(&contents, chunk, 100, 300)
+            .unwrap();
+        assert_eq!(extraction.cut_lines.len(), 211);
+        assert!(extraction.lines[0].starts_with("  20 | 00020aaa"));
+        assert!(extraction.lines[0].ends_with("… [+4700 chars]"));
+
+        let budget = ResponseBudget::new(10_000);
+        let output = handler.format_preview(
+            &extraction,
+            &absolute("/dist/app.min.js"),
+            "test-session",
+            &budget,
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        assert!(output.contains("┌─ CHUNK START"));
+        assert!(output.contains("└─ CHUNK END"));
+        let window: usize = output
+            .split("showing ±")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|w| w.parse().ok())
+            .expect("reduction note");
+        assert!(output.contains(&format!(
+            "**Context Reduced:** requested ±100 lines, showing ±{window} due to size limits"
+        )));
+        assert!(window < 100);
+        // Symmetric: the window's edges are shown, the lines beyond are not
+        assert!(output.contains(&format!("{:4} | ", 120 - window)));
+        assert!(output.contains(&format!("{:4} | ", 130 + window)));
+        assert!(!output.contains(&format!("{:4} | ", 119 - window)));
+        assert!(!output.contains(&format!("{:4} | ", 131 + window)));
+        let shown = 11 + 2 * window;
+        assert!(output.contains(&format!(
+            "**Long Lines:** {shown} lines cut at 300 characters"
+        )));
+        assert_eq!(output.matches("… [+").count(), shown);
+    }
+
+    #[test]
+    fn test_extract_context_lines_gutter_stays_aligned() {
+        let handler = create_test_handler();
+        let contents: String = (1..=12_000)
+            .map(|n| {
+                if n == 9_999 {
+                    "y".repeat(50)
+                } else {
+                    format!("l{n}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let start = contents.find("\nl10000").unwrap() + 1;
+        let extraction = handler
+            .extract_context_lines(&contents, start..start + 6, 2, 10)
+            .unwrap();
+
+        // Every gutter is as wide as the widest line number, cut or not
+        assert_eq!(extraction.lines[0], " 9998 | l9998");
+        assert_eq!(extraction.lines[1], " 9999 | yyyyyyyyyy… [+40 chars]");
+        assert_eq!(extraction.lines[2], "10000 | l10000");
+        let bars: Vec<usize> = extraction
+            .lines
+            .iter()
+            .map(|l| l.find('|').unwrap())
+            .collect();
+        assert!(bars.iter().all(|&b| b == bars[0]), "{bars:?}");
     }
 
     // --- Phase 2C: Execute error paths ---
@@ -698,6 +1162,93 @@ mod tests {
         assert!(text.contains("CHUNK END"));
     }
 
+    #[tokio::test]
+    async fn test_preview_chunk_shows_csv_records() {
+        let (handler, _temp) = create_test_handler_with_storage();
+
+        // Quoted fields span two lines, so records and lines differ
+        let repo_dir = tempfile::TempDir::new().unwrap();
+        let file_path = repo_dir.path().join("notes.csv");
+        let mut csv = String::from("id,note\n");
+        for i in 1..=30 {
+            csv.push_str(&format!("{i},\"line a\nline b\"\n"));
+        }
+        csv.push_str(&format!("31,\"{}\"\n32,end\n", "x".repeat(700)));
+        std::fs::write(&file_path, &csv).unwrap();
+        index_test_repo(&handler, repo_dir.path(), "preview-csv");
+
+        async fn preview(handler: &PreviewChunkHandler, path: &str, chunk_index: usize) -> String {
+            let args = serde_json::json!({
+                "session": "preview-csv",
+                "file_path": path,
+                "chunk_index": chunk_index,
+                "context_lines": 0
+            });
+            match &handler.execute(args).await.unwrap().content[0] {
+                crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+            }
+        }
+        let path = file_path.to_str().unwrap();
+
+        let text = preview(&handler, path, 0).await;
+        assert!(
+            text.contains("**Chunk Records:** 1–28 (of 33 total)"),
+            "{text}"
+        );
+        assert!(text.contains("**Chunk Lines:** 1-55"), "{text}");
+
+        // The 700-character record is split across two chunks
+        let text = preview(&handler, path, 2).await;
+        assert!(
+            text.contains("**Chunk Records:** part of record 32 (of 33 total)"),
+            "{text}"
+        );
+        let text = preview(&handler, path, 4).await;
+        assert!(
+            text.contains("**Chunk Records:** 33 (of 33 total)"),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_chunk_file_changed_since_indexing() {
+        let (handler, _temp) = create_test_handler_with_storage();
+
+        let repo_dir = tempfile::TempDir::new().unwrap();
+        let file_path = repo_dir.path().join("changed.rs");
+        std::fs::write(&file_path, "fn target() {\n    work();\n}\n").unwrap();
+        index_test_repo(&handler, repo_dir.path(), "preview-drift");
+
+        let args = serde_json::json!({
+            "session": "preview-drift",
+            "file_path": file_path.to_str().unwrap(),
+            "chunk_index": 0,
+            "context_lines": 0
+        });
+        async fn preview(handler: &PreviewChunkHandler, args: &Value) -> String {
+            match &handler.execute(args.clone()).await.unwrap().content[0] {
+                crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+            }
+        }
+
+        // Lines inserted before the chunk: offsets corrected
+        std::fs::write(
+            &file_path,
+            "// header\n// more\nfn target() {\n    work();\n}\n",
+        )
+        .unwrap();
+        let text = preview(&handler, &args).await;
+        assert!(text.contains("**Chunk Lines:** 3-5"), "{text}");
+        assert!(text.contains("chunk moved +18 bytes"), "{text}");
+
+        // Chunk deleted: the indexed text is shown
+        std::fs::write(&file_path, "fn other() {}\n").unwrap();
+        let text = preview(&handler, &args).await;
+        assert!(text.contains("no longer found in the file"), "{text}");
+        assert!(text.contains("fn target() {"), "{text}");
+        assert!(!text.contains("CHUNK START"));
+    }
+
     #[tokio::test]
     async fn test_preview_chunk_chunk_index_zero() {
         let (handler, _temp) = create_test_handler_with_storage();
@@ -1,8 +1,11 @@
 //! Read file tool handler
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::{detect_language, format_bytes};
+use super::helpers::{detect_language, format_bytes, resolve_session_path};
+use crate::core::archive::{self, Archive, MAX_ENTRY_READ_BYTES};
+use crate::core::paths::{self, ResolvedPath};
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use crate::mcp::utils::{build_read_file_warning, READ_FILE_MAX_CHARS};
@@ -10,7 +13,7 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::io::{Read, Seek, SeekFrom};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::Arc;
 
 const DEFAULT_MAX_SIZE_KB: usize = 1024; // 1 MB default
@@ -26,7 +29,8 @@ impl ReadFileHandler {
     }
 
     /// Validate file is within indexed paths
-    fn validate_file_in_session(&self, session: &str, file_path: &Path) -> Result<(), McpError> {
+    fn validate_file_in_session(&self, session: &str, file: &ResolvedPath) -> Result<(), McpError> {
+        let file_path = file.path.as_str();
         // Check if session exists first
         if !self.services.storage.session_exists(session) {
             return Err(McpError::InvalidRequest(format!(
@@ -61,11 +65,7 @@ impl ReadFileHandler {
         use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
         use tantivy::Term;
 
-        let file_path_str = file_path.to_str().ok_or_else(|| {
-            McpError::InvalidRequest("File path contains invalid UTF-8".to_string())
-        })?;
-
-        let file_term = Term::from_field_text(file_path_field, file_path_str);
+        let file_term = Term::from_field_text(file_path_field, file_path);
         let session_term = Term::from_field_text(session_field, session);
 
         let file_query: Box<dyn Query> = Box::new(TermQuery::new(file_term, Default::default()));
@@ -83,9 +83,10 @@ impl ReadFileHandler {
 
         if top_docs == 0 {
             return Err(McpError::InvalidRequest(format!(
-                "File '{file_path_str}' not indexed in \
+                "File {} not indexed in \
                  session '{session}'. Check file_path or \
-                 re-index the session."
+                 re-index the session.",
+                file.describe()
             )));
         }
 
@@ -178,24 +179,38 @@ impl ReadFileHandler {
         Ok((content, bytes_consumed, total_size))
     }
 
+    /// Extract an archive entry (`{archive}!{entry}`) into memory
+    fn read_archive_entry(&self, archive_path: &Path, entry: &str) -> Result<Vec<u8>, McpError> {
+        let archive = Archive::detect(archive_path).ok_or_else(|| {
+            McpError::InvalidRequest(format!(
+                "Archive not found: {}. It may have been moved or \
+                 deleted since indexing. Try re-indexing the session.",
+                archive_path.display()
+            ))
+        })?;
+        archive
+            .read_entry(entry, MAX_ENTRY_READ_BYTES)
+            .map_err(McpError::from)
+    }
+
     /// Format response with metadata
     fn format_response(
         &self,
-        file_path: &str,
+        file: &ResolvedPath,
         contents: &str,
         size_bytes: u64,
         session: &str,
     ) -> String {
-        let lang = detect_language(file_path);
+        let lang = detect_language(&file.path);
         let line_count = contents.lines().count();
 
         format!(
-            "**File:** `{}`\n\
+            "**File:** {}\n\
              **Session:** `{}`\n\
              **Size:** {} ({} lines)\n\
              **Language:** {}\n\n\
              ```{}\n{}\n```",
-            file_path,
+            file.label(),
             session,
             format_bytes(size_bytes),
             line_count,
@@ -208,24 +223,24 @@ impl ReadFileHandler {
     /// Format response with byte range info for offset reads
     fn format_response_with_offset(
         &self,
-        file_path: &str,
+        file: &ResolvedPath,
         contents: &str,
         total_size: usize,
         offset: usize,
         bytes_consumed: usize,
         session: &str,
     ) -> String {
-        let lang = detect_language(file_path);
+        let lang = detect_language(&file.path);
         let line_count = contents.lines().count();
         let end_byte = offset + bytes_consumed;
 
         format!(
-            "**File:** `{}`\n\
+            "**File:** {}\n\
              **Session:** `{}`\n\
              **Size:** {} (showing bytes {}-{} of {})\n\
              **Language:** {} ({} lines in chunk)\n\n\
              ```{}\n{}\n```",
-            file_path,
+            file.label(),
             session,
             format_bytes(total_size as u64),
             offset,
@@ -239,6 +254,40 @@ impl ReadFileHandler {
     }
 }
 
+/// [`ReadFileHandler::read_file_contents`] for an entry already in memory
+fn contents_from_bytes(bytes: &[u8]) -> Result<(String, bool, usize), McpError> {
+    if bytes.len() > READ_FILE_MAX_CHARS {
+        let content = ensure_utf8_boundary(&bytes[..READ_FILE_MAX_CHARS]);
+        return Ok((content, true, bytes.len()));
+    }
+    let content = std::str::from_utf8(bytes).map_err(|_| {
+        McpError::InvalidRequest(
+            "File contains non-UTF-8 data (binary file). \
+             Cannot display in MCP response."
+                .to_string(),
+        )
+    })?;
+    Ok((content.to_string(), false, bytes.len()))
+}
+
+/// [`ReadFileHandler::read_file_chunk`] for an entry already in memory
+fn chunk_from_bytes(bytes: &[u8], offset: usize, length: usize) -> (String, usize, usize) {
+    if offset >= bytes.len() {
+        return (String::new(), 0, bytes.len());
+    }
+    let chunk = &bytes[offset..bytes.len().min(offset + length)];
+    let start_skip = if offset > 0 {
+        find_utf8_start(chunk)
+    } else {
+        0
+    };
+    (
+        ensure_utf8_boundary(&chunk[start_skip..]),
+        chunk.len(),
+        bytes.len(),
+    )
+}
+
 /// Ensure buffer ends on UTF-8 character boundary
 ///
 /// If the buffer contains invalid UTF-8, this function will
@@ -287,7 +336,9 @@ impl McpToolHandler for ReadFileHandler {
                 truncated). Binary files are rejected. Returns \
                 Markdown-formatted code with syntax highlighting. \
                 Supports offset-based pagination for reading large \
-                files incrementally."
+                files incrementally. Pass a result handle from \
+                search_code (result='r3') instead of file_path \
+                to read that result's file."
                 .to_string(),
             input_schema: json!({
                 "type": "object",
@@ -296,15 +347,27 @@ impl McpToolHandler for ReadFileHandler {
                         "type": "string",
                         "description":
                             "Session ID containing the file",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "file_path": {
                         "type": "string",
                         "description":
-                            "Absolute path to file \
-                             (from search results or list_dir)",
+                            "Path to file: absolute (from search \
+                             results or list_dir) or relative to the \
+                             session's repository root (e.g. \
+                             'src/lib.rs'). Required unless result \
+                             is given.",
                         "minLength": 1
                     },
+                    "result": {
+                        "type": "string",
+                        "description":
+                            "Result handle from a recent \
+                             search_code or search_batch call \
+                             (e.g. 'r3'); reads that result's \
+                             file. Use instead of file_path.",
+                        "pattern": "^[rR][0-9]+$"
+                    },
                     "max_size_kb": {
                         "type": "integer",
                         "description":
@@ -335,7 +398,7 @@ impl McpToolHandler for ReadFileHandler {
                         "maximum": 20000
                     }
                 },
-                "required": ["session", "file_path"]
+                "required": ["session"]
             }),
         }
     }
@@ -344,7 +407,8 @@ impl McpToolHandler for ReadFileHandler {
         #[derive(Deserialize)]
         struct ReadFileArgs {
             session: String,
-            file_path: String,
+            file_path: Option<String>,
+            result: Option<String>,
             #[serde(default = "default_max_size")]
             max_size_kb: usize,
             offset: Option<usize>,
@@ -358,8 +422,28 @@ impl McpToolHandler for ReadFileHandler {
         let args: ReadFileArgs =
             serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
 
+        let file_path = match (args.result, args.file_path) {
+            (Some(handle), None) => {
+                self.services
+                    .resolve_result(&args.session, &handle)
+                    .map_err(McpError::from)?
+                    .file_path
+            }
+            (Some(_), Some(_)) => {
+                return Err(McpError::InvalidParams(
+                    "Pass either result or file_path, not both".to_string(),
+                ))
+            }
+            (None, Some(file_path)) => file_path,
+            (None, None) => {
+                return Err(McpError::InvalidParams(
+                    "file_path is required unless result is given".to_string(),
+                ))
+            }
+        };
+
         // Validate parameters
-        if args.file_path.trim().is_empty() {
+        if file_path.trim().is_empty() {
             return Err(McpError::InvalidParams(
                 "file_path cannot be empty".to_string(),
             ));
@@ -372,20 +456,29 @@ impl McpToolHandler for ReadFileHandler {
             )));
         }
 
-        let path = PathBuf::from(&args.file_path);
+        // Relative paths resolve against the repository root. The index
+        // stores normalized paths; the filesystem wants native ones.
+        let file = resolve_session_path(&self.services, &args.session, &file_path)?;
+        let file_path = file.path.clone();
+        let path = paths::to_native(&file_path);
 
         // Validate session exists and file is in session
-        self.validate_file_in_session(&args.session, &path)?;
-
-        // Check file exists
-        if !path.exists() {
-            return Err(McpError::InvalidRequest(format!(
-                "File not found: {}. File may have been \
-                 deleted since indexing. Try re-indexing \
-                 the session.",
-                args.file_path
-            )));
-        }
+        self.validate_file_in_session(&args.session, &file)?;
+        self.services.authorize_read(&file_path)?;
+
+        // Archive entries are extracted whole; files are read from disk
+        let entry = match archive::split_entry_path(&file_path) {
+            Some((archive_path, entry)) => Some(self.read_archive_entry(&archive_path, entry)?),
+            None if !path.exists() => {
+                return Err(McpError::InvalidRequest(format!(
+                    "File not found: {}. File may have been \
+                     deleted since indexing. Try re-indexing \
+                     the session.",
+                    file_path
+                )));
+            }
+            None => None,
+        };
 
         // Determine if using offset-based pagination
         let using_offset = args.offset.is_some() || args.length.is_some();
@@ -397,14 +490,16 @@ impl McpToolHandler for ReadFileHandler {
                 .unwrap_or(READ_FILE_MAX_CHARS)
                 .min(READ_FILE_MAX_CHARS);
 
-            let (contents, bytes_consumed, total_size) =
-                self.read_file_chunk(&path, offset, length)?;
+            let (contents, bytes_consumed, total_size) = match &entry {
+                Some(bytes) => chunk_from_bytes(bytes, offset, length),
+                None => self.read_file_chunk(&path, offset, length)?,
+            };
 
             let mut output = String::new();
 
             // Format with offset info
             let formatted = self.format_response_with_offset(
-                &args.file_path,
+                &file,
                 &contents,
                 total_size,
                 offset,
@@ -426,23 +521,22 @@ impl McpToolHandler for ReadFileHandler {
         } else {
             // Original behavior: read from start with
             // auto-truncation
-            let (contents, was_truncated, total_size) = self.read_file_contents(&path)?;
+            let (contents, was_truncated, total_size) = match &entry {
+                Some(bytes) => contents_from_bytes(bytes)?,
+                None => self.read_file_contents(&path)?,
+            };
 
             let mut output = String::new();
 
             if was_truncated {
                 let shown_lines = contents.lines().count();
-                let warning = build_read_file_warning(
-                    contents.len(),
-                    total_size,
-                    shown_lines,
-                    &args.file_path,
-                );
+                let warning =
+                    build_read_file_warning(contents.len(), total_size, shown_lines, &file_path);
                 output.push_str(&warning);
             }
 
             let formatted =
-                self.format_response(&args.file_path, &contents, total_size as u64, &args.session);
+                self.format_response(&file, &contents, total_size as u64, &args.session);
             output.push_str(&formatted);
 
             // Add next-offset hint if file was truncated
@@ -467,6 +561,7 @@ mod tests {
     use crate::core::types::Chunk;
     use std::fs;
     use std::io::Write;
+    use std::path::PathBuf;
     use tempfile::TempDir;
 
     async fn setup_test_handler() -> (ReadFileHandler, TempDir) {
@@ -480,24 +575,26 @@ mod tests {
         (handler, temp_dir)
     }
 
+    /// Index `file_path`, relative to a repository in the test's temp dir
     async fn create_test_session_with_file(
         services: &Arc<Services>,
         session_id: &str,
         file_path: &str,
         content: &str,
     ) -> PathBuf {
-        let full_path = PathBuf::from(file_path);
+        let full_path = services.storage.storage_root().join("repo").join(file_path);
         if let Some(parent) = full_path.parent() {
             fs::create_dir_all(parent).unwrap();
         }
         let mut file = fs::File::create(&full_path).unwrap();
         file.write_all(content.as_bytes()).unwrap();
 
+        // The file must be under the session's repository to be readable
         let mut index = services
             .storage
             .create_session(
                 session_id,
-                PathBuf::from("/test/repo"),
+                full_path.parent().unwrap().to_path_buf(),
                 SessionConfig::default(),
             )
             .unwrap();
@@ -508,6 +605,8 @@ mod tests {
             start_offset: 0,
             end_offset: content.len(),
             chunk_index: 0,
+            records: None,
+            section: None,
         }];
 
         index.add_chunks(&chunks, session_id).unwrap();
@@ -530,7 +629,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "test-session",
-            "/tmp/shebe-test-read-file.rs",
+            "shebe-test-read-file.rs",
             test_content,
         )
         .await;
@@ -551,13 +650,11 @@ mod tests {
         assert!(text.contains("**Size:**"));
         assert!(text.contains("**Language:**"));
         assert!(text.contains(test_content));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
     async fn test_read_file_not_found() {
-        let (handler, _temp) = setup_test_handler().await;
+        let (handler, temp) = setup_test_handler().await;
 
         {
             let mut index = handler
@@ -574,7 +671,7 @@ mod tests {
 
         let args = json!({
             "session": "test-session",
-            "file_path": "/tmp/nonexistent.rs",
+            "file_path": temp.path().join("nonexistent.rs"),
         });
 
         let result = handler.execute(args).await;
@@ -600,7 +697,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "test-session",
-            "/tmp/shebe-test-large.txt",
+            "shebe-test-large.txt",
             &large_content,
         )
         .await;
@@ -620,8 +717,6 @@ mod tests {
 
         // Should have next-offset hint
         assert!(text.contains("offset="));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -631,7 +726,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "test-session",
-            "/tmp/shebe-test-utf8.txt",
+            "shebe-test-utf8.txt",
             special_content,
         )
         .await;
@@ -647,17 +742,15 @@ mod tests {
         let tool_result = result.unwrap();
         let text = extract_text(&tool_result);
         assert!(text.contains(special_content));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
     async fn test_read_file_session_not_found() {
-        let (handler, _temp) = setup_test_handler().await;
+        let (handler, temp) = setup_test_handler().await;
 
         let args = json!({
             "session": "nonexistent-session",
-            "file_path": "/tmp/test.rs",
+            "file_path": temp.path().join("test.rs"),
         });
 
         let result = handler.execute(args).await;
@@ -681,7 +774,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "truncate-test",
-            "/tmp/shebe-read-truncate.txt",
+            "shebe-read-truncate.txt",
             &content,
         )
         .await;
@@ -701,8 +794,6 @@ mod tests {
         assert!(text.contains("FIRST 20000 CHARACTERS"));
         assert!(text.contains("of"));
         assert!(text.len() < content.len());
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -713,7 +804,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "small-test",
-            "/tmp/shebe-read-small.txt",
+            "shebe-read-small.txt",
             &content,
         )
         .await;
@@ -731,8 +822,6 @@ mod tests {
 
         assert!(!text.contains("FILE TRUNCATED"));
         assert!(text.contains(&content));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -745,7 +834,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "exact-limit-test",
-            "/tmp/shebe-read-exact.txt",
+            "shebe-read-exact.txt",
             &content,
         )
         .await;
@@ -763,8 +852,6 @@ mod tests {
 
         assert!(!text.contains("FILE TRUNCATED"));
         assert!(text.contains(&content));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -776,7 +863,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "utf8-test",
-            "/tmp/shebe-read-utf8.txt",
+            "shebe-read-utf8.txt",
             &content,
         )
         .await;
@@ -795,8 +882,6 @@ mod tests {
         assert!(text.contains("FILE TRUNCATED"));
         assert!(text.is_char_boundary(text.len()));
         assert!(text.contains("\u{1F600}"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -816,7 +901,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "mixed-utf8-test",
-            "/tmp/shebe-read-mixed.txt",
+            "shebe-read-mixed.txt",
             &content,
         )
         .await;
@@ -833,8 +918,6 @@ mod tests {
         let text = extract_text(&tool_result);
 
         assert!(text.is_char_boundary(text.len()));
-
-        let _ = fs::remove_file(file_path);
     }
 
     // Offset pagination tests
@@ -848,7 +931,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "offset-test",
-            "/tmp/shebe-read-offset.txt",
+            "shebe-read-offset.txt",
             content,
         )
         .await;
@@ -871,8 +954,6 @@ mod tests {
         assert!(text.contains("BBBB"));
         // Should show byte range info
         assert!(text.contains("showing bytes"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -883,7 +964,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "lastchunk-test",
-            "/tmp/shebe-read-lastchunk.txt",
+            "shebe-read-lastchunk.txt",
             content,
         )
         .await;
@@ -906,8 +987,6 @@ mod tests {
         assert!(text.contains("file"));
         // Should NOT have "more content available"
         assert!(!text.contains("offset="));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -918,7 +997,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "beyond-test",
-            "/tmp/shebe-read-beyond.txt",
+            "shebe-read-beyond.txt",
             content,
         )
         .await;
@@ -938,8 +1017,6 @@ mod tests {
 
         // Should show empty content (0 lines in chunk)
         assert!(text.contains("0 lines in chunk"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -952,7 +1029,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "utf8off-test",
-            "/tmp/shebe-read-utf8off.txt",
+            "shebe-read-utf8off.txt",
             content,
         )
         .await;
@@ -974,8 +1051,6 @@ mod tests {
 
         // Should be valid UTF-8 (no panic)
         assert!(text.is_char_boundary(text.len()));
-
-        let _ = fs::remove_file(file_path);
     }
 
     #[tokio::test]
@@ -987,7 +1062,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "morehint-test",
-            "/tmp/shebe-read-morehint.txt",
+            "shebe-read-morehint.txt",
             &content,
         )
         .await;
@@ -1009,8 +1084,6 @@ mod tests {
         // Should have "more content" hint with next offset
         assert!(text.contains("More content available"));
         assert!(text.contains("offset=10000"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     // Unit tests for find_utf8_start
@@ -1051,7 +1124,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "nooff-fmt-test",
-            "/tmp/shebe-rf-nooff-fmt.rs",
+            "shebe-rf-nooff-fmt.rs",
             content,
         )
         .await;
@@ -1076,8 +1149,6 @@ mod tests {
         assert!(!text.contains("offset="));
         assert!(!text.contains("More content available"));
         assert!(!text.contains("lines in chunk"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Center: offset=0 returns same file content as omitting
@@ -1089,7 +1160,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "off0-test",
-            "/tmp/shebe-rf-off0.txt",
+            "shebe-rf-off0.txt",
             content,
         )
         .await;
@@ -1120,22 +1191,20 @@ mod tests {
             text_off.contains(content),
             "Offset=0 response missing file content"
         );
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P0 Center: Sequential chunks reassemble to original.
     /// Uses read_file_chunk directly for byte-level testing.
     #[tokio::test]
     async fn test_read_file_full_reassembly_matches_original() {
-        let (handler, _temp) = setup_test_handler().await;
+        let (handler, temp) = setup_test_handler().await;
 
         // 50KB ASCII file with distinct lines
         let original: String = (0..2000)
             .map(|i| format!("Line {i:04}: test content\n"))
             .collect();
 
-        let file_path = PathBuf::from("/tmp/shebe-rf-reassemble.txt");
+        let file_path = temp.path().join("shebe-rf-reassemble.txt");
         fs::write(&file_path, &original).unwrap();
 
         let chunk_len = 10000;
@@ -1160,8 +1229,6 @@ mod tests {
             reassembled, original,
             "Reassembled chunks must match original"
         );
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Boundary: offset = file_size - 1 returns last byte.
@@ -1172,7 +1239,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "exactend-test",
-            "/tmp/shebe-rf-exactend.txt",
+            "shebe-rf-exactend.txt",
             content,
         )
         .await;
@@ -1190,8 +1257,6 @@ mod tests {
 
         assert!(text.contains("showing bytes 9-10 of 10"));
         assert!(!text.contains("More content available"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Beyond: offset = file_size exactly returns empty content.
@@ -1202,7 +1267,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "atsize-test",
-            "/tmp/shebe-rf-atsize.txt",
+            "shebe-rf-atsize.txt",
             content,
         )
         .await;
@@ -1220,8 +1285,6 @@ mod tests {
 
         assert!(text.contains("0 lines in chunk"));
         assert!(!text.contains("More content available"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Center: Small file needs no pagination. Entire file
@@ -1233,7 +1296,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "smallnp-test",
-            "/tmp/shebe-rf-smallnp.txt",
+            "shebe-rf-smallnp.txt",
             content,
         )
         .await;
@@ -1250,8 +1313,6 @@ mod tests {
         assert!(!text.contains("FILE TRUNCATED"));
         assert!(!text.contains("More content available"));
         assert!(!text.contains("offset="));
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Boundary: Offset on byte 2 of a 4-byte UTF-8 char
@@ -1267,7 +1328,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "utf8mid-test",
-            "/tmp/shebe-rf-utf8mid.txt",
+            "shebe-rf-utf8mid.txt",
             content,
         )
         .await;
@@ -1296,22 +1357,20 @@ mod tests {
 
         // endOffset reflects actual bytes consumed (4+6=10)
         assert!(text.contains("showing bytes 4-10 of 10"));
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P1 Boundary: 10KB ASCII file in 1KB chunks. No bytes
     /// lost or duplicated at chunk boundaries.
     #[tokio::test]
     async fn test_read_file_chunk_boundaries_no_data_loss() {
-        let (handler, _temp) = setup_test_handler().await;
+        let (handler, temp) = setup_test_handler().await;
 
         // 10000 bytes of distinct ASCII content
         let original: String = (0..10000_usize)
             .map(|i| char::from(b'A' + (i % 26) as u8))
             .collect();
 
-        let file_path = PathBuf::from("/tmp/shebe-rf-chunkbd.txt");
+        let file_path = temp.path().join("shebe-rf-chunkbd.txt");
         fs::write(&file_path, &original).unwrap();
 
         let chunk_len = 1000;
@@ -1341,8 +1400,6 @@ mod tests {
             reassembled, original,
             "Reassembled content must match original"
         );
-
-        let _ = fs::remove_file(file_path);
     }
 
     /// P2 Boundary: length larger than remaining bytes returns
@@ -1354,7 +1411,7 @@ mod tests {
         let file_path = create_test_session_with_file(
             &handler.services,
             "largerlen-test",
-            "/tmp/shebe-rf-largerlen.txt",
+            "shebe-rf-largerlen.txt",
             &content,
         )
         .await;
@@ -1372,7 +1429,5 @@ mod tests {
 
         assert!(text.contains("showing bytes 9000-10000 of 10000"));
         assert!(!text.contains("More content available"));
-
-        let _ = fs::remove_file(file_path);
     }
 }
@@ -1,42 +1,176 @@
 //! Tool registry for managing MCP tools
 
 use super::handler::McpToolHandler;
-use crate::mcp::protocol::ToolSchema;
-use std::collections::HashMap;
-use std::sync::Arc;
+use super::usage::{ToolCounters, ToolUsage};
+use crate::core::cancel::CancelToken;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema, METHOD_NOT_FOUND, READ_ONLY, TIMED_OUT};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 /// Registry for all available MCP tools
 ///
 /// Maintains a collection of tool handlers and provides methods
-/// for tool discovery and execution.
+/// for tool discovery and execution. Disabled tools stay registered
+/// but are left out of [`list`](Self::list) and refused by
+/// [`resolve`](Self::resolve). In read-only mode the same applies to
+/// every tool that [mutates](McpToolHandler::mutates) sessions. The
+/// disabled set can be replaced while serving, when the configuration
+/// is reloaded.
+///
+/// Calls made through [`execute`](Self::execute) are counted in the
+/// registry's [`ToolUsage`].
 pub struct ToolRegistry {
     handlers: HashMap<String, Arc<dyn McpToolHandler>>,
+    disabled: RwLock<HashSet<String>>,
+    read_only: bool,
+    usage: Arc<ToolUsage>,
+    /// Usage counters by tool name, fixed once tools are registered
+    counters: HashMap<String, Arc<ToolCounters>>,
 }
 
 impl ToolRegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
+        Self::with_usage(Arc::new(ToolUsage::default()))
+    }
+
+    /// Create an empty registry counting calls in `usage`
+    pub fn with_usage(usage: Arc<ToolUsage>) -> Self {
         Self {
             handlers: HashMap::new(),
+            disabled: RwLock::new(HashSet::new()),
+            read_only: false,
+            usage,
+            counters: HashMap::new(),
         }
     }
 
     /// Register a tool handler
     pub fn register(&mut self, handler: Arc<dyn McpToolHandler>) {
         let name = handler.name().to_string();
+        self.counters.insert(name.clone(), self.usage.track(&name));
         self.handlers.insert(name, handler);
     }
 
+    /// Usage statistics of the registered tools
+    pub fn usage(&self) -> &Arc<ToolUsage> {
+        &self.usage
+    }
+
+    /// Execute a resolved tool call within its time limit and count it
+    ///
+    /// See [`execute_with_timeout`]. The call is recorded under the
+    /// handler's name unless `[mcp] usage_stats` is off; errors and
+    /// timeouts count as failed calls.
+    pub async fn execute(
+        &self,
+        handler: &dyn McpToolHandler,
+        args: Value,
+        default: Duration,
+    ) -> Result<ToolResult, McpError> {
+        let start = Instant::now();
+        let outcome = execute_with_timeout(handler, args, default).await;
+        if self.usage.is_enabled() {
+            if let Some(counters) = self.counters.get(handler.name()) {
+                counters.record(start.elapsed(), outcome.is_err());
+            }
+        }
+        outcome
+    }
+
     /// Get a tool handler by name
     pub fn get(&self, name: &str) -> Option<&Arc<dyn McpToolHandler>> {
         self.handlers.get(name)
     }
 
-    /// List all available tool schemas
+    /// Get an enabled tool handler for a tools/call request
+    ///
+    /// # Errors
+    ///
+    /// `InvalidRequest` for unknown tools, a `METHOD_NOT_FOUND` tool
+    /// error for tools disabled in the server config and a `READ_ONLY`
+    /// tool error for mutating tools in read-only mode.
+    pub fn resolve(&self, name: &str) -> Result<&Arc<dyn McpToolHandler>, McpError> {
+        let handler = self
+            .handlers
+            .get(name)
+            .ok_or_else(|| McpError::InvalidRequest(format!("Tool not found: {name}")))?;
+        if self.is_disabled(name) {
+            return Err(McpError::ToolError(
+                METHOD_NOT_FOUND,
+                format!("Tool '{name}' is administratively disabled on this server ([mcp.tools])"),
+            ));
+        }
+        if self.read_only && handler.mutates() {
+            return Err(McpError::ToolError(
+                READ_ONLY,
+                format!(
+                    "Tool '{name}' is unavailable: server is in read-only mode (server.read_only)"
+                ),
+            ));
+        }
+        Ok(handler)
+    }
+
+    /// Hide and refuse mutating tools (`server.read_only`)
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Enable or disable registered tools by name
+    ///
+    /// Replaces any earlier settings: tools not listed as disabled are
+    /// enabled. Returns the names that do not match a registered tool,
+    /// which are otherwise ignored.
+    pub fn configure(&self, tools: &BTreeMap<String, bool>) -> Vec<String> {
+        let mut unknown = Vec::new();
+        let mut disabled = HashSet::new();
+        for (name, enabled) in tools {
+            if !self.handlers.contains_key(name) {
+                unknown.push(name.clone());
+            } else if !*enabled {
+                disabled.insert(name.clone());
+            }
+        }
+        *self.disabled.write().unwrap_or_else(|e| e.into_inner()) = disabled;
+        unknown
+    }
+
+    fn is_disabled(&self, name: &str) -> bool {
+        self.disabled
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(name)
+    }
+
+    /// Check if a registered tool is enabled (and allowed in read-only mode)
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.handlers
+            .get(name)
+            .is_some_and(|handler| self.is_available(name, handler.as_ref()))
+    }
+
+    fn is_available(&self, name: &str, handler: &dyn McpToolHandler) -> bool {
+        let blocked = self.read_only && handler.mutates();
+        !blocked && !self.is_disabled(name)
+    }
+
+    /// Names of all registered tools (enabled or not), sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// List the schemas of all enabled tools
     pub fn list(&self) -> Vec<ToolSchema> {
         self.handlers
-            .values()
-            .map(|handler| handler.schema())
+            .iter()
+            .filter(|(name, handler)| self.is_available(name, handler.as_ref()))
+            .map(|(_, handler)| handler.schema())
             .collect()
     }
 
@@ -56,6 +190,42 @@ impl ToolRegistry {
     }
 }
 
+/// Execute a tool call within its time limit
+///
+/// The limit is the handler's [`timeout`](McpToolHandler::timeout) for
+/// `args`, based on `default` (`limits.tool_timeout_sec`). A call that
+/// runs past it is abandoned with a `TIMED_OUT` tool error, and its
+/// [`CancelToken`] is cancelled so blocking work it started stops at
+/// its next [`check_cancelled`](crate::core::cancel::check_cancelled).
+pub async fn execute_with_timeout(
+    handler: &dyn McpToolHandler,
+    args: Value,
+    default: Duration,
+) -> Result<ToolResult, McpError> {
+    let limit = handler.timeout(&args, default);
+    let token = CancelToken::new();
+    let call = token.clone().scope(handler.execute(args));
+
+    let Some(limit) = limit else {
+        return call.await;
+    };
+    match tokio::time::timeout(limit, call).await {
+        Ok(outcome) => outcome,
+        Err(_) => {
+            token.cancel();
+            Err(McpError::ToolError(
+                TIMED_OUT,
+                format!(
+                    "Tool '{}' timed out after {}s — narrow the query or raise the limit \
+                     (timeout_sec where supported, limits.tool_timeout_sec)",
+                    handler.name(),
+                    limit.as_secs_f64()
+                ),
+            ))
+        }
+    }
+}
+
 impl Default for ToolRegistry {
     fn default() -> Self {
         Self::new()
@@ -65,7 +235,6 @@ impl Default for ToolRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mcp::error::McpError;
     use crate::mcp::protocol::{ContentBlock, ToolResult};
     use async_trait::async_trait;
     use serde_json::{json, Value};
@@ -73,6 +242,7 @@ mod tests {
     // Mock tool handler for testing
     struct MockToolHandler {
         name: String,
+        mutates: bool,
     }
 
     #[async_trait]
@@ -81,6 +251,10 @@ mod tests {
             &self.name
         }
 
+        fn mutates(&self) -> bool {
+            self.mutates
+        }
+
         fn schema(&self) -> ToolSchema {
             ToolSchema {
                 name: self.name.clone(),
@@ -113,6 +287,7 @@ mod tests {
         let mut registry = ToolRegistry::new();
         let handler = Arc::new(MockToolHandler {
             name: "test_tool".to_string(),
+            mutates: false,
         });
 
         registry.register(handler);
@@ -126,6 +301,7 @@ mod tests {
         let mut registry = ToolRegistry::new();
         let handler = Arc::new(MockToolHandler {
             name: "test_tool".to_string(),
+            mutates: false,
         });
 
         registry.register(handler);
@@ -146,9 +322,11 @@ mod tests {
         let mut registry = ToolRegistry::new();
         let handler1 = Arc::new(MockToolHandler {
             name: "tool1".to_string(),
+            mutates: false,
         });
         let handler2 = Arc::new(MockToolHandler {
             name: "tool2".to_string(),
+            mutates: false,
         });
 
         registry.register(handler1);
@@ -163,6 +341,7 @@ mod tests {
         let mut registry = ToolRegistry::new();
         let handler = Arc::new(MockToolHandler {
             name: "test_tool".to_string(),
+            mutates: false,
         });
 
         registry.register(handler);
@@ -175,4 +354,236 @@ mod tests {
         let registry = ToolRegistry::default();
         assert_eq!(registry.len(), 0);
     }
+
+    fn registry_with(names: &[&str]) -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        for name in names {
+            registry.register(Arc::new(MockToolHandler {
+                name: name.to_string(),
+                mutates: false,
+            }));
+        }
+        registry
+    }
+
+    #[test]
+    fn test_registry_disabled_tools_not_listed() {
+        let registry = registry_with(&["search_code", "read_file", "list_dir"]);
+        let tools = BTreeMap::from([
+            ("read_file".to_string(), false),
+            ("list_dir".to_string(), false),
+            ("search_code".to_string(), true),
+        ]);
+
+        let unknown = registry.configure(&tools);
+        assert!(unknown.is_empty());
+
+        let listed: Vec<String> = registry.list().into_iter().map(|s| s.name).collect();
+        assert_eq!(listed, vec!["search_code"]);
+        assert!(registry.is_enabled("search_code"));
+        assert!(!registry.is_enabled("read_file"));
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    fn test_registry_resolve_disabled_tool() {
+        let registry = registry_with(&["search_code", "read_file"]);
+        registry.configure(&BTreeMap::from([("read_file".to_string(), false)]));
+
+        assert!(registry.resolve("search_code").is_ok());
+        match registry.resolve("read_file") {
+            Err(McpError::ToolError(code, message)) => {
+                assert_eq!(code, METHOD_NOT_FOUND);
+                assert!(message.contains("administratively disabled"));
+            }
+            other => panic!("expected disabled error, got {:?}", other.map(|h| h.name())),
+        }
+        assert!(matches!(
+            registry.resolve("missing"),
+            Err(McpError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_registry_configure_reports_unknown_names() {
+        let registry = registry_with(&["search_code", "read_file"]);
+        let tools = BTreeMap::from([
+            ("read_fiel".to_string(), false),
+            ("read_file".to_string(), false),
+        ]);
+
+        assert_eq!(registry.configure(&tools), vec!["read_fiel"]);
+        assert_eq!(registry.names(), vec!["read_file", "search_code"]);
+
+        // Re-enabling restores the tool
+        registry.configure(&BTreeMap::from([("read_file".to_string(), true)]));
+        assert!(registry.is_enabled("read_file"));
+
+        // So does leaving it out of a later configuration
+        registry.configure(&BTreeMap::from([("read_file".to_string(), false)]));
+        registry.configure(&BTreeMap::new());
+        assert!(registry.is_enabled("read_file"));
+    }
+
+    #[test]
+    fn test_registry_read_only_hides_mutating_tools() {
+        let mut registry = registry_with(&["search_code"]);
+        registry.register(Arc::new(MockToolHandler {
+            name: "delete_session".to_string(),
+            mutates: true,
+        }));
+        assert!(registry.resolve("delete_session").is_ok());
+
+        registry.set_read_only(true);
+        let listed: Vec<String> = registry.list().into_iter().map(|s| s.name).collect();
+        assert_eq!(listed, vec!["search_code"]);
+        assert!(!registry.is_enabled("delete_session"));
+        assert!(registry.resolve("search_code").is_ok());
+        match registry.resolve("delete_session") {
+            Err(McpError::ToolError(code, message)) => {
+                assert_eq!(code, READ_ONLY);
+                assert!(message.contains("server is in read-only mode"));
+            }
+            other => panic!(
+                "expected read-only error, got {:?}",
+                other.map(|h| h.name())
+            ),
+        }
+    }
+
+    /// Blocking work that runs until its call is cancelled
+    struct SlowToolHandler {
+        mutates: bool,
+        stopped: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl McpToolHandler for SlowToolHandler {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn mutates(&self) -> bool {
+            self.mutates
+        }
+
+        fn schema(&self) -> ToolSchema {
+            ToolSchema {
+                name: "slow".to_string(),
+                description: "Slow tool".to_string(),
+                input_schema: json!({"type": "object", "properties": {}}),
+            }
+        }
+
+        async fn execute(&self, _args: Value) -> Result<ToolResult, McpError> {
+            use crate::core::cancel::check_cancelled;
+            use crate::core::logging::spawn_blocking_in_context;
+            use std::sync::atomic::Ordering;
+
+            let stopped = Arc::clone(&self.stopped);
+            let rounds = if self.mutates { 5 } else { usize::MAX };
+            spawn_blocking_in_context(move || {
+                for _ in 0..rounds {
+                    if check_cancelled().is_err() {
+                        stopped.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            })
+            .await
+            .unwrap();
+            Ok(ToolResult {
+                content: vec![ContentBlock::Text {
+                    text: "done".to_string(),
+                }],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_tool_times_out_and_stops() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let handler = SlowToolHandler {
+            mutates: false,
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+        let result = execute_with_timeout(&handler, json!({}), Duration::from_millis(100)).await;
+        match result {
+            Err(McpError::ToolError(code, message)) => {
+                assert_eq!(code, TIMED_OUT);
+                assert!(
+                    message.contains("'slow' timed out after 0.1s — narrow the query"),
+                    "{message}"
+                );
+            }
+            other => panic!("expected timeout, got {other:?}"),
+        }
+
+        // The blocking work sees the cancellation and stops
+        for _ in 0..200 {
+            if handler.stopped.load(Ordering::Relaxed) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("blocking work kept running after the timeout");
+    }
+
+    #[tokio::test]
+    async fn test_mutating_tool_has_no_time_limit() {
+        use std::sync::atomic::AtomicBool;
+
+        let handler = SlowToolHandler {
+            mutates: true,
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+        let result = execute_with_timeout(&handler, json!({}), Duration::from_millis(1)).await;
+        assert!(result.is_ok(), "{result:?}");
+    }
+    #[tokio::test]
+    async fn test_execute_counts_calls_and_errors() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(MockToolHandler {
+            name: "search_code".to_string(),
+            mutates: false,
+        }));
+        let slow = Arc::new(SlowToolHandler {
+            mutates: false,
+            stopped: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        });
+        registry.register(slow.clone());
+
+        let timeout = Duration::from_secs(5);
+        for _ in 0..3 {
+            let handler = registry.resolve("search_code").unwrap();
+            registry
+                .execute(handler.as_ref(), json!({}), timeout)
+                .await
+                .unwrap();
+        }
+        // A timed-out call counts as an error
+        let result = registry
+            .execute(slow.as_ref(), json!({}), Duration::from_millis(20))
+            .await;
+        assert!(result.is_err());
+
+        let snapshot = registry.usage().snapshot();
+        let search = &snapshot.tools["search_code"];
+        assert_eq!((search.calls, search.errors), (3, 0));
+        assert!(search.percentile(0.5).is_some());
+        let slow_stats = &snapshot.tools["slow"];
+        assert_eq!((slow_stats.calls, slow_stats.errors), (1, 1));
+        assert!(slow_stats.max_us >= 20_000);
+
+        // Nothing is counted while usage statistics are off
+        registry.usage().set_enabled(false);
+        let handler = registry.resolve("search_code").unwrap();
+        registry
+            .execute(handler.as_ref(), json!({}), timeout)
+            .await
+            .unwrap();
+        assert_eq!(registry.usage().snapshot().tools["search_code"].calls, 3);
+    }
 }
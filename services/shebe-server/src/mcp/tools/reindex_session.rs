@@ -1,8 +1,13 @@
 //! Re-index session tool handler
 
 use super::handler::{text_content, McpToolHandler};
-use super::helpers::format_bytes;
+use super::helpers::{
+    format_bytes, format_provenance, format_renamed_files, format_truncated_files,
+};
+use crate::core::archive::Archive;
+use crate::core::indexer::ReindexDryRun;
 use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
@@ -50,13 +55,159 @@ impl ReindexSessionHandler {
         old: &crate::core::storage::SessionConfig,
         new: &crate::core::storage::SessionConfig,
     ) -> ConfigComparison {
+        let chunk_size_changed = old.chunk_size != new.chunk_size;
+        let overlap_changed = old.overlap != new.overlap;
+        let storage_changed = old.store_chunk_text != new.store_chunk_text;
+        let bm25_changed = old.bm25_params() != new.bm25_params();
+        let max_chunks_changed = old.max_chunks_per_file != new.max_chunks_per_file;
         ConfigComparison {
-            chunk_size_changed: old.chunk_size != new.chunk_size,
-            overlap_changed: old.overlap != new.overlap,
-            any_changed: old.chunk_size != new.chunk_size || old.overlap != new.overlap,
+            chunk_size_changed,
+            overlap_changed,
+            storage_changed,
+            bm25_changed,
+            max_chunks_changed,
+            any_changed: chunk_size_changed
+                || overlap_changed
+                || storage_changed
+                || bm25_changed
+                || max_chunks_changed,
         }
     }
 
+    /// Config changes the re-index applies, empty if none
+    fn format_config_changes(
+        &self,
+        old_config: &crate::core::storage::SessionConfig,
+        new_config: &crate::core::storage::SessionConfig,
+    ) -> String {
+        let mut output = String::new();
+        let comparison = self.compare_configs(old_config, new_config);
+        if comparison.any_changed {
+            output.push_str("**Configuration Changes:**\n");
+
+            if comparison.chunk_size_changed {
+                output.push_str(&format!(
+                    "- Chunk size: {} -> {}\n",
+                    old_config.chunk_size, new_config.chunk_size
+                ));
+            }
+
+            if comparison.overlap_changed {
+                output.push_str(&format!(
+                    "- Overlap: {} -> {}\n",
+                    old_config.overlap, new_config.overlap
+                ));
+            }
+
+            if comparison.storage_changed {
+                output.push_str(&format!(
+                    "- Chunk text: {} -> {}\n",
+                    text_storage_label(old_config.store_chunk_text),
+                    text_storage_label(new_config.store_chunk_text)
+                ));
+            }
+
+            if comparison.bm25_changed {
+                output.push_str(&format!(
+                    "- BM25: k1={} b={} -> k1={} b={}\n",
+                    old_config.bm25_k1, old_config.bm25_b, new_config.bm25_k1, new_config.bm25_b
+                ));
+            }
+
+            if comparison.max_chunks_changed {
+                output.push_str(&format!(
+                    "- Max chunks per file: {} -> {}\n",
+                    old_config.max_chunks_per_file, new_config.max_chunks_per_file
+                ));
+            }
+
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Format a re-index preview
+    fn format_dry_run(
+        &self,
+        session: &str,
+        metadata: &crate::core::storage::SessionMetadata,
+        preview: &ReindexDryRun,
+        new_config: &crate::core::storage::SessionConfig,
+        would_run: bool,
+    ) -> String {
+        let mut output = format!(
+            "# Re-Index Dry Run: `{session}`\n\n\
+             **No changes made.** The session was not modified; run again without \
+             dry_run to re-index.\n\n\
+             **Repository:** {}\n",
+            metadata.repository_path.display()
+        );
+        match (&preview.resolved_path, preview.repository_moved) {
+            (_, false) => output.push_str("- Path moved: no\n\n"),
+            (Some(resolved), true) => output.push_str(&format!(
+                "- Path moved: yes, it now resolves to {}\n\n",
+                resolved.display()
+            )),
+            (None, true) => output
+                .push_str("- Path moved: yes, it no longer exists (re-indexing would fail)\n\n"),
+        }
+
+        let files = &preview.files;
+        output.push_str(&format!(
+            "**Files:**\n\
+             - Added: {}\n\
+             - Removed: {}\n\
+             - Unchanged: {}\n\
+             - Matched now: {} ({})\n\
+             - Chunks: {} indexed -> ~{} estimated\n\n",
+            files.added,
+            files.removed,
+            files.unchanged,
+            preview.report.files,
+            format_bytes(preview.report.total_bytes),
+            preview.indexed_chunks,
+            preview.report.estimated_chunks
+        ));
+
+        let config_changes = self.format_config_changes(&metadata.config, new_config);
+        if config_changes.is_empty() {
+            output.push_str("**Configuration:** unchanged\n\n");
+        } else {
+            output.push_str(&config_changes);
+        }
+
+        for (label, paths, count) in [
+            ("Added", &files.sample_added, files.added),
+            ("Removed", &files.sample_removed, files.removed),
+        ] {
+            if paths.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("**{label} files:**\n"));
+            for path in paths {
+                output.push_str(&format!("- {path}\n"));
+            }
+            if count > paths.len() {
+                output.push_str(&format!("- ... and {} more\n", count - paths.len()));
+            }
+            output.push('\n');
+        }
+
+        if metadata.remote.is_some() {
+            output.push_str(
+                "**Note:** The remote was not fetched; files are compared with the current \
+                 clone. A re-index fetches the recorded ref first.\n",
+            );
+        }
+        if !would_run && metadata.remote.is_none() {
+            output
+                .push_str("**Note:** Configuration unchanged; re-indexing requires force=true.\n");
+        }
+
+        output.trim_end().to_string()
+    }
+
     /// Format re-indexing result
     fn format_result(
         &self,
@@ -83,25 +234,27 @@ impl ReindexSessionHandler {
             stats.files_indexed as f64 / duration_secs
         );
 
-        // Show config changes if any
-        let comparison = self.compare_configs(old_config, new_config);
-        if comparison.any_changed {
-            output.push_str("**Configuration Changes:**\n");
+        output.push_str(&self.format_config_changes(old_config, new_config));
 
-            if comparison.chunk_size_changed {
-                output.push_str(&format!(
-                    "- Chunk size: {} -> {}\n",
-                    old_config.chunk_size, new_config.chunk_size
-                ));
-            }
+        if !stats.truncated_files.is_empty() {
+            output.push_str(&format_truncated_files(
+                &stats.truncated_files,
+                new_config.max_chunks_per_file,
+            ));
+            output.push('\n');
+        }
 
-            if comparison.overlap_changed {
-                output.push_str(&format!(
-                    "- Overlap: {} -> {}\n",
-                    old_config.overlap, new_config.overlap
-                ));
-            }
+        if stats.files_renamed > 0 {
+            output.push_str(&format_renamed_files(
+                &stats.renamed_files,
+                stats.files_renamed,
+            ));
+            output.push('\n');
+        }
 
+        if let Some(provenance) = &stats.provenance {
+            output.push_str("**Provenance:**\n");
+            output.push_str(&format_provenance(provenance));
             output.push('\n');
         }
 
@@ -119,14 +272,22 @@ impl McpToolHandler for ReindexSessionHandler {
         "reindex_session"
     }
 
+    fn mutates(&self) -> bool {
+        true
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "reindex_session".to_string(),
             description: "Re-index a session using stored repository path. \
                          Convenient for schema migrations or config changes. \
                          Automatically retrieves original path and config from metadata. \
-                         Supports config overrides (chunk_size, overlap). \
-                         Use force=true to re-index even if config unchanged."
+                         Supports config overrides (chunk_size, overlap, store_chunk_text, \
+                         bm25_k1, bm25_b, max_chunks_per_file). \
+                         Sessions indexed from a git URL fetch the same ref first and are \
+                         re-indexed when it has new commits. \
+                         Use force=true to re-index even if config (and commit) unchanged. \
+                         Use dry_run=true to preview the changes first."
                 .to_string(),
             input_schema: json!({
                 "type": "object",
@@ -134,7 +295,7 @@ impl McpToolHandler for ReindexSessionHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID to re-index",
-                        "pattern": "^[a-zA-Z0-9_-]{1,64}$"
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "chunk_size": {
                         "type": "integer",
@@ -148,10 +309,44 @@ impl McpToolHandler for ReindexSessionHandler {
                         "minimum": 0,
                         "maximum": 500
                     },
+                    "store_chunk_text": {
+                        "type": "boolean",
+                        "description": "Switch chunk text storage: false reads snippets from the \
+                                       files at query time for a smaller index (optional, default: \
+                                       use stored config)"
+                    },
+                    "bm25_k1": {
+                        "type": "number",
+                        "description": "Override BM25 term frequency saturation (optional, \
+                                       default: use stored config)",
+                        "minimum": 0,
+                        "maximum": 3
+                    },
+                    "bm25_b": {
+                        "type": "number",
+                        "description": "Override BM25 length normalization; lower values favor \
+                                       long chunks (optional, default: use stored config)",
+                        "minimum": 0,
+                        "maximum": 1
+                    },
+                    "max_chunks_per_file": {
+                        "type": "integer",
+                        "description": "Override the chunks indexed per file; raise it to index \
+                                       files reported as partially indexed in full (optional, \
+                                       default: use stored config)",
+                        "minimum": 1
+                    },
                     "force": {
                         "type": "boolean",
                         "description": "Force re-index even if config unchanged (default: false)",
                         "default": false
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Preview the re-index without changing anything: files \
+                                       added and removed since indexing, estimated chunks and \
+                                       the config changes it would apply (default: false)",
+                        "default": false
                     }
                 },
                 "required": ["session"]
@@ -179,8 +374,9 @@ impl McpToolHandler for ReindexSessionHandler {
                 _ => McpError::from(e),
             })?;
 
-        // 2. Validate repository path still exists
-        if !metadata.repository_path.exists() {
+        // 2. Validate repository path still exists (clones are restored by the fetch);
+        // a dry run reports a missing path instead
+        if metadata.remote.is_none() && !args.dry_run && !metadata.repository_path.exists() {
             return Err(McpError::InvalidRequest(format!(
                 "Repository path no longer exists: {}\n\
                  Session '{}' cannot be re-indexed.\n\
@@ -192,7 +388,11 @@ impl McpToolHandler for ReindexSessionHandler {
             )));
         }
 
-        if !metadata.repository_path.is_dir() {
+        if metadata.remote.is_none()
+            && metadata.archive.is_none()
+            && metadata.repository_path.exists()
+            && !metadata.repository_path.is_dir()
+        {
             return Err(McpError::InvalidRequest(format!(
                 "Repository path is not a directory: {}",
                 metadata.repository_path.display()
@@ -206,62 +406,171 @@ impl McpToolHandler for ReindexSessionHandler {
             overlap: args.overlap.unwrap_or(old_config.overlap),
             include_patterns: old_config.include_patterns.clone(), // Preserve patterns
             exclude_patterns: old_config.exclude_patterns.clone(),
+            languages: old_config.languages.clone(),
+            dedupe: old_config.dedupe,
+            boundary_hint: old_config.boundary_hint,
+            store_chunk_text: args.store_chunk_text.unwrap_or(old_config.store_chunk_text),
+            strip_high_entropy: old_config.strip_high_entropy,
+            strip_run_chars: old_config.strip_run_chars,
+            merge_tail_fragments: old_config.merge_tail_fragments,
+            record_chunking: old_config.record_chunking,
+            max_chunks_per_file: args
+                .max_chunks_per_file
+                .unwrap_or(old_config.max_chunks_per_file),
+            strict_patterns: old_config.strict_patterns,
+            git_metadata: old_config.git_metadata
+                && self.services.config.current().indexing.git_metadata,
+            sources: old_config.sources.clone().map(|sources| {
+                sources.with_chunk_overrides(args.chunk_size.is_some(), args.overlap.is_some())
+            }),
+            bm25_k1: args.bm25_k1.unwrap_or(old_config.bm25_k1),
+            bm25_b: args.bm25_b.unwrap_or(old_config.bm25_b),
         };
 
         // 4. Validate new configuration
         self.validate_config(new_config.chunk_size, new_config.overlap)?;
+        new_config
+            .validate()
+            .map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        // 5. Preview without fetching, locking or replacing anything
+        if args.dry_run {
+            let preview = self
+                .services
+                .dry_run_reindex(&args.session, &metadata.repository_path, &new_config)
+                .map_err(|e| McpError::InternalError(format!("Dry run failed: {e}")))?;
+            let archive_changed = match &metadata.archive {
+                Some(old) => Archive::detect(&metadata.repository_path)
+                    .map(|archive| archive.source())
+                    .transpose()?
+                    .is_some_and(|new| new.content_hash != old.content_hash),
+                None => false,
+            };
+            let comparison = self.compare_configs(&old_config, &new_config);
+            let would_run = comparison.any_changed || archive_changed || args.force;
+            return Ok(text_content(self.format_dry_run(
+                &args.session,
+                &metadata,
+                &preview,
+                &new_config,
+                would_run,
+            )));
+        }
+
+        // 6. Fetch the recorded ref of remote sessions
+        let (repository_path, source) = match &metadata.remote {
+            Some(remote) => {
+                let source = self.services.clones.update(&args.session, remote)?;
+                let clone_path = self
+                    .services
+                    .clones
+                    .clone_path(&args.session)
+                    .canonicalize()
+                    .map_err(|e| McpError::InternalError(format!("Cannot resolve clone: {e}")))?;
+                (clone_path, Some(source))
+            }
+            None => (metadata.repository_path.clone(), None),
+        };
+        let commit_changed = match (&metadata.remote, &source) {
+            (Some(old), Some(new)) => old.commit != new.commit,
+            _ => false,
+        };
+        // Archive sessions re-index when the archive was replaced
+        let archive = match &metadata.archive {
+            Some(_) => Archive::detect(&repository_path)
+                .map(|archive| archive.source())
+                .transpose()?,
+            None => None,
+        };
+        let archive_changed = match (&metadata.archive, &archive) {
+            (Some(old), Some(new)) => old.content_hash != new.content_hash,
+            _ => false,
+        };
 
-        // 5. Check if force is needed
+        // 7. Check if force is needed
         let comparison = self.compare_configs(&old_config, &new_config);
-        if !comparison.any_changed && !args.force {
+        if !comparison.any_changed && !commit_changed && !archive_changed && !args.force {
+            let commit_note = source
+                .as_ref()
+                .map(|s| {
+                    format!(
+                        " and {} is still at commit {}",
+                        s.ref_label(),
+                        s.short_commit()
+                    )
+                })
+                .or_else(|| {
+                    archive.as_ref().map(|a| {
+                        format!(
+                            " and the archive is unchanged (content hash {})",
+                            a.short_hash()
+                        )
+                    })
+                })
+                .unwrap_or_default();
             return Err(McpError::InvalidRequest(format!(
-                "Configuration unchanged for session '{}'. \
+                "Configuration unchanged for session '{}'{}. \
                  Use force=true to re-index anyway.\n\
                  Current config: chunk_size={}, overlap={}",
-                args.session, old_config.chunk_size, old_config.overlap
+                args.session, commit_note, old_config.chunk_size, old_config.overlap
             )));
         }
 
-        // 6. Delete existing session
-        self.services
-            .storage
-            .delete_session(&args.session)
-            .map_err(|e| McpError::InternalError(format!("Failed to delete session: {e}")))?;
-
-        // 7. Re-index repository
+        // 8. Re-index repository; the existing session is replaced only
+        // once the new index is complete
         let start = Instant::now();
         let stats = self
             .services
             .storage
-            .index_repository(
+            .index_repository_with_config(
                 &args.session,
-                &metadata.repository_path,
-                new_config.include_patterns.clone(),
-                new_config.exclude_patterns.clone(),
-                new_config.chunk_size,
-                new_config.overlap,
-                100,   // max_file_size_mb default
-                false, // force (already deleted above)
+                &repository_path,
+                new_config.clone(),
+                100,  // max_file_size_mb default
+                true, // replace the existing session
             )
             .map_err(|e| McpError::InternalError(format!("Re-indexing failed: {e}")))?;
         let duration_secs = start.elapsed().as_secs_f64();
+        if source.is_some() {
+            self.services
+                .storage
+                .set_remote_source(&args.session, source.clone())?;
+        }
 
-        // Get updated metadata to retrieve index size
-        let updated_metadata = self
+        // Measure the new index and store its size
+        let index_size_bytes = self
             .services
             .storage
-            .get_session_metadata(&args.session)
-            .map_err(|e| McpError::InternalError(format!("Failed to get updated metadata: {e}")))?;
+            .refresh_index_size(&args.session)
+            .map_err(|e| McpError::InternalError(format!("Failed to measure index size: {e}")))?;
 
-        // 8. Format result
-        let result = self.format_result(
+        // 9. Format result
+        let mut result = self.format_result(
             &args.session,
             &stats,
-            updated_metadata.index_size_bytes,
+            index_size_bytes,
             &old_config,
             &new_config,
             duration_secs,
         );
+        if let (Some(old), Some(new)) = (&metadata.remote, &source) {
+            result.push_str(&format!(
+                "\n**Source:** {} ({}), commit {} -> {}",
+                new.url,
+                new.ref_label(),
+                old.short_commit(),
+                new.short_commit()
+            ));
+        }
+        if let (Some(old), Some(new)) = (&metadata.archive, &archive) {
+            result.push_str(&format!(
+                "\n**Source:** {} archive {}, content hash {} -> {}",
+                new.kind.as_str(),
+                repository_path.display(),
+                old.short_hash(),
+                new.short_hash()
+            ));
+        }
 
         Ok(text_content(result))
     }
@@ -275,15 +584,36 @@ struct ReindexArgs {
     #[serde(default)]
     overlap: Option<usize>,
     #[serde(default)]
+    store_chunk_text: Option<bool>,
+    #[serde(default)]
+    bm25_k1: Option<f32>,
+    #[serde(default)]
+    bm25_b: Option<f32>,
+    #[serde(default)]
+    max_chunks_per_file: Option<usize>,
+    #[serde(default)]
     force: bool,
+    #[serde(default)]
+    dry_run: bool,
 }
 
 struct ConfigComparison {
     chunk_size_changed: bool,
     overlap_changed: bool,
+    storage_changed: bool,
+    bm25_changed: bool,
+    max_chunks_changed: bool,
     any_changed: bool,
 }
 
+fn text_storage_label(store_chunk_text: bool) -> &'static str {
+    if store_chunk_text {
+        "stored"
+    } else {
+        "not stored"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -386,6 +716,71 @@ mod tests {
         assert_eq!(metadata.config.overlap, 128);
     }
 
+    #[tokio::test]
+    async fn test_reindex_session_bm25_override() {
+        let (handler, temp_dir) = setup_test_handler().await;
+        let repo_path = temp_dir.path().join("test_repo");
+        create_test_session(&handler.services, &repo_path, "test-bm25").await;
+
+        let result = handler
+            .execute(json!({"session": "test-bm25", "bm25_k1": 2.0, "bm25_b": 0.3}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(text.contains("BM25: k1=1.2 b=0.75 -> k1=2 b=0.3"));
+
+        let metadata = handler
+            .services
+            .storage
+            .get_session_metadata("test-bm25")
+            .unwrap();
+        assert_eq!(metadata.config.bm25_k1, 2.0);
+        assert_eq!(metadata.config.bm25_b, 0.3);
+
+        let result = handler
+            .execute(json!({"session": "test-bm25", "bm25_b": 1.5}))
+            .await;
+        assert!(
+            matches!(result, Err(McpError::InvalidParams(ref msg)) if msg.contains("bm25_b must be between 0 and 1")),
+            "{result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reindex_session_switches_text_storage() {
+        let (handler, temp_dir) = setup_test_handler().await;
+        let repo_path = temp_dir.path().join("test_repo");
+        create_test_session(&handler.services, &repo_path, "test-storage").await;
+
+        let result = handler
+            .execute(json!({"session": "test-storage", "store_chunk_text": false}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(text.contains("Chunk text: stored -> not stored"));
+
+        let metadata = handler
+            .services
+            .storage
+            .get_session_metadata("test-storage")
+            .unwrap();
+        assert!(!metadata.config.store_chunk_text);
+        assert_eq!(metadata.schema_hash_matches(), Some(true));
+
+        // Search still returns text, read back from the files
+        let results = handler
+            .services
+            .search
+            .search_session("test-storage", "main", Some(5))
+            .unwrap();
+        assert!(!results.results.is_empty());
+        assert!(results.results.iter().all(|r| !r.text.is_empty()));
+    }
+
     #[tokio::test]
     async fn test_reindex_session_not_found() {
         let (handler, _temp) = setup_test_handler().await;
@@ -530,4 +925,96 @@ mod tests {
             .unwrap();
         assert!(new_metadata.last_indexed_at > old_timestamp);
     }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_reindex_session_remote_fetches_ref() {
+        use crate::core::remote::{CloneCache, RemoteSource};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().join("sessions");
+        let mut services = Services::new(config);
+        services.clones = Arc::new(
+            CloneCache::new(
+                temp_dir.path().join("clones"),
+                std::time::Duration::from_secs(60),
+            )
+            .with_local_sources(),
+        );
+        let handler = ReindexSessionHandler::new(Arc::new(services));
+
+        // Upstream repository and a clone of it, as index_repository leaves them
+        let upstream = temp_dir.path().join("upstream");
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "--quiet", "--initial-branch", "main"]);
+        std::fs::write(upstream.join("lib.rs"), "fn first() {}").unwrap();
+        git(&upstream, &["add", "."]);
+        git(&upstream, &["commit", "--quiet", "-m", "first"]);
+        let clone = handler.services.clones.clone_path("remote");
+        git(
+            temp_dir.path(),
+            &[
+                "clone",
+                "--quiet",
+                upstream.to_str().unwrap(),
+                clone.to_str().unwrap(),
+            ],
+        );
+        let first = git(&clone, &["rev-parse", "HEAD"]);
+        create_test_session(&handler.services, &clone, "remote").await;
+        handler
+            .services
+            .storage
+            .set_remote_source(
+                "remote",
+                Some(RemoteSource {
+                    url: upstream.to_string_lossy().into_owned(),
+                    git_ref: Some("main".to_string()),
+                    commit: first.clone(),
+                }),
+            )
+            .unwrap();
+
+        // Nothing changed upstream: force is still required
+        let err = handler
+            .execute(json!({"session": "remote"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("still at commit"), "{err}");
+
+        // A new upstream commit is fetched and indexed without force
+        std::fs::write(upstream.join("added.rs"), "fn added() {}").unwrap();
+        git(&upstream, &["add", "."]);
+        git(&upstream, &["commit", "--quiet", "-m", "second"]);
+        let second = git(&upstream, &["rev-parse", "HEAD"]);
+
+        let result = handler.execute(json!({"session": "remote"})).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(text.contains(&format!("-> {}", &second[..12])), "{text}");
+
+        let metadata = handler
+            .services
+            .storage
+            .get_session_metadata("remote")
+            .unwrap();
+        let remote = metadata.remote.unwrap();
+        assert_eq!(remote.commit, second);
+        assert_eq!(remote.git_ref.as_deref(), Some("main"));
+        // lib.rs and added.rs from upstream, test.rs written by create_test_session
+        assert_eq!(metadata.files_indexed, 3);
+    }
 }
@@ -0,0 +1,131 @@
+//! Reload config tool handler
+//!
+//! Re-reads the configuration of the running shebe-mcp server and
+//! applies it without a restart (see [`crate::core::reload`]).
+
+use super::handler::{text_content, McpToolHandler};
+use crate::core::reload::{ConfigChange, LiveConfig, ReloadReport};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct ReloadConfigHandler {
+    config: Arc<LiveConfig>,
+}
+
+impl ReloadConfigHandler {
+    pub fn new(config: Arc<LiveConfig>) -> Self {
+        Self { config }
+    }
+}
+
+/// Markdown summary of a reload
+fn format_report(report: &ReloadReport) -> String {
+    let mut output = String::from("# Configuration reloaded\n\n");
+    output.push_str(&format!("**Source:** {}\n\n", report.source));
+
+    if report.is_empty() {
+        output.push_str("No settings changed.\n");
+        return output;
+    }
+
+    let push_changes = |output: &mut String, changes: &[ConfigChange]| {
+        for change in changes {
+            output.push_str(&format!(
+                "- `{}`: {} -> {}\n",
+                change.key, change.old, change.new
+            ));
+        }
+    };
+
+    output.push_str(&format!("## Applied ({})\n\n", report.applied.len()));
+    if report.applied.is_empty() {
+        output.push_str("None.\n");
+    }
+    push_changes(&mut output, &report.applied);
+
+    if !report.requires_restart.is_empty() {
+        output.push_str(&format!(
+            "\n## Requires restart ({})\n\n",
+            report.requires_restart.len()
+        ));
+        output.push_str(
+            "These settings are only read at startup. The server keeps \
+             their running values until it is restarted.\n\n",
+        );
+        push_changes(&mut output, &report.requires_restart);
+    }
+
+    output
+}
+
+#[async_trait]
+impl McpToolHandler for ReloadConfigHandler {
+    fn name(&self) -> &str {
+        "reload_config"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "reload_config".to_string(),
+            description: "Re-read the shebe-mcp configuration (TOML file and SHEBE_* \
+                         environment variables) and apply it without restarting. \
+                         Lists the changed settings; settings only read at startup \
+                         (storage directories, listen address, job limits, ...) are \
+                         reported as requiring a restart and keep their running values. \
+                         An invalid configuration is rejected and the running one kept. \
+                         The same reload runs when the server receives SIGHUP."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {},
+                "required": []
+            }),
+        }
+    }
+
+    async fn execute(&self, _args: Value) -> Result<ToolResult, McpError> {
+        let report = self.config.reload()?;
+        Ok(text_content(format_report(&report)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(key: &str, old: &str, new: &str) -> ConfigChange {
+        ConfigChange {
+            key: key.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_report_no_changes() {
+        let report = ReloadReport {
+            source: "shebe.toml and environment".to_string(),
+            ..ReloadReport::default()
+        };
+        let text = format_report(&report);
+        assert!(text.contains("**Source:** shebe.toml and environment"));
+        assert!(text.contains("No settings changed."));
+        assert!(!text.contains("## Applied"));
+    }
+
+    #[test]
+    fn test_format_report_separates_restart_settings() {
+        let report = ReloadReport {
+            source: "shebe.toml and environment".to_string(),
+            applied: vec![change("search.max_k", "100", "50")],
+            requires_restart: vec![change("storage.index_dir", "/a", "/b")],
+        };
+        let text = format_report(&report);
+        assert!(text.contains("## Applied (1)\n\n- `search.max_k`: 100 -> 50\n"));
+        assert!(text.contains("## Requires restart (1)"));
+        assert!(text.contains("- `storage.index_dir`: /a -> /b\n"));
+    }
+}
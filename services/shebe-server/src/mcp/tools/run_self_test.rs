@@ -0,0 +1,174 @@
+//! Self-test tool handler
+//!
+//! Indexes a small corpus built into the binary in a scratch directory,
+//! checks searches and file reads against it and removes it again. A
+//! failed step is reported in the result, not as a tool error.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::run_blocking;
+use crate::core::selftest::{SelfTestReport, StepStatus};
+use crate::core::services::Services;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct RunSelfTestHandler {
+    services: Arc<Services>,
+}
+
+impl RunSelfTestHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the report as a Markdown table, one row per step
+    fn format_report(report: &SelfTestReport) -> String {
+        let verdict = if report.passed() { "PASSED" } else { "FAILED" };
+        let mut output = format!(
+            "**Self-test:** {verdict} ({} passed, {} failed, {} skipped in {}ms)\n\
+             **Scratch directory:** `{}`\n\n",
+            report.count(StepStatus::Passed),
+            report.count(StepStatus::Failed),
+            report.count(StepStatus::Skipped),
+            report.duration_ms,
+            report.scratch_dir.display()
+        );
+
+        output.push_str("| Step | Status | Time | Detail |\n");
+        output.push_str("|------|--------|------|--------|\n");
+        for step in &report.steps {
+            output.push_str(&format!(
+                "| {} | {} | {}ms | {} |\n",
+                step.name,
+                step.status.as_str(),
+                step.duration_ms,
+                step.detail.replace('|', "\\|")
+            ));
+        }
+        output
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for RunSelfTestHandler {
+    fn name(&self) -> &str {
+        "run_self_test"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "run_self_test".to_string(),
+            description: "Check that indexing, search and file reading work end to end. \
+                         Indexes a small built-in corpus (source, Markdown with emoji, CJK \
+                         and right-to-left text, CSV) into a scratch session under the \
+                         storage root, runs searches with known answers, reads the files \
+                         back as read_file and preview_chunk do, then deletes it. Existing \
+                         sessions are not touched. Reports each step as passed, failed or \
+                         skipped with its time. Use format='json' for structured output."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "description": "Output format: 'markdown' (default) or 'json'",
+                        "default": "markdown",
+                        "enum": ["markdown", "json"]
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct RunSelfTestArgs {
+            #[serde(default = "default_format")]
+            format: String,
+        }
+        fn default_format() -> String {
+            "markdown".to_string()
+        }
+
+        let args: RunSelfTestArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let json_output = match args.format.as_str() {
+            "markdown" => false,
+            "json" => true,
+            other => {
+                return Err(McpError::InvalidParams(format!(
+                    "Invalid format: '{other}'. Must be 'markdown' or 'json'."
+                )))
+            }
+        };
+
+        let services = Arc::clone(&self.services);
+        let report = run_blocking(move || services.self_test()).await?;
+
+        let text = if json_output {
+            serde_json::to_string_pretty(&report)
+                .map_err(|e| McpError::InternalError(format!("Failed to serialize report: {e}")))?
+        } else {
+            Self::format_report(&report)
+        };
+
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::mcp::protocol::ContentBlock;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (RunSelfTestHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        (RunSelfTestHandler::new(services), temp_dir)
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_self_test_markdown() {
+        let (handler, temp) = setup_test_handler();
+
+        let result = handler.execute(json!({})).await.unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.starts_with("**Self-test:** PASSED"), "{text}");
+        assert!(text.contains("| index | passed |"));
+        assert!(text.contains("| search \"全文検索\" | passed |"));
+        assert!(text.contains("| cleanup | passed |"));
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_self_test_json() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"format": "json"})).await.unwrap();
+        let report: SelfTestReport = serde_json::from_str(extract_text(&result)).unwrap();
+
+        assert!(report.passed());
+        assert_eq!(report.steps.last().unwrap().name, "cleanup");
+        assert!(!report.scratch_dir.exists());
+    }
+}
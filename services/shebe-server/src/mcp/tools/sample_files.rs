@@ -0,0 +1,357 @@
+//! Sample files tool handler
+//!
+//! Shows the first lines of the indexed files matching a few glob
+//! patterns (README, entrypoints, manifests) in one call, for a quick
+//! orientation in an unfamiliar session.
+
+use super::find_file::{glob_matcher, relative_root, session_file_paths};
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{detect_language, path_display};
+use crate::core::paths;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{
+    build_truncation_footer, estimate_tokens, ResponseBudget, READ_FILE_MAX_CHARS,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+const DEFAULT_LINES_PER_FILE: usize = 30;
+const MAX_LINES_PER_FILE: usize = 100;
+const DEFAULT_MAX_FILES: usize = 5;
+const MAX_MAX_FILES: usize = 15;
+
+/// Most patterns accepted in one call
+const MAX_PATTERNS: usize = 20;
+
+/// Shown instead of a file head dropped to fit the response budget
+const CONTENT_OMITTED: &str = "_Content omitted (response budget); view with read_file._";
+
+/// How to get files trimmed by the response budget
+const TRUNCATION_HINT: &str = "lower `max_files` or `lines_per_file`, or use read_file";
+
+/// The start of a sampled file
+#[derive(Debug, PartialEq)]
+enum Head {
+    /// Up to `lines_per_file` lines; `more` if the file goes on
+    Text { content: String, more: bool },
+    /// NUL bytes or invalid UTF-8
+    Binary,
+    /// Deleted (or moved) since indexing
+    Missing,
+}
+
+/// Read the first `lines` lines of `path`
+///
+/// At most [`READ_FILE_MAX_CHARS`] bytes are read; a multi-byte
+/// character cut by that limit is dropped.
+fn read_head(path: &Path, lines: usize) -> io::Result<Head> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Head::Missing),
+        Err(e) => return Err(e),
+    };
+    let mut buffer = Vec::new();
+    file.take(READ_FILE_MAX_CHARS as u64 + 1)
+        .read_to_end(&mut buffer)?;
+    let capped = buffer.len() > READ_FILE_MAX_CHARS;
+    buffer.truncate(READ_FILE_MAX_CHARS);
+
+    if buffer.contains(&0) {
+        return Ok(Head::Binary);
+    }
+    let text = match std::str::from_utf8(&buffer) {
+        Ok(text) => text,
+        // Only a character cut at the end of a capped read is tolerated
+        Err(e) if capped && e.error_len().is_none() => {
+            std::str::from_utf8(&buffer[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return Ok(Head::Binary),
+    };
+
+    let mut all = text.lines();
+    let content: Vec<&str> = all.by_ref().take(lines).collect();
+    Ok(Head::Text {
+        content: content.join("\n"),
+        more: capped || all.next().is_some(),
+    })
+}
+
+pub struct SampleFilesHandler {
+    services: Arc<Services>,
+}
+
+impl SampleFilesHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Indexed files matching any pattern, in pattern order
+    ///
+    /// Files within one pattern are sorted by path; a file matched by
+    /// several patterns is listed once. Returns all matches, not yet
+    /// capped to `max_files`.
+    fn matching_files(&self, session: &str, patterns: &[String]) -> Result<Vec<String>, McpError> {
+        let mut all_files = session_file_paths(&self.services, session)?;
+        all_files.sort();
+
+        let mut matched: Vec<String> = Vec::new();
+        for pattern in patterns {
+            let root = relative_root(&self.services, session, pattern)?;
+            let matches = glob_matcher(pattern, root.as_deref())?;
+            for path in &all_files {
+                if matches(path) && !matched.contains(path) {
+                    matched.push(path.clone());
+                }
+            }
+        }
+        Ok(matched)
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for SampleFilesHandler {
+    fn name(&self) -> &str {
+        "sample_files"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "sample_files".to_string(),
+            description: "Show the first lines of several indexed files in one call, for quick \
+                         orientation in an unfamiliar codebase. Takes glob patterns (e.g. \
+                         'README*', 'src/main.rs', 'Cargo.toml', '**/pyproject.toml') and \
+                         returns the head of each matching file as a code block, in pattern \
+                         order. Files deleted since indexing and binary files are listed as \
+                         skipped. Use read_file for a whole file."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to sample",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "patterns": {
+                        "type": "array",
+                        "items": {"type": "string", "minLength": 1},
+                        "minItems": 1,
+                        "maxItems": MAX_PATTERNS,
+                        "description": "Glob patterns, as for find_file. Relative patterns \
+                                       match paths relative to the repository root."
+                    },
+                    "lines_per_file": {
+                        "type": "integer",
+                        "description": "Lines shown from the start of each file \
+                                       (default: 30, max: 100)",
+                        "default": DEFAULT_LINES_PER_FILE,
+                        "minimum": 1,
+                        "maximum": MAX_LINES_PER_FILE
+                    },
+                    "max_files": {
+                        "type": "integer",
+                        "description": "Most files sampled (default: 5, max: 15)",
+                        "default": DEFAULT_MAX_FILES,
+                        "minimum": 1,
+                        "maximum": MAX_MAX_FILES
+                    }
+                },
+                "required": ["session", "patterns"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct SampleFilesArgs {
+            session: String,
+            patterns: Vec<String>,
+            #[serde(default = "default_lines_per_file")]
+            lines_per_file: usize,
+            #[serde(default = "default_max_files")]
+            max_files: usize,
+        }
+        fn default_lines_per_file() -> usize {
+            DEFAULT_LINES_PER_FILE
+        }
+        fn default_max_files() -> usize {
+            DEFAULT_MAX_FILES
+        }
+
+        let args: SampleFilesArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        if args.patterns.is_empty() || args.patterns.len() > MAX_PATTERNS {
+            return Err(McpError::InvalidParams(format!(
+                "patterns must contain between 1 and {MAX_PATTERNS} entries"
+            )));
+        }
+        if args.patterns.iter().any(|p| p.trim().is_empty()) {
+            return Err(McpError::InvalidParams(
+                "patterns cannot contain an empty pattern".to_string(),
+            ));
+        }
+        if !(1..=MAX_LINES_PER_FILE).contains(&args.lines_per_file) {
+            return Err(McpError::InvalidParams(format!(
+                "lines_per_file must be between 1 and {MAX_LINES_PER_FILE}"
+            )));
+        }
+        if !(1..=MAX_MAX_FILES).contains(&args.max_files) {
+            return Err(McpError::InvalidParams(format!(
+                "max_files must be between 1 and {MAX_MAX_FILES}"
+            )));
+        }
+
+        let matched = self.matching_files(&args.session, &args.patterns)?;
+        let display = path_display(&self.services, &args.session, None, None)?;
+        let patterns: Vec<String> = args.patterns.iter().map(|p| format!("`{p}`")).collect();
+
+        let mut output = format!(
+            "**Session:** `{}`\n**Patterns:** {}\n",
+            args.session,
+            patterns.join(", ")
+        );
+        if matched.is_empty() {
+            output.push_str(
+                "\nNo indexed files match these patterns. Try find_file or list_dir to see \
+                 what the session contains.",
+            );
+            return Ok(text_content(output));
+        }
+        output.push_str(&format!(
+            "**Files:** {} of {} matching (max_files: {})\n\n",
+            matched.len().min(args.max_files),
+            matched.len(),
+            args.max_files
+        ));
+
+        let mut entries: Vec<(String, Option<String>)> = Vec::new();
+        let mut skipped = Vec::new();
+        for file_path in matched.iter().take(args.max_files) {
+            let shown = display.path(file_path);
+            self.services.authorize_read(file_path)?;
+            let head = read_head(&paths::to_native(file_path), args.lines_per_file)
+                .map_err(|e| McpError::InternalError(format!("Failed to read {shown}: {e}")))?;
+            let (content, more) = match head {
+                Head::Text { content, more } => (content, more),
+                Head::Binary => {
+                    skipped.push(format!("`{shown}`: binary file, not shown"));
+                    continue;
+                }
+                Head::Missing => {
+                    skipped.push(format!(
+                        "`{shown}`: no longer exists (deleted since indexing; re-index the \
+                         session)"
+                    ));
+                    continue;
+                }
+            };
+
+            let line_count = content.lines().count();
+            let title = if more {
+                format!("## `{shown}` (first {line_count} lines)\n")
+            } else {
+                format!("## `{shown}` (whole file, {line_count} lines)\n")
+            };
+            let lang = detect_language(file_path);
+            entries.push((
+                format!("{title}```{lang}\n{content}\n```\n\n"),
+                Some(format!("{title}{CONTENT_OMITTED}\n\n")),
+            ));
+        }
+
+        let mut skipped_section = String::new();
+        if !skipped.is_empty() {
+            skipped_section.push_str("### Skipped\n");
+            for line in &skipped {
+                skipped_section.push_str(&format!("- {line}\n"));
+            }
+        }
+
+        // Files are in priority order, so the last ones are trimmed first
+        let budget = ResponseBudget::default();
+        let fixed = estimate_tokens(&output) + estimate_tokens(&skipped_section);
+        let (body, plan) = budget.fit(fixed, &entries);
+        output.push_str(&body);
+        output.push_str(&skipped_section);
+        if plan.is_truncated() {
+            let mut omitted = Vec::new();
+            if plan.compacted() > 0 {
+                omitted.push(format!(
+                    "Content omitted for the last {} of {} files",
+                    plan.compacted(),
+                    entries.len()
+                ));
+            }
+            if plan.dropped() > 0 {
+                omitted.push(format!("Last {} files not shown", plan.dropped()));
+            }
+            output.push_str(&build_truncation_footer(&budget, &omitted, TRUNCATION_HINT));
+        }
+
+        Ok(text_content(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_head() {
+        let dir = TempDir::new().unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "one\ntwo\nthree\n").unwrap();
+
+        assert_eq!(
+            read_head(&text, 2).unwrap(),
+            Head::Text {
+                content: "one\ntwo".to_string(),
+                more: true
+            }
+        );
+        assert_eq!(
+            read_head(&text, 3).unwrap(),
+            Head::Text {
+                content: "one\ntwo\nthree".to_string(),
+                more: false
+            }
+        );
+
+        let binary = dir.path().join("logo.png");
+        std::fs::write(&binary, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        assert_eq!(read_head(&binary, 5).unwrap(), Head::Binary);
+
+        let latin1 = dir.path().join("latin1.txt");
+        std::fs::write(&latin1, b"caf\xe9\n").unwrap();
+        assert_eq!(read_head(&latin1, 5).unwrap(), Head::Binary);
+
+        assert_eq!(
+            read_head(&dir.path().join("gone.rs"), 5).unwrap(),
+            Head::Missing
+        );
+    }
+
+    #[test]
+    fn test_read_head_drops_character_cut_by_cap() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wide.txt");
+        // 'é' is two bytes, so after one ASCII byte the cap splits one
+        let content = format!("a{}", "é".repeat(READ_FILE_MAX_CHARS / 2));
+        std::fs::write(&path, &content).unwrap();
+
+        let Head::Text { content, more } = read_head(&path, 1).unwrap() else {
+            panic!("expected text");
+        };
+        assert!(more);
+        assert_eq!(content.len(), READ_FILE_MAX_CHARS - 1);
+    }
+}
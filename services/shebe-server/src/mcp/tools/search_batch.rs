@@ -0,0 +1,661 @@
+//! Search batch tool handler
+//!
+//! Runs several `search_code` queries against one session in a single
+//! call. The session index is opened once and the queries run in order
+//! over the same searcher. A query that fails (bad syntax, unknown
+//! field, k too large) reports its error in its own block; the others
+//! still run.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{label_hits, path_display, run_blocking};
+use super::search_code::{
+    format_expansion_notes, format_overlaps_hidden, format_short_chunks_demoted, group_entries,
+    result_entries,
+};
+use crate::core::logging::time_phase;
+use crate::core::search::{preprocess_query, validate_query_fields};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::{SearchRequest, SearchResponse};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{build_truncation_footer, estimate_tokens, EntrySize, ResponseBudget};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Most queries accepted in one batch
+pub const MAX_BATCH_QUERIES: usize = 20;
+
+/// Most results per query (search_code allows 100)
+pub const MAX_BATCH_K: usize = 20;
+
+/// Results per query when `k` is not given
+const DEFAULT_BATCH_K: usize = 5;
+
+/// How to get results trimmed by the response budget
+const TRUNCATION_HINT: &str =
+    "run fewer queries or lower `k`; view omitted snippets with preview_chunk";
+
+/// One query of a batch
+#[derive(Debug, Deserialize)]
+struct BatchQuery {
+    query: String,
+    #[serde(default = "default_k")]
+    k: usize,
+    #[serde(default)]
+    literal: bool,
+    #[serde(default)]
+    group_by_file: bool,
+    #[serde(default = "default_dedupe_overlaps")]
+    dedupe_overlaps: bool,
+    path_boost: Option<f32>,
+    fuzziness: Option<u8>,
+    #[serde(default = "default_expand_identifiers")]
+    expand_identifiers: bool,
+}
+
+fn default_k() -> usize {
+    DEFAULT_BATCH_K
+}
+
+fn default_dedupe_overlaps() -> bool {
+    true
+}
+
+fn default_expand_identifiers() -> bool {
+    true
+}
+
+pub struct SearchBatchHandler {
+    services: Arc<Services>,
+}
+
+impl SearchBatchHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Build the search request for one query, or say why it is invalid
+    fn build_request(session: &str, query: &BatchQuery) -> Result<SearchRequest, String> {
+        if query.query.trim().is_empty() {
+            return Err("Query cannot be empty".to_string());
+        }
+        if query.k == 0 || query.k > MAX_BATCH_K {
+            return Err(format!(
+                "k must be between 1 and {MAX_BATCH_K} in a batch, got {}",
+                query.k
+            ));
+        }
+        // Skip field validation in literal mode (all colons are escaped anyway)
+        if !query.literal {
+            validate_query_fields(&query.query).map_err(|e| slot_error(McpError::from(e)))?;
+        }
+
+        Ok(SearchRequest {
+            query: preprocess_query(&query.query, query.literal),
+            session: session.to_string(),
+            k: Some(query.k),
+            group_by_file: query.group_by_file,
+            dedupe_overlaps: query.dedupe_overlaps,
+            path_boost: query.path_boost,
+            fuzziness: query.fuzziness,
+            expand_identifiers: query.expand_identifiers && !query.literal,
+            bm25_k1: None,
+            bm25_b: None,
+            within: None,
+            penalize_short_chunks: None,
+            modified_within: None,
+            section_filter: None,
+            exclude_terms: Vec::new(),
+            exclude_paths: Vec::new(),
+            explain: false,
+        })
+    }
+}
+
+/// First result handle and response of a query, or why it failed
+type Outcome = Result<(usize, SearchResponse), String>;
+
+/// Message of a failed query, without the error kind prefix
+fn slot_error(error: McpError) -> String {
+    match error {
+        McpError::InvalidParams(message)
+        | McpError::InvalidRequest(message)
+        | McpError::InternalError(message)
+        | McpError::ToolError(_, message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Format the batch as Markdown within `budget`
+///
+/// Every query keeps its header (and error, if it failed). Results
+/// compete for the budget by rank across queries: all first results
+/// come before any second result, so when the response is trimmed the
+/// lowest-ranked results of every query go first rather than whole
+/// queries at the end of the batch.
+fn format_batch(
+    session: &str,
+    queries: &[BatchQuery],
+    outcomes: &[Outcome],
+    total_ms: u64,
+    budget: &ResponseBudget,
+    display: &crate::core::display::PathDisplay,
+) -> String {
+    let failed = outcomes.iter().filter(|o| o.is_err()).count();
+    let mut header = format!(
+        "# Batch search: {} queries on '{session}' ({total_ms}ms total{})\n\n",
+        queries.len(),
+        match failed {
+            0 => String::new(),
+            n => format!(", {n} failed"),
+        }
+    );
+
+    // Per-query headers, summaries and rendered results
+    let mut blocks = Vec::with_capacity(outcomes.len());
+    for (i, (query, outcome)) in queries.iter().zip(outcomes).enumerate() {
+        let mut head = format!("## Query {}: `{}`\n", i + 1, query.query);
+        let entries = match outcome {
+            Ok((first_handle, response)) if query.group_by_file => {
+                header.push_str(&format!(
+                    "- Query {}: {} files, {}ms\n",
+                    i + 1,
+                    response.groups.len(),
+                    response.duration_ms
+                ));
+                head.push_str(&format!(
+                    "Found {} files ({}ms, grouped by file)\n\n",
+                    response.groups.len(),
+                    response.duration_ms
+                ));
+                head.push_str(&format_expansion_notes(response));
+                group_entries(response, display, "###", *first_handle, 1)
+            }
+            Ok((first_handle, response)) => {
+                header.push_str(&format!(
+                    "- Query {}: {} results, {}ms\n",
+                    i + 1,
+                    response.count,
+                    response.duration_ms
+                ));
+                head.push_str(&format!(
+                    "Found {} results ({}ms{}{})\n\n",
+                    response.count,
+                    response.duration_ms,
+                    format_overlaps_hidden(response.overlap_duplicates_dropped),
+                    format_short_chunks_demoted(response.short_chunks_demoted)
+                ));
+                head.push_str(&format_expansion_notes(response));
+                result_entries(response, display, "###", *first_handle, 1)
+            }
+            Err(message) => {
+                header.push_str(&format!("- Query {}: failed\n", i + 1));
+                head.push_str(&format!("**Error:** {message}\n\n"));
+                Vec::new()
+            }
+        };
+        blocks.push((head, entries));
+    }
+    header.push('\n');
+
+    // Results in priority order: by rank, then by query
+    let mut order: Vec<(usize, usize)> = blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(q, (_, entries))| (0..entries.len()).map(move |rank| (rank, q)))
+        .collect();
+    order.sort_unstable();
+    let sizes: Vec<EntrySize> = order
+        .iter()
+        .map(|&(rank, q)| {
+            let (full, compact) = &blocks[q].1[rank];
+            EntrySize::measure(full, compact.as_deref())
+        })
+        .collect();
+    let mut priority = vec![Vec::new(); blocks.len()];
+    for (p, &(_, q)) in order.iter().enumerate() {
+        priority[q].push(p);
+    }
+
+    let fixed = estimate_tokens(&header)
+        + blocks
+            .iter()
+            .map(|(head, _)| estimate_tokens(head))
+            .sum::<usize>();
+    let plan = budget.plan(fixed, &sizes);
+
+    let mut output = header;
+    for ((head, entries), priority) in blocks.iter().zip(&priority) {
+        output.push_str(head);
+        let mut dropped = 0;
+        for ((full, compact), &p) in entries.iter().zip(priority) {
+            if !plan.is_kept(p) {
+                dropped += 1;
+                continue;
+            }
+            match compact {
+                Some(compact) if plan.is_compacted(p) => output.push_str(compact),
+                _ => output.push_str(full),
+            }
+        }
+        if dropped > 0 {
+            output.push_str(&format!(
+                "_{dropped} lower-ranked results not shown (response budget)._\n\n"
+            ));
+        }
+    }
+
+    if plan.is_truncated() {
+        let mut omitted = Vec::new();
+        if plan.compacted() > 0 {
+            omitted.push(format!(
+                "Snippets omitted for {} of {} results (lowest-ranked first)",
+                plan.compacted(),
+                sizes.len()
+            ));
+        }
+        if plan.dropped() > 0 {
+            omitted.push(format!(
+                "{} lowest-ranked results not shown across all queries",
+                plan.dropped()
+            ));
+        }
+        output.push_str(&build_truncation_footer(budget, &omitted, TRUNCATION_HINT));
+    }
+
+    output
+}
+
+#[async_trait]
+impl McpToolHandler for SearchBatchHandler {
+    fn name(&self) -> &str {
+        "search_batch"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "search_batch".to_string(),
+            description: format!(
+                "Run up to {MAX_BATCH_QUERIES} BM25 searches against one session in a single call. \
+                 Same query syntax and options as search_code; the index is opened once and the \
+                 queries run in order. Returns one block per query, in order, after a header with \
+                 the total time and each query's result count and time. \
+                 \
+                 USE FOR: several related lookups at once (each field or method of a struct, every \
+                 caller of a set of functions) instead of many search_code calls. \
+                 \
+                 A query that fails (invalid syntax, unknown field) shows its error in its own block \
+                 without failing the batch. k defaults to {DEFAULT_BATCH_K} and is capped at \
+                 {MAX_BATCH_K} per query. When the response is over budget, the lowest-ranked results \
+                 of every query are trimmed first."
+            ),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to search. Use list_sessions to discover available sessions.",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "queries": {
+                        "type": "array",
+                        "description": "Queries to run, in order. Each takes the search_code options.",
+                        "minItems": 1,
+                        "maxItems": MAX_BATCH_QUERIES,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "query": {
+                                    "type": "string",
+                                    "description": "Search query (search_code syntax, auto-preprocessed).",
+                                    "minLength": 1,
+                                    "maxLength": 500
+                                },
+                                "k": {
+                                    "type": "integer",
+                                    "description": "Max results for this query.",
+                                    "default": DEFAULT_BATCH_K,
+                                    "minimum": 1,
+                                    "maximum": MAX_BATCH_K
+                                },
+                                "literal": {
+                                    "type": "boolean",
+                                    "description": "Search for the exact string (no query parsing).",
+                                    "default": false
+                                },
+                                "group_by_file": {
+                                    "type": "boolean",
+                                    "description": "One entry per file; k counts files.",
+                                    "default": false
+                                },
+                                "dedupe_overlaps": {
+                                    "type": "boolean",
+                                    "description": "Hide chunk overlap duplicates.",
+                                    "default": true
+                                },
+                                "path_boost": {
+                                    "type": "number",
+                                    "description": "Boost for query terms that also match the file path. \
+                                                   Default: server search.path_boost.",
+                                    "minimum": 0
+                                },
+                                "fuzziness": {
+                                    "type": "integer",
+                                    "description": "Edit distance for typo-tolerant matching.",
+                                    "minimum": 0,
+                                    "maximum": 2,
+                                    "default": 0
+                                },
+                                "expand_identifiers": {
+                                    "type": "boolean",
+                                    "description": "Also match other spellings of identifiers \
+                                                   (getUserId <-> get_user_id). Ignored with literal.",
+                                    "default": true
+                                }
+                            },
+                            "required": ["query"]
+                        }
+                    },
+                    "link_format": {
+                        "type": "string",
+                        "description": "Template for an \"open in editor\" link on each result path \
+                                       (see search_code). Default: server output.link_format (none)."
+                    },
+                    "relative_paths": {
+                        "type": "boolean",
+                        "description": "Show paths relative to the session's repository path. \
+                                       Default: server output.relative_paths (false)."
+                    }
+                },
+                "required": ["session", "queries"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct BatchArgs {
+            session: String,
+            queries: Vec<BatchQuery>,
+            link_format: Option<String>,
+            relative_paths: Option<bool>,
+        }
+
+        let args: BatchArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        if args.queries.is_empty() {
+            return Err(McpError::InvalidParams(
+                "queries must contain at least one query".to_string(),
+            ));
+        }
+        if args.queries.len() > MAX_BATCH_QUERIES {
+            return Err(McpError::InvalidParams(format!(
+                "queries cannot exceed {MAX_BATCH_QUERIES} (got {})",
+                args.queries.len()
+            )));
+        }
+
+        let display = path_display(
+            &self.services,
+            &args.session,
+            args.link_format.as_deref(),
+            args.relative_paths,
+        )?;
+
+        let start = Instant::now();
+
+        // Queries rejected here never reach the search service
+        let built: Vec<Result<SearchRequest, String>> = args
+            .queries
+            .iter()
+            .map(|query| Self::build_request(&args.session, query))
+            .collect();
+        let requests: Vec<SearchRequest> = built
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .cloned()
+            .collect();
+
+        let search = Arc::clone(&self.services.search);
+        let session = args.session.clone();
+        let mut responses = run_blocking(move || search.search_batch(&session, &requests))
+            .await?
+            .into_iter();
+        let outcomes: Vec<Outcome> = built
+            .into_iter()
+            .zip(&args.queries)
+            .map(|(built, query)| {
+                let response = built.and_then(|_| {
+                    responses
+                        .next()
+                        .expect("one response per valid request")
+                        .map_err(|e| slot_error(McpError::from(e)))
+                })?;
+                let first_handle = label_hits(
+                    &self.services,
+                    Some(&args.session),
+                    &query.query,
+                    &response.results,
+                );
+                Ok((first_handle, response))
+            })
+            .collect();
+
+        let total_ms = start.elapsed().as_millis() as u64;
+        let text = time_phase("format", || {
+            format_batch(
+                &args.session,
+                &args.queries,
+                &outcomes,
+                total_ms,
+                &ResponseBudget::default(),
+                &display,
+            )
+        });
+
+        Ok(text_content(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::display::PathDisplay;
+    use crate::core::storage::SessionConfig;
+    use crate::core::types::Chunk;
+    use crate::mcp::protocol::ContentBlock;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (SearchBatchHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let handler = SearchBatchHandler::new(Arc::new(Services::new(config)));
+        (handler, temp_dir)
+    }
+
+    fn create_test_session(services: &Arc<Services>, session_id: &str) {
+        let mut index = services
+            .storage
+            .create_session(
+                session_id,
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+
+        let chunk = |path: &str, index: usize, text: &str| Chunk {
+            text: text.to_string(),
+            file_path: PathBuf::from(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: index,
+            records: None,
+            section: None,
+        };
+        let chunks = vec![
+            chunk("user.rs", 0, "struct User { name: String, email: String }"),
+            chunk("auth.rs", 0, "fn login(user: &User) { check(user.email) }"),
+            chunk("mail.rs", 0, "fn send(email: &str) { smtp(email) }"),
+        ];
+
+        index.add_chunks(&chunks, session_id).unwrap();
+        index.commit().unwrap();
+    }
+
+    fn text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_returns_blocks_in_order() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(&handler.services, "batch");
+
+        let result = handler
+            .execute(json!({
+                "session": "batch",
+                "queries": [
+                    {"query": "email"},
+                    {"query": "login", "k": 1},
+                    {"query": "User", "group_by_file": true}
+                ]
+            }))
+            .await
+            .unwrap();
+        let text = text(&result);
+
+        assert!(text.starts_with("# Batch search: 3 queries on 'batch' ("));
+        assert!(text.contains("- Query 1: 3 results, "));
+        assert!(text.contains("- Query 2: 1 results, "));
+        assert!(text.contains("- Query 3: 2 files, "));
+        let first = text.find("## Query 1: `email`").unwrap();
+        let second = text.find("## Query 2: `login`").unwrap();
+        let third = text.find("## Query 3: `User`").unwrap();
+        assert!(first < second && second < third);
+        assert!(text[first..second].contains("### Result 3"));
+        assert!(text[second..third].contains("### Result 1 (score: "));
+        assert!(text[second..third].contains(", handle r4)"));
+        assert!(text[third..].contains("### File 1:"));
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_reports_failed_query_inline() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(&handler.services, "batch");
+
+        let result = handler
+            .execute(json!({
+                "session": "batch",
+                "queries": [
+                    {"query": "email AND ("},
+                    {"query": "nofield:email"},
+                    {"query": "email", "k": 50},
+                    {"query": "login"}
+                ]
+            }))
+            .await
+            .unwrap();
+        let text = text(&result);
+
+        assert!(text.contains("3 failed"));
+        assert!(text.contains("- Query 1: failed"));
+        assert!(text.contains("**Error:** Invalid query:"));
+        assert!(text.contains("Unknown field 'nofield'"));
+        assert!(text.contains("k must be between 1 and 20 in a batch, got 50"));
+        assert!(text.contains("- Query 4: 1 results"));
+        assert!(text.contains("`auth.rs` (chunk 0"));
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_rejects_bad_batches() {
+        let (handler, _temp) = setup_test_handler();
+        create_test_session(&handler.services, "batch");
+
+        let empty = handler
+            .execute(json!({"session": "batch", "queries": []}))
+            .await;
+        assert!(matches!(empty, Err(McpError::InvalidParams(_))));
+
+        let queries: Vec<Value> = (0..=MAX_BATCH_QUERIES)
+            .map(|_| json!({"query": "email"}))
+            .collect();
+        let too_many = handler
+            .execute(json!({"session": "batch", "queries": queries}))
+            .await;
+        assert!(matches!(too_many, Err(McpError::InvalidParams(_))));
+
+        let missing = handler
+            .execute(json!({"session": "missing", "queries": [{"query": "email"}]}))
+            .await;
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_format_batch_trims_lowest_ranks_across_queries() {
+        let result = |i: usize| crate::core::types::SearchResult {
+            score: 10.0 - i as f32,
+            text: "x".repeat(400),
+            file_path: format!("/repo/file{i}.rs"),
+            chunk_index: 0,
+            session: None,
+            start_offset: 0,
+            end_offset: 400,
+            also_present_at: Vec::new(),
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        };
+        let response = |query: &str| SearchResponse {
+            query: query.to_string(),
+            results: (0..5).map(result).collect(),
+            count: 5,
+            duration_ms: 1,
+            groups: Vec::new(),
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        };
+        let queries: Vec<BatchQuery> = ["a", "b"]
+            .iter()
+            .map(|q| serde_json::from_value(json!({ "query": q })).unwrap())
+            .collect();
+        let outcomes = vec![Ok((1, response("a"))), Ok((6, response("b")))];
+
+        // Room for the headers and about three full results
+        let display = PathDisplay::new(None, false, None);
+        let budget = ResponseBudget::new(500);
+        let text = format_batch("s", &queries, &outcomes, 2, &budget, &display);
+
+        let second = text.find("## Query 2").unwrap();
+        let (a, b) = text.split_at(second);
+        assert!(a.contains("### Result 1") && b.contains("### Result 1"));
+        assert!(!a.contains("### Result 5") && !b.contains("### Result 5"));
+        assert!(a.contains("lower-ranked results not shown"));
+        assert!(text.contains("**RESPONSE TRUNCATED**"));
+        assert!(text.contains("run fewer queries or lower `k`"));
+    }
+}
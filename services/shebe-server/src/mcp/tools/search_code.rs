@@ -1,19 +1,45 @@
 //! Search code tool handler
 
-use super::handler::{text_content, McpToolHandler};
-use super::helpers::{detect_language, truncate_text};
-use crate::core::search::{preprocess_query, validate_query_fields};
+use super::handler::{requested_timeout, text_content, McpToolHandler};
+use super::helpers::{
+    byte_offset_to_line_number, collection_display, detect_language, format_count, format_duration,
+    format_section, format_time_ago, label_hits, path_display, run_blocking, truncate_text,
+    SearchTarget,
+};
+use crate::core::display::PathDisplay;
+use crate::core::logging::time_phase;
+use crate::core::paths;
+use crate::core::results::handle_label;
+use crate::core::search::{
+    locate_result, match_terms, preprocess_query, validate_query_fields, MAX_EXCLUSIONS,
+};
 use crate::core::services::Services;
-use crate::core::types::SearchRequest;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::{
+    OutputMode, SearchRequest, SearchResponse, SearchResult, SearchWithin, MAX_K,
+};
 use crate::mcp::error::McpError;
+use crate::mcp::pagination::{query_fingerprint, session_fingerprint, SearchCursor};
 use crate::mcp::protocol::{ToolResult, ToolSchema};
+use crate::mcp::utils::{build_truncation_footer, estimate_tokens, BudgetPlan, ResponseBudget};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
 
 const MAX_RESULT_TEXT_CHARS: usize = 2000;
 
+/// Shown instead of a snippet dropped to fit the response budget
+const SNIPPET_OMITTED: &str = "_Snippet omitted (response budget); view with preview_chunk._";
+
+/// How to get results trimmed by the response budget
+const TRUNCATION_HINT: &str =
+    "lower `k` or narrow the query; view omitted snippets with preview_chunk";
+
 pub struct SearchCodeHandler {
     services: Arc<Services>,
 }
@@ -23,37 +49,522 @@ impl SearchCodeHandler {
         Self { services }
     }
 
-    fn format_results(&self, response: &crate::core::types::SearchResponse) -> String {
+    /// Format results as Markdown
+    ///
+    /// The first result is numbered `first_rank`. Returns the text and
+    /// the number of results shown within the budget.
+    fn format_results(
+        &self,
+        response: &SearchResponse,
+        first_handle: usize,
+        first_rank: usize,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
+    ) -> (String, usize) {
         let mut output = format!(
-            "Found {} results for query '{}' ({}ms):\n\n",
-            response.count, response.query, response.duration_ms
+            "Found {} results for query '{}' ({}{}{}):\n\n",
+            format_count(response.count as u64),
+            response.query,
+            format_duration(Duration::from_millis(response.duration_ms)),
+            format_overlaps_hidden(response.overlap_duplicates_dropped),
+            format_short_chunks_demoted(response.short_chunks_demoted)
         );
+        output.push_str(&format_expansion_notes(response));
 
         if response.results.is_empty() {
             output.push_str("No results found. Try different keywords or check session name.");
-            return output;
+            return (output, 0);
         }
 
-        for (i, result) in response.results.iter().enumerate() {
-            output.push_str(&format!(
-                "## Result {} (score: {:.2})\n",
-                i + 1,
-                result.score
-            ));
+        let entries = result_entries(response, display, "##", first_handle, first_rank);
+        let (body, plan) = budget.fit(estimate_tokens(&output), &entries);
+        output.push_str(&body);
+        output.push_str(&format_truncation(&plan, budget, "results", first_rank));
+
+        (output, plan.kept())
+    }
+
+    /// Format grouped results (one entry per file) as Markdown
+    fn format_grouped_results(
+        &self,
+        response: &SearchResponse,
+        first_handle: usize,
+        first_rank: usize,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
+    ) -> (String, usize) {
+        let mut output = format!(
+            "Found {} files for query '{}' ({}, grouped by file):\n\n",
+            format_count(response.groups.len() as u64),
+            response.query,
+            format_duration(Duration::from_millis(response.duration_ms))
+        );
+        output.push_str(&format_expansion_notes(response));
+
+        if response.groups.is_empty() {
+            output.push_str("No results found. Try different keywords or check session name.");
+            return (output, 0);
+        }
+
+        let entries = group_entries(response, display, "##", first_handle, first_rank);
+        let (body, plan) = budget.fit(estimate_tokens(&output), &entries);
+        output.push_str(&body);
+        output.push_str(&format_truncation(&plan, budget, "files", first_rank));
+
+        (output, plan.kept())
+    }
+
+    /// Format results as a compact table of locations (no chunk text)
+    ///
+    /// Grouped responses get one row per file, for its best chunk.
+    /// Returns the text and the number of rows shown within the budget.
+    fn format_locations(
+        &self,
+        response: &SearchResponse,
+        first_handle: usize,
+        first_rank: usize,
+        budget: &ResponseBudget,
+        display: &PathDisplay,
+        query: &str,
+    ) -> (String, usize) {
+        let (hits, noun): (Vec<&SearchResult>, _) = if response.groups.is_empty() {
+            (response.results.iter().collect(), "results")
+        } else {
+            (response.groups.iter().map(|g| g.best()).collect(), "files")
+        };
+
+        let mut output = format!(
+            "Found {} {noun} for query '{}' ({}{}{}, locations only):\n\n",
+            format_count(hits.len() as u64),
+            response.query,
+            format_duration(Duration::from_millis(response.duration_ms)),
+            format_overlaps_hidden(response.overlap_duplicates_dropped),
+            format_short_chunks_demoted(response.short_chunks_demoted)
+        );
+        output.push_str(&format_expansion_notes(response));
+
+        if hits.is_empty() {
+            output.push_str("No results found. Try different keywords or check session name.");
+            return (output, 0);
+        }
+
+        output.push_str(LOCATIONS_HEADER);
+        let entries = location_entries(
+            &hits,
+            display,
+            first_handle,
+            &match_terms(query, &response.identifier_expansions),
+        );
+        let (body, plan) = budget.fit(estimate_tokens(&output), &entries);
+        output.push_str(&body);
+        output.push_str(&format_truncation(&plan, budget, noun, first_rank));
 
-            output.push_str(&format!(
-                "**File:** `{}` (chunk {}, bytes {}-{})\n\n",
-                result.file_path, result.chunk_index, result.start_offset, result.end_offset
+        (output, plan.kept())
+    }
+}
+
+/// Header of the locations table
+const LOCATIONS_HEADER: &str =
+    "| Handle | File | Line | Score | Excerpt |\n|--------|------|------|-------|---------|\n";
+
+/// Render hits as one table row each (see [`locate_result`])
+///
+/// The line column falls back to the chunk index when the file cannot be
+/// read. Hit `i` is labelled with handle number `first_handle + i`.
+fn location_entries(
+    hits: &[&SearchResult],
+    display: &PathDisplay,
+    first_handle: usize,
+    terms: &[String],
+) -> Vec<(String, Option<String>)> {
+    let mut chunks = ChunkLookup::new(display);
+    hits.iter()
+        .enumerate()
+        .map(|(i, hit)| {
+            let location = locate_result(hit, chunks.content(&hit.file_path), terms);
+            let line = match location.line {
+                Some(line) => line.to_string(),
+                None => format!("chunk {}", hit.chunk_index),
+            };
+            let row = format!(
+                "| {} | {}{} | {} | {:.2} | {} |\n",
+                handle_label(first_handle + i),
+                hit.session
+                    .as_ref()
+                    .map(|session| format!("{session}: "))
+                    .unwrap_or_default(),
+                display.markdown(
+                    &hit.file_path,
+                    &display.path(&hit.file_path),
+                    location.line,
+                    None
+                ),
+                line,
+                hit.score,
+                location.excerpt.replace('|', "\\|")
+            );
+            (row, None)
+        })
+        .collect()
+}
+
+/// Render results as response budget entries (full, without snippet)
+///
+/// `heading` is the Markdown heading marker for each result. Result `i`
+/// is numbered `first_rank + i` and labelled with handle number
+/// `first_handle + i` (see [`Services::remember_results`]).
+pub(super) fn result_entries(
+    response: &SearchResponse,
+    display: &PathDisplay,
+    heading: &str,
+    first_handle: usize,
+    first_rank: usize,
+) -> Vec<(String, Option<String>)> {
+    let mut chunks = ChunkLookup::new(display);
+    let mut entries = Vec::with_capacity(response.results.len());
+    for (i, result) in response.results.iter().enumerate() {
+        let mut head = format!(
+            "{heading} Result {} (score: {:.2}, handle {}{})\n",
+            first_rank + i,
+            result.score,
+            handle_label(first_handle + i),
+            format_session(result)
+        );
+        let chunk = chunks.locate(result);
+        let line = chunk.line;
+
+        head.push_str(&format!(
+            "**File:** {} (chunk {}{}, bytes {}-{}{}{})\n\n",
+            display.markdown(
+                &result.file_path,
+                &display.path(&result.file_path),
+                line,
+                None
+            ),
+            result.chunk_index,
+            format_records(result),
+            chunk.range.start,
+            chunk.range.end,
+            match (result.matched_in_path, result.short_chunk_demoted) {
+                (true, true) => ", matched in path, short chunk demoted",
+                (true, false) => ", matched in path",
+                (false, true) => ", short chunk demoted",
+                (false, false) => "",
+            },
+            format_change_note(response, result)
+        ));
+        head.push_str(&format_section(
+            &display.path(&result.file_path),
+            result.section.as_deref(),
+        ));
+        head.push_str(&chunk.format_note());
+        head.push_str(&format_also_present(&result.also_present_at, display, line));
+        head.push_str(format_partial_note(result));
+        head.push_str(&format_explanation(result));
+
+        entries.push((
+            format!("{head}{}\n", format_snippet(result)),
+            Some(format!("{head}{SNIPPET_OMITTED}\n\n")),
+        ));
+    }
+    entries
+}
+
+/// Render grouped results as response budget entries, one per file
+///
+/// `heading` is the Markdown heading marker for each file. File `i` is
+/// numbered `first_rank + i` and its best chunk labelled with handle
+/// number `first_handle + i`.
+pub(super) fn group_entries(
+    response: &SearchResponse,
+    display: &PathDisplay,
+    heading: &str,
+    first_handle: usize,
+    first_rank: usize,
+) -> Vec<(String, Option<String>)> {
+    let mut chunks = ChunkLookup::new(display);
+    let mut entries = Vec::with_capacity(response.groups.len());
+    for (i, group) in response.groups.iter().enumerate() {
+        let best = group.best();
+        let chunk = chunks.locate(best);
+        let line = chunk.line;
+        let mut head = format!(
+            "{heading} File {}: {} (best score: {:.2}, handle {}{})\n",
+            first_rank + i,
+            display.markdown(
+                &group.file_path,
+                &display.path(&group.file_path),
+                line,
+                None
+            ),
+            group.best_score,
+            handle_label(first_handle + i),
+            format_session(best)
+        );
+
+        head.push_str(&format!(
+            "**Best chunk:** chunk {}{}, bytes {}-{}{}\n\n",
+            best.chunk_index,
+            format_records(best),
+            chunk.range.start,
+            chunk.range.end,
+            format_change_note(response, best)
+        ));
+        head.push_str(&format_section(
+            &display.path(&group.file_path),
+            best.section.as_deref(),
+        ));
+        head.push_str(&chunk.format_note());
+        head.push_str(&format_also_present(&best.also_present_at, display, line));
+        head.push_str(format_partial_note(best));
+        head.push_str(&format_explanation(best));
+
+        let mut tail = String::new();
+        let more = group.more_chunk_indexes();
+        if !more.is_empty() {
+            let indexes: Vec<String> = more.iter().map(|c| c.to_string()).collect();
+            tail.push_str(&format!(
+                "+{} more chunks: {} (view with preview_chunk)\n",
+                more.len(),
+                indexes.join(", ")
             ));
+        }
+        tail.push('\n');
+
+        entries.push((
+            format!("{head}{}{tail}", format_snippet(best)),
+            Some(format!("{head}{SNIPPET_OMITTED}\n{tail}")),
+        ));
+    }
+    entries
+}
+
+/// Result chunks checked against the files on disk
+///
+/// Files are read once per response. Offsets are corrected for files
+/// changed since indexing; files that cannot be read keep the indexed
+/// offsets and get no line.
+pub(super) struct ChunkLookup<'a> {
+    display: &'a PathDisplay,
+    files: HashMap<String, Option<String>>,
+}
+
+/// Where a result's chunk is in its current file
+pub(super) struct LocatedChunk {
+    /// Current byte range (the indexed one when the chunk is gone)
+    pub(super) range: Range<usize>,
+    /// Start line, when the link template uses one
+    pub(super) line: Option<usize>,
+    /// Why the chunk differs from the file, when it does
+    pub(super) note: Option<String>,
+}
+
+impl LocatedChunk {
+    pub(super) fn format_note(&self) -> String {
+        match &self.note {
+            Some(note) => format!("_{note}_\n\n"),
+            None => String::new(),
+        }
+    }
+}
+
+impl<'a> ChunkLookup<'a> {
+    pub(super) fn new(display: &'a PathDisplay) -> Self {
+        Self {
+            display,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Current contents of a file, read once
+    fn content(&mut self, file_path: &str) -> Option<&str> {
+        self.files
+            .entry(file_path.to_string())
+            .or_insert_with(|| fs::read_to_string(paths::to_native(file_path)).ok())
+            .as_deref()
+    }
 
-            // Detect language and truncate text if needed
-            let lang = detect_language(&result.file_path);
-            let text = truncate_text(&result.text, MAX_RESULT_TEXT_CHARS);
+    pub(super) fn locate(&mut self, result: &SearchResult) -> LocatedChunk {
+        let indexed = result.start_offset..result.end_offset;
+        let wants_line = self.display.wants_line();
+        let Some(content) = self.content(&result.file_path) else {
+            return LocatedChunk {
+                range: indexed,
+                line: None,
+                note: None,
+            };
+        };
 
-            output.push_str(&format!("```{lang}\n{text}\n```\n\n"));
+        let location = result.locate_in(content);
+        let range = location.range();
+        LocatedChunk {
+            line: range
+                .as_ref()
+                .filter(|_| wants_line)
+                .map(|range| byte_offset_to_line_number(content, range.start)),
+            range: range.unwrap_or(indexed),
+            note: location.note(),
         }
+    }
+}
+
+/// ", session `name`" for results of a collection search, else empty
+pub(super) fn format_session(result: &SearchResult) -> String {
+    match &result.session {
+        Some(session) => format!(", session `{session}`"),
+        None => String::new(),
+    }
+}
 
-        output
+/// Format a result's text as a code block, or say why it has none
+///
+/// Text is truncated to `MAX_RESULT_TEXT_CHARS`.
+pub(super) fn format_snippet(result: &SearchResult) -> String {
+    if let Some(reason) = &result.text_unavailable {
+        return format!("_Text unavailable, {reason} (this session does not store chunk text)._\n");
+    }
+    let lang = detect_language(&result.file_path);
+    let text = truncate_text(&result.text, MAX_RESULT_TEXT_CHARS);
+    format!("```{lang}\n{text}\n```\n")
+}
+
+/// Format the truncation footer for results trimmed to the budget
+///
+/// Results are numbered from `first_rank`. Returns an empty string when
+/// nothing was trimmed.
+fn format_truncation(
+    plan: &BudgetPlan,
+    budget: &ResponseBudget,
+    noun: &str,
+    first_rank: usize,
+) -> String {
+    if !plan.is_truncated() {
+        return String::new();
+    }
+
+    let total = plan.kept() + plan.dropped();
+    let last_rank = first_rank + total - 1;
+    let mut omitted = Vec::new();
+    if plan.compacted() > 0 {
+        omitted.push(format!(
+            "Snippets omitted for {} of {} {noun} (lowest-scoring first)",
+            plan.compacted(),
+            total
+        ));
+    }
+    if plan.dropped() > 0 {
+        omitted.push(format!(
+            "{} lowest-scoring {noun} not shown ({noun} {}-{last_rank})",
+            plan.dropped(),
+            first_rank + plan.kept()
+        ));
+    }
+
+    build_truncation_footer(budget, &omitted, TRUNCATION_HINT)
+}
+
+/// Format the note on hidden chunk overlap duplicates
+///
+/// Returns an empty string when nothing was hidden.
+pub(super) fn format_overlaps_hidden(dropped: usize) -> String {
+    match dropped {
+        0 => String::new(),
+        1 => ", 1 overlapping duplicate hidden".to_string(),
+        n => format!(", {n} overlapping duplicates hidden"),
+    }
+}
+
+/// Format the count of results demoted as very short chunks
+pub(super) fn format_short_chunks_demoted(demoted: usize) -> String {
+    match demoted {
+        0 => String::new(),
+        1 => ", 1 short chunk demoted".to_string(),
+        n => format!(", {n} short chunks demoted"),
+    }
+}
+
+/// Format when a result's file last changed, e.g. ", committed 3 days ago"
+///
+/// Returns an empty string for sessions indexed without change times.
+fn format_change_note(response: &SearchResponse, result: &SearchResult) -> String {
+    match (response.modified_times, result.modified_at) {
+        (Some(source), Some(at)) => format!(", {} {}", source.verb(), format_time_ago(at)),
+        _ => String::new(),
+    }
+}
+
+/// Format the notes on `modified_within`, `section_filter`, exclusions and
+/// `within` restrictions, identifier variants, fuzzy term expansions and an
+/// ignored `explain`
+///
+/// Returns an empty string when the search was not restricted, no term
+/// was expanded and fuzzy matching was disabled.
+pub(super) fn format_expansion_notes(response: &SearchResponse) -> String {
+    [
+        response.recency_summary(),
+        response.section_summary(),
+        response.exclusion_summary(),
+        response.refinement_summary(),
+        response.identifier_summary(),
+        response.fuzzy_summary(),
+        response
+            .explain_skipped
+            .as_ref()
+            .map(|reason| format!("**explain ignored:** {reason}")),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|summary| format!("{summary}\n\n"))
+    .collect()
+}
+
+/// Format the records a data file chunk holds, e.g. ", records 1,204–1,267"
+///
+/// Returns an empty string for chunks not chunked by record.
+fn format_records(result: &SearchResult) -> String {
+    match result.records {
+        Some(records) => format!(", {}", records.describe()),
+        None => String::new(),
+    }
+}
+
+/// Format the "also present at" line for deduplicated files
+///
+/// Duplicates have identical content, so their links share the result's
+/// line. Returns an empty string when the file has no duplicates.
+pub(super) fn format_also_present(
+    paths: &[String],
+    display: &PathDisplay,
+    line: Option<usize>,
+) -> String {
+    if paths.is_empty() {
+        return String::new();
+    }
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| display.markdown(p, &display.path(p), line, None))
+        .collect();
+    format!("**Also present at:** {}\n\n", paths.join(", "))
+}
+
+/// Per-term score breakdown of an `explain` search result
+///
+/// Returns an empty string for results without one.
+pub(super) fn format_explanation(result: &SearchResult) -> String {
+    match &result.explanation {
+        Some(explanation) => format!("**Score:** {}\n\n", explanation.describe()),
+        None => String::new(),
+    }
+}
+
+/// Warning for results from files cut off at `max_chunks_per_file`
+pub(super) fn format_partial_note(result: &SearchResult) -> &'static str {
+    if result.indexed_partially {
+        "**Indexed partially:** the file exceeds the session's max_chunks_per_file; \
+         later parts of it are not searchable (reindex_session with a higher \
+         max_chunks_per_file to index it in full)\n\n"
+    } else {
+        ""
     }
 }
 
@@ -63,6 +574,10 @@ impl McpToolHandler for SearchCodeHandler {
         "search_code"
     }
 
+    fn timeout(&self, args: &Value, default: Duration) -> Option<Duration> {
+        requested_timeout(args, default)
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "search_code".to_string(),
@@ -110,13 +625,23 @@ impl McpToolHandler for SearchCodeHandler {
                     },
                     "session": {
                         "type": "string",
-                        "description": "Session ID to search. Use list_sessions to discover available sessions.",
-                        "pattern": "^[a-zA-Z0-9_-]+$"
+                        "description": "Session ID to search. Use list_sessions to discover available sessions. \
+                                       Required unless collection is given.",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "collection": {
+                        "type": "string",
+                        "description": "Search every session of this collection instead of one session \
+                                       (see create_collection). Results are ranked together by score and \
+                                       labelled with their session; pass that session with their handles. \
+                                       Paths are shown in full and cursor is not supported.",
+                        "pattern": SESSION_ID_PATTERN
                     },
                     "k": {
                         "type": "integer",
                         "description": "Max results. Quick: k=5, Balanced: k=10 (default), Thorough: k=20. \
-                                       Limit configurable via max_k setting (default: 100).",
+                                       Limit configurable via max_k setting (default: 100). \
+                                       Default: the session's search defaults, then 10.",
                         "default": 10,
                         "minimum": 1,
                         "maximum": 500
@@ -127,9 +652,173 @@ impl McpToolHandler for SearchCodeHandler {
                                        characters are escaped. Use for searching code with special syntax \
                                        like 'fmt.Printf(\"%s\")' or 'array[0]'. Default: false.",
                         "default": false
+                    },
+                    "group_by_file": {
+                        "type": "boolean",
+                        "description": "If true, collapse results to one entry per file (best-scoring \
+                                       chunk shown, other matching chunk indexes listed for preview_chunk). \
+                                       Files are ranked by best chunk score and k counts FILES, not chunks. \
+                                       Use when one large file dominates the results. Default: false.",
+                        "default": false
+                    },
+                    "dedupe_overlaps": {
+                        "type": "boolean",
+                        "description": "If true, hide a chunk whose matches all fall in the region it shares \
+                                       with a better-scoring adjacent chunk of the same file, and fill k with \
+                                       the next results. The number hidden is reported. Default: true.",
+                        "default": true
+                    },
+                    "path_boost": {
+                        "type": "number",
+                        "description": "Boost for query terms that also match the file path (e.g. 'auth' \
+                                       ranks auth_handler.rs higher). Files matching only by path are not \
+                                       returned. 0 disables. Default: server search.path_boost (2.0).",
+                        "minimum": 0
+                    },
+                    "fuzziness": {
+                        "type": "integer",
+                        "description": "Tolerate typos: also match indexed terms within this edit distance \
+                                       of each query term (e.g. 'confg' finds 'config'). Terms under 4 \
+                                       characters and the first character are never changed. Exact \
+                                       matches rank higher. The expanded terms are listed. \
+                                       Default: the session's search defaults, then 0 (off).",
+                        "minimum": 0,
+                        "maximum": 2
+                    },
+                    "expand_identifiers": {
+                        "type": "boolean",
+                        "description": "If true, identifier terms also match their other spellings: \
+                                       'getUserId' finds get_user_id, GetUserId and get-user-id, and the \
+                                       other way round. Terms in quoted phrases are left as written. The \
+                                       variants searched are listed. Ignored with literal. Default: true.",
+                        "default": true
+                    },
+                    "bm25_k1": {
+                        "type": "number",
+                        "description": "BM25 term frequency saturation for this search, for ranking \
+                                       experiments: higher values reward repeated terms more. \
+                                       Default: the session's bm25_k1 (1.2).",
+                        "minimum": 0,
+                        "maximum": 3
+                    },
+                    "bm25_b": {
+                        "type": "number",
+                        "description": "BM25 length normalization for this search: 1 fully penalizes \
+                                       long chunks, 0 ignores chunk length. \
+                                       Default: the session's bm25_b (0.75).",
+                        "minimum": 0,
+                        "maximum": 1
+                    },
+                    "penalize_short_chunks": {
+                        "type": "boolean",
+                        "description": "Rank very short chunks (e.g. the last few lines of a file, \
+                                       under 15% of the chunk size) lower, so they do not outrank \
+                                       substantive chunks just for being short. The header counts \
+                                       demoted results. Default: server search.penalize_short_chunks \
+                                       (true)."
+                    },
+                    "within": {
+                        "type": "object",
+                        "description": "Refine a previous search: only chunks in the top k hits of this \
+                                       inner query are searched. Unlike AND-ing both queries, the inner \
+                                       query keeps its own syntax (a quoted phrase stays a phrase) and its \
+                                       own cutoff. The output states both queries and how many inner \
+                                       matches survived.",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Inner query (same syntax as query; literal applies)"
+                            },
+                            "k": {
+                                "type": "integer",
+                                "description": "Number of inner hits to search among (default: 10)",
+                                "minimum": 1,
+                                "maximum": 100
+                            }
+                        },
+                        "required": ["query"]
+                    },
+                    "section_filter": {
+                        "type": "string",
+                        "description": "Only search chunks of Markdown, MDX and reStructuredText \
+                                       files whose heading breadcrumb contains this text \
+                                       (case-insensitive), e.g. 'Storage' or 'Session layout'"
+                    },
+                    "exclude_terms": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "maxItems": MAX_EXCLUSIONS,
+                        "description": "Leave out chunks containing any of these terms, e.g. \
+                                       ['test', 'mock']; a term of several words is matched as a \
+                                       phrase. The output states how many otherwise matching \
+                                       chunks were removed. Excluding every query term is an error."
+                    },
+                    "exclude_paths": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "maxItems": MAX_EXCLUSIONS,
+                        "description": "Leave out files matching any of these globs, e.g. \
+                                       ['tests/**', '**/*_mock.rs']. Relative globs match paths \
+                                       inside the repository."
+                    },
+                    "modified_within": {
+                        "type": "string",
+                        "description": "Only search files last changed within this window ('12h', \
+                                       '30d', '2w') or since this date ('2026-09-01'). Change times \
+                                       are last commit times in git repositories, file modification \
+                                       times otherwise. Each result shows its file's change age."
+                    },
+                    "explain": {
+                        "type": "boolean",
+                        "description": "Debug ranking: show under each of the top 10 results how its \
+                                       score adds up, per matched term (term frequency, IDF, \
+                                       contribution). Default: false.",
+                        "default": false
+                    },
+                    "link_format": {
+                        "type": "string",
+                        "description": "Template for an \"open in editor\" link on each result path, using \
+                                       {path}, {line} and {column} (e.g. 'vscode://file/{path}:{line}', \
+                                       'file://{path}'). 'none' disables links. \
+                                       Default: server output.link_format (none)."
+                    },
+                    "relative_paths": {
+                        "type": "boolean",
+                        "description": "If true, show paths relative to the session's repository path \
+                                       (paths outside it stay absolute). Saves tokens. \
+                                       Default: server output.relative_paths (false)."
+                    },
+                    "output_mode": {
+                        "type": "string",
+                        "description": "'full' (default) shows each result with its code snippet. \
+                                       'locations' shows only a table of file, line, score and a short \
+                                       excerpt around the first match, for 'where is X used' questions; \
+                                       raise k (e.g. 100) to see every location cheaply. \
+                                       Default: the session's search defaults, then 'full'.",
+                        "enum": ["full", "locations"]
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "Pagination cursor from a previous search_code response \
+                                       ('More results available'). Returns the next k results \
+                                       of the same query; the query and search options must \
+                                       match the previous page, k may change. Omit for the \
+                                       first page."
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Absolute path of the repository the session covers (optional). \
+                                       Only used when no sessions exist yet: the error then suggests an \
+                                       index_repository call for this path."
+                    },
+                    "timeout_sec": {
+                        "type": "integer",
+                        "description": "Time limit for this call in seconds (default: \
+                                       limits.tool_timeout_sec, 60)",
+                        "minimum": 1
                     }
                 },
-                "required": ["query", "session"]
+                "required": ["query"]
             }),
         }
     }
@@ -138,15 +827,51 @@ impl McpToolHandler for SearchCodeHandler {
         #[derive(Deserialize)]
         struct SearchArgs {
             query: String,
-            session: String,
-            #[serde(default = "default_k")]
-            k: usize,
+            session: Option<String>,
+            collection: Option<String>,
+            k: Option<usize>,
             #[serde(default)]
             literal: bool,
+            #[serde(default)]
+            group_by_file: bool,
+            #[serde(default = "default_dedupe_overlaps")]
+            dedupe_overlaps: bool,
+            path_boost: Option<f32>,
+            fuzziness: Option<u8>,
+            #[serde(default = "default_expand_identifiers")]
+            expand_identifiers: bool,
+            bm25_k1: Option<f32>,
+            bm25_b: Option<f32>,
+            penalize_short_chunks: Option<bool>,
+            within: Option<WithinArgs>,
+            modified_within: Option<String>,
+            section_filter: Option<String>,
+            #[serde(default)]
+            exclude_terms: Vec<String>,
+            #[serde(default)]
+            exclude_paths: Vec<String>,
+            #[serde(default)]
+            explain: bool,
+            link_format: Option<String>,
+            relative_paths: Option<bool>,
+            output_mode: Option<String>,
+            cursor: Option<String>,
+        }
+        #[derive(Deserialize)]
+        struct WithinArgs {
+            query: String,
+            #[serde(default = "default_k")]
+            k: usize,
         }
         fn default_k() -> usize {
             10
         }
+        fn default_dedupe_overlaps() -> bool {
+            true
+        }
+        fn default_expand_identifiers() -> bool {
+            true
+        }
 
         // Parse and validate arguments
         let args: SearchArgs =
@@ -156,39 +881,230 @@ impl McpToolHandler for SearchCodeHandler {
             return Err(McpError::InvalidParams("Query cannot be empty".to_string()));
         }
 
-        if args.k > 100 {
-            return Err(McpError::InvalidParams("k cannot exceed 100".to_string()));
+        let target = SearchTarget::from_args(args.session.clone(), args.collection.clone())?;
+        if target.collection().is_some() && args.cursor.is_some() {
+            return Err(McpError::InvalidParams(
+                "cursor cannot be used with collection; raise k instead".to_string(),
+            ));
+        }
+
+        if args.k.is_some_and(|k| k > MAX_K) {
+            return Err(McpError::InvalidParams(format!("k cannot exceed {MAX_K}")));
+        }
+
+        if let Some(within) = &args.within {
+            if within.query.trim().is_empty() {
+                return Err(McpError::InvalidParams(
+                    "within.query cannot be empty".to_string(),
+                ));
+            }
+            if !(1..=MAX_K).contains(&within.k) {
+                return Err(McpError::InvalidParams(format!(
+                    "within.k must be between 1 and {MAX_K}"
+                )));
+            }
         }
 
+        // Explicit options win over the session's defaults
+        let session_defaults = target
+            .session()
+            .and_then(|session| self.services.storage.get_session_metadata(session).ok())
+            .and_then(|metadata| metadata.search_defaults)
+            .unwrap_or_default();
+        let output_mode = match args.output_mode.as_deref() {
+            Some(mode) => OutputMode::parse(mode).map_err(McpError::InvalidParams)?,
+            None => session_defaults.output_mode.unwrap_or_default(),
+        };
+        let locations_only = output_mode == OutputMode::Locations;
+
         // Skip field validation in literal mode (all colons are escaped anyway)
         if !args.literal {
             validate_query_fields(&args.query).map_err(McpError::from)?;
+            if let Some(within) = &args.within {
+                validate_query_fields(&within.query).map_err(McpError::from)?;
+            }
         }
 
         // Preprocess query for Tantivy compatibility
         let processed_query = preprocess_query(&args.query, args.literal);
 
+        let display = match &target {
+            SearchTarget::Session(session) => path_display(
+                &self.services,
+                session,
+                args.link_format.as_deref(),
+                args.relative_paths,
+            )?,
+            SearchTarget::Collection(_) => {
+                collection_display(&self.services, args.link_format.as_deref())?
+            }
+        };
+
         // Create Shebe search request
-        let request = SearchRequest {
+        let mut request = SearchRequest {
             query: processed_query,
-            session: args.session,
-            k: Some(args.k),
+            session: target.session().unwrap_or_default().to_string(),
+            k: args.k,
+            group_by_file: args.group_by_file,
+            dedupe_overlaps: args.dedupe_overlaps,
+            path_boost: args.path_boost,
+            fuzziness: args.fuzziness,
+            expand_identifiers: args.expand_identifiers && !args.literal,
+            bm25_k1: args.bm25_k1,
+            bm25_b: args.bm25_b,
+            within: args.within.as_ref().map(|within| SearchWithin {
+                query: preprocess_query(&within.query, args.literal),
+                k: Some(within.k),
+            }),
+            penalize_short_chunks: args.penalize_short_chunks,
+            modified_within: args.modified_within.clone(),
+            section_filter: args.section_filter.clone(),
+            exclude_terms: args.exclude_terms.clone(),
+            exclude_paths: args.exclude_paths.clone(),
+            explain: args.explain,
         };
 
-        // Execute search via Shebe service (synchronous)
-        let response = self
-            .services
-            .search
-            .search(request)
-            .map_err(McpError::from)?;
+        // Resolve the page: results after the cursor's rank, k at a time
+        let page_k = args
+            .k
+            .or(session_defaults.k)
+            .unwrap_or(self.services.config.current().search.default_k);
+        let offset = match &args.cursor {
+            Some(cursor) => self.cursor_offset(cursor, &request)?,
+            None => 0,
+        };
+        let max_k = self.services.config.current().search.max_k;
+        if offset > 0 && offset + page_k > max_k {
+            return Err(McpError::InvalidParams(format!(
+                "Results {}-{} are beyond the search depth limit (search.max_k = {max_k}). \
+                 Lower k, or narrow the query to bring later matches forward.",
+                offset + 1,
+                offset + page_k
+            )));
+        }
+        // One extra result shows whether another page exists
+        request.k = Some((offset + page_k + 1).min(max_k));
+
+        // Execute search via Shebe service (off the async runtime)
+        let services = Arc::clone(&self.services);
+        let search_request = request.clone();
+        let collection = target.collection().map(str::to_string);
+        let mut response = run_blocking(move || match collection {
+            Some(collection) => services.search_collection(&collection, search_request),
+            None => services.search.search(search_request),
+        })
+        .await?;
+        let has_more = page_slice(&mut response, offset, page_k);
+
+        let first_handle = label_hits(
+            &self.services,
+            target.session(),
+            &args.query,
+            &response.results,
+        );
 
         // Format results as Markdown
-        let text = self.format_results(&response);
+        let budget = ResponseBudget::default();
+        let first_rank = offset + 1;
+        let (mut text, shown) = time_phase("format", || {
+            if locations_only {
+                self.format_locations(
+                    &response,
+                    first_handle,
+                    first_rank,
+                    &budget,
+                    &display,
+                    &args.query,
+                )
+            } else if args.group_by_file {
+                self.format_grouped_results(&response, first_handle, first_rank, &budget, &display)
+            } else {
+                self.format_results(&response, first_handle, first_rank, &budget, &display)
+            }
+        });
+
+        // Results trimmed by the budget come first on the next page
+        if has_more || shown < response.count {
+            match target.session() {
+                Some(session) => {
+                    text.push_str(&self.next_page_note(session, &request, offset + shown)?)
+                }
+                None => text.push_str(
+                    "\nNOTE: More results available. \
+                     Raise k to see them (collection searches are not paged).\n",
+                ),
+            }
+        }
 
         Ok(text_content(text))
     }
 }
 
+impl SearchCodeHandler {
+    /// Results already returned before the page `cursor` points to
+    fn cursor_offset(&self, cursor: &str, request: &SearchRequest) -> Result<usize, McpError> {
+        let cursor = SearchCursor::decode(cursor).map_err(McpError::InvalidParams)?;
+        let metadata = self
+            .services
+            .storage
+            .get_session_metadata(&request.session)
+            .map_err(McpError::from)?;
+        cursor
+            .verify(request, &metadata)
+            .map_err(McpError::InvalidParams)?;
+        Ok(cursor.last_rank)
+    }
+
+    /// Footer with the cursor for the results after rank `last_rank`
+    fn next_page_note(
+        &self,
+        session: &str,
+        request: &SearchRequest,
+        last_rank: usize,
+    ) -> Result<String, McpError> {
+        let metadata = self
+            .services
+            .storage
+            .get_session_metadata(session)
+            .map_err(McpError::from)?;
+        let cursor = SearchCursor {
+            last_rank,
+            query: query_fingerprint(request),
+            fingerprint: session_fingerprint(&metadata),
+        };
+        Ok(format!(
+            "\nNOTE: More results available. \
+             Use cursor=\"{}\" to fetch next page.\n",
+            cursor.encode()
+        ))
+    }
+}
+
+/// Keep the `page_k` results after the first `offset` of `response`
+///
+/// Grouped responses are paged by file. Returns whether results follow
+/// the page.
+fn page_slice(response: &mut SearchResponse, offset: usize, page_k: usize) -> bool {
+    let has_more;
+    if response.groups.is_empty() {
+        response.results.drain(..offset.min(response.results.len()));
+        has_more = response.results.len() > page_k;
+        response.results.truncate(page_k);
+    } else {
+        response.groups.drain(..offset.min(response.groups.len()));
+        has_more = response.groups.len() > page_k;
+        response.groups.truncate(page_k);
+        response.results = response.groups.iter().map(|g| g.best().clone()).collect();
+    }
+    response.count = response.results.len();
+    response.short_chunks_demoted = response
+        .results
+        .iter()
+        .filter(|r| r.short_chunk_demoted)
+        .count();
+    has_more
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +1142,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 39,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
             Chunk {
                 text: "fn helper() { /* helper function */ }".to_string(),
@@ -233,6 +1151,8 @@ mod tests {
                 start_offset: 0,
                 end_offset: 37,
                 chunk_index: 0,
+                records: None,
+                section: None,
             },
         ];
 
@@ -351,14 +1271,42 @@ mod tests {
                 text: "fn test() {}".to_string(),
                 file_path: "test.rs".to_string(),
                 chunk_index: 0,
+                session: None,
                 start_offset: 0,
                 end_offset: 12,
+                also_present_at: vec![],
+                matched_in_path: false,
+                short_chunk_demoted: false,
+                modified_at: None,
+                text_unavailable: None,
+                indexed_partially: false,
+                explanation: None,
+                records: None,
+                section: None,
             }],
             count: 1,
             duration_ms: 42,
+            groups: vec![],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
         };
 
-        let output = handler.format_results(&response);
+        let (output, _) = handler.format_results(
+            &response,
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
 
         assert!(output.contains("Found 1 results"));
         assert!(output.contains("42ms"));
@@ -368,6 +1316,97 @@ mod tests {
         assert!(output.contains("test.rs"));
         assert!(output.contains("```rust"));
         assert!(output.contains("fn test() {}"));
+        assert!(!output.contains("Also present at"));
+        assert!(!output.contains("records"));
+
+        // Chunks of data files name their records
+        let mut response = response;
+        response.results[0].records = Some(crate::core::types::RecordRange {
+            first: 1204,
+            last: 1267,
+        });
+        let (output, _) = handler.format_results(
+            &response,
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
+        assert!(
+            output.contains("(chunk 0, records 1,204–1,267, bytes 0-12)"),
+            "{output}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_results_lists_duplicate_paths() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let response = crate::core::types::SearchResponse {
+            query: "helper".to_string(),
+            results: vec![crate::core::types::SearchResult {
+                score: 3.2,
+                text: "pub fn helper() {}".to_string(),
+                file_path: "src/util.rs".to_string(),
+                chunk_index: 0,
+                session: None,
+                start_offset: 0,
+                end_offset: 18,
+                also_present_at: vec!["vendor/util.rs".to_string(), "build/util.rs".to_string()],
+                matched_in_path: false,
+                short_chunk_demoted: false,
+                modified_at: None,
+                text_unavailable: None,
+                indexed_partially: false,
+                explanation: None,
+                records: None,
+                section: None,
+            }],
+            count: 1,
+            duration_ms: 3,
+            groups: vec![],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        };
+
+        let (output, _) = handler.format_results(
+            &response,
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
+
+        assert!(output.contains("**Also present at:** `vendor/util.rs`, `build/util.rs`"));
+    }
+
+    #[test]
+    fn test_format_overlaps_hidden() {
+        assert_eq!(format_overlaps_hidden(0), "");
+        assert_eq!(
+            format_overlaps_hidden(1),
+            ", 1 overlapping duplicate hidden"
+        );
+        assert_eq!(
+            format_overlaps_hidden(3),
+            ", 3 overlapping duplicates hidden"
+        );
+    }
+
+    #[test]
+    fn test_format_short_chunks_demoted() {
+        assert_eq!(format_short_chunks_demoted(0), "");
+        assert_eq!(format_short_chunks_demoted(1), ", 1 short chunk demoted");
+        assert_eq!(format_short_chunks_demoted(2), ", 2 short chunks demoted");
     }
 
     #[tokio::test]
@@ -379,14 +1418,141 @@ mod tests {
             results: vec![],
             count: 0,
             duration_ms: 10,
+            groups: vec![],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
         };
 
-        let output = handler.format_results(&response);
+        let (output, _) = handler.format_results(
+            &response,
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
 
         assert!(output.contains("Found 0 results"));
         assert!(output.contains("No results found"));
     }
 
+    /// Response with `count` results of 2000-char snippets, best first
+    fn oversized_response(count: usize) -> crate::core::types::SearchResponse {
+        let results: Vec<_> = (0..count)
+            .map(|i| crate::core::types::SearchResult {
+                score: 100.0 - i as f32,
+                text: "x".repeat(MAX_RESULT_TEXT_CHARS),
+                file_path: format!("src/file_{i}.rs"),
+                chunk_index: 0,
+                session: None,
+                start_offset: 0,
+                end_offset: MAX_RESULT_TEXT_CHARS,
+                also_present_at: vec![],
+                matched_in_path: false,
+                short_chunk_demoted: false,
+                modified_at: None,
+                text_unavailable: None,
+                indexed_partially: false,
+                explanation: None,
+                records: None,
+                section: None,
+            })
+            .collect();
+
+        crate::core::types::SearchResponse {
+            query: "x".to_string(),
+            count: results.len(),
+            results,
+            duration_ms: 1,
+            groups: vec![],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_format_results_drops_snippets_to_fit_budget() {
+        let (handler, _temp) = setup_test_handler().await;
+        let budget = ResponseBudget::default();
+
+        let (output, _) = handler.format_results(
+            &oversized_response(100),
+            1,
+            1,
+            &budget,
+            &PathDisplay::default(),
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        let omitted = output.matches(SNIPPET_OMITTED).count();
+        assert!(omitted > 0);
+        assert_eq!(output.matches("## Result ").count(), 100);
+        assert!(output.contains(&format!(
+            "Snippets omitted for {omitted} of 100 results (lowest-scoring first)"
+        )));
+        assert!(!output.contains("not shown"));
+        // Best results keep their snippets
+        assert!(output.contains("## Result 1 (score: 100.00, handle r1)\n**File:** `src/file_0.rs` (chunk 0, bytes 0-2000)\n\n```rust"));
+    }
+
+    #[tokio::test]
+    async fn test_format_results_drops_lowest_scoring_results() {
+        let (handler, _temp) = setup_test_handler().await;
+        let budget = ResponseBudget::new(1000);
+
+        let (output, _) = handler.format_results(
+            &oversized_response(100),
+            1,
+            1,
+            &budget,
+            &PathDisplay::default(),
+        );
+
+        assert!(estimate_tokens(&output) <= budget.tokens());
+        let shown = output.matches("## Result ").count();
+        assert!(shown < 100);
+        assert_eq!(output.matches(SNIPPET_OMITTED).count(), shown);
+        assert!(output.contains(&format!(
+            "{} lowest-scoring results not shown (results {}-100)",
+            100 - shown,
+            shown + 1
+        )));
+        assert!(output.contains(TRUNCATION_HINT));
+    }
+
+    #[tokio::test]
+    async fn test_format_results_within_budget_has_no_footer() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let (output, _) = handler.format_results(
+            &oversized_response(3),
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
+
+        assert!(!output.contains("RESPONSE TRUNCATED"));
+        assert!(!output.contains(SNIPPET_OMITTED));
+    }
+
     #[tokio::test]
     async fn test_search_code_literal_mode() {
         let (handler, _temp) = setup_test_handler().await;
@@ -435,4 +1601,365 @@ mod tests {
             err
         );
     }
+
+    #[tokio::test]
+    async fn test_search_code_group_by_file() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "fn",
+            "session": "test-session",
+            "group_by_file": true
+        });
+
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(text.contains("grouped by file"));
+        assert!(text.contains("## File 1:"));
+    }
+
+    #[tokio::test]
+    async fn test_search_code_fuzzy_lists_expanded_terms() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "helpr",
+            "session": "test-session",
+            "fuzziness": 1
+        });
+
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(text.contains("Fuzzy matching (distance 1): helpr -> helper"));
+        assert!(text.contains("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_search_code_within_refines_results() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "fn",
+            "session": "test-session",
+            "within": {"query": "\"helper function\""}
+        });
+
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(text.contains(
+            "Refined within '\"helper function\"': 1 of 1 inner matches also match 'fn'"
+        ));
+        assert!(text.contains("lib.rs"));
+        assert!(!text.contains("main.rs"));
+
+        let args = json!({
+            "query": "fn",
+            "session": "test-session",
+            "within": {"query": "helper", "k": 0}
+        });
+        let err = handler.execute(args).await.unwrap_err();
+        assert!(matches!(err, McpError::InvalidParams(ref m) if m.contains("within.k")));
+    }
+
+    #[tokio::test]
+    async fn test_search_code_explain_shows_term_breakdown() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "helper",
+            "session": "test-session",
+            "explain": true
+        });
+
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(text.contains("**Score:** term 'helper': tf="), "{text}");
+        assert!(text.contains("contribution"));
+    }
+
+    #[tokio::test]
+    async fn test_search_code_lists_identifier_variants() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "helperFunction",
+            "session": "test-session"
+        });
+
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(text.contains(
+            "Identifier variants: helperFunction -> helper_function, HelperFunction, \
+             helper-function, HELPER_FUNCTION"
+        ));
+        assert!(text.contains("lib.rs"));
+
+        // Literal searches match only the spelling given
+        let args = json!({
+            "query": "helperFunction",
+            "session": "test-session",
+            "literal": true
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text.clone(),
+        };
+        assert!(!text.contains("Identifier variants"));
+        assert!(!text.contains("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_format_grouped_results_lists_more_chunks() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let hit = |chunk_index: usize, score: f32| crate::core::types::SearchResult {
+            score,
+            text: format!("chunk {chunk_index}"),
+            file_path: "big.rs".to_string(),
+            chunk_index,
+            session: None,
+            start_offset: 0,
+            end_offset: 7,
+            also_present_at: vec![],
+            matched_in_path: false,
+            short_chunk_demoted: false,
+            modified_at: None,
+            text_unavailable: None,
+            indexed_partially: false,
+            explanation: None,
+            records: None,
+            section: None,
+        };
+        let group = crate::core::types::FileGroup {
+            file_path: "big.rs".to_string(),
+            best_score: 9.5,
+            hits: vec![hit(3, 9.5), hit(0, 4.0), hit(12, 2.0)],
+        };
+        let response = crate::core::types::SearchResponse {
+            query: "test".to_string(),
+            results: vec![group.best().clone()],
+            count: 1,
+            duration_ms: 5,
+            groups: vec![group],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        };
+
+        let (output, _) = handler.format_grouped_results(
+            &response,
+            1,
+            1,
+            &ResponseBudget::default(),
+            &PathDisplay::default(),
+        );
+
+        assert!(output.contains("Found 1 files"));
+        assert!(output.contains("## File 1: `big.rs` (best score: 9.50, handle r1)"));
+        assert!(output.contains("**Best chunk:** chunk 3"));
+        assert!(output.contains("+2 more chunks: 0, 12"));
+        assert!(output.contains("preview_chunk"));
+    }
+
+    /// Session over a real file whose `needle` chunk starts on line 3
+    async fn create_session_on_disk(services: &Arc<Services>, repo: &std::path::Path) -> String {
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        let content = "// header\n\nfn needle() {}\n";
+        std::fs::write(repo.join("src/main.rs"), content).unwrap();
+        let file_path = repo.join("src/main.rs");
+
+        let mut index = services
+            .storage
+            .create_session("disk-session", repo.to_path_buf(), SessionConfig::default())
+            .unwrap();
+        let chunks = vec![Chunk {
+            text: "fn needle() {}".to_string(),
+            file_path: file_path.clone(),
+            start_offset: 11,
+            end_offset: 25,
+            chunk_index: 0,
+            records: None,
+            section: None,
+        }];
+        index.add_chunks(&chunks, "disk-session").unwrap();
+        index.commit().unwrap();
+
+        file_path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_search_code_relative_paths_with_links() {
+        let (handler, temp) = setup_test_handler().await;
+        let repo = temp.path().join("repo");
+        let file_path = create_session_on_disk(&handler.services, &repo).await;
+
+        let args = json!({
+            "query": "needle",
+            "session": "disk-session",
+            "link_format": "vscode://file/{path}:{line}",
+            "relative_paths": true
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+
+        assert!(
+            text.contains(&format!(
+                "**File:** [`src/main.rs`](vscode://file/{file_path}:3)"
+            )),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_code_corrects_offsets_after_edit() {
+        let (handler, temp) = setup_test_handler().await;
+        let repo = temp.path().join("repo");
+        let file_path = create_session_on_disk(&handler.services, &repo).await;
+        std::fs::write(&file_path, "// header\n// new line\n\nfn needle() {}\n").unwrap();
+
+        let args = json!({
+            "query": "needle",
+            "session": "disk-session",
+            "link_format": "vscode://file/{path}:{line}"
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+
+        assert!(
+            text.contains(&format!("(vscode://file/{file_path}:4)")),
+            "{text}"
+        );
+        assert!(text.contains("bytes 23-37"), "{text}");
+        assert!(
+            text.contains("_File changed since indexing: chunk moved +12 bytes"),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_code_uses_output_config_defaults() {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp.path().join("index");
+        config.output.relative_paths = true;
+        config.output.link_format = Some("file://{path}".to_string());
+        let handler = SearchCodeHandler::new(Arc::new(Services::new(config)));
+        let file_path = create_session_on_disk(&handler.services, &temp.path().join("repo")).await;
+
+        // Per-request overrides win over the server settings
+        let args = json!({
+            "query": "needle",
+            "session": "disk-session",
+            "link_format": "none"
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(text.contains("**File:** `src/main.rs`"), "{text}");
+
+        let args = json!({
+            "query": "needle",
+            "session": "disk-session",
+            "relative_paths": false
+        });
+        let result = handler.execute(args).await.unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+        assert!(
+            text.contains(&format!("**File:** [`{file_path}`](file://{file_path})")),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_code_invalid_link_format() {
+        let (handler, _temp) = setup_test_handler().await;
+        create_test_session(&handler.services, "test-session").await;
+
+        let args = json!({
+            "query": "async",
+            "session": "test-session",
+            "link_format": "vscode://file/{file}"
+        });
+        let result = handler.execute(args).await;
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_format_results_relative_duplicate_paths() {
+        let (handler, _temp) = setup_test_handler().await;
+
+        let response = crate::core::types::SearchResponse {
+            query: "helper".to_string(),
+            results: vec![crate::core::types::SearchResult {
+                score: 3.2,
+                text: "pub fn helper() {}".to_string(),
+                file_path: "/repo/src/util.rs".to_string(),
+                chunk_index: 0,
+                session: None,
+                start_offset: 0,
+                end_offset: 18,
+                also_present_at: vec!["/vendored/util.rs".to_string()],
+                matched_in_path: false,
+                short_chunk_demoted: false,
+                modified_at: None,
+                text_unavailable: None,
+                indexed_partially: false,
+                explanation: None,
+                records: None,
+                section: None,
+            }],
+            count: 1,
+            duration_ms: 3,
+            groups: vec![],
+            overlap_duplicates_dropped: 0,
+            short_chunks_demoted: 0,
+            fuzziness: 0,
+            fuzzy_expansions: Vec::new(),
+            identifier_expansions: Vec::new(),
+            refinement: None,
+            modified_times: None,
+            recency: None,
+            section_filter: None,
+            exclusions: None,
+            explain_skipped: None,
+        };
+        let display = PathDisplay::new(Some(std::path::Path::new("/repo")), true, None);
+
+        let (output, _) =
+            handler.format_results(&response, 1, 1, &ResponseBudget::default(), &display);
+
+        assert!(output.contains("**File:** `src/util.rs` (chunk 0"));
+        // Duplicates outside the repository stay absolute
+        assert!(output.contains("**Also present at:** `/vendored/util.rs`"));
+    }
 }
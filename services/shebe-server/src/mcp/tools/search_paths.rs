@@ -0,0 +1,175 @@
+//! Search paths tool handler
+//!
+//! Ranks the indexed files of a session by how well a few words match
+//! their paths ("user sevice handler"), tolerating typos, without
+//! reading or scoring file contents.
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::run_blocking;
+use crate::core::path_search::{search_paths, PathHit, DEFAULT_PATH_SEARCH_K, MAX_PATH_SEARCH_K};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct SearchPathsHandler {
+    services: Arc<Services>,
+}
+
+impl SearchPathsHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format matching paths as a ranked list with matched words in bold
+    fn format_hits(&self, session: &str, query: &str, hits: &[PathHit]) -> String {
+        let mut output = format!(
+            "**Session:** `{session}`\n\
+             **Query:** `{query}`\n"
+        );
+
+        if hits.is_empty() {
+            output.push_str(
+                "\nNo file paths match. Try fewer or shorter words, find_file with a glob \
+                 pattern, or search_code to search file contents.",
+            );
+            return output;
+        }
+
+        output.push_str(&format!(
+            "**Found:** {} files by path (best first)\n\n",
+            hits.len()
+        ));
+        for (i, hit) in hits.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. {} ({})\n",
+                i + 1,
+                hit.highlighted("**", "**"),
+                hit.match_summary()
+            ));
+        }
+        output.push_str(
+            "\nOnly paths were matched, relative to the repository root. Use read_file to \
+             open a file or search_code to search contents.",
+        );
+
+        output
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for SearchPathsHandler {
+    fn name(&self) -> &str {
+        "search_paths"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "search_paths".to_string(),
+            description: "Find files whose path roughly matches a few words (e.g. 'user \
+                         sevice handler' finds src/user/service_handler.rs). Words match path \
+                         segments case-insensitively by prefix, small typos or letters in \
+                         order; files matching more words rank first, file name matches above \
+                         directory matches, shallow paths above deep ones. Only the indexed \
+                         file list is read, so it is fast on any session. Use find_file for \
+                         exact glob/regex patterns, locate to also search contents."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to search",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Words of the path to find",
+                        "minLength": 1
+                    },
+                    "k": {
+                        "type": "integer",
+                        "description": "Files returned (default: 20, max: 100)",
+                        "default": DEFAULT_PATH_SEARCH_K,
+                        "minimum": 1,
+                        "maximum": MAX_PATH_SEARCH_K
+                    }
+                },
+                "required": ["session", "query"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct SearchPathsArgs {
+            session: String,
+            query: String,
+            #[serde(default = "default_k")]
+            k: usize,
+        }
+        fn default_k() -> usize {
+            DEFAULT_PATH_SEARCH_K
+        }
+
+        let args: SearchPathsArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        if args.query.trim().is_empty() {
+            return Err(McpError::InvalidParams("query cannot be empty".to_string()));
+        }
+        if !(1..=MAX_PATH_SEARCH_K).contains(&args.k) {
+            return Err(McpError::InvalidParams(format!(
+                "k must be between 1 and {MAX_PATH_SEARCH_K}"
+            )));
+        }
+
+        // Listing the indexed files reads the index (off the async runtime)
+        let services = Arc::clone(&self.services);
+        let (session, query) = (args.session.clone(), args.query.clone());
+        let hits =
+            run_blocking(move || search_paths(&services.storage, &session, &query, args.k)).await?;
+
+        Ok(text_content(self.format_hits(
+            &args.session,
+            &args.query,
+            &hits,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (SearchPathsHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let handler = SearchPathsHandler::new(Arc::new(Services::new(config)));
+        (handler, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_search_paths_rejects_bad_arguments() {
+        let (handler, _temp) = setup_test_handler();
+
+        for args in [
+            json!({"session": "s", "query": "  "}),
+            json!({"session": "s", "query": "user", "k": 0}),
+            json!({"session": "s", "query": "user", "k": 101}),
+        ] {
+            let result = handler.execute(args.clone()).await;
+            assert!(
+                matches!(result, Err(McpError::InvalidParams(_))),
+                "{args}: {result:?}"
+            );
+        }
+    }
+}
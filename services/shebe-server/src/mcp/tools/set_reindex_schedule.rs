@@ -0,0 +1,226 @@
+//! Set reindex schedule tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::{format_schedule, format_scheduled_run};
+use crate::core::error::ShebeError;
+use crate::core::schedule::{schedule_status, Schedule};
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct SetReindexScheduleHandler {
+    services: Arc<Services>,
+}
+
+impl SetReindexScheduleHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the session's schedule after the change
+    fn format_result(&self, session: &str) -> Result<String, McpError> {
+        let metadata = self.services.storage.get_session_metadata(session)?;
+        let mut output = format!("**Reindex schedule updated:** `{session}`\n\n");
+
+        match schedule_status(&metadata, &self.services.config.current().schedule) {
+            Some(status) => {
+                output.push_str(&format!("**Schedule:** {}\n", format_schedule(&status)));
+                if let Some(run) = &status.last_run {
+                    output.push_str(&format!(
+                        "**Last scheduled run:** {}\n",
+                        format_scheduled_run(run)
+                    ));
+                }
+                if !status.scheduler_enabled && !status.schedule.is_off() {
+                    output.push_str(
+                        "\nThe scheduler is disabled. Set `enabled = true` in the \
+                         `[schedule]` section of the server configuration (or \
+                         SHEBE_SCHEDULE_ENABLED=true) and restart the server to run it.\n",
+                    );
+                }
+            }
+            None => output.push_str("**Schedule:** none (re-indexed on request only)\n"),
+        }
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for SetReindexScheduleHandler {
+    fn name(&self) -> &str {
+        "set_reindex_schedule"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "set_reindex_schedule".to_string(),
+            description: "Re-index a session in the background on a schedule, with its stored \
+                         configuration. schedule is an interval ('30m', '6h', '2d'), a daily UTC \
+                         time ('daily@03:00') or 'off'. Omit schedule to fall back to the \
+                         server's [schedule.sessions] configuration. The schedule is saved with \
+                         the session. Runs only happen while the server's scheduler is enabled; \
+                         get_session_info and list_sessions show the next and last run."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to schedule",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "schedule": {
+                        "type": "string",
+                        "description": "Interval ('30m', '6h', '2d'), 'daily@HH:MM' (UTC) or \
+                                       'off'. Omit to use the configured schedule."
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct ScheduleArgs {
+            session: String,
+            #[serde(default)]
+            schedule: Option<String>,
+        }
+
+        let args: ScheduleArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let schedule = args
+            .schedule
+            .as_deref()
+            .map(Schedule::parse)
+            .transpose()
+            .map_err(McpError::InvalidParams)?;
+
+        self.services
+            .storage
+            .set_reindex_schedule(&args.session, schedule)
+            .map_err(|e| match e {
+                ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
+                    "Session '{}' not found. Use list_sessions to see available sessions.",
+                    args.session
+                )),
+                _ => McpError::from(e),
+            })?;
+
+        Ok(text_content(self.format_result(&args.session)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::mcp::protocol::ContentBlock;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler(config: Config) -> (SetReindexScheduleHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = config;
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        services
+            .storage
+            .create_session(
+                "backend",
+                PathBuf::from("/test/repo"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        (SetReindexScheduleHandler::new(services), temp_dir)
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_schedule_persists() {
+        let (handler, _temp) = setup_test_handler(Config::default());
+
+        let result = handler
+            .execute(json!({"session": "backend", "schedule": "360m"}))
+            .await
+            .unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("`6h` (from set_reindex_schedule)"));
+        assert!(text.contains("scheduler disabled"));
+        let metadata = handler
+            .services
+            .storage
+            .get_session_metadata("backend")
+            .unwrap();
+        assert_eq!(
+            metadata.reindex_schedule,
+            Some(Schedule::Every { minutes: 360 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clear_schedule_falls_back_to_config() {
+        let mut config = Config::default();
+        config.schedule.enabled = true;
+        config
+            .schedule
+            .sessions
+            .insert("backend".to_string(), "daily@03:00".to_string());
+        let (handler, _temp) = setup_test_handler(config);
+
+        handler
+            .execute(json!({"session": "backend", "schedule": "off"}))
+            .await
+            .unwrap();
+        let result = handler
+            .execute(json!({"session": "backend"}))
+            .await
+            .unwrap();
+        let text = extract_text(&result);
+
+        assert!(text.contains("`daily@03:00` (from config), next run"));
+        assert!(!text.contains("disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_schedule() {
+        let (handler, _temp) = setup_test_handler(Config::default());
+
+        let result = handler
+            .execute(json!({"session": "backend", "schedule": "hourly"}))
+            .await;
+
+        assert!(matches!(result, Err(McpError::InvalidParams(msg)) if msg.contains("hourly")));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session() {
+        let (handler, _temp) = setup_test_handler(Config::default());
+
+        let result = handler
+            .execute(json!({"session": "missing", "schedule": "6h"}))
+            .await;
+
+        assert!(matches!(result, Err(McpError::InvalidRequest(_))));
+    }
+}
@@ -0,0 +1,265 @@
+//! Set session defaults tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use crate::core::error::ShebeError;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::core::types::{OutputMode, SessionSearchDefaults};
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct SetSessionDefaultsHandler {
+    services: Arc<Services>,
+}
+
+impl SetSessionDefaultsHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+
+    /// Format the session's defaults after the change
+    fn format_result(session: &str, defaults: &SessionSearchDefaults) -> String {
+        let mut output = format!("**Search defaults updated:** `{session}`\n\n");
+        if defaults.is_empty() {
+            output.push_str("**Search defaults:** none (server defaults apply)\n");
+        } else {
+            output.push_str(&format!("**Search defaults:** {}\n", defaults.summary()));
+            output.push_str("\nValues passed with a search still take precedence.\n");
+        }
+        output
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for SetSessionDefaultsHandler {
+    fn name(&self) -> &str {
+        "set_session_defaults"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "set_session_defaults".to_string(),
+            description: "Set the search options a session uses when a search_code call leaves \
+                         them out, e.g. k=20 with fuzziness=1 for a docs session. Options not \
+                         given keep their current default; clear=true removes all defaults \
+                         first. Values passed with a search always win. Only the session \
+                         metadata is updated (no re-indexing); get_session_info shows the \
+                         defaults."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to update",
+                        "pattern": SESSION_ID_PATTERN
+                    },
+                    "k": {
+                        "type": "integer",
+                        "description": "Default max results",
+                        "minimum": 1,
+                        "maximum": 100
+                    },
+                    "fuzziness": {
+                        "type": "integer",
+                        "description": "Default edit distance for typo-tolerant matching (0 = off)",
+                        "minimum": 0,
+                        "maximum": 2
+                    },
+                    "path_boost": {
+                        "type": "number",
+                        "description": "Default boost for query matches in the file path (0 disables)",
+                        "minimum": 0
+                    },
+                    "output_mode": {
+                        "type": "string",
+                        "description": "Default search_code output: 'full' or 'locations'",
+                        "enum": ["full", "locations"]
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Remove all defaults before applying the options given \
+                                       (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["session"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct DefaultsArgs {
+            session: String,
+            k: Option<usize>,
+            fuzziness: Option<u8>,
+            path_boost: Option<f32>,
+            output_mode: Option<String>,
+            #[serde(default)]
+            clear: bool,
+        }
+
+        let args: DefaultsArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+
+        let update = SessionSearchDefaults {
+            k: args.k,
+            fuzziness: args.fuzziness,
+            path_boost: args.path_boost,
+            output_mode: args
+                .output_mode
+                .as_deref()
+                .map(OutputMode::parse)
+                .transpose()
+                .map_err(McpError::InvalidParams)?,
+        };
+        update.validate().map_err(McpError::InvalidParams)?;
+
+        let not_found = |e: ShebeError| match e {
+            ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
+                "Session '{}' not found. Use list_sessions to see available sessions.",
+                args.session
+            )),
+            _ => McpError::from(e),
+        };
+        let current = self
+            .services
+            .storage
+            .get_session_metadata(&args.session)
+            .map_err(not_found)?
+            .search_defaults
+            .filter(|_| !args.clear)
+            .unwrap_or_default();
+        let defaults = current.merged(update);
+
+        self.services
+            .storage
+            .set_search_defaults(&args.session, (!defaults.is_empty()).then_some(defaults))
+            .map_err(not_found)?;
+
+        Ok(text_content(Self::format_result(&args.session, &defaults)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::core::storage::SessionConfig;
+    use crate::mcp::protocol::ContentBlock;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_test_handler() -> (SetSessionDefaultsHandler, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+
+        let services = Arc::new(Services::new(config));
+        services
+            .storage
+            .create_session(
+                "docs",
+                PathBuf::from("/test/docs"),
+                SessionConfig::default(),
+            )
+            .unwrap();
+        (SetSessionDefaultsHandler::new(services), temp_dir)
+    }
+
+    fn extract_text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ContentBlock::Text { text } => text,
+        }
+    }
+
+    fn stored_defaults(handler: &SetSessionDefaultsHandler) -> Option<SessionSearchDefaults> {
+        handler
+            .services
+            .storage
+            .get_session_metadata("docs")
+            .unwrap()
+            .search_defaults
+    }
+
+    #[tokio::test]
+    async fn test_set_defaults_persists_and_merges() {
+        let (handler, _temp) = setup_test_handler();
+
+        handler
+            .execute(json!({"session": "docs", "k": 20, "fuzziness": 1}))
+            .await
+            .unwrap();
+        let result = handler
+            .execute(json!({"session": "docs", "output_mode": "locations"}))
+            .await
+            .unwrap();
+
+        assert!(extract_text(&result)
+            .contains("**Search defaults:** k=20, fuzziness=1, output_mode=locations"));
+        assert_eq!(
+            stored_defaults(&handler),
+            Some(SessionSearchDefaults {
+                k: Some(20),
+                fuzziness: Some(1),
+                path_boost: None,
+                output_mode: Some(OutputMode::Locations),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clear_removes_defaults() {
+        let (handler, _temp) = setup_test_handler();
+
+        handler
+            .execute(json!({"session": "docs", "k": 20}))
+            .await
+            .unwrap();
+        let result = handler
+            .execute(json!({"session": "docs", "clear": true}))
+            .await
+            .unwrap();
+
+        assert!(extract_text(&result).contains("none (server defaults apply)"));
+        assert_eq!(stored_defaults(&handler), None);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_values_rejected() {
+        let (handler, _temp) = setup_test_handler();
+
+        for args in [
+            json!({"session": "docs", "k": 0}),
+            json!({"session": "docs", "k": 101}),
+            json!({"session": "docs", "fuzziness": 3}),
+            json!({"session": "docs", "path_boost": -1.0}),
+            json!({"session": "docs", "output_mode": "compact"}),
+        ] {
+            let result = handler.execute(args.clone()).await;
+            assert!(
+                matches!(result, Err(McpError::InvalidParams(_))),
+                "{args} should be rejected"
+            );
+        }
+        assert_eq!(stored_defaults(&handler), None);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session() {
+        let (handler, _temp) = setup_test_handler();
+
+        let result = handler.execute(json!({"session": "missing", "k": 5})).await;
+
+        assert!(matches!(result, Err(McpError::InvalidRequest(_))));
+    }
+}
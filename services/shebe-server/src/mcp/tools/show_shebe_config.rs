@@ -3,7 +3,10 @@
 //! Returns the current configuration of the running shebe-mcp server.
 
 use super::handler::{text_content, McpToolHandler};
-use crate::core::config::Config;
+use crate::core::access::{AllowedRoots, RootsSource};
+use crate::core::reload::LiveConfig;
+use crate::core::search::{DEFAULT_BM25_B, DEFAULT_BM25_K1};
+use crate::core::storage::StorageManager;
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
@@ -11,79 +14,173 @@ use serde_json::{json, Value};
 use std::sync::Arc;
 
 pub struct ShowShebeConfigHandler {
-    config: Arc<Config>,
+    config: Arc<LiveConfig>,
+    storage: Option<Arc<StorageManager>>,
 }
 
 impl ShowShebeConfigHandler {
-    pub fn new(config: Arc<Config>) -> Self {
-        Self { config }
+    pub fn new(config: Arc<LiveConfig>) -> Self {
+        Self {
+            config,
+            storage: None,
+        }
+    }
+
+    /// Storage whose sessions supply the allowed roots when
+    /// `security.allowed_roots` is unset
+    pub fn with_storage(mut self, storage: Arc<StorageManager>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Security section: the roots file-reading tools are limited to
+    fn format_security(&self, output: &mut String) {
+        let config = self.config.current();
+        output.push_str("\n## Security\n");
+        let roots = match &self.storage {
+            Some(storage) => AllowedRoots::resolve(&config.security, storage),
+            None => Ok(AllowedRoots::new(
+                config.security.allowed_roots.iter().cloned(),
+                RootsSource::Configured,
+            )),
+        };
+        match roots {
+            Ok(roots) => {
+                output.push_str(&format!(
+                    "- **Allowed Roots:** {} (from {})\n",
+                    roots.roots().len(),
+                    roots.source().describe()
+                ));
+                for root in roots.roots() {
+                    output.push_str(&format!("  - `{}`\n", root.display()));
+                }
+            }
+            Err(e) => output.push_str(&format!("- **Allowed Roots:** unavailable ({e})\n")),
+        }
     }
 
     fn format_config(&self) -> String {
+        let config = self.config.current();
         let mut output = String::from("# Shebe MCP Configuration\n\n");
 
         output.push_str("## Indexing\n");
         output.push_str(&format!(
             "- **Chunk Size:** {} chars\n",
-            self.config.indexing.chunk_size
+            config.indexing.chunk_size
         ));
         output.push_str(&format!(
             "- **Overlap:** {} chars\n",
-            self.config.indexing.overlap
+            config.indexing.overlap
         ));
         output.push_str(&format!(
             "- **Max File Size:** {} MB\n",
-            self.config.indexing.max_file_size_mb
+            config.indexing.max_file_size_mb
         ));
         output.push_str(&format!(
             "- **Include Patterns:** {} patterns\n",
-            self.config.indexing.include_patterns.len()
+            config.indexing.include_patterns.len()
         ));
         output.push_str(&format!(
-            "- **Exclude Patterns:** {} patterns\n\n",
-            self.config.indexing.exclude_patterns.len()
+            "- **Exclude Patterns:** {} patterns\n",
+            config.indexing.exclude_patterns.len()
+        ));
+        output.push_str(&format!(
+            "- **Git Metadata:** {}\n\n",
+            if config.indexing.git_metadata {
+                "enabled (last commit times)"
+            } else {
+                "disabled (file modification times)"
+            }
         ));
 
         output.push_str("## Storage\n");
+        output.push_str(&format!("- **Profile:** {}\n", config.profile_name()));
+        output.push_str(&format!(
+            "- **Index Directory:** {}\n",
+            config.storage.index_dir.display()
+        ));
         output.push_str(&format!(
-            "- **Index Directory:** {}\n\n",
-            self.config.storage.index_dir.display()
+            "- **Mode:** {}\n\n",
+            if config.server.read_only {
+                "read-only (server.read_only)"
+            } else {
+                "read-write"
+            }
         ));
 
         output.push_str("## Search\n");
+        output.push_str(&format!("- **Default K:** {}\n", config.search.default_k));
+        output.push_str(&format!("- **Max K:** {}\n", config.search.max_k));
         output.push_str(&format!(
-            "- **Default K:** {}\n",
-            self.config.search.default_k
+            "- **Max Query Length:** {}\n",
+            config.search.max_query_length
         ));
-        output.push_str(&format!("- **Max K:** {}\n", self.config.search.max_k));
+        output.push_str(&format!("- **Path Boost:** {}\n", config.search.path_boost));
         output.push_str(&format!(
-            "- **Max Query Length:** {}\n\n",
-            self.config.search.max_query_length
+            "- **BM25:** k1={DEFAULT_BM25_K1}, b={DEFAULT_BM25_B} (default for new sessions; \
+             see get_session_info)\n\n"
         ));
 
         output.push_str("## Limits\n");
         output.push_str(&format!(
             "- **Max Concurrent Indexes:** {}\n",
-            self.config.limits.max_concurrent_indexes
+            config.limits.max_concurrent_indexes
         ));
         output.push_str(&format!(
             "- **Request Timeout:** {}s\n",
-            self.config.limits.request_timeout_sec
+            config.limits.request_timeout_sec
+        ));
+        output.push_str(&format!(
+            "- **Tool Timeout:** {}s\n",
+            config.limits.tool_timeout_sec
+        ));
+        output.push_str(&format!(
+            "- **List Dir Max:** {} files\n",
+            config.limits.list_dir_max
         ));
 
+        output.push_str("\n## Tools\n");
+        if config.mcp.tools.is_empty() {
+            output.push_str("- All tools enabled\n");
+        } else {
+            for (name, enabled) in &config.mcp.tools {
+                let state = if *enabled { "enabled" } else { "disabled" };
+                output.push_str(&format!("- **{name}:** {state}\n"));
+            }
+            output.push_str("- Tools not listed are enabled\n");
+        }
+        output.push_str(&format!(
+            "- **Usage Statistics:** {}\n",
+            if config.mcp.usage_stats {
+                "counted (get_server_info)"
+            } else {
+                "off (mcp.usage_stats)"
+            }
+        ));
+        output.push_str(&format!(
+            "- **Experimental Capabilities:** {}\n",
+            if config.mcp.capabilities.experimental {
+                "advertised in initialize"
+            } else {
+                "off (mcp.capabilities.experimental)"
+            }
+        ));
+        self.format_security(&mut output);
+
         output
     }
 
     fn format_config_detailed(&self) -> String {
+        let config = self.config.current();
         let mut output = self.format_config();
 
         output.push_str("\n## Include Patterns\n");
-        for pattern in &self.config.indexing.include_patterns {
+        for pattern in &config.indexing.include_patterns {
             output.push_str(&format!("- `{pattern}`\n"));
         }
 
         output.push_str("\n## Exclude Patterns\n");
-        for pattern in &self.config.indexing.exclude_patterns {
+        for pattern in &config.indexing.exclude_patterns {
             output.push_str(&format!("- `{pattern}`\n"));
         }
 
@@ -101,7 +198,8 @@ impl McpToolHandler for ShowShebeConfigHandler {
         ToolSchema {
             name: "show_shebe_config".to_string(),
             description: "Show the current configuration of the running shebe-mcp server. \
-                         Shows all settings: indexing, search, storage and limits. \
+                         Shows all settings: indexing, search, storage, limits, \
+                         which tools are enabled and the roots files may be read from. \
                          Use this to understand how the server is configured. \
                          Fast operation (<1ms)."
                 .to_string(),
@@ -140,9 +238,10 @@ impl McpToolHandler for ShowShebeConfigHandler {
 mod tests {
     use super::*;
     use crate::core::config::Config;
+    use crate::core::reload::LiveConfig;
 
     fn setup_test_handler() -> ShowShebeConfigHandler {
-        let config = Arc::new(Config::default());
+        let config = Arc::new(LiveConfig::new(Config::default()));
         ShowShebeConfigHandler::new(config)
     }
 
@@ -220,4 +319,32 @@ mod tests {
         assert!(output.contains("`*.rs`"));
         assert!(output.contains("`**/target/**`"));
     }
+
+    #[tokio::test]
+    async fn test_show_shebe_config_tool_enablement() {
+        let mut config = Config::default();
+        config.mcp.tools.insert("read_file".to_string(), false);
+        config.mcp.tools.insert("search_code".to_string(), true);
+        let handler = ShowShebeConfigHandler::new(Arc::new(LiveConfig::new(config)));
+
+        let text = handler.format_config();
+        assert!(text.contains("## Tools"));
+        assert!(text.contains("- **read_file:** disabled"));
+        assert!(text.contains("- **search_code:** enabled"));
+
+        let text = setup_test_handler().format_config();
+        assert!(text.contains("- All tools enabled"));
+    }
+
+    #[tokio::test]
+    async fn test_show_shebe_config_read_only_mode() {
+        assert!(setup_test_handler()
+            .format_config()
+            .contains("- **Mode:** read-write"));
+
+        let mut config = Config::default();
+        config.server.read_only = true;
+        let text = ShowShebeConfigHandler::new(Arc::new(LiveConfig::new(config))).format_config();
+        assert!(text.contains("- **Mode:** read-only"));
+    }
 }
@@ -0,0 +1,93 @@
+//! Update collection tool handler
+
+use super::handler::{text_content, McpToolHandler};
+use super::helpers::format_collection;
+use crate::core::services::Services;
+use crate::core::storage::SESSION_ID_PATTERN;
+use crate::mcp::error::McpError;
+use crate::mcp::protocol::{ToolResult, ToolSchema};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub struct UpdateCollectionHandler {
+    services: Arc<Services>,
+}
+
+impl UpdateCollectionHandler {
+    pub fn new(services: Arc<Services>) -> Self {
+        Self { services }
+    }
+}
+
+#[async_trait]
+impl McpToolHandler for UpdateCollectionHandler {
+    fn name(&self) -> &str {
+        "update_collection"
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "update_collection".to_string(),
+            description: "Add sessions to and remove sessions from a collection \
+                         (see create_collection). Added sessions must exist; a collection \
+                         cannot be left without sessions (use delete_collection)."
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Collection to change"
+                    },
+                    "add": {
+                        "type": "array",
+                        "items": {"type": "string", "pattern": SESSION_ID_PATTERN},
+                        "description": "Session IDs to add (members already in it are kept once)"
+                    },
+                    "remove": {
+                        "type": "array",
+                        "items": {"type": "string", "pattern": SESSION_ID_PATTERN},
+                        "description": "Session IDs to remove"
+                    }
+                },
+                "required": ["name"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult, McpError> {
+        #[derive(Deserialize)]
+        struct UpdateArgs {
+            name: String,
+            #[serde(default)]
+            add: Vec<String>,
+            #[serde(default)]
+            remove: Vec<String>,
+        }
+
+        let args: UpdateArgs =
+            serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
+        if args.add.is_empty() && args.remove.is_empty() {
+            return Err(McpError::InvalidParams(
+                "Nothing to change: pass add and/or remove".to_string(),
+            ));
+        }
+
+        let collection =
+            self.services
+                .storage
+                .update_collection(&args.name, &args.add, &args.remove)?;
+
+        Ok(text_content(format!(
+            "**Collection Updated:** `{}`\n\n{}",
+            collection.name,
+            format_collection(&collection)
+        )))
+    }
+}
@@ -1,12 +1,15 @@
 //! Upgrade session tool handler
 //!
-//! Convenience tool that deletes and re-indexes a session in one step.
-//! Useful for schema migrations when a session uses an old schema version.
+//! Migrates a session to the current schema version, rewriting its
+//! metadata when the index is still compatible and re-indexing it
+//! otherwise (see [`crate::core::storage::StorageManager::upgrade_session`]).
 
 use super::handler::{text_content, McpToolHandler};
 use super::helpers::format_bytes;
 use crate::core::services::Services;
-use crate::core::storage::SCHEMA_VERSION;
+use crate::core::storage::{
+    current_schema_hash, UpgradeReport, UpgradeStrategy, SCHEMA_VERSION, SESSION_ID_PATTERN,
+};
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::{ToolResult, ToolSchema};
 use async_trait::async_trait;
@@ -25,42 +28,99 @@ impl UpgradeSessionHandler {
         Self { services }
     }
 
-    /// Format upgrade result
-    fn format_result(
-        &self,
-        session: &str,
-        old_schema: u32,
-        new_schema: u32,
-        stats: &crate::core::types::IndexStats,
-        index_size_bytes: u64,
-        duration_secs: f64,
-    ) -> String {
-        format!(
+    /// Format an applied upgrade
+    fn format_result(&self, session: &str, report: &UpgradeReport, duration_secs: f64) -> String {
+        let strategy = match (report.strategy, &report.repository_path) {
+            (UpgradeStrategy::Reindex, Some(path)) => format!(
+                "re-index (index schema changed; re-indexed `{}` with the stored configuration)",
+                path.display()
+            ),
+            (UpgradeStrategy::Reindex, None) => "re-index (index schema changed)".to_string(),
+            (strategy, _) => format!(
+                "{} (index already uses the current schema; not re-indexed)",
+                strategy.label()
+            ),
+        };
+
+        let mut text = format!(
             "# Session Upgraded: `{}`\n\n\
+             **Strategy:** {}\n\n\
              **Schema Migration:**\n\
              - Previous version: v{}\n\
              - Current version: v{}\n\n\
-             **Indexing Statistics:**\n\
-             - Files indexed: {}\n\
-             - Chunks created: {}\n\
-             - Index size: {}\n\
-             - Duration: {:.2}s\n\
-             - Throughput: {:.0} files/sec\n\n\
-             Session is now compatible with the current schema.",
+             **Changes:**\n{}\n",
             session,
-            old_schema,
-            new_schema,
-            stats.files_indexed,
-            stats.chunks_created,
-            format_bytes(index_size_bytes),
-            duration_secs,
-            if duration_secs > 0.0 {
-                stats.files_indexed as f64 / duration_secs
-            } else {
-                0.0
-            }
-        )
+            strategy,
+            report.from_version,
+            report.to_version,
+            format_changes(&report.changes),
+        );
+
+        if let Some(stats) = &report.stats {
+            let index_size_bytes = self
+                .services
+                .storage
+                .get_session_metadata(session)
+                .map(|metadata| metadata.index_size_bytes)
+                .unwrap_or(0);
+            text.push_str(&format!(
+                "**Indexing Statistics:**\n\
+                 - Files indexed: {}\n\
+                 - Chunks created: {}\n\
+                 - Index size: {}\n\
+                 - Duration: {:.2}s\n\
+                 - Throughput: {:.0} files/sec\n\n",
+                stats.files_indexed,
+                stats.chunks_created,
+                format_bytes(index_size_bytes),
+                duration_secs,
+                if duration_secs > 0.0 {
+                    stats.files_indexed as f64 / duration_secs
+                } else {
+                    0.0
+                }
+            ));
+        }
+
+        text.push_str("Session is now compatible with the current schema.");
+        text
     }
+
+    /// Format a session that needed no upgrade
+    fn format_up_to_date(&self, session: &str, report: &UpgradeReport) -> String {
+        let mut text = if report.from_version > SCHEMA_VERSION {
+            format!(
+                "Session '{}' is at schema v{}, newer than this version (v{}). \
+                 No upgrade needed.",
+                session, report.from_version, SCHEMA_VERSION
+            )
+        } else {
+            let hash = self
+                .services
+                .storage
+                .get_session_metadata(session)
+                .map(|metadata| metadata.tantivy_schema_hash)
+                .unwrap_or_else(|_| current_schema_hash());
+            format!(
+                "Session '{}' is already at schema v{} (current version) with schema \
+                 hash `{}`. No upgrade needed.",
+                session, report.from_version, hash
+            )
+        };
+        if !report.changes.is_empty() {
+            text.push_str("\n\nDifferences that do not require re-indexing:\n");
+            text.push_str(&format_changes(&report.changes));
+        }
+        text
+    }
+}
+
+/// Format changes as a Markdown list
+fn format_changes(changes: &[String]) -> String {
+    if changes.is_empty() {
+        return "- none\n".to_string();
+    }
+    changes.iter().map(|c| format!("- {c}\n")).collect()
 }
 
 #[async_trait]
@@ -69,13 +129,21 @@ impl McpToolHandler for UpgradeSessionHandler {
         "upgrade_session"
     }
 
+    fn mutates(&self) -> bool {
+        true
+    }
+
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "upgrade_session".to_string(),
             description: "Upgrade a session to the current schema version. \
-                         Deletes the existing session and re-indexes using the stored \
-                         repository path and configuration. Fast (~1-3 seconds). \
-                         Use when a session fails with 'old schema version' error."
+                         Use when a session fails with 'old schema version' error. \
+                         If the index already has the current schema, only the session \
+                         metadata is rewritten (missing fields filled in). Otherwise the \
+                         stored repository path is re-indexed with the stored configuration \
+                         (~1-3 seconds). Reports the strategy applied, the schema versions \
+                         before and after, and exactly what changed. Fails with a list of \
+                         what is missing when neither is possible."
                 .to_string(),
             input_schema: json!({
                 "type": "object",
@@ -83,7 +151,7 @@ impl McpToolHandler for UpgradeSessionHandler {
                     "session": {
                         "type": "string",
                         "description": "Session ID to upgrade",
-                        "pattern": "^[a-zA-Z0-9_-]{1,64}$"
+                        "pattern": SESSION_ID_PATTERN
                     }
                 },
                 "required": ["session"]
@@ -98,95 +166,26 @@ impl McpToolHandler for UpgradeSessionHandler {
         let args: UpgradeArgs =
             serde_json::from_value(args).map_err(|e| McpError::InvalidParams(e.to_string()))?;
 
-        // 1. Get session metadata (includes repository_path, config and schema_version)
-        let metadata = self
+        let start = Instant::now();
+        let report = self
             .services
             .storage
-            .get_session_metadata(&args.session)
+            .upgrade_session(&args.session)
             .map_err(|e| match e {
                 ShebeError::SessionNotFound(_) => McpError::InvalidRequest(format!(
                     "Session '{}' not found. Use list_sessions to see available sessions.",
                     args.session
                 )),
+                ShebeError::InvalidSession(message) => McpError::InvalidRequest(message),
                 _ => McpError::from(e),
             })?;
-
-        let old_schema = metadata.schema_version;
-
-        // 2. Check if upgrade is needed
-        if old_schema >= SCHEMA_VERSION {
-            return Ok(text_content(format!(
-                "Session '{}' is already at schema v{} (current version). No upgrade needed.",
-                args.session, old_schema
-            )));
-        }
-
-        // 3. Validate repository path still exists
-        if !metadata.repository_path.exists() {
-            return Err(McpError::InvalidRequest(format!(
-                "Repository path no longer exists: {}\n\
-                 Session '{}' cannot be upgraded.\n\
-                 Possible solutions:\n\
-                 - Move repository back to original location\n\
-                 - Delete session and create new one at current location",
-                metadata.repository_path.display(),
-                args.session
-            )));
-        }
-
-        if !metadata.repository_path.is_dir() {
-            return Err(McpError::InvalidRequest(format!(
-                "Repository path is not a directory: {}",
-                metadata.repository_path.display()
-            )));
-        }
-
-        // 4. Store config before deleting session
-        let config = metadata.config.clone();
-        let repo_path = metadata.repository_path.clone();
-
-        // 5. Delete old session
-        self.services
-            .storage
-            .delete_session(&args.session)
-            .map_err(|e| McpError::InternalError(format!("Failed to delete session: {e}")))?;
-
-        // 6. Re-index repository with same configuration
-        let start = Instant::now();
-        let stats = self
-            .services
-            .storage
-            .index_repository(
-                &args.session,
-                &repo_path,
-                config.include_patterns.clone(),
-                config.exclude_patterns.clone(),
-                config.chunk_size,
-                config.overlap,
-                100,   // max_file_size_mb default
-                false, // force (already deleted above)
-            )
-            .map_err(|e| McpError::InternalError(format!("Re-indexing failed: {e}")))?;
         let duration_secs = start.elapsed().as_secs_f64();
 
-        // 7. Get updated metadata to retrieve index size
-        let updated_metadata = self
-            .services
-            .storage
-            .get_session_metadata(&args.session)
-            .map_err(|e| McpError::InternalError(format!("Failed to get updated metadata: {e}")))?;
-
-        // 8. Format result
-        let result = self.format_result(
-            &args.session,
-            old_schema,
-            SCHEMA_VERSION,
-            &stats,
-            updated_metadata.index_size_bytes,
-            duration_secs,
-        );
-
-        Ok(text_content(result))
+        let text = match report.strategy {
+            UpgradeStrategy::UpToDate => self.format_up_to_date(&args.session, &report),
+            _ => self.format_result(&args.session, &report, duration_secs),
+        };
+        Ok(text_content(text))
     }
 }
 
@@ -199,6 +198,7 @@ struct UpgradeArgs {
 mod tests {
     use super::*;
     use crate::core::config::Config;
+    use crate::core::storage::{current_schema_hash_for, UNKNOWN};
 
     use tempfile::TempDir;
 
@@ -238,6 +238,17 @@ mod tests {
             .unwrap();
     }
 
+    /// Make a session's index unreadable, so upgrading must re-index
+    fn break_index(services: &Arc<Services>, session_id: &str) {
+        let tantivy_dir = services
+            .storage
+            .storage_root()
+            .join("sessions")
+            .join(session_id)
+            .join("tantivy");
+        std::fs::remove_dir_all(tantivy_dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_upgrade_session_handler_name() {
         let (handler, _temp) = setup_test_handler().await;
@@ -325,10 +336,89 @@ mod tests {
             crate::mcp::protocol::ContentBlock::Text { text } => text,
         };
 
+        // The index already has the current schema, so only meta.json changes
         assert!(text.contains("Session Upgraded:"));
+        assert!(text.contains("**Strategy:** metadata rewrite"));
         assert!(text.contains("Previous version: v1"));
         assert!(text.contains(&format!("Current version: v{}", SCHEMA_VERSION)));
-        assert!(text.contains("Files indexed:"));
+        assert!(text.contains(&format!("- schema version: v1 -> v{SCHEMA_VERSION}")));
+        assert!(!text.contains("Files indexed:"));
+
+        let upgraded = handler
+            .services
+            .storage
+            .get_session_metadata("test-old")
+            .unwrap();
+        assert_eq!(upgraded.schema_version, SCHEMA_VERSION);
+        assert!(handler.services.storage.open_session("test-old").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_session_reindexes_incompatible_index() {
+        let (handler, temp_dir) = setup_test_handler().await;
+        let repo_path = temp_dir.path().join("test_repo");
+        create_test_session(&handler.services, &repo_path, "test-broken").await;
+        break_index(&handler.services, "test-broken");
+
+        let result = handler
+            .execute(json!({"session": "test-broken"}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+
+        assert!(text.contains("**Strategy:** re-index"));
+        assert!(text.contains("- index: unreadable -> rebuilt"));
+        assert!(text.contains("Files indexed: 1"));
+        assert!(handler.services.storage.open_session("test-broken").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_session_unrecorded_hash() {
+        let (handler, temp_dir) = setup_test_handler().await;
+        let repo_path = temp_dir.path().join("test_repo");
+        create_test_session(&handler.services, &repo_path, "test-unknown").await;
+
+        // Simulate a meta.json written before diagnostics were recorded
+        let mut metadata = handler
+            .services
+            .storage
+            .get_session_metadata("test-unknown")
+            .unwrap();
+        metadata.created_by_version = UNKNOWN.to_string();
+        metadata.tantivy_schema_hash = UNKNOWN.to_string();
+        metadata.index_settings = None;
+        handler
+            .services
+            .storage
+            .update_session_metadata("test-unknown", &metadata)
+            .unwrap();
+
+        let result = handler
+            .execute(json!({"session": "test-unknown"}))
+            .await
+            .unwrap();
+        let text = match &result.content[0] {
+            crate::mcp::protocol::ContentBlock::Text { text } => text,
+        };
+
+        assert!(text.contains("Session Upgraded:"));
+        assert!(text.contains(&format!(
+            "- schema hash: `unknown` -> `{}`",
+            current_schema_hash_for(true)
+        )));
+        assert!(text.contains("- index settings: not recorded -> recorded"));
+        assert!(!text.contains("schema version:"));
+
+        let upgraded = handler
+            .services
+            .storage
+            .get_session_metadata("test-unknown")
+            .unwrap();
+        assert_eq!(upgraded.schema_hash_matches(), Some(true));
+        // Not re-indexed, so the creating version is still unknown
+        assert_eq!(upgraded.created_by_version, UNKNOWN);
     }
 
     #[tokio::test]
@@ -350,8 +440,9 @@ mod tests {
             .update_session_metadata("test-missing", &metadata)
             .unwrap();
 
-        // Delete repository directory
+        // Delete repository directory; the index needs rebuilding too
         std::fs::remove_dir_all(&repo_path).unwrap();
+        break_index(&handler.services, "test-missing");
 
         let args = json!({
             "session": "test-missing"
@@ -361,6 +452,7 @@ mod tests {
         assert!(result.is_err());
 
         if let Err(McpError::InvalidRequest(msg)) = result {
+            assert!(msg.contains("cannot be upgraded automatically"));
             assert!(msg.contains("no longer exists"));
             assert!(msg.contains("Possible solutions:"));
         } else {
@@ -406,6 +498,7 @@ mod tests {
             .storage
             .update_session_metadata("test-config", &metadata)
             .unwrap();
+        break_index(&handler.services, "test-config");
 
         let args = json!({
             "session": "test-config"
@@ -0,0 +1,426 @@
+//! Per-tool usage statistics
+//!
+//! [`ToolRegistry`](super::ToolRegistry) counts every tool call: calls,
+//! errors (including timeouts) and a latency histogram per tool. The
+//! counters of a tool are created when it is registered, so recording
+//! a call is a few relaxed atomic adds, without a lock.
+//!
+//! `shebe-mcp` loads the counts from `tool-usage.json` in the XDG state
+//! directory on startup and writes them back every minute and on
+//! shutdown ([`ToolUsage::persist_to`]), so restarts keep the history.
+//! `get_server_info` reports them; `[mcp] usage_stats = false` stops
+//! counting.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+/// How often a persisting server writes its counts
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Upper bounds (ms) of the latency histogram buckets
+///
+/// A last, open-ended bucket holds slower calls. Percentiles are
+/// reported as the bound of the bucket they fall in.
+const BUCKET_BOUNDS_MS: [u64; 24] = [
+    1, 2, 3, 5, 7, 10, 15, 20, 30, 50, 70, 100, 150, 200, 300, 500, 700, 1_000, 1_500, 2_000,
+    3_000, 5_000, 10_000, 30_000,
+];
+const BUCKETS: usize = BUCKET_BOUNDS_MS.len() + 1;
+
+/// Live counters of one tool
+#[derive(Debug, Default)]
+pub struct ToolCounters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl ToolCounters {
+    /// Count one call that took `elapsed`
+    pub fn record(&self, elapsed: Duration, failed: bool) {
+        let us = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let ms = us / 1_000;
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| ms < *bound)
+            .unwrap_or(BUCKETS - 1);
+
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> ToolStats {
+        ToolStats {
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            total_us: self.total_us.load(Ordering::Relaxed),
+            max_us: self.max_us.load(Ordering::Relaxed),
+            latency_buckets: self
+                .buckets
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Relaxed))
+                .collect(),
+        }
+    }
+
+    /// Add counts loaded from disk
+    fn add(&self, stats: &ToolStats) {
+        self.calls.fetch_add(stats.calls, Ordering::Relaxed);
+        self.errors.fetch_add(stats.errors, Ordering::Relaxed);
+        self.total_us.fetch_add(stats.total_us, Ordering::Relaxed);
+        self.max_us.fetch_max(stats.max_us, Ordering::Relaxed);
+        // Histograms written with other bucket bounds are dropped
+        if stats.latency_buckets.len() == BUCKETS {
+            for (bucket, count) in self.buckets.iter().zip(&stats.latency_buckets) {
+                bucket.fetch_add(*count, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn reset(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.total_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Counts of one tool at a point in time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    /// Calls that returned an error or timed out
+    pub errors: u64,
+    /// Summed latency of all calls (microseconds)
+    pub total_us: u64,
+    /// Slowest call (microseconds)
+    pub max_us: u64,
+    /// Calls per latency bucket (see `BUCKET_BOUNDS_MS`)
+    #[serde(default)]
+    pub latency_buckets: Vec<u64>,
+}
+
+impl ToolStats {
+    /// Share of calls that failed (0.0-1.0)
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+
+    /// Latency under which `quantile` (0.0-1.0) of the calls finished
+    ///
+    /// The bound of the histogram bucket the quantile falls in, or the
+    /// slowest call for the open-ended last bucket. `None` without calls.
+    pub fn percentile(&self, quantile: f64) -> Option<Duration> {
+        let total: u64 = self.latency_buckets.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let target = ((quantile * total as f64).ceil() as u64).clamp(1, total);
+        let mut seen = 0;
+        for (index, count) in self.latency_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let slowest = Duration::from_micros(self.max_us);
+                return Some(match BUCKET_BOUNDS_MS.get(index) {
+                    Some(bound) => Duration::from_millis(*bound).min(slowest),
+                    None => slowest,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Usage statistics of all tools, as written to disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    /// When counting started (first run or last reset)
+    pub since: DateTime<Utc>,
+    /// Tool name -> counts
+    pub tools: BTreeMap<String, ToolStats>,
+}
+
+impl UsageSnapshot {
+    /// Tools that were called, most calls first
+    pub fn called(&self) -> Vec<(&str, &ToolStats)> {
+        let mut called: Vec<(&str, &ToolStats)> = self
+            .tools
+            .iter()
+            .filter(|(_, stats)| stats.calls > 0)
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect();
+        called.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then(a.0.cmp(b.0)));
+        called
+    }
+
+    /// Read a snapshot written by [`UsageSnapshot::save`]
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the snapshot as JSON
+    ///
+    /// The JSON goes to a sibling temporary file that is renamed over
+    /// `path`, so a crash mid-write leaves the previous counts.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Clear the counts stored at `path`
+    ///
+    /// For `shebe get-server-info --reset-stats`. A running server keeps its own
+    /// counts and writes them back at its next flush; reset those with
+    /// `get_server_info` (`reset: true`).
+    pub fn reset_file(path: &Path) -> io::Result<()> {
+        Self {
+            since: Utc::now(),
+            tools: BTreeMap::new(),
+        }
+        .save(path)
+    }
+}
+
+/// Usage counters of every registered tool
+#[derive(Debug)]
+pub struct ToolUsage {
+    /// Written when tools are registered or counts loaded, not per call
+    tools: RwLock<BTreeMap<String, Arc<ToolCounters>>>,
+    /// Start of counting (Unix seconds)
+    since: AtomicI64,
+    enabled: AtomicBool,
+    /// File the counts are persisted to, once [`persist_to`](Self::persist_to) ran
+    file: OnceLock<PathBuf>,
+}
+
+impl Default for ToolUsage {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl ToolUsage {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            tools: RwLock::new(BTreeMap::new()),
+            since: AtomicI64::new(Utc::now().timestamp()),
+            enabled: AtomicBool::new(enabled),
+            file: OnceLock::new(),
+        }
+    }
+
+    /// Counters of `tool`, created on first use
+    pub fn track(&self, tool: &str) -> Arc<ToolCounters> {
+        let mut tools = self.tools.write().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(tools.entry(tool.to_string()).or_default())
+    }
+
+    /// Whether calls are counted (`[mcp] usage_stats`)
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// When counting started
+    pub fn since(&self) -> DateTime<Utc> {
+        Utc.timestamp_opt(self.since.load(Ordering::Relaxed), 0)
+            .single()
+            .unwrap_or_default()
+    }
+
+    /// Current counts of all tracked tools
+    pub fn snapshot(&self) -> UsageSnapshot {
+        let tools = self.tools.read().unwrap_or_else(|e| e.into_inner());
+        UsageSnapshot {
+            since: self.since(),
+            tools: tools
+                .iter()
+                .map(|(name, counters)| (name.clone(), counters.snapshot()))
+                .collect(),
+        }
+    }
+
+    /// Add the counts of `snapshot`, keeping its start of counting
+    pub fn merge(&self, snapshot: &UsageSnapshot) {
+        for (name, stats) in &snapshot.tools {
+            self.track(name).add(stats);
+        }
+        self.since
+            .fetch_min(snapshot.since.timestamp(), Ordering::Relaxed);
+    }
+
+    /// Zero all counters and restart counting now
+    ///
+    /// Persisted counts are cleared too.
+    pub fn reset(&self) {
+        {
+            let tools = self.tools.read().unwrap_or_else(|e| e.into_inner());
+            for counters in tools.values() {
+                counters.reset();
+            }
+        }
+        self.since.store(Utc::now().timestamp(), Ordering::Relaxed);
+        self.flush();
+    }
+
+    /// Load the counts at `path` and write them back periodically
+    ///
+    /// A missing file starts from zero; an unreadable one is logged and
+    /// replaced at the next flush. Only the first call has an effect.
+    /// Must be called within a tokio runtime.
+    pub fn persist_to(self: &Arc<Self>, path: PathBuf) {
+        match UsageSnapshot::load(&path) {
+            Ok(snapshot) => self.merge(&snapshot),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!(
+                "Ignoring tool usage statistics in {}: {}",
+                path.display(),
+                e
+            ),
+        }
+        if self.file.set(path).is_err() {
+            return;
+        }
+
+        let usage = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(FLUSH_INTERVAL);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                match usage.upgrade() {
+                    Some(usage) => usage.flush(),
+                    None => break,
+                }
+            }
+        });
+    }
+
+    /// Write the counts to the persisted file, if any
+    ///
+    /// Failures are logged; statistics never fail a call.
+    pub fn flush(&self) {
+        let Some(path) = self.file.get() else {
+            return;
+        };
+        if let Err(e) = self.snapshot().save(path) {
+            tracing::warn!(
+                "Failed to write tool usage statistics to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_percentiles() {
+        let usage = ToolUsage::default();
+        let counters = usage.track("search_code");
+        for ms in [4, 8, 12, 40, 900] {
+            counters.record(Duration::from_millis(ms), false);
+        }
+        counters.record(Duration::from_millis(6), true);
+
+        let snapshot = usage.snapshot();
+        let stats = &snapshot.tools["search_code"];
+        assert_eq!(stats.calls, 6);
+        assert_eq!(stats.errors, 1);
+        assert!((stats.error_rate() - 1.0 / 6.0).abs() < 1e-9);
+        assert_eq!(stats.percentile(0.5), Some(Duration::from_millis(10)));
+        assert_eq!(stats.percentile(0.95), Some(Duration::from_millis(900)));
+        assert_eq!(ToolStats::default().percentile(0.5), None);
+
+        // Slower than the last bound: the slowest call
+        let counters = usage.track("index_repository");
+        counters.record(Duration::from_secs(45), false);
+        let stats = &usage.snapshot().tools["index_repository"];
+        assert_eq!(stats.percentile(0.5), Some(Duration::from_secs(45)));
+    }
+
+    #[test]
+    fn test_called_sorts_by_calls() {
+        let usage = ToolUsage::default();
+        usage.track("read_file").record(Duration::ZERO, false);
+        usage.track("unused");
+        let search = usage.track("search_code");
+        search.record(Duration::ZERO, false);
+        search.record(Duration::ZERO, false);
+
+        let snapshot = usage.snapshot();
+        let names: Vec<&str> = snapshot.called().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["search_code", "read_file"]);
+    }
+
+    #[tokio::test]
+    async fn test_persist_roundtrip_and_reset() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state/tool-usage.json");
+
+        let first = Arc::new(ToolUsage::default());
+        first.persist_to(path.clone());
+        first
+            .track("search_code")
+            .record(Duration::from_millis(12), false);
+        first.flush();
+        let since = first.since();
+
+        // A restarted server continues from the saved counts
+        let second = Arc::new(ToolUsage::default());
+        let counters = second.track("search_code");
+        second.persist_to(path.clone());
+        counters.record(Duration::from_millis(3), true);
+        let snapshot = second.snapshot();
+        assert_eq!(snapshot.since, since);
+        assert_eq!(snapshot.tools["search_code"].calls, 2);
+        assert_eq!(snapshot.tools["search_code"].errors, 1);
+
+        second.reset();
+        let saved = UsageSnapshot::load(&path).unwrap();
+        assert_eq!(
+            saved.tools["search_code"],
+            ToolStats {
+                latency_buckets: vec![0; BUCKETS],
+                ..ToolStats::default()
+            }
+        );
+
+        UsageSnapshot::reset_file(&path).unwrap();
+        assert!(UsageSnapshot::load(&path).unwrap().tools.is_empty());
+    }
+}
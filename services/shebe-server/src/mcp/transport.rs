@@ -1,22 +1,39 @@
-//! Stdio transport for MCP protocol
+//! Transports for the MCP protocol
+//!
+//! Messages are newline-delimited JSON-RPC in both directions. The
+//! server reads requests from stdin (one client) or from TCP
+//! connections (one JSON-RPC session per connection, see
+//! [`crate::mcp::McpServer::serve_tcp`]); responses go out through a
+//! [`LineTransport`] over the matching writer.
 
 use crate::mcp::error::McpError;
 use crate::mcp::protocol::JsonRpcResponse;
-use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 use tracing::debug;
 
-pub struct StdioTransport {
-    stdout: BufWriter<tokio::io::Stdout>,
+/// Prefix of the line TCP clients send to authenticate
+pub const AUTH_PREFIX: &str = "Authorization: Bearer ";
+
+/// Longest `Authorization` line read before a client is refused (bytes)
+pub const MAX_AUTH_LINE: u64 = 4096;
+
+/// Writes JSON-RPC responses as newline-delimited JSON
+pub struct LineTransport<W: AsyncWrite + Unpin> {
+    writer: BufWriter<W>,
 }
 
-impl StdioTransport {
-    pub fn new() -> Self {
+/// Transport writing to stdout
+pub type StdioTransport = LineTransport<tokio::io::Stdout>;
+
+impl<W: AsyncWrite + Unpin> LineTransport<W> {
+    /// Transport writing to `writer`
+    pub fn from_writer(writer: W) -> Self {
         Self {
-            stdout: BufWriter::new(tokio::io::stdout()),
+            writer: BufWriter::new(writer),
         }
     }
 
-    /// Send JSON-RPC response to stdout
+    /// Send a JSON-RPC response
     pub async fn send_response(&mut self, response: JsonRpcResponse) -> Result<(), McpError> {
         // Skip responses for notifications (no id)
         if response.id.is_none() && response.result.is_none() && response.error.is_none() {
@@ -27,16 +44,73 @@ impl StdioTransport {
         debug!("Sending: {}", json);
 
         // Write JSON + newline
-        self.stdout.write_all(json.as_bytes()).await?;
-        self.stdout.write_all(b"\n").await?;
-        self.stdout.flush().await?;
+        self.writer.write_all(json.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await?;
 
         Ok(())
     }
 }
 
+impl StdioTransport {
+    pub fn new() -> Self {
+        Self::from_writer(tokio::io::stdout())
+    }
+}
+
 impl Default for StdioTransport {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// Whether `line` is `Authorization: Bearer <token>` with the expected token
+///
+/// Compares in constant time so the token cannot be guessed byte by
+/// byte from response timings.
+pub fn is_authorized(line: &str, token: &str) -> bool {
+    let Some(given) = line.trim().strip_prefix(AUTH_PREFIX) else {
+        return false;
+    };
+    let (given, token) = (given.trim().as_bytes(), token.as_bytes());
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized("Authorization: Bearer s3cret", "s3cret"));
+        assert!(is_authorized("Authorization: Bearer s3cret\r\n", "s3cret"));
+        assert!(!is_authorized("Authorization: Bearer s3cre", "s3cret"));
+        assert!(!is_authorized("Authorization: Bearer s3cret!", "s3cret"));
+        assert!(!is_authorized("Bearer s3cret", "s3cret"));
+        assert!(!is_authorized(r#"{"jsonrpc":"2.0"}"#, "s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_line_transport_writes_one_line_per_response() {
+        let mut buffer = Vec::new();
+        let mut transport = LineTransport::from_writer(&mut buffer);
+        let response = JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(1)),
+            result: Some(serde_json::json!({})),
+            error: None,
+        };
+        transport.send_response(response).await.unwrap();
+        drop(transport);
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n"
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! This module provides constants and helper functions for managing
 //! MCP protocol token limits and building user-friendly warning
-//! messages.
+//! messages, including the session suggestions shared with the CLI.
+
+use std::path::{Path, PathBuf};
 
 /// MCP protocol token limit (25,000 tokens)
 ///
@@ -17,7 +19,13 @@ pub const MCP_TOKEN_LIMIT: usize = 25_000;
 /// token usage for initial repository exploration.
 pub const LIST_DIR_DEFAULT_LIMIT: usize = 100;
 
-/// Maximum limit for list_dir (hard cap)
+/// Default limit for list_dir when extra columns are requested
+///
+/// Rows with a size, date and first line cost several times the
+/// tokens of a bare path, so the default page is smaller.
+pub const LIST_DIR_COLUMNS_DEFAULT_LIMIT: usize = 40;
+
+/// Default maximum limit for list_dir (`limits.list_dir_max`)
 ///
 /// At ~25-30 chars per path, 500 files = ~12-15k tokens
 /// (well under 25k limit)
@@ -32,6 +40,189 @@ pub const LIST_DIR_MAX_LIMIT: usize = 500;
 /// - UTF-8 safety with character-based truncation
 pub const READ_FILE_MAX_CHARS: usize = 20_000;
 
+/// Token budget for a single tool response (80% of the MCP limit)
+///
+/// Leaves headroom for the JSON-RPC envelope and for the error of the
+/// chars/4 token estimate on code with many short tokens.
+pub const RESPONSE_TOKEN_BUDGET: usize = MCP_TOKEN_LIMIT * 4 / 5;
+
+/// Tokens reserved for the truncation footer once trimming starts
+const FOOTER_RESERVE_TOKENS: usize = 150;
+
+/// Estimate the token count of a text (chars/4 heuristic)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Size of one response entry, in priority order (most important first)
+#[derive(Debug, Clone, Copy)]
+pub struct EntrySize {
+    /// Tokens of the entry with its context
+    pub full: usize,
+
+    /// Tokens without context, if the entry has context to drop
+    pub compact: Option<usize>,
+}
+
+impl EntrySize {
+    /// Measure an entry from its full and compact renderings
+    pub fn measure(full: &str, compact: Option<&str>) -> Self {
+        Self {
+            full: estimate_tokens(full),
+            compact: compact.map(estimate_tokens),
+        }
+    }
+}
+
+/// Which entries of a response fit the budget, and in what form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetPlan {
+    compacted: Vec<bool>,
+    kept: usize,
+}
+
+impl BudgetPlan {
+    /// Whether entry `i` is shown
+    pub fn is_kept(&self, i: usize) -> bool {
+        i < self.kept
+    }
+
+    /// Whether entry `i` is shown without its context
+    pub fn is_compacted(&self, i: usize) -> bool {
+        self.is_kept(i) && self.compacted[i]
+    }
+
+    /// Number of entries shown
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+
+    /// Number of shown entries whose context was dropped
+    pub fn compacted(&self) -> usize {
+        (0..self.kept).filter(|&i| self.compacted[i]).count()
+    }
+
+    /// Number of entries not shown
+    pub fn dropped(&self) -> usize {
+        self.compacted.len() - self.kept
+    }
+
+    /// Whether anything was trimmed
+    pub fn is_truncated(&self) -> bool {
+        self.compacted() > 0 || self.dropped() > 0
+    }
+}
+
+/// Token budget shared by tools whose output grows with their arguments
+///
+/// Tools describe their response as fixed text (headers, summaries)
+/// plus entries in priority order. When the full response is over
+/// budget, context is dropped from the lowest-priority entries first,
+/// then the lowest-priority entries are dropped, until it fits with
+/// room for the footer from [`build_truncation_footer`].
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseBudget {
+    tokens: usize,
+}
+
+impl Default for ResponseBudget {
+    fn default() -> Self {
+        Self::new(RESPONSE_TOKEN_BUDGET)
+    }
+}
+
+impl ResponseBudget {
+    /// Create a budget of `tokens` estimated tokens
+    pub fn new(tokens: usize) -> Self {
+        Self { tokens }
+    }
+
+    /// Budget in estimated tokens
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// Decide which entries to show and which to compact
+    ///
+    /// `fixed` is the token count of everything that is always shown.
+    pub fn plan(&self, fixed: usize, entries: &[EntrySize]) -> BudgetPlan {
+        let mut compacted = vec![false; entries.len()];
+        let mut size = fixed + entries.iter().map(|e| e.full).sum::<usize>();
+        if size <= self.tokens {
+            return BudgetPlan {
+                compacted,
+                kept: entries.len(),
+            };
+        }
+
+        let limit = self.tokens.saturating_sub(FOOTER_RESERVE_TOKENS);
+
+        // Drop context, lowest priority first
+        for (i, entry) in entries.iter().enumerate().rev() {
+            if size <= limit {
+                break;
+            }
+            if let Some(compact) = entry.compact.filter(|&c| c < entry.full) {
+                size -= entry.full - compact;
+                compacted[i] = true;
+            }
+        }
+
+        // Then drop whole entries, lowest priority first
+        let mut kept = entries.len();
+        while size > limit && kept > 0 {
+            kept -= 1;
+            let entry = entries[kept];
+            size -= match entry.compact {
+                Some(compact) if compacted[kept] => compact,
+                _ => entry.full,
+            };
+        }
+
+        BudgetPlan { compacted, kept }
+    }
+
+    /// Plan rendered entries and join the ones that fit
+    ///
+    /// Each entry is its full text and, if it has context to drop, its
+    /// compact text. Returns the joined entries with the plan used.
+    pub fn fit(&self, fixed: usize, entries: &[(String, Option<String>)]) -> (String, BudgetPlan) {
+        let sizes: Vec<EntrySize> = entries
+            .iter()
+            .map(|(full, compact)| EntrySize::measure(full, compact.as_deref()))
+            .collect();
+        let plan = self.plan(fixed, &sizes);
+
+        let mut body = String::new();
+        for (i, (full, compact)) in entries.iter().enumerate().take(plan.kept()) {
+            match compact {
+                Some(compact) if plan.is_compacted(i) => body.push_str(compact),
+                _ => body.push_str(full),
+            }
+        }
+
+        (body, plan)
+    }
+}
+
+/// Build the standard footer for a response trimmed to its budget
+///
+/// `omitted` lists what was left out (one line each) and `hint` says
+/// how to get the rest.
+pub fn build_truncation_footer(budget: &ResponseBudget, omitted: &[String], hint: &str) -> String {
+    let mut footer = format!(
+        "\n---\n\n**RESPONSE TRUNCATED** to fit the ~{}-token response budget \
+         (MCP {}k token limit):\n",
+        budget.tokens(),
+        MCP_TOKEN_LIMIT / 1000
+    );
+    for line in omitted {
+        footer.push_str(&format!("- {line}\n"));
+    }
+    footer.push_str(&format!("\n**To see the rest:** {hint}\n"));
+    footer
+}
+
 /// Build truncation warning message for list_dir
 ///
 /// Creates a user-friendly warning that explains:
@@ -42,19 +233,25 @@ pub const READ_FILE_MAX_CHARS: usize = 20_000;
 /// # Arguments
 /// * `shown_count` - Number of files actually displayed
 /// * `total_count` - Total number of files in the session
+/// * `max_limit` - Most files one call may display
 /// * `session` - Session ID for example commands
 ///
 /// # Returns
 /// Formatted markdown warning message
-pub fn build_list_dir_warning(shown_count: usize, total_count: usize, session: &str) -> String {
+pub fn build_list_dir_warning(
+    shown_count: usize,
+    total_count: usize,
+    max_limit: usize,
+    session: &str,
+) -> String {
     let not_shown = total_count.saturating_sub(shown_count);
     format!(
         "WARNING: OUTPUT TRUNCATED - \
-         MAXIMUM {LIST_DIR_MAX_LIMIT} FILES DISPLAYED\n\n\
+         MAXIMUM {max_limit} FILES DISPLAYED\n\n\
          Showing: {shown_count} of {total_count} files \
          (first {shown_count}, alphabetically sorted)\n\
          Reason: Maximum display limit is \
-         {LIST_DIR_MAX_LIMIT} files \
+         {max_limit} files \
          (MCP 25k token limit)\n\
          Not shown: {not_shown} files\n\n\
          SUGGESTIONS:\n\
@@ -122,13 +319,128 @@ pub fn build_read_file_warning(
     )
 }
 
+/// Maximum number of "Did you mean" session suggestions
+pub const MAX_SESSION_SUGGESTIONS: usize = 3;
+
+/// Below this many sessions, "not found" errors list all of them
+pub const SESSION_LIST_THRESHOLD: usize = 10;
+
+/// Levenshtein distance between two strings (by characters)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Find existing sessions close to a session ID that was not found
+///
+/// Compares case-insensitively against both the whole session ID and
+/// its prefix of the same length, so `backand` matches `backend` and
+/// `backend-v2`. Allows one edit per three characters (at least one).
+/// Returns at most [`MAX_SESSION_SUGGESTIONS`], closest first.
+pub fn suggest_sessions(missing: &str, sessions: &[String]) -> Vec<String> {
+    let wanted: Vec<char> = missing.to_lowercase().chars().collect();
+    if wanted.is_empty() {
+        return Vec::new();
+    }
+    let max_distance = (wanted.len() / 3).max(1);
+
+    let mut matches: Vec<(usize, usize, &String)> = sessions
+        .iter()
+        .filter(|session| session.as_str() != missing)
+        .filter_map(|session| {
+            let candidate: Vec<char> = session.to_lowercase().chars().collect();
+            let full = edit_distance(&wanted, &candidate);
+            let prefix = if candidate.len() > wanted.len() {
+                edit_distance(&wanted, &candidate[..wanted.len()])
+            } else {
+                full
+            };
+            let distance = full.min(prefix);
+            (distance <= max_distance).then_some((distance, full, session))
+        })
+        .collect();
+
+    matches.sort();
+    matches
+        .into_iter()
+        .take(MAX_SESSION_SUGGESTIONS)
+        .map(|(_, _, session)| session.clone())
+        .collect()
+}
+
+/// Build the hint appended to a "session not found" error
+///
+/// Adds "Did you mean: ...?" when there are close matches, and the full
+/// session list when there are fewer than [`SESSION_LIST_THRESHOLD`]
+/// sessions. Each part starts on a new line; empty if neither applies.
+pub fn session_not_found_hint(missing: &str, sessions: &[String]) -> String {
+    let mut hint = String::new();
+    let suggestions = suggest_sessions(missing, sessions);
+    if !suggestions.is_empty() {
+        hint.push_str(&format!("\nDid you mean: {}?", suggestions.join(", ")));
+    }
+    if sessions.is_empty() {
+        hint.push_str("\nNo sessions have been indexed yet.");
+    } else if sessions.len() < SESSION_LIST_THRESHOLD {
+        let mut all = sessions.to_vec();
+        all.sort();
+        hint.push_str(&format!("\nAvailable sessions: {}", all.join(", ")));
+    }
+    hint
+}
+
+/// Tools whose "session not found" error explains how to create a
+/// session when none exist yet (the usual first calls of a new user)
+pub const NO_SESSIONS_GUIDANCE_TOOLS: &[&str] = &["search_code", "find_references", "list_dir"];
+
+/// The repository at `dir`, if it is the root of a git checkout
+///
+/// Only `dir` itself is checked (for a `.git` directory, or the `.git`
+/// file of a worktree), not its parents.
+pub fn detect_repository(dir: &Path) -> Option<PathBuf> {
+    dir.join(".git").exists().then(|| dir.to_path_buf())
+}
+
+/// Build the guidance appended to a "session not found" error when no
+/// sessions exist at all
+///
+/// Gives a ready-to-use `index_repository` call creating `missing`,
+/// for `repository` when known (a `path` argument of the call or the
+/// server's working directory) and a placeholder path otherwise.
+pub fn no_sessions_guidance(missing: &str, repository: Option<&Path>) -> String {
+    let mut guidance =
+        String::from("\nNo sessions have been indexed yet. Index a repository first, then retry:");
+    let path = match repository {
+        Some(repository) => {
+            guidance.push_str(&format!("\nRepository detected: {}", repository.display()));
+            repository.display().to_string()
+        }
+        None => "/absolute/path/to/repo".to_string(),
+    };
+    guidance.push_str(&format!(
+        "\n{{\"name\": \"index_repository\", \"arguments\": {{\"path\": {}, \"session\": {}}}}}",
+        serde_json::Value::from(path),
+        serde_json::Value::from(missing)
+    ));
+    guidance
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_list_dir_warning_formatting() {
-        let warning = build_list_dir_warning(500, 5605, "istio");
+        let warning = build_list_dir_warning(500, 5605, LIST_DIR_MAX_LIMIT, "istio");
 
         // Verify key information is present
         assert!(warning.contains("OUTPUT TRUNCATED"));
@@ -142,7 +454,7 @@ mod tests {
 
     #[test]
     fn test_list_dir_warning_with_small_truncation() {
-        let warning = build_list_dir_warning(100, 150, "small-repo");
+        let warning = build_list_dir_warning(100, 150, LIST_DIR_MAX_LIMIT, "small-repo");
 
         assert!(warning.contains("100 of 150 files"));
         assert!(warning.contains("50 files")); // not shown
@@ -177,15 +489,187 @@ mod tests {
         assert!(warning.contains("0.0%"));
     }
 
+    fn sizes(entries: &[(usize, Option<usize>)]) -> Vec<EntrySize> {
+        entries
+            .iter()
+            .map(|&(full, compact)| EntrySize { full, compact })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes
+        assert_eq!(estimate_tokens("ééé"), 1);
+    }
+
+    #[test]
+    fn test_plan_within_budget_keeps_everything() {
+        let plan = ResponseBudget::new(1000).plan(100, &sizes(&[(300, Some(50)), (300, None)]));
+
+        assert_eq!(plan.kept(), 2);
+        assert!(!plan.is_truncated());
+    }
+
+    #[test]
+    fn test_plan_drops_context_before_entries() {
+        // 100 + 4 * 300 = 1300; compacting the last two saves 500
+        let entries = sizes(&[(300, Some(50)); 4]);
+        let plan = ResponseBudget::new(950).plan(100, &entries);
+
+        assert_eq!(plan.kept(), 4);
+        assert_eq!(plan.compacted(), 2);
+        assert!(!plan.is_compacted(0) && !plan.is_compacted(1));
+        assert!(plan.is_compacted(2) && plan.is_compacted(3));
+        assert_eq!(plan.dropped(), 0);
+    }
+
+    #[test]
+    fn test_plan_drops_lowest_priority_entries() {
+        let entries = sizes(&[(300, Some(200)); 4]);
+        let plan = ResponseBudget::new(600).plan(100, &entries);
+
+        // All compacted (900), then three dropped until it fits in 450
+        assert_eq!(plan.kept(), 1);
+        assert_eq!(plan.dropped(), 3);
+        assert!(plan.is_compacted(0));
+        assert!(!plan.is_kept(1));
+    }
+
+    #[test]
+    fn test_plan_ignores_entries_without_context() {
+        let entries = sizes(&[(300, None), (300, None)]);
+        let plan = ResponseBudget::new(500).plan(0, &entries);
+
+        assert_eq!(plan.compacted(), 0);
+        assert_eq!(plan.kept(), 1);
+    }
+
+    #[test]
+    fn test_truncation_footer() {
+        let footer = build_truncation_footer(
+            &ResponseBudget::new(20_000),
+            &["3 results not shown".to_string()],
+            "narrow the query",
+        );
+
+        assert!(footer.contains("RESPONSE TRUNCATED"));
+        assert!(footer.contains("~20000-token response budget"));
+        assert!(footer.contains("- 3 results not shown\n"));
+        assert!(footer.contains("**To see the rest:** narrow the query"));
+        assert!(estimate_tokens(&footer) < FOOTER_RESERVE_TOKENS);
+    }
+
     #[test]
     fn test_constants_are_reasonable() {
         assert_eq!(MCP_TOKEN_LIMIT, 25_000);
         assert_eq!(LIST_DIR_DEFAULT_LIMIT, 100);
+        assert_eq!(LIST_DIR_COLUMNS_DEFAULT_LIMIT, 40);
         assert_eq!(LIST_DIR_MAX_LIMIT, 500);
         assert_eq!(READ_FILE_MAX_CHARS, 20_000);
+        assert_eq!(RESPONSE_TOKEN_BUDGET, 20_000);
 
         // Verify safety margins
-        assert!(LIST_DIR_MAX_LIMIT * 30 / 4 < MCP_TOKEN_LIMIT);
-        assert!(READ_FILE_MAX_CHARS / 4 < MCP_TOKEN_LIMIT / 2);
+        const { assert!(LIST_DIR_MAX_LIMIT * 30 / 4 < MCP_TOKEN_LIMIT) };
+        const { assert!(READ_FILE_MAX_CHARS / 4 < MCP_TOKEN_LIMIT / 2) };
+    }
+
+    fn session_ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("backand"), &chars("backend")), 1);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+    }
+
+    #[test]
+    fn test_suggest_sessions_close_matches() {
+        let sessions = session_ids(&["frontend", "backend-v2", "backend", "docs"]);
+
+        assert_eq!(
+            suggest_sessions("backand", &sessions),
+            vec!["backend", "backend-v2"]
+        );
+        assert_eq!(suggest_sessions("Docs", &sessions), vec!["docs"]);
+        assert_eq!(
+            suggest_sessions("back", &sessions),
+            vec!["backend", "backend-v2"]
+        );
+    }
+
+    #[test]
+    fn test_suggest_sessions_no_matches() {
+        let sessions = session_ids(&["frontend", "backend"]);
+
+        assert!(suggest_sessions("kubernetes", &sessions).is_empty());
+        assert!(suggest_sessions("", &sessions).is_empty());
+        assert!(suggest_sessions("backend", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_sessions_capped() {
+        let sessions = session_ids(&["api-1", "api-2", "api-3", "api-4", "api-5"]);
+
+        let suggestions = suggest_sessions("api", &sessions);
+        assert_eq!(suggestions.len(), MAX_SESSION_SUGGESTIONS);
+        assert_eq!(suggestions, vec!["api-1", "api-2", "api-3"]);
+    }
+
+    #[test]
+    fn test_session_not_found_hint() {
+        let sessions = session_ids(&["frontend", "backend"]);
+        assert_eq!(
+            session_not_found_hint("backand", &sessions),
+            "\nDid you mean: backend?\nAvailable sessions: backend, frontend"
+        );
+        assert_eq!(
+            session_not_found_hint("zzz", &sessions),
+            "\nAvailable sessions: backend, frontend"
+        );
+        assert_eq!(
+            session_not_found_hint("zzz", &[]),
+            "\nNo sessions have been indexed yet."
+        );
+
+        // Large installations only get the suggestions
+        let many: Vec<String> = (0..SESSION_LIST_THRESHOLD)
+            .map(|i| format!("repo-{i}"))
+            .collect();
+        let hint = session_not_found_hint("repo-O", &many);
+        assert!(hint.starts_with("\nDid you mean: repo-0"));
+        assert!(!hint.contains("Available sessions"));
+        assert!(session_not_found_hint("zzz", &many).is_empty());
+    }
+
+    #[test]
+    fn test_no_sessions_guidance() {
+        let guidance = no_sessions_guidance("myproject", None);
+        assert!(guidance.starts_with("\nNo sessions have been indexed yet."));
+        assert!(!guidance.contains("Repository detected"));
+        assert!(guidance.ends_with(
+            r#"{"name": "index_repository", "arguments": {"path": "/absolute/path/to/repo", "session": "myproject"}}"#
+        ));
+
+        let guidance = no_sessions_guidance("myproject", Some(Path::new("/src/my project")));
+        assert!(guidance.contains("\nRepository detected: /src/my project\n"));
+        assert!(guidance.contains(r#""path": "/src/my project""#));
+    }
+
+    #[test]
+    fn test_detect_repository() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert_eq!(detect_repository(temp.path()), None);
+
+        std::fs::create_dir(temp.path().join(".git")).unwrap();
+        assert_eq!(
+            detect_repository(temp.path()),
+            Some(temp.path().to_path_buf())
+        );
     }
 }
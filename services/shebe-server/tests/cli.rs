@@ -6,9 +6,18 @@
 //! Test organization mirrors the CLI commands:
 //! - search: search-code command
 //! - session: list/info/delete/reindex commands
+//! - collection: create/update/delete-collection commands
 //! - index: index-repository command
+//! - exit_codes: process exit codes, --quiet, --first, --paths-only and
+//!   --format ndjson (runs the binary)
+//! - export: --output files for search-code and find-references
 //! - references: find-references command
+//! - repl: interactive search loop
+//! - self_test: self-test command
+//! - tree: list-tree command
+//! - diff: diff-sessions command
 //! - config: show-config command
+//! - defaults: init and config get/set/unset (runs the binary)
 //! - info: get-server-info command
 //! - output: output formatting helpers
 
@@ -16,11 +25,20 @@ mod common;
 
 // CLI submodules - tests/cli/ directory
 mod cli {
+    pub mod test_collection;
+    pub mod test_compare;
+    pub mod test_defaults;
+    pub mod test_diff;
+    pub mod test_exit_codes;
+    pub mod test_export;
     pub mod test_helpers;
     pub mod test_index;
     pub mod test_info;
     pub mod test_output;
     pub mod test_references;
+    pub mod test_repl;
     pub mod test_search;
+    pub mod test_self_test;
     pub mod test_session;
+    pub mod test_tree;
 }
@@ -0,0 +1,119 @@
+//! Tests for collection CLI commands
+//!
+//! Tests the collection command handlers:
+//! - create-collection: Name a group of sessions
+//! - update-collection: Add and remove member sessions
+//! - delete-collection: Delete the name, keeping the sessions
+//! - list-sessions --collection and delete-session of a member
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
+use shebe::cli::commands::collection::{
+    execute_create, execute_delete, execute_update, CreateArgs, DeleteArgs, UpdateArgs,
+};
+use shebe::cli::commands::session::{self, ListArgs};
+use shebe::cli::OutputFormat;
+use shebe::core::error::ShebeError;
+
+/// Test creating, updating and deleting a collection
+#[tokio::test]
+async fn test_collection_lifecycle() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("lib.rs", "pub fn shared() {}")]);
+    setup_indexed_session(&services, repo.path(), "api").await;
+    setup_indexed_session(&services, repo.path(), "web").await;
+
+    let args = CreateArgs {
+        name: "platform".to_string(),
+        sessions: vec!["api".to_string()],
+    };
+    execute_create(args, &services, OutputFormat::Human)
+        .await
+        .unwrap();
+
+    let args = UpdateArgs {
+        name: "platform".to_string(),
+        add: vec!["web".to_string()],
+        remove: Vec::new(),
+    };
+    execute_update(args, &services, OutputFormat::Json)
+        .await
+        .unwrap();
+    assert_eq!(
+        services
+            .storage
+            .get_collection("platform")
+            .unwrap()
+            .sessions,
+        vec!["api".to_string(), "web".to_string()]
+    );
+
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: Some("platform".to_string()),
+    };
+    session::execute_list(args, &services, OutputFormat::Human)
+        .await
+        .unwrap();
+
+    let args = DeleteArgs {
+        name: "platform".to_string(),
+    };
+    execute_delete(args, &services, OutputFormat::Human)
+        .await
+        .unwrap();
+    assert!(services.storage.list_collections().unwrap().is_empty());
+    assert!(services.storage.session_exists("api"));
+}
+
+/// Test that every member must be an existing session
+#[tokio::test]
+async fn test_create_collection_missing_session() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("lib.rs", "pub fn shared() {}")]);
+    setup_indexed_session(&services, repo.path(), "api").await;
+
+    let args = CreateArgs {
+        name: "platform".to_string(),
+        sessions: vec!["api".to_string(), "mobile".to_string()],
+    };
+    let error = execute_create(args, &services, OutputFormat::Human)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ShebeError>(),
+        Some(ShebeError::SessionNotFound(s)) if s == "mobile"
+    ));
+    assert!(services.storage.list_collections().unwrap().is_empty());
+}
+
+/// Test that deleting a member session removes it from the collection
+#[tokio::test]
+async fn test_delete_session_leaves_collection() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("lib.rs", "pub fn shared() {}")]);
+    setup_indexed_session(&services, repo.path(), "api").await;
+    setup_indexed_session(&services, repo.path(), "web").await;
+    services
+        .storage
+        .create_collection("platform", &["api".to_string(), "web".to_string()])
+        .unwrap();
+
+    let args = session::DeleteArgs {
+        session: "web".to_string(),
+        force: true,
+        purge_clone: false,
+    };
+    session::execute_delete(args, &services, OutputFormat::Json)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        services
+            .storage
+            .get_collection("platform")
+            .unwrap()
+            .sessions,
+        vec!["api".to_string()]
+    );
+}
@@ -0,0 +1,174 @@
+//! Tests for compare-configs CLI command
+//!
+//! Tests comparing two chunk sizes on a small repository:
+//! - Human and JSON output
+//! - Report structure and differing metrics
+//! - Temporary sessions deleted after success and after errors
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo};
+use shebe::cli::commands::compare::{execute, CompareArgs};
+use shebe::cli::OutputFormat;
+use shebe::core::compare::{is_comparison_session, CompareRequest, ConfigVariant};
+use shebe::core::services::Services;
+
+fn fixture() -> tempfile::TempDir {
+    let handler = "fn handle_request(req: Request) -> Response {\n    \
+                   authenticate(&req);\n    route(req)\n}\n"
+        .repeat(20);
+    create_test_repo(&[
+        ("src/server.rs", &handler),
+        (
+            "src/auth.rs",
+            "fn authenticate(req: &Request) { check_token(req); }\n",
+        ),
+        (
+            "src/router.rs",
+            "fn route(req: Request) -> Response { dispatch(req) }\n",
+        ),
+        (
+            "README.md",
+            "# Server\nHandles requests with token authentication.\n",
+        ),
+    ])
+}
+
+fn variant(spec: &str) -> ConfigVariant {
+    spec.parse().unwrap()
+}
+
+fn no_comparison_sessions(services: &Services) -> bool {
+    services
+        .storage
+        .list_session_ids()
+        .unwrap()
+        .iter()
+        .all(|id| !is_comparison_session(id))
+}
+
+/// Test compare output in both formats and temp session cleanup
+#[tokio::test]
+async fn test_compare_configs_human_and_json() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = fixture();
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = CompareArgs {
+            path: repo.path().to_path_buf(),
+            a: variant("chunk_size=100,overlap=10"),
+            b: variant("chunk_size=2000,overlap=100"),
+            queries: vec!["authenticate".to_string(), "route".to_string()],
+            queries_file: None,
+            k: 5,
+        };
+        let result = execute(args, &services, format).await;
+        assert!(result.is_ok(), "compare-configs failed: {:?}", result.err());
+    }
+    assert!(no_comparison_sessions(&services));
+}
+
+/// Test the report of two obviously different chunk sizes
+#[test]
+fn test_compare_configs_report() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = fixture();
+
+    let comparison = services
+        .compare_configs(&CompareRequest {
+            path: repo.path().to_path_buf(),
+            a: variant("chunk_size=100,overlap=10"),
+            b: variant("chunk_size=2000,overlap=100"),
+            queries: vec![
+                "authenticate".to_string(),
+                "token".to_string(),
+                "nonexistentterm".to_string(),
+            ],
+            k: 5,
+            defaults: None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        (comparison.a.chunk_size, comparison.b.chunk_size),
+        (100, 2000)
+    );
+    assert_eq!(comparison.a.files_indexed, 4);
+    assert_eq!(comparison.b.files_indexed, 4);
+    assert!(comparison.a.chunks_created > comparison.b.chunks_created);
+    assert_eq!(comparison.queries.len(), 3);
+    assert_eq!(comparison.k, 5);
+
+    let authenticate = &comparison.queries[0];
+    assert!(authenticate.a.files.contains(&"src/auth.rs".to_string()));
+    assert!(authenticate.a.top_score.is_some());
+    assert!(authenticate.a.results > authenticate.b.results);
+
+    let missing = &comparison.queries[2];
+    assert_eq!(missing.a.results, 0);
+    assert_eq!(missing.file_overlap, 1.0);
+    assert_eq!(comparison.a.queries_with_results, 2);
+    assert!(comparison.a.scores.count > comparison.b.scores.count);
+
+    let json = serde_json::to_value(&comparison).unwrap();
+    for key in ["repository", "k", "a", "b", "queries", "mean_file_overlap"] {
+        assert!(json.get(key).is_some(), "missing {key}");
+    }
+    assert_eq!(json["a"]["settings"]["chunk_size"], 100);
+    assert!(json["queries"][0]["a"].get("scores").is_none());
+
+    assert!(no_comparison_sessions(&services));
+}
+
+/// Test that a failing query still removes both temporary sessions
+#[test]
+fn test_compare_configs_cleans_up_on_error() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = fixture();
+
+    let result = services.compare_configs(&CompareRequest {
+        path: repo.path().to_path_buf(),
+        a: variant("chunk_size=100,overlap=10"),
+        b: variant("chunk_size=2000"),
+        queries: vec!["nosuchfield:value".to_string()],
+        k: 5,
+        defaults: None,
+    });
+    assert!(result.is_err());
+    assert!(no_comparison_sessions(&services));
+
+    // Invalid settings fail before anything is indexed
+    let result = services.compare_configs(&CompareRequest {
+        path: repo.path().to_path_buf(),
+        a: variant("chunk_size=100"),
+        b: variant("chunk_size=100,overlap=100"),
+        queries: vec!["route".to_string()],
+        k: 5,
+        defaults: None,
+    });
+    assert!(result.unwrap_err().to_string().contains("Overlap (100)"));
+    assert!(no_comparison_sessions(&services));
+}
+
+/// Test queries read from a file and a missing query list
+#[tokio::test]
+async fn test_compare_configs_queries_file() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = fixture();
+    let queries = repo.path().join("queries.txt");
+    std::fs::write(&queries, "# benchmark\nauthenticate\n\nroute\n").unwrap();
+
+    let args = |queries_file| CompareArgs {
+        path: repo.path().to_path_buf(),
+        a: variant("chunk_size=100"),
+        b: variant("chunk_size=500"),
+        queries: Vec::new(),
+        queries_file,
+        k: 3,
+    };
+    let result = execute(args(Some(queries.clone())), &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "compare-configs failed: {:?}", result.err());
+
+    let err = execute(args(None), &services, OutputFormat::Json)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("--query or --queries-file"));
+}
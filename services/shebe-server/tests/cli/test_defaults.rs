@@ -0,0 +1,147 @@
+//! Tests for per-user CLI defaults (`shebe init`, `shebe config`)
+//!
+//! These run the `shebe` binary with HOME pointing into a temp dir, so
+//! the defaults file lands in `$HOME/.config/shebe/cli.toml`.
+
+use super::test_helpers::create_test_repo;
+use assert_cmd::Command;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// `shebe` command with `home` as HOME and no other environment
+fn shebe(home: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("shebe").expect("shebe binary should build");
+    cmd.env_clear().env("NO_COLOR", "1").env("HOME", home);
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_defaults_fill_left_out_options() {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[
+        ("src/auth.rs", "pub fn authenticate(user: &str) {}\n"),
+        ("src/login.rs", "// calls authenticate before login\n"),
+    ]);
+    let other = create_test_repo(&[("lib.rs", "// authenticate elsewhere\n")]);
+    for (session, path) in [("myrepo", repo.path()), ("other", other.path())] {
+        shebe(home.path())
+            .args(["index-repository", "--session", session])
+            .arg(path)
+            .assert()
+            .code(0);
+    }
+
+    // Without defaults the session is required
+    shebe(home.path())
+        .args(["search-code", "authenticate"])
+        .assert()
+        .code(2);
+
+    shebe(home.path())
+        .args(["init", "--session", "myrepo"])
+        .assert()
+        .code(0);
+    assert!(home.path().join(".config/shebe/cli.toml").exists());
+    shebe(home.path())
+        .args(["config", "set", "k", "1"])
+        .assert()
+        .code(0);
+    shebe(home.path())
+        .args(["config", "set", "format", "json"])
+        .assert()
+        .code(0);
+    assert_eq!(
+        stdout(shebe(home.path()).args(["--format", "human", "config", "get", "session"])),
+        "myrepo\n"
+    );
+
+    // Values are checked against the known keys and ranges
+    for (key, value) in [("k", "0"), ("format", "yaml"), ("colour", "always")] {
+        shebe(home.path())
+            .args(["config", "set", key, value])
+            .assert()
+            .code(2);
+    }
+
+    // search-code works without --session, and --verbose says why
+    let output = shebe(home.path())
+        .args(["--verbose", "search-code", "authenticate"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["session"], "myrepo");
+    assert_eq!(json["results"].as_array().unwrap().len(), 1);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Using default session 'myrepo'"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Using default k '1'"), "{stderr}");
+
+    // Explicit options still win
+    let json: serde_json::Value = serde_json::from_str(&stdout(shebe(home.path()).args([
+        "search-code",
+        "authenticate",
+        "--session",
+        "other",
+        "-k",
+        "5",
+    ])))
+    .unwrap();
+    assert_eq!(json["session"], "other");
+    let human =
+        stdout(shebe(home.path()).args(["search-code", "authenticate", "--format", "human"]));
+    assert!(human.contains("in 'myrepo'"), "{human}");
+
+    // show-config lists the defaults next to the server configuration
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(shebe(home.path()).arg("show-config"))).unwrap();
+    assert_eq!(json["config_file"], serde_json::Value::Null);
+    assert_eq!(json["cli_defaults"]["defaults"]["k"], 1);
+    assert!(json["cli_defaults"]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with(".config/shebe/cli.toml"));
+
+    // init does not overwrite without --force
+    shebe(home.path()).arg("init").assert().code(2);
+    shebe(home.path())
+        .args(["config", "unset", "session"])
+        .assert()
+        .code(0);
+    shebe(home.path())
+        .args(["search-code", "authenticate"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_init_stores_format_only_when_given() {
+    let home = TempDir::new().unwrap();
+    let file = home.path().join(".config/shebe/cli.toml");
+
+    shebe(home.path())
+        .args(["init", "--session", "myrepo"])
+        .assert()
+        .code(0);
+    let contents = std::fs::read_to_string(&file).unwrap();
+    assert!(!contents.contains("format"), "{contents}");
+
+    shebe(home.path())
+        .args(["--format", "json", "init", "--force"])
+        .assert()
+        .code(0);
+    let contents = std::fs::read_to_string(&file).unwrap();
+    assert!(contents.contains("format = \"json\""), "{contents}");
+}
@@ -0,0 +1,66 @@
+//! Tests for diff-sessions CLI command
+//!
+//! Tests the diff command handler with real indexed repositories:
+//! - Human and JSON output
+//! - Added, removed and changed files between two sessions
+//! - Session not found errors
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
+use shebe::cli::commands::diff::{execute, DiffArgs};
+use shebe::cli::OutputFormat;
+
+fn diff_args(from: &str, to: &str) -> DiffArgs {
+    DiffArgs {
+        from: from.to_string(),
+        to: to.to_string(),
+        force: false,
+    }
+}
+
+/// Test diff output in both formats
+#[tokio::test]
+async fn test_diff_sessions_human_and_json() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/old.rs", "fn old() {}"),
+    ]);
+    setup_indexed_session(&services, repo.path(), "diff-v1").await;
+
+    std::fs::write(repo.path().join("src/main.rs"), "fn main() { run(); }").unwrap();
+    std::fs::remove_file(repo.path().join("src/old.rs")).unwrap();
+    std::fs::write(repo.path().join("src/new.rs"), "fn new() {}").unwrap();
+    setup_indexed_session(&services, repo.path(), "diff-v2").await;
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(diff_args("diff-v1", "diff-v2"), &services, format).await;
+        assert!(result.is_ok(), "diff-sessions failed: {:?}", result.err());
+    }
+
+    let diff = services
+        .storage
+        .diff_sessions("diff-v1", "diff-v2", false)
+        .unwrap();
+    assert_eq!(diff.added.files[0].path, "src/new.rs");
+    assert_eq!(diff.removed.files[0].path, "src/old.rs");
+    assert_eq!(diff.changed.files[0].path, "src/main.rs");
+    assert_eq!(diff.verdict, "3 files changed since diff-v1");
+}
+
+/// Test diff with a missing session
+#[tokio::test]
+async fn test_diff_sessions_session_not_found() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("main.rs", "fn main() {}")]);
+    setup_indexed_session(&services, repo.path(), "diff-only").await;
+
+    let result = execute(
+        diff_args("diff-only", "missing"),
+        &services,
+        OutputFormat::Human,
+    )
+    .await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("not found"), "Unexpected error: {err}");
+}
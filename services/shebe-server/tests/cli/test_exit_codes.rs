@@ -0,0 +1,499 @@
+//! Tests for CLI exit codes, --quiet, shell-friendly output and NDJSON
+//!
+//! These run the `shebe` binary so the process exit code is checked end
+//! to end. Every XDG directory points into a temp dir.
+
+use super::test_helpers::create_test_repo;
+use assert_cmd::Command;
+use shebe::cli::output::{Event, SummaryEvent};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// `shebe` command isolated to `home`
+fn shebe(home: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("shebe").expect("shebe binary should build");
+    cmd.env_clear()
+        .env("NO_COLOR", "1")
+        .env("SHEBE_CONFIG_DIR", home.join("config"))
+        .env("SHEBE_DATA_DIR", home.join("data"))
+        .env("SHEBE_STATE_DIR", home.join("state"))
+        .env("SHEBE_CACHE_DIR", home.join("cache"));
+    cmd
+}
+
+/// Temp home with `repo` indexed as session "exit-codes"
+fn indexed_home() -> (TempDir, TempDir) {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[(
+        "src/lib.rs",
+        "pub fn authenticate(user: &str) -> bool {\n    !user.is_empty()\n}\n",
+    )]);
+    shebe(home.path())
+        .args(["index-repository", "--session", "exit-codes"])
+        .arg(repo.path())
+        .assert()
+        .code(0);
+    (home, repo)
+}
+
+#[test]
+fn test_exit_code_success() {
+    let (home, _repo) = indexed_home();
+
+    shebe(home.path())
+        .args(["search-code", "authenticate", "--session", "exit-codes"])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn test_exit_code_invalid_args() {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[("main.rs", "fn main() {}\n")]);
+
+    // Rejected by clap
+    shebe(home.path())
+        .args(["search-code", "query", "--no-such-flag"])
+        .assert()
+        .code(2);
+
+    // Rejected by command validation
+    shebe(home.path())
+        .args(["index-repository", "--session", "bad", "--chunk-size", "5"])
+        .arg(repo.path())
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_exit_code_session_not_found() {
+    let home = TempDir::new().unwrap();
+
+    shebe(home.path())
+        .args(["search-code", "query", "--session", "missing"])
+        .assert()
+        .code(3);
+    shebe(home.path())
+        .args(["get-session-info", "missing"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_exit_code_index_error() {
+    let (home, _repo) = indexed_home();
+
+    // Corrupt the session's index so opening it fails
+    let session_dir = walkdir::WalkDir::new(home.path().join("data"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|entry| entry.file_name() == "exit-codes")
+        .expect("session directory should exist")
+        .into_path();
+    std::fs::remove_dir_all(session_dir.join("tantivy")).unwrap();
+    std::fs::write(session_dir.join("tantivy"), "not an index").unwrap();
+
+    shebe(home.path())
+        .args(["search-code", "authenticate", "--session", "exit-codes"])
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_exit_code_fail_on_empty() {
+    let (home, _repo) = indexed_home();
+
+    // Without the flag an empty result is still a success
+    shebe(home.path())
+        .args(["search-code", "nonexistent", "--session", "exit-codes"])
+        .assert()
+        .code(0);
+
+    shebe(home.path())
+        .args([
+            "search-code",
+            "nonexistent",
+            "--session",
+            "exit-codes",
+            "--fail-on-empty",
+        ])
+        .assert()
+        .code(5);
+    shebe(home.path())
+        .args([
+            "find-references",
+            "nonexistent",
+            "--session",
+            "exit-codes",
+            "--fail-on-empty",
+        ])
+        .assert()
+        .code(5);
+}
+
+#[test]
+fn test_quiet_keeps_json_output() {
+    let (home, _repo) = indexed_home();
+
+    let output = shebe(home.path())
+        .args([
+            "--quiet",
+            "--format",
+            "json",
+            "search-code",
+            "nonexistent",
+            "--session",
+            "exit-codes",
+            "--fail-on-empty",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+    assert_eq!(json["total_results"], 0);
+}
+
+#[test]
+fn test_profiles_keep_sessions_apart() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join("config")).unwrap();
+    std::fs::write(
+        home.path().join("config/config.toml"),
+        "[profiles.work]\n\n[profiles.oss]\nstorage_root = \"oss-root\"\n",
+    )
+    .unwrap();
+    let repo = create_test_repo(&[("main.rs", "fn main() {}\n")]);
+
+    shebe(home.path())
+        .args(["--profile", "work", "index-repository", "--session", "w"])
+        .arg(repo.path())
+        .assert()
+        .code(0);
+    assert!(home.path().join("data/profiles/work/sessions/w").is_dir());
+
+    let listed = |profile: &str| {
+        let output = shebe(home.path())
+            .args(["--profile", profile, "--format", "json", "list-sessions"])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert!(listed("work").contains("\"w\""));
+    assert!(!listed("oss").contains("\"w\""));
+
+    // SHEBE_PROFILE selects the profile when --profile is absent
+    let output = shebe(home.path())
+        .env("SHEBE_PROFILE", "oss")
+        .args(["--format", "json", "show-config"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"profile\": \"oss\""));
+    assert!(stdout.contains("oss-root"));
+}
+
+#[test]
+fn test_exit_code_unknown_profile() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join("config")).unwrap();
+    std::fs::write(
+        home.path().join("config/config.toml"),
+        "[profiles.work]\n[profiles.oss]\n",
+    )
+    .unwrap();
+
+    let output = shebe(home.path())
+        .args(["--profile", "home", "list-sessions"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unknown profile 'home' (available: oss, work)"));
+}
+
+#[test]
+fn test_first_prints_only_path_and_line() {
+    let (home, _repo) = indexed_home();
+
+    let output = shebe(home.path())
+        .args(["search-code", "empty", "--session", "exit-codes", "--first"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.ends_with("src/lib.rs:2\n"), "{stdout}");
+}
+
+#[test]
+fn test_first_without_hit_prints_nothing() {
+    let (home, _repo) = indexed_home();
+
+    let output = shebe(home.path())
+        .args([
+            "search-code",
+            "nonexistent",
+            "--session",
+            "exit-codes",
+            "--first",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("No results found"));
+}
+
+#[test]
+fn test_first_json() {
+    let (home, _repo) = indexed_home();
+
+    let output = shebe(home.path())
+        .args([
+            "--format",
+            "json",
+            "search-code",
+            "authenticate",
+            "--session",
+            "exit-codes",
+            "--first",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "one compact object: {stdout}");
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(json["file"].as_str().unwrap().ends_with("src/lib.rs"));
+    assert_eq!(json["line"], 1);
+    assert_eq!(json["offset"], 0);
+}
+
+#[test]
+fn test_paths_only() {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[
+        ("src/auth.rs", "fn login() { check_token(); }\n"),
+        ("src/token.rs", "fn check_token() {}\n"),
+        ("README.md", "nothing relevant\n"),
+    ]);
+    shebe(home.path())
+        .args(["index-repository", "--session", "paths"])
+        .arg(repo.path())
+        .assert()
+        .code(0);
+
+    let output = shebe(home.path())
+        .args([
+            "search-code",
+            "check_token",
+            "--session",
+            "paths",
+            "--paths-only",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].ends_with("src/auth.rs"));
+    assert!(lines[1].ends_with("src/token.rs"));
+}
+
+#[test]
+fn test_closed_pipe_exits_cleanly() {
+    let (home, _repo) = indexed_home();
+
+    for flags in [&["--paths-only"][..], &["--first"], &["--format", "ndjson"]] {
+        // As `shebe search-code ... | head -1` once head has exited
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("shebe"))
+            .env_clear()
+            .env("NO_COLOR", "1")
+            .env("SHEBE_CONFIG_DIR", home.path().join("config"))
+            .env("SHEBE_DATA_DIR", home.path().join("data"))
+            .env("SHEBE_STATE_DIR", home.path().join("state"))
+            .env("SHEBE_CACHE_DIR", home.path().join("cache"))
+            .args(["search-code", "authenticate", "--session", "exit-codes"])
+            .args(flags)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        drop(child.stdout.take());
+
+        let output = child.wait_with_output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(0), "{flags:?}: {stderr}");
+        assert!(stderr.is_empty(), "{flags:?}: {stderr}");
+    }
+}
+
+/// Parse every stdout line as an NDJSON event; the last must be the summary
+fn ndjson_events(stdout: &[u8]) -> (Vec<Event>, SummaryEvent) {
+    let stdout = String::from_utf8(stdout.to_vec()).unwrap();
+    assert!(stdout.ends_with('\n'), "{stdout}");
+    let mut events: Vec<Event> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{e}: {line}")))
+        .collect();
+    match events.pop() {
+        Some(Event::Summary(summary)) => (events, summary),
+        other => panic!("expected a closing summary, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_ndjson_search_results() {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[
+        ("src/auth.rs", "fn login() {\n    authenticate();\n}\n"),
+        (
+            "src/lib.rs",
+            "pub fn authenticate() -> bool {\n    true\n}\n",
+        ),
+        ("src/main.rs", "fn main() {\n    authenticate();\n}\n"),
+    ]);
+    shebe(home.path())
+        .args(["index-repository", "--session", "ndjson"])
+        .arg(repo.path())
+        .assert()
+        .code(0);
+
+    let output = shebe(home.path())
+        .args(["--format", "ndjson", "search-code", "authenticate"])
+        .args(["--session", "ndjson"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let (events, summary) = ndjson_events(&output.stdout);
+    assert_eq!(events.len(), 3);
+    assert_eq!(summary.events, events.len());
+    assert_eq!(summary.command, "search-code");
+    assert_eq!(summary.session, "ndjson");
+    for (i, event) in events.iter().enumerate() {
+        let Event::Result(result) = event else {
+            panic!("expected a result event, got {event:?}");
+        };
+        assert_eq!(result.rank, i + 1);
+        let line = if result.file.ends_with("src/lib.rs") {
+            1
+        } else {
+            2
+        };
+        assert_eq!(result.line, Some(line), "{}", result.file);
+        assert!(result.text.as_deref().unwrap().contains("authenticate"));
+    }
+}
+
+#[test]
+fn test_ndjson_references_for_several_symbols() {
+    let home = TempDir::new().unwrap();
+    let repo = create_test_repo(&[
+        (
+            "src/cart.rs",
+            "pub fn add_item() {}\npub fn remove_item() {}\n",
+        ),
+        (
+            "src/main.rs",
+            "fn main() {\n    add_item();\n    remove_item();\n    add_item();\n}\n",
+        ),
+    ]);
+    shebe(home.path())
+        .args(["index-repository", "--session", "ndjson-refs"])
+        .arg(repo.path())
+        .assert()
+        .code(0);
+
+    let output = shebe(home.path())
+        .args([
+            "--format",
+            "ndjson",
+            "find-references",
+            "add_item",
+            "remove_item",
+        ])
+        .args(["--session", "ndjson-refs"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let (events, summary) = ndjson_events(&output.stdout);
+    assert_eq!(summary.events, events.len());
+    assert_eq!(summary.command, "find-references");
+    let count = |symbol: &str| {
+        events
+            .iter()
+            .filter(|e| matches!(e, Event::Reference(r) if r.symbol == symbol))
+            .count()
+    };
+    assert_eq!(count("add_item") + count("remove_item"), events.len());
+    assert!(count("add_item") >= 2);
+    assert!(count("remove_item") >= 1);
+}
+
+#[test]
+fn test_ndjson_index_progress() {
+    let home = TempDir::new().unwrap();
+    let files: Vec<(String, String)> = (0..150)
+        .map(|i| (format!("src/f{i}.rs"), format!("fn f{i}() {{}}\n")))
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    let repo = create_test_repo(&files);
+
+    let output = shebe(home.path())
+        .args([
+            "--format",
+            "ndjson",
+            "index-repository",
+            "--session",
+            "ndjson-index",
+        ])
+        .arg(repo.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let (events, summary) = ndjson_events(&output.stdout);
+    assert_eq!(summary.events, events.len());
+    assert_eq!(summary.files_indexed, Some(150));
+    assert!(summary.chunks_created.unwrap() >= 150);
+    let processed: Vec<usize> = events
+        .iter()
+        .map(|e| match e {
+            Event::Progress(p) => p.files_processed,
+            other => panic!("expected a progress event, got {other:?}"),
+        })
+        .collect();
+    assert!(processed.windows(2).all(|w| w[0] < w[1]), "{processed:?}");
+    assert_eq!(processed.last(), Some(&150));
+}
+
+#[test]
+fn test_ndjson_rejected_elsewhere() {
+    let home = TempDir::new().unwrap();
+
+    shebe(home.path())
+        .args(["--format", "ndjson", "list-sessions"])
+        .assert()
+        .code(2)
+        .stdout("");
+    shebe(home.path())
+        .args(["--format", "ndjson", "search-code", "query", "--first"])
+        .args(["--session", "missing"])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,212 @@
+//! Tests for exporting results with --output
+//!
+//! search-code and find-references write their full result sets to a
+//! file; each format must parse back with one record per result.
+
+use crate::cli::test_helpers::{
+    create_cli_test_services, create_test_repo, references_test_files, setup_indexed_session,
+};
+use serde_json::Value;
+use shebe::cli::commands::references::{self, ReferencesArgs, SymbolTypeArg};
+use shebe::cli::commands::search::{self, SearchArgs};
+use shebe::cli::export::{ExportArgs, ExportFormat};
+use shebe::cli::{CliError, OutputFormat};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Files whose chunks contain commas, quotes and line breaks
+fn audit_files() -> Vec<(&'static str, &'static str)> {
+    vec![
+        (
+            "src/a.rs",
+            "// TODO: handle errors, retries and \"timeouts\"\nfn a() {}\n",
+        ),
+        ("src/b.rs", "fn b() {\n    // TODO, later\n}\n"),
+        ("src/c.rs", "/* TODO | pipes | too */\nfn c() {}\n"),
+        ("src/d.rs", "fn d() {} // nothing to do here\n"),
+    ]
+}
+
+fn search_args(output: PathBuf) -> SearchArgs {
+    SearchArgs {
+        query: "TODO".to_string(),
+        session: "audit".to_string(),
+        limit: Some(500),
+        files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs {
+            output: Some(output),
+            ..ExportArgs::default()
+        },
+    }
+}
+
+/// Export a TODO search of the audit files to `name` in a tempdir
+async fn export_search(name: &str, configure: impl FnOnce(&mut ExportArgs)) -> (TempDir, PathBuf) {
+    let (services, storage) = create_cli_test_services();
+    let repo = create_test_repo(&audit_files());
+    setup_indexed_session(&services, repo.path(), "audit").await;
+
+    let path = storage.path().join(name);
+    let mut args = search_args(path.clone());
+    configure(&mut args.export);
+    search::execute(args, &services, OutputFormat::Human)
+        .await
+        .expect("export should succeed");
+    (storage, path)
+}
+
+/// Records of a CSV file, honoring quoted fields with commas and newlines
+fn parse_csv(path: &Path) -> Vec<Vec<String>> {
+    let text = std::fs::read_to_string(path).unwrap();
+    let mut records = Vec::new();
+    let (mut record, mut field) = (Vec::new(), String::new());
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    assert!(!quoted, "unterminated quoted field");
+    records
+}
+
+#[tokio::test]
+async fn test_search_export_csv() {
+    let (_storage, path) = export_search("report.csv", |_| {}).await;
+    let records = parse_csv(&path);
+
+    assert_eq!(
+        records[0],
+        ["rank", "file", "line", "offset", "score", "snippet"]
+    );
+    let rows = &records[1..];
+    assert_eq!(rows.len(), 3);
+    for (i, row) in rows.iter().enumerate() {
+        assert_eq!(row.len(), 6, "{row:?}");
+        assert_eq!(row[0], (i + 1).to_string());
+        assert!(row[2].parse::<usize>().is_ok(), "line: {row:?}");
+        assert!(row[5].contains("TODO"), "snippet: {row:?}");
+    }
+    let a = rows.iter().find(|row| row[1].ends_with("a.rs")).unwrap();
+    assert!(a[5].contains("errors, retries and \"timeouts\"\nfn a()"));
+}
+
+#[tokio::test]
+async fn test_search_export_json_and_ndjson() {
+    let (_storage, path) = export_search("report.json", |_| {}).await;
+    let rows: Vec<Value> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["rank"], 1);
+    assert!(rows[0]["snippet"].as_str().unwrap().contains("TODO"));
+
+    let (_storage, path) = export_search("report.out", |export| {
+        export.output_format = Some(ExportFormat::Json);
+        export.ndjson = true;
+    })
+    .await;
+    let lines: Vec<Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2]["rank"], 3);
+}
+
+#[tokio::test]
+async fn test_search_export_markdown() {
+    let (_storage, path) = export_search("report.md", |_| {}).await;
+    let text = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "| rank | file | line | offset | score | snippet |"
+    );
+    assert_eq!(lines.len(), 2 + 3, "{text}");
+    assert!(text.contains("TODO \\| pipes \\| too */<br>fn c()"));
+}
+
+#[tokio::test]
+async fn test_export_rejects_unknown_extension() {
+    let (services, storage) = create_cli_test_services();
+    let repo = create_test_repo(&audit_files());
+    setup_indexed_session(&services, repo.path(), "audit").await;
+
+    let path = storage.path().join("report.txt");
+    let error = search::execute(search_args(path.clone()), &services, OutputFormat::Human)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CliError>(),
+        Some(CliError::InvalidArgs(_))
+    ));
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_references_export_csv() {
+    let (services, storage) = create_cli_test_services();
+    let repo = create_test_repo(&references_test_files());
+    setup_indexed_session(&services, repo.path(), "refs-export").await;
+
+    let path = storage.path().join("refs.csv");
+    let args = ReferencesArgs {
+        symbols: vec!["helper_function".to_string()],
+        session: "refs-export".to_string(),
+        symbol_type: SymbolTypeArg::Any,
+        defined_in: None,
+        include_definition: true,
+        context_lines: 1,
+        max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs {
+            output: Some(path.clone()),
+            ..ExportArgs::default()
+        },
+    };
+    references::execute(args, &services, OutputFormat::Json)
+        .await
+        .unwrap();
+
+    let records = parse_csv(&path);
+    let rows = &records[1..];
+    assert!(!rows.is_empty());
+    for row in rows {
+        assert_eq!(row.len(), 6, "{row:?}");
+        assert!(row[2].parse::<usize>().unwrap() >= 1);
+        assert!(row[5].contains("helper_function"), "{row:?}");
+    }
+}
@@ -223,7 +223,7 @@ mod tests {
     fn test_create_cli_test_services() {
         let (services, _temp) = create_cli_test_services();
         // Verify services are created and config is accessible
-        assert!(services.config.indexing.chunk_size > 0);
+        assert!(services.config.current().indexing.chunk_size > 0);
     }
 
     #[tokio::test]
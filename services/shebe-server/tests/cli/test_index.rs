@@ -4,11 +4,21 @@
 //! - Indexing a new repository
 //! - Force re-indexing over existing session
 //! - Custom include/exclude patterns
+//! - Language presets (--lang)
+//! - Duplicate file skipping (--dedupe)
+//! - Dry runs (--dry-run)
+//! - Per-stage timings (--profile-indexing)
+//! - Repository-local settings (.shebe.toml, .shebeignore)
+//! - Git URL validation and clone cleanup
+//! - Dangerous paths (--allow-dangerous-path)
+//! - Explicit file lists (--files-from, --files-from0)
+//! - File paths and repository root detection (--detect-root)
 //! - Error cases (invalid path, empty directory)
 
 use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
 use shebe::cli::commands::index::{execute, IndexArgs};
 use shebe::cli::OutputFormat;
+use shebe::core::indexer::repo_config::ConfigSource;
 
 /// Test indexing a new repository
 #[tokio::test]
@@ -22,13 +32,31 @@ async fn test_index_new_session_human() {
 
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "new-index".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -48,13 +76,31 @@ async fn test_index_new_session_json() {
 
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "new-index-json".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Json).await;
@@ -74,13 +120,31 @@ async fn test_index_force_reindex() {
     // Now force re-index
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "force-test".to_string(),
         force: true,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -100,19 +164,107 @@ async fn test_index_custom_patterns() {
 
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "patterns-test".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec!["**/*.rs".to_string()],
         exclude: vec!["**/tests/**".to_string()],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Index with patterns should succeed");
 }
 
+/// Test --lang presets combined with --include
+#[tokio::test]
+async fn test_index_language_presets() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("docs/guide.md", "# Guide"),
+        ("app.py", "print(1)"),
+        ("Makefile", "all:"),
+    ]);
+
+    let args = |session: &str, languages: &[&str]| IndexArgs {
+        path: repo.path().to_path_buf(),
+        git_ref: None,
+        session: session.to_string(),
+        force: false,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: None,
+        overlap: None,
+        include: vec!["Makefile".to_string()],
+        exclude: vec![],
+        languages: languages.iter().map(|s| s.to_string()).collect(),
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
+    };
+
+    execute(
+        args("lang", &["rust", "docs"]),
+        &services,
+        OutputFormat::Json,
+    )
+    .await
+    .unwrap();
+    let metadata = services.storage.get_session_metadata("lang").unwrap();
+    assert_eq!(metadata.files_indexed, 3);
+    assert_eq!(metadata.config.languages, ["rust", "docs"]);
+    assert_eq!(metadata.config.include_patterns[0], "Makefile");
+    assert!(metadata
+        .config
+        .include_patterns
+        .contains(&"**/*.md".to_string()));
+
+    let error = execute(
+        args("lang-bad", &["klingon"]),
+        &services,
+        OutputFormat::Json,
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("Available: c, config"),
+        "{error}"
+    );
+    assert!(!services.storage.session_exists("lang-bad"));
+}
+
 /// Test indexing with custom chunk size
 #[tokio::test]
 async fn test_index_custom_chunk_size() {
@@ -121,13 +273,31 @@ async fn test_index_custom_chunk_size() {
 
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "chunk-size-test".to_string(),
         force: false,
-        chunk_size: 256,
-        overlap: 32,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(256),
+        overlap: Some(32),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -137,6 +307,62 @@ async fn test_index_custom_chunk_size() {
     );
 }
 
+/// Test that .shebe.toml defaults and .shebeignore apply without arguments
+#[tokio::test]
+async fn test_index_repo_local_settings() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("generated/api.rs", "fn generated() {}"),
+        (".shebeignore", "generated/\n"),
+        (
+            ".shebe.toml",
+            "chunk_size = 800\ninclude_patterns = [\"*.rs\"]\noverlap = \"x\"\n",
+        ),
+    ]);
+
+    let args = IndexArgs {
+        path: repo.path().to_path_buf(),
+        git_ref: None,
+        session: "repo-local".to_string(),
+        force: false,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: None,
+        overlap: None,
+        include: vec![],
+        exclude: vec![],
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
+    };
+
+    let result = execute(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "Malformed lines must not abort: {result:?}");
+
+    let metadata = services.storage.get_session_metadata("repo-local").unwrap();
+    let sources = metadata.config.sources.unwrap();
+    assert_eq!(metadata.files_indexed, 1);
+    assert_eq!(metadata.config.chunk_size, 800);
+    assert_eq!(sources.chunk_size, ConfigSource::RepoConfig);
+    assert_eq!(sources.overlap, ConfigSource::Default);
+    assert_eq!(metadata.config.include_patterns, vec!["*.rs".to_string()]);
+    assert_eq!(sources.ignore_patterns, vec!["generated/".to_string()]);
+}
+
 /// Test indexing non-existent path
 #[tokio::test]
 async fn test_index_invalid_path() {
@@ -144,13 +370,31 @@ async fn test_index_invalid_path() {
 
     let args = IndexArgs {
         path: "/nonexistent/path/that/does/not/exist".into(),
+        git_ref: None,
         session: "invalid-path".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -165,13 +409,31 @@ async fn test_index_empty_directory() {
 
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "empty-dir".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     // Empty directory should either succeed with 0 files or fail gracefully
@@ -193,13 +455,31 @@ async fn test_index_session_exists_no_force() {
     // Try to index again without --force
     let args = IndexArgs {
         path: repo.path().to_path_buf(),
+        git_ref: None,
         session: "exists-test".to_string(),
         force: false,
-        chunk_size: 512,
-        overlap: 64,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
         include: vec![],
         exclude: vec![],
-        quiet: true,
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -207,3 +487,365 @@ async fn test_index_session_exists_no_force() {
     // The important thing is it handles the case gracefully
     let _ = result;
 }
+
+/// Test --dedupe skips identical files and records them
+#[tokio::test]
+async fn test_index_with_dedupe() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/util.rs", "pub fn helper() {}"),
+        ("third_party/util.rs", "pub fn helper() {}"),
+        ("src/main.rs", "fn main() {}"),
+    ]);
+
+    let args = IndexArgs {
+        path: repo.path().to_path_buf(),
+        git_ref: None,
+        session: "dedupe-index".to_string(),
+        force: false,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: Some(512),
+        overlap: Some(64),
+        include: vec![],
+        exclude: vec![],
+        languages: vec![],
+        verbose: false,
+        dedupe: true,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
+    };
+
+    let result = execute(args, &services, OutputFormat::Json).await;
+    assert!(
+        result.is_ok(),
+        "Dedupe index should succeed: {:?}",
+        result.err()
+    );
+
+    let metadata = services
+        .storage
+        .get_session_metadata("dedupe-index")
+        .unwrap();
+    assert!(metadata.config.dedupe);
+    assert_eq!(metadata.files_indexed, 2);
+    assert_eq!(metadata.files_deduplicated, 1);
+
+    let manifest = services
+        .storage
+        .get_content_manifest("dedupe-index")
+        .unwrap()
+        .unwrap();
+    assert_eq!(manifest.duplicate_count(), 1);
+}
+
+/// Test --dry-run reports without creating a session, even if the ID is taken
+#[tokio::test]
+async fn test_index_dry_run() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/main.rs", "fn main() {}"), ("README.md", "# Project")]);
+    setup_indexed_session(&services, repo.path(), "dry-run-test").await;
+    let before = services
+        .storage
+        .get_session_metadata("dry-run-test")
+        .unwrap();
+
+    for (session, format) in [
+        ("dry-run-test", OutputFormat::Human),
+        ("dry-run-new", OutputFormat::Json),
+    ] {
+        let args = IndexArgs {
+            path: repo.path().to_path_buf(),
+            git_ref: None,
+            session: session.to_string(),
+            force: false,
+            allow_dangerous_path: false,
+            detect_root: false,
+            chunk_size: Some(512),
+            overlap: Some(64),
+            include: vec!["*.rs".to_string()],
+            exclude: vec![],
+            languages: vec![],
+            verbose: false,
+            dedupe: false,
+            boundary_hint: None,
+            no_store_chunk_text: false,
+            strip_high_entropy: false,
+            strip_run_chars: None,
+            merge_tail_fragments: false,
+            no_record_chunking: false,
+            max_chunks_per_file: None,
+            no_git_metadata: false,
+            strict_patterns: false,
+            dry_run: true,
+            files_from: None,
+            files_from0: None,
+            profile_indexing: false,
+        };
+
+        let result = execute(args, &services, format).await;
+        assert!(result.is_ok(), "Dry run should succeed: {:?}", result.err());
+    }
+
+    assert!(!services.storage.session_exists("dry-run-new"));
+    let after = services
+        .storage
+        .get_session_metadata("dry-run-test")
+        .unwrap();
+    assert_eq!(after.last_indexed_at, before.last_indexed_at);
+}
+
+fn url_args(url: &str, session: &str) -> IndexArgs {
+    IndexArgs {
+        path: url.into(),
+        git_ref: None,
+        session: session.to_string(),
+        force: false,
+        allow_dangerous_path: false,
+        detect_root: false,
+        chunk_size: None,
+        overlap: None,
+        include: vec![],
+        exclude: vec![],
+        languages: vec![],
+        verbose: false,
+        dedupe: false,
+        boundary_hint: None,
+        no_store_chunk_text: false,
+        strip_high_entropy: false,
+        strip_run_chars: None,
+        merge_tail_fragments: false,
+        no_record_chunking: false,
+        max_chunks_per_file: None,
+        no_git_metadata: false,
+        strict_patterns: false,
+        dry_run: false,
+        files_from: None,
+        files_from0: None,
+        profile_indexing: false,
+    }
+}
+
+/// Test the storage root is refused without --allow-dangerous-path
+#[tokio::test]
+async fn test_index_refuses_storage_root() {
+    let (services, storage_temp) = create_cli_test_services();
+    std::fs::write(storage_temp.path().join("notes.md"), "# Notes").unwrap();
+    let root = storage_temp.path().to_str().unwrap();
+
+    let result = execute(url_args(root, "root"), &services, OutputFormat::Human).await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("the Shebe storage root"), "{err}");
+    assert!(err.contains("--allow-dangerous-path"), "{err}");
+    assert!(!services.storage.session_exists("root"));
+
+    let args = IndexArgs {
+        allow_dangerous_path: true,
+        detect_root: false,
+        ..url_args(root, "root")
+    };
+    let result = execute(args, &services, OutputFormat::Human).await;
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert!(services.storage.session_exists("root"));
+}
+
+/// Test file:// URLs are refused before git runs
+#[tokio::test]
+async fn test_index_rejects_file_url() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn test() {}")]);
+    let url = format!("file://{}", repo.path().display());
+
+    let result = execute(url_args(&url, "file-url"), &services, OutputFormat::Human).await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("file:// URLs are not supported"), "{err}");
+    assert!(!services.storage.session_exists("file-url"));
+}
+
+/// Test --ref is only accepted together with a git URL
+#[tokio::test]
+async fn test_index_ref_requires_url() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn test() {}")]);
+
+    let mut args = url_args(repo.path().to_str().unwrap(), "local-ref");
+    args.git_ref = Some("main".to_string());
+    let result = execute(args, &services, OutputFormat::Human).await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("--ref only applies"), "{err}");
+}
+
+/// Test a file path is refused with a hint, and --detect-root indexes its repository
+#[tokio::test]
+async fn test_index_file_path_detect_root() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+        ("src/main.rs", "fn main() {}"),
+        ("src/lib.rs", "pub fn lib() {}"),
+    ]);
+    let file = repo.path().join("src/main.rs");
+
+    let args = url_args(file.to_str().unwrap(), "from-file");
+    let err = execute(args, &services, OutputFormat::Human)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("is a file"), "{err}");
+    assert!(err.contains("--detect-root"), "{err}");
+    assert!(!services.storage.session_exists("from-file"));
+
+    let mut args = url_args(file.to_str().unwrap(), "from-file");
+    args.detect_root = true;
+    execute(args, &services, OutputFormat::Json).await.unwrap();
+
+    let metadata = services.storage.get_session_metadata("from-file").unwrap();
+    assert_eq!(
+        metadata.repository_path,
+        repo.path().canonicalize().unwrap()
+    );
+    assert_eq!(metadata.files_indexed, 3);
+}
+
+/// Test a failed clone leaves neither a session nor a partial checkout
+#[tokio::test]
+async fn test_index_unreachable_url_cleans_up() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let mut config = shebe::core::config::Config::default();
+    config.storage.index_dir = temp.path().join("sessions");
+    config.storage.clone_dir = temp.path().join("clones");
+    let services = std::sync::Arc::new(shebe::core::services::Services::new(config));
+
+    let args = url_args("https://127.0.0.1:1/org/repo.git", "unreachable");
+    let result = execute(args, &services, OutputFormat::Json).await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("Git error"), "{err}");
+    assert!(!services.storage.session_exists("unreachable"));
+    let leftovers: Vec<_> = std::fs::read_dir(temp.path().join("clones"))
+        .unwrap()
+        .collect();
+    assert!(leftovers.is_empty(), "clone dir not cleaned: {leftovers:?}");
+}
+
+/// Write a --files-from list outside the repository
+fn file_list(contents: &[u8]) -> tempfile::NamedTempFile {
+    let mut list = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut list, contents).unwrap();
+    list
+}
+
+/// Test --files-from indexes exactly the listed files, patterns aside
+#[tokio::test]
+async fn test_index_files_from_indexes_listed_files() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/lib.rs", "pub fn lib() {}"),
+        ("target/gen.rs", "fn generated() {}"),
+    ]);
+    let absolute = repo.path().join("target/gen.rs");
+    let list =
+        file_list(format!("src/lib.rs\r\n\n{}\nsrc/lib.rs\n", absolute.display()).as_bytes());
+
+    let mut args = url_args(repo.path().to_str().unwrap(), "listed");
+    args.files_from = Some(list.path().to_path_buf());
+    execute(args, &services, OutputFormat::Json).await.unwrap();
+
+    let metadata = services.storage.get_session_metadata("listed").unwrap();
+    assert_eq!(metadata.files_indexed, 2);
+}
+
+/// Test --files-from0 accepts paths containing newlines
+#[tokio::test]
+async fn test_index_files_from0_allows_newlines() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("odd\nname.rs", "fn odd() {}"),
+        ("src/lib.rs", "pub fn lib() {}"),
+        ("src/main.rs", "fn main() {}"),
+    ]);
+    let list = file_list(b"odd\nname.rs\0src/lib.rs\0");
+
+    let mut args = url_args(repo.path().to_str().unwrap(), "listed0");
+    args.files_from0 = Some(list.path().to_path_buf());
+    execute(args, &services, OutputFormat::Json).await.unwrap();
+
+    let metadata = services.storage.get_session_metadata("listed0").unwrap();
+    assert_eq!(metadata.files_indexed, 2);
+}
+
+/// Test the first invalid entry is reported with its line number
+#[tokio::test]
+async fn test_index_files_from_reports_invalid_line() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/lib.rs", "pub fn lib() {}")]);
+
+    for (contents, expected) in [
+        (
+            "src/lib.rs\nsrc/missing.rs\n../x.rs\n",
+            "line 2: 'src/missing.rs'",
+        ),
+        ("src/lib.rs\n\nsrc\n", "line 3: 'src' is not a file"),
+        (
+            "/etc/hostname\n",
+            "line 1: '/etc/hostname' is outside the repository",
+        ),
+    ] {
+        let list = file_list(contents.as_bytes());
+        let mut args = url_args(repo.path().to_str().unwrap(), "invalid-list");
+        args.files_from = Some(list.path().to_path_buf());
+
+        let err = execute(args, &services, OutputFormat::Human)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(expected), "{err}");
+        assert!(!services.storage.session_exists("invalid-list"));
+    }
+}
+
+/// Test an empty list indexes nothing instead of the whole repository
+#[tokio::test]
+async fn test_index_files_from_empty_is_noop() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/lib.rs", "pub fn lib() {}")]);
+    let list = file_list(b"\n\n");
+
+    let mut args = url_args(repo.path().to_str().unwrap(), "empty-list");
+    args.files_from = Some(list.path().to_path_buf());
+    execute(args, &services, OutputFormat::Human).await.unwrap();
+
+    assert!(!services.storage.session_exists("empty-list"));
+}
+
+/// Test --profile-indexing prints the breakdown after indexing
+#[tokio::test]
+async fn test_index_profile_indexing() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/lib.rs", "pub fn lib() {}"),
+    ]);
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let mut args = url_args(repo.path().to_str().unwrap(), "profiled");
+        args.force = true;
+        args.profile_indexing = true;
+        execute(args, &services, format).await.unwrap();
+    }
+    assert!(services.storage.session_exists("profiled"));
+}
@@ -16,7 +16,10 @@ use shebe::cli::OutputFormat;
 async fn test_server_info_human() {
     let (services, _storage_temp) = create_cli_test_services();
 
-    let args = InfoArgs { detailed: false };
+    let args = InfoArgs {
+        detailed: false,
+        reset_stats: false,
+    };
     let result = execute_info(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Get server info should succeed");
 }
@@ -26,7 +29,10 @@ async fn test_server_info_human() {
 async fn test_server_info_json() {
     let (services, _storage_temp) = create_cli_test_services();
 
-    let args = InfoArgs { detailed: false };
+    let args = InfoArgs {
+        detailed: false,
+        reset_stats: false,
+    };
     let result = execute_info(args, &services, OutputFormat::Json).await;
     assert!(result.is_ok(), "Get server info (JSON) should succeed");
 }
@@ -36,7 +42,10 @@ async fn test_server_info_json() {
 async fn test_server_info_detailed() {
     let (services, _storage_temp) = create_cli_test_services();
 
-    let args = InfoArgs { detailed: true };
+    let args = InfoArgs {
+        detailed: true,
+        reset_stats: false,
+    };
     let result = execute_info(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Get detailed server info should succeed");
 }
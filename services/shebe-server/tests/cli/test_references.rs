@@ -7,11 +7,16 @@
 //! - Session not found errors
 //! - Confidence filtering
 //! - Context lines extraction
+//! - Unreadable files (indexed text and a warning)
+//! - Several symbols (per-symbol results, combined file list)
 
 use crate::cli::test_helpers::{
     create_cli_test_services, create_test_repo, references_test_files, setup_indexed_session,
 };
-use shebe::cli::commands::references::{execute, ReferencesArgs, SymbolTypeArg};
+use shebe::cli::commands::references::{
+    execute, files_to_update, find_references, ReferencesArgs, SymbolTypeArg,
+};
+use shebe::cli::export::ExportArgs;
 use shebe::cli::OutputFormat;
 
 /// Test finding function references
@@ -23,13 +28,15 @@ async fn test_references_function_human() {
     setup_indexed_session(&services, repo.path(), "refs-func").await;
 
     let args = ReferencesArgs {
-        symbol: "helper_function".to_string(),
+        symbols: vec!["helper_function".to_string()],
         session: "refs-func".to_string(),
         symbol_type: SymbolTypeArg::Function,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -49,13 +56,15 @@ async fn test_references_function_json() {
     setup_indexed_session(&services, repo.path(), "refs-json").await;
 
     let args = ReferencesArgs {
-        symbol: "helper_function".to_string(),
+        symbols: vec!["helper_function".to_string()],
         session: "refs-json".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: true,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Json).await;
@@ -99,13 +108,15 @@ fn use_config(config: Config) {
     setup_indexed_session(&services, repo.path(), "refs-type").await;
 
     let args = ReferencesArgs {
-        symbol: "Config".to_string(),
+        symbols: vec!["Config".to_string()],
         session: "refs-type".to_string(),
         symbol_type: SymbolTypeArg::Type,
         defined_in: None,
         include_definition: true,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -125,13 +136,15 @@ async fn test_references_no_matches() {
     setup_indexed_session(&services, repo.path(), "refs-empty").await;
 
     let args = ReferencesArgs {
-        symbol: "nonexistent_symbol_xyz".to_string(),
+        symbols: vec!["nonexistent_symbol_xyz".to_string()],
         session: "refs-empty".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     // Should succeed even with no results
@@ -148,13 +161,15 @@ async fn test_references_no_matches_json() {
     setup_indexed_session(&services, repo.path(), "refs-empty-json").await;
 
     let args = ReferencesArgs {
-        symbol: "nonexistent_xyz".to_string(),
+        symbols: vec!["nonexistent_xyz".to_string()],
         session: "refs-empty-json".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Json).await;
@@ -167,13 +182,15 @@ async fn test_references_session_not_found() {
     let (services, _storage_temp) = create_cli_test_services();
 
     let args = ReferencesArgs {
-        symbol: "test_symbol".to_string(),
+        symbols: vec!["test_symbol".to_string()],
         session: "nonexistent-session".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -196,13 +213,15 @@ async fn test_references_symbol_too_short() {
     setup_indexed_session(&services, repo.path(), "refs-short").await;
 
     let args = ReferencesArgs {
-        symbol: "x".to_string(), // Only 1 character
+        symbols: vec!["x".to_string()], // Only 1 character
         session: "refs-short".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -225,13 +244,15 @@ async fn test_references_empty_symbol() {
     setup_indexed_session(&services, repo.path(), "refs-empty-sym").await;
 
     let args = ReferencesArgs {
-        symbol: "".to_string(),
+        symbols: vec!["".to_string()],
         session: "refs-empty-sym".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -253,13 +274,15 @@ async fn test_references_with_defined_in() {
     setup_indexed_session(&services, repo.path(), "refs-defined").await;
 
     let args = ReferencesArgs {
-        symbol: "my_func".to_string(),
+        symbols: vec!["my_func".to_string()],
         session: "refs-defined".to_string(),
         symbol_type: SymbolTypeArg::Function,
         defined_in: Some("lib.rs".to_string()), // Exclude definition
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -275,13 +298,15 @@ async fn test_references_max_results() {
     setup_indexed_session(&services, repo.path(), "refs-limit").await;
 
     let args = ReferencesArgs {
-        symbol: "helper_function".to_string(),
+        symbols: vec!["helper_function".to_string()],
         session: "refs-limit".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: true,
         context_lines: 2,
         max_results: 2, // Limit to 2 results
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -298,13 +323,15 @@ async fn test_references_context_lines() {
 
     // Test with 0 context lines
     let args = ReferencesArgs {
-        symbol: "helper_function".to_string(),
+        symbols: vec!["helper_function".to_string()],
         session: "refs-context".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: true,
         context_lines: 0,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -312,13 +339,15 @@ async fn test_references_context_lines() {
 
     // Test with max context lines (clamped to 10)
     let args_max = ReferencesArgs {
-        symbol: "helper_function".to_string(),
+        symbols: vec!["helper_function".to_string()],
         session: "refs-context".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: true,
         context_lines: 100, // Should be clamped to 10
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result_max = execute(args_max, &services, OutputFormat::Human).await;
@@ -340,13 +369,15 @@ async fn test_references_variable_type() {
     setup_indexed_session(&services, repo.path(), "refs-var").await;
 
     let args = ReferencesArgs {
-        symbol: "config".to_string(),
+        symbols: vec!["config".to_string()],
         session: "refs-var".to_string(),
         symbol_type: SymbolTypeArg::Variable,
         defined_in: None,
         include_definition: true,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -366,15 +397,129 @@ async fn test_references_whitespace_symbol() {
     setup_indexed_session(&services, repo.path(), "refs-ws").await;
 
     let args = ReferencesArgs {
-        symbol: "   ".to_string(), // Whitespace only
+        symbols: vec!["   ".to_string()], // Whitespace only
         session: "refs-ws".to_string(),
         symbol_type: SymbolTypeArg::Any,
         defined_in: None,
         include_definition: false,
         context_lines: 2,
         max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
     assert!(result.is_err(), "Whitespace-only symbol should fail");
 }
+
+/// Test that a reference in an unreadable file is kept, from the indexed
+/// text, and the file is listed
+#[cfg(unix)]
+#[tokio::test]
+async fn test_references_unreadable_file() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/lib.rs", "pub fn load_config() {}\n"),
+        (
+            "src/locked.rs",
+            "fn start() {\n    let config = load_config();\n}\n",
+        ),
+    ]);
+    setup_indexed_session(&services, repo.path(), "refs-unreadable").await;
+    let locked = repo.path().join("src/locked.rs");
+    crate::common::make_unreadable(&locked);
+
+    let args = ReferencesArgs {
+        symbols: vec!["load_config".to_string()],
+        session: "refs-unreadable".to_string(),
+        symbol_type: SymbolTypeArg::Any,
+        defined_in: None,
+        include_definition: false,
+        context_lines: 1,
+        max_results: 50,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
+    };
+    let output = find_references(&args, "load_config", &services).unwrap();
+
+    let degraded: Vec<_> = output
+        .references
+        .iter()
+        .filter(|r| r.context_unavailable.is_some())
+        .collect();
+    assert_eq!(degraded.len(), 1);
+    assert_eq!(output.degraded_context, 1);
+    assert!(degraded[0].file_path.ends_with("locked.rs"));
+    assert_eq!(degraded[0].line_number, 0);
+    assert!(degraded[0].context.contains("load_config();"));
+    assert!(degraded[0].confidence < 0.95);
+
+    assert_eq!(output.unreadable_files.len(), 1);
+    assert!(output.unreadable_files[0].file_path.ends_with("locked.rs"));
+    assert!(!output.unreadable_files[0].error.is_empty());
+
+    // Every output format still succeeds
+    let result = execute(args, &services, OutputFormat::Human).await;
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+/// Test several symbols: one result per symbol and a combined file list
+#[tokio::test]
+async fn test_references_several_symbols() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&references_test_files());
+    setup_indexed_session(&services, repo.path(), "refs-many").await;
+
+    let args = || ReferencesArgs {
+        symbols: vec!["helper_function".to_string(), "process_request".to_string()],
+        session: "refs-many".to_string(),
+        symbol_type: SymbolTypeArg::Any,
+        defined_in: None,
+        include_definition: false,
+        context_lines: 1,
+        max_results: 50,
+        fail_on_empty: true,
+        export: ExportArgs::default(),
+    };
+    let outputs = vec![
+        find_references(&args(), "helper_function", &services).unwrap(),
+        find_references(&args(), "process_request", &services).unwrap(),
+    ];
+
+    // lib.rs re-exports both
+    let files = files_to_update(&outputs);
+    let lib = files
+        .iter()
+        .find(|f| f.file_path.ends_with("src/lib.rs"))
+        .expect("lib.rs listed");
+    assert_eq!(lib.symbols, vec!["helper_function", "process_request"]);
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(args(), &services, format).await;
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+}
+
+/// Test that --max-results applies to each symbol
+#[tokio::test]
+async fn test_references_several_symbols_capped_each() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&references_test_files());
+    setup_indexed_session(&services, repo.path(), "refs-many-cap").await;
+
+    let args = ReferencesArgs {
+        symbols: vec!["helper_function".to_string(), "process_request".to_string()],
+        session: "refs-many-cap".to_string(),
+        symbol_type: SymbolTypeArg::Any,
+        defined_in: None,
+        include_definition: true,
+        context_lines: 0,
+        max_results: 1,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
+    };
+    for symbol in &args.symbols {
+        let output = find_references(&args, symbol, &services).unwrap();
+        assert_eq!(output.total_count, 1, "{symbol}");
+    }
+}
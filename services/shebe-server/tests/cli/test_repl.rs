@@ -0,0 +1,135 @@
+//! Tests for the repl CLI command
+//!
+//! Tests the REPL line handling without a terminal:
+//! - Parsing of `:` commands and queries
+//! - Settings changes (limit, session, output toggles)
+//! - Queries against an indexed session
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
+use shebe::cli::commands::repl::{handle_line, parse_line, LineOutcome, ReplCommand, ReplState};
+use shebe::cli::OutputFormat;
+
+fn state(session: &str) -> ReplState {
+    ReplState {
+        session: session.to_string(),
+        limit: 10,
+        format: OutputFormat::Human,
+        group_by_file: false,
+        files_only: false,
+    }
+}
+
+// =============================================================================
+// parse_line tests
+// =============================================================================
+
+/// Test that plain text is a search query
+#[test]
+fn test_parse_query() {
+    assert_eq!(
+        parse_line("  fn main  ").unwrap(),
+        ReplCommand::Search("fn main".to_string())
+    );
+    assert_eq!(parse_line("   ").unwrap(), ReplCommand::Empty);
+}
+
+/// Test parsing of REPL commands
+#[test]
+fn test_parse_commands() {
+    assert_eq!(parse_line(":k 20").unwrap(), ReplCommand::SetLimit(20));
+    assert_eq!(
+        parse_line(":session other").unwrap(),
+        ReplCommand::SwitchSession("other".to_string())
+    );
+    assert_eq!(parse_line(":json").unwrap(), ReplCommand::ToggleJson);
+    assert_eq!(parse_line(":group").unwrap(), ReplCommand::ToggleGroup);
+    assert_eq!(parse_line(":files").unwrap(), ReplCommand::ToggleFilesOnly);
+    assert_eq!(parse_line(":help").unwrap(), ReplCommand::Help);
+    assert_eq!(parse_line(":q").unwrap(), ReplCommand::Quit);
+}
+
+/// Test malformed and unknown commands
+#[test]
+fn test_parse_invalid_commands() {
+    assert!(parse_line(":k").is_err());
+    assert!(parse_line(":k many").unwrap_err().contains("Invalid limit"));
+    assert!(parse_line(":session").is_err());
+    assert!(parse_line(":bogus").unwrap_err().contains(":help"));
+}
+
+// =============================================================================
+// handle_line tests
+// =============================================================================
+
+/// Test settings commands update state
+#[tokio::test]
+async fn test_handle_settings() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let mut state = state("unused");
+
+    assert_eq!(
+        handle_line(&mut state, ":k 500", &services).await,
+        LineOutcome::Continue
+    );
+    assert_eq!(state.limit, 100, "Limit should be clamped");
+
+    handle_line(&mut state, ":json", &services).await;
+    assert_eq!(state.format, OutputFormat::Json);
+    handle_line(&mut state, ":json", &services).await;
+    assert_eq!(state.format, OutputFormat::Human);
+
+    handle_line(&mut state, ":group", &services).await;
+    assert!(state.group_by_file);
+
+    assert_eq!(
+        handle_line(&mut state, ":quit", &services).await,
+        LineOutcome::Exit
+    );
+}
+
+/// Test switching sessions only succeeds for existing sessions
+#[tokio::test]
+async fn test_handle_switch_session() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn repl_target() {}")]);
+    setup_indexed_session(&services, repo.path(), "repl-a").await;
+    setup_indexed_session(&services, repo.path(), "repl-b").await;
+
+    let mut state = state("repl-a");
+
+    handle_line(&mut state, ":session missing", &services).await;
+    assert_eq!(
+        state.session, "repl-a",
+        "Unknown session must not be selected"
+    );
+
+    handle_line(&mut state, ":session repl-b", &services).await;
+    assert_eq!(state.session, "repl-b");
+}
+
+/// Test running queries (including errors) keeps the REPL alive
+#[tokio::test]
+async fn test_handle_queries() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn repl_target() {}")]);
+    setup_indexed_session(&services, repo.path(), "repl-query").await;
+
+    let mut state = state("repl-query");
+
+    assert_eq!(
+        handle_line(&mut state, "repl_target", &services).await,
+        LineOutcome::Continue
+    );
+
+    handle_line(&mut state, ":json", &services).await;
+    assert_eq!(
+        handle_line(&mut state, "repl_target", &services).await,
+        LineOutcome::Continue
+    );
+
+    // Invalid query syntax is reported, not fatal
+    assert_eq!(
+        handle_line(&mut state, "AND", &services).await,
+        LineOutcome::Continue
+    );
+}
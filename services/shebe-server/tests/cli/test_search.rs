@@ -7,8 +7,9 @@
 //! - Output format variations
 
 use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
-use shebe::cli::commands::search::{execute, SearchArgs};
-use shebe::cli::OutputFormat;
+use shebe::cli::commands::search::{build_request, execute, SearchArgs};
+use shebe::cli::export::ExportArgs;
+use shebe::cli::{error_exit_code, OutputFormat};
 
 /// Test search with valid query returning results
 #[tokio::test]
@@ -24,8 +25,28 @@ async fn test_search_valid_query_human() {
     let args = SearchArgs {
         query: "println".to_string(),
         session: "search-test".to_string(),
-        limit: 10,
+        limit: Some(10),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -43,8 +64,28 @@ async fn test_search_valid_query_json() {
     let args = SearchArgs {
         query: "main".to_string(),
         session: "json-test".to_string(),
-        limit: 5,
+        limit: Some(5),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Json).await;
@@ -66,8 +107,28 @@ async fn test_search_empty_results() {
     let args = SearchArgs {
         query: "nonexistent_symbol_xyz".to_string(),
         session: "empty-test".to_string(),
-        limit: 10,
+        limit: Some(10),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -82,8 +143,28 @@ async fn test_search_session_not_found() {
     let args = SearchArgs {
         query: "test".to_string(),
         session: "nonexistent-session".to_string(),
-        limit: 10,
+        limit: Some(10),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -111,8 +192,28 @@ async fn test_search_files_only() {
     let args = SearchArgs {
         query: "test_function".to_string(),
         session: "files-only-test".to_string(),
-        limit: 10,
+        limit: Some(10),
         files_only: true,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -131,8 +232,28 @@ async fn test_search_limit_clamping() {
     let args = SearchArgs {
         query: "main".to_string(),
         session: "limit-test".to_string(),
-        limit: 500,
+        limit: Some(500),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
@@ -145,8 +266,28 @@ async fn test_search_limit_clamping() {
     let args_zero = SearchArgs {
         query: "main".to_string(),
         session: "limit-test".to_string(),
-        limit: 0,
+        limit: Some(0),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result_zero = execute(args_zero, &services, OutputFormat::Human).await;
@@ -172,10 +313,304 @@ async fn test_search_boolean_query() {
     let args = SearchArgs {
         query: "start AND server".to_string(),
         session: "bool-test".to_string(),
-        limit: 10,
+        limit: Some(10),
         files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: false,
+        export: ExportArgs::default(),
     };
 
     let result = execute(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Boolean AND query should succeed");
 }
+
+/// Test grouped search in human and JSON formats
+#[tokio::test]
+async fn test_search_group_by_file() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() { shared_helper(); }"),
+        ("src/lib.rs", "pub fn shared_helper() {}"),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "grouped-test").await;
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = SearchArgs {
+            query: "shared_helper".to_string(),
+            session: "grouped-test".to_string(),
+            limit: Some(10),
+            files_only: false,
+            locations: false,
+            first: false,
+            paths_only: false,
+            paths: false,
+            group_by_file: true,
+            keep_overlaps: false,
+            path_boost: None,
+            fuzziness: None,
+            no_expand_identifiers: false,
+            bm25_k1: None,
+            bm25_b: None,
+            keep_short_chunks: false,
+            within: None,
+            within_k: None,
+            modified_within: None,
+            not: Vec::new(),
+            not_path: Vec::new(),
+            explain: false,
+            fail_on_empty: false,
+            export: ExportArgs::default(),
+        };
+
+        let result = execute(args, &services, format).await;
+        assert!(
+            result.is_ok(),
+            "Grouped search should succeed: {:?}",
+            result.err()
+        );
+    }
+}
+
+/// Test locations-only search in human and JSON formats
+#[tokio::test]
+async fn test_search_locations() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {\n    shared_helper();\n}\n"),
+        ("src/lib.rs", "pub fn shared_helper() {}"),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "locations-test").await;
+
+    for (format, group_by_file) in [
+        (OutputFormat::Human, false),
+        (OutputFormat::Json, false),
+        (OutputFormat::Json, true),
+    ] {
+        let args = SearchArgs {
+            query: "shared_helper".to_string(),
+            session: "locations-test".to_string(),
+            limit: Some(100),
+            files_only: false,
+            locations: true,
+            first: false,
+            paths_only: false,
+            paths: false,
+            group_by_file,
+            keep_overlaps: false,
+            path_boost: None,
+            fuzziness: None,
+            no_expand_identifiers: false,
+            bm25_k1: None,
+            bm25_b: None,
+            keep_short_chunks: false,
+            within: None,
+            within_k: None,
+            modified_within: None,
+            not: Vec::new(),
+            not_path: Vec::new(),
+            explain: false,
+            fail_on_empty: false,
+            export: ExportArgs::default(),
+        };
+
+        let result = execute(args, &services, format).await;
+        assert!(
+            result.is_ok(),
+            "Locations search should succeed: {:?}",
+            result.err()
+        );
+    }
+}
+
+/// Test --within refines results to the inner query's hits
+#[tokio::test]
+async fn test_search_within() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/retry.rs", "// the retry policy sets a timeout\n"),
+        (
+            "src/client.rs",
+            "// retry when the policy allows, with a timeout\n",
+        ),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "within-test").await;
+
+    let args = || SearchArgs {
+        query: "timeout".to_string(),
+        session: "within-test".to_string(),
+        limit: Some(10),
+        files_only: true,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: Some("\"retry policy\"".to_string()),
+        within_k: Some(5),
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: true,
+        export: ExportArgs::default(),
+    };
+
+    let request = build_request(&args());
+    let within = request.within.as_ref().unwrap();
+    assert_eq!(within.query, "\"retry policy\"");
+    assert_eq!(within.k, Some(5));
+
+    // A plain AND of both queries would also return src/client.rs
+    let response = services.search.search(request).unwrap();
+    assert_eq!(response.count, 1);
+    assert!(response.results[0].file_path.ends_with("src/retry.rs"));
+    let refinement = response.refinement.unwrap();
+    assert_eq!((refinement.inner_matches, refinement.survived), (1, 1));
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(args(), &services, format).await;
+        assert!(
+            result.is_ok(),
+            "Refined search should succeed: {:?}",
+            result.err()
+        );
+    }
+}
+
+/// Test --not and --not-path leave chunks out of the search
+#[tokio::test]
+async fn test_search_not_terms_and_paths() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/retry.rs", "// retry with backoff\n"),
+        ("tests/retry_test.rs", "// test the retry loop\n"),
+        ("vendor/retry.rs", "// retry copied from upstream\n"),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "not-test").await;
+
+    let args = || SearchArgs {
+        query: "retry".to_string(),
+        session: "not-test".to_string(),
+        limit: Some(10),
+        files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: false,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: vec!["test".to_string()],
+        not_path: vec!["vendor/**".to_string()],
+        explain: false,
+        fail_on_empty: true,
+        export: ExportArgs::default(),
+    };
+
+    let response = services.search.search(build_request(&args())).unwrap();
+    assert_eq!(response.count, 1);
+    assert!(response.results[0].file_path.ends_with("src/retry.rs"));
+    assert_eq!(response.exclusions.unwrap().removed, 2);
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(args(), &services, format).await;
+        assert!(result.is_ok(), "Search should succeed: {:?}", result.err());
+    }
+
+    // Excluding the only query term leaves nothing to search
+    let mut only_term = args();
+    only_term.not = vec!["retry".to_string()];
+    assert!(execute(only_term, &services, OutputFormat::Human)
+        .await
+        .is_err());
+}
+
+/// Test --paths matches the query against file paths only
+#[tokio::test]
+async fn test_search_paths_mode() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/user/service_handler.rs", "fn handle() {}\n"),
+        ("src/main.rs", "// zebra crossing\n"),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "paths-test").await;
+
+    let args = |query: &str| SearchArgs {
+        query: query.to_string(),
+        session: "paths-test".to_string(),
+        limit: Some(10),
+        files_only: false,
+        locations: false,
+        first: false,
+        paths_only: false,
+        paths: true,
+        group_by_file: false,
+        keep_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        no_expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        keep_short_chunks: false,
+        within: None,
+        within_k: None,
+        modified_within: None,
+        not: Vec::new(),
+        not_path: Vec::new(),
+        explain: false,
+        fail_on_empty: true,
+        export: ExportArgs::default(),
+    };
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(args("user sevice handler"), &services, format).await;
+        assert!(
+            result.is_ok(),
+            "Path search should succeed: {:?}",
+            result.err()
+        );
+    }
+
+    // Content is not searched: "zebra" is in a file but in no path
+    let err = execute(args("zebra"), &services, OutputFormat::Human)
+        .await
+        .unwrap_err();
+    assert_eq!(error_exit_code(err.as_ref()), 5, "{err}");
+}
@@ -0,0 +1,81 @@
+//! Tests for the self-test CLI command
+//!
+//! Runs the self-test against a tempdir storage root:
+//! - Human and JSON output succeed and leave nothing behind
+//! - Existing sessions are left alone
+//! - A failing run exits with an error, and read-only mode refuses to run
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo};
+use shebe::cli::commands::self_test::{execute, SelfTestArgs};
+use shebe::cli::{error_exit_code, OutputFormat};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use std::sync::Arc;
+
+/// Test both output formats and scratch directory cleanup
+#[tokio::test]
+async fn test_self_test_human_and_json() {
+    let (services, storage_temp) = create_cli_test_services();
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let result = execute(SelfTestArgs {}, &services, format).await;
+        assert!(result.is_ok(), "self-test should pass: {result:?}");
+    }
+
+    let leftovers: Vec<_> = std::fs::read_dir(storage_temp.path())
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name())
+        .collect();
+    assert!(leftovers.is_empty(), "left behind: {leftovers:?}");
+}
+
+/// Test that a stored session survives a run untouched
+#[tokio::test]
+async fn test_self_test_keeps_existing_sessions() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/lib.rs", "pub fn keep_me() {}\n")]);
+    services.index(repo.path()).session("keep").run().unwrap();
+
+    execute(SelfTestArgs {}, &services, OutputFormat::Json)
+        .await
+        .unwrap();
+
+    assert_eq!(services.storage.list_session_ids().unwrap(), ["keep"]);
+    let response = services
+        .search
+        .search_session("keep", "keep_me", Some(5))
+        .unwrap();
+    assert_eq!(response.results.len(), 1);
+}
+
+/// Test that failed steps make the command fail with exit code 1
+#[tokio::test]
+async fn test_self_test_failure_exit_code() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let root = temp.path().join("root");
+    std::fs::write(&root, "a file, not a directory").unwrap();
+    let mut config = Config::default();
+    config.storage.index_dir = root;
+    let services = Arc::new(Services::new(config));
+
+    let error = execute(SelfTestArgs {}, &services, OutputFormat::Human)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("write corpus"), "{error}");
+    assert_eq!(error_exit_code(error.as_ref()), 1);
+}
+
+/// Test that read-only mode refuses to run
+#[tokio::test]
+async fn test_self_test_read_only() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.storage.index_dir = temp.path().to_path_buf();
+    config.server.read_only = true;
+    let services = Arc::new(Services::new(config));
+
+    let result = execute(SelfTestArgs {}, &services, OutputFormat::Human).await;
+    assert!(result.is_err());
+    assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+}
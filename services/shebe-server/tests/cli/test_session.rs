@@ -1,17 +1,28 @@
-//! Tests for session CLI commands (list, info, delete, reindex)
+//! Tests for session CLI commands (list, info, delete, reindex, compact, optimize,
+//! set-session-defaults)
 //!
 //! Tests the session command handlers:
 //! - list-sessions: List all indexed sessions
 //! - get-session-info: Get detailed session metadata
 //! - delete-session: Delete a session (with --force)
 //! - reindex-session: Re-index a session
+//! - compact-session: Merge index segments
+//! - optimize-session: Merge segments down to a target count
+//! - set-session-defaults: Store default search options
 
 use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
 use shebe::cli::commands::session::{
-    execute_delete, execute_info, execute_list, execute_reindex, DeleteArgs, InfoArgs, ListArgs,
-    ReindexArgs,
+    execute_compact, execute_delete, execute_info, execute_list, execute_optimize, execute_reindex,
+    execute_set_defaults, CompactArgs, DeleteArgs, InfoArgs, ListArgs, OptimizeArgs, ReindexArgs,
+    SetDefaultsArgs,
 };
 use shebe::cli::OutputFormat;
+use shebe::core::config::Config;
+use shebe::core::error::ShebeError;
+use shebe::core::services::Services;
+use shebe::core::storage::MetadataValidator;
+use shebe::core::types::{OutputMode, SearchRequest, SessionSearchDefaults};
+use std::sync::Arc;
 
 // =============================================================================
 // list-sessions tests
@@ -22,7 +33,11 @@ use shebe::cli::OutputFormat;
 async fn test_list_sessions_empty_human() {
     let (services, _storage_temp) = create_cli_test_services();
 
-    let args = ListArgs {};
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: None,
+    };
     let result = execute_list(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "List empty sessions should succeed");
 }
@@ -32,7 +47,11 @@ async fn test_list_sessions_empty_human() {
 async fn test_list_sessions_empty_json() {
     let (services, _storage_temp) = create_cli_test_services();
 
-    let args = ListArgs {};
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: None,
+    };
     let result = execute_list(args, &services, OutputFormat::Json).await;
     assert!(result.is_ok(), "List empty sessions (JSON) should succeed");
 }
@@ -45,7 +64,11 @@ async fn test_list_sessions_single() {
 
     setup_indexed_session(&services, repo.path(), "single-session").await;
 
-    let args = ListArgs {};
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: None,
+    };
     let result = execute_list(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "List single session should succeed");
 }
@@ -60,11 +83,49 @@ async fn test_list_sessions_multiple() {
     setup_indexed_session(&services, repo1.path(), "session-one").await;
     setup_indexed_session(&services, repo2.path(), "session-two").await;
 
-    let args = ListArgs {};
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: None,
+    };
     let result = execute_list(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "List multiple sessions should succeed");
 }
 
+/// Test --prune-corrupt deletes corrupt and orphaned sessions only
+#[tokio::test]
+async fn test_list_sessions_prune_corrupt() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn keep() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "kept").await;
+    setup_indexed_session(&services, repo.path(), "corrupt").await;
+    let corrupt = services.storage.get_session_path("corrupt");
+    std::fs::write(corrupt.join("meta.json"), "\u{0}garbage").unwrap();
+    let orphan = services.storage.get_session_path("orphan");
+    std::fs::create_dir_all(&orphan).unwrap();
+
+    let args = ListArgs {
+        prune_corrupt: false,
+        force: false,
+        collection: None,
+    };
+    let result = execute_list(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "List with broken sessions should succeed");
+    assert!(corrupt.exists() && orphan.exists());
+
+    let args = ListArgs {
+        prune_corrupt: true,
+        force: true,
+        collection: None,
+    };
+    let result = execute_list(args, &services, OutputFormat::Human).await;
+    assert!(result.is_ok(), "List with --prune-corrupt should succeed");
+    assert!(!corrupt.exists());
+    assert!(!orphan.exists());
+    assert!(services.storage.session_exists("kept"));
+}
+
 // =============================================================================
 // get-session-info tests
 // =============================================================================
@@ -82,6 +143,9 @@ async fn test_info_valid_session_human() {
 
     let args = InfoArgs {
         session: "info-test".to_string(),
+        why_not_indexed: None,
+        check_chunks: false,
+        check_split_tokens: false,
     };
     let result = execute_info(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Get session info should succeed");
@@ -97,11 +161,165 @@ async fn test_info_valid_session_json() {
 
     let args = InfoArgs {
         session: "info-json".to_string(),
+        why_not_indexed: None,
+        check_chunks: false,
+        check_split_tokens: false,
     };
     let result = execute_info(args, &services, OutputFormat::Json).await;
     assert!(result.is_ok(), "Get session info (JSON) should succeed");
 }
 
+/// Test session info for a repository moved after indexing
+#[tokio::test]
+async fn test_info_moved_repository() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let root = tempfile::TempDir::new().unwrap();
+    let repo = root.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    std::fs::write(repo.join("file.rs"), "fn test() {}").unwrap();
+
+    setup_indexed_session(&services, &repo, "info-moved").await;
+    std::fs::rename(&repo, root.path().join("moved")).unwrap();
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = InfoArgs {
+            session: "info-moved".to_string(),
+            why_not_indexed: None,
+            check_chunks: false,
+            check_split_tokens: false,
+        };
+        let result = execute_info(args, &services, format).await;
+        assert!(result.is_ok(), "Info on a moved repository should succeed");
+    }
+
+    // The `health` object of the JSON output
+    let health = services.storage.session_health("info-moved", 7).unwrap();
+    let json = serde_json::to_value(&health).unwrap();
+    assert_eq!(json["repository_exists"], false);
+    assert_eq!(json["files"], serde_json::Value::Null);
+    assert_eq!(json["actions"][0]["issue"], "repository_missing");
+}
+
+/// Test explaining why a file was left out of a session
+#[tokio::test]
+async fn test_info_why_not_indexed() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/main.rs", "fn main() {}"), ("debug.log", "log")]);
+
+    setup_indexed_session(&services, repo.path(), "info-why").await;
+
+    let args = InfoArgs {
+        session: "info-why".to_string(),
+        why_not_indexed: Some("debug.log".into()),
+        check_chunks: false,
+        check_split_tokens: false,
+    };
+    let result = execute_info(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "Explaining an existing file should succeed");
+
+    let args = InfoArgs {
+        session: "info-why".to_string(),
+        why_not_indexed: Some("missing.rs".into()),
+        check_chunks: false,
+        check_split_tokens: false,
+    };
+    let result = execute_info(args, &services, OutputFormat::Human).await;
+    assert!(result.is_err(), "Explaining a missing file should fail");
+}
+
+/// Test checking that chunk indexes are contiguous
+#[tokio::test]
+async fn test_info_check_chunks() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let long_file = "fn step() {}\n".repeat(200);
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/steps.rs", &long_file),
+    ]);
+
+    setup_indexed_session(&services, repo.path(), "info-check").await;
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = InfoArgs {
+            session: "info-check".to_string(),
+            why_not_indexed: None,
+            check_chunks: true,
+            check_split_tokens: false,
+        };
+        let result = execute_info(args, &services, format).await;
+        assert!(result.is_ok(), "Freshly indexed session should pass");
+    }
+}
+
+/// Test that chunk cuts avoid identifiers and the split token check
+#[tokio::test]
+async fn test_info_check_split_tokens() {
+    let (services, _storage_temp) = create_cli_test_services();
+    // With 100-byte chunks the cut at byte 100 falls inside the identifier
+    let content = format!(
+        "{}extremely_long_identifier_name = 1;\n{}",
+        "ab ".repeat(30),
+        "cd ".repeat(60)
+    );
+    let repo = create_test_repo(&[("src/names.rs", &content)]);
+    services
+        .storage
+        .index_repository(
+            "info-split",
+            repo.path(),
+            vec!["**/*".to_string()],
+            vec![],
+            100, // chunk_size
+            4,   // overlap, too small to repeat the identifier
+            10,
+            true,
+        )
+        .unwrap();
+
+    let request = SearchRequest {
+        query: "extremely_long_identifier_name".to_string(),
+        session: "info-split".to_string(),
+        k: Some(10),
+        group_by_file: false,
+        dedupe_overlaps: false,
+        path_boost: None,
+        fuzziness: None,
+        expand_identifiers: false,
+        bm25_k1: None,
+        bm25_b: None,
+        within: None,
+        penalize_short_chunks: None,
+        modified_within: None,
+        section_filter: None,
+        exclude_terms: Vec::new(),
+        exclude_paths: Vec::new(),
+        explain: false,
+    };
+    let response = services.search.search(request).unwrap();
+    assert_eq!(response.count, 1);
+    let hit = &response.results[0];
+    assert!(hit.text.contains("extremely_long_identifier_name"));
+    assert_eq!(&content[hit.start_offset..hit.end_offset], hit.text);
+
+    let report = MetadataValidator::new(&services.storage)
+        .check_split_tokens("info-split")
+        .unwrap();
+    assert_eq!(report.files_sampled, 1);
+    assert!(report.pairs_checked >= 2, "{report:?}");
+    assert_eq!(report.split_pairs, 0, "{report:?}");
+
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = InfoArgs {
+            session: "info-split".to_string(),
+            why_not_indexed: None,
+            check_chunks: false,
+            check_split_tokens: true,
+        };
+        let result = execute_info(args, &services, format).await;
+        assert!(result.is_ok(), "Split token check should succeed");
+    }
+}
+
 /// Test getting info for non-existent session
 #[tokio::test]
 async fn test_info_session_not_found() {
@@ -109,6 +327,9 @@ async fn test_info_session_not_found() {
 
     let args = InfoArgs {
         session: "nonexistent".to_string(),
+        why_not_indexed: None,
+        check_chunks: false,
+        check_split_tokens: false,
     };
     let result = execute_info(args, &services, OutputFormat::Human).await;
     assert!(result.is_err(), "Get info for missing session should fail");
@@ -137,6 +358,7 @@ async fn test_delete_force_human() {
     let args = DeleteArgs {
         session: "delete-test".to_string(),
         force: true,
+        purge_clone: false,
     };
     let result = execute_delete(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Delete with --force should succeed");
@@ -157,11 +379,42 @@ async fn test_delete_force_json() {
     let args = DeleteArgs {
         session: "delete-json".to_string(),
         force: true,
+        purge_clone: false,
     };
     let result = execute_delete(args, &services, OutputFormat::Json).await;
     assert!(result.is_ok(), "Delete with --force (JSON) should succeed");
 }
 
+/// Test --force does not bypass read-only mode
+#[tokio::test]
+async fn test_delete_read_only() {
+    let (services, storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn keep_me() {}")]);
+    setup_indexed_session(&services, repo.path(), "kept").await;
+
+    let mut config = Config::default();
+    config.storage.index_dir = storage_temp.path().to_path_buf();
+    config.server.read_only = true;
+    let read_only = Arc::new(Services::new(config));
+
+    let args = DeleteArgs {
+        session: "kept".to_string(),
+        force: true,
+        purge_clone: false,
+    };
+    let err = execute_delete(args, &read_only, OutputFormat::Human)
+        .await
+        .expect_err("Delete should fail in read-only mode");
+    assert!(
+        matches!(
+            err.downcast_ref::<ShebeError>(),
+            Some(ShebeError::ReadOnly(_))
+        ),
+        "Unexpected error: {err}"
+    );
+    assert!(services.storage.session_exists("kept"));
+}
+
 /// Test deleting non-existent session
 #[tokio::test]
 async fn test_delete_session_not_found() {
@@ -170,6 +423,7 @@ async fn test_delete_session_not_found() {
     let args = DeleteArgs {
         session: "nonexistent".to_string(),
         force: true,
+        purge_clone: false,
     };
     let result = execute_delete(args, &services, OutputFormat::Human).await;
     assert!(result.is_err(), "Delete missing session should fail");
@@ -182,6 +436,42 @@ async fn test_delete_session_not_found() {
     );
 }
 
+/// Test --purge-clone removes the cached clone of a remote session
+#[tokio::test]
+async fn test_delete_purge_clone() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let clones = tempfile::TempDir::new().unwrap();
+    let mut config = (*services.config.current()).clone();
+    config.storage.clone_dir = clones.path().to_path_buf();
+    let services = std::sync::Arc::new(shebe::core::services::Services::new(config));
+
+    let clone_path = services.clones.clone_path("remote-delete");
+    std::fs::create_dir_all(&clone_path).unwrap();
+    std::fs::write(clone_path.join("file.rs"), "fn cloned() {}").unwrap();
+    setup_indexed_session(&services, &clone_path, "remote-delete").await;
+    services
+        .storage
+        .set_remote_source(
+            "remote-delete",
+            Some(shebe::core::remote::RemoteSource {
+                url: "https://github.com/org/repo.git".to_string(),
+                git_ref: None,
+                commit: "abc123".to_string(),
+            }),
+        )
+        .unwrap();
+
+    let args = DeleteArgs {
+        session: "remote-delete".to_string(),
+        force: true,
+        purge_clone: true,
+    };
+    let result = execute_delete(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "Delete with --purge-clone should succeed");
+    assert!(!services.storage.session_exists("remote-delete"));
+    assert!(!clone_path.exists(), "Cached clone should be removed");
+}
+
 // =============================================================================
 // reindex-session tests
 // =============================================================================
@@ -198,7 +488,12 @@ async fn test_reindex_with_force_human() {
         session: "reindex-test".to_string(),
         chunk_size: None,
         overlap: None,
+        store_chunk_text: None,
+        bm25_k1: None,
+        bm25_b: None,
+        max_chunks_per_file: None,
         force: true,
+        dry_run: false,
     };
     let result = execute_reindex(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Reindex with --force should succeed");
@@ -216,7 +511,12 @@ async fn test_reindex_config_change() {
         session: "reindex-config".to_string(),
         chunk_size: Some(256),
         overlap: None,
+        store_chunk_text: None,
+        bm25_k1: None,
+        bm25_b: None,
+        max_chunks_per_file: None,
         force: false, // Config change should allow reindex without --force
+        dry_run: false,
     };
     let result = execute_reindex(args, &services, OutputFormat::Human).await;
     assert!(result.is_ok(), "Reindex with config change should succeed");
@@ -234,7 +534,12 @@ async fn test_reindex_no_change_error() {
         session: "reindex-nochange".to_string(),
         chunk_size: None,
         overlap: None,
+        store_chunk_text: None,
+        bm25_k1: None,
+        bm25_b: None,
+        max_chunks_per_file: None,
         force: false,
+        dry_run: false,
     };
     let result = execute_reindex(args, &services, OutputFormat::Human).await;
     assert!(
@@ -260,7 +565,12 @@ async fn test_reindex_path_not_exists() {
         session: "reindex-deleted".to_string(),
         chunk_size: None,
         overlap: None,
+        store_chunk_text: None,
+        bm25_k1: None,
+        bm25_b: None,
+        max_chunks_per_file: None,
         force: true,
+        dry_run: false,
     };
     let result = execute_reindex(args, &services, OutputFormat::Human).await;
     assert!(result.is_err(), "Reindex with missing path should fail");
@@ -282,7 +592,12 @@ async fn test_reindex_session_not_found() {
         session: "nonexistent".to_string(),
         chunk_size: None,
         overlap: None,
+        store_chunk_text: None,
+        bm25_k1: None,
+        bm25_b: None,
+        max_chunks_per_file: None,
         force: true,
+        dry_run: false,
     };
     let result = execute_reindex(args, &services, OutputFormat::Human).await;
     assert!(result.is_err(), "Reindex missing session should fail");
@@ -294,3 +609,263 @@ async fn test_reindex_session_not_found() {
         err_msg
     );
 }
+
+/// Test --dry-run previews changes and leaves the session alone
+#[tokio::test]
+async fn test_reindex_dry_run() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("kept.rs", "fn kept() {}"), ("old.rs", "fn old() {}")]);
+    setup_indexed_session(&services, repo.path(), "reindex-dry").await;
+    let before = services
+        .storage
+        .get_session_metadata("reindex-dry")
+        .unwrap();
+
+    std::fs::write(repo.path().join("new.rs"), "fn new() {}").unwrap();
+    std::fs::remove_file(repo.path().join("old.rs")).unwrap();
+
+    let preview = services
+        .dry_run_reindex("reindex-dry", &before.repository_path, &before.config)
+        .unwrap();
+    assert_eq!(preview.files.added, 1);
+    assert_eq!(preview.files.removed, 1);
+    assert_eq!(preview.files.unchanged, 1);
+    assert_eq!(preview.files.sample_added, vec!["new.rs"]);
+    assert!(!preview.repository_moved);
+
+    // Neither a config change nor --force is needed for a preview
+    for format in [OutputFormat::Human, OutputFormat::Json] {
+        let args = ReindexArgs {
+            session: "reindex-dry".to_string(),
+            chunk_size: Some(256),
+            overlap: None,
+            store_chunk_text: None,
+            bm25_k1: None,
+            bm25_b: None,
+            max_chunks_per_file: None,
+            force: false,
+            dry_run: true,
+        };
+        let result = execute_reindex(args, &services, format).await;
+        assert!(result.is_ok(), "Dry run should succeed: {:?}", result.err());
+    }
+
+    let after = services
+        .storage
+        .get_session_metadata("reindex-dry")
+        .unwrap();
+    assert_eq!(after.config.chunk_size, before.config.chunk_size);
+    assert_eq!(after.last_indexed_at, before.last_indexed_at);
+    let results = services
+        .search
+        .search_session("reindex-dry", "old", Some(5))
+        .unwrap();
+    assert!(!results.results.is_empty(), "removed file is still indexed");
+}
+
+// =============================================================================
+// compact-session tests
+// =============================================================================
+
+/// Test compacting an indexed session
+#[tokio::test]
+async fn test_compact_session_human() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn compact_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "compact-test").await;
+
+    let args = CompactArgs {
+        session: "compact-test".to_string(),
+    };
+    let result = execute_compact(args, &services, OutputFormat::Human).await;
+    assert!(result.is_ok(), "Compact should succeed: {:?}", result.err());
+}
+
+/// Test compacting an indexed session (JSON format)
+#[tokio::test]
+async fn test_compact_session_json() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn compact_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "compact-json").await;
+
+    let args = CompactArgs {
+        session: "compact-json".to_string(),
+    };
+    let result = execute_compact(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "Compact (JSON) should succeed");
+}
+
+/// Test compacting non-existent session
+#[tokio::test]
+async fn test_compact_session_not_found() {
+    let (services, _storage_temp) = create_cli_test_services();
+
+    let args = CompactArgs {
+        session: "nonexistent".to_string(),
+    };
+    let result = execute_compact(args, &services, OutputFormat::Human).await;
+    assert!(result.is_err(), "Compact missing session should fail");
+
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("not found"),
+        "Error should mention 'not found': {}",
+        err_msg
+    );
+}
+
+// =============================================================================
+// optimize-session tests
+// =============================================================================
+
+/// Test optimizing an indexed session with an explicit target
+#[tokio::test]
+async fn test_optimize_session_human() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn optimize_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "optimize-test").await;
+
+    let args = OptimizeArgs {
+        session: "optimize-test".to_string(),
+        target_segments: Some(2),
+    };
+    let result = execute_optimize(args, &services, OutputFormat::Human).await;
+    assert!(
+        result.is_ok(),
+        "Optimize should succeed: {:?}",
+        result.err()
+    );
+}
+
+/// Test optimizing with the configured target (JSON format)
+#[tokio::test]
+async fn test_optimize_session_json() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn optimize_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "optimize-json").await;
+
+    let args = OptimizeArgs {
+        session: "optimize-json".to_string(),
+        target_segments: None,
+    };
+    let result = execute_optimize(args, &services, OutputFormat::Json).await;
+    assert!(result.is_ok(), "Optimize (JSON) should succeed");
+}
+
+/// Test optimizing non-existent session
+#[tokio::test]
+async fn test_optimize_session_not_found() {
+    let (services, _storage_temp) = create_cli_test_services();
+
+    let args = OptimizeArgs {
+        session: "nonexistent".to_string(),
+        target_segments: None,
+    };
+    let result = execute_optimize(args, &services, OutputFormat::Human).await;
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("not found"),
+        "Error should mention 'not found': {}",
+        err_msg
+    );
+}
+
+// =============================================================================
+// set-session-defaults tests
+// =============================================================================
+
+fn defaults_args(session: &str) -> SetDefaultsArgs {
+    SetDefaultsArgs {
+        session: session.to_string(),
+        limit: None,
+        fuzziness: None,
+        path_boost: None,
+        output_mode: None,
+        clear: false,
+    }
+}
+
+/// Test setting defaults keeps the options not given
+#[tokio::test]
+async fn test_set_session_defaults_merges() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn defaults_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "defaults-test").await;
+
+    let args = SetDefaultsArgs {
+        limit: Some(20),
+        fuzziness: Some(1),
+        ..defaults_args("defaults-test")
+    };
+    execute_set_defaults(args, &services, OutputFormat::Human)
+        .await
+        .unwrap();
+    let args = SetDefaultsArgs {
+        output_mode: Some("locations".to_string()),
+        ..defaults_args("defaults-test")
+    };
+    execute_set_defaults(args, &services, OutputFormat::Json)
+        .await
+        .unwrap();
+
+    let metadata = services
+        .storage
+        .get_session_metadata("defaults-test")
+        .unwrap();
+    assert_eq!(
+        metadata.search_defaults,
+        Some(SessionSearchDefaults {
+            k: Some(20),
+            fuzziness: Some(1),
+            path_boost: None,
+            output_mode: Some(OutputMode::Locations),
+        })
+    );
+
+    let args = SetDefaultsArgs {
+        clear: true,
+        ..defaults_args("defaults-test")
+    };
+    execute_set_defaults(args, &services, OutputFormat::Human)
+        .await
+        .unwrap();
+    let metadata = services
+        .storage
+        .get_session_metadata("defaults-test")
+        .unwrap();
+    assert_eq!(metadata.search_defaults, None);
+}
+
+/// Test invalid defaults are rejected
+#[tokio::test]
+async fn test_set_session_defaults_invalid() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("file.rs", "fn defaults_me() {}")]);
+
+    setup_indexed_session(&services, repo.path(), "defaults-invalid").await;
+
+    let args = SetDefaultsArgs {
+        output_mode: Some("compact".to_string()),
+        ..defaults_args("defaults-invalid")
+    };
+    let err_msg = execute_set_defaults(args, &services, OutputFormat::Human)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err_msg.contains("Invalid output_mode"), "{err_msg}");
+
+    let args = SetDefaultsArgs {
+        limit: Some(0),
+        ..defaults_args("nonexistent")
+    };
+    let err_msg = execute_set_defaults(args, &services, OutputFormat::Human)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err_msg.contains("not found"), "{err_msg}");
+}
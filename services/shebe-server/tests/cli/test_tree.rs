@@ -0,0 +1,102 @@
+//! Tests for list-tree CLI command
+//!
+//! Tests the tree command handler with real indexed repositories:
+//! - Human and JSON output
+//! - Repository prefix stripping and subtree roots
+//! - Session and path not found errors
+
+use crate::cli::test_helpers::{create_cli_test_services, create_test_repo, setup_indexed_session};
+use shebe::cli::commands::tree::{execute, TreeArgs};
+use shebe::cli::OutputFormat;
+use shebe::core::tree::TreeOptions;
+
+fn tree_args(session: &str, path: Option<&str>) -> TreeArgs {
+    TreeArgs {
+        session: session.to_string(),
+        path: path.map(str::to_string),
+        depth: 3,
+        max_children: 20,
+    }
+}
+
+/// Test tree output in both formats
+#[tokio::test]
+async fn test_list_tree_human_and_json() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/core/lib.rs", "pub fn lib() {}"),
+        ("README.md", "# readme"),
+    ]);
+    setup_indexed_session(&services, repo.path(), "tree-test").await;
+
+    let result = execute(tree_args("tree-test", None), &services, OutputFormat::Human).await;
+    assert!(result.is_ok(), "Tree should succeed: {:?}", result.err());
+
+    let result = execute(tree_args("tree-test", None), &services, OutputFormat::Json).await;
+    assert!(
+        result.is_ok(),
+        "Tree JSON should succeed: {:?}",
+        result.err()
+    );
+}
+
+/// Test that indexed paths are shown relative to the repository
+#[tokio::test]
+async fn test_list_tree_strips_repository_prefix() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[
+        ("src/main.rs", "fn main() {}"),
+        ("src/core/lib.rs", "pub fn lib() {}"),
+        ("README.md", "# readme"),
+    ]);
+    setup_indexed_session(&services, repo.path(), "tree-prefix").await;
+
+    let tree = services
+        .storage
+        .file_tree("tree-prefix", None, TreeOptions::default())
+        .unwrap();
+
+    assert_eq!(tree.file_count, 3);
+    let names: Vec<&str> = tree.children.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["src", "README.md"]);
+
+    let core = services
+        .storage
+        .file_tree("tree-prefix", Some("src/core"), TreeOptions::default())
+        .unwrap();
+    assert_eq!(core.name, "src/core");
+    assert_eq!(core.children[0].name, "lib.rs");
+}
+
+/// Test tree for a missing session
+#[tokio::test]
+async fn test_list_tree_session_not_found() {
+    let (services, _storage_temp) = create_cli_test_services();
+
+    let result = execute(tree_args("missing", None), &services, OutputFormat::Human).await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("not found"), "Unexpected error: {err}");
+}
+
+/// Test tree for a path with no indexed files
+#[tokio::test]
+async fn test_list_tree_unknown_path() {
+    let (services, _storage_temp) = create_cli_test_services();
+    let repo = create_test_repo(&[("src/main.rs", "fn main() {}")]);
+    setup_indexed_session(&services, repo.path(), "tree-path").await;
+
+    let result = execute(
+        tree_args("tree-path", Some("docs")),
+        &services,
+        OutputFormat::Human,
+    )
+    .await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(
+        err.contains("No indexed files under 'docs'"),
+        "Unexpected error: {err}"
+    );
+}
@@ -1,10 +1,15 @@
 // Test helper functions
 
+use serde_json::{json, Value};
 use shebe::core::config::Config;
 use shebe::core::indexer::IndexingPipeline;
 use shebe::core::services::Services;
 use shebe::core::storage::SessionConfig;
 use shebe::core::types::IndexStats;
+use shebe::mcp::error::McpError;
+use shebe::mcp::handlers::ProtocolHandlers;
+use shebe::mcp::protocol::{ContentBlock, JsonRpcRequest, JsonRpcResponse, ToolResult};
+use shebe::mcp::tools::handler::McpToolHandler;
 use std::path::Path;
 
 /// Create test services with temporary storage
@@ -66,7 +71,7 @@ pub async fn index_test_repository_with_patterns(
     include_patterns: Vec<String>,
     exclude_patterns: Vec<String>,
 ) -> IndexStats {
-    let config = &services.config;
+    let config = services.config.current();
 
     // Prepare patterns for both pipeline and SessionConfig
     let include_for_config = if include_patterns.is_empty() {
@@ -86,14 +91,21 @@ pub async fn index_test_repository_with_patterns(
     };
 
     // Create indexing pipeline
-    let pipeline = IndexingPipeline::new(
-        config.indexing.chunk_size,
-        config.indexing.overlap,
-        include_patterns,
-        exclude_patterns,
-        config.indexing.max_file_size_mb,
-    )
-    .expect("Failed to create indexing pipeline");
+    let pipeline = IndexingPipeline::builder()
+        .chunk_size(config.indexing.chunk_size)
+        .overlap(config.indexing.overlap)
+        .include_patterns(include_patterns)
+        .exclude_patterns(exclude_patterns)
+        .max_file_size_mb(config.indexing.max_file_size_mb)
+        .build()
+        .expect("Failed to create indexing pipeline");
+    let session_config = SessionConfig::builder()
+        .chunk_size(config.indexing.chunk_size)
+        .overlap(config.indexing.overlap)
+        .include_patterns(include_for_config)
+        .exclude_patterns(exclude_for_config)
+        .build()
+        .expect("Failed to build session config");
 
     // Index directory
     let start = std::time::Instant::now();
@@ -104,16 +116,7 @@ pub async fn index_test_repository_with_patterns(
     // Create session
     let mut index = services
         .storage
-        .create_session(
-            session_id,
-            repo_path.to_path_buf(),
-            SessionConfig {
-                chunk_size: config.indexing.chunk_size,
-                overlap: config.indexing.overlap,
-                include_patterns: include_for_config.clone(),
-                exclude_patterns: exclude_for_config.clone(),
-            },
-        )
+        .create_session(session_id, repo_path.to_path_buf(), session_config.clone())
         .expect("Failed to create session");
 
     // Add chunks to index
@@ -131,7 +134,9 @@ pub async fn index_test_repository_with_patterns(
     let index_size_bytes = calculate_index_size(&session_path);
 
     use chrono::Utc;
-    use shebe::core::storage::SessionMetadata;
+    use shebe::core::storage::{
+        current_schema_hash_for, IndexSettings, SessionMetadata, SCHEMA_VERSION,
+    };
     let now = Utc::now();
     let metadata = SessionMetadata {
         id: session_id.to_string(),
@@ -141,13 +146,21 @@ pub async fn index_test_repository_with_patterns(
         files_indexed: stats.files_indexed,
         chunks_created: stats.chunks_created,
         index_size_bytes,
-        config: SessionConfig {
-            chunk_size: config.indexing.chunk_size,
-            overlap: config.indexing.overlap,
-            include_patterns: include_for_config,
-            exclude_patterns: exclude_for_config,
-        },
-        schema_version: 3,
+        schema_version: SCHEMA_VERSION,
+        files_deduplicated: 0,
+        created_by_version: env!("CARGO_PKG_VERSION").to_string(),
+        tantivy_schema_hash: current_schema_hash_for(session_config.store_chunk_text),
+        index_settings: Some(IndexSettings::current_for(session_config.store_chunk_text)),
+        remote: None,
+        archive: None,
+        skipped: stats.skipped.clone(),
+        chunk_text_bytes: 0,
+        reindex_schedule: None,
+        last_scheduled_run: None,
+        search_defaults: None,
+        modified_times: None,
+        provenance: None,
+        config: session_config,
     };
 
     services
@@ -160,6 +173,18 @@ pub async fn index_test_repository_with_patterns(
         chunks_created: stats.chunks_created,
         duration_ms,
         session: session_id.to_string(),
+        files_deduplicated: stats.files_deduplicated,
+        skipped: stats.skipped,
+        skipped_files: stats.skipped_files,
+        bytes_stripped: stats.bytes_stripped,
+        files_streamed: stats.files_streamed,
+        truncated_files: stats.truncated_files,
+        files_renamed: stats.files_renamed,
+        renamed_files: stats.renamed_files,
+        patterns: stats.patterns,
+        pattern_warning: stats.pattern_warning,
+        provenance: None,
+        profile: None,
     }
 }
 
@@ -188,6 +213,80 @@ fn calculate_index_size(session_path: &Path) -> u64 {
     total_size
 }
 
+/// Make a file unreadable (chmod 000)
+///
+/// Root ignores file permissions, so a file that can still be read is
+/// replaced by a directory of the same name, which cannot be read as a
+/// file either.
+#[cfg(unix)]
+#[allow(dead_code)] // Used in integration tests
+pub fn make_unreadable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o000)).unwrap();
+    if std::fs::read_to_string(path).is_ok() {
+        std::fs::remove_file(path).unwrap();
+        std::fs::create_dir(path).unwrap();
+    }
+}
+
+/// Call an MCP tool through a JSON-RPC `tools/call` request
+#[allow(dead_code)] // Used in integration tests
+pub async fn call(handlers: &ProtocolHandlers, name: &str, arguments: Value) -> JsonRpcResponse {
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": name, "arguments": arguments })),
+    };
+    handlers.handle_tools_call(request).await.unwrap()
+}
+
+/// Call an MCP tool that should succeed and return its text
+#[allow(dead_code)] // Used in integration tests
+pub async fn call_text(handlers: &ProtocolHandlers, name: &str, arguments: Value) -> String {
+    let response = call(handlers, name, arguments).await;
+    match response.result {
+        Some(result) => result["content"][0]["text"].as_str().unwrap().to_string(),
+        None => panic!("{name} failed: {:?}", response.error),
+    }
+}
+
+/// Call an MCP tool that should fail and return the error message
+#[allow(dead_code)] // Used in integration tests
+pub async fn call_error(handlers: &ProtocolHandlers, name: &str, arguments: Value) -> String {
+    let response = call(handlers, name, arguments).await;
+    match response.error {
+        Some(error) => error.message,
+        None => panic!("{name} succeeded: {:?}", response.result),
+    }
+}
+
+/// Text of a successful `tools/call` response
+#[allow(dead_code)] // Used in integration tests
+pub fn response_text(response: &JsonRpcResponse) -> String {
+    match &response.result {
+        Some(result) => result["content"][0]["text"].as_str().unwrap().to_string(),
+        None => panic!("tool failed: {:?}", response.error),
+    }
+}
+
+/// Text of a tool handler's result
+#[allow(dead_code)] // Used in integration tests
+pub fn extract_text(result: &ToolResult) -> &str {
+    let ContentBlock::Text { text } = &result.content[0];
+    text
+}
+
+/// Execute a tool handler directly and return its text
+#[allow(dead_code)] // Used in integration tests
+pub async fn execute_text(handler: &dyn McpToolHandler, args: Value) -> Result<String, McpError> {
+    handler
+        .execute(args)
+        .await
+        .map(|result| extract_text(&result).to_string())
+}
+
 /// Wait for async operation with timeout
 #[allow(dead_code)] // Reserved for future async tests
 pub async fn wait_with_timeout<F, T>(future: F, timeout_ms: u64) -> Result<T, String>
@@ -7,8 +7,11 @@ pub mod helpers;
 // Note: These may appear unused in unit tests but are used in integration tests
 #[allow(unused_imports)]
 pub use fixtures::{OpenEmrData, TestRepo};
+#[cfg(unix)]
+#[allow(unused_imports)]
+pub use helpers::make_unreadable;
 #[allow(unused_imports)]
 pub use helpers::{
-    assert_valid_stats, create_test_services, index_test_repository,
-    index_test_repository_with_patterns,
+    assert_valid_stats, call, call_error, call_text, create_test_services, execute_text,
+    extract_text, index_test_repository, index_test_repository_with_patterns, response_text,
 };
@@ -4,12 +4,18 @@
 //! - Storage: Session management and persistence
 //! - Search: BM25 search functionality
 //! - Indexer: UTF-8 safe chunking and file processing
+//! - Health: Storage readiness checks
+//! - Schedule: Background re-indexing on a schedule
+//! - Maintenance: Session optimization and idle-time merging
 
 mod common;
 
 // Core submodules - tests/core/ directory
 mod core {
+    pub mod health;
     pub mod indexer;
+    pub mod maintenance;
+    pub mod schedule;
     pub mod search;
     pub mod storage;
 }
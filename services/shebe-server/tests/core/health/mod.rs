@@ -0,0 +1,7 @@
+//! Readiness and session health tests
+//!
+//! Tests for storage root, session listing and canary session checks,
+//! and for index health and freshness of a single session.
+
+mod test_readiness;
+mod test_session_health;
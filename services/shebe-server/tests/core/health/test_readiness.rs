@@ -0,0 +1,95 @@
+// Integration tests for readiness checks
+
+use crate::common::{index_test_repository, TestRepo};
+use shebe::core::config::Config;
+use shebe::core::health::{CHECK_CANARY_SESSION, CHECK_STORAGE_ROOT};
+use shebe::core::services::Services;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+fn services_at(temp_dir: &TempDir, canary: Option<&str>) -> Services {
+    let mut config = Config::default();
+    config.storage.index_dir = temp_dir.path().to_path_buf();
+    config.health.canary_session = canary.map(str::to_string);
+    Services::new(config)
+}
+
+#[tokio::test]
+async fn test_ready_with_canary_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let services = services_at(&temp_dir, Some("canary"));
+
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "canary").await;
+
+    let report = services.readiness();
+    assert!(report.ready, "Expected ready, got {:?}", report.checks);
+    assert_eq!(report.checks.len(), 3);
+}
+
+// Relies on Unix permission bits
+#[cfg(unix)]
+#[test]
+fn test_unwritable_storage_root_not_ready() {
+    let temp_dir = TempDir::new().unwrap();
+    let services = services_at(&temp_dir, None);
+
+    fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o555)).unwrap();
+
+    // Root ignores permission bits, so there is nothing to simulate
+    let probe = temp_dir.path().join("probe");
+    if fs::write(&probe, b"x").is_ok() {
+        fs::remove_file(&probe).unwrap();
+        fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        return;
+    }
+
+    let report = services.readiness();
+    fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert!(!report.ready);
+    let failing: Vec<_> = report.failing().collect();
+    assert_eq!(failing.len(), 1);
+    assert_eq!(failing[0].name, CHECK_STORAGE_ROOT);
+    assert!(failing[0].detail.contains("not writable"));
+}
+
+#[test]
+fn test_storage_root_is_file_not_ready() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("sessions");
+    fs::write(&root, b"not a directory").unwrap();
+
+    let mut config = Config::default();
+    config.storage.index_dir = root;
+    let services = Services::new(config);
+
+    let report = services.readiness();
+
+    assert!(!report.ready);
+    assert!(report.failing().any(|c| c.name == CHECK_STORAGE_ROOT));
+}
+
+#[tokio::test]
+async fn test_corrupt_canary_index_not_ready() {
+    let temp_dir = TempDir::new().unwrap();
+    let services = services_at(&temp_dir, Some("canary"));
+
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "canary").await;
+
+    // Replace the index with garbage
+    let tantivy_dir = services.storage.get_session_path("canary").join("tantivy");
+    fs::remove_dir_all(&tantivy_dir).unwrap();
+    fs::create_dir_all(&tantivy_dir).unwrap();
+    fs::write(tantivy_dir.join("meta.json"), b"{ corrupt").unwrap();
+
+    let report = services.readiness();
+
+    assert!(!report.ready);
+    let failing: Vec<_> = report.failing().collect();
+    assert_eq!(failing.len(), 1);
+    assert_eq!(failing[0].name, CHECK_CANARY_SESSION);
+}
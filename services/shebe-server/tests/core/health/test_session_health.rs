@@ -0,0 +1,126 @@
+// Integration tests for session health and freshness
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use chrono::{Duration, Utc};
+use shebe::core::storage::{HealthAction, MAX_FILE_CHECKS};
+use std::fs;
+use tempfile::TempDir;
+
+const STALE_AFTER_DAYS: u64 = 7;
+
+#[tokio::test]
+async fn test_fresh_session_is_healthy() {
+    let services = create_test_services();
+    let repo = TestRepo::small();
+    let stats = index_test_repository(&services, repo.path(), "fresh").await;
+
+    let health = services
+        .storage
+        .session_health("fresh", STALE_AFTER_DAYS)
+        .unwrap();
+    assert!(health.is_healthy(), "{:?}", health.actions);
+    assert!(health.repository_exists);
+    assert_eq!(health.index_docs, Some(stats.chunks_created as u64));
+    let files = health.files.unwrap();
+    assert_eq!(files.checked, stats.files_indexed);
+    assert!(!files.sampled);
+    assert_eq!(files.changed(), 0);
+    assert!(!health.stale);
+}
+
+#[tokio::test]
+async fn test_moved_repository() {
+    let services = create_test_services();
+    let root = TempDir::new().unwrap();
+    let repo = root.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    fs::write(repo.join("main.rs"), "fn main() {}").unwrap();
+    index_test_repository(&services, &repo, "moved").await;
+
+    fs::rename(&repo, root.path().join("elsewhere")).unwrap();
+
+    let health = services
+        .storage
+        .session_health("moved", STALE_AFTER_DAYS)
+        .unwrap();
+    assert!(!health.repository_exists);
+    assert!(health.files.is_none(), "files are not checked");
+    assert!(matches!(
+        health.actions.as_slice(),
+        [HealthAction::RepositoryMissing { .. }]
+    ));
+}
+
+#[tokio::test]
+async fn test_stale_session() {
+    let services = create_test_services();
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "stale").await;
+
+    let mut metadata = services.storage.get_session_metadata("stale").unwrap();
+    metadata.last_indexed_at = Utc::now() - Duration::days(30);
+    services
+        .storage
+        .update_session_metadata("stale", &metadata)
+        .unwrap();
+
+    let health = services
+        .storage
+        .session_health("stale", STALE_AFTER_DAYS)
+        .unwrap();
+    assert!(health.stale);
+    assert_eq!(health.age_days(), 30);
+    // Every file was written after the (backdated) indexing
+    let files = health.files.as_ref().unwrap();
+    assert_eq!(files.modified, files.checked);
+    assert!(health
+        .actions
+        .contains(&HealthAction::Stale { age_days: 30 }));
+    assert!(health
+        .actions
+        .iter()
+        .any(|a| matches!(a, HealthAction::FilesChanged { .. })));
+
+    let health = services.storage.session_health("stale", 60).unwrap();
+    assert!(!health.stale);
+}
+
+#[tokio::test]
+async fn test_deleted_files_counted() {
+    let services = create_test_services();
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "deleted").await;
+
+    fs::remove_file(repo.path().join("src/lib.rs")).unwrap();
+
+    let health = services
+        .storage
+        .session_health("deleted", STALE_AFTER_DAYS)
+        .unwrap();
+    let files = health.files.as_ref().unwrap();
+    assert_eq!((files.missing, files.modified), (1, 0));
+    assert_eq!(health.actions[0].summary(), "1 file changed since indexing");
+}
+
+#[tokio::test]
+async fn test_large_session_is_sampled() {
+    let services = create_test_services();
+    let contents: Vec<(String, String)> = (0..MAX_FILE_CHECKS + 100)
+        .map(|i| (format!("src/f{i:04}.rs"), format!("fn f{i}() {{}}")))
+        .collect();
+    let files: Vec<(&str, &str)> = contents
+        .iter()
+        .map(|(path, text)| (path.as_str(), text.as_str()))
+        .collect();
+    let repo = TestRepo::with_files(&files);
+    index_test_repository(&services, repo.path(), "large").await;
+
+    let health = services
+        .storage
+        .session_health("large", STALE_AFTER_DAYS)
+        .unwrap();
+    let files = health.files.unwrap();
+    assert!(files.sampled);
+    assert_eq!(files.checked, MAX_FILE_CHECKS);
+    assert_eq!(files.indexed, MAX_FILE_CHECKS + 100);
+}
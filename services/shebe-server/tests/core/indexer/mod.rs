@@ -1,8 +1,12 @@
 //! Indexer layer tests
 //!
 //! UTF-8 safety tests for text chunking with emojis, multi-byte characters,
-//! and mixed content.
+//! and mixed content, stripping of long blobs before chunking, and
+//! streaming of large files.
 
 mod test_emoji;
 mod test_mixed;
 mod test_multibyte;
+mod test_profile;
+mod test_streaming;
+mod test_strip;
@@ -0,0 +1,63 @@
+// Profiled indexing runs
+//
+// A run built with `profile(true)` reports the time spent in every
+// stage and the slowest files in its stats; an unprofiled run reports
+// nothing.
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::types::IndexStage;
+
+#[test]
+fn test_profiled_run_reports_every_stage() {
+    let services = create_test_services();
+    let repo = TestRepo::medium();
+
+    let stats = services
+        .index(repo.path())
+        .session("profiled")
+        .profile(true)
+        .run()
+        .unwrap()
+        .stats;
+
+    let profile = stats.profile.expect("profiled run has a profile");
+    let stages: Vec<IndexStage> = profile.stages.iter().map(|s| s.stage).collect();
+    assert_eq!(stages, IndexStage::ALL);
+    for timing in &profile.stages {
+        let share = profile.percent(timing);
+        assert!((0.0..=100.0).contains(&share), "{timing:?}: {share}%");
+    }
+    assert!(profile.chunk_text_bytes > 0);
+
+    // Ten of the 50 files, slowest first, under their indexed paths
+    assert_eq!(profile.slowest_files.len(), 10);
+    assert!(profile
+        .slowest_files
+        .windows(2)
+        .all(|w| w[0].duration_us >= w[1].duration_us));
+    let root = repo.path().canonicalize().unwrap();
+    for file in &profile.slowest_files {
+        assert!(
+            file.path.starts_with(root.to_str().unwrap()),
+            "{}",
+            file.path
+        );
+    }
+}
+
+#[test]
+fn test_unprofiled_run_has_no_profile() {
+    let services = create_test_services();
+    let repo = TestRepo::small();
+
+    let stats = services
+        .index(repo.path())
+        .session("plain")
+        .run()
+        .unwrap()
+        .stats;
+
+    assert!(stats.profile.is_none());
+    let json = serde_json::to_value(&stats).unwrap();
+    assert!(json.get("profile").is_none());
+}
@@ -0,0 +1,114 @@
+// Streaming of large files and the maximum line length
+//
+// A 50MB single-line file (the shape of a JSON dump) is over the
+// streaming threshold: it is chunked while being read, with the same
+// chunks as reading it whole would give. Under the default maximum
+// line length it is skipped instead of indexed.
+
+use crate::common::TestRepo;
+use shebe::core::indexer::{Chunker, IndexingPipeline};
+use shebe::core::types::SkipReason;
+use std::fs;
+
+const DUMP_BYTES: usize = 50 * 1024 * 1024;
+
+/// One line of JSON records, `DUMP_BYTES` long
+fn json_dump() -> String {
+    let mut text = String::with_capacity(DUMP_BYTES);
+    text.push('[');
+    let mut i = 0;
+    while text.len() < DUMP_BYTES - 64 {
+        text.push_str(&format!(
+            "{{\"id\":{i},\"name\":\"user-{i}\",\"city\":\"Zürich\"}},"
+        ));
+        i += 1;
+    }
+    text.push_str(&" ".repeat(DUMP_BYTES - 1 - text.len()));
+    text.push(']');
+    text
+}
+
+#[test]
+fn test_large_single_line_file_is_streamed() {
+    let dump = json_dump();
+    let repo = TestRepo::with_files(&[("data/dump.json", &dump), ("src/lib.rs", "pub fn a() {}")]);
+
+    let pipeline = IndexingPipeline::builder()
+        .include_patterns(["**/*.json", "**/*.rs"])
+        .max_file_size_mb(64)
+        .max_line_length(0)
+        // The whole dump, not just the default 2000 chunks
+        .max_chunks_per_file(usize::MAX)
+        .build()
+        .unwrap();
+    let (chunks, stats) = pipeline.index_directory(repo.path()).unwrap();
+
+    assert_eq!(stats.files_indexed, 2);
+    assert_eq!(
+        stats.files_streamed, 1,
+        "only the dump is over the threshold"
+    );
+
+    let dump_path = repo.path().join("data/dump.json");
+    let dump_chunks: Vec<_> = chunks.iter().filter(|c| c.file_path == dump_path).collect();
+    let chunker = Chunker::new(512, 64);
+    for (i, chunk) in dump_chunks.iter().enumerate() {
+        assert_eq!(chunk.chunk_index, i);
+        assert_eq!(chunk.text, dump[chunk.start_offset..chunk.end_offset]);
+    }
+    // Cuts move to word edges, so chunks overlap by varying amounts but
+    // leave no gaps
+    for pair in dump_chunks.windows(2) {
+        assert!(pair[1].start_offset < pair[0].end_offset);
+    }
+    assert_eq!(dump_chunks[0].start_offset, 0);
+    assert_eq!(dump_chunks.last().unwrap().end_offset, dump.len());
+
+    // Every streamed chunk is one reading the file whole would produce
+    let sample = &dump[..3 * 1024 * 1024];
+    let sample_path = repo.path().join("data/sample.json");
+    fs::write(&sample_path, sample).unwrap();
+    let whole = chunker.chunk_text(sample, &sample_path);
+    for (streamed, whole) in dump_chunks.iter().zip(&whole[..whole.len() - 1]) {
+        assert_eq!(
+            (streamed.start_offset, streamed.end_offset),
+            (whole.start_offset, whole.end_offset)
+        );
+    }
+}
+
+#[test]
+fn test_overlong_line_is_skipped() {
+    let dump = json_dump();
+    let repo = TestRepo::with_files(&[("data/dump.json", &dump), ("src/lib.rs", "pub fn a() {}")]);
+
+    // Default maximum line length (1,000,000 characters)
+    let pipeline = IndexingPipeline::builder()
+        .include_patterns(["**/*.json", "**/*.rs"])
+        .max_file_size_mb(64)
+        .build()
+        .unwrap();
+    let (_, stats) = pipeline.index_directory(repo.path()).unwrap();
+
+    assert_eq!(stats.files_indexed, 1);
+    assert_eq!(stats.skipped.line_too_long, 1);
+    let skipped = &stats.skipped_files[0];
+    assert_eq!(skipped.path, "data/dump.json");
+    assert_eq!(skipped.reason, SkipReason::LineTooLong);
+    assert!(skipped.detail.contains("line 1"), "{}", skipped.detail);
+
+    // Small files are checked too, without streaming
+    let pipeline = IndexingPipeline::builder()
+        .include_patterns(["**/*.rs"])
+        .max_line_length(10)
+        .build()
+        .unwrap();
+    let (_, stats) = pipeline.index_directory(repo.path()).unwrap();
+    assert_eq!(stats.files_streamed, 0);
+    assert_eq!(stats.skipped.line_too_long, 1);
+
+    let explained = pipeline
+        .explain(repo.path(), &repo.path().join("src/lib.rs"))
+        .unwrap();
+    assert_eq!(explained.reason, Some(SkipReason::LineTooLong));
+}
@@ -0,0 +1,90 @@
+// High-entropy blob stripping
+//
+// A file with a 50KB single-line base64 blob indexes smaller with
+// strip_high_entropy, and its ordinary code stays searchable.
+
+use crate::common::{create_test_services, TestRepo};
+
+/// Base64-looking text from a fixed-seed generator
+fn base64_blob(len: usize) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ALPHABET[(state % 64) as usize] as char
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_strip_high_entropy_shrinks_index() {
+    let source = format!(
+        "fn load_logo() -> &'static str {{\n    LOGO_PNG\n}}\n\n\
+         const LOGO_PNG: &str = \"data:image/png;base64,{}\";\n\n\
+         fn render_banner(title: &str) -> String {{\n    format!(\"<h1>{{title}}</h1>\")\n}}\n",
+        base64_blob(50 * 1024)
+    );
+    let repo = TestRepo::with_files(&[("src/logo.rs", &source)]);
+    let services = create_test_services();
+
+    let plain = services
+        .index(repo.path())
+        .session("plain")
+        .run()
+        .unwrap()
+        .stats;
+    let stripped = services
+        .index(repo.path())
+        .session("stripped")
+        .strip_high_entropy(true)
+        .run()
+        .unwrap()
+        .stats;
+
+    assert_eq!(plain.bytes_stripped, 0);
+    assert!(stripped.bytes_stripped > 50 * 1024, "{stripped:?}");
+    assert!(stripped.chunks_created < plain.chunks_created / 10);
+
+    let size = |session: &str| {
+        services
+            .storage
+            .get_session_metadata(session)
+            .unwrap()
+            .index_size_bytes
+    };
+    assert!(
+        size("stripped") < size("plain"),
+        "stripped {} vs plain {}",
+        size("stripped"),
+        size("plain")
+    );
+
+    // Ordinary identifiers on both sides of the blob are still found
+    for identifier in ["load_logo", "render_banner"] {
+        let results = services
+            .search
+            .search_session("stripped", identifier, Some(5))
+            .unwrap();
+        assert!(
+            results.results[0].file_path.ends_with("src/logo.rs"),
+            "{identifier}"
+        );
+    }
+
+    // Offsets point into the real file, so the blob is still there
+    let results = services
+        .search
+        .search_session("stripped", "LOGO_PNG", Some(10))
+        .unwrap();
+    let blob_chunk = results
+        .results
+        .iter()
+        .find(|r| r.text.contains("[stripped "))
+        .expect("chunk holding the placeholder");
+    assert!(source[blob_chunk.start_offset..blob_chunk.end_offset].contains("base64,"));
+    assert!(blob_chunk.locate_in(&source).range().is_some());
+    assert!(!blob_chunk.locate_in(&source).is_drifted());
+}
@@ -0,0 +1,5 @@
+//! Idle maintenance tests
+//!
+//! Tests for session optimization and the idle-time maintenance pass.
+
+mod test_maintenance;
@@ -0,0 +1,118 @@
+// Integration tests for session optimization and idle maintenance
+
+use crate::common::create_test_services;
+use shebe::core::maintenance::MaintenanceTask;
+use shebe::core::services::Services;
+use shebe::core::storage::SessionConfig;
+use shebe::core::types::{Chunk, SearchResponse};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Create a session spread over many small commits
+fn create_fragmented_session(services: &Services, session: &str) {
+    services
+        .storage
+        .create_session(
+            session,
+            PathBuf::from("/test/repo"),
+            SessionConfig::default(),
+        )
+        .expect("Failed to create session");
+
+    let mut index = services.storage.open_session(session).unwrap();
+    for i in 0..30 {
+        let text = format!("fn shared_symbol_{i}() {{ shared_symbol() }}");
+        let chunk = Chunk {
+            end_offset: text.len(),
+            text,
+            file_path: PathBuf::from(format!("/test/repo/src/mod{i}.rs")),
+            start_offset: 0,
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        index.add_chunks(&[chunk], session).unwrap();
+        index.commit().unwrap();
+    }
+    index.release_writer().unwrap();
+}
+
+fn result_paths(services: &Services, session: &str) -> Vec<(String, usize)> {
+    let response: SearchResponse = services
+        .search
+        .search_session(session, "shared_symbol", Some(50))
+        .unwrap();
+    let mut paths: Vec<(String, usize)> = response
+        .results
+        .into_iter()
+        .map(|r| (r.file_path, r.chunk_index))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn test_optimize_session_reduces_segments() {
+    let services = create_test_services();
+    create_fragmented_session(&services, "fragmented");
+    let before = result_paths(&services, "fragmented");
+
+    // Background merges leave a few segments behind; optimizing merges
+    // them down to the target
+    let stats = services.storage.optimize_session("fragmented", 1).unwrap();
+    assert!(
+        stats.segments_before > stats.segments_after,
+        "{} -> {}",
+        stats.segments_before,
+        stats.segments_after
+    );
+    assert_eq!(stats.segments_after, 1);
+
+    // Same results, and the stored size matches the optimized index
+    assert_eq!(before.len(), 30);
+    assert_eq!(result_paths(&services, "fragmented"), before);
+    let metadata = services.storage.get_session_metadata("fragmented").unwrap();
+    assert_eq!(metadata.index_size_bytes, stats.size_after_bytes);
+
+    // Already at the target: nothing left to merge
+    let again = services.storage.optimize_session("fragmented", 1).unwrap();
+    assert_eq!(again.segments_before, again.segments_after);
+}
+
+#[tokio::test]
+async fn test_optimize_session_not_found() {
+    let services = create_test_services();
+    assert!(services.storage.optimize_session("missing", 1).is_err());
+}
+
+#[tokio::test]
+async fn test_maintenance_tick_optimizes_idle_sessions_once() {
+    let services = Arc::new(create_test_services());
+    create_fragmented_session(&services, "idle-a");
+    create_fragmented_session(&services, "idle-b");
+    let before = result_paths(&services, "idle-a");
+
+    let mut task = MaintenanceTask::new(Arc::clone(&services));
+
+    // Not idle long enough yet
+    assert!(task.tick(Instant::now()).is_empty());
+
+    let idle = Instant::now() + Duration::from_secs(3600);
+    let mut optimized = task.tick(idle);
+    optimized.sort_by(|a, b| a.session.cmp(&b.session));
+    assert_eq!(optimized.len(), 2);
+    assert_eq!(optimized[0].session, "idle-a");
+    assert!(optimized.iter().all(|stats| stats.segments_after == 1));
+    assert_eq!(result_paths(&services, "idle-a"), before);
+
+    // One pass per idle period
+    assert!(task.tick(idle + Duration::from_secs(60)).is_empty());
+
+    // A tool call starts a new period; none runs while it is in flight
+    let guard = services.activity.begin();
+    assert!(task.tick(idle).is_empty());
+    drop(guard);
+    let later = Instant::now() + Duration::from_secs(3600);
+    assert_eq!(task.tick(later).len(), 2);
+}
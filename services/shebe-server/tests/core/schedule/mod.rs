@@ -0,0 +1,5 @@
+//! Scheduled re-index tests
+//!
+//! Tests for due detection, run recording and schedule carry-over.
+
+mod test_scheduler;
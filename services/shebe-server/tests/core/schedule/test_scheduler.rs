@@ -0,0 +1,160 @@
+// Integration tests for the background reindex scheduler
+
+use crate::common::TestRepo;
+use chrono::{DateTime, TimeDelta, Utc};
+use shebe::core::config::Config;
+use shebe::core::jobs::JobState;
+use shebe::core::schedule::{schedule_status, ReindexScheduler, Schedule};
+use shebe::core::services::Services;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Services whose config schedules `session` every 30 minutes
+fn scheduled_services(session: &str) -> Arc<Services> {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut config = Config::default();
+    config.storage.index_dir = temp_dir.path().to_path_buf();
+    config.schedule.enabled = true;
+    config
+        .schedule
+        .sessions
+        .insert(session.to_string(), "30m".to_string());
+    std::mem::forget(temp_dir);
+    Arc::new(Services::new(config))
+}
+
+fn index(services: &Services, repo_path: &Path, session_id: &str) {
+    services
+        .storage
+        .index_repository(session_id, repo_path, vec![], vec![], 512, 64, 10, false)
+        .expect("Failed to index repository");
+}
+
+fn last_indexed_at(services: &Services, session: &str) -> DateTime<Utc> {
+    services
+        .storage
+        .get_session_metadata(session)
+        .unwrap()
+        .last_indexed_at
+}
+
+/// Tick until the scheduler has recorded the session's run
+fn wait_for_run(scheduler: &mut ReindexScheduler, services: &Services, session: &str) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        scheduler.tick(Utc::now());
+        let metadata = services.storage.get_session_metadata(session).unwrap();
+        if metadata.last_scheduled_run.is_some() {
+            return;
+        }
+        assert!(Instant::now() < deadline, "Scheduled run was not recorded");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_due_session_is_reindexed() {
+    let services = scheduled_services("due");
+    let repo = TestRepo::small();
+    index(&services, repo.path(), "due");
+    services
+        .storage
+        .set_reindex_schedule("due", Some(Schedule::Every { minutes: 30 }))
+        .unwrap();
+    let indexed_at = last_indexed_at(&services, "due");
+    let mut scheduler = ReindexScheduler::new(Arc::clone(&services));
+
+    assert!(
+        scheduler
+            .tick(indexed_at + TimeDelta::minutes(29))
+            .is_empty(),
+        "Not due yet"
+    );
+    assert_eq!(scheduler.tick(indexed_at + TimeDelta::minutes(31)), ["due"]);
+    // Running, so not started twice
+    assert!(scheduler
+        .tick(indexed_at + TimeDelta::minutes(32))
+        .is_empty());
+
+    wait_for_run(&mut scheduler, &services, "due");
+
+    let metadata = services.storage.get_session_metadata("due").unwrap();
+    let run = metadata.last_scheduled_run.clone().unwrap();
+    assert_eq!(run.state, JobState::Completed, "{run:?}");
+    assert!(run.job_id.is_some());
+    assert!(metadata.last_indexed_at > indexed_at);
+    assert_eq!(
+        metadata.reindex_schedule,
+        Some(Schedule::Every { minutes: 30 }),
+        "Schedule should survive the re-index"
+    );
+    let status = schedule_status(&metadata, &services.config.current().schedule).unwrap();
+    assert_eq!(
+        status.next_run,
+        Some(run.finished_at.unwrap().max(metadata.last_indexed_at) + TimeDelta::minutes(30))
+    );
+}
+
+#[test]
+fn test_failed_run_is_recorded_and_not_retried() {
+    let services = scheduled_services("gone");
+    let repo = TestRepo::small();
+    index(&services, repo.path(), "gone");
+    let indexed_at = last_indexed_at(&services, "gone");
+    drop(repo);
+    let mut scheduler = ReindexScheduler::new(Arc::clone(&services));
+
+    let due = indexed_at + TimeDelta::minutes(31);
+    assert!(scheduler.tick(due).is_empty(), "No job for a missing path");
+
+    let metadata = services.storage.get_session_metadata("gone").unwrap();
+    let run = metadata.last_scheduled_run.clone().unwrap();
+    assert_eq!(run.state, JobState::Failed);
+    assert!(run.error.is_some());
+    // The old index is kept and the next try waits for the next slot
+    assert_eq!(metadata.last_indexed_at, indexed_at);
+    let next_run = schedule_status(&metadata, &services.config.current().schedule)
+        .unwrap()
+        .next_run
+        .unwrap();
+    assert_eq!(next_run, due + TimeDelta::minutes(30));
+    assert!(scheduler.tick(due + TimeDelta::minutes(29)).is_empty());
+    assert!(services.jobs.list().is_empty());
+}
+
+#[test]
+fn test_session_schedule_overrides_config() {
+    let services = scheduled_services("paused");
+    let repo = TestRepo::small();
+    index(&services, repo.path(), "paused");
+    services
+        .storage
+        .set_reindex_schedule("paused", Some(Schedule::Off))
+        .unwrap();
+    let mut scheduler = ReindexScheduler::new(Arc::clone(&services));
+
+    let later = last_indexed_at(&services, "paused") + TimeDelta::days(7);
+    assert!(scheduler.tick(later).is_empty());
+
+    services
+        .storage
+        .set_reindex_schedule("paused", None)
+        .unwrap();
+    assert_eq!(scheduler.tick(later), ["paused"]);
+    wait_for_run(&mut scheduler, &services, "paused");
+}
+
+#[test]
+fn test_unscheduled_sessions_are_left_alone() {
+    let services = scheduled_services("other");
+    let repo = TestRepo::small();
+    index(&services, repo.path(), "manual");
+    let mut scheduler = ReindexScheduler::new(Arc::clone(&services));
+
+    let later = last_indexed_at(&services, "manual") + TimeDelta::days(7);
+    assert!(scheduler.tick(later).is_empty());
+    let metadata = services.storage.get_session_metadata("manual").unwrap();
+    assert!(metadata.last_scheduled_run.is_none());
+    assert!(schedule_status(&metadata, &services.config.current().schedule).is_none());
+}
@@ -1,5 +1,9 @@
 //! Search layer tests
 //!
-//! Tests for BM25 search functionality, query parsing and result ranking.
+//! Tests for BM25 search functionality, query parsing and result ranking,
+//! including short-chunk demotion and the modified_within filter.
 
+mod test_concurrency;
+mod test_recency;
 mod test_search;
+mod test_short_chunks;
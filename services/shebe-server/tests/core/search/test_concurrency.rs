@@ -0,0 +1,131 @@
+// Stress tests for concurrent searches across sessions
+//
+// Searches share one cached reader per session (see
+// core::storage::readers); these tests check that parallel searches
+// return each session's own results and that a committed update is
+// visible to the next search.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use shebe::core::types::Chunk;
+use std::sync::Arc;
+use std::thread;
+
+const SESSIONS: usize = 4;
+const THREADS: usize = 8;
+const SEARCHES_PER_THREAD: usize = 25;
+
+fn session_repo(n: usize) -> TestRepo {
+    let marker = format!("marker_{n}");
+    let files: Vec<(String, String)> = (0..5)
+        .map(|i| {
+            (
+                format!("src/file_{i}.rs"),
+                format!("pub fn {marker}_func_{i}() {{ shared_helper(); }}\n// {marker}\n"),
+            )
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(p, c)| (p.as_str(), c.as_str()))
+        .collect();
+    TestRepo::with_files(&files)
+}
+
+#[tokio::test]
+async fn test_parallel_searches_across_sessions() {
+    let services = Arc::new(create_test_services());
+    let repos: Vec<TestRepo> = (0..SESSIONS).map(session_repo).collect();
+    for (n, repo) in repos.iter().enumerate() {
+        index_test_repository(&services, repo.path(), &format!("concurrent-{n}")).await;
+    }
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let services = Arc::clone(&services);
+            thread::spawn(move || {
+                for i in 0..SEARCHES_PER_THREAD {
+                    let n = (t + i) % SESSIONS;
+                    let session = format!("concurrent-{n}");
+                    let response = services
+                        .search
+                        .search_session(&session, &format!("marker_{n}"), Some(10))
+                        .expect("Search failed");
+
+                    assert_eq!(response.results.len(), 5, "session {session}");
+                    for result in &response.results {
+                        assert!(result.text.contains(&format!("marker_{n}")));
+                    }
+                    // Other sessions' content never leaks in
+                    let other = (n + 1) % SESSIONS;
+                    let leaked = services
+                        .search
+                        .search_session(&session, &format!("marker_{other}"), Some(10))
+                        .expect("Search failed");
+                    assert!(leaked.results.is_empty(), "session {session}");
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().expect("Search thread panicked");
+    }
+}
+
+#[tokio::test]
+async fn test_search_sees_committed_update() {
+    let services = Arc::new(create_test_services());
+    let repo = session_repo(0);
+    index_test_repository(&services, repo.path(), "fresh").await;
+
+    // Cache a reader, then search from several threads while a writer commits
+    assert!(services
+        .search
+        .search_session("fresh", "late_addition", Some(5))
+        .unwrap()
+        .results
+        .is_empty());
+
+    let searchers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let services = Arc::clone(&services);
+            thread::spawn(move || {
+                for _ in 0..SEARCHES_PER_THREAD {
+                    let response = services
+                        .search
+                        .search_session("fresh", "marker_0", Some(10))
+                        .expect("Search failed");
+                    assert!(!response.results.is_empty());
+                }
+            })
+        })
+        .collect();
+
+    let mut index = services.storage.open_session("fresh").unwrap();
+    let text = "pub fn late_addition() {}\n";
+    index
+        .add_chunks(
+            &[Chunk {
+                text: text.to_string(),
+                file_path: repo.path().join("src/late.rs"),
+                start_offset: 0,
+                end_offset: text.len(),
+                chunk_index: 0,
+                records: None,
+                section: None,
+            }],
+            "fresh",
+        )
+        .unwrap();
+    index.commit().unwrap();
+
+    for handle in searchers {
+        handle.join().expect("Search thread panicked");
+    }
+
+    let response = services
+        .search
+        .search_session("fresh", "late_addition", Some(5))
+        .unwrap();
+    assert_eq!(response.results.len(), 1);
+}
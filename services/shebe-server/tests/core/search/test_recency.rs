@@ -0,0 +1,165 @@
+// Searching recently changed files (modified_within)
+//
+// The fixture is a git repository built with the git CLI, whose two
+// commits are backdated: one file last changed a year ago, the other
+// two days ago. Outside git, and with git_metadata off, the filter
+// uses file modification times instead.
+
+use crate::common::{create_test_services, TestRepo};
+use chrono::{Duration, Utc};
+use shebe::core::types::ModifiedTimeSource;
+use shebe::Shebe;
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in `dir` with a fixed identity, failing the test on error
+fn git(dir: &Path, args: &[&str], date: Option<&str>) {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args);
+    if let Some(date) = date {
+        cmd.env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date);
+    }
+    let output = cmd.output().expect("git should run");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// `old.rs` committed a year ago, `recent.rs` two days ago
+fn git_repo() -> TestRepo {
+    let repo = TestRepo::with_files(&[
+        (
+            "src/old.rs",
+            "pub fn settle_ledger() { /* ledger from last year */ }",
+        ),
+        (
+            "src/recent.rs",
+            "pub fn audit_ledger() { /* ledger added this week */ }",
+        ),
+    ]);
+    let dir = repo.path();
+    let year_ago = (Utc::now() - Duration::days(365)).to_rfc3339();
+    let days_ago = (Utc::now() - Duration::days(2)).to_rfc3339();
+
+    git(dir, &["init", "-q"], None);
+    git(dir, &["add", "src/old.rs"], None);
+    git(dir, &["commit", "-q", "-m", "old"], Some(&year_ago));
+    git(dir, &["add", "src/recent.rs"], None);
+    git(dir, &["commit", "-q", "-m", "recent"], Some(&days_ago));
+    repo
+}
+
+#[tokio::test]
+async fn test_modified_within_uses_commit_times() {
+    let repo = git_repo();
+    let shebe = Shebe::from(create_test_services());
+    shebe.index(repo.path()).session("history").run().unwrap();
+
+    let all = shebe.search("ledger").session("history").run().unwrap();
+    assert_eq!(all.results.len(), 2);
+    assert_eq!(all.modified_times, Some(ModifiedTimeSource::Git));
+    let old = all
+        .results
+        .iter()
+        .find(|r| r.file_path.ends_with("src/old.rs"))
+        .unwrap();
+    let age = Utc::now() - old.modified_at.expect("commit time recorded");
+    assert!(
+        age.num_days() >= 364,
+        "old.rs was committed a year ago: {age}"
+    );
+
+    let recent = shebe
+        .search("ledger")
+        .session("history")
+        .modified_within("30d")
+        .run()
+        .unwrap();
+    assert_eq!(recent.results.len(), 1);
+    assert!(recent.results[0].file_path.ends_with("src/recent.rs"));
+    assert_eq!(
+        recent.results[0].score,
+        all.results
+            .iter()
+            .find(|r| r.file_path.ends_with("src/recent.rs"))
+            .unwrap()
+            .score
+    );
+    let summary = recent.recency_summary().unwrap();
+    assert!(summary.contains("committed"), "{summary}");
+    assert!(!summary.contains("Note"), "{summary}");
+
+    // A date before both commits keeps everything
+    let since = (Utc::now() - Duration::days(400))
+        .format("%Y-%m-%d")
+        .to_string();
+    let both = shebe
+        .search("ledger")
+        .session("history")
+        .modified_within(since)
+        .run()
+        .unwrap();
+    assert_eq!(both.results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_git_metadata_off_uses_mtime() {
+    let repo = git_repo();
+    let shebe = Shebe::from(create_test_services());
+    shebe
+        .index(repo.path())
+        .session("no-git")
+        .git_metadata(false)
+        .run()
+        .unwrap();
+
+    // Both files were just written, so both count as recent
+    let response = shebe
+        .search("ledger")
+        .session("no-git")
+        .modified_within("1d")
+        .run()
+        .unwrap();
+    assert_eq!(response.results.len(), 2);
+    assert_eq!(response.modified_times, Some(ModifiedTimeSource::Mtime));
+    let summary = response.recency_summary().unwrap();
+    assert!(summary.contains("no git history"), "{summary}");
+}
+
+#[tokio::test]
+async fn test_non_git_directory_falls_back_to_mtime() {
+    let repo = TestRepo::with_files(&[("src/plain.rs", "pub fn ledger_total() {}")]);
+    let shebe = Shebe::from(create_test_services());
+    shebe.index(repo.path()).session("plain").run().unwrap();
+
+    let response = shebe
+        .search("ledger_total")
+        .session("plain")
+        .modified_within("12h")
+        .run()
+        .unwrap();
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(response.modified_times, Some(ModifiedTimeSource::Mtime));
+    assert!(response.results[0].modified_at.is_some());
+}
+
+#[tokio::test]
+async fn test_invalid_modified_within_is_rejected() {
+    let repo = TestRepo::with_files(&[("src/plain.rs", "pub fn ledger_total() {}")]);
+    let shebe = Shebe::from(create_test_services());
+    shebe.index(repo.path()).session("plain").run().unwrap();
+
+    let err = shebe
+        .search("ledger_total")
+        .session("plain")
+        .modified_within("last week")
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("modified_within"), "{err}");
+}
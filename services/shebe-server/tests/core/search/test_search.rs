@@ -239,3 +239,116 @@ async fn test_search_multiple_sessions() {
         "Session-1 should not contain 'beta'"
     );
 }
+
+/// Index `repo` as `session`, storing chunk text or not
+fn index_with_text_storage(
+    state: &shebe::core::services::Services,
+    repo: &TestRepo,
+    session: &str,
+    store_chunk_text: bool,
+) {
+    let config = shebe::core::storage::SessionConfig::builder()
+        .chunk_size(120)
+        .overlap(20)
+        .store_chunk_text(store_chunk_text)
+        .build()
+        .unwrap();
+    state
+        .storage
+        .index_repository_with_config(session, repo.path(), config, 10, false)
+        .expect("Indexing failed");
+}
+
+#[tokio::test]
+async fn test_no_store_session_matches_stored_results() {
+    let body: String = (0..12)
+        .map(|i| format!("fn handler_{i}() {{ authenticate(user_{i}); log_request(); }}\n"))
+        .collect();
+    let repo = TestRepo::with_files(&[
+        ("src/handlers.rs", body.as_str()),
+        (
+            "src/auth.rs",
+            "pub fn authenticate(user: &str) -> bool { !user.is_empty() }\n",
+        ),
+        (
+            "README.md",
+            "Requests are logged; authenticate before use.\n",
+        ),
+    ]);
+
+    let state = create_test_services();
+    index_with_text_storage(&state, &repo, "text-stored", true);
+    index_with_text_storage(&state, &repo, "text-not-stored", false);
+
+    for query in [
+        "authenticate",
+        "log_request OR user",
+        "\"authenticate before\"",
+    ] {
+        let stored = state
+            .search
+            .search_session("text-stored", query, Some(20))
+            .unwrap();
+        let not_stored = state
+            .search
+            .search_session("text-not-stored", query, Some(20))
+            .unwrap();
+
+        let key = |r: &shebe::core::types::SearchResult| {
+            (r.file_path.clone(), r.start_offset, r.end_offset, r.score)
+        };
+        assert!(!stored.results.is_empty(), "no results for {query}");
+        assert_eq!(
+            stored.results.iter().map(key).collect::<Vec<_>>(),
+            not_stored.results.iter().map(key).collect::<Vec<_>>(),
+            "result sets differ for {query}"
+        );
+        assert_eq!(
+            stored.overlap_duplicates_dropped,
+            not_stored.overlap_duplicates_dropped
+        );
+        for (a, b) in stored.results.iter().zip(&not_stored.results) {
+            assert_eq!(a.text, b.text, "text read from the file should match");
+            assert!(b.text_unavailable.is_none());
+        }
+    }
+
+    // The no-store index is smaller and records the text it saved
+    let stored = state.storage.get_session_metadata("text-stored").unwrap();
+    let not_stored = state
+        .storage
+        .get_session_metadata("text-not-stored")
+        .unwrap();
+    assert!(!not_stored.config.store_chunk_text);
+    assert!(not_stored.chunk_text_bytes > 0);
+    assert_eq!(stored.chunk_text_bytes, not_stored.chunk_text_bytes);
+    assert_eq!(not_stored.schema_hash_matches(), Some(true));
+}
+
+#[tokio::test]
+async fn test_no_store_session_with_missing_file() {
+    let repo = TestRepo::with_files(&[
+        ("kept.rs", "fn kept() { authenticate(); }\n"),
+        ("gone.rs", "fn gone() { authenticate(); }\n"),
+    ]);
+
+    let state = create_test_services();
+    index_with_text_storage(&state, &repo, "text-missing", false);
+    std::fs::remove_file(repo.path().join("gone.rs")).unwrap();
+
+    let results = state
+        .search
+        .search_session("text-missing", "authenticate", Some(10))
+        .unwrap();
+    assert_eq!(results.results.len(), 2, "missing files still match");
+
+    for result in &results.results {
+        if result.file_path.ends_with("gone.rs") {
+            assert!(result.text.is_empty());
+            assert_eq!(result.text_unavailable.as_deref(), Some("file missing"));
+        } else {
+            assert!(result.text.contains("fn kept()"));
+            assert!(result.text_unavailable.is_none());
+        }
+    }
+}
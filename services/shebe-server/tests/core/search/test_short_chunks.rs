@@ -0,0 +1,124 @@
+// Short-chunk demotion and tail fragment merging
+//
+// A file's last few characters form a tiny chunk that BM25 scores far
+// above its worth. Neither the demotion pass nor merging the fragment
+// at index time should let it outrank a substantive chunk.
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::Shebe;
+
+/// 960 characters: with the default 512/64 chunking, the last chunk
+/// holds only the final 64, which mention `evict` twice
+fn file_with_tail_mention() -> String {
+    let mut text = String::new();
+    let mut i = 0;
+    while text.len() < 944 {
+        text.push_str(&format!("    let value_{i:03} = compute(input, {i:03});\n"));
+        i += 1;
+    }
+    text.truncate(944);
+    text.push_str("// evict evict\n");
+    text.push_str(&" ".repeat(960 - text.len()));
+    text
+}
+
+/// One full chunk that actually implements eviction
+const CACHE_SOURCE: &str = "\
+pub struct LruCache { entries: Vec<Entry>, capacity: usize, hits: u64, misses: u64 }
+
+impl LruCache {
+    /// Evict the least recently used entry once the cache is full
+    pub fn insert(&mut self, entry: Entry) {
+        if self.entries.len() >= self.capacity {
+            self.evict();
+        }
+        self.entries.push(entry);
+    }
+
+    fn evict(&mut self) {
+        let oldest = self.entries.iter().enumerate().min_by_key(|(_, e)| e.last_used);
+        if let Some((index, _)) = oldest {
+            self.entries.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) { self.entries.clear(); self.hits = 0; self.misses = 0; }
+}
+";
+
+fn setup() -> (TestRepo, Shebe) {
+    let tail = file_with_tail_mention();
+    let repo = TestRepo::with_files(&[("src/values.rs", &tail), ("src/cache.rs", CACHE_SOURCE)]);
+    (repo, Shebe::from(create_test_services()))
+}
+
+#[tokio::test]
+async fn test_short_tail_chunk_no_longer_outranks_substantive_chunk() {
+    let (repo, shebe) = setup();
+    shebe.index(repo.path()).session("tails").run().unwrap();
+
+    // Keep overlap duplicates: the tail lies inside the previous chunk
+    let plain = shebe
+        .search("evict")
+        .session("tails")
+        .dedupe_overlaps(false)
+        .penalize_short_chunks(false)
+        .run()
+        .unwrap();
+    assert!(
+        plain.results[0].file_path.ends_with("src/values.rs"),
+        "without the penalty the tail fragment wins: {:?}",
+        plain.results
+    );
+    assert_eq!(plain.short_chunks_demoted, 0);
+
+    let demoted = shebe
+        .search("evict")
+        .session("tails")
+        .dedupe_overlaps(false)
+        .run()
+        .unwrap();
+    assert!(
+        demoted.results[0].file_path.ends_with("src/cache.rs"),
+        "{:?}",
+        demoted.results
+    );
+    let tail = demoted
+        .results
+        .iter()
+        .find(|r| r.short_chunk_demoted)
+        .expect("tail fragment is still returned");
+    assert!(tail.file_path.ends_with("src/values.rs"));
+    assert!(tail.end_offset - tail.start_offset < 77);
+    assert_eq!(demoted.short_chunks_demoted, 1);
+}
+
+#[tokio::test]
+async fn test_merged_tail_fragment_no_longer_outranks_substantive_chunk() {
+    let (repo, shebe) = setup();
+    let plain = shebe.index(repo.path()).session("plain").run().unwrap();
+    let merged = shebe
+        .index(repo.path())
+        .session("merged")
+        .merge_tail_fragments(true)
+        .run()
+        .unwrap();
+    assert!(merged.config.merge_tail_fragments);
+    assert_eq!(merged.stats.chunks_created, plain.stats.chunks_created - 1);
+
+    let response = shebe
+        .search("evict")
+        .session("merged")
+        .penalize_short_chunks(false)
+        .run()
+        .unwrap();
+    assert!(
+        response.results[0].file_path.ends_with("src/cache.rs"),
+        "{:?}",
+        response.results
+    );
+    assert!(response
+        .results
+        .iter()
+        .all(|r| r.end_offset - r.start_offset >= 77));
+}
@@ -1,6 +1,17 @@
 //! Storage layer tests
 //!
-//! Tests for session management, indexing operations and metadata handling.
+//! Tests for session management, indexing operations, metadata handling,
+//! chunk index consistency, file manifests, disk usage reports, read-only
+//! mode, storage profiles, index provenance, renamed files on re-index and
+//! upgrades from older schema versions.
 
+mod test_chunk_indexes;
+mod test_file_manifest;
 mod test_indexing;
+mod test_profiles;
+mod test_provenance;
+mod test_read_only;
+mod test_renames;
 mod test_sessions;
+mod test_storage_report;
+mod test_upgrade;
@@ -0,0 +1,114 @@
+// Chunk index consistency across randomized repositories
+//
+// preview_chunk and friends find chunks by (file_path, chunk_index).
+// For random file sets and chunking settings, every index below a
+// file's chunk count must resolve to exactly one chunk through the
+// same query preview_chunk runs.
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::indexer::BoundaryHint;
+use shebe::core::storage::MetadataValidator;
+use tantivy::collector::TopDocs;
+use tantivy::schema::Value;
+use tantivy::TantivyDocument;
+
+/// Small deterministic generator (xorshift64)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> usize {
+        (self.next() % n) as usize
+    }
+}
+
+/// Random source-like text, including blank lines, multi-byte
+/// characters and the occasional long base64 run
+fn random_contents(rng: &mut Rng) -> String {
+    const PIECES: &[&str] = &[
+        "fn handler() {",
+        "    let value = compute(42);",
+        "}",
+        "",
+        "// コメント with émojis 🚀",
+        "    return Ok(value);",
+        "struct Config { name: String }",
+    ];
+    let lines = rng.below(120);
+    let mut text = String::new();
+    for _ in 0..lines {
+        if rng.below(40) == 0 {
+            text.push_str(&"QUJDRA".repeat(40 + rng.below(60)));
+        } else {
+            text.push_str(PIECES[rng.below(PIECES.len() as u64)]);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[tokio::test]
+async fn test_every_chunk_index_resolves() {
+    let services = create_test_services();
+    let hints = [
+        BoundaryHint::None,
+        BoundaryHint::BlankLine,
+        BoundaryHint::Newline,
+    ];
+
+    for seed in 1..=6u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let files: Vec<(String, String)> = (0..1 + rng.below(12))
+            .map(|i| (format!("src/m{i}/file{i}.rs"), random_contents(&mut rng)))
+            .collect();
+        let file_refs: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(path, contents)| (path.as_str(), contents.as_str()))
+            .collect();
+        let repo = TestRepo::with_files(&file_refs);
+
+        let session = format!("random-{seed}");
+        let chunk_size = 100 + rng.below(600);
+        let overlap = rng.below(chunk_size as u64 / 2);
+        let stats = services
+            .index(repo.path())
+            .session(&session)
+            .chunk_size(chunk_size)
+            .overlap(overlap)
+            .boundary_hint(hints[rng.below(3)])
+            .strip_high_entropy(rng.below(2) == 0)
+            .run()
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"))
+            .stats;
+
+        let report = MetadataValidator::new(&services.storage)
+            .validate_chunk_indexes(&session)
+            .unwrap();
+        assert!(report.is_consistent(), "seed {seed}: {:?}", report.problems);
+        assert_eq!(report.chunks_checked, stats.chunks_created, "seed {seed}");
+
+        let index = services.storage.open_session(&session).unwrap();
+        let chunk_index_field = index.schema().get_field("chunk_index").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        for (path, count) in index.file_chunk_counts().unwrap() {
+            for i in 0..count {
+                let query = index.chunk_query(&path, i).unwrap();
+                let hits = searcher.search(&query, &TopDocs::with_limit(2)).unwrap();
+                assert_eq!(hits.len(), 1, "seed {seed}: {path} chunk {i}");
+
+                let doc: TantivyDocument = searcher.doc(hits[0].1).unwrap();
+                let stored = doc.get_first(chunk_index_field).and_then(|v| v.as_i64());
+                assert_eq!(stored, Some(i as i64), "seed {seed}: {path}");
+            }
+            let past_end = index.chunk_query(&path, count).unwrap();
+            let hits = searcher.search(&past_end, &TopDocs::with_limit(1)).unwrap();
+            assert!(hits.is_empty(), "seed {seed}: {path} has chunk {count}");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// File manifest of a large session
+//
+// Listing the files of a 10k-file session from files.json must give
+// the same files and chunk counts as scanning every stored chunk, and
+// be faster than the scan.
+
+use crate::common::create_test_services;
+use shebe::core::storage::FileManifest;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const FILES: usize = 10_000;
+
+/// Best of `runs` timings of `f`
+fn best_of<T>(runs: usize, mut f: impl FnMut() -> T) -> (T, Duration) {
+    let mut best = Duration::MAX;
+    let mut last = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        last = Some(f());
+        best = best.min(start.elapsed());
+    }
+    (last.unwrap(), best)
+}
+
+#[tokio::test]
+async fn test_manifest_matches_scan_and_is_faster() {
+    let services = create_test_services();
+    let repo = TempDir::new().unwrap();
+    for i in 0..FILES {
+        let dir = repo.path().join(format!("module_{:03}", i % 100));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Every few files spans several chunks
+        let body = format!("pub fn item_{i}() -> usize {{ {i} }}\n").repeat(1 + i % 7);
+        std::fs::write(dir.join(format!("item_{i}.rs")), body).unwrap();
+    }
+
+    let stats = services
+        .storage
+        .index_repository("big", repo.path(), vec![], vec![], 256, 32, 10, false)
+        .unwrap();
+    assert_eq!(stats.files_indexed, FILES);
+
+    let index = services.storage.open_session("big").unwrap();
+    let (scanned, scan_time) = best_of(3, || FileManifest::scan(&index).unwrap());
+    let (listed, manifest_time) = best_of(3, || services.storage.session_files("big").unwrap());
+
+    // Read from files.json: indexed order, same files and counts
+    let written = services.storage.get_file_manifest("big").unwrap().unwrap();
+    assert_eq!(listed, written);
+    assert_eq!(listed.files.len(), FILES);
+    assert_eq!(listed.total_chunks, scanned.total_chunks);
+    assert_eq!(listed.chunk_counts(), scanned.chunk_counts());
+    assert_eq!(listed.sorted_paths(), scanned.sorted_paths());
+    assert_eq!(
+        services.storage.searchable_file_paths("big").unwrap(),
+        scanned.sorted_paths()
+    );
+
+    assert!(
+        manifest_time < scan_time,
+        "manifest took {manifest_time:?}, scan {scan_time:?}"
+    );
+    println!("{FILES} files: manifest {manifest_time:?}, scan {scan_time:?}");
+}
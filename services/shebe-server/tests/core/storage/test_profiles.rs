@@ -0,0 +1,76 @@
+// Integration tests for storage profiles ([profiles.<name>])
+//
+// A config file in a temp XDG config directory defines two profiles;
+// services built from each must only see their own sessions.
+
+use crate::common::{index_test_repository, TestRepo};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::core::xdg::XdgDirs;
+use tempfile::TempDir;
+
+/// XDG directories inside `home`, with a config file defining
+/// profiles "work" (explicit root) and "oss" (default root)
+fn profile_home(home: &TempDir) -> XdgDirs {
+    let xdg = XdgDirs {
+        config_dir: home.path().join("config"),
+        data_dir: home.path().join("data"),
+        state_dir: home.path().join("state"),
+        cache_dir: home.path().join("cache"),
+    };
+    std::fs::create_dir_all(&xdg.config_dir).unwrap();
+    let work_root = home.path().join("work-root");
+    std::fs::write(
+        xdg.config_file(),
+        format!(
+            "[profiles.work]\nstorage_root = {:?}\n\n[profiles.oss]\n",
+            work_root.to_string_lossy()
+        ),
+    )
+    .unwrap();
+    xdg
+}
+
+#[tokio::test]
+async fn test_profiles_isolate_sessions() {
+    let home = TempDir::new().unwrap();
+    let xdg = profile_home(&home);
+    let repo = TestRepo::with_files(&[("src/lib.rs", "pub fn work_only() {}\n")]);
+
+    let work_config = Config::load_with_profile(&xdg, Some("work")).unwrap();
+    assert_eq!(work_config.profile_name(), "work");
+    assert_eq!(work_config.storage.index_dir, home.path().join("work-root"));
+    let work = Services::new(work_config);
+    index_test_repository(&work, repo.path(), "work-session").await;
+
+    let oss_config = Config::load_with_profile(&xdg, Some("oss")).unwrap();
+    assert_eq!(
+        oss_config.storage.index_dir,
+        xdg.data_dir.join("profiles/oss")
+    );
+    let oss = Services::new(oss_config);
+
+    let ids = |services: &Services| -> Vec<String> {
+        services
+            .storage
+            .list_sessions()
+            .unwrap()
+            .into_iter()
+            .map(|metadata| metadata.id)
+            .collect()
+    };
+    assert_eq!(ids(&work), ["work-session"]);
+    assert!(ids(&oss).is_empty());
+    assert!(!oss.storage.session_exists("work-session"));
+}
+
+#[test]
+fn test_unknown_profile_lists_available() {
+    let home = TempDir::new().unwrap();
+    let xdg = profile_home(&home);
+
+    let err = Config::load_with_profile(&xdg, Some("home")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unknown profile 'home' (available: oss, work)"));
+}
@@ -0,0 +1,110 @@
+// Integration tests for the provenance recorded with each index run
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::storage::SessionConfig;
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in `dir` with a fixed identity, returning trimmed stdout
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("git should run");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// Repository with one commit
+fn git_repo() -> TestRepo {
+    let repo = TestRepo::with_files(&[
+        ("src/lib.rs", "pub fn ledger() {}\n"),
+        ("README.md", "# Ledger\n"),
+    ]);
+    git(repo.path(), &["init", "-q"]);
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "initial"]);
+    repo
+}
+
+fn index(
+    services: &shebe::core::services::Services,
+    path: &Path,
+    session: &str,
+) -> shebe::core::types::IndexStats {
+    services
+        .storage
+        .index_repository_with_config(session, path, SessionConfig::default(), 10, true)
+        .expect("indexing should succeed")
+}
+
+#[tokio::test]
+async fn test_provenance_records_git_head() {
+    let services = create_test_services();
+    let repo = git_repo();
+    let head = git(repo.path(), &["rev-parse", "HEAD"]);
+
+    let stats = index(&services, repo.path(), "prov-git");
+    let provenance = stats.provenance.expect("stats should carry provenance");
+    let git_state = provenance
+        .git
+        .as_ref()
+        .expect("git state should be recorded");
+    assert_eq!(git_state.commit, head);
+    assert!(!git_state.dirty);
+    assert_eq!(provenance.shebe_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        provenance.repository_path,
+        repo.path().canonicalize().unwrap()
+    );
+    assert_eq!(
+        provenance.config.include_patterns,
+        SessionConfig::default().include_patterns
+    );
+    assert!(!provenance.tokenizer.is_empty());
+    assert!(!provenance.platform.is_empty());
+    assert!(provenance.started_at <= provenance.finished_at);
+
+    // Persisted with the session
+    let metadata = services.storage.get_session_metadata("prov-git").unwrap();
+    let stored = metadata
+        .provenance
+        .expect("metadata should carry provenance");
+    assert_eq!(stored.git, provenance.git);
+    assert_eq!(stored.finished_at, provenance.finished_at);
+
+    // Uncommitted changes to a tracked file mark the checkout dirty
+    std::fs::write(repo.path().join("src/lib.rs"), "pub fn ledger2() {}\n").unwrap();
+    let stats = index(&services, repo.path(), "prov-git");
+    let git_state = stats.provenance.unwrap().git.unwrap();
+    assert_eq!(git_state.commit, head);
+    assert!(git_state.dirty);
+}
+
+#[tokio::test]
+async fn test_provenance_without_git() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(&[("src/lib.rs", "pub fn ledger() {}\n")]);
+
+    let stats = index(&services, repo.path(), "prov-plain");
+    let provenance = stats.provenance.expect("stats should carry provenance");
+    assert!(provenance.git.is_none());
+
+    // Written as an explicit null, not left out
+    let meta_path = services
+        .storage
+        .get_session_path("prov-plain")
+        .join("meta.json");
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(meta_path).unwrap()).unwrap();
+    assert!(json["provenance"].is_object());
+    assert!(json["provenance"]["git"].is_null());
+    assert!(json["provenance"].as_object().unwrap().contains_key("git"));
+}
@@ -0,0 +1,79 @@
+// Integration tests for read-only mode (server.read_only)
+//
+// Each test indexes a session with writable services, then reopens the
+// same storage directory with `server.read_only = true` and checks that
+// every change is refused while searching keeps working.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use shebe::core::config::Config;
+use shebe::core::error::ShebeError;
+use shebe::core::indexer::IndexOverrides;
+use shebe::core::jobs::{IndexJobRequest, IndexSource};
+use shebe::core::schedule::Schedule;
+use shebe::core::services::Services;
+use shebe::core::storage::SessionConfig;
+
+/// Read-only services over the storage of `services`
+fn read_only(services: &Services) -> Services {
+    let mut config = Config::default();
+    config.storage.index_dir = services.storage.storage_root().to_path_buf();
+    config.server.read_only = true;
+    Services::new(config)
+}
+
+/// A session "shared" indexed from a small repository
+async fn indexed() -> (Services, TestRepo) {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "shared").await;
+    (services, repo)
+}
+
+fn assert_read_only<T: std::fmt::Debug>(result: shebe::core::error::Result<T>) {
+    match result {
+        Err(ShebeError::ReadOnly(_)) => {}
+        other => panic!("Expected ReadOnly, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_read_only_refuses_changes() {
+    let (services, repo) = indexed().await;
+    let ro = read_only(&services);
+    assert!(ro.storage.is_read_only());
+
+    assert_read_only(ro.storage.delete_session("shared"));
+    assert_read_only(ro.storage.upgrade_session("shared"));
+    assert_read_only(ro.storage.compact_session("shared"));
+    assert_read_only(
+        ro.storage
+            .set_reindex_schedule("shared", Some(Schedule::Every { minutes: 30 })),
+    );
+    assert_read_only(ro.index(repo.path()).session("fresh").run());
+    assert_read_only(ro.index(repo.path()).session("shared").force(true).run());
+    assert_read_only(ro.start_index_job(IndexJobRequest {
+        session: "job".to_string(),
+        source: IndexSource::Local(repo.path().to_path_buf()),
+        overrides: IndexOverrides::default(),
+        defaults: SessionConfig::default(),
+        force: false,
+        allow_dangerous_path: false,
+    }));
+
+    assert!(ro.storage.session_exists("shared"));
+    assert!(!ro.storage.session_exists("fresh"));
+    assert!(ro.list_jobs().is_empty());
+}
+
+#[tokio::test]
+async fn test_read_only_still_searches() {
+    let (services, _repo) = indexed().await;
+    let ro = read_only(&services);
+
+    let response = ro
+        .search
+        .search_session("shared", "authenticate_user", Some(5))
+        .expect("Search should work in read-only mode");
+    assert!(!response.results.is_empty());
+    assert_eq!(ro.storage.list_sessions().unwrap().len(), 1);
+}
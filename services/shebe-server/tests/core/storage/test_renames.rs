@@ -0,0 +1,80 @@
+// Renamed files detected when a session is re-indexed
+//
+// A file moved without changes is reported in IndexStats::files_renamed,
+// its chunks carry the new path and the chunk count stays the same.
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::services::Services;
+use shebe::core::storage::SessionConfig;
+use shebe::core::types::IndexStats;
+use std::fs;
+use std::path::Path;
+
+fn index(services: &Services, path: &Path, session: &str) -> IndexStats {
+    services
+        .storage
+        .index_repository_with_config(session, path, SessionConfig::default(), 10, true)
+        .expect("indexing should succeed")
+}
+
+/// Stored path of every file with chunks in the index
+fn chunk_paths(services: &Services, session: &str) -> Vec<String> {
+    let index = services.storage.open_session(session).unwrap();
+    index.file_chunk_counts().unwrap().into_keys().collect()
+}
+
+#[tokio::test]
+async fn test_reindex_detects_renamed_file() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(&[
+        (
+            "src/ledger.rs",
+            &"pub fn post_entry(amount: i64) -> i64 { amount }\n".repeat(40),
+        ),
+        ("src/main.rs", "fn main() { ledger::post_entry(1); }\n"),
+    ]);
+
+    let first = index(&services, repo.path(), "renames");
+    assert_eq!(first.files_renamed, 0);
+
+    fs::create_dir_all(repo.path().join("src/accounts")).unwrap();
+    fs::rename(
+        repo.path().join("src/ledger.rs"),
+        repo.path().join("src/accounts/ledger.rs"),
+    )
+    .unwrap();
+    let second = index(&services, repo.path(), "renames");
+
+    assert_eq!(second.files_renamed, 1);
+    let rename = &second.renamed_files[0];
+    assert!(rename.from.ends_with("src/ledger.rs"), "{rename:?}");
+    assert!(rename.to.ends_with("src/accounts/ledger.rs"), "{rename:?}");
+    assert_eq!(second.chunks_created, first.chunks_created);
+    assert_eq!(second.files_indexed, first.files_indexed);
+
+    let paths = chunk_paths(&services, "renames");
+    assert!(paths.iter().any(|p| p.ends_with("src/accounts/ledger.rs")));
+    assert!(!paths.iter().any(|p| p.ends_with("src/ledger.rs")));
+}
+
+#[tokio::test]
+async fn test_reindex_edited_and_new_files_are_not_renames() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(&[
+        ("src/old.rs", "pub fn before() {}\n"),
+        ("src/keep.rs", "pub fn keep() {}\n"),
+    ]);
+    index(&services, repo.path(), "no-renames");
+
+    // Moved and edited: removed and added, not renamed
+    fs::remove_file(repo.path().join("src/old.rs")).unwrap();
+    fs::write(repo.path().join("src/new.rs"), "pub fn after() {}\n").unwrap();
+    let stats = index(&services, repo.path(), "no-renames");
+
+    assert_eq!(stats.files_renamed, 0);
+    assert!(stats.renamed_files.is_empty());
+
+    // A new session has nothing to compare against
+    let fresh = index(&services, repo.path(), "fresh");
+    assert_eq!(fresh.files_renamed, 0);
+}
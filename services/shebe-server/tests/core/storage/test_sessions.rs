@@ -1,7 +1,8 @@
 // Integration tests for session management
 
 use crate::common::{create_test_services, index_test_repository, TestRepo};
-use shebe::core::storage::SessionConfig;
+use shebe::core::storage::{SessionConfig, SessionEntry, StorageManager};
+use shebe::core::types::SessionSearchDefaults;
 use std::path::PathBuf;
 
 #[tokio::test]
@@ -112,6 +113,30 @@ async fn test_session_metadata() {
     let _ = session.index_size_bytes; // Verify field exists
 }
 
+#[tokio::test]
+async fn test_search_defaults_set_by_another_manager() {
+    let state = create_test_services();
+    let repo = TestRepo::small();
+    index_test_repository(&state, repo.path(), "defaults-test").await;
+
+    let reader = state.storage.session_reader("defaults-test").unwrap();
+    assert!(reader.search_defaults().is_empty());
+
+    // As `shebe set-session-defaults` does while shebe-mcp is running
+    let other = StorageManager::new(state.storage.storage_root().to_path_buf());
+    let defaults = SessionSearchDefaults {
+        k: Some(20),
+        fuzziness: Some(1),
+        ..Default::default()
+    };
+    other
+        .set_search_defaults("defaults-test", Some(defaults))
+        .unwrap();
+
+    let reader = state.storage.session_reader("defaults-test").unwrap();
+    assert_eq!(reader.search_defaults(), defaults);
+}
+
 #[tokio::test]
 async fn test_session_isolation() {
     let state = create_test_services();
@@ -186,6 +211,67 @@ async fn test_session_delete_nonexistent() {
     assert!(result.is_err(), "Expected error when deleting nonexistent");
 }
 
+#[tokio::test]
+async fn test_broken_sessions_are_listed_and_pruned() {
+    let state = create_test_services();
+    let repo = TestRepo::small();
+    index_test_repository(&state, repo.path(), "healthy").await;
+    index_test_repository(&state, repo.path(), "garbled").await;
+    std::fs::write(
+        state.storage.get_session_path("garbled").join("meta.json"),
+        "{\"id\": \"garbled\", trailing garbage",
+    )
+    .unwrap();
+    let orphan = state.storage.get_session_path("orphan");
+    std::fs::create_dir_all(orphan.join("index")).unwrap();
+    std::fs::write(orphan.join("index/segment.bin"), vec![7u8; 4096]).unwrap();
+
+    // Only the healthy session can be searched
+    let sessions = state.storage.list_sessions().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, "healthy");
+
+    let entries = state.storage.list_session_entries().unwrap();
+    let ids: Vec<&str> = entries.iter().map(SessionEntry::id).collect();
+    assert_eq!(ids, ["garbled", "healthy", "orphan"]);
+    assert!(matches!(&entries[0], SessionEntry::Corrupt { error, .. } if !error.is_empty()));
+    assert!(entries[1].is_valid());
+    assert!(matches!(
+        &entries[2],
+        SessionEntry::Orphaned { size_bytes, .. } if *size_bytes >= 4096
+    ));
+
+    let garbled_size = state.storage.measure_session_size("garbled");
+    let pruned = state.storage.prune_broken_sessions().unwrap();
+    assert_eq!(
+        pruned,
+        [
+            ("garbled".to_string(), garbled_size),
+            ("orphan".to_string(), 4096)
+        ]
+    );
+    assert!(!state.storage.session_exists("garbled"));
+    assert!(!orphan.exists());
+    assert!(state.storage.session_exists("healthy"));
+    assert!(state.storage.prune_broken_sessions().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_delete_corrupt_session() {
+    let state = create_test_services();
+    let repo = TestRepo::small();
+    index_test_repository(&state, repo.path(), "corrupt").await;
+    std::fs::write(
+        state.storage.get_session_path("corrupt").join("meta.json"),
+        "not json at all",
+    )
+    .unwrap();
+    assert!(state.storage.get_session_metadata("corrupt").is_err());
+
+    state.storage.delete_session("corrupt").unwrap();
+    assert!(!state.storage.session_exists("corrupt"));
+}
+
 #[tokio::test]
 async fn test_session_open_existing() {
     let state = create_test_services();
@@ -243,3 +329,70 @@ async fn test_multiple_sessions_concurrent() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_compaction_after_incremental_adds() {
+    use shebe::core::types::Chunk;
+
+    let state = create_test_services();
+    state
+        .storage
+        .create_session(
+            "incremental",
+            PathBuf::from("/test/repo"),
+            SessionConfig::default(),
+        )
+        .expect("Failed to create session");
+
+    // One writer held across many small add/commit cycles
+    let mut index = state.storage.open_session("incremental").unwrap();
+    for i in 0..20 {
+        let text = format!("fn shared_symbol_{i}() {{ shared_symbol() }}");
+        let chunk = Chunk {
+            end_offset: text.len(),
+            text,
+            file_path: PathBuf::from(format!("/test/repo/src/mod{i}.rs")),
+            start_offset: 0,
+            chunk_index: 0,
+            records: None,
+            section: None,
+        };
+        index.add_chunks(&[chunk], "incremental").unwrap();
+        index.commit().unwrap();
+    }
+    index.release_writer().unwrap();
+    drop(index);
+
+    let paths = |response: shebe::core::types::SearchResponse| {
+        let mut paths: Vec<(String, usize)> = response
+            .results
+            .into_iter()
+            .map(|r| (r.file_path, r.chunk_index))
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let before = paths(
+        state
+            .search
+            .search_session("incremental", "shared_symbol", Some(50))
+            .unwrap(),
+    );
+
+    // Background merges may already have reduced the count, but
+    // compaction must always end with a single segment
+    let stats = state.storage.compact_session("incremental").unwrap();
+    assert!(stats.segments_before >= stats.segments_after);
+    assert_eq!(stats.segments_after, 1);
+
+    let after = paths(
+        state
+            .search
+            .search_session("incremental", "shared_symbol", Some(50))
+            .unwrap(),
+    );
+
+    assert_eq!(before.len(), 20);
+    assert_eq!(before, after);
+}
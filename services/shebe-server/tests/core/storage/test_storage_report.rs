@@ -0,0 +1,155 @@
+// Integration tests for the storage report and index size accuracy
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::services::Services;
+use shebe::core::storage::MetadataValidator;
+use shebe::core::types::Chunk;
+use std::path::Path;
+
+/// Index through `StorageManager`, which records `index_size_bytes`
+fn index(services: &Services, repo_path: &Path, session_id: &str) {
+    services
+        .storage
+        .index_repository(
+            session_id,
+            repo_path,
+            vec![],
+            vec![],
+            services.config.current().indexing.chunk_size,
+            services.config.current().indexing.overlap,
+            services.config.current().indexing.max_file_size_mb,
+            false,
+        )
+        .expect("Failed to index repository");
+}
+
+/// Repository large enough that deleting most of it shrinks the index
+/// well past the drift thresholds
+fn large_repo() -> TestRepo {
+    let files: Vec<(String, String)> = (0..300)
+        .map(|i| {
+            let body = (0..40)
+                .map(|j| format!("    let value_{i}_{j} = compute_{i}_{j}(input_{j});\n"))
+                .collect::<String>();
+            (
+                format!("src/module_{i}.rs"),
+                format!("pub fn module_{i}() {{\n{body}}}\n"),
+            )
+        })
+        .collect();
+    let refs: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    TestRepo::with_files(&refs)
+}
+
+/// Delete the chunks of all but `keep` files, add a small one and
+/// compact the index, leaving the recorded `index_size_bytes` untouched
+fn delete_most_chunks(services: &Services, repo: &TestRepo, session: &str, keep: usize) {
+    let mut index = services.storage.open_session(session).unwrap();
+    for i in keep..300 {
+        let path = repo.path().join(format!("src/module_{i}.rs"));
+        index.delete_file(&path.to_string_lossy()).unwrap();
+    }
+    // A second segment, so merging rewrites the first without the
+    // deleted chunks
+    let chunk = Chunk {
+        text: "pub fn added() {}".to_string(),
+        file_path: repo.path().join("src/added.rs"),
+        start_offset: 0,
+        end_offset: 17,
+        chunk_index: 0,
+        records: None,
+        section: None,
+    };
+    index.add_chunks(&[chunk], session).unwrap();
+    assert_eq!(index.merge_segments().unwrap(), 1);
+}
+
+#[test]
+fn test_storage_report_matches_after_indexing() {
+    let services = create_test_services();
+    let small = TestRepo::small();
+    let large = large_repo();
+    index(&services, small.path(), "small");
+    index(&services, large.path(), "large");
+
+    let report = services.storage.storage_report().unwrap();
+
+    let ids: Vec<_> = report
+        .sessions
+        .iter()
+        .map(|s| s.session_id.as_str())
+        .collect();
+    assert_eq!(ids, ["large", "small"], "Largest session should come first");
+    for session in &report.sessions {
+        assert_eq!(
+            session.metadata_bytes,
+            Some(session.actual_bytes),
+            "Freshly indexed session '{}' should record its size on disk",
+            session.session_id
+        );
+        assert!(!session.drifted);
+    }
+    assert_eq!(report.total_session_bytes, report.total_metadata_bytes);
+    assert_eq!(report.drifted_count(), 0);
+}
+
+#[test]
+fn test_storage_report_detects_deleted_chunks() {
+    let services = create_test_services();
+    let repo = large_repo();
+    index(&services, repo.path(), "shrunk");
+    let recorded = services
+        .storage
+        .get_session_metadata("shrunk")
+        .unwrap()
+        .index_size_bytes;
+
+    delete_most_chunks(&services, &repo, "shrunk", 10);
+
+    let report = services.storage.storage_report().unwrap();
+    let usage = &report.sessions[0];
+    assert_eq!(usage.metadata_bytes, Some(recorded));
+    assert!(
+        usage.actual_bytes < recorded,
+        "Index should shrink: {} >= {}",
+        usage.actual_bytes,
+        recorded
+    );
+    assert!(usage.drifted, "Drift should be flagged: {usage:?}");
+    assert!(usage.drift_percent.unwrap() > 10.0);
+    assert_eq!(report.drifted_count(), 1);
+
+    // The validator uses the same threshold
+    let validation = MetadataValidator::new(&services.storage)
+        .validate_session("shrunk")
+        .unwrap();
+    assert!(!validation.size_matches);
+    assert!(!validation.is_consistent);
+}
+
+#[test]
+fn test_refresh_index_size_clears_drift() {
+    let services = create_test_services();
+    let repo = large_repo();
+    index(&services, repo.path(), "refresh");
+    delete_most_chunks(&services, &repo, "refresh", 10);
+
+    let size = services.storage.refresh_index_size("refresh").unwrap();
+
+    let metadata = services.storage.get_session_metadata("refresh").unwrap();
+    assert_eq!(metadata.index_size_bytes, size);
+    assert_eq!(size, services.storage.measure_session_size("refresh"));
+    let report = services.storage.storage_report().unwrap();
+    assert!(!report.sessions[0].drifted);
+}
+
+#[test]
+fn test_measure_session_size_missing_session() {
+    let services = create_test_services();
+
+    assert_eq!(services.storage.measure_session_size("nonexistent"), 0);
+    assert!(services.storage.refresh_index_size("nonexistent").is_err());
+}
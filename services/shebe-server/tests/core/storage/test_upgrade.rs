@@ -0,0 +1,226 @@
+// Integration tests for upgrading sessions from older schema versions
+//
+// The fixtures under tests/fixtures/sessions are meta.json files in the
+// shape schema v1 and v2 wrote them: no repository_path, last_indexed_at
+// or include/exclude patterns (added in v3).
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::error::ShebeError;
+use shebe::core::services::Services;
+use shebe::core::storage::{TantivyIndex, UpgradeStrategy, SCHEMA_VERSION};
+use shebe::core::types::Chunk;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tantivy::schema::{Schema, INDEXED, STORED, STRING, TEXT};
+use tantivy::{doc, Index};
+
+const META_V1: &str = include_str!("../../fixtures/sessions/meta_v1.json");
+const META_V2: &str = include_str!("../../fixtures/sessions/meta_v2.json");
+
+const FILES: &[(&str, &str)] = &[
+    ("main.rs", "fn main() {\n    run();\n}\n"),
+    ("src/lib.rs", "pub fn run() {\n    authenticate();\n}\n"),
+];
+
+fn session_dir(services: &Services, session_id: &str) -> PathBuf {
+    services
+        .storage
+        .storage_root()
+        .join("sessions")
+        .join(session_id)
+}
+
+/// Schema of v1 (chunk_index stored only) or v2/v3 (chunk_index indexed)
+fn legacy_schema(version: u32) -> Schema {
+    let mut builder = Schema::builder();
+    builder.add_text_field("text", TEXT | STORED);
+    builder.add_text_field("file_path", STRING | STORED);
+    builder.add_text_field("session", STRING | STORED);
+    builder.add_i64_field("offset_start", STORED);
+    builder.add_i64_field("offset_end", STORED);
+    if version == 1 {
+        builder.add_i64_field("chunk_index", STORED);
+    } else {
+        builder.add_i64_field("chunk_index", INDEXED | STORED);
+    }
+    builder.add_date_field("indexed_at", STORED);
+    builder.build()
+}
+
+/// One chunk per file of `repo`
+fn chunks(repo: &Path) -> Vec<Chunk> {
+    FILES
+        .iter()
+        .map(|(path, text)| Chunk {
+            text: text.to_string(),
+            file_path: repo.join(path),
+            start_offset: 0,
+            end_offset: text.len(),
+            chunk_index: 0,
+            records: None,
+            section: None,
+        })
+        .collect()
+}
+
+/// Install `meta` and an index with the schema of `index_version`
+///
+/// `index_version` of `SCHEMA_VERSION` writes an index in the current
+/// layout; older versions write the legacy schema.
+fn install_session(
+    services: &Services,
+    session_id: &str,
+    meta: &str,
+    index_version: u32,
+    repo: &Path,
+) {
+    let dir = session_dir(services, session_id);
+    let tantivy_dir = dir.join("tantivy");
+    fs::create_dir_all(&tantivy_dir).unwrap();
+    fs::write(dir.join("meta.json"), meta).unwrap();
+
+    if index_version == SCHEMA_VERSION {
+        let mut index = TantivyIndex::create(&tantivy_dir).unwrap();
+        index.add_chunks(&chunks(repo), session_id).unwrap();
+        index.commit().unwrap();
+        return;
+    }
+
+    let schema = legacy_schema(index_version);
+    let index = Index::create_in_dir(&tantivy_dir, schema.clone()).unwrap();
+    let mut writer = index.writer(15_000_000).unwrap();
+    let field = |name: &str| schema.get_field(name).unwrap();
+    for chunk in chunks(repo) {
+        writer
+            .add_document(doc!(
+                field("text") => chunk.text.as_str(),
+                field("file_path") => chunk.file_path.to_string_lossy().as_ref(),
+                field("session") => session_id,
+                field("offset_start") => chunk.start_offset as i64,
+                field("offset_end") => chunk.end_offset as i64,
+                field("chunk_index") => chunk.chunk_index as i64,
+                field("indexed_at") => tantivy::DateTime::from_timestamp_secs(0),
+            ))
+            .unwrap();
+    }
+    writer.commit().unwrap();
+}
+
+#[test]
+fn test_legacy_sessions_need_upgrade() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(FILES);
+    install_session(&services, "legacy-v1", META_V1, 1, repo.path());
+
+    // Older metadata lacks required fields, and the index is outdated
+    assert!(services.storage.get_session_metadata("legacy-v1").is_err());
+    assert!(services.storage.open_session("legacy-v1").is_err());
+}
+
+#[test]
+fn test_upgrade_v2_metadata_rewrite() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(FILES);
+    install_session(&services, "legacy-v2", META_V2, SCHEMA_VERSION, repo.path());
+
+    let report = services.storage.upgrade_session("legacy-v2").unwrap();
+
+    assert_eq!(report.strategy, UpgradeStrategy::MetadataRewrite);
+    assert_eq!(report.from_version, 2);
+    assert_eq!(report.to_version, SCHEMA_VERSION);
+    assert!(report.stats.is_none());
+    let repo_line = format!(
+        "repository_path: not recorded -> {} (common directory of indexed files)",
+        repo.path().display()
+    );
+    assert!(report.changes.contains(&repo_line), "{:?}", report.changes);
+    assert!(report
+        .changes
+        .contains(&"last_indexed_at: not recorded -> created_at".to_string()));
+    assert!(report
+        .changes
+        .contains(&format!("schema version: v2 -> v{SCHEMA_VERSION}")));
+
+    let metadata = services.storage.get_session_metadata("legacy-v2").unwrap();
+    assert_eq!(metadata.schema_version, SCHEMA_VERSION);
+    assert_eq!(metadata.repository_path, repo.path());
+    assert_eq!(metadata.last_indexed_at, metadata.created_at);
+    assert_eq!(metadata.config.chunk_size, 256);
+    assert_eq!(metadata.config.include_patterns, vec!["**/*".to_string()]);
+    assert_eq!(metadata.schema_hash_matches(), Some(true));
+
+    // The kept index is searchable
+    let results = services
+        .search
+        .search_session("legacy-v2", "authenticate", Some(5))
+        .unwrap();
+    assert_eq!(results.results.len(), 1);
+
+    // A second upgrade has nothing left to do
+    let again = services.storage.upgrade_session("legacy-v2").unwrap();
+    assert_eq!(again.strategy, UpgradeStrategy::UpToDate);
+}
+
+#[test]
+fn test_upgrade_v1_reindexes() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(FILES);
+    install_session(&services, "legacy-v1", META_V1, 1, repo.path());
+
+    let report = services.storage.upgrade_session("legacy-v1").unwrap();
+
+    assert_eq!(report.strategy, UpgradeStrategy::Reindex);
+    assert_eq!(report.from_version, 1);
+    assert_eq!(report.repository_path.as_deref(), Some(repo.path()));
+    assert!(report
+        .changes
+        .iter()
+        .any(|c| c.starts_with("field added: path_tokens")));
+    assert!(report
+        .changes
+        .iter()
+        .any(|c| c.starts_with("field changed: chunk_index")));
+    assert_eq!(report.stats.unwrap().files_indexed, 2);
+
+    let metadata = services.storage.get_session_metadata("legacy-v1").unwrap();
+    assert_eq!(metadata.schema_version, SCHEMA_VERSION);
+    // Stored config is reused for the re-index
+    assert_eq!(metadata.config.chunk_size, 256);
+    assert_eq!(metadata.config.overlap, 32);
+    assert!(services.storage.open_session("legacy-v1").is_ok());
+}
+
+#[test]
+fn test_upgrade_v2_without_repository_lists_missing() {
+    let services = create_test_services();
+    let repo = TestRepo::with_files(FILES);
+    install_session(&services, "legacy-v2", META_V2, 2, repo.path());
+    let repo_path = repo.path().to_path_buf();
+    drop(repo);
+
+    let err = services.storage.upgrade_session("legacy-v2").unwrap_err();
+
+    let ShebeError::InvalidSession(message) = err else {
+        panic!("expected InvalidSession, got {err:?}");
+    };
+    assert!(message.contains("(schema v2) cannot be upgraded automatically"));
+    assert!(message.contains("the index schema changed"));
+    assert!(message.contains(&format!(
+        "- repository_path: {} no longer exists",
+        repo_path.display()
+    )));
+    // Nothing was changed
+    assert_eq!(
+        fs::read_to_string(session_dir(&services, "legacy-v2").join("meta.json")).unwrap(),
+        META_V2
+    );
+}
+
+#[test]
+fn test_upgrade_missing_session() {
+    let services = create_test_services();
+    assert!(matches!(
+        services.storage.upgrade_session("missing"),
+        Err(ShebeError::SessionNotFound(_))
+    ));
+}
@@ -0,0 +1,13 @@
+//! gRPC adapter integration tests
+//!
+//! Tests for the `shebe.v1.Search` service over a real listener. Built
+//! only with the `grpc` feature.
+
+#![cfg(feature = "grpc")]
+
+mod common;
+
+// gRPC submodules - tests/grpc/ directory
+mod grpc {
+    pub mod service_tests;
+}
@@ -0,0 +1,233 @@
+//! Tests for the gRPC Search service
+//!
+//! Each test starts a server on an ephemeral port and calls it with the
+//! generated client:
+//! - Index, Search and SearchStream round trip
+//! - ListSessions and DeleteSession
+//! - Status codes for bad requests
+//! - Bearer token authentication
+//! - Health and reflection services
+
+use crate::common::{create_test_services, TestRepo};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::grpc::proto;
+use shebe::grpc::proto::search_client::SearchClient;
+use shebe::grpc::GrpcServer;
+use std::sync::Arc;
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+use tonic::{Code, Request};
+
+/// Serve `services` on 127.0.0.1 and return a connected channel
+async fn start(services: Services) -> Channel {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = GrpcServer::new(Arc::new(services));
+    tokio::spawn(async move { server.serve_with_listener(listener).await });
+
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn index_request(repo: &TestRepo, session: &str) -> proto::IndexRequest {
+    proto::IndexRequest {
+        path: repo.path().to_string_lossy().into_owned(),
+        session: session.to_string(),
+        ..Default::default()
+    }
+}
+
+fn search_request(session: &str, query: &str) -> proto::SearchRequest {
+    proto::SearchRequest {
+        session: session.to_string(),
+        query: query.to_string(),
+        k: Some(5),
+        ..Default::default()
+    }
+}
+
+/// Test indexing, then searching with both the unary and streaming calls
+#[tokio::test]
+async fn test_index_and_search() {
+    let mut client = SearchClient::new(start(create_test_services()).await);
+    let repo = TestRepo::small();
+
+    let stats = client
+        .index(index_request(&repo, "grpc-test"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(stats.session, "grpc-test");
+    assert!(stats.files_indexed > 0);
+    assert!(stats.chunks_created > 0);
+    assert!(stats.skipped.is_some());
+
+    let response = client
+        .search(search_request("grpc-test", "authenticate"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.count > 0);
+    assert!(response.results[0].file_path.ends_with("src/auth.rs"));
+
+    let mut stream = client
+        .search_stream(search_request("grpc-test", "authenticate"))
+        .await
+        .unwrap()
+        .into_inner();
+    let mut streamed = Vec::new();
+    while let Some(result) = stream.next().await {
+        streamed.push(result.unwrap());
+    }
+    assert_eq!(streamed, response.results);
+}
+
+/// Test listing and deleting sessions
+#[tokio::test]
+async fn test_list_and_delete_sessions() {
+    let mut client = SearchClient::new(start(create_test_services()).await);
+    let repo = TestRepo::small();
+    client
+        .index(index_request(&repo, "grpc-list"))
+        .await
+        .unwrap();
+
+    let sessions = client
+        .list_sessions(proto::ListSessionsRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .sessions;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, "grpc-list");
+    assert!(sessions[0].chunk_size > 0);
+    assert!(!sessions[0].created_at.is_empty());
+
+    let deleted = client
+        .delete_session(proto::DeleteSessionRequest {
+            session: "grpc-list".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(deleted.freed_bytes > 0);
+
+    let status = client
+        .delete_session(proto::DeleteSessionRequest {
+            session: "grpc-list".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+/// Test the status codes of invalid requests
+#[tokio::test]
+async fn test_error_status_codes() {
+    let mut client = SearchClient::new(start(create_test_services()).await);
+    let repo = TestRepo::small();
+
+    let status = client
+        .search(search_request("missing", "anything"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let status = client
+        .search(search_request("../etc", "anything"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let mut request = index_request(&repo, "grpc-errors");
+    request.path = "relative/path".to_string();
+    let status = client.index(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    client
+        .index(index_request(&repo, "grpc-errors"))
+        .await
+        .unwrap();
+    let status = client
+        .index(index_request(&repo, "grpc-errors"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::AlreadyExists);
+    let mut forced = index_request(&repo, "grpc-errors");
+    forced.force = true;
+    assert!(client.index(forced).await.is_ok());
+}
+
+/// Test that Search calls need the configured token, health checks do not
+#[tokio::test]
+async fn test_bearer_token() {
+    let mut config = Config::default();
+    let storage = tempfile::tempdir().unwrap();
+    config.storage.index_dir = storage.path().to_path_buf();
+    config.server.grpc_auth_token = Some("s3cret".to_string());
+    let channel = start(Services::new(config)).await;
+    let mut client = SearchClient::new(channel.clone());
+
+    let status = client
+        .list_sessions(proto::ListSessionsRequest {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut request = Request::new(proto::ListSessionsRequest {});
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer wrong".parse().unwrap());
+    let status = client.list_sessions(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut request = Request::new(proto::ListSessionsRequest {});
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer s3cret".parse().unwrap());
+    assert!(client.list_sessions(request).await.is_ok());
+
+    let mut health = tonic_health::pb::health_client::HealthClient::new(channel);
+    let response = health
+        .check(tonic_health::pb::HealthCheckRequest {
+            service: "shebe.v1.Search".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        response.status,
+        tonic_health::pb::health_check_response::ServingStatus::Serving as i32
+    );
+}
+
+/// Test that reflection lists the Search service
+#[tokio::test]
+async fn test_reflection_lists_service() {
+    use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+    use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+    use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+    use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+    let mut client = ServerReflectionClient::new(start(create_test_services()).await);
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client
+        .server_reflection_info(tokio_stream::iter([request]))
+        .await
+        .unwrap()
+        .into_inner();
+    let response = responses.next().await.unwrap().unwrap();
+
+    let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+        panic!("unexpected reflection response");
+    };
+    let names: Vec<_> = list.service.iter().map(|s| s.name.as_str()).collect();
+    assert!(names.contains(&"shebe.v1.Search"), "{names:?}");
+}
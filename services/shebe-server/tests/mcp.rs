@@ -6,8 +6,32 @@ mod common;
 
 // MCP submodules - tests/mcp/ directory
 mod mcp {
+    pub mod allowed_roots_tests;
+    pub mod archive_tests;
+    pub mod chunk_cap_tests;
+    pub mod collection_tests;
+    pub mod compare_configs_tests;
+    pub mod corruption_tests;
+    pub mod exclusion_tests;
     pub mod find_references_tests;
+    pub mod find_similar_tests;
     pub mod handler_tests;
+    pub mod locate_tests;
+    pub mod locations_mode_tests;
+    pub mod namespace_tests;
     pub mod pagination_tests;
+    pub mod profile_tests;
     pub mod protocol_tests;
+    pub mod read_only_tests;
+    pub mod reindex_dry_run_tests;
+    pub mod relative_path_tests;
+    pub mod reload_config_tests;
+    pub mod result_handle_tests;
+    pub mod sample_files_tests;
+    pub mod search_paths_tests;
+    pub mod section_tests;
+    pub mod session_defaults_tests;
+    pub mod session_info_tests;
+    pub mod tcp_tests;
+    pub mod unavailable_session_tests;
 }
@@ -0,0 +1,111 @@
+//! Integration tests for the allowed roots of file-reading tools
+//!
+//! read_file, preview_chunk and sample_files only read files under
+//! `security.allowed_roots` (by default the sessions' repository
+//! paths), judging symlinks by their targets.
+
+use crate::common::{call, create_test_services, index_test_repository, response_text, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use shebe::mcp::protocol::{JsonRpcResponse, ACCESS_DENIED};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Each file-reading tool's arguments for `file` of `session`
+fn reads(session: &str, file: &str) -> Vec<(&'static str, Value)> {
+    vec![
+        ("read_file", json!({"session": session, "file_path": file})),
+        (
+            "preview_chunk",
+            json!({"session": session, "file_path": file, "chunk_index": 0}),
+        ),
+        (
+            "sample_files",
+            json!({"session": session, "patterns": ["src/auth.rs"]}),
+        ),
+    ]
+}
+
+fn assert_denied(response: &JsonRpcResponse, tool: &str) {
+    let error = response.error.as_ref().unwrap_or_else(|| {
+        panic!("{tool} should be refused: {:?}", response.result);
+    });
+    assert_eq!(error.code, ACCESS_DENIED, "{tool}: {}", error.message);
+    assert!(
+        error
+            .message
+            .starts_with("Access outside allowed roots denied:"),
+        "{tool}: {}",
+        error.message
+    );
+    assert!(!error.message.contains("token"), "{}", error.message);
+}
+
+#[tokio::test]
+async fn test_session_files_are_readable_by_default() {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "roots").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    for (tool, arguments) in reads("roots", "src/auth.rs") {
+        let output = response_text(&call(&handlers, tool, arguments).await);
+        assert!(output.contains("authenticate_user"), "{tool}: {output}");
+    }
+
+    let output = response_text(&call(&handlers, "show_shebe_config", json!({})).await);
+    let root = repo.path().canonicalize().unwrap();
+    assert!(
+        output.contains("- **Allowed Roots:** 1 (from session repository paths"),
+        "{output}"
+    );
+    assert!(
+        output.contains(&format!("`{}`", root.display())),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn test_indexed_file_outside_allowed_roots_is_refused() {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let other = TestRepo::with_files(&[("lib.rs", "fn other() {}\n")]);
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "outside").await;
+
+    let mut config = Config::default();
+    config.storage.index_dir = services.storage.storage_root().to_path_buf();
+    config.security.allowed_roots = vec![other.path().to_path_buf()];
+    let handlers = ProtocolHandlers::new(Arc::new(Services::new(config)));
+
+    for (tool, arguments) in reads("outside", "src/auth.rs") {
+        assert_denied(&call(&handlers, tool, arguments).await, tool);
+    }
+
+    let output = response_text(&call(&handlers, "show_shebe_config", json!({})).await);
+    assert!(
+        output.contains("- **Allowed Roots:** 1 (from security.allowed_roots)"),
+        "{output}"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlink_escaping_the_root_is_refused() {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let secrets = TestRepo::with_files(&[("secret.txt", "token = hunter2\n")]);
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "escape").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    // Swapped for a link to a file outside the repository after indexing.
+    // Absolute paths (as in search results) skip the relative path check.
+    let indexed: PathBuf = repo.path().join("src/auth.rs");
+    std::fs::remove_file(&indexed).unwrap();
+    std::os::unix::fs::symlink(secrets.path().join("secret.txt"), &indexed).unwrap();
+
+    for (tool, arguments) in reads("escape", indexed.to_str().unwrap()) {
+        assert_denied(&call(&handlers, tool, arguments).await, tool);
+    }
+}
@@ -0,0 +1,151 @@
+//! Integration tests for indexing zip archives
+//!
+//! Entries are searched and read back as `{archive}!{entry}` without
+//! extracting the archive; entries outside the archive root are refused.
+
+use crate::common::{create_test_services, extract_text};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::protocol::ToolResult;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::{
+    IndexRepositoryHandler, ReadFileHandler, ReindexSessionHandler, SearchCodeHandler,
+};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+/// Write a zip holding `files`
+fn write_zip(path: &Path, files: &[(&str, &str)]) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, content) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+async fn index(services: &Arc<Services>, archive: &Path) -> Result<ToolResult, McpError> {
+    IndexRepositoryHandler::new(Arc::clone(services))
+        .execute(json!({"session": "drop", "path": archive.to_str().unwrap()}))
+        .await
+}
+
+async fn read(services: &Arc<Services>, file_path: &str) -> Result<ToolResult, McpError> {
+    ReadFileHandler::new(Arc::clone(services))
+        .execute(json!({"session": "drop", "file_path": file_path}))
+        .await
+}
+
+async fn reindex(services: &Arc<Services>, args: Value) -> Result<ToolResult, McpError> {
+    ReindexSessionHandler::new(Arc::clone(services))
+        .execute(args)
+        .await
+}
+
+#[tokio::test]
+async fn test_archive_search_and_read_file() {
+    let services = Arc::new(create_test_services());
+    let dir = TempDir::new().unwrap();
+    let archive = dir.path().join("vendor.zip");
+    write_zip(
+        &archive,
+        &[
+            (
+                "src/auth.rs",
+                "pub fn authenticate_vendor() -> bool { true }\n",
+            ),
+            ("README.md", "# Vendor drop\n"),
+        ],
+    );
+
+    let result = index(&services, &archive).await.unwrap();
+    assert!(
+        extract_text(&result).contains("Files indexed: 2"),
+        "{}",
+        extract_text(&result)
+    );
+
+    let entry = format!("{}!src/auth.rs", archive.canonicalize().unwrap().display());
+    let result = SearchCodeHandler::new(Arc::clone(&services))
+        .execute(json!({"session": "drop", "query": "authenticate_vendor"}))
+        .await
+        .unwrap();
+    assert!(
+        extract_text(&result).contains(&entry),
+        "{}",
+        extract_text(&result)
+    );
+
+    // Absolute entry paths and paths relative to the archive both read
+    for path in [entry.as_str(), "src/auth.rs", "./src/auth.rs"] {
+        let result = read(&services, path).await.unwrap();
+        assert!(
+            extract_text(&result).contains("pub fn authenticate_vendor()"),
+            "{path}: {}",
+            extract_text(&result)
+        );
+    }
+
+    // Relative paths cannot leave the archive
+    let err = read(&services, "../secrets.txt").await.unwrap_err();
+    assert!(
+        matches!(err, McpError::InvalidParams(ref msg) if msg.contains("outside the archive")),
+        "{err:?}"
+    );
+
+    // A replaced archive is re-indexed without force
+    let err = reindex(&services, json!({"session": "drop"}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, McpError::InvalidRequest(ref msg) if msg.contains("archive is unchanged")),
+        "{err:?}"
+    );
+    write_zip(
+        &archive,
+        &[(
+            "src/auth.rs",
+            "pub fn authorize_vendor() -> bool { false }\n",
+        )],
+    );
+    let result = reindex(&services, json!({"session": "drop"}))
+        .await
+        .unwrap();
+    assert!(
+        extract_text(&result).contains("content hash"),
+        "{}",
+        extract_text(&result)
+    );
+    let result = read(&services, "src/auth.rs").await.unwrap();
+    assert!(
+        extract_text(&result).contains("authorize_vendor"),
+        "{}",
+        extract_text(&result)
+    );
+}
+
+#[tokio::test]
+async fn test_archive_rejects_path_traversal() {
+    let services = Arc::new(create_test_services());
+    let dir = TempDir::new().unwrap();
+    let archive = dir.path().join("evil.zip");
+    write_zip(
+        &archive,
+        &[
+            ("src/lib.rs", "pub fn lib() {}\n"),
+            ("../../etc/cron.d/evil", "* * * * * root true\n"),
+        ],
+    );
+
+    let err = index(&services, &archive).await.unwrap_err();
+    assert!(
+        matches!(err, McpError::InvalidParams(ref msg)
+            if msg.contains("outside the archive root") && msg.contains("../../etc/cron.d/evil")),
+        "{err:?}"
+    );
+    assert!(!services.storage.session_exists("drop"));
+}
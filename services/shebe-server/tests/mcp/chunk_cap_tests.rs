@@ -0,0 +1,123 @@
+//! Integration tests for the per-file chunk cap
+//!
+//! A file over `max_chunks_per_file` is indexed up to the cap, listed
+//! by index_repository, marked in search results, and indexed in full
+//! by reindex_session with a higher cap.
+
+use crate::common::{create_test_services, extract_text, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::{IndexRepositoryHandler, ReindexSessionHandler, SearchCodeHandler};
+use std::sync::Arc;
+
+/// A generated file of 40 chunks with a marker at each end
+fn generated_file() -> String {
+    let filler = "let value = compute(); ".repeat(170);
+    format!("fn early_marker() {{}}\n{filler}\nfn late_marker() {{}}\n")
+}
+
+async fn search(services: &Arc<Services>, query: &str) -> String {
+    let result = SearchCodeHandler::new(Arc::clone(services))
+        .execute(json!({"session": "capped", "query": query}))
+        .await
+        .unwrap();
+    extract_text(&result).to_string()
+}
+
+async fn index(services: &Arc<Services>, repo: &TestRepo, args: Value) -> String {
+    let mut request = json!({
+        "session": "capped",
+        "path": repo.path().to_str().unwrap(),
+        "chunk_size": 100,
+        "overlap": 0
+    });
+    request
+        .as_object_mut()
+        .unwrap()
+        .extend(args.as_object().unwrap().clone());
+    let result = IndexRepositoryHandler::new(Arc::clone(services))
+        .execute(request)
+        .await
+        .unwrap();
+    extract_text(&result).to_string()
+}
+
+#[tokio::test]
+async fn test_capped_file_is_listed_and_marked() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(&[
+        ("src/generated.rs", &generated_file()),
+        (
+            "src/lib.rs",
+            "fn early_marker_caller() { early_marker(); }\n",
+        ),
+    ]);
+
+    let output = index(&services, &repo, json!({"max_chunks_per_file": 3})).await;
+    assert!(output.contains("Chunks created: 4"), "{output}");
+    assert!(output.contains("Partially indexed files: 1"), "{output}");
+    assert!(
+        output.contains("generated.rs: 3 chunks, 301 B of"),
+        "{output}"
+    );
+
+    let metadata = services.storage.get_session_metadata("capped").unwrap();
+    assert_eq!(metadata.config.max_chunks_per_file, 3);
+    let manifest = services
+        .storage
+        .get_content_manifest("capped")
+        .unwrap()
+        .unwrap();
+    assert_eq!(manifest.truncated.len(), 1);
+    assert!(manifest
+        .truncated
+        .iter()
+        .all(|p| p.ends_with("generated.rs")));
+
+    let found = search(&services, "early_marker").await;
+    assert!(found.contains("**Indexed partially:**"), "{found}");
+
+    // Text past the cap was not indexed
+    let missing = search(&services, "late_marker").await;
+    assert!(!missing.contains("generated.rs"), "{missing}");
+}
+
+#[tokio::test]
+async fn test_reindex_with_higher_cap_indexes_whole_file() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(&[("src/generated.rs", &generated_file())]);
+    index(&services, &repo, json!({"max_chunks_per_file": 3})).await;
+
+    let result = ReindexSessionHandler::new(Arc::clone(&services))
+        .execute(json!({"session": "capped", "max_chunks_per_file": 1000}))
+        .await
+        .unwrap();
+    let output = extract_text(&result);
+    assert!(
+        output.contains("Max chunks per file: 3 -> 1000"),
+        "{output}"
+    );
+    assert!(!output.contains("Partially indexed files"), "{output}");
+
+    let found = search(&services, "late_marker").await;
+    assert!(found.contains("generated.rs"), "{found}");
+    assert!(!found.contains("Indexed partially"), "{found}");
+    let manifest = services
+        .storage
+        .get_content_manifest("capped")
+        .unwrap()
+        .unwrap();
+    assert!(manifest.truncated.is_empty());
+}
+
+#[tokio::test]
+async fn test_default_cap_leaves_files_whole() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(&[("src/generated.rs", &generated_file())]);
+
+    let output = index(&services, &repo, json!({})).await;
+    assert!(!output.contains("Partially indexed"), "{output}");
+    let metadata = services.storage.get_session_metadata("capped").unwrap();
+    assert_eq!(metadata.config.max_chunks_per_file, 2000);
+}
@@ -0,0 +1,219 @@
+//! Integration tests for repository collections
+//!
+//! A collection names a group of sessions. search_code and
+//! find_references take `collection` instead of `session` and label
+//! each result with the session it came from; deleting a member session
+//! drops it from the collection.
+
+use crate::common::{call_error, call_text, create_test_services, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+
+/// Two services that both call the shared token check
+async fn platform() -> (TestRepo, TestRepo, Arc<Services>, ProtocolHandlers) {
+    let api = TestRepo::with_files(&[
+        (
+            "src/auth.rs",
+            "pub fn login(token: &str) -> bool { verify_token(token) }",
+        ),
+        (
+            "src/routes.rs",
+            "pub fn routes() -> Vec<Route> { Vec::new() }",
+        ),
+    ]);
+    let web = TestRepo::with_files(&[(
+        "src/session.ts",
+        "export function restore(token: string) { return verify_token(token) && verify_token(token); }",
+    )]);
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, api.path(), "api").await;
+    index_test_repository(&services, web.path(), "web").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+    (api, web, services, handlers)
+}
+
+#[tokio::test]
+async fn test_collection_crud_through_tools() {
+    let (_api, _web, services, handlers) = platform().await;
+
+    let output = call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api"]}),
+    )
+    .await;
+    assert!(
+        output.contains("**Collection Created:** `platform`"),
+        "{output}"
+    );
+    assert!(output.contains("**Sessions (1):** `api`"), "{output}");
+
+    let output = call_text(
+        &handlers,
+        "update_collection",
+        json!({"name": "platform", "add": ["web"]}),
+    )
+    .await;
+    assert!(
+        output.contains("**Sessions (2):** `api`, `web`"),
+        "{output}"
+    );
+
+    let output = call_text(&handlers, "get_server_info", json!({})).await;
+    assert!(output.contains("## Collections"), "{output}");
+    assert!(output.contains("**platform:** 2 session(s)"), "{output}");
+
+    let output = call_text(&handlers, "delete_collection", json!({"name": "platform"})).await;
+    assert!(
+        output.contains("**Collection Deleted:** `platform`"),
+        "{output}"
+    );
+    assert!(services.storage.list_collections().unwrap().is_empty());
+    assert!(services.storage.session_exists("api"));
+}
+
+#[tokio::test]
+async fn test_collection_members_must_exist() {
+    let (_api, _web, _services, handlers) = platform().await;
+
+    let error = call_error(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api", "mobile"]}),
+    )
+    .await;
+    assert!(error.contains("mobile"), "{error}");
+
+    call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api"]}),
+    )
+    .await;
+    let error = call_error(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["web"]}),
+    )
+    .await;
+    assert!(error.contains("already exists"), "{error}");
+    let error = call_error(&handlers, "update_collection", json!({"name": "platform"})).await;
+    assert!(error.contains("Nothing to change"), "{error}");
+}
+
+#[tokio::test]
+async fn test_search_code_expands_collection() {
+    let (_api, _web, _services, handlers) = platform().await;
+    call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api", "web"]}),
+    )
+    .await;
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"query": "verify_token", "collection": "platform"}),
+    )
+    .await;
+    assert!(output.contains("session `api`"), "{output}");
+    assert!(output.contains("session `web`"), "{output}");
+    assert!(output.contains("src/auth.rs"), "{output}");
+    assert!(output.contains("src/session.ts"), "{output}");
+    assert!(!output.contains("src/routes.rs"), "{output}");
+
+    let error = call_error(
+        &handlers,
+        "search_code",
+        json!({"query": "verify_token", "session": "api", "collection": "platform"}),
+    )
+    .await;
+    assert!(error.contains("not both"), "{error}");
+    let error = call_error(
+        &handlers,
+        "search_code",
+        json!({"query": "verify_token", "collection": "mobile"}),
+    )
+    .await;
+    assert!(error.contains("Collection not found: mobile"), "{error}");
+}
+
+#[tokio::test]
+async fn test_find_references_expands_collection() {
+    let (_api, _web, _services, handlers) = platform().await;
+    call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api", "web"]}),
+    )
+    .await;
+
+    let output = call_text(
+        &handlers,
+        "find_references",
+        json!({"symbol": "verify_token", "collection": "platform"}),
+    )
+    .await;
+    assert!(output.contains("src/auth.rs"), "{output}");
+    assert!(output.contains("src/session.ts"), "{output}");
+    assert!(output.contains("`api`"), "{output}");
+    assert!(output.contains("`web`"), "{output}");
+}
+
+#[tokio::test]
+async fn test_list_sessions_filters_by_collection() {
+    let (_api, _web, _services, handlers) = platform().await;
+    call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "backend", "sessions": ["api"]}),
+    )
+    .await;
+
+    let output = call_text(&handlers, "list_sessions", json!({"collection": "backend"})).await;
+    assert!(output.contains("Collection: backend"), "{output}");
+    assert!(output.contains("api"), "{output}");
+    assert!(!output.contains("web"), "{output}");
+}
+
+#[tokio::test]
+async fn test_deleting_member_session_updates_collection() {
+    let (_api, _web, services, handlers) = platform().await;
+    call_text(
+        &handlers,
+        "create_collection",
+        json!({"name": "platform", "sessions": ["api", "web"]}),
+    )
+    .await;
+
+    let output = call_text(
+        &handlers,
+        "delete_session",
+        json!({"session": "web", "confirm": true}),
+    )
+    .await;
+    assert!(
+        output.contains("**Warning:** the session was removed from collection(s) `platform`"),
+        "{output}"
+    );
+    assert_eq!(
+        services
+            .storage
+            .get_collection("platform")
+            .unwrap()
+            .sessions,
+        vec!["api".to_string()]
+    );
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"query": "verify_token", "collection": "platform"}),
+    )
+    .await;
+    assert!(output.contains("src/auth.rs"), "{output}");
+    assert!(!output.contains("src/session.ts"), "{output}");
+}
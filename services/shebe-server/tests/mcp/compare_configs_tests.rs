@@ -0,0 +1,83 @@
+//! Integration tests for compare_configs
+//!
+//! The tool compares two chunk sizes in temporary sessions, deletes
+//! them afterwards, and list_sessions never shows comparison sessions.
+
+use crate::common::{create_test_services, extract_text, TestRepo};
+use serde_json::json;
+use shebe::core::compare::is_comparison_session;
+use shebe::core::storage::SessionConfig;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::{CompareConfigsHandler, ListSessionsHandler};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_compare_configs_markdown_and_json() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    let handler = CompareConfigsHandler::new(Arc::clone(&services));
+    let args = |format: &str| {
+        json!({
+            "path": repo.path().to_str().unwrap(),
+            "config_a": {"chunk_size": 100, "overlap": 10},
+            "config_b": {"chunk_size": 1000, "overlap": 10},
+            "queries": ["authenticate", "connect", "  "],
+            "k": 5,
+            "format": format
+        })
+    };
+
+    let result = handler.execute(args("markdown")).await.unwrap();
+    let output = extract_text(&result);
+    assert!(output.contains("# Config Comparison"), "{output}");
+    assert!(
+        output.contains("| Chunk size / overlap | 100 / 10 | 1000 / 10 |"),
+        "{output}"
+    );
+    assert!(output.contains("| `authenticate` |"), "{output}");
+    assert!(output.contains("**Mean file overlap:**"), "{output}");
+
+    let result = handler.execute(args("json")).await.unwrap();
+    let report: serde_json::Value = serde_json::from_str(extract_text(&result)).unwrap();
+    assert_eq!(report["queries"].as_array().unwrap().len(), 2);
+    assert_eq!(report["b"]["chunk_size"], 1000);
+
+    let ids = services.storage.list_session_ids().unwrap();
+    assert!(ids.iter().all(|id| !is_comparison_session(id)), "{ids:?}");
+}
+
+#[tokio::test]
+async fn test_compare_configs_rejects_unknown_settings() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    let err = CompareConfigsHandler::new(services)
+        .execute(json!({
+            "path": repo.path().to_str().unwrap(),
+            "config_a": {"chunk_size": 100},
+            "config_b": {"chunk": 1000},
+            "queries": ["connect"]
+        }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown field `chunk`"), "{err}");
+}
+
+#[tokio::test]
+async fn test_list_sessions_hides_comparison_sessions() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    for session in ["visible", "_cmp-1a2b3c4d-a"] {
+        services
+            .storage
+            .index_repository_with_config(session, repo.path(), SessionConfig::default(), 10, true)
+            .unwrap();
+    }
+
+    let result = ListSessionsHandler::new(Arc::clone(&services))
+        .execute(json!({}))
+        .await
+        .unwrap();
+    let output = extract_text(&result);
+    assert!(output.contains("visible"), "{output}");
+    assert!(!output.contains("_cmp-"), "{output}");
+}
@@ -0,0 +1,139 @@
+//! Integration tests for sessions whose Tantivy index is corrupted
+//!
+//! A truncated segment file fails with INDEX_CORRUPTED instead of an
+//! internal error, list_sessions still lists the session, and with
+//! `storage.reindex_on_corruption` the session is re-indexed.
+
+use crate::common::{call, index_test_repository, response_text, TestRepo};
+use serde_json::json;
+use shebe::core::config::Config;
+use shebe::core::jobs::JobState;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use shebe::mcp::protocol::INDEX_CORRUPTED;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Session "docs" whose doc store has been truncated
+struct Fixture {
+    handlers: ProtocolHandlers,
+    services: Arc<Services>,
+    _repo: TestRepo,
+    _storage: TempDir,
+}
+
+async fn corrupted_session(reindex_on_corruption: bool) -> Fixture {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let storage = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.storage.index_dir = storage.path().to_path_buf();
+    config.storage.reindex_on_corruption = reindex_on_corruption;
+    let services = Arc::new(Services::new(config));
+    index_test_repository(&services, repo.path(), "docs").await;
+
+    // Cut a segment file in half, as a power loss might
+    let tantivy_dir = services.storage.get_session_path("docs").join("tantivy");
+    let store = std::fs::read_dir(&tantivy_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "store"))
+        .expect("segment doc store");
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&store)
+        .unwrap();
+    file.set_len(file.metadata().unwrap().len() / 2).unwrap();
+
+    Fixture {
+        handlers: ProtocolHandlers::new(Arc::clone(&services)),
+        services,
+        _repo: repo,
+        _storage: storage,
+    }
+}
+
+#[tokio::test]
+async fn test_corrupted_index_is_reported() {
+    let fixture = corrupted_session(false).await;
+    let handlers = &fixture.handlers;
+
+    for (tool, arguments) in [
+        (
+            "search_code",
+            json!({"session": "docs", "query": "authenticate"}),
+        ),
+        (
+            "preview_chunk",
+            json!({"session": "docs", "file_path": "src/auth.rs", "chunk_index": 0}),
+        ),
+    ] {
+        let response = call(handlers, tool, arguments).await;
+        let error = response.error.expect("corruption error");
+        assert_eq!(error.code, INDEX_CORRUPTED, "{tool}: {}", error.message);
+        assert!(
+            error
+                .message
+                .contains("Index of session 'docs' is corrupted")
+                && error.message.contains("reindex_session")
+                && error.message.contains("delete_session"),
+            "{tool}: {}",
+            error.message
+        );
+        assert!(!error.message.contains("Re-indexing automatically"));
+    }
+}
+
+#[tokio::test]
+async fn test_list_sessions_shows_corrupted_index() {
+    let fixture = corrupted_session(false).await;
+    let handlers = &fixture.handlers;
+
+    let response = call(handlers, "list_sessions", json!({})).await;
+    let listing = response_text(&response);
+    assert!(listing.contains("## docs"), "{listing}");
+    assert!(listing.contains("corrupted index"), "{listing}");
+    // A recoverable session is not offered for pruning
+    assert!(!listing.contains("Broken sessions"), "{listing}");
+}
+
+#[tokio::test]
+async fn test_reindex_on_corruption_recovers_session() {
+    let fixture = corrupted_session(true).await;
+    let handlers = &fixture.handlers;
+
+    let response = call(
+        handlers,
+        "search_code",
+        json!({"session": "docs", "query": "authenticate"}),
+    )
+    .await;
+    let error = response.error.expect("corruption error");
+    assert_eq!(error.code, INDEX_CORRUPTED);
+    assert!(
+        error.message.contains("Re-indexing automatically as job-"),
+        "{}",
+        error.message
+    );
+
+    // Wait for the re-index started by the failed call
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let job = loop {
+        let jobs = fixture.services.list_jobs();
+        if jobs.iter().all(|job| job.state.is_finished()) {
+            break jobs.into_iter().next().expect("re-index job");
+        }
+        assert!(Instant::now() < deadline, "re-index did not finish");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    assert_eq!(job.state, JobState::Completed, "{:?}", job.error);
+
+    let response = call(
+        handlers,
+        "search_code",
+        json!({"session": "docs", "query": "authenticate"}),
+    )
+    .await;
+    assert!(response.error.is_none(), "{:?}", response.error);
+    assert!(response_text(&response).contains("authenticate_user"));
+}
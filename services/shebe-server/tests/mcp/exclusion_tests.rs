@@ -0,0 +1,140 @@
+//! Integration tests for search_code exclude_terms and exclude_paths
+//!
+//! Exclusions are `MustNot` clauses of the search itself, so the top k
+//! is filled from the remaining chunks and the header reports how many
+//! otherwise matching chunks were left out.
+
+use crate::common::{call, call_text, create_test_services, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+
+/// Test files mention the parser more often than the code itself
+fn parser_repo() -> TestRepo {
+    TestRepo::with_files(&[
+        (
+            "src/parser.rs",
+            "pub fn parse_header(input: &str) -> Header { parser::read(input) }",
+        ),
+        (
+            "src/lexer.rs",
+            "pub fn tokens(input: &str) -> Vec<Token> { parser::split(input) }",
+        ),
+        (
+            "tests/parser_test.rs",
+            "#[test]\nfn test_parser() { parser::read(\"a\"); parser::read(\"b\"); parser::read(\"c\"); }",
+        ),
+        (
+            "tests/lexer_test.rs",
+            "#[test]\nfn test_lexer_parser() { parser::split(\"a\"); parser::split(\"b\"); }",
+        ),
+        (
+            "tests/fixtures/mock_parser.rs",
+            "pub fn mock_parser() { parser::read(\"mock\"); parser::read(\"mock\"); }",
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn test_exclude_terms_removes_test_files_from_top_k() {
+    let repo = parser_repo();
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "excl").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"query": "parser", "session": "excl", "k": 2}),
+    )
+    .await;
+    assert!(output.contains("tests/parser_test.rs"), "{output}");
+    assert!(!output.contains("Excluded"), "{output}");
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"query": "parser", "session": "excl", "k": 2, "exclude_terms": ["test"]}),
+    )
+    .await;
+    assert!(output.contains("src/parser.rs"), "{output}");
+    assert!(!output.contains("_test.rs"), "{output}");
+    assert!(
+        output.contains("Excluded terms 'test': 2 otherwise matching chunks removed"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn test_exclude_paths_matches_relative_globs() {
+    let repo = parser_repo();
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "excl-paths").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({
+            "query": "parser",
+            "session": "excl-paths",
+            "exclude_terms": ["mock"],
+            "exclude_paths": ["tests/*_test.rs"]
+        }),
+    )
+    .await;
+    assert!(output.contains("src/parser.rs"), "{output}");
+    assert!(output.contains("src/lexer.rs"), "{output}");
+    assert!(!output.contains("/tests/"), "{output}");
+    assert!(
+        output.contains(
+            "Excluded terms 'mock' and paths 'tests/*_test.rs': 3 otherwise matching chunks removed"
+        ),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn test_excluding_every_query_term_is_an_error() {
+    let repo = parser_repo();
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "excl-all").await;
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    let response = call(
+        &handlers,
+        "search_code",
+        json!({"query": "parser", "session": "excl-all", "exclude_terms": ["Parser"]}),
+    )
+    .await;
+    let message = response.error.expect("error").message;
+    assert!(
+        message.contains("exclude_terms excludes every query term (parser)"),
+        "{message}"
+    );
+
+    // Identifier spellings added by the search are not query terms
+    let response = call(
+        &handlers,
+        "search_code",
+        json!({"query": "parseHeader", "session": "excl-all", "exclude_terms": ["parseHeader"]}),
+    )
+    .await;
+    let message = response.error.expect("error").message;
+    assert!(
+        message.contains("exclude_terms excludes every query term (parseheader)"),
+        "{message}"
+    );
+
+    let response = call(
+        &handlers,
+        "search_code",
+        json!({"query": "parser", "session": "excl-all", "exclude_paths": ["[src"]}),
+    )
+    .await;
+    let message = response.error.expect("error").message;
+    assert!(
+        message.contains("Invalid exclude_paths glob '[src'"),
+        "{message}"
+    );
+}
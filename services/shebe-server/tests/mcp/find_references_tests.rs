@@ -405,7 +405,7 @@ async fn test_max_results_200() {
     let mut files = Vec::new();
     for i in 0..50 {
         let filename = format!("src/mod_{}.rs", i);
-        let content = format!("fn process() {{ my_func(); }}\nfn my_func() {{}}");
+        let content = "fn process() { my_func(); }\nfn my_func() {}".to_string();
         files.push((filename, content));
     }
     let files_ref: Vec<(&str, &str)> = files
@@ -856,3 +856,453 @@ async fn test_go_function_call() {
         text
     );
 }
+
+// =============================================================================
+// Column Tests (2 tests)
+// =============================================================================
+
+#[tokio::test]
+async fn test_columns_for_symbol_twice_on_line() {
+    let files = &[(
+        "src/lib.rs",
+        "fn run() {\n    let x = compute_total(compute_total(1));\n}",
+    )];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "col-twice").await;
+
+    let args = json!({
+        "symbol": "compute_total",
+        "session": "col-twice"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(
+        text.contains("lib.rs:2:13"),
+        "Expected file:line:col: {text}"
+    );
+    assert!(
+        text.contains("- **Columns:** 13, 27"),
+        "Expected both columns: {text}"
+    );
+}
+
+#[tokio::test]
+async fn test_column_counts_multibyte_chars() {
+    let files = &[("src/lib.rs", "// 世界\nlet é = \"日本\"; render_page();\n")];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "col-utf8").await;
+
+    let args = json!({
+        "symbol": "render_page",
+        "session": "col-utf8"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    // The symbol starts 19 bytes into the line, but only 14 characters precede the symbol
+    assert!(
+        text.contains("lib.rs:2:15"),
+        "Expected character column: {text}"
+    );
+    assert!(
+        !text.contains("**Columns:**"),
+        "Single match per line: {text}"
+    );
+}
+
+#[tokio::test]
+async fn test_references_follow_edits_since_indexing() {
+    let files = &[
+        ("src/lib.rs", "fn run() {\n    render_page();\n}\n"),
+        ("src/gone.rs", "fn old() {\n    render_page();\n}\n"),
+    ];
+    let (handler, _services, repo) = setup_handler_with_session(files, "drifted").await;
+
+    // Two lines inserted above the chunk; the other file's chunk is deleted
+    std::fs::write(
+        repo.path().join("src/lib.rs"),
+        "// added\n// lines\nfn run() {\n    render_page();\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo.path().join("src/gone.rs"), "fn other() {}\n").unwrap();
+
+    let args = json!({
+        "symbol": "render_page",
+        "session": "drifted"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(
+        text.contains("lib.rs:4:5"),
+        "Expected corrected line: {text}"
+    );
+    assert!(!text.contains("gone.rs"), "Deleted chunk reported: {text}");
+}
+
+// =============================================================================
+// Comment and String Region Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_reference_in_block_comment_is_low_confidence() {
+    // Only the middle line mentions the symbol, without a comment prefix
+    let files = &[(
+        "src/lib.rs",
+        "pub fn run() {}\n\
+         /*\n\
+            Migration notes\n\
+            ===============\n\
+            The old entry point was\n\
+            process_order(order) and callers\n\
+            should switch to run().\n\
+            Nothing else changed.\n\
+            See the changelog.\n\
+         */\n\
+         pub fn other() {}\n",
+    )];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "block-comment").await;
+
+    let args = json!({
+        "symbol": "process_order",
+        "session": "block-comment"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(text.contains("lib.rs:6:1"), "Expected location: {text}");
+    assert!(text.contains("**Pattern:** in_comment"), "{text}");
+    assert!(text.contains("Low Confidence"), "{text}");
+    assert!(!text.contains("Files to update"), "{text}");
+}
+
+#[tokio::test]
+async fn test_reference_in_template_literal_is_low_confidence() {
+    let files = &[(
+        "src/help.js",
+        "const help = `\n\
+         Usage:\n\
+           call process_order(order) to submit\n\
+         `;\n\
+         export default help;\n",
+    )];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "template").await;
+
+    let args = json!({
+        "symbol": "process_order",
+        "session": "template"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(text.contains("help.js:3:"), "Expected location: {text}");
+    assert!(text.contains("**Pattern:** in_string"), "{text}");
+    assert!(text.contains("Low Confidence"), "{text}");
+    assert!(!text.contains("Files to update"), "{text}");
+}
+
+#[tokio::test]
+async fn test_reference_in_python_docstring_is_low_confidence() {
+    let files = &[(
+        "app/orders.py",
+        "def submit(order):\n    \"\"\"Submit an order.\n\n    Replaces process_order(order).\n    \"\"\"\n    return order\n",
+    )];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "docstring").await;
+
+    let args = json!({
+        "symbol": "process_order",
+        "session": "docstring"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(text.contains("**Pattern:** in_string"), "{text}");
+    assert!(!text.contains("Files to update"), "{text}");
+}
+
+#[tokio::test]
+async fn test_code_after_block_comment_keeps_confidence() {
+    let files = &[(
+        "src/lib.rs",
+        "/* setup\n   notes */\nfn main() {\n    process_order(order);\n}\n",
+    )];
+    let (handler, _services, _repo) = setup_handler_with_session(files, "after-comment").await;
+
+    let args = json!({
+        "symbol": "process_order",
+        "session": "after-comment"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(text.contains("**Pattern:** function_call"), "{text}");
+    assert!(text.contains("Files to update"), "{text}");
+}
+
+// =============================================================================
+// Definition Tests
+// =============================================================================
+
+/// One definition and three usages, each in its own file
+const DEFINITION_FIXTURE: &[(&str, &str)] = &[
+    (
+        "src/pricing.rs",
+        "pub fn apply_discount(total: f64) -> f64 {\n    total * 0.9\n}\n",
+    ),
+    (
+        "src/cart.rs",
+        "fn checkout(total: f64) -> f64 {\n    apply_discount(total)\n}\n",
+    ),
+    (
+        "src/invoice.rs",
+        "fn invoice_total(sum: f64) -> f64 {\n    apply_discount(sum) + 1.0\n}\n",
+    ),
+    (
+        "src/quote.rs",
+        "fn quote(base: f64) -> f64 {\n    let price = apply_discount(base);\n    price\n}\n",
+    ),
+];
+
+/// The "Files to update" list of a find_references output
+fn files_to_update(text: &str) -> &str {
+    text.split("**Files to update:**").nth(1).unwrap_or("")
+}
+
+#[tokio::test]
+async fn test_definition_listed_first_and_not_updated() {
+    let (handler, _services, _repo) =
+        setup_handler_with_session(DEFINITION_FIXTURE, "definition-sections").await;
+
+    let args = json!({
+        "symbol": "apply_discount",
+        "session": "definition-sections"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    // The definition section comes before every confidence group
+    let first_section = text.split("\n### ").nth(1).unwrap();
+    assert!(first_section.starts_with("Definition (1)"), "{text}");
+    assert!(first_section.contains("pricing.rs:1:8"), "{text}");
+    assert!(first_section.contains("**Pattern:** definition"), "{text}");
+    let high = text.split("### High Confidence (3)").nth(1).unwrap();
+    assert!(!high.contains("pricing.rs:1"), "{text}");
+    assert!(text.contains("- Definitions: 1"), "{text}");
+
+    let update = files_to_update(text);
+    for usage in ["cart.rs", "invoice.rs", "quote.rs"] {
+        assert!(update.contains(usage), "{text}");
+    }
+    assert!(!update.contains("pricing.rs"), "{text}");
+}
+
+#[tokio::test]
+async fn test_include_definition_adds_definition_to_files_to_update() {
+    let (handler, _services, _repo) =
+        setup_handler_with_session(DEFINITION_FIXTURE, "definition-included").await;
+
+    let args = json!({
+        "symbol": "apply_discount",
+        "session": "definition-included",
+        "include_definition": true
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    let first_section = text.split("\n### ").nth(1).unwrap();
+    assert!(first_section.starts_with("Definition (1)"), "{text}");
+    let update = files_to_update(text);
+    for file in ["pricing.rs", "cart.rs", "invoice.rs", "quote.rs"] {
+        assert!(update.contains(file), "{text}");
+    }
+}
+
+#[tokio::test]
+async fn test_defined_in_still_excludes_definition_file() {
+    let (handler, _services, _repo) =
+        setup_handler_with_session(DEFINITION_FIXTURE, "definition-defined-in").await;
+
+    let args = json!({
+        "symbol": "apply_discount",
+        "session": "definition-defined-in",
+        "defined_in": "src/pricing.rs"
+    });
+
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(!text.contains("### Definition"), "{text}");
+    assert!(!text.contains("pricing.rs"), "{text}");
+    assert!(text.contains("### High Confidence (3)"), "{text}");
+}
+
+// =============================================================================
+// Unreadable Files
+// =============================================================================
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unreadable_file_keeps_reference_with_warning() {
+    let files = &[
+        ("src/lib.rs", "pub fn load_config() {}\n"),
+        (
+            "src/locked.rs",
+            "fn start() {\n    let config = load_config();\n}\n",
+        ),
+    ];
+    let (handler, _services, repo) = setup_handler_with_session(files, "unreadable").await;
+    crate::common::make_unreadable(&repo.path().join("src/locked.rs"));
+
+    let args = json!({
+        "symbol": "load_config",
+        "session": "unreadable"
+    });
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    // Still listed, from the indexed text, at its byte offset
+    let offset = "fn start() {\n    let config = ".len();
+    assert!(
+        text.contains(&format!("locked.rs (byte {offset})")),
+        "{text}"
+    );
+    assert!(text.contains("let config = load_config();"), "{text}");
+    assert!(
+        text.contains("- **Context:** context_unavailable ("),
+        "{text}"
+    );
+    // function_call (0.95) less the penalty
+    assert!(text.contains("- **Confidence:** 0.85"), "{text}");
+    assert!(
+        text.contains("- Degraded context: 1 references (file unreadable)"),
+        "{text}"
+    );
+
+    let warnings = &text[text.find("### Warnings").expect("Warnings section")..];
+    assert!(warnings.contains("1 file(s) could not be read"), "{text}");
+    assert!(warnings.contains("locked.rs`: "), "{text}");
+    assert!(!warnings.contains("lib.rs"), "{text}");
+}
+
+// =============================================================================
+// Several Symbols
+// =============================================================================
+
+/// A type and its method, used together in some files
+const CART_FIXTURE: &[(&str, &str)] = &[
+    (
+        "src/cart.rs",
+        "pub struct Cart {}\n\nimpl Cart {\n    pub fn add_item(&mut self) {}\n}\n",
+    ),
+    (
+        "src/checkout.rs",
+        "fn checkout(cart: Cart) {\n    cart.add_item();\n}\n",
+    ),
+    (
+        "src/report.rs",
+        "fn report(cart: Cart) -> usize {\n    0\n}\n",
+    ),
+    ("src/shared.rs", "fn shared() {\n    Cart::add_item();\n}\n"),
+];
+
+#[tokio::test]
+async fn test_symbol_array_groups_by_symbol_and_annotates_files() {
+    let (handler, _services, _repo) = setup_handler_with_session(CART_FIXTURE, "cart").await;
+
+    let args = json!({
+        "symbol": ["Cart", "add_item"],
+        "session": "cart"
+    });
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(
+        text.starts_with("# References to 2 symbols: `Cart`, `add_item` ("),
+        "{text}"
+    );
+    let cart = text.find("## `Cart` (").expect("Cart section");
+    let add_item = text.find("## `add_item` (").expect("add_item section");
+    assert!(cart < add_item, "{text}");
+
+    // A location found for both symbols is listed once, under the first
+    assert_eq!(text.matches("shared.rs:2:").count(), 1, "{text}");
+    assert!(text[cart..add_item].contains("shared.rs:2:"), "{text}");
+    assert!(text.contains("- **Also matches:** `add_item`"), "{text}");
+
+    // One combined list, noting which symbols touch each file
+    let files = &text[text.find("**Files to update:**").expect("file list")..];
+    assert!(
+        files.contains("checkout.rs` (`Cart`, `add_item`)"),
+        "{files}"
+    );
+    assert!(files.contains("report.rs` (`Cart`)"), "{files}");
+    assert!(files.contains("shared.rs` (`add_item`)"), "{files}");
+    assert!(!files.contains("cart.rs` ("), "{files}");
+    assert_eq!(text.matches("**Files to update:**").count(), 1);
+}
+
+#[tokio::test]
+async fn test_symbol_array_caps_each_symbol() {
+    let mut files: Vec<(String, String)> = (0..6)
+        .map(|i| {
+            (
+                format!("src/use_{i}.rs"),
+                format!("fn use_{i}(cart: Cart) {{\n    let n = 1;\n    cart.add_item();\n}}\n"),
+            )
+        })
+        .collect();
+    files.push((
+        "src/cart.rs".to_string(),
+        "pub struct Cart {}\n".to_string(),
+    ));
+    let files_ref: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(f, c)| (f.as_str(), c.as_str()))
+        .collect();
+    let (handler, _services, _repo) = setup_handler_with_session(&files_ref, "cart-cap").await;
+
+    let args = json!({
+        "symbol": ["Cart", "add_item"],
+        "session": "cart-cap",
+        "max_results": 2,
+        "context_lines": 0
+    });
+    let result = handler.execute(args).await.expect("Execute failed");
+    let text = extract_text(&result);
+
+    assert!(text.contains("## `Cart` (2 found)"), "{text}");
+    assert!(text.contains("## `add_item` (2 found)"), "{text}");
+    assert!(
+        text.contains("symbols: `Cart`, `add_item` (4 found)"),
+        "{text}"
+    );
+}
+
+#[tokio::test]
+async fn test_symbol_array_size_rejected() {
+    let (handler, _services, _repo) = setup_handler_with_session(CART_FIXTURE, "cart-size").await;
+
+    for symbols in [json!([]), json!(["Cart"]), json!(vec!["Cart"; 21])] {
+        let args = json!({"symbol": symbols, "session": "cart-size"});
+        match handler.execute(args).await {
+            Err(McpError::InvalidParams(message)) => {
+                assert!(message.contains("2 to 20 symbols"), "{message}")
+            }
+            other => panic!("expected InvalidParams, got {other:?}"),
+        }
+    }
+
+    let args = json!({"symbol": ["Cart", "x"], "session": "cart-size"});
+    assert!(matches!(
+        handler.execute(args).await,
+        Err(McpError::InvalidParams(_))
+    ));
+}
@@ -0,0 +1,120 @@
+//! Integration tests for the find_similar tool
+//!
+//! A chunk's distinctive terms should lead to chunks of other files
+//! using the same vocabulary, and a chunk whose terms appear nowhere
+//! else should get an honest "nothing similar" answer.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::FindSimilarHandler;
+use std::sync::Arc;
+
+fn fixture() -> TestRepo {
+    TestRepo::with_files(&[
+        (
+            "src/rate_limit.rs",
+            "pub fn refill_token_bucket(bucket: &mut TokenBucket, elapsed: Duration) {\n    \
+             let tokens = bucket.refill_rate * elapsed.as_secs_f64();\n    \
+             bucket.available_tokens = (bucket.available_tokens + tokens).min(bucket.capacity);\n}\n",
+        ),
+        (
+            "src/throttle.rs",
+            "pub fn allow_request(bucket: &mut TokenBucket) -> bool {\n    \
+             if bucket.available_tokens < 1.0 {\n        return false;\n    }\n    \
+             bucket.available_tokens -= 1.0;\n    bucket.capacity > 0.0\n}\n",
+        ),
+        (
+            "src/render.rs",
+            "pub fn render_page(html: &str) -> String {\n    html.to_uppercase()\n}\n",
+        ),
+        (
+            "LICENSE_HEADER.txt",
+            "Copyright Zorblax Quimsy Frobnicators. Unpublished glarnwork, \
+             distributed verbatim nowhere.\n",
+        ),
+    ])
+}
+
+async fn find_similar(services: &Arc<Services>, args: Value) -> Result<String, McpError> {
+    let handler = FindSimilarHandler::new(Arc::clone(services));
+    let result = handler.execute(args).await?;
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    Ok(text.clone())
+}
+
+#[tokio::test]
+async fn test_files_sharing_vocabulary_find_each_other() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "similar").await;
+
+    for (source, expected) in [
+        ("src/rate_limit.rs", "throttle.rs"),
+        ("src/throttle.rs", "rate_limit.rs"),
+    ] {
+        let output = find_similar(
+            &services,
+            json!({"session": "similar", "file_path": source, "chunk_index": 0}),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.contains("**Seed terms:**"), "{output}");
+        assert!(output.contains("## Result 1"), "{output}");
+        let first = output.split("## Result 1").nth(1).unwrap();
+        assert!(first.contains(expected), "{output}");
+        assert!(first.contains("**Matched terms:**"), "{output}");
+        assert!(first.contains("bucket"), "{output}");
+        assert!(!output.contains("render.rs"), "{output}");
+        assert!(!output.contains("LICENSE_HEADER"), "{output}");
+        assert!(!output.contains("Nothing similar"), "{output}");
+    }
+}
+
+#[tokio::test]
+async fn test_unique_chunk_finds_nothing_similar() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "similar").await;
+
+    let output = find_similar(
+        &services,
+        json!({"session": "similar", "file_path": "LICENSE_HEADER.txt", "chunk_index": 0}),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("Nothing similar found"), "{output}");
+    assert!(!output.contains("## Result"), "{output}");
+}
+
+#[tokio::test]
+async fn test_find_similar_from_result_handle() {
+    use shebe::mcp::tools::SearchCodeHandler;
+
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "similar").await;
+
+    SearchCodeHandler::new(Arc::clone(&services))
+        .execute(json!({"session": "similar", "query": "refill_rate", "k": 1}))
+        .await
+        .unwrap();
+    let output = find_similar(&services, json!({"session": "similar", "result": "r1"}))
+        .await
+        .unwrap();
+    let header = output.lines().next().unwrap();
+    assert!(header.contains("rate_limit.rs"), "{output}");
+    assert!(output.contains("throttle.rs"), "{output}");
+
+    let err = find_similar(
+        &services,
+        json!({"session": "similar", "result": "r1", "file_path": "src/render.rs"}),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, McpError::InvalidParams(_)), "{err:?}");
+}
@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
+    use serde_json::{json, Value};
     use shebe::core::config::Config;
     use shebe::core::services::Services;
     use shebe::mcp::handlers::ProtocolHandlers;
@@ -79,8 +79,13 @@ mod tests {
         let result = response.result.unwrap();
         let tools = result["tools"].as_array().unwrap();
         // search, list, info, index, server_info, config, read, delete, list_dir, find,
-        // find_references, preview, reindex, upgrade
-        assert_eq!(tools.len(), 14);
+        // find_references, preview, reindex, upgrade, compact, list_tree,
+        // index_async, job_status, cancel_job, search_batch, storage_report,
+        // set_reindex_schedule, diff_sessions, compare_configs, find_similar, reload_config,
+        // optimize_session, sample_files, locate, search_paths, run_self_test,
+        // set_session_defaults, create_collection, update_collection,
+        // delete_collection
+        assert_eq!(tools.len(), 35);
     }
 
     #[tokio::test]
@@ -173,6 +178,85 @@ mod tests {
             "Error message should indicate path is not a directory, got: {}",
             error.message
         );
+        assert!(
+            error.message.contains("is a file") && error.message.contains("detect_root"),
+            "Error message should suggest alternatives, got: {}",
+            error.message
+        );
+    }
+
+    fn index_request(arguments: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(5)),
+            method: "tools/call".to_string(),
+            params: Some(json!({"name": "index_repository", "arguments": arguments})),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_detect_root_from_nested_file() {
+        let (handlers, temp) = create_test_handlers();
+
+        let repo_dir = temp.path().join("test-repo");
+        std::fs::create_dir_all(repo_dir.join(".git")).unwrap();
+        std::fs::create_dir_all(repo_dir.join("src/auth")).unwrap();
+        let file = repo_dir.join("src/auth/login.rs");
+        std::fs::write(&file, "pub fn login() {}").unwrap();
+        std::fs::write(repo_dir.join("README.md"), "# Test repo").unwrap();
+
+        let response = handlers
+            .handle_tools_call(index_request(json!({
+                "path": file.to_str().unwrap(),
+                "session": "detected",
+                "detect_root": true
+            })))
+            .await
+            .unwrap();
+
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        let root = repo_dir.canonicalize().unwrap();
+        assert!(
+            content.contains(&format!(
+                "Repository root: {} (contains .git; detected from {})",
+                root.display(),
+                root.join("src/auth/login.rs").display()
+            )),
+            "{content}"
+        );
+        assert!(content.contains("Files indexed: 2"), "{content}");
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_detect_root_without_marker() {
+        let (handlers, temp) = create_test_handlers();
+
+        let loose = temp.path().join("loose");
+        std::fs::create_dir_all(&loose).unwrap();
+        let file = loose.join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+
+        let response = handlers
+            .handle_tools_call(index_request(json!({
+                "path": file.to_str().unwrap(),
+                "session": "loose",
+                "detect_root": true
+            })))
+            .await
+            .unwrap();
+
+        let error = response.error.expect("no repository root above the file");
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert!(
+            error.message.contains("No repository root found"),
+            "{}",
+            error.message
+        );
+        assert!(error
+            .message
+            .contains(&loose.canonicalize().unwrap().display().to_string()));
     }
 
     #[tokio::test]
@@ -282,6 +366,185 @@ mod tests {
             "Expected 'Indexing complete', got: {content}"
         );
         assert!(content.contains("Files indexed: 2"));
+        assert!(
+            content.contains("Include patterns: **/* (2 files)"),
+            "{content}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_verbose_lists_skipped_files() {
+        let (handlers, temp) = create_test_handlers();
+
+        let repo_dir = temp.path().join("test-repo");
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        std::fs::write(repo_dir.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo_dir.join("app.log"), "started").unwrap();
+        std::fs::write(repo_dir.join("logo.rs"), [b'P', 0, b'N', b'G']).unwrap();
+
+        let call = |verbose: bool, id: i64| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo_dir.to_str().unwrap(),
+                    "session": "test-verbose",
+                    "exclude_patterns": ["*.log"],
+                    "verbose": verbose
+                }
+            })),
+        };
+
+        let response = handlers.handle_tools_call(call(false, 23)).await.unwrap();
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(
+            content.contains("Files skipped: 2 (1 excluded by pattern, 1 binary)"),
+            "{content}"
+        );
+        assert!(!content.contains("Skipped files:"), "{content}");
+
+        let response = handlers.handle_tools_call(call(true, 24)).await.unwrap();
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("Skipped files:"), "{content}");
+        assert!(
+            content.contains("- app.log (excluded by pattern: matches exclude pattern `*.log`)"),
+            "{content}"
+        );
+        assert!(
+            content.contains("- logo.rs (binary: NUL byte at offset 1)"),
+            "{content}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_unmatched_include_patterns() {
+        let (handlers, temp) = create_test_handlers();
+
+        let repo_dir = temp.path().join("test-repo");
+        std::fs::create_dir_all(repo_dir.join("src")).unwrap();
+        for i in 0..12 {
+            std::fs::write(
+                repo_dir.join(format!("src/m{i}.rs")),
+                format!("fn m{i}() {{}}"),
+            )
+            .unwrap();
+        }
+
+        let call = |strict: bool, id: i64| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo_dir.to_str().unwrap(),
+                    "session": "test-patterns",
+                    "include_patterns": ["src/*.rs"],
+                    "strict_patterns": strict
+                }
+            })),
+        };
+
+        let response = handlers.handle_tools_call(call(false, 25)).await.unwrap();
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("Files indexed: 0"), "{content}");
+        assert!(
+            content.contains("Include patterns: src/*.rs (0 files)"),
+            "{content}"
+        );
+        assert!(
+            content.contains("WARNING: No file matches the include patterns (12 files seen)"),
+            "{content}"
+        );
+        assert!(
+            content.contains("'**/src/*.rs' would match 12"),
+            "{content}"
+        );
+
+        let response = handlers.handle_tools_call(call(true, 26)).await.unwrap();
+        let error = response.error.expect("strict_patterns should fail");
+        assert!(
+            error.message.contains("'**/src/*.rs' would match 12"),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_refuses_storage_root() {
+        let (handlers, temp) = create_test_handlers();
+        std::fs::write(temp.path().join("notes.md"), "# Notes").unwrap();
+
+        let call = |allow: bool, id: i64| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": temp.path().to_str().unwrap(),
+                    "session": "storage-root",
+                    "allow_dangerous_path": allow
+                }
+            })),
+        };
+
+        let response = handlers.handle_tools_call(call(false, 25)).await.unwrap();
+        let err = response.error.expect("storage root should be refused");
+        assert!(
+            err.message.contains("the Shebe storage root"),
+            "{}",
+            err.message
+        );
+        assert!(
+            err.message.contains("allow_dangerous_path"),
+            "{}",
+            err.message
+        );
+
+        let response = handlers.handle_tools_call(call(true, 26)).await.unwrap();
+        assert!(response.error.is_none(), "{:?}", response.error);
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_over_file_limit() {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp.path().join("index");
+        config.limits.max_files_per_session = 1;
+        let handlers = ProtocolHandlers::new(Arc::new(Services::new(config)));
+
+        let repo_dir = temp.path().join("repo");
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        std::fs::write(repo_dir.join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(repo_dir.join("b.rs"), "fn b() {}").unwrap();
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(27)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo_dir.to_str().unwrap(),
+                    "session": "too-big"
+                }
+            })),
+        };
+        let response = handlers.handle_tools_call(request).await.unwrap();
+
+        let err = response.error.expect("walk over the limit should fail");
+        assert!(err.message.contains("Index too large"), "{}", err.message);
+        assert!(
+            err.message.contains("limits.max_files_per_session"),
+            "{}",
+            err.message
+        );
+        assert!(!temp.path().join("index/sessions/too-big").exists());
     }
 
     #[tokio::test]
@@ -333,6 +596,237 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_index_repository_dry_run() {
+        let (handlers, temp) = create_test_handlers();
+
+        let repo_dir = temp.path().join("repo-dry-run");
+        std::fs::create_dir_all(repo_dir.join("src")).unwrap();
+        std::fs::create_dir_all(repo_dir.join("node_modules/pkg")).unwrap();
+        std::fs::write(repo_dir.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo_dir.join("README.md"), "# Readme").unwrap();
+        std::fs::write(repo_dir.join("node_modules/pkg/index.js"), "x").unwrap();
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(13)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo_dir.to_str().unwrap(),
+                    "session": "dry-run",
+                    "force": false,
+                    "dry_run": true
+                }
+            })),
+        };
+        let response = handlers.handle_tools_call(request).await.unwrap();
+
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("No session was created"));
+        assert!(content.contains("- **Files:** 2"));
+        assert!(content.contains("| `**/node_modules/**` | 0 | 1 |"));
+        assert!(content.contains("- `src/main.rs`"));
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(14)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "list_sessions",
+                "arguments": {}
+            })),
+        };
+        let response = handlers.handle_tools_call(request).await.unwrap();
+        let result = response.result.unwrap();
+        assert!(!result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("dry-run"));
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_repo_local_settings() {
+        let (handlers, temp) = create_test_handlers();
+
+        let repo_dir = temp.path().join("repo-local-settings");
+        std::fs::create_dir_all(repo_dir.join("logs")).unwrap();
+        std::fs::write(repo_dir.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo_dir.join("logs/app.log"), "started").unwrap();
+        std::fs::write(repo_dir.join(".shebeignore"), "logs/\n!keep.log\n").unwrap();
+        std::fs::write(
+            repo_dir.join(".shebe.toml"),
+            "chunk_size = 1024\noverlap = 100\nbogus = true\n",
+        )
+        .unwrap();
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(15)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo_dir.to_str().unwrap(),
+                    "session": "repo-local",
+                    "overlap": 50,
+                    "include_patterns": ["*.rs", "*.log"]
+                }
+            })),
+        };
+        let response = handlers.handle_tools_call(request).await.unwrap();
+
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("Files indexed: 1"), "{content}");
+        assert!(content.contains("From `.shebe.toml`: chunk_size"));
+        assert!(content.contains("From `.shebeignore`: 1 exclude pattern(s)"));
+        assert!(content.contains("- .shebeignore:2: Negated pattern"));
+        assert!(content.contains("- .shebe.toml:3: Unknown key 'bogus'"));
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(16)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "get_session_info",
+                "arguments": {"session": "repo-local"}
+            })),
+        };
+        let response = handlers.handle_tools_call(request).await.unwrap();
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("- **Chunk size:** 1024 chars (from .shebe.toml)"));
+        assert!(content.contains("- **Overlap:** 50 chars (from argument)"));
+        assert!(content.contains("*.rs, *.log (from argument)"));
+        assert!(content.contains("(from default)"));
+        assert!(content.contains("- **.shebeignore patterns:** logs/"));
+    }
+
+    /// Call a tool and return the response
+    async fn call_tool(
+        handlers: &ProtocolHandlers,
+        name: &str,
+        arguments: Value,
+    ) -> JsonRpcResponse {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: "tools/call".to_string(),
+            params: Some(json!({"name": name, "arguments": arguments})),
+        };
+        handlers.handle_tools_call(request).await.unwrap()
+    }
+
+    fn preset_repo(temp: &TempDir, name: &str) -> String {
+        let repo_dir = temp.path().join(name);
+        std::fs::create_dir_all(repo_dir.join("docs")).unwrap();
+        std::fs::write(repo_dir.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo_dir.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(repo_dir.join("docs/guide.md"), "# Guide").unwrap();
+        std::fs::write(repo_dir.join("script.py"), "print(1)").unwrap();
+        std::fs::write(repo_dir.join("Makefile"), "all:").unwrap();
+        repo_dir.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_language_presets() {
+        let (handlers, temp) = create_test_handlers();
+        let path = preset_repo(&temp, "repo-presets");
+
+        let response = call_tool(
+            &handlers,
+            "index_repository",
+            json!({"path": path, "session": "presets", "languages": ["rust"]}),
+        )
+        .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let content = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(content.contains("Files indexed: 2"), "{content}");
+
+        let response =
+            call_tool(&handlers, "get_session_info", json!({"session": "presets"})).await;
+        let content = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(
+            content.contains("- **Include patterns:** **/*.rs, **/*.toml (from argument)"),
+            "{content}"
+        );
+        assert!(
+            content.contains("- **Language presets:** rust"),
+            "{content}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_presets_with_include_patterns() {
+        let (handlers, temp) = create_test_handlers();
+        let path = preset_repo(&temp, "repo-presets-union");
+
+        let response = call_tool(
+            &handlers,
+            "index_repository",
+            json!({
+                "path": path,
+                "session": "presets-union",
+                "languages": ["rust", "docs"],
+                "include_patterns": ["Makefile"]
+            }),
+        )
+        .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let content = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(content.contains("Files indexed: 4"), "{content}");
+
+        let response = call_tool(
+            &handlers,
+            "get_session_info",
+            json!({"session": "presets-union"}),
+        )
+        .await;
+        let content = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(
+            content.contains("Makefile, **/*.rs, **/*.toml, **/*.md, **/*.rst, **/*.txt"),
+            "{content}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_repository_unknown_preset() {
+        let (handlers, temp) = create_test_handlers();
+        let path = preset_repo(&temp, "repo-presets-unknown");
+
+        let response = call_tool(
+            &handlers,
+            "index_repository",
+            json!({"path": path, "session": "presets-unknown", "languages": ["cobol"]}),
+        )
+        .await;
+        let error = response.error.expect("unknown preset should fail");
+        assert!(error.message.contains("Unknown language preset 'cobol'"));
+        assert!(
+            error.message.contains("docs, go, java"),
+            "{}",
+            error.message
+        );
+        assert!(!temp.path().join("sessions/presets-unknown").exists());
+    }
+
     #[tokio::test]
     async fn test_index_repository_force_true_reindex() {
         let (handlers, temp) = create_test_handlers();
@@ -536,4 +1030,290 @@ mod tests {
             err.message
         );
     }
+
+    // --- Session suggestions ---
+
+    #[tokio::test]
+    async fn test_tools_call_unknown_session_suggests_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        let services = Arc::new(Services::new(config));
+        for id in ["backend", "backend-v2", "frontend"] {
+            services
+                .storage
+                .create_session(id, temp_dir.path().to_path_buf(), Default::default())
+                .unwrap();
+        }
+        let handlers = ProtocolHandlers::new(services);
+
+        for (tool, arguments) in [
+            (
+                "search_code",
+                json!({"session": "backand", "query": "main"}),
+            ),
+            ("get_session_info", json!({"session": "backand"})),
+            (
+                "delete_session",
+                json!({"session": "backand", "confirm": true}),
+            ),
+        ] {
+            let request = JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: Some(json!(21)),
+                method: "tools/call".to_string(),
+                params: Some(json!({"name": tool, "arguments": arguments})),
+            };
+
+            let response = handlers.handle_tools_call(request).await.unwrap();
+            let message = response.error.unwrap().message;
+            assert!(
+                message.contains("Did you mean: backend, backend-v2?"),
+                "{tool}: {message}"
+            );
+            assert!(
+                message.contains("Available sessions: backend, backend-v2, frontend"),
+                "{tool}: {message}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_session_hint_only_for_missing_session() {
+        let (handlers, _temp) = create_test_handlers();
+
+        // index_repository creates the session; its errors get no hint
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(22)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {"path": "/nonexistent/path", "session": "new"}
+            })),
+        };
+
+        let response = handlers.handle_tools_call(request).await.unwrap();
+        let message = response.error.unwrap().message;
+        assert!(!message.contains("Available sessions"), "{message}");
+        assert!(!message.contains("No sessions"), "{message}");
+    }
+
+    fn tools_call(tool: &str, arguments: serde_json::Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(23)),
+            method: "tools/call".to_string(),
+            params: Some(json!({"name": tool, "arguments": arguments})),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_no_sessions_explains_indexing() {
+        let (handlers, _temp) = create_test_handlers();
+        let handlers = handlers.with_repository_hint(None);
+
+        for (tool, arguments) in [
+            (
+                "search_code",
+                json!({"session": "myproject", "query": "main"}),
+            ),
+            (
+                "find_references",
+                json!({"session": "myproject", "symbol": "main"}),
+            ),
+            ("list_dir", json!({"session": "myproject"})),
+        ] {
+            let response = handlers
+                .handle_tools_call(tools_call(tool, arguments))
+                .await
+                .unwrap();
+            let message = response.error.unwrap().message;
+            assert!(
+                message.contains("No sessions have been indexed yet. Index a repository first"),
+                "{tool}: {message}"
+            );
+            assert!(
+                message.contains(
+                    r#"{"name": "index_repository", "arguments": {"path": "/absolute/path/to/repo", "session": "myproject"}}"#
+                ),
+                "{tool}: {message}"
+            );
+            assert!(
+                !message.contains("Repository detected"),
+                "{tool}: {message}"
+            );
+        }
+
+        // Other tools keep the short note
+        let response = handlers
+            .handle_tools_call(tools_call(
+                "get_session_info",
+                json!({"session": "myproject"}),
+            ))
+            .await
+            .unwrap();
+        let message = response.error.unwrap().message;
+        assert!(
+            message.ends_with("No sessions have been indexed yet."),
+            "{message}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_no_sessions_suggests_repository() {
+        let (handlers, temp) = create_test_handlers();
+        let repo = temp.path().join("checkout");
+        let handlers = handlers.with_repository_hint(Some(repo.clone()));
+
+        // The server's working directory is suggested...
+        let response = handlers
+            .handle_tools_call(tools_call(
+                "search_code",
+                json!({"session": "myproject", "query": "main"}),
+            ))
+            .await
+            .unwrap();
+        let message = response.error.unwrap().message;
+        assert!(
+            message.contains(&format!("Repository detected: {}", repo.display())),
+            "{message}"
+        );
+        assert!(
+            message.contains(&format!(r#""path": "{}""#, repo.display())),
+            "{message}"
+        );
+
+        // ...unless the call names the repository
+        let response = handlers
+            .handle_tools_call(tools_call(
+                "list_dir",
+                json!({"session": "myproject", "path": "/work/myproject"}),
+            ))
+            .await
+            .unwrap();
+        let message = response.error.unwrap().message;
+        assert!(
+            message.contains("Repository detected: /work/myproject"),
+            "{message}"
+        );
+        assert!(
+            message.contains(r#""path": "/work/myproject""#),
+            "{message}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_wrong_session_gets_suggestions_not_guidance() {
+        let temp = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp.path().to_path_buf();
+        let services = Arc::new(Services::new(config));
+        services
+            .storage
+            .create_session("backend", temp.path().to_path_buf(), Default::default())
+            .unwrap();
+        let handlers =
+            ProtocolHandlers::new(services).with_repository_hint(Some(temp.path().to_path_buf()));
+
+        let response = handlers
+            .handle_tools_call(tools_call(
+                "search_code",
+                json!({"session": "backand", "query": "main"}),
+            ))
+            .await
+            .unwrap();
+        let message = response.error.unwrap().message;
+        assert!(message.contains("Did you mean: backend?"), "{message}");
+        assert!(!message.contains("index_repository"), "{message}");
+        assert!(!message.contains("No sessions"), "{message}");
+    }
+
+    // --- Tool enablement ---
+
+    #[tokio::test]
+    async fn test_disabled_tools_hidden_and_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.storage.index_dir = temp_dir.path().to_path_buf();
+        config.mcp.tools.insert("read_file".to_string(), false);
+        config.mcp.tools.insert("list_dir".to_string(), false);
+        config.mcp.tools.insert("no_such_tool".to_string(), false);
+        let handlers = ProtocolHandlers::new(Arc::new(Services::new(config)));
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(30)),
+            method: "tools/list".to_string(),
+            params: None,
+        };
+        let result = handlers
+            .handle_tools_list(request)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        let names: Vec<&str> = result["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"search_code"));
+        assert!(!names.contains(&"read_file"));
+        assert!(!names.contains(&"list_dir"));
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(31)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "read_file",
+                "arguments": {"session": "any", "file_path": "/etc/hosts"}
+            })),
+        };
+        let error = handlers
+            .handle_tools_call(request)
+            .await
+            .unwrap()
+            .error
+            .unwrap();
+        assert_eq!(error.code, METHOD_NOT_FOUND);
+        assert!(error.message.contains("administratively disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_search_completes_within_time_limit() {
+        let (handlers, temp) = create_test_handlers();
+        let repo_dir = temp.path().join("timed-repo");
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        std::fs::write(repo_dir.join("ledger.rs"), "pub fn post_ledger() {}\n").unwrap();
+
+        let index = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(40)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {"path": repo_dir.to_str().unwrap(), "session": "timed"}
+            })),
+        };
+        let response = handlers.handle_tools_call(index).await.unwrap();
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        let search = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(41)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "search_code",
+                "arguments": {"query": "post_ledger", "session": "timed", "timeout_sec": 30}
+            })),
+        };
+        let response = handlers.handle_tools_call(search).await.unwrap();
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let result = response.result.unwrap();
+        let content = result["content"][0]["text"].as_str().unwrap();
+        assert!(content.contains("ledger.rs"), "got: {content}");
+    }
 }
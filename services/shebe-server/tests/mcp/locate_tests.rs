@@ -0,0 +1,123 @@
+//! Integration tests for the locate tool
+//!
+//! A needle can match a file path, file contents or both; files found
+//! both ways rank first, and every row says how it was found.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::LocateHandler;
+use std::sync::Arc;
+
+fn fixture() -> TestRepo {
+    TestRepo::with_files(&[
+        (
+            "src/net/retry_config.rs",
+            "pub struct Settings { pub attempts: u32 }\n",
+        ),
+        (
+            "config/retry.yaml",
+            "retry:\n  attempts: 3\n  config_version: 2\n",
+        ),
+        (
+            "src/client.rs",
+            "// Retry config: exponential backoff between attempts\nfn backoff() {}\n",
+        ),
+        ("src/dispatcher.rs", "pub fn route() {}\n"),
+        ("src/main.rs", "fn main() {}\n"),
+    ])
+}
+
+/// Table rows as (file name, found by)
+async fn locate(services: &Arc<Services>, args: Value) -> Vec<(String, String)> {
+    let handler = LocateHandler::new(Arc::clone(services));
+    let result = handler.execute(args).await.unwrap();
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    text.lines()
+        .filter(|line| line.starts_with("| ") && !line.starts_with("| #"))
+        .map(|line| {
+            let cells: Vec<&str> = line.split(" | ").collect();
+            let file = cells[1].trim_matches('`');
+            let name = file.rsplit('/').next().unwrap().to_string();
+            (name, cells[2].to_string())
+        })
+        .collect()
+}
+
+fn rows(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(file, found_by)| (file.to_string(), found_by.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_locate_filename_only() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "locate").await;
+
+    let found = locate(
+        &services,
+        json!({"session": "locate", "needle": "dispatcher"}),
+    )
+    .await;
+    assert_eq!(found, rows(&[("dispatcher.rs", "path match")]));
+}
+
+#[tokio::test]
+async fn test_locate_content_only() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "locate").await;
+
+    let found = locate(
+        &services,
+        json!({"session": "locate", "needle": "exponential backoff"}),
+    )
+    .await;
+    assert_eq!(found, rows(&[("client.rs", "content match")]));
+}
+
+#[tokio::test]
+async fn test_locate_merges_path_and_content() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "locate").await;
+
+    let found = locate(
+        &services,
+        json!({"session": "locate", "needle": "retry config"}),
+    )
+    .await;
+    assert_eq!(
+        found,
+        rows(&[
+            ("retry.yaml", "path + content match"),
+            ("retry_config.rs", "path match"),
+            ("client.rs", "content match"),
+        ])
+    );
+
+    // Strategies restrict to one kind of match
+    let found = locate(
+        &services,
+        json!({"session": "locate", "needle": "retry config", "strategy": "path"}),
+    )
+    .await;
+    assert_eq!(
+        found,
+        rows(&[
+            ("retry.yaml", "path match"),
+            ("retry_config.rs", "path match"),
+        ])
+    );
+    let found = locate(
+        &services,
+        json!({"session": "locate", "needle": "retry config", "strategy": "content", "k": 1}),
+    )
+    .await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1, "content match");
+}
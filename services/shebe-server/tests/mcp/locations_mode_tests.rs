@@ -0,0 +1,203 @@
+//! Integration tests for search_code's locations output mode
+//!
+//! `output_mode: "locations"` replaces snippets with a table of file,
+//! line, score and a short excerpt, so large `k` stays cheap.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::search::EXCERPT_MAX_CHARS;
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::SearchCodeHandler;
+use std::sync::Arc;
+
+/// Characters a table row may use beyond its path and excerpt
+/// (handle, line, score and separators)
+const ROW_OVERHEAD_CHARS: usize = 40;
+
+/// Many files that each mention `session_timeout` once, on line 17
+fn fixture() -> TestRepo {
+    let padding = |range: std::ops::Range<usize>| {
+        range
+            .map(|j| format!("    let filler_{j} = compute_value({j}, \"ü→日本\");\n"))
+            .collect::<String>()
+    };
+    let files: Vec<(String, String)> = (0..60)
+        .map(|i| {
+            (
+                format!("src/handler_{i}.rs"),
+                format!(
+                    "pub fn handler_{i}(config: &Config) {{\n{}    \
+                     let timeout = config.session_timeout * {i}; // seconds\n{}}}\n",
+                    padding(0..15),
+                    padding(15..30)
+                ),
+            )
+        })
+        .collect();
+    let refs: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    TestRepo::with_files(&refs)
+}
+
+async fn search(services: &Arc<Services>, args: Value) -> Result<String, McpError> {
+    let handler = SearchCodeHandler::new(Arc::clone(services));
+    let result = handler.execute(args).await?;
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    Ok(text.clone())
+}
+
+/// Table rows of a locations response
+fn rows(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| line.starts_with("| r"))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_locations_rows_within_budget() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "locations").await;
+
+    let output = search(
+        &services,
+        json!({
+            "session": "locations",
+            "query": "session_timeout",
+            "k": 100,
+            "output_mode": "locations",
+            "relative_paths": true
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("locations only"));
+    assert!(output.contains("| Handle | File | Line | Score | Excerpt |"));
+    assert!(!output.contains("```"), "No snippets in locations mode");
+
+    let rows = rows(&output);
+    assert_eq!(rows.len(), 60);
+    for row in &rows {
+        let cells: Vec<&str> = row.split(" | ").collect();
+        let path = cells[1];
+        let excerpt = cells[4].trim_end_matches(" |");
+        assert!(
+            excerpt.chars().count() <= EXCERPT_MAX_CHARS,
+            "Excerpt too long: {excerpt}"
+        );
+        assert!(
+            row.chars().count() <= path.chars().count() + EXCERPT_MAX_CHARS + ROW_OVERHEAD_CHARS,
+            "Row over budget: {row}"
+        );
+        // Centred on the matched term, on the line where it is
+        assert!(excerpt.contains("session_timeout"), "{row}");
+        assert_eq!(cells[2], "17", "Line of the match: {row}");
+    }
+}
+
+#[tokio::test]
+async fn test_locations_find_identifier_variant() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "variants").await;
+
+    // Only the snake case spelling is in the files
+    let output = search(
+        &services,
+        json!({
+            "session": "variants",
+            "query": "sessionTimeout",
+            "k": 5,
+            "output_mode": "locations"
+        }),
+    )
+    .await
+    .unwrap();
+
+    let rows = rows(&output);
+    assert_eq!(rows.len(), 5, "{output}");
+    for row in &rows {
+        let cells: Vec<&str> = row.split(" | ").collect();
+        assert!(cells[4].contains("session_timeout"), "{row}");
+        assert_eq!(cells[2], "17", "Line of the variant match: {row}");
+    }
+}
+
+#[tokio::test]
+async fn test_locations_smaller_than_full_output() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "compare").await;
+
+    let locations = search(
+        &services,
+        json!({
+            "session": "compare",
+            "query": "session_timeout",
+            "k": 100,
+            "output_mode": "locations"
+        }),
+    )
+    .await
+    .unwrap();
+    let full = search(
+        &services,
+        json!({"session": "compare", "query": "session_timeout", "k": 10}),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(rows(&locations).len(), 60);
+    assert!(
+        locations.len() < full.len(),
+        "k=100 locations ({} bytes) should be smaller than k=10 full ({} bytes)",
+        locations.len(),
+        full.len()
+    );
+}
+
+#[tokio::test]
+async fn test_locations_grouped_and_missing_file() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "grouped").await;
+    std::fs::remove_file(repo.path().join("src/handler_7.rs")).unwrap();
+
+    let output = search(
+        &services,
+        json!({
+            "session": "grouped",
+            "query": "handler_7",
+            "output_mode": "locations",
+            "group_by_file": true
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("files for query"));
+    let rows = rows(&output);
+    assert_eq!(rows.len(), 1);
+    // The file is gone, so the line falls back to the chunk index
+    assert!(rows[0].contains("| chunk 0 |"), "{}", rows[0]);
+    assert!(rows[0].contains("pub fn handler_7"));
+}
+
+#[tokio::test]
+async fn test_locations_invalid_output_mode() {
+    let services = Arc::new(create_test_services());
+
+    let result = search(
+        &services,
+        json!({"session": "any", "query": "x", "output_mode": "compact"}),
+    )
+    .await;
+
+    assert!(matches!(result, Err(McpError::InvalidParams(msg)) if msg.contains("output_mode")));
+}
@@ -0,0 +1,122 @@
+//! Integration tests for session namespaces
+//!
+//! With `[namespaces.*]` configured, `team-a/backend` and
+//! `team-b/backend` are different sessions under different storage
+//! roots. A session is never visible through another namespace; bare
+//! IDs resolve to the default namespace.
+
+use crate::common::{call_error, call_text, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::core::config::{Config, NamespaceConfig};
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Services with namespaces team-a and team-b beside the default one
+fn namespaced_services(temp: &TempDir) -> Services {
+    let mut config = Config::default();
+    config.storage.index_dir = temp.path().join("default");
+    for name in ["team-a", "team-b"] {
+        config.namespaces.insert(
+            name.to_string(),
+            NamespaceConfig {
+                storage_root: temp.path().join(name),
+            },
+        );
+    }
+    config.validate().unwrap();
+    Services::new(config)
+}
+
+#[tokio::test]
+async fn test_sessions_are_invisible_across_namespaces() {
+    let temp = TempDir::new().unwrap();
+    let services = namespaced_services(&temp);
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_team_a() {}\n")]);
+    index_test_repository(&services, repo.path(), "team-a/backend").await;
+    let handlers = ProtocolHandlers::new(Arc::new(services));
+
+    // Stored under team-a's root only
+    assert!(temp.path().join("team-a/sessions/backend").is_dir());
+    assert!(!temp.path().join("team-b/sessions/backend").exists());
+    assert!(!temp.path().join("default/sessions/backend").exists());
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "team-a/backend", "query": "authenticate_team_a"}),
+    )
+    .await;
+    assert!(output.contains("src/auth.rs"), "{output}");
+
+    for session in ["team-b/backend", "backend", "default/backend"] {
+        let error = call_error(
+            &handlers,
+            "search_code",
+            json!({"session": session, "query": "authenticate_team_a"}),
+        )
+        .await;
+        assert!(error.contains("not found"), "{session}: {error}");
+        let error = call_error(&handlers, "get_session_info", json!({"session": session})).await;
+        assert!(error.contains("not found"), "{session}: {error}");
+    }
+
+    let info = call_text(
+        &handlers,
+        "get_session_info",
+        json!({"session": "team-a/backend"}),
+    )
+    .await;
+    assert!(info.contains("team-a/backend"), "{info}");
+}
+
+#[tokio::test]
+async fn test_list_sessions_by_namespace() {
+    let temp = TempDir::new().unwrap();
+    let services = namespaced_services(&temp);
+    let repo = TestRepo::with_files(&[("lib.rs", "fn shared() {}\n")]);
+    index_test_repository(&services, repo.path(), "team-a/backend").await;
+    index_test_repository(&services, repo.path(), "team-b/frontend").await;
+    index_test_repository(&services, repo.path(), "tools").await;
+    let handlers = ProtocolHandlers::new(Arc::new(services));
+
+    // Default namespace only
+    let output = call_text(&handlers, "list_sessions", json!({})).await;
+    assert!(output.contains("Namespace: default"), "{output}");
+    assert!(output.contains("## tools"));
+    assert!(!output.contains("backend") && !output.contains("frontend"));
+
+    let output = call_text(&handlers, "list_sessions", json!({"namespace": "team-a"})).await;
+    assert!(output.contains("## team-a/backend"), "{output}");
+    assert!(!output.contains("frontend") && !output.contains("## tools"));
+
+    let output = call_text(&handlers, "list_sessions", json!({"all_namespaces": true})).await;
+    assert!(
+        output.contains("Namespaces: default, team-a, team-b"),
+        "{output}"
+    );
+    for id in ["team-a/backend", "team-b/frontend", "tools"] {
+        assert!(output.contains(&format!("## {id}\n")), "{id}: {output}");
+    }
+
+    let error = call_error(&handlers, "list_sessions", json!({"namespace": "team-c"})).await;
+    assert!(error.contains("Unknown namespace 'team-c'"), "{error}");
+}
+
+#[tokio::test]
+async fn test_unknown_namespace_is_refused_when_indexing() {
+    let temp = TempDir::new().unwrap();
+    let handlers = ProtocolHandlers::new(Arc::new(namespaced_services(&temp)));
+    let repo = TestRepo::with_files(&[("lib.rs", "fn f() {}\n")]);
+
+    let error = call_error(
+        &handlers,
+        "index_repository",
+        json!({"path": repo.path(), "session": "team-c/backend"}),
+    )
+    .await;
+    assert!(error.contains("Unknown namespace 'team-c'"), "{error}");
+    assert!(error.contains("default, team-a, team-b"), "{error}");
+    assert!(!temp.path().join("team-c").exists());
+}
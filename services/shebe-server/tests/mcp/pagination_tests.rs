@@ -1,8 +1,8 @@
 //! Integration tests for pagination (Phase 4)
 //!
 //! These tests exercise the full MCP handler stack (not just
-//! individual tool handlers) for both list_dir cursor-based
-//! pagination and read_file offset-based pagination.
+//! individual tool handlers) for list_dir and search_code
+//! cursor-based pagination and read_file offset-based pagination.
 
 #[cfg(test)]
 mod tests {
@@ -121,6 +121,36 @@ mod tests {
         handlers.handle_tools_call(request).await.unwrap()
     }
 
+    /// Call search_code through the full handler stack.
+    async fn call_search_code(
+        handlers: &ProtocolHandlers,
+        arguments: serde_json::Value,
+        cursor: Option<&str>,
+    ) -> JsonRpcResponse {
+        let mut arguments = arguments;
+        if let Some(c) = cursor {
+            arguments["cursor"] = json!(c);
+        }
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(99)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "search_code",
+                "arguments": arguments
+            })),
+        };
+
+        handlers.handle_tools_call(request).await.unwrap()
+    }
+
+    /// Extract the error message of a failed tool call response.
+    fn extract_error(response: &JsonRpcResponse) -> &str {
+        assert!(response.result.is_none(), "Expected an error");
+        &response.error.as_ref().unwrap().message
+    }
+
     /// Extract the text content from a successful tool call
     /// response.
     fn extract_text(response: &JsonRpcResponse) -> &str {
@@ -145,7 +175,19 @@ mod tests {
             .collect()
     }
 
-    /// Extract cursor value from list_dir output text.
+    /// Extract result file paths from search_code output.
+    fn extract_result_paths(text: &str) -> Vec<String> {
+        text.lines()
+            .filter(|line| line.starts_with("**File:** `"))
+            .filter_map(|line| {
+                let start = line.find('`')? + 1;
+                let end = start + line[start..].find('`')?;
+                Some(line[start..end].to_string())
+            })
+            .collect()
+    }
+
+    /// Extract cursor value from list_dir or search_code output text.
     fn extract_cursor(text: &str) -> Option<String> {
         let prefix = "cursor=\"";
         let start = text.find(prefix)? + prefix.len();
@@ -504,4 +546,169 @@ mod tests {
             "No chunk metadata in non-offset read"
         );
     }
+
+    // ---------------------------------------------------------
+    // Test 6: search_code full pagination loop
+    // ---------------------------------------------------------
+
+    /// Index 45 files that all match. Paginate with k=10.
+    /// 5 pages returned, numbered by overall rank, with no
+    /// result repeated across pages.
+    #[tokio::test]
+    async fn test_search_code_full_pagination_loop() {
+        let session = "integ-search-loop";
+        let (handlers, _storage, _repo) = setup_indexed_session(45, session).await;
+
+        let mut all_paths = Vec::new();
+        let mut cursor_str: Option<String> = None;
+        let mut page_count = 0;
+
+        loop {
+            let response = call_search_code(
+                &handlers,
+                json!({"session": session, "query": "usize", "k": 10}),
+                cursor_str.as_deref(),
+            )
+            .await;
+
+            let text = extract_text(&response);
+            let first_rank = page_count * 10 + 1;
+            assert!(
+                text.contains(&format!("## Result {first_rank} (")),
+                "page {page_count} should start at rank {first_rank}: {text}"
+            );
+            all_paths.extend(extract_result_paths(text));
+            page_count += 1;
+
+            cursor_str = extract_cursor(text);
+            if cursor_str.is_none() {
+                assert!(!text.contains("More results available"));
+                break;
+            }
+            assert!(text.contains("NOTE: More results available."));
+        }
+
+        assert_eq!(page_count, 5, "45 results / 10 per page = 5 pages");
+        let unique: std::collections::HashSet<_> = all_paths.iter().collect();
+        assert_eq!(all_paths.len(), 45, "All results must be returned");
+        assert_eq!(unique.len(), 45, "No result may appear on two pages");
+    }
+
+    // ---------------------------------------------------------
+    // Test 7: search_code cursor rejection
+    // ---------------------------------------------------------
+
+    /// A cursor is refused for another query or other search
+    /// options, and after the session is reindexed.
+    #[tokio::test]
+    async fn test_search_code_cursor_rejected_for_changed_query_or_session() {
+        let session = "integ-search-reject";
+        let (handlers, _storage, repo) = setup_indexed_session(15, session).await;
+
+        let first = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 5}),
+            None,
+        )
+        .await;
+        let cursor = extract_cursor(extract_text(&first)).expect("cursor on first page");
+
+        // A different page size is fine
+        let next = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 7}),
+            Some(&cursor),
+        )
+        .await;
+        assert!(extract_text(&next).contains("## Result 6 ("));
+
+        for changed in [
+            json!({"session": session, "query": "File", "k": 5}),
+            json!({"session": session, "query": "usize", "k": 5, "fuzziness": 1}),
+            json!({"session": session, "query": "usize", "k": 5, "group_by_file": true}),
+        ] {
+            let response = call_search_code(&handlers, changed.clone(), Some(&cursor)).await;
+            let error = extract_error(&response);
+            assert!(
+                error.contains("Cursor does not match this query"),
+                "{changed}: {error}"
+            );
+        }
+
+        // Reindex with one more file
+        std::fs::write(repo.path().join("extra.rs"), "fn extra() -> usize { 0 }\n").unwrap();
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(2)),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "index_repository",
+                "arguments": {
+                    "path": repo.path().to_str().unwrap(),
+                    "session": session,
+                    "force": true
+                }
+            })),
+        };
+        let reindexed = handlers.handle_tools_call(request).await.unwrap();
+        assert!(reindexed.error.is_none(), "{:?}", reindexed.error);
+
+        let response = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 5}),
+            Some(&cursor),
+        )
+        .await;
+        assert!(extract_error(&response).contains("Cursor is stale"));
+
+        let response = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 5}),
+            Some("not-a-cursor"),
+        )
+        .await;
+        assert!(extract_error(&response).contains("Invalid cursor"));
+    }
+
+    // ---------------------------------------------------------
+    // Test 8: search_code pagination depth limit
+    // ---------------------------------------------------------
+
+    /// Pages may not reach past search.max_k (100) results.
+    #[tokio::test]
+    async fn test_search_code_cursor_depth_limit() {
+        let session = "integ-search-depth";
+        let (handlers, _storage, _repo) = setup_indexed_session(70, session).await;
+
+        let first = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 60}),
+            None,
+        )
+        .await;
+        let cursor = extract_cursor(extract_text(&first)).expect("cursor on first page");
+
+        let response = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 60}),
+            Some(&cursor),
+        )
+        .await;
+        let error = extract_error(&response);
+        assert!(
+            error.contains("Results 61-120 are beyond the search depth limit (search.max_k = 100)"),
+            "{error}"
+        );
+
+        // The rest fits within the limit
+        let response = call_search_code(
+            &handlers,
+            json!({"session": session, "query": "usize", "k": 40}),
+            Some(&cursor),
+        )
+        .await;
+        let text = extract_text(&response);
+        assert_eq!(extract_result_paths(text).len(), 10);
+        assert!(extract_cursor(text).is_none());
+    }
 }
@@ -0,0 +1,61 @@
+//! Integration tests for profiled indexing
+//!
+//! index_repository with `profile: true` appends a performance
+//! breakdown: every stage with its time and share, then the slowest
+//! files.
+
+use crate::common::{create_test_services, TestRepo};
+use serde_json::json;
+use shebe::mcp::protocol::ContentBlock;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::IndexRepositoryHandler;
+use std::sync::Arc;
+
+async fn index(profile: bool) -> String {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::medium();
+    let result = IndexRepositoryHandler::new(services)
+        .execute(json!({
+            "session": "profiled",
+            "path": repo.path().to_str().unwrap(),
+            "profile": profile
+        }))
+        .await
+        .unwrap();
+    let ContentBlock::Text { text } = &result.content[0];
+    text.clone()
+}
+
+#[tokio::test]
+async fn test_profiled_index_reports_breakdown() {
+    let output = index(true).await;
+
+    let breakdown = output
+        .split_once("Performance breakdown (")
+        .unwrap_or_else(|| panic!("no breakdown: {output}"))
+        .1;
+    for stage in ["walk", "read", "decode", "chunk", "add_document", "commit"] {
+        assert!(
+            breakdown.contains(&format!("| {stage} | ")),
+            "{stage}: {breakdown}"
+        );
+    }
+
+    let slowest: Vec<&str> = breakdown
+        .split_once("Slowest files:\n")
+        .unwrap()
+        .1
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .collect();
+    assert_eq!(slowest.len(), 10, "{breakdown}");
+    assert!(slowest[0].starts_with("1. ") && slowest[0].contains("module_"));
+    assert!(slowest[9].starts_with("10. "));
+}
+
+#[tokio::test]
+async fn test_unprofiled_index_has_no_breakdown() {
+    let output = index(false).await;
+    assert!(output.contains("Indexing complete!"), "{output}");
+    assert!(!output.contains("Performance breakdown"), "{output}");
+}
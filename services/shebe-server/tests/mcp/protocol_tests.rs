@@ -49,10 +49,12 @@ mod tests {
                 tools: ToolsCapability {
                     list_changed: false,
                 },
+                experimental: None,
             },
             server_info: ServerInfo {
                 name: "shebe-mcp".to_string(),
                 version: "0.1.0".to_string(),
+                profile: None,
             },
         };
 
@@ -60,6 +62,8 @@ mod tests {
         assert_eq!(json["protocolVersion"], "2024-11-05");
         assert_eq!(json["serverInfo"]["name"], "shebe-mcp");
         assert_eq!(json["capabilities"]["tools"]["listChanged"], false);
+        assert!(json["capabilities"].get("experimental").is_none());
+        assert!(json["serverInfo"].get("profile").is_none());
     }
 
     #[test]
@@ -0,0 +1,115 @@
+//! Integration tests for the MCP server in read-only mode
+//!
+//! With `server.read_only` set, tools that change sessions are hidden
+//! from tools/list and fail with READ_ONLY when called anyway.
+
+use crate::common::{call, create_test_services, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use shebe::mcp::protocol::{JsonRpcRequest, READ_ONLY};
+use std::sync::Arc;
+
+const MUTATING: &[&str] = &[
+    "index_repository",
+    "index_repository_async",
+    "reindex_session",
+    "delete_session",
+    "upgrade_session",
+    "compact_session",
+    "optimize_session",
+    "set_reindex_schedule",
+    "set_session_defaults",
+    "run_self_test",
+];
+
+/// Read-only handlers over a storage directory holding session "shared"
+async fn read_only_handlers() -> (ProtocolHandlers, TestRepo) {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "shared").await;
+
+    let mut config = Config::default();
+    config.storage.index_dir = services.storage.storage_root().to_path_buf();
+    config.server.read_only = true;
+    let handlers = ProtocolHandlers::new(Arc::new(Services::new(config)));
+    (handlers, repo)
+}
+
+#[tokio::test]
+async fn test_read_only_hides_mutating_tools() {
+    let (handlers, _repo) = read_only_handlers().await;
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/list".to_string(),
+        params: None,
+    };
+    let response = handlers.handle_tools_list(request).await.unwrap();
+    let names: Vec<String> = response.result.unwrap()["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect();
+
+    for tool in MUTATING {
+        assert!(!names.iter().any(|n| n == tool), "{tool} should be hidden");
+    }
+    assert!(names.iter().any(|n| n == "search_code"));
+    assert!(names.iter().any(|n| n == "get_server_info"));
+}
+
+#[tokio::test]
+async fn test_read_only_rejects_mutating_calls() {
+    let (handlers, repo) = read_only_handlers().await;
+    let path = repo.path().to_string_lossy().to_string();
+    let calls = [
+        (
+            "index_repository",
+            json!({"path": path, "session": "fresh"}),
+        ),
+        ("reindex_session", json!({"session": "shared"})),
+        (
+            "delete_session",
+            json!({"session": "shared", "confirm": true}),
+        ),
+        ("upgrade_session", json!({"session": "shared"})),
+    ];
+
+    for (name, arguments) in calls {
+        let response = call(&handlers, name, arguments).await;
+        let error = response
+            .error
+            .unwrap_or_else(|| panic!("{name} should fail"));
+        assert_eq!(error.code, READ_ONLY, "{name}: {}", error.message);
+        assert!(
+            error.message.contains("read-only mode"),
+            "{name}: {}",
+            error.message
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_read_only_allows_search() {
+    let (handlers, _repo) = read_only_handlers().await;
+
+    let response = call(
+        &handlers,
+        "search_code",
+        json!({"session": "shared", "query": "authenticate_user"}),
+    )
+    .await;
+    assert!(response.error.is_none(), "{:?}", response.error);
+    let text = response.result.unwrap()["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(text.contains("auth.rs"), "{text}");
+
+    let info = call(&handlers, "get_server_info", json!({})).await;
+    let text = info.result.unwrap()["content"][0]["text"].to_string();
+    assert!(text.contains("read-only"), "{text}");
+}
@@ -0,0 +1,136 @@
+//! Integration tests for reindex_session dry runs
+//!
+//! `dry_run: true` walks the repository with the effective config and
+//! compares the matched files with the session's, without locking,
+//! deleting or replacing anything.
+
+use crate::common::{call, call_text, create_test_services, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_dry_run_reports_file_changes() {
+    let repo = TestRepo::small();
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "dry").await;
+    let before = services.storage.get_session_metadata("dry").unwrap();
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+    let hits = || {
+        let response = services
+            .search
+            .search_session("dry", "authenticate helper", Some(10))
+            .unwrap();
+        response
+            .results
+            .into_iter()
+            .map(|r| (r.file_path, r.chunk_index, r.score, r.text))
+            .collect::<Vec<_>>()
+    };
+    let results_before = hits();
+    assert!(!results_before.is_empty());
+
+    // Two files added, one removed since indexing
+    std::fs::write(repo.path().join("src/cache.rs"), "pub fn evict() {}").unwrap();
+    std::fs::write(repo.path().join("src/queue.rs"), "pub fn push() {}").unwrap();
+    std::fs::remove_file(repo.path().join("src/db.rs")).unwrap();
+
+    let output = call_text(
+        &handlers,
+        "reindex_session",
+        json!({"session": "dry", "dry_run": true}),
+    )
+    .await;
+
+    assert!(output.contains("**No changes made.**"), "{output}");
+    assert!(output.contains("- Added: 2\n"), "{output}");
+    assert!(output.contains("- Removed: 1\n"), "{output}");
+    assert!(output.contains("- Unchanged: 9\n"), "{output}");
+    assert!(output.contains("- Path moved: no\n"), "{output}");
+    assert!(
+        output.contains("**Added files:**\n- src/cache.rs\n- src/queue.rs\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("**Removed files:**\n- src/db.rs\n"),
+        "{output}"
+    );
+    assert!(output.contains("**Configuration:** unchanged"), "{output}");
+    assert!(
+        output.contains("re-indexing requires force=true"),
+        "{output}"
+    );
+
+    // The session is untouched and answers searches as before
+    assert_eq!(hits(), results_before);
+    let after = services.storage.get_session_metadata("dry").unwrap();
+    assert_eq!(after.files_indexed, before.files_indexed);
+    assert_eq!(after.last_indexed_at, before.last_indexed_at);
+}
+
+#[tokio::test]
+async fn test_dry_run_shows_config_delta() {
+    let repo = TestRepo::medium();
+    let services = Arc::new(create_test_services());
+    index_test_repository(&services, repo.path(), "delta").await;
+    let before = services.storage.get_session_metadata("delta").unwrap();
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+
+    let output = call_text(
+        &handlers,
+        "reindex_session",
+        json!({"session": "delta", "dry_run": true, "chunk_size": 1024, "bm25_b": 0.3}),
+    )
+    .await;
+
+    assert!(output.contains("**Configuration Changes:**"), "{output}");
+    assert!(output.contains("- Chunk size: 512 -> 1024\n"), "{output}");
+    assert!(
+        output.contains("- BM25: k1=1.2 b=0.75 -> k1=1.2 b=0.3"),
+        "{output}"
+    );
+    assert!(
+        output.contains("- Added: 0\n- Removed: 0\n- Unchanged: 50\n"),
+        "{output}"
+    );
+    assert!(!output.contains("requires force=true"), "{output}");
+
+    // Nothing was written
+    let after = services.storage.get_session_metadata("delta").unwrap();
+    assert_eq!(after.config.chunk_size, before.config.chunk_size);
+    assert_eq!(after.config.bm25_b, before.config.bm25_b);
+    assert_eq!(after.last_indexed_at, before.last_indexed_at);
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_missing_repository() {
+    let repo = TestRepo::small();
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "gone").await;
+    let handlers = ProtocolHandlers::new(Arc::new(services));
+    let path = repo.path().to_path_buf();
+    drop(repo);
+    assert!(!path.exists());
+
+    let output = call_text(
+        &handlers,
+        "reindex_session",
+        json!({"session": "gone", "dry_run": true}),
+    )
+    .await;
+
+    assert!(
+        output.contains("- Path moved: yes, it no longer exists"),
+        "{output}"
+    );
+    assert!(output.contains("- Added: 0\n- Removed: 10\n"), "{output}");
+
+    // Without dry_run the missing path is still an error
+    let response = call(
+        &handlers,
+        "reindex_session",
+        json!({"session": "gone", "force": true}),
+    )
+    .await;
+    assert!(response.error.is_some());
+}
@@ -0,0 +1,213 @@
+//! Integration tests for paths relative to the repository root
+//!
+//! read_file, preview_chunk and find_file accept `src/lib.rs` as well as
+//! absolute paths; relative paths resolve against the session's
+//! repository_path and may not escape it.
+
+use crate::common::{create_test_services, execute_text, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::{FindFileHandler, PreviewChunkHandler, ReadFileHandler};
+use std::sync::Arc;
+
+const FILES: &[(&str, &str)] = &[
+    (
+        "src/lib.rs",
+        "pub fn authenticate(user: &str) -> bool {\n    !user.is_empty()\n}\n",
+    ),
+    ("src/main.rs", "fn main() {\n    println!(\"hello\");\n}\n"),
+    ("docs/notes.md", "# Notes\n"),
+];
+
+async fn setup(session: &str) -> (Arc<Services>, TestRepo) {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(FILES);
+    index_test_repository(&services, repo.path(), session).await;
+    (services, repo)
+}
+
+fn error_message(err: McpError) -> String {
+    match err {
+        McpError::InvalidParams(msg) | McpError::InvalidRequest(msg) => msg,
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_read_file_relative_path() {
+    let (services, repo) = setup("relative").await;
+    let handler = ReadFileHandler::new(Arc::clone(&services));
+    let absolute = repo.path().join("src/lib.rs");
+
+    let output = execute_text(
+        &handler,
+        json!({"session": "relative", "file_path": "src/lib.rs"}),
+    )
+    .await
+    .unwrap();
+    assert!(output.contains("pub fn authenticate"), "{output}");
+    // Both the requested and the resolved form are shown
+    assert!(
+        output.contains(&format!(
+            "**File:** `src/lib.rs` (`{}`)",
+            absolute.display()
+        )),
+        "{output}"
+    );
+
+    // Absolute paths work unchanged
+    let output = execute_text(
+        &handler,
+        json!({"session": "relative", "file_path": absolute.to_str().unwrap()}),
+    )
+    .await
+    .unwrap();
+    assert!(
+        output.contains(&format!("**File:** `{}`", absolute.display())),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn test_preview_chunk_relative_path() {
+    let (services, repo) = setup("relative-preview").await;
+    let handler = PreviewChunkHandler::new(Arc::clone(&services));
+
+    let output = execute_text(
+        &handler,
+        json!({"session": "relative-preview", "file_path": "./src/main.rs", "chunk_index": 0}),
+    )
+    .await
+    .unwrap();
+    assert!(output.contains("println!"), "{output}");
+    assert!(
+        output.contains(&format!(
+            "**File:** `./src/main.rs` (`{}`)",
+            repo.path().join("src/main.rs").display()
+        )),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn test_relative_traversal_rejected() {
+    let (services, repo) = setup("traversal").await;
+    let read = ReadFileHandler::new(Arc::clone(&services));
+    let preview = PreviewChunkHandler::new(Arc::clone(&services));
+
+    let message = error_message(
+        execute_text(
+            &read,
+            json!({"session": "traversal", "file_path": "../../etc/passwd"}),
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert!(message.contains("outside the repository root"), "{message}");
+    assert!(
+        message.contains(&repo.path().display().to_string()),
+        "{message}"
+    );
+
+    let message = error_message(
+        execute_text(
+            &preview,
+            json!({"session": "traversal", "file_path": "src/../../x.rs", "chunk_index": 0}),
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert!(message.contains("outside the repository root"), "{message}");
+
+    let message = error_message(
+        execute_text(
+            &FindFileHandler::new(Arc::clone(&services)),
+            json!({"session": "traversal", "pattern": "../**/*.rs"}),
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert!(message.contains("cannot contain '..'"), "{message}");
+}
+
+#[tokio::test]
+async fn test_relative_path_not_indexed_shows_resolution() {
+    let (services, repo) = setup("not-indexed").await;
+    std::fs::write(repo.path().join("src/new.rs"), "fn new() {}\n").unwrap();
+
+    let message = error_message(
+        execute_text(
+            &ReadFileHandler::new(Arc::clone(&services)),
+            json!({"session": "not-indexed", "file_path": "src/new.rs"}),
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert!(
+        message.contains(&format!(
+            "'src/new.rs' (resolved to '{}' under repository root '{}')",
+            repo.path().join("src/new.rs").display(),
+            repo.path().display()
+        )),
+        "{message}"
+    );
+}
+
+#[tokio::test]
+async fn test_relative_path_with_stale_repository_path() {
+    let (services, repo) = setup("stale").await;
+    // The repository moved after indexing
+    let moved = tempfile::TempDir::new().unwrap();
+    let new_location = moved.path().join("repo");
+    std::fs::rename(repo.path(), &new_location).unwrap();
+
+    let message = error_message(
+        execute_text(
+            &ReadFileHandler::new(Arc::clone(&services)),
+            json!({"session": "stale", "file_path": "src/lib.rs"}),
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert!(
+        message.contains(&format!(
+            "repository root '{}' no longer exists",
+            repo.path().display()
+        )),
+        "{message}"
+    );
+    assert!(message.contains("re-index the session"), "{message}");
+}
+
+#[tokio::test]
+async fn test_find_file_relative_glob() {
+    let (services, repo) = setup("relative-find").await;
+    let handler = FindFileHandler::new(Arc::clone(&services));
+
+    let output = execute_text(
+        &handler,
+        json!({"session": "relative-find", "pattern": "src/*.rs"}),
+    )
+    .await
+    .unwrap();
+    assert!(
+        output.contains(&format!(
+            "**Pattern:** `src/*.rs` (relative to `{}`)",
+            repo.path().display()
+        )),
+        "{output}"
+    );
+    assert!(output.contains("**Matches:** 2 of 3"), "{output}");
+    assert!(!output.contains("notes.md"), "{output}");
+
+    // Absolute globs are matched as before
+    let pattern = format!("{}/docs/*", repo.path().display());
+    let output = execute_text(
+        &handler,
+        json!({"session": "relative-find", "pattern": pattern}),
+    )
+    .await
+    .unwrap();
+    assert!(output.contains("**Matches:** 1 of 3"), "{output}");
+}
@@ -0,0 +1,94 @@
+//! Integration tests for reloading the configuration of a running server
+//!
+//! The reload_config tool re-reads the config file: changed limits
+//! apply to the next tool call, startup-only settings are reported as
+//! requiring a restart.
+
+use crate::common::{call, index_test_repository, response_text, TestRepo};
+use serde_json::json;
+use shebe::core::reload::ConfigSource;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Config file with the given storage directory and list_dir maximum
+fn write_config(path: &Path, index_dir: &Path, list_dir_max: usize) {
+    let toml = format!(
+        "[storage]\nindex_dir = {:?}\n\n[limits]\nlist_dir_max = {list_dir_max}\n",
+        index_dir.to_string_lossy()
+    );
+    std::fs::write(path, toml).unwrap();
+}
+
+/// Handlers whose configuration reloads from a file in `temp`
+fn handlers_from_file(
+    temp: &TempDir,
+    list_dir_max: usize,
+) -> (ProtocolHandlers, Arc<Services>, PathBuf) {
+    let config_path = temp.path().join("shebe.toml");
+    write_config(&config_path, &temp.path().join("sessions"), list_dir_max);
+
+    let source = ConfigSource::File(config_path.clone());
+    let services = Arc::new(Services::new(source.load().unwrap()));
+    services.config.set_source(source);
+    let handlers = ProtocolHandlers::new(Arc::clone(&services));
+    (handlers, services, config_path)
+}
+
+#[tokio::test]
+async fn test_reload_applies_new_list_dir_limit() {
+    let temp = TempDir::new().unwrap();
+    let (handlers, services, config_path) = handlers_from_file(&temp, 500);
+    let repo = TestRepo::with_files(&[
+        ("a.rs", "fn a() {}\n"),
+        ("b.rs", "fn b() {}\n"),
+        ("c.rs", "fn c() {}\n"),
+        ("d.rs", "fn d() {}\n"),
+        ("e.rs", "fn e() {}\n"),
+    ]);
+    index_test_repository(&services, repo.path(), "reload").await;
+    let listed = |output: &str| output.matches(".rs`").count();
+
+    let before = response_text(&call(&handlers, "list_dir", json!({"session": "reload"})).await);
+    assert_eq!(listed(&before), 5);
+
+    write_config(&config_path, &temp.path().join("sessions"), 2);
+    let report = response_text(&call(&handlers, "reload_config", json!({})).await);
+    assert!(report.contains("## Applied (1)"), "{report}");
+    assert!(
+        report.contains("`limits.list_dir_max`: 500 -> 2"),
+        "{report}"
+    );
+
+    let after = response_text(&call(&handlers, "list_dir", json!({"session": "reload"})).await);
+    assert_eq!(listed(&after), 2, "{after}");
+    assert!(after.contains("MAXIMUM 2 FILES DISPLAYED"));
+}
+
+#[tokio::test]
+async fn test_reload_reports_settings_requiring_restart() {
+    let temp = TempDir::new().unwrap();
+    let (handlers, services, config_path) = handlers_from_file(&temp, 500);
+    let running_dir = services.config.current().storage.index_dir.clone();
+
+    write_config(&config_path, &temp.path().join("elsewhere"), 500);
+    let report = response_text(&call(&handlers, "reload_config", json!({})).await);
+
+    assert!(report.contains("## Requires restart (1)"), "{report}");
+    assert!(report.contains("`storage.index_dir`"), "{report}");
+    assert_eq!(services.config.current().storage.index_dir, running_dir);
+}
+
+#[tokio::test]
+async fn test_reload_keeps_running_config_when_invalid() {
+    let temp = TempDir::new().unwrap();
+    let (handlers, services, config_path) = handlers_from_file(&temp, 500);
+
+    write_config(&config_path, &temp.path().join("sessions"), 0);
+    let response = call(&handlers, "reload_config", json!({})).await;
+
+    assert!(response.error.is_some());
+    assert_eq!(services.config.current().limits.list_dir_max, 500);
+}
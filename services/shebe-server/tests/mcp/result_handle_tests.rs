@@ -0,0 +1,160 @@
+//! Integration tests for search result handles
+//!
+//! search_code labels each hit with a handle (`r1`, `r2`, ...) that
+//! preview_chunk and read_file accept instead of a path and chunk index.
+
+use crate::common::{create_test_services, execute_text, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::core::results::ResultCache;
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::{PreviewChunkHandler, ReadFileHandler, SearchCodeHandler};
+use std::sync::Arc;
+use std::time::Duration;
+
+const FILES: &[(&str, &str)] = &[
+    (
+        "src/auth.rs",
+        "pub fn authenticate(user: &str) -> bool {\n    !user.is_empty()\n}\n",
+    ),
+    ("src/main.rs", "fn main() {\n    println!(\"hello\");\n}\n"),
+];
+
+async fn index(services: &Arc<Services>, repo: &TestRepo, session: &str) {
+    index_test_repository(services, repo.path(), session).await;
+}
+
+async fn search(services: &Arc<Services>, session: &str, query: &str) -> String {
+    execute_text(
+        &SearchCodeHandler::new(Arc::clone(services)),
+        json!({"session": session, "query": query}),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_preview_and_read_by_result_handle() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(FILES);
+    index(&services, &repo, "handles").await;
+
+    let output = search(&services, "handles", "authenticate").await;
+    assert!(output.contains("## Result 1 (score: "));
+    assert!(output.contains(", handle r1)"));
+
+    let hit = services
+        .search
+        .search_session("handles", "authenticate", Some(1))
+        .unwrap()
+        .results
+        .remove(0);
+    let preview = PreviewChunkHandler::new(Arc::clone(&services));
+    let by_handle = execute_text(&preview, json!({"session": "handles", "result": "r1"}))
+        .await
+        .unwrap();
+    let by_path = execute_text(
+        &preview,
+        json!({
+            "session": "handles",
+            "file_path": hit.file_path,
+            "chunk_index": hit.chunk_index
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(by_handle, by_path);
+    assert!(by_handle.contains("authenticate"));
+
+    let read = execute_text(
+        &ReadFileHandler::new(Arc::clone(&services)),
+        json!({"session": "handles", "result": "r1"}),
+    )
+    .await
+    .unwrap();
+    assert!(read.contains("auth.rs"));
+    assert!(read.contains("!user.is_empty()"));
+
+    // Handles from earlier searches stay valid
+    let output = search(&services, "handles", "println").await;
+    assert!(output.contains(", handle r2)"));
+    assert!(
+        execute_text(&preview, json!({"session": "handles", "result": "r1"}))
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_expired_result_handle() {
+    let mut services = create_test_services();
+    services.results = Arc::new(ResultCache::new(Duration::ZERO, 20));
+    let services = Arc::new(services);
+    let repo = TestRepo::with_files(FILES);
+    index(&services, &repo, "expiring").await;
+
+    search(&services, "expiring", "authenticate").await;
+
+    let err = execute_text(
+        &PreviewChunkHandler::new(Arc::clone(&services)),
+        json!({"session": "expiring", "result": "r1"}),
+    )
+    .await
+    .unwrap_err();
+    let McpError::InvalidParams(message) = err else {
+        panic!("expected invalid params, got {err:?}");
+    };
+    assert!(message.contains("expired; rerun the search"), "{message}");
+}
+
+#[tokio::test]
+async fn test_result_handle_from_other_session_rejected() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(FILES);
+    index(&services, &repo, "first").await;
+    index(&services, &repo, "second").await;
+
+    search(&services, "first", "authenticate").await;
+
+    for handler in [
+        Box::new(PreviewChunkHandler::new(Arc::clone(&services))) as Box<dyn McpToolHandler>,
+        Box::new(ReadFileHandler::new(Arc::clone(&services))),
+    ] {
+        let err = execute_text(
+            handler.as_ref(),
+            json!({"session": "second", "result": "r1"}),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("r1 is a result from session 'first', not 'second'"),
+            "{err}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_result_handle_argument_validation() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::with_files(FILES);
+    index(&services, &repo, "args").await;
+    let preview = PreviewChunkHandler::new(Arc::clone(&services));
+
+    let both = execute_text(
+        &preview,
+        json!({"session": "args", "result": "r1", "file_path": "/x", "chunk_index": 0}),
+    )
+    .await;
+    assert!(matches!(both, Err(McpError::InvalidParams(_))));
+
+    let neither = execute_text(&preview, json!({"session": "args", "chunk_index": 0})).await;
+    assert!(matches!(neither, Err(McpError::InvalidParams(_))));
+
+    let malformed = execute_text(&preview, json!({"session": "args", "result": "chunk-1"})).await;
+    assert!(malformed
+        .unwrap_err()
+        .to_string()
+        .contains("is not a result handle"));
+}
@@ -0,0 +1,120 @@
+//! Integration tests for the sample_files tool
+//!
+//! Heads of the files matching several patterns come back in pattern
+//! order, capped by max_files; files that turned binary or vanished
+//! after indexing are listed as skipped.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::SampleFilesHandler;
+use std::sync::Arc;
+
+fn fixture() -> TestRepo {
+    let main: String = (1..=40).map(|i| format!("// line {i}\n")).collect();
+    TestRepo::with_files(&[
+        ("README.md", "# Demo\n\nA small demo project.\n"),
+        ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+        ("src/main.rs", &main),
+        ("src/lib.rs", "pub fn helper() {}\n"),
+        ("src/util.rs", "pub fn util() {}\n"),
+        ("assets/data.txt", "plain text for now\n"),
+    ])
+}
+
+async fn sample(services: &Arc<Services>, args: Value) -> Result<String, McpError> {
+    let handler = SampleFilesHandler::new(Arc::clone(services));
+    let result = handler.execute(args).await?;
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    Ok(text.clone())
+}
+
+#[tokio::test]
+async fn test_sample_files_multiple_patterns() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "sample").await;
+
+    let output = sample(
+        &services,
+        json!({
+            "session": "sample",
+            "patterns": ["README*", "Cargo.toml", "src/main.rs"],
+            "lines_per_file": 5
+        }),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("**Files:** 3 of 3 matching"), "{output}");
+    let readme = output.find("README.md` (whole file, 3 lines)").unwrap();
+    let cargo = output.find("Cargo.toml` (whole file, 2 lines)").unwrap();
+    let main = output.find("main.rs` (first 5 lines)").unwrap();
+    assert!(readme < cargo && cargo < main, "{output}");
+    assert!(output.contains("```markdown\n# Demo"));
+    assert!(output.contains("```toml\n[package]"));
+    assert!(output.contains("// line 5\n```"));
+    assert!(!output.contains("// line 6"));
+    assert!(!output.contains("lib.rs"));
+}
+
+#[tokio::test]
+async fn test_sample_files_max_files_cap() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "sample").await;
+
+    let output = sample(
+        &services,
+        json!({"session": "sample", "patterns": ["src/*.rs"], "max_files": 2}),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("**Files:** 2 of 3 matching (max_files: 2)"));
+    assert!(output.contains("lib.rs"));
+    assert!(output.contains("main.rs"));
+    assert!(!output.contains("util.rs"));
+
+    let err = sample(
+        &services,
+        json!({"session": "sample", "patterns": ["*"], "max_files": 16}),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, McpError::InvalidParams(_)));
+}
+
+#[tokio::test]
+async fn test_sample_files_skips_binary_and_missing() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "sample").await;
+
+    // Changed after indexing
+    std::fs::write(
+        repo.path().join("assets/data.txt"),
+        b"\x89PNG\r\n\x1a\n\0\0",
+    )
+    .unwrap();
+    std::fs::remove_file(repo.path().join("src/util.rs")).unwrap();
+
+    let output = sample(
+        &services,
+        json!({"session": "sample", "patterns": ["assets/*", "src/util.rs", "README.md"]}),
+    )
+    .await
+    .unwrap();
+
+    assert!(output.contains("**Files:** 3 of 3 matching"));
+    assert!(output.contains("### Skipped"));
+    assert!(
+        output.contains("data.txt`: binary file, not shown"),
+        "{output}"
+    );
+    assert!(output.contains("util.rs`: no longer exists"), "{output}");
+    assert!(output.contains("README.md` (whole file"));
+    assert!(!output.contains("PNG"));
+}
@@ -0,0 +1,114 @@
+//! Integration tests for the search_paths tool
+//!
+//! Query words match path words with typos tolerated; files matching
+//! more words rank first, and file name matches beat directory matches.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::services::Services;
+use shebe::mcp::error::McpError;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::SearchPathsHandler;
+use std::sync::Arc;
+
+fn fixture() -> TestRepo {
+    TestRepo::with_files(&[
+        ("src/user/service_handler.rs", "pub fn handle() {}\n"),
+        ("src/user/model.rs", "pub struct User;\n"),
+        ("src/billing/service.rs", "pub fn charge() {}\n"),
+        ("src/handlers/mod.rs", "pub mod user;\n"),
+        ("docs/user-guide.md", "# Guide\n"),
+    ])
+}
+
+/// Output text of a search_paths call
+async fn search(services: &Arc<Services>, args: Value) -> String {
+    let handler = SearchPathsHandler::new(Arc::clone(services));
+    let result = handler.execute(args).await.unwrap();
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    text.clone()
+}
+
+/// Ranked rows ("1. ...") of the output
+fn rows(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_search_paths_tolerates_typos() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "paths").await;
+
+    let text = search(
+        &services,
+        json!({"session": "paths", "query": "user sevice handler"}),
+    )
+    .await;
+    let rows = rows(&text);
+    assert_eq!(
+        rows[0],
+        "1. src/**user**/**service**_**handler**.rs \
+         (3/3 words, 2 in file name; sevice ~ service)",
+        "{text}"
+    );
+    // Partial matches follow the full match
+    assert!(rows.len() > 1, "{text}");
+    assert!(rows[1..].iter().all(|row| !row.contains("3/3")), "{text}");
+}
+
+#[tokio::test]
+async fn test_search_paths_file_name_before_directory() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "paths").await;
+
+    let text = search(&services, json!({"session": "paths", "query": "service"})).await;
+    assert_eq!(
+        rows(&text),
+        [
+            "1. src/billing/**service**.rs (1/1 words, 1 in file name)",
+            "2. src/user/**service**_handler.rs (1/1 words, 1 in file name)",
+        ]
+    );
+
+    // Limited to k files
+    let text = search(
+        &services,
+        json!({"session": "paths", "query": "user", "k": 2}),
+    )
+    .await;
+    assert_eq!(rows(&text).len(), 2, "{text}");
+}
+
+#[tokio::test]
+async fn test_search_paths_no_match() {
+    let services = Arc::new(create_test_services());
+    let repo = fixture();
+    index_test_repository(&services, repo.path(), "paths").await;
+
+    let text = search(
+        &services,
+        json!({"session": "paths", "query": "zebra inventory"}),
+    )
+    .await;
+    assert!(rows(&text).is_empty());
+    assert!(text.contains("No file paths match."), "{text}");
+    assert!(text.contains("find_file"), "{text}");
+}
+
+#[tokio::test]
+async fn test_search_paths_unknown_session() {
+    let services = Arc::new(create_test_services());
+    let handler = SearchPathsHandler::new(Arc::clone(&services));
+
+    let result = handler
+        .execute(json!({"session": "missing", "query": "user"}))
+        .await;
+    let Err(McpError::ToolError(_, message)) = result else {
+        panic!("expected a session error, got {result:?}");
+    };
+    assert!(message.contains("not found"), "{message}");
+}
@@ -0,0 +1,161 @@
+//! Integration tests for document section breadcrumbs
+//!
+//! Chunks of Markdown and reStructuredText files record the headings in
+//! effect at their start. search_code and find_references show them
+//! after the file, and search_code's `section_filter` keeps only
+//! chunks whose breadcrumb contains the filter text.
+
+use crate::common::{call, call_text, create_test_services, index_test_repository, TestRepo};
+use serde_json::json;
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+
+/// Markdown with nested headings; each section is longer than a
+/// chunk and names its topic at its end
+fn architecture() -> String {
+    let filler = "Filler prose that pads the section out well past one chunk. ".repeat(12);
+    let section = |heading: &str, topic: &str| format!("{heading}\n\n{filler}\n\n{topic}\n\n");
+    [
+        section(
+            "# Architecture",
+            "Shebe indexes repositories into sessions.",
+        ),
+        section("## Storage", "Every session lives in its own directory."),
+        section(
+            "### Session layout",
+            "The session directory holds meta.json. A SessionLock guards writes.",
+        ),
+        section(
+            "### Retention",
+            "Old sessions are pruned after their retention period.",
+        ),
+        section(
+            "## Search",
+            "Queries run BM25 over the session index without a SessionLock.",
+        ),
+    ]
+    .concat()
+}
+
+/// Session "docs" with a Markdown file of nested headings and a Rust file
+async fn docs_session() -> (ProtocolHandlers, TestRepo) {
+    let repo = TestRepo::with_files(&[
+        ("docs/architecture.md", &architecture()),
+        (
+            "src/lock.rs",
+            "/// Held while a session directory is written\npub struct SessionLock;\n",
+        ),
+    ]);
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "docs").await;
+    (ProtocolHandlers::new(Arc::new(services)), repo)
+}
+
+#[tokio::test]
+async fn test_search_results_show_breadcrumbs() {
+    let (handlers, _repo) = docs_session().await;
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "retention period"}),
+    )
+    .await;
+    assert!(
+        output.contains("**Section:** docs/architecture.md › Architecture › Storage › Retention\n"),
+        "{output}"
+    );
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "BM25"}),
+    )
+    .await;
+    assert!(
+        output.contains("**Section:** docs/architecture.md › Architecture › Search\n"),
+        "{output}"
+    );
+
+    // Source files have no section
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "SessionLock", "k": 10}),
+    )
+    .await;
+    let results: Vec<&str> = output.split("## Result ").skip(1).collect();
+    assert_eq!(results.len(), 3, "{output}");
+    for result in results {
+        let is_doc = result.contains("docs/architecture.md");
+        assert_eq!(result.contains("**Section:**"), is_doc, "{result}");
+    }
+}
+
+#[tokio::test]
+async fn test_section_filter_keeps_matching_breadcrumbs() {
+    let (handlers, _repo) = docs_session().await;
+
+    let unfiltered = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "session"}),
+    )
+    .await;
+    assert!(unfiltered.contains("› Search\n"), "{unfiltered}");
+    assert!(unfiltered.contains("src/lock.rs"), "{unfiltered}");
+
+    // Case-insensitive substring of the breadcrumb
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "session", "section_filter": "storage"}),
+    )
+    .await;
+    assert!(
+        output.contains("Only document sections matching 'storage'"),
+        "{output}"
+    );
+    assert!(output.contains("› Storage"), "{output}");
+    assert!(!output.contains("› Search\n"), "{output}");
+    assert!(!output.contains("src/lock.rs"), "{output}");
+
+    let output = call_text(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "session", "section_filter": "Deployment"}),
+    )
+    .await;
+    assert!(output.contains("No results found"), "{output}");
+
+    let response = call(
+        &handlers,
+        "search_code",
+        json!({"session": "docs", "relative_paths": true, "query": "session", "section_filter": "  "}),
+    )
+    .await;
+    let error = response.error.expect("blank section_filter");
+    assert!(error.message.contains("section_filter cannot be empty"));
+}
+
+#[tokio::test]
+async fn test_find_references_shows_breadcrumbs() {
+    let (handlers, _repo) = docs_session().await;
+
+    let output = call_text(
+        &handlers,
+        "find_references",
+        json!({"session": "docs", "relative_paths": true, "symbol": "SessionLock", "symbol_type": "type"}),
+    )
+    .await;
+    assert!(
+        output.contains(
+            "**Section:** docs/architecture.md › Architecture › Storage › Session layout\n"
+        ),
+        "{output}"
+    );
+    assert!(
+        output.contains("**Section:** docs/architecture.md › Architecture › Search\n"),
+        "{output}"
+    );
+}
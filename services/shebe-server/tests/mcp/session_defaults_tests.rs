@@ -0,0 +1,135 @@
+//! Integration tests for per-session search defaults
+//!
+//! `set_session_defaults` stores k, fuzziness, path_boost and
+//! output_mode in the session metadata. search_code applies them when a
+//! call leaves the option out; values given with the call always win.
+
+use crate::common::{call, call_text, create_test_services, index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::mcp::handlers::ProtocolHandlers;
+use std::sync::Arc;
+
+/// Session "docs" with 30 files that each mention `authenticate`
+async fn docs_session() -> (ProtocolHandlers, TestRepo) {
+    let files: Vec<(String, String)> = (0..30)
+        .map(|i| {
+            (
+                format!("docs/page_{i}.md"),
+                format!("# Page {i}\n\nUsers authenticate with a token.\n"),
+            )
+        })
+        .collect();
+    let refs: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    let repo = TestRepo::with_files(&refs);
+    let services = create_test_services();
+    index_test_repository(&services, repo.path(), "docs").await;
+    (ProtocolHandlers::new(Arc::new(services)), repo)
+}
+
+/// Search "docs" with a misspelled query and the given overrides
+async fn search(handlers: &ProtocolHandlers, overrides: Value) -> String {
+    let mut arguments = json!({"session": "docs", "query": "authentcate"});
+    for (key, value) in overrides.as_object().unwrap() {
+        arguments[key] = value.clone();
+    }
+    call_text(handlers, "search_code", arguments).await
+}
+
+fn location_rows(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|line| line.starts_with("| r"))
+        .count()
+}
+
+fn full_results(output: &str) -> usize {
+    output.matches("## Result ").count()
+}
+
+#[tokio::test]
+async fn test_defaults_apply_and_request_values_win() {
+    let (handlers, _repo) = docs_session().await;
+
+    // Without defaults the misspelling finds nothing
+    assert!(search(&handlers, json!({}))
+        .await
+        .contains("No results found"));
+
+    let updated = call_text(
+        &handlers,
+        "set_session_defaults",
+        json!({"session": "docs", "k": 20, "fuzziness": 1, "output_mode": "locations"}),
+    )
+    .await;
+    assert!(updated.contains("**Search defaults:** k=20, fuzziness=1, output_mode=locations"));
+
+    let info = call_text(&handlers, "get_session_info", json!({"session": "docs"})).await;
+    assert!(
+        info.contains("- **Search defaults:** k=20, fuzziness=1, output_mode=locations"),
+        "{info}"
+    );
+
+    // No overrides: fuzzy matching, 20 results, locations table
+    let output = search(&handlers, json!({})).await;
+    assert_eq!(location_rows(&output), 20, "{output}");
+    assert_eq!(full_results(&output), 0);
+
+    // One override per call; the other defaults still apply
+    assert_eq!(location_rows(&search(&handlers, json!({"k": 5})).await), 5);
+    assert!(search(&handlers, json!({"fuzziness": 0}))
+        .await
+        .contains("No results found"));
+    let output = search(&handlers, json!({"output_mode": "full"})).await;
+    assert_eq!(full_results(&output), 20, "{output}");
+    assert_eq!(location_rows(&output), 0);
+}
+
+#[tokio::test]
+async fn test_clear_restores_server_defaults() {
+    let (handlers, _repo) = docs_session().await;
+
+    call_text(
+        &handlers,
+        "set_session_defaults",
+        json!({"session": "docs", "k": 3, "fuzziness": 1}),
+    )
+    .await;
+    assert_eq!(full_results(&search(&handlers, json!({})).await), 3);
+
+    call_text(
+        &handlers,
+        "set_session_defaults",
+        json!({"session": "docs", "clear": true, "fuzziness": 1}),
+    )
+    .await;
+    let info = call_text(&handlers, "get_session_info", json!({"session": "docs"})).await;
+    assert!(
+        info.contains("- **Search defaults:** fuzziness=1\n"),
+        "{info}"
+    );
+    assert_eq!(full_results(&search(&handlers, json!({})).await), 10);
+}
+
+#[tokio::test]
+async fn test_invalid_defaults_rejected() {
+    let (handlers, _repo) = docs_session().await;
+
+    let response = call(
+        &handlers,
+        "set_session_defaults",
+        json!({"session": "docs", "k": 500}),
+    )
+    .await;
+    let error = response.error.expect("k above the search limit");
+    assert!(
+        error.message.contains("k must be between 1 and 100"),
+        "{}",
+        error.message
+    );
+
+    let info = call_text(&handlers, "get_session_info", json!({"session": "docs"})).await;
+    assert!(!info.contains("Search defaults"));
+}
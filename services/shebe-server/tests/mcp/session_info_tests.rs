@@ -0,0 +1,113 @@
+//! Integration tests for get_session_info health and provenance reporting
+//!
+//! A moved repository and a stale index each come with a recommended
+//! action naming the tool that fixes them.
+
+use crate::common::{create_test_services, index_test_repository, TestRepo};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use shebe::core::services::Services;
+use shebe::mcp::tools::handler::McpToolHandler;
+use shebe::mcp::tools::GetSessionInfoHandler;
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+async fn session_info(services: &Arc<Services>, session: &str) -> String {
+    let handler = GetSessionInfoHandler::new(Arc::clone(services));
+    let result = handler.execute(json!({"session": session})).await.unwrap();
+    let shebe::mcp::protocol::ContentBlock::Text { text } = &result.content[0];
+    text.clone()
+}
+
+#[tokio::test]
+async fn test_healthy_session_has_no_actions() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "healthy").await;
+
+    let output = session_info(&services, "healthy").await;
+    assert!(output.contains("## Health"), "{output}");
+    assert!(output.contains("(matches recorded chunks)"));
+    assert!(output.contains("- **Repository:** present"));
+    assert!(
+        output.contains("0 missing, 0 modified since indexing (all 10 indexed files checked)"),
+        "{output}"
+    );
+    assert!(output.contains("fresh (stale after 7 days)"));
+    assert!(output.contains(
+        "## Recommended actions\n- None: the index matches its metadata and the repository"
+    ));
+}
+
+#[tokio::test]
+async fn test_moved_repository_recommends_delete_or_reindex() {
+    let services = Arc::new(create_test_services());
+    let root = TempDir::new().unwrap();
+    let repo = root.path().join("repo");
+    fs::create_dir(&repo).unwrap();
+    fs::write(repo.join("main.rs"), "fn main() {}").unwrap();
+    index_test_repository(&services, &repo, "moved").await;
+    fs::rename(&repo, root.path().join("elsewhere")).unwrap();
+
+    let output = session_info(&services, "moved").await;
+    assert!(output.contains("- **Repository:** missing"), "{output}");
+    assert!(output.contains("- **Files on disk:** not checked (repository missing)"));
+    assert!(output.contains("is missing — delete the session with delete_session"));
+    assert!(!output.contains("reindex_session"));
+}
+
+#[tokio::test]
+async fn test_stale_index_recommends_reindex() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    index_test_repository(&services, repo.path(), "stale").await;
+    let mut metadata = services.storage.get_session_metadata("stale").unwrap();
+    metadata.last_indexed_at = Utc::now() - Duration::days(30);
+    services
+        .storage
+        .update_session_metadata("stale", &metadata)
+        .unwrap();
+
+    let output = session_info(&services, "stale").await;
+    assert!(
+        output.contains("- **Freshness:** indexed 30 days ago, stale (stale after 7 days)"),
+        "{output}"
+    );
+    assert!(output
+        .contains("- 10 files changed since indexing — run reindex_session with force=true\n"));
+    assert!(output.contains(
+        "- last indexed 30 days ago — run reindex_session with force=true, or keep it fresh \
+         with set_reindex_schedule\n"
+    ));
+}
+
+#[tokio::test]
+async fn test_session_info_shows_provenance() {
+    let services = Arc::new(create_test_services());
+    let repo = TestRepo::small();
+    services
+        .storage
+        .index_repository_with_config(
+            "provenance",
+            repo.path(),
+            shebe::core::storage::SessionConfig::default(),
+            10,
+            false,
+        )
+        .unwrap();
+
+    let output = session_info(&services, "provenance").await;
+    assert!(output.contains("## Provenance"), "{output}");
+    assert!(output.contains(&format!(
+        "- **Shebe version:** {}",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(output.contains("- **Git:** none (not a git checkout, or git_metadata off)"));
+    assert!(output.contains("- **Config:** `{\"chunk_size\":"));
+
+    // Sessions built without the indexing pipeline have none to show
+    index_test_repository(&services, repo.path(), "no-provenance").await;
+    let output = session_info(&services, "no-provenance").await;
+    assert!(!output.contains("## Provenance"));
+}
@@ -0,0 +1,169 @@
+//! Integration tests for MCP over TCP
+//!
+//! Several clients share one server and one set of services; each
+//! connection is its own JSON-RPC session.
+
+use crate::common::{index_test_repository, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::mcp::protocol::UNAUTHORIZED;
+use shebe::mcp::transport::MAX_AUTH_LINE;
+use shebe::mcp::McpServer;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Start a server on a random local port
+async fn spawn_server(auth_token: Option<&str>) -> (SocketAddr, Arc<Services>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.storage.index_dir = temp_dir.path().to_path_buf();
+    config.mcp.auth_token = auth_token.map(str::to_string);
+    let services = Arc::new(Services::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(Arc::clone(&services));
+    tokio::spawn(async move { server.serve_tcp(listener).await });
+    (addr, services, temp_dir)
+}
+
+/// A line-oriented JSON-RPC client
+struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    async fn connect(addr: SocketAddr) -> Self {
+        let (reader, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        }
+    }
+
+    async fn send_line(&mut self, line: &str) {
+        self.writer.write_all(line.as_bytes()).await.unwrap();
+        self.writer.write_all(b"\n").await.unwrap();
+    }
+
+    async fn read_response(&mut self) -> Option<Value> {
+        let line = self.lines.next_line().await.unwrap()?;
+        Some(serde_json::from_str(&line).unwrap())
+    }
+
+    async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        self.send_line(&request.to_string()).await;
+        self.read_response().await.expect("response")
+    }
+
+    async fn initialize(&mut self) {
+        let response = self
+            .request(
+                0,
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "tcp-test", "version": "1.0"}
+                }),
+            )
+            .await;
+        assert_eq!(response["result"]["serverInfo"]["name"], "shebe-mcp");
+        self.send_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await;
+    }
+}
+
+fn result_text(response: &Value) -> &str {
+    response["result"]["content"][0]["text"].as_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_two_clients_share_one_server() {
+    let (addr, services, _temp) = spawn_server(None).await;
+    let repo = TestRepo::with_files(&[(
+        "src/auth.rs",
+        "pub fn authenticate_user(name: &str) -> bool {\n    !name.is_empty()\n}\n",
+    )]);
+    index_test_repository(&services, repo.path(), "shared").await;
+
+    let mut first = Client::connect(addr).await;
+    let mut second = Client::connect(addr).await;
+    first.initialize().await;
+    second.initialize().await;
+
+    let search = json!({
+        "name": "search_code",
+        "arguments": {"query": "authenticate_user", "session": "shared"}
+    });
+    let (listed, found, found_again) = tokio::join!(
+        first.request(1, "tools/list", json!({})),
+        second.request(1, "tools/call", search.clone()),
+        async {
+            let mut third = Client::connect(addr).await;
+            third.request(7, "tools/call", search.clone()).await
+        }
+    );
+
+    assert!(listed["result"]["tools"].as_array().unwrap().len() > 20);
+    for response in [&found, &found_again] {
+        assert!(response["error"].is_null(), "{response}");
+        assert!(result_text(response).contains("src/auth.rs"));
+    }
+    assert_eq!(found["id"], 1);
+    assert_eq!(found_again["id"], 7);
+
+    // Each connection keeps working after the others' requests
+    let ping = first.request(2, "ping", json!({})).await;
+    assert!(ping["error"].is_null());
+}
+
+#[tokio::test]
+async fn test_bearer_token_required_when_configured() {
+    let (addr, _services, _temp) = spawn_server(Some("s3cret")).await;
+
+    // A request without the Authorization line is refused
+    let mut anonymous = Client::connect(addr).await;
+    let response = anonymous.request(1, "ping", json!({})).await;
+    assert_eq!(response["error"]["code"], UNAUTHORIZED);
+    assert!(response["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Authorization: Bearer"));
+    assert!(anonymous.read_response().await.is_none(), "disconnected");
+
+    // So is a wrong token
+    let mut wrong = Client::connect(addr).await;
+    wrong.send_line("Authorization: Bearer guess").await;
+    let response = wrong.read_response().await.unwrap();
+    assert_eq!(response["error"]["code"], UNAUTHORIZED);
+
+    let mut client = Client::connect(addr).await;
+    client.send_line("Authorization: Bearer s3cret").await;
+    client.initialize().await;
+    let response = client.request(1, "ping", json!({})).await;
+    assert!(response["error"].is_null(), "{response}");
+}
+
+#[tokio::test]
+async fn test_overlong_authorization_line_refused() {
+    let (addr, _services, _temp) = spawn_server(Some("s3cret")).await;
+
+    // Refused once the limit is reached, without waiting for a newline
+    let mut client = Client::connect(addr).await;
+    let line = "A".repeat(MAX_AUTH_LINE as usize);
+    client.writer.write_all(line.as_bytes()).await.unwrap();
+    let response = tokio::time::timeout(Duration::from_secs(5), client.read_response())
+        .await
+        .expect("refused before the authorization timeout")
+        .unwrap();
+    assert_eq!(response["error"]["code"], UNAUTHORIZED);
+}
@@ -0,0 +1,143 @@
+//! Integration tests for sessions that keep failing to open
+//!
+//! After `limits.open_failure_threshold` failed opens a session fails
+//! fast with SESSION_UNAVAILABLE, get_server_info lists it, and
+//! get_session_info with `reset: true` lets the next call open it.
+
+use crate::common::{call, index_test_repository, response_text, TestRepo};
+use serde_json::{json, Value};
+use shebe::core::config::Config;
+use shebe::core::services::Services;
+use shebe::mcp::handlers::ProtocolHandlers;
+use shebe::mcp::protocol::SESSION_UNAVAILABLE;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Session "docs" whose Tantivy directory has been swapped for a file
+struct Fixture {
+    handlers: ProtocolHandlers,
+    tantivy_dir: PathBuf,
+    moved_dir: PathBuf,
+    _repo: TestRepo,
+    _storage: TempDir,
+}
+
+impl Fixture {
+    /// Put the real index back
+    fn repair(&self) {
+        std::fs::remove_file(&self.tantivy_dir).unwrap();
+        std::fs::rename(&self.moved_dir, &self.tantivy_dir).unwrap();
+    }
+}
+
+async fn broken_session() -> Fixture {
+    let repo = TestRepo::with_files(&[("src/auth.rs", "fn authenticate_user() {}\n")]);
+    let storage = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.storage.index_dir = storage.path().to_path_buf();
+    config.limits.open_failure_threshold = 2;
+    config.limits.open_failure_cooldown_sec = 600;
+    let services = Arc::new(Services::new(config));
+    index_test_repository(&services, repo.path(), "docs").await;
+
+    // A file where the index directory is expected fails every open
+    let session_dir = services.storage.get_session_path("docs");
+    let tantivy_dir = session_dir.join("tantivy");
+    let moved_dir = session_dir.join("tantivy.moved");
+    std::fs::rename(&tantivy_dir, &moved_dir).unwrap();
+    std::fs::write(&tantivy_dir, "not an index").unwrap();
+
+    Fixture {
+        handlers: ProtocolHandlers::new(services),
+        tantivy_dir,
+        moved_dir,
+        _repo: repo,
+        _storage: storage,
+    }
+}
+
+fn search() -> Value {
+    json!({"session": "docs", "query": "authenticate"})
+}
+
+#[tokio::test]
+async fn test_session_fails_fast_after_threshold() {
+    let fixture = broken_session().await;
+    let handlers = &fixture.handlers;
+
+    // Failures below the threshold report the underlying error
+    for _ in 0..2 {
+        let error = call(handlers, "search_code", search())
+            .await
+            .error
+            .expect("open error");
+        assert_ne!(error.code, SESSION_UNAVAILABLE, "{}", error.message);
+    }
+
+    // Then every tool that opens the session fails fast, even once the
+    // index is back, until the cooldown ends
+    fixture.repair();
+    for (tool, arguments) in [
+        ("search_code", search()),
+        (
+            "preview_chunk",
+            json!({"session": "docs", "file_path": "src/auth.rs", "chunk_index": 0}),
+        ),
+    ] {
+        let error = call(handlers, tool, arguments)
+            .await
+            .error
+            .expect("fast failure");
+        assert_eq!(error.code, SESSION_UNAVAILABLE, "{tool}: {}", error.message);
+        assert!(
+            error
+                .message
+                .contains("Session 'docs' is temporarily unavailable (last error: "),
+            "{}",
+            error.message
+        );
+        assert!(error.message.contains("retrying after "));
+        assert!(error.message.contains("reset: true"));
+    }
+
+    let info = call(handlers, "get_server_info", json!({})).await;
+    assert!(response_text(&info).contains("## Unavailable Sessions"));
+    assert!(response_text(&info).contains("- **docs:** failed to open 2 time(s)"));
+
+    let session_info = call(handlers, "get_session_info", json!({"session": "docs"})).await;
+    assert!(
+        response_text(&session_info)
+            .contains("- **Status:** Unavailable: failed to open 2 time(s)"),
+        "{}",
+        response_text(&session_info)
+    );
+}
+
+#[tokio::test]
+async fn test_reset_lets_session_open_again() {
+    let fixture = broken_session().await;
+    let handlers = &fixture.handlers;
+    for _ in 0..3 {
+        call(handlers, "search_code", search()).await;
+    }
+    fixture.repair();
+    let error = call(handlers, "search_code", search()).await.error.unwrap();
+    assert_eq!(error.code, SESSION_UNAVAILABLE);
+
+    let reset = call(
+        handlers,
+        "get_session_info",
+        json!({"session": "docs", "reset": true}),
+    )
+    .await;
+    assert!(response_text(&reset).starts_with("Recent open failures cleared"));
+    assert!(response_text(&reset).contains("- **Status:** Ready\n"));
+
+    let response = call(handlers, "search_code", search()).await;
+    assert!(response.error.is_none(), "{:?}", response.error);
+    assert!(response_text(&response).contains("auth.rs"));
+
+    let info = call(handlers, "get_server_info", json!({})).await;
+    assert!(!response_text(&info).contains("Unavailable Sessions"));
+}